@@ -0,0 +1,404 @@
+//! Assertion breakpoints for using ferros as a test oracle.
+//!
+//! An assertion is a hidden breakpoint that passes or fails the session
+//! rather than stopping for the user:
+//!
+//! - [`AssertionKind::NotReached`]: reaching the location is a [`Violation`],
+//!   recorded with the symbolicated backtrace and the argument registers.
+//!   The target is then resumed or killed per the [`ViolationAction`].
+//! - [`AssertionKind::Reached`]: a one-shot breakpoint; the session fails
+//!   with a [`ViolationKind::NeverReached`] violation if it never trapped.
+//!
+//! Front-ends install an [`AssertionSet`] before the target runs, hand it
+//! every stop with [`AssertionSet::on_stop`], and call
+//! [`AssertionSet::finish`] at the end of the session. Violations go into the
+//! session summary, and a session with any fails even if the target exited 0.
+//!
+//! ```rust
+//! use ferros_core::assertions::{AssertionKind, AssertionSet, ViolationAction};
+//!
+//! let mut assertions = AssertionSet::new(ViolationAction::Continue);
+//! assertions.add(AssertionKind::NotReached, "abort").unwrap();
+//! assertions
+//!     .add(AssertionKind::Reached, "src/main.rs:42")
+//!     .unwrap();
+//! assert_eq!(assertions.len(), 2);
+//! // Nothing installed and nothing hit: the reached assertion fails
+//! assert_eq!(assertions.finish().len(), 1);
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::breakpoints::{BreakpointBuilder, BreakpointId, BreakpointLocation, BreakpointRequestKind};
+use crate::debugger::Debugger;
+use crate::error::{DebuggerError, Result};
+use crate::session::CrashFrame;
+use crate::types::{StopReason, ThreadId, argument_values};
+
+/// Frames unwound for a violation's backtrace.
+pub const VIOLATION_MAX_FRAMES: usize = 32;
+
+/// Breakpoint group assertion breakpoints are installed in.
+pub const ASSERTION_GROUP: &str = "assertions";
+
+/// Whether an assertion's location must or must not be reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionKind
+{
+    /// Reaching the location fails the session (`--assert-not-reached`).
+    NotReached,
+    /// Not reaching the location by the end of the session fails it (`--assert-reached`).
+    Reached,
+}
+
+impl fmt::Display for AssertionKind
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::NotReached => "not-reached",
+            Self::Reached => "reached",
+        })
+    }
+}
+
+/// What to do with the target after a not-reached assertion is hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViolationAction
+{
+    /// Record the violation and resume, to collect every violation in one run.
+    #[default]
+    Continue,
+    /// Record the violation and kill the target.
+    Kill,
+}
+
+impl FromStr for ViolationAction
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err>
+    {
+        match s.to_ascii_lowercase().as_str() {
+            "continue" => Ok(Self::Continue),
+            "kill" => Ok(Self::Kill),
+            _ => Err(format!("invalid violation action '{s}' (expected continue or kill)")),
+        }
+    }
+}
+
+impl fmt::Display for ViolationAction
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::Continue => "continue",
+            Self::Kill => "kill",
+        })
+    }
+}
+
+/// How an assertion failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind
+{
+    /// A location that must not be reached was.
+    Reached,
+    /// A location that must be reached never was.
+    NeverReached,
+}
+
+/// A failed assertion, as reported in the session summary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation
+{
+    /// The assertion as given, e.g. `not-reached abort`.
+    pub assertion: String,
+    /// How it failed.
+    pub kind: ViolationKind,
+    /// Thread that reached the location.
+    pub thread: Option<u64>,
+    /// Symbolicated backtrace at the hit, innermost first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backtrace: Vec<CrashFrame>,
+    /// Argument registers at the hit, named after the function's parameters
+    /// where debug info has them, e.g. `path (x0) = 0x16fdff0a8`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<String>,
+}
+
+impl fmt::Display for Violation
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self.kind {
+            ViolationKind::Reached => write!(f, "assertion {} failed: location was reached", self.assertion)?,
+            ViolationKind::NeverReached => write!(f, "assertion {} failed: location was never reached", self.assertion)?,
+        }
+        if let Some(thread) = self.thread {
+            write!(f, " (thread {thread})")?;
+        }
+        if let Some(function) = self.backtrace.first().and_then(|frame| frame.function.as_deref()) {
+            write!(f, " in {function}")?;
+        }
+        Ok(())
+    }
+}
+
+/// One assertion and the breakpoints installed for it.
+#[derive(Debug, Clone)]
+pub struct Assertion
+{
+    /// Whether the location must or must not be reached.
+    pub kind: AssertionKind,
+    /// Where the assertion traps.
+    pub location: BreakpointLocation,
+    /// Breakpoints installed for it; empty until [`AssertionSet::install`].
+    pub breakpoints: Vec<BreakpointId>,
+    /// Number of times the location was reached.
+    pub hits: u64,
+}
+
+impl fmt::Display for Assertion
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{} {}", self.kind, self.location)
+    }
+}
+
+/// What a stop meant to the assertions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssertionStop
+{
+    /// A location that must be reached was; resume the target.
+    Reached(String),
+    /// A location that must not be reached was.
+    Violated(Violation),
+}
+
+/// The assertions of a session and the violations found so far.
+#[derive(Debug, Clone, Default)]
+pub struct AssertionSet
+{
+    assertions: Vec<Assertion>,
+    action: ViolationAction,
+    violations: Vec<Violation>,
+}
+
+impl AssertionSet
+{
+    /// An empty set that applies `action` after a violation.
+    #[must_use]
+    pub fn new(action: ViolationAction) -> Self
+    {
+        Self {
+            action,
+            ..Self::default()
+        }
+    }
+
+    /// Add an assertion on `spec`: `0x<hex>`, `<file>:<line>` or a function name.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidArgument` if `spec` isn't a location.
+    pub fn add(&mut self, kind: AssertionKind, spec: &str) -> Result<()>
+    {
+        let location = spec.parse().map_err(DebuggerError::InvalidArgument)?;
+        self.assertions.push(Assertion {
+            kind,
+            location,
+            breakpoints: Vec::new(),
+            hits: 0,
+        });
+        Ok(())
+    }
+
+    /// What to do with the target after a violation.
+    #[must_use]
+    pub fn action(&self) -> ViolationAction
+    {
+        self.action
+    }
+
+    /// The assertions, in the order they were added.
+    #[must_use]
+    pub fn assertions(&self) -> &[Assertion]
+    {
+        &self.assertions
+    }
+
+    /// Violations found so far.
+    #[must_use]
+    pub fn violations(&self) -> &[Violation]
+    {
+        &self.violations
+    }
+
+    /// Number of assertions.
+    #[must_use]
+    pub fn len(&self) -> usize
+    {
+        self.assertions.len()
+    }
+
+    /// Whether there are no assertions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.assertions.is_empty()
+    }
+
+    /// Set a hidden breakpoint for every assertion.
+    ///
+    /// Call this before the target runs. Reached assertions are one-shot,
+    /// since one hit is all they need.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the error of the first breakpoint that can't be added, naming
+    /// its assertion.
+    pub fn install(&mut self, debugger: &mut dyn Debugger) -> Result<()>
+    {
+        for assertion in &mut self.assertions {
+            let mut builder = BreakpointBuilder::new(BreakpointRequestKind::Location(assertion.location.clone()))
+                .group(ASSERTION_GROUP)
+                .hidden();
+            if assertion.kind == AssertionKind::Reached {
+                builder = builder.one_shot();
+            }
+            let id = debugger
+                .add_breakpoint(builder.build())
+                .map_err(|err| DebuggerError::InvalidArgument(format!("can't set assertion {assertion}: {err}")))?;
+            assertion.breakpoints.push(id);
+        }
+        Ok(())
+    }
+
+    /// Check a stop against the assertions.
+    ///
+    /// Returns `None` for stops that aren't at an assertion breakpoint. A
+    /// violation is recorded with its backtrace before it's returned; the
+    /// caller then resumes or kills the target per [`action`](Self::action).
+    pub fn on_stop(
+        &mut self,
+        debugger: &mut dyn Debugger,
+        reason: StopReason,
+        thread: Option<ThreadId>,
+    ) -> Option<AssertionStop>
+    {
+        let StopReason::Breakpoint(address) = reason else {
+            return None;
+        };
+        let hit: Vec<BreakpointId> = debugger
+            .breakpoints()
+            .iter()
+            .filter(|info| info.address.value() == address)
+            .map(|info| info.id)
+            .collect();
+        let assertion = self
+            .assertions
+            .iter_mut()
+            .find(|assertion| assertion.breakpoints.iter().any(|id| hit.contains(id)))?;
+        assertion.hits += 1;
+        let name = assertion.to_string();
+        if assertion.kind == AssertionKind::Reached {
+            return Some(AssertionStop::Reached(name));
+        }
+
+        let frames = debugger.stack_trace(VIOLATION_MAX_FRAMES).unwrap_or_default();
+        let violation = Violation {
+            assertion: name,
+            kind: ViolationKind::Reached,
+            thread: thread.map(|thread| thread.raw()),
+            backtrace: frames.iter().map(CrashFrame::from).collect(),
+            arguments: debugger
+                .read_registers()
+                .map(|registers| {
+                    argument_values(&registers, frames.first().map_or(&[], |frame| frame.parameters.as_slice()))
+                })
+                .unwrap_or_default(),
+        };
+        self.violations.push(violation.clone());
+        Some(AssertionStop::Violated(violation))
+    }
+
+    /// End the session: record a violation for every reached assertion that
+    /// never trapped, and return all violations.
+    pub fn finish(&mut self) -> Vec<Violation>
+    {
+        for assertion in &self.assertions {
+            let name = assertion.to_string();
+            let missed = assertion.kind == AssertionKind::Reached && assertion.hits == 0;
+            if missed && !self.violations.iter().any(|violation| violation.assertion == name) {
+                self.violations.push(Violation {
+                    assertion: name,
+                    kind: ViolationKind::NeverReached,
+                    thread: None,
+                    backtrace: Vec::new(),
+                    arguments: Vec::new(),
+                });
+            }
+        }
+        self.violations.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn specs_parse_and_actions_round_trip()
+    {
+        let mut assertions = AssertionSet::new(ViolationAction::Kill);
+        assertions.add(AssertionKind::NotReached, "std::process::abort").unwrap();
+        assertions.add(AssertionKind::Reached, "0x1000").unwrap();
+        assert!(assertions.add(AssertionKind::Reached, "").is_err());
+        let names: Vec<_> = assertions.assertions().iter().map(ToString::to_string).collect();
+        assert_eq!(names, ["not-reached std::process::abort", "reached 0x0000000000001000"]);
+
+        assert_eq!("KILL".parse(), Ok(ViolationAction::Kill));
+        assert_eq!(ViolationAction::Continue.to_string(), "continue");
+        assert!("ignore".parse::<ViolationAction>().is_err());
+    }
+
+    #[test]
+    fn unreached_assertions_fail_once_at_finish()
+    {
+        let mut assertions = AssertionSet::new(ViolationAction::Continue);
+        assertions.add(AssertionKind::Reached, "main").unwrap();
+        assertions.add(AssertionKind::NotReached, "abort").unwrap();
+
+        let violations = assertions.finish();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, ViolationKind::NeverReached);
+        assert_eq!(
+            violations[0].to_string(),
+            "assertion reached main failed: location was never reached"
+        );
+        // Finishing again doesn't duplicate it
+        assert_eq!(assertions.finish().len(), 1);
+    }
+
+    #[test]
+    fn violations_serialize_without_empty_details()
+    {
+        let violation = Violation {
+            assertion: "reached main".to_string(),
+            kind: ViolationKind::NeverReached,
+            thread: None,
+            backtrace: Vec::new(),
+            arguments: Vec::new(),
+        };
+        let json = serde_json::to_string(&violation).unwrap();
+        assert_eq!(json, r#"{"assertion":"reached main","kind":"never_reached","thread":null}"#);
+        assert_eq!(serde_json::from_str::<Violation>(&json).unwrap(), violation);
+    }
+}
@@ -2,115 +2,87 @@
 //!
 //! Builder pattern for configuring breakpoints before installation.
 //!
-//! This module provides a fluent API for configuring breakpoints with
-//! advanced features like conditional breakpoints, hit counts, and
-//! thread-specific breakpoints.
+//! [`Breakpoint`] has one entry point per kind of breakpoint. Each returns a
+//! [`BreakpointBuilder`] whose chained methods set the options, and
+//! [`BreakpointBuilder::build`] produces the [`BreakpointRequest`] that
+//! [`Debugger::add_breakpoint`](crate::debugger::Debugger::add_breakpoint)
+//! takes.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ferros_core::breakpoints::{Breakpoint, BreakpointAction, BreakpointRequestKind};
+//! use ferros_core::types::{Address, ThreadId};
+//!
+//! let request = Breakpoint::software(Address::from(0x1000))
+//!     .condition("$x2 > 3")
+//!     .group("parser")
+//!     .thread(ThreadId::from(2))
+//!     .action(BreakpointAction::Log("parsing".to_string()))
+//!     .build();
+//!
+//! assert_eq!(
+//!     request.kind,
+//!     BreakpointRequestKind::Software {
+//!         address: Address::from(0x1000)
+//!     }
+//! );
+//! assert_eq!(request.options.condition.as_deref(), Some("$x2 > 3"));
+//! assert_eq!(request.options.group.as_deref(), Some("parser"));
+//! assert!(request.enabled);
+//! ```
 
-use crate::breakpoints::{BreakpointRequest, WatchpointAccess};
+use crate::breakpoints::{
+    BreakpointAction, BreakpointId, BreakpointLocation, BreakpointRequest, BreakpointRequestKind, WatchpointAccess,
+};
 use crate::error::Result;
-use crate::types::{Address, Registers, ThreadId};
+use crate::types::{Address, ThreadId};
 
-/// Type alias for breakpoint condition functions.
-///
-/// A condition function takes a reference to the current register state
-/// and returns `true` if the breakpoint should trigger, `false` otherwise.
-pub type BreakpointCondition = Box<dyn Fn(&Registers) -> bool + Send + Sync>;
-
-/// Builder for configuring breakpoints before installation.
-///
-/// This builder allows you to configure advanced breakpoint features
-/// like conditions, hit counts, and thread-specific breakpoints.
+/// Entry points for building a [`BreakpointRequest`].
 ///
-/// ## Example
-///
-/// ```rust,no_run
-/// use ferros_core::Debugger;
-/// use ferros_core::breakpoints::builder::BreakpointBuilder;
-/// use ferros_core::types::Address;
-///
-/// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
-/// let bp_id = BreakpointBuilder::software(Address::from(0x1000))
-///     .with_hit_count(5)  // Break after 5 hits
-///     .with_condition(|regs| regs.pc.value() > 0x2000)  // Only break if PC > 0x2000
-///     .install(&mut debugger)?;
-/// # Ok::<(), ferros_core::error::DebuggerError>(())
-/// ```
-pub struct BreakpointBuilder
-{
-    request: BreakpointRequest,
-    hit_count: Option<u64>,
-    condition: Option<BreakpointCondition>,
-    thread_id: Option<ThreadId>,
-    commands: Vec<String>,
-}
+/// This type has no values; use its associated functions.
+#[derive(Debug)]
+pub enum Breakpoint {}
 
-impl std::fmt::Debug for BreakpointBuilder
+impl Breakpoint
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
-    {
-        f.debug_struct("BreakpointBuilder")
-            .field("request", &self.request)
-            .field("hit_count", &self.hit_count)
-            .field("condition", &self.condition.is_some())
-            .field("thread_id", &self.thread_id)
-            .field("commands", &self.commands)
-            .finish()
-    }
-}
-
-impl BreakpointBuilder
-{
-    /// Create a builder for a software breakpoint.
-    ///
-    /// ## Parameters
-    ///
-    /// - `address`: The memory address where the breakpoint should be installed.
+    /// Software breakpoint at `address`.
     ///
     /// ## Example
     ///
-    /// ```rust,no_run
-    /// use ferros_core::breakpoints::builder::BreakpointBuilder;
+    /// ```rust
+    /// use ferros_core::breakpoints::Breakpoint;
     /// use ferros_core::types::Address;
     ///
-    /// let builder = BreakpointBuilder::software(Address::from(0x1000));
+    /// let request = Breakpoint::software(Address::from(0x1000)).build();
     /// ```
-    pub fn software(address: Address) -> Self
+    pub fn software(address: Address) -> BreakpointBuilder
     {
-        Self {
-            request: BreakpointRequest::Software { address },
-            hit_count: None,
-            condition: None,
-            thread_id: None,
-            commands: Vec::new(),
-        }
+        BreakpointBuilder::new(BreakpointRequestKind::Software { address })
     }
 
-    /// Create a builder for a hardware breakpoint.
-    ///
-    /// ## Parameters
+    /// Hardware execution breakpoint at `address`.
     ///
-    /// - `address`: The memory address where the breakpoint should be installed.
+    /// Adding it fails with `NoHardwareSlots` once every debug register is
+    /// taken, unless the builder [allows a fallback](BreakpointBuilder::allow_fallback).
     ///
     /// ## Example
     ///
-    /// ```rust,no_run
-    /// use ferros_core::breakpoints::builder::BreakpointBuilder;
+    /// ```rust
+    /// use ferros_core::breakpoints::Breakpoint;
     /// use ferros_core::types::Address;
     ///
-    /// let builder = BreakpointBuilder::hardware(Address::from(0x1000));
+    /// let request = Breakpoint::hardware(Address::from(0x1000)).build();
     /// ```
-    pub fn hardware(address: Address) -> Self
+    pub fn hardware(address: Address) -> BreakpointBuilder
     {
-        Self {
-            request: BreakpointRequest::Hardware { address },
-            hit_count: None,
-            condition: None,
-            thread_id: None,
-            commands: Vec::new(),
-        }
+        BreakpointBuilder::new(BreakpointRequestKind::Hardware {
+            address,
+            allow_fallback: false,
+        })
     }
 
-    /// Create a builder for a watchpoint.
+    /// Watchpoint on `length` bytes at `address`.
     ///
     /// ## Parameters
     ///
@@ -120,157 +92,208 @@ impl BreakpointBuilder
     ///
     /// ## Example
     ///
-    /// ```rust,no_run
-    /// use ferros_core::breakpoints::WatchpointAccess;
-    /// use ferros_core::breakpoints::builder::BreakpointBuilder;
+    /// ```rust
+    /// use ferros_core::breakpoints::{Breakpoint, WatchpointAccess};
     /// use ferros_core::types::Address;
     ///
-    /// let builder = BreakpointBuilder::watchpoint(Address::from(0x1000), 8, WatchpointAccess::Write);
+    /// let request = Breakpoint::watchpoint(Address::from(0x1000), 8, WatchpointAccess::Write).build();
     /// ```
-    pub fn watchpoint(address: Address, length: usize, access: WatchpointAccess) -> Self
+    pub fn watchpoint(address: Address, length: usize, access: WatchpointAccess) -> BreakpointBuilder
     {
-        Self {
-            request: BreakpointRequest::Watchpoint { address, length, access },
-            hit_count: None,
-            condition: None,
-            thread_id: None,
-            commands: Vec::new(),
-        }
+        BreakpointBuilder::new(BreakpointRequestKind::Watchpoint { address, length, access })
     }
 
-    /// Set the hit count threshold.
-    ///
-    /// The breakpoint will only trigger after it has been hit `count` times.
-    /// This is useful for debugging loops or frequently-called functions.
+    /// Software breakpoint at the entry of the function `name`.
     ///
-    /// ## Parameters
-    ///
-    /// - `count`: The number of times the breakpoint must be hit before it triggers.
+    /// The name is resolved when the breakpoint is installed; see
+    /// [`BreakpointLocation::Function`] for the names that match.
     ///
     /// ## Example
     ///
-    /// ```rust,no_run
-    /// use ferros_core::breakpoints::builder::BreakpointBuilder;
-    /// use ferros_core::types::Address;
+    /// ```rust
+    /// use ferros_core::breakpoints::Breakpoint;
     ///
-    /// let builder = BreakpointBuilder::software(Address::from(0x1000))
-    ///     .with_hit_count(10);  // Break on the 10th hit
+    /// let request = Breakpoint::symbol("my_crate::run").build();
     /// ```
-    pub fn with_hit_count(mut self, count: u64) -> Self
+    pub fn symbol(name: impl Into<String>) -> BreakpointBuilder
     {
-        self.hit_count = Some(count);
-        self
+        BreakpointBuilder::new(BreakpointRequestKind::Location(BreakpointLocation::Function(name.into())))
     }
 
-    /// Set a condition that must be true for the breakpoint to trigger.
-    ///
-    /// The condition is evaluated each time the breakpoint is hit. If the
-    /// condition returns `false`, the breakpoint is ignored and execution
-    /// continues.
-    ///
-    /// ## Parameters
+    /// Software breakpoint on a source line.
     ///
-    /// - `condition`: A closure that takes the current register state and
-    ///   returns `true` if the breakpoint should trigger.
+    /// `file` may be a suffix of the compiled path and `line` is 1-based. The
+    /// line is resolved when the breakpoint is installed.
     ///
     /// ## Example
     ///
-    /// ```rust,no_run
-    /// use ferros_core::breakpoints::builder::BreakpointBuilder;
-    /// use ferros_core::types::Address;
+    /// ```rust
+    /// use ferros_core::breakpoints::Breakpoint;
     ///
-    /// let builder = BreakpointBuilder::software(Address::from(0x1000)).with_condition(|regs| {
-    ///     // Only break if R0 (first general register) is 42
-    ///     regs.general.get(0).map(|&r| r == 42).unwrap_or(false)
-    /// });
+    /// let request = Breakpoint::line("src/main.rs", 42).build();
     /// ```
-    ///
-    /// ## Note
-    ///
-    /// Conditional breakpoints are not yet fully implemented. This method
-    /// stores the condition but it may not be evaluated until full support
-    /// is added.
-    pub fn with_condition<F>(mut self, condition: F) -> Self
-    where
-        F: Fn(&crate::types::Registers) -> bool + Send + Sync + 'static,
+    pub fn line(file: impl Into<String>, line: u32) -> BreakpointBuilder
     {
-        self.condition = Some(Box::new(condition));
-        self
+        BreakpointBuilder::new(BreakpointRequestKind::Location(BreakpointLocation::Line {
+            file: file.into(),
+            line,
+        }))
     }
+}
 
-    /// Make the breakpoint thread-specific.
-    ///
-    /// The breakpoint will only trigger when hit by the specified thread.
-    /// Other threads will ignore the breakpoint.
-    ///
-    /// ## Parameters
+/// Builder for configuring breakpoints before installation.
+///
+/// Created by the [`Breakpoint`] entry points. The breakpoint is enabled and
+/// has no options unless set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct BreakpointBuilder
+{
+    request: BreakpointRequest,
+}
+
+impl BreakpointBuilder
+{
+    /// Create a builder for a breakpoint of `kind`.
+    pub fn new(kind: BreakpointRequestKind) -> Self
+    {
+        Self {
+            request: BreakpointRequest::from(kind),
+        }
+    }
+
+    /// Only stop when `expression` holds at the time of the hit.
     ///
-    /// - `thread_id`: The thread ID that should trigger this breakpoint.
+    /// See [`Condition`](super::Condition) for the syntax; backends reject a
+    /// condition that doesn't parse when the breakpoint is added.
     ///
     /// ## Example
     ///
-    /// ```rust,no_run
-    /// use ferros_core::breakpoints::builder::BreakpointBuilder;
-    /// use ferros_core::types::{Address, ThreadId};
+    /// ```rust
+    /// use ferros_core::breakpoints::Breakpoint;
     ///
-    /// let thread_id = ThreadId::from(123);
-    /// let builder = BreakpointBuilder::software(Address::from(0x1000)).for_thread(thread_id);
+    /// let request = Breakpoint::symbol("parse").condition("$x0 == 0").build();
     /// ```
-    ///
-    /// ## Note
-    ///
-    /// Thread-specific breakpoints are not yet fully implemented. This method
-    /// stores the thread ID but it may not be enforced until full support
-    /// is added.
-    pub fn for_thread(mut self, thread_id: ThreadId) -> Self
+    pub fn condition(mut self, expression: impl Into<String>) -> Self
     {
-        self.thread_id = Some(thread_id);
+        self.request.options.condition = Some(expression.into());
         self
     }
 
-    /// Add a command to run when the breakpoint is hit.
+    /// Put the breakpoint in the group `name`.
+    pub fn group(mut self, name: impl Into<String>) -> Self
+    {
+        self.request.options.group = Some(name.into());
+        self
+    }
+
+    /// Only stop when `thread` hits the breakpoint.
     ///
-    /// Commands are executed in order when the breakpoint triggers. This
-    /// allows you to automate debugging tasks like printing variable values
-    /// or modifying registers.
+    /// ## Example
     ///
-    /// ## Parameters
+    /// ```rust
+    /// use ferros_core::breakpoints::Breakpoint;
+    /// use ferros_core::types::{Address, ThreadId};
     ///
-    /// - `command`: A command string to execute (e.g., "print $r0").
+    /// let request = Breakpoint::software(Address::from(0x1000))
+    ///     .thread(ThreadId::from(123))
+    ///     .build();
+    /// ```
+    pub fn thread(mut self, thread: ThreadId) -> Self
+    {
+        self.request.options.thread = Some(thread);
+        self
+    }
+
+    /// Remove the breakpoint after its first hit.
+    pub fn one_shot(mut self) -> Self
+    {
+        self.request.options.one_shot = true;
+        self
+    }
+
+    /// Leave the breakpoint out of breakpoint lists.
+    pub fn hidden(mut self) -> Self
+    {
+        self.request.options.hidden = true;
+        self
+    }
+
+    /// Run `action` whenever the breakpoint stops the target, after the actions added before it.
     ///
     /// ## Example
     ///
-    /// ```rust,no_run
-    /// use ferros_core::breakpoints::builder::BreakpointBuilder;
+    /// ```rust
+    /// use ferros_core::breakpoints::{Breakpoint, BreakpointAction};
     /// use ferros_core::types::Address;
     ///
-    /// let builder = BreakpointBuilder::software(Address::from(0x1000))
-    ///     .with_command("print $r0")
-    ///     .with_command("continue");
+    /// let request = Breakpoint::software(Address::from(0x1000))
+    ///     .action(BreakpointAction::Command("print $x0".to_string()))
+    ///     .action(BreakpointAction::Log("checked".to_string()))
+    ///     .build();
     /// ```
-    ///
-    /// ## Note
-    ///
-    /// Breakpoint commands are not yet fully implemented. This method stores
-    /// the command but it may not be executed until full support is added.
-    pub fn with_command(mut self, command: impl Into<String>) -> Self
+    pub fn action(mut self, action: BreakpointAction) -> Self
     {
-        self.commands.push(command.into());
+        self.request.options.actions.push(action);
         self
     }
 
-    /// Install the breakpoint using the configured options.
+    /// Install a software breakpoint instead if every hardware slot is taken.
     ///
-    /// This method creates the breakpoint request and installs it using the
-    /// debugger's `add_breakpoint()` method.
+    /// Only hardware breakpoints have slots; other kinds are unchanged.
     ///
-    /// ## Parameters
+    /// ## Example
     ///
-    /// - `debugger`: A mutable reference to a debugger instance.
+    /// ```rust
+    /// use ferros_core::breakpoints::{Breakpoint, BreakpointRequestKind};
+    /// use ferros_core::types::Address;
     ///
-    /// ## Returns
+    /// let request = Breakpoint::hardware(Address::from(0x1000))
+    ///     .allow_fallback()
+    ///     .build();
+    /// assert!(matches!(
+    ///     request.kind,
+    ///     BreakpointRequestKind::Hardware {
+    ///         allow_fallback: true,
+    ///         ..
+    ///     }
+    /// ));
+    /// ```
+    pub fn allow_fallback(mut self) -> Self
+    {
+        if let BreakpointRequestKind::Hardware { allow_fallback, .. } = &mut self.request.kind {
+            *allow_fallback = true;
+        }
+        self
+    }
+
+    /// Place a software breakpoint even where it doesn't start an instruction.
     ///
-    /// The `BreakpointId` of the newly installed breakpoint.
+    /// Without this, an address in the middle of an instruction is refused,
+    /// since the trap would corrupt it; see
+    /// [`check_instruction_boundary`](crate::breakpoints::check_instruction_boundary).
+    pub fn force(mut self) -> Self
+    {
+        self.request.options.force = true;
+        self
+    }
+
+    /// Install the breakpoint disarmed; enable it later with
+    /// [`Debugger::enable_breakpoint`](crate::debugger::Debugger::enable_breakpoint).
+    pub fn disabled(mut self) -> Self
+    {
+        self.request.enabled = false;
+        self
+    }
+
+    /// Finish the request.
+    #[must_use]
+    pub fn build(self) -> BreakpointRequest
+    {
+        self.request
+    }
+
+    /// Build the request and install it with the debugger's `add_breakpoint()`.
     ///
     /// ## Errors
     ///
@@ -278,55 +301,119 @@ impl BreakpointBuilder
     /// - The debugger is not attached to a process
     /// - A breakpoint already exists at the address
     /// - Hardware breakpoint slots are exhausted (for hardware breakpoints)
-    /// - The breakpoint request is invalid
+    /// - A symbolic location doesn't resolve to any address
     ///
     /// ## Example
     ///
     /// ```rust,no_run
     /// use ferros_core::Debugger;
-    /// use ferros_core::breakpoints::builder::BreakpointBuilder;
-    /// use ferros_core::types::Address;
+    /// use ferros_core::breakpoints::Breakpoint;
     ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
     /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// let bp_id = BreakpointBuilder::software(Address::from(0x1000))
-    ///     .with_hit_count(5)
-    ///     .install(&mut debugger)?;
+    /// let bp_id = Breakpoint::symbol("main")
+    ///     .one_shot()
+    ///     .install(debugger.as_mut())?;
     /// println!("Installed breakpoint with ID: {}", bp_id.raw());
     /// # Ok::<(), ferros_core::error::DebuggerError>(())
     /// ```
-    pub fn install<D: crate::debugger::Debugger>(self, debugger: &mut D) -> Result<crate::breakpoints::BreakpointId>
+    pub fn install<D: crate::debugger::Debugger + ?Sized>(self, debugger: &mut D) -> Result<BreakpointId>
     {
-        // For now, we just install the basic breakpoint.
-        // Advanced features (hit count, conditions, commands) will be
-        // implemented when the breakpoint hit handler supports them.
-        debugger.add_breakpoint(self.request)
+        debugger.add_breakpoint(self.build())
     }
+}
 
-    /// Get the underlying breakpoint request.
-    ///
-    /// This is useful if you want to inspect the request before installing it,
-    /// or if you need to pass it to a custom installation function.
-    pub fn request(&self) -> &BreakpointRequest
-    {
-        &self.request
-    }
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::breakpoints::BreakpointOptions;
 
-    /// Get the configured hit count threshold, if any.
-    pub fn hit_count(&self) -> Option<u64>
+    #[test]
+    fn entry_points_set_the_kind_and_default_options()
     {
-        self.hit_count
-    }
+        let address = Address::from(0x1000);
+        let request = Breakpoint::software(address).build();
+        assert_eq!(request, BreakpointRequest::from(BreakpointRequestKind::Software { address }));
+        assert_eq!(request.options, BreakpointOptions::default());
+        assert!(request.enabled);
+        assert_eq!(request.address(), Some(address));
 
-    /// Get the configured thread ID, if any.
-    pub fn thread_id(&self) -> Option<ThreadId>
-    {
-        self.thread_id
+        assert_eq!(
+            Breakpoint::hardware(address).build().kind,
+            BreakpointRequestKind::Hardware {
+                address,
+                allow_fallback: false,
+            }
+        );
+        assert_eq!(
+            Breakpoint::hardware(address).allow_fallback().build().kind,
+            BreakpointRequestKind::Hardware {
+                address,
+                allow_fallback: true,
+            }
+        );
+        assert_eq!(
+            Breakpoint::software(address).allow_fallback().build().kind,
+            BreakpointRequestKind::Software { address }
+        );
+        assert_eq!(
+            Breakpoint::watchpoint(address, 4, WatchpointAccess::Read).build().kind,
+            BreakpointRequestKind::Watchpoint {
+                address,
+                length: 4,
+                access: WatchpointAccess::Read,
+            }
+        );
+
+        let symbol = Breakpoint::symbol("main").build();
+        assert_eq!(
+            symbol.kind,
+            BreakpointRequestKind::Location(BreakpointLocation::Function("main".to_string()))
+        );
+        assert_eq!(symbol.address(), None);
+        assert_eq!(
+            Breakpoint::line("src/main.rs", 42).build().kind,
+            BreakpointRequestKind::Location(BreakpointLocation::Line {
+                file: "src/main.rs".to_string(),
+                line: 42,
+            })
+        );
     }
 
-    /// Get the configured commands.
-    pub fn commands(&self) -> &[String]
+    #[test]
+    fn chained_options_accumulate()
     {
-        &self.commands
+        let request = Breakpoint::symbol("parse")
+            .condition("$x0 == 0")
+            .group("parser")
+            .thread(ThreadId::from(7))
+            .one_shot()
+            .hidden()
+            .action(BreakpointAction::Command("print $x0".to_string()))
+            .action(BreakpointAction::Log("parsed".to_string()))
+            .disabled()
+            .build();
+
+        assert_eq!(
+            request.options,
+            BreakpointOptions {
+                condition: Some("$x0 == 0".to_string()),
+                group: Some("parser".to_string()),
+                thread: Some(ThreadId::from(7)),
+                one_shot: true,
+                force: false,
+                hidden: true,
+                actions: vec![
+                    BreakpointAction::Command("print $x0".to_string()),
+                    BreakpointAction::Log("parsed".to_string()),
+                ],
+            }
+        );
+        assert!(!request.enabled);
+
+        // Setting an option twice keeps the last value
+        let request = Breakpoint::symbol("parse").group("a").group("b").build();
+        assert_eq!(request.options.group.as_deref(), Some("b"));
     }
 }
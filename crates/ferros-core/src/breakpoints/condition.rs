@@ -0,0 +1,1093 @@
+//! Breakpoint conditions.
+//!
+//! A [`Condition`] is parsed from a breakpoint's
+//! [`condition`](super::BreakpointOptions::condition) string and evaluated
+//! against the registers and memory of the thread that hit the breakpoint. The
+//! target only stops when it holds.
+//!
+//! ## Grammar
+//!
+//! - Registers: `$x0`-`$x30`, `$fp`, `$lr`, `$sp`, `$pc` on ARM64; `$rax`-`$r15`,
+//!   `$rbp`, `$rsp`, `$rip` on x86-64; `$flags` on both
+//! - Integers: `42`, `0x2a`, `true`, `false`
+//! - Arithmetic: `+ - * / % &` and unary `-`
+//! - Dereferences: `*(u32*)($x1 + 0x10)` reads a `u8`/`u16`/`u32`/`u64` or
+//!   `i8`/`i16`/`i32`/`i64`; a bare `*expr` reads a `u64`
+//! - Strings: `str(addr, n)` reads up to `n` bytes (at most [`MAX_STRING_LEN`])
+//!   and stops at the first NUL; compare it with `==`/`!=` against a literal such
+//!   as `"login"` (`\n`, `\t`, `\0`, `\\`, `\"` and `\xNN` escapes) or another `str`
+//! - Comparisons: `== != < <= > >=`, signed
+//! - Logic: `&& || !`, short-circuiting; a bare integer is true when non-zero
+//!
+//! ## Budgets
+//!
+//! Conditions run while the target is held in the exception handler, so every
+//! evaluation is bounded by a [`ConditionBudget`]: how many reads it may issue,
+//! how many bytes it may read and how long it may take. Going over the budget,
+//! or any read failing, is a [`ConditionFailure`] rather than an error; the
+//! [`BreakpointStore`](super::BreakpointStore) treats it as the condition being
+//! false and counts it in the breakpoint's [`stats`](super::BreakpointStats).
+//!
+//! ```rust
+//! use ferros_core::breakpoints::{Condition, ConditionBudget};
+//! use ferros_core::types::{Address, Architecture, Registers};
+//!
+//! let condition = Condition::parse(r#"*(u32*)($x1 + 0x10) == 0 && str($x0, 16) == "login""#)?;
+//!
+//! let mut registers = Registers::new().with_arch(Architecture::Arm64);
+//! registers.general = vec![0; 31];
+//! registers.general[0] = 0x1000;
+//! registers.general[1] = 0x2000;
+//! let mut memory = |address: Address, len: usize| match address.value() {
+//!     0x1000 => Ok(b"login\0garbage!!!"[..len].to_vec()),
+//!     _ => Ok(vec![0; len]),
+//! };
+//!
+//! assert_eq!(
+//!     condition.evaluate(&registers, &mut memory, &ConditionBudget::default()),
+//!     Ok(true)
+//! );
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::error::{DebuggerError, Result};
+use crate::types::{Address, Arm64Register, RegisterId, Registers, X86_64Register};
+
+/// Longest `str(addr, n)` read a condition may ask for.
+pub const MAX_STRING_LEN: usize = 256;
+
+/// Page size `str()` splits its reads at, so a short string near the end of a
+/// mapping doesn't fail because the rest of `n` is unmapped.
+const STRING_PAGE_SIZE: u64 = 4096;
+
+/// Limits on a single condition evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConditionBudget
+{
+    /// Memory reads one evaluation may issue.
+    pub max_reads: usize,
+    /// Bytes one evaluation may read in total.
+    pub max_bytes: usize,
+    /// Wall time one evaluation may take.
+    pub max_time: Duration,
+}
+
+impl Default for ConditionBudget
+{
+    fn default() -> Self
+    {
+        Self {
+            max_reads: 16,
+            max_bytes: 1024,
+            max_time: Duration::from_millis(1),
+        }
+    }
+}
+
+/// Why a condition couldn't be evaluated.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConditionFailure
+{
+    /// The register isn't available on the target's architecture.
+    #[error("register ${name} is not available on this target")]
+    MissingRegister
+    {
+        /// Register name as written in the condition.
+        name: String,
+    },
+    /// A memory read failed or came back short.
+    #[error("reading {len} bytes at {address} failed")]
+    ReadFailed
+    {
+        /// Address of the read.
+        address: Address,
+        /// Bytes requested.
+        len: usize,
+    },
+    /// The evaluation needed more reads than [`ConditionBudget::max_reads`].
+    #[error("needed more than {limit} memory reads")]
+    TooManyReads
+    {
+        /// The budget's read limit.
+        limit: usize,
+    },
+    /// The evaluation needed more bytes than [`ConditionBudget::max_bytes`].
+    #[error("needed more than {limit} bytes of memory")]
+    TooManyBytes
+    {
+        /// The budget's byte limit.
+        limit: usize,
+    },
+    /// The evaluation ran longer than [`ConditionBudget::max_time`].
+    #[error("took longer than {limit:?}")]
+    TimedOut
+    {
+        /// The budget's time limit.
+        limit: Duration,
+    },
+    /// `/` or `%` by zero.
+    #[error("division by zero")]
+    DivisionByZero,
+}
+
+/// Reads target memory for a condition.
+pub trait ConditionMemory
+{
+    /// Read `len` bytes at `address`.
+    ///
+    /// ## Errors
+    ///
+    /// Any error, which fails the evaluation with [`ConditionFailure::ReadFailed`].
+    fn read(&mut self, address: Address, len: usize) -> Result<Vec<u8>>;
+}
+
+impl<F> ConditionMemory for F
+where
+    F: FnMut(Address, usize) -> Result<Vec<u8>>,
+{
+    fn read(&mut self, address: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self(address, len)
+    }
+}
+
+/// A parsed breakpoint condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition
+{
+    source: String,
+    expr: Expr,
+}
+
+impl Condition
+{
+    /// Parse a condition.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: The text isn't a valid condition, with the column of the problem
+    pub fn parse(source: &str) -> Result<Self>
+    {
+        let invalid = |message: String| DebuggerError::InvalidArgument(format!("Invalid condition `{source}`: {message}"));
+        let (expr, kind) = parse_expression(source, "condition").map_err(invalid)?;
+        if kind != Kind::Int {
+            return Err(invalid("a string must be compared with `==` or `!=`".to_string()));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// The text the condition was parsed from.
+    #[must_use]
+    pub fn source(&self) -> &str
+    {
+        &self.source
+    }
+
+    /// Evaluate the condition for a thread with `registers`.
+    ///
+    /// ## Errors
+    ///
+    /// A [`ConditionFailure`] if a register is missing, a read fails or the
+    /// evaluation goes over `budget`.
+    pub fn evaluate(
+        &self,
+        registers: &Registers,
+        memory: &mut dyn ConditionMemory,
+        budget: &ConditionBudget,
+    ) -> std::result::Result<bool, ConditionFailure>
+    {
+        let mut eval = Evaluator::new(registers, memory, budget);
+        Ok(eval.int(&self.expr)? != 0)
+    }
+}
+
+impl fmt::Display for Condition
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(&self.source)
+    }
+}
+
+/// Width and signedness of a typed dereference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct IntType
+{
+    size: usize,
+    signed: bool,
+}
+
+impl IntType
+{
+    fn from_name(name: &str) -> Option<Self>
+    {
+        let (signed, bits) = match name.split_at_checked(1)? {
+            ("u", bits) => (false, bits),
+            ("i", bits) => (true, bits),
+            _ => return None,
+        };
+        let size = match bits {
+            "8" => 1,
+            "16" => 2,
+            "32" => 4,
+            "64" => 8,
+            _ => return None,
+        };
+        Some(Self { size, signed })
+    }
+
+    fn decode(self, bytes: &[u8]) -> i128
+    {
+        let mut raw = [0_u8; 8];
+        raw[..self.size].copy_from_slice(bytes);
+        let value = u64::from_le_bytes(raw);
+        if self.signed {
+            let shift = 64 - self.size * 8;
+            i128::from(((value << shift) as i64) >> shift)
+        } else {
+            i128::from(value)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinaryOp
+{
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    BitAnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CompareOp
+{
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Expr
+{
+    Int(i128),
+    Bytes(Vec<u8>),
+    Register
+    {
+        name: String,
+        id: RegisterId,
+    },
+    Deref
+    {
+        ty: IntType,
+        address: Box<Expr>,
+    },
+    Str
+    {
+        address: Box<Expr>,
+        max_len: usize,
+    },
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary
+    {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    Compare
+    {
+        op: CompareOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// Static type of an expression: an integer, or bytes from a string literal or `str()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind
+{
+    Int,
+    Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind
+{
+    Int(i128),
+    Str(Vec<u8>),
+    Ident(String),
+    Register(String),
+    Punct(&'static str),
+}
+
+impl fmt::Display for TokenKind
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            Self::Int(value) => write!(f, "`{value}`"),
+            Self::Str(_) => f.write_str("string"),
+            Self::Ident(name) => write!(f, "`{name}`"),
+            Self::Register(name) => write!(f, "`${name}`"),
+            Self::Punct(punct) => write!(f, "`{punct}`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token
+{
+    kind: TokenKind,
+    /// 1-based column of the token's first character.
+    column: usize,
+}
+
+/// Punctuation, longest first so `==` isn't read as `=` `=`.
+static PUNCTUATION: [&str; 18] = [
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "+", "-", "*", "/", "%", "&", "(", ")", ",",
+];
+
+fn tokenize(source: &str) -> std::result::Result<Vec<Token>, String>
+{
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let word_end = |start: usize| {
+            (start..chars.len())
+                .find(|&j| !(chars[j].is_ascii_alphanumeric() || chars[j] == '_'))
+                .unwrap_or(chars.len())
+        };
+        let kind = if c.is_ascii_digit() {
+            let end = word_end(i);
+            let text: String = chars[i..end].iter().collect();
+            i = end;
+            let value = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => text.parse::<u64>(),
+            }
+            .map_err(|_| format!("invalid number `{text}` at column {column}"))?;
+            TokenKind::Int(i128::from(value))
+        } else if c == '$' {
+            let end = word_end(i + 1);
+            if end == i + 1 {
+                return Err(format!("expected a register name after `$` at column {column}"));
+            }
+            let name: String = chars[i + 1..end].iter().collect();
+            i = end;
+            TokenKind::Register(name.to_ascii_lowercase())
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = word_end(i);
+            let name: String = chars[i..end].iter().collect();
+            i = end;
+            TokenKind::Ident(name)
+        } else if c == '"' {
+            let (bytes, end) =
+                string_literal(&chars, i + 1).ok_or_else(|| format!("unterminated string at column {column}"))?;
+            i = end;
+            TokenKind::Str(bytes)
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let punct = PUNCTUATION
+                .iter()
+                .find(|punct| rest.starts_with(*punct))
+                .ok_or_else(|| format!("unexpected `{c}` at column {column}"))?;
+            i += punct.len();
+            TokenKind::Punct(punct)
+        };
+        tokens.push(Token { kind, column });
+    }
+    Ok(tokens)
+}
+
+/// Read a string literal body starting after the opening quote; returns the
+/// bytes and the index after the closing quote.
+fn string_literal(chars: &[char], start: usize) -> Option<(Vec<u8>, usize)>
+{
+    let mut bytes = Vec::new();
+    let mut i = start;
+    loop {
+        match *chars.get(i)? {
+            '"' => return Some((bytes, i + 1)),
+            '\\' => {
+                let escaped = *chars.get(i + 1)?;
+                i += 2;
+                match escaped {
+                    'n' => bytes.push(b'\n'),
+                    't' => bytes.push(b'\t'),
+                    'r' => bytes.push(b'\r'),
+                    '0' => bytes.push(0),
+                    'x' => {
+                        let hex: String = chars.get(i..i + 2)?.iter().collect();
+                        bytes.push(u8::from_str_radix(&hex, 16).ok()?);
+                        i += 2;
+                    }
+                    other => {
+                        let mut buffer = [0; 4];
+                        bytes.extend_from_slice(other.encode_utf8(&mut buffer).as_bytes());
+                    }
+                }
+            }
+            c => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Map a register name (without `$`) to its identifier.
+fn register_id(name: &str) -> Option<RegisterId>
+{
+    let id = match name {
+        "pc" | "rip" => RegisterId::Pc,
+        "sp" | "rsp" => RegisterId::Sp,
+        "fp" | "rbp" => RegisterId::Fp,
+        "flags" | "cpsr" | "rflags" => RegisterId::Status,
+        "lr" => RegisterId::Arm64(Arm64Register::X(30)),
+        "rax" => RegisterId::X86_64(X86_64Register::Rax),
+        "rbx" => RegisterId::X86_64(X86_64Register::Rbx),
+        "rcx" => RegisterId::X86_64(X86_64Register::Rcx),
+        "rdx" => RegisterId::X86_64(X86_64Register::Rdx),
+        "rsi" => RegisterId::X86_64(X86_64Register::Rsi),
+        "rdi" => RegisterId::X86_64(X86_64Register::Rdi),
+        "r8" => RegisterId::X86_64(X86_64Register::R8),
+        "r9" => RegisterId::X86_64(X86_64Register::R9),
+        "r10" => RegisterId::X86_64(X86_64Register::R10),
+        "r11" => RegisterId::X86_64(X86_64Register::R11),
+        "r12" => RegisterId::X86_64(X86_64Register::R12),
+        "r13" => RegisterId::X86_64(X86_64Register::R13),
+        "r14" => RegisterId::X86_64(X86_64Register::R14),
+        "r15" => RegisterId::X86_64(X86_64Register::R15),
+        _ => {
+            let index: u8 = name.strip_prefix('x')?.parse().ok()?;
+            return (index <= 30).then_some(RegisterId::Arm64(Arm64Register::X(index)));
+        }
+    };
+    Some(id)
+}
+
+impl Expr
+{
+    /// Whether the value is a pointer: `$pc`, `$sp`, `$fp` or `$lr`, possibly
+    /// offset by an integer.
+    pub(crate) fn is_pointer(&self) -> bool
+    {
+        match self {
+            Self::Register { id, .. } => matches!(
+                id,
+                RegisterId::Pc | RegisterId::Sp | RegisterId::Fp | RegisterId::Arm64(Arm64Register::X(30))
+            ),
+            Self::Binary {
+                op: BinaryOp::Add,
+                lhs,
+                rhs,
+            } => (lhs.is_pointer() && !rhs.is_pointer()) || (rhs.is_pointer() && !lhs.is_pointer()),
+            Self::Binary {
+                op: BinaryOp::Sub,
+                lhs,
+                rhs,
+            } => lhs.is_pointer() && !rhs.is_pointer(),
+            _ => false,
+        }
+    }
+}
+
+/// Parse all of `source` as one expression; `what` names it in messages.
+///
+/// Messages name the offending token and its column.
+pub(crate) fn parse_expression(source: &str, what: &'static str) -> std::result::Result<(Expr, Kind), String>
+{
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0, what };
+    let parsed = parser.logical_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected {} at column {}", token.kind, token.column));
+    }
+    Ok(parsed)
+}
+
+struct Parser
+{
+    tokens: Vec<Token>,
+    pos: usize,
+    /// What is being parsed, for "but the condition ended".
+    what: &'static str,
+}
+
+type Parsed = std::result::Result<(Expr, Kind), String>;
+
+impl Parser
+{
+    fn peek(&self) -> Option<&Token>
+    {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_kind(&self, offset: usize) -> Option<&TokenKind>
+    {
+        self.tokens.get(self.pos + offset).map(|token| &token.kind)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool
+    {
+        if matches!(self.peek_kind(0), Some(TokenKind::Punct(p)) if *p == punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> std::result::Result<(), String>
+    {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(format!("expected `{punct}` {}", self.location()))
+        }
+    }
+
+    fn location(&self) -> String
+    {
+        match self.peek() {
+            Some(token) => format!("but found {} at column {}", token.kind, token.column),
+            None => format!("but the {} ended", self.what),
+        }
+    }
+
+    fn int_operand(&mut self, operand: Parsed, what: &str) -> std::result::Result<Expr, String>
+    {
+        match operand? {
+            (expr, Kind::Int) => Ok(expr),
+            (_, Kind::Bytes) => Err(format!("{what} needs an integer, not a string")),
+        }
+    }
+
+    fn logical_or(&mut self) -> Parsed
+    {
+        let mut lhs = self.logical_and()?;
+        while self.eat("||") {
+            let left = self.int_operand(Ok(lhs), "`||`")?;
+            let right = self.logical_and();
+            let right = self.int_operand(right, "`||`")?;
+            lhs = (Expr::Or(Box::new(left), Box::new(right)), Kind::Int);
+        }
+        Ok(lhs)
+    }
+
+    fn logical_and(&mut self) -> Parsed
+    {
+        let mut lhs = self.comparison()?;
+        while self.eat("&&") {
+            let left = self.int_operand(Ok(lhs), "`&&`")?;
+            let right = self.comparison();
+            let right = self.int_operand(right, "`&&`")?;
+            lhs = (Expr::And(Box::new(left), Box::new(right)), Kind::Int);
+        }
+        Ok(lhs)
+    }
+
+    fn comparison(&mut self) -> Parsed
+    {
+        let (lhs, lhs_kind) = self.bit_and()?;
+        let op = match self.peek_kind(0) {
+            Some(TokenKind::Punct("==")) => CompareOp::Eq,
+            Some(TokenKind::Punct("!=")) => CompareOp::Ne,
+            Some(TokenKind::Punct("<")) => CompareOp::Lt,
+            Some(TokenKind::Punct("<=")) => CompareOp::Le,
+            Some(TokenKind::Punct(">")) => CompareOp::Gt,
+            Some(TokenKind::Punct(">=")) => CompareOp::Ge,
+            _ => return Ok((lhs, lhs_kind)),
+        };
+        self.pos += 1;
+        let (rhs, rhs_kind) = self.bit_and()?;
+        if lhs_kind != rhs_kind {
+            return Err("can't compare a string with an integer".to_string());
+        }
+        if lhs_kind == Kind::Bytes && !matches!(op, CompareOp::Eq | CompareOp::Ne) {
+            return Err("strings can only be compared with `==` or `!=`".to_string());
+        }
+        Ok((
+            Expr::Compare {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+            Kind::Int,
+        ))
+    }
+
+    fn binary_level(&mut self, ops: &[(&'static str, BinaryOp)], next: fn(&mut Self) -> Parsed) -> Parsed
+    {
+        let mut lhs = next(self)?;
+        'outer: loop {
+            for &(punct, op) in ops {
+                if self.eat(punct) {
+                    let what = format!("`{punct}`");
+                    let left = self.int_operand(Ok(lhs), &what)?;
+                    let right = next(self);
+                    let right = self.int_operand(right, &what)?;
+                    lhs = (
+                        Expr::Binary {
+                            op,
+                            lhs: Box::new(left),
+                            rhs: Box::new(right),
+                        },
+                        Kind::Int,
+                    );
+                    continue 'outer;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn bit_and(&mut self) -> Parsed
+    {
+        self.binary_level(&[("&", BinaryOp::BitAnd)], Self::sum)
+    }
+
+    fn sum(&mut self) -> Parsed
+    {
+        self.binary_level(&[("+", BinaryOp::Add), ("-", BinaryOp::Sub)], Self::product)
+    }
+
+    fn product(&mut self) -> Parsed
+    {
+        self.binary_level(
+            &[("*", BinaryOp::Mul), ("/", BinaryOp::Div), ("%", BinaryOp::Rem)],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Parsed
+    {
+        if self.eat("!") {
+            let operand = self.unary();
+            return Ok((Expr::Not(Box::new(self.int_operand(operand, "`!`")?)), Kind::Int));
+        }
+        if self.eat("-") {
+            let operand = self.unary();
+            return Ok((Expr::Neg(Box::new(self.int_operand(operand, "`-`")?)), Kind::Int));
+        }
+        if self.eat("*") {
+            let ty = self.pointer_cast()?.unwrap_or(IntType { size: 8, signed: false });
+            let operand = self.unary();
+            let address = self.int_operand(operand, "a dereference")?;
+            return Ok((
+                Expr::Deref {
+                    ty,
+                    address: Box::new(address),
+                },
+                Kind::Int,
+            ));
+        }
+        self.primary()
+    }
+
+    /// Parse `(type*)` after a `*`, if present.
+    fn pointer_cast(&mut self) -> std::result::Result<Option<IntType>, String>
+    {
+        let (Some(TokenKind::Punct("(")), Some(TokenKind::Ident(name)), Some(TokenKind::Punct("*"))) =
+            (self.peek_kind(0), self.peek_kind(1), self.peek_kind(2))
+        else {
+            return Ok(None);
+        };
+        let ty = IntType::from_name(name).ok_or_else(|| format!("unknown type `{name}`, expected u8-u64 or i8-i64"))?;
+        self.pos += 3;
+        self.expect(")")?;
+        Ok(Some(ty))
+    }
+
+    fn primary(&mut self) -> Parsed
+    {
+        let Some(token) = self.peek().cloned() else {
+            return Err(format!("expected a value but the {} ended", self.what));
+        };
+        self.pos += 1;
+        match token.kind {
+            TokenKind::Int(value) => Ok((Expr::Int(value), Kind::Int)),
+            TokenKind::Str(bytes) => Ok((Expr::Bytes(bytes), Kind::Bytes)),
+            TokenKind::Register(name) => {
+                let id =
+                    register_id(&name).ok_or_else(|| format!("unknown register `${name}` at column {}", token.column))?;
+                Ok((Expr::Register { name, id }, Kind::Int))
+            }
+            TokenKind::Ident(name) if name == "true" => Ok((Expr::Int(1), Kind::Int)),
+            TokenKind::Ident(name) if name == "false" => Ok((Expr::Int(0), Kind::Int)),
+            TokenKind::Ident(name) if name == "str" => self.string_call(),
+            TokenKind::Ident(name) => Err(format!(
+                "unknown name `{name}` at column {}; refer to registers as `$x0` or `$rdi`",
+                token.column
+            )),
+            TokenKind::Punct("(") => {
+                let inner = self.logical_or()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            kind => Err(format!("expected a value but found {kind} at column {}", token.column)),
+        }
+    }
+
+    /// Parse the `(addr, n)` of `str(addr, n)`.
+    fn string_call(&mut self) -> Parsed
+    {
+        self.expect("(")?;
+        let address = self.logical_or();
+        let address = self.int_operand(address, "`str()`")?;
+        self.expect(",")?;
+        let max_len = match self.peek_kind(0) {
+            Some(TokenKind::Int(len)) => usize::try_from(*len).unwrap_or(usize::MAX),
+            _ => return Err(format!("expected a length for `str()` {}", self.location())),
+        };
+        if max_len == 0 || max_len > MAX_STRING_LEN {
+            return Err(format!("`str()` reads 1 to {MAX_STRING_LEN} bytes, not {max_len}"));
+        }
+        self.pos += 1;
+        self.expect(")")?;
+        Ok((
+            Expr::Str {
+                address: Box::new(address),
+                max_len,
+            },
+            Kind::Bytes,
+        ))
+    }
+}
+
+pub(crate) struct Evaluator<'a>
+{
+    registers: &'a Registers,
+    memory: &'a mut dyn ConditionMemory,
+    budget: &'a ConditionBudget,
+    reads: usize,
+    bytes: usize,
+    deadline: Instant,
+}
+
+type Evaluated<T> = std::result::Result<T, ConditionFailure>;
+
+impl<'a> Evaluator<'a>
+{
+    pub(crate) fn new(registers: &'a Registers, memory: &'a mut dyn ConditionMemory, budget: &'a ConditionBudget) -> Self
+    {
+        Self {
+            registers,
+            memory,
+            budget,
+            reads: 0,
+            bytes: 0,
+            deadline: Instant::now() + budget.max_time,
+        }
+    }
+
+    pub(crate) fn int(&mut self, expr: &Expr) -> Evaluated<i128>
+    {
+        if Instant::now() >= self.deadline {
+            return Err(ConditionFailure::TimedOut {
+                limit: self.budget.max_time,
+            });
+        }
+        let value = match expr {
+            Expr::Int(value) => *value,
+            Expr::Register { name, id } => self
+                .registers
+                .get(*id)
+                .map(i128::from)
+                .ok_or_else(|| ConditionFailure::MissingRegister { name: name.clone() })?,
+            Expr::Deref { ty, address } => {
+                let address = self.address(address)?;
+                let bytes = self.read(address, ty.size)?;
+                ty.decode(&bytes)
+            }
+            Expr::Neg(operand) => self.int(operand)?.wrapping_neg(),
+            Expr::Not(operand) => i128::from(self.int(operand)? == 0),
+            Expr::Binary { op, lhs, rhs } => {
+                let (lhs, rhs) = (self.int(lhs)?, self.int(rhs)?);
+                match op {
+                    BinaryOp::Add => lhs.wrapping_add(rhs),
+                    BinaryOp::Sub => lhs.wrapping_sub(rhs),
+                    BinaryOp::Mul => lhs.wrapping_mul(rhs),
+                    BinaryOp::Div => lhs.checked_div(rhs).ok_or(ConditionFailure::DivisionByZero)?,
+                    BinaryOp::Rem => lhs.checked_rem(rhs).ok_or(ConditionFailure::DivisionByZero)?,
+                    BinaryOp::BitAnd => lhs & rhs,
+                }
+            }
+            Expr::Compare { op, lhs, rhs } => i128::from(self.compare(*op, lhs, rhs)?),
+            Expr::And(lhs, rhs) => i128::from(self.int(lhs)? != 0 && self.int(rhs)? != 0),
+            Expr::Or(lhs, rhs) => i128::from(self.int(lhs)? != 0 || self.int(rhs)? != 0),
+            Expr::Bytes(_) | Expr::Str { .. } => unreachable!("the parser only accepts strings in comparisons"),
+        };
+        Ok(value)
+    }
+
+    fn compare(&mut self, op: CompareOp, lhs: &Expr, rhs: &Expr) -> Evaluated<bool>
+    {
+        if let (Some(lhs), Some(rhs)) = (self.bytes(lhs)?, self.bytes(rhs)?) {
+            return Ok((lhs == rhs) == (op == CompareOp::Eq));
+        }
+        let (lhs, rhs) = (self.int(lhs)?, self.int(rhs)?);
+        Ok(match op {
+            CompareOp::Eq => lhs == rhs,
+            CompareOp::Ne => lhs != rhs,
+            CompareOp::Lt => lhs < rhs,
+            CompareOp::Le => lhs <= rhs,
+            CompareOp::Gt => lhs > rhs,
+            CompareOp::Ge => lhs >= rhs,
+        })
+    }
+
+    /// The bytes of a string operand, or `None` for an integer one.
+    pub(crate) fn bytes(&mut self, expr: &Expr) -> Evaluated<Option<Vec<u8>>>
+    {
+        match expr {
+            Expr::Bytes(bytes) => Ok(Some(bytes.clone())),
+            Expr::Str { address, max_len } => {
+                let start = self.address(address)?;
+                self.c_string(start, *max_len).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Read up to `max_len` bytes at `start`, stopping at a NUL, one page at a time.
+    fn c_string(&mut self, start: Address, max_len: usize) -> Evaluated<Vec<u8>>
+    {
+        let mut string = Vec::new();
+        while string.len() < max_len {
+            let address = start.value().wrapping_add(string.len() as u64);
+            let to_page_end = STRING_PAGE_SIZE - address % STRING_PAGE_SIZE;
+            let len = (max_len - string.len()).min(usize::try_from(to_page_end).unwrap_or(usize::MAX));
+            let chunk = self.read(Address::from(address), len)?;
+            if let Some(nul) = chunk.iter().position(|&byte| byte == 0) {
+                string.extend_from_slice(&chunk[..nul]);
+                break;
+            }
+            string.extend_from_slice(&chunk);
+        }
+        Ok(string)
+    }
+
+    fn address(&mut self, expr: &Expr) -> Evaluated<Address>
+    {
+        // Truncation is the point: addresses wrap like the target's pointers
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Ok(Address::from(self.int(expr)? as u64))
+    }
+
+    fn read(&mut self, address: Address, len: usize) -> Evaluated<Vec<u8>>
+    {
+        if self.reads >= self.budget.max_reads {
+            return Err(ConditionFailure::TooManyReads {
+                limit: self.budget.max_reads,
+            });
+        }
+        if self.bytes + len > self.budget.max_bytes {
+            return Err(ConditionFailure::TooManyBytes {
+                limit: self.budget.max_bytes,
+            });
+        }
+        if Instant::now() >= self.deadline {
+            return Err(ConditionFailure::TimedOut {
+                limit: self.budget.max_time,
+            });
+        }
+        self.reads += 1;
+        self.bytes += len;
+        match self.memory.read(address, len) {
+            Ok(bytes) if bytes.len() == len => Ok(bytes),
+            _ => Err(ConditionFailure::ReadFailed { address, len }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::Architecture;
+
+    /// Sparse memory whose reads fail for unmapped bytes or on demand.
+    #[derive(Default)]
+    struct MockMemory
+    {
+        bytes: HashMap<u64, u8>,
+        fail_reads: bool,
+        reads: usize,
+    }
+
+    impl MockMemory
+    {
+        fn with(mut self, address: u64, bytes: &[u8]) -> Self
+        {
+            for (offset, byte) in bytes.iter().enumerate() {
+                self.bytes.insert(address + offset as u64, *byte);
+            }
+            self
+        }
+    }
+
+    impl ConditionMemory for MockMemory
+    {
+        fn read(&mut self, address: Address, len: usize) -> Result<Vec<u8>>
+        {
+            self.reads += 1;
+            if self.fail_reads {
+                return Err(DebuggerError::InvalidArgument("read failed".to_string()));
+            }
+            (0..len as u64)
+                .map(|offset| self.bytes.get(&(address.value() + offset)).copied())
+                .collect::<Option<Vec<u8>>>()
+                .ok_or(DebuggerError::MemoryReadFailed {
+                    address,
+                    length: len,
+                    kern_return: None,
+                })
+        }
+    }
+
+    fn arm64(x0: u64, x1: u64) -> Registers
+    {
+        let mut registers = Registers::new().with_arch(Architecture::Arm64);
+        registers.general = vec![0; 31];
+        registers.general[0] = x0;
+        registers.general[1] = x1;
+        registers
+    }
+
+    fn eval(source: &str, memory: &mut MockMemory) -> std::result::Result<bool, ConditionFailure>
+    {
+        Condition::parse(source)
+            .unwrap()
+            .evaluate(&arm64(0x1000, 0x2000), memory, &ConditionBudget::default())
+    }
+
+    #[test]
+    fn typed_dereferences_strings_and_logic_evaluate()
+    {
+        let mut memory = MockMemory::default()
+            .with(0x1000, b"login\0garbage!!!")
+            .with(0x2010, &[0, 0, 0, 0])
+            .with(0x2018, &[0xfe, 0xff, 0xff, 0xff]);
+
+        assert_eq!(eval("*(u32*)($x1+0x10) == 0", &mut memory), Ok(true));
+        assert_eq!(eval("*(i32*)($x1 + 0x18) == -2", &mut memory), Ok(true));
+        assert_eq!(eval("*(u32*)($x1 + 0x18) == 0xfffffffe", &mut memory), Ok(true));
+        assert_eq!(eval(r#"str($x0, 16) == "login""#, &mut memory), Ok(true));
+        assert_eq!(eval(r#"str($x0, 3) == "log""#, &mut memory), Ok(true));
+        assert_eq!(eval(r#"str($x0, 16) != "logout""#, &mut memory), Ok(true));
+        assert_eq!(eval(r#"str($x0, 16) == "log\x69n""#, &mut memory), Ok(true));
+        assert_eq!(eval("$x0 == 0x1000 && !($x1 < 0x1000) || $x0 / 0", &mut memory), Ok(true));
+        assert_eq!(eval("$x0 == 0 && $x0 / 0", &mut memory), Ok(false));
+        assert_eq!(eval("($x1 - $x0) * 2 == 0x2000 && ($x1 & 0xf00) == 0", &mut memory), Ok(true));
+        assert_eq!(eval("$x0 / 0", &mut memory), Err(ConditionFailure::DivisionByZero));
+    }
+
+    #[test]
+    fn malformed_conditions_are_rejected_with_a_column()
+    {
+        let message = |source: &str| Condition::parse(source).unwrap_err().to_string();
+
+        assert!(message("count > 3").contains("unknown name `count` at column 1"));
+        assert!(message("$x0 ==").contains("expected a value but the condition ended"));
+        assert!(message("$q7 == 1").contains("unknown register `$q7`"));
+        assert!(message("*(u128*)$x0 == 0").contains("unknown type `u128`"));
+        assert!(message(r#"str($x0, 16) < "a""#).contains("only be compared with `==` or `!=`"));
+        assert!(message(r#"str($x0, 16) == 1"#).contains("can't compare a string with an integer"));
+        assert!(message("str($x0, 4096) == \"a\"").contains("1 to 256 bytes"));
+        assert!(message(r#"str($x0, 4)"#).contains("must be compared"));
+        assert!(message("$x0 == 1 )").contains("unexpected `)` at column 10"));
+        assert!(message(r#"$x0 == "abc"#).contains("unterminated string"));
+    }
+
+    #[test]
+    fn failed_reads_and_budget_overruns_are_failures_not_panics()
+    {
+        let mut failing = MockMemory {
+            fail_reads: true,
+            ..MockMemory::default()
+        };
+        assert_eq!(
+            eval("*(u64*)$x0 == 0", &mut failing),
+            Err(ConditionFailure::ReadFailed {
+                address: Address::from(0x1000),
+                len: 8,
+            })
+        );
+
+        let mut memory = MockMemory::default().with(0x1000, &[0; 64]);
+        let condition = Condition::parse("*$x0 + *($x0 + 8) + *($x0 + 16) == 0").unwrap();
+        let registers = arm64(0x1000, 0);
+        let two_reads = ConditionBudget {
+            max_reads: 2,
+            ..ConditionBudget::default()
+        };
+        assert_eq!(
+            condition.evaluate(&registers, &mut memory, &two_reads),
+            Err(ConditionFailure::TooManyReads { limit: 2 })
+        );
+        // The third read was refused before it reached the target
+        assert_eq!(memory.reads, 2);
+
+        let twenty_bytes = ConditionBudget {
+            max_bytes: 20,
+            ..ConditionBudget::default()
+        };
+        assert_eq!(
+            condition.evaluate(&registers, &mut memory, &twenty_bytes),
+            Err(ConditionFailure::TooManyBytes { limit: 20 })
+        );
+
+        let no_time = ConditionBudget {
+            max_time: Duration::ZERO,
+            ..ConditionBudget::default()
+        };
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(matches!(
+            condition.evaluate(&registers, &mut memory, &no_time),
+            Err(ConditionFailure::TimedOut { .. })
+        ));
+
+        assert_eq!(
+            eval("$rax == 0", &mut memory),
+            Err(ConditionFailure::MissingRegister { name: "rax".to_string() })
+        );
+    }
+
+    #[test]
+    fn strings_stop_at_page_ends_and_nul()
+    {
+        // "hi" ends two bytes before an unmapped page; a single 16-byte read would fail
+        let mut memory = MockMemory::default().with(0x1ffc, b"ahi\0");
+        let condition = Condition::parse(r#"str($x0, 16) == "hi""#).unwrap();
+        assert_eq!(
+            condition.evaluate(&arm64(0x1ffd, 0), &mut memory, &ConditionBudget::default()),
+            Ok(true)
+        );
+        assert_eq!(memory.reads, 1);
+    }
+}
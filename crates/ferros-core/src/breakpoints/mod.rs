@@ -5,15 +5,49 @@
 //! registers, etc.). The backend is responsible for actually installing /
 //! restoring traps but can use this structure to track ids, states, and hit
 //! counts in a thread-safe manner.
+//!
+//! Breakpoints are described with a [`BreakpointRequest`], most easily put
+//! together with the fluent [`builder`]:
+//!
+//! ```rust
+//! use ferros_core::breakpoints::{Breakpoint, BreakpointLocation, BreakpointRequestKind};
+//!
+//! let request = Breakpoint::line("src/main.rs", 42)
+//!     .condition("$x1 == 0")
+//!     .one_shot()
+//!     .build();
+//! assert_eq!(
+//!     request.kind,
+//!     BreakpointRequestKind::Location(BreakpointLocation::Line {
+//!         file: "src/main.rs".to_string(),
+//!         line: 42,
+//!     })
+//! );
+//! assert!(request.options.one_shot);
+//! ```
 
 pub mod builder;
+pub mod condition;
+pub mod persist;
+pub mod stats;
 use std::collections::HashMap;
-use std::time::SystemTime;
+use std::ops::Range;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::types::Address;
+pub use builder::{Breakpoint, BreakpointBuilder};
+pub use condition::{Condition, ConditionBudget, ConditionFailure, ConditionMemory};
+pub use persist::{BreakpointFile, RestoreReport, SavedBreakpoint, UnresolvedBreakpoint};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+pub use stats::{BreakpointStats, OVERHEAD_WARNING_FRACTION, STATS_WINDOW};
+
+use crate::disassembly::disassemble;
+use crate::error::{DebuggerError, Result};
+use crate::types::{Address, Architecture, Registers, ThreadId};
 
 /// Unique identifier for a breakpoint managed by the debugger.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
 pub struct BreakpointId(u64);
 
 impl BreakpointId
@@ -34,7 +68,8 @@ impl BreakpointId
 }
 
 /// Different kinds of breakpoints / watchpoints that can be tracked.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BreakpointKind
 {
     /// Software breakpoint implemented via trap instructions (BRK/INT3).
@@ -46,7 +81,8 @@ pub enum BreakpointKind
 }
 
 /// Access type for data watchpoints.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum WatchpointAccess
 {
     /// Trigger on read access to the watched memory region.
@@ -57,9 +93,9 @@ pub enum WatchpointAccess
     ReadWrite,
 }
 
-/// High-level request to create a breakpoint.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum BreakpointRequest
+/// What a [`BreakpointRequest`] traps on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BreakpointRequestKind
 {
     /// Software breakpoint at an address.
     Software
@@ -72,6 +108,8 @@ pub enum BreakpointRequest
     {
         /// The memory address where the breakpoint should be placed.
         address: Address,
+        /// Install a software breakpoint instead when every hardware slot is taken.
+        allow_fallback: bool,
     },
     /// Watchpoint on a memory range.
     Watchpoint
@@ -83,10 +121,176 @@ pub enum BreakpointRequest
         /// The type of memory access that should trigger the watchpoint.
         access: WatchpointAccess,
     },
+    /// Software breakpoint at a symbolic location.
+    ///
+    /// Backends resolve it with
+    /// [`Debugger::resolve_location`](crate::debugger::Debugger::resolve_location)
+    /// when installing and trap on the first address it resolves to.
+    Location(BreakpointLocation),
+}
+
+/// Something to do when a breakpoint is hit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakpointAction
+{
+    /// Log a message.
+    Log(String),
+    /// Run a front-end command, such as `print $x0`.
+    Command(String),
+}
+
+/// Behaviour attached to a breakpoint beyond where it traps.
+///
+/// Backends record these on the breakpoint's [`BreakpointInfo`] and enforce
+/// the condition, thread filter and one-shot removal on a hit; front-ends run
+/// the [`actions`](Self::actions) once the stop reaches them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BreakpointOptions
+{
+    /// Expression that must hold for a hit to stop the target, see [`Condition`].
+    pub condition: Option<String>,
+    /// Name of the group the breakpoint belongs to, for enabling and disabling
+    /// together, as the TUI's `enable <group>` and `disable <group>` do.
+    pub group: Option<String>,
+    /// Only stop when this thread hits the breakpoint; see
+    /// [`BreakpointStore::stops_thread`].
+    pub thread: Option<ThreadId>,
+    /// Remove the breakpoint after its first hit.
+    pub one_shot: bool,
+    /// Place a software breakpoint even where it doesn't start an
+    /// instruction; see [`check_instruction_boundary`].
+    pub force: bool,
+    /// Leave the breakpoint out of breakpoint lists, for breakpoints ferros
+    /// sets for itself such as assertions.
+    pub hidden: bool,
+    /// Actions the front-end runs when the breakpoint stops the target, in order.
+    pub actions: Vec<BreakpointAction>,
+}
+
+impl BreakpointOptions
+{
+    /// Parse [`condition`](Self::condition), if there is one.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: The condition doesn't parse
+    pub fn parsed_condition(&self) -> Result<Option<Condition>>
+    {
+        self.condition.as_deref().map(Condition::parse).transpose()
+    }
+}
+
+/// High-level request to create a breakpoint.
+///
+/// Build one with [`Breakpoint`], or convert a bare [`BreakpointRequestKind`]
+/// for an enabled breakpoint with no options.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BreakpointRequest
+{
+    /// Where and how the breakpoint traps.
+    pub kind: BreakpointRequestKind,
+    /// Condition, group, thread filter and other options.
+    pub options: BreakpointOptions,
+    /// Whether the breakpoint is armed once installed.
+    pub enabled: bool,
+}
+
+impl BreakpointRequest
+{
+    /// Address the request traps on, or `None` while the location is unresolved.
+    #[must_use]
+    pub fn address(&self) -> Option<Address>
+    {
+        match &self.kind {
+            BreakpointRequestKind::Software { address }
+            | BreakpointRequestKind::Hardware { address, .. }
+            | BreakpointRequestKind::Watchpoint { address, .. } => Some(*address),
+            BreakpointRequestKind::Location(BreakpointLocation::Address(address)) => Some(*address),
+            BreakpointRequestKind::Location(_) => None,
+        }
+    }
+}
+
+impl From<BreakpointRequestKind> for BreakpointRequest
+{
+    fn from(kind: BreakpointRequestKind) -> Self
+    {
+        Self {
+            kind,
+            options: BreakpointOptions::default(),
+            enabled: true,
+        }
+    }
+}
+
+/// User-facing description of where a breakpoint should go.
+///
+/// Symbolic locations are turned into addresses with
+/// [`Debugger::resolve_location`](crate::debugger::Debugger::resolve_location).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BreakpointLocation
+{
+    /// A raw address.
+    Address(Address),
+    /// Entry of a function, matched against plain and fully-qualified names
+    /// (`main`, `my_crate::run`), C symbol names (`malloc`) and Objective-C
+    /// methods (`-[NSView drawRect:]`).
+    Function(String),
+    /// A source line. `file` may be a suffix of the compiled path (`src/main.rs`).
+    Line
+    {
+        /// Source file path or path suffix.
+        file: String,
+        /// 1-based line number.
+        line: u32,
+    },
+}
+
+impl std::str::FromStr for BreakpointLocation
+{
+    type Err = String;
+
+    /// Parse `0x<hex>` as an address, `<file>:<line>` as a line, and anything
+    /// else as a function name.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err>
+    {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("empty breakpoint location".to_string());
+        }
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            return u64::from_str_radix(hex, 16)
+                .map(|address| Self::Address(Address::from(address)))
+                .map_err(|_| format!("invalid address '{s}'"));
+        }
+        if let Some((file, line)) = s.rsplit_once(':')
+            && let Ok(line) = line.parse()
+        {
+            return Ok(Self::Line {
+                file: file.to_string(),
+                line,
+            });
+        }
+        Ok(Self::Function(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for BreakpointLocation
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self {
+            Self::Address(address) => write!(f, "{address}"),
+            Self::Function(name) => write!(f, "{name}"),
+            Self::Line { file, line } => write!(f, "{file}:{line}"),
+        }
+    }
 }
 
 /// Lifecycle states for a breakpoint entry.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BreakpointState
 {
     /// Requested but not yet installed.
@@ -97,6 +301,44 @@ pub enum BreakpointState
     Disabled,
 }
 
+/// Number of user breakpoints in each [`BreakpointState`].
+///
+/// Hidden breakpoints, such as the ones assertions install, aren't counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BreakpointCounts
+{
+    /// Breakpoints still waiting to be installed.
+    pub requested: usize,
+    /// Breakpoints installed in the target.
+    pub resolved: usize,
+    /// Breakpoints whose trap has been removed.
+    pub disabled: usize,
+}
+
+impl BreakpointCounts
+{
+    /// Count the states of `breakpoints`.
+    pub fn of<'a>(breakpoints: impl IntoIterator<Item = &'a BreakpointInfo>) -> Self
+    {
+        let mut counts = Self::default();
+        for info in breakpoints.into_iter().filter(|info| !info.options.hidden) {
+            match info.state {
+                BreakpointState::Requested => counts.requested += 1,
+                BreakpointState::Resolved => counts.resolved += 1,
+                BreakpointState::Disabled => counts.disabled += 1,
+            }
+        }
+        counts
+    }
+
+    /// Total number of breakpoints counted.
+    #[must_use]
+    pub const fn total(&self) -> usize
+    {
+        self.requested + self.resolved + self.disabled
+    }
+}
+
 /// Public information about a breakpoint.
 #[derive(Debug, Clone)]
 pub struct BreakpointInfo
@@ -113,6 +355,8 @@ pub struct BreakpointInfo
     pub enabled: bool,
     /// Number of times this breakpoint has been hit.
     pub hit_count: u64,
+    /// Hits still to skip before the breakpoint stops the target; each skipped hit uses one up.
+    pub ignore_count: u64,
     /// Timestamp when the breakpoint was first requested.
     pub requested_at: SystemTime,
     /// Timestamp when the breakpoint was successfully installed, if resolved.
@@ -121,6 +365,12 @@ pub struct BreakpointInfo
     pub watch_access: Option<WatchpointAccess>,
     /// Length in bytes for watchpoints (None for execution breakpoints).
     pub watch_length: Option<usize>,
+    /// Debug register slot an armed hardware breakpoint occupies (None otherwise).
+    pub hardware_slot: Option<u32>,
+    /// Options the breakpoint was requested with.
+    pub options: BreakpointOptions,
+    /// Handling time and hit rate, for spotting breakpoints that slow the target.
+    pub stats: BreakpointStats,
 }
 
 impl BreakpointInfo
@@ -137,14 +387,58 @@ impl BreakpointInfo
             state: BreakpointState::Requested,
             enabled: false,
             hit_count: 0,
+            ignore_count: 0,
             requested_at: SystemTime::now(),
             resolved_at: None,
             watch_access: None,
             watch_length: None,
+            hardware_slot: None,
+            options: BreakpointOptions::default(),
+            stats: BreakpointStats::default(),
         }
     }
 }
 
+/// Serializes the breakpoint as a list shows it: id, hex address, kind, state,
+/// counts and the options it was requested with; watchpoints add their access
+/// and length, and armed hardware breakpoints their slot
+impl Serialize for BreakpointInfo
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("address", &self.address)?;
+        map.serialize_entry("kind", &self.kind)?;
+        map.serialize_entry("state", &self.state)?;
+        map.serialize_entry("enabled", &self.enabled)?;
+        map.serialize_entry("hit_count", &self.hit_count)?;
+        map.serialize_entry("ignore_count", &self.ignore_count)?;
+        map.serialize_entry("one_shot", &self.options.one_shot)?;
+        map.serialize_entry("condition", &self.options.condition)?;
+        map.serialize_entry("group", &self.options.group)?;
+        map.serialize_entry("thread", &self.options.thread)?;
+        if let Some(access) = self.watch_access {
+            map.serialize_entry("watch_access", &access)?;
+            map.serialize_entry("watch_length", &self.watch_length)?;
+        }
+        if let Some(slot) = self.hardware_slot {
+            map.serialize_entry("hardware_slot", &slot)?;
+        }
+        map.end()
+    }
+}
+
+/// A hit counted by [`BreakpointStore::record_hit`].
+#[derive(Debug, Clone)]
+pub struct BreakpointHit
+{
+    /// The breakpoint, with the hit counted.
+    pub info: BreakpointInfo,
+    /// Whether the hit used up one of the breakpoint's ignore count, so the target shouldn't stop.
+    pub ignored: bool,
+}
+
 /// Internal payload used by the backend to restore state.
 #[derive(Debug, Clone)]
 pub enum BreakpointPayload
@@ -197,6 +491,8 @@ pub struct BreakpointStore
     next_id: u64,
     by_id: HashMap<BreakpointId, BreakpointEntry>,
     by_kind: HashMap<(Address, BreakpointKind), BreakpointId>,
+    /// Parsed conditions, so hits don't re-parse them.
+    conditions: HashMap<BreakpointId, Condition>,
 }
 
 impl BreakpointStore
@@ -250,10 +546,59 @@ impl BreakpointStore
         self.by_kind.get(&(address, kind)).copied()
     }
 
+    /// Debug register slots held by armed hardware breakpoints, lowest first.
+    ///
+    /// A disabled hardware breakpoint gives its slot back and takes a free
+    /// one again when it is re-enabled.
+    #[must_use]
+    pub fn hardware_slots_in_use(&self) -> Vec<u32>
+    {
+        let mut slots: Vec<u32> = self
+            .by_id
+            .values()
+            .filter(|entry| entry.info.enabled)
+            .filter_map(|entry| match entry.payload {
+                BreakpointPayload::Hardware { slot, .. } => Some(slot),
+                _ => None,
+            })
+            .collect();
+        slots.sort_unstable();
+        slots
+    }
+
+    /// Payloads of the armed breakpoints that live in debug registers.
+    ///
+    /// Debug registers are per thread, so these are what a thread that
+    /// started after they were installed still needs programmed.
+    #[must_use]
+    pub fn armed_debug_registers(&self) -> Vec<BreakpointPayload>
+    {
+        self.by_id
+            .values()
+            .filter(|entry| entry.info.enabled)
+            .filter(|entry| !matches!(entry.payload, BreakpointPayload::Software { .. }))
+            .map(|entry| entry.payload.clone())
+            .collect()
+    }
+
+    /// Lowest of the CPU's `available` hardware breakpoint slots that no armed breakpoint holds.
+    ///
+    /// ## Errors
+    ///
+    /// - `NoHardwareSlots`: Every slot is held
+    pub fn free_hardware_slot(&self, available: usize) -> Result<u32>
+    {
+        let in_use = self.hardware_slots_in_use();
+        (0..u32::try_from(available).unwrap_or(u32::MAX))
+            .find(|slot| in_use.binary_search(slot).is_err())
+            .ok_or(DebuggerError::NoHardwareSlots(available))
+    }
+
     /// Remove a breakpoint from the store, returning the entry if it was present.
     pub fn remove(&mut self, id: BreakpointId) -> Option<BreakpointEntry>
     {
         if let Some(entry) = self.by_id.remove(&id) {
+            self.conditions.remove(&id);
             self.by_kind.remove(&(entry.info.address, entry.info.kind));
             Some(entry)
         } else {
@@ -268,15 +613,113 @@ impl BreakpointStore
         self.by_id.values().map(|entry| entry.info.clone()).collect()
     }
 
+    /// Count the tracked breakpoints by state without cloning their info.
+    pub fn counts(&self) -> BreakpointCounts
+    {
+        BreakpointCounts::of(self.by_id.values().map(|entry| &entry.info))
+    }
+
     /// Fetch the public info for a specific breakpoint id.
     pub fn info(&self, id: BreakpointId) -> Option<BreakpointInfo>
     {
         self.by_id.get(&id).map(|entry| entry.info.clone())
     }
 
+    /// Record `options` on breakpoint `id` and parse its condition.
+    ///
+    /// ## Errors
+    ///
+    /// - `BreakpointNotFound`: No breakpoint has this id
+    /// - `InvalidArgument`: The condition doesn't parse; the old options are kept
+    pub fn set_options(&mut self, id: BreakpointId, options: BreakpointOptions) -> Result<()>
+    {
+        let condition = options.parsed_condition()?;
+        let entry = self.by_id.get_mut(&id).ok_or(DebuggerError::BreakpointNotFound(id))?;
+        entry.info.options = options;
+        match condition {
+            Some(condition) => self.conditions.insert(id, condition),
+            None => self.conditions.remove(&id),
+        };
+        Ok(())
+    }
+
+    /// Enabled software or hardware breakpoint trapping at `address`.
+    pub fn trap_at(&self, address: Address) -> Option<BreakpointId>
+    {
+        let id = self
+            .id_for_kind(address, BreakpointKind::Software)
+            .or_else(|| self.id_for_kind(address, BreakpointKind::Hardware))?;
+        self.by_id.get(&id).is_some_and(|entry| entry.info.enabled).then_some(id)
+    }
+
+    /// Whether a hit at `address` by `thread` passes the breakpoint's
+    /// [`thread`](BreakpointOptions::thread) filter.
+    ///
+    /// Hits by other threads don't stop the target and, like hits where the
+    /// condition is false, don't count.
+    pub fn stops_thread(&self, address: Address, thread: ThreadId) -> bool
+    {
+        self.trap_at(address)
+            .and_then(|id| self.by_id.get(&id))
+            .and_then(|entry| entry.info.options.thread)
+            .is_none_or(|only| only == thread)
+    }
+
+    /// Whether the enabled breakpoint at `address` has a condition to evaluate.
+    pub fn has_condition(&self, address: Address) -> bool
+    {
+        self.trap_at(address).is_some_and(|id| self.conditions.contains_key(&id))
+    }
+
+    /// Whether the target should stop for a hit at `address` by a thread with `registers`.
+    ///
+    /// Breakpoints without a condition always stop. A condition that can't be
+    /// evaluated within `budget` counts as false; the failure is recorded in the
+    /// breakpoint's [`stats`](BreakpointInfo::stats) rather than returned, since
+    /// the caller is usually the exception handler.
+    pub fn condition_holds(
+        &mut self,
+        address: Address,
+        registers: &Registers,
+        memory: &mut dyn ConditionMemory,
+        budget: &ConditionBudget,
+    ) -> bool
+    {
+        let Some(id) = self.trap_at(address) else {
+            return true;
+        };
+        let Some(condition) = self.conditions.get(&id) else {
+            return true;
+        };
+        match condition.evaluate(registers, memory, budget) {
+            Ok(holds) => holds,
+            Err(failure) => {
+                self.record_condition_failure(address, format!("Condition `{condition}` failed: {failure}"));
+                false
+            }
+        }
+    }
+
+    /// Count a failed condition evaluation for the breakpoint at `address`,
+    /// for failures that happen before [`condition_holds`](Self::condition_holds)
+    /// can run, such as the thread's registers being unreadable.
+    pub fn record_condition_failure(&mut self, address: Address, reason: String)
+    {
+        let Some(entry) = self.trap_at(address).and_then(|id| self.by_id.get_mut(&id)) else {
+            return;
+        };
+        tracing::warn!("Breakpoint {} at {address}: {reason}", entry.info.id.raw());
+        entry.info.stats.record_condition_failure(reason);
+    }
+
     /// Record that a breakpoint at the provided address was hit. The entry's hit
     /// counter is incremented only if the breakpoint is currently enabled.
-    pub fn record_hit(&mut self, address: Address) -> Option<BreakpointInfo>
+    ///
+    /// A hit while the breakpoint has an [`ignore_count`](BreakpointInfo::ignore_count)
+    /// left uses one up and comes back [`ignored`](BreakpointHit::ignored): the
+    /// caller resumes the target instead of stopping. Check the condition
+    /// first, so hits where it's false don't count against the ignore count.
+    pub fn record_hit(&mut self, address: Address) -> Option<BreakpointHit>
     {
         let id = self
             .id_for_kind(address, BreakpointKind::Software)
@@ -286,7 +729,171 @@ impl BreakpointStore
             return None;
         }
         entry.info.hit_count = entry.info.hit_count.saturating_add(1);
-        Some(entry.info.clone())
+        let ignored = entry.info.ignore_count > 0;
+        if ignored {
+            entry.info.ignore_count -= 1;
+        }
+        Some(BreakpointHit {
+            info: entry.info.clone(),
+            ignored,
+        })
+    }
+
+    /// Record that an access to `fault` tripped a watchpoint, and find which.
+    ///
+    /// The hardware matches accesses by 8-byte doubleword, so an access that
+    /// starts before the watched bytes but overlaps them reports an address in
+    /// the same doubleword; a watchpoint covering `fault` wins over one that
+    /// only shares its doubleword. Watchpoint hits always stop: the access
+    /// traps before it completes, so skipping one means stepping over it
+    /// first, which the exception loop can't do.
+    pub fn record_watchpoint_hit(&mut self, fault: Address) -> Option<BreakpointHit>
+    {
+        let fault = fault.value();
+        let (_, entry) = self
+            .by_id
+            .values_mut()
+            .filter_map(|entry| match entry.payload {
+                BreakpointPayload::Watchpoint { address, length, .. } if entry.info.enabled => {
+                    let (start, end) = (address.value(), address.value().saturating_add(length as u64));
+                    let covers = (start..end).contains(&fault);
+                    let in_doubleword = (start & !7..end.next_multiple_of(8)).contains(&fault);
+                    (covers || in_doubleword).then_some((covers, entry))
+                }
+                _ => None,
+            })
+            .max_by_key(|&(covers, _)| covers)?;
+        entry.info.hit_count = entry.info.hit_count.saturating_add(1);
+        Some(BreakpointHit {
+            info: entry.info.clone(),
+            ignored: false,
+        })
+    }
+
+    /// Skip the next `count` hits of breakpoint `id`.
+    ///
+    /// Replaces whatever was left of an earlier ignore count; 0 makes every
+    /// hit stop again.
+    ///
+    /// ## Errors
+    ///
+    /// - `BreakpointNotFound`: No breakpoint has this id
+    /// - `InvalidArgument`: The breakpoint is a watchpoint (see
+    ///   [`record_watchpoint_hit`](Self::record_watchpoint_hit))
+    pub fn set_ignore_count(&mut self, id: BreakpointId, count: u64) -> Result<()>
+    {
+        let entry = self.by_id.get_mut(&id).ok_or(DebuggerError::BreakpointNotFound(id))?;
+        if entry.info.kind == BreakpointKind::Watchpoint {
+            return Err(DebuggerError::InvalidArgument(
+                "Watchpoints always stop; ignore counts only apply to breakpoints".to_string(),
+            ));
+        }
+        entry.info.ignore_count = count;
+        Ok(())
+    }
+
+    /// One-shot breakpoint at `address` that has already been hit and should be
+    /// removed before the target resumes.
+    pub fn spent_one_shot(&self, address: Address) -> Option<BreakpointId>
+    {
+        [BreakpointKind::Software, BreakpointKind::Hardware]
+            .into_iter()
+            .filter_map(|kind| self.id_for_kind(address, kind))
+            .find(|id| {
+                self.by_id
+                    .get(id)
+                    .is_some_and(|entry| entry.info.options.one_shot && entry.info.hit_count > 0)
+            })
+    }
+
+    /// Record that handling a hit on `id`, received at `at`, held the target for
+    /// `handling`. Backends call this once they have replied to the exception.
+    pub fn record_handling(&mut self, id: BreakpointId, at: Instant, handling: Duration)
+    {
+        if let Some(entry) = self.by_id.get_mut(&id) {
+            entry.info.stats.record(at, handling);
+        }
+    }
+
+    /// Software breakpoints whose patched bytes overlap the `len` bytes at `address`.
+    pub fn software_overlapping(&self, address: Address, len: usize) -> Vec<BreakpointId>
+    {
+        let mut ids: Vec<BreakpointId> = self.software_spans(address, len).map(|(entry, ..)| entry.info.id).collect();
+        ids.sort_by_key(|id| id.raw());
+        ids
+    }
+
+    /// Put `trap` back over the enabled software breakpoints that a write of
+    /// `bytes` at `address` would otherwise overwrite, so they stay armed.
+    pub fn overlay_traps(&self, address: Address, bytes: &mut [u8], trap: &[u8])
+    {
+        for (entry, _, span) in self.software_spans(address, bytes.len()) {
+            if let Some(trap) = trap.get(span.in_patch)
+                && entry.info.enabled
+            {
+                bytes[span.in_range].copy_from_slice(trap);
+            }
+        }
+    }
+
+    /// Take a write of `bytes` at `address` as the new code under the software
+    /// breakpoints it overlaps.
+    ///
+    /// Their saved bytes are updated, so removing or disabling them later
+    /// restores what was written rather than what was there at install time.
+    /// Returns the breakpoints whose saved bytes changed.
+    pub fn absorb_write(&mut self, address: Address, bytes: &[u8]) -> Vec<BreakpointId>
+    {
+        let ids = self.software_overlapping(address, bytes.len());
+        for id in &ids {
+            if let Some(entry) = self.by_id.get_mut(id)
+                && let BreakpointPayload::Software { original_bytes } = &mut entry.payload
+                && let Some(span) = overlap(address, bytes.len(), entry.info.address, original_bytes.len())
+            {
+                original_bytes[span.in_patch].copy_from_slice(&bytes[span.in_range]);
+            }
+        }
+        ids
+    }
+
+    /// Replace the traps of enabled software breakpoints in `bytes`, read from
+    /// `address`, with the code they patched over.
+    ///
+    /// `bytes` can be any slice of memory, such as one chunk of a streamed
+    /// read; a breakpoint straddling its edge has just the overlapping part
+    /// replaced.
+    pub fn logical_view(&self, address: Address, bytes: &mut [u8])
+    {
+        for (entry, original, span) in self.software_spans(address, bytes.len()) {
+            if entry.info.enabled {
+                bytes[span.in_range].copy_from_slice(&original[span.in_patch]);
+            }
+        }
+    }
+
+    /// Saved bytes of the enabled software breakpoint patched in at exactly
+    /// `address`, for putting the real instruction back while a thread steps
+    /// over it.
+    pub fn patched_code_at(&self, address: Address) -> Option<Vec<u8>>
+    {
+        let entry = self.by_id.get(&self.trap_at(address)?)?;
+        match &entry.payload {
+            BreakpointPayload::Software { original_bytes } => Some(original_bytes.clone()),
+            _ => None,
+        }
+    }
+
+    /// Software breakpoints overlapping the `len` bytes at `address`, with
+    /// their saved bytes and where the two overlap.
+    fn software_spans(&self, address: Address, len: usize) -> impl Iterator<Item = (&BreakpointEntry, &[u8], Overlap)>
+    {
+        self.by_id.values().filter_map(move |entry| match &entry.payload {
+            BreakpointPayload::Software { original_bytes } => {
+                overlap(address, len, entry.info.address, original_bytes.len())
+                    .map(|span| (entry, original_bytes.as_slice(), span))
+            }
+            _ => None,
+        })
     }
 
     /// Drain the store, returning all entries and resetting the internal
@@ -294,6 +901,561 @@ impl BreakpointStore
     pub fn drain(&mut self) -> Vec<BreakpointRecord>
     {
         self.by_kind.clear();
+        self.conditions.clear();
         self.by_id.drain().map(|(_, entry)| entry).collect()
     }
 }
+
+/// Where a memory range and a breakpoint's patch overlap, as offsets into each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Overlap
+{
+    in_patch: Range<usize>,
+    in_range: Range<usize>,
+}
+
+/// Overlap of the `len` bytes at `address` with the `patch_len` bytes at `patch`.
+fn overlap(address: Address, len: usize, patch: Address, patch_len: usize) -> Option<Overlap>
+{
+    let (address, patch) = (address.value(), patch.value());
+    let start = address.max(patch);
+    let end = address.saturating_add(len as u64).min(patch.saturating_add(patch_len as u64));
+    if start >= end {
+        return None;
+    }
+    let count = usize::try_from(end - start).ok()?;
+    let in_patch = usize::try_from(start - patch).ok()?;
+    let in_range = usize::try_from(start - address).ok()?;
+    Some(Overlap {
+        in_patch: in_patch..in_patch + count,
+        in_range: in_range..in_range + count,
+    })
+}
+
+/// Check that a watchpoint on `length` bytes at `address` fits the debug registers.
+///
+/// ARM64 watchpoints watch up to 8 bytes within one 8-byte doubleword. ferros
+/// asks for 1, 2, 4 or 8 bytes aligned to their length, which always fits and
+/// matches the sizes of the values a watchpoint is set on.
+///
+/// ## Errors
+///
+/// Returns `InvalidArgument` for any other length, or a misaligned address.
+pub fn check_watch_range(address: Address, length: usize) -> Result<()>
+{
+    if !matches!(length, 1 | 2 | 4 | 8) {
+        return Err(DebuggerError::InvalidArgument(format!(
+            "Watchpoints cover 1, 2, 4 or 8 bytes, not {length}"
+        )));
+    }
+    if !address.value().is_multiple_of(length as u64) {
+        return Err(DebuggerError::InvalidArgument(format!(
+            "A {length}-byte watchpoint needs a {length}-byte aligned address, not {address}"
+        )));
+    }
+    Ok(())
+}
+
+/// Check that `address` can start an instruction before patching a trap over it.
+///
+/// A trap written into the middle of an instruction corrupts it, and the
+/// target then crashes in a way that looks like its own bug. ARM64
+/// instructions are 4 bytes and 4-byte aligned, so a misaligned address is
+/// refused with the start of the instruction it falls in.
+///
+/// x86-64 instructions have no alignment, so `known` gives `code` read from
+/// an address known to start an instruction, such as the enclosing function's
+/// entry or a line-table row, through the end of the instruction at
+/// `address`. The code is decoded forward from there to find the instruction
+/// `address` falls in. Without `known`, or when `code` ends before `address`
+/// is reached, x86-64 addresses pass unchecked.
+///
+/// [`BreakpointOptions::force`] skips the check.
+///
+/// ## Errors
+///
+/// Returns `InvalidArgument` naming the enclosing instruction's start.
+pub fn check_instruction_boundary(
+    architecture: Architecture,
+    address: Address,
+    known: Option<(Address, &[u8])>,
+) -> Result<()>
+{
+    if architecture != Architecture::X86_64 {
+        let alignment = architecture.instruction_alignment();
+        let offset = address.value() % alignment;
+        if offset == 0 {
+            return Ok(());
+        }
+        let start = Address::from(address.value() - offset);
+        return Err(DebuggerError::InvalidArgument(format!(
+            "{address} is {offset} byte(s) into the {architecture} instruction at {start}; {architecture} instructions are \
+             {alignment}-byte aligned, break at {start} instead"
+        )));
+    }
+
+    let Some((start, code)) = known.filter(|&(start, _)| start <= address) else {
+        return Ok(());
+    };
+    let listing = disassemble(architecture, start, code, code.len(), None)?;
+    let Some(enclosing) = listing.iter().find(|insn| insn.address < address && address < insn.end()) else {
+        return Ok(());
+    };
+    Err(DebuggerError::InvalidArgument(format!(
+        "{address} is {} byte(s) into the {architecture} instruction `{}` at {}; break at {} instead, or force the \
+         breakpoint to place it anyway",
+        address.value() - enclosing.address.value(),
+        enclosing.text(),
+        enclosing.address,
+        enclosing.address,
+    )))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn software(store: &mut BreakpointStore, address: u64, one_shot: bool) -> BreakpointId
+    {
+        let mut info = BreakpointInfo::new(BreakpointId::from_raw(0), Address::from(address), BreakpointKind::Software);
+        info.enabled = true;
+        info.options.one_shot = one_shot;
+        store.insert(BreakpointEntry {
+            info,
+            payload: BreakpointPayload::Software {
+                original_bytes: vec![0; 4],
+            },
+        })
+    }
+
+    #[test]
+    fn locations_parse_from_addresses_lines_and_names()
+    {
+        assert_eq!("0x1f00".parse(), Ok(BreakpointLocation::Address(Address::from(0x1f00))));
+        assert_eq!(
+            "src/main.rs:42".parse(),
+            Ok(BreakpointLocation::Line {
+                file: "src/main.rs".to_string(),
+                line: 42,
+            })
+        );
+        assert_eq!("abort".parse(), Ok(BreakpointLocation::Function("abort".to_string())));
+        // A path segment is not a line number
+        assert_eq!(
+            "core::panicking::panic".parse(),
+            Ok(BreakpointLocation::Function("core::panicking::panic".to_string()))
+        );
+        assert!("0xzz".parse::<BreakpointLocation>().is_err());
+        assert!(" ".parse::<BreakpointLocation>().is_err());
+    }
+
+    #[test]
+    fn counts_group_visible_breakpoints_by_state()
+    {
+        let mut store = BreakpointStore::new();
+        let resolved = software(&mut store, 0x1000, false);
+        store.get_mut(resolved).unwrap().info.state = BreakpointState::Resolved;
+        software(&mut store, 0x2000, false);
+        let hidden = software(&mut store, 0x3000, false);
+        store.get_mut(hidden).unwrap().info.options.hidden = true;
+
+        let counts = store.counts();
+        assert_eq!(
+            counts,
+            BreakpointCounts {
+                requested: 1,
+                resolved: 1,
+                disabled: 0,
+            }
+        );
+        assert_eq!(counts.total(), 2);
+        assert_eq!(BreakpointCounts::of(&store.list()), counts);
+    }
+
+    #[test]
+    fn one_shot_breakpoints_are_spent_after_their_first_hit()
+    {
+        let mut store = BreakpointStore::new();
+        let once = software(&mut store, 0x1000, true);
+        software(&mut store, 0x2000, false);
+
+        assert_eq!(store.spent_one_shot(Address::from(0x1000)), None);
+        store.record_hit(Address::from(0x1000));
+        store.record_hit(Address::from(0x2000));
+        assert_eq!(store.spent_one_shot(Address::from(0x1000)), Some(once));
+        assert_eq!(store.spent_one_shot(Address::from(0x2000)), None);
+    }
+
+    #[test]
+    fn ignore_counts_skip_hits_before_stopping()
+    {
+        let mut store = BreakpointStore::new();
+        let id = software(&mut store, 0x1000, false);
+        store.set_ignore_count(id, 2).unwrap();
+        assert!(store.set_ignore_count(BreakpointId::from_raw(99), 1).is_err());
+
+        let hits: Vec<(bool, u64, u64)> = (0..3)
+            .map(|_| {
+                let hit = store.record_hit(Address::from(0x1000)).unwrap();
+                (hit.ignored, hit.info.hit_count, hit.info.ignore_count)
+            })
+            .collect();
+        assert_eq!(hits, [(true, 1, 1), (true, 2, 0), (false, 3, 0)]);
+
+        // Disabled breakpoints aren't hit, so they keep their ignore count
+        store.set_ignore_count(id, 1).unwrap();
+        store.get_mut(id).unwrap().info.enabled = false;
+        assert!(store.record_hit(Address::from(0x1000)).is_none());
+        assert_eq!(store.info(id).unwrap().ignore_count, 1);
+    }
+
+    fn watchpoint(store: &mut BreakpointStore, address: u64, length: usize) -> BreakpointId
+    {
+        let mut info = BreakpointInfo::new(BreakpointId::from_raw(0), Address::from(address), BreakpointKind::Watchpoint);
+        info.enabled = true;
+        store.insert(BreakpointEntry {
+            info,
+            payload: BreakpointPayload::Watchpoint {
+                address: Address::from(address),
+                length,
+                access: WatchpointAccess::Write,
+                slot: 0,
+            },
+        })
+    }
+
+    #[test]
+    fn watchpoint_hits_map_the_faulting_access_back_to_the_watchpoint()
+    {
+        let mut store = BreakpointStore::new();
+        let low = watchpoint(&mut store, 0x2000, 4);
+        let high = watchpoint(&mut store, 0x2004, 2);
+        let wide = watchpoint(&mut store, 0x3000, 8);
+
+        let hit = |store: &mut BreakpointStore, fault: u64| {
+            store
+                .record_watchpoint_hit(Address::from(fault))
+                .map(|hit| (hit.info.id, hit.ignored))
+        };
+        assert_eq!(hit(&mut store, 0x2002), Some((low, false)));
+        assert_eq!(hit(&mut store, 0x2005), Some((high, false)));
+        // An 8-byte store at the doubleword's start reports its own address
+        assert_eq!(hit(&mut store, 0x3000), Some((wide, false)));
+        assert_eq!(hit(&mut store, 0x3008), None);
+        assert_eq!(hit(&mut store, 0x1ff8), None);
+        assert_eq!(store.info(low).unwrap().hit_count, 1);
+
+        assert!(store.set_ignore_count(low, 3).is_err());
+        store.get_mut(high).unwrap().info.enabled = false;
+        assert_eq!(hit(&mut store, 0x2005), Some((low, false)));
+    }
+
+    #[test]
+    fn watch_ranges_are_small_and_aligned()
+    {
+        assert!(check_watch_range(Address::from(0x1000), 8).is_ok());
+        assert!(check_watch_range(Address::from(0x1006), 2).is_ok());
+        assert!(check_watch_range(Address::from(0x1003), 1).is_ok());
+        assert!(check_watch_range(Address::from(0x1004), 8).is_err());
+        assert!(check_watch_range(Address::from(0x1000), 3).is_err());
+        assert!(check_watch_range(Address::from(0x1000), 16).is_err());
+    }
+
+    #[test]
+    fn conditions_decide_whether_a_hit_stops_and_failures_are_counted()
+    {
+        let mut store = BreakpointStore::new();
+        let id = software(&mut store, 0x1000, false);
+        let plain = software(&mut store, 0x2000, false);
+        let condition = |text: &str| BreakpointOptions {
+            condition: Some(text.to_string()),
+            ..BreakpointOptions::default()
+        };
+        assert!(store.set_options(id, condition("$x0 ==")).is_err());
+        store.set_options(id, condition("*(u32*)$x0 == 7")).unwrap();
+
+        let mut registers = Registers::new().with_arch(crate::types::Architecture::Arm64);
+        registers.general = vec![0; 31];
+        registers.general[0] = 0x5000;
+        let budget = ConditionBudget::default();
+        let mut seven = |_: Address, len: usize| Ok([7, 0, 0, 0][..len].to_vec());
+        let mut unmapped = |_: Address, _: usize| Err(DebuggerError::InvalidArgument("unmapped".to_string()));
+
+        assert!(store.has_condition(Address::from(0x1000)));
+        assert!(!store.has_condition(Address::from(0x2000)));
+        assert!(store.condition_holds(Address::from(0x1000), &registers, &mut seven, &budget));
+        assert!(store.condition_holds(Address::from(0x2000), &registers, &mut unmapped, &budget));
+        assert!(!store.condition_holds(Address::from(0x1000), &registers, &mut unmapped, &budget));
+
+        let stats = store.info(id).unwrap().stats;
+        assert_eq!(stats.condition_failures, 1);
+        assert_eq!(
+            stats.last_condition_failure.as_deref(),
+            Some("Condition `*(u32*)$x0 == 7` failed: reading 4 bytes at 0x0000000000005000 failed")
+        );
+        assert_eq!(store.info(plain).unwrap().stats.condition_failures, 0);
+
+        store.set_options(id, BreakpointOptions::default()).unwrap();
+        assert!(!store.has_condition(Address::from(0x1000)));
+    }
+
+    #[test]
+    fn thread_filters_only_stop_their_thread()
+    {
+        let mut store = BreakpointStore::new();
+        let id = software(&mut store, 0x1000, false);
+        software(&mut store, 0x2000, false);
+        let options = BreakpointOptions {
+            thread: Some(ThreadId::from(7)),
+            ..BreakpointOptions::default()
+        };
+        store.set_options(id, options).unwrap();
+
+        assert!(store.stops_thread(Address::from(0x1000), ThreadId::from(7)));
+        assert!(!store.stops_thread(Address::from(0x1000), ThreadId::from(8)));
+        assert!(store.stops_thread(Address::from(0x2000), ThreadId::from(8)));
+        // Nothing traps at an address without a breakpoint
+        assert!(store.stops_thread(Address::from(0x3000), ThreadId::from(8)));
+    }
+
+    #[test]
+    fn arm64_breakpoints_must_start_an_instruction()
+    {
+        assert!(check_instruction_boundary(Architecture::Arm64, Address::from(0x1_0000_3f20), None).is_ok());
+        let Err(DebuggerError::InvalidArgument(message)) =
+            check_instruction_boundary(Architecture::Arm64, Address::from(0x1_0000_3f22), None)
+        else {
+            panic!("a misaligned ARM64 address was accepted");
+        };
+        assert_eq!(
+            message,
+            "0x0000000100003f22 is 2 byte(s) into the arm64 instruction at 0x0000000100003f20; arm64 instructions are \
+             4-byte aligned, break at 0x0000000100003f20 instead"
+        );
+    }
+
+    /// push rbp; mov rbp, rsp; lock add dword ptr [rax], 1; mov eax, 0x12345678; ret, at 0x1000
+    const X86_CODE: [u8; 17] = [
+        0x55, 0x48, 0x89, 0xe5, 0xf0, 0x83, 0x00, 0x01, 0xb8, 0x78, 0x56, 0x34, 0x12, 0xc3, 0x90, 0x90, 0x90,
+    ];
+
+    fn x86_boundary(address: u64) -> Result<()>
+    {
+        check_instruction_boundary(
+            Architecture::X86_64,
+            Address::from(address),
+            Some((Address::from(0x1000), &X86_CODE)),
+        )
+    }
+
+    #[test]
+    fn x86_64_breakpoints_must_start_a_decoded_instruction()
+    {
+        for start in [0x1000, 0x1001, 0x1004, 0x1008, 0x100d] {
+            assert!(x86_boundary(start).is_ok(), "{start:#x}");
+        }
+        // Without a known instruction start there is nothing to decode from
+        assert!(check_instruction_boundary(Architecture::X86_64, Address::from(0x1005), None).is_ok());
+    }
+
+    #[test]
+    fn x86_64_breakpoints_inside_a_prefix_or_immediate_are_refused()
+    {
+        // Just past the `lock` prefix
+        let Err(DebuggerError::InvalidArgument(message)) = x86_boundary(0x1005) else {
+            panic!("an address inside a prefixed instruction was accepted");
+        };
+        assert_eq!(
+            message,
+            "0x0000000000001005 is 1 byte(s) into the x86_64 instruction `lock add dword ptr [rax], 1` at \
+             0x0000000000001004; break at 0x0000000000001004 instead, or force the breakpoint to place it anyway"
+        );
+
+        // In the middle of the 32-bit immediate
+        let Err(DebuggerError::InvalidArgument(message)) = x86_boundary(0x100a) else {
+            panic!("an address inside an immediate was accepted");
+        };
+        assert!(
+            message.starts_with(
+                "0x000000000000100a is 2 byte(s) into the x86_64 instruction `mov eax, 0x12345678` at 0x0000000000001008"
+            ),
+            "{message}"
+        );
+    }
+
+    const BRK: [u8; 4] = [0x00, 0x00, 0x20, 0xd4];
+
+    /// 16 bytes of code at 0x1000 with a software breakpoint patched over the
+    /// instruction at 0x1004, as a backend would leave them.
+    fn patched_code(store: &mut BreakpointStore) -> (Vec<u8>, BreakpointId)
+    {
+        let mut memory: Vec<u8> = (0..16).collect();
+        let mut info = BreakpointInfo::new(BreakpointId::from_raw(0), Address::from(0x1004), BreakpointKind::Software);
+        info.enabled = true;
+        let id = store.insert(BreakpointEntry {
+            info,
+            payload: BreakpointPayload::Software {
+                original_bytes: memory[4..8].to_vec(),
+            },
+        });
+        memory[4..8].copy_from_slice(&BRK);
+        (memory, id)
+    }
+
+    /// Write `data` at `address` the way a backend does: traps re-overlaid,
+    /// then the saved bytes updated.
+    fn write(store: &mut BreakpointStore, memory: &mut [u8], address: u64, data: &[u8]) -> Vec<BreakpointId>
+    {
+        let mut bytes = data.to_vec();
+        store.overlay_traps(Address::from(address), &mut bytes, &BRK);
+        let offset = usize::try_from(address - 0x1000).unwrap();
+        memory[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        store.absorb_write(Address::from(address), data)
+    }
+
+    fn saved_bytes(store: &BreakpointStore, id: BreakpointId) -> Vec<u8>
+    {
+        match &store.get(id).unwrap().payload {
+            BreakpointPayload::Software { original_bytes } => original_bytes.clone(),
+            payload => panic!("not a software breakpoint: {payload:?}"),
+        }
+    }
+
+    #[test]
+    fn a_write_over_a_breakpoint_survives_its_removal()
+    {
+        let mut store = BreakpointStore::new();
+        let (mut memory, id) = patched_code(&mut store);
+
+        assert_eq!(write(&mut store, &mut memory, 0x1004, &[0xaa; 4]), [id]);
+        // Still armed
+        assert_eq!(memory[4..8], BRK);
+        assert_eq!(saved_bytes(&store, id), [0xaa; 4]);
+
+        // Removing restores the written bytes, not the install-time ones
+        let entry = store.remove(id).unwrap();
+        let BreakpointPayload::Software { original_bytes } = entry.payload else {
+            unreachable!();
+        };
+        memory[4..8].copy_from_slice(&original_bytes);
+        assert_eq!(memory[..8], [0, 1, 2, 3, 0xaa, 0xaa, 0xaa, 0xaa]);
+    }
+
+    #[test]
+    fn a_partly_overlapping_write_updates_only_the_overlap()
+    {
+        let mut store = BreakpointStore::new();
+        let (mut memory, id) = patched_code(&mut store);
+
+        assert_eq!(write(&mut store, &mut memory, 0x1006, &[0xbb; 4]), [id]);
+        assert_eq!(memory[4..10], [0x00, 0x00, 0x20, 0xd4, 0xbb, 0xbb]);
+        assert_eq!(saved_bytes(&store, id), [4, 5, 0xbb, 0xbb]);
+
+        assert_eq!(write(&mut store, &mut memory, 0x1000, &[0xcc; 5]), [id]);
+        assert_eq!(memory[..8], [0xcc, 0xcc, 0xcc, 0xcc, 0x00, 0x00, 0x20, 0xd4]);
+        assert_eq!(saved_bytes(&store, id), [0xcc, 5, 0xbb, 0xbb]);
+
+        // Writes that only touch neighbours leave it alone
+        assert!(write(&mut store, &mut memory, 0x1008, &[0xdd; 8]).is_empty());
+        assert!(write(&mut store, &mut memory, 0x1000, &[0xee; 4]).is_empty());
+        assert_eq!(saved_bytes(&store, id), [0xcc, 5, 0xbb, 0xbb]);
+
+        // A disabled breakpoint isn't re-armed, but its saved bytes still follow the write
+        store.get_mut(id).unwrap().info.enabled = false;
+        memory[4..8].copy_from_slice(&saved_bytes(&store, id));
+        assert_eq!(write(&mut store, &mut memory, 0x1004, &[0x11; 4]), [id]);
+        assert_eq!(memory[4..8], [0x11; 4]);
+        assert_eq!(saved_bytes(&store, id), [0x11; 4]);
+    }
+
+    #[test]
+    fn logical_reads_hide_traps_across_chunk_edges()
+    {
+        let mut store = BreakpointStore::new();
+        let (memory, id) = patched_code(&mut store);
+
+        let mut whole = memory.clone();
+        store.logical_view(Address::from(0x1000), &mut whole);
+        assert_eq!(whole, (0..16).collect::<Vec<u8>>());
+
+        // Two chunks splitting the patched instruction in half
+        let (mut first, mut second) = (memory[..6].to_vec(), memory[6..].to_vec());
+        store.logical_view(Address::from(0x1000), &mut first);
+        store.logical_view(Address::from(0x1006), &mut second);
+        assert_eq!([first, second].concat(), whole);
+
+        // Disabled breakpoints have nothing patched in to hide
+        store.get_mut(id).unwrap().info.enabled = false;
+        let mut raw = memory.clone();
+        store.logical_view(Address::from(0x1000), &mut raw);
+        assert_eq!(raw, memory);
+    }
+
+    #[test]
+    fn stepping_over_a_breakpoint_finds_the_code_it_patched()
+    {
+        let mut store = BreakpointStore::new();
+        let (_, id) = patched_code(&mut store);
+
+        assert_eq!(store.patched_code_at(Address::from(0x1004)), Some(vec![4, 5, 6, 7]));
+        // Only the instruction the trap starts at, not the bytes inside it
+        assert_eq!(store.patched_code_at(Address::from(0x1006)), None);
+
+        store.get_mut(id).unwrap().info.enabled = false;
+        assert_eq!(store.patched_code_at(Address::from(0x1004)), None);
+    }
+
+    #[test]
+    fn breakpoints_serialize_with_hex_addresses_and_states()
+    {
+        let mut store = BreakpointStore::new();
+        let id = software(&mut store, 0x1f00, true);
+        let info = store.get_mut(id).unwrap().info.clone();
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["id"], id.raw());
+        assert_eq!(json["address"], "0x1f00");
+        assert_eq!(json["kind"], "software");
+        assert_eq!(json["state"], "requested");
+        assert_eq!(json["one_shot"], true);
+        assert!(json.get("watch_access").is_none());
+        assert!(json.get("hardware_slot").is_none());
+    }
+
+    #[test]
+    fn hardware_slots_are_handed_out_lowest_first_and_freed_by_disabling()
+    {
+        fn hardware(store: &mut BreakpointStore, address: u64) -> Result<BreakpointId>
+        {
+            let slot = store.free_hardware_slot(2)?;
+            let address = Address::from(address);
+            let mut info = BreakpointInfo::new(BreakpointId::from_raw(0), address, BreakpointKind::Hardware);
+            info.enabled = true;
+            info.hardware_slot = Some(slot);
+            Ok(store.insert(BreakpointEntry {
+                info,
+                payload: BreakpointPayload::Hardware { address, slot },
+            }))
+        }
+
+        let mut store = BreakpointStore::new();
+        let first = hardware(&mut store, 0x1000).unwrap();
+        software(&mut store, 0x2000, false);
+        let second = hardware(&mut store, 0x3000).unwrap();
+        assert_eq!(store.hardware_slots_in_use(), vec![0, 1]);
+        assert!(matches!(hardware(&mut store, 0x4000), Err(DebuggerError::NoHardwareSlots(2))));
+
+        let json = serde_json::to_value(&store.get(second).unwrap().info).unwrap();
+        assert_eq!(json["hardware_slot"], 1);
+
+        store.get_mut(first).unwrap().info.enabled = false;
+        assert_eq!(store.hardware_slots_in_use(), vec![1]);
+        assert_eq!(store.free_hardware_slot(2).unwrap(), 0);
+
+        // Only the armed one still needs programming on new threads
+        let armed = store.armed_debug_registers();
+        assert_eq!(armed.len(), 1);
+        assert!(matches!(armed[0], BreakpointPayload::Hardware { slot: 1, .. }));
+    }
+}
@@ -0,0 +1,525 @@
+//! Saving breakpoints to disk and restoring them in a later run.
+//!
+//! A [`BreakpointFile`] is a JSON list of [`SavedBreakpoint`]s. Addresses
+//! move between runs (ASLR, or a rebuilt binary), so each code breakpoint is
+//! saved with the function it is in and its offset from the function's
+//! entry, and with its source line when the image has line information. On
+//! restore the breakpoint is re-resolved from those, in that order; the saved
+//! address is only used when neither was known.
+//!
+//! Restarting a target keeps its address space close enough that
+//! [`BreakpointFile::relocate`] can also move breakpoints saved only by
+//! address, by their offset into the image they were in.
+//!
+//! A breakpoint that doesn't re-resolve, for example because its image isn't
+//! loaded yet, is returned as an [`UnresolvedBreakpoint`] rather than dropped,
+//! so the front-end can keep it requested and try again later.
+//!
+//! ```rust
+//! use ferros_core::breakpoints::{BreakpointFile, BreakpointKind, SavedBreakpoint};
+//!
+//! let json = r#"{ "version": 1, "breakpoints": [
+//!     { "kind": "software", "address": 4294971392, "function": "app::run", "offset": 16, "enabled": true }
+//! ] }"#;
+//! let file: BreakpointFile = serde_json::from_str(json).unwrap();
+//! let saved: &SavedBreakpoint = &file.breakpoints[0];
+//! assert_eq!(saved.kind, BreakpointKind::Software);
+//! assert_eq!(saved.to_string(), "app::run+0x10");
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::{fmt, fs};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    BreakpointAction, BreakpointId, BreakpointInfo, BreakpointKind, BreakpointLocation, BreakpointOptions,
+    BreakpointRequest, BreakpointRequestKind, WatchpointAccess,
+};
+use crate::debugger::Debugger;
+use crate::error::{DebuggerError, Result};
+use crate::inspector::TargetInspector;
+use crate::notes::ImageAnchor;
+use crate::symbols::demangle::plain_name;
+use crate::types::{Address, AddressExpr, MemoryRegion};
+
+/// Version of the breakpoint file layout. Bump on incompatible changes.
+pub const BREAKPOINT_FILE_VERSION: u32 = 1;
+
+/// Length restored for a watchpoint saved without one.
+const DEFAULT_WATCH_LENGTH: usize = 8;
+
+/// One breakpoint as written to a [`BreakpointFile`].
+///
+/// The thread filter isn't saved: thread IDs don't carry over to another run.
+/// Neither are hit and ignore counts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedBreakpoint
+{
+    /// Software, hardware or watchpoint.
+    pub kind: BreakpointKind,
+    /// Address in the run the breakpoint was saved from.
+    pub address: u64,
+    /// Function the address is in, as typed to break on it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// Bytes from the function's entry to the address.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: u64,
+    /// Source file the address was compiled from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Source line the address was compiled from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Bytes watched, for watchpoints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    /// Accesses that trigger a watchpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access: Option<WatchpointAccess>,
+    /// Whether the breakpoint was armed.
+    pub enabled: bool,
+    /// Condition that must hold for a hit to stop the target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// Group the breakpoint belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Removed after its first hit.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub one_shot: bool,
+    /// Placed without checking it starts an instruction.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force: bool,
+    /// Actions run on a hit.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<BreakpointAction>,
+}
+
+fn is_zero(value: &u64) -> bool
+{
+    *value == 0
+}
+
+impl SavedBreakpoint
+{
+    /// Describe `info` for saving, symbolicating its address with `target`.
+    ///
+    /// Watchpoints watch data, which rarely has a symbol, so only their
+    /// address is saved.
+    pub fn capture<T: TargetInspector + ?Sized>(info: &BreakpointInfo, target: &T) -> Self
+    {
+        let code = info.kind != BreakpointKind::Watchpoint;
+        let function = code
+            .then(|| target.function_start(info.address).zip(target.function_name(info.address)))
+            .flatten();
+        let location = code.then(|| target.source_location(info.address)).flatten();
+        Self {
+            kind: info.kind,
+            address: info.address.value(),
+            offset: function.as_ref().map_or(0, |(start, _)| info.address.value() - start.value()),
+            function: function.map(|(_, symbol)| plain_name(&symbol)),
+            line: location.as_ref().and_then(|location| location.line),
+            file: location.map(|location| location.file),
+            length: info.watch_length,
+            access: info.watch_access,
+            enabled: info.enabled,
+            condition: info.options.condition.clone(),
+            group: info.options.group.clone(),
+            one_shot: info.options.one_shot,
+            force: info.options.force,
+            actions: info.options.actions.clone(),
+        }
+    }
+
+    /// Find the breakpoint's address in this run.
+    ///
+    /// Uses the function and offset when they were saved, then the source
+    /// line, and only then the saved address, which ASLR has usually moved.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: The function isn't loaded, or the line has no code yet
+    /// - Anything [`Debugger::resolve_location`] returns
+    pub fn resolve<D: Debugger + ?Sized>(&self, debugger: &mut D) -> Result<Address>
+    {
+        if let Some(name) = &self.function {
+            let offset = i64::try_from(self.offset)
+                .map_err(|_| DebuggerError::InvalidArgument(format!("{self} is too far into its function")))?;
+            return debugger.resolve_address(&AddressExpr::Symbol {
+                name: name.clone(),
+                offset,
+            });
+        }
+        if let (Some(file), Some(line)) = (&self.file, self.line) {
+            let location = BreakpointLocation::Line {
+                file: file.clone(),
+                line,
+            };
+            return debugger
+                .resolve_location(&location)?
+                .first()
+                .copied()
+                .ok_or_else(|| DebuggerError::InvalidArgument(format!("No code at {location}")));
+        }
+        Ok(Address::from(self.address))
+    }
+
+    /// Request for this breakpoint at `address`, its address in this run.
+    #[must_use]
+    pub fn request(&self, address: Address) -> BreakpointRequest
+    {
+        let kind = match self.kind {
+            BreakpointKind::Software => BreakpointRequestKind::Software { address },
+            BreakpointKind::Hardware => BreakpointRequestKind::Hardware {
+                address,
+                allow_fallback: false,
+            },
+            BreakpointKind::Watchpoint => BreakpointRequestKind::Watchpoint {
+                address,
+                length: self.length.unwrap_or(DEFAULT_WATCH_LENGTH),
+                access: self.access.unwrap_or(WatchpointAccess::Write),
+            },
+        };
+        BreakpointRequest {
+            kind,
+            options: BreakpointOptions {
+                condition: self.condition.clone(),
+                group: self.group.clone(),
+                thread: None,
+                one_shot: self.one_shot,
+                force: self.force,
+                hidden: false,
+                actions: self.actions.clone(),
+            },
+            enabled: self.enabled,
+        }
+    }
+}
+
+impl fmt::Display for SavedBreakpoint
+{
+    /// `function+0x10`, `file:line` or the saved address, whichever restore resolves from.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match (&self.function, &self.file, self.line) {
+            (Some(function), ..) if self.offset == 0 => write!(f, "{function}"),
+            (Some(function), ..) => write!(f, "{function}+{:#x}", self.offset),
+            (None, Some(file), Some(line)) => write!(f, "{file}:{line}"),
+            _ => write!(f, "{:#x}", self.address),
+        }
+    }
+}
+
+/// A saved breakpoint that couldn't be installed in this run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedBreakpoint
+{
+    /// The breakpoint as saved, to try again later.
+    pub saved: SavedBreakpoint,
+    /// Why it couldn't be resolved or installed.
+    pub reason: String,
+}
+
+/// What [`BreakpointFile::restore`] did with each saved breakpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreReport
+{
+    /// Breakpoints installed, in file order.
+    pub installed: Vec<BreakpointId>,
+    /// Breakpoints still waiting for their location.
+    pub unresolved: Vec<UnresolvedBreakpoint>,
+}
+
+/// A saved list of breakpoints, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakpointFile
+{
+    /// Layout version, [`BREAKPOINT_FILE_VERSION`] when written.
+    pub version: u32,
+    /// Breakpoints in the order they were listed.
+    #[serde(default)]
+    pub breakpoints: Vec<SavedBreakpoint>,
+}
+
+impl Default for BreakpointFile
+{
+    fn default() -> Self
+    {
+        Self {
+            version: BREAKPOINT_FILE_VERSION,
+            breakpoints: Vec::new(),
+        }
+    }
+}
+
+impl BreakpointFile
+{
+    /// Path of the breakpoint file for `program`: `~/.ferros/breakpoints-<file name>.json`.
+    ///
+    /// Returns `None` if `HOME` isn't set.
+    #[must_use]
+    pub fn path_for(program: &Path) -> Option<PathBuf>
+    {
+        let home = std::env::var_os("HOME")?;
+        let name = program
+            .file_name()
+            .map_or_else(|| "target".into(), |name| name.to_string_lossy());
+        Some(PathBuf::from(home).join(".ferros").join(format!("breakpoints-{name}.json")))
+    }
+
+    /// The user breakpoints of `target`, lowest ID first; hidden ones aren't saved.
+    pub fn capture<T: TargetInspector + ?Sized>(target: &T) -> Self
+    {
+        let mut breakpoints = target.breakpoints();
+        breakpoints.retain(|info| !info.options.hidden);
+        breakpoints.sort_by_key(|info| info.id.raw());
+        Self {
+            version: BREAKPOINT_FILE_VERSION,
+            breakpoints: breakpoints
+                .iter()
+                .map(|info| SavedBreakpoint::capture(info, target))
+                .collect(),
+        }
+    }
+
+    /// Install every saved breakpoint in `debugger`, keeping the ones that
+    /// don't resolve in the report instead of failing.
+    pub fn restore<D: Debugger + ?Sized>(&self, debugger: &mut D) -> RestoreReport
+    {
+        let mut report = RestoreReport::default();
+        for saved in &self.breakpoints {
+            let installed = saved
+                .resolve(debugger)
+                .and_then(|address| debugger.add_breakpoint(saved.request(address)));
+            match installed {
+                Ok(id) => report.installed.push(id),
+                Err(e) => report.unresolved.push(UnresolvedBreakpoint {
+                    saved: saved.clone(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        report
+    }
+
+    /// Move the breakpoints saved only by address from the image they were in
+    /// under `previous` to where it is mapped in `regions`. Returns how many moved.
+    ///
+    /// Breakpoints with a function or source line re-resolve from those
+    /// instead. `uuid_of` reads an image's UUID, so a rebuilt image isn't
+    /// trusted with old offsets; front-ends pass
+    /// [`image_uuid`](crate::symbols::image_uuid).
+    pub fn relocate(
+        &mut self,
+        previous: &[MemoryRegion],
+        regions: &[MemoryRegion],
+        uuid_of: impl Fn(&Path) -> Option<String>,
+    ) -> usize
+    {
+        let mut moved = 0;
+        for saved in &mut self.breakpoints {
+            let symbolic = saved.function.is_some() || (saved.file.is_some() && saved.line.is_some());
+            if symbolic {
+                continue;
+            }
+            let relocated = ImageAnchor::for_address(saved.address, previous, &uuid_of)
+                .and_then(|anchor| anchor.relocate(regions, &uuid_of));
+            if let Some(address) = relocated
+                && address != saved.address
+            {
+                saved.address = address;
+                moved += 1;
+            }
+        }
+        moved
+    }
+
+    /// Load a breakpoint file. A missing file has no breakpoints.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Io` if the file exists but can't be read, or `InvalidArgument`
+    /// if it isn't a valid breakpoint file or is from a newer ferros.
+    pub fn load(path: &Path) -> Result<Self>
+    {
+        let file: Self = match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|err| {
+                DebuggerError::InvalidArgument(format!("invalid breakpoint file {}: {err}", path.display()))
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        if file.version > BREAKPOINT_FILE_VERSION {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "breakpoint file {} is version {}; this ferros reads up to {BREAKPOINT_FILE_VERSION}",
+                path.display(),
+                file.version
+            )));
+        }
+        Ok(file)
+    }
+
+    /// Write the breakpoint file, creating parent directories as needed.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Io` if the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<()>
+    {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| DebuggerError::InvalidArgument(format!("failed to serialize breakpoint file: {err}")))?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn saved(kind: BreakpointKind) -> SavedBreakpoint
+    {
+        SavedBreakpoint {
+            kind,
+            address: 0x1_0000_4010,
+            function: None,
+            offset: 0,
+            file: None,
+            line: None,
+            length: None,
+            access: None,
+            enabled: true,
+            condition: None,
+            group: None,
+            one_shot: false,
+            force: false,
+            actions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn saved_breakpoints_round_trip_through_json()
+    {
+        let path = std::env::temp_dir().join(format!("ferros-breakpoints-{}.json", std::process::id()));
+        let file = BreakpointFile {
+            version: BREAKPOINT_FILE_VERSION,
+            breakpoints: vec![
+                SavedBreakpoint {
+                    function: Some("app::run".to_string()),
+                    offset: 0x10,
+                    file: Some("src/main.rs".to_string()),
+                    line: Some(42),
+                    condition: Some("$x0 == 3".to_string()),
+                    one_shot: true,
+                    actions: vec![BreakpointAction::Log("hit".to_string())],
+                    ..saved(BreakpointKind::Software)
+                },
+                SavedBreakpoint {
+                    length: Some(4),
+                    access: Some(WatchpointAccess::ReadWrite),
+                    enabled: false,
+                    ..saved(BreakpointKind::Watchpoint)
+                },
+            ],
+        };
+        file.save(&path).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains(r#""access": "read_write""#), "{json}");
+        assert_eq!(BreakpointFile::load(&path).unwrap(), file);
+        fs::remove_file(&path).unwrap();
+
+        // Missing is empty; a newer layout is refused
+        assert!(BreakpointFile::load(&path).unwrap().breakpoints.is_empty());
+        fs::write(&path, r#"{ "version": 99 }"#).unwrap();
+        assert!(BreakpointFile::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn requests_keep_the_options_but_not_the_thread()
+    {
+        let watch = SavedBreakpoint {
+            access: Some(WatchpointAccess::Read),
+            group: Some("io".to_string()),
+            enabled: false,
+            ..saved(BreakpointKind::Watchpoint)
+        };
+        let request = watch.request(Address::from(0x2000));
+        assert_eq!(
+            request.kind,
+            BreakpointRequestKind::Watchpoint {
+                address: Address::from(0x2000),
+                length: DEFAULT_WATCH_LENGTH,
+                access: WatchpointAccess::Read,
+            }
+        );
+        assert_eq!(request.options.group.as_deref(), Some("io"));
+        assert!(request.options.thread.is_none() && !request.enabled);
+
+        assert_eq!(watch.to_string(), "0x100004010");
+        let line = SavedBreakpoint {
+            file: Some("src/lib.rs".to_string()),
+            line: Some(7),
+            ..saved(BreakpointKind::Hardware)
+        };
+        assert_eq!(line.to_string(), "src/lib.rs:7");
+    }
+
+    #[test]
+    fn address_only_breakpoints_move_with_their_image()
+    {
+        let mapping = |start: u64| {
+            MemoryRegion::new(
+                crate::types::MemoryRegionId(0),
+                Address::from(start),
+                Address::from(start + 0x8000),
+                "r-x".to_string(),
+                Some("/work/app".to_string()),
+            )
+        };
+        let uuid = |_: &Path| Some("UUID".to_string());
+        let mut file = BreakpointFile {
+            version: BREAKPOINT_FILE_VERSION,
+            breakpoints: vec![
+                saved(BreakpointKind::Software),
+                SavedBreakpoint {
+                    function: Some("app::run".to_string()),
+                    ..saved(BreakpointKind::Software)
+                },
+                SavedBreakpoint {
+                    address: 0x7000_0000,
+                    ..saved(BreakpointKind::Hardware)
+                },
+            ],
+        };
+
+        let moved = file.relocate(&[mapping(0x1_0000_4000)], &[mapping(0x1_0020_4000)], uuid);
+        assert_eq!(moved, 1);
+        let addresses: Vec<u64> = file.breakpoints.iter().map(|saved| saved.address).collect();
+        assert_eq!(addresses, [0x1_0020_4010, 0x1_0000_4010, 0x7000_0000]);
+
+        // A rebuilt image keeps the old address
+        let rebuilt = |_: &Path| None;
+        assert_eq!(
+            file.relocate(&[mapping(0x1_0020_4000)], &[mapping(0x1_0000_4000)], rebuilt),
+            0
+        );
+    }
+
+    #[test]
+    fn files_are_named_after_the_program()
+    {
+        let Some(path) = BreakpointFile::path_for(Path::new("/work/target/debug/app")) else {
+            return;
+        };
+        assert!(path.ends_with(".ferros/breakpoints-app.json"), "{}", path.display());
+    }
+}
@@ -0,0 +1,216 @@
+//! Per-breakpoint overhead accounting.
+//!
+//! Every time a breakpoint is hit the target is held in the exception handler
+//! until the debugger replies. A breakpoint that is hit thousands of times a
+//! second, such as a conditional breakpoint in a hot loop, can spend a large
+//! share of the target's wall time there. [`BreakpointStats`] keeps the total
+//! handling time and a sliding window of recent hits so front-ends can show
+//! each breakpoint's hit rate and overhead and flag the expensive ones.
+//!
+//! The window is [`STATS_WINDOW`] long, kept as one bucket per second, so
+//! recording a hit is O(1) and needs nothing but the monotonic clock.
+//!
+//! ```rust
+//! use std::time::{Duration, Instant};
+//!
+//! use ferros_core::breakpoints::BreakpointStats;
+//!
+//! let start = Instant::now();
+//! let mut stats = BreakpointStats::default();
+//! for hit in 0..2000 {
+//!     stats.record(
+//!         start + Duration::from_millis(hit),
+//!         Duration::from_micros(200),
+//!     );
+//! }
+//! let now = start + Duration::from_secs(2);
+//! assert_eq!(stats.hits_per_second(now), 1000.0);
+//! assert!(stats.is_expensive(now)); // 20% of the last two seconds
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Length of the sliding window used for the hit rate and overhead.
+pub const STATS_WINDOW: Duration = Duration::from_secs(10);
+
+/// Share of wall time in the window above which a breakpoint is expensive.
+pub const OVERHEAD_WARNING_FRACTION: f64 = 0.10;
+
+/// One bucket per second of the window.
+const BUCKETS: usize = STATS_WINDOW.as_secs() as usize;
+
+/// Hits and handling time recorded during one second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Bucket
+{
+    /// Seconds since the first recorded hit.
+    second: u64,
+    hits: u64,
+    handling: Duration,
+}
+
+/// Hit rate and handling time of one breakpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BreakpointStats
+{
+    /// Hits whose handling was timed.
+    pub timed_hits: u64,
+    /// Time the target spent held in the handler for this breakpoint,
+    /// not counting time it sat stopped for the user.
+    pub total_handling: Duration,
+    /// Hits whose condition couldn't be evaluated and was treated as false.
+    pub condition_failures: u64,
+    /// Why the most recent condition evaluation failed.
+    pub last_condition_failure: Option<String>,
+    /// Monotonic time of the first timed hit; bucket seconds count from here.
+    epoch: Option<Instant>,
+    buckets: [Bucket; BUCKETS],
+}
+
+impl BreakpointStats
+{
+    /// Record a hit at `at` that held the target for `handling`.
+    pub fn record(&mut self, at: Instant, handling: Duration)
+    {
+        let epoch = *self.epoch.get_or_insert(at);
+        let second = at.saturating_duration_since(epoch).as_secs();
+        let bucket = &mut self.buckets[(second % BUCKETS as u64) as usize];
+        if bucket.second != second || bucket.hits == 0 {
+            *bucket = Bucket {
+                second,
+                ..Bucket::default()
+            };
+        }
+        bucket.hits += 1;
+        bucket.handling += handling;
+        self.timed_hits += 1;
+        self.total_handling += handling;
+    }
+
+    /// Count a hit whose condition failed to evaluate because of `reason`.
+    pub fn record_condition_failure(&mut self, reason: String)
+    {
+        self.condition_failures += 1;
+        self.last_condition_failure = Some(reason);
+    }
+
+    /// Mean handling time per hit, or `None` before the first timed hit.
+    #[must_use]
+    pub fn average_handling(&self) -> Option<Duration>
+    {
+        let hits = u32::try_from(self.timed_hits).unwrap_or(u32::MAX);
+        (hits > 0).then(|| self.total_handling / hits)
+    }
+
+    /// Hits per second over the window ending at `now`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn hits_per_second(&self, now: Instant) -> f64
+    {
+        let (hits, _) = self.window(now);
+        hits as f64 / self.span(now).as_secs_f64()
+    }
+
+    /// Share of wall time in the window ending at `now` spent handling hits, in `0.0..=1.0`.
+    #[must_use]
+    pub fn overhead(&self, now: Instant) -> f64
+    {
+        let (_, handling) = self.window(now);
+        (handling.as_secs_f64() / self.span(now).as_secs_f64()).min(1.0)
+    }
+
+    /// Whether the overhead is over [`OVERHEAD_WARNING_FRACTION`].
+    #[must_use]
+    pub fn is_expensive(&self, now: Instant) -> bool
+    {
+        self.overhead(now) > OVERHEAD_WARNING_FRACTION
+    }
+
+    /// Hits and handling time in the buckets still inside the window.
+    fn window(&self, now: Instant) -> (u64, Duration)
+    {
+        let Some(epoch) = self.epoch else {
+            return (0, Duration::ZERO);
+        };
+        let current = now.saturating_duration_since(epoch).as_secs();
+        self.buckets
+            .iter()
+            .filter(|bucket| bucket.hits > 0 && bucket.second <= current && bucket.second + BUCKETS as u64 > current)
+            .fold((0, Duration::ZERO), |(hits, handling), bucket| {
+                (hits + bucket.hits, handling + bucket.handling)
+            })
+    }
+
+    /// Wall time the window covers: since the first hit, between one second and [`STATS_WINDOW`].
+    fn span(&self, now: Instant) -> Duration
+    {
+        self.epoch
+            .map_or(STATS_WINDOW, |epoch| now.saturating_duration_since(epoch))
+            .clamp(Duration::from_secs(1), STATS_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn idle_breakpoints_cost_nothing()
+    {
+        let stats = BreakpointStats::default();
+        let now = Instant::now();
+        assert_eq!(stats.hits_per_second(now), 0.0);
+        assert_eq!(stats.overhead(now), 0.0);
+        assert_eq!(stats.average_handling(), None);
+        assert!(!stats.is_expensive(now));
+    }
+
+    #[test]
+    fn window_slides_and_totals_keep_everything()
+    {
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let mut stats = BreakpointStats::default();
+
+        // 100 hits of 2ms in each of the first 10 seconds
+        for second in 0..10 {
+            for hit in 0..100 {
+                stats.record(at(second * 1000 + hit * 10), Duration::from_millis(2));
+            }
+        }
+        let now = at(9_999);
+        assert!((stats.hits_per_second(now) - 100.0).abs() < 0.1);
+        assert!((stats.overhead(now) - 0.2).abs() < 1e-3);
+        assert!(stats.is_expensive(now));
+        assert_eq!(stats.average_handling(), Some(Duration::from_millis(2)));
+
+        // Five quiet seconds later half of the window has slid out
+        let later = at(14_500);
+        assert!((stats.hits_per_second(later) - 50.0).abs() < 1e-9);
+        assert!((stats.overhead(later) - 0.1).abs() < 1e-9);
+        assert!(!stats.is_expensive(later));
+
+        // A new hit reuses the oldest bucket without counting what was in it
+        stats.record(at(15_000), Duration::from_millis(2));
+        assert!((stats.hits_per_second(at(15_000)) - 40.1).abs() < 1e-9);
+
+        // Long after, only the totals remain
+        let idle = at(60_000);
+        assert_eq!(stats.hits_per_second(idle), 0.0);
+        assert_eq!(stats.timed_hits, 1001);
+        assert_eq!(stats.total_handling, Duration::from_millis(2002));
+    }
+
+    #[test]
+    fn first_second_is_not_overstated()
+    {
+        let start = Instant::now();
+        let mut stats = BreakpointStats::default();
+        stats.record(start, Duration::from_millis(50));
+        // One slow hit right after the start is measured against a full second
+        let now = start + Duration::from_millis(60);
+        assert!((stats.overhead(now) - 0.05).abs() < 1e-9);
+        assert!(!stats.is_expensive(now));
+    }
+}
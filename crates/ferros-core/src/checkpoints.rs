@@ -0,0 +1,573 @@
+//! Lightweight checkpoints for looking back at earlier stops.
+//!
+//! This is not record/replay: nothing is re-executed and the live target is
+//! never touched. At each user-visible stop the [`CheckpointHistory`] can
+//! capture a [`Checkpoint`] holding
+//!
+//! - the registers of the thread the stop is about (and of every other
+//!   thread the debugger can read per-thread),
+//! - the call stack already unwound for that thread, and
+//! - its stack memory from SP upwards, bounded by
+//!   [`CheckpointOptions::stack_budget`].
+//!
+//! Checkpoints live in a ring of [`CheckpointOptions::capacity`] entries; the
+//! oldest is dropped when it is full. With a zero stack budget a checkpoint is
+//! just registers and frames, cheap enough to take at every stop. Stack memory is stored per page and a
+//! page whose contents did not change since the previous checkpoint is shared
+//! with it rather than copied, so a run of stops in the same frame costs
+//! little more than its dirty pages.
+//!
+//! ## Navigation
+//!
+//! The newest checkpoint mirrors the present stop, so [`back`](CheckpointHistory::back)
+//! from the live state moves to the checkpoint before it. Moving
+//! [`forward`](CheckpointHistory::forward) onto the newest checkpoint returns
+//! to the live state.
+//!
+//! ```rust
+//! use ferros_core::checkpoints::{CheckpointHistory, CheckpointOptions};
+//!
+//! let history = CheckpointHistory::new(CheckpointOptions::new(16).with_stack_budget(32 * 1024));
+//! assert!(history.is_live());
+//! assert!(history.current().is_none());
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::crash::CrashSite;
+use crate::error::Result;
+use crate::inspector::TargetInspector;
+use crate::types::{Address, Registers, StackFrame, StopReason, ThreadId};
+
+/// Granularity of captured stack memory and of page sharing between checkpoints.
+pub const CHECKPOINT_PAGE_SIZE: u64 = 4096;
+
+/// Options controlling how many checkpoints are kept and how much they hold.
+///
+/// ```rust
+/// use ferros_core::checkpoints::CheckpointOptions;
+///
+/// let options = CheckpointOptions::new(8).with_stack_budget(16 * 1024);
+/// assert_eq!(options.capacity, 8);
+/// assert_eq!(options.stack_budget, 16 * 1024);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointOptions
+{
+    /// Number of checkpoints kept before the oldest is dropped.
+    pub capacity: usize,
+    /// Bytes of stack captured per checkpoint, rounded down to whole pages.
+    pub stack_budget: usize,
+}
+
+impl CheckpointOptions
+{
+    /// Keep at most `capacity` checkpoints with the default stack budget.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self
+    {
+        Self {
+            capacity,
+            stack_budget: 64 * 1024,
+        }
+    }
+
+    /// Capture at most `bytes` of stack per checkpoint.
+    #[must_use]
+    pub fn with_stack_budget(mut self, bytes: usize) -> Self
+    {
+        self.stack_budget = bytes;
+        self
+    }
+}
+
+impl Default for CheckpointOptions
+{
+    fn default() -> Self
+    {
+        Self::new(32)
+    }
+}
+
+/// Target state captured at one stop.
+#[derive(Debug, Clone)]
+pub struct Checkpoint
+{
+    /// Sequence number, increasing across the whole session.
+    pub id: u64,
+    /// When the checkpoint was captured.
+    pub timestamp: Instant,
+    /// Why the target was stopped.
+    pub reason: StopReason,
+    /// Thread the stop is about: the faulting thread, else the active one.
+    pub thread: Option<ThreadId>,
+    /// Registers of [`thread`](Self::thread).
+    pub registers: Registers,
+    /// Registers of every thread that could be read per-thread.
+    pub thread_registers: Vec<(ThreadId, Registers)>,
+    /// Call stack of [`thread`](Self::thread) at the stop.
+    pub frames: Vec<StackFrame>,
+    /// Faulting thread and crash frame, when the stop was a crash.
+    pub crash: Option<CrashSite>,
+    /// Stack pages by page address; unchanged pages are shared with the previous checkpoint.
+    pages: BTreeMap<u64, Arc<[u8]>>,
+    /// Bytes of stack this checkpoint did not share with its predecessor.
+    new_bytes: usize,
+}
+
+impl Checkpoint
+{
+    /// Registers of `thread`, if they were captured.
+    #[must_use]
+    pub fn registers_for(&self, thread: ThreadId) -> Option<&Registers>
+    {
+        if self.thread == Some(thread) {
+            return Some(&self.registers);
+        }
+        self.thread_registers
+            .iter()
+            .find(|(id, _)| *id == thread)
+            .map(|(_, registers)| registers)
+    }
+
+    /// Read captured memory, or `None` if any byte of the range wasn't captured.
+    #[must_use]
+    pub fn read_memory(&self, addr: Address, len: usize) -> Option<Vec<u8>>
+    {
+        let mut out = Vec::with_capacity(len);
+        let mut cursor = addr.value();
+        let end = cursor.checked_add(len as u64)?;
+        while cursor < end {
+            let page = cursor - cursor % CHECKPOINT_PAGE_SIZE;
+            let bytes = self.pages.get(&page)?;
+            let offset = usize::try_from(cursor - page).ok()?;
+            let take = (bytes.len() - offset).min(usize::try_from(end - cursor).ok()?);
+            out.extend_from_slice(bytes.get(offset..offset + take)?);
+            cursor += take as u64;
+        }
+        Some(out)
+    }
+
+    /// Contiguous captured memory ranges as `(start, length)`.
+    #[must_use]
+    pub fn captured_ranges(&self) -> Vec<(Address, usize)>
+    {
+        let mut ranges: Vec<(Address, usize)> = Vec::new();
+        for (&page, bytes) in &self.pages {
+            match ranges.last_mut() {
+                Some((start, len)) if start.value() + *len as u64 == page => *len += bytes.len(),
+                _ => ranges.push((Address::from(page), bytes.len())),
+            }
+        }
+        ranges
+    }
+
+    /// Bytes of stack this checkpoint holds that its predecessor didn't.
+    #[must_use]
+    pub fn new_bytes(&self) -> usize
+    {
+        self.new_bytes
+    }
+}
+
+/// Ring of checkpoints plus a cursor for read-only navigation.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointHistory
+{
+    options: CheckpointOptions,
+    checkpoints: VecDeque<Checkpoint>,
+    /// Index into `checkpoints` being inspected, or `None` when live.
+    cursor: Option<usize>,
+    next_id: u64,
+}
+
+impl CheckpointHistory
+{
+    /// Create an empty history.
+    #[must_use]
+    pub fn new(options: CheckpointOptions) -> Self
+    {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Options this history was created with.
+    #[must_use]
+    pub fn options(&self) -> CheckpointOptions
+    {
+        self.options
+    }
+
+    /// Change how much stack later captures copy; existing checkpoints keep theirs.
+    pub fn set_stack_budget(&mut self, bytes: usize)
+    {
+        self.options.stack_budget = bytes;
+    }
+
+    /// Checkpoints oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Checkpoint>
+    {
+        self.checkpoints.iter()
+    }
+
+    /// Capture the stopped target's state as a new checkpoint.
+    ///
+    /// `thread` is the thread the stop is about, such as the one that faulted;
+    /// `None` means the active thread. `frames` is the call stack already
+    /// unwound for it; it's stored as-is rather than unwound again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `thread`'s registers can't be read. Other threads'
+    /// registers and stack pages are captured best-effort.
+    pub fn capture(
+        &mut self,
+        target: &dyn TargetInspector,
+        reason: StopReason,
+        thread: Option<ThreadId>,
+        frames: Vec<StackFrame>,
+    ) -> Result<&Checkpoint>
+    {
+        let active = target.active_thread();
+        let thread = thread.or(active);
+        let registers = match thread {
+            Some(id) if thread != active => target.read_registers_for(id)?,
+            _ => target.read_registers()?,
+        };
+        let thread_registers = target
+            .threads()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&id| Some(id) != thread)
+            .filter_map(|id| target.read_registers_for(id).ok().map(|regs| (id, regs)))
+            .collect();
+
+        // Capture from SP up to the end of the stack mapping, within budget
+        let sp = registers.sp.value();
+        let stack_end = if self.options.stack_budget < CHECKPOINT_PAGE_SIZE as usize {
+            None
+        } else {
+            target
+                .get_memory_regions()
+                .unwrap_or_default()
+                .iter()
+                .find(|region| region.start.value() <= sp && sp < region.end.value())
+                .map(|region| region.end.value())
+        };
+        let state = CapturedState {
+            reason,
+            thread,
+            registers,
+            thread_registers,
+            frames,
+        };
+        Ok(self.push(state, stack_end, |addr, len| target.read_memory(addr, len)))
+    }
+
+    fn push(
+        &mut self,
+        state: CapturedState,
+        stack_end: Option<u64>,
+        mut read: impl FnMut(Address, usize) -> Result<Vec<u8>>,
+    ) -> &Checkpoint
+    {
+        let budget_pages = self.options.stack_budget as u64 / CHECKPOINT_PAGE_SIZE;
+        let start = state.registers.sp.value() - state.registers.sp.value() % CHECKPOINT_PAGE_SIZE;
+        let end = start
+            .saturating_add(budget_pages * CHECKPOINT_PAGE_SIZE)
+            .min(stack_end.unwrap_or(u64::MAX));
+
+        let previous = self.checkpoints.back();
+        let mut pages = BTreeMap::new();
+        let mut new_bytes = 0;
+        for page in (start..end).step_by(CHECKPOINT_PAGE_SIZE as usize) {
+            // Unreadable pages are skipped; the rest of the stack is still useful
+            let Ok(bytes) = read(Address::from(page), CHECKPOINT_PAGE_SIZE as usize) else {
+                continue;
+            };
+            let shared = previous
+                .and_then(|checkpoint| checkpoint.pages.get(&page))
+                .filter(|old| old[..] == bytes[..]);
+            let bytes = if let Some(old) = shared {
+                Arc::clone(old)
+            } else {
+                new_bytes += bytes.len();
+                Arc::from(bytes)
+            };
+            pages.insert(page, bytes);
+        }
+
+        if self.checkpoints.len() == self.options.capacity.max(1) {
+            self.checkpoints.pop_front();
+            self.cursor = self.cursor.map(|index| index.saturating_sub(1));
+        }
+        self.checkpoints.push_back(Checkpoint {
+            id: self.next_id,
+            timestamp: Instant::now(),
+            reason: state.reason,
+            thread: state.thread,
+            registers: state.registers,
+            thread_registers: state.thread_registers,
+            crash: CrashSite::locate(state.reason, state.thread, &state.frames),
+            frames: state.frames,
+            pages,
+            new_bytes,
+        });
+        self.next_id += 1;
+        self.checkpoints.back().expect("checkpoint was just pushed")
+    }
+
+    /// Step back one checkpoint; returns the checkpoint now being inspected.
+    ///
+    /// Returns `None` (and leaves the cursor alone) if there's nothing older.
+    pub fn back(&mut self) -> Option<&Checkpoint>
+    {
+        let index = match self.cursor {
+            Some(index) => index.checked_sub(1)?,
+            None => self.checkpoints.len().checked_sub(2)?,
+        };
+        self.cursor = Some(index);
+        self.checkpoints.get(index)
+    }
+
+    /// Step forward one checkpoint; returns to live when reaching the newest.
+    ///
+    /// Returns the checkpoint now being inspected, or `None` when live.
+    pub fn forward(&mut self) -> Option<&Checkpoint>
+    {
+        let index = self.cursor? + 1;
+        if index + 1 >= self.checkpoints.len() {
+            self.cursor = None;
+            return None;
+        }
+        self.cursor = Some(index);
+        self.checkpoints.get(index)
+    }
+
+    /// Inspect the checkpoint with `id`; seeking the newest one returns to live.
+    ///
+    /// Returns `false` (and leaves the cursor alone) if that checkpoint has
+    /// been dropped from the ring.
+    pub fn seek(&mut self, id: u64) -> bool
+    {
+        let Some(index) = self.checkpoints.iter().position(|checkpoint| checkpoint.id == id) else {
+            return false;
+        };
+        self.cursor = (index + 1 < self.checkpoints.len()).then_some(index);
+        true
+    }
+
+    /// Return to the live state.
+    pub fn live(&mut self)
+    {
+        self.cursor = None;
+    }
+
+    /// Whether the live target (rather than a checkpoint) is being inspected.
+    #[must_use]
+    pub fn is_live(&self) -> bool
+    {
+        self.cursor.is_none()
+    }
+
+    /// The checkpoint being inspected, or `None` when live.
+    #[must_use]
+    pub fn current(&self) -> Option<&Checkpoint>
+    {
+        self.checkpoints.get(self.cursor?)
+    }
+
+    /// 1-based position of the inspected checkpoint and the number of checkpoints.
+    #[must_use]
+    pub fn position(&self) -> Option<(usize, usize)>
+    {
+        self.cursor.map(|index| (index + 1, self.checkpoints.len()))
+    }
+
+    /// Number of checkpoints held.
+    #[must_use]
+    pub fn len(&self) -> usize
+    {
+        self.checkpoints.len()
+    }
+
+    /// Whether no checkpoint has been captured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.checkpoints.is_empty()
+    }
+
+    /// Bytes of stack memory held across all checkpoints, counting shared pages once.
+    #[must_use]
+    pub fn footprint(&self) -> usize
+    {
+        self.checkpoints.iter().map(Checkpoint::new_bytes).sum()
+    }
+
+    /// Drop every checkpoint and return to live.
+    pub fn clear(&mut self)
+    {
+        self.checkpoints.clear();
+        self.cursor = None;
+    }
+}
+
+/// Everything in a checkpoint except its memory.
+struct CapturedState
+{
+    reason: StopReason,
+    thread: Option<ThreadId>,
+    registers: Registers,
+    thread_registers: Vec<(ThreadId, Registers)>,
+    frames: Vec<StackFrame>,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::error::DebuggerError;
+
+    const STACK_TOP: u64 = 0x7000_8000;
+
+    fn state(pc: u64, sp: u64) -> CapturedState
+    {
+        let mut registers = Registers::new();
+        registers.pc = Address::from(pc);
+        registers.sp = Address::from(sp);
+        CapturedState {
+            reason: StopReason::Suspended,
+            thread: Some(ThreadId::from(1)),
+            registers,
+            thread_registers: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Stack whose bytes are all `fill`
+    fn stack(fill: u8) -> impl FnMut(Address, usize) -> Result<Vec<u8>>
+    {
+        move |_, len| Ok(vec![fill; len])
+    }
+
+    #[test]
+    fn back_and_forward_walk_recorded_stops()
+    {
+        let mut history = CheckpointHistory::new(CheckpointOptions::new(16).with_stack_budget(0));
+        for step in 0..10u64 {
+            history.push(state(0x1000 + step * 4, 0x7000_0000 - step * 16), None, stack(0));
+        }
+        assert!(history.is_live());
+        // A zero budget keeps registers and frames only
+        assert!(history.iter().all(|checkpoint| checkpoint.captured_ranges().is_empty()));
+
+        for back in 1..=5u64 {
+            let checkpoint = history.back().unwrap();
+            let step = 9 - back;
+            assert_eq!(checkpoint.id, step);
+            assert_eq!(checkpoint.registers.pc, Address::from(0x1000 + step * 4));
+            assert_eq!(checkpoint.registers.sp, Address::from(0x7000_0000 - step * 16));
+        }
+        assert_eq!(history.position(), Some((5, 10)));
+
+        assert_eq!(history.forward().unwrap().id, 5);
+        for _ in 0..3 {
+            assert!(history.forward().is_some());
+        }
+        // Moving onto the newest checkpoint is the live state
+        assert!(history.forward().is_none());
+        assert!(history.is_live());
+        assert!(history.current().is_none());
+
+        assert!(history.seek(3));
+        assert_eq!(history.current().unwrap().id, 3);
+        assert!(history.seek(9));
+        assert!(history.is_live());
+        assert!(!history.seek(10));
+    }
+
+    #[test]
+    fn unchanged_pages_are_shared_and_budget_bounds_capture()
+    {
+        let options = CheckpointOptions::new(4).with_stack_budget(3 * CHECKPOINT_PAGE_SIZE as usize + 100);
+        let mut history = CheckpointHistory::new(options);
+        let sp = STACK_TOP - 2 * CHECKPOINT_PAGE_SIZE + 8;
+
+        // Stack mapping ends two pages above SP's page, below the budget
+        let first = history.push(state(0x1000, sp), Some(STACK_TOP), stack(0xaa));
+        assert_eq!(
+            first.captured_ranges(),
+            vec![(
+                Address::from(STACK_TOP - 2 * CHECKPOINT_PAGE_SIZE),
+                2 * CHECKPOINT_PAGE_SIZE as usize
+            )]
+        );
+        assert_eq!(first.new_bytes(), 2 * CHECKPOINT_PAGE_SIZE as usize);
+
+        // Only the page holding SP changes
+        let dirty_page = STACK_TOP - 2 * CHECKPOINT_PAGE_SIZE;
+        let second = history.push(state(0x1004, sp), Some(STACK_TOP), |addr, len| {
+            Ok(vec![if addr.value() == dirty_page { 0xbb } else { 0xaa }; len])
+        });
+        assert_eq!(second.new_bytes(), CHECKPOINT_PAGE_SIZE as usize);
+        assert_eq!(history.footprint(), 3 * CHECKPOINT_PAGE_SIZE as usize);
+
+        // Without a known mapping the budget alone bounds the capture
+        let third = history.push(state(0x1008, sp), None, stack(0xaa));
+        assert_eq!(third.captured_ranges()[0].1, 3 * CHECKPOINT_PAGE_SIZE as usize);
+
+        // Dropping the budget affects later captures only
+        let footprint = history.footprint();
+        history.set_stack_budget(0);
+        assert!(
+            history
+                .push(state(0x100c, sp), None, stack(0xaa))
+                .captured_ranges()
+                .is_empty()
+        );
+        assert_eq!(history.footprint(), footprint);
+    }
+
+    #[test]
+    fn captured_memory_reads_back_and_gaps_are_unreadable()
+    {
+        let mut history =
+            CheckpointHistory::new(CheckpointOptions::new(2).with_stack_budget(2 * CHECKPOINT_PAGE_SIZE as usize));
+        let sp = STACK_TOP - 2 * CHECKPOINT_PAGE_SIZE;
+        let checkpoint = history.push(state(0x1000, sp), None, |addr, len| {
+            if addr.value() == sp {
+                Err(DebuggerError::InvalidArgument("unmapped".to_string()))
+            } else {
+                Ok((0..len).map(|i| u8::try_from(i % 251).unwrap()).collect())
+            }
+        });
+
+        let page = STACK_TOP - CHECKPOINT_PAGE_SIZE;
+        assert_eq!(checkpoint.read_memory(Address::from(page + 10), 3), Some(vec![10, 11, 12]));
+        assert_eq!(checkpoint.read_memory(Address::from(sp + 10), 3), None);
+        assert_eq!(checkpoint.read_memory(Address::from(page - 1), 2), None);
+    }
+
+    #[test]
+    fn full_ring_drops_the_oldest_checkpoint()
+    {
+        let mut history = CheckpointHistory::new(CheckpointOptions::new(3).with_stack_budget(0));
+        for step in 0..5 {
+            history.push(state(0x1000 + step, 0x7000_0000), None, stack(0));
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history.iter().rev().map(|checkpoint| checkpoint.id).collect::<Vec<_>>(),
+            vec![4, 3, 2]
+        );
+        assert_eq!(history.back().unwrap().id, 3);
+        assert_eq!(history.back().unwrap().id, 2);
+        assert!(history.back().is_none());
+        assert_eq!(history.current().unwrap().id, 2);
+    }
+}
@@ -0,0 +1,431 @@
+//! Locating the frame a crash happened in.
+//!
+//! When a target faults or aborts, the innermost frames of the faulting thread
+//! are rarely the interesting ones: an abort goes through `abort`, `raise` and
+//! `pthread_kill`, and a Rust panic through the `std::panicking` machinery,
+//! before the signal is delivered. [`crash_frame`] skips those frames so a
+//! front-end can put the user on the code that crashed.
+//!
+//! For a bad access, [`FaultRegion`] tells what the faulting address points
+//! into, which separates null pointers from wild ones and from writes to
+//! read-only memory, and [`ProbableOverflow`] recognises one that landed just
+//! below the faulting thread's stack.
+//!
+//! ```rust
+//! use ferros_core::crash::is_crash;
+//! use ferros_core::types::StopReason;
+//!
+//! assert!(is_crash(StopReason::Signal(libc::SIGSEGV)));
+//! assert!(!is_crash(StopReason::Breakpoint(0x1000)));
+//! ```
+
+use std::fmt::{self, Write as _};
+
+use crate::platform::stack_guard::GUARD_WINDOW;
+use crate::types::{Address, MemoryRegion, StackFrame, StopReason, ThreadId};
+
+/// Functions on the signal and abort path, matched against the whole symbol name.
+const SIGNAL_AND_ABORT: &[&str] = &[
+    "_sigtramp",
+    "__sigtramp",
+    "__pthread_kill",
+    "pthread_kill",
+    "__abort",
+    "abort",
+    "raise",
+    "__raise",
+    "rust_begin_unwind",
+    "rust_panic",
+    "__rust_start_panic",
+    "__rust_abort",
+];
+
+/// Rust panic and abort machinery, matched as a prefix of the demangled name.
+const PANIC_MACHINERY: &[&str] = &[
+    "std::panicking::",
+    "core::panicking::",
+    "std::process::abort",
+    "std::sys::abort_internal",
+    "std::sys::pal::unix::abort_internal",
+    "panic_abort::",
+    "__rustc::",
+];
+
+/// Faulting addresses below this are a null pointer plus a field or element offset.
+pub const NULL_WINDOW: u64 = 0x1_0000;
+
+/// Whether a stop is a crash: a fatal signal, a bad access or a stack overflow.
+#[must_use]
+pub fn is_crash(reason: StopReason) -> bool
+{
+    match reason {
+        StopReason::Signal(signal) => signal_name(signal).is_some(),
+        StopReason::BadAccess { .. } | StopReason::StackOverflow { .. } => true,
+        _ => false,
+    }
+}
+
+/// Name of a signal that kills a process by default, or `None` for others.
+#[must_use]
+pub fn signal_name(signal: i32) -> Option<&'static str>
+{
+    Some(match signal {
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGILL => "SIGILL",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGTRAP => "SIGTRAP",
+        _ => return None,
+    })
+}
+
+/// Whether `frame` is part of the signal, abort or panic path rather than the code that crashed.
+#[must_use]
+pub fn is_crash_machinery(frame: &StackFrame) -> bool
+{
+    let Some(symbol) = frame.symbol.as_ref() else {
+        return false;
+    };
+    let name = symbol.display_name();
+    SIGNAL_AND_ABORT.contains(&symbol.raw())
+        || SIGNAL_AND_ABORT.contains(&name)
+        || PANIC_MACHINERY.iter().any(|prefix| name.starts_with(prefix))
+}
+
+/// Index of the frame the crash happened in: the innermost one that isn't crash machinery.
+///
+/// Returns `None` for an empty backtrace and `Some(0)` when every frame is
+/// machinery, so there is always a frame to show.
+#[must_use]
+pub fn crash_frame(frames: &[StackFrame]) -> Option<usize>
+{
+    if frames.is_empty() {
+        return None;
+    }
+    Some(frames.iter().position(|frame| !is_crash_machinery(frame)).unwrap_or(0))
+}
+
+/// Where a crash happened: the faulting thread and its crash frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrashSite
+{
+    /// Why the target stopped.
+    pub reason: StopReason,
+    /// Thread that faulted, if known.
+    pub thread: Option<ThreadId>,
+    /// Index of the crash frame in that thread's backtrace.
+    pub frame: usize,
+}
+
+impl CrashSite
+{
+    /// Locate the crash in `frames`, the backtrace of the thread that stopped.
+    ///
+    /// Returns `None` if `reason` isn't a crash or there are no frames. A stack
+    /// overflow names its own thread; otherwise `thread` is used.
+    #[must_use]
+    pub fn locate(reason: StopReason, thread: Option<ThreadId>, frames: &[StackFrame]) -> Option<Self>
+    {
+        if !is_crash(reason) {
+            return None;
+        }
+        let thread = match reason {
+            StopReason::StackOverflow { thread, .. } => Some(thread),
+            _ => thread,
+        };
+        Some(Self {
+            reason,
+            thread,
+            frame: crash_frame(frames)?,
+        })
+    }
+
+    /// One-line summary such as `SIGSEGV at 0x100003f2c in demo::foo (src/foo.rs:42)`.
+    ///
+    /// A bad access leads with the address it faulted on instead of the PC:
+    /// ``EXC_BAD_ACCESS (read) at 0x10 — in function `demo::foo` (foo.rs:42)``.
+    #[must_use]
+    pub fn summary(&self, frames: &[StackFrame]) -> String
+    {
+        if let StopReason::BadAccess { address, kind } = self.reason {
+            let mut summary = format!("EXC_BAD_ACCESS ({kind}) at 0x{address:x}");
+            let Some(frame) = frames.get(self.frame) else {
+                return summary;
+            };
+            match &frame.symbol {
+                Some(symbol) => {
+                    let _ = write!(summary, " — in function `{}`", symbol.display_name());
+                }
+                None => {
+                    let _ = write!(summary, " — at 0x{:x}", frame.pc.value());
+                }
+            }
+            if let Some(location) = &frame.location {
+                let file = location.file.rsplit('/').next().unwrap_or(&location.file);
+                let _ = match location.line {
+                    Some(line) => write!(summary, " ({file}:{line})"),
+                    None => write!(summary, " ({file})"),
+                };
+            }
+            return summary;
+        }
+
+        let what = match self.reason {
+            StopReason::Signal(signal) => signal_name(signal).map_or_else(|| format!("Signal {signal}"), str::to_string),
+            StopReason::StackOverflow { .. } => "Stack overflow".to_string(),
+            _ => "Crash".to_string(),
+        };
+        let Some(frame) = frames.get(self.frame) else {
+            return what;
+        };
+        let mut summary = format!("{what} at 0x{:x}", frame.pc.value());
+        if let Some(symbol) = &frame.symbol {
+            let _ = write!(summary, " in {}", symbol.display_name());
+        }
+        if let Some(location) = &frame.location {
+            let _ = match location.line {
+                Some(line) => write!(summary, " ({}:{line})", location.file),
+                None => write!(summary, " ({})", location.file),
+            };
+        }
+        summary
+    }
+}
+
+/// What a faulting address points into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaultRegion
+{
+    /// Within [`NULL_WINDOW`] of zero: a null pointer, possibly plus an offset
+    Null,
+    /// Inside a mapped region whose permissions don't allow the access
+    Mapped(MemoryRegion),
+    /// Outside every mapped region: a dangling or corrupted pointer
+    Unmapped,
+}
+
+impl FaultRegion
+{
+    /// Classify `address` against the target's memory map.
+    #[must_use]
+    pub fn classify(address: u64, regions: &[MemoryRegion]) -> Self
+    {
+        if address < NULL_WINDOW {
+            return FaultRegion::Null;
+        }
+        regions
+            .iter()
+            .find(|region| region.contains(Address::from(address)))
+            .map_or(FaultRegion::Unmapped, |region| FaultRegion::Mapped(region.clone()))
+    }
+}
+
+/// `null pointer dereference`, `in [heap] (rw-)` or `not in any mapped region`
+impl fmt::Display for FaultRegion
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            FaultRegion::Null => f.write_str("null pointer dereference"),
+            FaultRegion::Mapped(region) => match &region.name {
+                Some(name) => write!(f, "in {name} ({})", region.permissions),
+                None => write!(f, "in the region at {}-{} ({})", region.start, region.end, region.permissions),
+            },
+            FaultRegion::Unmapped => f.write_str("not in any mapped region"),
+        }
+    }
+}
+
+/// A bad access close enough below the faulting thread's stack to be a stack overflow.
+///
+/// The backend turns a fault in a guard page it can see into
+/// [`StopReason::StackOverflow`]; this catches the rest, such as a frame large
+/// enough to jump over the guard page into whatever is mapped below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbableOverflow
+{
+    /// Stack pointer of the faulting thread
+    pub sp: u64,
+    /// Highest address of its stack, where the stack grows down from
+    pub stack_base: u64,
+}
+
+impl ProbableOverflow
+{
+    /// Check a fault at `address` by a thread with stack pointer `sp` whose
+    /// stack occupies `bounds` (as returned by
+    /// [`Debugger::thread_stack_bounds`](crate::Debugger::thread_stack_bounds)).
+    ///
+    /// It is an overflow if the address is below the stack by at most the guard
+    /// window, or SP has already left the stack and the address is near SP.
+    #[must_use]
+    pub fn detect(address: u64, sp: u64, bounds: (Address, Address)) -> Option<Self>
+    {
+        let low = bounds.0.value();
+        let below_stack = address < low && low - address <= GUARD_WINDOW;
+        let near_escaped_sp = sp < low && address.abs_diff(sp) <= GUARD_WINDOW;
+        (below_stack || near_escaped_sp).then_some(Self {
+            sp,
+            stack_base: bounds.1.value(),
+        })
+    }
+}
+
+/// `probable stack overflow (SP = 0x16fdff000, stack base = 0x16fe00000)`
+impl fmt::Display for ProbableOverflow
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(
+            f,
+            "probable stack overflow (SP = 0x{:x}, stack base = 0x{:x})",
+            self.sp, self.stack_base
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::{Address, FrameId, FrameKind, FrameStatus, SourceLocation, SymbolLanguage, SymbolName};
+
+    fn frame(index: usize, function: &str) -> StackFrame
+    {
+        let thread = ThreadId::from(1);
+        let pc = Address::from(0x1_0000_1000 + index as u64 * 0x40);
+        StackFrame {
+            id: FrameId::new(thread, u32::try_from(index).unwrap(), 0, pc, Address::ZERO),
+            thread,
+            index,
+            kind: FrameKind::Physical,
+            pc,
+            sp: Address::ZERO,
+            fp: Address::ZERO,
+            return_address: None,
+            symbol: Some(SymbolName::new(function.to_string(), None, SymbolLanguage::Rust)),
+            location: Some(SourceLocation {
+                file: "src/foo.rs".to_string(),
+                line: Some(42),
+                column: None,
+                dwarf_file: None,
+            }),
+            parameters: Vec::new(),
+            status: FrameStatus::Complete,
+        }
+    }
+
+    fn backtrace(functions: &[&str]) -> Vec<StackFrame>
+    {
+        functions.iter().enumerate().map(|(index, name)| frame(index, name)).collect()
+    }
+
+    #[test]
+    fn abort_and_panic_frames_are_skipped()
+    {
+        let frames = backtrace(&[
+            "__pthread_kill",
+            "pthread_kill",
+            "abort",
+            "std::sys::pal::unix::abort_internal",
+            "std::process::abort",
+            "std::panicking::rust_panic_with_hook",
+            "core::panicking::panic_fmt",
+            "demo::foo",
+            "demo::main",
+        ]);
+        assert_eq!(crash_frame(&frames), Some(7));
+
+        // A fault in the program's own code is the innermost frame
+        assert_eq!(crash_frame(&backtrace(&["demo::foo", "demo::main"])), Some(0));
+        // Libraries the program called are not machinery
+        assert_eq!(crash_frame(&backtrace(&["_platform_memmove", "demo::foo"])), Some(0));
+        assert_eq!(crash_frame(&backtrace(&["_sigtramp", "abort"])), Some(0));
+        assert_eq!(crash_frame(&[]), None);
+    }
+
+    #[test]
+    fn only_crashes_have_a_site()
+    {
+        let frames = backtrace(&["abort", "demo::foo"]);
+        let site = CrashSite::locate(StopReason::Signal(libc::SIGABRT), Some(ThreadId::from(3)), &frames).unwrap();
+        assert_eq!((site.thread, site.frame), (Some(ThreadId::from(3)), 1));
+        assert_eq!(site.summary(&frames), "SIGABRT at 0x100001040 in demo::foo (src/foo.rs:42)");
+
+        let overflow = StopReason::StackOverflow {
+            thread: ThreadId::from(2),
+            sp: 0x7000,
+            guard_page: 0x6000,
+        };
+        let site = CrashSite::locate(overflow, Some(ThreadId::from(1)), &frames).unwrap();
+        assert_eq!(site.thread, Some(ThreadId::from(2)));
+
+        let bad_access = StopReason::BadAccess {
+            address: 0x10,
+            kind: crate::types::BadAccessKind::Read,
+        };
+        let site = CrashSite::locate(bad_access, Some(ThreadId::from(1)), &frames).unwrap();
+        assert_eq!(
+            site.summary(&frames),
+            "EXC_BAD_ACCESS (read) at 0x10 — in function `demo::foo` (foo.rs:42)"
+        );
+
+        assert!(CrashSite::locate(StopReason::Suspended, None, &frames).is_none());
+        assert!(CrashSite::locate(StopReason::Signal(libc::SIGINT), None, &frames).is_none());
+        assert!(CrashSite::locate(StopReason::Signal(libc::SIGSEGV), None, &[]).is_none());
+    }
+
+    #[test]
+    fn fault_addresses_are_classified_by_region()
+    {
+        use crate::types::MemoryRegionId;
+
+        let regions = vec![
+            MemoryRegion::new(
+                MemoryRegionId(0),
+                Address::from(0x1_0000_0000),
+                Address::from(0x1_0000_4000),
+                "r-x".to_string(),
+                Some("/tmp/demo".to_string()),
+            ),
+            MemoryRegion::new(
+                MemoryRegionId(1),
+                Address::from(0x6000_0000),
+                Address::from(0x6000_8000),
+                "rw-".to_string(),
+                None,
+            ),
+        ];
+        assert_eq!(FaultRegion::classify(0, &regions), FaultRegion::Null);
+        assert_eq!(FaultRegion::classify(0x18, &regions).to_string(), "null pointer dereference");
+        assert_eq!(
+            FaultRegion::classify(0x1_0000_0010, &regions).to_string(),
+            "in /tmp/demo (r-x)"
+        );
+        assert_eq!(
+            FaultRegion::classify(0x6000_0010, &regions).to_string(),
+            "in the region at 0x0000000060000000-0x0000000060008000 (rw-)"
+        );
+        assert_eq!(FaultRegion::classify(0xdead_beef_0000, &regions), FaultRegion::Unmapped);
+    }
+
+    #[test]
+    fn faults_just_below_the_stack_are_probable_overflows()
+    {
+        let bounds = (Address::from(0x7000_4000), Address::from(0x7008_4000));
+        let sp = 0x7000_4010;
+
+        let overflow = ProbableOverflow::detect(0x7000_3ff8, sp, bounds).unwrap();
+        assert_eq!(
+            overflow.to_string(),
+            "probable stack overflow (SP = 0x70004010, stack base = 0x70084000)"
+        );
+        // A huge frame moved SP a long way below the stack before touching it
+        assert!(ProbableOverflow::detect(0x6000_0000, 0x6000_0040, bounds).is_some());
+
+        assert!(ProbableOverflow::detect(0x10, sp, bounds).is_none());
+        assert!(ProbableOverflow::detect(0x7000_5000, sp, bounds).is_none());
+        assert!(ProbableOverflow::detect(0x6000_0000, sp, bounds).is_none());
+    }
+}
@@ -24,12 +24,29 @@
 //! - **Safe**: Wrap unsafe system calls in safe abstractions
 //! - **Explicit**: Clear about what they do and when they can fail
 
+use std::collections::BTreeSet;
 use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::breakpoints::{BreakpointId, BreakpointInfo, BreakpointRequest};
+use crate::breakpoints::{BreakpointFile, BreakpointId, BreakpointLocation, BreakpointRequest, RestoreReport};
+use crate::disassembly;
 use crate::error::{DebuggerError, Result};
 use crate::events::DebuggerEventReceiver;
-use crate::types::{Address, Architecture, ProcessId, Registers, StackFrame, StopReason, ThreadId};
+use crate::inspector::TargetInspector;
+use crate::operations::OperationLog;
+use crate::output::OutputSink;
+use crate::search::{self, MemorySearch, MemorySearchScope};
+use crate::signals::{SignalPolicies, SignalPolicy};
+use crate::snapshot::{Snapshot, SnapshotOptions};
+use crate::symbols::ImageInfo;
+use crate::symbols::paths::SourceMap;
+use crate::symbols::tls::ThreadLocalValue;
+use crate::symbols::unwind::{StackTrace, UnwindOptions};
+use crate::types::{
+    Address, AddressExpr, DisassembledInstruction, LaunchOptions, ProcessId, RegisterId, Registers, StackFrame, StopPolicy,
+    StopReason, SymbolName, ThreadId,
+};
 
 /// Main debugger interface
 ///
@@ -44,20 +61,84 @@ use crate::types::{Address, Architecture, ProcessId, Registers, StackFrame, Stop
 /// 3. Inspect/manipulate: `read_registers()`, `write_registers()`, etc.
 /// 4. Detach: `detach()`
 ///
+/// ## Read-only access
+///
+/// Reading memory, registers, threads and breakpoints lives in the
+/// [`TargetInspector`] supertrait, so code that only looks at the target can
+/// take a `&dyn TargetInspector` instead.
+///
 /// ## Thread Safety
 ///
 /// The debugger is **not** thread-safe. Each debugger instance should be
 /// used from a single thread. If you need multi-threaded access, wrap it
 /// in a `Mutex` or use channels to communicate with a debugger thread.
-pub trait Debugger
+pub trait Debugger: TargetInspector
 {
     /// Configure whether stdout/stderr from launched processes should be captured.
     ///
+    /// Capturing also gives the target a stdin pipe, see
+    /// [`Debugger::take_process_stdin`].
+    ///
     /// The default implementation does nothing. Platform-specific implementations
     /// can override this to enable or disable stdio redirection before calling
     /// [`Debugger::launch`].
     fn set_capture_process_output(&mut self, _capture: bool) {}
 
+    /// Remap build-machine source paths in symbolicated locations to local ones.
+    ///
+    /// Also lets `file:line` breakpoints written against the local checkout
+    /// resolve. The default implementation does nothing.
+    fn set_source_map(&mut self, _source_map: SourceMap) {}
+
+    /// The source map applied to symbolicated locations, to extend it with another remap.
+    ///
+    /// The default implementation returns an empty map.
+    fn source_map(&self) -> SourceMap
+    {
+        SourceMap::default()
+    }
+
+    /// Also look for the dSYM bundles of images without DWARF in `path`.
+    ///
+    /// `path` is a `.dSYM` bundle or a directory of them. A bundle is only
+    /// used if its UUID matches the image's. Images already loaded without
+    /// DWARF are loaded again; returns how many.
+    ///
+    /// The default implementation returns `InvalidArgument`.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: `path` isn't a directory, or the debugger has no symbol cache
+    fn add_dsym_search_path(&mut self, _path: PathBuf) -> Result<usize>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "dSYM search paths are not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Parse symbols on a background thread instead of in the call that needs them.
+    ///
+    /// With it on, a stack trace taken while images are still loading has
+    /// addresses but no names; a
+    /// [`SymbolsLoaded`](crate::events::DebuggerEvent::SymbolsLoaded) event
+    /// says when to take it again. Interactive front-ends turn it on so a
+    /// large binary's DWARF doesn't freeze them. The default implementation
+    /// does nothing.
+    ///
+    /// ## Errors
+    ///
+    /// - `Io`: The loader thread couldn't be started
+    fn set_background_symbol_loading(&mut self, _enabled: bool) -> Result<()>
+    {
+        Ok(())
+    }
+
+    /// Images whose symbols are still loading in the background.
+    fn pending_symbol_loads(&self) -> usize
+    {
+        0
+    }
+
     /// Take ownership of the captured stdout stream for the most recently launched process.
     ///
     /// Returns `None` if output capture is disabled or unsupported.
@@ -74,10 +155,41 @@ pub trait Debugger
         None
     }
 
+    /// Take ownership of the write end of the most recently launched process's stdin.
+    ///
+    /// Dropping the file closes the target's stdin, so a target reading until
+    /// EOF can finish. Returns `None` if output capture is disabled or unsupported.
+    fn take_process_stdin(&mut self) -> Option<File>
+    {
+        None
+    }
+
+    /// Pump the captured stdout/stderr of the most recently launched process into sinks.
+    ///
+    /// Each stream is copied on its own [`OutputPump`](crate::output::OutputPump) thread until the target
+    /// closes it, its sink returns `false`, or the pumps are stopped by
+    /// [`Debugger::stop_output_pumps`], [`Debugger::detach`] or turning capture
+    /// off. The streams are taken, so [`Debugger::take_process_stdout`] returns
+    /// `None` afterwards. The default implementation does nothing.
+    ///
+    /// ## Errors
+    ///
+    /// - `Io`: A pipe couldn't be made non-blocking or a pump thread couldn't start
+    fn pump_process_output(&mut self, _stdout: Box<dyn OutputSink>, _stderr: Box<dyn OutputSink>) -> Result<()>
+    {
+        Ok(())
+    }
+
+    /// Stop the pumps started by [`Debugger::pump_process_output`] and join their threads.
+    ///
+    /// Output the target already wrote is still delivered for up to `drain`.
+    /// The default implementation does nothing.
+    fn stop_output_pumps(&mut self, _drain: Duration) {}
+
     /// Take ownership of the debugger event receiver, if supported.
     ///
     /// This is used by interactive frontends (TUI/GUI) or headless event loops to
-    /// react to stop/resume notifications without polling [`Debugger::is_stopped`].
+    /// react to stop/resume notifications without polling [`TargetInspector::is_stopped`].
     /// The default implementation returns `None`, indicating the backend does not
     /// expose an event channel.
     fn take_event_receiver(&mut self) -> Option<DebuggerEventReceiver>
@@ -85,6 +197,18 @@ pub trait Debugger
         None
     }
 
+    /// Record every backend operation in `log`, or stop recording with `None`.
+    ///
+    /// Backends don't implement this themselves: wrap them in a
+    /// [`JournaledDebugger`](crate::operations::JournaledDebugger), which
+    /// does. The default implementation returns `InvalidArgument`.
+    fn set_operation_log(&mut self, _log: Option<OperationLog>) -> Result<()>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Operation logging needs the debugger wrapped in a JournaledDebugger".to_string(),
+        ))
+    }
+
     /// Launch a new process under debugger control
     ///
     /// Spawns a new process from the given executable path and arguments, and
@@ -127,7 +251,38 @@ pub trait Debugger
     /// println!("Launched process with PID: {}", pid.0);
     /// # Ok::<(), ferros_core::error::DebuggerError>(())
     /// ```
-    fn launch(&mut self, program: &str, args: &[&str]) -> Result<ProcessId>;
+    fn launch(&mut self, program: &str, args: &[&str]) -> Result<ProcessId>
+    {
+        self.launch_with_options(program, &LaunchOptions::new(args.iter().copied()))
+    }
+
+    /// Launch a new process under debugger control with its environment and working directory
+    ///
+    /// Like [`launch`](Self::launch), which calls this with ferros's own
+    /// environment and working directory. See [`LaunchOptions`] for how the
+    /// target's environment is built.
+    ///
+    /// ## Errors
+    ///
+    /// As [`launch`](Self::launch), and:
+    ///
+    /// - `InvalidArgument`: A variable or the working directory contains a NUL byte
+    /// - `AttachFailed`: The working directory doesn't exist or can't be entered
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::debugger::create_debugger;
+    /// use ferros_core::types::LaunchOptions;
+    ///
+    /// let mut debugger = create_debugger()?;
+    /// let options = LaunchOptions::new(["app"])
+    ///     .env("APP_CONFIG", "dev.toml")
+    ///     .cwd("/tmp");
+    /// let pid = debugger.launch_with_options("/usr/local/bin/app", &options)?;
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn launch_with_options(&mut self, program: &str, options: &LaunchOptions) -> Result<ProcessId>;
 
     /// Attach to a running process
     ///
@@ -178,33 +333,22 @@ pub trait Debugger
     /// But we provide this method for consistency across platforms.
     fn detach(&mut self) -> Result<()>;
 
-    /// Read registers from the attached process
+    /// Kill the target process and detach from it
     ///
-    /// Reads the current values of all CPU registers from the target process.
-    /// This gives you a snapshot of the process's execution state.
+    /// Used when a session gives up on the target, e.g. after a `--timeout`.
     ///
     /// ## Platform-specific behavior
     ///
-    /// - **macOS**: Calls `thread_get_state()` with `ARM_THREAD_STATE64` or `X86_THREAD_STATE64`
-    /// - **Linux**: Will call `ptrace(PTRACE_GETREGS, pid)`
-    /// - **Windows**: Will call `GetThreadContext()`
-    ///
-    /// ## Errors
-    ///
-    /// - `NotAttached`: Not attached to a process
-    /// - `ReadRegistersFailed`: Failed to read thread state
+    /// - **macOS**: Sends `SIGKILL`, resumes the task so it can exit, then detaches
+    /// - **Linux**: Will call `kill(pid, SIGKILL)` and reap the tracee
     ///
-    /// ## Example
-    ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
-    ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
-    /// let regs = debugger.read_registers()?;
-    /// println!("Program counter: {}", regs.pc);
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    fn read_registers(&self) -> Result<Registers>;
+    /// The default implementation returns `InvalidArgument`.
+    fn kill(&mut self) -> Result<()>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Killing the target is not supported on this debugger".to_string(),
+        ))
+    }
 
     /// Write registers to the attached process
     ///
@@ -227,29 +371,98 @@ pub trait Debugger
     /// Not yet implemented - returns `InvalidArgument` error.
     fn write_registers(&mut self, regs: &Registers) -> Result<()>;
 
-    /// Read registers for a specific thread without changing the active thread.
+    /// Write registers for a specific thread without changing the active thread.
     ///
     /// Default implementation returns `InvalidArgument` if the backend does not support
     /// per-thread register access without switching the active thread.
-    fn read_registers_for(&self, _thread: ThreadId) -> Result<Registers>
+    fn write_registers_for(&mut self, _thread: ThreadId, _regs: &Registers) -> Result<()>
     {
         Err(DebuggerError::InvalidArgument(
             "Per-thread register access not supported for this debugger".to_string(),
         ))
     }
 
-    /// Write registers for a specific thread without changing the active thread.
+    /// Change one register of the active thread.
     ///
-    /// Default implementation returns `InvalidArgument` if the backend does not support
-    /// per-thread register access without switching the active thread.
-    fn write_registers_for(&mut self, _thread: ThreadId, _regs: &Registers) -> Result<()>
+    /// Reads the thread's registers, changes `id` with [`Registers::set`] and
+    /// writes them back. Find `id` from a name such as `x0` or `rip` with
+    /// [`register_by_name`](crate::types::registers::order::register_by_name).
+    ///
+    /// ## Errors
+    ///
+    /// - `NotStopped`: The target is running
+    /// - `InvalidArgument`: The register doesn't exist on the target's architecture
+    /// - Any error from [`read_registers`](TargetInspector::read_registers) or
+    ///   [`write_registers`](Self::write_registers)
+    fn set_register(&mut self, id: RegisterId, value: u64) -> Result<()>
+    {
+        if !self.is_stopped() {
+            return Err(DebuggerError::NotStopped);
+        }
+        let mut registers = self.read_registers()?;
+        registers.set(id, value).ok_or_else(|| {
+            DebuggerError::InvalidArgument(format!("{id:?} is not a register on {}", registers.architecture()))
+        })?;
+        self.write_registers(&registers)
+    }
+
+    /// Read a thread's thread-specific data slot for a raw pthread key.
+    ///
+    /// Returns the slot's value, i.e. what `pthread_getspecific(key)` returns
+    /// on that thread.
+    ///
+    /// ## Platform-specific behavior
+    ///
+    /// - **macOS**: Reads the thread's TSD array, found through
+    ///   `thread_info(THREAD_IDENTIFIER_INFO)`
+    ///
+    /// The default implementation returns `InvalidArgument`.
+    fn read_tls_slot(&self, _thread: ThreadId, _key: u64) -> Result<u64>
     {
         Err(DebuggerError::InvalidArgument(
-            "Per-thread register access not supported for this debugger".to_string(),
+            "Thread-local storage is not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// The `(lowest, end)` addresses of a thread's stack.
+    ///
+    /// Used to tell a stack overflow apart from other bad accesses; see
+    /// [`ProbableOverflow`](crate::crash::ProbableOverflow).
+    ///
+    /// ## Platform-specific behavior
+    ///
+    /// - **macOS**: The readable and writable memory region containing the
+    ///   thread's SP, or the closest one above it if SP has already run past
+    ///   the bottom of the stack
+    ///
+    /// The default implementation returns `InvalidArgument`.
+    fn thread_stack_bounds(&self, _thread: ThreadId) -> Result<(Address, Address)>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Thread stack bounds are not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Read a thread's copy of the thread-local variable `name`.
+    ///
+    /// `name` is matched against the DWARF names of thread-locals, including
+    /// `thread_local!` keys and `#[thread_local]` statics, by `::`-separated
+    /// suffix. Returns one value per matching variable; an empty list means no
+    /// loaded image has one by that name. See [`crate::symbols::tls`].
+    ///
+    /// The default implementation returns `InvalidArgument`.
+    fn read_thread_local(&mut self, _thread: ThreadId, _name: &str) -> Result<Vec<ThreadLocalValue>>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Thread-local storage is not supported on this debugger".to_string(),
         ))
     }
 
     /// Add a breakpoint or watchpoint.
+    ///
+    /// Requests are usually built with [`Breakpoint`](crate::breakpoints::Breakpoint).
+    /// Backends resolve a [`BreakpointRequestKind::Location`](crate::breakpoints::BreakpointRequestKind::Location)
+    /// with [`resolve_location`](Self::resolve_location) and trap on its first address.
     fn add_breakpoint(&mut self, request: BreakpointRequest) -> Result<BreakpointId>
     {
         let _ = request;
@@ -290,18 +503,163 @@ pub trait Debugger
         ))
     }
 
-    /// Query information about a specific breakpoint.
-    fn breakpoint_info(&self, _id: BreakpointId) -> Result<BreakpointInfo>
+    /// Let the next `count` hits of a breakpoint pass without stopping the target.
+    ///
+    /// Replaces what was left of an earlier count; 0 stops on every hit again.
+    /// A breakpoint with a condition only uses up its ignore count on hits
+    /// where the condition holds. The count left is the breakpoint's
+    /// [`ignore_count`](crate::breakpoints::BreakpointInfo::ignore_count).
+    fn set_breakpoint_ignore_count(&mut self, _id: BreakpointId, _count: u64) -> Result<()>
     {
         Err(DebuggerError::InvalidArgument(
             "Breakpoints are not supported on this debugger".to_string(),
         ))
     }
 
-    /// List all known breakpoints.
-    fn breakpoints(&self) -> Vec<BreakpointInfo>
+    /// Save the user breakpoints to a [`BreakpointFile`] at `path`. Returns how many were saved.
+    ///
+    /// Code breakpoints are saved with their function and offset and their
+    /// source line when known, so [`load_breakpoints`](Self::load_breakpoints)
+    /// can find them again after the addresses move.
+    fn save_breakpoints(&self, path: &Path) -> Result<usize>
+    {
+        let file = BreakpointFile::capture(self);
+        file.save(path)?;
+        Ok(file.breakpoints.len())
+    }
+
+    /// Install the breakpoints saved at `path`, re-resolving each one.
+    ///
+    /// Breakpoints that don't resolve in this run are returned in the
+    /// report's `unresolved` list rather than dropped. A missing file
+    /// restores nothing.
+    fn load_breakpoints(&mut self, path: &Path) -> Result<RestoreReport>
+    {
+        Ok(BreakpointFile::load(path)?.restore(self))
+    }
+
+    /// Resolve a breakpoint location to runtime addresses, lowest first.
+    ///
+    /// An empty list means the location is not known yet, for example because
+    /// the image that contains it hasn't been loaded; callers can keep the
+    /// breakpoint pending and retry after the next stop.
+    ///
+    /// The default implementation only understands raw addresses.
+    fn resolve_location(&mut self, location: &BreakpointLocation) -> Result<Vec<Address>>
+    {
+        match location {
+            BreakpointLocation::Address(address) => Ok(vec![*address]),
+            _ => Err(DebuggerError::InvalidArgument(
+                "Symbolic breakpoint locations are not supported on this debugger".to_string(),
+            )),
+        }
+    }
+
+    /// Lines of `file` that compiled to code in the loaded images, i.e. where a line breakpoint can land.
+    ///
+    /// `file` matches as in a [`BreakpointLocation::Line`]. Lets the Source
+    /// view tell code from comments and blank lines.
+    ///
+    /// The default implementation returns `InvalidArgument`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the line tables can't be read.
+    fn code_lines(&mut self, _file: &str) -> Result<BTreeSet<u32>>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Line tables are not available on this debugger".to_string(),
+        ))
+    }
+
+    /// Functions called `name`, with the name each one resolved to, lowest address first.
+    ///
+    /// Lets front-ends tell apart the functions a name like `run` matches
+    /// before breaking on it; see [`SymbolCache::find_function`](crate::symbols::SymbolCache::find_function)
+    /// for how names match. An empty list means no loaded image has one.
+    ///
+    /// The default implementation returns `InvalidArgument`.
+    fn find_function(&mut self, _name: &str) -> Result<Vec<(Address, SymbolName)>>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Function lookup is not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Binary images the symbol cache has loaded, lowest load address first.
+    ///
+    /// Images are loaded lazily, on the first stop that needs symbols, so a
+    /// dylib only shows up here once something has looked at its code. See
+    /// [`SymbolCache::loaded_images`](crate::symbols::SymbolCache::loaded_images).
+    ///
+    /// The default implementation returns `InvalidArgument`.
+    fn modules(&self) -> Result<Vec<ImageInfo>>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Image lists are not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Save the stopped target to `path` for post-mortem inspection.
+    ///
+    /// Saves every thread's registers, the region table and the contents of
+    /// the writable regions, up to [`DEFAULT_SNAPSHOT_MAX_BYTES`](crate::snapshot::DEFAULT_SNAPSHOT_MAX_BYTES).
+    /// Open the file again with [`SnapshotDebugger`](crate::snapshot::SnapshotDebugger).
+    ///
+    /// The default implementation calls
+    /// [`write_snapshot_with_options`](Self::write_snapshot_with_options) with
+    /// the default [`SnapshotOptions`].
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: There is no target
+    /// - `NotStopped`: The target is running
+    /// - `Io`: The file can't be written
+    fn write_snapshot(&mut self, path: &Path) -> Result<()>
+    {
+        self.write_snapshot_with_options(path, SnapshotOptions::default())
+    }
+
+    /// Save the stopped target to `path`, choosing which memory is saved.
+    ///
+    /// See [`snapshot`](crate::snapshot) for the file format. The default
+    /// implementation captures through [`TargetInspector`] reads with
+    /// [`Snapshot::capture`].
+    ///
+    /// ## Errors
+    ///
+    /// As [`write_snapshot`](Self::write_snapshot).
+    fn write_snapshot_with_options(&mut self, path: &Path, options: SnapshotOptions) -> Result<()>
     {
-        Vec::new()
+        Snapshot::capture(&*self, options)?.save(path)
+    }
+
+    /// Resolve an address typed by the user (see [`parse_address`](crate::types::parse_address)).
+    ///
+    /// A symbol resolves through [`resolve_location`](Self::resolve_location)
+    /// to its lowest entry address, then the offset is applied.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: The symbol is unknown or the offset leaves the address space
+    /// - Anything [`resolve_location`](Self::resolve_location) returns
+    fn resolve_address(&mut self, expr: &AddressExpr) -> Result<Address>
+    {
+        let (name, offset) = match expr {
+            AddressExpr::Absolute(address) => return Ok(*address),
+            AddressExpr::Symbol { name, offset } => (name, *offset),
+        };
+        let base = self
+            .resolve_location(&BreakpointLocation::Function(name.clone()))?
+            .first()
+            .copied()
+            .ok_or_else(|| DebuggerError::InvalidArgument(format!("No function named {name}")))?;
+        let address = if offset < 0 {
+            base.checked_sub(offset.unsigned_abs())
+        } else {
+            base.checked_add(offset.unsigned_abs())
+        };
+        address.ok_or_else(|| DebuggerError::InvalidArgument(format!("{expr} is outside the address space")))
     }
 
     /// Capture a stack trace for the active thread.
@@ -317,35 +675,48 @@ pub trait Debugger
         ))
     }
 
-    /// Read memory from the target process
-    ///
-    /// Reads `len` bytes starting at the given address from the attached process.
-    /// Returns a vector containing the read bytes.
+    /// Capture a stack trace for `thread`, which needn't be the active one.
     ///
-    /// ## Platform-specific behavior
+    /// Backends unwind from that thread's registers
+    /// ([`read_registers_for`](TargetInspector::read_registers_for)), so the
+    /// active thread stays as it was. Used to show every thread's stack at once.
     ///
-    /// - **macOS**: Uses `vm_read()` to read memory from the Mach task
-    /// - **Linux**: Uses `ptrace(PTRACE_PEEKDATA)` in a loop to read word-aligned data
-    /// - **Windows**: Uses `ReadProcessMemory()`
+    /// The default implementation makes `thread` active, calls
+    /// [`stack_trace`](Self::stack_trace) and makes the previous thread active again.
     ///
     /// ## Errors
     ///
-    /// - `NotAttached`: Not attached to a process
-    /// - `InvalidArgument`: Invalid memory address or length
-    /// - `Io`: Failed to read memory (e.g., invalid address, permission denied)
+    /// - `ThreadNotFound`: The thread isn't in the current thread list
+    /// - `InvalidArgument`: The backend can't unwind
+    /// - `ReadRegistersFailed`: The thread's registers couldn't be read
+    fn stack_trace_for(&mut self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        let active = self.active_thread();
+        let frames = self.set_active_thread(thread).and_then(|()| self.stack_trace(max_frames));
+        if let Some(active) = active
+            && active != thread
+        {
+            let _ = self.set_active_thread(active);
+        }
+        frames
+    }
+
+    /// Capture a stack trace for the active thread with explicit unwind options.
     ///
-    /// ## Example
+    /// When `options.collect_diagnostics` is set, implementations record an
+    /// [`UnwindDiagnostic`](crate::symbols::unwind::UnwindDiagnostic) per physical
+    /// frame explaining which strategy recovered its caller and why the others failed.
     ///
-    /// ```rust,no_run
-    /// use ferros_core::{Address, Debugger};
-    ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
-    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// let data = debugger.read_memory(Address::from(0x1000), 16)?;
-    /// println!("Read {} bytes: {:?}", data.len(), data);
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>;
+    /// The default implementation forwards to [`stack_trace`](Self::stack_trace) and
+    /// returns no diagnostics.
+    fn stack_trace_with_options(&mut self, options: UnwindOptions) -> Result<StackTrace>
+    {
+        let frames = self.stack_trace(options.max_frames)?;
+        Ok(StackTrace {
+            frames,
+            ..StackTrace::default()
+        })
+    }
 
     /// Write memory to the target process
     ///
@@ -358,6 +729,12 @@ pub trait Debugger
     /// Only write to writable memory regions (e.g., stack, heap).
     /// Writing to code segments may corrupt the program.
     ///
+    /// ## Software breakpoints
+    ///
+    /// A write over a software breakpoint's trap becomes the code the
+    /// breakpoint restores when it is removed or disabled, and an enabled
+    /// breakpoint's trap is written back so it keeps firing.
+    ///
     /// ## Platform-specific behavior
     ///
     /// - **macOS**: Uses `vm_write()` to write memory to the Mach task
@@ -384,155 +761,88 @@ pub trait Debugger
     /// ```
     fn write_memory(&mut self, addr: Address, data: &[u8]) -> Result<usize>;
 
-    /// Get memory regions for the attached process
-    ///
-    /// Returns a list of all memory regions (segments) in the target process.
-    /// This includes code segments, data segments, stack, heap, and mapped files.
-    ///
-    /// Each region contains information about its address range, permissions
-    /// (read/write/execute), and optionally a name or description.
-    ///
-    /// ## Platform-specific behavior
+    /// Write `data` at `addr` once the regions it lands in are known to be writable.
     ///
-    /// - **macOS**: Uses `mach_vm_region()` to enumerate memory regions
-    /// - **Linux**: Parses `/proc/[pid]/maps` file
-    /// - **Windows**: Uses `VirtualQueryEx()` to enumerate memory regions
+    /// [`write_memory`](Self::write_memory) into code or a read-only mapping
+    /// fails with whatever the kernel reports; this refuses it first with the
+    /// region's permission string (see [`check_writable`](crate::types::check_writable)).
+    /// When the regions can't be listed the write is attempted anyway.
     ///
     /// ## Errors
     ///
-    /// - `NotAttached`: Not attached to a process
-    /// - `Io`: Failed to read memory map information
-    ///
-    /// ## Example
-    ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
-    ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
-    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// let regions = debugger.get_memory_regions()?;
-    /// for region in regions {
-    ///     println!(
-    ///         "{}-{} {} {:?}",
-    ///         region.start, region.end, region.permissions, region.name
-    ///     );
-    /// }
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    fn get_memory_regions(&self) -> Result<Vec<crate::types::MemoryRegion>>;
+    /// - `InvalidArgument`: Part of the range isn't mapped or isn't writable
+    /// - Any error from [`write_memory`](Self::write_memory)
+    fn write_memory_checked(&mut self, addr: Address, data: &[u8]) -> Result<usize>
+    {
+        if let Ok(regions) = self.get_memory_regions()
+            && !regions.is_empty()
+        {
+            crate::types::check_writable(&regions, addr, data.len())?;
+        }
+        self.write_memory(addr, data)
+    }
 
-    /// Get the CPU architecture of the debug target
-    ///
-    /// Returns the architecture of the process being debugged. This is typically
-    /// determined when attaching to the process, though some platforms may detect
-    /// it earlier.
-    ///
-    /// ## Platform-Specific Behavior
-    ///
-    /// - **macOS**: Uses the architecture of the debugger binary as a hint, but
-    ///   the actual target process architecture may differ (e.g., debugging x86-64
-    ///   process from ARM64 debugger)
-    /// - **Linux**: Detected from `/proc/[pid]/exe` or ELF headers
-    /// - **Windows**: Detected from PE headers
-    ///
-    /// ## Example
+    /// Find every occurrence of `pattern` in the target's memory
     ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
-    /// use ferros_core::types::Architecture;
+    /// Reads the regions `scope` includes in chunks, as the program's own code
+    /// sees them (see [`read_memory_logical`](TargetInspector::read_memory_logical)),
+    /// so a breakpoint's trap doesn't hide a match. Hits come back lowest
+    /// address first, at most [`MemorySearchScope::max_hits`] of them. See
+    /// [`search`](crate::search).
     ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
-    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// match debugger.architecture() {
-    ///     Architecture::Arm64 => println!("Debugging ARM64 process"),
-    ///     Architecture::X86_64 => println!("Debugging x86-64 process"),
-    ///     Architecture::Unknown(name) => println!("Unknown architecture: {}", name),
-    /// }
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    fn architecture(&self) -> Architecture;
-
-    /// Check whether the debugger is currently attached to a process
-    ///
-    /// Returns `true` if `attach()` has been called successfully and the debugger
-    /// is still connected to the target process. Returns `false` if:
-    /// - The debugger was never attached
-    /// - The debugger was detached via `detach()`
-    /// - The target process has exited
+    /// ## Platform-specific behavior
     ///
-    /// ## Example
+    /// - **macOS**: Reads through the page cache, so the pages the views show
+    ///   are read once
     ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
+    /// ## Errors
     ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
-    /// if !debugger.is_attached() {
-    ///     debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// }
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    fn is_attached(&self) -> bool;
+    /// - `InvalidArgument`: `pattern` is empty
+    /// - Anything [`get_memory_regions`](TargetInspector::get_memory_regions) returns
+    fn search_memory(&self, pattern: &[u8], scope: MemorySearchScope) -> Result<MemorySearch>
+    {
+        let regions = self.get_memory_regions()?;
+        search::search(pattern, &regions, &scope, |address, len| {
+            self.read_memory_logical(address, len)
+        })
+    }
 
-    /// Check whether the debuggee is currently stopped/suspended
-    ///
-    /// Returns `true` if the target process is currently stopped (suspended,
-    /// hit a breakpoint, received a signal, etc.). Returns `false` if the process
-    /// is running.
-    ///
-    /// ## Relationship to `stop_reason()`
-    ///
-    /// - `is_stopped() == true` → `stop_reason()` is not `StopReason::Running`
-    /// - `is_stopped() == false` → `stop_reason()` is `StopReason::Running`
-    ///
-    /// ## Example
+    /// Decode `count` instructions starting at `start`
     ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
+    /// Reads the code with [`read_memory_logical`](TargetInspector::read_memory_logical),
+    /// so breakpoints show as the instructions they replaced, and decodes it
+    /// for the target's [`architecture`](TargetInspector::architecture). The
+    /// instruction at the active thread's PC is marked as current. Fewer than
+    /// `count` come back when the readable code ends first; `start` has to be
+    /// on an instruction boundary, since x86-64 code can't be decoded from the
+    /// middle of an instruction.
     ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
-    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// if debugger.is_stopped() {
-    ///     println!("Process is stopped, can inspect registers/memory");
-    /// } else {
-    ///     println!("Process is running");
-    /// }
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    fn is_stopped(&self) -> bool;
-
-    /// Get the most recent stop reason
-    ///
-    /// Returns the reason why the process is currently stopped (if at all).
-    /// This can be used to determine what action to take next:
+    /// ## Errors
     ///
-    /// - `StopReason::Running`: Process is running (not stopped)
-    /// - `StopReason::Suspended`: Process was explicitly suspended
-    /// - `StopReason::Signal(n)`: Process received a signal
-    /// - `StopReason::Breakpoint(addr)`: Process hit a breakpoint
-    /// - `StopReason::Exited(code)`: Process has exited
-    /// - `StopReason::Unknown`: Unknown reason
+    /// - `NotAttached`: Not attached to a process
+    /// - `InvalidArgument`: The architecture can't be disassembled
+    /// - Anything [`read_memory_logical`](TargetInspector::read_memory_logical) returns for `start`
     ///
     /// ## Example
     ///
     /// ```rust,no_run
-    /// use ferros_core::Debugger;
-    /// use ferros_core::types::StopReason;
+    /// use ferros_core::{Debugger, TargetInspector};
     ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
     /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// match debugger.stop_reason() {
-    ///     StopReason::Breakpoint(addr) => {
-    ///         println!("Hit breakpoint at 0x{:x}", addr);
-    ///         // Inspect registers, memory, etc.
-    ///     }
-    ///     StopReason::Signal(sig) => {
-    ///         println!("Stopped by signal: {}", sig);
-    ///     }
-    ///     _ => {}
+    /// let pc = debugger.read_registers()?.pc;
+    /// for insn in debugger.disassemble(pc, 8)? {
+    ///     println!("{} {}", insn.address, insn.text());
     /// }
     /// # Ok::<(), ferros_core::error::DebuggerError>(())
     /// ```
-    fn stop_reason(&self) -> StopReason;
+    fn disassemble(&mut self, start: Address, count: usize) -> Result<Vec<DisassembledInstruction>>
+    {
+        let architecture = self.architecture();
+        let code = disassembly::read_code(architecture, start, count, |addr, len| self.read_memory_logical(addr, len))?;
+        let pc = self.read_registers().ok().map(|registers| registers.pc);
+        disassembly::disassemble(architecture, start, &code, count, pc)
+    }
 
     /// Suspend execution of the target process
     ///
@@ -555,7 +865,7 @@ pub trait Debugger
     /// ## Example
     ///
     /// ```rust,no_run
-    /// use ferros_core::Debugger;
+    /// use ferros_core::{Debugger, TargetInspector};
     ///
     /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
     /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
@@ -571,6 +881,10 @@ pub trait Debugger
     /// Resumes execution of the target process. The process will continue running
     /// from where it was stopped (unless registers were modified).
     ///
+    /// A thread stopped by a watchpoint is first stepped over the access that
+    /// tripped it; if that step stops somewhere else, such as on a breakpoint,
+    /// the target stays stopped there.
+    ///
     /// ## Platform-Specific Behavior
     ///
     /// - **macOS**: Calls `task_resume()` to resume the Mach task
@@ -598,136 +912,314 @@ pub trait Debugger
     /// ```
     fn resume(&mut self) -> Result<()>;
 
-    /// List all available threads in the target process
+    /// Select the active thread that subsequent operations should target
     ///
-    /// Returns a vector of `ThreadId` values representing all threads currently
-    /// in the target process. This list is cached and may become stale if threads
-    /// are created or destroyed. Call `refresh_threads()` to update it.
+    /// Sets the active thread for register operations. After calling this, `read_registers()`
+    /// and `write_registers()` will operate on the specified thread.
     ///
-    /// ## Platform-Specific Behavior
+    /// ## Parameters
     ///
-    /// - **macOS**: Returns thread ports from `task_threads()`
-    ///   - See: [task_threads documentation](https://developer.apple.com/documentation/kernel/1537751-task_threads/)
-    /// - **Linux**: Will parse `/proc/[pid]/task/` directory
-    /// - **Windows**: Will use `CreateToolhelp32Snapshot()` with `TH32CS_SNAPTHREAD`
+    /// - `thread`: The thread ID to make active. Must be a valid thread from `threads()`.
     ///
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
-    /// - `AttachFailed`: Failed to enumerate threads
+    /// - `ThreadNotFound`: The thread ID is not valid (not in the thread list)
     ///
     /// ## Example
     ///
     /// ```rust,no_run
-    /// use ferros_core::Debugger;
+    /// use ferros_core::{Debugger, TargetInspector};
     ///
     /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
     /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
     /// let threads = debugger.threads()?;
-    /// println!("Process has {} threads", threads.len());
-    /// for thread in threads {
-    ///     println!("Thread: {}", thread.raw());
+    /// if let Some(thread) = threads.get(1) {
+    ///     debugger.set_active_thread(*thread)?;
+    ///     // Now read_registers() will read from thread 1
+    ///     let regs = debugger.read_registers()?;
     /// }
     /// # Ok::<(), ferros_core::error::DebuggerError>(())
     /// ```
-    fn threads(&self) -> Result<Vec<ThreadId>>;
+    fn set_active_thread(&mut self, thread: ThreadId) -> Result<()>;
 
-    /// Get the currently selected active thread (if any)
+    /// Refresh the thread list from the operating system
+    ///
+    /// Updates the cached thread list by querying the operating system for the
+    /// current set of threads in the target process. This should be called if:
+    /// - Threads may have been created or destroyed since the last call
+    /// - You need up-to-date thread information
+    ///
+    /// ## Note
     ///
-    /// Returns `Some(thread_id)` if an active thread has been selected via
-    /// `set_active_thread()`, or `None` if no thread is selected. The active
-    /// thread is used for register operations (`read_registers()`, `write_registers()`).
+    /// The active thread is preserved if it still exists. If the active thread
+    /// has exited, the first thread in the new list becomes the active thread.
     ///
-    /// ## Default Behavior
+    /// ## Platform-Specific Behavior
     ///
-    /// When attaching to a process, the first thread (typically the main thread)
-    /// is automatically selected as the active thread.
+    /// - **macOS**: Calls `task_threads()` to refresh the thread list
+    ///   - See: [task_threads documentation](https://developer.apple.com/documentation/kernel/1537751-task_threads/)
+    /// - **Linux**: Will re-read `/proc/[pid]/task/` directory
+    /// - **Windows**: Will use `CreateToolhelp32Snapshot()` again
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `AttachFailed`: Failed to enumerate threads
     ///
     /// ## Example
     ///
     /// ```rust,no_run
-    /// use ferros_core::Debugger;
+    /// use ferros_core::{Debugger, TargetInspector};
     ///
     /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
     /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// if let Some(thread) = debugger.active_thread() {
-    ///     println!("Active thread: {}", thread.raw());
-    /// } else {
-    ///     println!("No active thread selected");
-    /// }
+    /// // Thread list may be stale
+    /// debugger.refresh_threads()?; // Update thread list
+    /// let threads = debugger.threads()?; // Now up-to-date
+    ///
     /// # Ok::<(), ferros_core::error::DebuggerError>(())
     /// ```
-    fn active_thread(&self) -> Option<ThreadId>;
+    fn refresh_threads(&mut self) -> Result<()>;
 
-    /// Select the active thread that subsequent operations should target
+    /// Suspend a single thread, leaving the rest of the task running
     ///
-    /// Sets the active thread for register operations. After calling this, `read_registers()`
-    /// and `write_registers()` will operate on the specified thread.
+    /// Unlike `suspend()`, which stops every thread in the task, this freezes only
+    /// `thread`. Suspensions nest: a thread suspended twice needs two `resume_thread()`
+    /// calls before it runs again.
     ///
-    /// ## Parameters
+    /// ## Platform-Specific Behavior
     ///
-    /// - `thread`: The thread ID to make active. Must be a valid thread from `threads()`.
+    /// - **macOS**: Calls `thread_suspend()` on the thread port
+    ///   - See: [thread_suspend documentation](https://developer.apple.com/documentation/kernel/1418833-thread_suspend/)
+    /// - **Linux**: Will send `SIGSTOP` to the TID via `tgkill()`
     ///
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
-    /// - `InvalidArgument`: The thread ID is not valid (not in the thread list)
+    /// - `ThreadNotFound`: The thread is not part of the process
+    /// - `InvalidArgument`: The backend doesn't support per-thread control (the
+    ///   default implementation)
+    /// - `SuspendFailed`: The platform call failed
     ///
     /// ## Example
     ///
     /// ```rust,no_run
-    /// use ferros_core::Debugger;
+    /// use ferros_core::{Debugger, TargetInspector};
     ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
     /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
     /// let threads = debugger.threads()?;
-    /// if let Some(thread) = threads.get(1) {
-    ///     debugger.set_active_thread(*thread)?;
-    ///     // Now read_registers() will read from thread 1
-    ///     let regs = debugger.read_registers()?;
+    /// if let Some(thread) = threads.first() {
+    ///     debugger.suspend_thread(*thread)?;
     /// }
     /// # Ok::<(), ferros_core::error::DebuggerError>(())
     /// ```
-    fn set_active_thread(&mut self, thread: ThreadId) -> Result<()>;
+    fn suspend_thread(&mut self, _thread: ThreadId) -> Result<()>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Per-thread suspend is not supported on this debugger".to_string(),
+        ))
+    }
 
-    /// Refresh the thread list from the operating system
+    /// Resume a single thread previously frozen with `suspend_thread()`
     ///
-    /// Updates the cached thread list by querying the operating system for the
-    /// current set of threads in the target process. This should be called if:
-    /// - Threads may have been created or destroyed since the last call
-    /// - You need up-to-date thread information
+    /// ## Platform-Specific Behavior
     ///
-    /// ## Note
+    /// - **macOS**: Calls `thread_resume()` on the thread port
+    ///   - See: [thread_resume documentation](https://developer.apple.com/documentation/kernel/1418926-thread_resume/)
+    /// - **Linux**: Will send `SIGCONT` to the TID via `tgkill()`
     ///
-    /// The active thread is preserved if it still exists. If the active thread
-    /// has exited, the first thread in the new list becomes the active thread.
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `ThreadNotFound`: The thread is not part of the process
+    /// - `InvalidArgument`: The backend doesn't support per-thread control (the
+    ///   default implementation)
+    /// - `ResumeFailed`: The platform call failed
+    fn resume_thread(&mut self, _thread: ThreadId) -> Result<()>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Per-thread resume is not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Choose what happens when the target gets `signal`
+    ///
+    /// [`SignalPolicy::Stop`] reports the signal as a
+    /// [`StopReason::Signal`] stop, as happens for every signal until a policy
+    /// is set. [`SignalPolicy::PassThrough`] delivers it to the target and
+    /// [`SignalPolicy::Ignore`] drops it; either way the target keeps running
+    /// and no stop is reported. Policies last until the debugger is dropped.
     ///
     /// ## Platform-Specific Behavior
     ///
-    /// - **macOS**: Calls `task_threads()` to refresh the thread list
-    ///   - See: [task_threads documentation](https://developer.apple.com/documentation/kernel/1537751-task_threads/)
-    /// - **Linux**: Will re-read `/proc/[pid]/task/` directory
-    /// - **Windows**: Will use `CreateToolhelp32Snapshot()` again
+    /// - **macOS**: Consulted by the exception handler for the signals Mach
+    ///   exceptions stand for (`EXC_BAD_ACCESS` is `SIGSEGV`, ...) and for
+    ///   `EXC_SOFT_SIGNAL`. Passing a signal fails the exception reply, so the
+    ///   kernel delivers it as a Unix signal
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: `signal` is not a signal number, or the backend
+    ///   doesn't support signal policies (the default implementation)
+    fn set_signal_policy(&mut self, _signal: i32, _policy: SignalPolicy) -> Result<()>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Signal policies are not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Signals whose policy isn't [`SignalPolicy::Stop`]
+    fn signal_policies(&self) -> SignalPolicies
+    {
+        SignalPolicies::default()
+    }
+
+    /// Choose which threads stop when one of them hits a breakpoint
+    ///
+    /// Under [`StopPolicy::HitThreadOnly`], the default, only the thread that
+    /// stopped waits for `resume()` and the rest of the target keeps running.
+    /// Under [`StopPolicy::AllThreads`] every other thread is suspended before
+    /// the stop is reported and `resume()` lets them all go again. Those
+    /// suspensions are the debugger's own: they don't show in
+    /// [`thread_suspend_count`](TargetInspector::thread_suspend_count) and
+    /// don't disturb threads frozen with `suspend_thread()`. The policy lasts
+    /// until the debugger is dropped.
+    ///
+    /// ## Platform-Specific Behavior
+    ///
+    /// - **macOS**: The exception handler calls `thread_suspend()` on every
+    ///   other thread before publishing the stop; `resume()` refreshes the
+    ///   thread list and calls `thread_resume()` on those still alive before
+    ///   replying to the exception
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: The backend can't hold the other threads (the
+    ///   default implementation)
+    fn set_stop_policy(&mut self, _policy: StopPolicy) -> Result<()>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Stop policies are not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Which threads stop when one of them hits a breakpoint
+    fn stop_policy(&self) -> StopPolicy
+    {
+        StopPolicy::default()
+    }
+
+    /// Execute exactly one instruction on the active thread and stop again
+    ///
+    /// Blocks until the step completes and returns the new stop reason, normally
+    /// [`StopReason::Step`] with the program counter after the instruction. If
+    /// the instruction faults, the fault is returned instead. The other threads
+    /// are kept suspended for the duration of the step so they can't race ahead.
+    ///
+    /// Stepping from an address with an enabled software breakpoint executes the
+    /// original instruction: its saved bytes are written back for the step and
+    /// the trap is re-patched afterwards.
+    ///
+    /// ## Platform-Specific Behavior
+    ///
+    /// - **macOS (ARM64)**: Sets the SS bit in `MDSCR_EL1` through the thread's
+    ///   debug state and waits for the resulting `EXC_BREAKPOINT`
+    /// - **macOS (x86-64)**: Sets the trap flag (TF) in RFLAGS
+    /// - **Linux**: Will use `ptrace(PTRACE_SINGLESTEP)`
+    ///
+    /// An instruction that doesn't complete in time (a blocking system call)
+    /// leaves the task suspended and returns [`StopReason::Suspended`].
     ///
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
-    /// - `AttachFailed`: Failed to enumerate threads
+    /// - `NotStopped`: The target is running
+    /// - `InvalidArgument`: The backend can't single-step (the default implementation)
+    /// - `ResumeFailed`: The thread couldn't be set running for the step
     ///
     /// ## Example
     ///
     /// ```rust,no_run
     /// use ferros_core::Debugger;
+    /// use ferros_core::types::StopReason;
     ///
-    /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
     /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// // Thread list may be stale
-    /// debugger.refresh_threads()?; // Update thread list
-    /// let threads = debugger.threads()?; // Now up-to-date
-    ///
+    /// debugger.suspend()?;
+    /// if let StopReason::Step(pc) = debugger.step_instruction()? {
+    ///     println!("Next instruction at 0x{pc:x}");
+    /// }
     /// # Ok::<(), ferros_core::error::DebuggerError>(())
     /// ```
-    fn refresh_threads(&mut self) -> Result<()>;
+    fn step_instruction(&mut self) -> Result<StopReason>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Single-stepping is not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Execute one instruction on the active thread, running any call it makes to completion
+    ///
+    /// If the instruction at the PC is a call (see
+    /// [`instructions::decode_call`](crate::instructions::decode_call)), the call
+    /// is stepped into and a temporary breakpoint set at its return address
+    /// runs the callee until it returns; the result is then
+    /// [`StopReason::Step`] at the return address. Any other instruction is
+    /// a plain [`step_instruction`](Self::step_instruction).
+    ///
+    /// The temporary breakpoint never shows up in
+    /// [`breakpoints()`](TargetInspector::breakpoints): it is removed before
+    /// this returns, including when the target stops for another reason
+    /// first (a signal, another breakpoint, or another thread reaching the
+    /// return address), whose stop reason is returned instead.
+    ///
+    /// A callee that doesn't return in time leaves the task suspended inside
+    /// it and returns [`StopReason::Suspended`].
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `NotStopped`: The target is running
+    /// - `InvalidArgument`: The backend can't step over calls (the default implementation)
+    /// - Any error from [`step_instruction`](Self::step_instruction) or from
+    ///   installing the temporary breakpoint
+    fn step_over(&mut self) -> Result<StopReason>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Stepping over calls is not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// Run until the active thread's current function returns to its caller
+    ///
+    /// The caller's PC comes from unwinding the active thread (see
+    /// [`return_frame`](crate::symbols::unwind::return_frame)); a temporary
+    /// breakpoint there runs the target until the function returns, and the
+    /// result is [`StopReason::Step`] at that address. Stepping out of an
+    /// inlined function runs to the caller of the function it was inlined into.
+    ///
+    /// The stop is published as a [`DebuggerEvent::TargetStopped`](crate::events::DebuggerEvent::TargetStopped)
+    /// like any other, so frontends listening for events update on their own.
+    /// As with [`step_over`](Self::step_over), the temporary breakpoint is
+    /// removed before this returns, whichever way the target stopped, and a
+    /// function that doesn't return in time leaves the task suspended and
+    /// returns [`StopReason::Suspended`].
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `NotStopped`: The target is running
+    /// - `UnreliableReturnAddress`: The unwinder found no caller, or only a
+    ///   heuristic one whose return address may be garbage
+    /// - `InvalidArgument`: The backend can't step out (the default implementation)
+    fn step_out(&mut self) -> Result<StopReason>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Stepping out is not supported on this debugger".to_string(),
+        ))
+    }
 
     // Future methods (commented out until implemented):
     //
@@ -738,10 +1230,6 @@ pub trait Debugger
     // /// Continue execution of the target process
     // /// Resumes execution after being stopped (by breakpoint, signal, etc.)
     // fn continue_execution(&mut self) -> Result<()>;
-    //
-    // /// Single-step execution (execute one instruction)
-    // /// Uses hardware single-step support: TF flag (x86) or SS bit (ARM)
-    // fn single_step(&mut self) -> Result<()>;
 }
 
 /// Factory function to create a platform-specific debugger
@@ -0,0 +1,231 @@
+//! Instruction decoding for the disassembly view.
+//!
+//! [`Debugger::disassemble`](crate::Debugger::disassemble) reads target memory
+//! and hands the bytes to [`disassemble`], which decodes them with Capstone for
+//! the target's architecture. Bytes that don't decode are reported as `(bad)`
+//! and skipped (4 bytes on ARM64, 1 on x86-64) so one bad word doesn't end
+//! the listing.
+//!
+//! ```rust
+//! use ferros_core::disassembly::disassemble;
+//! use ferros_core::types::{Address, Architecture};
+//!
+//! // nop; ret
+//! let code = [0x1f, 0x20, 0x03, 0xd5, 0xc0, 0x03, 0x5f, 0xd6];
+//! let listing = disassemble(Architecture::Arm64, Address::from(0x1000), &code, 2, None)?;
+//! assert_eq!(listing[1].mnemonic, "ret");
+//! # Ok::<(), ferros_core::error::DebuggerError>(())
+//! ```
+
+use capstone::Capstone;
+use capstone::arch::{self, BuildsCapstone, BuildsCapstoneSyntax};
+
+use crate::error::{DebuggerError, Result};
+use crate::instructions::MAX_INSTRUCTION_LEN;
+use crate::types::{Address, Architecture, DisassembledInstruction};
+
+/// Mnemonic given to bytes that don't decode.
+pub const BAD_MNEMONIC: &str = "(bad)";
+
+/// Page size used to keep a short read inside one mapping.
+const CODE_PAGE_SIZE: u64 = 4096;
+
+/// Most bytes `count` instructions can take on `architecture`.
+///
+/// How much memory to read so [`disassemble`] can decode `count` instructions.
+#[must_use]
+pub fn max_code_len(architecture: Architecture, count: usize) -> usize
+{
+    match architecture {
+        Architecture::X86_64 => count.saturating_mul(MAX_INSTRUCTION_LEN),
+        Architecture::Arm64 | Architecture::Unknown(_) => count.saturating_mul(4),
+    }
+}
+
+/// Read the code for `count` instructions at `start` with `read`.
+///
+/// Reading [`max_code_len`] bytes can run past the end of the mapping the
+/// code is in, so when that fails this reads up to the end of `start`'s page
+/// instead.
+///
+/// ## Errors
+///
+/// Whatever `read` returns for the shorter read.
+pub fn read_code(
+    architecture: Architecture,
+    start: Address,
+    count: usize,
+    read: impl Fn(Address, usize) -> Result<Vec<u8>>,
+) -> Result<Vec<u8>>
+{
+    let len = max_code_len(architecture, count);
+    read(start, len).or_else(|_| {
+        let to_page_end = CODE_PAGE_SIZE - start.value() % CODE_PAGE_SIZE;
+        read(start, len.min(to_page_end as usize))
+    })
+}
+
+/// Decode up to `count` instructions from `code`, which was read at `start`.
+///
+/// The instruction that starts at `pc` is marked as the current one. Stops
+/// early when `code` runs out, so an instruction cut off by the end of
+/// `code` isn't returned, and neither are bytes near the end that don't
+/// decode.
+///
+/// ## Errors
+///
+/// - `InvalidArgument`: The architecture is unknown or the decoder couldn't start
+pub fn disassemble(
+    architecture: Architecture,
+    start: Address,
+    code: &[u8],
+    count: usize,
+    pc: Option<Address>,
+) -> Result<Vec<DisassembledInstruction>>
+{
+    let (decoder, bad_len) = decoder(architecture)?;
+    let longest = max_code_len(architecture, 1);
+    let mut listing = Vec::with_capacity(count);
+    let mut offset = 0;
+    while listing.len() < count && offset < code.len() {
+        let address = start + offset as u64;
+        let decoded = decoder
+            .disasm_count(&code[offset..], address.value(), 1)
+            .map_err(|error| DebuggerError::InvalidArgument(format!("Couldn't decode at {address}: {error}")))?;
+        let (bytes, mnemonic, operands) = match decoded.iter().next() {
+            Some(insn) => (
+                insn.bytes().to_vec(),
+                insn.mnemonic().unwrap_or(BAD_MNEMONIC).to_string(),
+                insn.op_str().unwrap_or_default().to_string(),
+            ),
+            // Too few bytes left for the longest instruction: it may just be cut off
+            None if code.len() - offset >= longest => (
+                code[offset..offset + bad_len].to_vec(),
+                BAD_MNEMONIC.to_string(),
+                String::new(),
+            ),
+            None => break,
+        };
+        offset += bytes.len();
+        listing.push(DisassembledInstruction {
+            address,
+            bytes,
+            mnemonic,
+            operands,
+            is_current_pc: pc == Some(address),
+        });
+    }
+    Ok(listing)
+}
+
+/// A decoder for `architecture` and how many bytes to skip when it fails.
+fn decoder(architecture: Architecture) -> Result<(Capstone, usize)>
+{
+    let built = match architecture {
+        Architecture::Arm64 => Capstone::new()
+            .arm64()
+            .mode(arch::arm64::ArchMode::Arm)
+            .build()
+            .map(|cs| (cs, 4)),
+        Architecture::X86_64 => Capstone::new()
+            .x86()
+            .mode(arch::x86::ArchMode::Mode64)
+            .syntax(arch::x86::ArchSyntax::Intel)
+            .build()
+            .map(|cs| (cs, 1)),
+        Architecture::Unknown(name) => {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "Can't disassemble {name} instructions"
+            )));
+        }
+    };
+    built.map_err(|error| DebuggerError::InvalidArgument(format!("Couldn't start the {architecture} decoder: {error}")))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn text(listing: &[DisassembledInstruction]) -> Vec<String>
+    {
+        listing.iter().map(DisassembledInstruction::text).collect()
+    }
+
+    #[test]
+    fn arm64_decodes_words_and_marks_the_pc()
+    {
+        // stp x29, x30, [sp, #-0x10]!; mov x29, sp; nop; ret
+        let code = [
+            0xfd, 0x7b, 0xbf, 0xa9, 0xfd, 0x03, 0x00, 0x91, 0x1f, 0x20, 0x03, 0xd5, 0xc0, 0x03, 0x5f, 0xd6,
+        ];
+        let start = Address::from(0x1000);
+        let listing = disassemble(Architecture::Arm64, start, &code, 8, Some(Address::from(0x1008))).unwrap();
+
+        assert_eq!(text(&listing), ["stp x29, x30, [sp, #-0x10]!", "mov x29, sp", "nop", "ret"]);
+        assert_eq!(listing[3].address, Address::from(0x100c));
+        let current: Vec<bool> = listing.iter().map(|insn| insn.is_current_pc).collect();
+        assert_eq!(current, [false, false, true, false]);
+
+        let first_two = disassemble(Architecture::Arm64, start, &code, 2, None).unwrap();
+        assert_eq!(first_two.len(), 2);
+    }
+
+    #[test]
+    fn undecodable_bytes_are_skipped_as_bad()
+    {
+        // An undefined ARM64 word, then nop, then half a word
+        let code = [0x00, 0x00, 0x00, 0x00, 0x1f, 0x20, 0x03, 0xd5, 0x1f, 0x20];
+        let listing = disassemble(Architecture::Arm64, Address::from(0x2000), &code, 8, None).unwrap();
+        assert_eq!(text(&listing), [BAD_MNEMONIC, "nop"]);
+        assert_eq!(listing[1].address, Address::from(0x2004));
+
+        // push es doesn't exist in 64-bit mode
+        let mut code = vec![0x06];
+        code.extend([0x90; MAX_INSTRUCTION_LEN]);
+        let listing = disassemble(Architecture::X86_64, Address::from(0x2000), &code, 2, None).unwrap();
+        assert_eq!(text(&listing), [BAD_MNEMONIC, "nop"]);
+    }
+
+    #[test]
+    fn x86_64_decodes_variable_length_instructions()
+    {
+        // push rbp; mov rbp, rsp; call rel32; pop rbp; ret
+        let code = [0x55, 0x48, 0x89, 0xe5, 0xe8, 0x00, 0x00, 0x00, 0x00, 0x5d, 0xc3];
+        let start = Address::from(0x1000);
+        let listing = disassemble(Architecture::X86_64, start, &code, 16, Some(Address::from(0x1004))).unwrap();
+
+        assert_eq!(text(&listing), ["push rbp", "mov rbp, rsp", "call 0x1009", "pop rbp", "ret"]);
+        assert_eq!(listing[2].bytes.len(), 5);
+        assert_eq!(listing[2].end(), Address::from(0x1009));
+        assert!(listing[2].is_current_pc);
+
+        // The call is cut off, so only the first two come back
+        let cut = disassemble(Architecture::X86_64, start, &code[..6], 16, None).unwrap();
+        assert_eq!(cut.len(), 2);
+    }
+
+    #[test]
+    fn reads_are_sized_for_the_longest_instructions()
+    {
+        assert_eq!(max_code_len(Architecture::Arm64, 10), 40);
+        assert_eq!(max_code_len(Architecture::X86_64, 10), 150);
+
+        // Code 16 bytes before the end of a mapping
+        let mapped_end = 0x2000;
+        let read = |address: Address, len: usize| {
+            if address.value() + len as u64 > mapped_end {
+                Err(DebuggerError::InvalidArgument("unmapped".to_string()))
+            } else {
+                Ok(vec![0x90; len])
+            }
+        };
+        let code = read_code(Architecture::X86_64, Address::from(0x1ff0), 4, read).unwrap();
+        assert_eq!(code.len(), 16);
+        assert_eq!(
+            read_code(Architecture::X86_64, Address::from(0x1000), 4, read).unwrap().len(),
+            60
+        );
+        assert!(disassemble(Architecture::Unknown("riscv64"), Address::from(0), &[0; 4], 1, None).is_err());
+    }
+}
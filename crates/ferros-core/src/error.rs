@@ -8,6 +8,10 @@
 
 use thiserror::Error;
 
+use crate::breakpoints::BreakpointId;
+use crate::permissions::PermissionAdvice;
+use crate::types::{Address, Architecture, ThreadId};
+
 /// Main error type for debugger operations
 ///
 /// This enum represents all the ways a debugger operation can fail.
@@ -21,16 +25,22 @@ use thiserror::Error;
 /// - **Better error messages**: Each variant can have a custom message
 /// - **Error chaining**: Can convert from platform-specific errors (like MachError)
 ///
+/// The enum is `#[non_exhaustive]`: match it with a wildcard arm, since new
+/// variants are added as more failures get their own context.
+///
 /// ## Error Categories
 ///
-/// 1. **Process errors**: ProcessNotFound, AttachFailed, NotAttached
-/// 2. **State errors**: NotStopped, SuspendFailed, ResumeFailed
-/// 3. **Breakpoint errors**: NoBreakpoint, BreakpointIdNotFound
+/// 1. **Process errors**: ProcessNotFound, AttachFailed, NotAttached, TargetGone
+/// 2. **State errors**: NotStopped, SuspendFailed, ResumeFailed, TimedOut, AssertionsFailed
+/// 3. **Breakpoint errors**: NoBreakpoint, BreakpointNotFound
 /// 4. **Permission errors**: PermissionDenied
-/// 5. **Resource errors**: ResourceExhausted (hardware breakpoint/watchpoint limits)
-/// 6. **Platform errors**: MachError (macOS-specific)
+/// 5. **Resource errors**: ResourceExhausted, NoHardwareSlots (hardware breakpoint/watchpoint limits)
+/// 6. **Platform errors**: MachError (macOS-specific), UnsupportedArchitecture
 /// 7. **I/O errors**: Io (for file operations, etc.)
+/// 8. **Stepping errors**: UnreliableReturnAddress, CfiUnavailable
+/// 9. **Target errors**: MemoryReadFailed, ThreadNotFound
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum DebuggerError
 {
     /// The process with the given PID doesn't exist or has exited
@@ -52,16 +62,25 @@ pub enum DebuggerError
     /// - `ptrace(PTRACE_ATTACH)` failed with EPERM
     /// - You need to be root or the process owner
     ///
+    /// Backends attach [`PermissionAdvice`] saying which privilege was
+    /// missing and how to get it; see [`permission_advice`](Self::permission_advice).
+    ///
     /// See: [macOS Debugging Entitlements](https://developer.apple.com/documentation/bundleresources/entitlements/com_apple_security_cs_debugger)
-    #[error("Permission denied: {0}")]
-    PermissionDenied(String),
+    #[error("Permission denied: {message}")]
+    PermissionDenied
+    {
+        /// What was refused
+        message: String,
+        /// What to do about it, if the backend could tell
+        advice: Option<Box<PermissionAdvice>>,
+    },
 
     /// Invalid argument passed to a debugger function
     ///
     /// Examples:
-    /// - Trying to read registers before attaching
-    /// - Invalid memory address
-    /// - Unsupported architecture
+    /// - A malformed breakpoint condition or location
+    /// - A watchpoint length the hardware can't watch
+    /// - A feature the backend doesn't implement
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
 
@@ -89,6 +108,19 @@ pub enum DebuggerError
     #[error("Not attached to a process")]
     NotAttached,
 
+    /// The target exited or was killed without the debugger seeing it
+    ///
+    /// Returned in place of the raw failure once a backend finds its target
+    /// gone, e.g. killed from another terminal. The debugger has released the
+    /// process and reports itself detached; every later operation returns
+    /// this same error until the next attach or launch.
+    #[error("Process {pid} is gone (exited or killed outside the debugger)")]
+    TargetGone
+    {
+        /// PID of the process that disappeared
+        pid: u32,
+    },
+
     /// Operation requires the process to be stopped
     ///
     /// This error occurs when trying to perform an operation that requires
@@ -105,13 +137,62 @@ pub enum DebuggerError
     /// No breakpoint found at the specified address
     ///
     /// This error occurs when trying to remove, disable, or query a breakpoint
-    /// that doesn't exist at the given address.
+    /// that doesn't exist at the given address. Lookups by
+    /// [`BreakpointId`] return [`BreakpointNotFound`](Self::BreakpointNotFound)
+    /// instead.
     #[error("No breakpoint at address 0x{0:016x}")]
     NoBreakpoint(u64),
 
     /// No breakpoint exists for the given identifier.
-    #[error("No breakpoint with id {0}")]
-    BreakpointIdNotFound(u64),
+    ///
+    /// The id-based counterpart of [`NoBreakpoint`](Self::NoBreakpoint), which
+    /// is for lookups by address.
+    #[error("No breakpoint with id {}", .0.raw())]
+    BreakpointNotFound(BreakpointId),
+
+    /// Target memory couldn't be read
+    ///
+    /// The range is the part of the request that failed, which may be a
+    /// single page of a longer read. `kern_return` is the code the kernel
+    /// refused the read with, if it got that far; `None` means the read came
+    /// back short or the range isn't mapped.
+    #[error(
+        "Unable to read {length} bytes at {address}{}",
+        .kern_return.map(|code| format!(" (kern_return {code})")).unwrap_or_default()
+    )]
+    MemoryReadFailed
+    {
+        /// First address of the range that couldn't be read
+        address: Address,
+        /// Length of that range in bytes
+        length: usize,
+        /// Kernel return code of the failed read
+        kern_return: Option<i32>,
+    },
+
+    /// The target's architecture isn't one this build of ferros can debug
+    ///
+    /// Returned for targets whose CPU type wasn't recognized, and for an
+    /// architecture the running build wasn't compiled with register support
+    /// for (an x86-64 process under Rosetta seen from an arm64 build).
+    #[error("Unsupported architecture: {0}")]
+    UnsupportedArchitecture(Architecture),
+
+    /// The thread isn't part of the target
+    ///
+    /// It has exited, or the ID was taken from a thread list that has since
+    /// been refreshed.
+    #[error("Thread {} is not part of the process", .0.raw())]
+    ThreadNotFound(ThreadId),
+
+    /// No call frame information covers the code being unwound
+    ///
+    /// Returned by operations that need the DWARF CFI (`.eh_frame` or
+    /// `.debug_frame`) of a function rather than a best-effort walk, such as
+    /// an unwind with [`UnwindOptions::cfi_only`](crate::symbols::unwind::UnwindOptions::cfi_only),
+    /// when the image has none for that address.
+    #[error("No call frame information (CFI) is available for this code")]
+    CfiUnavailable,
 
     /// A required resource has been exhausted
     ///
@@ -133,17 +214,17 @@ pub enum DebuggerError
     ///
     /// ```rust,no_run
     /// use ferros_core::Debugger;
-    /// use ferros_core::breakpoints::BreakpointRequest;
+    /// use ferros_core::breakpoints::Breakpoint;
     /// use ferros_core::error::DebuggerError;
     /// use ferros_core::types::Address;
     ///
     /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
     /// # let address = Address::from(0x1000);
-    /// let breakpoint_id = match debugger.add_breakpoint(BreakpointRequest::Hardware { address }) {
+    /// let breakpoint_id = match debugger.add_breakpoint(Breakpoint::hardware(address).build()) {
     ///     Err(DebuggerError::ResourceExhausted(msg)) => {
     ///         println!("Hardware breakpoint limit reached: {}", msg);
     ///         // Try software breakpoint instead
-    ///         debugger.add_breakpoint(BreakpointRequest::Software { address })?
+    ///         debugger.add_breakpoint(Breakpoint::software(address).build())?
     ///     }
     ///     Ok(id) => id,
     ///     Err(e) => return Err(e),
@@ -153,6 +234,19 @@ pub enum DebuggerError
     #[error("Resource exhausted: {0}")]
     ResourceExhausted(String),
 
+    /// Every hardware breakpoint slot is taken
+    ///
+    /// Returned when adding or re-enabling a hardware breakpoint while each of
+    /// the CPU's breakpoint registers already holds an armed one: 4 on x86-64,
+    /// and as many as the core implements on ARM64 (6 on Apple Silicon). The
+    /// value is the number of slots.
+    ///
+    /// Disable or remove a hardware breakpoint, or request the breakpoint with
+    /// [`allow_fallback`](crate::breakpoints::BreakpointBuilder::allow_fallback)
+    /// to get a software breakpoint instead.
+    #[error("All {0} hardware breakpoint slots are in use")]
+    NoHardwareSlots(usize),
+
     /// Failed to suspend the target process
     ///
     /// This error occurs when `suspend()` fails. This can happen if:
@@ -171,6 +265,32 @@ pub enum DebuggerError
     #[error("Failed to resume process: {0}")]
     ResumeFailed(String),
 
+    /// The target didn't stop within the allotted time
+    ///
+    /// Returned by [`wait_for_stop`](crate::events::wait_for_stop) when neither
+    /// a stop nor an exit arrives before the deadline. The target is left as it
+    /// was; the caller decides whether to suspend, kill or keep waiting.
+    #[error("Timed out after {0:?} waiting for the target to stop")]
+    TimedOut(std::time::Duration),
+
+    /// Assertion breakpoints failed during the session
+    ///
+    /// Returned at the end of a session run with `--assert-reached` or
+    /// `--assert-not-reached` when any assertion failed, even if the target
+    /// itself exited 0. The violations are in the session summary; see
+    /// [`assertions`](crate::assertions).
+    #[error("{0} assertion(s) failed")]
+    AssertionsFailed(usize),
+
+    /// The current function's return address can't be trusted
+    ///
+    /// Returned by [`step_out`](crate::Debugger::step_out) when the unwinder
+    /// found no caller, or only recovered it heuristically (stack scanning or
+    /// the link register), instead of setting a breakpoint at what may be a
+    /// garbage address. The string says which.
+    #[error("Can't tell where the current function returns: {0}")]
+    UnreliableReturnAddress(String),
+
     /// Failed to read registers from the target process
     ///
     /// This can happen if:
@@ -189,7 +309,7 @@ pub enum DebuggerError
         /// Description of the operation that failed
         operation: String,
         /// Thread ID if the operation was thread-specific
-        thread_id: Option<crate::types::ThreadId>,
+        thread_id: Option<ThreadId>,
         /// Additional error details
         details: String,
     },
@@ -214,6 +334,29 @@ pub enum DebuggerError
     Io(#[from] std::io::Error),
 }
 
+impl DebuggerError
+{
+    /// `PermissionDenied` with `advice` on what to do about it.
+    #[must_use]
+    pub fn permission_denied(message: impl Into<String>, advice: Option<PermissionAdvice>) -> Self
+    {
+        Self::PermissionDenied {
+            message: message.into(),
+            advice: advice.map(Box::new),
+        }
+    }
+
+    /// Advice attached to a `PermissionDenied` error.
+    #[must_use]
+    pub fn permission_advice(&self) -> Option<&PermissionAdvice>
+    {
+        match self {
+            Self::PermissionDenied { advice, .. } => advice.as_deref(),
+            _ => None,
+        }
+    }
+}
+
 /// Convenience type alias for `Result<T, DebuggerError>`
 ///
 /// This makes error handling more ergonomic. Instead of writing:
@@ -233,3 +376,41 @@ pub enum DebuggerError
 /// }
 /// ```
 pub type Result<T> = std::result::Result<T, DebuggerError>;
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn structured_errors_keep_readable_messages()
+    {
+        let refused = DebuggerError::MemoryReadFailed {
+            address: Address::from(0x1000),
+            length: 4,
+            kern_return: Some(1),
+        };
+        assert_eq!(
+            refused.to_string(),
+            "Unable to read 4 bytes at 0x0000000000001000 (kern_return 1)"
+        );
+        let short = DebuggerError::MemoryReadFailed {
+            address: Address::from(0x1000),
+            length: 4,
+            kern_return: None,
+        };
+        assert_eq!(short.to_string(), "Unable to read 4 bytes at 0x0000000000001000");
+        assert_eq!(
+            DebuggerError::ThreadNotFound(ThreadId(7)).to_string(),
+            "Thread 7 is not part of the process"
+        );
+        assert_eq!(
+            DebuggerError::UnsupportedArchitecture(Architecture::Unknown("ppc")).to_string(),
+            "Unsupported architecture: ppc"
+        );
+        assert_eq!(
+            DebuggerError::BreakpointNotFound(BreakpointId::from_raw(3)).to_string(),
+            "No breakpoint with id 3"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Expressions for the `print` command.
+//!
+//! An [`Expression`] uses the grammar of breakpoint
+//! [conditions](crate::breakpoints::condition): registers (`$pc`, `$x0`,
+//! `$rax`), integer literals, `*expr` and `*(u32*)expr` dereferences,
+//! arithmetic (`+ - * / % &`), comparisons and `str(addr, n)`. It is evaluated
+//! against a target's memory and the registers of a frame, and gives a typed
+//! [`Value`]. Names other than registers are rejected until frames carry their
+//! local variables.
+//!
+//! ```rust
+//! use ferros_core::eval::{Expression, Value};
+//! use ferros_core::types::{Address, Architecture, Registers};
+//!
+//! let mut registers = Registers::new().with_arch(Architecture::Arm64);
+//! registers.general = vec![0; 31];
+//! registers.general[0] = 40;
+//! registers.sp = Address::from(0x7000);
+//! let mut memory = |_: Address, len: usize| Ok(vec![0; len]);
+//!
+//! let value = Expression::parse("$x0 + 2")?.evaluate_with(&registers, &mut memory)?;
+//! assert_eq!(value, Value::Integer(42));
+//! let value = Expression::parse("$sp + 0x10")?.evaluate_with(&registers, &mut memory)?;
+//! assert_eq!(value, Value::Address(Address::from(0x7010)));
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::breakpoints::condition::{self, ConditionBudget, ConditionFailure, ConditionMemory, Evaluator, Expr, Kind};
+use crate::error::{DebuggerError, Result};
+use crate::inspector::TargetInspector;
+use crate::types::{Address, Registers};
+
+/// Limits on one evaluation.
+///
+/// Looser than a condition's: the target is stopped and a person is waiting,
+/// not a thread held in the exception handler.
+pub const EVAL_BUDGET: ConditionBudget = ConditionBudget {
+    max_reads: 64,
+    max_bytes: 16 * 1024,
+    max_time: Duration::from_millis(250),
+};
+
+/// Result of an [`Expression`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value
+{
+    /// A number: a literal, a register that doesn't hold a pointer, a dereference or arithmetic on them.
+    Integer(i128),
+    /// A pointer: `$pc`, `$sp`, `$fp` or `$lr`, possibly offset by an integer.
+    Address(Address),
+    /// Bytes from a string literal or `str(addr, n)`.
+    Bytes(Vec<u8>),
+}
+
+impl Value
+{
+    /// The value as a 64-bit register or pointer value, or `None` for bytes.
+    ///
+    /// Integers wider than 64 bits keep their low 64 bits.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64>
+    {
+        match self {
+            // Truncation is the point: values wrap like the target's registers
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Self::Integer(value) => Some(*value as u64),
+            Self::Address(address) => Some(address.value()),
+            Self::Bytes(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Value
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            // Negative numbers show their 64-bit pattern, like a register would
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Self::Integer(value) if *value < 0 => write!(f, "{value} ({:#x})", *value as u64),
+            Self::Integer(value) => write!(f, "{value} ({value:#x})"),
+            Self::Address(address) => write!(f, "{address}"),
+            Self::Bytes(bytes) => write!(f, "\"{}\"", String::from_utf8_lossy(bytes).escape_debug()),
+        }
+    }
+}
+
+/// A parsed `print` expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression
+{
+    source: String,
+    expr: Expr,
+    kind: Kind,
+}
+
+impl Expression
+{
+    /// Parse an expression.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: The text isn't a valid expression, naming the offending token and its column
+    pub fn parse(source: &str) -> Result<Self>
+    {
+        let (expr, kind) = condition::parse_expression(source, "expression")
+            .map_err(|message| DebuggerError::InvalidArgument(format!("Invalid expression `{source}`: {message}")))?;
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+            kind,
+        })
+    }
+
+    /// The text the expression was parsed from.
+    #[must_use]
+    pub fn source(&self) -> &str
+    {
+        &self.source
+    }
+
+    /// Evaluate against `target`'s memory and the frame's `registers`.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: A register is missing, a read fails, a division is by
+    ///   zero or the evaluation goes over [`EVAL_BUDGET`]
+    pub fn evaluate<T: TargetInspector + ?Sized>(&self, target: &T, registers: &Registers) -> Result<Value>
+    {
+        self.evaluate_with(registers, &mut |address: Address, len: usize| {
+            target.read_memory(address, len)
+        })
+    }
+
+    /// Evaluate against `memory` and `registers`.
+    ///
+    /// ## Errors
+    ///
+    /// As [`evaluate`](Self::evaluate).
+    pub fn evaluate_with(&self, registers: &Registers, memory: &mut dyn ConditionMemory) -> Result<Value>
+    {
+        let failed = |failure: ConditionFailure| {
+            DebuggerError::InvalidArgument(format!("Couldn't evaluate `{}`: {failure}", self.source))
+        };
+        let mut eval = Evaluator::new(registers, memory, &EVAL_BUDGET);
+        match self.kind {
+            Kind::Bytes => Ok(Value::Bytes(eval.bytes(&self.expr).map_err(failed)?.unwrap_or_default())),
+            Kind::Int => {
+                let value = eval.int(&self.expr).map_err(failed)?;
+                // Truncation is the point: addresses wrap like the target's pointers
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                Ok(if self.expr.is_pointer() {
+                    Value::Address(Address::from(value as u64))
+                } else {
+                    Value::Integer(value)
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expression
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::Architecture;
+
+    fn registers() -> Registers
+    {
+        let mut registers = Registers::new().with_arch(Architecture::Arm64);
+        registers.general = vec![0; 31];
+        registers.general[0] = 0x1000;
+        registers.general[1] = 3;
+        registers.pc = Address::from(0x4000);
+        registers
+    }
+
+    fn eval(source: &str) -> Result<Value>
+    {
+        let mapped = [0x2a, 0, 0, 0, 0, 0, 0, 0, b'h', b'i', 0, 0, 0, 0, 0, 0];
+        let mut memory = |address: Address, len: usize| {
+            let start = usize::try_from(address.value().wrapping_sub(0x1000)).unwrap_or(usize::MAX);
+            mapped
+                .get(start..start.saturating_add(len))
+                .map(<[u8]>::to_vec)
+                .ok_or_else(|| DebuggerError::InvalidArgument("unmapped".to_string()))
+        };
+        Expression::parse(source)?.evaluate_with(&registers(), &mut memory)
+    }
+
+    #[test]
+    fn values_are_typed_by_what_they_hold()
+    {
+        assert_eq!(eval("$x1 * 2 + 1").unwrap(), Value::Integer(7));
+        assert_eq!(eval("*$x0").unwrap(), Value::Integer(42));
+        assert_eq!(eval("*(u8*)$x0 & 0xf").unwrap(), Value::Integer(10));
+        assert_eq!(eval("$pc + 8").unwrap(), Value::Address(Address::from(0x4008)));
+        assert_eq!(eval("$pc - $x1").unwrap(), Value::Address(Address::from(0x3ffd)));
+        assert_eq!(eval("$pc - $pc").unwrap(), Value::Integer(0));
+        assert_eq!(eval("str($x0 + 8, 8)").unwrap(), Value::Bytes(b"hi".to_vec()));
+
+        assert_eq!(Value::Integer(-2).as_u64(), Some(u64::MAX - 1));
+        assert_eq!(Value::Bytes(Vec::new()).as_u64(), None);
+        assert_eq!(Value::Integer(42).to_string(), "42 (0x2a)");
+        assert_eq!(Value::Integer(-1).to_string(), "-1 (0xffffffffffffffff)");
+        assert_eq!(Value::Bytes(b"a\n".to_vec()).to_string(), r#""a\n""#);
+    }
+
+    #[test]
+    fn bad_expressions_name_the_offending_token()
+    {
+        let message = |source: &str| eval(source).unwrap_err().to_string();
+
+        assert!(message("$x0 + ) 1").contains("expected a value but found `)` at column 7"));
+        assert!(message("$x0 +").contains("expected a value but the expression ended"));
+        assert!(message("count + 1").contains("unknown name `count` at column 1"));
+        assert!(message("$x0 @ 1").contains("unexpected `@` at column 5"));
+        assert!(message("*$x1").contains("Couldn't evaluate `*$x1`: reading 8 bytes"));
+        assert!(message("$x0 / 0").contains("division by zero"));
+    }
+}
@@ -5,10 +5,18 @@
 //! `stop_reason()`. Platform backends publish events whenever the kernel
 //! delivers an exception (Mach) or a wait result (`waitpid`, Windows debug
 //! loop, etc.).
+//!
+//! Events travel in an [`EventEnvelope`] that records when they were emitted,
+//! so consumers can measure how long delivery took. [`LatencyWindow`] keeps
+//! recent measurements for percentile reporting.
 
-use std::sync::mpsc;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::types::{StopReason, ThreadId};
+use crate::error::{DebuggerError, Result};
+use crate::types::{Address, StopReason, ThreadId};
 
 /// Event emitted by a debugger backend.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,6 +32,30 @@ pub enum DebuggerEvent
     },
     /// Target resumed execution.
     TargetResumed,
+    /// The target loaded an image after the first time its image list was read.
+    ImageLoaded
+    {
+        /// Path of the image.
+        path: PathBuf,
+        /// Address its `__TEXT` segment (or equivalent) is loaded at.
+        base: Address,
+    },
+    /// The target unloaded an image, e.g. with `dlclose()`.
+    ImageUnloaded
+    {
+        /// Path of the image.
+        path: PathBuf,
+        /// Address it was loaded at.
+        base: Address,
+    },
+    /// Symbols loading in the background finished for a batch of images.
+    ///
+    /// Stack traces taken before it may lack the names these images resolve.
+    SymbolsLoaded
+    {
+        /// Images whose symbols loaded.
+        images: usize,
+    },
 }
 
 impl DebuggerEvent
@@ -41,10 +73,151 @@ impl DebuggerEvent
                 description
             }
             Self::TargetResumed => "Target resumed execution".to_string(),
+            Self::ImageLoaded { path, base } => format!("Loaded {} at {base}", path.display()),
+            Self::ImageUnloaded { path, base } => format!("Unloaded {} from {base}", path.display()),
+            Self::SymbolsLoaded { images: 1 } => "Loaded symbols for 1 image".to_string(),
+            Self::SymbolsLoaded { images } => format!("Loaded symbols for {images} images"),
         }
     }
 }
 
+/// A [`DebuggerEvent`] together with its delivery timing.
+///
+/// `emitted_at`/`emitted` are captured when the backend sends the event.
+/// `source_received` is when the backend received the underlying OS event,
+/// e.g. the return of `mach_msg()` in the exception loop. Mach exception
+/// messages carry no kernel timestamp trailer, so that is the earliest point
+/// ferros can observe; the gap to `emitted` is time spent in the handler
+/// (PC rewinding, stack overflow classification, bookkeeping).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventEnvelope
+{
+    /// The event itself.
+    pub event: DebuggerEvent,
+    /// Wall-clock emission time, for display and logs.
+    pub emitted_at: SystemTime,
+    /// Monotonic emission time, for latency measurements.
+    pub emitted: Instant,
+    /// When the backend received the OS event this was derived from, if it
+    /// came from one.
+    pub source_received: Option<Instant>,
+}
+
+impl EventEnvelope
+{
+    /// Wrap `event`, stamping it with the current time.
+    #[must_use]
+    pub fn new(event: DebuggerEvent) -> Self
+    {
+        Self {
+            event,
+            emitted_at: SystemTime::now(),
+            emitted: Instant::now(),
+            source_received: None,
+        }
+    }
+
+    /// Record when the backend received the underlying OS event.
+    #[must_use]
+    pub fn with_source_received(mut self, received: Instant) -> Self
+    {
+        self.source_received = Some(received);
+        self
+    }
+
+    /// Time the backend spent between receiving the OS event and emitting this.
+    #[must_use]
+    pub fn handler_delay(&self) -> Option<Duration>
+    {
+        self.source_received
+            .map(|received| self.emitted.saturating_duration_since(received))
+    }
+
+    /// Emission to `received` latency, e.g. when a consumer dequeued the event.
+    #[must_use]
+    pub fn latency_at(&self, received: Instant) -> Duration
+    {
+        received.saturating_duration_since(self.emitted)
+    }
+}
+
+/// Bounded window of recent latency samples.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyWindow
+{
+    samples: VecDeque<Duration>,
+    capacity: usize,
+    total: u64,
+}
+
+impl LatencyWindow
+{
+    /// Create a window keeping the most recent `capacity` samples.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self
+    {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            total: 0,
+        }
+    }
+
+    /// Add a sample, evicting the oldest one when full.
+    pub fn record(&mut self, sample: Duration)
+    {
+        self.total += 1;
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Number of samples currently in the window.
+    #[must_use]
+    pub fn len(&self) -> usize
+    {
+        self.samples.len()
+    }
+
+    /// Whether no samples have been recorded (or the window keeps none).
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.samples.is_empty()
+    }
+
+    /// Number of samples ever recorded, including evicted ones.
+    #[must_use]
+    pub fn total(&self) -> u64
+    {
+        self.total
+    }
+
+    /// Nearest-rank percentile (`0..=100`) of the samples in the window.
+    #[must_use]
+    pub fn percentile(&self, percent: u8) -> Option<Duration>
+    {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = (usize::from(percent.min(100)) * sorted.len()).div_ceil(100).max(1);
+        Some(sorted[rank - 1])
+    }
+
+    /// Largest sample in the window.
+    #[must_use]
+    pub fn max(&self) -> Option<Duration>
+    {
+        self.samples.iter().max().copied()
+    }
+}
+
 /// Format a [`StopReason`] into a user-facing message.
 #[must_use]
 pub fn format_stop_reason(reason: StopReason) -> String
@@ -54,15 +227,21 @@ pub fn format_stop_reason(reason: StopReason) -> String
         StopReason::Suspended => "Process is suspended".to_string(),
         StopReason::Signal(sig) => format!("Stopped by signal: {sig}"),
         StopReason::Breakpoint(addr) => format!("Hit breakpoint at 0x{addr:x}"),
-        StopReason::Exited(code) => format!("Process exited with code: {code}"),
+        StopReason::Step(pc) => format!("Stepped to 0x{pc:x}"),
+        StopReason::Watchpoint { address, pc } => format!("Watchpoint on 0x{address:x} hit at 0x{pc:x}"),
+        StopReason::BadAccess { address, kind } => format!("EXC_BAD_ACCESS ({kind}) at 0x{address:x}"),
+        StopReason::StackOverflow { sp, guard_page, .. } => {
+            format!("Stack overflow: SP 0x{sp:x} ran into the guard page at 0x{guard_page:x}")
+        }
+        StopReason::Exited(code) => format!("Process exited with code {code}"),
         StopReason::Unknown => "Stopped for unknown reason".to_string(),
     }
 }
 
 /// Sender side of the debugger event channel.
-pub type DebuggerEventSender = mpsc::Sender<DebuggerEvent>;
+pub type DebuggerEventSender = mpsc::Sender<EventEnvelope>;
 /// Receiver side of the debugger event channel.
-pub type DebuggerEventReceiver = mpsc::Receiver<DebuggerEvent>;
+pub type DebuggerEventReceiver = mpsc::Receiver<EventEnvelope>;
 
 /// Create a new debugger event channel.
 #[must_use]
@@ -70,3 +249,135 @@ pub fn event_channel() -> (DebuggerEventSender, DebuggerEventReceiver)
 {
     mpsc::channel()
 }
+
+/// Block until the target stops or exits, for at most `timeout`.
+///
+/// Events already queued are drained first; if the latest of them is a stop,
+/// the target is stopped now and that event is returned immediately. Every
+/// event received, including resumes, is passed to `observe` so callers can
+/// keep recording them.
+///
+/// ## Errors
+///
+/// - `TimedOut`: no stop arrived within `timeout`
+/// - `NotAttached`: the backend dropped its sender (the debugger is gone)
+pub fn wait_for_stop(
+    events: &DebuggerEventReceiver,
+    timeout: Duration,
+    mut observe: impl FnMut(&EventEnvelope),
+) -> Result<EventEnvelope>
+{
+    let deadline = Instant::now() + timeout;
+    let mut latest = None;
+    for envelope in events.try_iter() {
+        observe(&envelope);
+        latest = Some(envelope);
+    }
+    if let Some(envelope) = latest.filter(|envelope| matches!(envelope.event, DebuggerEvent::TargetStopped { .. })) {
+        return Ok(envelope);
+    }
+
+    loop {
+        match events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(envelope) => {
+                observe(&envelope);
+                if matches!(envelope.event, DebuggerEvent::TargetStopped { .. }) {
+                    return Ok(envelope);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => return Err(DebuggerError::TimedOut(timeout)),
+            Err(RecvTimeoutError::Disconnected) => return Err(DebuggerError::NotAttached),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn envelope_measures_handler_delay_and_latency()
+    {
+        let received = Instant::now();
+        let envelope = EventEnvelope::new(DebuggerEvent::TargetResumed).with_source_received(received);
+        assert_eq!(envelope.handler_delay(), Some(envelope.emitted - received));
+
+        let consumed = envelope.emitted + Duration::from_millis(40);
+        assert_eq!(envelope.latency_at(consumed), Duration::from_millis(40));
+        // A receipt time before emission (clock skew between stamps) is not negative
+        assert_eq!(envelope.latency_at(received), Duration::ZERO);
+        assert_eq!(EventEnvelope::new(DebuggerEvent::TargetResumed).handler_delay(), None);
+    }
+
+    #[test]
+    fn envelopes_round_trip_through_the_channel()
+    {
+        let (sender, receiver) = event_channel();
+        let envelope = EventEnvelope::new(DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x1000),
+            thread: Some(ThreadId::from(7)),
+        });
+        sender.send(envelope.clone()).unwrap();
+        assert_eq!(receiver.recv().unwrap(), envelope);
+    }
+
+    #[test]
+    fn latency_window_percentiles()
+    {
+        let mut window = LatencyWindow::new(10);
+        assert_eq!(window.percentile(50), None);
+
+        for ms in [5, 1, 9, 3, 7, 2, 10, 4, 8, 6, 100] {
+            window.record(Duration::from_millis(ms));
+        }
+        // The first sample (5ms) was evicted
+        assert_eq!((window.len(), window.total()), (10, 11));
+        assert_eq!(window.percentile(50), Some(Duration::from_millis(6)));
+        assert_eq!(window.percentile(95), Some(Duration::from_millis(100)));
+        assert_eq!(window.percentile(0), Some(Duration::from_millis(1)));
+        assert_eq!(window.max(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn wait_for_stop_skips_stale_stops_and_times_out()
+    {
+        let (sender, receiver) = event_channel();
+        let stop = |reason| DebuggerEvent::TargetStopped { reason, thread: None };
+
+        // Launch suspends and resumes before the wait starts
+        sender.send(EventEnvelope::new(stop(StopReason::Suspended))).unwrap();
+        sender.send(EventEnvelope::new(DebuggerEvent::TargetResumed)).unwrap();
+        let mut seen = 0;
+        let started = Instant::now();
+        let result = wait_for_stop(&receiver, Duration::from_millis(50), |_| seen += 1);
+        assert!(matches!(result, Err(DebuggerError::TimedOut(timeout)) if timeout == Duration::from_millis(50)));
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(seen, 2);
+
+        sender.send(EventEnvelope::new(stop(StopReason::Exited(0)))).unwrap();
+        let stopped = wait_for_stop(&receiver, Duration::from_secs(5), |_| {}).unwrap();
+        assert_eq!(stopped.event, stop(StopReason::Exited(0)));
+
+        drop(sender);
+        assert!(matches!(
+            wait_for_stop(&receiver, Duration::from_secs(5), |_| {}),
+            Err(DebuggerError::NotAttached)
+        ));
+    }
+
+    #[test]
+    fn stop_reasons_serialize_under_their_key()
+    {
+        let json = |reason| serde_json::to_value(reason).unwrap();
+        assert_eq!(
+            json(StopReason::Breakpoint(0x1f00)),
+            serde_json::json!({"reason": "breakpoint", "address": "0x1f00"})
+        );
+        assert_eq!(
+            json(StopReason::Exited(3)),
+            serde_json::json!({"reason": "exited", "code": 3})
+        );
+        assert_eq!(json(StopReason::Suspended), serde_json::json!({"reason": "suspended"}));
+    }
+}
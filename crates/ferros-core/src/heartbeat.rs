@@ -0,0 +1,575 @@
+//! Main-thread hang probing for running GUI targets.
+//!
+//! A macOS app's main thread spends most of its life parked in `mach_msg`
+//! under `__CFRunLoopServiceMachPort`, waiting for the next event. When it
+//! sits anywhere else for seconds at a time, the UI is frozen. A
+//! [`HangProbe`] looks at the main thread at a fixed interval while the
+//! target runs: it reads that one thread's registers without suspending the
+//! task and notes which function the PC is in. Once the PC has stayed in the
+//! same function for enough consecutive samples it unwinds the thread once
+//! and asks the [`HangDetector`] whether that is the run loop's park or a
+//! stuck thread.
+//!
+//! The probe is approximate. A thread can leave and come back between two
+//! samples, and the unwind races the running thread, so a hang is a strong
+//! hint rather than proof.
+//!
+//! ```rust
+//! use ferros_core::heartbeat::{HangDetector, HeartbeatSample, Probe};
+//! use ferros_core::types::Address;
+//!
+//! let mut detector = HangDetector::new(3);
+//! let sample = || HeartbeatSample::new(Address::from(0x1000), Some("mach_msg2_trap"));
+//! assert_eq!(detector.observe(sample()), Probe::Quiet);
+//! assert_eq!(detector.observe(sample()), Probe::Quiet);
+//! assert_eq!(detector.observe(sample()), Probe::Unwind);
+//! ```
+
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+use crate::debugger::Debugger;
+use crate::error::Result;
+use crate::inspector::{LiveInspector, TargetInspector};
+use crate::types::{Address, StackFrame, ThreadId};
+use crate::watchdog::BLOCKING_CALLS;
+
+/// Default time between two samples of the main thread.
+pub const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Default number of consecutive samples in one function before it counts as a hang.
+pub const DEFAULT_HANG_SAMPLES: usize = 8;
+
+/// Frames unwound from a main thread suspected of hanging.
+pub const HANG_MAX_FRAMES: usize = 32;
+
+/// Frames of the backtrace quoted in a hang's summary.
+const SUMMARY_FRAMES: usize = 8;
+
+/// The Mach message calls the run loop waits in, innermost first on the stack.
+const MACH_MSG_CALLS: &[&str] = &[
+    "mach_msg_trap",
+    "mach_msg2_trap",
+    "mach_msg2_internal",
+    "mach_msg_overwrite",
+    "mach_msg",
+];
+
+/// Where `CFRunLoop` parks while it waits for work.
+const RUNLOOP_PARK: &str = "__CFRunLoopServiceMachPort";
+
+/// One look at the main thread's program counter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeartbeatSample
+{
+    /// The sampled PC.
+    pub pc: Address,
+    /// Name of the function containing the PC, if it symbolicates.
+    pub function: Option<String>,
+}
+
+impl HeartbeatSample
+{
+    /// Sample at `pc`, inside `function` if it's known.
+    #[must_use]
+    pub fn new(pc: Address, function: Option<&str>) -> Self
+    {
+        Self {
+            pc,
+            function: function.map(str::to_string),
+        }
+    }
+
+    /// Read `thread`'s PC and name the function it's in.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the error of the register read.
+    pub fn take(target: &dyn TargetInspector, thread: ThreadId) -> Result<Self>
+    {
+        let pc = target.read_registers_for(thread)?.pc;
+        let function = target.function_name(pc).map(|name| name.display_name().to_string());
+        Ok(Self { pc, function })
+    }
+
+    /// The function's name, or the PC for code without symbols.
+    fn label(&self) -> String
+    {
+        self.function.clone().unwrap_or_else(|| format!("0x{:x}", self.pc.value()))
+    }
+}
+
+/// What the caller should do after a sample.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Probe
+{
+    /// Nothing to do.
+    Quiet,
+    /// The PC has stayed put long enough: unwind the thread and pass the
+    /// frames to [`HangDetector::judge`].
+    Unwind,
+    /// A thread reported as hung has moved on after `samples` samples in `function`.
+    Recovered
+    {
+        /// Where it was stuck.
+        function: String,
+        /// How many samples it was stuck for.
+        samples: usize,
+    },
+}
+
+/// A main thread that looks stuck.
+#[derive(Debug, Clone)]
+pub struct Hang
+{
+    /// Function every sample landed in, or its PC without symbols.
+    pub function: String,
+    /// The wait primitive it's in, if it's blocked rather than busy.
+    pub blocked_in: Option<String>,
+    /// Consecutive samples it's been there for.
+    pub samples: usize,
+    /// The thread's frames, innermost first; empty if it couldn't be unwound.
+    pub frames: Vec<StackFrame>,
+}
+
+impl Hang
+{
+    /// One-line summary for a timeline, quoting the innermost frames.
+    ///
+    /// `interval` is the time between samples, used to estimate how long the
+    /// thread has been stuck.
+    #[must_use]
+    pub fn summary(&self, interval: Duration) -> String
+    {
+        let stuck = interval * u32::try_from(self.samples.saturating_sub(1)).unwrap_or(u32::MAX);
+        let mut summary = match &self.blocked_in {
+            Some(call) => format!("Main thread blocked in {call} for ~{stuck:.1?}"),
+            None => format!("Main thread busy in {} for ~{stuck:.1?}", self.function),
+        };
+        let _ = write!(summary, " ({} samples)", self.samples);
+        if self.frames.is_empty() {
+            summary.push_str("; no backtrace");
+            return summary;
+        }
+        let names: Vec<&str> = self
+            .frames
+            .iter()
+            .take(SUMMARY_FRAMES)
+            .map(|frame| frame.symbol.as_ref().map_or("<unknown>", |symbol| symbol.display_name()))
+            .collect();
+        let _ = write!(summary, ": {}", names.join(" <- "));
+        if self.frames.len() > SUMMARY_FRAMES {
+            summary.push_str(" <- ...");
+        }
+        summary
+    }
+}
+
+/// Judgement on the current run of samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict
+{
+    /// Waiting for events in the run loop; fine however long it lasts.
+    Parked,
+    /// Reported as a hang.
+    Hung,
+}
+
+/// Consecutive samples in one function.
+#[derive(Debug, Clone)]
+struct Streak
+{
+    label: String,
+    samples: usize,
+    verdict: Option<Verdict>,
+}
+
+/// State machine that turns a sequence of samples into hang reports.
+///
+/// It only ever sees samples and, when it asks for them, frames, so it can
+/// be driven without a target. Each run of consecutive samples in the same
+/// function is judged at most once: a run judged to be the run loop's park
+/// stays quiet however long it lasts, and a run reported as a hang is
+/// reported once and followed by [`Probe::Recovered`] when the PC moves on.
+#[derive(Debug, Clone)]
+pub struct HangDetector
+{
+    threshold: usize,
+    streak: Option<Streak>,
+}
+
+impl HangDetector
+{
+    /// Detector that asks for an unwind after `samples` consecutive samples
+    /// in one function. Fewer than two samples would flag every function, so
+    /// the threshold is at least two.
+    #[must_use]
+    pub fn new(samples: usize) -> Self
+    {
+        Self {
+            threshold: samples.max(2),
+            streak: None,
+        }
+    }
+
+    /// Samples needed before an unwind.
+    #[must_use]
+    pub fn threshold(&self) -> usize
+    {
+        self.threshold
+    }
+
+    /// Forget the current run, e.g. because the target stopped.
+    pub fn reset(&mut self)
+    {
+        self.streak = None;
+    }
+
+    /// Feed the next sample.
+    pub fn observe(&mut self, sample: HeartbeatSample) -> Probe
+    {
+        let label = sample.label();
+        if let Some(streak) = &mut self.streak
+            && streak.label == label
+        {
+            streak.samples += 1;
+            return if streak.samples >= self.threshold && streak.verdict.is_none() {
+                Probe::Unwind
+            } else {
+                Probe::Quiet
+            };
+        }
+
+        let previous = self.streak.replace(Streak {
+            label,
+            samples: 1,
+            verdict: None,
+        });
+        match previous {
+            Some(Streak {
+                label,
+                samples,
+                verdict: Some(Verdict::Hung),
+            }) => Probe::Recovered {
+                function: label,
+                samples,
+            },
+            _ => Probe::Quiet,
+        }
+    }
+
+    /// Judge the current run from the thread's frames, innermost first.
+    ///
+    /// Returns the hang to report, or `None` if the thread is parked in the
+    /// run loop or there is no run to judge. Empty frames (the unwind failed)
+    /// can't show a park, so they're reported as a hang.
+    pub fn judge(&mut self, frames: Vec<StackFrame>) -> Option<Hang>
+    {
+        let streak = self.streak.as_mut()?;
+        if is_runloop_park(&frames) {
+            streak.verdict = Some(Verdict::Parked);
+            return None;
+        }
+        streak.verdict = Some(Verdict::Hung);
+        Some(Hang {
+            blocked_in: BLOCKING_CALLS.contains(&streak.label.as_str()).then(|| streak.label.clone()),
+            function: streak.label.clone(),
+            samples: streak.samples,
+            frames,
+        })
+    }
+}
+
+/// Whether the frames are the run loop waiting for events: Mach message
+/// calls directly under `__CFRunLoopServiceMachPort`.
+fn is_runloop_park(frames: &[StackFrame]) -> bool
+{
+    let names = frames
+        .iter()
+        .map(|frame| frame.symbol.as_ref().map_or("", |symbol| symbol.display_name()));
+    let mut waited = false;
+    for name in names {
+        if MACH_MSG_CALLS.contains(&name) {
+            waited = true;
+            continue;
+        }
+        return waited && name == RUNLOOP_PARK;
+    }
+    false
+}
+
+/// What a probe found worth reporting.
+#[derive(Debug, Clone)]
+pub enum HangEvent
+{
+    /// The main thread looks stuck.
+    Hung(Hang),
+    /// A hung main thread has moved on.
+    Recovered
+    {
+        /// Where it was stuck.
+        function: String,
+        /// Roughly how long it was stuck for.
+        stuck: Duration,
+    },
+}
+
+/// Periodic sampler of a running target's main thread.
+///
+/// Call [`poll`](Self::poll) as often as convenient while the target runs;
+/// it samples once per interval and does nothing in between. Call
+/// [`reset`](Self::reset) whenever the target stops, so time spent stopped
+/// doesn't count towards a hang.
+#[derive(Debug, Clone)]
+pub struct HangProbe
+{
+    interval: Duration,
+    detector: HangDetector,
+    main_thread: Option<ThreadId>,
+    last_sample: Option<Instant>,
+}
+
+impl HangProbe
+{
+    /// Probe sampling every `interval` and reporting after `samples` samples in one function.
+    #[must_use]
+    pub fn new(interval: Duration, samples: usize) -> Self
+    {
+        Self {
+            interval,
+            detector: HangDetector::new(samples),
+            main_thread: None,
+            last_sample: None,
+        }
+    }
+
+    /// Time between samples.
+    #[must_use]
+    pub fn interval(&self) -> Duration
+    {
+        self.interval
+    }
+
+    /// Consecutive samples in one function that count as a hang.
+    #[must_use]
+    pub fn samples(&self) -> usize
+    {
+        self.detector.threshold()
+    }
+
+    /// Start over, e.g. because the target stopped or was replaced.
+    pub fn reset(&mut self)
+    {
+        self.detector.reset();
+        self.last_sample = None;
+    }
+
+    /// Sample the main thread if an interval has passed since the last sample.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the error of the register read. The main thread is looked up
+    /// again on the next sample, in case it's the thread that went away.
+    pub fn poll(&mut self, debugger: &mut dyn Debugger, now: Instant) -> Result<Option<HangEvent>>
+    {
+        if self.last_sample.is_some_and(|last| now.duration_since(last) < self.interval) {
+            return Ok(None);
+        }
+        self.last_sample = Some(now);
+
+        let Some(thread) = self.main_thread.or_else(|| debugger.main_thread()) else {
+            return Ok(None);
+        };
+        let sample = match HeartbeatSample::take(debugger, thread) {
+            Ok(sample) => sample,
+            Err(err) => {
+                self.main_thread = None;
+                self.detector.reset();
+                return Err(err);
+            }
+        };
+        self.main_thread = Some(thread);
+
+        Ok(match self.detector.observe(sample) {
+            Probe::Quiet => None,
+            Probe::Recovered { function, samples } => Some(HangEvent::Recovered {
+                function,
+                stuck: self.interval * u32::try_from(samples.saturating_sub(1)).unwrap_or(u32::MAX),
+            }),
+            Probe::Unwind => {
+                let frames = LiveInspector::new(debugger)
+                    .backtrace(thread, HANG_MAX_FRAMES)
+                    .unwrap_or_default();
+                self.detector.judge(frames).map(HangEvent::Hung)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::{FrameId, FrameKind, FrameStatus, SymbolLanguage, SymbolName};
+
+    fn stack(functions: &[&str]) -> Vec<StackFrame>
+    {
+        let thread = ThreadId::from(1);
+        functions
+            .iter()
+            .enumerate()
+            .map(|(index, function)| {
+                let pc = Address::from(0x1_8000_0000 + index as u64 * 0x40);
+                StackFrame {
+                    id: FrameId::new(thread, u32::try_from(index).unwrap(), 0, pc, Address::ZERO),
+                    thread,
+                    index,
+                    kind: FrameKind::Physical,
+                    pc,
+                    sp: Address::ZERO,
+                    fp: Address::ZERO,
+                    return_address: None,
+                    symbol: Some(SymbolName::new((*function).to_string(), None, SymbolLanguage::C)),
+                    location: None,
+                    parameters: Vec::new(),
+                    status: FrameStatus::Complete,
+                }
+            })
+            .collect()
+    }
+
+    fn sample(function: &str) -> HeartbeatSample
+    {
+        HeartbeatSample::new(Address::from(0x1_8000_0000), Some(function))
+    }
+
+    /// Feed `count` samples in `function`, returning what the last one asked for.
+    fn feed(detector: &mut HangDetector, function: &str, count: usize) -> Probe
+    {
+        (0..count).map(|_| detector.observe(sample(function))).last().unwrap()
+    }
+
+    const RUNLOOP: &[&str] = &[
+        "mach_msg2_trap",
+        "mach_msg2_internal",
+        "mach_msg_overwrite",
+        "mach_msg",
+        "__CFRunLoopServiceMachPort",
+        "__CFRunLoopRun",
+        "CFRunLoopRunSpecific",
+        "-[NSApplication run]",
+        "NSApplicationMain",
+        "main",
+    ];
+
+    #[test]
+    fn a_long_wait_in_the_runloop_park_is_not_a_hang()
+    {
+        let mut detector = HangDetector::new(4);
+        assert_eq!(feed(&mut detector, "mach_msg2_trap", 3), Probe::Quiet);
+        assert_eq!(detector.observe(sample("mach_msg2_trap")), Probe::Unwind);
+        assert!(detector.judge(stack(RUNLOOP)).is_none());
+
+        // Judged once for the whole run, however long it lasts
+        assert_eq!(feed(&mut detector, "mach_msg2_trap", 100), Probe::Quiet);
+        // Leaving the park isn't a recovery
+        assert_eq!(detector.observe(sample("-[AppDelegate tick:]")), Probe::Quiet);
+    }
+
+    #[test]
+    fn a_mach_msg_outside_the_runloop_is_a_hang()
+    {
+        let mut detector = HangDetector::new(3);
+        assert_eq!(feed(&mut detector, "mach_msg2_trap", 3), Probe::Unwind);
+        let hang = detector
+            .judge(stack(&[
+                "mach_msg2_trap",
+                "mach_msg2_internal",
+                "mach_msg",
+                "xpc_connection_send_message_with_reply_sync",
+                "-[AppDelegate refresh:]",
+                "__CFRunLoopDoTimer",
+                "__CFRunLoopRun",
+            ]))
+            .expect("hang");
+        assert_eq!(hang.blocked_in.as_deref(), Some("mach_msg2_trap"));
+        assert_eq!(hang.samples, 3);
+        assert_eq!(
+            hang.summary(Duration::from_millis(250)),
+            "Main thread blocked in mach_msg2_trap for ~500.0ms (3 samples): mach_msg2_trap <- mach_msg2_internal <- \
+             mach_msg <- xpc_connection_send_message_with_reply_sync <- -[AppDelegate refresh:] <- __CFRunLoopDoTimer <- \
+             __CFRunLoopRun"
+        );
+
+        // Reported once, then a recovery when the PC moves on
+        assert_eq!(feed(&mut detector, "mach_msg2_trap", 5), Probe::Quiet);
+        assert_eq!(
+            detector.observe(sample("-[AppDelegate refresh:]")),
+            Probe::Recovered {
+                function: "mach_msg2_trap".to_string(),
+                samples: 8,
+            }
+        );
+        // The new run starts from scratch
+        assert_eq!(feed(&mut detector, "-[AppDelegate refresh:]", 2), Probe::Unwind);
+    }
+
+    #[test]
+    fn a_lock_wait_or_busy_loop_is_a_hang()
+    {
+        let mut detector = HangDetector::new(2);
+        assert_eq!(feed(&mut detector, "__psynch_mutexwait", 2), Probe::Unwind);
+        let hang = detector
+            .judge(stack(&[
+                "__psynch_mutexwait",
+                "_pthread_mutex_firstfit_lock_wait",
+                "-[Model save]",
+            ]))
+            .expect("hang");
+        assert_eq!(hang.blocked_in.as_deref(), Some("__psynch_mutexwait"));
+
+        detector.reset();
+        assert_eq!(feed(&mut detector, "app::layout::solve", 2), Probe::Unwind);
+        let hang = detector.judge(stack(&["app::layout::solve", "main"])).expect("hang");
+        assert_eq!(hang.blocked_in, None);
+        assert!(
+            hang.summary(Duration::from_secs(1))
+                .starts_with("Main thread busy in app::layout::solve for ~1.0s (2 samples): ")
+        );
+    }
+
+    #[test]
+    fn moving_between_functions_never_asks_for_an_unwind()
+    {
+        let mut detector = HangDetector::new(3);
+        for _ in 0..10 {
+            assert_eq!(feed(&mut detector, "mach_msg2_trap", 2), Probe::Quiet);
+            assert_eq!(feed(&mut detector, "-[NSView display]", 2), Probe::Quiet);
+        }
+    }
+
+    #[test]
+    fn unsymbolicated_samples_group_by_pc_and_a_failed_unwind_is_a_hang()
+    {
+        let mut detector = HangDetector::new(2);
+        let at = |pc| HeartbeatSample::new(Address::from(pc), None);
+        assert_eq!(detector.observe(at(0x4000)), Probe::Quiet);
+        assert_eq!(detector.observe(at(0x4004)), Probe::Quiet);
+        assert_eq!(detector.observe(at(0x4004)), Probe::Unwind);
+        let hang = detector.judge(Vec::new()).expect("hang");
+        assert_eq!(hang.function, "0x4004");
+        assert!(
+            hang.summary(Duration::from_millis(100))
+                .ends_with("(2 samples); no backtrace")
+        );
+    }
+
+    #[test]
+    fn mach_msg_without_the_park_frame_is_not_the_park()
+    {
+        assert!(is_runloop_park(&stack(RUNLOOP)));
+        assert!(!is_runloop_park(&stack(&["__CFRunLoopServiceMachPort", "__CFRunLoopRun"])));
+        assert!(!is_runloop_park(&stack(&["mach_msg2_trap", "mach_msg", "-[Worker wait]"])));
+        assert!(!is_runloop_park(&[]));
+    }
+}
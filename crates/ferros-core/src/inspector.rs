@@ -0,0 +1,937 @@
+//! # Target Inspector
+//!
+//! Read-only access to a stopped target.
+//!
+//! Crash and hang reports, table renderers, pins and region checkpoints only
+//! look at a target: they read memory, registers, threads and breakpoints but
+//! never resume, write or set anything. [`TargetInspector`] is that subset of
+//! [`Debugger`], which extends it, so those tools take a
+//! `&dyn TargetInspector` and work the same against a live debugger, a saved
+//! snapshot or a remote backend that only implements this trait.
+//!
+//! Front-ends reach the target only through these traits, never through a
+//! backend type, so they work unchanged on any backend. Features some
+//! backends can't provide (breakpoints, thread control, thread-locals) are
+//! checked with [`TargetInspector::capabilities`] first.
+//!
+//! A `&dyn Debugger` converts to a `&dyn TargetInspector` directly. Stack
+//! traces are the exception: unwinding a live target switches its active
+//! thread, so [`TargetInspector::backtrace`] is only supported through the
+//! [`LiveInspector`] adapter or by implementations that already hold their
+//! stacks.
+//!
+//! ```rust
+//! use ferros_core::inspector::TargetInspector;
+//!
+//! fn describe(target: &dyn TargetInspector) -> String
+//! {
+//!     let threads = target.threads().map_or(0, |threads| threads.len());
+//!     format!("{} target with {threads} thread(s)", target.architecture())
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::breakpoints::{BreakpointCounts, BreakpointId, BreakpointInfo};
+use crate::debugger::Debugger;
+use crate::error::{DebuggerError, Result};
+use crate::range_checkpoints::{self, RangeDiff, RangeSnapshot, WatchedRange};
+use crate::stream::{ReadStats, StreamOptions, stream_memory};
+use crate::symbols::{DebugLevel, TypeSummary};
+use crate::types::{
+    Address, Architecture, MemoryCacheStats, MemoryRegion, Registers, SourceLocation, StackFrame, StopReason, SymbolName,
+    ThreadId, ThreadInfo,
+};
+
+/// Optional features of a backend
+///
+/// Everything on [`TargetInspector`] without a default works on every
+/// backend; these don't. Front-ends check them before offering a feature
+/// rather than assuming what the macOS backend can do. The default from
+/// [`TargetInspector::capabilities`] is [`Capabilities::NONE`], so a new
+/// backend opts in to each feature as it implements it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities
+{
+    /// Software and hardware breakpoints
+    pub breakpoints: bool,
+    /// Hardware watchpoints
+    pub watchpoints: bool,
+    /// Suspending and resuming single threads
+    pub thread_control: bool,
+    /// Reading thread-local variables
+    pub thread_locals: bool,
+}
+
+impl Capabilities
+{
+    /// A target that only answers the required reads
+    pub const NONE: Self = Self {
+        breakpoints: false,
+        watchpoints: false,
+        thread_control: false,
+        thread_locals: false,
+    };
+    /// Every optional feature
+    pub const ALL: Self = Self {
+        breakpoints: true,
+        watchpoints: true,
+        thread_control: true,
+        thread_locals: true,
+    };
+}
+
+/// Read-only view of a debug target
+///
+/// Every method takes `&self` and leaves the target as it found it. Backends
+/// implement this alongside [`Debugger`]; snapshots and other targets that
+/// can't be controlled implement only this.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use ferros_core::debugger::create_debugger;
+/// use ferros_core::inspector::TargetInspector;
+/// use ferros_core::types::ProcessId;
+///
+/// let mut debugger = create_debugger()?;
+/// debugger.attach(ProcessId::from(12345))?;
+/// let target: &dyn TargetInspector = debugger.as_ref();
+/// println!("{} regions", target.get_memory_regions()?.len());
+/// # Ok::<(), ferros_core::error::DebuggerError>(())
+/// ```
+pub trait TargetInspector
+{
+    /// Get the CPU architecture of the debug target
+    ///
+    /// Returns the architecture of the process being debugged. This is typically
+    /// determined when attaching to the process, though some platforms may detect
+    /// it earlier.
+    ///
+    /// ## Platform-Specific Behavior
+    ///
+    /// - **macOS**: Uses the architecture of the debugger binary as a hint, but
+    ///   the actual target process architecture may differ (e.g., debugging x86-64
+    ///   process from ARM64 debugger)
+    /// - **Linux**: Detected from `/proc/[pid]/exe` or ELF headers
+    /// - **Windows**: Detected from PE headers
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::types::Architecture;
+    /// use ferros_core::{Debugger, TargetInspector};
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
+    /// match debugger.architecture() {
+    ///     Architecture::Arm64 => println!("Debugging ARM64 process"),
+    ///     Architecture::X86_64 => println!("Debugging x86-64 process"),
+    ///     Architecture::Unknown(name) => println!("Unknown architecture: {}", name),
+    /// }
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn architecture(&self) -> Architecture;
+
+    /// Check whether the debugger is currently attached to a process
+    ///
+    /// Returns `true` if `attach()` has been called successfully and the debugger
+    /// is still connected to the target process. Returns `false` if:
+    /// - The debugger was never attached
+    /// - The debugger was detached via `detach()`
+    /// - The target process has exited
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::{Debugger, TargetInspector};
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// if !debugger.is_attached() {
+    ///     debugger.attach(ferros_core::types::ProcessId::from(12345))?;
+    /// }
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn is_attached(&self) -> bool;
+
+    /// Check whether the debuggee is currently stopped/suspended
+    ///
+    /// Returns `true` if the target process is currently stopped (suspended,
+    /// hit a breakpoint, received a signal, etc.). Returns `false` if the process
+    /// is running.
+    ///
+    /// ## Relationship to `stop_reason()`
+    ///
+    /// - `is_stopped() == true` → `stop_reason()` is not `StopReason::Running`
+    /// - `is_stopped() == false` → `stop_reason()` is `StopReason::Running`
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::{Debugger, TargetInspector};
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
+    /// if debugger.is_stopped() {
+    ///     println!("Process is stopped, can inspect registers/memory");
+    /// } else {
+    ///     println!("Process is running");
+    /// }
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn is_stopped(&self) -> bool;
+
+    /// Get the most recent stop reason
+    ///
+    /// Returns the reason why the process is currently stopped (if at all).
+    /// This can be used to determine what action to take next:
+    ///
+    /// - `StopReason::Running`: Process is running (not stopped)
+    /// - `StopReason::Suspended`: Process was explicitly suspended
+    /// - `StopReason::Signal(n)`: Process received a signal
+    /// - `StopReason::BadAccess { .. }`: A thread faulted on an address
+    /// - `StopReason::Breakpoint(addr)`: Process hit a breakpoint
+    /// - `StopReason::Exited(code)`: Process has exited
+    /// - `StopReason::Unknown`: Unknown reason
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::types::StopReason;
+    /// use ferros_core::{Debugger, TargetInspector};
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
+    /// match debugger.stop_reason() {
+    ///     StopReason::Breakpoint(addr) => {
+    ///         println!("Hit breakpoint at 0x{:x}", addr);
+    ///         // Inspect registers, memory, etc.
+    ///     }
+    ///     StopReason::Signal(sig) => {
+    ///         println!("Stopped by signal: {}", sig);
+    ///     }
+    ///     _ => {}
+    /// }
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn stop_reason(&self) -> StopReason;
+
+    /// List all available threads in the target process
+    ///
+    /// Returns a vector of `ThreadId` values representing all threads currently
+    /// in the target process. This list is cached and may become stale if threads
+    /// are created or destroyed. Call `refresh_threads()` to update it.
+    ///
+    /// ## Platform-Specific Behavior
+    ///
+    /// - **macOS**: Enumerates thread ports with `task_threads()` and returns each
+    ///   thread's stable global id (see [`ThreadId`])
+    ///   - See: [task_threads documentation](https://developer.apple.com/documentation/kernel/1537751-task_threads/)
+    /// - **Linux**: Will parse `/proc/[pid]/task/` directory
+    /// - **Windows**: Will use `CreateToolhelp32Snapshot()` with `TH32CS_SNAPTHREAD`
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `AttachFailed`: Failed to enumerate threads
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::{Debugger, TargetInspector};
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
+    /// let threads = debugger.threads()?;
+    /// println!("Process has {} threads", threads.len());
+    /// for thread in threads {
+    ///     println!("Thread: {}", thread.raw());
+    /// }
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn threads(&self) -> Result<Vec<ThreadId>>;
+
+    /// Get the currently selected active thread (if any)
+    ///
+    /// Returns `Some(thread_id)` if an active thread has been selected via
+    /// `set_active_thread()`, or `None` if no thread is selected. The active
+    /// thread is used for register operations (`read_registers()`, `write_registers()`).
+    ///
+    /// ## Default Behavior
+    ///
+    /// When attaching to a process, the first thread (typically the main thread)
+    /// is automatically selected as the active thread.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::{Debugger, TargetInspector};
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
+    /// if let Some(thread) = debugger.active_thread() {
+    ///     println!("Active thread: {}", thread.raw());
+    /// } else {
+    ///     println!("No active thread selected");
+    /// }
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn active_thread(&self) -> Option<ThreadId>;
+
+    /// Number of outstanding `suspend_thread()` calls this debugger has made on `thread`
+    ///
+    /// This only counts suspensions issued by the debugger itself; suspensions made by
+    /// the target (or by whole-task `suspend()`) are not included. Returns 0 for
+    /// unknown threads and for backends without per-thread control.
+    fn thread_suspend_count(&self, _thread: ThreadId) -> u32
+    {
+        0
+    }
+
+    /// Name, run state, suspend count and CPU use of `thread`
+    ///
+    /// ## Platform-specific behavior
+    ///
+    /// - **macOS**: Calls `thread_info()` with `THREAD_EXTENDED_INFO` and `THREAD_BASIC_INFO`
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `InvalidArgument`: The thread isn't in the current thread list, or the
+    ///   backend can't describe threads
+    fn thread_info(&self, _thread: ThreadId) -> Result<ThreadInfo>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Thread details are not supported by this backend".to_string(),
+        ))
+    }
+
+    /// The thread the process started on
+    ///
+    /// The default is the first thread `threads()` lists, which is the main
+    /// thread on every kernel ferros supports; backends that can tell for
+    /// sure override it. Returns `None` if the threads can't be listed.
+    fn main_thread(&self) -> Option<ThreadId>
+    {
+        self.threads().ok()?.first().copied()
+    }
+
+    /// Read registers from the attached process
+    ///
+    /// Reads the current values of all CPU registers from the target process.
+    /// This gives you a snapshot of the process's execution state.
+    ///
+    /// ## Platform-specific behavior
+    ///
+    /// - **macOS**: Calls `thread_get_state()` with `ARM_THREAD_STATE64` or `X86_THREAD_STATE64`
+    /// - **Linux**: Will call `ptrace(PTRACE_GETREGS, pid)`
+    /// - **Windows**: Will call `GetThreadContext()`
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `ReadRegistersFailed`: Failed to read thread state
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::TargetInspector;
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// let regs = debugger.read_registers()?;
+    /// println!("Program counter: {}", regs.pc);
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn read_registers(&self) -> Result<Registers>;
+
+    /// Read registers for a specific thread without changing the active thread.
+    ///
+    /// Default implementation returns `InvalidArgument` if the backend does not support
+    /// per-thread register access without switching the active thread.
+    fn read_registers_for(&self, _thread: ThreadId) -> Result<Registers>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Per-thread register access not supported for this debugger".to_string(),
+        ))
+    }
+
+    /// Read memory from the target process
+    ///
+    /// Reads `len` bytes starting at the given address from the attached process.
+    /// Returns a vector containing the read bytes.
+    ///
+    /// ## Platform-specific behavior
+    ///
+    /// - **macOS**: Uses `vm_read()` to read memory from the Mach task
+    /// - **Linux**: Uses `ptrace(PTRACE_PEEKDATA)` in a loop to read word-aligned data
+    /// - **Windows**: Uses `ReadProcessMemory()`
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `InvalidArgument`: Invalid memory address or length
+    /// - `Io`: Failed to read memory (e.g., invalid address, permission denied)
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::{Address, Debugger, TargetInspector};
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
+    /// let data = debugger.read_memory(Address::from(0x1000), 16)?;
+    /// println!("Read {} bytes: {:?}", data.len(), data);
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>;
+
+    /// Read memory while the target keeps running
+    ///
+    /// Unlike [`read_memory`](Self::read_memory) this neither suspends the target
+    /// nor goes through the page cache, so repeated reads observe the target's
+    /// writes. The result may be torn if the target writes the range concurrently.
+    ///
+    /// The default implementation forwards to [`read_memory`](Self::read_memory).
+    fn read_memory_live(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self.read_memory(addr, len)
+    }
+
+    /// Read memory as the program's own code sees it
+    ///
+    /// Software breakpoints patch trap instructions into the code; this shows
+    /// the instructions they replaced instead, for anything that decodes or
+    /// dumps code. Streamed reads get the same view from
+    /// [`StreamOptions::logical`].
+    ///
+    /// The default implementation forwards to [`read_memory`](Self::read_memory),
+    /// for targets without software breakpoints.
+    fn read_memory_logical(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self.read_memory(addr, len)
+    }
+
+    /// Stream a large memory range to `sink` in chunks of at most `chunk_size` bytes
+    ///
+    /// At most one chunk is buffered at a time. Unreadable pages are written
+    /// as the options' fill byte and listed as holes in the returned
+    /// [`ReadStats`]; the options also carry the progress callback (which can
+    /// cancel the read) and an optional bytes-per-second limit. See
+    /// [`stream`](crate::stream).
+    ///
+    /// The default implementation reads each chunk with
+    /// [`read_memory`](Self::read_memory), or with
+    /// [`read_memory_logical`](Self::read_memory_logical) when the options
+    /// ask for the logical view.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: `chunk_size` is zero or the range is reversed
+    /// - `Io`: The sink failed
+    fn read_memory_streamed(
+        &self,
+        range: Range<Address>,
+        chunk_size: usize,
+        sink: &mut dyn Write,
+        options: &mut StreamOptions<'_>,
+    ) -> Result<ReadStats>
+    {
+        let logical = options.logical;
+        stream_memory(range, chunk_size, sink, options, |address, buffer| {
+            let bytes = if logical {
+                self.read_memory_logical(address, buffer.len())?
+            } else {
+                self.read_memory(address, buffer.len())?
+            };
+            let len = bytes.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&bytes[..len]);
+            Ok(len)
+        })
+    }
+
+    /// Read a little-endian `u64` at `addr`
+    ///
+    /// ## Errors
+    ///
+    /// Returns whatever [`read_memory`](Self::read_memory) returns, or
+    /// `InvalidArgument` if fewer than eight bytes were read.
+    fn read_u64(&self, addr: Address) -> Result<u64>
+    {
+        Ok(u64::from_le_bytes(read_exact(self.read_memory(addr, 8)?, addr)?))
+    }
+
+    /// Read a little-endian `u32` at `addr`
+    ///
+    /// ## Errors
+    ///
+    /// Returns whatever [`read_memory`](Self::read_memory) returns, or
+    /// `InvalidArgument` if fewer than four bytes were read.
+    fn read_u32(&self, addr: Address) -> Result<u32>
+    {
+        Ok(u32::from_le_bytes(read_exact(self.read_memory(addr, 4)?, addr)?))
+    }
+
+    /// Get memory regions for the attached process
+    ///
+    /// Returns a list of all memory regions (segments) in the target process.
+    /// This includes code segments, data segments, stack, heap, and mapped files.
+    ///
+    /// Each region contains information about its address range, permissions
+    /// (read/write/execute), and optionally a name or description.
+    ///
+    /// ## Platform-specific behavior
+    ///
+    /// - **macOS**: Uses `mach_vm_region()` to enumerate memory regions
+    /// - **Linux**: Parses `/proc/[pid]/maps` file
+    /// - **Windows**: Uses `VirtualQueryEx()` to enumerate memory regions
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `Io`: Failed to read memory map information
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use ferros_core::{Debugger, TargetInspector};
+    ///
+    /// # let mut debugger = ferros_core::debugger::create_debugger()?;
+    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
+    /// let regions = debugger.get_memory_regions()?;
+    /// for region in regions {
+    ///     println!(
+    ///         "{}-{} {} {:?}",
+    ///         region.start, region.end, region.permissions, region.name
+    ///     );
+    /// }
+    /// # Ok::<(), ferros_core::error::DebuggerError>(())
+    /// ```
+    fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>;
+
+    /// Hit and miss counters of the backend's memory cache
+    ///
+    /// `None` for backends that read the target directly, which is the default.
+    fn memory_cache_stats(&self) -> Option<MemoryCacheStats>
+    {
+        None
+    }
+
+    /// Capture the current bytes of `ranges` for a later [`diff_ranges`](Self::diff_ranges)
+    ///
+    /// Reads go through [`read_memory_streamed`](Self::read_memory_streamed);
+    /// unreadable pages are recorded as holes. See
+    /// [`range_checkpoints`](crate::range_checkpoints).
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: The ranges exceed
+    ///   [`MAX_CHECKPOINT_BYTES`](crate::range_checkpoints::MAX_CHECKPOINT_BYTES)
+    fn capture_ranges(&self, ranges: &[WatchedRange]) -> Result<Vec<RangeSnapshot>>
+    {
+        range_checkpoints::capture(self, ranges)
+    }
+
+    /// Compare `snapshots` from [`capture_ranges`](Self::capture_ranges) with memory as it is now
+    ///
+    /// The ranges are re-read in chunks and compared as they arrive, so only
+    /// the snapshots and one chunk are held at a time.
+    ///
+    /// ## Errors
+    ///
+    /// Returns whatever [`read_memory_streamed`](Self::read_memory_streamed) returns.
+    fn diff_ranges(&self, snapshots: &[RangeSnapshot]) -> Result<Vec<RangeDiff>>
+    {
+        range_checkpoints::diff(self, snapshots)
+    }
+
+    /// List all known breakpoints.
+    fn breakpoints(&self) -> Vec<BreakpointInfo>
+    {
+        Vec::new()
+    }
+
+    /// Number of user breakpoints in each state.
+    ///
+    /// The default counts [`breakpoints`](Self::breakpoints); backends that
+    /// keep a store override it to count without cloning every entry.
+    fn breakpoint_counts(&self) -> BreakpointCounts
+    {
+        BreakpointCounts::of(&self.breakpoints())
+    }
+
+    /// Query information about a specific breakpoint.
+    fn breakpoint_info(&self, _id: BreakpointId) -> Result<BreakpointInfo>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Breakpoints are not supported on this debugger".to_string(),
+        ))
+    }
+
+    /// How much debug information the image containing `address` carries
+    ///
+    /// Front-ends use it to explain missing source lines and refuse
+    /// line-based breakpoints in images without DWARF. `None` means the
+    /// address is in no loaded image, or the target doesn't track images.
+    fn debug_level(&self, _address: Address) -> Option<DebugLevel>
+    {
+        None
+    }
+
+    /// Start of the function containing `address`
+    ///
+    /// Front-ends show code addresses as `symbol+offset` with it. `None`
+    /// means no loaded image has a symbol at or below the address.
+    fn function_start(&self, _address: Address) -> Option<Address>
+    {
+        None
+    }
+
+    /// Name of the function containing `address`
+    ///
+    /// The symbolication hook for annotating code addresses; unlike a stack
+    /// trace it needs no unwinding. Inlined calls are not expanded: this is
+    /// the outermost function, the one [`function_start`](Self::function_start)
+    /// finds.
+    fn function_name(&self, _address: Address) -> Option<SymbolName>
+    {
+        None
+    }
+
+    /// Source line `address` was compiled from
+    ///
+    /// For an inlined call this is the innermost line, the one a `file:line`
+    /// breakpoint on it resolves to. `None` means the image has no line
+    /// information for the address, or the target doesn't track images.
+    fn source_location(&self, _address: Address) -> Option<SourceLocation>
+    {
+        None
+    }
+
+    /// Layout of the type named `name`, from the first loaded image that describes it
+    ///
+    /// What [`render_value`](crate::symbols::render::render_value) interprets
+    /// memory with. `None` means no image has DWARF for the type, or the
+    /// target doesn't track images.
+    fn describe_type(&self, _name: &str) -> Option<Arc<TypeSummary>>
+    {
+        None
+    }
+
+    /// Optional features this target supports
+    ///
+    /// See [`Capabilities`]; the default supports none of them.
+    fn capabilities(&self) -> Capabilities
+    {
+        Capabilities::NONE
+    }
+
+    /// Call stack of `thread`, innermost frame first, at most `max_frames` long
+    ///
+    /// The default implementation returns `InvalidArgument`: a live backend
+    /// can only unwind by switching its active thread, which needs
+    /// [`LiveInspector`]. Snapshots return the stacks they recorded.
+    fn backtrace(&self, _thread: ThreadId, _max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "Stack traces need a LiveInspector on this target".to_string(),
+        ))
+    }
+}
+
+/// Live debugger seen as a [`TargetInspector`] that can unwind any thread
+///
+/// [`backtrace`](TargetInspector::backtrace) unwinds with
+/// [`Debugger::stack_trace_for`], which leaves the active thread as it was.
+/// Everything else forwards to the debugger unchanged.
+///
+/// ```rust,no_run
+/// use ferros_core::debugger::create_debugger;
+/// use ferros_core::inspector::{LiveInspector, TargetInspector};
+/// use ferros_core::types::ProcessId;
+///
+/// let mut debugger = create_debugger()?;
+/// debugger.attach(ProcessId::from(12345))?;
+/// let inspector = LiveInspector::new(debugger.as_mut());
+/// for thread in inspector.threads()? {
+///     println!(
+///         "{}: {} frames",
+///         thread.raw(),
+///         inspector.backtrace(thread, 64)?.len()
+///     );
+/// }
+/// # Ok::<(), ferros_core::error::DebuggerError>(())
+/// ```
+pub struct LiveInspector<'a>
+{
+    debugger: RefCell<&'a mut dyn Debugger>,
+}
+
+impl<'a> LiveInspector<'a>
+{
+    /// Inspect `debugger` for as long as the adapter is borrowed.
+    pub fn new(debugger: &'a mut dyn Debugger) -> Self
+    {
+        Self {
+            debugger: RefCell::new(debugger),
+        }
+    }
+}
+
+impl TargetInspector for LiveInspector<'_>
+{
+    fn architecture(&self) -> Architecture
+    {
+        self.debugger.borrow().architecture()
+    }
+
+    fn is_attached(&self) -> bool
+    {
+        self.debugger.borrow().is_attached()
+    }
+
+    fn is_stopped(&self) -> bool
+    {
+        self.debugger.borrow().is_stopped()
+    }
+
+    fn stop_reason(&self) -> StopReason
+    {
+        self.debugger.borrow().stop_reason()
+    }
+
+    fn threads(&self) -> Result<Vec<ThreadId>>
+    {
+        self.debugger.borrow().threads()
+    }
+
+    fn active_thread(&self) -> Option<ThreadId>
+    {
+        self.debugger.borrow().active_thread()
+    }
+
+    fn thread_suspend_count(&self, thread: ThreadId) -> u32
+    {
+        self.debugger.borrow().thread_suspend_count(thread)
+    }
+
+    fn thread_info(&self, thread: ThreadId) -> Result<ThreadInfo>
+    {
+        self.debugger.borrow().thread_info(thread)
+    }
+
+    fn main_thread(&self) -> Option<ThreadId>
+    {
+        self.debugger.borrow().main_thread()
+    }
+
+    fn read_registers(&self) -> Result<Registers>
+    {
+        self.debugger.borrow().read_registers()
+    }
+
+    fn read_registers_for(&self, thread: ThreadId) -> Result<Registers>
+    {
+        self.debugger.borrow().read_registers_for(thread)
+    }
+
+    fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self.debugger.borrow().read_memory(addr, len)
+    }
+
+    fn read_memory_live(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self.debugger.borrow().read_memory_live(addr, len)
+    }
+
+    fn read_memory_logical(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self.debugger.borrow().read_memory_logical(addr, len)
+    }
+
+    fn read_memory_streamed(
+        &self,
+        range: Range<Address>,
+        chunk_size: usize,
+        sink: &mut dyn Write,
+        options: &mut StreamOptions<'_>,
+    ) -> Result<ReadStats>
+    {
+        self.debugger.borrow().read_memory_streamed(range, chunk_size, sink, options)
+    }
+
+    fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+    {
+        self.debugger.borrow().get_memory_regions()
+    }
+
+    fn memory_cache_stats(&self) -> Option<MemoryCacheStats>
+    {
+        self.debugger.borrow().memory_cache_stats()
+    }
+
+    fn breakpoints(&self) -> Vec<BreakpointInfo>
+    {
+        self.debugger.borrow().breakpoints()
+    }
+
+    fn breakpoint_counts(&self) -> BreakpointCounts
+    {
+        self.debugger.borrow().breakpoint_counts()
+    }
+
+    fn breakpoint_info(&self, id: BreakpointId) -> Result<BreakpointInfo>
+    {
+        self.debugger.borrow().breakpoint_info(id)
+    }
+
+    fn debug_level(&self, address: Address) -> Option<DebugLevel>
+    {
+        self.debugger.borrow().debug_level(address)
+    }
+
+    fn function_start(&self, address: Address) -> Option<Address>
+    {
+        self.debugger.borrow().function_start(address)
+    }
+
+    fn function_name(&self, address: Address) -> Option<SymbolName>
+    {
+        self.debugger.borrow().function_name(address)
+    }
+
+    fn source_location(&self, address: Address) -> Option<SourceLocation>
+    {
+        self.debugger.borrow().source_location(address)
+    }
+
+    fn describe_type(&self, name: &str) -> Option<Arc<TypeSummary>>
+    {
+        self.debugger.borrow().describe_type(name)
+    }
+
+    fn capabilities(&self) -> Capabilities
+    {
+        self.debugger.borrow().capabilities()
+    }
+
+    fn backtrace(&self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        self.debugger.borrow_mut().stack_trace_for(thread, max_frames)
+    }
+}
+
+/// The first `N` bytes of `bytes`, read from `addr`
+fn read_exact<const N: usize>(bytes: Vec<u8>, addr: Address) -> Result<[u8; N]>
+{
+    bytes
+        .get(..N)
+        .and_then(|bytes| <[u8; N]>::try_from(bytes).ok())
+        .ok_or_else(|| DebuggerError::InvalidArgument(format!("Short read of {N} bytes at {addr}")))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Stopped target recorded in memory, with no way to control it.
+    struct Snapshot
+    {
+        memory: HashMap<u64, u8>,
+    }
+
+    impl TargetInspector for Snapshot
+    {
+        fn architecture(&self) -> Architecture
+        {
+            Architecture::Arm64
+        }
+
+        fn is_attached(&self) -> bool
+        {
+            false
+        }
+
+        fn is_stopped(&self) -> bool
+        {
+            true
+        }
+
+        fn stop_reason(&self) -> StopReason
+        {
+            StopReason::Suspended
+        }
+
+        fn threads(&self) -> Result<Vec<ThreadId>>
+        {
+            Ok(vec![ThreadId::from(1)])
+        }
+
+        fn active_thread(&self) -> Option<ThreadId>
+        {
+            Some(ThreadId::from(1))
+        }
+
+        fn read_registers(&self) -> Result<Registers>
+        {
+            Err(DebuggerError::NotAttached)
+        }
+
+        fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+        {
+            Ok((addr.value()..addr.value() + len as u64)
+                .map_while(|address| self.memory.get(&address).copied())
+                .collect())
+        }
+
+        fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+        {
+            Ok(Vec::new())
+        }
+    }
+
+    fn snapshot() -> Snapshot
+    {
+        Snapshot {
+            memory: (0..12).map(|offset| (0x1000 + offset, offset as u8 + 1)).collect(),
+        }
+    }
+
+    #[test]
+    fn inspector_is_object_safe_and_typed_reads_work_through_it()
+    {
+        let target: Box<dyn TargetInspector> = Box::new(snapshot());
+        assert_eq!(target.read_u64(Address::from(0x1000)).unwrap(), 0x0807_0605_0403_0201);
+        assert_eq!(target.read_u32(Address::from(0x1008)).unwrap(), 0x0c0b_0a09);
+        // Only four bytes are recorded past 0x1008
+        assert!(matches!(
+            target.read_u64(Address::from(0x1008)),
+            Err(DebuggerError::InvalidArgument(_))
+        ));
+        assert!(target.backtrace(ThreadId::from(1), 8).is_err());
+        assert!(target.breakpoints().is_empty());
+        // Optional features are off unless a backend claims them
+        assert_eq!(target.capabilities(), Capabilities::NONE);
+    }
+
+    #[test]
+    fn region_checkpoints_run_against_an_inspector_only_target()
+    {
+        let mut target = snapshot();
+        let ranges = [WatchedRange {
+            name: Some("buf".to_string()),
+            start: Address::from(0x1000),
+            len: 8,
+        }];
+        let snapshots = target.capture_ranges(&ranges).unwrap();
+        target.memory.insert(0x1003, 0xff);
+        let diffs = target.diff_ranges(&snapshots).unwrap();
+        assert_eq!(diffs[0].summary(), "buf: 1 of 8 bytes changed in 1 run");
+    }
+}
@@ -0,0 +1,214 @@
+//! Minimal instruction decoding.
+//!
+//! Stepping over a call only needs to know whether the instruction at the PC
+//! is a call and how long it is, so this recognises call instructions and
+//! nothing else; it isn't a disassembler.
+//!
+//! ## Recognised calls
+//!
+//! - **ARM64**: `BL`, `BLR` and the pointer-authenticating `BLRAA`, `BLRAAZ`,
+//!   `BLRAB`, `BLRABZ`; always 4 bytes
+//! - **x86-64**: `CALL rel32` (`E8`), and `CALL r/m64` / far `CALL m16:64`
+//!   (`FF /2`, `FF /3`) with any legacy or REX prefixes
+//!
+//! ```rust
+//! use ferros_core::instructions::decode_call;
+//! use ferros_core::types::{Address, Architecture};
+//!
+//! // bl #0x40
+//! let call = decode_call(Architecture::Arm64, &[0x10, 0x00, 0x00, 0x94]).unwrap();
+//! assert_eq!(
+//!     call.return_address(Address::from(0x1000)),
+//!     Address::from(0x1004)
+//! );
+//!
+//! // call qword ptr [rip + 0x2000]
+//! let call = decode_call(Architecture::X86_64, &[0xff, 0x15, 0x00, 0x20, 0x00, 0x00]).unwrap();
+//! assert_eq!(call.length, 6);
+//! ```
+
+use crate::types::{Address, Architecture};
+
+/// Longest x86-64 instruction, and so the most bytes [`decode_call`] looks at.
+pub const MAX_INSTRUCTION_LEN: usize = 15;
+
+/// ARM64 `BL imm26`.
+const ARM64_BL: (u32, u32) = (0xfc00_0000, 0x9400_0000);
+/// ARM64 `BLR Xn`.
+const ARM64_BLR: (u32, u32) = (0xffff_fc1f, 0xd63f_0000);
+/// ARM64 `BLRAAZ Xn` / `BLRABZ Xn`.
+const ARM64_BLRAZ: (u32, u32) = (0xffff_f81f, 0xd63f_081f);
+/// ARM64 `BLRAA Xn, Xm` / `BLRAB Xn, Xm`.
+const ARM64_BLRA: (u32, u32) = (0xffff_f800, 0xd73f_0800);
+
+/// A call instruction at the PC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Call
+{
+    /// Length of the instruction in bytes.
+    pub length: usize,
+}
+
+impl Call
+{
+    /// Where the callee returns to when the call is at `pc`.
+    #[must_use]
+    pub fn return_address(self, pc: Address) -> Address
+    {
+        pc + self.length as u64
+    }
+}
+
+/// How many bytes [`decode_call`] needs to recognise any call on `architecture`.
+#[must_use]
+pub fn max_call_len(architecture: Architecture) -> usize
+{
+    match architecture {
+        Architecture::X86_64 => MAX_INSTRUCTION_LEN,
+        Architecture::Arm64 | Architecture::Unknown(_) => 4,
+    }
+}
+
+/// The call instruction at the start of `code`, if there is one.
+///
+/// `code` should hold the original bytes at the PC, with any breakpoint traps
+/// replaced (see [`read_memory_logical`](crate::Debugger::read_memory_logical)).
+/// Returns `None` for anything that isn't a call, for bytes that end before
+/// the instruction does, and for unknown architectures.
+#[must_use]
+pub fn decode_call(architecture: Architecture, code: &[u8]) -> Option<Call>
+{
+    match architecture {
+        Architecture::Arm64 => decode_call_arm64(code),
+        Architecture::X86_64 => decode_call_x86_64(code),
+        Architecture::Unknown(_) => None,
+    }
+}
+
+fn decode_call_arm64(code: &[u8]) -> Option<Call>
+{
+    let word = u32::from_le_bytes(code.get(..4)?.try_into().ok()?);
+    [ARM64_BL, ARM64_BLR, ARM64_BLRAZ, ARM64_BLRA]
+        .iter()
+        .any(|&(mask, value)| word & mask == value)
+        .then_some(Call { length: 4 })
+}
+
+fn decode_call_x86_64(code: &[u8]) -> Option<Call>
+{
+    let mut at = 0;
+    // Legacy prefixes (lock, rep, segment, operand and address size), then at most one REX
+    while let Some(0xf0 | 0xf2 | 0xf3 | 0x2e | 0x36 | 0x3e | 0x26 | 0x64 | 0x65 | 0x66 | 0x67) = code.get(at) {
+        at += 1;
+    }
+    if let Some(0x40..=0x4f) = code.get(at) {
+        at += 1;
+    }
+
+    let length = match *code.get(at)? {
+        0xe8 => at + 5,
+        0xff => {
+            let modrm = *code.get(at + 1)?;
+            if !matches!((modrm >> 3) & 7, 2 | 3) {
+                return None;
+            }
+            at + 2 + modrm_operand_len(modrm, code.get(at + 2).copied())?
+        }
+        _ => return None,
+    };
+    (length <= code.len().min(MAX_INSTRUCTION_LEN)).then_some(Call { length })
+}
+
+/// Bytes after a ModRM byte taken by its SIB byte and displacement.
+///
+/// `sib` is the byte following the ModRM, if there is one.
+fn modrm_operand_len(modrm: u8, sib: Option<u8>) -> Option<usize>
+{
+    let (mode, rm) = (modrm >> 6, modrm & 7);
+    if mode == 3 {
+        return Some(0);
+    }
+    let sib_len = usize::from(rm == 4);
+    let displacement = match mode {
+        0 if rm == 5 => 4,
+        // A SIB byte with no base register is followed by a 32-bit displacement
+        0 if rm == 4 && sib? & 7 == 5 => 4,
+        0 => 0,
+        1 => 1,
+        _ => 4,
+    };
+    Some(sib_len + displacement)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn length(architecture: Architecture, code: &[u8]) -> Option<usize>
+    {
+        decode_call(architecture, code).map(|call| call.length)
+    }
+
+    #[test]
+    fn arm64_recognises_branch_with_link_forms_only()
+    {
+        let arm64 = |word: u32| length(Architecture::Arm64, &word.to_le_bytes());
+        // bl, bl backwards, blr x8, blraaz x16, blrabz x1, blraa x8, x9, blrab x17, sp
+        for word in [
+            0x9400_0010,
+            0x97ff_fff0,
+            0xd63f_0100,
+            0xd63f_0a1f,
+            0xd63f_0c3f,
+            0xd73f_0909,
+            0xd73f_0e3f,
+        ] {
+            assert_eq!(arm64(word), Some(4), "{word:#x}");
+        }
+        // b, br x8, ret, nop, braaz x16
+        for word in [0x1400_0010, 0xd61f_0100, 0xd65f_03c0, 0xd503_201f, 0xd61f_0a1f] {
+            assert_eq!(arm64(word), None, "{word:#x}");
+        }
+        assert_eq!(length(Architecture::Arm64, &[0x10, 0x00, 0x00]), None);
+    }
+
+    #[test]
+    fn x86_64_measures_calls_through_prefixes_and_addressing_modes()
+    {
+        let cases: Vec<(&[u8], usize)> = vec![
+            // call rel32
+            (&[0xe8, 0x10, 0x00, 0x00, 0x00], 5),
+            // call rax, call r11
+            (&[0xff, 0xd0], 2),
+            (&[0x41, 0xff, 0xd3], 3),
+            // call [rip + disp32], with a notrack prefix
+            (&[0xff, 0x15, 0x00, 0x20, 0x00, 0x00], 6),
+            (&[0x3e, 0xff, 0x15, 0x00, 0x20, 0x00, 0x00], 7),
+            // call [rax + 8], call [rax + 0x100]
+            (&[0xff, 0x50, 0x08], 3),
+            (&[0xff, 0x90, 0x00, 0x01, 0x00, 0x00], 6),
+            // call [rax + rbx*8], call [rbx*8 + disp32], call [rsp + 8]
+            (&[0xff, 0x14, 0xd8], 3),
+            (&[0xff, 0x14, 0xdd, 0x00, 0x10, 0x00, 0x00], 7),
+            (&[0xff, 0x54, 0x24, 0x08], 4),
+            // far call [rax]
+            (&[0x48, 0xff, 0x18], 3),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(length(Architecture::X86_64, code), Some(expected), "{code:02x?}");
+        }
+
+        // jmp rax, inc qword [rax], ret, jmp rel32, and a call cut short
+        let others: Vec<&[u8]> = vec![
+            &[0xff, 0xe0],
+            &[0x48, 0xff, 0x00],
+            &[0xc3],
+            &[0xe9, 0, 0, 0, 0],
+            &[0xe8, 0x10, 0x00],
+        ];
+        for code in others {
+            assert_eq!(length(Architecture::X86_64, code), None, "{code:02x?}");
+        }
+    }
+}
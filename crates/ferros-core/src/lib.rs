@@ -29,24 +29,60 @@
 #![allow(unsafe_code)] // Required for low-level system APIs (Mach, ptrace, etc.)
 #![warn(missing_docs)]
 
+pub mod assertions;
 pub mod breakpoints;
+pub mod checkpoints;
+pub mod crash;
 pub mod debugger;
+pub mod disassembly;
 pub mod error;
+pub mod eval;
 pub mod events;
+pub mod heartbeat;
+pub mod inspector;
+pub mod instructions;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod notes;
+pub mod operations;
+pub mod output;
+pub mod panics;
+pub mod permissions;
+pub mod pins;
 pub mod platform;
+pub mod processes;
+pub mod range_checkpoints;
+pub mod search;
+pub mod session;
+pub mod signals;
+pub mod snapshot;
+pub mod stream;
 pub mod symbols;
+pub mod transcript;
 pub mod types;
+pub mod watchdog;
 
-pub use breakpoints::{BreakpointId, BreakpointInfo, BreakpointKind, BreakpointRequest, BreakpointState, WatchpointAccess};
+pub use breakpoints::{
+    Breakpoint, BreakpointAction, BreakpointBuilder, BreakpointCounts, BreakpointHit, BreakpointId, BreakpointInfo,
+    BreakpointKind, BreakpointLocation, BreakpointOptions, BreakpointRequest, BreakpointRequestKind, BreakpointState,
+    BreakpointStats, WatchpointAccess,
+};
 pub use debugger::Debugger;
 // Re-export commonly used types
 pub use error::{DebuggerError, Result};
-pub use events::{DebuggerEvent, DebuggerEventReceiver, DebuggerEventSender, format_stop_reason};
+pub use events::{
+    DebuggerEvent, DebuggerEventReceiver, DebuggerEventSender, EventEnvelope, LatencyWindow, format_stop_reason,
+    wait_for_stop,
+};
+pub use inspector::{Capabilities, LiveInspector, TargetInspector};
 #[cfg(target_os = "macos")]
 pub use platform::macos::MacOSDebugger;
-pub use symbols::{SymbolCache, SymbolFrame, Symbolication, TypeField, TypeKind, TypeSummary, TypeVariant};
+pub use symbols::{
+    ImageInfo, RecursionCycle, StackTrace, SymbolCache, SymbolFrame, Symbolication, TypeField, TypeKind, TypeSummary,
+    TypeVariant, UnwindDiagnostic, UnwindOptions,
+};
 pub use types::{
     Address, Architecture, FloatingPointState, FrameId, FrameKind, FrameStatus, MemoryRegion, MemoryRegionId, ProcessId,
-    RegisterId, Registers, SourceLocation, StackFrame, StopReason, SymbolLanguage, SymbolName, ThreadId,
-    VectorRegisterValue,
+    RegisterId, Registers, SourceLocation, StackFrame, StopReason, SymbolLanguage, SymbolName, ThreadId, ThreadInfo,
+    ThreadRunState, VectorRegisterValue,
 };
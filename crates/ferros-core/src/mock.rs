@@ -0,0 +1,855 @@
+//! In-memory `Debugger` for the tests of ferros-core's frontends
+//!
+//! Built with the `mock` feature, which the UI, DAP and MI crates
+//! enable for their tests only. [`MockDebugger::with_threads`] gives an
+//! attached, stopped target whose every answer comes from [`MockState`];
+//! [`MockDebugger::scripted`] gives one that still has to be launched and,
+//! once resumed, replays a breakpoint hit → exit script over the event
+//! channel, the way an editor session sees a real target.
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::breakpoints::BreakpointRequestKind;
+use crate::error::{DebuggerError, Result};
+use crate::events::event_channel;
+use crate::permissions::PermissionAdvice;
+use crate::signals::{SignalPolicies, SignalPolicy};
+use crate::symbols::paths::SourceMap;
+use crate::symbols::unwind::return_frame;
+use crate::symbols::{DebugLevel, ImageInfo, ThreadLocalValue, ThreadLocalVariable, TlsLocation, TypeSummary};
+use crate::types::{
+    Address, Architecture, FrameId, FrameKind, FrameStatus, FunctionParameter, LaunchOptions, MemoryRegion, ProcessId,
+    Registers, SourceLocation, StackFrame, StopPolicy, StopReason, SymbolLanguage, SymbolName, ThreadId, ThreadInfo,
+};
+use crate::{
+    BreakpointId, BreakpointInfo, BreakpointLocation, BreakpointRequest, Capabilities, Debugger, DebuggerEvent,
+    DebuggerEventReceiver, DebuggerEventSender, EventEnvelope, StackTrace, TargetInspector, UnwindOptions,
+};
+
+/// Address `src/main.rs:12` resolves to in a [`scripted`](MockDebugger::scripted) mock
+pub const MAIN_LINE_12: u64 = 0x1000;
+/// Address `late_init` resolves to in a scripted mock, once the target has stopped once
+pub const LATE_INIT: u64 = 0x2000;
+
+/// Observable state shared between a test and the boxed mock
+#[derive(Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct MockState
+{
+    /// Threads of the target, in enumeration order
+    pub threads: Vec<ThreadId>,
+    /// Thread that `read_registers` and the stack trace belong to
+    pub active_thread: Option<ThreadId>,
+    /// Whether a target has been launched or attached to; `resolve_location` fails until then
+    pub attached: bool,
+    /// Whether the target is stopped
+    pub stopped: bool,
+    /// Set by `detach`
+    pub detached: bool,
+    /// Outstanding per-thread suspensions
+    pub suspend_counts: HashMap<ThreadId, u32>,
+    /// Returned by `thread_info`; listed threads without an entry have nothing known
+    pub thread_infos: HashMap<ThreadId, ThreadInfo>,
+    /// Threads whose per-thread suspend/resume calls should fail
+    pub failing_threads: Vec<ThreadId>,
+    /// Every per-thread call made, in order (`"suspend 2"`, `"resume 3"`)
+    pub calls: Vec<String>,
+    /// Trace returned by `stack_trace_with_options`
+    pub stack: StackTrace,
+    /// Options passed to `stack_trace_with_options`, in order
+    pub unwind_options: Vec<UnwindOptions>,
+    /// Frames `stack_trace_for` returns per thread; other threads get `stack`'s
+    pub thread_stacks: HashMap<ThreadId, Vec<StackFrame>>,
+    /// Mapped bytes by address; reads touching anything else fail
+    pub memory: HashMap<u64, u8>,
+    /// PC returned by `read_registers`
+    pub pc: u64,
+    /// PCs `read_registers_for` returns per thread; other threads get `pc`
+    pub thread_pcs: HashMap<ThreadId, u64>,
+    /// SP returned by `read_registers`
+    pub sp: u64,
+    /// General-purpose registers returned by `read_registers`
+    pub general: Vec<u64>,
+    /// Calls to `read_registers` and `read_registers_for`
+    pub register_reads: usize,
+    /// Returned by `get_memory_regions`
+    pub regions: Vec<MemoryRegion>,
+    /// `u64` thread-locals by qualified name and thread
+    pub thread_locals: HashMap<(String, ThreadId), u64>,
+    /// PID of a target that has died; calls fail with `TargetGone` once set
+    pub gone: Option<u32>,
+    /// Whether a call has reported `gone`, after which the mock is detached
+    pub gone_reported: bool,
+    /// Returned by `breakpoints`
+    pub breakpoints: Vec<BreakpointInfo>,
+    /// Requests passed to `add_breakpoint`, in order
+    pub breakpoint_requests: Vec<BreakpointRequest>,
+    /// Ids passed to `remove_breakpoint`, in order
+    pub removed: Vec<BreakpointId>,
+    /// When set, `add_breakpoint` is refused with `PermissionDenied` carrying this advice
+    pub breakpoint_denial: Option<PermissionAdvice>,
+    /// Images by address range, with the debug info `debug_level` reports for them
+    pub images: Vec<(std::ops::Range<u64>, DebugLevel)>,
+    /// Returned by `modules`
+    pub modules: Vec<ImageInfo>,
+    /// Function start addresses `function_start` picks the closest at or below from
+    pub function_starts: Vec<u64>,
+    /// Names `function_name` reports, by function start
+    pub function_names: HashMap<u64, String>,
+    /// Returned by `capabilities`; every feature when unset
+    pub capabilities: Option<Capabilities>,
+    /// Reported by `stop_reason` while stopped, instead of `Suspended`
+    pub stop_reason: Option<StopReason>,
+    /// Steps taken, of any kind
+    pub steps: usize,
+    /// Calls to `step_over`
+    pub steps_over: usize,
+    /// Addresses `resolve_location` returns for a `file:line`, and where `source_location` finds them;
+    /// other lines resolve to nothing
+    pub source_lines: HashMap<(String, u32), Vec<u64>>,
+    /// Layouts `describe_type` returns, by type name
+    pub types: HashMap<String, Arc<TypeSummary>>,
+    /// Policies set with `set_signal_policy`
+    pub signal_policies: SignalPolicies,
+    /// Policy set with `set_stop_policy`
+    pub stop_policy: StopPolicy,
+    /// Process passed to the last successful `attach`
+    pub attached_to: Option<ProcessId>,
+    /// When set, `attach` is refused with `PermissionDenied` carrying this advice
+    pub attach_denial: Option<PermissionAdvice>,
+    /// Programs and options passed to `launch_with_options`; the nth launch gets PID 1000 + n
+    pub launches: Vec<(String, LaunchOptions)>,
+    /// Calls to `kill`
+    pub kills: usize,
+    /// Returned by `pending_symbol_loads`
+    pub pending_symbol_loads: usize,
+    /// Paths passed to `add_dsym_search_path`, in order
+    pub dsym_search_paths: Vec<std::path::PathBuf>,
+    /// Set with `set_source_map`; applied to the frames `stack_trace_with_options` returns
+    pub source_map: SourceMap,
+    /// Returned by `code_lines` for every file; `None` fails it like a binary without DWARF
+    pub code_lines: Option<BTreeSet<u32>>,
+    /// When set, each `resume` publishes a stop: a hit of the first requested
+    /// software breakpoint while any hits remain, then an exit with status 0
+    pub hits_before_exit: Option<u32>,
+    /// Functions whose image loads at the first scripted stop; only then do
+    /// they join `function_names`
+    pub late_functions: Vec<(u64, String)>,
+}
+
+impl MockState
+{
+    /// Addresses of the software breakpoints requested so far, in order
+    #[must_use]
+    pub fn software_breakpoints(&self) -> Vec<Address>
+    {
+        self.breakpoint_requests
+            .iter()
+            .filter_map(|request| match request.kind {
+                BreakpointRequestKind::Software { address } => Some(address),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Physical frame `index` of thread `thread` in `function`, at `line` of `src/main.rs`
+///
+/// # Panics
+///
+/// Panics if `index` doesn't fit a `u32`.
+#[must_use]
+pub fn frame(thread: ThreadId, index: usize, function: &str, line: u32) -> StackFrame
+{
+    let pc = Address::from(0x1_0000_1000 + index as u64 * 0x40);
+    let sp = Address::from(0x7000_0000 + index as u64 * 0x100);
+    StackFrame {
+        id: FrameId::new(thread, u32::try_from(index).unwrap(), 0, pc, sp),
+        thread,
+        index,
+        kind: FrameKind::Physical,
+        pc,
+        sp,
+        fp: Address::ZERO,
+        return_address: None,
+        symbol: Some(SymbolName::new(
+            function.to_string(),
+            Some(function.to_string()),
+            SymbolLanguage::Rust,
+        )),
+        location: Some(SourceLocation {
+            file: "src/main.rs".to_string(),
+            line: Some(line),
+            column: None,
+            dwarf_file: None,
+        }),
+        parameters: Vec::new(),
+        status: FrameStatus::Complete,
+    }
+}
+
+/// Debugger whose every answer comes from a shared [`MockState`]
+pub struct MockDebugger
+{
+    /// State shared with the test that created the mock
+    pub state: Rc<RefCell<MockState>>,
+    /// Handed out by `take_event_receiver`; only scripted mocks have one
+    events: Option<DebuggerEventReceiver>,
+    sender: DebuggerEventSender,
+}
+
+impl MockDebugger
+{
+    /// Create an attached, stopped mock with threads `1..=count` and return it alongside its shared state
+    #[must_use]
+    pub fn with_threads(count: u64) -> (Box<dyn Debugger>, Rc<RefCell<MockState>>)
+    {
+        let threads: Vec<ThreadId> = (1..=count).map(ThreadId::from).collect();
+        let state = MockState {
+            active_thread: threads.first().copied(),
+            threads,
+            attached: true,
+            stopped: true,
+            ..MockState::default()
+        };
+        Self::boxed(state, false)
+    }
+
+    /// Create a mock that has to be launched, whose target hits its first breakpoint `hits` times before exiting
+    ///
+    /// `src/main.rs:12` resolves to [`MAIN_LINE_12`], where the stack is
+    /// `app::main` called from `std::rt::lang_start`, and `late_init`
+    /// resolves to [`LATE_INIT`] after the first stop.
+    #[must_use]
+    pub fn scripted(hits: u32) -> (Box<dyn Debugger>, Rc<RefCell<MockState>>)
+    {
+        let thread = ThreadId::from(1);
+        let frames = [(MAIN_LINE_12, "app::main", 12), (0x3000, "std::rt::lang_start", 100)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (pc, function, line))| StackFrame {
+                pc: Address::from(pc),
+                sp: Address::from(0x7000),
+                id: FrameId::new(
+                    thread,
+                    u32::try_from(index).unwrap(),
+                    0,
+                    Address::from(pc),
+                    Address::from(0x7000),
+                ),
+                parameters: vec![FunctionParameter {
+                    name: Some("argc".to_string()),
+                    type_name: Some("isize".to_string()),
+                    location: Some("x0".to_string()),
+                }],
+                ..frame(thread, index, function, line)
+            })
+            .collect();
+        let state = MockState {
+            threads: vec![thread],
+            active_thread: Some(thread),
+            pc: MAIN_LINE_12,
+            general: (1..=31).collect(),
+            stack: StackTrace {
+                frames,
+                ..StackTrace::default()
+            },
+            source_lines: HashMap::from([(("src/main.rs".to_string(), 12), vec![MAIN_LINE_12])]),
+            hits_before_exit: Some(hits),
+            late_functions: vec![(LATE_INIT, "late_init".to_string())],
+            ..MockState::default()
+        };
+        Self::boxed(state, true)
+    }
+
+    fn boxed(state: MockState, events: bool) -> (Box<dyn Debugger>, Rc<RefCell<MockState>>)
+    {
+        let state = Rc::new(RefCell::new(state));
+        let (sender, receiver) = event_channel();
+        (
+            Box::new(Self {
+                state: Rc::clone(&state),
+                events: events.then_some(receiver),
+                sender,
+            }),
+            state,
+        )
+    }
+
+    /// Publish the next stop of the script: a breakpoint hit while any remain, else the exit
+    fn play_script(&self, state: &mut MockState, hits: u32)
+    {
+        let _ = self.sender.send(EventEnvelope::new(DebuggerEvent::TargetResumed));
+        let reason = match state.software_breakpoints().first() {
+            Some(&address) if hits > 0 => {
+                state.hits_before_exit = Some(hits - 1);
+                state.pc = address.value();
+                StopReason::Breakpoint(address.value())
+            }
+            _ => StopReason::Exited(0),
+        };
+        state.stopped = true;
+        let late = std::mem::take(&mut state.late_functions);
+        state.function_names.extend(late);
+        let _ = self.sender.send(EventEnvelope::new(DebuggerEvent::TargetStopped {
+            reason,
+            thread: state.active_thread,
+        }));
+    }
+
+    fn per_thread_call(&self, verb: &str, thread: ThreadId) -> Result<()>
+    {
+        let mut state = self.state.borrow_mut();
+        state.calls.push(format!("{verb} {}", thread.raw()));
+        if !state.threads.contains(&thread) {
+            return Err(DebuggerError::ThreadNotFound(thread));
+        }
+        if state.failing_threads.contains(&thread) {
+            return Err(DebuggerError::InvalidArgument(format!("thread {} rejected", thread.raw())));
+        }
+        Ok(())
+    }
+
+    fn set_breakpoint_enabled(&self, id: BreakpointId, enabled: bool) -> Result<()>
+    {
+        let mut state = self.state.borrow_mut();
+        let info = state
+            .breakpoints
+            .iter_mut()
+            .find(|info| info.id == id)
+            .ok_or(DebuggerError::BreakpointNotFound(id))?;
+        info.enabled = enabled;
+        Ok(())
+    }
+
+    fn check_gone(&self) -> Result<()>
+    {
+        let mut state = self.state.borrow_mut();
+        match state.gone {
+            Some(pid) => {
+                state.gone_reported = true;
+                Err(DebuggerError::TargetGone { pid })
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl TargetInspector for MockDebugger
+{
+    fn read_registers(&self) -> Result<Registers>
+    {
+        self.check_gone()?;
+        self.state.borrow_mut().register_reads += 1;
+        let state = self.state.borrow();
+        let mut registers = Registers::new().with_arch(Architecture::Arm64);
+        registers.pc = Address::from(state.pc);
+        registers.sp = Address::from(state.sp);
+        registers.general.clone_from(&state.general);
+        Ok(registers)
+    }
+
+    fn read_registers_for(&self, thread: ThreadId) -> Result<Registers>
+    {
+        let mut registers = self.read_registers()?;
+        if let Some(&pc) = self.state.borrow().thread_pcs.get(&thread) {
+            registers.pc = Address::from(pc);
+        }
+        Ok(registers)
+    }
+
+    fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        let state = self.state.borrow();
+        if state.memory.is_empty() {
+            return Ok(vec![0; len]);
+        }
+        (addr.value()..addr.value() + len as u64)
+            .map(|a| state.memory.get(&a).copied())
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(DebuggerError::MemoryReadFailed {
+                address: addr,
+                length: len,
+                kern_return: None,
+            })
+    }
+
+    fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+    {
+        self.check_gone()?;
+        Ok(self.state.borrow().regions.clone())
+    }
+
+    fn architecture(&self) -> Architecture
+    {
+        Architecture::Arm64
+    }
+
+    fn is_attached(&self) -> bool
+    {
+        !self.state.borrow().gone_reported
+    }
+
+    fn is_stopped(&self) -> bool
+    {
+        self.state.borrow().stopped
+    }
+
+    fn stop_reason(&self) -> StopReason
+    {
+        if self.is_stopped() {
+            self.state.borrow().stop_reason.unwrap_or(StopReason::Suspended)
+        } else {
+            StopReason::Running
+        }
+    }
+
+    fn threads(&self) -> Result<Vec<ThreadId>>
+    {
+        self.check_gone()?;
+        Ok(self.state.borrow().threads.clone())
+    }
+
+    fn active_thread(&self) -> Option<ThreadId>
+    {
+        self.state.borrow().active_thread
+    }
+
+    fn breakpoints(&self) -> Vec<BreakpointInfo>
+    {
+        self.state.borrow().breakpoints.clone()
+    }
+
+    fn thread_suspend_count(&self, thread: ThreadId) -> u32
+    {
+        self.state.borrow().suspend_counts.get(&thread).copied().unwrap_or(0)
+    }
+
+    fn thread_info(&self, thread: ThreadId) -> Result<ThreadInfo>
+    {
+        let state = self.state.borrow();
+        if let Some(info) = state.thread_infos.get(&thread) {
+            return Ok(info.clone());
+        }
+        if state.threads.contains(&thread) {
+            Ok(ThreadInfo::new(thread))
+        } else {
+            Err(DebuggerError::ThreadNotFound(thread))
+        }
+    }
+
+    fn debug_level(&self, address: Address) -> Option<DebugLevel>
+    {
+        let state = self.state.borrow();
+        state
+            .images
+            .iter()
+            .find(|(range, _)| range.contains(&address.value()))
+            .map(|&(_, level)| level)
+    }
+
+    fn function_start(&self, address: Address) -> Option<Address>
+    {
+        let state = self.state.borrow();
+        state
+            .function_starts
+            .iter()
+            .filter(|&&start| start <= address.value())
+            .max()
+            .map(|&start| Address::from(start))
+    }
+
+    fn function_name(&self, address: Address) -> Option<SymbolName>
+    {
+        let start = self.function_start(address)?;
+        let name = self.state.borrow().function_names.get(&start.value())?.clone();
+        Some(SymbolName::new(name.clone(), Some(name), SymbolLanguage::Rust))
+    }
+
+    fn source_location(&self, address: Address) -> Option<SourceLocation>
+    {
+        let state = self.state.borrow();
+        let ((file, line), _) = state
+            .source_lines
+            .iter()
+            .find(|(_, addresses)| addresses.contains(&address.value()))?;
+        Some(SourceLocation {
+            line: Some(*line),
+            ..SourceLocation::from_file(file.clone())
+        })
+    }
+
+    fn describe_type(&self, name: &str) -> Option<Arc<TypeSummary>>
+    {
+        self.state.borrow().types.get(name).cloned()
+    }
+
+    fn capabilities(&self) -> Capabilities
+    {
+        self.state.borrow().capabilities.unwrap_or(Capabilities::ALL)
+    }
+}
+
+impl Debugger for MockDebugger
+{
+    fn take_event_receiver(&mut self) -> Option<DebuggerEventReceiver>
+    {
+        self.events.take()
+    }
+
+    fn launch_with_options(&mut self, program: &str, options: &LaunchOptions) -> Result<ProcessId>
+    {
+        let mut state = self.state.borrow_mut();
+        state.launches.push((program.to_string(), options.clone()));
+        state.attached = true;
+        state.gone = None;
+        state.gone_reported = false;
+        state.stopped = true;
+        Ok(ProcessId::from(1000 + u32::try_from(state.launches.len()).unwrap()))
+    }
+
+    fn attach(&mut self, pid: ProcessId) -> Result<()>
+    {
+        let mut state = self.state.borrow_mut();
+        if let Some(advice) = &state.attach_denial {
+            return Err(DebuggerError::permission_denied(
+                "task_for_pid() refused",
+                Some(advice.clone()),
+            ));
+        }
+        state.attached_to = Some(pid);
+        state.attached = true;
+        Ok(())
+    }
+
+    fn detach(&mut self) -> Result<()>
+    {
+        self.state.borrow_mut().detached = true;
+        Ok(())
+    }
+
+    fn kill(&mut self) -> Result<()>
+    {
+        self.check_gone()?;
+        let mut state = self.state.borrow_mut();
+        state.kills += 1;
+        // Its breakpoints went with it
+        state.breakpoints.clear();
+        state.gone_reported = true;
+        state.stopped = false;
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &Registers) -> Result<()>
+    {
+        self.check_gone()?;
+        let mut state = self.state.borrow_mut();
+        state.pc = regs.pc.value();
+        state.sp = regs.sp.value();
+        state.general.clone_from(&regs.general);
+        Ok(())
+    }
+
+    fn write_memory(&mut self, addr: Address, data: &[u8]) -> Result<usize>
+    {
+        // Only a test that maps memory reads back what it wrote
+        let mut state = self.state.borrow_mut();
+        if !state.memory.is_empty() {
+            state.memory.extend((addr.value()..).zip(data.iter().copied()));
+        }
+        Ok(data.len())
+    }
+
+    fn suspend(&mut self) -> Result<()>
+    {
+        self.check_gone()?;
+        self.state.borrow_mut().stopped = true;
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<()>
+    {
+        self.check_gone()?;
+        let mut state = self.state.borrow_mut();
+        state.stopped = false;
+        if let Some(hits) = state.hits_before_exit {
+            self.play_script(&mut state, hits);
+        }
+        Ok(())
+    }
+
+    fn set_active_thread(&mut self, thread: ThreadId) -> Result<()>
+    {
+        self.state.borrow_mut().active_thread = Some(thread);
+        Ok(())
+    }
+
+    fn refresh_threads(&mut self) -> Result<()>
+    {
+        self.check_gone()
+    }
+
+    fn suspend_thread(&mut self, thread: ThreadId) -> Result<()>
+    {
+        self.per_thread_call("suspend", thread)?;
+        *self.state.borrow_mut().suspend_counts.entry(thread).or_insert(0) += 1;
+        Ok(())
+    }
+
+    fn resume_thread(&mut self, thread: ThreadId) -> Result<()>
+    {
+        self.per_thread_call("resume", thread)?;
+        let mut state = self.state.borrow_mut();
+        if let Some(count) = state.suspend_counts.get_mut(&thread) {
+            *count = count.saturating_sub(1);
+        }
+        Ok(())
+    }
+
+    fn set_signal_policy(&mut self, signal: i32, policy: SignalPolicy) -> Result<()>
+    {
+        self.state.borrow_mut().signal_policies.set(signal, policy)
+    }
+
+    fn signal_policies(&self) -> SignalPolicies
+    {
+        self.state.borrow().signal_policies.clone()
+    }
+
+    fn set_stop_policy(&mut self, policy: StopPolicy) -> Result<()>
+    {
+        self.state.borrow_mut().stop_policy = policy;
+        Ok(())
+    }
+
+    fn stop_policy(&self) -> StopPolicy
+    {
+        self.state.borrow().stop_policy
+    }
+
+    fn pending_symbol_loads(&self) -> usize
+    {
+        self.state.borrow().pending_symbol_loads
+    }
+
+    fn set_source_map(&mut self, source_map: SourceMap)
+    {
+        self.state.borrow_mut().source_map = source_map;
+    }
+
+    fn source_map(&self) -> SourceMap
+    {
+        self.state.borrow().source_map.clone()
+    }
+
+    fn code_lines(&mut self, _file: &str) -> Result<BTreeSet<u32>>
+    {
+        self.state
+            .borrow()
+            .code_lines
+            .clone()
+            .ok_or_else(|| DebuggerError::InvalidArgument("No line tables".to_string()))
+    }
+
+    /// Reports every module without DWARF as reloaded
+    fn add_dsym_search_path(&mut self, path: std::path::PathBuf) -> Result<usize>
+    {
+        let mut state = self.state.borrow_mut();
+        state.dsym_search_paths.push(path);
+        Ok(state.modules.iter().filter(|image| !image.debug_level.has_dwarf()).count())
+    }
+
+    /// Advances the PC by one arm64 instruction
+    fn step_instruction(&mut self) -> Result<StopReason>
+    {
+        self.check_gone()?;
+        let mut state = self.state.borrow_mut();
+        if !state.stopped {
+            return Err(DebuggerError::NotStopped);
+        }
+        state.steps += 1;
+        state.pc += 4;
+        let reason = StopReason::Step(state.pc);
+        state.stop_reason = Some(reason);
+        Ok(reason)
+    }
+
+    /// Every arm64 call returns to the next instruction, so this steps like `step_instruction`
+    fn step_over(&mut self) -> Result<StopReason>
+    {
+        self.state.borrow_mut().steps_over += 1;
+        self.step_instruction()
+    }
+
+    /// Runs to the PC of the caller in `stack`
+    fn step_out(&mut self) -> Result<StopReason>
+    {
+        self.check_gone()?;
+        let mut state = self.state.borrow_mut();
+        if !state.stopped {
+            return Err(DebuggerError::NotStopped);
+        }
+        let pc = return_frame(&state.stack.frames)?.pc.value();
+        state.steps += 1;
+        state.pc = pc;
+        let reason = StopReason::Step(pc);
+        state.stop_reason = Some(reason);
+        Ok(reason)
+    }
+
+    fn stack_trace(&mut self, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        Ok(self.state.borrow().stack.frames.iter().take(max_frames).cloned().collect())
+    }
+
+    fn stack_trace_for(&mut self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        let state = self.state.borrow();
+        let frames = state.thread_stacks.get(&thread).unwrap_or(&state.stack.frames);
+        Ok(frames.iter().take(max_frames).cloned().collect())
+    }
+
+    fn stack_trace_with_options(&mut self, options: UnwindOptions) -> Result<StackTrace>
+    {
+        let mut state = self.state.borrow_mut();
+        state.unwind_options.push(options);
+        let mut stack = state.stack.clone();
+        for location in stack.frames.iter_mut().filter_map(|frame| frame.location.as_mut()) {
+            if let Some(local) = state.source_map.to_local(&location.file) {
+                location.file = local;
+            }
+        }
+        Ok(stack)
+    }
+
+    fn thread_stack_bounds(&self, _thread: ThreadId) -> Result<(Address, Address)>
+    {
+        let state = self.state.borrow();
+        state
+            .regions
+            .iter()
+            .find(|region| region.start.value() <= state.sp && state.sp < region.end.value())
+            .map(|region| (region.start, region.end))
+            .ok_or_else(|| DebuggerError::InvalidArgument("No stack region holds SP".to_string()))
+    }
+
+    fn read_thread_local(&mut self, thread: ThreadId, name: &str) -> Result<Vec<ThreadLocalValue>>
+    {
+        let state = self.state.borrow();
+        Ok(state
+            .thread_locals
+            .iter()
+            .filter(|((qualified, owner), _)| *owner == thread && qualified.ends_with(name))
+            .map(|((qualified, _), value)| ThreadLocalValue {
+                variable: ThreadLocalVariable {
+                    name: qualified.clone(),
+                    type_name: Some("Cell<u64>".to_string()),
+                    size: Some(8),
+                    location: TlsLocation::Descriptor(Address::from(0x1_0000_8000)),
+                },
+                thread,
+                address: Some(Address::from(0x6000_0000 + thread.raw() * 0x1000)),
+                bytes: value.to_le_bytes().to_vec(),
+            })
+            .collect())
+    }
+
+    fn resolve_location(&mut self, location: &BreakpointLocation) -> Result<Vec<Address>>
+    {
+        if !self.state.borrow().attached {
+            return Err(DebuggerError::NotAttached);
+        }
+        match location {
+            BreakpointLocation::Address(address) => Ok(vec![*address]),
+            BreakpointLocation::Line { file, line } => Ok(self
+                .state
+                .borrow()
+                .source_lines
+                .get(&(file.clone(), *line))
+                .map(|addresses| addresses.iter().copied().map(Address::from).collect())
+                .unwrap_or_default()),
+            BreakpointLocation::Function(name) => {
+                let mut starts: Vec<Address> = self
+                    .state
+                    .borrow()
+                    .function_names
+                    .iter()
+                    .filter(|&(_, function)| function == name)
+                    .map(|(&start, _)| Address::from(start))
+                    .collect();
+                starts.sort();
+                Ok(starts)
+            }
+        }
+    }
+
+    fn remove_breakpoint(&mut self, id: BreakpointId) -> Result<()>
+    {
+        let mut state = self.state.borrow_mut();
+        state.removed.push(id);
+        state.breakpoints.retain(|info| info.id != id);
+        Ok(())
+    }
+
+    fn enable_breakpoint(&mut self, id: BreakpointId) -> Result<()>
+    {
+        self.set_breakpoint_enabled(id, true)
+    }
+
+    fn disable_breakpoint(&mut self, id: BreakpointId) -> Result<()>
+    {
+        self.set_breakpoint_enabled(id, false)
+    }
+
+    fn set_breakpoint_ignore_count(&mut self, id: BreakpointId, count: u64) -> Result<()>
+    {
+        let mut state = self.state.borrow_mut();
+        let info = state
+            .breakpoints
+            .iter_mut()
+            .find(|info| info.id == id)
+            .ok_or(DebuggerError::BreakpointNotFound(id))?;
+        info.ignore_count = count;
+        Ok(())
+    }
+
+    fn modules(&self) -> Result<Vec<ImageInfo>>
+    {
+        Ok(self.state.borrow().modules.clone())
+    }
+
+    fn find_function(&mut self, name: &str) -> Result<Vec<(Address, SymbolName)>>
+    {
+        let mut functions: Vec<(Address, SymbolName)> = self
+            .state
+            .borrow()
+            .function_names
+            .iter()
+            .filter(|(_, function)| {
+                *function == name || function.strip_suffix(name).is_some_and(|prefix| prefix.ends_with("::"))
+            })
+            .map(|(&start, function)| {
+                let symbol = SymbolName::new(function.clone(), Some(function.clone()), SymbolLanguage::Rust);
+                (Address::from(start), symbol)
+            })
+            .collect();
+        functions.sort_by_key(|&(address, _)| address);
+        Ok(functions)
+    }
+
+    fn add_breakpoint(&mut self, request: BreakpointRequest) -> Result<BreakpointId>
+    {
+        let mut state = self.state.borrow_mut();
+        if let Some(advice) = &state.breakpoint_denial {
+            return Err(DebuggerError::permission_denied(
+                "thread_set_state refused",
+                Some(advice.clone()),
+            ));
+        }
+        state.breakpoint_requests.push(request);
+        Ok(BreakpointId::from_raw(state.breakpoint_requests.len() as u64))
+    }
+}
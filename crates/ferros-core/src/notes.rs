@@ -0,0 +1,482 @@
+//! Session notes: free-text findings attached to what they are about.
+//!
+//! A [`Note`] points at an address, a breakpoint or a timeline entry through
+//! its [`NoteTarget`]. The [`NoteRegistry`] is saved in the per-binary
+//! [`SessionFile`](crate::session::SessionFile), so notes written during one
+//! investigation are there the next time the binary is debugged.
+//!
+//! ## Surviving ASLR
+//!
+//! Addresses move between runs. When a note is attached to an address inside
+//! a mapped image, the registry also records an [`ImageAnchor`]: the image's
+//! path, its build UUID and the address's offset from the image base. After
+//! the next launch, [`NoteRegistry::rebase`] moves every anchored note to the
+//! image's new base, but only if the image on disk still has the same UUID;
+//! a rebuilt binary keeps the old absolute addresses rather than pointing
+//! notes at unrelated code.
+//!
+//! Timeline entries only exist for one session, so notes on them record the
+//! session they belong to and the entry's message for listing later.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ferros_core::notes::{NoteRegistry, NoteTarget};
+//!
+//! let mut notes = NoteRegistry::new();
+//! let id = notes.add(NoteTarget::address(0x1042ab000), "connection table")?;
+//!
+//! assert_eq!(notes.at_address(0x1042ab000).next().unwrap().id, id);
+//! assert!(notes.remove(id));
+//! # Ok::<(), ferros_core::error::DebuggerError>(())
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DebuggerError, Result};
+use crate::types::MemoryRegion;
+
+/// Marker shown next to anything that has a note.
+pub const NOTE_MARKER: &str = "📝";
+
+/// Where an address sits in its image, for relocating it in a later run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageAnchor
+{
+    /// Path of the image, as named by its memory region.
+    pub path: String,
+    /// Build UUID of the image, see [`image_uuid`](crate::symbols::image_uuid).
+    pub uuid: String,
+    /// Offset of the address from the image's lowest mapped address.
+    pub offset: u64,
+}
+
+impl ImageAnchor
+{
+    /// Anchor for `address` if it lies in a named region whose file has a UUID.
+    ///
+    /// `uuid_of` reads the UUID of an image file; front-ends pass
+    /// [`image_uuid`](crate::symbols::image_uuid).
+    pub fn for_address(address: u64, regions: &[MemoryRegion], uuid_of: impl Fn(&Path) -> Option<String>) -> Option<Self>
+    {
+        let path = regions.iter().find(|region| region.contains(address.into()))?.name.clone()?;
+        let base = image_base(&path, regions)?;
+        Some(Self {
+            uuid: uuid_of(Path::new(&path))?,
+            offset: address - base,
+            path,
+        })
+    }
+
+    /// Where the anchored address is in `regions`, if its image is mapped and still has the same UUID.
+    pub fn relocate(&self, regions: &[MemoryRegion], uuid_of: impl Fn(&Path) -> Option<String>) -> Option<u64>
+    {
+        let base = image_base(&self.path, regions)?;
+        (uuid_of(Path::new(&self.path)).as_deref() == Some(self.uuid.as_str())).then_some(base + self.offset)
+    }
+}
+
+/// Lowest start address among the regions mapping `path`.
+fn image_base(path: &str, regions: &[MemoryRegion]) -> Option<u64>
+{
+    regions
+        .iter()
+        .filter(|region| region.name.as_deref() == Some(path))
+        .map(|region| region.start.value())
+        .min()
+}
+
+/// What a note is attached to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NoteTarget
+{
+    /// An address, such as a frame's PC or a data structure.
+    Address
+    {
+        /// Absolute address in the current (or last) run.
+        address: u64,
+        /// Image-relative form, when the address is inside an image.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        image: Option<ImageAnchor>,
+    },
+    /// A breakpoint, identified by its address since IDs are reassigned each run.
+    Breakpoint
+    {
+        /// Breakpoint ID when the note was written, for display.
+        id: u64,
+        /// Address the breakpoint is at.
+        address: u64,
+        /// Image-relative form of `address`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        image: Option<ImageAnchor>,
+    },
+    /// A timeline entry of one session.
+    Timeline
+    {
+        /// Session the entry belongs to: its start, in milliseconds since the Unix epoch.
+        session: u64,
+        /// Sequence number of the entry within the session.
+        entry: u64,
+        /// The entry's message, for listing the note after the session ends.
+        message: String,
+    },
+}
+
+impl NoteTarget
+{
+    /// Note on `address`, without an image anchor.
+    #[must_use]
+    pub fn address(address: u64) -> Self
+    {
+        NoteTarget::Address { address, image: None }
+    }
+
+    /// Address the target refers to, for address and breakpoint notes.
+    #[must_use]
+    pub fn address_value(&self) -> Option<u64>
+    {
+        match self {
+            NoteTarget::Address { address, .. } | NoteTarget::Breakpoint { address, .. } => Some(*address),
+            NoteTarget::Timeline { .. } => None,
+        }
+    }
+
+    /// Attach the image-relative form of the target's address.
+    #[must_use]
+    pub fn with_image(mut self, anchor: Option<ImageAnchor>) -> Self
+    {
+        if let NoteTarget::Address { image, .. } | NoteTarget::Breakpoint { image, .. } = &mut self {
+            *image = anchor;
+        }
+        self
+    }
+}
+
+impl fmt::Display for NoteTarget
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            NoteTarget::Address { address, .. } => write!(f, "0x{address:x}"),
+            NoteTarget::Breakpoint { id, address, .. } => write!(f, "bp {id} @ 0x{address:x}"),
+            NoteTarget::Timeline { entry, message, .. } => write!(f, "timeline #{entry} ({message})"),
+        }
+    }
+}
+
+/// A note and what it is attached to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note
+{
+    /// Identifier, unique within the registry.
+    pub id: u64,
+    /// What the note is about.
+    pub target: NoteTarget,
+    /// The note itself.
+    pub text: String,
+}
+
+impl fmt::Display for Note
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "[{}] {}: {}", self.id, self.target, self.text)
+    }
+}
+
+/// Notes of a session, in the order they were written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteRegistry
+{
+    notes: Vec<Note>,
+}
+
+impl NoteRegistry
+{
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Rebuild a registry from persisted notes, skipping duplicate IDs.
+    #[must_use]
+    pub fn from_notes(notes: &[Note]) -> Self
+    {
+        let mut registry = Self::new();
+        for note in notes {
+            if registry.get(note.id).is_none() {
+                registry.notes.push(note.clone());
+            }
+        }
+        registry
+    }
+
+    /// Notes in their persisted form.
+    #[must_use]
+    pub fn notes(&self) -> &[Note]
+    {
+        &self.notes
+    }
+
+    /// Attach `text` to `target` and return the new note's ID.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidArgument` if `text` is blank.
+    pub fn add(&mut self, target: NoteTarget, text: &str) -> Result<u64>
+    {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(DebuggerError::InvalidArgument("a note needs some text".to_string()));
+        }
+        let id = self.notes.iter().map(|note| note.id).max().unwrap_or(0) + 1;
+        self.notes.push(Note {
+            id,
+            target,
+            text: text.to_string(),
+        });
+        Ok(id)
+    }
+
+    /// Remove note `id`. Returns `false` if there was none.
+    pub fn remove(&mut self, id: u64) -> bool
+    {
+        let before = self.notes.len();
+        self.notes.retain(|note| note.id != id);
+        self.notes.len() != before
+    }
+
+    /// Look up a note by ID.
+    #[must_use]
+    pub fn get(&self, id: u64) -> Option<&Note>
+    {
+        self.notes.iter().find(|note| note.id == id)
+    }
+
+    /// Notes in the order they were written.
+    pub fn iter(&self) -> impl Iterator<Item = &Note>
+    {
+        self.notes.iter()
+    }
+
+    /// Number of notes.
+    #[must_use]
+    pub fn len(&self) -> usize
+    {
+        self.notes.len()
+    }
+
+    /// Whether there are no notes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.notes.is_empty()
+    }
+
+    /// Notes attached to `address` itself (not to a breakpoint there).
+    pub fn at_address(&self, address: u64) -> impl Iterator<Item = &Note>
+    {
+        self.notes
+            .iter()
+            .filter(move |note| matches!(note.target, NoteTarget::Address { address: a, .. } if a == address))
+    }
+
+    /// Address notes inside `range`, in address order.
+    #[must_use]
+    pub fn in_range(&self, range: Range<u64>) -> Vec<&Note>
+    {
+        let mut notes: Vec<_> = self
+            .notes
+            .iter()
+            .filter(|note| matches!(note.target, NoteTarget::Address { address, .. } if range.contains(&address)))
+            .collect();
+        notes.sort_by_key(|note| note.target.address_value());
+        notes
+    }
+
+    /// Notes on the breakpoint at `address`.
+    pub fn for_breakpoint(&self, address: u64) -> impl Iterator<Item = &Note>
+    {
+        self.notes
+            .iter()
+            .filter(move |note| matches!(note.target, NoteTarget::Breakpoint { address: a, .. } if a == address))
+    }
+
+    /// Notes on timeline entry `entry` of `session`.
+    pub fn for_timeline(&self, session: u64, entry: u64) -> impl Iterator<Item = &Note>
+    {
+        self.notes.iter().filter(move |note| {
+            matches!(note.target, NoteTarget::Timeline { session: s, entry: e, .. } if s == session && e == entry)
+        })
+    }
+
+    /// Move anchored notes to where their image is mapped in `regions`.
+    ///
+    /// A note moves only if a region maps its image's path and `uuid_of`
+    /// reports the same UUID it was written against. Returns the number of
+    /// notes whose address changed.
+    pub fn rebase(&mut self, regions: &[MemoryRegion], uuid_of: impl Fn(&Path) -> Option<String>) -> usize
+    {
+        let mut moved = 0;
+        for note in &mut self.notes {
+            let (NoteTarget::Address {
+                address,
+                image: Some(anchor),
+            }
+            | NoteTarget::Breakpoint {
+                address,
+                image: Some(anchor),
+                ..
+            }) = &mut note.target
+            else {
+                continue;
+            };
+            let Some(relocated) = anchor.relocate(regions, &uuid_of) else {
+                continue;
+            };
+            if *address != relocated {
+                *address = relocated;
+                moved += 1;
+            }
+        }
+        moved
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::{Address, MemoryRegionId};
+
+    fn mapping(start: u64, end: u64, name: &str) -> MemoryRegion
+    {
+        MemoryRegion::new(
+            MemoryRegionId(0),
+            Address::from(start),
+            Address::from(end),
+            "r-x".to_string(),
+            Some(name.to_string()),
+        )
+    }
+
+    fn uuid(path: &Path) -> Option<String>
+    {
+        (path == Path::new("/bin/app")).then(|| "00112233".to_string())
+    }
+
+    #[test]
+    fn notes_are_added_looked_up_and_removed()
+    {
+        let mut notes = NoteRegistry::new();
+        let table = notes.add(NoteTarget::address(0x2000), "  connection table ").unwrap();
+        let bp = notes
+            .add(
+                NoteTarget::Breakpoint {
+                    id: 4,
+                    address: 0x1000,
+                    image: None,
+                },
+                "the suspicious path",
+            )
+            .unwrap();
+        let stop = notes
+            .add(
+                NoteTarget::Timeline {
+                    session: 7,
+                    entry: 3,
+                    message: "Stopped".to_string(),
+                },
+                "first stop",
+            )
+            .unwrap();
+        assert!(notes.add(NoteTarget::address(0x2000), " ").is_err());
+
+        assert_eq!(notes.get(table).unwrap().text, "connection table");
+        assert_eq!(notes.at_address(0x2000).count(), 1);
+        assert_eq!(notes.at_address(0x1000).count(), 0, "breakpoint notes aren't address notes");
+        assert_eq!(notes.for_breakpoint(0x1000).next().unwrap().id, bp);
+        assert_eq!(notes.for_timeline(7, 3).next().unwrap().id, stop);
+        assert_eq!(notes.for_timeline(8, 3).count(), 0, "entries of another session");
+        assert_eq!(notes.in_range(0x1000..0x3000).len(), 1);
+        assert_eq!(notes.get(bp).unwrap().to_string(), "[2] bp 4 @ 0x1000: the suspicious path");
+
+        assert!(notes.remove(table));
+        assert!(!notes.remove(table));
+        // IDs aren't reused while later notes exist
+        assert_eq!(notes.add(NoteTarget::address(0x10), "again").unwrap(), 4);
+    }
+
+    #[test]
+    fn notes_round_trip_through_json()
+    {
+        let mut notes = NoteRegistry::new();
+        let anchor = ImageAnchor::for_address(0x1_0000_4abc, &[mapping(0x1_0000_0000, 0x1_0001_0000, "/bin/app")], uuid);
+        notes
+            .add(NoteTarget::address(0x1_0000_4abc).with_image(anchor), "parser state")
+            .unwrap();
+        notes
+            .add(
+                NoteTarget::Timeline {
+                    session: 1_700_000_000_000,
+                    entry: 12,
+                    message: "Breakpoint 1 hit".to_string(),
+                },
+                "slow here",
+            )
+            .unwrap();
+
+        let json = serde_json::to_string(notes.notes()).unwrap();
+        assert!(json.contains("\"kind\":\"address\""), "{json}");
+        assert!(json.contains("\"offset\":19132"), "{json}");
+        let restored = NoteRegistry::from_notes(&serde_json::from_str::<Vec<Note>>(&json).unwrap());
+        assert_eq!(restored, notes);
+
+        // An address note written before anchors existed still loads
+        let legacy: Note = serde_json::from_str(r#"{"id":1,"target":{"kind":"address","address":16},"text":"x"}"#).unwrap();
+        assert_eq!(legacy.target, NoteTarget::address(0x10));
+    }
+
+    #[test]
+    fn anchored_notes_follow_the_image_when_the_uuid_matches()
+    {
+        let first_run = [
+            mapping(0x1_0000_0000, 0x1_0000_4000, "/bin/app"),
+            mapping(0x1_0000_4000, 0x1_0000_8000, "/bin/app"),
+        ];
+        let anchor = ImageAnchor::for_address(0x1_0000_5010, &first_run, uuid).unwrap();
+        assert_eq!(anchor.offset, 0x5010, "offset is from the lowest mapping of the image");
+        assert_eq!(ImageAnchor::for_address(0x1_0000_5010, &first_run, |_| None), None);
+
+        let mut notes = NoteRegistry::new();
+        notes
+            .add(NoteTarget::address(0x1_0000_5010).with_image(Some(anchor.clone())), "table")
+            .unwrap();
+        let bp = NoteTarget::Breakpoint {
+            id: 1,
+            address: 0x1_0000_0100,
+            image: None,
+        };
+        let bp_anchor = ImageAnchor::for_address(0x1_0000_0100, &first_run, uuid);
+        notes.add(bp.with_image(bp_anchor), "entry").unwrap();
+        notes.add(NoteTarget::address(0x7000), "heap").unwrap();
+
+        let second_run = [mapping(0x1_0420_0000, 0x1_0420_8000, "/bin/app")];
+        assert_eq!(notes.rebase(&second_run, uuid), 2);
+        assert_eq!(notes.at_address(0x1_0420_5010).count(), 1);
+        assert_eq!(notes.for_breakpoint(0x1_0420_0100).count(), 1);
+        assert_eq!(notes.at_address(0x7000).count(), 1, "unanchored notes stay put");
+
+        // A rebuilt binary has a new UUID: leave the notes alone
+        let third_run = [mapping(0x1_0900_0000, 0x1_0900_8000, "/bin/app")];
+        assert_eq!(notes.rebase(&third_run, |_| Some("ffff".to_string())), 0);
+        assert_eq!(notes.at_address(0x1_0420_5010).count(), 1);
+    }
+}
@@ -0,0 +1,1590 @@
+//! Structured journal of backend operations.
+//!
+//! For remote support ("send me what the debugger actually did") every call
+//! into the backend can be appended to a JSONL file, separate from the human
+//! tracing logs: the operation, its key arguments, whether it succeeded, how
+//! long it took and what it returned.
+//!
+//! ```json
+//! {"seq":0,"wall_ms":1700000000001,"op":"attach","args":{"pid":4242},"status":"ok","duration_us":812}
+//! {"seq":1,"wall_ms":1700000000002,"op":"read_memory","args":{"addr":"0x16fdff000","len":8},"status":"ok","duration_us":9,"result":{"len":8,"fnv1a":"0x8a4f2d3b1c2e9f01"}}
+//! ```
+//!
+//! [`JournaledDebugger`] wraps any backend and records through whichever
+//! [`OperationLog`] was last handed to [`Debugger::set_operation_log`], so
+//! backends don't each implement it. Without a log every call is forwarded
+//! straight through: arguments aren't formatted and nothing is allocated.
+//!
+//! Addresses are hex strings. Memory contents, read or written, are recorded
+//! as a length and an FNV-1a digest unless [`OperationLogOptions::full_memory`]
+//! is set. State accessors a front-end polls every frame (`architecture`,
+//! `is_attached`, `is_stopped`, `stop_reason`, `active_thread`,
+//! `capabilities`, `breakpoints`, `breakpoint_counts`) only read state the
+//! backend already holds and aren't recorded.
+//!
+//! ```rust,no_run
+//! use ferros_core::Debugger;
+//! use ferros_core::debugger::create_debugger;
+//! use ferros_core::operations::{JournaledDebugger, OperationLog, OperationLogOptions};
+//! use ferros_core::types::ProcessId;
+//!
+//! let mut debugger = JournaledDebugger::new(create_debugger()?);
+//! let log = OperationLog::create("ops.jsonl".as_ref(), OperationLogOptions::default())?;
+//! debugger.set_operation_log(Some(log))?;
+//! debugger.attach(ProcessId::from(12345))?;
+//! # Ok::<(), ferros_core::error::DebuggerError>(())
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::breakpoints::{
+    BreakpointCounts, BreakpointId, BreakpointInfo, BreakpointKind, BreakpointLocation, BreakpointRequest,
+};
+use crate::debugger::Debugger;
+use crate::error::Result;
+use crate::events::{DebuggerEventReceiver, format_stop_reason};
+use crate::inspector::{Capabilities, TargetInspector};
+use crate::output::OutputSink;
+use crate::range_checkpoints::{RangeDiff, RangeSnapshot, WatchedRange};
+use crate::search::{MemorySearch, MemorySearchScope};
+use crate::signals::{SignalPolicies, SignalPolicy};
+use crate::snapshot::{SnapshotOptions, SnapshotRegions};
+use crate::stream::{ReadStats, StreamOptions};
+use crate::symbols::paths::SourceMap;
+use crate::symbols::tls::ThreadLocalValue;
+use crate::symbols::unwind::{StackTrace, UnwindOptions};
+use crate::symbols::{DebugLevel, ImageInfo, TypeSummary};
+use crate::types::{
+    Address, AddressExpr, Architecture, DisassembledInstruction, LaunchOptions, MemoryCacheStats, MemoryRegion, ProcessId,
+    RegisterId, Registers, SourceLocation, StackFrame, StopPolicy, StopReason, SymbolName, ThreadId, ThreadInfo,
+};
+
+/// Whether an operation succeeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus
+{
+    /// The backend returned a value.
+    Ok,
+    /// The backend returned an error, recorded in [`OperationRecord::error`].
+    Error,
+}
+
+/// One line of an operation log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationRecord
+{
+    /// Position in the log, from 0.
+    pub seq: u64,
+    /// Wall-clock time the operation started, in milliseconds since the Unix epoch.
+    pub wall_ms: u64,
+    /// Trait method that was called, e.g. `read_memory`.
+    pub op: String,
+    /// Key arguments, as an object.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub args: Value,
+    /// Whether it succeeded.
+    pub status: OperationStatus,
+    /// The error, for failed operations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Time spent in the backend, in microseconds.
+    pub duration_us: u64,
+    /// Summary of what it returned, for operations whose result is worth keeping.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub result: Value,
+}
+
+/// What an operation log records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OperationLogOptions
+{
+    /// Record memory contents as hex instead of a length and digest (`--ops-log-full`).
+    pub full_memory: bool,
+}
+
+/// Shared handle to an operation log.
+///
+/// Clones write to the same log. Each record is flushed as it's written, so
+/// the log is complete up to the last operation even if ferros dies.
+#[derive(Clone)]
+pub struct OperationLog
+{
+    writer: Arc<Mutex<OperationWriter>>,
+}
+
+struct OperationWriter
+{
+    out: Box<dyn Write + Send>,
+    options: OperationLogOptions,
+    next_seq: u64,
+    failed: bool,
+}
+
+impl fmt::Debug for OperationLog
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_struct("OperationLog")
+            .field("options", &self.options())
+            .finish_non_exhaustive()
+    }
+}
+
+impl OperationLog
+{
+    /// Log to `out`.
+    pub fn new(out: impl Write + Send + 'static, options: OperationLogOptions) -> Self
+    {
+        Self {
+            writer: Arc::new(Mutex::new(OperationWriter {
+                out: Box::new(out),
+                options,
+                next_seq: 0,
+                failed: false,
+            })),
+        }
+    }
+
+    /// Log to a new (or truncated) file at `path`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Io` if the file can't be created.
+    pub fn create(path: &Path, options: OperationLogOptions) -> Result<Self>
+    {
+        Ok(Self::new(BufWriter::new(File::create(path)?), options))
+    }
+
+    /// What the log records.
+    #[must_use]
+    pub fn options(&self) -> OperationLogOptions
+    {
+        self.writer.lock().unwrap_or_else(PoisonError::into_inner).options
+    }
+
+    /// Append a record, numbering it. Writing stops after the first I/O error.
+    fn write(&self, mut record: OperationRecord)
+    {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        if writer.failed {
+            return;
+        }
+        record.seq = writer.next_seq;
+        writer.next_seq += 1;
+        let written = serde_json::to_writer(&mut writer.out, &record)
+            .map_err(std::io::Error::from)
+            .and_then(|()| writer.out.write_all(b"\n"))
+            .and_then(|()| writer.out.flush());
+        if let Err(e) = written {
+            tracing::warn!("Operation log stopped: {e}");
+            writer.failed = true;
+        }
+    }
+}
+
+/// Run `call`, recording it in `log` if there is one
+///
+/// `args` and `result` are only evaluated when logging; both get whether
+/// memory contents are recorded in full.
+fn traced<T>(
+    log: Option<&OperationLog>,
+    op: &'static str,
+    args: impl FnOnce(bool) -> Value,
+    call: impl FnOnce() -> Result<T>,
+    result: impl FnOnce(&T, bool) -> Value,
+) -> Result<T>
+{
+    let Some(log) = log else {
+        return call();
+    };
+    let wall = SystemTime::now();
+    let started = Instant::now();
+    let outcome = call();
+    let duration = started.elapsed();
+
+    let full = log.options().full_memory;
+    let (status, error, summary) = match &outcome {
+        Ok(value) => (OperationStatus::Ok, None, result(value, full)),
+        Err(e) => (OperationStatus::Error, Some(e.to_string()), Value::Null),
+    };
+    log.write(OperationRecord {
+        seq: 0,
+        wall_ms: wall
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| u64::try_from(since.as_millis()).unwrap_or(u64::MAX)),
+        op: op.to_string(),
+        args: args(full),
+        status,
+        error,
+        duration_us: u64::try_from(duration.as_micros()).unwrap_or(u64::MAX),
+        result: summary,
+    });
+    outcome
+}
+
+/// No arguments, or a result not worth recording
+fn none<T>(_: &T, _: bool) -> Value
+{
+    Value::Null
+}
+
+fn hex(address: Address) -> Value
+{
+    Value::String(format!("0x{:x}", address.value()))
+}
+
+/// FNV-1a, so digests compare across machines and toolchains
+fn fnv1a(bytes: &[u8]) -> u64
+{
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Memory contents as a length and digest, or in full
+fn bytes_summary(bytes: &[u8], full: bool) -> Value
+{
+    if full {
+        let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        json!({ "len": bytes.len(), "hex": hex })
+    } else {
+        json!({ "len": bytes.len(), "fnv1a": format!("0x{:016x}", fnv1a(bytes)) })
+    }
+}
+
+fn registers_summary(registers: &Registers) -> Value
+{
+    json!({ "pc": hex(registers.pc), "sp": hex(registers.sp) })
+}
+
+/// Software breakpoints whose trap a write of `len` bytes at `addr` lands on.
+///
+/// A trap is one instruction long, which is the instruction alignment on
+/// both supported architectures.
+fn patched_breakpoints(target: &dyn TargetInspector, addr: Address, len: usize) -> Vec<u64>
+{
+    let trap = target.architecture().instruction_alignment();
+    let end = addr.value().saturating_add(len as u64);
+    let mut ids: Vec<u64> = target
+        .breakpoints()
+        .iter()
+        .filter(|bp| {
+            bp.kind == BreakpointKind::Software && bp.address.value() < end && addr.value() < bp.address.value() + trap
+        })
+        .map(|bp| bp.id.raw())
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// Backend wrapper that records every operation in an [`OperationLog`]
+///
+/// Forwards everything to the wrapped backend, so it can wrap the platform
+/// debugger for a whole session and have logging switched on and off with
+/// [`Debugger::set_operation_log`].
+pub struct JournaledDebugger
+{
+    inner: Box<dyn Debugger>,
+    log: Option<OperationLog>,
+}
+
+impl JournaledDebugger
+{
+    /// Wrap `inner`, not logging until a log is set.
+    #[must_use]
+    pub fn new(inner: Box<dyn Debugger>) -> Self
+    {
+        Self { inner, log: None }
+    }
+
+    /// The wrapped backend.
+    #[must_use]
+    pub fn into_inner(self) -> Box<dyn Debugger>
+    {
+        self.inner
+    }
+}
+
+impl TargetInspector for JournaledDebugger
+{
+    fn architecture(&self) -> Architecture
+    {
+        self.inner.architecture()
+    }
+
+    fn is_attached(&self) -> bool
+    {
+        self.inner.is_attached()
+    }
+
+    fn is_stopped(&self) -> bool
+    {
+        self.inner.is_stopped()
+    }
+
+    fn stop_reason(&self) -> StopReason
+    {
+        self.inner.stop_reason()
+    }
+
+    fn threads(&self) -> Result<Vec<ThreadId>>
+    {
+        traced(
+            self.log.as_ref(),
+            "threads",
+            |_| Value::Null,
+            || self.inner.threads(),
+            |threads, _| json!({ "count": threads.len() }),
+        )
+    }
+
+    fn active_thread(&self) -> Option<ThreadId>
+    {
+        self.inner.active_thread()
+    }
+
+    fn thread_suspend_count(&self, thread: ThreadId) -> u32
+    {
+        traced(
+            self.log.as_ref(),
+            "thread_suspend_count",
+            |_| json!({ "thread": thread.raw() }),
+            || Ok(self.inner.thread_suspend_count(thread)),
+            |count, _| json!({ "count": count }),
+        )
+        .unwrap_or_default()
+    }
+
+    fn thread_info(&self, thread: ThreadId) -> Result<ThreadInfo>
+    {
+        traced(
+            self.log.as_ref(),
+            "thread_info",
+            |_| json!({ "thread": thread.raw() }),
+            || self.inner.thread_info(thread),
+            |info, _| json!({ "name": info.name, "state": info.state.to_string() }),
+        )
+    }
+
+    fn main_thread(&self) -> Option<ThreadId>
+    {
+        traced(
+            self.log.as_ref(),
+            "main_thread",
+            |_| Value::Null,
+            || Ok(self.inner.main_thread()),
+            |thread, _| thread.map_or(Value::Null, |thread| json!(thread.raw())),
+        )
+        .unwrap_or_default()
+    }
+
+    fn read_registers(&self) -> Result<Registers>
+    {
+        traced(
+            self.log.as_ref(),
+            "read_registers",
+            |_| Value::Null,
+            || self.inner.read_registers(),
+            |registers, _| registers_summary(registers),
+        )
+    }
+
+    fn read_registers_for(&self, thread: ThreadId) -> Result<Registers>
+    {
+        traced(
+            self.log.as_ref(),
+            "read_registers_for",
+            |_| json!({ "thread": thread.raw() }),
+            || self.inner.read_registers_for(thread),
+            |registers, _| registers_summary(registers),
+        )
+    }
+
+    fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        traced(
+            self.log.as_ref(),
+            "read_memory",
+            |_| json!({ "addr": hex(addr), "len": len }),
+            || self.inner.read_memory(addr, len),
+            |bytes, full| bytes_summary(bytes, full),
+        )
+    }
+
+    fn read_memory_live(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        traced(
+            self.log.as_ref(),
+            "read_memory_live",
+            |_| json!({ "addr": hex(addr), "len": len }),
+            || self.inner.read_memory_live(addr, len),
+            |bytes, full| bytes_summary(bytes, full),
+        )
+    }
+
+    fn read_memory_logical(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        traced(
+            self.log.as_ref(),
+            "read_memory_logical",
+            |_| json!({ "addr": hex(addr), "len": len }),
+            || self.inner.read_memory_logical(addr, len),
+            |bytes, full| bytes_summary(bytes, full),
+        )
+    }
+
+    fn read_memory_streamed(
+        &self,
+        range: Range<Address>,
+        chunk_size: usize,
+        sink: &mut dyn Write,
+        options: &mut StreamOptions<'_>,
+    ) -> Result<ReadStats>
+    {
+        let (start, end, logical) = (range.start, range.end, options.logical);
+        traced(
+            self.log.as_ref(),
+            "read_memory_streamed",
+            |_| json!({ "start": hex(start), "end": hex(end), "chunk_size": chunk_size, "logical": logical }),
+            || self.inner.read_memory_streamed(range, chunk_size, sink, options),
+            |stats, _| json!({ "written": stats.written, "holes": stats.holes.len(), "cancelled": stats.cancelled }),
+        )
+    }
+
+    fn read_u64(&self, addr: Address) -> Result<u64>
+    {
+        traced(
+            self.log.as_ref(),
+            "read_u64",
+            |_| json!({ "addr": hex(addr) }),
+            || self.inner.read_u64(addr),
+            |value, full| bytes_summary(&value.to_le_bytes(), full),
+        )
+    }
+
+    fn read_u32(&self, addr: Address) -> Result<u32>
+    {
+        traced(
+            self.log.as_ref(),
+            "read_u32",
+            |_| json!({ "addr": hex(addr) }),
+            || self.inner.read_u32(addr),
+            |value, full| bytes_summary(&value.to_le_bytes(), full),
+        )
+    }
+
+    fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+    {
+        traced(
+            self.log.as_ref(),
+            "get_memory_regions",
+            |_| Value::Null,
+            || self.inner.get_memory_regions(),
+            |regions, _| json!({ "count": regions.len() }),
+        )
+    }
+
+    fn memory_cache_stats(&self) -> Option<MemoryCacheStats>
+    {
+        self.inner.memory_cache_stats()
+    }
+
+    fn capture_ranges(&self, ranges: &[WatchedRange]) -> Result<Vec<RangeSnapshot>>
+    {
+        traced(
+            self.log.as_ref(),
+            "capture_ranges",
+            |_| json!({ "ranges": ranges.len() }),
+            || self.inner.capture_ranges(ranges),
+            none,
+        )
+    }
+
+    fn diff_ranges(&self, snapshots: &[RangeSnapshot]) -> Result<Vec<RangeDiff>>
+    {
+        traced(
+            self.log.as_ref(),
+            "diff_ranges",
+            |_| json!({ "ranges": snapshots.len() }),
+            || self.inner.diff_ranges(snapshots),
+            |diffs, _| json!({ "count": diffs.len() }),
+        )
+    }
+
+    fn breakpoints(&self) -> Vec<BreakpointInfo>
+    {
+        self.inner.breakpoints()
+    }
+
+    fn breakpoint_counts(&self) -> BreakpointCounts
+    {
+        self.inner.breakpoint_counts()
+    }
+
+    fn breakpoint_info(&self, id: BreakpointId) -> Result<BreakpointInfo>
+    {
+        traced(
+            self.log.as_ref(),
+            "breakpoint_info",
+            |_| json!({ "id": id.raw() }),
+            || self.inner.breakpoint_info(id),
+            |info, _| json!({ "address": hex(info.address), "enabled": info.enabled }),
+        )
+    }
+
+    fn debug_level(&self, address: Address) -> Option<DebugLevel>
+    {
+        traced(
+            self.log.as_ref(),
+            "debug_level",
+            |_| json!({ "addr": hex(address) }),
+            || Ok(self.inner.debug_level(address)),
+            |level, _| level.map_or(Value::Null, |level| Value::String(format!("{level:?}"))),
+        )
+        .unwrap_or_default()
+    }
+
+    fn function_start(&self, address: Address) -> Option<Address>
+    {
+        traced(
+            self.log.as_ref(),
+            "function_start",
+            |_| json!({ "addr": hex(address) }),
+            || Ok(self.inner.function_start(address)),
+            |start, _| start.map_or(Value::Null, hex),
+        )
+        .unwrap_or_default()
+    }
+
+    fn function_name(&self, address: Address) -> Option<SymbolName>
+    {
+        traced(
+            self.log.as_ref(),
+            "function_name",
+            |_| json!({ "addr": hex(address) }),
+            || Ok(self.inner.function_name(address)),
+            |name, _| {
+                name.as_ref()
+                    .map_or(Value::Null, |name| Value::String(name.display_name().to_string()))
+            },
+        )
+        .unwrap_or_default()
+    }
+
+    fn source_location(&self, address: Address) -> Option<SourceLocation>
+    {
+        traced(
+            self.log.as_ref(),
+            "source_location",
+            |_| json!({ "addr": hex(address) }),
+            || Ok(self.inner.source_location(address)),
+            |location, _| {
+                location.as_ref().map_or(
+                    Value::Null,
+                    |location| json!({ "file": location.file, "line": location.line }),
+                )
+            },
+        )
+        .unwrap_or_default()
+    }
+
+    fn describe_type(&self, name: &str) -> Option<Arc<TypeSummary>>
+    {
+        traced(
+            self.log.as_ref(),
+            "describe_type",
+            |_| json!({ "name": name }),
+            || Ok(self.inner.describe_type(name)),
+            |summary, _| {
+                summary
+                    .as_ref()
+                    .map_or(Value::Null, |summary| json!({ "name": summary.name }))
+            },
+        )
+        .unwrap_or_default()
+    }
+
+    fn capabilities(&self) -> Capabilities
+    {
+        self.inner.capabilities()
+    }
+
+    fn backtrace(&self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        traced(
+            self.log.as_ref(),
+            "backtrace",
+            |_| json!({ "thread": thread.raw(), "max_frames": max_frames }),
+            || self.inner.backtrace(thread, max_frames),
+            |frames, _| json!({ "frames": frames.len() }),
+        )
+    }
+}
+
+impl Debugger for JournaledDebugger
+{
+    fn set_operation_log(&mut self, log: Option<OperationLog>) -> Result<()>
+    {
+        self.log = log;
+        Ok(())
+    }
+
+    fn set_capture_process_output(&mut self, capture: bool)
+    {
+        let Self { inner, log } = self;
+        let _ = traced(
+            log.as_ref(),
+            "set_capture_process_output",
+            |_| json!({ "capture": capture }),
+            || {
+                inner.set_capture_process_output(capture);
+                Ok(())
+            },
+            none,
+        );
+    }
+
+    fn set_source_map(&mut self, source_map: SourceMap)
+    {
+        let Self { inner, log } = self;
+        let _ = traced(
+            log.as_ref(),
+            "set_source_map",
+            |_| Value::Null,
+            || {
+                inner.set_source_map(source_map);
+                Ok(())
+            },
+            none,
+        );
+    }
+
+    fn source_map(&self) -> SourceMap
+    {
+        self.inner.source_map()
+    }
+
+    fn add_dsym_search_path(&mut self, path: PathBuf) -> Result<usize>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "add_dsym_search_path",
+            |_| json!({ "path": path.display().to_string() }),
+            || inner.add_dsym_search_path(path.clone()),
+            |reloaded, _| json!({ "reloaded": reloaded }),
+        )
+    }
+
+    fn set_background_symbol_loading(&mut self, enabled: bool) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "set_background_symbol_loading",
+            |_| json!({ "enabled": enabled }),
+            || inner.set_background_symbol_loading(enabled),
+            none,
+        )
+    }
+
+    fn pending_symbol_loads(&self) -> usize
+    {
+        self.inner.pending_symbol_loads()
+    }
+
+    fn take_process_stdout(&mut self) -> Option<File>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "take_process_stdout",
+            |_| Value::Null,
+            || Ok(inner.take_process_stdout()),
+            |file, _| json!({ "present": file.is_some() }),
+        )
+        .unwrap_or_default()
+    }
+
+    fn take_process_stderr(&mut self) -> Option<File>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "take_process_stderr",
+            |_| Value::Null,
+            || Ok(inner.take_process_stderr()),
+            |file, _| json!({ "present": file.is_some() }),
+        )
+        .unwrap_or_default()
+    }
+
+    fn take_process_stdin(&mut self) -> Option<File>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "take_process_stdin",
+            |_| Value::Null,
+            || Ok(inner.take_process_stdin()),
+            |file, _| json!({ "present": file.is_some() }),
+        )
+        .unwrap_or_default()
+    }
+
+    fn pump_process_output(&mut self, stdout: Box<dyn OutputSink>, stderr: Box<dyn OutputSink>) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "pump_process_output",
+            |_| Value::Null,
+            || inner.pump_process_output(stdout, stderr),
+            none,
+        )
+    }
+
+    fn stop_output_pumps(&mut self, drain: Duration)
+    {
+        let Self { inner, log } = self;
+        let _ = traced(
+            log.as_ref(),
+            "stop_output_pumps",
+            |_| json!({ "drain_ms": u64::try_from(drain.as_millis()).unwrap_or(u64::MAX) }),
+            || {
+                inner.stop_output_pumps(drain);
+                Ok(())
+            },
+            none,
+        );
+    }
+
+    fn take_event_receiver(&mut self) -> Option<DebuggerEventReceiver>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "take_event_receiver",
+            |_| Value::Null,
+            || Ok(inner.take_event_receiver()),
+            |receiver, _| json!({ "present": receiver.is_some() }),
+        )
+        .unwrap_or_default()
+    }
+
+    fn launch_with_options(&mut self, program: &str, options: &LaunchOptions) -> Result<ProcessId>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "launch",
+            // Variable names only; values may be credentials
+            |_| {
+                json!({
+                    "program": program,
+                    "args": options.args,
+                    "env": options.env.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+                    "inherit_env": options.inherit_env,
+                    "clear_env": options.clear_env,
+                    "cwd": options.cwd,
+                })
+            },
+            || inner.launch_with_options(program, options),
+            |pid, _| json!({ "pid": pid.0 }),
+        )
+    }
+
+    fn attach(&mut self, pid: ProcessId) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "attach",
+            |_| json!({ "pid": pid.0 }),
+            || inner.attach(pid),
+            none,
+        )
+    }
+
+    fn detach(&mut self) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(log.as_ref(), "detach", |_| Value::Null, || inner.detach(), none)
+    }
+
+    fn kill(&mut self) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(log.as_ref(), "kill", |_| Value::Null, || inner.kill(), none)
+    }
+
+    fn write_registers(&mut self, regs: &Registers) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "write_registers",
+            |_| registers_summary(regs),
+            || inner.write_registers(regs),
+            none,
+        )
+    }
+
+    fn write_registers_for(&mut self, thread: ThreadId, regs: &Registers) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "write_registers_for",
+            |_| json!({ "thread": thread.raw(), "pc": hex(regs.pc), "sp": hex(regs.sp) }),
+            || inner.write_registers_for(thread, regs),
+            none,
+        )
+    }
+
+    fn set_register(&mut self, id: RegisterId, value: u64) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "set_register",
+            |_| json!({ "register": format!("{id:?}"), "value": hex(Address::from(value)) }),
+            || inner.set_register(id, value),
+            none,
+        )
+    }
+
+    fn read_tls_slot(&self, thread: ThreadId, key: u64) -> Result<u64>
+    {
+        traced(
+            self.log.as_ref(),
+            "read_tls_slot",
+            |_| json!({ "thread": thread.raw(), "key": key }),
+            || self.inner.read_tls_slot(thread, key),
+            |value, full| bytes_summary(&value.to_le_bytes(), full),
+        )
+    }
+
+    fn search_memory(&self, pattern: &[u8], scope: MemorySearchScope) -> Result<MemorySearch>
+    {
+        traced(
+            self.log.as_ref(),
+            "search_memory",
+            |full| {
+                json!({
+                    "pattern": bytes_summary(pattern, full),
+                    "unreadable": scope.unreadable,
+                    "large": scope.large,
+                    "max_hits": scope.max_hits,
+                })
+            },
+            || self.inner.search_memory(pattern, scope),
+            |found, _| json!({ "hits": found.hits.len(), "truncated": found.truncated }),
+        )
+    }
+
+    fn thread_stack_bounds(&self, thread: ThreadId) -> Result<(Address, Address)>
+    {
+        traced(
+            self.log.as_ref(),
+            "thread_stack_bounds",
+            |_| json!({ "thread": thread.raw() }),
+            || self.inner.thread_stack_bounds(thread),
+            |(start, end), _| json!({ "start": hex(*start), "end": hex(*end) }),
+        )
+    }
+
+    fn read_thread_local(&mut self, thread: ThreadId, name: &str) -> Result<Vec<ThreadLocalValue>>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "read_thread_local",
+            |_| json!({ "thread": thread.raw(), "name": name }),
+            || inner.read_thread_local(thread, name),
+            |values, _| json!({ "count": values.len() }),
+        )
+    }
+
+    fn add_breakpoint(&mut self, request: BreakpointRequest) -> Result<BreakpointId>
+    {
+        let Self { inner, log } = self;
+        let kind = format!("{:?}", request.kind);
+        let enabled = request.enabled;
+        traced(
+            log.as_ref(),
+            "add_breakpoint",
+            |_| json!({ "kind": kind, "enabled": enabled }),
+            || inner.add_breakpoint(request),
+            |id, _| json!({ "id": id.raw() }),
+        )
+    }
+
+    fn remove_breakpoint(&mut self, id: BreakpointId) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "remove_breakpoint",
+            |_| json!({ "id": id.raw() }),
+            || inner.remove_breakpoint(id),
+            none,
+        )
+    }
+
+    fn enable_breakpoint(&mut self, id: BreakpointId) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "enable_breakpoint",
+            |_| json!({ "id": id.raw() }),
+            || inner.enable_breakpoint(id),
+            none,
+        )
+    }
+
+    fn disable_breakpoint(&mut self, id: BreakpointId) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "disable_breakpoint",
+            |_| json!({ "id": id.raw() }),
+            || inner.disable_breakpoint(id),
+            none,
+        )
+    }
+
+    fn toggle_breakpoint(&mut self, id: BreakpointId) -> Result<bool>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "toggle_breakpoint",
+            |_| json!({ "id": id.raw() }),
+            || inner.toggle_breakpoint(id),
+            |enabled, _| json!({ "enabled": enabled }),
+        )
+    }
+
+    fn set_breakpoint_ignore_count(&mut self, id: BreakpointId, count: u64) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "set_breakpoint_ignore_count",
+            |_| json!({ "id": id.raw(), "count": count }),
+            || inner.set_breakpoint_ignore_count(id, count),
+            none,
+        )
+    }
+
+    fn resolve_location(&mut self, location: &BreakpointLocation) -> Result<Vec<Address>>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "resolve_location",
+            |_| json!({ "location": format!("{location:?}") }),
+            || inner.resolve_location(location),
+            |addresses, _| Value::Array(addresses.iter().map(|&address| hex(address)).collect()),
+        )
+    }
+
+    fn code_lines(&mut self, file: &str) -> Result<BTreeSet<u32>>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "code_lines",
+            |_| json!({ "file": file }),
+            || inner.code_lines(file),
+            |lines, _| json!({ "lines": lines.len() }),
+        )
+    }
+
+    fn find_function(&mut self, name: &str) -> Result<Vec<(Address, SymbolName)>>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "find_function",
+            |_| json!({ "name": name }),
+            || inner.find_function(name),
+            |functions, _| {
+                Value::Array(
+                    functions
+                        .iter()
+                        .map(|(address, symbol)| json!({ "addr": hex(*address), "name": symbol.display_name() }))
+                        .collect(),
+                )
+            },
+        )
+    }
+
+    fn modules(&self) -> Result<Vec<ImageInfo>>
+    {
+        self.inner.modules()
+    }
+
+    fn write_snapshot_with_options(&mut self, path: &Path, options: SnapshotOptions) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "write_snapshot",
+            |_| {
+                json!({
+                    "path": path.display().to_string(),
+                    "all_regions": options.regions == SnapshotRegions::All,
+                    "max_bytes": options.max_bytes,
+                })
+            },
+            || inner.write_snapshot_with_options(path, options),
+            none,
+        )
+    }
+
+    fn resolve_address(&mut self, expr: &AddressExpr) -> Result<Address>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "resolve_address",
+            |_| json!({ "expr": expr.to_string() }),
+            || inner.resolve_address(expr),
+            |&address, _| hex(address),
+        )
+    }
+
+    fn stack_trace(&mut self, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "stack_trace",
+            |_| json!({ "max_frames": max_frames }),
+            || inner.stack_trace(max_frames),
+            |frames, _| json!({ "frames": frames.len() }),
+        )
+    }
+
+    fn stack_trace_for(&mut self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "stack_trace_for",
+            |_| json!({ "thread": thread.raw(), "max_frames": max_frames }),
+            || inner.stack_trace_for(thread, max_frames),
+            |frames, _| json!({ "frames": frames.len() }),
+        )
+    }
+
+    fn stack_trace_with_options(&mut self, options: UnwindOptions) -> Result<StackTrace>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "stack_trace_with_options",
+            |_| json!({ "max_frames": options.max_frames, "diagnostics": options.collect_diagnostics }),
+            || inner.stack_trace_with_options(options),
+            |trace, _| json!({ "frames": trace.frames.len() }),
+        )
+    }
+
+    fn write_memory(&mut self, addr: Address, data: &[u8]) -> Result<usize>
+    {
+        let Self { inner, log } = self;
+        // Writes over a software breakpoint change the code it restores
+        let patched = log.as_ref().map(|_| patched_breakpoints(inner.as_ref(), addr, data.len()));
+        traced(
+            log.as_ref(),
+            "write_memory",
+            |full| json!({ "addr": hex(addr), "data": bytes_summary(data, full) }),
+            || inner.write_memory(addr, data),
+            |written, _| match patched.filter(|ids| !ids.is_empty()) {
+                Some(ids) => json!({ "written": written, "breakpoints": ids }),
+                None => json!({ "written": written }),
+            },
+        )
+    }
+
+    fn write_memory_checked(&mut self, addr: Address, data: &[u8]) -> Result<usize>
+    {
+        let Self { inner, log } = self;
+        let patched = log.as_ref().map(|_| patched_breakpoints(inner.as_ref(), addr, data.len()));
+        traced(
+            log.as_ref(),
+            "write_memory_checked",
+            |full| json!({ "addr": hex(addr), "data": bytes_summary(data, full) }),
+            || inner.write_memory_checked(addr, data),
+            |written, _| match patched.filter(|ids| !ids.is_empty()) {
+                Some(ids) => json!({ "written": written, "breakpoints": ids }),
+                None => json!({ "written": written }),
+            },
+        )
+    }
+
+    fn disassemble(&mut self, start: Address, count: usize) -> Result<Vec<DisassembledInstruction>>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "disassemble",
+            |_| json!({ "addr": hex(start), "count": count }),
+            || inner.disassemble(start, count),
+            |listing, _| json!({ "decoded": listing.len() }),
+        )
+    }
+
+    fn suspend(&mut self) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(log.as_ref(), "suspend", |_| Value::Null, || inner.suspend(), none)
+    }
+
+    fn resume(&mut self) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(log.as_ref(), "resume", |_| Value::Null, || inner.resume(), none)
+    }
+
+    fn step_instruction(&mut self) -> Result<StopReason>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "step_instruction",
+            |_| Value::Null,
+            || inner.step_instruction(),
+            |reason, _| json!({ "reason": format_stop_reason(*reason) }),
+        )
+    }
+
+    fn step_over(&mut self) -> Result<StopReason>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "step_over",
+            |_| Value::Null,
+            || inner.step_over(),
+            |reason, _| json!({ "reason": format_stop_reason(*reason) }),
+        )
+    }
+
+    fn step_out(&mut self) -> Result<StopReason>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "step_out",
+            |_| Value::Null,
+            || inner.step_out(),
+            |reason, _| json!({ "reason": format_stop_reason(*reason) }),
+        )
+    }
+
+    fn set_active_thread(&mut self, thread: ThreadId) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "set_active_thread",
+            |_| json!({ "thread": thread.raw() }),
+            || inner.set_active_thread(thread),
+            none,
+        )
+    }
+
+    fn refresh_threads(&mut self) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "refresh_threads",
+            |_| Value::Null,
+            || inner.refresh_threads(),
+            none,
+        )
+    }
+
+    fn suspend_thread(&mut self, thread: ThreadId) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "suspend_thread",
+            |_| json!({ "thread": thread.raw() }),
+            || inner.suspend_thread(thread),
+            none,
+        )
+    }
+
+    fn resume_thread(&mut self, thread: ThreadId) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "resume_thread",
+            |_| json!({ "thread": thread.raw() }),
+            || inner.resume_thread(thread),
+            none,
+        )
+    }
+
+    fn set_signal_policy(&mut self, signal: i32, policy: SignalPolicy) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "set_signal_policy",
+            |_| json!({ "signal": signal, "policy": policy.to_string() }),
+            || inner.set_signal_policy(signal, policy),
+            none,
+        )
+    }
+
+    fn signal_policies(&self) -> SignalPolicies
+    {
+        self.inner.signal_policies()
+    }
+
+    fn set_stop_policy(&mut self, policy: StopPolicy) -> Result<()>
+    {
+        let Self { inner, log } = self;
+        traced(
+            log.as_ref(),
+            "set_stop_policy",
+            |_| json!({ "policy": policy.to_string() }),
+            || inner.set_stop_policy(policy),
+            none,
+        )
+    }
+
+    fn stop_policy(&self) -> StopPolicy
+    {
+        self.inner.stop_policy()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::error::DebuggerError;
+
+    /// Passes through to the system allocator, counting allocations per thread
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator
+    {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8
+        {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout)
+        {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations(call: impl FnOnce()) -> usize
+    {
+        let before = ALLOCATIONS.with(Cell::get);
+        call();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    /// Backend whose memory is all `0x5a` except the unmapped page at 0, with
+    /// a software breakpoint at 0x2004
+    #[derive(Default)]
+    struct Fake
+    {
+        attached: bool,
+    }
+
+    impl TargetInspector for Fake
+    {
+        fn architecture(&self) -> Architecture
+        {
+            Architecture::Arm64
+        }
+
+        fn is_attached(&self) -> bool
+        {
+            self.attached
+        }
+
+        fn is_stopped(&self) -> bool
+        {
+            true
+        }
+
+        fn stop_reason(&self) -> StopReason
+        {
+            StopReason::Suspended
+        }
+
+        fn breakpoints(&self) -> Vec<BreakpointInfo>
+        {
+            vec![BreakpointInfo::new(
+                BreakpointId::from_raw(7),
+                Address::from(0x2004),
+                BreakpointKind::Software,
+            )]
+        }
+
+        fn threads(&self) -> Result<Vec<ThreadId>>
+        {
+            Ok(vec![ThreadId::from(1)])
+        }
+
+        fn active_thread(&self) -> Option<ThreadId>
+        {
+            Some(ThreadId::from(1))
+        }
+
+        fn read_registers(&self) -> Result<Registers>
+        {
+            Err(DebuggerError::NotAttached)
+        }
+
+        fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+        {
+            if addr.value() < 0x1000 {
+                return Err(DebuggerError::InvalidArgument("unmapped".to_string()));
+            }
+            Ok(vec![0x5a; len])
+        }
+
+        fn read_u64(&self, _addr: Address) -> Result<u64>
+        {
+            Ok(0x5a5a_5a5a_5a5a_5a5a)
+        }
+
+        fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+        {
+            Ok(Vec::new())
+        }
+    }
+
+    impl Debugger for Fake
+    {
+        fn launch_with_options(&mut self, _program: &str, _options: &LaunchOptions) -> Result<ProcessId>
+        {
+            Ok(ProcessId::from(7))
+        }
+
+        fn attach(&mut self, _pid: ProcessId) -> Result<()>
+        {
+            self.attached = true;
+            Ok(())
+        }
+
+        fn detach(&mut self) -> Result<()>
+        {
+            self.attached = false;
+            Ok(())
+        }
+
+        fn write_registers(&mut self, _regs: &Registers) -> Result<()>
+        {
+            Ok(())
+        }
+
+        fn write_memory(&mut self, _addr: Address, data: &[u8]) -> Result<usize>
+        {
+            Ok(data.len())
+        }
+
+        fn suspend(&mut self) -> Result<()>
+        {
+            Ok(())
+        }
+
+        fn resume(&mut self) -> Result<()>
+        {
+            Ok(())
+        }
+
+        fn set_active_thread(&mut self, _thread: ThreadId) -> Result<()>
+        {
+            Ok(())
+        }
+
+        fn refresh_threads(&mut self) -> Result<()>
+        {
+            Ok(())
+        }
+    }
+
+    /// Log buffer the test keeps a handle to
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared
+    {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize>
+        {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()>
+        {
+            Ok(())
+        }
+    }
+
+    impl Shared
+    {
+        fn lines(&self) -> Vec<String>
+        {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(ToString::to_string)
+                .collect()
+        }
+
+        fn records(&self) -> Vec<OperationRecord>
+        {
+            self.lines().iter().map(|line| serde_json::from_str(line).unwrap()).collect()
+        }
+    }
+
+    fn journaled(options: OperationLogOptions) -> (JournaledDebugger, Shared)
+    {
+        let out = Shared::default();
+        let mut debugger = JournaledDebugger::new(Box::new(Fake::default()));
+        debugger
+            .set_operation_log(Some(OperationLog::new(out.clone(), options)))
+            .unwrap();
+        (debugger, out)
+    }
+
+    #[test]
+    fn operations_are_recorded_with_arguments_results_and_errors()
+    {
+        let (mut debugger, out) = journaled(OperationLogOptions::default());
+
+        debugger.attach(ProcessId::from(4242)).unwrap();
+        assert!(debugger.is_attached() && debugger.is_stopped());
+        debugger.read_memory(Address::from(0x1000), 4).unwrap();
+        debugger.read_memory(Address::from(0), 4).unwrap_err();
+        debugger.write_memory(Address::from(0x2000), &[1, 2, 3]).unwrap();
+        debugger.resume().unwrap();
+
+        let records = out.records();
+        let ops: Vec<_> = records.iter().map(|record| record.op.as_str()).collect();
+        assert_eq!(ops, ["attach", "read_memory", "read_memory", "write_memory", "resume"]);
+        assert!(records.iter().enumerate().all(|(seq, record)| record.seq == seq as u64));
+        assert!(records.iter().all(|record| record.wall_ms > 0));
+
+        assert_eq!(records[0].args, json!({ "pid": 4242 }));
+        assert_eq!(records[0].status, OperationStatus::Ok);
+        let attach = &out.lines()[0];
+        assert!(!attach.contains("\"result\"") && !attach.contains("\"error\""), "{attach}");
+
+        assert_eq!(records[1].args, json!({ "addr": "0x1000", "len": 4 }));
+        assert_eq!(
+            records[1].result,
+            json!({ "len": 4, "fnv1a": format!("0x{:016x}", fnv1a(&[0x5a; 4])) })
+        );
+
+        assert_eq!(records[2].status, OperationStatus::Error);
+        assert_eq!(records[2].error.as_deref(), Some("Invalid argument: unmapped"));
+        assert_eq!(records[2].result, Value::Null);
+
+        // Written bytes are digested too
+        assert_eq!(records[3].args["data"]["len"], 3);
+        assert!(records[3].args["data"].get("hex").is_none());
+        assert_eq!(records[3].result, json!({ "written": 3 }));
+        assert_eq!(records[4].args, Value::Null);
+    }
+
+    #[test]
+    fn memory_is_only_recorded_in_full_when_asked()
+    {
+        let (mut debugger, out) = journaled(OperationLogOptions { full_memory: true });
+
+        debugger.read_memory(Address::from(0x1000), 2).unwrap();
+        debugger.write_memory(Address::from(0x2000), &[0xde, 0xad]).unwrap();
+
+        let records = out.records();
+        assert_eq!(records[0].result, json!({ "len": 2, "hex": "5a5a" }));
+        assert_eq!(records[1].args["data"], json!({ "len": 2, "hex": "dead" }));
+    }
+
+    #[test]
+    fn writes_over_a_software_breakpoint_name_it()
+    {
+        let (mut debugger, out) = journaled(OperationLogOptions::default());
+
+        debugger.write_memory(Address::from(0x2006), &[0; 4]).unwrap();
+        debugger.write_memory(Address::from(0x2008), &[0; 4]).unwrap();
+
+        let records = out.records();
+        assert_eq!(records[0].result, json!({ "written": 4, "breakpoints": [7] }));
+        assert_eq!(records[1].result, json!({ "written": 4 }));
+    }
+
+    #[test]
+    fn turning_the_log_off_stops_recording()
+    {
+        let (mut debugger, out) = journaled(OperationLogOptions::default());
+        debugger.suspend().unwrap();
+        debugger.set_operation_log(None).unwrap();
+        debugger.resume().unwrap();
+        assert_eq!(out.records().len(), 1);
+
+        // Plain backends have nothing to record with
+        assert!(Fake::default().set_operation_log(None).is_err());
+    }
+
+    #[test]
+    fn disabled_log_adds_no_allocations_to_reads()
+    {
+        let mut debugger = JournaledDebugger::new(Box::new(Fake::default()));
+        debugger.attach(ProcessId::from(1)).unwrap();
+        let fake = Fake { attached: true };
+        let address = Address::from(0x1000);
+
+        assert_eq!(allocations(|| assert!(debugger.read_u64(address).is_ok())), 0);
+        assert_eq!(allocations(|| assert_eq!(debugger.function_start(address), None)), 0);
+        assert_eq!(
+            allocations(|| assert_eq!(debugger.thread_suspend_count(ThreadId::from(1)), 0)),
+            0
+        );
+        assert_eq!(
+            allocations(|| assert!(debugger.read_memory(address, 16).is_ok())),
+            allocations(|| assert!(fake.read_memory(address, 16).is_ok()))
+        );
+    }
+}
@@ -0,0 +1,460 @@
+//! Background pumps for a launched target's captured stdout/stderr.
+//!
+//! A blocking `read()` on a pipe only returns when the target writes or closes
+//! its end, so a reader thread for a quiet target can't be stopped: joining it
+//! on shutdown hangs until the target happens to print something. An
+//! [`OutputPump`] instead puts the pipe in non-blocking mode and waits on it
+//! with `poll()` alongside a private wakeup pipe. [`OutputPump::shutdown`]
+//! writes to the wakeup pipe, the pump drains whatever the target already
+//! wrote until a deadline, hands it to its [`OutputSink`] and exits, and the
+//! caller joins the thread.
+//!
+//! Each `read()` is capped at [`MAX_READ_CHUNK`] bytes, so one huge write
+//! reaches the sink in pieces and the pump checks for shutdown in between.
+//!
+//! ```rust
+//! use std::process::{Command, Stdio};
+//! use std::time::Duration;
+//!
+//! use ferros_core::output::{LineSink, OutputPump};
+//!
+//! // A target that stays alive but never prints
+//! let mut child = Command::new("sleep")
+//!     .arg("5")
+//!     .stdout(Stdio::piped())
+//!     .spawn()?;
+//! let stdout = std::fs::File::from(std::os::fd::OwnedFd::from(child.stdout.take().unwrap()));
+//!
+//! let pump = OutputPump::spawn(
+//!     "stdout",
+//!     stdout,
+//!     Box::new(LineSink::new(|line| {
+//!         println!("{line}");
+//!         true
+//!     })),
+//! )?;
+//! // Returns promptly instead of waiting five seconds for EOF
+//! pump.shutdown(Duration::from_millis(50));
+//! # child.kill()?;
+//! # child.wait()?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, RawFd};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Largest number of bytes a pump reads from its pipe in one go.
+pub const MAX_READ_CHUNK: usize = 16 * 1024;
+
+/// How long [`OutputPump`]'s `Drop` keeps draining buffered output.
+pub const DEFAULT_DRAIN_DEADLINE: Duration = Duration::from_millis(100);
+
+/// Receives the bytes an [`OutputPump`] reads.
+pub trait OutputSink: Send
+{
+    /// Handle the next chunk of output; return `false` to stop the pump.
+    fn write(&mut self, bytes: &[u8]) -> bool;
+
+    /// Called once when the pump stops, after the last [`OutputSink::write`].
+    fn finish(&mut self) {}
+}
+
+impl<F> OutputSink for F
+where
+    F: FnMut(&[u8]) -> bool + Send,
+{
+    fn write(&mut self, bytes: &[u8]) -> bool
+    {
+        self(bytes)
+    }
+}
+
+/// An [`OutputSink`] that splits output into lines.
+///
+/// Line endings (`\n` or `\r\n`) are stripped, invalid UTF-8 is replaced, and
+/// a trailing line without a newline is emitted when the pump stops.
+pub struct LineSink<F>
+{
+    pending: Vec<u8>,
+    emit: F,
+}
+
+impl<F> LineSink<F>
+where
+    F: FnMut(String) -> bool + Send,
+{
+    /// Call `emit` with each complete line; it returns `false` to stop the pump.
+    pub fn new(emit: F) -> Self
+    {
+        Self {
+            pending: Vec::new(),
+            emit,
+        }
+    }
+
+    fn emit_line(&mut self, mut line: &[u8]) -> bool
+    {
+        if let Some(stripped) = line.strip_suffix(b"\r") {
+            line = stripped;
+        }
+        (self.emit)(String::from_utf8_lossy(line).into_owned())
+    }
+}
+
+impl<F> OutputSink for LineSink<F>
+where
+    F: FnMut(String) -> bool + Send,
+{
+    fn write(&mut self, bytes: &[u8]) -> bool
+    {
+        let mut rest = bytes;
+        while let Some(newline) = rest.iter().position(|&byte| byte == b'\n') {
+            let keep_going = if self.pending.is_empty() {
+                self.emit_line(&rest[..newline])
+            } else {
+                let mut line = std::mem::take(&mut self.pending);
+                line.extend_from_slice(&rest[..newline]);
+                self.emit_line(&line)
+            };
+            if !keep_going {
+                return false;
+            }
+            rest = &rest[newline + 1..];
+        }
+        self.pending.extend_from_slice(rest);
+        true
+    }
+
+    fn finish(&mut self)
+    {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.emit_line(&line);
+        }
+    }
+}
+
+/// A thread copying one captured pipe into an [`OutputSink`].
+///
+/// Dropping the pump shuts it down with [`DEFAULT_DRAIN_DEADLINE`].
+pub struct OutputPump
+{
+    name: String,
+    wakeup: File,
+    /// Read end of the wakeup pipe, held so writing to it never raises `SIGPIPE`.
+    _wakeup_read: Arc<File>,
+    drain_millis: Arc<AtomicU64>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl OutputPump
+{
+    /// Start pumping `source` into `sink` on a new thread.
+    ///
+    /// `source` is switched to non-blocking mode. `name` labels the thread.
+    ///
+    /// ## Errors
+    ///
+    /// Fails if the pipe can't be made non-blocking, the wakeup pipe can't be
+    /// created or the thread can't be spawned.
+    pub fn spawn(name: &str, source: File, mut sink: Box<dyn OutputSink>) -> io::Result<Self>
+    {
+        set_nonblocking(source.as_raw_fd())?;
+        let (wakeup_read, wakeup) = wakeup_pipe()?;
+        let wakeup_read = Arc::new(wakeup_read);
+        let pump_wakeup = wakeup_read.clone();
+        let drain_millis = Arc::new(AtomicU64::new(0));
+        let drain = drain_millis.clone();
+        let handle = thread::Builder::new().name(format!("ferros-output-{name}")).spawn(move || {
+            pump(&source, &pump_wakeup, &drain, sink.as_mut());
+            sink.finish();
+        })?;
+        Ok(Self {
+            name: name.to_string(),
+            wakeup,
+            _wakeup_read: wakeup_read,
+            drain_millis,
+            handle: Some(handle),
+        })
+    }
+
+    /// Name given to [`OutputPump::spawn`].
+    #[must_use]
+    pub fn name(&self) -> &str
+    {
+        &self.name
+    }
+
+    /// Whether the pump has stopped on its own (EOF, a read error or its sink
+    /// returning `false`).
+    #[must_use]
+    pub fn is_finished(&self) -> bool
+    {
+        self.handle.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Stop the pump and join its thread.
+    ///
+    /// Output the target wrote before the call is still delivered for up to
+    /// `drain`. Returns `false` if the pump thread panicked.
+    pub fn shutdown(mut self, drain: Duration) -> bool
+    {
+        self.stop(drain)
+    }
+
+    fn stop(&mut self, drain: Duration) -> bool
+    {
+        let Some(handle) = self.handle.take() else {
+            return true;
+        };
+        let millis = u64::try_from(drain.as_millis()).unwrap_or(u64::MAX);
+        self.drain_millis.store(millis, Ordering::Relaxed);
+        // A full wakeup pipe already holds a wakeup, so a failed write is fine
+        let _ = (&self.wakeup).write(&[1]);
+        let joined = handle.join().is_ok();
+        if !joined {
+            tracing::warn!("Output pump for {} panicked", self.name);
+        }
+        joined
+    }
+}
+
+impl Drop for OutputPump
+{
+    fn drop(&mut self)
+    {
+        self.stop(DEFAULT_DRAIN_DEADLINE);
+    }
+}
+
+/// Outcome of one non-blocking read.
+enum ReadOutcome
+{
+    Data(usize),
+    Empty,
+    Closed,
+}
+
+fn pump(source: &File, wakeup: &File, drain_millis: &AtomicU64, sink: &mut dyn OutputSink)
+{
+    let mut buffer = vec![0_u8; MAX_READ_CHUNK];
+    let mut fds = [
+        libc::pollfd {
+            fd: source.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: wakeup.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    loop {
+        fds[0].revents = 0;
+        fds[1].revents = 0;
+        // SAFETY: `fds` is a valid array of two pollfd structs for the call's duration
+        if unsafe { libc::poll(fds.as_mut_ptr(), 2, -1) } < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            tracing::warn!("Polling process output failed: {err}");
+            return;
+        }
+        if fds[1].revents != 0 {
+            let deadline = Instant::now() + Duration::from_millis(drain_millis.load(Ordering::Relaxed));
+            drain(source, &mut buffer, deadline, sink);
+            return;
+        }
+        if fds[0].revents == 0 {
+            continue;
+        }
+        match read_chunk(source, &mut buffer) {
+            ReadOutcome::Data(read) => {
+                if !sink.write(&buffer[..read]) {
+                    return;
+                }
+            }
+            ReadOutcome::Empty => {}
+            ReadOutcome::Closed => return,
+        }
+    }
+}
+
+/// Hand over what's already buffered in the pipe, giving up at `deadline`.
+fn drain(source: &File, buffer: &mut [u8], deadline: Instant, sink: &mut dyn OutputSink)
+{
+    while Instant::now() < deadline {
+        match read_chunk(source, buffer) {
+            ReadOutcome::Data(read) => {
+                if !sink.write(&buffer[..read]) {
+                    return;
+                }
+            }
+            ReadOutcome::Empty | ReadOutcome::Closed => return,
+        }
+    }
+}
+
+fn read_chunk(mut source: &File, buffer: &mut [u8]) -> ReadOutcome
+{
+    loop {
+        match source.read(buffer) {
+            Ok(0) => return ReadOutcome::Closed,
+            Ok(read) => return ReadOutcome::Data(read),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return ReadOutcome::Empty,
+            Err(err) => {
+                tracing::warn!("Failed to read process output: {err}");
+                return ReadOutcome::Closed;
+            }
+        }
+    }
+}
+
+fn set_nonblocking(fd: RawFd) -> io::Result<()>
+{
+    // SAFETY: fcntl on a descriptor we own, with no pointer arguments
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn set_cloexec(fd: RawFd) -> io::Result<()>
+{
+    // SAFETY: as in `set_nonblocking`
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFD);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Create the `(read, write)` pipe used to interrupt a pump's `poll()`.
+fn wakeup_pipe() -> io::Result<(File, File)>
+{
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for the two descriptors pipe() writes
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: pipe() just returned these descriptors and nothing else owns them
+    let (read, write) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    for fd in fds {
+        set_cloexec(fd)?;
+        set_nonblocking(fd)?;
+    }
+    Ok((read, write))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::os::fd::OwnedFd;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn spawn_fixture(script: &str) -> (Child, File)
+    {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdout = File::from(OwnedFd::from(child.stdout.take().unwrap()));
+        (child, stdout)
+    }
+
+    fn collect_lines(lines: &Arc<Mutex<Vec<String>>>) -> Box<dyn OutputSink>
+    {
+        let lines = lines.clone();
+        Box::new(LineSink::new(move |line| {
+            lines.lock().unwrap().push(line);
+            true
+        }))
+    }
+
+    #[test]
+    fn quitting_on_a_silent_target_returns_promptly_and_joins_the_pump()
+    {
+        let (mut child, stdout) = spawn_fixture("sleep 30");
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let pump = OutputPump::spawn("stdout", stdout, collect_lines(&lines)).unwrap();
+        assert!(!pump.is_finished());
+
+        let started = Instant::now();
+        assert!(pump.shutdown(Duration::from_millis(50)));
+        assert!(
+            started.elapsed() < Duration::from_secs(2),
+            "shutdown took {:?}",
+            started.elapsed()
+        );
+        // The pump thread has exited and released its sink
+        assert_eq!(Arc::strong_count(&lines), 1);
+        assert!(lines.lock().unwrap().is_empty());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn buffered_output_is_drained_on_shutdown()
+    {
+        let (mut child, stdout) = spawn_fixture("printf 'one\\r\\ntwo\\nthree'; sleep 30");
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let pump = OutputPump::spawn("stdout", stdout, collect_lines(&lines)).unwrap();
+        let started = Instant::now();
+        while lines.lock().unwrap().len() < 2 && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(pump.shutdown(Duration::from_millis(50)));
+        assert_eq!(*lines.lock().unwrap(), ["one", "two", "three"]);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn large_writes_reach_the_sink_in_bounded_chunks()
+    {
+        let (mut child, stdout) = spawn_fixture("head -c 200000 /dev/zero");
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let seen = chunks.clone();
+        let pump = OutputPump::spawn(
+            "stdout",
+            stdout,
+            Box::new(move |bytes: &[u8]| {
+                seen.lock().unwrap().push(bytes.len());
+                true
+            }),
+        )
+        .unwrap();
+        child.wait().unwrap();
+        let started = Instant::now();
+        while !pump.is_finished() && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(pump.is_finished(), "pump should stop at EOF");
+        drop(pump);
+
+        let chunks = chunks.lock().unwrap();
+        assert_eq!(chunks.iter().sum::<usize>(), 200_000);
+        assert!(chunks.iter().all(|&len| len <= MAX_READ_CHUNK));
+    }
+}
@@ -0,0 +1,364 @@
+//! Stopping at Rust panics.
+//!
+//! Every panic in a Rust program goes through `rust_begin_unwind`, the
+//! standard library's panic handler, before it unwinds or aborts; `rust_panic`
+//! follows once the panic hook has printed the message. [`PanicBreakpoints`]
+//! sets a hidden breakpoint on the first of the two the target has, found by
+//! symbol name, and [`PanicBreakpoints::on_stop`] recognises a stop there.
+//!
+//! ## Panic Message
+//!
+//! At `rust_begin_unwind` the first argument register holds a
+//! `&core::panic::PanicInfo`, which points at the `fmt::Arguments` of the
+//! message and the `Location` of the `panic!`. Neither layout is stable, so
+//! [`read_panic_info`] expects the one of current toolchains, checks that
+//! every length and string it reads is plausible, and gives up on the rest.
+//! The message is made of the literal pieces of the format string, with
+//! `{…}` for each formatted argument since their types aren't known.
+//!
+//! Without a location from `PanicInfo`, the report falls back to the source
+//! line of the first frame outside the panic machinery.
+//!
+//! ```rust
+//! use ferros_core::panics::PanicReport;
+//!
+//! let report = PanicReport {
+//!     message: Some("index out of bounds: the len is {…} but the index is {…}".to_string()),
+//!     location: Some("src/main.rs:4:5".to_string()),
+//! };
+//! assert_eq!(
+//!     report.to_string(),
+//!     "Panicked at src/main.rs:4:5: index out of bounds: the len is {…} but the index is {…}"
+//! );
+//! ```
+
+use std::fmt;
+
+use crate::breakpoints::{BreakpointBuilder, BreakpointId, BreakpointRequestKind};
+use crate::crash::crash_frame;
+use crate::debugger::Debugger;
+use crate::error::Result;
+use crate::types::{Address, RegisterCategory, StopReason, ThreadId, register_display_order};
+
+/// Breakpoint group panic breakpoints are installed in.
+pub const PANIC_GROUP: &str = "panics";
+
+/// Functions every panic passes through, in order of preference.
+pub const PANIC_ENTRY_POINTS: [&str; 2] = ["rust_begin_unwind", "rust_panic"];
+
+/// Frames unwound to find the `panic!` when `PanicInfo` has no location.
+const PANIC_MAX_FRAMES: usize = 32;
+
+/// Longest string read for a piece or file name; anything longer means the layout isn't the expected one.
+const MAX_STR_LEN: u64 = 4096;
+
+/// Most literal pieces or arguments read from one `fmt::Arguments`.
+const MAX_PIECES: u64 = 64;
+
+/// A panic the target stopped at.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PanicReport
+{
+    /// The panic message, with `{…}` for formatted arguments.
+    pub message: Option<String>,
+    /// Where the panic was raised, as `file:line` or `file:line:column`.
+    pub location: Option<String>,
+}
+
+/// `Panicked at src/main.rs:4:5: boom`, leaving out whatever couldn't be read
+impl fmt::Display for PanicReport
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str("Panicked")?;
+        if let Some(location) = &self.location {
+            write!(f, " at {location}")?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, ": {message}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The hidden breakpoints that stop a target at its panics.
+#[derive(Debug, Clone, Default)]
+pub struct PanicBreakpoints
+{
+    /// Entry point the breakpoints are on, once installed
+    entry_point: Option<&'static str>,
+    /// Breakpoints installed, with the address each traps at
+    breakpoints: Vec<(BreakpointId, Address)>,
+}
+
+impl PanicBreakpoints
+{
+    /// Whether the breakpoints are installed.
+    #[must_use]
+    pub fn is_installed(&self) -> bool
+    {
+        !self.breakpoints.is_empty()
+    }
+
+    /// Entry point the breakpoints are on, e.g. `rust_begin_unwind`.
+    #[must_use]
+    pub fn entry_point(&self) -> Option<&'static str>
+    {
+        self.entry_point
+    }
+
+    /// Set a hidden breakpoint on the first of [`PANIC_ENTRY_POINTS`] the target has.
+    ///
+    /// Returns `false` while none of them resolves, for example because the
+    /// target isn't a Rust program or its symbols haven't loaded yet; call it
+    /// again once they have. Does nothing if already installed.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the error of a breakpoint that can't be added.
+    pub fn install(&mut self, debugger: &mut dyn Debugger) -> Result<bool>
+    {
+        if self.is_installed() {
+            return Ok(true);
+        }
+        for name in PANIC_ENTRY_POINTS {
+            let functions = debugger.find_function(name).unwrap_or_default();
+            if functions.is_empty() {
+                continue;
+            }
+            for (address, _) in functions {
+                let request = BreakpointBuilder::new(BreakpointRequestKind::Software { address })
+                    .group(PANIC_GROUP)
+                    .hidden()
+                    .build();
+                let id = debugger.add_breakpoint(request)?;
+                self.breakpoints.push((id, address));
+            }
+            self.entry_point = Some(name);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Remove the breakpoints, ignoring ones the target no longer has.
+    pub fn remove(&mut self, debugger: &mut dyn Debugger)
+    {
+        for (id, _) in self.breakpoints.drain(..) {
+            let _ = debugger.remove_breakpoint(id);
+        }
+        self.entry_point = None;
+    }
+
+    /// Check a stop against the panic breakpoints.
+    ///
+    /// Returns `None` for stops that aren't at one. Otherwise reads what it
+    /// can of the panic from `thread`, the thread that stopped.
+    pub fn on_stop(&self, debugger: &mut dyn Debugger, reason: StopReason, thread: Option<ThreadId>) -> Option<PanicReport>
+    {
+        let StopReason::Breakpoint(address) = reason else {
+            return None;
+        };
+        if !self.breakpoints.iter().any(|(_, at)| at.value() == address) {
+            return None;
+        }
+
+        let mut report = PanicReport::default();
+        if self.entry_point == Some(PANIC_ENTRY_POINTS[0])
+            && let Some(info) = first_argument(debugger, thread)
+        {
+            let read = |address: u64, len: usize| debugger.read_memory(Address::from(address), len).ok();
+            (report.message, report.location) = read_panic_info(info, &read);
+        }
+        if report.location.is_none() {
+            let frames = match thread {
+                Some(thread) => debugger.stack_trace_for(thread, PANIC_MAX_FRAMES),
+                None => debugger.stack_trace(PANIC_MAX_FRAMES),
+            }
+            .unwrap_or_default();
+            report.location = crash_frame(&frames)
+                .and_then(|index| frames[index].location.as_ref())
+                .map(|location| match location.line {
+                    Some(line) => format!("{}:{line}", location.file),
+                    None => location.file.clone(),
+                });
+        }
+        Some(report)
+    }
+}
+
+/// Value of the first argument register of `thread`, or of the active thread
+fn first_argument(debugger: &dyn Debugger, thread: Option<ThreadId>) -> Option<u64>
+{
+    let registers = match thread {
+        Some(thread) => debugger.read_registers_for(thread),
+        None => debugger.read_registers(),
+    }
+    .ok()?;
+    let row = register_display_order(registers.architecture())
+        .into_iter()
+        .find(|row| row.category == RegisterCategory::Arguments)?;
+    registers.get(row.id)
+}
+
+/// `(message, location)` of the `PanicInfo` at `info`, reading target memory with `read`.
+///
+/// Expects `PanicInfo { message: &fmt::Arguments, location: &Location, .. }`;
+/// either part is `None` if it doesn't read back as expected.
+#[must_use]
+pub fn read_panic_info(info: u64, read: &dyn Fn(u64, usize) -> Option<Vec<u8>>) -> (Option<String>, Option<String>)
+{
+    let message = read_u64(read, info).and_then(|arguments| read_arguments(read, arguments));
+    let location = read_u64(read, info + 8).and_then(|location| read_location(read, location));
+    (message, location)
+}
+
+/// Message of `fmt::Arguments { pieces: &[&str], fmt: Option<&[Placeholder]>, args: &[Argument] }`
+fn read_arguments(read: &dyn Fn(u64, usize) -> Option<Vec<u8>>, arguments: u64) -> Option<String>
+{
+    let pieces = read_u64(read, arguments)?;
+    let piece_count = read_u64(read, arguments + 8)?;
+    let argument_count = read_u64(read, arguments + 40)?;
+    if piece_count > MAX_PIECES || argument_count > MAX_PIECES || piece_count + argument_count == 0 {
+        return None;
+    }
+    let mut message = String::new();
+    for index in 0..piece_count.max(argument_count) {
+        if index < piece_count {
+            let piece = pieces + index * 16;
+            message.push_str(&read_str(read, read_u64(read, piece)?, read_u64(read, piece + 8)?)?);
+        }
+        if index < argument_count {
+            message.push_str("{…}");
+        }
+    }
+    Some(message)
+}
+
+/// `file:line:column` of `Location { file: &str, line: u32, col: u32 }`
+fn read_location(read: &dyn Fn(u64, usize) -> Option<Vec<u8>>, location: u64) -> Option<String>
+{
+    let file = read_str(read, read_u64(read, location)?, read_u64(read, location + 8)?)?;
+    // Newer toolchains keep the file name NUL-terminated
+    let file = file.trim_end_matches('\0');
+    let line = read_u32(read, location + 16)?;
+    let column = read_u32(read, location + 20)?;
+    (!file.is_empty() && line != 0).then(|| format!("{file}:{line}:{column}"))
+}
+
+fn read_u64(read: &dyn Fn(u64, usize) -> Option<Vec<u8>>, address: u64) -> Option<u64>
+{
+    Some(u64::from_le_bytes(read(address, 8)?.get(..8)?.try_into().ok()?))
+}
+
+fn read_u32(read: &dyn Fn(u64, usize) -> Option<Vec<u8>>, address: u64) -> Option<u32>
+{
+    Some(u32::from_le_bytes(read(address, 4)?.get(..4)?.try_into().ok()?))
+}
+
+/// UTF-8 text of `len` bytes at `pointer`, if it looks like text
+fn read_str(read: &dyn Fn(u64, usize) -> Option<Vec<u8>>, pointer: u64, len: u64) -> Option<String>
+{
+    if len == 0 {
+        return Some(String::new());
+    }
+    if pointer == 0 || len > MAX_STR_LEN {
+        return None;
+    }
+    let text = String::from_utf8(read(pointer, usize::try_from(len).ok()?)?).ok()?;
+    let binary = text.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\t' | '\0'));
+    (!binary).then_some(text)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Target memory built up from byte strings at fixed addresses
+    #[derive(Default)]
+    struct Memory(HashMap<u64, u8>);
+
+    impl Memory
+    {
+        fn put(&mut self, address: u64, bytes: &[u8])
+        {
+            for (offset, byte) in bytes.iter().enumerate() {
+                self.0.insert(address + offset as u64, *byte);
+            }
+        }
+
+        fn put_u64s(&mut self, address: u64, values: &[u64])
+        {
+            let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+            self.put(address, &bytes);
+        }
+
+        fn read(&self, address: u64, len: usize) -> Option<Vec<u8>>
+        {
+            (address..address + len as u64).map(|at| self.0.get(&at).copied()).collect()
+        }
+    }
+
+    /// `panic!("index {} out of {}", ..)` at `src/main.rs:4:5`, with `PanicInfo` at 0x1000
+    fn panic_memory() -> Memory
+    {
+        let mut memory = Memory::default();
+        // PanicInfo: &Arguments, &Location, can_unwind, force_no_backtrace
+        memory.put_u64s(0x1000, &[0x2000, 0x3000, 1]);
+        // Arguments: pieces, fmt: None, args
+        memory.put_u64s(0x2000, &[0x4000, 2, 0, 0, 0x5000, 2]);
+        memory.put_u64s(0x4000, &[0x6000, 6, 0x6010, 8]);
+        memory.put(0x6000, b"index ");
+        memory.put(0x6010, b" out of ");
+        // Location: file, line, column
+        memory.put_u64s(0x3000, &[0x7000, 11]);
+        memory.put(0x3010, &[4, 0, 0, 0, 5, 0, 0, 0]);
+        memory.put(0x7000, b"src/main.rs");
+        memory
+    }
+
+    #[test]
+    fn panic_info_yields_the_message_pieces_and_location()
+    {
+        let memory = panic_memory();
+        let (message, location) = read_panic_info(0x1000, &|address, len| memory.read(address, len));
+        assert_eq!(message.as_deref(), Some("index {…} out of {…}"));
+        assert_eq!(location.as_deref(), Some("src/main.rs:4:5"));
+    }
+
+    #[test]
+    fn implausible_layouts_are_not_read_as_text()
+    {
+        let mut memory = panic_memory();
+        // A piece count no format string has
+        memory.put_u64s(0x2008, &[100_000]);
+        // A file name that isn't text
+        memory.put(0x7000, &[0xff, 0x01, 0x02]);
+        let (message, location) = read_panic_info(0x1000, &|address, len| memory.read(address, len));
+        assert_eq!((message, location), (None, None));
+
+        // Unmapped PanicInfo
+        assert_eq!(
+            read_panic_info(0x9000, &|address, len| memory.read(address, len)),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn reports_leave_out_what_could_not_be_read()
+    {
+        let report = |message: Option<&str>, location: Option<&str>| {
+            PanicReport {
+                message: message.map(str::to_string),
+                location: location.map(str::to_string),
+            }
+            .to_string()
+        };
+        assert_eq!(report(Some("boom"), Some("src/lib.rs:9")), "Panicked at src/lib.rs:9: boom");
+        assert_eq!(report(Some("boom"), None), "Panicked: boom");
+        assert_eq!(report(None, Some("src/lib.rs:9")), "Panicked at src/lib.rs:9");
+        assert_eq!(report(None, None), "Panicked");
+    }
+}
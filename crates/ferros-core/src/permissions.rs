@@ -0,0 +1,640 @@
+//! # Permission Advice
+//!
+//! Explains why a privileged operation was refused and what to do about it.
+//!
+//! Debugging another process on macOS needs root or the
+//! `com.apple.security.cs.debugger` entitlement, and even then the kernel
+//! refuses targets it protects (System Integrity Protection, hardened binaries
+//! without `get-task-allow`). A bare `KERN_PROTECTION_FAILURE` says none of
+//! that, so backends attach a [`PermissionAdvice`] to every
+//! [`DebuggerError::PermissionDenied`](crate::error::DebuggerError::PermissionDenied)
+//! they return. The advice is chosen from the operation that failed and the
+//! [`PermissionEnvironment`]: who ferros runs as, how its executable is signed
+//! and whether SIP is on.
+//!
+//! The environment comes from an [`EnvironmentProbe`]. [`current_environment`]
+//! probes the running system once and caches the result.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ferros_core::permissions::{
+//!     CodeSigning, PermissionAdvice, PermissionCause, PermissionEnvironment, PrivilegedOperation,
+//! };
+//!
+//! let environment = PermissionEnvironment {
+//!     euid: 501,
+//!     executable: None,
+//!     signing: Some(CodeSigning::Unsigned),
+//!     sip_enabled: Some(true),
+//!     command: Some("ferros attach 4242".to_string()),
+//! };
+//! let advice = PermissionAdvice::for_operation(PrivilegedOperation::Attach, &environment);
+//! assert_eq!(advice.cause, PermissionCause::MissingPrivileges);
+//! assert!(advice.remedies[0].contains("sudo ferros attach 4242"));
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use object::read::macho::MachOFile64;
+use object::{Endianness, macho};
+
+/// Entitlement that lets a signed debugger call `task_for_pid()` without root.
+pub const DEBUGGER_ENTITLEMENT: &str = "com.apple.security.cs.debugger";
+
+/// Entitlement that lets a target be debugged despite the hardened runtime.
+pub const GET_TASK_ALLOW_ENTITLEMENT: &str = "com.apple.security.get-task-allow";
+
+/// Magic of the embedded signature super blob (`CSMAGIC_EMBEDDED_SIGNATURE`).
+const EMBEDDED_SIGNATURE_MAGIC: u32 = 0xfade_0cc0;
+
+/// Magic of the entitlements plist blob (`CSMAGIC_EMBEDDED_ENTITLEMENTS`).
+const ENTITLEMENTS_MAGIC: u32 = 0xfade_7171;
+
+/// Index slot holding the entitlements blob (`CSSLOT_ENTITLEMENTS`).
+const ENTITLEMENTS_SLOT: u32 = 5;
+
+/// Operation that needs more than the usual user privileges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrivilegedOperation
+{
+    /// Getting a process's task port with `task_for_pid()`, on attach and launch
+    Attach,
+    /// Writing a thread's debug registers with `thread_set_state()`
+    HardwareBreakpoint,
+    /// Taking over the target's exception ports
+    ExceptionPorts,
+}
+
+impl fmt::Display for PrivilegedOperation
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::Attach => "getting the target's task port (task_for_pid)",
+            Self::HardwareBreakpoint => "writing debug registers (thread_set_state)",
+            Self::ExceptionPorts => "taking over the target's exception ports",
+        })
+    }
+}
+
+/// How the ferros executable is code signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeSigning
+{
+    /// No embedded signature
+    Unsigned,
+    /// Signed, but without the debugger entitlement
+    Signed,
+    /// Signed with [`DEBUGGER_ENTITLEMENT`]
+    Entitled,
+}
+
+impl fmt::Display for CodeSigning
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            Self::Unsigned => f.write_str("unsigned"),
+            Self::Signed => write!(f, "signed without {DEBUGGER_ENTITLEMENT}"),
+            Self::Entitled => write!(f, "signed with {DEBUGGER_ENTITLEMENT}"),
+        }
+    }
+}
+
+/// What decides whether ferros may debug other processes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionEnvironment
+{
+    /// Effective user ID ferros runs as
+    pub euid: u32,
+    /// Path of the ferros executable, if known
+    pub executable: Option<PathBuf>,
+    /// How the executable is signed; `None` if it couldn't be read
+    pub signing: Option<CodeSigning>,
+    /// Whether SIP's debugging restrictions are on; `None` if unknown
+    pub sip_enabled: Option<bool>,
+    /// Command line ferros was started with, for `sudo` suggestions
+    pub command: Option<String>,
+}
+
+impl PermissionEnvironment
+{
+    /// Ask `probe` for each part of the environment.
+    pub fn probe(probe: &dyn EnvironmentProbe) -> Self
+    {
+        let executable = probe.executable();
+        let signing = executable.as_deref().and_then(|path| probe.code_signing(path));
+        Self {
+            euid: probe.euid(),
+            executable,
+            signing,
+            sip_enabled: probe.sip_enabled(),
+            command: probe.command(),
+        }
+    }
+
+    /// Whether ferros runs as root.
+    #[must_use]
+    pub fn is_root(&self) -> bool
+    {
+        self.euid == 0
+    }
+
+    /// Whether the executable carries the debugger entitlement.
+    #[must_use]
+    pub fn is_entitled(&self) -> bool
+    {
+        self.signing == Some(CodeSigning::Entitled)
+    }
+
+    /// One `Label: value` line per part of the environment, for `ferros doctor`
+    /// and the TUI's details popup.
+    #[must_use]
+    pub fn summary_lines(&self) -> Vec<String>
+    {
+        let user = if self.is_root() { "root" } else { "not root" };
+        let signing = self
+            .signing
+            .map_or_else(|| "unknown".to_string(), |signing| signing.to_string());
+        let sip = match self.sip_enabled {
+            Some(true) => "enabled",
+            Some(false) => "debugging restrictions disabled",
+            None => "unknown",
+        };
+        let attach = if self.is_root() {
+            "yes (root)"
+        } else if self.is_entitled() {
+            "yes (entitled), to processes of the same user"
+        } else {
+            "no, only to processes ferros launches"
+        };
+        vec![
+            format!("User: {user} (euid {})", self.euid),
+            format!(
+                "Executable: {}",
+                self.executable
+                    .as_deref()
+                    .map_or_else(|| "unknown".to_string(), |path| path.display().to_string())
+            ),
+            format!("Code signing: {signing}"),
+            format!("System Integrity Protection: {sip}"),
+            format!("Can attach to running processes: {attach}"),
+        ]
+    }
+
+    fn sudo_command(&self) -> String
+    {
+        format!("sudo {}", self.command.as_deref().unwrap_or("ferros …"))
+    }
+
+    fn codesign_command(&self) -> String
+    {
+        let executable = self
+            .executable
+            .as_deref()
+            .map_or_else(|| "target/debug/ferros".to_string(), |path| path.display().to_string());
+        format!("codesign --entitlements crates/ferros-core/ferros.entitlements --force --sign - {executable}")
+    }
+}
+
+/// Source of the facts in a [`PermissionEnvironment`].
+///
+/// [`SystemProbe`] asks the running system; tests substitute their own.
+pub trait EnvironmentProbe
+{
+    /// Effective user ID.
+    fn euid(&self) -> u32;
+
+    /// Path of the ferros executable.
+    fn executable(&self) -> Option<PathBuf>;
+
+    /// How the executable at `path` is signed.
+    fn code_signing(&self, path: &Path) -> Option<CodeSigning>;
+
+    /// Whether SIP's debugging restrictions are on.
+    fn sip_enabled(&self) -> Option<bool>;
+
+    /// Command line ferros was started with.
+    fn command(&self) -> Option<String>;
+}
+
+/// [`EnvironmentProbe`] for the running system.
+///
+/// The signature is read from the executable's own `LC_CODE_SIGNATURE` data
+/// and SIP is queried with `csr_get_active_config()` on macOS; both are
+/// unknown elsewhere.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemProbe;
+
+impl EnvironmentProbe for SystemProbe
+{
+    fn euid(&self) -> u32
+    {
+        // SAFETY: geteuid has no preconditions and can't fail
+        unsafe { libc::geteuid() }
+    }
+
+    fn executable(&self) -> Option<PathBuf>
+    {
+        std::env::current_exe().ok()
+    }
+
+    fn code_signing(&self, path: &Path) -> Option<CodeSigning>
+    {
+        code_signing(&std::fs::read(path).ok()?)
+    }
+
+    fn sip_enabled(&self) -> Option<bool>
+    {
+        #[cfg(target_os = "macos")]
+        {
+            crate::platform::macos::permissions::sip_enabled()
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            None
+        }
+    }
+
+    fn command(&self) -> Option<String>
+    {
+        let mut args = std::env::args();
+        let program = Path::new(&args.next()?).file_name()?.to_string_lossy().into_owned();
+        Some(std::iter::once(program).chain(args).collect::<Vec<_>>().join(" "))
+    }
+}
+
+/// The running system's environment, probed with [`SystemProbe`] on first use.
+pub fn current_environment() -> &'static PermissionEnvironment
+{
+    static ENVIRONMENT: OnceLock<PermissionEnvironment> = OnceLock::new();
+    ENVIRONMENT.get_or_init(|| PermissionEnvironment::probe(&SystemProbe))
+}
+
+/// Why the kernel refused, as far as the environment tells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PermissionCause
+{
+    /// Ferros is neither root nor entitled
+    MissingPrivileges,
+    /// Ferros is privileged, but SIP or the target's hardened runtime protects it
+    ProtectedTarget,
+    /// Ferros is entitled but not root, and SIP is off: the target belongs to
+    /// another user
+    OtherUser,
+    /// Ferros is root and SIP is off; nothing in the environment explains it
+    Unexplained,
+}
+
+/// Targeted explanation of a refused [`PrivilegedOperation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionAdvice
+{
+    /// Operation that was refused
+    pub operation: PrivilegedOperation,
+    /// Why, judging by the environment
+    pub cause: PermissionCause,
+    /// What to try, most likely fix first
+    pub remedies: Vec<String>,
+    /// Environment the advice was chosen for
+    pub environment: PermissionEnvironment,
+}
+
+impl PermissionAdvice
+{
+    /// Advice for `operation` having been refused in `environment`.
+    #[must_use]
+    pub fn for_operation(operation: PrivilegedOperation, environment: &PermissionEnvironment) -> Self
+    {
+        let cause = if !environment.is_root() && !environment.is_entitled() {
+            PermissionCause::MissingPrivileges
+        } else if environment.sip_enabled != Some(false) {
+            PermissionCause::ProtectedTarget
+        } else if environment.is_root() {
+            PermissionCause::Unexplained
+        } else {
+            PermissionCause::OtherUser
+        };
+
+        let mut remedies = Vec::new();
+        match cause {
+            PermissionCause::MissingPrivileges => {
+                remedies.push(format!("run as root: {}", environment.sudo_command()));
+                remedies.push(format!(
+                    "or sign ferros with {DEBUGGER_ENTITLEMENT}: {}",
+                    environment.codesign_command()
+                ));
+                if operation == PrivilegedOperation::Attach {
+                    remedies.push("or start the target with `ferros launch`, which needs neither".to_string());
+                }
+            }
+            PermissionCause::ProtectedTarget => {
+                remedies.push(format!(
+                    "debug a copy of the target re-signed with {GET_TASK_ALLOW_ENTITLEMENT}, or rebuild it without the \
+                     hardened runtime"
+                ));
+                remedies.push(
+                    "Apple-signed system binaries can only be debugged with SIP's debugging restrictions off (`csrutil \
+                     enable --without debug` from Recovery)"
+                        .to_string(),
+                );
+            }
+            PermissionCause::OtherUser => {
+                remedies.push(format!(
+                    "if the target belongs to another user, run as root: {}",
+                    environment.sudo_command()
+                ));
+            }
+            PermissionCause::Unexplained => {
+                remedies.push("check that the target is still running and retry".to_string());
+            }
+        }
+
+        Self {
+            operation,
+            cause,
+            remedies,
+            environment: environment.clone(),
+        }
+    }
+
+    /// One-line explanation, without the remedies.
+    #[must_use]
+    pub fn summary(&self) -> String
+    {
+        let env = &self.environment;
+        let privileges = match (env.is_root(), env.is_entitled()) {
+            (true, true) => "root and entitled",
+            (true, false) => "root",
+            (false, _) => "entitled",
+        };
+        match self.cause {
+            PermissionCause::MissingPrivileges => {
+                let signing = match env.signing {
+                    Some(CodeSigning::Unsigned) => "unsigned",
+                    Some(_) => "without the entitlement",
+                    None => "with an unreadable signature",
+                };
+                format!(
+                    "{} needs the {DEBUGGER_ENTITLEMENT} entitlement or root; ferros is running {signing} and not as root",
+                    capitalize(&self.operation.to_string())
+                )
+            }
+            PermissionCause::ProtectedTarget => format!(
+                "{} was refused although ferros is {privileges}: the target is protected by SIP or by the hardened runtime \
+                 without {GET_TASK_ALLOW_ENTITLEMENT}",
+                capitalize(&self.operation.to_string())
+            ),
+            PermissionCause::OtherUser => format!(
+                "{} was refused although ferros is entitled: the entitlement only covers your own processes",
+                capitalize(&self.operation.to_string())
+            ),
+            PermissionCause::Unexplained => format!(
+                "{} was refused although ferros is root and SIP is off",
+                capitalize(&self.operation.to_string())
+            ),
+        }
+    }
+}
+
+impl fmt::Display for PermissionAdvice
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}", self.summary())?;
+        for remedy in &self.remedies {
+            write!(f, "\n  - {remedy}")?;
+        }
+        Ok(())
+    }
+}
+
+fn capitalize(text: &str) -> String
+{
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// How the 64-bit Mach-O image `binary` is signed.
+///
+/// Returns `None` if `binary` isn't a thin 64-bit Mach-O image or its
+/// signature can't be located.
+#[must_use]
+pub fn code_signing(binary: &[u8]) -> Option<CodeSigning>
+{
+    let file = MachOFile64::<Endianness, _>::parse(binary).ok()?;
+    let endian = file.endian();
+    let mut commands = file.macho_load_commands().ok()?;
+    while let Ok(Some(command)) = commands.next() {
+        if command.cmd() != macho::LC_CODE_SIGNATURE {
+            continue;
+        }
+        let data = command.data::<macho::LinkeditDataCommand<Endianness>>().ok()?;
+        let start = data.dataoff.get(endian) as usize;
+        let signature = binary.get(start..start.checked_add(data.datasize.get(endian) as usize)?)?;
+        let entitled = signature_entitlements(signature).is_some_and(|plist| grants(plist, DEBUGGER_ENTITLEMENT));
+        return Some(if entitled {
+            CodeSigning::Entitled
+        } else {
+            CodeSigning::Signed
+        });
+    }
+    Some(CodeSigning::Unsigned)
+}
+
+/// The entitlements plist in an embedded signature super blob, if it has one.
+fn signature_entitlements(signature: &[u8]) -> Option<&[u8]>
+{
+    if read_be_u32(signature, 0)? != EMBEDDED_SIGNATURE_MAGIC {
+        return None;
+    }
+    let count = read_be_u32(signature, 8)? as usize;
+    for index in 0..count {
+        let entry = 12 + index * 8;
+        if read_be_u32(signature, entry)? != ENTITLEMENTS_SLOT {
+            continue;
+        }
+        let offset = read_be_u32(signature, entry + 4)? as usize;
+        if read_be_u32(signature, offset)? != ENTITLEMENTS_MAGIC {
+            return None;
+        }
+        // The blob length includes its 8-byte header
+        let length = read_be_u32(signature, offset + 4)? as usize;
+        return signature.get(offset + 8..offset.checked_add(length)?);
+    }
+    None
+}
+
+/// Whether the entitlements `plist` sets `key` to true.
+fn grants(plist: &[u8], key: &str) -> bool
+{
+    let plist = String::from_utf8_lossy(plist);
+    let needle = format!("<key>{key}</key>");
+    plist
+        .find(&needle)
+        .is_some_and(|at| plist[at + needle.len()..].trim_start().starts_with("<true/>"))
+}
+
+fn read_be_u32(data: &[u8], offset: usize) -> Option<u32>
+{
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    struct FakeProbe
+    {
+        euid: u32,
+        signing: Option<CodeSigning>,
+        sip_enabled: Option<bool>,
+    }
+
+    impl EnvironmentProbe for FakeProbe
+    {
+        fn euid(&self) -> u32
+        {
+            self.euid
+        }
+
+        fn executable(&self) -> Option<PathBuf>
+        {
+            Some(PathBuf::from("/opt/ferros/bin/ferros"))
+        }
+
+        fn code_signing(&self, _path: &Path) -> Option<CodeSigning>
+        {
+            self.signing
+        }
+
+        fn sip_enabled(&self) -> Option<bool>
+        {
+            self.sip_enabled
+        }
+
+        fn command(&self) -> Option<String>
+        {
+            Some("ferros attach 4242".to_string())
+        }
+    }
+
+    fn environment(euid: u32, signing: Option<CodeSigning>, sip_enabled: Option<bool>) -> PermissionEnvironment
+    {
+        PermissionEnvironment::probe(&FakeProbe {
+            euid,
+            signing,
+            sip_enabled,
+        })
+    }
+
+    fn signature(entitlements: Option<&str>) -> Vec<u8>
+    {
+        let mut blob = Vec::new();
+        blob.extend(EMBEDDED_SIGNATURE_MAGIC.to_be_bytes());
+        let plist = entitlements.map(str::as_bytes).unwrap_or_default();
+        let count: u32 = if entitlements.is_some() { 2 } else { 1 };
+        let header = 12 + count * 8;
+        blob.extend((header + 8 + u32::try_from(plist.len()).unwrap()).to_be_bytes());
+        blob.extend(count.to_be_bytes());
+        // Code directory slot first, pointing at a blob the parser must skip
+        blob.extend(0u32.to_be_bytes());
+        blob.extend(header.to_be_bytes());
+        if entitlements.is_some() {
+            blob.extend(ENTITLEMENTS_SLOT.to_be_bytes());
+            blob.extend(header.to_be_bytes());
+            blob.extend(ENTITLEMENTS_MAGIC.to_be_bytes());
+            blob.extend((8 + u32::try_from(plist.len()).unwrap()).to_be_bytes());
+            blob.extend(plist);
+        }
+        blob
+    }
+
+    #[test]
+    fn advice_follows_privileges_and_sip()
+    {
+        use CodeSigning::{Entitled, Signed, Unsigned};
+        use PermissionCause::{MissingPrivileges, OtherUser, ProtectedTarget, Unexplained};
+
+        let matrix = [
+            (501, Some(Unsigned), Some(true), MissingPrivileges),
+            (501, Some(Signed), Some(false), MissingPrivileges),
+            (501, None, None, MissingPrivileges),
+            (501, Some(Entitled), Some(true), ProtectedTarget),
+            (501, Some(Entitled), None, ProtectedTarget),
+            (501, Some(Entitled), Some(false), OtherUser),
+            (0, Some(Unsigned), Some(true), ProtectedTarget),
+            (0, Some(Entitled), None, ProtectedTarget),
+            (0, Some(Unsigned), Some(false), Unexplained),
+        ];
+        for (euid, signing, sip, cause) in matrix {
+            let advice = PermissionAdvice::for_operation(PrivilegedOperation::Attach, &environment(euid, signing, sip));
+            assert_eq!(advice.cause, cause, "euid {euid}, {signing:?}, SIP {sip:?}");
+            assert!(!advice.remedies.is_empty());
+        }
+    }
+
+    #[test]
+    fn missing_privileges_suggest_sudo_codesign_and_launch()
+    {
+        let env = environment(501, Some(CodeSigning::Unsigned), Some(true));
+        let advice = PermissionAdvice::for_operation(PrivilegedOperation::Attach, &env);
+        assert_eq!(
+            advice.summary(),
+            "Getting the target's task port (task_for_pid) needs the com.apple.security.cs.debugger entitlement or root; \
+             ferros is running unsigned and not as root"
+        );
+        assert_eq!(advice.remedies[0], "run as root: sudo ferros attach 4242");
+        assert!(advice.remedies[1].ends_with("--force --sign - /opt/ferros/bin/ferros"));
+        assert!(advice.remedies[2].contains("ferros launch"));
+        assert!(advice.to_string().contains("\n  - run as root"));
+
+        // Launching doesn't help once the target is running
+        let advice = PermissionAdvice::for_operation(PrivilegedOperation::HardwareBreakpoint, &env);
+        assert_eq!(advice.remedies.len(), 2);
+        assert!(advice.summary().starts_with("Writing debug registers"));
+    }
+
+    #[test]
+    fn entitlements_are_read_from_the_signature_blob()
+    {
+        let entitled = signature(Some(
+            "<plist><dict><key>com.apple.security.cs.debugger</key>\n    <true/></dict></plist>",
+        ));
+        let plist = signature_entitlements(&entitled).unwrap();
+        assert!(grants(plist, DEBUGGER_ENTITLEMENT));
+        assert!(!grants(plist, GET_TASK_ALLOW_ENTITLEMENT));
+
+        let denied = signature(Some("<dict><key>com.apple.security.cs.debugger</key><false/></dict>"));
+        assert!(!grants(signature_entitlements(&denied).unwrap(), DEBUGGER_ENTITLEMENT));
+
+        assert_eq!(signature_entitlements(&signature(None)), None);
+        assert_eq!(signature_entitlements(&entitled[..20]), None);
+        assert_eq!(signature_entitlements(b"\x00\x00\x00\x00"), None);
+
+        assert_eq!(code_signing(b"not a Mach-O image"), None);
+    }
+
+    #[test]
+    fn summary_lines_describe_the_environment()
+    {
+        let lines = environment(0, Some(CodeSigning::Signed), None).summary_lines();
+        assert_eq!(
+            lines,
+            vec![
+                "User: root (euid 0)".to_string(),
+                "Executable: /opt/ferros/bin/ferros".to_string(),
+                "Code signing: signed without com.apple.security.cs.debugger".to_string(),
+                "System Integrity Protection: unknown".to_string(),
+                "Can attach to running processes: yes (root)".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,580 @@
+//! Pinned memory values: named, typed reads re-evaluated at every stop.
+//!
+//! A pin is a `(name, address, type)` triple registered with `:pin`. The
+//! [`PinRegistry`] re-reads every pin when asked (at each stop, and optionally
+//! on a timer while the target runs), remembers the previous value, and reports
+//! a [`PinChange`] for every pin whose formatted value differs from last time.
+//!
+//! Reads never fail the refresh: an address that is unmapped (or becomes
+//! unmapped) simply shows as [`PinValue::Unreadable`] until it can be read
+//! again.
+//!
+//! ## Types
+//!
+//! | Spelling   | Read                                   | Shown as            |
+//! |------------|----------------------------------------|---------------------|
+//! | `u8`..`u64`| 1, 2, 4 or 8 bytes, little-endian      | decimal and hex     |
+//! | `f32`/`f64`| 4 or 8 bytes                           | float               |
+//! | `cstr`     | up to [`MAX_CSTR_LEN`] bytes, NUL-ended| quoted, escaped     |
+//! | `bytes:N`  | `N` bytes (1..=[`MAX_PIN_BYTES`])      | hex bytes           |
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ferros_core::pins::{PinRegistry, PinType, PinValue};
+//! use ferros_core::types::Address;
+//!
+//! let mut pins = PinRegistry::new();
+//! pins.pin("counter", Address::from(0x1000), "u32".parse()?)?;
+//!
+//! let changes = pins.refresh(|_, len| Ok(vec![7; len]));
+//! assert!(changes.is_empty()); // the first read is not a change
+//! assert_eq!(
+//!     pins.get("counter").unwrap().value,
+//!     Some(PinValue::Value("117901063 (0x7070707)".to_string()))
+//! );
+//! # Ok::<(), ferros_core::error::DebuggerError>(())
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DebuggerError, Result};
+use crate::inspector::TargetInspector;
+use crate::types::Address;
+
+/// Longest C string read for a `cstr` pin.
+pub const MAX_CSTR_LEN: usize = 128;
+
+/// Largest `bytes:N` pin.
+pub const MAX_PIN_BYTES: usize = 64;
+
+/// Page granularity used to retry a `cstr` read that runs off a mapping.
+const READ_RETRY_PAGE: u64 = 4096;
+
+/// How a pinned address is read and displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum PinType
+{
+    /// Unsigned 8-bit integer
+    U8,
+    /// Unsigned 16-bit integer
+    U16,
+    /// Unsigned 32-bit integer
+    U32,
+    /// Unsigned 64-bit integer
+    U64,
+    /// 32-bit float
+    F32,
+    /// 64-bit float
+    F64,
+    /// NUL-terminated string
+    CStr,
+    /// Raw bytes
+    Bytes(usize),
+}
+
+impl PinType
+{
+    /// Number of bytes requested from the target.
+    #[must_use]
+    pub fn read_len(self) -> usize
+    {
+        match self {
+            PinType::U8 => 1,
+            PinType::U16 => 2,
+            PinType::U32 | PinType::F32 => 4,
+            PinType::U64 | PinType::F64 => 8,
+            PinType::CStr => MAX_CSTR_LEN,
+            PinType::Bytes(len) => len,
+        }
+    }
+
+    /// Format raw target bytes, or `None` if too few were read.
+    #[must_use]
+    pub fn format(self, bytes: &[u8]) -> Option<String>
+    {
+        let mut word = [0_u8; 8];
+        let len = self.read_len();
+        if self != PinType::CStr {
+            let bytes = bytes.get(..len)?;
+            word[..len.min(8)].copy_from_slice(&bytes[..len.min(8)]);
+        }
+        let unsigned = u64::from_le_bytes(word);
+
+        Some(match self {
+            PinType::U8 | PinType::U16 | PinType::U32 | PinType::U64 => format!("{unsigned} (0x{unsigned:x})"),
+            PinType::F32 => f32::from_bits(unsigned as u32).to_string(),
+            PinType::F64 => f64::from_bits(unsigned).to_string(),
+            PinType::CStr => {
+                let end = bytes.iter().position(|&byte| byte == 0);
+                let text = String::from_utf8_lossy(&bytes[..end.unwrap_or(bytes.len())]);
+                let ellipsis = if end.is_some() { "" } else { "…" };
+                format!("\"{}\"{ellipsis}", text.escape_debug())
+            }
+            PinType::Bytes(_) => bytes[..len]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+        })
+    }
+}
+
+impl fmt::Display for PinType
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            PinType::U8 => f.write_str("u8"),
+            PinType::U16 => f.write_str("u16"),
+            PinType::U32 => f.write_str("u32"),
+            PinType::U64 => f.write_str("u64"),
+            PinType::F32 => f.write_str("f32"),
+            PinType::F64 => f.write_str("f64"),
+            PinType::CStr => f.write_str("cstr"),
+            PinType::Bytes(len) => write!(f, "bytes:{len}"),
+        }
+    }
+}
+
+impl FromStr for PinType
+{
+    type Err = DebuggerError;
+
+    fn from_str(s: &str) -> Result<Self>
+    {
+        Ok(match s {
+            "u8" => PinType::U8,
+            "u16" => PinType::U16,
+            "u32" => PinType::U32,
+            "u64" => PinType::U64,
+            "f32" => PinType::F32,
+            "f64" => PinType::F64,
+            "cstr" => PinType::CStr,
+            _ => {
+                let len = s
+                    .strip_prefix("bytes:")
+                    .and_then(|len| len.parse::<usize>().ok())
+                    .filter(|len| (1..=MAX_PIN_BYTES).contains(len))
+                    .ok_or_else(|| {
+                        DebuggerError::InvalidArgument(format!(
+                            "unknown pin type '{s}' (expected u8, u16, u32, u64, f32, f64, cstr or \
+                             bytes:1..={MAX_PIN_BYTES})"
+                        ))
+                    })?;
+                PinType::Bytes(len)
+            }
+        })
+    }
+}
+
+impl From<PinType> for String
+{
+    fn from(ty: PinType) -> Self
+    {
+        ty.to_string()
+    }
+}
+
+impl TryFrom<String> for PinType
+{
+    type Error = DebuggerError;
+
+    fn try_from(value: String) -> Result<Self>
+    {
+        value.parse()
+    }
+}
+
+/// Result of reading a pin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinValue
+{
+    /// Formatted value
+    Value(String),
+    /// The address couldn't be read (unmapped, protected, target gone)
+    Unreadable,
+}
+
+impl fmt::Display for PinValue
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            PinValue::Value(value) => f.write_str(value),
+            PinValue::Unreadable => f.write_str("unreadable"),
+        }
+    }
+}
+
+/// Persisted form of a pin, as stored in the per-binary session file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinSpec
+{
+    /// Name shown in the UI and used by `:unpin`.
+    pub name: String,
+    /// Address to read.
+    pub address: u64,
+    /// How to read and display it.
+    #[serde(rename = "type")]
+    pub ty: PinType,
+}
+
+/// A registered pin and its latest value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedValue
+{
+    /// Name shown in the UI and used by `:unpin`.
+    pub name: String,
+    /// Address to read.
+    pub address: Address,
+    /// How to read and display it.
+    pub ty: PinType,
+    /// Latest value; `None` until the first refresh.
+    pub value: Option<PinValue>,
+    /// Whether the latest refresh changed the value.
+    pub changed: bool,
+}
+
+/// A pin whose value differs from the previous refresh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinChange
+{
+    /// Pin name.
+    pub name: String,
+    /// Value before the refresh.
+    pub old: PinValue,
+    /// Value after the refresh.
+    pub new: PinValue,
+}
+
+impl fmt::Display for PinChange
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}: {} → {}", self.name, self.old, self.new)
+    }
+}
+
+/// Ordered set of pins, keyed by name.
+#[derive(Debug, Clone, Default)]
+pub struct PinRegistry
+{
+    pins: Vec<PinnedValue>,
+}
+
+impl PinRegistry
+{
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Rebuild a registry from persisted pins, skipping duplicate names.
+    #[must_use]
+    pub fn from_specs(specs: &[PinSpec]) -> Self
+    {
+        let mut registry = Self::new();
+        for spec in specs {
+            if registry.get(&spec.name).is_none() {
+                let _ = registry.pin(&spec.name, Address::from(spec.address), spec.ty);
+            }
+        }
+        registry
+    }
+
+    /// Pins in their persisted form, in registration order.
+    #[must_use]
+    pub fn specs(&self) -> Vec<PinSpec>
+    {
+        self.pins
+            .iter()
+            .map(|pin| PinSpec {
+                name: pin.name.clone(),
+                address: pin.address.value(),
+                ty: pin.ty,
+            })
+            .collect()
+    }
+
+    /// Register a pin, replacing any existing pin with the same name.
+    ///
+    /// Returns `true` if an existing pin was replaced.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidArgument` if the name is empty, contains whitespace, or
+    /// is one of the `:pin` subcommands (`list`, `live`).
+    pub fn pin(&mut self, name: &str, address: Address, ty: PinType) -> Result<bool>
+    {
+        if name.is_empty() || name.contains(char::is_whitespace) || matches!(name, "list" | "live") {
+            return Err(DebuggerError::InvalidArgument(format!("invalid pin name '{name}'")));
+        }
+        let pinned = PinnedValue {
+            name: name.to_string(),
+            address,
+            ty,
+            value: None,
+            changed: false,
+        };
+        if let Some(existing) = self.pins.iter_mut().find(|pin| pin.name == name) {
+            *existing = pinned;
+            return Ok(true);
+        }
+        self.pins.push(pinned);
+        Ok(false)
+    }
+
+    /// Remove the pin called `name`. Returns `false` if there was none.
+    pub fn unpin(&mut self, name: &str) -> bool
+    {
+        let before = self.pins.len();
+        self.pins.retain(|pin| pin.name != name);
+        self.pins.len() != before
+    }
+
+    /// Look up a pin by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&PinnedValue>
+    {
+        self.pins.iter().find(|pin| pin.name == name)
+    }
+
+    /// Pins in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &PinnedValue>
+    {
+        self.pins.iter()
+    }
+
+    /// Number of pins.
+    #[must_use]
+    pub fn len(&self) -> usize
+    {
+        self.pins.len()
+    }
+
+    /// Whether no pins are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.pins.is_empty()
+    }
+
+    /// Re-read every pin through `target`.
+    ///
+    /// Uses the cached [`read_memory`](TargetInspector::read_memory) path when the
+    /// target is stopped and [`read_memory_live`](TargetInspector::read_memory_live)
+    /// while it runs.
+    pub fn refresh_from(&mut self, target: &dyn TargetInspector) -> Vec<PinChange>
+    {
+        if target.is_stopped() {
+            self.refresh(|address, len| target.read_memory(address, len))
+        } else {
+            self.refresh(|address, len| target.read_memory_live(address, len))
+        }
+    }
+
+    /// Re-read every pin with `read` and report the ones whose value changed.
+    ///
+    /// The first value a pin gets is not reported as a change.
+    pub fn refresh(&mut self, mut read: impl FnMut(Address, usize) -> Result<Vec<u8>>) -> Vec<PinChange>
+    {
+        let mut changes = Vec::new();
+        for pin in &mut self.pins {
+            let value = read_pin(pin.address, pin.ty, &mut read);
+            pin.changed = pin.value.as_ref().is_some_and(|old| *old != value);
+            if pin.changed
+                && let Some(old) = pin.value.clone()
+            {
+                changes.push(PinChange {
+                    name: pin.name.clone(),
+                    old,
+                    new: value.clone(),
+                });
+            }
+            pin.value = Some(value);
+        }
+        changes
+    }
+}
+
+/// Read and format one pin, degrading to `Unreadable` on any failure.
+fn read_pin(address: Address, ty: PinType, read: &mut impl FnMut(Address, usize) -> Result<Vec<u8>>) -> PinValue
+{
+    let mut bytes = read(address, ty.read_len());
+    if bytes.is_err() && ty == PinType::CStr {
+        // A short string near the end of a mapping: retry up to the page boundary
+        let to_boundary = READ_RETRY_PAGE - address.value() % READ_RETRY_PAGE;
+        if (to_boundary as usize) < ty.read_len() {
+            bytes = read(address, to_boundary as usize);
+        }
+    }
+    bytes
+        .ok()
+        .and_then(|bytes| ty.format(&bytes))
+        .map_or(PinValue::Unreadable, PinValue::Value)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Target memory that a test can rewrite between refreshes
+    #[derive(Default)]
+    struct FakeMemory(HashMap<u64, u8>);
+
+    impl FakeMemory
+    {
+        fn write(&mut self, address: u64, bytes: &[u8])
+        {
+            for (offset, byte) in bytes.iter().enumerate() {
+                self.0.insert(address + offset as u64, *byte);
+            }
+        }
+
+        fn unmap(&mut self, address: u64, len: u64)
+        {
+            for offset in 0..len {
+                self.0.remove(&(address + offset));
+            }
+        }
+
+        /// All-or-nothing read, like `vm_read`
+        fn read(&self, address: Address, len: usize) -> Result<Vec<u8>>
+        {
+            (0..len as u64)
+                .map(|offset| {
+                    self.0
+                        .get(&(address.value() + offset))
+                        .copied()
+                        .ok_or(DebuggerError::MemoryReadFailed {
+                            address,
+                            length: len,
+                            kern_return: None,
+                        })
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn types_parse_and_round_trip()
+    {
+        for spelling in ["u8", "u16", "u32", "u64", "f32", "f64", "cstr", "bytes:16"] {
+            assert_eq!(spelling.parse::<PinType>().unwrap().to_string(), spelling);
+        }
+        assert_eq!("bytes:4".parse::<PinType>().unwrap(), PinType::Bytes(4));
+        assert!("bytes:0".parse::<PinType>().is_err());
+        assert!("bytes:65".parse::<PinType>().is_err());
+        assert!("i32".parse::<PinType>().is_err());
+    }
+
+    #[test]
+    fn values_are_formatted_per_type()
+    {
+        assert_eq!(PinType::U16.format(&[0x34, 0x12]).unwrap(), "4660 (0x1234)");
+        assert_eq!(PinType::F64.format(&1.5_f64.to_le_bytes()).unwrap(), "1.5");
+        assert_eq!(PinType::CStr.format(b"hi\n\0junk").unwrap(), "\"hi\\n\"");
+        assert_eq!(PinType::CStr.format(b"no end").unwrap(), "\"no end\"…");
+        assert_eq!(PinType::Bytes(3).format(&[0xde, 0xad, 0xbe]).unwrap(), "de ad be");
+        assert_eq!(PinType::U32.format(&[1, 2]), None);
+    }
+
+    #[test]
+    fn refresh_reports_changes_after_first_read()
+    {
+        let mut memory = FakeMemory::default();
+        memory.write(0x1000, &5_u32.to_le_bytes());
+        memory.write(0x2000, &[0; MAX_CSTR_LEN]);
+        memory.write(0x2000, b"idle\0");
+        let mut pins = PinRegistry::new();
+        pins.pin("count", Address::from(0x1000), PinType::U32).unwrap();
+        pins.pin("state", Address::from(0x2000), PinType::CStr).unwrap();
+
+        assert!(pins.refresh(|address, len| memory.read(address, len)).is_empty());
+
+        memory.write(0x1000, &6_u32.to_le_bytes());
+        let changes = pins.refresh(|address, len| memory.read(address, len));
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to_string(), "count: 5 (0x5) → 6 (0x6)");
+        assert!(pins.get("count").unwrap().changed);
+        assert!(!pins.get("state").unwrap().changed);
+        assert_eq!(
+            pins.get("state").unwrap().value,
+            Some(PinValue::Value("\"idle\"".to_string()))
+        );
+    }
+
+    #[test]
+    fn unmapped_address_degrades_to_unreadable()
+    {
+        let mut memory = FakeMemory::default();
+        memory.write(0x1000, &[1; 8]);
+        let mut pins = PinRegistry::new();
+        pins.pin("ptr", Address::from(0x1000), PinType::U64).unwrap();
+        pins.refresh(|address, len| memory.read(address, len));
+
+        memory.unmap(0x1000, 8);
+        let changes = pins.refresh(|address, len| memory.read(address, len));
+
+        assert_eq!(changes[0].new, PinValue::Unreadable);
+        assert_eq!(pins.get("ptr").unwrap().value, Some(PinValue::Unreadable));
+    }
+
+    #[test]
+    fn cstr_near_end_of_mapping_is_retried_up_to_page_boundary()
+    {
+        let mut memory = FakeMemory::default();
+        // Only the last 16 bytes of the page are mapped
+        memory.write(0x1ff0, b"tail\0");
+        memory.write(0x1ff5, &[0; 11]);
+        let mut pins = PinRegistry::new();
+        pins.pin("tail", Address::from(0x1ff0), PinType::CStr).unwrap();
+
+        pins.refresh(|address, len| memory.read(address, len));
+
+        assert_eq!(pins.get("tail").unwrap().value, Some(PinValue::Value("\"tail\"".to_string())));
+    }
+
+    #[test]
+    fn pin_replaces_by_name_and_unpin_removes()
+    {
+        let mut pins = PinRegistry::new();
+        assert!(!pins.pin("a", Address::from(0x10), PinType::U8).unwrap());
+        assert!(!pins.pin("b", Address::from(0x20), PinType::U8).unwrap());
+        assert!(pins.pin("a", Address::from(0x30), PinType::U16).unwrap());
+        assert!(pins.pin("list", Address::from(0x30), PinType::U16).is_err());
+
+        let names: Vec<_> = pins.iter().map(|pin| pin.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(pins.get("a").unwrap().address, Address::from(0x30));
+
+        assert!(pins.unpin("a"));
+        assert!(!pins.unpin("a"));
+        assert_eq!(pins.len(), 1);
+    }
+
+    #[test]
+    fn specs_round_trip_through_json()
+    {
+        let mut pins = PinRegistry::new();
+        pins.pin("buf", Address::from(0x4000), PinType::Bytes(8)).unwrap();
+        pins.pin("ratio", Address::from(0x5000), PinType::F32).unwrap();
+
+        let json = serde_json::to_string(&pins.specs()).unwrap();
+        assert!(json.contains("\"type\":\"bytes:8\""), "{json}");
+        let restored = PinRegistry::from_specs(&serde_json::from_str::<Vec<PinSpec>>(&json).unwrap());
+
+        assert_eq!(restored.specs(), pins.specs());
+    }
+}
@@ -0,0 +1,87 @@
+//! # Fault Decoding
+//!
+//! A bad access only says which address faulted. Whether the instruction was
+//! loading, storing or fetching code is in the CPU's own fault state, which
+//! the kernel hands back in the thread's exception state:
+//!
+//! - **ARM64**: the Exception Syndrome Register (`ESR_EL1`). Its exception
+//!   class separates instruction aborts from data aborts, and a data abort's
+//!   `WnR` bit says whether it was a write.
+//!   See: [ESR_EL1](https://developer.arm.com/documentation/ddi0601/latest/AArch64-Registers/ESR-EL1--Exception-Syndrome-Register--EL1-)
+//! - **x86-64**: the page fault error code, for trap 14 only. Bit 1 is set
+//!   for writes and bit 4 for instruction fetches. General protection faults
+//!   (non-canonical addresses) carry no access kind.
+
+use crate::types::BadAccessKind;
+
+/// Instruction abort from a lower exception level, i.e. from user code
+const ESR_EC_INSTRUCTION_ABORT_LOWER: u32 = 0x20;
+const ESR_EC_INSTRUCTION_ABORT_SAME: u32 = 0x21;
+/// Data abort from a lower exception level
+const ESR_EC_DATA_ABORT_LOWER: u32 = 0x24;
+const ESR_EC_DATA_ABORT_SAME: u32 = 0x25;
+/// `WnR`: the data abort was caused by a write
+const ESR_ISS_WNR: u32 = 1 << 6;
+
+/// x86 page fault trap number
+const X86_TRAP_PAGE_FAULT: u16 = 14;
+/// Page fault error code bit for a write access
+const X86_PF_WRITE: u32 = 1 << 1;
+/// Page fault error code bit for an instruction fetch
+const X86_PF_INSTRUCTION: u32 = 1 << 4;
+
+/// Access kind of an ARM64 fault, from its `ESR`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn access_kind_arm64(esr: u32) -> BadAccessKind
+{
+    match esr >> 26 {
+        ESR_EC_INSTRUCTION_ABORT_LOWER | ESR_EC_INSTRUCTION_ABORT_SAME => BadAccessKind::Execute,
+        ESR_EC_DATA_ABORT_LOWER | ESR_EC_DATA_ABORT_SAME if esr & ESR_ISS_WNR != 0 => BadAccessKind::Write,
+        ESR_EC_DATA_ABORT_LOWER | ESR_EC_DATA_ABORT_SAME => BadAccessKind::Read,
+        _ => BadAccessKind::Unknown,
+    }
+}
+
+/// Access kind of an x86-64 fault, from its trap number and error code
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn access_kind_x86(trapno: u16, err: u32) -> BadAccessKind
+{
+    if trapno != X86_TRAP_PAGE_FAULT {
+        BadAccessKind::Unknown
+    } else if err & X86_PF_INSTRUCTION != 0 {
+        BadAccessKind::Execute
+    } else if err & X86_PF_WRITE != 0 {
+        BadAccessKind::Write
+    } else {
+        BadAccessKind::Read
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn arm64_syndromes_tell_loads_stores_and_fetches_apart()
+    {
+        // ldr x0, [x1] through a null x1: data abort, translation fault, WnR clear
+        assert_eq!(access_kind_arm64(0x9200_0006), BadAccessKind::Read);
+        // str x0, [x1]: the same with WnR set
+        assert_eq!(access_kind_arm64(0x9200_0046), BadAccessKind::Write);
+        // blr to an unmapped address
+        assert_eq!(access_kind_arm64(0x8200_0006), BadAccessKind::Execute);
+        // brk #0 is not an abort
+        assert_eq!(access_kind_arm64(0xf200_0000), BadAccessKind::Unknown);
+    }
+
+    #[test]
+    fn x86_page_fault_codes_tell_loads_stores_and_fetches_apart()
+    {
+        assert_eq!(access_kind_x86(14, 0b0_0100), BadAccessKind::Read);
+        assert_eq!(access_kind_x86(14, 0b0_0110), BadAccessKind::Write);
+        assert_eq!(access_kind_x86(14, 0b1_0100), BadAccessKind::Execute);
+        // General protection fault on a non-canonical address
+        assert_eq!(access_kind_x86(13, 0), BadAccessKind::Unknown);
+    }
+}
@@ -11,7 +11,10 @@
 //! - **Software breakpoints**: Modify code by replacing instructions with trap
 //!   instructions (INT3 on x86-64, BRK on ARM64). Limited only by available memory.
 //! - **Hardware breakpoints**: Use CPU debug registers (DR0-DR7 on x86-64,
-//!   DBGBVR/DBGBCR on ARM64). Limited to 4 on x86-64, 16 on ARM64.
+//!   DBGBVR/DBGBCR on ARM64). Limited to 4 on x86-64 and to what the core
+//!   implements on ARM64 (6 on Apple Silicon). Slots are handed out from the
+//!   [BreakpointStore](../breakpoints/struct.BreakpointStore.html), so a full
+//!   set fails with `NoHardwareSlots` instead of overwriting one.
 //!
 //! ## References
 //!
@@ -24,13 +27,18 @@ use std::time::SystemTime;
 use libc::{mach_port_t, thread_act_t};
 
 use crate::breakpoints::{
-    BreakpointEntry, BreakpointId, BreakpointInfo, BreakpointKind, BreakpointPayload, BreakpointRequest, BreakpointState,
-    BreakpointStore,
+    BreakpointCounts, BreakpointEntry, BreakpointId, BreakpointInfo, BreakpointKind, BreakpointPayload, BreakpointRequest,
+    BreakpointRequestKind, BreakpointState, BreakpointStore, check_instruction_boundary,
 };
+use crate::disassembly::max_code_len;
 use crate::error::{DebuggerError, Result};
-use crate::platform::macos::{constants, registers};
+use crate::permissions::PrivilegedOperation;
+use crate::platform::macos::{constants, permissions, registers};
 use crate::types::{Address, Architecture};
 
+/// Furthest a breakpoint can be from a known instruction start and still be decoded to.
+const MAX_BOUNDARY_SCAN: usize = 64 * 1024;
+
 /// Trait for breakpoint operations that require access to debugger internals.
 ///
 /// This trait allows the breakpoint module to access memory read/write operations
@@ -106,6 +114,12 @@ pub(crate) trait BreakpointOperations
     ///
     /// The Mach task port (`mach_port_t`) for the target process.
     fn task_port(&self) -> mach_port_t;
+
+    /// Closest address at or before `address` known to start an instruction.
+    ///
+    /// The covering line-table row or the enclosing function's entry, from
+    /// the target's symbols; `None` when they don't cover `address`.
+    fn known_instruction_start(&mut self, address: Address) -> Option<Address>;
 }
 
 /// Breakpoint management functions for macOS debugger.
@@ -147,7 +161,7 @@ impl BreakpointManager
     ///
     /// ## Errors
     ///
-    /// - `DebuggerError::InvalidArgument`: Architecture not supported or not available in this build
+    /// - `DebuggerError::UnsupportedArchitecture`: Architecture not supported or not available in this build
     pub(crate) fn software_trap_bytes(architecture: Architecture) -> Result<Vec<u8>>
     {
         match architecture {
@@ -158,9 +172,7 @@ impl BreakpointManager
                 }
                 #[cfg(not(target_arch = "aarch64"))]
                 {
-                    Err(DebuggerError::InvalidArgument(
-                        "ARM64 breakpoints not supported on this build".to_string(),
-                    ))
+                    Err(DebuggerError::UnsupportedArchitecture(architecture))
                 }
             }
             Architecture::X86_64 => {
@@ -170,14 +182,10 @@ impl BreakpointManager
                 }
                 #[cfg(not(target_arch = "x86_64"))]
                 {
-                    Err(DebuggerError::InvalidArgument(
-                        "x86-64 breakpoints not supported on this build".to_string(),
-                    ))
+                    Err(DebuggerError::UnsupportedArchitecture(architecture))
                 }
             }
-            Architecture::Unknown(label) => Err(DebuggerError::InvalidArgument(format!(
-                "Software breakpoints unsupported for architecture: {label}"
-            ))),
+            Architecture::Unknown(_) => Err(DebuggerError::UnsupportedArchitecture(architecture)),
         }
     }
 
@@ -192,26 +200,32 @@ impl BreakpointManager
     /// - `ops`: Operations trait providing memory access and architecture info
     /// - `breakpoints`: Shared breakpoint store for tracking breakpoints
     /// - `address`: Memory address where the breakpoint should be installed
+    /// - `force`: Skip the instruction boundary check
     ///
     /// ## Returns
     ///
     /// `Ok(BreakpointId)` with the ID of the newly created breakpoint, or an error if:
+    /// - The address isn't an instruction boundary (see [`check_instruction_boundary`]) and `force` isn't set
     /// - A breakpoint already exists at this address
     /// - The address cannot be read or written
     /// - The debugger is not attached to a process
     ///
     /// ## Errors
     ///
-    /// - `DebuggerError::InvalidArgument`: Breakpoint already exists or memory access failed
+    /// - `DebuggerError::InvalidArgument`: Misaligned address, breakpoint already exists or memory access failed
     /// - `DebuggerError::AttachFailed`: Debugger is not attached to a process
     pub(crate) fn install_software_breakpoint<Ops: BreakpointOperations>(
         ops: &mut Ops,
         breakpoints: &Arc<Mutex<BreakpointStore>>,
         address: Address,
+        force: bool,
     ) -> Result<BreakpointId>
     {
         ops.ensure_attached()?;
         let trap = BreakpointManager::software_trap_bytes(ops.architecture())?;
+        if !force {
+            Self::check_boundary(ops, breakpoints, address)?;
+        }
 
         {
             let store = breakpoints.lock().unwrap();
@@ -225,11 +239,11 @@ impl BreakpointManager
 
         let original = ops.read_memory(address, trap.len())?;
         if original.len() != trap.len() {
-            return Err(DebuggerError::InvalidArgument(format!(
-                "Unable to read {} bytes at 0x{:016x} to install breakpoint",
-                trap.len(),
-                address.value()
-            )));
+            return Err(DebuggerError::MemoryReadFailed {
+                address,
+                length: trap.len(),
+                kern_return: None,
+            });
         }
 
         // Try to write directly first - vm_write() may work on read-only segments
@@ -291,6 +305,39 @@ impl BreakpointManager
         Ok(store.insert(entry))
     }
 
+    /// Check that `address` starts an instruction before a trap is patched over it.
+    ///
+    /// On x86-64 the code is decoded forward from the closest known instruction
+    /// start, with the traps of other breakpoints replaced by the code they
+    /// patched over. Code that can't be read, or is further than
+    /// [`MAX_BOUNDARY_SCAN`] bytes from a known start, passes unchecked.
+    fn check_boundary<Ops: BreakpointOperations>(
+        ops: &mut Ops,
+        breakpoints: &Arc<Mutex<BreakpointStore>>,
+        address: Address,
+    ) -> Result<()>
+    {
+        let architecture = ops.architecture();
+        let known = if architecture == Architecture::X86_64 {
+            ops.known_instruction_start(address).and_then(|start| {
+                let offset = usize::try_from(address.value().checked_sub(start.value())?).ok()?;
+                (offset <= MAX_BOUNDARY_SCAN).then_some((start, offset))
+            })
+        } else {
+            None
+        };
+        let Some((start, offset)) = known else {
+            return check_instruction_boundary(architecture, address, None);
+        };
+        // Through the end of the longest instruction that could cover `address`
+        let len = offset + max_code_len(architecture, 1);
+        let Ok(mut code) = ops.read_memory(start, len) else {
+            return check_instruction_boundary(architecture, address, None);
+        };
+        breakpoints.lock().unwrap().logical_view(start, &mut code);
+        check_instruction_boundary(architecture, address, Some((start, &code)))
+    }
+
     /// Helper function to try hardware breakpoint fallback when software breakpoint fails
     fn try_hardware_fallback<Ops: BreakpointOperations>(
         ops: &mut Ops,
@@ -326,9 +373,11 @@ impl BreakpointManager
     /// ## Hardware Breakpoint Limitations
     ///
     /// - **x86-64**: Limited to 4 hardware breakpoints (DR0-DR7 registers)
-    /// - **ARM64**: Limited to 16 hardware breakpoints (DBGBVR/DBGBCR registers)
+    /// - **ARM64**: Limited to the DBGBVR/DBGBCR pairs the core implements, see
+    ///   [`registers::hardware_breakpoint_slots`]
     ///
-    /// If all available slots are in use, this method will return an error.
+    /// The lowest slot no armed hardware breakpoint holds is used on every
+    /// thread. If all of them are in use, this method returns `NoHardwareSlots`.
     ///
     /// ## Parameters
     ///
@@ -346,7 +395,8 @@ impl BreakpointManager
     /// ## Errors
     ///
     /// - `DebuggerError::InvalidArgument`: Breakpoint already exists
-    /// - `DebuggerError::AttachFailed`: No threads available or slot allocation failed
+    /// - `DebuggerError::NoHardwareSlots`: Every hardware breakpoint slot is in use
+    /// - `DebuggerError::AttachFailed`: No threads available
     pub(crate) fn install_hardware_breakpoint<Ops: BreakpointOperations>(
         ops: &mut Ops,
         breakpoints: &Arc<Mutex<BreakpointStore>>,
@@ -355,7 +405,7 @@ impl BreakpointManager
     {
         ops.ensure_attached()?;
 
-        {
+        let slot = {
             let store = breakpoints.lock().unwrap();
             if store.id_for_kind(address, BreakpointKind::Hardware).is_some() {
                 return Err(DebuggerError::InvalidArgument(format!(
@@ -363,29 +413,15 @@ impl BreakpointManager
                     address.value()
                 )));
             }
-        }
-
-        // Install on all threads
-        let mut used_slot = None;
-
-        for &thread in ops.thread_ports() {
-            let slot = registers::set_hardware_breakpoint(thread, address)?;
-            if let Some(s) = used_slot {
-                if s != slot {
-                    tracing::warn!("Hardware breakpoint slots inconsistent across threads: {} vs {}", s, slot);
-                }
-            } else {
-                used_slot = Some(slot);
-            }
-        }
-
-        let slot = used_slot
-            .ok_or_else(|| DebuggerError::AttachFailed("No threads available to set hardware breakpoint".into()))?;
+            store.free_hardware_slot(registers::hardware_breakpoint_slots())?
+        };
+        Self::arm_hardware_slot(ops, address, slot)?;
 
         let mut info = BreakpointInfo::new(BreakpointId::from_raw(0), address, BreakpointKind::Hardware);
         info.state = BreakpointState::Resolved;
         info.enabled = true;
         info.resolved_at = Some(SystemTime::now());
+        info.hardware_slot = Some(slot);
 
         let entry = BreakpointEntry {
             info,
@@ -396,11 +432,83 @@ impl BreakpointManager
         Ok(store.insert(entry))
     }
 
+    /// Arm hardware breakpoint `slot` at `address` on every thread.
+    ///
+    /// A thread that refuses it has the slot cleared again on the threads
+    /// already done, so a breakpoint is never left armed on only some threads.
+    fn arm_hardware_slot<Ops: BreakpointOperations>(ops: &Ops, address: Address, slot: u32) -> Result<()>
+    {
+        let threads = ops.thread_ports();
+        if threads.is_empty() {
+            return Err(DebuggerError::AttachFailed(
+                "No threads available to set hardware breakpoint".into(),
+            ));
+        }
+        for (done, &thread) in threads.iter().enumerate() {
+            if let Err(err) = registers::set_hardware_breakpoint(thread, address, slot) {
+                for &thread in &threads[..done] {
+                    if let Err(e) = registers::clear_hardware_breakpoint(thread, slot) {
+                        tracing::warn!("Failed to roll back hardware breakpoint on thread {}: {}", thread, e);
+                    }
+                }
+                return Err(permissions::advise(err, PrivilegedOperation::HardwareBreakpoint));
+            }
+        }
+        Ok(())
+    }
+
+    /// Program every armed hardware breakpoint and watchpoint on threads that started after they were installed.
+    ///
+    /// Software breakpoints patch memory and so cover every thread, but debug
+    /// registers are per thread and a new thread starts with them clear. Each
+    /// one goes into the slot the store recorded for it, the same slot every
+    /// other thread has it in, so removing it by slot still clears it
+    /// everywhere. A thread that refuses one (it exited meanwhile) is logged
+    /// and skipped.
+    pub(crate) fn arm_started_threads(breakpoints: &Mutex<BreakpointStore>, threads: &[thread_act_t])
+    {
+        if threads.is_empty() {
+            return;
+        }
+        let armed = breakpoints.lock().unwrap().armed_debug_registers();
+        for payload in &armed {
+            for &thread in threads {
+                let result = match *payload {
+                    BreakpointPayload::Hardware { address, slot } => {
+                        registers::set_hardware_breakpoint(thread, address, slot)
+                    }
+                    BreakpointPayload::Watchpoint {
+                        address,
+                        length,
+                        access,
+                        slot,
+                    } => registers::set_watchpoint_in_slot(thread, address, length, access, slot),
+                    BreakpointPayload::Software { .. } => Ok(()),
+                };
+                if let Err(err) = result {
+                    tracing::warn!("Failed to arm debug registers on new thread {}: {}", thread, err);
+                }
+            }
+        }
+    }
+
     /// Install a data watchpoint at the given address and length.
     ///
     /// This method uses CPU debug registers to break on data access to a memory
     /// region instead of instruction execution. On macOS this is currently
     /// implemented for ARM64 (Apple Silicon) only; x86-64 will return an error.
+    ///
+    /// The watchpoint is set on every thread, in the same slot where the
+    /// threads agree. A thread that can't take it (all 4 slots in use) has it
+    /// cleared again from the threads already done, so a watchpoint is never
+    /// left armed on only some threads.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: The range isn't 1, 2, 4 or 8 aligned bytes (see
+    ///   [`check_watch_range`](crate::breakpoints::check_watch_range)), or a
+    ///   watchpoint already starts at `address`
+    /// - `ResourceExhausted`: Every watchpoint slot is in use
     pub(crate) fn install_watchpoint<Ops: BreakpointOperations>(
         ops: &mut Ops,
         breakpoints: &Arc<Mutex<BreakpointStore>>,
@@ -410,6 +518,7 @@ impl BreakpointManager
     ) -> Result<BreakpointId>
     {
         ops.ensure_attached()?;
+        crate::breakpoints::check_watch_range(address, length)?;
 
         {
             let store = breakpoints.lock().unwrap();
@@ -422,21 +531,27 @@ impl BreakpointManager
         }
 
         // Install on all threads
-        let mut used_slot = None;
-
+        let mut installed = Vec::new();
         for &thread in ops.thread_ports() {
-            let slot = registers::set_watchpoint(thread, address, length, access)?;
-            if let Some(s) = used_slot {
-                if s != slot {
-                    tracing::warn!("Watchpoint slots inconsistent across threads: {} vs {}", s, slot);
+            match registers::set_watchpoint(thread, address, length, access) {
+                Ok(slot) => installed.push((thread, slot)),
+                Err(err) => {
+                    for &(thread, slot) in &installed {
+                        if let Err(e) = registers::clear_watchpoint(thread, slot) {
+                            tracing::warn!("Failed to roll back data watchpoint on thread {}: {}", thread, e);
+                        }
+                    }
+                    return Err(err);
                 }
-            } else {
-                used_slot = Some(slot);
             }
         }
+        if installed.windows(2).any(|pair| pair[0].1 != pair[1].1) {
+            tracing::warn!("Watchpoint at {address} is in different slots on different threads");
+        }
 
-        let slot =
-            used_slot.ok_or_else(|| DebuggerError::AttachFailed("No threads available to set data watchpoint".into()))?;
+        let &(_, slot) = installed
+            .first()
+            .ok_or_else(|| DebuggerError::AttachFailed("No threads available to set data watchpoint".into()))?;
 
         let mut info = BreakpointInfo::new(BreakpointId::from_raw(0), address, BreakpointKind::Watchpoint);
         info.state = BreakpointState::Resolved;
@@ -610,8 +725,13 @@ impl BreakpointManager
     ///
     /// `Ok(BreakpointId)` with the ID of the newly created breakpoint, or an error if:
     /// - The breakpoint type is not supported (e.g., watchpoints)
+    /// - The request still names a symbolic location (resolve it first)
+    /// - The request's condition doesn't parse
     /// - Installation fails for any reason
     ///
+    /// The request's options are recorded on the breakpoint, and a request
+    /// that isn't enabled is disarmed right after installation.
+    ///
     /// ## Errors
     pub(crate) fn add_breakpoint<Ops: BreakpointOperations>(
         ops: &mut Ops,
@@ -619,13 +739,36 @@ impl BreakpointManager
         request: BreakpointRequest,
     ) -> Result<BreakpointId>
     {
-        match request {
-            BreakpointRequest::Software { address } => Self::install_software_breakpoint(ops, breakpoints, address),
-            BreakpointRequest::Hardware { address } => Self::install_hardware_breakpoint(ops, breakpoints, address),
-            BreakpointRequest::Watchpoint { address, length, access } => {
+        // Reject a bad condition before anything is patched into the target
+        request.options.parsed_condition()?;
+        let id = match request.kind {
+            BreakpointRequestKind::Software { address } => {
+                Self::install_software_breakpoint(ops, breakpoints, address, request.options.force)
+            }
+            BreakpointRequestKind::Hardware { address, allow_fallback } => {
+                match Self::install_hardware_breakpoint(ops, breakpoints, address) {
+                    Err(DebuggerError::NoHardwareSlots(slots)) if allow_fallback => {
+                        tracing::debug!(
+                            "All {slots} hardware breakpoint slots in use, installing a software breakpoint at {address}"
+                        );
+                        Self::install_software_breakpoint(ops, breakpoints, address, request.options.force)
+                    }
+                    result => result,
+                }
+            }
+            BreakpointRequestKind::Watchpoint { address, length, access } => {
                 Self::install_watchpoint(ops, breakpoints, address, length, access)
             }
+            BreakpointRequestKind::Location(location) => Err(DebuggerError::InvalidArgument(format!(
+                "Breakpoint location {location} has not been resolved"
+            ))),
+        }?;
+
+        breakpoints.lock().unwrap().set_options(id, request.options)?;
+        if !request.enabled {
+            Self::disable_breakpoint(ops, breakpoints, id)?;
         }
+        Ok(id)
     }
 
     /// Remove a breakpoint permanently.
@@ -649,7 +792,7 @@ impl BreakpointManager
     ///
     /// ## Errors
     ///
-    /// - `DebuggerError::BreakpointIdNotFound`: The breakpoint ID does not exist
+    /// - `DebuggerError::BreakpointNotFound`: The breakpoint ID does not exist
     /// - `DebuggerError::InvalidArgument`: Failed to restore original instruction
     pub(crate) fn remove_breakpoint<Ops: BreakpointOperations>(
         ops: &mut Ops,
@@ -661,7 +804,7 @@ impl BreakpointManager
             let mut store = breakpoints.lock().unwrap();
             store.remove(id)
         }
-        .ok_or_else(|| DebuggerError::BreakpointIdNotFound(id.raw()))?;
+        .ok_or_else(|| DebuggerError::BreakpointNotFound(id))?;
 
         if entry.info.enabled {
             match entry.info.kind {
@@ -697,8 +840,9 @@ impl BreakpointManager
     ///
     /// ## Errors
     ///
-    /// - `DebuggerError::BreakpointIdNotFound`: The breakpoint ID does not exist
+    /// - `DebuggerError::BreakpointNotFound`: The breakpoint ID does not exist
     /// - `DebuggerError::InvalidArgument`: Failed to enable the breakpoint
+    /// - `DebuggerError::NoHardwareSlots`: A hardware breakpoint found every slot taken
     pub(crate) fn enable_breakpoint<Ops: BreakpointOperations>(
         ops: &mut Ops,
         breakpoints: &Arc<Mutex<BreakpointStore>>,
@@ -707,7 +851,7 @@ impl BreakpointManager
     {
         let (kind, address, payload) = {
             let store = breakpoints.lock().unwrap();
-            let entry = store.get(id).ok_or_else(|| DebuggerError::BreakpointIdNotFound(id.raw()))?;
+            let entry = store.get(id).ok_or_else(|| DebuggerError::BreakpointNotFound(id))?;
             if entry.info.enabled {
                 return Ok(());
             }
@@ -726,24 +870,17 @@ impl BreakpointManager
                 }
             }
             BreakpointKind::Hardware => {
-                // Re-install on all threads
-                let mut used_slot = None;
-                for &thread in ops.thread_ports() {
-                    let slot = registers::set_hardware_breakpoint(thread, address)?;
-                    if let Some(s) = used_slot {
-                        if s != slot {
-                            tracing::warn!("Hardware breakpoint slots inconsistent: {} vs {}", s, slot);
-                        }
-                    } else {
-                        used_slot = Some(slot);
-                    }
-                }
+                // Its old slot may have been handed out while it was disabled
+                let slot = breakpoints
+                    .lock()
+                    .unwrap()
+                    .free_hardware_slot(registers::hardware_breakpoint_slots())?;
+                Self::arm_hardware_slot(ops, address, slot)?;
 
-                if let Some(new_slot) = used_slot {
-                    let mut store = breakpoints.lock().unwrap();
-                    if let Some(entry) = store.get_mut(id) {
-                        entry.payload = BreakpointPayload::Hardware { address, slot: new_slot };
-                    }
+                let mut store = breakpoints.lock().unwrap();
+                if let Some(entry) = store.get_mut(id) {
+                    entry.payload = BreakpointPayload::Hardware { address, slot };
+                    entry.info.hardware_slot = Some(slot);
                 }
             }
             BreakpointKind::Watchpoint => {
@@ -814,7 +951,7 @@ impl BreakpointManager
     ///
     /// ## Errors
     ///
-    /// - `DebuggerError::BreakpointIdNotFound`: The breakpoint ID does not exist
+    /// - `DebuggerError::BreakpointNotFound`: The breakpoint ID does not exist
     /// - `DebuggerError::InvalidArgument`: Failed to disable the breakpoint
     pub(crate) fn disable_breakpoint<Ops: BreakpointOperations>(
         ops: &mut Ops,
@@ -824,7 +961,7 @@ impl BreakpointManager
     {
         let (kind, address, payload) = {
             let store = breakpoints.lock().unwrap();
-            let entry = store.get(id).ok_or_else(|| DebuggerError::BreakpointIdNotFound(id.raw()))?;
+            let entry = store.get(id).ok_or_else(|| DebuggerError::BreakpointNotFound(id))?;
             if !entry.info.enabled {
                 return Ok(());
             }
@@ -867,6 +1004,8 @@ impl BreakpointManager
         if let Some(entry) = store.get_mut(id) {
             entry.info.state = BreakpointState::Disabled;
             entry.info.enabled = false;
+            // The slot is free for other hardware breakpoints until this one is re-enabled
+            entry.info.hardware_slot = None;
         }
         Ok(())
     }
@@ -890,7 +1029,7 @@ impl BreakpointManager
     ///
     /// ## Errors
     ///
-    /// - `DebuggerError::BreakpointIdNotFound`: The breakpoint ID does not exist
+    /// - `DebuggerError::BreakpointNotFound`: The breakpoint ID does not exist
     /// - `DebuggerError::InvalidArgument`: Failed to toggle the breakpoint
     pub(crate) fn toggle_breakpoint<Ops: BreakpointOperations>(
         ops: &mut Ops,
@@ -902,7 +1041,7 @@ impl BreakpointManager
             let store = breakpoints.lock().unwrap();
             store
                 .get(id)
-                .ok_or_else(|| DebuggerError::BreakpointIdNotFound(id.raw()))?
+                .ok_or_else(|| DebuggerError::BreakpointNotFound(id))?
                 .info
                 .enabled
         };
@@ -933,11 +1072,11 @@ impl BreakpointManager
     ///
     /// ## Errors
     ///
-    /// - `DebuggerError::BreakpointIdNotFound`: The breakpoint ID does not exist
+    /// - `DebuggerError::BreakpointNotFound`: The breakpoint ID does not exist
     pub(crate) fn breakpoint_info(breakpoints: &Arc<Mutex<BreakpointStore>>, id: BreakpointId) -> Result<BreakpointInfo>
     {
         let store = breakpoints.lock().unwrap();
-        store.info(id).ok_or_else(|| DebuggerError::BreakpointIdNotFound(id.raw()))
+        store.info(id).ok_or_else(|| DebuggerError::BreakpointNotFound(id))
     }
 
     /// List all breakpoints.
@@ -958,4 +1097,10 @@ impl BreakpointManager
     {
         breakpoints.lock().unwrap().list()
     }
+
+    /// Count the breakpoints in `breakpoints` by state, without cloning them.
+    pub(crate) fn breakpoint_counts(breakpoints: &Arc<Mutex<BreakpointStore>>) -> BreakpointCounts
+    {
+        breakpoints.lock().unwrap().counts()
+    }
 }
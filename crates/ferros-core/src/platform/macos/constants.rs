@@ -108,6 +108,100 @@ pub const ARM_DEBUG_STATE64: c_int = 15;
 #[cfg(target_arch = "aarch64")]
 pub const ARM_DEBUG_STATE64_COUNT: mach_msg_type_number_t = 130;
 
+/// ARM64 exception state flavor (flavor 7)
+///
+/// Used with `thread_get_state()` to read the fault address register (FAR),
+/// the exception syndrome (ESR) and the exception number of the last fault.
+#[cfg(target_arch = "aarch64")]
+pub const ARM_EXCEPTION_STATE64: c_int = 7;
+
+/// ARM64 exception state count (16 bytes / 4 = 4 u32 values)
+///
+/// The number of `natural_t` (u32) values required to hold ARM64 exception state.
+#[cfg(target_arch = "aarch64")]
+pub const ARM_EXCEPTION_STATE64_COUNT: mach_msg_type_number_t = 4;
+
+/// x86-64 exception state flavor (flavor 6)
+///
+/// Used with `thread_get_state()` to read the trap number, error code and
+/// faulting virtual address of the last fault.
+#[cfg(target_arch = "x86_64")]
+pub const X86_EXCEPTION_STATE64: c_int = 6;
+
+/// x86-64 exception state count (16 bytes / 4 = 4 u32 values)
+///
+/// The number of `natural_t` (u32) values required to hold x86-64 exception state.
+#[cfg(target_arch = "x86_64")]
+pub const X86_EXCEPTION_STATE64_COUNT: mach_msg_type_number_t = 4;
+
+// ============================================================================
+// Thread Info Flavors
+// ============================================================================
+
+/// Thread identifier info flavor (flavor 4)
+///
+/// Used with `thread_info()` to read `thread_identifier_info`, whose `thread_id`
+/// field is the kernel's system-wide thread id. Unlike the thread port, it stays
+/// the same for the thread's whole lifetime and is never reused.
+pub const THREAD_IDENTIFIER_INFO: c_int = 4;
+
+/// Thread identifier info count (24 bytes / 4 = 6 u32 values)
+///
+/// The number of `natural_t` (u32) values required to hold `thread_identifier_info`.
+pub const THREAD_IDENTIFIER_INFO_COUNT: mach_msg_type_number_t = 6;
+
+/// Thread basic info flavor (flavor 3)
+///
+/// Used with `thread_info()` to read `thread_basic_info`, for the thread's
+/// total suspend count.
+pub const THREAD_BASIC_INFO: c_int = 3;
+
+/// Thread basic info count (40 bytes / 4 = 10 u32 values)
+pub const THREAD_BASIC_INFO_COUNT: mach_msg_type_number_t = 10;
+
+/// Thread extended info flavor (flavor 5)
+///
+/// Used with `thread_info()` to read `thread_extended_info`: the thread's
+/// name (`pth_name`), run state and CPU usage.
+pub const THREAD_EXTENDED_INFO: c_int = 5;
+
+/// Thread extended info count (112 bytes / 4 = 28 u32 values)
+pub const THREAD_EXTENDED_INFO_COUNT: mach_msg_type_number_t = 28;
+
+/// `pth_run_state`: running or runnable
+pub const TH_STATE_RUNNING: i32 = 1;
+
+/// `pth_run_state`: stopped by a suspension
+pub const TH_STATE_STOPPED: i32 = 2;
+
+/// `pth_run_state`: waiting normally
+pub const TH_STATE_WAITING: i32 = 3;
+
+/// `pth_run_state`: waiting, and can't be interrupted
+pub const TH_STATE_UNINTERRUPTIBLE: i32 = 4;
+
+/// `pth_run_state`: halted at a clean point, on its way out
+pub const TH_STATE_HALTED: i32 = 5;
+
+/// `pth_cpu_usage` is in thousandths of a CPU
+pub const TH_USAGE_SCALE: i32 = 1000;
+
+// ============================================================================
+// Task Info Flavors
+// ============================================================================
+
+/// Task dyld info flavor (flavor 17)
+///
+/// Used with `task_info()` to find the target's `dyld_all_image_infos`, the
+/// list of images dyld has loaded and where.
+pub const TASK_DYLD_INFO: c_int = 17;
+
+/// Task dyld info count (20 bytes / 4 = 5 u32 values)
+///
+/// `task_dyld_info` is packed to 4 bytes, so its two `u64`s and one `i32`
+/// take 20 bytes.
+pub const TASK_DYLD_INFO_COUNT: mach_msg_type_number_t = 5;
+
 // ============================================================================
 // Instruction Sizes
 // ============================================================================
@@ -159,6 +253,18 @@ pub const ARM64_BRK_INSTRUCTION: &[u8] = &[0x00, 0x00, 0x20, 0xD4];
 #[cfg(target_arch = "x86_64")]
 pub const X86_64_INT3_INSTRUCTION: &[u8] = &[0xCC];
 
+/// Hardware breakpoint slots on x86-64 (`DR0`-`DR3`)
+#[cfg(target_arch = "x86_64")]
+pub const X86_64_HARDWARE_BREAKPOINT_SLOTS: usize = 4;
+
+/// Hardware breakpoint slots assumed on ARM64 when the kernel doesn't report them
+///
+/// The debug state has room for 16 `DBGBVR`/`DBGBCR` pairs, but cores
+/// implement fewer; Apple Silicon has 6. The real count comes from the
+/// `hw.optional.breakpoint` sysctl.
+#[cfg(target_arch = "aarch64")]
+pub const ARM64_HARDWARE_BREAKPOINT_SLOTS: usize = 6;
+
 // ============================================================================
 // ARM64 Breakpoint Control Register Values
 // ============================================================================
@@ -179,6 +285,61 @@ pub const X86_64_INT3_INSTRUCTION: &[u8] = &[0xCC];
 #[cfg(target_arch = "aarch64")]
 pub const ARM64_BP_CTRL_USER_EXEC: u64 = 0x1E5;
 
+// ============================================================================
+// ARM64 Watchpoint Control Register Fields
+// ============================================================================
+
+/// Watchpoint slots to use on ARM64
+///
+/// The debug state has room for 16 `DBGWVR`/`DBGWCR` pairs, but Apple
+/// Silicon cores implement 4 and the kernel ignores the rest.
+#[cfg(target_arch = "aarch64")]
+pub const ARM64_WATCHPOINT_SLOTS: usize = 4;
+
+/// `DBGWCR.E` (bit 0) with `DBGWCR.PAC` = 10 (bits 1-2): enabled, matching EL0 (user mode) accesses
+#[cfg(target_arch = "aarch64")]
+pub const ARM64_WP_CTRL_USER: u64 = 0b101;
+
+/// `DBGWCR.LSC` = 01 (bits 3-4): trap on loads
+#[cfg(target_arch = "aarch64")]
+pub const ARM64_WP_CTRL_LOAD: u64 = 0b01 << 3;
+
+/// `DBGWCR.LSC` = 10 (bits 3-4): trap on stores
+#[cfg(target_arch = "aarch64")]
+pub const ARM64_WP_CTRL_STORE: u64 = 0b10 << 3;
+
+/// Shift of `DBGWCR.BAS` (bits 5-12), one bit per byte of the doubleword in `DBGWVR`
+#[cfg(target_arch = "aarch64")]
+pub const ARM64_WP_CTRL_BAS_SHIFT: u32 = 5;
+
+/// `EXC_ARM_DA_DEBUG`: the `EXC_BREAKPOINT` code of a watchpoint hit on ARM64
+///
+/// The second exception code holds the address of the access that tripped it.
+pub const EXC_ARM_DA_DEBUG: i64 = 0x102;
+
+/// `EXC_SOFT_SIGNAL`: the `EXC_SOFTWARE` code of a Unix signal
+///
+/// The second exception code holds the signal number.
+pub const EXC_SOFT_SIGNAL: i64 = 0x10003;
+
+// ============================================================================
+// Single-Step Control Bits
+// ============================================================================
+
+/// ARM64 `MDSCR_EL1.SS` (bit 0): software step enable
+///
+/// With this set in the thread's debug state, the thread takes a debug
+/// exception (delivered as `EXC_BREAKPOINT`) after executing one instruction.
+#[cfg(target_arch = "aarch64")]
+pub const ARM64_MDSCR_SS: u64 = 1 << 0;
+
+/// x86-64 `RFLAGS.TF` (bit 8): trap flag
+///
+/// With this set, the CPU raises a debug exception (delivered as
+/// `EXC_BREAKPOINT`) after executing one instruction.
+#[cfg(target_arch = "x86_64")]
+pub const X86_64_RFLAGS_TF: u64 = 1 << 8;
+
 // ============================================================================
 // Bit Masks
 // ============================================================================
@@ -242,3 +403,13 @@ pub const ARM64_PC_INDEX_HIGH: usize = 65;
 /// It's a single u32 value (the second u32 at index 67 is padding).
 #[cfg(target_arch = "aarch64")]
 pub const ARM64_CPSR_INDEX: usize = 66;
+
+// ============================================================================
+// System Integrity Protection
+// ============================================================================
+
+/// `CSR_ALLOW_TASK_FOR_PID`: set when SIP's debugging restriction is off
+///
+/// With it clear, `task_for_pid()` is refused for Apple-signed and hardened
+/// processes even to root. `csrutil enable --without debug` sets it.
+pub const CSR_ALLOW_TASK_FOR_PID: u32 = 1 << 2;
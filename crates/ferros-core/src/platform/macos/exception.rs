@@ -16,8 +16,11 @@
 //! - [task_set_exception_ports(3) man page](https://developer.apple.com/documentation/kernel/1538049-task_set_exception_ports/)
 //! - [thread_set_state(3) man page](https://developer.apple.com/documentation/kernel/1418827-thread_set_state/)
 
+use std::collections::HashSet;
 use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, mpsc};
+use std::time::Instant;
 
 use libc::{c_int, mach_port_t, natural_t, thread_act_t};
 #[cfg(target_os = "macos")]
@@ -27,7 +30,7 @@ use mach2::exception_types::{
     EXC_ARITHMETIC, EXC_BAD_ACCESS, EXC_BAD_INSTRUCTION, EXC_BREAKPOINT, EXC_SOFTWARE, exception_type_t,
 };
 #[cfg(target_os = "macos")]
-use mach2::kern_return::KERN_SUCCESS;
+use mach2::kern_return::{KERN_FAILURE, KERN_SUCCESS, kern_return_t};
 #[cfg(target_os = "macos")]
 use mach2::message::{
     MACH_MSG_SUCCESS, MACH_MSG_TIMEOUT_NONE, MACH_MSG_TYPE_MOVE_SEND_ONCE, MACH_MSGH_BITS, MACH_RCV_LARGE, MACH_RCV_MSG,
@@ -39,11 +42,13 @@ use mach2::ndr::NDR_record;
 use mach2::port::MACH_PORT_NULL;
 use tracing::{debug, error, warn};
 
-use crate::breakpoints::BreakpointStore;
+use crate::breakpoints::{BreakpointStore, ConditionBudget};
 use crate::error::{DebuggerError, Result};
-use crate::events::{self, DebuggerEvent};
-use crate::platform::macos::{constants, ffi};
-use crate::types::{Address, Architecture, StopReason, ThreadId};
+use crate::events::{self, DebuggerEvent, EventEnvelope};
+use crate::platform::macos::{breakpoints as macos_breakpoints, constants, ffi, memory, registers, threads};
+use crate::platform::{fault, stack_guard};
+use crate::signals::{SignalPolicies, SignalPolicy};
+use crate::types::{Address, Architecture, BadAccessKind, Registers, StopPolicy, StopReason, ThreadId};
 
 /// Shared exception state manipulated by the Mach exception loop and debugger methods.
 #[derive(Debug)]
@@ -52,6 +57,18 @@ pub(crate) struct ExceptionSharedState
     pub stopped: bool,
     pub stop_reason: StopReason,
     pub pending_thread: Option<thread_act_t>,
+    /// Thread being single-stepped; its next exception reports a step rather than a breakpoint.
+    pub stepping: Option<thread_act_t>,
+    /// Thread stepping over a call and the call's return address; its hit there
+    /// reports a step rather than a breakpoint.
+    pub step_return: Option<(thread_act_t, u64)>,
+    /// Stops the loop has reported, so a waiter can tell a new stop from the one it resumed.
+    pub stops: u64,
+    /// Whether a stop holds the other threads too; kept across attaches.
+    pub stop_policy: StopPolicy,
+    /// Threads the loop suspended for the current stop under
+    /// [`StopPolicy::AllThreads`], each once, to be resumed with it.
+    pub held_threads: Vec<ThreadId>,
 }
 
 impl ExceptionSharedState
@@ -62,6 +79,27 @@ impl ExceptionSharedState
             stopped: false,
             stop_reason: StopReason::Running,
             pending_thread: None,
+            stepping: None,
+            step_return: None,
+            stops: 0,
+            stop_policy: StopPolicy::default(),
+            held_threads: Vec::new(),
+        }
+    }
+
+    /// Forget the current stop when the loop shuts down
+    ///
+    /// An exit is kept: the process won't stop or run again, and its code is
+    /// still what [`stop_reason`](crate::debugger::Debugger::stop_reason) reports.
+    /// Held threads are forgotten; the debugger releases them first if the
+    /// task is still alive.
+    pub(crate) fn clear_stop(&mut self)
+    {
+        self.pending_thread = None;
+        self.held_threads.clear();
+        if !matches!(self.stop_reason, StopReason::Exited(_)) {
+            self.stopped = false;
+            self.stop_reason = StopReason::Running;
         }
     }
 }
@@ -70,6 +108,9 @@ impl ExceptionSharedState
 pub(crate) enum ExceptionLoopCommand
 {
     Continue,
+    /// Reply to the pending exception without reporting a resume, for when the
+    /// task has already been suspended and the target stays stopped.
+    Release,
     Shutdown,
 }
 
@@ -93,16 +134,22 @@ pub(crate) fn thread_state_flavor_for_arch(architecture: Architecture) -> c_int
     }
 }
 
-/// Rewind the program counter after a breakpoint hit.
+/// Put the program counter back on the software breakpoint that trapped.
 ///
-/// When a breakpoint is hit, the PC points to the instruction after the
-/// breakpoint. This function rewinds it to point at the breakpoint instruction
-/// itself, allowing the debugger to single-step over it.
+/// Where a trap leaves the PC differs by architecture:
 ///
-/// ## Architecture-Specific Behavior
+/// - **ARM64**: `BRK` reports the address of the `BRK` itself, so the PC is
+///   left alone
+/// - **x86-64**: `INT3` reports the address after the 1-byte trap, so the PC
+///   is moved back one byte, but only onto one of our software breakpoints.
+///   A hardware breakpoint faults before its instruction runs, and an `INT3`
+///   compiled into the target isn't ours: rewinding onto it would trap on it
+///   again forever
 ///
-/// - **ARM64**: Subtracts 4 bytes (instruction size)
-/// - **x86-64**: Subtracts 1 byte (INT3 instruction size)
+/// Returns the PC of the breakpoint, which is where the thread resumes. The
+/// trap is still patched in there, so it has to step over the original
+/// instruction before running on (see [`StepOver`] and the debugger's
+/// `step_off_trap()`).
 ///
 /// Uses `thread_get_state()` and `thread_set_state()` to read and modify
 /// the thread's register state.
@@ -110,60 +157,21 @@ pub(crate) fn thread_state_flavor_for_arch(architecture: Architecture) -> c_int
 /// See:
 /// - [thread_get_state(3) man page](https://developer.apple.com/documentation/kernel/1418576-thread_get_state/)
 /// - [thread_set_state(3) man page](https://developer.apple.com/documentation/kernel/1418576-thread_set_state/)
-pub(crate) fn rewind_breakpoint_pc(thread: thread_act_t, architecture: Architecture) -> Result<Option<u64>>
+pub(crate) fn rewind_breakpoint_pc(
+    thread: thread_act_t,
+    architecture: Architecture,
+    breakpoints: &Mutex<BreakpointStore>,
+) -> Result<Option<u64>>
 {
     match architecture {
-        Architecture::Arm64 => rewind_breakpoint_pc_arm64(thread),
-        Architecture::X86_64 => rewind_breakpoint_pc_x86(thread),
+        Architecture::Arm64 => thread_registers(thread, architecture).map(|registers| Some(registers.pc.value())),
+        Architecture::X86_64 => rewind_breakpoint_pc_x86(thread, breakpoints),
         Architecture::Unknown(_) => Ok(None),
     }
 }
 
-#[cfg(target_arch = "aarch64")]
-fn rewind_breakpoint_pc_arm64(thread: thread_act_t) -> Result<Option<u64>>
-{
-    unsafe {
-        let mut state: [natural_t; constants::ARM_THREAD_STATE64_COUNT as usize] =
-            [0; constants::ARM_THREAD_STATE64_COUNT as usize];
-        let mut count = constants::ARM_THREAD_STATE64_COUNT;
-        let mut kr = ffi::thread_get_state(thread, constants::ARM_THREAD_STATE64, state.as_mut_ptr(), &mut count);
-        if kr != KERN_SUCCESS {
-            return Err(DebuggerError::MachError(kr.into()));
-        }
-
-        let read_u64 = |idx: usize, buf: &[natural_t]| -> u64 {
-            let lo = buf[idx * 2] as u64;
-            let hi = buf[idx * 2 + 1] as u64;
-            lo | (hi << 32)
-        };
-
-        let pc = read_u64(constants::ARM64_PC_INDEX, &state);
-        let new_pc = pc.saturating_sub(constants::ARM64_INSTRUCTION_SIZE);
-        state[constants::ARM64_PC_INDEX_LOW] = (new_pc & constants::U32_MASK) as natural_t;
-        state[constants::ARM64_PC_INDEX_HIGH] = (new_pc >> 32) as natural_t;
-
-        kr = ffi::thread_set_state(
-            thread,
-            constants::ARM_THREAD_STATE64,
-            state.as_ptr(),
-            constants::ARM_THREAD_STATE64_COUNT,
-        );
-        if kr != KERN_SUCCESS {
-            return Err(DebuggerError::MachError(kr.into()));
-        }
-
-        Ok(Some(new_pc))
-    }
-}
-
-#[cfg(not(target_arch = "aarch64"))]
-fn rewind_breakpoint_pc_arm64(_thread: thread_act_t) -> Result<Option<u64>>
-{
-    Ok(None)
-}
-
 #[cfg(target_arch = "x86_64")]
-fn rewind_breakpoint_pc_x86(thread: thread_act_t) -> Result<Option<u64>>
+fn rewind_breakpoint_pc_x86(thread: thread_act_t, breakpoints: &Mutex<BreakpointStore>) -> Result<Option<u64>>
 {
     #[repr(C)]
     #[derive(Default, Clone, Copy)]
@@ -209,6 +217,9 @@ fn rewind_breakpoint_pc_x86(thread: thread_act_t) -> Result<Option<u64>>
         }
 
         let new_pc = state.rip.saturating_sub(constants::X86_64_INSTRUCTION_SIZE);
+        if breakpoints.lock().unwrap().patched_code_at(Address::from(new_pc)).is_none() {
+            return Ok(Some(state.rip));
+        }
         state.rip = new_pc;
 
         kr = ffi::thread_set_state(
@@ -226,11 +237,260 @@ fn rewind_breakpoint_pc_x86(thread: thread_act_t) -> Result<Option<u64>>
 }
 
 #[cfg(not(target_arch = "x86_64"))]
-fn rewind_breakpoint_pc_x86(_thread: thread_act_t) -> Result<Option<u64>>
+fn rewind_breakpoint_pc_x86(_thread: thread_act_t, _breakpoints: &Mutex<BreakpointStore>) -> Result<Option<u64>>
 {
     Ok(None)
 }
 
+/// Stack pointer and access kind of a thread that took a bad access
+struct FaultContext
+{
+    sp: u64,
+    kind: BadAccessKind,
+}
+
+/// Describe a bad access at `address`, or reclassify it as a stack overflow when it hit a thread's guard page.
+///
+/// `address` is the second exception code of the `EXC_BAD_ACCESS`. SP and
+/// the access kind come from the thread's state and exception state (`ESR`
+/// on ARM64, the page fault error code on x86-64); SP and the task's memory
+/// map go to [`stack_guard::stack_overflow_guard`]. If the exception state
+/// can't be read the kind is `Unknown`, and if the memory map can't be read
+/// the fault stays a bad access.
+pub(crate) fn classify_bad_access(
+    task: mach_port_t,
+    thread: thread_act_t,
+    architecture: Architecture,
+    address: u64,
+) -> StopReason
+{
+    let context = match architecture {
+        Architecture::Arm64 => fault_context_arm64(thread),
+        Architecture::X86_64 => fault_context_x86(thread),
+        Architecture::Unknown(_) => None,
+    };
+    let Some(FaultContext { sp, kind }) = context else {
+        return StopReason::BadAccess {
+            address,
+            kind: BadAccessKind::Unknown,
+        };
+    };
+    if let Ok(regions) = memory::get_memory_regions(task)
+        && let Some(guard) = stack_guard::stack_overflow_guard(Address::from(address), Address::from(sp), &regions)
+    {
+        return StopReason::StackOverflow {
+            thread: threads::ThreadManager::stable_thread_id(thread),
+            sp,
+            guard_page: guard.value(),
+        };
+    }
+    StopReason::BadAccess { address, kind }
+}
+
+/// Read SP and the access kind of a faulting ARM64 thread.
+#[cfg(target_arch = "aarch64")]
+fn fault_context_arm64(thread: thread_act_t) -> Option<FaultContext>
+{
+    unsafe {
+        let mut state: [natural_t; constants::ARM_THREAD_STATE64_COUNT as usize] =
+            [0; constants::ARM_THREAD_STATE64_COUNT as usize];
+        let mut count = constants::ARM_THREAD_STATE64_COUNT;
+        if ffi::thread_get_state(thread, constants::ARM_THREAD_STATE64, state.as_mut_ptr(), &mut count) != KERN_SUCCESS {
+            return None;
+        }
+        let sp = state[constants::ARM64_SP_INDEX * 2] as u64 | ((state[constants::ARM64_SP_INDEX * 2 + 1] as u64) << 32);
+
+        // far: u64, esr: u32, exception: u32
+        let mut exception: [natural_t; constants::ARM_EXCEPTION_STATE64_COUNT as usize] =
+            [0; constants::ARM_EXCEPTION_STATE64_COUNT as usize];
+        let mut count = constants::ARM_EXCEPTION_STATE64_COUNT;
+        if ffi::thread_get_state(thread, constants::ARM_EXCEPTION_STATE64, exception.as_mut_ptr(), &mut count)
+            != KERN_SUCCESS
+        {
+            return None;
+        }
+
+        Some(FaultContext {
+            sp,
+            kind: fault::access_kind_arm64(exception[2]),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn fault_context_arm64(_thread: thread_act_t) -> Option<FaultContext>
+{
+    None
+}
+
+/// Read RSP and the access kind of a faulting x86-64 thread.
+#[cfg(target_arch = "x86_64")]
+fn fault_context_x86(thread: thread_act_t) -> Option<FaultContext>
+{
+    /// Index of RSP in `x86_THREAD_STATE64`, counted in u64 registers
+    const RSP_INDEX: usize = 7;
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct X86ExceptionState64
+    {
+        trapno: u16,
+        cpu: u16,
+        err: u32,
+        faultvaddr: u64,
+    }
+
+    unsafe {
+        let mut state = [0_u64; constants::X86_THREAD_STATE64_COUNT as usize / 2];
+        let mut count = constants::X86_THREAD_STATE64_COUNT;
+        if ffi::thread_get_state(
+            thread,
+            constants::X86_THREAD_STATE64,
+            state.as_mut_ptr() as *mut natural_t,
+            &mut count,
+        ) != KERN_SUCCESS
+        {
+            return None;
+        }
+
+        let mut exception = X86ExceptionState64::default();
+        let mut count = constants::X86_EXCEPTION_STATE64_COUNT;
+        if ffi::thread_get_state(
+            thread,
+            constants::X86_EXCEPTION_STATE64,
+            &mut exception as *mut _ as *mut natural_t,
+            &mut count,
+        ) != KERN_SUCCESS
+        {
+            return None;
+        }
+
+        Some(FaultContext {
+            sp: state[RSP_INDEX],
+            kind: fault::access_kind_x86(exception.trapno, exception.err),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn fault_context_x86(_thread: thread_act_t) -> Option<FaultContext>
+{
+    None
+}
+
+/// Read the registers of a thread stopped in the exception handler.
+fn thread_registers(thread: thread_act_t, architecture: Architecture) -> Result<Registers>
+{
+    match architecture {
+        #[cfg(target_arch = "aarch64")]
+        Architecture::Arm64 => registers::read_registers_arm64(thread),
+        #[cfg(target_arch = "x86_64")]
+        Architecture::X86_64 => registers::read_registers_x86_64(thread),
+        _ => Err(DebuggerError::UnsupportedArchitecture(architecture)),
+    }
+}
+
+/// A thread the handler is stepping over a software breakpoint before it runs on.
+///
+/// A hit that doesn't surface as a stop (a false condition, an ignored hit)
+/// is continued from the handler itself. The thread is back on the trap, so
+/// the original instruction is written back, every other thread is held so
+/// none runs past the breakpoint meanwhile, and the thread single-steps. Its
+/// next exception ends the step, and the trap goes back in before anything
+/// else runs.
+struct StepOver
+{
+    task: mach_port_t,
+    thread: thread_act_t,
+    address: Address,
+    held: Vec<ThreadId>,
+}
+
+impl StepOver
+{
+    /// Put the original instruction back at `address` and arm a single step on `thread`.
+    ///
+    /// Returns `None`, with the trap left in place, when no enabled software
+    /// breakpoint is patched in at `address` or the step can't be armed.
+    fn begin(
+        task: mach_port_t,
+        thread: thread_act_t,
+        address: Address,
+        architecture: Architecture,
+        breakpoints: &Mutex<BreakpointStore>,
+    ) -> Option<Self>
+    {
+        let original = breakpoints.lock().unwrap().patched_code_at(address)?;
+        if let Err(err) = memory::write_memory(task, address, &original) {
+            error!("Failed to restore the instruction under the breakpoint at {address}: {err}");
+            return None;
+        }
+        let over = Self {
+            task,
+            thread,
+            address,
+            held: threads::ThreadManager::suspend_other_threads(task, thread),
+        };
+        if let Err(err) = registers::set_single_step(thread, true) {
+            error!("Failed to step over the breakpoint at {address}: {err}");
+            over.finish(architecture, breakpoints);
+            return None;
+        }
+        Some(over)
+    }
+
+    /// End the step: re-patch the trap, unless the breakpoint went away meanwhile, and release the other threads.
+    fn finish(self, architecture: Architecture, breakpoints: &Mutex<BreakpointStore>)
+    {
+        if let Err(err) = registers::set_single_step(self.thread, false) {
+            warn!("Failed to clear single-step on thread port {}: {err}", self.thread);
+        }
+        if breakpoints.lock().unwrap().patched_code_at(self.address).is_some() {
+            let repatched = macos_breakpoints::BreakpointManager::software_trap_bytes(architecture)
+                .and_then(|trap| memory::write_memory(self.task, self.address, &trap));
+            if let Err(err) = repatched {
+                error!(
+                    "Failed to re-patch the breakpoint at {} after stepping over it: {err}",
+                    self.address
+                );
+            }
+        }
+        threads::ThreadManager::resume_threads(self.task, &self.held);
+    }
+}
+
+/// Check the thread filter and evaluate the condition of the breakpoint a thread trapped on.
+///
+/// The thread's registers are only read when the breakpoint has a condition.
+/// Returns whether the target should stop; a hit by a thread the breakpoint
+/// isn't filtered to never stops, and a condition that can't be evaluated
+/// counts as false and is recorded in the breakpoint's stats.
+fn breakpoint_condition_holds(
+    breakpoints: &Mutex<BreakpointStore>,
+    address: Address,
+    task: mach_port_t,
+    thread: thread_act_t,
+    architecture: Architecture,
+) -> bool
+{
+    let mut store = breakpoints.lock().unwrap();
+    if !store.stops_thread(address, threads::ThreadManager::stable_thread_id(thread)) {
+        return false;
+    }
+    if !store.has_condition(address) {
+        return true;
+    }
+    let registers = match thread_registers(thread, architecture) {
+        Ok(registers) => registers,
+        Err(err) => {
+            store.record_condition_failure(address, format!("Failed to read the thread's registers: {err}"));
+            return false;
+        }
+    };
+    let mut read = |addr: Address, len: usize| memory::read_memory(task, addr, len);
+    store.condition_holds(address, &registers, &mut read, &ConditionBudget::default())
+}
+
 /// Convert a Mach exception to a StopReason.
 ///
 /// Maps Mach exception types to platform-agnostic stop reasons.
@@ -238,17 +498,23 @@ fn rewind_breakpoint_pc_x86(_thread: thread_act_t) -> Result<Option<u64>>
 /// ## Exception Types
 ///
 /// - `EXC_BREAKPOINT` → `StopReason::Breakpoint`
-/// - `EXC_BAD_ACCESS` → `StopReason::Signal(SIGSEGV)`
+/// - `EXC_BAD_ACCESS` → `StopReason::BadAccess` at the address in the second code, of unknown kind
+///   until [`classify_bad_access`] reads the thread's fault state
 /// - `EXC_BAD_INSTRUCTION` → `StopReason::Signal(SIGILL)`
 /// - `EXC_ARITHMETIC` → `StopReason::Signal(SIGFPE)`
-/// - `EXC_SOFTWARE` → `StopReason::Signal(SIGTRAP)`
+/// - `EXC_SOFTWARE` with `EXC_SOFT_SIGNAL` → `StopReason::Signal` of the signal in the second code
+/// - Other `EXC_SOFTWARE` → `StopReason::Signal(SIGTRAP)`
 ///
 /// See: [Mach Exception Types](https://developer.apple.com/documentation/kernel/1402149-exception_types/)
-pub(crate) fn stop_reason_from_exception(exception: exception_type_t, pc: Option<u64>, _codes: [i64; 2]) -> StopReason
+pub(crate) fn stop_reason_from_exception(exception: exception_type_t, pc: Option<u64>, codes: [i64; 2]) -> StopReason
 {
     match exception as u32 {
+        EXC_SOFTWARE if codes[0] == constants::EXC_SOFT_SIGNAL => StopReason::Signal(codes[1] as i32),
         EXC_BREAKPOINT => StopReason::Breakpoint(pc.unwrap_or(0)),
-        EXC_BAD_ACCESS => StopReason::Signal(libc::SIGSEGV),
+        EXC_BAD_ACCESS => StopReason::BadAccess {
+            address: codes[1] as u64,
+            kind: BadAccessKind::Unknown,
+        },
         EXC_BAD_INSTRUCTION => StopReason::Signal(libc::SIGILL),
         EXC_ARITHMETIC => StopReason::Signal(libc::SIGFPE),
         EXC_SOFTWARE => StopReason::Signal(libc::SIGTRAP),
@@ -262,11 +528,26 @@ pub(crate) fn stop_reason_from_exception(exception: exception_type_t, pc: Option
 /// exception messages from the Mach kernel via `mach_msg()`. When an
 /// exception is received, it:
 ///
-/// 1. Determines the stop reason (breakpoint, signal, etc.)
-/// 2. Updates shared exception state
-/// 3. Sends a `DebuggerEvent::TargetStopped` event
-/// 4. Waits for a resume command
-/// 5. Sends an exception reply via `send_exception_reply()`
+/// 1. Determines the stop reason (breakpoint, signal, etc.), and replies
+///    straight away to a breakpoint whose condition is false or whose ignore
+///    count skips the hit (conditions are checked first), stepping the thread
+///    over a software breakpoint's original instruction first; the trap that
+///    ends a single step, or reaching the return address of a call being
+///    stepped over, is reported as `StopReason::Step`, and an
+///    `EXC_ARM_DA_DEBUG` breakpoint as `StopReason::Watchpoint` naming the
+///    address of the watchpoint the access tripped. A signal whose
+///    [`SignalPolicy`] passes or ignores it is replied to straight away too,
+///    failing the reply to pass it on
+/// 2. Programs armed hardware breakpoints and watchpoints on threads that
+///    started since the last stop, since debug registers are per thread
+/// 3. Updates shared exception state, first suspending every other thread
+///    when the [`StopPolicy`] is `AllThreads` (not for the trap ending a
+///    single step, whose other threads are already held), and advances the
+///    memory cache's generation so pages read before the stop are re-read
+/// 4. Sends a `DebuggerEvent::TargetStopped` event, stamped with the time
+///    `mach_msg()` returned (the message itself carries no kernel timestamp)
+/// 5. Waits for a resume command
+/// 6. Sends an exception reply via `send_exception_reply()`
 ///
 /// ## Mach Message Protocol
 ///
@@ -287,8 +568,13 @@ pub(crate) fn run_exception_loop(
     architecture: Architecture,
     event_tx: events::DebuggerEventSender,
     breakpoints: Arc<Mutex<BreakpointStore>>,
+    signal_policies: Arc<Mutex<SignalPolicies>>,
+    cache_generation: Arc<AtomicU64>,
 )
 {
+    // Threads whose debug registers this loop has brought up to date
+    let mut armed_threads = HashSet::new();
+    let mut step_over: Option<StepOver> = None;
     loop {
         let mut request = MaybeUninit::<__Request__exception_raise_t>::uninit();
         let recv_size = std::mem::size_of::<__Request__exception_raise_t>() as mach_msg_size_t;
@@ -312,13 +598,45 @@ pub(crate) fn run_exception_loop(
             }
             continue;
         }
+        let received = Instant::now();
 
         let message = unsafe { request.assume_init() };
         let thread_port = message.thread.name as thread_act_t;
         let codes = [message.code[0] as i64, message.code[1] as i64];
+        // A watchpoint traps before the access completes, with the accessed address in the second code
+        let watch_fault = (architecture == Architecture::Arm64
+            && message.exception == EXC_BREAKPOINT as exception_type_t
+            && codes[0] == constants::EXC_ARM_DA_DEBUG)
+            .then_some(codes[1] as u64);
+
+        // The thread the handler continued past a breakpoint has finished its step
+        if let Some(over) = step_over.take_if(|over| over.thread == thread_port) {
+            over.finish(architecture, &breakpoints);
+            // Anything but the step's own trap (a fault in the stepped instruction) is handled as usual
+            if message.exception == EXC_BREAKPOINT as exception_type_t && watch_fault.is_none() {
+                if let Err(err) = send_exception_reply(&message) {
+                    error!("Failed to send Mach exception reply: {err}");
+                    break;
+                }
+                continue;
+            }
+        }
+
+        let stepped = {
+            let mut shared = shared_state.lock().unwrap();
+            if shared.stepping == Some(thread_port) {
+                shared.stepping.take()
+            } else {
+                None
+            }
+        };
 
-        let rewound_pc = if message.exception == EXC_BREAKPOINT as exception_type_t {
-            match rewind_breakpoint_pc(thread_port, architecture) {
+        let rewound_pc = if stepped.is_some() || watch_fault.is_some() {
+            // The trap from a single step leaves the PC on the next instruction,
+            // and a watchpoint's on the instruction making the access
+            None
+        } else if message.exception == EXC_BREAKPOINT as exception_type_t {
+            match rewind_breakpoint_pc(thread_port, architecture, &breakpoints) {
                 Ok(value) => value,
                 Err(err) => {
                     error!("Failed to rewind breakpoint PC: {err}");
@@ -329,42 +647,189 @@ pub(crate) fn run_exception_loop(
             None
         };
 
-        let stop_reason = stop_reason_from_exception(message.exception, rewound_pc, codes);
+        let mut stop_reason = stop_reason_from_exception(message.exception, rewound_pc, codes);
+        let watch_hit =
+            watch_fault.and_then(|fault| breakpoints.lock().unwrap().record_watchpoint_hit(Address::from(fault)));
+        if let Some(fault) = watch_fault {
+            // Also ends a step onto the access, which hasn't happened yet
+            let pc = match thread_registers(thread_port, architecture) {
+                Ok(registers) => registers.pc.value(),
+                Err(err) => {
+                    error!("Failed to read PC at watchpoint hit: {err}");
+                    0
+                }
+            };
+            stop_reason = StopReason::Watchpoint {
+                address: watch_hit.as_ref().map_or(fault, |hit| hit.info.address.value()),
+                pc,
+            };
+        } else if stepped.is_some() && message.exception == EXC_BREAKPOINT as exception_type_t {
+            match thread_registers(thread_port, architecture) {
+                Ok(registers) => stop_reason = StopReason::Step(registers.pc.value()),
+                Err(err) => error!("Failed to read PC after single step: {err}"),
+            }
+        }
+        // A signal the user passes on or ignores never surfaces as a stop; a fault during a step still ends it
+        let signal = match stop_reason {
+            StopReason::Signal(signal) => Some(signal),
+            StopReason::BadAccess { .. } => Some(libc::SIGSEGV),
+            _ => None,
+        };
+        if let Some(signal) = signal
+            && stepped.is_none()
+        {
+            let policy = signal_policies.lock().unwrap().policy(signal);
+            if policy != SignalPolicy::Stop {
+                debug!("Handling signal {signal} without stopping: {policy}");
+                // A failed reply sends the exception on to the kernel, which raises the Unix signal
+                let code = if policy == SignalPolicy::PassThrough {
+                    KERN_FAILURE
+                } else {
+                    KERN_SUCCESS
+                };
+                if let Err(err) = send_exception_reply_with(&message, code) {
+                    error!("Failed to send Mach exception reply: {err}");
+                    break;
+                }
+                continue;
+            }
+        }
+        if let StopReason::BadAccess { address, .. } = stop_reason {
+            stop_reason = classify_bad_access(message.task.name, thread_port, architecture, address);
+        }
+
+        // A hit by a thread the breakpoint isn't filtered to, or whose condition is false, never surfaces as a stop
+        if let StopReason::Breakpoint(addr) = stop_reason
+            && !breakpoint_condition_holds(
+                &breakpoints,
+                Address::from(addr),
+                message.task.name,
+                thread_port,
+                architecture,
+            )
+        {
+            step_over = StepOver::begin(
+                message.task.name,
+                thread_port,
+                Address::from(addr),
+                architecture,
+                &breakpoints,
+            );
+            if let Err(err) = send_exception_reply(&message) {
+                error!("Failed to send Mach exception reply: {err}");
+                break;
+            }
+            let mut store = breakpoints.lock().unwrap();
+            if let Some(id) = store.trap_at(Address::from(addr)) {
+                store.record_handling(id, received, received.elapsed());
+            }
+            continue;
+        }
+
+        // Reaching the return address of a step ends the step rather than hitting a breakpoint there
+        let ends_step = matches!(
+            stop_reason,
+            StopReason::Breakpoint(addr) if shared_state.lock().unwrap().step_return == Some((thread_port, addr))
+        );
+        let hit = match stop_reason {
+            StopReason::Breakpoint(addr) if !ends_step => breakpoints.lock().unwrap().record_hit(Address::from(addr)),
+            _ => watch_hit,
+        };
+
+        // Neither does a hit the breakpoint's ignore count skips
+        if let Some(hit) = &hit
+            && hit.ignored
+        {
+            if let StopReason::Breakpoint(addr) = stop_reason {
+                step_over = StepOver::begin(
+                    message.task.name,
+                    thread_port,
+                    Address::from(addr),
+                    architecture,
+                    &breakpoints,
+                );
+            }
+            if let Err(err) = send_exception_reply(&message) {
+                error!("Failed to send Mach exception reply: {err}");
+                break;
+            }
+            breakpoints
+                .lock()
+                .unwrap()
+                .record_handling(hit.info.id, received, received.elapsed());
+            continue;
+        }
+        let hit = hit.map(|hit| hit.info.id);
+
+        // Threads that started while the target ran have clear debug registers
+        if !breakpoints.lock().unwrap().armed_debug_registers().is_empty() {
+            threads::ThreadManager::visit_started_threads(message.task.name, &mut armed_threads, |started| {
+                macos_breakpoints::BreakpointManager::arm_started_threads(&breakpoints, started);
+            });
+        }
+
+        let hold_others = stepped.is_none() && shared_state.lock().unwrap().stop_policy == StopPolicy::AllThreads;
+        let held = if hold_others {
+            threads::ThreadManager::suspend_other_threads(message.task.name, thread_port)
+        } else {
+            Vec::new()
+        };
+
         {
             let mut shared = shared_state.lock().unwrap();
+            shared.held_threads.extend(held);
+            if ends_step && let StopReason::Breakpoint(addr) = stop_reason {
+                shared.step_return = None;
+                stop_reason = StopReason::Step(addr);
+            }
             shared.stopped = true;
             shared.stop_reason = stop_reason;
             shared.pending_thread = Some(thread_port);
+            shared.stops += 1;
         }
+        // Memory cached before the target ran is stale now
+        cache_generation.fetch_add(1, Ordering::Relaxed);
 
-        if let StopReason::Breakpoint(addr) = stop_reason {
-            let mut store = breakpoints.lock().unwrap();
-            store.record_hit(Address::from(addr));
-        }
-
-        if let Err(err) = event_tx.send(DebuggerEvent::TargetStopped {
+        let stopped = EventEnvelope::new(DebuggerEvent::TargetStopped {
             reason: stop_reason,
-            thread: Some(ThreadId::from(thread_port as u64)),
-        }) {
+            thread: Some(threads::ThreadManager::stable_thread_id(thread_port)),
+        })
+        .with_source_received(received);
+        if let Err(err) = event_tx.send(stopped) {
             warn!("Failed to send stop event from Mach loop: {err}");
         }
+        let surfaced = Instant::now();
 
         match resume_rx.recv() {
             Ok(ExceptionLoopCommand::Continue) => {
+                let resumed = Instant::now();
                 if let Err(err) = send_exception_reply(&message) {
                     error!("Failed to send Mach exception reply: {err}");
                     break;
                 }
+                if let Some(id) = hit {
+                    // Time spent waiting for the resume command is the user looking
+                    // at the stop, not overhead the breakpoint put on the target.
+                    let handling = received.elapsed().saturating_sub(resumed - surfaced);
+                    breakpoints.lock().unwrap().record_handling(id, received, handling);
+                }
 
                 let mut shared = shared_state.lock().unwrap();
                 shared.stopped = false;
                 shared.stop_reason = StopReason::Running;
                 shared.pending_thread = None;
 
-                if let Err(err) = event_tx.send(DebuggerEvent::TargetResumed) {
+                if let Err(err) = event_tx.send(EventEnvelope::new(DebuggerEvent::TargetResumed)) {
                     warn!("Failed to send resume event from Mach loop: {err}");
                 }
             }
+            Ok(ExceptionLoopCommand::Release) => {
+                if let Err(err) = send_exception_reply(&message) {
+                    error!("Failed to send Mach exception reply: {err}");
+                    break;
+                }
+                shared_state.lock().unwrap().pending_thread = None;
+            }
             Ok(ExceptionLoopCommand::Shutdown) | Err(_) => {
                 let mut shared = shared_state.lock().unwrap();
                 shared.stopped = false;
@@ -374,6 +839,11 @@ pub(crate) fn run_exception_loop(
             }
         }
     }
+
+    // Shut down mid-step: put the trap back and let the held threads go
+    if let Some(over) = step_over {
+        over.finish(architecture, &breakpoints);
+    }
 }
 
 /// Send a reply to a Mach exception message.
@@ -387,6 +857,16 @@ pub(crate) fn run_exception_loop(
 /// See: [mach_msg(3) man page](https://developer.apple.com/documentation/kernel/1402149-mach_msg/)
 #[cfg(target_os = "macos")]
 fn send_exception_reply(request: &__Request__exception_raise_t) -> Result<()>
+{
+    send_exception_reply_with(request, KERN_SUCCESS)
+}
+
+/// [`send_exception_reply`] with `ret_code` as the reply's result.
+///
+/// Anything but `KERN_SUCCESS` tells the kernel the exception wasn't handled,
+/// so it moves on to the next handler, ending with the BSD signal.
+#[cfg(target_os = "macos")]
+fn send_exception_reply_with(request: &__Request__exception_raise_t, ret_code: kern_return_t) -> Result<()>
 {
     let mut reply = __Reply__exception_raise_t {
         Head: mach_msg_header_t {
@@ -398,7 +878,7 @@ fn send_exception_reply(request: &__Request__exception_raise_t) -> Result<()>
             msgh_id: request.Head.msgh_id + 100,
         },
         NDR: unsafe { NDR_record },
-        RetCode: KERN_SUCCESS,
+        RetCode: ret_code,
     };
 
     let kr = unsafe {
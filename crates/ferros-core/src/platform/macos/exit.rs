@@ -0,0 +1,179 @@
+//! # Exit Monitoring
+//!
+//! Watching the target for its exit.
+//!
+//! The exception port hears about breakpoints and crashes, but not about a
+//! process that returns from `main`, calls `exit()` or is killed; after that
+//! every Mach call on the task fails in its own way. An [`ExitMonitor`]
+//! thread waits for the exit on a kqueue `EVFILT_PROC` filter with
+//! `NOTE_EXIT` instead, which works for attached processes as well as
+//! launched ones.
+//!
+//! A launched target is our child, so it is then reaped with `waitpid()`,
+//! which gives its exit status (`128 + signal` for a signal, as a shell
+//! reports it). A process ferros only attached to can't be waited for, so its
+//! code is [`UNKNOWN_EXIT_CODE`].
+//!
+//! ## References
+//!
+//! - [kqueue(2) man page](https://developer.apple.com/library/archive/documentation/System/Conceptual/ManPages_iPhoneOS/man2/kqueue.2.html)
+//! - [waitpid(2) man page](https://developer.apple.com/library/archive/documentation/System/Conceptual/ManPages_iPhoneOS/man2/waitpid.2.html)
+
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use libc::c_int;
+
+use crate::error::{DebuggerError, Result};
+use crate::types::ProcessId;
+
+/// Exit code reported for an attached process, whose status only its parent can read.
+pub const UNKNOWN_EXIT_CODE: i32 = -1;
+
+/// How often the monitor checks whether it was stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A thread waiting for the target to exit, see the [module docs](self).
+#[derive(Debug)]
+pub(crate) struct ExitMonitor
+{
+    cancel: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ExitMonitor
+{
+    /// Watch `pid` and call `on_exit` with its exit code when it exits
+    ///
+    /// `child` is whether ferros launched it, and so can reap it. A process
+    /// that is already gone calls `on_exit` straight away.
+    ///
+    /// ## Errors
+    ///
+    /// - `Io`: The kqueue couldn't be created or the process couldn't be watched
+    /// - `AttachFailed`: The thread couldn't be spawned
+    pub(crate) fn spawn(pid: ProcessId, child: bool, on_exit: impl FnOnce(i32) + Send + 'static) -> Result<Self>
+    {
+        let pid = pid.0 as libc::pid_t;
+        // SAFETY: kqueue() takes no arguments and returns a descriptor or -1
+        let queue = unsafe { libc::kqueue() };
+        if queue < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        let change = libc::kevent {
+            ident: pid as libc::uintptr_t,
+            filter: libc::EVFILT_PROC,
+            flags: libc::EV_ADD | libc::EV_ONESHOT,
+            fflags: libc::NOTE_EXIT,
+            data: 0,
+            udata: std::ptr::null_mut(),
+        };
+        // SAFETY: one change in, no room for events out
+        let registered = unsafe { libc::kevent(queue, &change, 1, std::ptr::null_mut(), 0, std::ptr::null()) };
+        // ESRCH: it exited between the attach and now
+        let already_gone = registered < 0 && io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH);
+        if registered < 0 && !already_gone {
+            let err = io::Error::last_os_error();
+            // SAFETY: the descriptor is ours and not used again
+            unsafe { libc::close(queue) };
+            return Err(err.into());
+        }
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&cancel);
+        let handle = thread::Builder::new()
+            .name("ferros-mac-exit".to_string())
+            .spawn(move || {
+                let exited = already_gone || wait_for_exit(queue, &stopped);
+                // SAFETY: the descriptor is ours and not used again
+                unsafe { libc::close(queue) };
+                if !exited {
+                    return;
+                }
+                // Reaped even after a stop, so a killed child doesn't linger as a zombie
+                let code = if child { reap(pid) } else { UNKNOWN_EXIT_CODE };
+                if !stopped.load(Ordering::Acquire) {
+                    on_exit(code);
+                }
+            })
+            .map_err(|e| {
+                // SAFETY: the thread never started, so the descriptor is still only ours
+                unsafe { libc::close(queue) };
+                DebuggerError::AttachFailed(format!("Failed to spawn exit monitor: {e}"))
+            })?;
+        Ok(Self {
+            cancel,
+            handle: Some(handle),
+        })
+    }
+
+    /// Stop watching; once this returns the callback won't run
+    ///
+    /// Waits up to [`POLL_INTERVAL`] for the thread to notice.
+    pub(crate) fn stop(mut self)
+    {
+        self.cancel.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Wait on `queue` until the watched process exits or `cancel` is set
+///
+/// Returns whether the process exited.
+fn wait_for_exit(queue: c_int, cancel: &AtomicBool) -> bool
+{
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: POLL_INTERVAL.subsec_nanos().into(),
+    };
+    // SAFETY: kevent is plain data, all zeroes is a valid value
+    let mut event: libc::kevent = unsafe { std::mem::zeroed() };
+    while !cancel.load(Ordering::Acquire) {
+        // SAFETY: no changes in, room for one event out
+        let ready = unsafe { libc::kevent(queue, std::ptr::null(), 0, &mut event, 1, &timeout) };
+        if ready > 0 {
+            return true;
+        }
+        if ready < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                tracing::warn!("Stopped watching for the target's exit: {err}");
+                return false;
+            }
+        }
+    }
+    false
+}
+
+/// Reap the exited child `pid` and decode its status
+fn reap(pid: libc::pid_t) -> i32
+{
+    let mut status: c_int = 0;
+    loop {
+        // SAFETY: waitpid writes a single int
+        let reaped = unsafe { libc::waitpid(pid, &mut status, 0) };
+        if reaped == pid {
+            break;
+        }
+        if reaped < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+        }
+        tracing::warn!(
+            "Couldn't read the exit status of process {pid}: {}",
+            io::Error::last_os_error()
+        );
+        return UNKNOWN_EXIT_CODE;
+    }
+    if libc::WIFEXITED(status) {
+        libc::WEXITSTATUS(status)
+    } else if libc::WIFSIGNALED(status) {
+        128 + libc::WTERMSIG(status)
+    } else {
+        UNKNOWN_EXIT_CODE
+    }
+}
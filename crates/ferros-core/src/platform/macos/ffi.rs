@@ -74,6 +74,136 @@ pub struct VmRegionBasicInfoData
     pub user_wired_count: u16,
 }
 
+/// Structure for thread_identifier_info
+///
+/// This matches the structure returned by `thread_info()` with the
+/// `THREAD_IDENTIFIER_INFO` flavor (`thread_identifier_info_data_t`).
+///
+/// ## Field Descriptions
+///
+/// - `thread_id`: System-wide unique thread id, stable for the thread's lifetime
+/// - `thread_handle`: Pthread handle (`pthread_t`) in the target's address space
+/// - `dispatch_qaddr`: Address of the thread's current dispatch queue, if any
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadIdentifierInfo
+{
+    /// System-wide unique thread id
+    pub thread_id: u64,
+    /// Pthread handle in the target process
+    pub thread_handle: u64,
+    /// Dispatch queue address in the target process
+    pub dispatch_qaddr: u64,
+}
+
+/// Structure for task_dyld_info
+///
+/// This matches the structure returned by `task_info()` with the
+/// `TASK_DYLD_INFO` flavor (`task_dyld_info_data_t`), which the headers
+/// declare under `#pragma pack(4)`.
+///
+/// ## Field Descriptions
+///
+/// - `all_image_info_addr`: Address of `dyld_all_image_infos` in the target
+/// - `all_image_info_size`: Size of that structure, which grows with its version
+/// - `all_image_info_format`: `TASK_DYLD_ALL_IMAGE_INFO_64` (1) for 64-bit targets
+#[repr(C, packed(4))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TaskDyldInfo
+{
+    /// Address of `dyld_all_image_infos` in the target process
+    pub all_image_info_addr: u64,
+    /// Size of `dyld_all_image_infos` in bytes
+    pub all_image_info_size: u64,
+    /// Pointer width of the structure: 0 for 32-bit, 1 for 64-bit
+    pub all_image_info_format: i32,
+}
+
+/// Structure for thread_basic_info
+///
+/// This matches the structure returned by `thread_info()` with the
+/// `THREAD_BASIC_INFO` flavor (`thread_basic_info_data_t`). Ferros only reads
+/// `suspend_count` from it; the rest is in [`ThreadExtendedInfo`] too.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadBasicInfo
+{
+    /// User run time (seconds, microseconds)
+    pub user_time: [i32; 2],
+    /// System run time (seconds, microseconds)
+    pub system_time: [i32; 2],
+    /// Scaled CPU usage percentage
+    pub cpu_usage: i32,
+    /// Scheduling policy in effect
+    pub policy: i32,
+    /// Run state (`TH_STATE_*`)
+    pub run_state: i32,
+    /// Various flags (`TH_FLAGS_*`)
+    pub flags: i32,
+    /// Suspend count for the thread, from anyone
+    pub suspend_count: i32,
+    /// Seconds the thread has been sleeping
+    pub sleep_time: i32,
+}
+
+/// Structure for thread_extended_info
+///
+/// This matches the structure returned by `thread_info()` with the
+/// `THREAD_EXTENDED_INFO` flavor (`thread_extended_info_data_t`).
+///
+/// ## Field Descriptions
+///
+/// - `pth_cpu_usage`: CPU usage in thousandths (`TH_USAGE_SCALE`)
+/// - `pth_run_state`: One of the `TH_STATE_*` constants
+/// - `pth_name`: NUL-terminated name set with `pthread_setname_np()`, empty if none
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ThreadExtendedInfo
+{
+    /// User run time in nanoseconds
+    pub pth_user_time: u64,
+    /// System run time in nanoseconds
+    pub pth_system_time: u64,
+    /// Scaled CPU usage percentage
+    pub pth_cpu_usage: i32,
+    /// Scheduling policy in effect
+    pub pth_policy: i32,
+    /// Run state (`TH_STATE_*`)
+    pub pth_run_state: i32,
+    /// Various flags (`TH_FLAGS_*`)
+    pub pth_flags: i32,
+    /// Seconds the thread has been sleeping
+    pub pth_sleep_time: i32,
+    /// Current priority
+    pub pth_curpri: i32,
+    /// Base priority
+    pub pth_priority: i32,
+    /// Maximum priority
+    pub pth_maxpriority: i32,
+    /// Thread name (`MAXTHREADNAMESIZE` bytes)
+    pub pth_name: [u8; 64],
+}
+
+impl Default for ThreadExtendedInfo
+{
+    fn default() -> Self
+    {
+        Self {
+            pth_user_time: 0,
+            pth_system_time: 0,
+            pth_cpu_usage: 0,
+            pth_policy: 0,
+            pth_run_state: 0,
+            pth_flags: 0,
+            pth_sleep_time: 0,
+            pth_curpri: 0,
+            pth_priority: 0,
+            pth_maxpriority: 0,
+            pth_name: [0; 64],
+        }
+    }
+}
+
 // Task and Process Management Functions
 //
 // These functions deal with Mach tasks (processes) and obtaining access to them.
@@ -120,6 +250,52 @@ unsafe extern "C" {
     /// - XNU kernel source: [osfmk/kern/task.c](https://github.com/apple-oss-distributions/xnu)
     pub fn task_for_pid(target_task: mach_port_t, pid: c_int, task: *mut mach_port_t) -> kern_return_t;
 
+    /// Get the process ID of the process a task port refers to
+    ///
+    /// The inverse of `task_for_pid()`. Fails once the task has terminated,
+    /// even while its PID still exists as a zombie.
+    ///
+    /// ## Returns
+    ///
+    /// - `KERN_SUCCESS` (0) on success
+    /// - `KERN_FAILURE` if the port is dead or doesn't name a live task
+    ///
+    /// ## Safety
+    ///
+    /// `pid` must point to writable memory for one `c_int`.
+    pub fn pid_for_task(task: mach_port_t, pid: *mut c_int) -> kern_return_t;
+
+    /// Get information about a task
+    ///
+    /// Fills `task_info_out` with the structure for `flavor`. Ferros uses the
+    /// `TASK_DYLD_INFO` flavor to find the target's loaded image list.
+    ///
+    /// ## Parameters
+    ///
+    /// - `target_task`: Task port (from `task_for_pid()`)
+    /// - `flavor`: Info flavor (`TASK_DYLD_INFO`, etc.)
+    /// - `task_info_out`: Output buffer for the info structure
+    /// - `task_info_out_count`: Input/output - size of buffer / actual size used
+    ///
+    /// ## Returns
+    ///
+    /// - `KERN_SUCCESS` (0) on success
+    /// - `KERN_INVALID_ARGUMENT` if the task port or flavor is invalid
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe because:
+    /// - It requires a valid task port
+    /// - The output buffer must be correctly sized for the flavor
+    ///
+    /// See: [task_info(3) man page](https://developer.apple.com/documentation/kernel/1537934-task_info/)
+    pub fn task_info(
+        target_task: mach_port_t,
+        flavor: c_int,
+        task_info_out: *mut natural_t,
+        task_info_out_count: *mut mach_msg_type_number_t,
+    ) -> kern_return_t;
+
     /// Deallocate a Mach port
     ///
     /// This function releases a Mach port that was previously obtained (e.g., from
@@ -298,6 +474,38 @@ unsafe extern "C" {
     ///
     /// See: [thread_resume(3) man page](https://developer.apple.com/documentation/kernel/1402805-thread_resume/)
     pub fn thread_resume(target_act: thread_act_t) -> kern_return_t;
+
+    /// Query information about a thread
+    ///
+    /// This function fills `thread_info_out` with the structure selected by
+    /// `flavor`. Ferros uses it with `THREAD_IDENTIFIER_INFO` to translate a
+    /// thread port into the kernel's stable thread id.
+    ///
+    /// ## Parameters
+    ///
+    /// - `target_act`: Thread port (from `task_threads()`)
+    /// - `flavor`: Info flavor (`THREAD_IDENTIFIER_INFO`, `THREAD_BASIC_INFO`, etc.)
+    /// - `thread_info_out`: Output buffer for the info structure
+    /// - `thread_info_out_count`: Input/output - size of buffer / actual size used
+    ///
+    /// ## Returns
+    ///
+    /// - `KERN_SUCCESS` (0) on success
+    /// - `KERN_INVALID_ARGUMENT` if the thread port or flavor is invalid
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe because:
+    /// - It requires a valid thread port
+    /// - The output buffer must be correctly sized for the flavor
+    ///
+    /// See: [thread_info(3) man page](https://developer.apple.com/documentation/kernel/1418630-thread_info/)
+    pub fn thread_info(
+        target_act: thread_act_t,
+        flavor: c_int,
+        thread_info_out: *mut natural_t,
+        thread_info_out_count: *mut mach_msg_type_number_t,
+    ) -> kern_return_t;
 }
 
 // Virtual Memory Functions
@@ -565,6 +773,55 @@ unsafe extern "C" {
     ///
     /// See: [posix_spawnattr_setflags(3) man page](https://developer.apple.com/library/archive/documentation/System/Conceptual/ManPages_iPhoneOS/man3/posix_spawnattr_setflags.3.html)
     pub fn posix_spawnattr_setflags(attrp: *mut libc::posix_spawnattr_t, flags: libc::c_short) -> libc::c_int;
+
+    /// Add a change of working directory to spawn file actions
+    ///
+    /// The spawned process starts in `path`. Like the other file actions, it
+    /// runs in the child before the program is executed, so a relative
+    /// program path would be resolved from `path`; pass an absolute one.
+    /// Available since macOS 10.15.
+    ///
+    /// ## Parameters
+    ///
+    /// - `file_actions`: Pointer to an initialized file actions structure
+    /// - `path`: Directory to change to (NUL-terminated)
+    ///
+    /// ## Returns
+    ///
+    /// - `0` on success
+    /// - Non-zero errno value on failure; a missing directory only fails `posix_spawn()` itself
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe because:
+    /// - It modifies the file actions structure
+    /// - Must be called on an initialized file actions structure
+    pub fn posix_spawn_file_actions_addchdir_np(
+        file_actions: *mut libc::posix_spawn_file_actions_t,
+        path: *const libc::c_char,
+    ) -> libc::c_int;
+}
+
+// System Integrity Protection
+//
+// Private but stable libsystem call used by `csrutil status` and other debuggers.
+#[link(name = "c", kind = "dylib")]
+unsafe extern "C" {
+    /// Read the active System Integrity Protection configuration
+    ///
+    /// Writes the bitmask of `CSR_ALLOW_*` flags that are currently allowed, i.e.
+    /// the restrictions that have been turned off. A flag that is clear means
+    /// the corresponding restriction is in force.
+    ///
+    /// ## Returns
+    ///
+    /// - `0` on success
+    /// - Non-zero on failure (`config` is left untouched)
+    ///
+    /// ## Safety
+    ///
+    /// `config` must point to a writable `u32`.
+    pub fn csr_get_active_config(config: *mut u32) -> libc::c_int;
 }
 
 /// Spawn flags for posix_spawn
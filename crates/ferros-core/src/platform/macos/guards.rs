@@ -12,10 +12,10 @@
 //! ## Example
 //!
 //! ```rust,no_run
-//! use ferros_core::Debugger;
 //! use ferros_core::platform::macos::MacOSDebugger;
 //! use ferros_core::platform::macos::guards::ThreadSuspendGuard;
 //! use ferros_core::types::ThreadId;
+//! use ferros_core::{Debugger, TargetInspector};
 //!
 //! # let mut debugger = MacOSDebugger::new()?;
 //! # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
@@ -46,10 +46,10 @@ use crate::platform::macos::ffi;
 /// ## Example
 ///
 /// ```rust,no_run
-/// use ferros_core::Debugger;
 /// use ferros_core::platform::macos::MacOSDebugger;
 /// use ferros_core::platform::macos::guards::ThreadSuspendGuard;
 /// use ferros_core::types::ThreadId;
+/// use ferros_core::{Debugger, TargetInspector};
 ///
 /// # let mut debugger = MacOSDebugger::new()?;
 /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
@@ -146,16 +146,14 @@ impl Drop for ThreadSuspendGuard
 ///
 /// ```rust,no_run
 /// use ferros_core::Debugger;
-/// use ferros_core::breakpoints::BreakpointRequest;
+/// use ferros_core::breakpoints::Breakpoint;
 /// use ferros_core::platform::macos::MacOSDebugger;
 /// use ferros_core::platform::macos::guards::BreakpointRestoreGuard;
 /// use ferros_core::types::Address;
 ///
 /// # let mut debugger = MacOSDebugger::new()?;
 /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-/// let bp_id = debugger.add_breakpoint(BreakpointRequest::Software {
-///     address: Address::from(0x1000),
-/// })?;
+/// let bp_id = debugger.add_breakpoint(Breakpoint::software(Address::from(0x1000)).build())?;
 /// {
 ///     let _guard = BreakpointRestoreGuard::new(&mut debugger, bp_id)?;
 ///     // Breakpoint is disabled, code can execute without hitting it
@@ -182,7 +180,7 @@ impl<'a> BreakpointRestoreGuard<'a>
     ///
     /// ## Errors
     ///
-    /// - `BreakpointIdNotFound`: Breakpoint doesn't exist
+    /// - `BreakpointNotFound`: Breakpoint doesn't exist
     /// - `InvalidArgument`: Failed to disable the breakpoint
     pub fn new(debugger: &'a mut dyn crate::debugger::Debugger, breakpoint_id: BreakpointId) -> Result<Self>
     {
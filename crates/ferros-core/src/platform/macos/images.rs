@@ -0,0 +1,254 @@
+//! # macOS Image Discovery
+//!
+//! Find every image loaded in a task, with its exact load address, from dyld's
+//! own bookkeeping.
+//!
+//! dyld keeps a `dyld_all_image_infos` structure in every process it starts.
+//! The kernel records where it lives, and `task_info(TASK_DYLD_INFO)` hands that
+//! address to a debugger. The structure points at an array of
+//! `dyld_image_info` entries, one per loaded image, each giving the address of
+//! the image's Mach-O header (the start of its `__TEXT` segment) and its path.
+//!
+//! ## Layout (64-bit)
+//!
+//! ```text
+//! dyld_all_image_infos        dyld_image_info (24 bytes each)
+//!   +0  version: u32            +0  imageLoadAddress: *mach_header
+//!   +4  infoArrayCount: u32     +8  imageFilePath: *char
+//!   +8  infoArray: *info        +16 imageFileModDate: uintptr_t
+//!   ...
+//!   +32 dyldImageLoadAddress    (version >= 2)
+//! ```
+//!
+//! dyld sets `infoArray` to NULL while it updates the list; a NULL array or an
+//! empty list reads as no images, and callers fall back to scanning memory
+//! regions.
+//!
+//! ## References
+//!
+//! - [dyld_images.h](https://github.com/apple-oss-distributions/dyld/blob/main/include/mach-o/dyld_images.h)
+//! - [task_info(3) man page](https://developer.apple.com/documentation/kernel/1537934-task_info/)
+
+use std::path::PathBuf;
+
+use libc::mach_port_t;
+#[cfg(target_os = "macos")]
+use mach2::kern_return::KERN_SUCCESS;
+
+use crate::error::{DebuggerError, Result};
+use crate::platform::macos::{constants, ffi};
+use crate::symbols::ImageDescriptor;
+use crate::types::Address;
+
+/// Size of one 64-bit `dyld_image_info` entry
+const IMAGE_INFO_SIZE: usize = 24;
+
+/// Bytes of `dyld_all_image_infos` read: up to and including `dyldImageLoadAddress`
+const ALL_IMAGE_INFOS_HEADER: usize = 40;
+
+/// Longest image path read from the target
+const MAX_PATH_LEN: usize = 1024;
+
+/// Path bytes read at a time, aligned so no read crosses a page boundary
+const PATH_CHUNK: usize = 256;
+
+/// Path reported for dyld itself, which isn't in the image array
+const DYLD_PATH: &str = "/usr/lib/dyld";
+
+/// Address of the task's `dyld_all_image_infos`, from `task_info(TASK_DYLD_INFO)`
+///
+/// ## Errors
+///
+/// - `InvalidArgument`: `task_info()` failed, or the task has no image list yet
+///   (it was launched suspended and dyld hasn't run)
+pub(crate) fn all_image_infos_address(task: mach_port_t) -> Result<Address>
+{
+    let mut info = ffi::TaskDyldInfo::default();
+    let mut count = constants::TASK_DYLD_INFO_COUNT;
+    let result = unsafe { ffi::task_info(task, constants::TASK_DYLD_INFO, (&raw mut info).cast(), &mut count) };
+    if result != KERN_SUCCESS {
+        return Err(DebuggerError::InvalidArgument(format!(
+            "task_info(TASK_DYLD_INFO) failed: {result}"
+        )));
+    }
+    let address = info.all_image_info_addr;
+    if address == 0 {
+        return Err(DebuggerError::InvalidArgument(
+            "dyld has not published an image list yet".to_string(),
+        ));
+    }
+    Ok(Address::from(address))
+}
+
+/// Every image dyld lists at `infos`, plus dyld itself
+///
+/// `read` reads `len` bytes of target memory at an address. Images whose path
+/// can't be read are skipped; an array dyld is in the middle of updating gives
+/// an empty list.
+pub(crate) fn read_image_list(
+    infos: Address,
+    read: &dyn Fn(Address, usize) -> Result<Vec<u8>>,
+) -> Result<Vec<ImageDescriptor>>
+{
+    let header = read(infos, ALL_IMAGE_INFOS_HEADER)?;
+    let version = u32_at(&header, 0);
+    let count = u32_at(&header, 4) as usize;
+    let array = u64_at(&header, 8);
+    if array == 0 || count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let entries = read(Address::from(array), count * IMAGE_INFO_SIZE)?;
+    let mut images: Vec<ImageDescriptor> = entries
+        .chunks_exact(IMAGE_INFO_SIZE)
+        .filter_map(|entry| {
+            let load_address = u64_at(entry, 0);
+            let path = read_c_string(Address::from(u64_at(entry, 8)), read)?;
+            (load_address != 0).then(|| ImageDescriptor {
+                path: PathBuf::from(path),
+                load_address,
+            })
+        })
+        .collect();
+
+    let dyld = u64_at(&header, 32);
+    if version >= 2 && dyld != 0 {
+        images.push(ImageDescriptor {
+            path: PathBuf::from(DYLD_PATH),
+            load_address: dyld,
+        });
+    }
+    Ok(images)
+}
+
+/// A NUL-terminated string at `address`, read in aligned chunks
+fn read_c_string(address: Address, read: &dyn Fn(Address, usize) -> Result<Vec<u8>>) -> Option<String>
+{
+    if address.value() == 0 {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    while bytes.len() < MAX_PATH_LEN {
+        let at = address.value() + bytes.len() as u64;
+        // A path ending just before an unmapped page must not fail the read
+        let to_chunk_end = PATH_CHUNK - (at as usize % PATH_CHUNK);
+        let chunk = read(Address::from(at), to_chunk_end.min(MAX_PATH_LEN - bytes.len())).ok()?;
+        if let Some(end) = chunk.iter().position(|&b| b == 0) {
+            bytes.extend_from_slice(&chunk[..end]);
+            return String::from_utf8(bytes).ok().filter(|path| !path.is_empty());
+        }
+        if chunk.is_empty() {
+            return None;
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    None
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32
+{
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> u64
+{
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn unmapped(address: Address, length: usize) -> DebuggerError
+    {
+        DebuggerError::MemoryReadFailed {
+            address,
+            length,
+            kern_return: None,
+        }
+    }
+
+    /// Target memory made of byte runs keyed by their start address
+    fn reader(memory: BTreeMap<u64, Vec<u8>>) -> impl Fn(Address, usize) -> Result<Vec<u8>>
+    {
+        move |address, len| {
+            let (start, bytes) = memory.range(..=address.value()).next_back().ok_or(unmapped(address, len))?;
+            let offset = (address.value() - start) as usize;
+            if offset >= bytes.len() {
+                return Err(unmapped(address, len));
+            }
+            Ok(bytes[offset..(offset + len).min(bytes.len())].to_vec())
+        }
+    }
+
+    fn all_image_infos(version: u32, count: u32, array: u64, dyld: u64) -> Vec<u8>
+    {
+        let mut bytes = vec![0; ALL_IMAGE_INFOS_HEADER];
+        bytes[0..4].copy_from_slice(&version.to_le_bytes());
+        bytes[4..8].copy_from_slice(&count.to_le_bytes());
+        bytes[8..16].copy_from_slice(&array.to_le_bytes());
+        bytes[32..40].copy_from_slice(&dyld.to_le_bytes());
+        bytes
+    }
+
+    fn image_info(load_address: u64, path: u64) -> Vec<u8>
+    {
+        let mut bytes = vec![0; IMAGE_INFO_SIZE];
+        bytes[0..8].copy_from_slice(&load_address.to_le_bytes());
+        bytes[8..16].copy_from_slice(&path.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn reads_every_listed_image_and_dyld()
+    {
+        let mut array = image_info(0x1_0000_0000, 0x5000);
+        array.extend(image_info(0x1_8000_0000, 0x5100));
+        let memory = BTreeMap::from([
+            (0x1000, all_image_infos(17, 2, 0x2000, 0x1_9000_0000)),
+            (0x2000, array),
+            (0x5000, b"/tmp/target\0".to_vec()),
+            (0x5100, b"/usr/lib/libSystem.B.dylib\0".to_vec()),
+        ]);
+
+        let images = read_image_list(Address::from(0x1000), &reader(memory)).unwrap();
+        let found: Vec<_> = images
+            .iter()
+            .map(|image| (image.path.to_str().unwrap(), image.load_address))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("/tmp/target", 0x1_0000_0000),
+                ("/usr/lib/libSystem.B.dylib", 0x1_8000_0000),
+                (DYLD_PATH, 0x1_9000_0000),
+            ]
+        );
+    }
+
+    #[test]
+    fn an_array_being_updated_reads_as_no_images()
+    {
+        let memory = BTreeMap::from([(0x1000, all_image_infos(17, 3, 0, 0x1_9000_0000))]);
+        assert!(read_image_list(Address::from(0x1000), &reader(memory)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn images_with_unreadable_paths_are_skipped()
+    {
+        let mut array = image_info(0x1_0000_0000, 0x9000);
+        array.extend(image_info(0x1_8000_0000, 0x5000));
+        let memory = BTreeMap::from([
+            (0x1000, all_image_infos(1, 2, 0x2000, 0)),
+            (0x2000, array),
+            (0x5000, b"/usr/lib/libc++.1.dylib\0".to_vec()),
+        ]);
+
+        let images = read_image_list(Address::from(0x1000), &reader(memory)).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].path, PathBuf::from("/usr/lib/libc++.1.dylib"));
+    }
+}
@@ -10,21 +10,26 @@
 //!
 //! - **posix_spawn()**: Launch a new process
 //! - **posix_spawnattr_setflags()**: Set spawn attributes (START_SUSPENDED)
-//! - **posix_spawn_file_actions_***(): Redirect stdout/stderr if needed
+//! - **posix_spawn_file_actions_***(): Redirect stdin/stdout/stderr if needed
+//! - **posix_spawn_file_actions_addchdir_np()**: Start in the requested working directory
+//!
+//! The environment is built by [`LaunchOptions::environment`] and passed as
+//! `posix_spawn()`'s `envp`.
 //!
 //! ## References
 //!
 //! - [posix_spawn(3) man page](https://developer.apple.com/library/archive/documentation/System/Conceptual/ManPages_iPhoneOS/man3/posix_spawn.3.html)
 
-use std::collections::HashMap;
 use std::ffi::CString;
 use std::os::fd::RawFd;
+use std::os::unix::ffi::OsStrExt;
 use std::ptr;
 
 use libc::c_int;
 
 use crate::error::{DebuggerError, Result};
 use crate::platform::macos::ffi;
+use crate::types::LaunchOptions;
 
 /// Trait for launch operations that require access to debugger internals.
 pub(crate) trait LaunchOperations
@@ -37,6 +42,9 @@ pub(crate) trait LaunchOperations
 
     /// Set the stderr pipe file descriptor.
     fn set_stderr_pipe(&mut self, fd: RawFd);
+
+    /// Set the write end of the stdin pipe.
+    fn set_stdin_pipe(&mut self, fd: RawFd);
 }
 
 /// Process launch functions for macOS debugger.
@@ -44,56 +52,27 @@ pub(crate) struct LaunchManager;
 
 impl LaunchManager
 {
-    /// Build an environment with debug-friendly flags enabled.
+    /// Build the target's environment as `KEY=VALUE` strings for `posix_spawn()`'s `envp`.
     ///
-    /// This function creates an environment that includes:
-    /// - All current environment variables
-    /// - Debug flags for Rust programs (RUST_BACKTRACE, etc.)
-    /// - Other helpful debugging environment variables
+    /// ## Errors
     ///
-    /// Returns a vector of CStrings suitable for passing to posix_spawn.
-    fn build_debug_environment() -> Result<Vec<CString>>
+    /// - `InvalidArgument`: A variable given in `options` contains a NUL byte
+    fn build_environment(options: &LaunchOptions) -> Result<Vec<CString>>
     {
-        use std::env;
-
         use tracing::debug;
 
-        debug!("Building debug environment");
-
-        // Start with current environment
-        let mut env_vars: HashMap<String, String> = env::vars().collect();
-
-        // Set Rust debug flags (these are helpful for debugging Rust programs)
-        // RUST_BACKTRACE=1 enables backtraces on panic
-        // Only set if not already set by user
-        if env::var("RUST_BACKTRACE").is_err() {
-            env_vars.insert("RUST_BACKTRACE".to_string(), "1".to_string());
-        }
-
-        // For C/C++ programs, these can be helpful too
-        // MallocStackLogging helps with memory debugging on macOS
-        if env::var("MallocStackLogging").is_err() {
-            env_vars.insert("MallocStackLogging".to_string(), "1".to_string());
-        }
-
-        // Convert to CString array (format: "KEY=VALUE\0")
-        let mut env_cstrings = Vec::new();
-        for (key, value) in env_vars {
-            let env_entry = format!("{key}={value}");
-            match CString::new(env_entry) {
-                Ok(cstr) => env_cstrings.push(cstr),
-                Err(e) => {
-                    debug!("Skipping invalid environment variable: {e}");
-                    // Skip invalid entries rather than failing
-                }
-            }
-        }
-
-        debug!("Built environment with {} variables", env_cstrings.len());
-        Ok(env_cstrings)
+        let environment = options.environment(std::env::vars());
+        debug!("Built environment with {} variables", environment.len());
+        environment
+            .into_iter()
+            .map(|(key, value)| {
+                CString::new(format!("{key}={value}"))
+                    .map_err(|_| DebuggerError::InvalidArgument(format!("Environment variable {key} contains a NUL byte")))
+            })
+            .collect()
     }
 
-    /// Create a pipe pair for process output redirection.
+    /// Create a pipe pair for redirecting one of the process's standard streams.
     ///
     /// Creates a pipe and sets the `FD_CLOEXEC` flag on both file descriptors
     /// so they're closed when the process execs.
@@ -141,6 +120,7 @@ impl LaunchManager
         file_actions: &mut libc::posix_spawn_file_actions_t,
         stdout_pipe_fds: &mut Option<(RawFd, RawFd)>,
         stderr_pipe_fds: &mut Option<(RawFd, RawFd)>,
+        stdin_pipe_fds: &mut Option<(RawFd, RawFd)>,
     ) -> Result<()>
     {
         if result == 0 {
@@ -154,6 +134,7 @@ impl LaunchManager
         }
         Self::close_pipe_pair(stdout_pipe_fds);
         Self::close_pipe_pair(stderr_pipe_fds);
+        Self::close_pipe_pair(stdin_pipe_fds);
         Err(DebuggerError::AttachFailed(format!("Failed to {desc}: {err}")))
     }
 
@@ -166,7 +147,7 @@ impl LaunchManager
     /// ## Parameters
     ///
     /// - `program`: Path to the executable
-    /// - `args`: Command-line arguments (first argument should be the program name)
+    /// - `options`: Arguments (the first being the program name), environment and working directory
     /// - `ops`: Launch operations trait object for accessing debugger state
     ///
     /// ## Returns
@@ -177,15 +158,19 @@ impl LaunchManager
     ///
     /// Returns errors if:
     /// - Program path is invalid
-    /// - Arguments contain null bytes
-    /// - `posix_spawn()` fails
-    /// - Pipe creation fails (if output capture is enabled)
-    pub(crate) fn launch<Ops: LaunchOperations>(ops: &mut Ops, program: &str, args: &[&str]) -> Result<libc::pid_t>
+    /// - Arguments, environment variables or the working directory contain null bytes
+    /// - `posix_spawn()` fails, including for a working directory that can't be entered
+    /// - Pipe creation fails (if output capture is enabled, which also pipes stdin)
+    pub(crate) fn launch<Ops: LaunchOperations>(ops: &mut Ops, program: &str, options: &LaunchOptions)
+    -> Result<libc::pid_t>
     {
         use tracing::{debug, info, trace};
 
+        let args = &options.args;
         info!("Launching process: {} with args: {:?}", program, args);
-        info!("Debug environment enabled: RUST_BACKTRACE=1, MallocStackLogging=1");
+        if let Some(cwd) = &options.cwd {
+            info!("Working directory: {}", cwd.display());
+        }
         debug!("Validating launch parameters");
 
         // Validate inputs
@@ -203,9 +188,19 @@ impl LaunchManager
         // Convert arguments to CStrings
         let mut arg_cstrs = Vec::new();
         for arg in args {
-            arg_cstrs
-                .push(CString::new(*arg).map_err(|e| DebuggerError::InvalidArgument(format!("Invalid argument: {}", e)))?);
+            arg_cstrs.push(
+                CString::new(arg.as_str())
+                    .map_err(|e| DebuggerError::InvalidArgument(format!("Invalid argument: {}", e)))?,
+            );
         }
+        let cwd_cstr = options
+            .cwd
+            .as_ref()
+            .map(|cwd| CString::new(cwd.as_os_str().as_bytes()))
+            .transpose()
+            .map_err(|e| DebuggerError::InvalidArgument(format!("Invalid working directory: {}", e)))?;
+        trace!("Building environment");
+        let env_cstrings = Self::build_environment(options)?;
 
         // Create argv array (null-terminated)
         let mut argv: Vec<*const libc::c_char> = arg_cstrs.iter().map(|s| s.as_ptr()).collect();
@@ -213,10 +208,12 @@ impl LaunchManager
 
         let mut stdout_pipe_fds: Option<(RawFd, RawFd)> = None;
         let mut stderr_pipe_fds: Option<(RawFd, RawFd)> = None;
+        let mut stdin_pipe_fds: Option<(RawFd, RawFd)> = None;
 
         if ops.capture_output() {
             stdout_pipe_fds = Some(Self::create_pipe_pair("stdout")?);
             stderr_pipe_fds = Some(Self::create_pipe_pair("stderr")?);
+            stdin_pipe_fds = Some(Self::create_pipe_pair("stdin")?);
         }
 
         unsafe {
@@ -227,6 +224,7 @@ impl LaunchManager
             if result != 0 {
                 Self::close_pipe_pair(&mut stdout_pipe_fds);
                 Self::close_pipe_pair(&mut stderr_pipe_fds);
+                Self::close_pipe_pair(&mut stdin_pipe_fds);
                 return Err(DebuggerError::AttachFailed(format!(
                     "Failed to initialize spawn attributes: {}",
                     std::io::Error::from_raw_os_error(result)
@@ -240,6 +238,7 @@ impl LaunchManager
                 let _ = ffi::posix_spawnattr_destroy(&mut attr);
                 Self::close_pipe_pair(&mut stdout_pipe_fds);
                 Self::close_pipe_pair(&mut stderr_pipe_fds);
+                Self::close_pipe_pair(&mut stdin_pipe_fds);
                 return Err(DebuggerError::AttachFailed(format!(
                     "Failed to set spawn flags: {}",
                     std::io::Error::from_raw_os_error(flags_result)
@@ -249,13 +248,14 @@ impl LaunchManager
             let mut file_actions: libc::posix_spawn_file_actions_t = std::mem::zeroed();
             let mut file_actions_initialized = false;
 
-            if ops.capture_output() {
+            if ops.capture_output() || cwd_cstr.is_some() {
                 file_actions_initialized = true;
                 let init_result = libc::posix_spawn_file_actions_init(&mut file_actions);
                 if init_result != 0 {
                     let _ = ffi::posix_spawnattr_destroy(&mut attr);
                     Self::close_pipe_pair(&mut stdout_pipe_fds);
                     Self::close_pipe_pair(&mut stderr_pipe_fds);
+                    Self::close_pipe_pair(&mut stdin_pipe_fds);
                     return Err(DebuggerError::AttachFailed(format!(
                         "Failed to initialize file actions: {}",
                         std::io::Error::from_raw_os_error(init_result)
@@ -271,6 +271,7 @@ impl LaunchManager
                         &mut file_actions,
                         &mut stdout_pipe_fds,
                         &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
                     )?;
 
                     let result = libc::posix_spawn_file_actions_adddup2(&mut file_actions, write_fd, libc::STDOUT_FILENO);
@@ -281,6 +282,7 @@ impl LaunchManager
                         &mut file_actions,
                         &mut stdout_pipe_fds,
                         &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
                     )?;
 
                     let result = libc::posix_spawn_file_actions_addclose(&mut file_actions, write_fd);
@@ -291,6 +293,7 @@ impl LaunchManager
                         &mut file_actions,
                         &mut stdout_pipe_fds,
                         &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
                     )?;
                 }
 
@@ -303,6 +306,7 @@ impl LaunchManager
                         &mut file_actions,
                         &mut stdout_pipe_fds,
                         &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
                     )?;
 
                     let result = libc::posix_spawn_file_actions_adddup2(&mut file_actions, write_fd, libc::STDERR_FILENO);
@@ -313,6 +317,7 @@ impl LaunchManager
                         &mut file_actions,
                         &mut stdout_pipe_fds,
                         &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
                     )?;
 
                     let result = libc::posix_spawn_file_actions_addclose(&mut file_actions, write_fd);
@@ -323,19 +328,66 @@ impl LaunchManager
                         &mut file_actions,
                         &mut stdout_pipe_fds,
                         &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
+                    )?;
+                }
+
+                // The child reads the pipe ferros writes typed input to
+                if let Some((read_fd, write_fd)) = stdin_pipe_fds {
+                    let result = libc::posix_spawn_file_actions_addclose(&mut file_actions, write_fd);
+                    Self::ensure_file_action_success(
+                        "close stdin write end",
+                        result,
+                        &mut attr,
+                        &mut file_actions,
+                        &mut stdout_pipe_fds,
+                        &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
+                    )?;
+
+                    let result = libc::posix_spawn_file_actions_adddup2(&mut file_actions, read_fd, libc::STDIN_FILENO);
+                    Self::ensure_file_action_success(
+                        "redirect stdin",
+                        result,
+                        &mut attr,
+                        &mut file_actions,
+                        &mut stdout_pipe_fds,
+                        &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
+                    )?;
+
+                    let result = libc::posix_spawn_file_actions_addclose(&mut file_actions, read_fd);
+                    Self::ensure_file_action_success(
+                        "close stdin read end",
+                        result,
+                        &mut attr,
+                        &mut file_actions,
+                        &mut stdout_pipe_fds,
+                        &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
+                    )?;
+                }
+
+                if let Some(cwd) = &cwd_cstr {
+                    let result = ffi::posix_spawn_file_actions_addchdir_np(&mut file_actions, cwd.as_ptr());
+                    Self::ensure_file_action_success(
+                        "set the working directory",
+                        result,
+                        &mut attr,
+                        &mut file_actions,
+                        &mut stdout_pipe_fds,
+                        &mut stderr_pipe_fds,
+                        &mut stdin_pipe_fds,
                     )?;
                 }
             }
 
-            trace!("Building debug environment");
-            // Build environment with debug flags
-            let env_cstrings = Self::build_debug_environment()?;
             let mut env_ptrs: Vec<*const libc::c_char> = env_cstrings.iter().map(|s| s.as_ptr()).collect();
             // Add null terminator for the environment array
             env_ptrs.push(ptr::null());
 
             trace!("Calling posix_spawn");
-            // Spawn the process with debug environment
+            // Spawn the process with the built environment
             let mut pid: libc::pid_t = 0;
             let spawn_result = ffi::posix_spawn(
                 &mut pid,
@@ -347,7 +399,7 @@ impl LaunchManager
                 },
                 &attr, // Use attributes with START_SUSPENDED flag
                 argv.as_ptr(),
-                env_ptrs.as_ptr(), // Use debug environment
+                env_ptrs.as_ptr(),
             );
 
             if file_actions_initialized {
@@ -360,6 +412,7 @@ impl LaunchManager
             if spawn_result != 0 {
                 Self::close_pipe_pair(&mut stdout_pipe_fds);
                 Self::close_pipe_pair(&mut stderr_pipe_fds);
+                Self::close_pipe_pair(&mut stdin_pipe_fds);
                 return Err(DebuggerError::AttachFailed(format!(
                     "Failed to spawn process '{}': {}",
                     program,
@@ -377,6 +430,11 @@ impl LaunchManager
                 ops.set_stderr_pipe(read_fd);
             }
 
+            if let Some((read_fd, write_fd)) = stdin_pipe_fds.take() {
+                let _ = libc::close(read_fd);
+                ops.set_stdin_pipe(write_fd);
+            }
+
             info!("Successfully spawned process with PID: {}", pid);
             Ok(pid)
         }
@@ -22,6 +22,7 @@
 use std::cmp::min;
 use std::collections::HashMap;
 use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
 use libc::{c_int, mach_port_t, vm_address_t, vm_map_t, vm_offset_t};
@@ -45,7 +46,7 @@ use once_cell::sync::Lazy;
 
 use crate::error::{DebuggerError, Result};
 use crate::platform::macos::{constants, ffi};
-use crate::types::{Address, MemoryRegion, MemoryRegionId};
+use crate::types::{Address, MemoryCacheStats, MemoryRegion, MemoryRegionId};
 
 // Use constants from the centralized constants module
 
@@ -60,11 +61,34 @@ fn page_align_down(value: u64, page_size: usize) -> u64
     value & mask
 }
 
-/// Simple read-through memory cache that stores pages fetched from the target.
+/// Pages cached since one stop
+#[derive(Default)]
+struct CachedPages
+{
+    /// Stop generation every page in `pages` was read in
+    generation: u64,
+    pages: HashMap<u64, Arc<Vec<u8>>>,
+}
+
+/// Read-through memory cache that stores pages fetched from the target.
+///
+/// Pages belong to the stop they were read at. The exception loop advances
+/// the cache's generation whenever the target stops (see
+/// [`generation_counter`](Self::generation_counter)), and pages from an
+/// earlier generation are misses: the target ran in between and may have
+/// written or unmapped them. [`invalidate_all`](Self::invalidate_all) drops
+/// every page when the debugger resumes the target.
+///
+/// Region boundaries are page-aligned, so a page never straddles two
+/// regions and a read spanning a boundary is split into per-region page
+/// reads. If one fails, the error names the sub-range that couldn't be read.
 pub struct MemoryCache
 {
     page_size: usize,
-    pages: RwLock<HashMap<u64, Arc<Vec<u8>>>>,
+    generation: Arc<AtomicU64>,
+    pages: RwLock<CachedPages>,
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 impl Default for MemoryCache
@@ -89,14 +113,53 @@ impl MemoryCache
         let size = page_size.max(1024).next_power_of_two();
         Self {
             page_size: size,
-            pages: RwLock::new(HashMap::new()),
+            generation: Arc::new(AtomicU64::new(0)),
+            pages: RwLock::new(CachedPages::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
-    /// Clears all cached pages.
-    pub fn clear(&self)
+    /// Counter the exception loop bumps at every stop, retiring the pages cached before it.
+    pub fn generation_counter(&self) -> Arc<AtomicU64>
+    {
+        Arc::clone(&self.generation)
+    }
+
+    /// Start a new generation, so pages cached so far are re-read on their next use.
+    pub fn advance_generation(&self)
+    {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Drops every cached page, for when the target runs again.
+    pub fn invalidate_all(&self)
+    {
+        self.advance_generation();
+        self.pages.write().unwrap().pages.clear();
+    }
+
+    /// Resets the hit and miss counters.
+    pub fn reset_stats(&self)
     {
-        self.pages.write().unwrap().clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Hits, misses and bytes cached in the current generation.
+    pub fn stats(&self) -> MemoryCacheStats
+    {
+        let cached = self.pages.read().unwrap();
+        let bytes = if cached.generation == self.generation.load(Ordering::Relaxed) {
+            cached.pages.values().map(|page| page.len() as u64).sum()
+        } else {
+            0
+        };
+        MemoryCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            bytes,
+        }
     }
 
     /// Invalidates any cached pages overlapping the provided range.
@@ -107,33 +170,53 @@ impl MemoryCache
         }
         let start = addr.value();
         let end = start.saturating_add(len as u64);
-        let mut pages = self.pages.write().unwrap();
+        let mut cached = self.pages.write().unwrap();
         let page_size = self.page_size as u64;
         let mut base = page_align_down(start, self.page_size);
         while base < end {
-            pages.remove(&base);
+            cached.pages.remove(&base);
             base = base.saturating_add(page_size);
         }
     }
 
     fn fetch_page(&self, task: mach_port_t, base: u64) -> Result<Arc<Vec<u8>>>
     {
-        if let Some(existing) = self.pages.read().unwrap().get(&base) {
-            return Ok(existing.clone());
+        let generation = self.generation.load(Ordering::Relaxed);
+        {
+            let cached = self.pages.read().unwrap();
+            if cached.generation == generation
+                && let Some(existing) = cached.pages.get(&base)
+            {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(existing.clone());
+            }
         }
 
-        let mut pages = self.pages.write().unwrap();
-        if let Some(existing) = pages.get(&base) {
+        let mut cached = self.pages.write().unwrap();
+        if cached.generation != generation {
+            // The target ran since these were read
+            cached.pages.clear();
+            cached.generation = generation;
+        }
+        if let Some(existing) = cached.pages.get(&base) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
             return Ok(existing.clone());
         }
 
+        self.misses.fetch_add(1, Ordering::Relaxed);
         let data = read_memory(task, Address::from(base), self.page_size)?;
         let arc = Arc::new(data);
-        pages.insert(base, arc.clone());
+        cached.pages.insert(base, arc.clone());
         Ok(arc)
     }
 
     /// Reads a range, using cached pages when available.
+    ///
+    /// ## Errors
+    ///
+    /// Fails on the first page that can't be read, naming the part of the
+    /// range it covers; reads on the other side of a region boundary may
+    /// still have succeeded.
     pub fn read(&self, task: mach_port_t, addr: Address, len: usize) -> Result<Vec<u8>>
     {
         if len == 0 {
@@ -149,11 +232,14 @@ impl MemoryCache
             let page_offset = (absolute - page_base) as usize;
             let remaining = len - copied;
             let chunk = remaining.min(self.page_size - page_offset);
-            let page = self.fetch_page(task, page_base)?;
+            let page = self
+                .fetch_page(task, page_base)
+                .map_err(|err| unreadable_subrange(err, absolute, chunk))?;
 
             if page_offset + chunk > page.len() {
                 // Page shorter than expected; fall back to direct read.
-                let bytes = read_memory(task, Address::from(absolute), chunk)?;
+                let bytes = read_memory(task, Address::from(absolute), chunk)
+                    .map_err(|err| unreadable_subrange(err, absolute, chunk))?;
                 output[copied..copied + bytes.len()].copy_from_slice(&bytes);
                 copied += bytes.len();
                 continue;
@@ -177,10 +263,11 @@ impl MemoryCache
     {
         let bytes = self.read(task, addr, 8)?;
         if bytes.len() < 8 {
-            return Err(DebuggerError::Io(Error::new(
-                ErrorKind::UnexpectedEof,
-                "failed to read full 8 bytes",
-            )));
+            return Err(DebuggerError::MemoryReadFailed {
+                address: addr,
+                length: 8,
+                kern_return: None,
+            });
         }
         Ok(u64::from_le_bytes([
             bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
@@ -188,6 +275,26 @@ impl MemoryCache
     }
 }
 
+/// Name the sub-range `start..start + len` of a cached read in the error from reading it
+fn unreadable_subrange(err: DebuggerError, start: u64, len: usize) -> DebuggerError
+{
+    match err {
+        DebuggerError::Io(io) => DebuggerError::Io(Error::new(
+            io.kind(),
+            format!(
+                "cannot read 0x{start:016x}..0x{:016x}: {io}",
+                start.saturating_add(len as u64)
+            ),
+        )),
+        DebuggerError::MemoryReadFailed { kern_return, .. } => DebuggerError::MemoryReadFailed {
+            address: Address::from(start),
+            length: len,
+            kern_return,
+        },
+        other => other,
+    }
+}
+
 /// Read memory from a Mach task
 ///
 /// Uses `mach_vm_read_overwrite()` to read memory from the target process in bounded chunks.
@@ -211,6 +318,9 @@ impl MemoryCache
 /// - Insufficient permissions to read the memory
 /// - The memory region is not readable
 ///
+/// Returns `MemoryReadFailed`, with the kernel's return code, if
+/// `mach_vm_read_overwrite()` refuses a chunk of the range.
+///
 /// ## Safety
 ///
 /// This function is marked `unsafe` internally because it calls the Mach API `vm_read()`.
@@ -436,10 +546,11 @@ pub fn read_memory_into(task: mach_port_t, addr: Address, dst: &mut [u8]) -> Res
         };
 
         if result != KERN_SUCCESS {
-            return Err(DebuggerError::Io(Error::other(format!(
-                "mach_vm_read_overwrite failed: {}",
-                result
-            ))));
+            return Err(DebuggerError::MemoryReadFailed {
+                address: Address::from(cursor),
+                length: chunk_len,
+                kern_return: Some(result),
+            });
         }
 
         if actual == 0 {
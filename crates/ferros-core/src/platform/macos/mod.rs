@@ -62,11 +62,16 @@
 //! Entitlements are optional and mainly useful for development workflows where you
 //! frequently attach to processes.
 //!
+//! When a Mach call is refused, the `PermissionDenied` error carries advice chosen
+//! for the current user, code signature and SIP status (see [`permissions`]);
+//! `ferros doctor` prints the same environment summary.
+//!
 //! ## Key Mach APIs Used
 //!
 //! - `task_for_pid()`: Get a Mach port to a process (declared ourselves - not in mach2)
 //! - `task_threads()`: Enumerate threads in a task (from `mach2` crate)
 //! - `thread_get_state()`: Read thread registers (declared ourselves - not in mach2)
+//! - `task_info(TASK_DYLD_INFO)`: Find dyld's list of loaded images (see [`images`])
 //! - `thread_set_state()`: Write thread registers (future)
 //! - `posix_spawn()`: Launch processes with `POSIX_SPAWN_START_SUSPENDED` flag
 //!
@@ -93,10 +98,13 @@ pub mod breakpoints;
 pub mod constants;
 pub mod error;
 pub mod exception;
+pub mod exit;
 pub mod ffi;
 pub mod guards;
+pub mod images;
 pub mod launch;
 pub mod memory;
+pub mod permissions;
 pub mod registers;
 pub mod task;
 pub mod threads;
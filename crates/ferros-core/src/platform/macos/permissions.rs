@@ -0,0 +1,52 @@
+//! # macOS Permission Checks
+//!
+//! System Integrity Protection status and the conversion of refused Mach calls
+//! into [`DebuggerError::PermissionDenied`] with [`PermissionAdvice`].
+//!
+//! The Mach APIs report a missing privilege as `KERN_PROTECTION_FAILURE` (and
+//! `task_for_pid()` sometimes as `KERN_FAILURE`). Call sites pass such errors
+//! through [`advise`] with the operation they attempted, so every permission
+//! failure reaches the user with the same kind of remediation message.
+//!
+//! See: [`csrutil(8)`](https://support.apple.com/guide/security/system-integrity-protection-secb7ea06b49/web)
+
+use super::constants::CSR_ALLOW_TASK_FOR_PID;
+use super::error::MachError;
+use super::ffi;
+use crate::error::DebuggerError;
+use crate::permissions::{PermissionAdvice, PrivilegedOperation, current_environment};
+
+/// Whether SIP's debugging restriction is in force.
+///
+/// Returns `None` if the configuration can't be read.
+pub fn sip_enabled() -> Option<bool>
+{
+    let mut config: u32 = 0;
+    // SAFETY: config is a valid, writable u32
+    let result = unsafe { ffi::csr_get_active_config(&mut config) };
+    (result == 0).then_some(config & CSR_ALLOW_TASK_FOR_PID == 0)
+}
+
+/// `PermissionDenied` for `operation`, with advice for the current environment.
+pub(crate) fn permission_denied(operation: PrivilegedOperation, message: impl Into<String>) -> DebuggerError
+{
+    DebuggerError::permission_denied(
+        message,
+        Some(PermissionAdvice::for_operation(operation, current_environment())),
+    )
+}
+
+/// Turn a permission failure from `operation` into `PermissionDenied` with advice.
+///
+/// `KERN_PROTECTION_FAILURE` and advice-less `PermissionDenied` errors are
+/// converted; anything else is returned unchanged.
+pub(crate) fn advise(err: DebuggerError, operation: PrivilegedOperation) -> DebuggerError
+{
+    match err {
+        DebuggerError::MachError(MachError::ProtectionFailure) => {
+            permission_denied(operation, format!("{operation} failed with KERN_PROTECTION_FAILURE"))
+        }
+        DebuggerError::PermissionDenied { message, advice: None } => permission_denied(operation, message),
+        err => err,
+    }
+}
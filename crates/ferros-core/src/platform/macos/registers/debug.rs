@@ -57,7 +57,42 @@ struct ArmDebugState64
     mdscr_el1: u64,
 }
 
-/// Set a hardware breakpoint at the given address.
+/// Number of hardware breakpoint slots the CPU implements.
+///
+/// - **x86-64**: Always 4 (DR0-DR3)
+/// - **ARM64**: Read from the `hw.optional.breakpoint` sysctl, which reports
+///   how many DBGBVR/DBGBCR pairs the core implements (6 on Apple Silicon).
+///   Falls back to [`ARM64_HARDWARE_BREAKPOINT_SLOTS`](constants::ARM64_HARDWARE_BREAKPOINT_SLOTS)
+///   if the sysctl can't be read.
+pub fn hardware_breakpoint_slots() -> usize
+{
+    #[cfg(target_arch = "x86_64")]
+    {
+        constants::X86_64_HARDWARE_BREAKPOINT_SLOTS
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        let mut slots: u32 = 0;
+        let mut size = std::mem::size_of::<u32>();
+        let rc = unsafe {
+            libc::sysctlbyname(
+                c"hw.optional.breakpoint".as_ptr(),
+                (&raw mut slots).cast(),
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        if rc == 0 && slots > 0 {
+            // The debug state only has room for 16 pairs
+            (slots as usize).min(16)
+        } else {
+            constants::ARM64_HARDWARE_BREAKPOINT_SLOTS
+        }
+    }
+}
+
+/// Set a hardware breakpoint at the given address in debug register `slot`.
 ///
 /// Hardware breakpoints use CPU debug registers (DR0-DR7 on x86-64, DBGBVR/DBGBCR on ARM64)
 /// to break on instruction execution without modifying code. This is useful for:
@@ -66,10 +101,14 @@ struct ArmDebugState64
 /// - Self-modifying code
 /// - Performance-critical paths
 ///
+/// The caller picks the slot, so the same breakpoint occupies the same slot
+/// on every thread; whatever the slot held before is overwritten. See
+/// [`BreakpointStore::free_hardware_slot`](crate::breakpoints::BreakpointStore::free_hardware_slot).
+///
 /// ## Limitations
 ///
 /// - **x86-64**: Only 4 hardware breakpoints available (DR0-DR3)
-/// - **ARM64**: Up to 16 hardware breakpoints available (DBGBVR0-15)
+/// - **ARM64**: As many as [`hardware_breakpoint_slots()`] reports
 ///
 /// ## Mach APIs Used
 ///
@@ -81,29 +120,25 @@ struct ArmDebugState64
 /// - **x86-64**: `x86_DEBUG_STATE64` (flavor 11)
 /// - **ARM64**: `ARM_DEBUG_STATE64` (flavor 15)
 ///
-/// ## Returns
-///
-/// The slot index used (0-3 for x86-64, 0-15 for ARM64).
-///
 /// ## Errors
 ///
 /// Returns `MachError` if `thread_get_state()` or `thread_set_state()` fails.
-/// Returns `InvalidArgument` if all hardware breakpoint slots are in use.
+/// Returns `InvalidArgument` if the slot index is out of range.
 ///
 /// ## See Also
 ///
 /// - `clear_hardware_breakpoint()`: Removes a hardware breakpoint
 /// - [thread_get_state(3) man page](https://developer.apple.com/documentation/kernel/1418576-thread_get_state/)
 /// - [thread_set_state(3) man page](https://developer.apple.com/documentation/kernel/1418576-thread_set_state/)
-pub fn set_hardware_breakpoint(thread: thread_act_t, address: Address) -> Result<u32>
+pub fn set_hardware_breakpoint(thread: thread_act_t, address: Address, slot: u32) -> Result<()>
 {
     #[cfg(target_arch = "x86_64")]
     {
-        set_hw_bp_x86(thread, address)
+        set_hw_bp_x86(thread, address, slot)
     }
     #[cfg(target_arch = "aarch64")]
     {
-        set_hw_bp_arm64(thread, address)
+        set_hw_bp_arm64(thread, address, slot)
     }
 }
 
@@ -151,19 +186,25 @@ pub fn clear_hardware_breakpoint(thread: thread_act_t, slot: u32) -> Result<()>
 ///
 /// ## Platform support
 ///
-/// - **ARM64 (Apple Silicon)**: Supported for 8-byte read/write watchpoints in user mode.
+/// - **ARM64 (Apple Silicon)**: User-mode watchpoints on 1, 2, 4 or 8 bytes
+///   aligned to their length, trapping on loads, stores or both.
 /// - **x86-64**: Not yet implemented. This function will return `InvalidArgument`.
 ///
 /// ## Parameters
 ///
 /// - `thread`: The thread port to modify.
 /// - `address`: The starting address of the watched region.
-/// - `length`: Size in bytes of the watched region (currently must be 8 on ARM64).
+/// - `length`: Size in bytes of the watched region (see
+///   [`check_watch_range`](crate::breakpoints::check_watch_range)).
 /// - `access`: Type of access that should trigger the watchpoint.
 ///
+/// ## Returns
+///
+/// The slot index used (0-3 on ARM64).
+///
 /// ## Errors
 ///
-/// - `InvalidArgument` if the architecture is not supported, or parameters are unsupported.
+/// - `InvalidArgument` if the architecture is not supported, or the range doesn't fit.
 /// - `ResourceExhausted` if all watchpoint slots are in use.
 /// - `MachError` if `thread_get_state()` or `thread_set_state()` fails.
 pub fn set_watchpoint(thread: thread_act_t, address: Address, length: usize, access: WatchpointAccess) -> Result<u32>
@@ -181,6 +222,47 @@ pub fn set_watchpoint(thread: thread_act_t, address: Address, length: usize, acc
     }
 }
 
+/// Program a data watchpoint into a specific slot.
+///
+/// Used to give a thread that started after the watchpoint was installed the
+/// same slot every other thread has it in, so removing it by slot later
+/// clears it everywhere.
+///
+/// ## Errors
+///
+/// - `InvalidArgument` if the architecture is not supported, the range doesn't
+///   fit, or `slot` is out of range.
+/// - `MachError` if `thread_get_state()` or `thread_set_state()` fails.
+pub fn set_watchpoint_in_slot(
+    thread: thread_act_t,
+    address: Address,
+    length: usize,
+    access: WatchpointAccess,
+    slot: u32,
+) -> Result<()>
+{
+    #[cfg(target_arch = "x86_64")]
+    {
+        let _ = (thread, address, length, access, slot);
+        Err(DebuggerError::InvalidArgument(
+            "Data watchpoints are not yet supported on x86-64 macOS".to_string(),
+        ))
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        crate::breakpoints::check_watch_range(address, length)?;
+        let slot = slot as usize;
+        if slot >= constants::ARM64_WATCHPOINT_SLOTS {
+            return Err(DebuggerError::InvalidArgument("Invalid watchpoint slot".into()));
+        }
+        let (value, control) = watchpoint_registers_arm64(address, length, access);
+        update_debug_state_arm64(thread, |state| {
+            state.wvr[slot] = value;
+            state.wcr[slot] = control;
+        })
+    }
+}
+
 /// Clear a data watchpoint from the given slot.
 ///
 /// On ARM64 this clears DBGWCR/DBGWVR for the given slot. On x86-64 this
@@ -200,9 +282,166 @@ pub fn clear_watchpoint(thread: thread_act_t, slot: u32) -> Result<()>
     }
 }
 
+/// Turn off every watchpoint on a thread, returning what to turn back on.
+///
+/// A watchpoint traps before the access completes, so the thread has to step
+/// over the instruction with its watchpoints off or it traps again straight
+/// away. Pass the result to [`rearm_watchpoints`] after the step.
+///
+/// ## Errors
+///
+/// Returns `MachError` if `thread_get_state()` or `thread_set_state()` fails.
+pub fn disarm_watchpoints(thread: thread_act_t) -> Result<Vec<u64>>
+{
+    #[cfg(target_arch = "x86_64")]
+    {
+        let _ = thread;
+        Ok(Vec::new())
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        update_debug_state_arm64(thread, |state| {
+            let saved = state.wcr.to_vec();
+            for control in &mut state.wcr {
+                *control &= !1;
+            }
+            saved
+        })
+    }
+}
+
+/// Restore the watchpoint controls [`disarm_watchpoints`] saved.
+///
+/// ## Errors
+///
+/// Returns `MachError` if `thread_get_state()` or `thread_set_state()` fails.
+pub fn rearm_watchpoints(thread: thread_act_t, saved: &[u64]) -> Result<()>
+{
+    #[cfg(target_arch = "x86_64")]
+    {
+        let _ = (thread, saved);
+        Ok(())
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        update_debug_state_arm64(thread, |state| {
+            for (control, &saved) in state.wcr.iter_mut().zip(saved) {
+                *control = saved;
+            }
+        })
+    }
+}
+
+/// Read a thread's ARM64 debug state, change it with `update` and write it back.
+#[cfg(target_arch = "aarch64")]
+fn update_debug_state_arm64<T>(thread: thread_act_t, update: impl FnOnce(&mut ArmDebugState64) -> T) -> Result<T>
+{
+    unsafe {
+        let mut state = ArmDebugState64::default();
+        let mut count = constants::ARM_DEBUG_STATE64_COUNT;
+        let kr = ffi::thread_get_state(
+            thread,
+            constants::ARM_DEBUG_STATE64,
+            &mut state as *mut _ as *mut natural_t,
+            &mut count,
+        );
+
+        if kr != KERN_SUCCESS {
+            return Err(DebuggerError::MachError(kr.into()));
+        }
+
+        let result = update(&mut state);
+
+        let kr = ffi::thread_set_state(
+            thread,
+            constants::ARM_DEBUG_STATE64,
+            &state as *const _ as *const natural_t,
+            constants::ARM_DEBUG_STATE64_COUNT,
+        );
+
+        if kr != KERN_SUCCESS {
+            return Err(DebuggerError::MachError(kr.into()));
+        }
+
+        Ok(result)
+    }
+}
+
+/// Turn hardware single-stepping on or off for a thread.
+///
+/// While enabled, the thread traps with `EXC_BREAKPOINT` after every
+/// instruction it executes, so callers clear it again once the step they
+/// wanted has been reported.
+///
+/// - **ARM64**: Sets `MDSCR_EL1.SS` in `ARM_DEBUG_STATE64`
+/// - **x86-64**: Sets `TF` in `RFLAGS` (part of `x86_THREAD_STATE64`, not the debug state)
+///
+/// ## Errors
+///
+/// Returns `MachError` if `thread_get_state()` or `thread_set_state()` fails.
+pub fn set_single_step(thread: thread_act_t, enabled: bool) -> Result<()>
+{
+    #[cfg(target_arch = "x86_64")]
+    {
+        let mut regs = super::read_registers_x86_64(thread)?;
+        if enabled {
+            regs.status |= constants::X86_64_RFLAGS_TF;
+        } else {
+            regs.status &= !constants::X86_64_RFLAGS_TF;
+        }
+        super::write_registers_x86_64(thread, &regs)
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        set_single_step_arm64(thread, enabled)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn set_single_step_arm64(thread: thread_act_t, enabled: bool) -> Result<()>
+{
+    unsafe {
+        let mut state = ArmDebugState64::default();
+        let mut count = constants::ARM_DEBUG_STATE64_COUNT;
+        let kr = ffi::thread_get_state(
+            thread,
+            constants::ARM_DEBUG_STATE64,
+            &mut state as *mut _ as *mut natural_t,
+            &mut count,
+        );
+
+        if kr != KERN_SUCCESS {
+            return Err(DebuggerError::MachError(kr.into()));
+        }
+
+        if enabled {
+            state.mdscr_el1 |= constants::ARM64_MDSCR_SS;
+        } else {
+            state.mdscr_el1 &= !constants::ARM64_MDSCR_SS;
+        }
+
+        let kr = ffi::thread_set_state(
+            thread,
+            constants::ARM_DEBUG_STATE64,
+            &state as *const _ as *const natural_t,
+            constants::ARM_DEBUG_STATE64_COUNT,
+        );
+
+        if kr != KERN_SUCCESS {
+            return Err(DebuggerError::MachError(kr.into()));
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(target_arch = "x86_64")]
-fn set_hw_bp_x86(thread: thread_act_t, address: Address) -> Result<u32>
+fn set_hw_bp_x86(thread: thread_act_t, address: Address, slot: u32) -> Result<()>
 {
+    if slot as usize >= constants::X86_64_HARDWARE_BREAKPOINT_SLOTS {
+        return Err(DebuggerError::InvalidArgument(format!("Invalid breakpoint slot {slot}")));
+    }
+
     unsafe {
         let mut state = X86DebugState64::default();
         let mut count = constants::X86_DEBUG_STATE64_COUNT;
@@ -217,21 +456,6 @@ fn set_hw_bp_x86(thread: thread_act_t, address: Address) -> Result<u32>
             return Err(DebuggerError::MachError(kr.into()));
         }
 
-        // Find a free slot
-        let slot = if (state.dr7 & (1 << 0)) == 0 {
-            0
-        } else if (state.dr7 & (1 << 2)) == 0 {
-            1
-        } else if (state.dr7 & (1 << 4)) == 0 {
-            2
-        } else if (state.dr7 & (1 << 6)) == 0 {
-            3
-        } else {
-            return Err(DebuggerError::ResourceExhausted(
-                "No free hardware breakpoint slots (maximum 4 on x86-64)".into(),
-            ));
-        };
-
         // Set address in appropriate DR register
         match slot {
             0 => state.dr0 = address.value(),
@@ -268,7 +492,7 @@ fn set_hw_bp_x86(thread: thread_act_t, address: Address) -> Result<u32>
             return Err(DebuggerError::MachError(kr.into()));
         }
 
-        Ok(slot)
+        Ok(())
     }
 }
 
@@ -308,8 +532,13 @@ fn clear_hw_bp_x86(thread: thread_act_t, slot: u32) -> Result<()>
 }
 
 #[cfg(target_arch = "aarch64")]
-fn set_hw_bp_arm64(thread: thread_act_t, address: Address) -> Result<u32>
+fn set_hw_bp_arm64(thread: thread_act_t, address: Address, slot: u32) -> Result<()>
 {
+    let slot = slot as usize;
+    if slot >= 16 {
+        return Err(DebuggerError::InvalidArgument(format!("Invalid breakpoint slot {slot}")));
+    }
+
     unsafe {
         let mut state = ArmDebugState64::default();
         let mut count = constants::ARM_DEBUG_STATE64_COUNT;
@@ -324,20 +553,6 @@ fn set_hw_bp_arm64(thread: thread_act_t, address: Address) -> Result<u32>
             return Err(DebuggerError::MachError(kr.into()));
         }
 
-        // Find a free slot
-        // BCR (Breakpoint Control Register) bit 0 is enable (E)
-        let mut slot = None;
-        for i in 0..16 {
-            if (state.bcr[i] & 1) == 0 {
-                slot = Some(i);
-                break;
-            }
-        }
-
-        let slot = slot.ok_or_else(|| {
-            DebuggerError::ResourceExhausted("No free hardware breakpoint slots (maximum 16 on ARM64)".into())
-        })?;
-
         // Set BVR (Breakpoint Value Register) to address
         state.bvr[slot] = address.value();
 
@@ -374,7 +589,7 @@ fn set_hw_bp_arm64(thread: thread_act_t, address: Address) -> Result<u32>
             return Err(DebuggerError::MachError(kr.into()));
         }
 
-        Ok(slot as u32)
+        Ok(())
     }
 }
 
@@ -420,78 +635,44 @@ fn clear_hw_bp_arm64(thread: thread_act_t, slot: u32) -> Result<()>
 #[cfg(target_arch = "aarch64")]
 fn set_wp_arm64(thread: thread_act_t, address: Address, length: usize, access: WatchpointAccess) -> Result<u32>
 {
-    // For the initial implementation we support a single, naturally aligned
-    // 8-byte watchpoint that triggers on both loads and stores. This matches
-    // the common case of watching a pointer-sized value.
-    if length != 8 {
-        return Err(DebuggerError::InvalidArgument(
-            "ARM64 watchpoints currently only support length == 8 bytes".to_string(),
-        ));
-    }
-
-    if !matches!(access, WatchpointAccess::ReadWrite) {
-        return Err(DebuggerError::InvalidArgument(
-            "ARM64 watchpoints currently only support ReadWrite access".to_string(),
-        ));
-    }
-
-    unsafe {
-        let mut state = ArmDebugState64::default();
-        let mut count = constants::ARM_DEBUG_STATE64_COUNT;
-        let kr = ffi::thread_get_state(
-            thread,
-            constants::ARM_DEBUG_STATE64,
-            &mut state as *mut _ as *mut natural_t,
-            &mut count,
-        );
-
-        if kr != KERN_SUCCESS {
-            return Err(DebuggerError::MachError(kr.into()));
-        }
-
-        // Find a free slot in the watchpoint control registers.
-        // WCR (Watchpoint Control Register) bit 0 is enable (E).
-        let mut slot = None;
-        for i in 0..16 {
-            if (state.wcr[i] & 1) == 0 {
-                slot = Some(i);
-                break;
-            }
-        }
-
-        let slot = slot.ok_or_else(|| {
-            DebuggerError::ResourceExhausted("No free hardware watchpoint slots (maximum 16 on ARM64)".into())
-        })?;
-
-        // Program the watchpoint value register with the watched address.
-        state.wvr[slot] = address.value();
-
-        // Program the watchpoint control register.
-        //
-        // NOTE: The exact DBGWCR_EL1 encoding is architecture-specific and
-        // nuanced (LSC, BAS, PAC, etc.). For now we reuse the same control
-        // value as execution breakpoints, which configures:
-        //   - E = 1 (enable)
-        //   - PMC = user mode
-        //   - BAS = match all bytes in the 8-byte region
-        //
-        // This is sufficient to get a functioning user-mode data watchpoint
-        // on Apple Silicon, and can be refined later if needed.
-        state.wcr[slot] = constants::ARM64_BP_CTRL_USER_EXEC;
-
-        let kr = ffi::thread_set_state(
-            thread,
-            constants::ARM_DEBUG_STATE64,
-            &state as *const _ as *const natural_t,
-            constants::ARM_DEBUG_STATE64_COUNT,
-        );
-
-        if kr != KERN_SUCCESS {
-            return Err(DebuggerError::MachError(kr.into()));
-        }
+    crate::breakpoints::check_watch_range(address, length)?;
+    let (value, control) = watchpoint_registers_arm64(address, length, access);
+
+    let slot = update_debug_state_arm64(thread, |state| {
+        // WCR (Watchpoint Control Register) bit 0 is enable (E)
+        let slot = state.wcr[..constants::ARM64_WATCHPOINT_SLOTS]
+            .iter()
+            .position(|&control| control & 1 == 0)?;
+        state.wvr[slot] = value;
+        state.wcr[slot] = control;
+        Some(slot)
+    })?;
+
+    slot.map(|slot| slot as u32).ok_or_else(|| {
+        DebuggerError::ResourceExhausted(format!(
+            "All {} hardware watchpoint slots are in use; remove a watchpoint to watch {address}",
+            constants::ARM64_WATCHPOINT_SLOTS
+        ))
+    })
+}
 
-        Ok(slot as u32)
-    }
+/// `(DBGWVR, DBGWCR)` for a user-mode watchpoint on `length` bytes at `address`.
+///
+/// DBGWVR holds the doubleword the bytes are in, and the byte address select
+/// field of DBGWCR picks the watched bytes within it; the range has already
+/// been checked to fit in one doubleword.
+#[cfg(target_arch = "aarch64")]
+fn watchpoint_registers_arm64(address: Address, length: usize, access: WatchpointAccess) -> (u64, u64)
+{
+    let offset = address.value() & 7;
+    let bytes = ((1_u64 << length) - 1) << offset;
+    let lsc = match access {
+        WatchpointAccess::Read => constants::ARM64_WP_CTRL_LOAD,
+        WatchpointAccess::Write => constants::ARM64_WP_CTRL_STORE,
+        WatchpointAccess::ReadWrite => constants::ARM64_WP_CTRL_LOAD | constants::ARM64_WP_CTRL_STORE,
+    };
+    let control = constants::ARM64_WP_CTRL_USER | lsc | (bytes << constants::ARM64_WP_CTRL_BAS_SHIFT);
+    (address.value() & !7, control)
 }
 
 #[cfg(target_arch = "aarch64")]
@@ -34,6 +34,9 @@ pub mod debug;
 #[cfg(target_arch = "aarch64")]
 pub use arm64::{read_registers_arm64, write_registers_arm64};
 // Re-export debug register functions
-pub use debug::{clear_hardware_breakpoint, clear_watchpoint, set_hardware_breakpoint, set_watchpoint};
+pub use debug::{
+    clear_hardware_breakpoint, clear_watchpoint, disarm_watchpoints, hardware_breakpoint_slots, rearm_watchpoints,
+    set_hardware_breakpoint, set_single_step, set_watchpoint, set_watchpoint_in_slot,
+};
 #[cfg(target_arch = "x86_64")]
 pub use x86_64::{read_registers_x86_64, write_registers_x86_64};
@@ -20,10 +20,13 @@
 //! - [Apple Mach Kernel Programming](https://developer.apple.com/library/archive/documentation/Darwin/Conceptual/KernelProgramming/Mach/Mach.html)
 //! - [XNU Kernel Source](https://github.com/apple-oss-distributions/xnu) (for `task_for_pid` and `task_threads` implementation)
 
+use std::collections::{BTreeSet, HashMap};
 use std::fs::File;
 use std::os::fd::{FromRawFd, RawFd};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use libc::{c_int, mach_msg_type_number_t, mach_port_t, thread_act_t};
 #[cfg(target_os = "macos")]
@@ -44,19 +47,49 @@ use mach2::task::{task_resume, task_set_exception_ports, task_suspend, task_thre
 #[cfg(target_os = "macos")]
 use mach2::traps::mach_task_self;
 
-use crate::breakpoints::{BreakpointEntry, BreakpointId, BreakpointInfo, BreakpointRequest, BreakpointStore};
+use crate::breakpoints::{
+    Breakpoint, BreakpointCounts, BreakpointEntry, BreakpointId, BreakpointInfo, BreakpointKind, BreakpointLocation,
+    BreakpointRequest, BreakpointRequestKind, BreakpointStore,
+};
 use crate::debugger::Debugger;
 use crate::error::{DebuggerError, Result};
-use crate::events::{self, DebuggerEvent};
+use crate::events::{self, DebuggerEvent, EventEnvelope};
+use crate::inspector::{Capabilities, TargetInspector};
+use crate::instructions;
+use crate::output::{self, OutputPump, OutputSink};
+use crate::permissions::{self, PrivilegedOperation};
 use crate::platform::macos::memory::{MemoryCache, get_memory_regions, write_memory};
 #[cfg(target_arch = "aarch64")]
 use crate::platform::macos::registers::{read_registers_arm64, write_registers_arm64};
 #[cfg(target_arch = "x86_64")]
 use crate::platform::macos::registers::{read_registers_x86_64, write_registers_x86_64};
-use crate::platform::macos::{breakpoints, exception, ffi, launch, threads};
-use crate::symbols::unwind::{MemoryAccess, StackUnwinder};
-use crate::symbols::{ImageDescriptor, SymbolCache};
-use crate::types::{Address, Architecture, MemoryRegion, ProcessId, Registers, StackFrame, StopReason, ThreadId};
+use crate::platform::macos::{
+    breakpoints, exception, exit, ffi, images, launch, permissions as macos_permissions, registers, threads,
+};
+use crate::platform::stack_guard;
+use crate::platform::thread_map::ThreadHandleMap;
+use crate::search::{MemorySearch, MemorySearchScope};
+use crate::signals::{SignalPolicies, SignalPolicy};
+use crate::symbols::paths::SourceMap;
+use crate::symbols::tls::{self, ThreadLocalValue, TlsLocation};
+use crate::symbols::unwind::{self, MemoryAccess, StackTrace, StackUnwinder, UnwindOptions};
+use crate::symbols::{DebugLevel, ImageDescriptor, ImageInfo, SymbolCache, TypeSummary};
+use crate::types::{
+    Address, Architecture, LaunchOptions, MemoryCacheStats, MemoryRegion, ProcessId, Registers, SourceLocation, StackFrame,
+    StopPolicy, StopReason, SymbolName, ThreadId, ThreadInfo,
+};
+
+/// How long `step_instruction()` waits for a single step before suspending the task.
+///
+/// One instruction normally traps within microseconds; only one that blocks,
+/// such as a system call waiting for input, runs into this.
+const STEP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long `step_over()` and `step_out()` let a function run before suspending the task inside it.
+const STEP_OVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Frames `step_out()` unwinds to find the caller; enough to get past the inlined frames at the top.
+const STEP_OUT_FRAMES: usize = 64;
 
 /// macOS debugger implementation using Mach APIs
 ///
@@ -85,6 +118,9 @@ pub struct MacOSDebugger
     /// Cached thread ports for the target task.
     threads: Vec<thread_act_t>,
 
+    /// Port<->stable `ThreadId` map for the cached thread ports.
+    thread_ids: ThreadHandleMap<thread_act_t>,
+
     /// Active thread used for register operations.
     current_thread: Option<thread_act_t>,
 
@@ -117,10 +153,26 @@ pub struct MacOSDebugger
     stdout_pipe: Option<File>,
     /// Read end of the stderr pipe for the most recently launched process.
     stderr_pipe: Option<File>,
-    /// Symbol cache for DWARF and symbol resolution.
-    symbol_cache: SymbolCache,
+    /// Write end of the stdin pipe for the most recently launched process.
+    stdin_pipe: Option<File>,
+    /// Pumps draining the captured pipes, started by `pump_process_output()`.
+    output_pumps: Vec<OutputPump>,
+    /// Symbol cache for DWARF and symbol resolution, shared with its background loader.
+    symbol_cache: Arc<SymbolCache>,
     /// Cached memory pages for repeated reads.
     memory_cache: MemoryCache,
+    /// Outstanding `thread_suspend()` calls we've issued, keyed by thread.
+    thread_suspensions: HashMap<ThreadId, u32>,
+    /// PID of a target that died behind our back, 0 while it's alive.
+    ///
+    /// Set from `&self` methods when a failure turns out to be a dead task,
+    /// so it's atomic, and from the exit monitor, so it's shared; cleared by
+    /// the next attach or launch.
+    gone_pid: Arc<AtomicU32>,
+    /// Watches for the target's exit while attached.
+    exit_monitor: Option<exit::ExitMonitor>,
+    /// What the exception handler does with each signal; kept across attaches.
+    signal_policies: Arc<Mutex<SignalPolicies>>,
 }
 
 // Trait implementations for modular operations
@@ -148,16 +200,22 @@ impl breakpoints::BreakpointOperations for MacOSDebugger
 
     fn ensure_attached(&self) -> Result<()>
     {
-        if !self.attached || self.task == 0 {
-            return Err(DebuggerError::NotAttached);
-        }
-        Ok(())
+        self.ensure_target()
     }
 
     fn task_port(&self) -> mach_port_t
     {
         self.task
     }
+
+    fn known_instruction_start(&mut self, address: Address) -> Option<Address>
+    {
+        if let Err(err) = self.load_symbol_images() {
+            tracing::debug!("No symbols to check the instruction boundary at {address}: {err}");
+            return None;
+        }
+        self.symbol_cache.known_instruction_start(address)
+    }
 }
 
 impl threads::ThreadOperations for MacOSDebugger
@@ -177,6 +235,16 @@ impl threads::ThreadOperations for MacOSDebugger
         &self.threads
     }
 
+    fn thread_map(&self) -> &ThreadHandleMap<thread_act_t>
+    {
+        &self.thread_ids
+    }
+
+    fn thread_map_mut(&mut self) -> &mut ThreadHandleMap<thread_act_t>
+    {
+        &mut self.thread_ids
+    }
+
     fn current_thread(&self) -> Option<thread_act_t>
     {
         self.current_thread
@@ -186,11 +254,6 @@ impl threads::ThreadOperations for MacOSDebugger
     {
         self.current_thread = thread;
     }
-
-    fn pid(&self) -> u32
-    {
-        self.pid.0
-    }
 }
 
 impl launch::LaunchOperations for MacOSDebugger
@@ -215,6 +278,14 @@ impl launch::LaunchOperations for MacOSDebugger
             self.stderr_pipe = Some(File::from_raw_fd(fd));
         }
     }
+
+    fn set_stdin_pipe(&mut self, fd: RawFd)
+    {
+        use std::fs::File;
+        unsafe {
+            self.stdin_pipe = Some(File::from_raw_fd(fd));
+        }
+    }
 }
 
 impl MacOSDebugger
@@ -237,11 +308,14 @@ impl MacOSDebugger
     /// ```
     pub fn new() -> Result<Self>
     {
+        // Probe once up front so a permission failure doesn't pay for reading our own signature
+        tracing::debug!("Permission environment: {:?}", permissions::current_environment());
         let (event_tx, event_rx) = events::event_channel();
 
         Ok(Self {
             task: 0,
             threads: Vec::new(),
+            thread_ids: ThreadHandleMap::new(),
             current_thread: None,
             pid: ProcessId(0),
             architecture: Architecture::current(),
@@ -256,25 +330,39 @@ impl MacOSDebugger
             capture_output: false,
             stdout_pipe: None,
             stderr_pipe: None,
-            symbol_cache: SymbolCache::new(),
+            stdin_pipe: None,
+            output_pumps: Vec::new(),
+            symbol_cache: Arc::new(SymbolCache::new()),
             memory_cache: MemoryCache::new(),
+            thread_suspensions: HashMap::new(),
+            gone_pid: Arc::new(AtomicU32::new(0)),
+            exit_monitor: None,
+            signal_policies: Arc::new(Mutex::new(SignalPolicies::default())),
         })
     }
 
     fn publish_stop_event(&self, reason: StopReason, thread: Option<thread_act_t>)
     {
-        let thread_id = thread.map(|port| ThreadId::from(port as u64));
-        if let Err(err) = self.event_tx.send(DebuggerEvent::TargetStopped {
+        self.memory_cache.advance_generation();
+        let thread_id = thread.map(|port| threads::ThreadManager::thread_id_for_port(self, port));
+        if let Err(err) = self.event_tx.send(EventEnvelope::new(DebuggerEvent::TargetStopped {
             reason,
             thread: thread_id,
-        }) {
+        })) {
             tracing::warn!("Failed to dispatch stop event: {err}");
         }
     }
 
+    fn publish_image_event(&self, event: DebuggerEvent)
+    {
+        if let Err(err) = self.event_tx.send(EventEnvelope::new(event)) {
+            tracing::warn!("Failed to dispatch image event: {err}");
+        }
+    }
+
     fn publish_resumed_event(&self)
     {
-        if let Err(err) = self.event_tx.send(DebuggerEvent::TargetResumed) {
+        if let Err(err) = self.event_tx.send(EventEnvelope::new(DebuggerEvent::TargetResumed)) {
             tracing::warn!("Failed to dispatch resume event: {err}");
         }
     }
@@ -362,16 +450,21 @@ impl MacOSDebugger
                     let process_exists = libc::kill(pid.0 as libc::pid_t, 0) == 0;
 
                     if process_exists {
-                        return Err(DebuggerError::PermissionDenied(format!(
-                            "task_for_pid() failed with KERN_FAILURE, but process {} exists. This means insufficient \
-                             permissions.\n\nQuick fix: Run with sudo:\n  sudo ferros attach {}\n\nAlternatively, use \
-                             launch() to spawn processes under debugger control, which doesn't require special permissions.",
-                            pid.0, pid.0
-                        )));
+                        return Err(macos_permissions::permission_denied(
+                            PrivilegedOperation::Attach,
+                            format!(
+                                "task_for_pid() failed with KERN_FAILURE, but process {} exists, so this is a permissions \
+                                 problem",
+                                pid.0
+                            ),
+                        ));
                     }
                 }
 
-                return Err(DebuggerError::MachError(result.into()));
+                return Err(macos_permissions::advise(
+                    DebuggerError::MachError(result.into()),
+                    PrivilegedOperation::Attach,
+                ));
             }
 
             let mut threads: *mut thread_act_t = std::ptr::null_mut();
@@ -386,10 +479,11 @@ impl MacOSDebugger
             let slice = std::slice::from_raw_parts(threads, thread_count as usize);
             self.task = task;
             self.pid = pid;
-            self.threads = slice.to_vec();
+            threads::ThreadManager::record_thread_ports(self, slice.to_vec());
             threads::ThreadManager::deallocate_threads_array(threads, thread_count);
             self.current_thread = self.threads.first().copied();
             self.attached = true;
+            self.gone_pid.store(0, Ordering::Relaxed);
         }
 
         {
@@ -397,8 +491,12 @@ impl MacOSDebugger
             shared.stopped = false;
             shared.stop_reason = StopReason::Running;
             shared.pending_thread = None;
-            self.memory_cache.clear();
+            self.memory_cache.invalidate_all();
+            self.memory_cache.reset_stats();
         }
+        // Images cached for a previous process sit at its addresses, and a
+        // relaunch maps the same binaries at new ones
+        self.symbol_cache.clear();
 
         self.start_exception_handler()?;
 
@@ -423,12 +521,102 @@ impl MacOSDebugger
     /// call this internally to ensure the debugger is attached before proceeding.
     fn ensure_attached(&self) -> Result<()>
     {
+        self.ensure_target()
+    }
+
+    /// `TargetGone` once the target has died, `NotAttached` if there is none
+    fn ensure_target(&self) -> Result<()>
+    {
+        let gone = self.gone_pid.load(Ordering::Relaxed);
+        if gone != 0 {
+            return Err(DebuggerError::TargetGone { pid: gone });
+        }
         if !self.attached || self.task == 0 {
             return Err(DebuggerError::NotAttached);
         }
         Ok(())
     }
 
+    /// [`ensure_target`](Self::ensure_target) for `&mut self` operations
+    ///
+    /// Also releases the ports of a target that a `&self` call found dead.
+    fn ensure_live(&mut self) -> Result<()>
+    {
+        let result = self.ensure_target();
+        if matches!(result, Err(DebuggerError::TargetGone { .. })) && self.attached {
+            let _ = self.detach();
+        }
+        result
+    }
+
+    /// Whether the target process still exists and the task port still refers to it
+    ///
+    /// `kill(pid, 0)` alone accepts zombies and recycled PIDs, so the task
+    /// port is also checked with `pid_for_task`, which fails once the task
+    /// has terminated.
+    pub fn is_target_alive(&self) -> bool
+    {
+        if self.task == 0 || self.pid.0 == 0 {
+            return false;
+        }
+        let pid = self.pid.0 as libc::pid_t;
+        // SAFETY: signal 0 only checks that the PID exists
+        let exists =
+            unsafe { libc::kill(pid, 0) } == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+        if !exists {
+            return false;
+        }
+        let mut task_pid: c_int = 0;
+        // SAFETY: pid_for_task writes a single int
+        let result = unsafe { ffi::pid_for_task(self.task, &mut task_pid) };
+        result == KERN_SUCCESS && task_pid == pid
+    }
+
+    /// Replace a failure caused by a dead task with `TargetGone`
+    ///
+    /// A task that died behind our back makes every Mach call fail, each in
+    /// its own way (`MACH_SEND_INVALID_DEST`, `KERN_INVALID_ARGUMENT`,
+    /// `KERN_TERMINATED`, ...). On such a failure the target is probed with
+    /// [`is_target_alive`](Self::is_target_alive); if it's gone the debugger
+    /// is marked so and every later call reports the same error.
+    fn check_target(&self, err: DebuggerError) -> DebuggerError
+    {
+        let suspicious = matches!(
+            err,
+            DebuggerError::MachError(_)
+                | DebuggerError::Io(_)
+                | DebuggerError::InvalidArgument(_)
+                | DebuggerError::MemoryReadFailed { .. }
+                | DebuggerError::ThreadNotFound(_)
+                | DebuggerError::AttachFailed(_)
+                | DebuggerError::SuspendFailed(_)
+                | DebuggerError::ResumeFailed(_)
+                | DebuggerError::ReadRegistersFailed { .. }
+        );
+        if !suspicious || !self.attached || self.is_target_alive() {
+            return err;
+        }
+        tracing::warn!("Process {} is gone ({err})", self.pid.0);
+        self.mark_gone()
+    }
+
+    fn mark_gone(&self) -> DebuggerError
+    {
+        let pid = self.pid.0;
+        self.gone_pid.store(pid, Ordering::Relaxed);
+        DebuggerError::TargetGone { pid }
+    }
+
+    /// [`check_target`](Self::check_target), releasing the ports if the target is gone
+    fn release_if_gone(&mut self, err: DebuggerError) -> DebuggerError
+    {
+        let err = self.check_target(err);
+        if matches!(err, DebuggerError::TargetGone { .. }) {
+            let _ = self.detach();
+        }
+        err
+    }
+
     /// Get the Mach thread port for the currently active thread
     ///
     /// This is an internal helper method that returns the `thread_act_t` (Mach thread port)
@@ -453,13 +641,22 @@ impl MacOSDebugger
         threads::ThreadManager::thread_port_for_id(self, thread)
     }
 
+    /// Base of a thread's pthread TSD array in the target.
+    fn tsd_base(&self, thread: ThreadId) -> Result<Address>
+    {
+        let port = self.thread_port_for_id(thread)?;
+        let unavailable = || DebuggerError::InvalidArgument(format!("Unable to locate the TSD of thread {}", thread.raw()));
+        let handle = threads::ThreadManager::thread_handle(port).ok_or_else(unavailable)?;
+        tls::tsd_base_from_handle(handle, |address| self.read_u64(address))?.ok_or_else(unavailable)
+    }
+
     // Internal breakpoint methods - these are wrappers around BreakpointManager
     // methods. They're kept for potential future use or internal consistency.
     #[allow(dead_code)]
     fn install_software_breakpoint(&mut self, address: Address) -> Result<BreakpointId>
     {
         let breakpoints = self.breakpoints.clone();
-        breakpoints::BreakpointManager::install_software_breakpoint(self, &breakpoints, address)
+        breakpoints::BreakpointManager::install_software_breakpoint(self, &breakpoints, address, false)
     }
 
     #[allow(dead_code)]
@@ -487,6 +684,44 @@ impl MacOSDebugger
         breakpoints::BreakpointManager::restore_all_breakpoints(self, &breakpoints);
     }
 
+    /// Undo every `suspend_thread()` we issued so the target isn't left frozen after detach.
+    fn release_thread_suspensions(&mut self)
+    {
+        for (thread, count) in std::mem::take(&mut self.thread_suspensions) {
+            for _ in 0..count {
+                if let Err(err) = threads::ThreadManager::resume_thread(self, thread) {
+                    tracing::warn!("Failed to release suspension on thread {}: {err}", thread.raw());
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Resume the threads the exception handler held for the current stop under `StopPolicy::AllThreads`.
+    ///
+    /// Threads may have started or exited while the target was stopped, so
+    /// the thread list is refreshed first; each held thread still alive gets
+    /// exactly one `thread_resume()`, matching the handler's `thread_suspend()`.
+    fn release_held_threads(&mut self)
+    {
+        let held = std::mem::take(&mut self.exception_state.lock().unwrap().held_threads);
+        if held.is_empty() {
+            return;
+        }
+        if let Err(err) = self.refresh_thread_list() {
+            tracing::warn!("Failed to refresh threads before releasing held ones: {err}");
+        }
+        for thread in held {
+            if !self.thread_ids.contains(thread) {
+                tracing::debug!("Held thread {} exited while stopped", thread.raw());
+                continue;
+            }
+            if let Err(err) = threads::ThreadManager::resume_thread(self, thread) {
+                tracing::warn!("Failed to release held thread {}: {err}", thread.raw());
+            }
+        }
+    }
+
     fn read_registers_from_port(&self, thread: thread_act_t) -> Result<Registers>
     {
         match self.architecture {
@@ -497,9 +732,7 @@ impl MacOSDebugger
                 }
                 #[cfg(not(target_arch = "aarch64"))]
                 {
-                    Err(DebuggerError::InvalidArgument(
-                        "arm64 register access not supported on this build".to_string(),
-                    ))
+                    Err(DebuggerError::UnsupportedArchitecture(self.architecture))
                 }
             }
             Architecture::X86_64 => {
@@ -509,14 +742,10 @@ impl MacOSDebugger
                 }
                 #[cfg(not(target_arch = "x86_64"))]
                 {
-                    Err(DebuggerError::InvalidArgument(
-                        "x86_64 register access not supported on this build".to_string(),
-                    ))
+                    Err(DebuggerError::UnsupportedArchitecture(self.architecture))
                 }
             }
-            Architecture::Unknown(label) => {
-                Err(DebuggerError::InvalidArgument(format!("Unsupported architecture: {label}")))
-            }
+            Architecture::Unknown(_) => Err(DebuggerError::UnsupportedArchitecture(self.architecture)),
         }
     }
 
@@ -530,9 +759,7 @@ impl MacOSDebugger
                 }
                 #[cfg(not(target_arch = "aarch64"))]
                 {
-                    Err(DebuggerError::InvalidArgument(
-                        "arm64 register access not supported on this build".to_string(),
-                    ))
+                    Err(DebuggerError::UnsupportedArchitecture(self.architecture))
                 }
             }
             Architecture::X86_64 => {
@@ -542,14 +769,10 @@ impl MacOSDebugger
                 }
                 #[cfg(not(target_arch = "x86_64"))]
                 {
-                    Err(DebuggerError::InvalidArgument(
-                        "x86_64 register access not supported on this build".to_string(),
-                    ))
+                    Err(DebuggerError::UnsupportedArchitecture(self.architecture))
                 }
             }
-            Architecture::Unknown(label) => {
-                Err(DebuggerError::InvalidArgument(format!("Unsupported architecture: {label}")))
-            }
+            Architecture::Unknown(_) => Err(DebuggerError::UnsupportedArchitecture(self.architecture)),
         }
     }
 
@@ -565,7 +788,7 @@ impl MacOSDebugger
     ///
     /// ## Errors
     ///
-    /// Returns `InvalidArgument` if the thread port is not in the current thread list.
+    /// Returns `ThreadNotFound` if the thread port is not in the current thread list.
     ///
     /// ## See Also
     ///
@@ -575,10 +798,27 @@ impl MacOSDebugger
         threads::ThreadManager::set_active_thread_by_port(self, port)
     }
 
+    /// Re-enumerate the threads and program the debug registers of any that just started.
     fn refresh_thread_list(&mut self) -> Result<()>
     {
         self.ensure_attached()?;
-        threads::ThreadManager::refresh_thread_list(self)
+        let started = threads::ThreadManager::refresh_thread_list(self)?;
+        breakpoints::BreakpointManager::arm_started_threads(&self.breakpoints, &started);
+        Ok(())
+    }
+
+    /// Refresh the thread list before programming debug registers on every thread.
+    ///
+    /// Hardware breakpoints and watchpoints go into each thread's debug
+    /// registers, so a thread that started since the last refresh would
+    /// otherwise be missed. A failed refresh only loses that thread.
+    fn refresh_threads_for_debug_registers(&mut self)
+    {
+        if self.attached
+            && let Err(err) = self.refresh_thread_list()
+        {
+            tracing::warn!("Failed to refresh threads before arming debug registers: {err}");
+        }
     }
 
     /// Attempt to continue execution if we're currently stopped inside the Mach exception loop.
@@ -587,11 +827,23 @@ impl MacOSDebugger
     /// the normal `task_resume` path should be used instead.
     fn try_resume_pending_exception(&mut self) -> Result<bool>
     {
-        let has_pending = self.exception_state.lock().unwrap().pending_thread.is_some();
+        let (has_pending, stop_reason) = {
+            let shared = self.exception_state.lock().unwrap();
+            (shared.pending_thread.is_some(), shared.stop_reason)
+        };
         if !has_pending {
             return Ok(false);
         }
 
+        // A one-shot breakpoint that trapped this stop is removed (restoring the
+        // original instruction) before the thread runs again.
+        if let StopReason::Breakpoint(addr) = stop_reason {
+            let spent = self.breakpoints.lock().unwrap().spent_one_shot(Address::from(addr));
+            if let Some(id) = spent {
+                self.remove_breakpoint(id)?;
+            }
+        }
+
         let sender = self
             .exception_resume_tx
             .as_ref()
@@ -604,173 +856,1051 @@ impl MacOSDebugger
         Ok(true)
     }
 
-    /// Start the Mach exception handler thread.
-    ///
-    /// This creates a Mach receive port, registers it with `task_set_exception_ports()`,
-    /// and spawns a background thread to handle exceptions (breakpoints, signals, etc.).
-    ///
-    /// ## Mach APIs Used
-    ///
-    /// - **mach_port_allocate()**: Creates a Mach receive port
-    /// - **mach_port_insert_right()**: Makes the port sendable
-    /// - **task_set_exception_ports()**: Registers the exception port with the task
-    ///
-    /// ## Exception Mask
-    ///
-    /// The handler receives:
-    /// - `EXC_MASK_BREAKPOINT`: Software breakpoints (INT3/BRK)
-    /// - `EXC_MASK_BAD_ACCESS`: Memory access violations
-    /// - `EXC_MASK_BAD_INSTRUCTION`: Illegal instructions
-    /// - `EXC_MASK_ARITHMETIC`: Arithmetic exceptions (div by zero, overflow)
-    /// - `EXC_MASK_SOFTWARE`: Software-generated exceptions
-    ///
-    /// ## Thread Safety
+    /// Bring the symbol cache in line with the images dyld lists for the task
     ///
-    /// The exception handler runs in a separate thread and communicates via channels.
-    /// See `exception::run_exception_loop()` for the handler implementation.
-    ///
-    /// ## Errors
-    ///
-    /// Returns `MachError` if port allocation or exception port registration fails.
-    /// Returns `AttachFailed` if the handler thread cannot be spawned.
-    ///
-    /// ## See Also
-    ///
-    /// - `stop_exception_handler()`: Stops the exception handler
-    /// - `exception::run_exception_loop()`: The exception handling loop
-    /// - [task_set_exception_ports(3) man page](https://developer.apple.com/documentation/kernel/1402149-task_set_exception_ports/)
-    /// - [mach_port_allocate(3) man page](https://developer.apple.com/documentation/kernel/1402149-mach_port_allocate/)
-    fn start_exception_handler(&mut self) -> Result<()>
-    {
-        #[cfg(target_os = "macos")]
-        {
-            use tracing::info;
-
-            self.stop_exception_handler();
-
-            if self.task == MACH_PORT_NULL {
-                return Ok(());
-            }
-
-            unsafe {
-                let self_task = mach_task_self();
-                let mut port: mach_port_t = MACH_PORT_NULL;
-                let mut kr = mach_port_allocate(self_task, MACH_PORT_RIGHT_RECEIVE, &mut port);
-                if kr != KERN_SUCCESS {
-                    return Err(DebuggerError::MachError(kr.into()));
-                }
-
-                kr = mach_port_insert_right(self_task, port, port, MACH_MSG_TYPE_MAKE_SEND);
-                if kr != KERN_SUCCESS {
-                    let _ = mach_port_destroy(self_task, port);
-                    return Err(DebuggerError::MachError(kr.into()));
-                }
-
-                let mask: exception_mask_t = EXC_MASK_BREAKPOINT
-                    | EXC_MASK_BAD_ACCESS
-                    | EXC_MASK_BAD_INSTRUCTION
-                    | EXC_MASK_ARITHMETIC
-                    | EXC_MASK_SOFTWARE;
-                let behavior: exception_behavior_t = (EXCEPTION_DEFAULT | MACH_EXCEPTION_CODES) as exception_behavior_t;
-                let flavor = exception::thread_state_flavor_for_arch(self.architecture);
-                kr = task_set_exception_ports(self.task, mask, port, behavior, flavor);
-                if kr != KERN_SUCCESS {
-                    let _ = mach_port_destroy(self_task, port);
-                    return Err(DebuggerError::MachError(kr.into()));
-                }
-
-                let (tx, rx) = mpsc::channel();
-                let shared_state = Arc::clone(&self.exception_state);
-                let breakpoints = self.breakpoints.clone();
-                let architecture = self.architecture;
-                let event_tx = self.event_tx.clone();
-                info!("Spawning Mach exception handler thread");
-                let handle = thread::Builder::new()
-                    .name("ferros-mac-exc".to_string())
-                    .spawn(move || {
-                        exception::run_exception_loop(port, rx, shared_state, architecture, event_tx, breakpoints)
-                    })
-                    .map_err(|e| {
-                        let _ = mach_port_destroy(self_task, port);
-                        DebuggerError::AttachFailed(format!("Failed to spawn exception handler: {e}"))
-                    })?;
-
-                self.exception_port = port;
-                self.exception_thread = Some(handle);
-                self.exception_resume_tx = Some(tx);
-            }
-        }
-
-        Ok(())
-    }
-
-    fn stop_exception_handler(&mut self)
+    /// Load addresses come from `dyld_all_image_infos`, so they are exact.
+    /// Images loaded or unloaded since the last call are published as
+    /// `ImageLoaded` / `ImageUnloaded` events; the first list read is the
+    /// baseline and publishes nothing. Returns `false` when the list can't be
+    /// read or is empty (dyld hasn't run yet, or is updating it), and the
+    /// caller falls back to region scanning.
+    fn load_dyld_images(&self) -> bool
     {
-        #[cfg(target_os = "macos")]
+        let task = self.task;
+        let cache = &self.memory_cache;
+        let read = |address: Address, len: usize| cache.read(task, address, len);
+        let descriptors = match images::all_image_infos_address(task).and_then(|infos| images::read_image_list(infos, &read))
         {
-            if let Some(tx) = self.exception_resume_tx.take() {
-                let _ = tx.send(exception::ExceptionLoopCommand::Shutdown);
+            Ok(descriptors) if !descriptors.is_empty() => descriptors,
+            Ok(_) => {
+                tracing::debug!("dyld lists no images for process {}; scanning memory regions", self.pid.0);
+                return false;
             }
-
-            if self.exception_port != MACH_PORT_NULL {
-                unsafe {
-                    let _ = mach_port_destroy(mach_task_self(), self.exception_port);
-                }
-                self.exception_port = MACH_PORT_NULL;
+            Err(err) => {
+                tracing::debug!("Failed to read dyld's image list for process {}: {err}", self.pid.0);
+                return false;
             }
-
-            if let Some(handle) = self.exception_thread.take() {
-                let _ = handle.join();
+        };
+        // Drop images an earlier region scan placed at a guessed address
+        let listed: HashMap<&std::path::Path, u64> = descriptors
+            .iter()
+            .map(|descriptor| (descriptor.path.as_path(), descriptor.load_address))
+            .collect();
+        for image in self.symbol_cache.loaded_images() {
+            if listed
+                .get(image.path.as_path())
+                .is_some_and(|&load_address| load_address != image.load_address.value())
+            {
+                self.symbol_cache.invalidate_image(image.id);
             }
-
-            let mut shared = self.exception_state.lock().unwrap();
-            shared.pending_thread = None;
-            shared.stopped = false;
-            shared.stop_reason = StopReason::Running;
         }
-    }
-}
 
-impl Debugger for MacOSDebugger
-{
-    fn set_capture_process_output(&mut self, capture: bool)
-    {
-        self.capture_output = capture;
-        if !capture {
-            self.stdout_pipe = None;
-            self.stderr_pipe = None;
+        let changes = self.symbol_cache.sync_images(descriptors);
+        if changes.initial {
+            return true;
+        }
+        for image in changes.unloaded {
+            self.publish_image_event(DebuggerEvent::ImageUnloaded {
+                path: image.path,
+                base: Address::from(image.load_address),
+            });
         }
+        for image in changes.loaded {
+            self.publish_image_event(DebuggerEvent::ImageLoaded {
+                path: image.path,
+                base: Address::from(image.load_address),
+            });
+        }
+        true
     }
 
-    fn take_process_stdout(&mut self) -> Option<File>
+    /// Guess image load addresses from the task's memory regions
+    ///
+    /// The main executable is placed at the region containing `pc` when there
+    /// is one, and other images at the start of the region named after them.
+    /// Only used when dyld's image list can't be read.
+    fn guess_images_from_regions(&mut self, pc: u64) -> Result<()>
     {
-        self.stdout_pipe.take()
-    }
+        let regions = get_memory_regions(self.task)?;
 
-    fn take_process_stderr(&mut self) -> Option<File>
-    {
-        self.stderr_pipe.take()
-    }
+        // First, try to load the main executable explicitly
+        // We need to find the __TEXT segment's load address, not just any region
+        let exec_path = Self::get_executable_path(self.pid);
+        if let Some(exec_path) = &exec_path {
+            use tracing::info;
+            info!("Attempting to load executable: {}", exec_path.display());
 
-    fn take_event_receiver(&mut self) -> Option<events::DebuggerEventReceiver>
-    {
-        self.event_rx.take()
-    }
+            // First, parse the binary to get the __TEXT segment's expected virtual address
+            let text_vmaddr = Self::get_text_segment_vmaddr(exec_path);
 
-    fn add_breakpoint(&mut self, request: BreakpointRequest) -> Result<BreakpointId>
-    {
-        let breakpoints = self.breakpoints.clone();
-        breakpoints::BreakpointManager::add_breakpoint(self, &breakpoints, request)
-    }
+            let pc_addr = pc;
+            info!("PC address: 0x{:x}", pc_addr);
 
-    fn remove_breakpoint(&mut self, id: BreakpointId) -> Result<()>
-    {
-        let breakpoints = self.breakpoints.clone();
-        breakpoints::BreakpointManager::remove_breakpoint(self, &breakpoints, id)
-    }
+            if let Some(vmaddr) = text_vmaddr {
+                info!("Executable __TEXT segment vmaddr: 0x{:x}", vmaddr);
+            }
 
-    fn enable_breakpoint(&mut self, id: BreakpointId) -> Result<()>
+            // Find the region containing the PC (this should be the __TEXT segment)
+            if let Some(pc_region) = regions.iter().find(|r| r.start.value() <= pc_addr && pc_addr < r.end.value()) {
+                info!(
+                    "PC region: 0x{:x} - 0x{:x}, perms: {}",
+                    pc_region.start.value(),
+                    pc_region.end.value(),
+                    pc_region.permissions
+                );
+
+                // Calculate the load address
+                // The BinaryImage expects load_address to be where __TEXT is loaded
+                // If we know the __TEXT vmaddr, we can calculate the slide
+                let load_address = if let Some(vmaddr) = text_vmaddr {
+                    // The slide is: runtime_load_address - file_vmaddr
+                    // We need to find where __TEXT is actually loaded
+                    // The PC region should contain the __TEXT segment
+                    // Calculate slide: if PC is at runtime_addr, and file expects vmaddr,
+                    // then the segment starts at: runtime_addr - (pc_offset_in_segment)
+                    // But we don't know the offset, so we'll try the region start
+                    // and let BinaryImage calculate the slide
+                    let calculated_load = pc_region.start.value();
+                    let slide = calculated_load as i64 - vmaddr as i64;
+                    info!(
+                        "Calculated load address: 0x{:x}, slide: 0x{:x} (from vmaddr 0x{:x})",
+                        calculated_load, slide, vmaddr
+                    );
+                    calculated_load
+                } else {
+                    // Fall back to PC region start
+                    info!("No vmaddr found, using PC region start: 0x{:x}", pc_region.start.value());
+                    pc_region.start.value()
+                };
+
+                let desc = ImageDescriptor {
+                    path: exec_path.clone(),
+                    load_address,
+                };
+                match self.symbol_cache.load_image(desc) {
+                    Ok(_) => {
+                        info!(
+                            "Successfully loaded executable: {} at address 0x{:x}",
+                            exec_path.display(),
+                            load_address
+                        );
+                    }
+                    Err(e) => {
+                        use tracing::warn;
+                        warn!(
+                            "Failed to load executable {} at 0x{:x}: {}",
+                            exec_path.display(),
+                            load_address,
+                            e
+                        );
+                        // Try loading at different addresses - maybe the PC region isn't the right one
+                        // Try all executable regions
+                        for region in &regions {
+                            if region.permissions.contains('x') && region.permissions.contains('r') {
+                                let desc = ImageDescriptor {
+                                    path: exec_path.clone(),
+                                    load_address: region.start.value(),
+                                };
+                                match self.symbol_cache.load_image(desc) {
+                                    Ok(_) => {
+                                        info!(
+                                            "Successfully loaded executable: {} at alternative address 0x{:x}",
+                                            exec_path.display(),
+                                            region.start.value()
+                                        );
+                                        break;
+                                    }
+                                    Err(e2) => {
+                                        use tracing::debug;
+                                        debug!("Failed to load at 0x{:x}: {}", region.start.value(), e2);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            } else {
+                use tracing::warn;
+                warn!("Could not find memory region containing PC 0x{:x}", pc_addr);
+                // Try to load executable at any executable region
+                for region in &regions {
+                    if region.permissions.contains('x') && region.permissions.contains('r') {
+                        let desc = ImageDescriptor {
+                            path: exec_path.clone(),
+                            load_address: region.start.value(),
+                        };
+                        match self.symbol_cache.load_image(desc) {
+                            Ok(_) => {
+                                use tracing::info;
+                                info!(
+                                    "Successfully loaded executable: {} at address 0x{:x} (fallback)",
+                                    exec_path.display(),
+                                    region.start.value()
+                                );
+                                break;
+                            }
+                            Err(_) => {
+                                // Continue trying
+                            }
+                        }
+                    }
+                }
+            }
+        } else {
+            use tracing::warn;
+            warn!("Could not get executable path for PID {}", self.pid.0);
+        }
+
+        // Load all other images (shared libraries and named regions) in parallel,
+        // in the background when that's on
+        self.symbol_cache
+            .queue_images(Self::library_descriptors(regions, exec_path.as_deref()));
+
+        Ok(())
+    }
+
+    /// Unwind `thread_id` from `regs`, loading the images symbols are needed from first
+    fn unwind_thread(&mut self, thread_id: ThreadId, regs: &Registers, options: UnwindOptions) -> Result<StackTrace>
+    {
+        // Load images into symbol cache if not already loaded
+        if !self.load_dyld_images() {
+            self.guess_images_from_regions(regs.pc.value())?;
+        }
+
+        // Implement MemoryAccess for MacOSDebugger
+        struct MacOSMemoryAccess<'a>
+        {
+            task: mach_port_t,
+            cache: &'a MemoryCache,
+        }
+
+        impl<'a> MemoryAccess for MacOSMemoryAccess<'a>
+        {
+            fn read_u64(&self, address: Address) -> Result<u64>
+            {
+                self.cache.read_u64(self.task, address)
+            }
+        }
+
+        let memory = MacOSMemoryAccess {
+            task: self.task,
+            cache: &self.memory_cache,
+        };
+
+        let unwinder = StackUnwinder::new(self.architecture, &self.symbol_cache, &memory);
+        unwinder.unwind_with_options(thread_id, regs, options)
+    }
+
+    /// Single-step `port` while the other threads are held.
+    ///
+    /// The other threads get a `thread_suspend()` of their own for the step,
+    /// separate from the ones `suspend_thread()` tracks, and are released
+    /// once it finishes.
+    fn step_port(&mut self, port: thread_act_t) -> Result<StopReason>
+    {
+        let pc = self.read_registers_from_port(port)?.pc;
+
+        let others: Vec<ThreadId> = self
+            .threads
+            .iter()
+            .filter(|&&other| other != port)
+            .map(|&other| threads::ThreadManager::thread_id_for_port(self, other))
+            .collect();
+        let mut held = Vec::with_capacity(others.len());
+        for thread in others {
+            match threads::ThreadManager::suspend_thread(self, thread) {
+                Ok(()) => held.push(thread),
+                Err(err) => tracing::warn!("Failed to hold thread {} for the step: {err}", thread.raw()),
+            }
+        }
+
+        let result = self.step_thread(port, pc);
+
+        for thread in held {
+            if let Err(err) = threads::ThreadManager::resume_thread(self, thread) {
+                tracing::warn!("Failed to release thread {} after the step: {err}", thread.raw());
+            }
+        }
+        result
+    }
+
+    /// Step the thread a breakpoint or watchpoint stopped off the instruction that trapped.
+    ///
+    /// A watchpoint's access traps before it completes, and a software
+    /// breakpoint leaves the thread on its trap, so resuming straight away
+    /// would trap on it again. `step_thread()` runs the original instruction
+    /// under the breakpoint and puts the trap back, so it fires again next
+    /// time round. Returns `true` when the step stopped for something else
+    /// (a breakpoint, a fault, the process exiting), which is then the stop
+    /// to report instead of resuming.
+    fn step_off_trap(&mut self) -> Result<bool>
+    {
+        let port = {
+            let shared = self.exception_state.lock().unwrap();
+            match (shared.stop_reason, shared.pending_thread) {
+                (StopReason::Watchpoint { .. }, Some(port)) => port,
+                (StopReason::Breakpoint(addr), Some(port))
+                    if self
+                        .breakpoints
+                        .lock()
+                        .unwrap()
+                        .patched_code_at(Address::from(addr))
+                        .is_some() =>
+                {
+                    // The thread's PC may have been moved off the trap since
+                    if self.read_registers_from_port(port)?.pc.value() != addr {
+                        return Ok(false);
+                    }
+                    port
+                }
+                _ => return Ok(false),
+            }
+        };
+        Ok(!matches!(self.step_port(port)?, StopReason::Step(_)))
+    }
+
+    /// Single-step `port`, which is stopped at `pc`, while the other threads are held.
+    ///
+    /// An enabled software breakpoint at `pc` has its saved bytes written back
+    /// for the step and its trap re-patched afterwards, unless resuming removed
+    /// it (a spent one-shot breakpoint). A thread stopped by a watchpoint has
+    /// its watchpoints turned off for the step, so the access it's stopped on
+    /// can complete.
+    fn step_thread(&mut self, port: thread_act_t, pc: Address) -> Result<StopReason>
+    {
+        let patched = self.breakpoints.lock().unwrap().patched_code_at(pc);
+        if let Some(original) = &patched {
+            write_memory(self.task, pc, original)?;
+        }
+        let on_watchpoint = {
+            let shared = self.exception_state.lock().unwrap();
+            matches!(shared.stop_reason, StopReason::Watchpoint { .. }) && shared.pending_thread == Some(port)
+        };
+        let disarmed = if on_watchpoint {
+            Some(registers::disarm_watchpoints(port)?)
+        } else {
+            None
+        };
+
+        let result = self.run_single_step(port);
+
+        if let Some(saved) = disarmed
+            && let Err(err) = registers::rearm_watchpoints(port, &saved)
+        {
+            tracing::warn!("Failed to re-arm watchpoints on thread port {port} after stepping: {err}");
+        }
+
+        if patched.is_some() && self.breakpoints.lock().unwrap().patched_code_at(pc).is_some() {
+            let repatched = breakpoints::BreakpointManager::software_trap_bytes(self.architecture)
+                .and_then(|trap| write_memory(self.task, pc, &trap));
+            if let Err(err) = repatched {
+                tracing::warn!("Failed to re-patch breakpoint at {pc} after stepping: {err}");
+            }
+        }
+        self.memory_cache.invalidate_all();
+        result
+    }
+
+    /// Arm single-stepping on `port`, let it run and wait for the exception loop to report the step.
+    ///
+    /// When the target stopped in the exception loop the step continues the
+    /// pending exception. Otherwise the task is resumed with `task_resume()`,
+    /// suspended again once the step traps, and the step's exception released
+    /// without reporting a resume, so the target is left suspended as before.
+    fn run_single_step(&mut self, port: thread_act_t) -> Result<StopReason>
+    {
+        registers::set_single_step(port, true)?;
+        self.exception_state.lock().unwrap().stepping = Some(port);
+
+        let resumed_task = match self.try_resume_pending_exception() {
+            Ok(true) => false,
+            Ok(false) => {
+                let result = unsafe { task_resume(self.task) };
+                if result != KERN_SUCCESS {
+                    self.exception_state.lock().unwrap().stepping = None;
+                    let _ = registers::set_single_step(port, false);
+                    let err = DebuggerError::ResumeFailed(format!("task_resume failed: {}", result));
+                    return Err(self.release_if_gone(err));
+                }
+                true
+            }
+            Err(err) => {
+                self.exception_state.lock().unwrap().stepping = None;
+                let _ = registers::set_single_step(port, false);
+                return Err(err);
+            }
+        };
+
+        let deadline = Instant::now() + STEP_TIMEOUT;
+        let mut stepped = false;
+        while Instant::now() < deadline {
+            {
+                let shared = self.exception_state.lock().unwrap();
+                if shared.stepping.is_none() && shared.pending_thread.is_some() {
+                    stepped = true;
+                    break;
+                }
+            }
+            thread::sleep(Duration::from_micros(200));
+        }
+
+        if !stepped || resumed_task {
+            let result = unsafe { task_suspend(self.task) };
+            if result != KERN_SUCCESS {
+                let err = DebuggerError::SuspendFailed(format!("task_suspend failed: {}", result));
+                return Err(self.release_if_gone(err));
+            }
+        }
+
+        let reason = {
+            let mut shared = self.exception_state.lock().unwrap();
+            // The step may have trapped between the deadline and the suspend
+            stepped |= shared.stepping.take().is_none() && shared.pending_thread.is_some();
+            if !stepped {
+                shared.stopped = true;
+                shared.stop_reason = StopReason::Suspended;
+            }
+            shared.stop_reason
+        };
+
+        if let Err(err) = registers::set_single_step(port, false) {
+            tracing::warn!("Failed to clear single-step on thread port {port}: {err}");
+        }
+        if stepped
+            && resumed_task
+            && let Some(sender) = &self.exception_resume_tx
+        {
+            let _ = sender.send(exception::ExceptionLoopCommand::Release);
+        }
+        if !stepped {
+            tracing::debug!("Single step on process {} timed out; task suspended", self.pid.0);
+            self.publish_stop_event(StopReason::Suspended, None);
+        }
+        Ok(reason)
+    }
+
+    /// Resume the target and wait for the exception loop to report its next stop.
+    ///
+    /// A target that doesn't stop within `timeout` is suspended, and the
+    /// suspension is the stop returned.
+    fn run_until_stop(&mut self, timeout: Duration) -> Result<StopReason>
+    {
+        // Stepping off a trap is a stop of its own, so count from after it
+        if self.step_off_trap()? {
+            return Ok(self.stop_reason());
+        }
+        let seen = self.exception_state.lock().unwrap().stops;
+        self.resume()?;
+
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            {
+                let shared = self.exception_state.lock().unwrap();
+                if shared.stops != seen && shared.stopped {
+                    return Ok(shared.stop_reason);
+                }
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        self.suspend()?;
+        Ok(self.stop_reason())
+    }
+
+    /// Run the target until `port` reaches `return_address`, where a function it is in returns to.
+    ///
+    /// A hidden software breakpoint is set at the return address for the
+    /// run, and the exception loop reports the thread's hit there as a
+    /// `Step`. A breakpoint the user already has there does the job instead;
+    /// if it's disabled, it is enabled just for the run. Either way the
+    /// address is back as it was before this returns.
+    fn run_to_return(&mut self, port: thread_act_t, return_address: Address) -> Result<StopReason>
+    {
+        let existing = self
+            .breakpoints
+            .lock()
+            .unwrap()
+            .id_for_kind(return_address, BreakpointKind::Software);
+        let (temporary, enabled_for_run) = match existing {
+            Some(id) if self.breakpoint_info(id)?.enabled => (None, None),
+            Some(id) => {
+                self.enable_breakpoint(id)?;
+                (None, Some(id))
+            }
+            None => {
+                let request = Breakpoint::software(return_address).hidden().build();
+                (Some(self.add_breakpoint(request)?), None)
+            }
+        };
+        if existing.is_none() || enabled_for_run.is_some() {
+            self.exception_state.lock().unwrap().step_return = Some((port, return_address.value()));
+        }
+
+        let result = self.run_until_stop(STEP_OVER_TIMEOUT);
+
+        self.exception_state.lock().unwrap().step_return = None;
+        if let Some(id) = temporary
+            && let Err(err) = self.remove_breakpoint(id)
+        {
+            tracing::warn!("Failed to remove the temporary breakpoint at {return_address}: {err}");
+        }
+        if let Some(id) = enabled_for_run
+            && let Err(err) = self.disable_breakpoint(id)
+        {
+            tracing::warn!("Failed to disable breakpoint {} again after stepping: {err}", id.raw());
+        }
+        result
+    }
+
+    /// Start the Mach exception handler thread.
+    ///
+    /// This creates a Mach receive port, registers it with `task_set_exception_ports()`,
+    /// and spawns a background thread to handle exceptions (breakpoints, signals, etc.).
+    ///
+    /// ## Mach APIs Used
+    ///
+    /// - **mach_port_allocate()**: Creates a Mach receive port
+    /// - **mach_port_insert_right()**: Makes the port sendable
+    /// - **task_set_exception_ports()**: Registers the exception port with the task
+    ///
+    /// ## Exception Mask
+    ///
+    /// The handler receives:
+    /// - `EXC_MASK_BREAKPOINT`: Software breakpoints (INT3/BRK)
+    /// - `EXC_MASK_BAD_ACCESS`: Memory access violations
+    /// - `EXC_MASK_BAD_INSTRUCTION`: Illegal instructions
+    /// - `EXC_MASK_ARITHMETIC`: Arithmetic exceptions (div by zero, overflow)
+    /// - `EXC_MASK_SOFTWARE`: Software-generated exceptions
+    ///
+    /// ## Thread Safety
+    ///
+    /// The exception handler runs in a separate thread and communicates via channels.
+    /// See `exception::run_exception_loop()` for the handler implementation.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `MachError` if port allocation or exception port registration fails.
+    /// Returns `AttachFailed` if the handler thread cannot be spawned.
+    ///
+    /// ## See Also
+    ///
+    /// - `stop_exception_handler()`: Stops the exception handler
+    /// - `exception::run_exception_loop()`: The exception handling loop
+    /// - [task_set_exception_ports(3) man page](https://developer.apple.com/documentation/kernel/1402149-task_set_exception_ports/)
+    /// - [mach_port_allocate(3) man page](https://developer.apple.com/documentation/kernel/1402149-mach_port_allocate/)
+    fn start_exception_handler(&mut self) -> Result<()>
+    {
+        #[cfg(target_os = "macos")]
+        {
+            use tracing::info;
+
+            self.stop_exception_handler();
+
+            if self.task == MACH_PORT_NULL {
+                return Ok(());
+            }
+
+            unsafe {
+                let self_task = mach_task_self();
+                let mut port: mach_port_t = MACH_PORT_NULL;
+                let mut kr = mach_port_allocate(self_task, MACH_PORT_RIGHT_RECEIVE, &mut port);
+                if kr != KERN_SUCCESS {
+                    return Err(DebuggerError::MachError(kr.into()));
+                }
+
+                kr = mach_port_insert_right(self_task, port, port, MACH_MSG_TYPE_MAKE_SEND);
+                if kr != KERN_SUCCESS {
+                    let _ = mach_port_destroy(self_task, port);
+                    return Err(DebuggerError::MachError(kr.into()));
+                }
+
+                let mask: exception_mask_t = EXC_MASK_BREAKPOINT
+                    | EXC_MASK_BAD_ACCESS
+                    | EXC_MASK_BAD_INSTRUCTION
+                    | EXC_MASK_ARITHMETIC
+                    | EXC_MASK_SOFTWARE;
+                let behavior: exception_behavior_t = (EXCEPTION_DEFAULT | MACH_EXCEPTION_CODES) as exception_behavior_t;
+                let flavor = exception::thread_state_flavor_for_arch(self.architecture);
+                kr = task_set_exception_ports(self.task, mask, port, behavior, flavor);
+                if kr != KERN_SUCCESS {
+                    let _ = mach_port_destroy(self_task, port);
+                    return Err(macos_permissions::advise(
+                        DebuggerError::MachError(kr.into()),
+                        PrivilegedOperation::ExceptionPorts,
+                    ));
+                }
+
+                let (tx, rx) = mpsc::channel();
+                let shared_state = Arc::clone(&self.exception_state);
+                let breakpoints = self.breakpoints.clone();
+                let architecture = self.architecture;
+                let event_tx = self.event_tx.clone();
+                let signal_policies = Arc::clone(&self.signal_policies);
+                let cache_generation = self.memory_cache.generation_counter();
+                info!("Spawning Mach exception handler thread");
+                let handle = thread::Builder::new()
+                    .name("ferros-mac-exc".to_string())
+                    .spawn(move || {
+                        exception::run_exception_loop(
+                            port,
+                            rx,
+                            shared_state,
+                            architecture,
+                            event_tx,
+                            breakpoints,
+                            signal_policies,
+                            cache_generation,
+                        )
+                    })
+                    .map_err(|e| {
+                        let _ = mach_port_destroy(self_task, port);
+                        DebuggerError::AttachFailed(format!("Failed to spawn exception handler: {e}"))
+                    })?;
+
+                self.exception_port = port;
+                self.exception_thread = Some(handle);
+                self.exception_resume_tx = Some(tx);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stop_exception_handler(&mut self)
+    {
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(tx) = self.exception_resume_tx.take() {
+                let _ = tx.send(exception::ExceptionLoopCommand::Shutdown);
+            }
+
+            if self.exception_port != MACH_PORT_NULL {
+                unsafe {
+                    let _ = mach_port_destroy(mach_task_self(), self.exception_port);
+                }
+                self.exception_port = MACH_PORT_NULL;
+            }
+
+            if let Some(handle) = self.exception_thread.take() {
+                let _ = handle.join();
+            }
+
+            self.exception_state.lock().unwrap().clear_stop();
+        }
+    }
+
+    /// Watch for the target's exit, see [`exit`]
+    ///
+    /// On exit the target is marked gone and the exit is reported as a
+    /// [`StopReason::Exited`] stop, both through
+    /// [`stop_reason`](Debugger::stop_reason) and as a `TargetStopped` event.
+    /// Failing to watch only loses that report, so it is logged rather than
+    /// failing the attach.
+    fn start_exit_monitor(&mut self, child: bool)
+    {
+        let pid = self.pid.0;
+        let gone_pid = Arc::clone(&self.gone_pid);
+        let shared_state = Arc::clone(&self.exception_state);
+        let event_tx = self.event_tx.clone();
+        let on_exit = move |code: i32| {
+            tracing::info!("Process {pid} exited with code {code}");
+            gone_pid.store(pid, Ordering::Relaxed);
+            {
+                let mut shared = shared_state.lock().unwrap();
+                shared.stopped = true;
+                shared.stop_reason = StopReason::Exited(code);
+                shared.pending_thread = None;
+                shared.stops += 1;
+            }
+            if let Err(err) = event_tx.send(EventEnvelope::new(DebuggerEvent::TargetStopped {
+                reason: StopReason::Exited(code),
+                thread: None,
+            })) {
+                tracing::warn!("Failed to dispatch exit event: {err}");
+            }
+        };
+        match exit::ExitMonitor::spawn(self.pid, child, on_exit) {
+            Ok(monitor) => self.exit_monitor = Some(monitor),
+            Err(err) => tracing::warn!("Not watching process {pid} for its exit: {err}"),
+        }
+    }
+
+    fn stop_exit_monitor(&mut self)
+    {
+        if let Some(monitor) = self.exit_monitor.take() {
+            monitor.stop();
+        }
+    }
+}
+
+impl TargetInspector for MacOSDebugger
+{
+    fn breakpoint_info(&self, id: BreakpointId) -> Result<BreakpointInfo>
+    {
+        breakpoints::BreakpointManager::breakpoint_info(&self.breakpoints, id)
+    }
+
+    fn breakpoints(&self) -> Vec<BreakpointInfo>
+    {
+        breakpoints::BreakpointManager::breakpoints(&self.breakpoints)
+    }
+
+    fn breakpoint_counts(&self) -> BreakpointCounts
+    {
+        breakpoints::BreakpointManager::breakpoint_counts(&self.breakpoints)
+    }
+
+    fn debug_level(&self, address: Address) -> Option<DebugLevel>
+    {
+        self.symbol_cache.debug_level_for(address)
+    }
+
+    fn function_start(&self, address: Address) -> Option<Address>
+    {
+        self.symbol_cache.function_start(address)
+    }
+
+    fn function_name(&self, address: Address) -> Option<SymbolName>
+    {
+        let symbolication = self.symbol_cache.symbolicate_function(address)?;
+        symbolication.frames.into_iter().next().map(|frame| frame.symbol)
+    }
+
+    fn source_location(&self, address: Address) -> Option<SourceLocation>
+    {
+        let symbolication = self.symbol_cache.symbolicate(address)?;
+        symbolication.frames.into_iter().next_back().and_then(|frame| frame.location)
+    }
+
+    fn describe_type(&self, name: &str) -> Option<Arc<TypeSummary>>
+    {
+        self.symbol_cache.describe_type(name).ok().flatten()
+    }
+
+    fn capabilities(&self) -> Capabilities
+    {
+        Capabilities::ALL
+    }
+
+    fn read_registers_for(&self, thread: ThreadId) -> Result<Registers>
+    {
+        let port = self.thread_port_for_id(thread)?;
+        self.read_registers_from_port(port).map_err(|err| self.check_target(err))
+    }
+
+    /// Read registers from the attached process
+    ///
+    /// Delegates to platform-specific register reading functions based on
+    /// the CPU architecture (ARM64 vs x86-64).
+    ///
+    /// ## Architecture Detection
+    ///
+    /// We use `#[cfg(target_arch = "...")]` to compile different code
+    /// for different architectures:
+    ///
+    /// - `aarch64`: Apple Silicon (M1, M2, M3, M4, etc.)
+    /// - `x86_64`: Intel Macs
+    ///
+    /// This is done at compile time, so there's no runtime overhead.
+    fn read_registers(&self) -> Result<Registers>
+    {
+        self.ensure_attached()?;
+        let thread = self.active_thread_port()?;
+        self.read_registers_from_port(thread).map_err(|err| self.check_target(err))
+    }
+
+    /// Read memory from the target process
+    ///
+    /// Uses `vm_read()` to read memory from the Mach task.
+    fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self.ensure_attached()?;
+        self.memory_cache
+            .read(self.task, addr, len)
+            .map_err(|err| self.check_target(err))
+    }
+
+    fn read_memory_live(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self.ensure_attached()?;
+        crate::platform::macos::memory::read_memory(self.task, addr, len).map_err(|err| self.check_target(err))
+    }
+
+    fn read_memory_logical(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        let mut bytes = self.read_memory(addr, len)?;
+        self.breakpoints.lock().unwrap().logical_view(addr, &mut bytes);
+        Ok(bytes)
+    }
+
+    /// Stream memory straight into one reused buffer with `mach_vm_read_overwrite`
+    ///
+    /// Bypasses the page cache so a multi-gigabyte dump doesn't evict the
+    /// pages the views are using.
+    fn read_memory_streamed(
+        &self,
+        range: std::ops::Range<Address>,
+        chunk_size: usize,
+        sink: &mut dyn std::io::Write,
+        options: &mut crate::stream::StreamOptions<'_>,
+    ) -> Result<crate::stream::ReadStats>
+    {
+        self.ensure_attached()?;
+        let logical = options.logical;
+        crate::stream::stream_memory(range, chunk_size, sink, options, |address, buffer| {
+            let read = crate::platform::macos::memory::read_memory_into(self.task, address, buffer)?;
+            if logical {
+                self.breakpoints.lock().unwrap().logical_view(address, &mut buffer[..read]);
+            }
+            Ok(read)
+        })
+    }
+
+    /// Get memory regions for the attached process
+    ///
+    /// Uses `vm_region()` to enumerate memory regions in the Mach task.
+    fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+    {
+        self.ensure_attached()?;
+        get_memory_regions(self.task).map_err(|err| self.check_target(err))
+    }
+
+    fn memory_cache_stats(&self) -> Option<MemoryCacheStats>
+    {
+        Some(self.memory_cache.stats())
+    }
+
+    fn architecture(&self) -> Architecture
+    {
+        self.architecture
+    }
+
+    fn is_attached(&self) -> bool
+    {
+        self.attached && self.gone_pid.load(Ordering::Relaxed) == 0
+    }
+
+    fn is_stopped(&self) -> bool
+    {
+        self.exception_state.lock().unwrap().stopped
+    }
+
+    fn stop_reason(&self) -> StopReason
+    {
+        self.exception_state.lock().unwrap().stop_reason
+    }
+
+    /// List all threads in the target process
+    ///
+    /// Returns the cached thread list as `ThreadId` values. The list is maintained
+    /// internally and updated when `refresh_threads()` is called.
+    ///
+    /// ## Thread List Caching
+    ///
+    /// The thread list is cached for performance. It's updated:
+    /// - When `attach()` is called (initial enumeration)
+    /// - When `refresh_threads()` is called (manual refresh)
+    ///
+    /// If threads are created or destroyed, call `refresh_threads()` to update the list.
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `TargetGone`: The process died behind the debugger's back
+    fn threads(&self) -> Result<Vec<ThreadId>>
+    {
+        self.ensure_attached()?;
+        // The cached list would otherwise outlive the process
+        if !self.is_target_alive() {
+            return Err(self.mark_gone());
+        }
+        Ok(self.thread_ids.ids())
+    }
+
+    /// Get the currently active thread
+    ///
+    /// Returns `Some(thread_id)` if an active thread has been selected, or `None`
+    /// if no thread is active. The active thread is used for register operations.
+    ///
+    /// ## Default Behavior
+    ///
+    /// When attaching to a process, the first thread (typically the main thread)
+    /// is automatically selected as the active thread.
+    fn active_thread(&self) -> Option<ThreadId>
+    {
+        self.current_thread
+            .map(|port| threads::ThreadManager::thread_id_for_port(self, port))
+    }
+
+    fn thread_suspend_count(&self, thread: ThreadId) -> u32
+    {
+        self.thread_suspensions.get(&thread).copied().unwrap_or(0)
+    }
+
+    /// Name, run state, suspend count and CPU use of `thread`
+    ///
+    /// ## Errors
+    ///
+    /// - `NotAttached`: Not attached to a process
+    /// - `InvalidArgument`: The thread isn't in the current thread list
+    fn thread_info(&self, thread: ThreadId) -> Result<ThreadInfo>
+    {
+        self.ensure_attached()?;
+        threads::ThreadManager::thread_info(self, thread)
+    }
+
+    /// The thread whose pthread is libpthread's own
+    ///
+    /// libpthread allocates the main thread's `struct _pthread` statically in
+    /// its data segment, where every other thread's lives at the top of its
+    /// stack allocation. So the thread whose `thread_handle` falls inside
+    /// `libsystem_pthread` is the main thread. That image is only known once
+    /// symbols for it have loaded; until then this is the first thread
+    /// `task_threads()` listed, which the kernel keeps in creation order.
+    fn main_thread(&self) -> Option<ThreadId>
+    {
+        if self.ensure_attached().is_err() {
+            return None;
+        }
+        let is_main = |port: thread_act_t| {
+            threads::ThreadManager::thread_handle(port)
+                .and_then(|handle| self.symbol_cache.image_for_address(Address::from(handle)))
+                .is_some_and(|image| {
+                    image
+                        .path()
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with("libsystem_pthread"))
+                })
+        };
+        let port = self
+            .threads
+            .iter()
+            .copied()
+            .find(|&port| is_main(port))
+            .or_else(|| self.threads.first().copied())?;
+        Some(threads::ThreadManager::thread_id_for_port(self, port))
+    }
+}
+
+impl Debugger for MacOSDebugger
+{
+    fn set_capture_process_output(&mut self, capture: bool)
+    {
+        self.capture_output = capture;
+        if !capture {
+            self.stop_output_pumps(output::DEFAULT_DRAIN_DEADLINE);
+            self.stdout_pipe = None;
+            self.stderr_pipe = None;
+            self.stdin_pipe = None;
+        }
+    }
+
+    fn set_source_map(&mut self, source_map: SourceMap)
+    {
+        self.symbol_cache.set_source_map(source_map);
+    }
+
+    fn source_map(&self) -> SourceMap
+    {
+        self.symbol_cache.source_map()
+    }
+
+    fn add_dsym_search_path(&mut self, path: std::path::PathBuf) -> Result<usize>
+    {
+        if !path.is_dir() {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "{} is not a dSYM bundle or a directory",
+                path.display()
+            )));
+        }
+        Ok(self.symbol_cache.add_dsym_search_path(path))
+    }
+
+    fn set_background_symbol_loading(&mut self, enabled: bool) -> Result<()>
+    {
+        if !enabled {
+            self.symbol_cache.stop_background_loading();
+            return Ok(());
+        }
+        if self.symbol_cache.is_loading_in_background() {
+            return Ok(());
+        }
+        let event_tx = self.event_tx.clone();
+        self.symbol_cache.start_background_loading(move |images| {
+            if let Err(err) = event_tx.send(EventEnvelope::new(DebuggerEvent::SymbolsLoaded { images })) {
+                tracing::warn!("Failed to dispatch symbols event: {err}");
+            }
+        })
+    }
+
+    fn pending_symbol_loads(&self) -> usize
+    {
+        self.symbol_cache.pending_loads()
+    }
+
+    fn take_process_stdout(&mut self) -> Option<File>
+    {
+        self.stdout_pipe.take()
+    }
+
+    fn take_process_stderr(&mut self) -> Option<File>
+    {
+        self.stderr_pipe.take()
+    }
+
+    fn take_process_stdin(&mut self) -> Option<File>
+    {
+        self.stdin_pipe.take()
+    }
+
+    fn pump_process_output(&mut self, stdout: Box<dyn OutputSink>, stderr: Box<dyn OutputSink>) -> Result<()>
+    {
+        if let Some(pipe) = self.stdout_pipe.take() {
+            self.output_pumps.push(OutputPump::spawn("stdout", pipe, stdout)?);
+        }
+        if let Some(pipe) = self.stderr_pipe.take() {
+            self.output_pumps.push(OutputPump::spawn("stderr", pipe, stderr)?);
+        }
+        Ok(())
+    }
+
+    fn stop_output_pumps(&mut self, drain: Duration)
+    {
+        for pump in self.output_pumps.drain(..) {
+            pump.shutdown(drain);
+        }
+    }
+
+    fn take_event_receiver(&mut self) -> Option<events::DebuggerEventReceiver>
+    {
+        self.event_rx.take()
+    }
+
+    fn add_breakpoint(&mut self, mut request: BreakpointRequest) -> Result<BreakpointId>
+    {
+        if let BreakpointRequestKind::Location(location) = &request.kind {
+            let address = self
+                .resolve_location(location)?
+                .first()
+                .copied()
+                .ok_or_else(|| self.symbol_cache.unresolved_location_error(location))?;
+            request.kind = BreakpointRequestKind::Software { address };
+        }
+        if !matches!(request.kind, BreakpointRequestKind::Software { .. }) {
+            self.refresh_threads_for_debug_registers();
+        }
+        let breakpoints = self.breakpoints.clone();
+        breakpoints::BreakpointManager::add_breakpoint(self, &breakpoints, request)
+    }
+
+    fn remove_breakpoint(&mut self, id: BreakpointId) -> Result<()>
+    {
+        let breakpoints = self.breakpoints.clone();
+        breakpoints::BreakpointManager::remove_breakpoint(self, &breakpoints, id)
+    }
+
+    fn enable_breakpoint(&mut self, id: BreakpointId) -> Result<()>
     {
+        self.refresh_threads_for_debug_registers();
         let breakpoints = self.breakpoints.clone();
         breakpoints::BreakpointManager::enable_breakpoint(self, &breakpoints, id)
     }
@@ -783,24 +1913,46 @@ impl Debugger for MacOSDebugger
 
     fn toggle_breakpoint(&mut self, id: BreakpointId) -> Result<bool>
     {
+        self.refresh_threads_for_debug_registers();
         let breakpoints = self.breakpoints.clone();
         breakpoints::BreakpointManager::toggle_breakpoint(self, &breakpoints, id)
     }
 
-    fn breakpoint_info(&self, id: BreakpointId) -> Result<BreakpointInfo>
+    fn set_breakpoint_ignore_count(&mut self, id: BreakpointId, count: u64) -> Result<()>
     {
-        breakpoints::BreakpointManager::breakpoint_info(&self.breakpoints, id)
+        self.breakpoints.lock().unwrap().set_ignore_count(id, count)
     }
 
-    fn breakpoints(&self) -> Vec<BreakpointInfo>
+    fn resolve_location(&mut self, location: &BreakpointLocation) -> Result<Vec<Address>>
     {
-        breakpoints::BreakpointManager::breakpoints(&self.breakpoints)
+        if let BreakpointLocation::Address(address) = location {
+            return Ok(vec![*address]);
+        }
+        self.ensure_attached()?;
+        self.load_symbol_images()?;
+        self.symbol_cache.resolve_location(location)
     }
 
-    fn read_registers_for(&self, thread: ThreadId) -> Result<Registers>
+    fn code_lines(&mut self, file: &str) -> Result<BTreeSet<u32>>
     {
-        let port = self.thread_port_for_id(thread)?;
-        self.read_registers_from_port(port)
+        self.ensure_attached()?;
+        self.load_symbol_images()?;
+        self.symbol_cache.code_lines(file)
+    }
+
+    fn find_function(&mut self, name: &str) -> Result<Vec<(Address, SymbolName)>>
+    {
+        self.ensure_attached()?;
+        self.load_symbol_images()?;
+        self.symbol_cache.find_function(name)
+    }
+
+    fn modules(&self) -> Result<Vec<ImageInfo>>
+    {
+        self.ensure_attached()?;
+        // Picks up libraries loaded or unloaded since the last stop
+        self.load_dyld_images();
+        Ok(self.symbol_cache.loaded_images())
     }
 
     fn write_registers_for(&mut self, thread: ThreadId, regs: &Registers) -> Result<()>
@@ -809,186 +1961,81 @@ impl Debugger for MacOSDebugger
         self.write_registers_to_port(port, regs)
     }
 
-    fn stack_trace(&mut self, max_frames: usize) -> Result<Vec<StackFrame>>
+    fn read_tls_slot(&self, thread: ThreadId, key: u64) -> Result<u64>
     {
         self.ensure_attached()?;
-        let thread = self.active_thread_port()?;
-        let thread_id = ThreadId::from(thread as u64);
-        let regs = self.read_registers_from_port(thread)?;
-
-        // Load images into symbol cache if not already loaded
-        let regions = get_memory_regions(self.task)?;
-
-        // First, try to load the main executable explicitly
-        // We need to find the __TEXT segment's load address, not just any region
-        let exec_path = Self::get_executable_path(self.pid);
-        if let Some(exec_path) = &exec_path {
-            use tracing::info;
-            info!("Attempting to load executable: {}", exec_path.display());
-
-            // First, parse the binary to get the __TEXT segment's expected virtual address
-            let text_vmaddr = Self::get_text_segment_vmaddr(exec_path);
-
-            let pc_addr = regs.pc.value();
-            info!("PC address: 0x{:x}", pc_addr);
-
-            if let Some(vmaddr) = text_vmaddr {
-                info!("Executable __TEXT segment vmaddr: 0x{:x}", vmaddr);
-            }
-
-            // Find the region containing the PC (this should be the __TEXT segment)
-            if let Some(pc_region) = regions.iter().find(|r| r.start.value() <= pc_addr && pc_addr < r.end.value()) {
-                info!(
-                    "PC region: 0x{:x} - 0x{:x}, perms: {}",
-                    pc_region.start.value(),
-                    pc_region.end.value(),
-                    pc_region.permissions
-                );
+        let tsd_base = self.tsd_base(thread)?;
+        self.read_u64(tls::tsd_slot(tsd_base, key))
+    }
 
-                // Calculate the load address
-                // The BinaryImage expects load_address to be where __TEXT is loaded
-                // If we know the __TEXT vmaddr, we can calculate the slide
-                let load_address = if let Some(vmaddr) = text_vmaddr {
-                    // The slide is: runtime_load_address - file_vmaddr
-                    // We need to find where __TEXT is actually loaded
-                    // The PC region should contain the __TEXT segment
-                    // Calculate slide: if PC is at runtime_addr, and file expects vmaddr,
-                    // then the segment starts at: runtime_addr - (pc_offset_in_segment)
-                    // But we don't know the offset, so we'll try the region start
-                    // and let BinaryImage calculate the slide
-                    let calculated_load = pc_region.start.value();
-                    let slide = calculated_load as i64 - vmaddr as i64;
-                    info!(
-                        "Calculated load address: 0x{:x}, slide: 0x{:x} (from vmaddr 0x{:x})",
-                        calculated_load, slide, vmaddr
-                    );
-                    calculated_load
-                } else {
-                    // Fall back to PC region start
-                    info!("No vmaddr found, using PC region start: 0x{:x}", pc_region.start.value());
-                    pc_region.start.value()
-                };
+    fn thread_stack_bounds(&self, thread: ThreadId) -> Result<(Address, Address)>
+    {
+        self.ensure_attached()?;
+        let sp = self.read_registers_for(thread)?.sp;
+        let regions = self.get_memory_regions()?;
+        stack_guard::stack_bounds(sp, &regions).ok_or_else(|| {
+            DebuggerError::InvalidArgument(format!("No stack region holds SP {sp} of thread {}", thread.raw()))
+        })
+    }
 
-                let desc = ImageDescriptor {
-                    path: exec_path.clone(),
-                    load_address,
-                };
-                match self.symbol_cache.load_image(desc) {
-                    Ok(_) => {
-                        info!(
-                            "Successfully loaded executable: {} at address 0x{:x}",
-                            exec_path.display(),
-                            load_address
-                        );
-                    }
-                    Err(e) => {
-                        use tracing::warn;
-                        warn!(
-                            "Failed to load executable {} at 0x{:x}: {}",
-                            exec_path.display(),
-                            load_address,
-                            e
-                        );
-                        // Try loading at different addresses - maybe the PC region isn't the right one
-                        // Try all executable regions
-                        for region in &regions {
-                            if region.permissions.contains('x') && region.permissions.contains('r') {
-                                let desc = ImageDescriptor {
-                                    path: exec_path.clone(),
-                                    load_address: region.start.value(),
-                                };
-                                match self.symbol_cache.load_image(desc) {
-                                    Ok(_) => {
-                                        info!(
-                                            "Successfully loaded executable: {} at alternative address 0x{:x}",
-                                            exec_path.display(),
-                                            region.start.value()
-                                        );
-                                        break;
-                                    }
-                                    Err(e2) => {
-                                        use tracing::debug;
-                                        debug!("Failed to load at 0x{:x}: {}", region.start.value(), e2);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            } else {
-                use tracing::warn;
-                warn!("Could not find memory region containing PC 0x{:x}", pc_addr);
-                // Try to load executable at any executable region
-                for region in &regions {
-                    if region.permissions.contains('x') && region.permissions.contains('r') {
-                        let desc = ImageDescriptor {
-                            path: exec_path.clone(),
-                            load_address: region.start.value(),
-                        };
-                        match self.symbol_cache.load_image(desc) {
-                            Ok(_) => {
-                                use tracing::info;
-                                info!(
-                                    "Successfully loaded executable: {} at address 0x{:x} (fallback)",
-                                    exec_path.display(),
-                                    region.start.value()
-                                );
-                                break;
-                            }
-                            Err(_) => {
-                                // Continue trying
-                            }
-                        }
-                    }
-                }
-            }
-        } else {
-            use tracing::warn;
-            warn!("Could not get executable path for PID {}", self.pid.0);
+    fn read_thread_local(&mut self, thread: ThreadId, name: &str) -> Result<Vec<ThreadLocalValue>>
+    {
+        self.ensure_attached()?;
+        self.load_symbol_images()?;
+        let variables = self.symbol_cache.thread_locals(name)?;
+        if variables.is_empty() {
+            return Ok(Vec::new());
         }
 
-        // Load all other images (shared libraries and named regions)
-        for region in regions {
-            if let Some(name) = &region.name
-                && name.starts_with('/')
-                && (name.ends_with(".dylib") || name.ends_with(".so") || !name.contains('['))
-            {
-                // Skip if this is the main executable (we already loaded it above)
-                if let Some(ref exec_path) = exec_path {
-                    if name == exec_path.to_str().unwrap_or("") {
-                        continue;
-                    }
+        let tsd_base = self.tsd_base(thread)?;
+        let mut values = Vec::with_capacity(variables.len());
+        for variable in variables {
+            let address = match variable.location {
+                TlsLocation::Descriptor(descriptor) => {
+                    tls::tlv_address(descriptor, tsd_base, |address| self.read_u64(address))?
                 }
-                let desc = ImageDescriptor {
-                    path: std::path::PathBuf::from(name),
-                    load_address: region.start.value(),
-                };
-                let _ = self.symbol_cache.load_image(desc);
-            }
-        }
-
-        // Implement MemoryAccess for MacOSDebugger
-        struct MacOSMemoryAccess<'a>
-        {
-            task: mach_port_t,
-            cache: &'a MemoryCache,
+                TlsLocation::BlockOffset(_) => {
+                    return Err(DebuggerError::InvalidArgument(format!(
+                        "{} uses ELF thread-local storage, which is not supported on macOS",
+                        variable.name
+                    )));
+                }
+            };
+            let bytes = match address {
+                Some(address) => self.memory_cache.read(self.task, address, variable.read_len())?,
+                None => Vec::new(),
+            };
+            values.push(ThreadLocalValue {
+                variable,
+                thread,
+                address,
+                bytes,
+            });
         }
+        Ok(values)
+    }
 
-        impl<'a> MemoryAccess for MacOSMemoryAccess<'a>
-        {
-            fn read_u64(&self, address: Address) -> Result<u64>
-            {
-                self.cache.read_u64(self.task, address)
-            }
-        }
+    fn stack_trace(&mut self, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        self.stack_trace_with_options(UnwindOptions::new(max_frames))
+            .map(|trace| trace.frames)
+    }
 
-        let memory = MacOSMemoryAccess {
-            task: self.task,
-            cache: &self.memory_cache,
-        };
+    fn stack_trace_for(&mut self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        self.ensure_attached()?;
+        let regs = self.read_registers_for(thread)?;
+        self.unwind_thread(thread, &regs, UnwindOptions::new(max_frames))
+            .map(|trace| trace.frames)
+    }
 
-        let unwinder = StackUnwinder::new(self.architecture, &self.symbol_cache, &memory);
-        unwinder.unwind(thread_id, &regs, max_frames)
+    fn stack_trace_with_options(&mut self, options: UnwindOptions) -> Result<StackTrace>
+    {
+        self.ensure_attached()?;
+        let thread = self.active_thread_port()?;
+        let thread_id = threads::ThreadManager::thread_id_for_port(self, thread);
+        let regs = self.read_registers_from_port(thread)?;
+        self.unwind_thread(thread_id, &regs, options)
     }
 
     /// Launch a new process under debugger control using posix_spawn
@@ -1021,12 +2068,12 @@ impl Debugger for MacOSDebugger
     /// ## Parameters
     ///
     /// - `program`: Path to the executable to launch (must be absolute or in PATH)
-    /// - `args`: Command-line arguments (first argument should typically be the program name)
+    /// - `options`: Arguments (the first typically the program name), environment and working directory
     ///
     /// ## Errors
     ///
-    /// - `InvalidArgument`: Invalid program path or empty arguments
-    /// - `AttachFailed`: Failed to spawn process or attach to it
+    /// - `InvalidArgument`: Invalid program path, empty arguments, or a NUL byte in the environment or working directory
+    /// - `AttachFailed`: Failed to spawn process (including a missing working directory) or attach to it
     /// - `Io`: I/O error (e.g., file not found, permission denied)
     ///
     /// ## Example
@@ -1034,18 +2081,20 @@ impl Debugger for MacOSDebugger
     /// ```rust,no_run
     /// use ferros_core::Debugger;
     /// use ferros_core::platform::macos::MacOSDebugger;
+    /// use ferros_core::types::LaunchOptions;
     ///
     /// let mut debugger = MacOSDebugger::new()?;
-    /// debugger.launch("/usr/bin/echo", &["echo", "Hello, world!"])?;
+    /// let options = LaunchOptions::new(["echo", "Hello, world!"]).env("LANG", "C");
+    /// debugger.launch_with_options("/bin/echo", &options)?;
     /// // Process is now suspended and ready for debugging
     /// # Ok::<(), ferros_core::error::DebuggerError>(())
     /// ```
-    fn launch(&mut self, program: &str, args: &[&str]) -> Result<ProcessId>
+    fn launch_with_options(&mut self, program: &str, options: &LaunchOptions) -> Result<ProcessId>
     {
         use tracing::{debug, info};
 
-        info!("Launching process: {} with args: {:?}", program, args);
-        let pid = launch::LaunchManager::launch(self, program, args)?;
+        info!("Launching process: {} with args: {:?}", program, options.args);
+        let pid = launch::LaunchManager::launch(self, program, options)?;
         debug!("Attaching to spawned process");
         // Attach to the spawned process
         let process_id = ProcessId::from(pid as u32);
@@ -1055,6 +2104,7 @@ impl Debugger for MacOSDebugger
             shared.stopped = true;
             shared.stop_reason = StopReason::Suspended;
         }
+        self.start_exit_monitor(true);
         info!("Successfully launched and attached to process {}", pid);
         Ok(process_id)
     }
@@ -1106,7 +2156,9 @@ impl Debugger for MacOSDebugger
     {
         self.stdout_pipe = None;
         self.stderr_pipe = None;
+        self.stdin_pipe = None;
         self.attach_task(pid)?;
+        self.start_exit_monitor(false);
         // Suspend immediately so the debugger has control.
         self.suspend()?;
         Ok(())
@@ -1145,13 +2197,24 @@ impl Debugger for MacOSDebugger
     {
         use tracing::{debug, info};
 
+        // Pumps outlive the attachment if the target handed its pipes to a child
+        self.stop_output_pumps(output::DEFAULT_DRAIN_DEADLINE);
         if !self.attached {
             debug!("Detach called but not attached, no-op");
             return Ok(());
         }
 
+        self.stop_exit_monitor();
+        // Stopping the handler forgets the threads it held for the stop
+        if self.gone_pid.load(Ordering::Relaxed) == 0 {
+            self.release_held_threads();
+        }
         self.stop_exception_handler();
-        self.restore_all_breakpoints();
+        // A dead task has no memory to restore breakpoints into
+        if self.gone_pid.load(Ordering::Relaxed) == 0 {
+            self.restore_all_breakpoints();
+            self.release_thread_suspensions();
+        }
 
         let pid = self.pid.0;
         info!("Detaching from process {}", pid);
@@ -1173,40 +2236,37 @@ impl Debugger for MacOSDebugger
 
         // Clear all state
         self.task = 0;
-        self.threads.clear();
-        self.current_thread = None;
-        self.pid = ProcessId(0);
-        self.attached = false;
-        {
-            let mut shared = self.exception_state.lock().unwrap();
-            shared.stopped = false;
-            shared.stop_reason = StopReason::Running;
-            shared.pending_thread = None;
-        }
-
-        info!("Successfully detached from process {}", pid);
-        Ok(())
-    }
-
-    /// Read registers from the attached process
-    ///
-    /// Delegates to platform-specific register reading functions based on
-    /// the CPU architecture (ARM64 vs x86-64).
-    ///
-    /// ## Architecture Detection
-    ///
-    /// We use `#[cfg(target_arch = "...")]` to compile different code
-    /// for different architectures:
-    ///
-    /// - `aarch64`: Apple Silicon (M1, M2, M3, M4, etc.)
-    /// - `x86_64`: Intel Macs
+        self.threads.clear();
+        self.thread_ids.clear();
+        self.current_thread = None;
+        self.pid = ProcessId(0);
+        self.attached = false;
+        self.exception_state.lock().unwrap().clear_stop();
+
+        info!("Successfully detached from process {}", pid);
+        Ok(())
+    }
+
+    /// Kill the target with `SIGKILL` and detach
     ///
-    /// This is done at compile time, so there's no runtime overhead.
-    fn read_registers(&self) -> Result<Registers>
+    /// A suspended task can't run its exit path, so the task is resumed after
+    /// the signal is sent.
+    fn kill(&mut self) -> Result<()>
     {
         self.ensure_attached()?;
-        let thread = self.active_thread_port()?;
-        self.read_registers_from_port(thread)
+        let pid = self.pid.0;
+        tracing::info!("Killing process {}", pid);
+
+        // SAFETY: kill(2) only takes plain integers
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        if self.is_stopped()
+            && let Err(err) = self.resume()
+        {
+            tracing::warn!("Failed to resume process {} after SIGKILL: {err}", pid);
+        }
+        self.detach()
     }
 
     fn write_registers(&mut self, regs: &Registers) -> Result<()>
@@ -1216,55 +2276,49 @@ impl Debugger for MacOSDebugger
         self.write_registers_to_port(thread, regs)
     }
 
-    /// Read memory from the target process
-    ///
-    /// Uses `vm_read()` to read memory from the Mach task.
-    fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
-    {
-        self.ensure_attached()?;
-        self.memory_cache.read(self.task, addr, len)
-    }
-
     /// Write memory to the target process
     ///
     /// Uses `vm_write()` to write memory to the Mach task.
+    /// Write memory, keeping software breakpoints in the range armed
+    ///
+    /// The written bytes become the code the overlapped breakpoints restore
+    /// when they're removed or disabled, and their traps are written back
+    /// over them so they keep firing.
     fn write_memory(&mut self, addr: Address, data: &[u8]) -> Result<usize>
     {
         self.ensure_attached()?;
-        let written = write_memory(self.task, addr, data)?;
+        let mut bytes = data.to_vec();
+        if let Ok(trap) = breakpoints::BreakpointManager::software_trap_bytes(self.architecture) {
+            self.breakpoints.lock().unwrap().overlay_traps(addr, &mut bytes, &trap);
+        }
+        let written = write_memory(self.task, addr, &bytes)?;
         if written > 0 {
             self.memory_cache.invalidate_range(addr, written);
+            let patched = self.breakpoints.lock().unwrap().absorb_write(addr, &data[..written]);
+            if !patched.is_empty() {
+                tracing::debug!(
+                    "Write of {written} byte(s) at {addr} updated the saved code of breakpoint(s) {:?}",
+                    patched.iter().map(|id| id.raw()).collect::<Vec<_>>()
+                );
+            }
         }
         Ok(written)
     }
 
-    /// Get memory regions for the attached process
+    /// Search memory through the page cache
     ///
-    /// Uses `vm_region()` to enumerate memory regions in the Mach task.
-    fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+    /// The breakpoint table is locked once for the whole search rather than
+    /// per chunk, to put the original code back over the traps.
+    fn search_memory(&self, pattern: &[u8], scope: MemorySearchScope) -> Result<MemorySearch>
     {
         self.ensure_attached()?;
-        get_memory_regions(self.task)
-    }
-
-    fn architecture(&self) -> Architecture
-    {
-        self.architecture
-    }
-
-    fn is_attached(&self) -> bool
-    {
-        self.attached
-    }
-
-    fn is_stopped(&self) -> bool
-    {
-        self.exception_state.lock().unwrap().stopped
-    }
-
-    fn stop_reason(&self) -> StopReason
-    {
-        self.exception_state.lock().unwrap().stop_reason
+        let regions = get_memory_regions(self.task).map_err(|err| self.check_target(err))?;
+        let breakpoints = self.breakpoints.lock().unwrap();
+        crate::search::search(pattern, &regions, &scope, |address, len| {
+            let mut bytes = self.memory_cache.read(self.task, address, len)?;
+            breakpoints.logical_view(address, &mut bytes);
+            Ok(bytes)
+        })
     }
 
     /// Suspend execution of the target process using Mach APIs
@@ -1294,12 +2348,13 @@ impl Debugger for MacOSDebugger
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
+    /// - `TargetGone`: The process died behind the debugger's back
     /// - `SuspendFailed`: `task_suspend()` failed
     fn suspend(&mut self) -> Result<()>
     {
         use tracing::{debug, info};
 
-        self.ensure_attached()?;
+        self.ensure_live()?;
         if self.is_stopped() {
             debug!("Process {} already suspended", self.pid.0);
             return Ok(());
@@ -1311,7 +2366,8 @@ impl Debugger for MacOSDebugger
         unsafe {
             let result = task_suspend(self.task);
             if result != KERN_SUCCESS {
-                return Err(DebuggerError::SuspendFailed(format!("task_suspend failed: {}", result)));
+                let err = DebuggerError::SuspendFailed(format!("task_suspend failed: {}", result));
+                return Err(self.release_if_gone(err));
             }
         }
 
@@ -1353,12 +2409,20 @@ impl Debugger for MacOSDebugger
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
+    /// - `TargetGone`: The process died behind the debugger's back
     /// - `ResumeFailed`: `task_resume()` failed
     fn resume(&mut self) -> Result<()>
     {
         use tracing::{debug, info};
 
-        self.ensure_attached()?;
+        self.ensure_live()?;
+        // Cached pages go stale as soon as the target runs again
+        self.memory_cache.invalidate_all();
+        if self.step_off_trap()? {
+            info!("Process {} stopped again stepping off a trap", self.pid.0);
+            return Ok(());
+        }
+        self.release_held_threads();
         if self.try_resume_pending_exception()? {
             info!("Continuing from Mach exception for process {}", self.pid.0);
             return Ok(());
@@ -1374,7 +2438,8 @@ impl Debugger for MacOSDebugger
         unsafe {
             let result = task_resume(self.task);
             if result != KERN_SUCCESS {
-                return Err(DebuggerError::ResumeFailed(format!("task_resume failed: {}", result)));
+                let err = DebuggerError::ResumeFailed(format!("task_resume failed: {}", result));
+                return Err(self.release_if_gone(err));
             }
         }
 
@@ -1389,54 +2454,19 @@ impl Debugger for MacOSDebugger
         Ok(())
     }
 
-    /// List all threads in the target process
-    ///
-    /// Returns the cached thread list as `ThreadId` values. The list is maintained
-    /// internally and updated when `refresh_threads()` is called.
-    ///
-    /// ## Thread List Caching
-    ///
-    /// The thread list is cached for performance. It's updated:
-    /// - When `attach()` is called (initial enumeration)
-    /// - When `refresh_threads()` is called (manual refresh)
-    ///
-    /// If threads are created or destroyed, call `refresh_threads()` to update the list.
-    ///
-    /// ## Errors
-    ///
-    /// - `NotAttached`: Not attached to a process
-    fn threads(&self) -> Result<Vec<ThreadId>>
-    {
-        self.ensure_attached()?;
-        Ok(self.threads.iter().copied().map(|t| ThreadId::from(t as u64)).collect())
-    }
-
-    /// Get the currently active thread
-    ///
-    /// Returns `Some(thread_id)` if an active thread has been selected, or `None`
-    /// if no thread is active. The active thread is used for register operations.
-    ///
-    /// ## Default Behavior
-    ///
-    /// When attaching to a process, the first thread (typically the main thread)
-    /// is automatically selected as the active thread.
-    fn active_thread(&self) -> Option<ThreadId>
-    {
-        self.current_thread.map(|t| ThreadId::from(t as u64))
-    }
-
     /// Set the active thread for register operations
     ///
-    /// Sets the active thread by converting the `ThreadId` to a Mach thread port
-    /// and calling `set_active_thread_by_port()`.
+    /// Sets the active thread by looking up the `ThreadId`'s current Mach thread
+    /// port and calling `set_active_thread_by_port()`.
     ///
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
-    /// - `InvalidArgument`: The thread ID is not valid (not in the thread list)
+    /// - `ThreadNotFound`: The thread ID is not valid (not in the thread list)
     fn set_active_thread(&mut self, thread: ThreadId) -> Result<()>
     {
-        self.set_active_thread_by_port(thread.raw() as thread_act_t)
+        let port = self.thread_port_for_id(thread)?;
+        self.set_active_thread_by_port(port)
     }
 
     /// Refresh the thread list from the operating system
@@ -1450,190 +2480,172 @@ impl Debugger for MacOSDebugger
     /// - `AttachFailed`: `task_threads()` failed
     fn refresh_threads(&mut self) -> Result<()>
     {
-        self.refresh_thread_list()
+        self.ensure_live()?;
+        if let Err(err) = self.refresh_thread_list() {
+            return Err(self.release_if_gone(err));
+        }
+        // Forget suspensions on threads that have exited
+        let live = &self.thread_ids;
+        self.thread_suspensions.retain(|thread, _| live.contains(*thread));
+        Ok(())
     }
-}
 
-impl Drop for MacOSDebugger
-{
-    fn drop(&mut self)
+    fn suspend_thread(&mut self, thread: ThreadId) -> Result<()>
     {
-        self.restore_all_breakpoints();
-        self.stop_exception_handler();
+        self.ensure_attached()?;
+        threads::ThreadManager::suspend_thread(self, thread)?;
+        *self.thread_suspensions.entry(thread).or_insert(0) += 1;
+        Ok(())
     }
-}
 
-impl MacOSDebugger
-{
-    /// Suspend a specific thread
-    ///
-    /// This function suspends execution of a single thread within the target task.
-    /// Unlike `suspend()`, which suspends all threads, this allows fine-grained control
-    /// over individual threads.
-    ///
-    /// ## Mach API: thread_suspend()
-    ///
-    /// ```c
-    /// kern_return_t thread_suspend(thread_act_t target_act);
-    /// ```
-    ///
-    /// **Parameters**:
-    /// - `target_act`: Thread port (from `task_threads()`) to suspend
-    ///
-    /// **Returns**: `KERN_SUCCESS` (0) on success, error code otherwise.
-    ///
-    /// See: [thread_suspend documentation](https://developer.apple.com/documentation/kernel/1418833-thread_suspend/)
-    ///
-    /// ## Architecture Notes
-    ///
-    /// - **ARM64**: `thread_suspend()` is the preferred method for per-thread control
-    /// - **Intel**: You may need to use `thread_set_state()` with `X86_THREAD_STATE64` flavor
-    ///   to coordinate per-thread operations (not yet implemented)
-    ///
-    /// ## Errors
-    ///
-    /// - `NotAttached`: Not attached to a process
-    /// - `InvalidArgument`: Thread ID is not valid (not in the thread list)
-    /// - `SuspendFailed`: `thread_suspend()` failed
-    ///
-    /// ## Example
-    ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
-    /// use ferros_core::platform::macos::MacOSDebugger;
-    /// use ferros_core::types::ThreadId;
-    ///
-    /// # let mut debugger = MacOSDebugger::new()?;
-    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// let threads = debugger.threads()?;
-    /// if let Some(thread) = threads.first() {
-    ///     debugger.suspend_thread(*thread)?;
-    /// }
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    pub fn suspend_thread(&mut self, thread_id: ThreadId) -> Result<()>
+    fn resume_thread(&mut self, thread: ThreadId) -> Result<()>
     {
         self.ensure_attached()?;
-        threads::ThreadManager::suspend_thread(self, thread_id)
+        threads::ThreadManager::resume_thread(self, thread)?;
+        if let Some(count) = self.thread_suspensions.get_mut(&thread) {
+            *count -= 1;
+            if *count == 0 {
+                self.thread_suspensions.remove(&thread);
+            }
+        }
+        Ok(())
     }
 
-    /// Resume a specific thread
-    ///
-    /// This function resumes execution of a single thread within the target task.
-    /// The thread will continue from where it was suspended.
-    ///
-    /// ## Mach API: thread_resume()
-    ///
-    /// ```c
-    /// kern_return_t thread_resume(thread_act_t target_act);
-    /// ```
-    ///
-    /// **Parameters**:
-    /// - `target_act`: Thread port (from `task_threads()`) to resume
-    ///
-    /// **Returns**: `KERN_SUCCESS` (0) on success, error code otherwise.
+    /// Set how the exception handler treats `signal`
     ///
-    /// See: [thread_resume documentation](https://developer.apple.com/documentation/kernel/1418926-thread_resume/)
+    /// Takes effect from the next exception; works before an attach too.
+    fn set_signal_policy(&mut self, signal: i32, policy: SignalPolicy) -> Result<()>
+    {
+        self.signal_policies.lock().unwrap().set(signal, policy)
+    }
+
+    fn signal_policies(&self) -> SignalPolicies
+    {
+        self.signal_policies.lock().unwrap().clone()
+    }
+
+    /// Set which threads the exception handler stops
     ///
-    /// ## Architecture Notes
+    /// Takes effect from the next stop; works before an attach too. Threads
+    /// held for a stop already reported are still released by `resume()`.
+    fn set_stop_policy(&mut self, policy: StopPolicy) -> Result<()>
+    {
+        self.exception_state.lock().unwrap().stop_policy = policy;
+        Ok(())
+    }
+
+    fn stop_policy(&self) -> StopPolicy
+    {
+        self.exception_state.lock().unwrap().stop_policy
+    }
+
+    /// Execute one instruction on the active thread using hardware single-stepping
     ///
-    /// - **ARM64**: `thread_resume()` is the preferred method for per-thread control
-    /// - **Intel**: You may need to use `thread_set_state()` with `X86_THREAD_STATE64` flavor
-    ///   to coordinate per-thread operations (not yet implemented)
+    /// The other threads get a `thread_suspend()` of their own for the step,
+    /// separate from the ones `suspend_thread()` tracks, and are released
+    /// once it finishes.
     ///
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
-    /// - `InvalidArgument`: Thread ID is not valid (not in the thread list)
-    /// - `ResumeFailed`: `thread_resume()` failed
-    ///
-    /// ## Example
-    ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
-    /// use ferros_core::platform::macos::MacOSDebugger;
-    /// use ferros_core::types::ThreadId;
-    ///
-    /// # let mut debugger = MacOSDebugger::new()?;
-    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// let threads = debugger.threads()?;
-    /// if let Some(thread) = threads.first() {
-    ///     debugger.suspend_thread(*thread)?;
-    ///     // ... inspect thread state ...
-    ///     debugger.resume_thread(*thread)?;
-    /// }
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    #[allow(unsafe_code)] // Required for thread_resume() call
-    pub fn resume_thread(&mut self, thread_id: ThreadId) -> Result<()>
-    {
-        self.ensure_attached()?;
-        threads::ThreadManager::resume_thread(self, thread_id)
+    /// - `TargetGone`: The process died behind the debugger's back
+    /// - `NotStopped`: The target is running
+    /// - `InvalidArgument`: No active thread has been selected
+    /// - `MachError`: The thread's state couldn't be read or the SS/TF bit set
+    /// - `ResumeFailed` / `SuspendFailed`: `task_resume()` or `task_suspend()` failed
+    fn step_instruction(&mut self) -> Result<StopReason>
+    {
+        self.ensure_live()?;
+        if !self.is_stopped() {
+            return Err(DebuggerError::NotStopped);
+        }
+        let port = self.active_thread_port()?;
+        self.step_port(port)
     }
 
-    /// Read a 64-bit value from memory at the given address.
+    /// Step over the instruction at the active thread's PC, running calls to completion
     ///
-    /// This is a convenience method that reads 8 bytes and interprets them as a little-endian u64.
+    /// A call is stepped into with `step_instruction()`, then the whole task
+    /// runs until the callee returns to the call's return address (see
+    /// `run_to_return()`).
     ///
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
-    /// - `InvalidArgument`: Invalid memory address or unable to read 8 bytes
-    ///
-    /// ## Example
-    ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
-    /// use ferros_core::platform::macos::MacOSDebugger;
-    /// use ferros_core::types::Address;
-    ///
-    /// # let mut debugger = MacOSDebugger::new()?;
-    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// let value = debugger.read_memory_u64(Address::from(0x1000))?;
-    /// println!("Value at 0x1000: 0x{:016x}", value);
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    pub fn read_memory_u64(&self, addr: Address) -> Result<u64>
+    /// - `TargetGone`: The process died behind the debugger's back
+    /// - `NotStopped`: The target is running
+    /// - `InvalidArgument`: No active thread, or the return address can't take a breakpoint
+    /// - Errors from `step_instruction()`, `resume()` and `suspend()`
+    fn step_over(&mut self) -> Result<StopReason>
     {
-        self.ensure_attached()?;
-        self.memory_cache.read_u64(self.task, addr)
+        self.ensure_live()?;
+        if !self.is_stopped() {
+            return Err(DebuggerError::NotStopped);
+        }
+        let port = self.active_thread_port()?;
+        let pc = self.read_registers_from_port(port)?.pc;
+        let code = self.read_memory_logical(pc, instructions::max_call_len(self.architecture))?;
+        let Some(call) = instructions::decode_call(self.architecture, &code) else {
+            return self.step_instruction();
+        };
+        let return_address = call.return_address(pc);
+
+        let reason = self.step_instruction()?;
+        if !matches!(reason, StopReason::Step(_)) {
+            return Ok(reason);
+        }
+
+        self.run_to_return(port, return_address)
     }
 
-    /// Read a 32-bit value from memory at the given address.
+    /// Run until the active thread's current function returns
     ///
-    /// This is a convenience method that reads 4 bytes and interprets them as a little-endian u32.
+    /// The caller comes from a plain unwind of the active thread, and the
+    /// target runs to its PC (see `run_to_return()`). If the thread is stopped
+    /// on one of its own software breakpoints, that instruction is stepped
+    /// first so resuming doesn't trap on it again straight away.
     ///
     /// ## Errors
     ///
     /// - `NotAttached`: Not attached to a process
-    /// - `InvalidArgument`: Invalid memory address or unable to read 4 bytes
-    ///
-    /// ## Example
-    ///
-    /// ```rust,no_run
-    /// use ferros_core::Debugger;
-    /// use ferros_core::platform::macos::MacOSDebugger;
-    /// use ferros_core::types::Address;
-    ///
-    /// # let mut debugger = MacOSDebugger::new()?;
-    /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
-    /// let value = debugger.read_memory_u32(Address::from(0x1000))?;
-    /// println!("Value at 0x1000: 0x{:08x}", value);
-    /// # Ok::<(), ferros_core::error::DebuggerError>(())
-    /// ```
-    pub fn read_memory_u32(&self, addr: Address) -> Result<u32>
-    {
-        self.ensure_attached()?;
-        let bytes = self.memory_cache.read(self.task, addr, 4)?;
-        if bytes.len() < 4 {
-            return Err(DebuggerError::InvalidArgument(format!(
-                "Unable to read 4 bytes from address 0x{:016x}",
-                addr.value()
-            )));
+    /// - `TargetGone`: The process died behind the debugger's back
+    /// - `NotStopped`: The target is running
+    /// - `UnreliableReturnAddress`: No caller frame, or only a heuristic one
+    /// - `InvalidArgument`: No active thread, or the return address can't take a breakpoint
+    /// - Errors from `step_instruction()`, `resume()` and `suspend()`
+    fn step_out(&mut self) -> Result<StopReason>
+    {
+        self.ensure_live()?;
+        if !self.is_stopped() {
+            return Err(DebuggerError::NotStopped);
+        }
+        let port = self.active_thread_port()?;
+        let frames = self.stack_trace(STEP_OUT_FRAMES)?;
+        let return_address = unwind::return_frame(&frames)?.pc;
+
+        let pc = frames[0].pc;
+        if self.breakpoints.lock().unwrap().patched_code_at(pc).is_some() {
+            match self.step_instruction()? {
+                StopReason::Step(next) if next != return_address.value() => {}
+                // Returned already, or stopped for something else
+                reason => return Ok(reason),
+            }
         }
-        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        self.run_to_return(port, return_address)
+    }
+}
+
+impl Drop for MacOSDebugger
+{
+    fn drop(&mut self)
+    {
+        self.restore_all_breakpoints();
+        self.stop_exception_handler();
     }
+}
 
+impl MacOSDebugger
+{
     /// Find symbol information for a given address.
     ///
     /// This method symbolicates an address, returning function names and source locations
@@ -1677,8 +2689,22 @@ impl MacOSDebugger
     pub fn find_symbol(&mut self, address: Address) -> Result<Option<crate::symbols::Symbolication>>
     {
         self.ensure_attached()?;
+        self.load_symbol_images()?;
+        Ok(self.symbol_cache.symbolicate(address))
+    }
 
-        // Ensure images are loaded (same logic as stack_trace)
+    /// Load the main executable and every mapped image into the symbol cache
+    ///
+    /// dyld's image list is used when it can be read; otherwise images are
+    /// placed at the regions they are mapped in. Images that are already cached
+    /// are skipped by the cache itself. Callers need the symbols now, so this
+    /// waits for images still loading in the background.
+    fn load_symbol_images(&mut self) -> Result<()>
+    {
+        if self.load_dyld_images() {
+            self.symbol_cache.wait_for_loads();
+            return Ok(());
+        }
         let regions = get_memory_regions(self.task)?;
 
         // Load the main executable if we can find it
@@ -1699,26 +2725,32 @@ impl MacOSDebugger
         }
 
         // Load all other images
-        for region in regions {
-            if let Some(name) = &region.name
-                && name.starts_with('/')
-                && (name.ends_with(".dylib") || name.ends_with(".so") || !name.contains('['))
-            {
-                // Skip if this is the main executable
-                if let Some(ref exec_path) = exec_path {
-                    if name == exec_path.to_str().unwrap_or("") {
-                        continue;
-                    }
+        let _ = self
+            .symbol_cache
+            .load_images(Self::library_descriptors(regions, exec_path.as_deref()));
+
+        Ok(())
+    }
+
+    /// Images other than the main executable mapped in `regions`
+    fn library_descriptors(regions: Vec<MemoryRegion>, exec_path: Option<&std::path::Path>) -> Vec<ImageDescriptor>
+    {
+        regions
+            .into_iter()
+            .filter_map(|region| {
+                let name = region.name?;
+                let is_image =
+                    name.starts_with('/') && (name.ends_with(".dylib") || name.ends_with(".so") || !name.contains('['));
+                // The main executable is loaded first, at the right address
+                if !is_image || exec_path.is_some_and(|exec_path| exec_path.to_str() == Some(name.as_str())) {
+                    return None;
                 }
-                let desc = crate::symbols::ImageDescriptor {
+                Some(ImageDescriptor {
                     path: std::path::PathBuf::from(name),
                     load_address: region.start.value(),
-                };
-                let _ = self.symbol_cache.load_image(desc);
-            }
-        }
-
-        Ok(self.symbol_cache.symbolicate(address))
+                })
+            })
+            .collect()
     }
 
     /// Get the executable path for a process using libproc
@@ -1760,3 +2792,84 @@ impl MacOSDebugger
         None
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::atomic::AtomicUsize;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn is_gone(result: Result<impl Sized>, pid: ProcessId) -> bool
+    {
+        matches!(result, Err(DebuggerError::TargetGone { pid: gone }) if gone == pid.0)
+    }
+
+    #[test]
+    #[ignore = "needs task_for_pid rights (debugger entitlement or root)"]
+    fn externally_killed_target_is_reported_gone()
+    {
+        let mut debugger = MacOSDebugger::new().unwrap();
+        let pid = debugger.launch("/bin/sleep", &["sleep", "30"]).unwrap();
+        debugger.resume().unwrap();
+
+        // SAFETY: kill(2) only takes plain integers
+        assert_eq!(unsafe { libc::kill(pid.0 as libc::pid_t, libc::SIGKILL) }, 0);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while debugger.is_target_alive() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(is_gone(debugger.read_registers(), pid));
+        assert!(is_gone(debugger.threads(), pid));
+        assert!(is_gone(debugger.suspend(), pid));
+        assert!(!debugger.is_attached());
+        assert_eq!(debugger.task, 0);
+    }
+
+    #[test]
+    #[ignore = "needs task_for_pid rights (debugger entitlement or root)"]
+    fn breakpoint_in_a_loop_fires_on_every_resume()
+    {
+        let mut debugger = MacOSDebugger::new().unwrap();
+        debugger.set_capture_process_output(true);
+        debugger.launch("/usr/bin/yes", &["yes"]).unwrap();
+        let written = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&written);
+        let stdout = Box::new(move |bytes: &[u8]| {
+            counter.fetch_add(bytes.len(), Ordering::Relaxed);
+            true
+        });
+        debugger.pump_process_output(stdout, Box::new(|_: &[u8]| true)).unwrap();
+
+        // Let dyld map libsystem so `write` resolves
+        debugger.resume().unwrap();
+        thread::sleep(Duration::from_millis(200));
+        debugger.suspend().unwrap();
+        let location = BreakpointLocation::Function("write".into());
+        let id = debugger
+            .add_breakpoint(BreakpointRequest::from(BreakpointRequestKind::Location(location)))
+            .unwrap();
+        let address = debugger.breakpoint_info(id).unwrap().address;
+        thread::sleep(Duration::from_millis(100));
+        let before = written.load(Ordering::Relaxed);
+
+        let mut hits = 0;
+        for _ in 0..5 {
+            let reason = debugger.run_until_stop(Duration::from_secs(5)).unwrap();
+            assert_eq!(reason, StopReason::Breakpoint(address.value()));
+            let count = debugger.breakpoint_info(id).unwrap().hit_count;
+            assert!(count > hits, "hit count stuck at {count}");
+            hits = count;
+        }
+
+        // Re-trapping without running `write` would count hits too, but write nothing
+        thread::sleep(Duration::from_millis(100));
+        assert!(
+            written.load(Ordering::Relaxed) > before,
+            "`write` never ran past the breakpoint"
+        );
+        debugger.kill().unwrap();
+    }
+}
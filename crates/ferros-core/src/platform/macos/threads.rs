@@ -5,9 +5,19 @@
 //! This module handles thread enumeration, active thread selection, and
 //! per-thread suspend/resume operations using Mach APIs.
 //!
+//! ## Thread Identity
+//!
+//! Mach thread ports are not stable: `task_threads()` can hand out different
+//! port names for the same thread and the kernel reuses names after a thread
+//! exits. The public [`ThreadId`] therefore carries the kernel's global thread
+//! id from `thread_info(THREAD_IDENTIFIER_INFO)`, and every enumeration rebuilds
+//! a port<->id map that backend code uses to find the current port for a thread.
+//!
 //! ## Mach APIs Used
 //!
 //! - **task_threads()**: Enumerate threads in a task
+//! - **thread_info()**: Resolve a thread port to its global thread id, and
+//!   read its name, run state and CPU usage
 //! - **thread_suspend()**: Suspend a single thread
 //! - **thread_resume()**: Resume a single thread
 //! - **mach_port_deallocate()**: Release thread ports
@@ -16,9 +26,11 @@
 //! ## References
 //!
 //! - [task_threads(3) man page](https://developer.apple.com/documentation/kernel/1402149-task_threads/)
+//! - [thread_info(3) man page](https://developer.apple.com/documentation/kernel/1418630-thread_info/)
 //! - [thread_suspend(3) man page](https://developer.apple.com/documentation/kernel/1418926-thread_suspend/)
 //! - [thread_resume(3) man page](https://developer.apple.com/documentation/kernel/1418926-thread_resume/)
 
+use std::collections::HashSet;
 use std::mem;
 
 use libc::{mach_msg_type_number_t, thread_act_t, vm_address_t, vm_size_t};
@@ -30,8 +42,9 @@ use mach2::task::task_threads;
 use mach2::traps::mach_task_self;
 
 use crate::error::{DebuggerError, Result};
-use crate::platform::macos::ffi;
-use crate::types::ThreadId;
+use crate::platform::macos::{constants, ffi};
+use crate::platform::thread_map::ThreadHandleMap;
+use crate::types::{ThreadId, ThreadInfo, ThreadRunState};
 
 /// Trait for thread operations that require access to debugger internals.
 pub(crate) trait ThreadOperations
@@ -45,14 +58,17 @@ pub(crate) trait ThreadOperations
     /// Get the list of thread ports (immutable).
     fn thread_ports(&self) -> &[thread_act_t];
 
+    /// Get the port<->stable id map for the current thread list.
+    fn thread_map(&self) -> &ThreadHandleMap<thread_act_t>;
+
+    /// Get the port<->stable id map for the current thread list (mutable).
+    fn thread_map_mut(&mut self) -> &mut ThreadHandleMap<thread_act_t>;
+
     /// Get the current active thread port.
     fn current_thread(&self) -> Option<thread_act_t>;
 
     /// Set the current active thread port.
     fn set_current_thread(&mut self, thread: Option<thread_act_t>);
-
-    /// Get the process ID (for error messages).
-    fn pid(&self) -> u32;
 }
 
 /// Thread management functions for macOS debugger.
@@ -92,12 +108,146 @@ impl ThreadManager
         }
     }
 
+    /// Resolve a thread port to the kernel's global thread id.
+    ///
+    /// ## Mach API: thread_info()
+    ///
+    /// ```c
+    /// kern_return_t thread_info(
+    ///     thread_inspect_t target_act,              // Thread port
+    ///     thread_flavor_t flavor,                   // THREAD_IDENTIFIER_INFO
+    ///     thread_info_t thread_info_out,            // Output: thread_identifier_info
+    ///     mach_msg_type_number_t *thread_info_outCnt
+    /// );
+    /// ```
+    ///
+    /// Returns `None` if the port is dead or the call fails.
+    pub(crate) fn global_thread_id(port: thread_act_t) -> Option<u64>
+    {
+        Self::identifier_info(port).map(|info| info.thread_id)
+    }
+
+    /// The `thread_handle` XNU reports for a thread port.
+    ///
+    /// It is the value libpthread hands the kernel as the thread's TSD base
+    /// (`TPIDRRO_EL0` on arm64, the GS base on x86_64), or the `pthread_t`
+    /// behind it on older releases; see
+    /// [`tsd_base_from_handle`](crate::symbols::tls::tsd_base_from_handle).
+    pub(crate) fn thread_handle(port: thread_act_t) -> Option<u64>
+    {
+        Self::identifier_info(port).map(|info| info.thread_handle)
+    }
+
+    fn identifier_info(port: thread_act_t) -> Option<ffi::ThreadIdentifierInfo>
+    {
+        let mut info = ffi::ThreadIdentifierInfo::default();
+        let mut count = constants::THREAD_IDENTIFIER_INFO_COUNT;
+        let result =
+            unsafe { ffi::thread_info(port, constants::THREAD_IDENTIFIER_INFO, (&raw mut info).cast(), &mut count) };
+        (result == KERN_SUCCESS).then_some(info)
+    }
+
+    /// Name, run state, suspend count and CPU usage of a thread.
+    ///
+    /// ## Mach API: thread_info()
+    ///
+    /// Called twice: with `THREAD_EXTENDED_INFO` for `pth_name` (the name
+    /// `pthread_getname_np()` would return), `pth_run_state` and
+    /// `pth_cpu_usage`, and with `THREAD_BASIC_INFO` for `suspend_count`, which
+    /// the extended flavor doesn't carry.
+    ///
+    /// ## Errors
+    ///
+    /// - `ThreadNotFound`: The thread is not in the current thread list, or
+    ///   `thread_info()` failed because it has exited
+    pub(crate) fn thread_info<Ops: ThreadOperations>(ops: &Ops, thread: ThreadId) -> Result<ThreadInfo>
+    {
+        let port = Self::thread_port_for_id(ops, thread)?;
+        let failed = |flavor: &str, result| {
+            tracing::debug!("thread_info({flavor}) failed for thread {}: {result}", thread.raw());
+            DebuggerError::ThreadNotFound(thread)
+        };
+
+        let mut extended = ffi::ThreadExtendedInfo::default();
+        let mut count = constants::THREAD_EXTENDED_INFO_COUNT;
+        let result =
+            unsafe { ffi::thread_info(port, constants::THREAD_EXTENDED_INFO, (&raw mut extended).cast(), &mut count) };
+        if result != KERN_SUCCESS {
+            return Err(failed("THREAD_EXTENDED_INFO", result));
+        }
+        let mut basic = ffi::ThreadBasicInfo::default();
+        let mut count = constants::THREAD_BASIC_INFO_COUNT;
+        let result = unsafe { ffi::thread_info(port, constants::THREAD_BASIC_INFO, (&raw mut basic).cast(), &mut count) };
+        if result != KERN_SUCCESS {
+            return Err(failed("THREAD_BASIC_INFO", result));
+        }
+
+        let name_len = extended
+            .pth_name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(extended.pth_name.len());
+        let name = String::from_utf8_lossy(&extended.pth_name[..name_len]).into_owned();
+        let suspend_count = u32::try_from(basic.suspend_count).unwrap_or(0);
+        // A suspended thread keeps the run state it had when it was suspended
+        let state = match extended.pth_run_state {
+            _ if suspend_count > 0 => ThreadRunState::Suspended,
+            constants::TH_STATE_RUNNING => ThreadRunState::Running,
+            constants::TH_STATE_STOPPED => ThreadRunState::Suspended,
+            constants::TH_STATE_WAITING | constants::TH_STATE_UNINTERRUPTIBLE => ThreadRunState::Waiting,
+            constants::TH_STATE_HALTED => ThreadRunState::Halted,
+            _ => ThreadRunState::Unknown,
+        };
+        Ok(ThreadInfo {
+            id: thread,
+            name: (!name.is_empty()).then_some(name),
+            state,
+            suspend_count,
+            cpu_percent: f64::from(extended.pth_cpu_usage) * 100.0 / f64::from(constants::TH_USAGE_SCALE),
+        })
+    }
+
+    /// Stable `ThreadId` for a thread port.
+    ///
+    /// Falls back to the port value if `thread_info()` fails, which only happens
+    /// for threads that are already gone.
+    pub(crate) fn stable_thread_id(port: thread_act_t) -> ThreadId
+    {
+        match Self::global_thread_id(port) {
+            Some(id) => ThreadId::from(id),
+            None => {
+                tracing::warn!("thread_info(THREAD_IDENTIFIER_INFO) failed for port {port}; using the port as its id");
+                ThreadId::from(u64::from(port))
+            }
+        }
+    }
+
+    /// Store a fresh `task_threads()` enumeration and rebuild the port<->id map.
+    ///
+    /// Returns the ports of threads that weren't in the previous enumeration.
+    pub(crate) fn record_thread_ports<Ops: ThreadOperations>(ops: &mut Ops, ports: Vec<thread_act_t>) -> Vec<thread_act_t>
+    {
+        let entries = ports.iter().map(|&port| (port, Self::stable_thread_id(port))).collect();
+        let started = ops.thread_map_mut().replace(entries);
+        *ops.thread_ports_mut() = ports;
+        started
+    }
+
+    /// Stable `ThreadId` for a port from the current enumeration.
+    pub(crate) fn thread_id_for_port<Ops: ThreadOperations>(ops: &Ops, port: thread_act_t) -> ThreadId
+    {
+        ops.thread_map().id_for(port).unwrap_or_else(|| Self::stable_thread_id(port))
+    }
+
     /// Refresh the thread list from the operating system.
     ///
     /// This is an internal helper method that updates the cached thread list by
     /// calling `task_threads()` to get the current set of threads in the target
     /// process. It also updates the active thread if the current one no longer exists.
     ///
+    /// Returns the ports of threads that started since the previous enumeration,
+    /// which still need any hardware breakpoints and watchpoints programmed.
+    ///
     /// ## Mach API: task_threads()
     ///
     /// ```c
@@ -114,7 +264,11 @@ impl ThreadManager
     ///
     /// ## Implementation Notes
     ///
-    /// - Deallocates old thread ports before getting new ones to prevent port leaks
+    /// - Deallocates old thread ports before getting new ones to prevent port leaks,
+    ///   which is all an exited thread needs: breakpoints are tracked per address
+    ///   and slot, not per thread
+    /// - Rebuilds the port<->`ThreadId` map, since ports may change between calls
+    /// - Keeps the active thread by identity, following it to its new port
     /// - Updates the active thread to the first thread if the current one no longer exists
     ///
     /// ## Mach APIs Used
@@ -129,7 +283,7 @@ impl ThreadManager
     /// ## See Also
     ///
     /// - [task_threads(3) man page](https://developer.apple.com/documentation/kernel/1402149-task_threads/)
-    pub(crate) fn refresh_thread_list<Ops: ThreadOperations>(ops: &mut Ops) -> Result<()>
+    pub(crate) fn refresh_thread_list<Ops: ThreadOperations>(ops: &mut Ops) -> Result<Vec<thread_act_t>>
     {
         unsafe {
             // Deallocate old thread ports before getting new ones to prevent port leaks
@@ -147,21 +301,133 @@ impl ThreadManager
                 )));
             }
 
+            // Remember who the active thread is before its port goes stale
+            let current_id = ops.current_thread().and_then(|port| ops.thread_map().id_for(port));
+
             let slice = std::slice::from_raw_parts(threads, thread_count as usize);
-            *ops.thread_ports_mut() = slice.to_vec();
+            let started = Self::record_thread_ports(ops, slice.to_vec());
             Self::deallocate_threads_array(threads, thread_count);
 
             // Update active thread - use first thread if current one no longer exists
-            if let Some(current) = ops.current_thread() {
-                if !ops.thread_ports().contains(&current) {
-                    ops.set_current_thread(ops.thread_ports().first().copied());
+            let current_port = current_id
+                .and_then(|id| ops.thread_map().handle_for(id))
+                .or_else(|| ops.thread_ports().first().copied());
+            ops.set_current_thread(current_port);
+
+            Ok(started)
+        }
+    }
+
+    /// Suspend every thread of `task` except `keep`, returning the ones suspended.
+    ///
+    /// Used by the exception handler to hold the rest of the target while a
+    /// stop under [`StopPolicy::AllThreads`](crate::types::StopPolicy::AllThreads)
+    /// is reported. It has no debugger to go through, so it enumerates the
+    /// threads itself with `task_threads()` and names them by stable
+    /// `ThreadId`, which `resume_thread()` can find again after a refresh.
+    /// A thread that can't be suspended (it exited meanwhile) is skipped.
+    #[cfg(target_os = "macos")]
+    pub(crate) fn suspend_other_threads(task: libc::mach_port_t, keep: thread_act_t) -> Vec<ThreadId>
+    {
+        let mut held = Vec::new();
+        unsafe {
+            let mut threads: *mut thread_act_t = std::ptr::null_mut();
+            let mut thread_count: mach_msg_type_number_t = 0;
+            let result = task_threads(task, &mut threads, &mut thread_count);
+            if result != KERN_SUCCESS {
+                tracing::warn!("Failed to enumerate threads to hold: {result}");
+                return held;
+            }
+
+            let slice = std::slice::from_raw_parts(threads, thread_count as usize);
+            for &port in slice {
+                if port != keep {
+                    let result = ffi::thread_suspend(port);
+                    if result == KERN_SUCCESS {
+                        held.push(Self::stable_thread_id(port));
+                    } else {
+                        tracing::debug!("Failed to hold thread port {port}: {result}");
+                    }
                 }
-            } else {
-                ops.set_current_thread(ops.thread_ports().first().copied());
+                let _ = ffi::mach_port_deallocate(mach_task_self(), port);
             }
+            Self::deallocate_threads_array(threads, thread_count);
         }
+        held
+    }
 
-        Ok(())
+    /// Resume each of `held`, as suspended by [`suspend_other_threads`](Self::suspend_other_threads), once.
+    ///
+    /// Like its counterpart it goes straight to `task_threads()`, finding each
+    /// thread by stable `ThreadId`; one that exited meanwhile is skipped.
+    #[cfg(target_os = "macos")]
+    pub(crate) fn resume_threads(task: libc::mach_port_t, held: &[ThreadId])
+    {
+        if held.is_empty() {
+            return;
+        }
+        unsafe {
+            let mut threads: *mut thread_act_t = std::ptr::null_mut();
+            let mut thread_count: mach_msg_type_number_t = 0;
+            let result = task_threads(task, &mut threads, &mut thread_count);
+            if result != KERN_SUCCESS {
+                tracing::warn!("Failed to enumerate threads to release: {result}");
+                return;
+            }
+
+            let slice = std::slice::from_raw_parts(threads, thread_count as usize);
+            for &port in slice {
+                if held.contains(&Self::stable_thread_id(port)) {
+                    let result = ffi::thread_resume(port);
+                    if result != KERN_SUCCESS {
+                        tracing::debug!("Failed to release thread port {port}: {result}");
+                    }
+                }
+                let _ = ffi::mach_port_deallocate(mach_task_self(), port);
+            }
+            Self::deallocate_threads_array(threads, thread_count);
+        }
+    }
+
+    /// Hand `visit` the ports of threads of `task` whose ids aren't in `seen` yet, then add them.
+    ///
+    /// The exception handler's counterpart to the new-thread detection in
+    /// `refresh_thread_list()`: it keeps its own `seen` set across stops so
+    /// a thread that started while the target ran gets its debug registers
+    /// at the next stop, even if nobody refreshes the thread list. Threads
+    /// that exited are dropped from `seen`, so a reused id is never skipped.
+    #[cfg(target_os = "macos")]
+    pub(crate) fn visit_started_threads(
+        task: libc::mach_port_t,
+        seen: &mut HashSet<ThreadId>,
+        visit: impl FnOnce(&[thread_act_t]),
+    )
+    {
+        unsafe {
+            let mut threads: *mut thread_act_t = std::ptr::null_mut();
+            let mut thread_count: mach_msg_type_number_t = 0;
+            let result = task_threads(task, &mut threads, &mut thread_count);
+            if result != KERN_SUCCESS {
+                tracing::warn!("Failed to enumerate threads to arm: {result}");
+                return;
+            }
+
+            let slice = std::slice::from_raw_parts(threads, thread_count as usize);
+            let ids: Vec<ThreadId> = slice.iter().map(|&port| Self::stable_thread_id(port)).collect();
+            let started: Vec<thread_act_t> = slice
+                .iter()
+                .zip(&ids)
+                .filter(|(_, id)| !seen.contains(id))
+                .map(|(&port, _)| port)
+                .collect();
+            visit(&started);
+            *seen = ids.into_iter().collect();
+
+            for &port in slice {
+                let _ = ffi::mach_port_deallocate(mach_task_self(), port);
+            }
+            Self::deallocate_threads_array(threads, thread_count);
+        }
     }
 
     /// Get the active thread port.
@@ -171,18 +437,16 @@ impl ThreadManager
             .ok_or_else(|| DebuggerError::InvalidArgument("No active thread selected".to_string()))
     }
 
-    /// Get the thread port for a given ThreadId.
+    /// Get the current thread port for a given ThreadId.
+    ///
+    /// The lookup goes through the port<->id map built by the last enumeration,
+    /// so a `ThreadId` stays valid across refreshes even if its port changes.
     pub(crate) fn thread_port_for_id<Ops: ThreadOperations>(ops: &Ops, thread: ThreadId) -> Result<thread_act_t>
     {
-        let port = thread.raw() as thread_act_t;
-        if ops.thread_ports().contains(&port) {
+        if let Some(port) = ops.thread_map().handle_for(thread) {
             Ok(port)
         } else {
-            Err(DebuggerError::InvalidArgument(format!(
-                "Thread {} is not part of process {}. Call refresh_threads() to update the thread list.",
-                thread.raw(),
-                ops.pid()
-            )))
+            Err(DebuggerError::ThreadNotFound(thread))
         }
     }
 
@@ -198,17 +462,15 @@ impl ThreadManager
     ///
     /// ## Errors
     ///
-    /// Returns `InvalidArgument` if the thread port is not in the current thread list.
+    /// Returns `ThreadNotFound` if the thread port is not in the current thread list.
     pub(crate) fn set_active_thread_by_port<Ops: ThreadOperations>(ops: &mut Ops, port: thread_act_t) -> Result<()>
     {
         if ops.thread_ports().contains(&port) {
             ops.set_current_thread(Some(port));
             Ok(())
         } else {
-            Err(DebuggerError::InvalidArgument(format!(
-                "Thread {port} is not part of process {}",
-                ops.pid()
-            )))
+            let thread = ops.thread_map().id_for(port).unwrap_or_else(|| Self::stable_thread_id(port));
+            Err(DebuggerError::ThreadNotFound(thread))
         }
     }
 
@@ -232,18 +494,11 @@ impl ThreadManager
     ///
     /// ## Errors
     ///
-    /// - `InvalidArgument`: Thread ID is not valid (not in the thread list)
+    /// - `ThreadNotFound`: Thread ID is not valid (not in the thread list)
     /// - `SuspendFailed`: `thread_suspend()` failed
     pub(crate) fn suspend_thread<Ops: ThreadOperations>(ops: &Ops, thread_id: ThreadId) -> Result<()>
     {
-        let thread_port = thread_id.raw() as thread_act_t;
-        if !ops.thread_ports().contains(&thread_port) {
-            return Err(DebuggerError::InvalidArgument(format!(
-                "Thread {} is not part of process {}",
-                thread_id.raw(),
-                ops.pid()
-            )));
-        }
+        let thread_port = Self::thread_port_for_id(ops, thread_id)?;
 
         unsafe {
             let result = ffi::thread_suspend(thread_port);
@@ -279,18 +534,11 @@ impl ThreadManager
     ///
     /// ## Errors
     ///
-    /// - `InvalidArgument`: Thread ID is not valid (not in the thread list)
+    /// - `ThreadNotFound`: Thread ID is not valid (not in the thread list)
     /// - `ResumeFailed`: `thread_resume()` failed
     pub(crate) fn resume_thread<Ops: ThreadOperations>(ops: &Ops, thread_id: ThreadId) -> Result<()>
     {
-        let thread_port = thread_id.raw() as thread_act_t;
-        if !ops.thread_ports().contains(&thread_port) {
-            return Err(DebuggerError::InvalidArgument(format!(
-                "Thread {} is not part of process {}",
-                thread_id.raw(),
-                ops.pid()
-            )));
-        }
+        let thread_port = Self::thread_port_for_id(ops, thread_id)?;
 
         unsafe {
             let result = ffi::thread_resume(thread_port);
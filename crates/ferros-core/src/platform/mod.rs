@@ -29,6 +29,11 @@
 #[cfg(target_os = "macos")]
 pub mod macos;
 
+// Helpers shared by platform backends
+pub(crate) mod fault;
+pub(crate) mod stack_guard;
+pub(crate) mod thread_map;
+
 // Future platform modules:
 // #[cfg(target_os = "linux")]
 // pub mod linux;
@@ -0,0 +1,148 @@
+//! # Stack Guard Classification
+//!
+//! A stack overflow reaches the debugger as an ordinary bad access
+//! (`EXC_BAD_ACCESS` on macOS, `SIGSEGV` elsewhere) whose fault address sits
+//! just below the thread's stack. Threads are created with an inaccessible
+//! guard region directly below the stack so that running off the end faults
+//! instead of silently corrupting the neighbouring mapping; this module
+//! recognises that pattern from the thread's SP and the process memory map.
+//!
+//! ## Heuristic
+//!
+//! 1. The stack is the readable and writable region containing SP. If SP has
+//!    already moved past the end of the stack (a single large frame can do
+//!    that), the nearest such region above SP within [`GUARD_WINDOW`] is used.
+//! 2. The guard is the inaccessible region ending exactly where the stack
+//!    starts. If the kernel doesn't report one, the [`GUARD_WINDOW`] bytes
+//!    below the stack are treated as the guard.
+//! 3. The fault is a stack overflow if it lands inside the guard.
+
+use crate::types::{Address, MemoryRegion};
+
+/// Size of the area below a stack that counts as its guard when the memory map
+/// has no explicit guard region. Generous on purpose: it only has to be larger
+/// than the biggest frame that can jump over the real guard page.
+pub(crate) const GUARD_WINDOW: u64 = 64 * 1024;
+
+/// The `(lowest, end)` addresses of the stack of the thread whose stack
+/// pointer is `sp`, found as in step 1 of the heuristic.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn stack_bounds(sp: Address, regions: &[MemoryRegion]) -> Option<(Address, Address)>
+{
+    let sp = sp.value();
+    let is_stack = |region: &MemoryRegion| region.permissions.contains('r') && region.permissions.contains('w');
+
+    let stack = regions
+        .iter()
+        .filter(|region| is_stack(region))
+        .find(|region| region.start.value() <= sp && sp < region.end.value())
+        .or_else(|| {
+            regions
+                .iter()
+                .filter(|region| is_stack(region) && region.start.value() > sp)
+                .filter(|region| region.start.value() - sp <= GUARD_WINDOW)
+                .min_by_key(|region| region.start.value())
+        })?;
+    Some((stack.start, stack.end))
+}
+
+/// If `fault` is a stack overflow of the thread whose stack pointer is `sp`,
+/// return the start of the guard region it hit.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn stack_overflow_guard(fault: Address, sp: Address, regions: &[MemoryRegion]) -> Option<Address>
+{
+    let fault = fault.value();
+    let stack_start = stack_bounds(sp, regions)?.0.value();
+
+    let (guard_start, guard_end) = regions
+        .iter()
+        .find(|region| region.end.value() == stack_start && !region.permissions.contains('r'))
+        .map_or((stack_start.saturating_sub(GUARD_WINDOW), stack_start), |guard| {
+            (guard.start.value(), guard.end.value())
+        });
+
+    (guard_start <= fault && fault < guard_end).then(|| Address::from(guard_start))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::MemoryRegionId;
+
+    fn region(start: u64, end: u64, permissions: &str) -> MemoryRegion
+    {
+        MemoryRegion::new(
+            MemoryRegionId(0),
+            Address::from(start),
+            Address::from(end),
+            permissions.to_string(),
+            None,
+        )
+    }
+
+    /// Heap, then a 16 KiB guard page, then an 512 KiB thread stack
+    fn thread_map() -> Vec<MemoryRegion>
+    {
+        vec![
+            region(0x1_0000_0000, 0x1_0010_0000, "rw-"),
+            region(0x7000_0000, 0x7000_4000, "---"),
+            region(0x7000_4000, 0x7008_4000, "rw-"),
+        ]
+    }
+
+    #[test]
+    fn stack_bounds_come_from_the_region_holding_sp()
+    {
+        let regions = thread_map();
+        let stack = Some((Address::from(0x7000_4000), Address::from(0x7008_4000)));
+        assert_eq!(stack_bounds(Address::from(0x7008_0000), &regions), stack);
+        assert_eq!(stack_bounds(Address::from(0x7000_0800), &regions), stack);
+        assert_eq!(stack_bounds(Address::from(0x2_0000_0000), &regions), None);
+    }
+
+    #[test]
+    fn fault_in_guard_below_stack_is_overflow()
+    {
+        let regions = thread_map();
+        let guard = stack_overflow_guard(Address::from(0x7000_3ff8), Address::from(0x7000_4010), &regions);
+        assert_eq!(guard, Some(Address::from(0x7000_0000)));
+    }
+
+    #[test]
+    fn sp_already_inside_guard_still_finds_stack()
+    {
+        // A large frame moved SP below the stack before the first store faulted
+        let regions = thread_map();
+        let guard = stack_overflow_guard(Address::from(0x7000_1000), Address::from(0x7000_0800), &regions);
+        assert_eq!(guard, Some(Address::from(0x7000_0000)));
+    }
+
+    #[test]
+    fn missing_guard_region_uses_window()
+    {
+        let regions = vec![region(0x7000_4000, 0x7008_4000, "rw-")];
+        let sp = Address::from(0x7000_4100);
+        assert_eq!(
+            stack_overflow_guard(Address::from(0x7000_3000), sp, &regions),
+            Some(Address::from(0x7000_4000 - GUARD_WINDOW))
+        );
+        assert_eq!(stack_overflow_guard(Address::from(0x6000_0000), sp, &regions), None);
+    }
+
+    #[test]
+    fn ordinary_bad_accesses_are_not_overflows()
+    {
+        let regions = thread_map();
+        let sp = Address::from(0x7008_0000);
+        // Null pointer dereference
+        assert_eq!(stack_overflow_guard(Address::from(0x0), sp, &regions), None);
+        // Use-after-free style access into the heap
+        assert_eq!(stack_overflow_guard(Address::from(0x1_0000_0010), sp, &regions), None);
+        // Guard page of a different stack than the faulting thread's
+        assert_eq!(
+            stack_overflow_guard(Address::from(0x7000_3ff8), Address::from(0x1_0000_0000), &regions),
+            None
+        );
+    }
+}
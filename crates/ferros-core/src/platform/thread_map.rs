@@ -0,0 +1,170 @@
+//! # Thread Handle Map
+//!
+//! Backend-internal mapping between native thread handles and stable [`ThreadId`]s.
+//!
+//! Native handles are not stable identities: on macOS a Mach thread port can
+//! change between `task_threads()` calls and port names are reused by the kernel
+//! once a thread dies. The public API therefore hands out the kernel's stable
+//! thread id, and backends use this map to translate back to the handle they
+//! need for the next system call.
+//!
+//! The map is rebuilt wholesale every time the backend re-enumerates threads, so
+//! a handle or id that disappears from the enumeration disappears from the map.
+
+use crate::types::ThreadId;
+
+/// Ordered mapping between native thread handles and stable thread ids.
+///
+/// Entries keep the order in which the OS enumerated them, so the first entry is
+/// usually the main thread.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) struct ThreadHandleMap<H>
+{
+    entries: Vec<(H, ThreadId)>,
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+impl<H: Copy + PartialEq> ThreadHandleMap<H>
+{
+    /// Create an empty map.
+    pub(crate) fn new() -> Self
+    {
+        Self { entries: Vec::new() }
+    }
+
+    /// Replace the map with a fresh enumeration of `(handle, id)` pairs.
+    ///
+    /// Returns the handles of threads the previous enumeration didn't have,
+    /// in enumeration order. Threads that exited simply drop out.
+    pub(crate) fn replace(&mut self, entries: Vec<(H, ThreadId)>) -> Vec<H>
+    {
+        let started = entries
+            .iter()
+            .filter(|(_, id)| !self.contains(*id))
+            .map(|(handle, _)| *handle)
+            .collect();
+        self.entries = entries;
+        started
+    }
+
+    /// Forget every thread.
+    pub(crate) fn clear(&mut self)
+    {
+        self.entries.clear();
+    }
+
+    /// Current native handle for `thread`, if it is still alive.
+    pub(crate) fn handle_for(&self, thread: ThreadId) -> Option<H>
+    {
+        self.entries.iter().find(|(_, id)| *id == thread).map(|(handle, _)| *handle)
+    }
+
+    /// Stable id of the thread currently behind `handle`.
+    pub(crate) fn id_for(&self, handle: H) -> Option<ThreadId>
+    {
+        self.entries
+            .iter()
+            .find(|(candidate, _)| *candidate == handle)
+            .map(|(_, id)| *id)
+    }
+
+    /// Whether `thread` is part of the latest enumeration.
+    pub(crate) fn contains(&self, thread: ThreadId) -> bool
+    {
+        self.handle_for(thread).is_some()
+    }
+
+    /// Stable ids in enumeration order.
+    pub(crate) fn ids(&self) -> Vec<ThreadId>
+    {
+        self.entries.iter().map(|(_, id)| *id).collect()
+    }
+
+    /// Native handles in enumeration order.
+    pub(crate) fn handles(&self) -> Vec<H>
+    {
+        self.entries.iter().map(|(handle, _)| *handle).collect()
+    }
+
+    /// Number of known threads.
+    pub(crate) fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+}
+
+impl<H: Copy + PartialEq> Default for ThreadHandleMap<H>
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// Simulate a `task_threads()` enumeration where the kernel hands out fresh
+    /// port names for the same underlying threads.
+    fn enumerate(map: &mut ThreadHandleMap<u32>, cycle: u32, global_ids: &[u64])
+    {
+        let entries = global_ids
+            .iter()
+            .enumerate()
+            .map(|(index, &id)| (0x1000 + cycle * 0x100 + index as u32, ThreadId::from(id)))
+            .collect();
+        map.replace(entries);
+    }
+
+    #[test]
+    fn thread_id_survives_refreshes_while_port_changes()
+    {
+        let mut map = ThreadHandleMap::new();
+        let main = ThreadId::from(7_000_001);
+        let mut seen_ports = Vec::new();
+
+        for cycle in 0..4 {
+            enumerate(&mut map, cycle, &[7_000_001, 7_000_002]);
+            let port = map.handle_for(main).expect("main thread should stay resolvable");
+            assert_eq!(map.id_for(port), Some(main));
+            assert_eq!(map.ids(), vec![main, ThreadId::from(7_000_002)]);
+            seen_ports.push(port);
+        }
+
+        seen_ports.dedup();
+        assert_eq!(seen_ports.len(), 4, "ports should differ across refreshes");
+    }
+
+    #[test]
+    fn exited_thread_is_forgotten_even_if_port_is_reused()
+    {
+        let mut map = ThreadHandleMap::new();
+        map.replace(vec![(0x1003, ThreadId::from(10)), (0x1107, ThreadId::from(11))]);
+
+        // Thread 11 exits and the kernel reuses its port name for new thread 12
+        map.replace(vec![(0x1003, ThreadId::from(10)), (0x1107, ThreadId::from(12))]);
+
+        assert!(!map.contains(ThreadId::from(11)));
+        assert_eq!(map.handle_for(ThreadId::from(11)), None);
+        assert_eq!(map.id_for(0x1107), Some(ThreadId::from(12)));
+        assert_eq!(map.handles(), vec![0x1003, 0x1107]);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn replace_reports_only_threads_started_since_the_last_enumeration()
+    {
+        let mut map = ThreadHandleMap::new();
+        assert_eq!(map.replace(vec![(0x1003, ThreadId::from(10))]), vec![0x1003]);
+
+        // Thread 10 changes port, thread 11 starts and later exits while 12 starts
+        let started = map.replace(vec![(0x1203, ThreadId::from(10)), (0x1207, ThreadId::from(11))]);
+        assert_eq!(started, vec![0x1207]);
+        let started = map.replace(vec![(0x1303, ThreadId::from(10)), (0x1207, ThreadId::from(12))]);
+        assert_eq!(started, vec![0x1207]);
+        assert!(map.replace(vec![(0x1303, ThreadId::from(10))]).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+//! Listing the processes ferros could attach to.
+//!
+//! [`list_processes`] reads every process's PID, name and executable path:
+//! through libproc's `proc_listpids`, `proc_name` and `proc_pidpath` on
+//! macOS, and from `/proc` on Linux. A path the caller may not read (another
+//! user's process) is left out rather than failing the listing.
+//!
+//! [`find_processes`] narrows that to the processes whose name contains a
+//! substring, for `ferros attach --name` and `ferros ps`.
+//!
+//! ```rust,no_run
+//! use ferros_core::processes::find_processes;
+//!
+//! for process in find_processes("my_app")? {
+//!     println!("{} {}", process.pid.0, process.name);
+//! }
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use std::path::PathBuf;
+
+use crate::error::Result;
+use crate::types::ProcessId;
+
+/// A running process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo
+{
+    /// Its process ID.
+    pub pid: ProcessId,
+    /// Short name the kernel keeps for it, usually its executable's file name.
+    pub name: String,
+    /// Full path of its executable, when it can be read.
+    pub path: Option<PathBuf>,
+}
+
+impl ProcessInfo
+{
+    /// Whether the name contains `filter`, ignoring case.
+    #[must_use]
+    pub fn matches(&self, filter: &str) -> bool
+    {
+        self.name.to_lowercase().contains(&filter.to_lowercase())
+    }
+}
+
+/// Every process on the system, lowest PID first.
+///
+/// ## Errors
+///
+/// - `Io`: The process list couldn't be read
+/// - `InvalidArgument`: Listing processes isn't supported on this platform
+pub fn list_processes() -> Result<Vec<ProcessInfo>>
+{
+    let mut processes = platform_processes()?;
+    processes.sort_by_key(|process| process.pid.0);
+    Ok(processes)
+}
+
+/// Processes whose name contains `filter`, ignoring case, lowest PID first.
+///
+/// ferros itself is left out, so `--name ferros` doesn't find the debugger.
+///
+/// ## Errors
+///
+/// As [`list_processes`].
+pub fn find_processes(filter: &str) -> Result<Vec<ProcessInfo>>
+{
+    let own = std::process::id();
+    Ok(list_processes()?
+        .into_iter()
+        .filter(|process| process.pid.0 != own && process.matches(filter))
+        .collect())
+}
+
+#[cfg(target_os = "macos")]
+fn platform_processes() -> Result<Vec<ProcessInfo>>
+{
+    use libproc::libproc::proc_pid;
+    use libproc::processes::{ProcFilter, pids_by_type};
+
+    let pids = pids_by_type(ProcFilter::All)?;
+    Ok(pids
+        .into_iter()
+        // PID 0 is the kernel, which can't be attached to
+        .filter(|pid| *pid != 0)
+        .filter_map(|pid| {
+            let path = proc_pid::pidpath(pid as i32).ok().map(PathBuf::from);
+            // Processes that exit while the list is read have no name either
+            let name = proc_pid::name(pid as i32).ok().filter(|name| !name.is_empty()).or_else(|| {
+                path.as_ref()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+            })?;
+            Some(ProcessInfo {
+                pid: ProcessId(pid),
+                name,
+                path,
+            })
+        })
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+fn platform_processes() -> Result<Vec<ProcessInfo>>
+{
+    let mut processes = Vec::new();
+    for entry in std::fs::read_dir("/proc")? {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        // Processes that exit while the list is read have no comm file
+        let Ok(name) = std::fs::read_to_string(entry.path().join("comm")) else {
+            continue;
+        };
+        processes.push(ProcessInfo {
+            pid: ProcessId(pid),
+            name: name.trim_end().to_string(),
+            path: std::fs::read_link(entry.path().join("exe")).ok(),
+        });
+    }
+    Ok(processes)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn platform_processes() -> Result<Vec<ProcessInfo>>
+{
+    Err(crate::error::DebuggerError::InvalidArgument(
+        "Listing processes is not supported on this platform".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn names_match_substrings_ignoring_case()
+    {
+        let process = ProcessInfo {
+            pid: ProcessId(42),
+            name: "My_App".to_string(),
+            path: None,
+        };
+        assert!(process.matches("my_app"));
+        assert!(process.matches("APP"));
+        assert!(!process.matches("other"));
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    #[test]
+    fn the_listing_includes_this_process_but_searches_leave_it_out()
+    {
+        let own = std::process::id();
+        let processes = list_processes().unwrap();
+        let this = processes
+            .iter()
+            .find(|process| process.pid.0 == own)
+            .expect("this process is listed");
+        assert!(!this.name.is_empty());
+        assert!(processes.windows(2).all(|pair| pair[0].pid.0 < pair[1].pid.0));
+
+        assert!(find_processes(&this.name).unwrap().iter().all(|process| process.pid.0 != own));
+    }
+}
@@ -0,0 +1,513 @@
+//! Region checkpoints: what a stretch of execution changed in chosen memory ranges.
+//!
+//! Register the ranges of interest in a [`RangeSet`], [`capture`] them while
+//! the target is stopped, let it run to the next stop and [`diff`] the
+//! snapshots against memory as it is now. Each [`RangeDiff`] groups the
+//! changed offsets into contiguous [`DiffRun`]s.
+//!
+//! Both reads go through the streaming path
+//! ([`TargetInspector::read_memory_streamed`]), so unreadable pages become holes
+//! instead of failing the whole range. The diff is computed chunk by chunk as
+//! the new bytes arrive: only the snapshot and one chunk are held, and each
+//! run keeps at most [`RUN_PREVIEW_BYTES`] of its old and new bytes.
+//!
+//! ```rust
+//! use ferros_core::range_checkpoints::diff_bytes;
+//!
+//! let old = [0_u8; 16];
+//! let mut new = old;
+//! new[2..5].fill(0xff);
+//! new[9] = 1;
+//!
+//! let runs = diff_bytes(&old, &new);
+//! assert_eq!(runs.len(), 2);
+//! assert_eq!((runs[0].offset, runs[0].len), (2, 3));
+//! assert_eq!(runs[1].to_string(), "+0x9 (1 byte): 00 → 01");
+//! ```
+
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+
+use crate::error::{DebuggerError, Result};
+use crate::inspector::TargetInspector;
+use crate::stream::{MemoryHole, StreamOptions};
+use crate::types::{Address, AddressExpr, parse_address};
+
+/// Most bytes a [`RangeSet`] may cover in total; every capture holds a copy of all of them.
+pub const MAX_CHECKPOINT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Old and new bytes kept per run for display.
+pub const RUN_PREVIEW_BYTES: usize = 16;
+
+/// Chunk size of the reads behind captures and diffs.
+const CHECKPOINT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A registered memory range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchedRange
+{
+    /// Name given when the range was registered.
+    pub name: Option<String>,
+    /// First byte of the range.
+    pub start: Address,
+    /// Length in bytes.
+    pub len: usize,
+}
+
+impl WatchedRange
+{
+    /// Name of the range, or its start address if it has none.
+    #[must_use]
+    pub fn label(&self) -> String
+    {
+        self.name.clone().unwrap_or_else(|| self.start.to_string())
+    }
+
+    fn end(&self) -> Address
+    {
+        Address::from(self.start.value() + self.len as u64)
+    }
+}
+
+/// Ranges to capture, bounded by [`MAX_CHECKPOINT_BYTES`] in total.
+#[derive(Debug, Clone, Default)]
+pub struct RangeSet
+{
+    ranges: Vec<WatchedRange>,
+}
+
+impl RangeSet
+{
+    /// Create an empty set.
+    #[must_use]
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Register `len` bytes at `start`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidArgument` if the range is empty, runs past the end of
+    /// the address space, or would take the set over [`MAX_CHECKPOINT_BYTES`].
+    pub fn add(&mut self, start: Address, len: usize, name: Option<String>) -> Result<()>
+    {
+        if len == 0 {
+            return Err(DebuggerError::InvalidArgument("range length must be non-zero".to_string()));
+        }
+        if start.checked_add(len as u64).is_none() {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "{len} bytes at {start} run past the end of the address space"
+            )));
+        }
+        let total = self.total_bytes() + len;
+        if total > MAX_CHECKPOINT_BYTES {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "checkpointed ranges would cover {total} bytes, more than the {MAX_CHECKPOINT_BYTES} byte budget"
+            )));
+        }
+        self.ranges.push(WatchedRange { name, start, len });
+        Ok(())
+    }
+
+    /// Forget every range.
+    pub fn clear(&mut self)
+    {
+        self.ranges.clear();
+    }
+
+    /// Registered ranges, in registration order.
+    #[must_use]
+    pub fn ranges(&self) -> &[WatchedRange]
+    {
+        &self.ranges
+    }
+
+    /// Bytes covered by all ranges.
+    #[must_use]
+    pub fn total_bytes(&self) -> usize
+    {
+        self.ranges.iter().map(|range| range.len).sum()
+    }
+
+    /// Whether no ranges are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.ranges.is_empty()
+    }
+}
+
+/// Contents of a range at capture time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeSnapshot
+{
+    /// The range captured.
+    pub range: WatchedRange,
+    /// Its bytes; unreadable pages are zero.
+    pub bytes: Vec<u8>,
+    /// Pages that couldn't be read.
+    pub holes: Vec<MemoryHole>,
+}
+
+/// Contiguous changed bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRun
+{
+    /// Offset of the first changed byte from the start of the range.
+    pub offset: u64,
+    /// Number of changed bytes.
+    pub len: u64,
+    /// Up to [`RUN_PREVIEW_BYTES`] of the bytes before.
+    pub old: Vec<u8>,
+    /// Up to [`RUN_PREVIEW_BYTES`] of the bytes after.
+    pub new: Vec<u8>,
+}
+
+impl fmt::Display for DiffRun
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let hex = |bytes: &[u8]| bytes.iter().map(|byte| format!("{byte:02x}")).collect::<Vec<_>>().join(" ");
+        let more = if self.len > self.old.len() as u64 { " …" } else { "" };
+        let unit = if self.len == 1 { "byte" } else { "bytes" };
+        write!(
+            f,
+            "+0x{:x} ({} {unit}): {}{more} → {}{more}",
+            self.offset,
+            self.len,
+            hex(&self.old),
+            hex(&self.new)
+        )
+    }
+}
+
+/// What changed in one range between a capture and a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeDiff
+{
+    /// The range compared.
+    pub range: WatchedRange,
+    /// Changed bytes grouped into runs, in offset order.
+    pub runs: Vec<DiffRun>,
+    /// Total changed bytes.
+    pub changed: u64,
+    /// Pages unreadable in either read; changes inside them aren't reported.
+    pub holes: Vec<MemoryHole>,
+}
+
+impl RangeDiff
+{
+    /// One-line summary such as `buf: 96 of 4096 bytes changed in 2 runs`.
+    #[must_use]
+    pub fn summary(&self) -> String
+    {
+        let label = self.range.label();
+        let mut summary = if self.changed == 0 {
+            format!("{label}: unchanged ({} bytes)", self.range.len)
+        } else {
+            let runs = if self.runs.len() == 1 { "run" } else { "runs" };
+            format!(
+                "{label}: {} of {} bytes changed in {} {runs}",
+                self.changed,
+                self.range.len,
+                self.runs.len()
+            )
+        };
+        let unreadable: u64 = self.holes.iter().map(|hole| hole.len).sum();
+        if unreadable > 0 {
+            let _ = write!(summary, ", {unreadable} bytes unreadable");
+        }
+        summary
+    }
+}
+
+/// Parse a range length: decimal, or hex with a `0x` prefix.
+///
+/// ## Errors
+///
+/// Returns `InvalidArgument` if `text` isn't a number.
+pub fn parse_length(text: &str) -> Result<usize>
+{
+    let parsed = match text.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    parsed.map_err(|_| DebuggerError::InvalidArgument(format!("invalid length '{text}'")))
+}
+
+/// Parse `ADDRESS:LEN`, e.g. `0x1000:4096` or `buf+8:0x40`.
+///
+/// The address is anything [`parse_address`] accepts; the length follows the last `:`.
+///
+/// ## Errors
+///
+/// Returns `InvalidArgument` if the `:` is missing or either half doesn't parse.
+pub fn parse_range_spec(spec: &str) -> Result<(AddressExpr, usize)>
+{
+    let (address, len) = spec
+        .rsplit_once(':')
+        .ok_or_else(|| DebuggerError::InvalidArgument(format!("expected ADDRESS:LEN, got '{spec}'")))?;
+    Ok((parse_address(address)?, parse_length(len)?))
+}
+
+/// Groups changed bytes into runs as chunks of a range are compared.
+#[derive(Debug, Default)]
+struct RunCollector
+{
+    runs: Vec<DiffRun>,
+    changed: u64,
+}
+
+impl RunCollector
+{
+    /// Compare `old` and `new`, which start `offset` bytes into the range.
+    ///
+    /// A run that reaches the end of one chunk is continued by a change at
+    /// the start of the next.
+    fn compare(&mut self, offset: u64, old: &[u8], new: &[u8])
+    {
+        for (index, (&before, &after)) in old.iter().zip(new).enumerate() {
+            if before == after {
+                continue;
+            }
+            let at = offset + index as u64;
+            self.changed += 1;
+            match self.runs.last_mut() {
+                Some(run) if run.offset + run.len == at => {
+                    run.len += 1;
+                    if run.old.len() < RUN_PREVIEW_BYTES {
+                        run.old.push(before);
+                        run.new.push(after);
+                    }
+                }
+                _ => self.runs.push(DiffRun {
+                    offset: at,
+                    len: 1,
+                    old: vec![before],
+                    new: vec![after],
+                }),
+            }
+        }
+    }
+}
+
+/// Group the bytes that differ between `old` and `new` into runs.
+///
+/// Only the common prefix of the two slices is compared.
+#[must_use]
+pub fn diff_bytes(old: &[u8], new: &[u8]) -> Vec<DiffRun>
+{
+    let mut collector = RunCollector::default();
+    collector.compare(0, old, new);
+    collector.runs
+}
+
+/// Sink for a streamed re-read that compares each chunk with the snapshot as it arrives.
+struct DiffSink<'a>
+{
+    old: &'a [u8],
+    offset: usize,
+    collector: RunCollector,
+}
+
+impl Write for DiffSink<'_>
+{
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize>
+    {
+        let end = (self.offset + bytes.len()).min(self.old.len());
+        self.collector.compare(self.offset as u64, &self.old[self.offset..end], bytes);
+        self.offset = end;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()>
+    {
+        Ok(())
+    }
+}
+
+/// Read every range in `ranges`.
+///
+/// ## Errors
+///
+/// Returns `InvalidArgument` if the ranges cover more than
+/// [`MAX_CHECKPOINT_BYTES`] in total. Unreadable pages are not an error;
+/// they are recorded in each snapshot's holes.
+pub fn capture<D: TargetInspector + ?Sized>(debugger: &D, ranges: &[WatchedRange]) -> Result<Vec<RangeSnapshot>>
+{
+    let total: usize = ranges.iter().map(|range| range.len).sum();
+    if total > MAX_CHECKPOINT_BYTES {
+        return Err(DebuggerError::InvalidArgument(format!(
+            "ranges cover {total} bytes, more than the {MAX_CHECKPOINT_BYTES} byte budget"
+        )));
+    }
+    ranges
+        .iter()
+        .map(|range| {
+            let mut bytes = Vec::with_capacity(range.len);
+            let stats = debugger.read_memory_streamed(
+                range.start..range.end(),
+                CHECKPOINT_CHUNK_SIZE,
+                &mut bytes,
+                &mut StreamOptions::new(),
+            )?;
+            Ok(RangeSnapshot {
+                range: range.clone(),
+                bytes,
+                holes: stats.holes,
+            })
+        })
+        .collect()
+}
+
+/// Re-read each snapshot's range and report what changed.
+///
+/// Runs that lie entirely in a page unreadable in either read are dropped.
+///
+/// ## Errors
+///
+/// Returns whatever [`TargetInspector::read_memory_streamed`] returns for a bad range.
+pub fn diff<D: TargetInspector + ?Sized>(debugger: &D, snapshots: &[RangeSnapshot]) -> Result<Vec<RangeDiff>>
+{
+    snapshots
+        .iter()
+        .map(|snapshot| {
+            let range = &snapshot.range;
+            let mut sink = DiffSink {
+                old: &snapshot.bytes,
+                offset: 0,
+                collector: RunCollector::default(),
+            };
+            let stats = debugger.read_memory_streamed(
+                range.start..range.end(),
+                CHECKPOINT_CHUNK_SIZE,
+                &mut sink,
+                &mut StreamOptions::new(),
+            )?;
+
+            let mut holes = snapshot.holes.clone();
+            holes.extend(stats.holes);
+            holes.sort_by_key(|hole| hole.start);
+            let base = range.start.value();
+            let in_hole = |run: &DiffRun| {
+                let (start, end) = (base + run.offset, base + run.offset + run.len);
+                holes
+                    .iter()
+                    .any(|hole| hole.start.value() <= start && end <= hole.start.value() + hole.len)
+            };
+            let mut collector = sink.collector;
+            let runs: Vec<DiffRun> = collector.runs.drain(..).filter(|run| !in_hole(run)).collect();
+            Ok(RangeDiff {
+                range: range.clone(),
+                changed: runs.iter().map(|run| run.len).sum(),
+                runs,
+                holes,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn range(len: usize) -> WatchedRange
+    {
+        WatchedRange {
+            name: Some("buf".to_string()),
+            start: Address::from(0x1000),
+            len,
+        }
+    }
+
+    #[test]
+    fn runs_group_contiguous_changes()
+    {
+        let old = vec![0_u8; 64];
+        let mut new = old.clone();
+        new[0] = 1;
+        new[10..14].copy_from_slice(&[1, 2, 3, 4]);
+        new[15] = 9;
+        new[63] = 7;
+
+        let runs = diff_bytes(&old, &new);
+        let spans: Vec<(u64, u64)> = runs.iter().map(|run| (run.offset, run.len)).collect();
+        assert_eq!(spans, [(0, 1), (10, 4), (15, 1), (63, 1)]);
+        assert_eq!(runs[1].new, [1, 2, 3, 4]);
+        assert_eq!(runs[1].to_string(), "+0xa (4 bytes): 00 00 00 00 → 01 02 03 04");
+
+        assert!(diff_bytes(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn runs_continue_across_chunks_and_cap_their_preview()
+    {
+        let old = vec![0_u8; 100];
+        let new = [0xab_u8; 100];
+        let mut sink = DiffSink {
+            old: &old,
+            offset: 0,
+            collector: RunCollector::default(),
+        };
+        for chunk in new.chunks(7) {
+            sink.write_all(chunk).unwrap();
+        }
+
+        let runs = sink.collector.runs;
+        assert_eq!(runs.len(), 1);
+        assert_eq!((runs[0].offset, runs[0].len), (0, 100));
+        assert_eq!(runs[0].old.len(), RUN_PREVIEW_BYTES);
+        assert_eq!(sink.collector.changed, 100);
+        assert!(runs[0].to_string().ends_with("ab …"));
+    }
+
+    #[test]
+    fn range_set_enforces_the_budget()
+    {
+        let mut set = RangeSet::new();
+        set.add(Address::from(0x1000), MAX_CHECKPOINT_BYTES - 16, None).unwrap();
+        set.add(Address::from(0x10), 16, Some("tail".to_string())).unwrap();
+        assert!(set.add(Address::from(0x20), 1, None).is_err());
+        assert!(set.add(Address::from(0x20), 0, None).is_err());
+        assert!(set.add(Address::from(u64::MAX), 2, None).is_err());
+        assert_eq!(set.ranges()[0].label(), "0x0000000000001000");
+        assert_eq!(set.ranges()[1].label(), "tail");
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn range_specs_split_on_the_last_colon()
+    {
+        let (address, len) = parse_range_spec("0x1000:4096").unwrap();
+        assert_eq!((address, len), (AddressExpr::Absolute(Address::from(0x1000)), 4096));
+        let (address, len) = parse_range_spec("buf+8:0x40").unwrap();
+        assert_eq!(address.to_string(), "buf+0x8");
+        assert_eq!(len, 0x40);
+        assert!(parse_range_spec("0x1000").is_err());
+        assert!(parse_range_spec("0x1000:lots").is_err());
+        assert_eq!(parse_length("0x10").unwrap(), 16);
+    }
+
+    #[test]
+    fn summaries_count_runs_and_holes()
+    {
+        let mut diff = RangeDiff {
+            range: range(4096),
+            runs: diff_bytes(&[0, 0, 0, 0], &[1, 0, 1, 1]),
+            changed: 3,
+            holes: Vec::new(),
+        };
+        assert_eq!(diff.summary(), "buf: 3 of 4096 bytes changed in 2 runs");
+        diff.holes.push(MemoryHole {
+            start: Address::from(0x2000),
+            len: 4096,
+        });
+        diff.runs.clear();
+        diff.changed = 0;
+        assert_eq!(diff.summary(), "buf: unchanged (4096 bytes), 4096 bytes unreadable");
+    }
+}
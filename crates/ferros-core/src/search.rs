@@ -0,0 +1,256 @@
+//! Searching target memory for a byte pattern.
+//!
+//! [`search`] walks the target's regions in address order and reads each in
+//! [`SEARCH_CHUNK_SIZE`] chunks. The last `pattern.len() - 1` bytes of a chunk
+//! are carried into the next, so a match that straddles two chunks, or two
+//! adjacent regions, is still found. A chunk that can't be read ends the run
+//! of carried bytes and the search goes on after it.
+//!
+//! Regions that aren't readable and regions larger than [`LARGE_REGION`] are
+//! skipped unless the [`MemorySearchScope`] includes them, and the search
+//! stops once it has [`MemorySearchScope::max_hits`] hits.
+//!
+//! ```rust
+//! use ferros_core::search::{MemorySearchScope, search};
+//! use ferros_core::types::{Address, MemoryRegion, MemoryRegionId};
+//!
+//! let regions = [MemoryRegion::new(
+//!     MemoryRegionId(0),
+//!     Address::from(0x1000),
+//!     Address::from(0x1010),
+//!     "rw-".to_string(),
+//!     None,
+//! )];
+//! let memory = *b"..ferros..ferros";
+//! let found = search(
+//!     b"ferros",
+//!     &regions,
+//!     &MemorySearchScope::default(),
+//!     |address, len| {
+//!         let start = (address.value() - 0x1000) as usize;
+//!         Ok(memory[start..start + len].to_vec())
+//!     },
+//! )?;
+//! assert_eq!(found.hits, [Address::from(0x1002), Address::from(0x100a)]);
+//! assert!(!found.truncated);
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use crate::error::{DebuggerError, Result};
+use crate::types::{Address, MemoryRegion};
+
+/// Hits a search returns by default before it stops.
+pub const MAX_SEARCH_HITS: usize = 1000;
+
+/// Regions larger than this are skipped unless [`MemorySearchScope::large`] is set.
+pub const LARGE_REGION: u64 = 256 * 1024 * 1024;
+
+/// Bytes read at a time.
+pub const SEARCH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Which regions a search reads, and when it stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemorySearchScope
+{
+    /// Also read regions without `r`. Most reads of them fail and are skipped.
+    pub unreadable: bool,
+    /// Also read regions larger than [`LARGE_REGION`].
+    pub large: bool,
+    /// Stop after this many hits.
+    pub max_hits: usize,
+}
+
+impl Default for MemorySearchScope
+{
+    fn default() -> Self
+    {
+        Self {
+            unreadable: false,
+            large: false,
+            max_hits: MAX_SEARCH_HITS,
+        }
+    }
+}
+
+impl MemorySearchScope
+{
+    /// Whether `region` is searched.
+    #[must_use]
+    pub fn includes(&self, region: &MemoryRegion) -> bool
+    {
+        (self.unreadable || region.is_readable()) && (self.large || region.size() <= LARGE_REGION)
+    }
+}
+
+/// What a search found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemorySearch
+{
+    /// Start of every match, lowest first.
+    pub hits: Vec<Address>,
+    /// The search stopped at [`MemorySearchScope::max_hits`]; there may be more.
+    pub truncated: bool,
+    /// Regions the scope left out.
+    pub skipped_regions: usize,
+}
+
+/// Search `regions` for `pattern`, reading them with `read`.
+///
+/// `read` is called with chunks of at most [`SEARCH_CHUNK_SIZE`] bytes; a
+/// failed read or a short one only leaves that chunk's bytes unsearched.
+///
+/// ## Errors
+///
+/// - `InvalidArgument`: `pattern` is empty
+pub fn search(
+    pattern: &[u8],
+    regions: &[MemoryRegion],
+    scope: &MemorySearchScope,
+    mut read: impl FnMut(Address, usize) -> Result<Vec<u8>>,
+) -> Result<MemorySearch>
+{
+    if pattern.is_empty() {
+        return Err(DebuggerError::InvalidArgument("Nothing to search for".to_string()));
+    }
+    let mut regions: Vec<&MemoryRegion> = regions.iter().collect();
+    regions.sort_by_key(|region| region.start);
+
+    let mut found = MemorySearch::default();
+    // Tail of the last chunk read, and the address just after it
+    let mut carry: Vec<u8> = Vec::new();
+    let mut carry_end = Address::from(0);
+    for region in regions {
+        if !scope.includes(region) {
+            found.skipped_regions += 1;
+            continue;
+        }
+        let mut address = region.start;
+        while address < region.end {
+            let len = usize::try_from(region.end.value() - address.value())
+                .map_or(SEARCH_CHUNK_SIZE, |left| left.min(SEARCH_CHUNK_SIZE));
+            let chunk = read(address, len).unwrap_or_default();
+            if carry_end != address {
+                carry.clear();
+            }
+            let start = Address::from(address.value() - carry.len() as u64);
+            carry.extend_from_slice(&chunk[..chunk.len().min(len)]);
+            for (offset, window) in carry.windows(pattern.len()).enumerate() {
+                if window != pattern {
+                    continue;
+                }
+                if found.hits.len() == scope.max_hits {
+                    found.truncated = true;
+                    return Ok(found);
+                }
+                found.hits.push(Address::from(start.value() + offset as u64));
+            }
+            carry_end = Address::from(start.value() + carry.len() as u64);
+            let keep = carry.len().min(pattern.len() - 1);
+            carry.drain(..carry.len() - keep);
+            address = Address::from(address.value() + len as u64);
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::MemoryRegionId;
+
+    fn region(start: u64, end: u64, permissions: &str) -> MemoryRegion
+    {
+        MemoryRegion::new(
+            MemoryRegionId(0),
+            Address::from(start),
+            Address::from(end),
+            permissions.to_string(),
+            None,
+        )
+    }
+
+    /// Reads from `memory`, which starts at 0x10000; 0xdd outside it
+    fn reader(memory: &[u8]) -> impl FnMut(Address, usize) -> Result<Vec<u8>> + '_
+    {
+        move |address, len| {
+            let start = usize::try_from(address.value() - 0x10000).unwrap();
+            Ok((start..start + len)
+                .map(|at| memory.get(at).copied().unwrap_or(0xdd))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn matches_straddling_chunks_and_regions_are_found()
+    {
+        let size = SEARCH_CHUNK_SIZE * 2;
+        let mut memory = vec![0; size];
+        // Across the first chunk boundary, inside the second chunk, and across the two regions
+        memory[SEARCH_CHUNK_SIZE - 2..SEARCH_CHUNK_SIZE + 2].copy_from_slice(b"NEED");
+        memory[size / 2 + 100..size / 2 + 104].copy_from_slice(b"NEED");
+        memory[size - 1] = b'N';
+        let regions = [
+            region(0x10000, 0x10000 + size as u64, "rw-"),
+            region(0x10000 + size as u64, 0x10000 + size as u64 + 16, "r--"),
+        ];
+        let mut tail = memory.clone();
+        tail.extend_from_slice(b"EED");
+
+        let found = search(b"NEED", &regions, &MemorySearchScope::default(), reader(&tail)).unwrap();
+        let at = |offset: usize| Address::from(0x10000 + offset as u64);
+        assert_eq!(found.hits, [at(SEARCH_CHUNK_SIZE - 2), at(size / 2 + 100), at(size - 1)]);
+        assert!(!found.truncated);
+    }
+
+    #[test]
+    fn hits_stop_at_the_cap_and_failed_reads_break_the_carry()
+    {
+        let memory = b"aaaaaaaa".to_vec();
+        let regions = [region(0x10000, 0x10008, "rw-")];
+        let scope = MemorySearchScope {
+            max_hits: 3,
+            ..MemorySearchScope::default()
+        };
+        let found = search(b"aa", &regions, &scope, reader(&memory)).unwrap();
+        assert_eq!(found.hits.len(), 3);
+        assert!(found.truncated);
+
+        // A chunk that can't be read doesn't join the bytes either side of it
+        let regions = [region(0x10000, 0x10000 + SEARCH_CHUNK_SIZE as u64 * 3, "rw-")];
+        let mut memory = vec![0; SEARCH_CHUNK_SIZE * 3];
+        memory[SEARCH_CHUNK_SIZE - 1] = b'x';
+        memory[SEARCH_CHUNK_SIZE * 2] = b'y';
+        let mut read = reader(&memory);
+        let found = search(b"xy", &regions, &MemorySearchScope::default(), |address, len| {
+            if address.value() == 0x10000 + SEARCH_CHUNK_SIZE as u64 {
+                return Err(DebuggerError::InvalidArgument("unreadable".to_string()));
+            }
+            read(address, len)
+        })
+        .unwrap();
+        assert!(found.hits.is_empty());
+    }
+
+    #[test]
+    fn unreadable_and_large_regions_are_skipped_unless_included()
+    {
+        let memory = vec![b'z'; 16];
+        let regions = [
+            region(0x10000, 0x10008, "---"),
+            region(0x10008, 0x10010, "r--"),
+            region(0x10010, 0x10010 + LARGE_REGION + 1, "rw-"),
+        ];
+        let found = search(b"z", &regions, &MemorySearchScope::default(), reader(&memory)).unwrap();
+        assert_eq!(found.hits.len(), 8);
+        assert_eq!(found.skipped_regions, 2);
+
+        let scope = MemorySearchScope {
+            unreadable: true,
+            ..MemorySearchScope::default()
+        };
+        let found = search(b"z", &regions[..2], &scope, reader(&memory)).unwrap();
+        assert_eq!(found.hits.len(), 16);
+        assert!(search(b"", &regions, &scope, reader(&memory)).is_err());
+    }
+}
@@ -0,0 +1,999 @@
+//! Session recording and the machine-readable session summary.
+//!
+//! Front-ends (headless CLI, TUI) feed a [`SessionRecorder`] with the same
+//! [`DebuggerEvent`] stream and ask it for a [`SessionSummary`] when the session
+//! ends, so every mode produces an identical document. The summary is plain
+//! JSON intended for CI artifact collection:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "invocation": { "mode": "launch", "program": "/bin/app", "args": [], "pid": 4242 },
+//!   "started_at_ms": 1700000000000,
+//!   "ended_at_ms": 1700000001500,
+//!   "outcome": { "kind": "exited", "code": 3 },
+//!   "stops_by_reason": { "breakpoint": 2, "exited": 1 },
+//!   "breakpoints": [ { "id": 1, "address": 4096, "kind": "software", "enabled": true, "hit_count": 2 } ],
+//!   "timeline": [ ... ],
+//!   "memory_samples": [],
+//!   "artifacts": [],
+//!   "violations": [],
+//!   "error": null
+//! }
+//! ```
+//!
+//! The recorder tolerates partially-initialized sessions: if attach or launch
+//! fails, [`SessionRecorder::record_error`] captures the failure and the summary
+//! still serializes with an `not_started` outcome.
+//!
+//! State the user builds up while debugging a particular program (pinned
+//! values and notes) lives separately in a per-binary [`SessionFile`] under
+//! `~/.ferros/sessions/`, so it is restored the next time that binary is
+//! launched.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::assertions::Violation;
+use crate::breakpoints::{BreakpointInfo, BreakpointKind};
+use crate::debugger::Debugger;
+use crate::error::{DebuggerError, Result};
+use crate::events::DebuggerEvent;
+use crate::notes::Note;
+use crate::pins::PinSpec;
+use crate::symbols::unwind::{RecursionCycle, UnwindOptions};
+use crate::types::{StackFrame, StopReason};
+use crate::watchdog::TimeoutAction;
+
+/// Version of the summary document layout. Bump on incompatible changes.
+pub const SUMMARY_SCHEMA_VERSION: u32 = 1;
+
+/// Default number of timeline entries kept for the summary.
+pub const DEFAULT_TIMELINE_ENTRIES: usize = 50;
+
+/// How the debugger was pointed at the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionInvocation
+{
+    /// `"launch"`, `"attach"` or `"mi"`.
+    pub mode: String,
+    /// Program path (launch only).
+    pub program: Option<String>,
+    /// Program arguments (launch only).
+    pub args: Vec<String>,
+    /// Target process ID, once known.
+    pub pid: Option<u32>,
+}
+
+impl SessionInvocation
+{
+    /// Invocation for `ferros launch <program> <args...>`.
+    #[must_use]
+    pub fn launch(program: impl Into<String>, args: Vec<String>) -> Self
+    {
+        Self {
+            mode: "launch".to_string(),
+            program: Some(program.into()),
+            args,
+            pid: None,
+        }
+    }
+
+    /// Invocation for `ferros attach <pid>`.
+    #[must_use]
+    pub fn attach(pid: u32) -> Self
+    {
+        Self {
+            mode: "attach".to_string(),
+            program: None,
+            args: Vec::new(),
+            pid: Some(pid),
+        }
+    }
+
+    /// Invocation for `ferros mi`, where the client picks the target later.
+    #[must_use]
+    pub fn mi() -> Self
+    {
+        Self {
+            mode: "mi".to_string(),
+            program: None,
+            args: Vec::new(),
+            pid: None,
+        }
+    }
+}
+
+/// How the session ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionOutcome
+{
+    /// The target exited on its own.
+    Exited
+    {
+        /// Exit status reported by the OS.
+        code: i32,
+    },
+    /// The target was stopped by a fatal signal when the session ended.
+    Crashed
+    {
+        /// Signal number.
+        signal: i32,
+        /// Symbolicated frame where the target was stopped, if it could be unwound.
+        frame: Option<CrashFrame>,
+    },
+    /// A thread ran into its stack guard page, almost always runaway recursion.
+    StackOverflow
+    {
+        /// Thread that overflowed.
+        thread: u64,
+        /// Stack pointer when the fault was taken.
+        sp: u64,
+        /// Start of the guard region that was hit.
+        guard_page: u64,
+        /// Symbolicated frame where the target was stopped, if it could be unwound.
+        frame: Option<CrashFrame>,
+        /// The recursive run found in the backtrace, if any.
+        recursion: Option<RecursionSummary>,
+    },
+    /// The target neither stopped nor exited before the `--timeout` deadline.
+    TimedOut
+    {
+        /// The timeout that expired, in milliseconds.
+        after_ms: u64,
+        /// What was done with the target: `"kill"`, `"detach"` or `"keep"`.
+        action: String,
+    },
+    /// The target disappeared without ferros seeing it exit, e.g. killed from
+    /// another terminal.
+    TerminatedExternally
+    {
+        /// PID of the process that disappeared.
+        pid: u32,
+    },
+    /// The debugger detached (or quit) while the target was still alive.
+    Detached,
+    /// Attach or launch never succeeded.
+    NotStarted,
+}
+
+/// Symbolicated location of a crash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashFrame
+{
+    /// Program counter.
+    pub pc: u64,
+    /// Function name, if symbolicated.
+    pub function: Option<String>,
+    /// Source file, if known, as a normalized local path.
+    pub file: Option<String>,
+    /// Source file as DWARF recorded it, when that differs from `file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dwarf_file: Option<String>,
+    /// Source line, if known.
+    pub line: Option<u32>,
+}
+
+impl From<&StackFrame> for CrashFrame
+{
+    fn from(frame: &StackFrame) -> Self
+    {
+        Self {
+            pc: frame.pc.value(),
+            function: frame.symbol.as_ref().map(|symbol| symbol.display_name().to_string()),
+            file: frame.location.as_ref().map(|location| location.file.clone()),
+            dwarf_file: frame
+                .location
+                .as_ref()
+                .and_then(|location| location.original_file().map(str::to_string)),
+            line: frame.location.as_ref().and_then(|location| location.line),
+        }
+    }
+}
+
+/// Collapsed recursion reported with a stack overflow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecursionSummary
+{
+    /// Recursive function, if symbolicated.
+    pub function: Option<String>,
+    /// Number of physical frames in one repetition (1 for direct recursion).
+    pub period: usize,
+    /// Approximate number of repetitions on the stack.
+    pub repeats: usize,
+    /// Whether `repeats` is only a lower bound because the walk was cut short.
+    pub truncated: bool,
+}
+
+impl From<&RecursionCycle> for RecursionSummary
+{
+    fn from(cycle: &RecursionCycle) -> Self
+    {
+        Self {
+            function: cycle.function.clone(),
+            period: cycle.period,
+            repeats: cycle.repeats,
+            truncated: cycle.truncated,
+        }
+    }
+}
+
+/// Final state of one breakpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakpointSummary
+{
+    /// Breakpoint identifier.
+    pub id: u64,
+    /// Address the breakpoint was placed at.
+    pub address: u64,
+    /// `"software"`, `"hardware"` or `"watchpoint"`.
+    pub kind: String,
+    /// Whether it was enabled at session end.
+    pub enabled: bool,
+    /// Number of hits over the whole session.
+    pub hit_count: u64,
+}
+
+impl From<&BreakpointInfo> for BreakpointSummary
+{
+    fn from(info: &BreakpointInfo) -> Self
+    {
+        let kind = match info.kind {
+            BreakpointKind::Software => "software",
+            BreakpointKind::Hardware => "hardware",
+            BreakpointKind::Watchpoint => "watchpoint",
+        };
+        Self {
+            id: info.id.raw(),
+            address: info.address.value(),
+            kind: kind.to_string(),
+            enabled: info.enabled,
+            hit_count: info.hit_count,
+        }
+    }
+}
+
+/// One entry of the session timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineRecord
+{
+    /// Milliseconds since the session started.
+    pub offset_ms: u64,
+    /// Short category (`"stop"`, `"resume"`, `"error"`, ...).
+    pub kind: String,
+    /// Human-readable description.
+    pub message: String,
+}
+
+/// Resident memory footprint sample of the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemorySample
+{
+    /// Milliseconds since the session started.
+    pub offset_ms: u64,
+    /// Resident set size in bytes.
+    pub resident_bytes: u64,
+}
+
+/// Machine-readable summary of a debugging session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSummary
+{
+    /// Layout version, see [`SUMMARY_SCHEMA_VERSION`].
+    pub schema_version: u32,
+    /// How the target was started or attached.
+    pub invocation: SessionInvocation,
+    /// Session start, milliseconds since the Unix epoch.
+    pub started_at_ms: u64,
+    /// Session end, milliseconds since the Unix epoch.
+    pub ended_at_ms: u64,
+    /// How the session ended.
+    pub outcome: SessionOutcome,
+    /// Number of stops keyed by reason (`"breakpoint"`, `"signal"`, ...).
+    pub stops_by_reason: BTreeMap<String, u64>,
+    /// Every breakpoint with its final hit count.
+    pub breakpoints: Vec<BreakpointSummary>,
+    /// Most recent timeline entries, oldest first.
+    pub timeline: Vec<TimelineRecord>,
+    /// Peak memory footprint samples (empty unless sampling was enabled).
+    pub memory_samples: Vec<MemorySample>,
+    /// Crash reports, snapshots and other files produced during the session.
+    pub artifacts: Vec<PathBuf>,
+    /// Failed `--assert-reached` / `--assert-not-reached` assertions. Any
+    /// entry fails the session, whatever the target's exit status.
+    #[serde(default)]
+    pub violations: Vec<Violation>,
+    /// Error that prevented or ended the session, if any.
+    pub error: Option<String>,
+}
+
+impl SessionSummary
+{
+    /// Serialize to pretty-printed JSON.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidArgument` if serialization fails.
+    pub fn to_json(&self) -> Result<String>
+    {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DebuggerError::InvalidArgument(format!("failed to serialize session summary: {err}")))
+    }
+
+    /// Parse a summary previously produced by [`to_json`](Self::to_json).
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidArgument` if the document doesn't match the schema.
+    pub fn from_json(json: &str) -> Result<Self>
+    {
+        serde_json::from_str(json).map_err(|err| DebuggerError::InvalidArgument(format!("invalid session summary: {err}")))
+    }
+
+    /// Write the summary as JSON to `path`, creating parent directories as needed.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Io` if the file can't be written.
+    pub fn write_to(&self, path: &Path) -> Result<()>
+    {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+/// Per-binary state persisted between sessions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionFile
+{
+    /// Pinned memory values, in registration order.
+    #[serde(default)]
+    pub pins: Vec<PinSpec>,
+    /// Notes, in the order they were written.
+    #[serde(default)]
+    pub notes: Vec<Note>,
+}
+
+impl SessionFile
+{
+    /// Path of the session file for `binary`:
+    /// `~/.ferros/sessions/<file name>-<path hash>.json`.
+    ///
+    /// The hash keeps two binaries with the same name apart. Returns `None` if
+    /// `HOME` isn't set.
+    #[must_use]
+    pub fn path_for(binary: &Path) -> Option<PathBuf>
+    {
+        let home = std::env::var_os("HOME")?;
+        let binary = fs::canonicalize(binary).unwrap_or_else(|_| binary.to_path_buf());
+        let name = binary
+            .file_name()
+            .map_or_else(|| "target".into(), |name| name.to_string_lossy());
+        // FNV-1a, so the file name is stable across toolchains
+        let hash = binary
+            .to_string_lossy()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+            });
+        Some(
+            PathBuf::from(home)
+                .join(".ferros")
+                .join("sessions")
+                .join(format!("{name}-{hash:016x}.json")),
+        )
+    }
+
+    /// Load a session file. A missing file is an empty session.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Io` if the file exists but can't be read, or `InvalidArgument`
+    /// if it isn't a valid session file.
+    pub fn load(path: &Path) -> Result<Self>
+    {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|err| DebuggerError::InvalidArgument(format!("invalid session file {}: {err}", path.display()))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Write the session file, creating parent directories as needed.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Io` if the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<()>
+    {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| DebuggerError::InvalidArgument(format!("failed to serialize session file: {err}")))?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// Accumulates session activity and produces a [`SessionSummary`].
+///
+/// ## Example
+///
+/// ```rust
+/// use ferros_core::events::DebuggerEvent;
+/// use ferros_core::session::{SessionInvocation, SessionOutcome, SessionRecorder};
+/// use ferros_core::types::StopReason;
+///
+/// let mut recorder = SessionRecorder::new(SessionInvocation::attach(42));
+/// recorder.record_event(&DebuggerEvent::TargetStopped {
+///     reason: StopReason::Exited(0),
+///     thread: None,
+/// });
+/// let summary = recorder.finish(None);
+/// assert_eq!(summary.outcome, SessionOutcome::Exited { code: 0 });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionRecorder
+{
+    invocation: SessionInvocation,
+    started_at: SystemTime,
+    stops_by_reason: BTreeMap<String, u64>,
+    timeline: VecDeque<TimelineRecord>,
+    timeline_capacity: usize,
+    last_stop: Option<StopReason>,
+    recursion: Option<RecursionSummary>,
+    timed_out: Option<(Duration, TimeoutAction)>,
+    started: bool,
+    memory_samples: Vec<MemorySample>,
+    artifacts: Vec<PathBuf>,
+    violations: Vec<Violation>,
+    error: Option<String>,
+    gone: Option<u32>,
+}
+
+impl SessionRecorder
+{
+    /// Start recording a session.
+    #[must_use]
+    pub fn new(invocation: SessionInvocation) -> Self
+    {
+        Self {
+            invocation,
+            started_at: SystemTime::now(),
+            stops_by_reason: BTreeMap::new(),
+            timeline: VecDeque::new(),
+            timeline_capacity: DEFAULT_TIMELINE_ENTRIES,
+            last_stop: None,
+            recursion: None,
+            timed_out: None,
+            started: false,
+            memory_samples: Vec::new(),
+            artifacts: Vec::new(),
+            violations: Vec::new(),
+            error: None,
+            gone: None,
+        }
+    }
+
+    /// Keep at most `entries` timeline records in the summary.
+    #[must_use]
+    pub fn with_timeline_capacity(mut self, entries: usize) -> Self
+    {
+        self.timeline_capacity = entries;
+        self
+    }
+
+    /// Mark the target as running under the debugger (attach/launch succeeded).
+    pub fn record_started(&mut self, pid: u32)
+    {
+        self.started = true;
+        self.invocation.pid = Some(pid);
+        self.push_timeline("start", format!("Debugging process {pid}"));
+    }
+
+    /// Record a debugger event.
+    pub fn record_event(&mut self, event: &DebuggerEvent)
+    {
+        // An event implies the target exists, even if record_started wasn't called
+        self.started = true;
+        match event {
+            DebuggerEvent::TargetStopped { reason, .. } => {
+                *self.stops_by_reason.entry(reason.key().to_string()).or_insert(0) += 1;
+                self.last_stop = Some(*reason);
+                self.push_timeline("stop", event.describe());
+            }
+            DebuggerEvent::TargetResumed => {
+                self.last_stop = None;
+                self.recursion = None;
+                self.push_timeline("resume", event.describe());
+            }
+            DebuggerEvent::ImageLoaded { .. } | DebuggerEvent::ImageUnloaded { .. } => {
+                self.push_timeline("image", event.describe());
+            }
+            DebuggerEvent::SymbolsLoaded { .. } => {}
+        }
+    }
+
+    /// Record an error. The first error is kept as the session error.
+    ///
+    /// [`DebuggerError::TargetGone`] also makes the outcome
+    /// [`SessionOutcome::TerminatedExternally`].
+    pub fn record_error(&mut self, error: &DebuggerError)
+    {
+        if let DebuggerError::TargetGone { pid } = error {
+            self.gone = Some(*pid);
+        }
+        let message = error.to_string();
+        if self.error.is_none() {
+            self.error = Some(message.clone());
+        }
+        self.push_timeline("error", message);
+    }
+
+    /// Record the recursion the unwinder collapsed at a stack overflow.
+    ///
+    /// [`finish`](Self::finish) does this itself when given a debugger; front-ends
+    /// that already unwound the crashed thread can call it directly.
+    pub fn record_recursion(&mut self, cycle: &RecursionCycle)
+    {
+        self.recursion = Some(RecursionSummary::from(cycle));
+    }
+
+    /// Record that the session gave up on the target after `timeout`.
+    ///
+    /// The summary outcome becomes [`SessionOutcome::TimedOut`] regardless of
+    /// what is recorded afterwards.
+    pub fn record_timeout(&mut self, timeout: Duration, action: TimeoutAction)
+    {
+        self.timed_out = Some((timeout, action));
+        self.push_timeline("timeout", format!("No stop within {timeout:?}; {action} the target"));
+    }
+
+    /// Record a memory footprint sample.
+    pub fn record_memory_sample(&mut self, resident_bytes: u64)
+    {
+        self.memory_samples.push(MemorySample {
+            offset_ms: self.elapsed_ms(),
+            resident_bytes,
+        });
+    }
+
+    /// Record a failed assertion. Violations already recorded are ignored, so
+    /// the final list from [`AssertionSet::finish`](crate::assertions::AssertionSet::finish)
+    /// can be recorded after the live ones.
+    pub fn record_violation(&mut self, violation: &Violation)
+    {
+        if !self.violations.contains(violation) {
+            self.violations.push(violation.clone());
+            self.push_timeline("violation", violation.to_string());
+        }
+    }
+
+    /// Record a file produced during the session (crash report, snapshot, ...).
+    pub fn record_artifact(&mut self, path: impl Into<PathBuf>)
+    {
+        self.artifacts.push(path.into());
+    }
+
+    /// Build the summary, querying `debugger` for breakpoints and the crash frame.
+    ///
+    /// Call this before detaching so breakpoint state is still available. Passing
+    /// `None` (e.g. attach failed) produces a summary from recorded events only.
+    pub fn finish(&mut self, debugger: Option<&mut dyn Debugger>) -> SessionSummary
+    {
+        let (breakpoints, crash_frame) = match debugger {
+            Some(debugger) => {
+                let breakpoints = debugger.breakpoints();
+                let crash_frame = match self.last_stop {
+                    Some(StopReason::Signal(_) | StopReason::BadAccess { .. }) => debugger
+                        .stack_trace(1)
+                        .ok()
+                        .and_then(|frames| frames.first().map(CrashFrame::from)),
+                    Some(StopReason::StackOverflow { .. }) => {
+                        // Unwind far enough for the unwinder to spot and collapse the recursion
+                        let trace = debugger.stack_trace_with_options(UnwindOptions::default()).ok();
+                        if let Some(cycle) = trace.as_ref().and_then(|trace| trace.recursion.first()) {
+                            self.record_recursion(cycle);
+                        }
+                        trace.and_then(|trace| trace.frames.first().map(CrashFrame::from))
+                    }
+                    _ => None,
+                };
+                (breakpoints, crash_frame)
+            }
+            None => (Vec::new(), None),
+        };
+        self.summarize(&breakpoints, crash_frame)
+    }
+
+    /// Build the summary from explicit breakpoint state.
+    pub fn summarize(&mut self, breakpoints: &[BreakpointInfo], crash_frame: Option<CrashFrame>) -> SessionSummary
+    {
+        let outcome = match self.last_stop {
+            _ if !self.started => SessionOutcome::NotStarted,
+            _ if let Some((timeout, action)) = self.timed_out => SessionOutcome::TimedOut {
+                after_ms: timeout.as_millis() as u64,
+                action: action.to_string(),
+            },
+            _ if let Some(pid) = self.gone => SessionOutcome::TerminatedExternally { pid },
+            Some(StopReason::Exited(code)) => SessionOutcome::Exited { code },
+            Some(StopReason::Signal(signal)) => SessionOutcome::Crashed {
+                signal,
+                frame: crash_frame,
+            },
+            // The faulting address is in the stop's timeline record
+            Some(StopReason::BadAccess { .. }) => SessionOutcome::Crashed {
+                signal: libc::SIGSEGV,
+                frame: crash_frame,
+            },
+            Some(StopReason::StackOverflow { thread, sp, guard_page }) => SessionOutcome::StackOverflow {
+                thread: thread.raw(),
+                sp,
+                guard_page,
+                frame: crash_frame,
+                recursion: self.recursion.clone(),
+            },
+            _ => SessionOutcome::Detached,
+        };
+
+        SessionSummary {
+            schema_version: SUMMARY_SCHEMA_VERSION,
+            invocation: self.invocation.clone(),
+            started_at_ms: unix_ms(self.started_at),
+            ended_at_ms: unix_ms(SystemTime::now()),
+            outcome,
+            stops_by_reason: self.stops_by_reason.clone(),
+            breakpoints: breakpoints.iter().map(BreakpointSummary::from).collect(),
+            timeline: self.timeline.iter().cloned().collect(),
+            memory_samples: self.memory_samples.clone(),
+            artifacts: self.artifacts.clone(),
+            violations: self.violations.clone(),
+            error: self.error.clone(),
+        }
+    }
+
+    fn push_timeline(&mut self, kind: &str, message: String)
+    {
+        if self.timeline_capacity == 0 {
+            return;
+        }
+        if self.timeline.len() == self.timeline_capacity {
+            self.timeline.pop_front();
+        }
+        self.timeline.push_back(TimelineRecord {
+            offset_ms: self.elapsed_ms(),
+            kind: kind.to_string(),
+            message,
+        });
+    }
+
+    fn elapsed_ms(&self) -> u64
+    {
+        self.started_at
+            .elapsed()
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+fn unix_ms(time: SystemTime) -> u64
+{
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::breakpoints::BreakpointId;
+    use crate::types::{Address, ThreadId};
+
+    fn stop(reason: StopReason) -> DebuggerEvent
+    {
+        DebuggerEvent::TargetStopped {
+            reason,
+            thread: Some(ThreadId::from(1)),
+        }
+    }
+
+    #[test]
+    fn summary_round_trips_through_json()
+    {
+        let summary = SessionSummary {
+            schema_version: SUMMARY_SCHEMA_VERSION,
+            invocation: SessionInvocation::launch("/bin/app", vec!["--flag".to_string()]),
+            started_at_ms: 1,
+            ended_at_ms: 2,
+            outcome: SessionOutcome::Crashed {
+                signal: 11,
+                frame: Some(CrashFrame {
+                    pc: 0x1000,
+                    function: Some("main".to_string()),
+                    file: Some("main.rs".to_string()),
+                    dwarf_file: Some("./main.rs".to_string()),
+                    line: Some(7),
+                }),
+            },
+            stops_by_reason: BTreeMap::from([("signal".to_string(), 1)]),
+            breakpoints: vec![BreakpointSummary {
+                id: 1,
+                address: 0x1000,
+                kind: "software".to_string(),
+                enabled: true,
+                hit_count: 4,
+            }],
+            timeline: vec![TimelineRecord {
+                offset_ms: 5,
+                kind: "stop".to_string(),
+                message: "Stopped by signal: 11".to_string(),
+            }],
+            memory_samples: vec![MemorySample {
+                offset_ms: 3,
+                resident_bytes: 4096,
+            }],
+            artifacts: vec![PathBuf::from("/tmp/crash.json")],
+            violations: Vec::new(),
+            error: None,
+        };
+
+        let json = summary.to_json().unwrap();
+        assert!(json.contains("\"kind\": \"crashed\""));
+        assert_eq!(SessionSummary::from_json(&json).unwrap(), summary);
+    }
+
+    #[test]
+    fn failed_attach_still_produces_summary()
+    {
+        let mut recorder = SessionRecorder::new(SessionInvocation::attach(99));
+        recorder.record_error(&DebuggerError::ProcessNotFound(99));
+
+        let summary = recorder.finish(None);
+
+        assert_eq!(summary.outcome, SessionOutcome::NotStarted);
+        assert!(summary.error.is_some());
+        assert!(summary.breakpoints.is_empty());
+        assert_eq!(SessionSummary::from_json(&summary.to_json().unwrap()).unwrap(), summary);
+    }
+
+    #[test]
+    fn breakpoint_hit_twice_then_exit_3()
+    {
+        // Mirrors a fixture run: one breakpoint, hit twice, then `exit(3)`
+        let mut recorder = SessionRecorder::new(SessionInvocation::launch("fixture", Vec::new()));
+        recorder.record_started(4242);
+        for _ in 0..2 {
+            recorder.record_event(&stop(StopReason::Breakpoint(0x1000)));
+            recorder.record_event(&DebuggerEvent::TargetResumed);
+        }
+        recorder.record_event(&stop(StopReason::Exited(3)));
+
+        let mut breakpoint = BreakpointInfo::new(BreakpointId::from_raw(1), Address::from(0x1000), BreakpointKind::Software);
+        breakpoint.enabled = true;
+        breakpoint.hit_count = 2;
+        let summary = recorder.summarize(&[breakpoint], None);
+
+        assert_eq!(summary.invocation.pid, Some(4242));
+        assert_eq!(summary.outcome, SessionOutcome::Exited { code: 3 });
+        assert_eq!(
+            summary.stops_by_reason,
+            BTreeMap::from([("breakpoint".to_string(), 2), ("exited".to_string(), 1)])
+        );
+        assert_eq!(summary.breakpoints.len(), 1);
+        assert_eq!(summary.breakpoints[0].hit_count, 2);
+        assert!(summary.ended_at_ms >= summary.started_at_ms);
+        assert_eq!(
+            summary.timeline.last().unwrap().message,
+            "Process exited with code 3 (thread 1)"
+        );
+    }
+
+    #[test]
+    fn stack_overflow_reports_recursive_function()
+    {
+        let mut recorder = SessionRecorder::new(SessionInvocation::launch("stack_overflow", Vec::new()));
+        recorder.record_started(7);
+        recorder.record_event(&stop(StopReason::StackOverflow {
+            thread: ThreadId::from(1),
+            sp: 0x7000_4010,
+            guard_page: 0x7000_0000,
+        }));
+        recorder.record_recursion(&RecursionCycle {
+            first_frame: 1,
+            frame_count: 1,
+            period: 1,
+            repeats: 17_400,
+            function: Some("stack_overflow::recurse".to_string()),
+            truncated: false,
+        });
+
+        let summary = recorder.summarize(&[], None);
+
+        assert_eq!(summary.stops_by_reason, BTreeMap::from([("stack_overflow".to_string(), 1)]));
+        let SessionOutcome::StackOverflow {
+            guard_page, recursion, ..
+        } = &summary.outcome
+        else {
+            panic!("expected a stack overflow, got {:?}", summary.outcome);
+        };
+        assert_eq!(*guard_page, 0x7000_0000);
+        let recursion = recursion.as_ref().unwrap();
+        assert_eq!(recursion.function.as_deref(), Some("stack_overflow::recurse"));
+        assert_eq!(recursion.repeats, 17_400);
+
+        let json = summary.to_json().unwrap();
+        assert!(json.contains("\"kind\": \"stack_overflow\""));
+        assert_eq!(SessionSummary::from_json(&json).unwrap(), summary);
+    }
+
+    #[test]
+    fn session_file_round_trips_pins()
+    {
+        use crate::pins::PinType;
+
+        let path = std::env::temp_dir().join(format!("ferros-session-{}.json", std::process::id()));
+        assert_eq!(SessionFile::load(&path).unwrap(), SessionFile::default());
+
+        let file = SessionFile {
+            pins: vec![PinSpec {
+                name: "counter".to_string(),
+                address: 0x1000,
+                ty: PinType::U32,
+            }],
+            ..SessionFile::default()
+        };
+        file.save(&path).unwrap();
+        let loaded = SessionFile::load(&path);
+        fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.unwrap(), file);
+        let a = SessionFile::path_for(Path::new("/work/a/app"));
+        let b = SessionFile::path_for(Path::new("/work/b/app"));
+        if let (Some(a), Some(b)) = (a, b) {
+            assert_ne!(a, b);
+            assert!(a.file_name().unwrap().to_string_lossy().starts_with("app-"));
+        }
+    }
+
+    #[test]
+    fn session_file_round_trips_notes()
+    {
+        use crate::notes::{ImageAnchor, NoteRegistry, NoteTarget};
+
+        let mut notes = NoteRegistry::new();
+        let anchor = ImageAnchor {
+            path: "/bin/app".to_string(),
+            uuid: "00112233".to_string(),
+            offset: 0x42ab0,
+        };
+        notes
+            .add(
+                NoteTarget::address(0x1_0004_2ab0).with_image(Some(anchor)),
+                "connection table",
+            )
+            .unwrap();
+        notes
+            .add(
+                NoteTarget::Breakpoint {
+                    id: 4,
+                    address: 0x1_0000_1000,
+                    image: None,
+                },
+                "the suspicious path",
+            )
+            .unwrap();
+        let file = SessionFile {
+            notes: notes.notes().to_vec(),
+            ..SessionFile::default()
+        };
+
+        let json = serde_json::to_string(&file).unwrap();
+        let loaded: SessionFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(NoteRegistry::from_notes(&loaded.notes), notes);
+
+        // Files written before notes existed still load
+        let old: SessionFile = serde_json::from_str(r#"{"pins":[]}"#).unwrap();
+        assert!(old.notes.is_empty());
+    }
+
+    #[test]
+    fn timeline_keeps_most_recent_entries()
+    {
+        let mut recorder = SessionRecorder::new(SessionInvocation::attach(1)).with_timeline_capacity(2);
+        recorder.record_event(&stop(StopReason::Suspended));
+        recorder.record_event(&DebuggerEvent::TargetResumed);
+        recorder.record_event(&stop(StopReason::Signal(2)));
+
+        let summary = recorder.finish(None);
+
+        let kinds: Vec<_> = summary.timeline.iter().map(|record| record.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["resume", "stop"]);
+        assert_eq!(summary.outcome, SessionOutcome::Crashed { signal: 2, frame: None });
+    }
+
+    #[test]
+    fn timeout_outcome_wins_over_the_suspend_it_caused()
+    {
+        let mut recorder = SessionRecorder::new(SessionInvocation::launch("hang_forever", Vec::new()));
+        recorder.record_started(11);
+        recorder.record_timeout(Duration::from_secs(2), TimeoutAction::Kill);
+        recorder.record_event(&stop(StopReason::Suspended));
+        recorder.record_artifact("/tmp/ferros-hang.log");
+
+        let summary = recorder.finish(None);
+
+        assert_eq!(
+            summary.outcome,
+            SessionOutcome::TimedOut {
+                after_ms: 2000,
+                action: "kill".to_string(),
+            }
+        );
+        assert_eq!(summary.timeline[1].message, "No stop within 2s; kill the target");
+        let json = summary.to_json().unwrap();
+        assert!(json.contains("\"kind\": \"timed_out\""));
+        assert_eq!(SessionSummary::from_json(&json).unwrap(), summary);
+    }
+
+    #[test]
+    fn target_gone_records_external_termination()
+    {
+        let mut recorder = SessionRecorder::new(SessionInvocation::attach(42));
+        recorder.record_started(42);
+        recorder.record_event(&stop(StopReason::Breakpoint(0x1000)));
+        recorder.record_error(&DebuggerError::TargetGone { pid: 42 });
+
+        let summary = recorder.finish(None);
+
+        assert_eq!(summary.outcome, SessionOutcome::TerminatedExternally { pid: 42 });
+        assert_eq!(
+            summary.error.as_deref(),
+            Some("Process 42 is gone (exited or killed outside the debugger)")
+        );
+        let json = summary.to_json().unwrap();
+        assert!(json.contains("\"kind\": \"terminated_externally\""));
+    }
+
+    #[test]
+    fn violations_are_recorded_once()
+    {
+        use crate::assertions::{Violation, ViolationKind};
+
+        let mut recorder = SessionRecorder::new(SessionInvocation::launch("fixture", Vec::new()));
+        let violation = Violation {
+            assertion: "not-reached src/main.rs:12".to_string(),
+            kind: ViolationKind::Reached,
+            thread: Some(1),
+            backtrace: Vec::new(),
+            arguments: vec!["X0=0x2a".to_string()],
+        };
+        recorder.record_violation(&violation);
+        recorder.record_violation(&violation);
+
+        let summary = recorder.finish(None);
+
+        assert_eq!(summary.violations, [violation]);
+        assert_eq!(
+            summary.timeline.last().unwrap().message,
+            "assertion not-reached src/main.rs:12 failed: location was reached (thread 1)"
+        );
+        let json = summary.to_json().unwrap();
+        assert!(json.contains("\"kind\": \"reached\""));
+        assert_eq!(SessionSummary::from_json(&json).unwrap(), summary);
+    }
+}
@@ -0,0 +1,241 @@
+//! What the debugger does when the target gets a signal.
+//!
+//! Each signal has a [`SignalPolicy`]: stop the target and report it, pass it
+//! on to the target and keep running, or swallow it and keep running. Signals
+//! without an entry in [`SignalPolicies`] stop.
+//!
+//! ```rust
+//! use ferros_core::signals::{SignalPolicies, SignalPolicy, parse_signal};
+//!
+//! let mut policies = SignalPolicies::default();
+//! let usr1 = parse_signal("SIGUSR1")?;
+//! policies.set(usr1, SignalPolicy::PassThrough)?;
+//! assert_eq!(policies.policy(usr1), SignalPolicy::PassThrough);
+//! assert_eq!(policies.policy(parse_signal("11")?), SignalPolicy::Stop);
+//! assert_eq!("ignore".parse::<SignalPolicy>()?, SignalPolicy::Ignore);
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{DebuggerError, Result};
+
+/// Signal numbers run from 1 to this, as on macOS and Linux.
+pub const MAX_SIGNAL: i32 = 31;
+
+/// What to do with one signal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SignalPolicy
+{
+    /// Stop the target and report the signal, like any other stop.
+    #[default]
+    Stop,
+    /// Deliver the signal to the target and keep running.
+    PassThrough,
+    /// Drop the signal and keep running.
+    ///
+    /// A fault (`SIGSEGV`, `SIGBUS`, `SIGILL`, `SIGFPE`) that is dropped
+    /// re-runs the faulting instruction, which usually faults again.
+    Ignore,
+}
+
+impl fmt::Display for SignalPolicy
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::Stop => "stop",
+            Self::PassThrough => "pass",
+            Self::Ignore => "ignore",
+        })
+    }
+}
+
+impl FromStr for SignalPolicy
+{
+    type Err = DebuggerError;
+
+    fn from_str(text: &str) -> Result<Self>
+    {
+        match text {
+            "stop" => Ok(Self::Stop),
+            "pass" => Ok(Self::PassThrough),
+            "ignore" => Ok(Self::Ignore),
+            _ => Err(DebuggerError::InvalidArgument(format!(
+                "Unknown signal policy '{text}', expected stop, pass or ignore"
+            ))),
+        }
+    }
+}
+
+/// Policies by signal number.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignalPolicies
+{
+    policies: BTreeMap<i32, SignalPolicy>,
+}
+
+impl SignalPolicies
+{
+    /// Policy for `signal`; [`SignalPolicy::Stop`] unless one was set.
+    #[must_use]
+    pub fn policy(&self, signal: i32) -> SignalPolicy
+    {
+        self.policies.get(&signal).copied().unwrap_or_default()
+    }
+
+    /// Set the policy for `signal`. Setting [`SignalPolicy::Stop`] removes its entry.
+    ///
+    /// ## Errors
+    ///
+    /// - `InvalidArgument`: `signal` is not between 1 and [`MAX_SIGNAL`]
+    pub fn set(&mut self, signal: i32, policy: SignalPolicy) -> Result<()>
+    {
+        if !(1..=MAX_SIGNAL).contains(&signal) {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "{signal} is not a signal number (1-{MAX_SIGNAL})"
+            )));
+        }
+        if policy == SignalPolicy::Stop {
+            self.policies.remove(&signal);
+        } else {
+            self.policies.insert(signal, policy);
+        }
+        Ok(())
+    }
+
+    /// Signals with a policy other than [`SignalPolicy::Stop`], lowest first.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, SignalPolicy)> + '_
+    {
+        self.policies.iter().map(|(signal, policy)| (*signal, *policy))
+    }
+
+    /// Whether every signal stops.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.policies.is_empty()
+    }
+}
+
+/// Signals by name.
+const NAMES: &[(&str, i32)] = &[
+    ("SIGHUP", libc::SIGHUP),
+    ("SIGINT", libc::SIGINT),
+    ("SIGQUIT", libc::SIGQUIT),
+    ("SIGILL", libc::SIGILL),
+    ("SIGTRAP", libc::SIGTRAP),
+    ("SIGABRT", libc::SIGABRT),
+    ("SIGFPE", libc::SIGFPE),
+    ("SIGKILL", libc::SIGKILL),
+    ("SIGBUS", libc::SIGBUS),
+    ("SIGSEGV", libc::SIGSEGV),
+    ("SIGSYS", libc::SIGSYS),
+    ("SIGPIPE", libc::SIGPIPE),
+    ("SIGALRM", libc::SIGALRM),
+    ("SIGTERM", libc::SIGTERM),
+    ("SIGURG", libc::SIGURG),
+    ("SIGSTOP", libc::SIGSTOP),
+    ("SIGTSTP", libc::SIGTSTP),
+    ("SIGCONT", libc::SIGCONT),
+    ("SIGCHLD", libc::SIGCHLD),
+    ("SIGTTIN", libc::SIGTTIN),
+    ("SIGTTOU", libc::SIGTTOU),
+    ("SIGIO", libc::SIGIO),
+    ("SIGXCPU", libc::SIGXCPU),
+    ("SIGXFSZ", libc::SIGXFSZ),
+    ("SIGVTALRM", libc::SIGVTALRM),
+    ("SIGPROF", libc::SIGPROF),
+    ("SIGWINCH", libc::SIGWINCH),
+    ("SIGUSR1", libc::SIGUSR1),
+    ("SIGUSR2", libc::SIGUSR2),
+];
+
+/// Name of `signal` with its `SIG` prefix, e.g. `SIGUSR1`, or `None` for an unknown number.
+#[must_use]
+pub fn signal_name(signal: i32) -> Option<&'static str>
+{
+    NAMES.iter().find(|(_, number)| *number == signal).map(|(name, _)| *name)
+}
+
+/// Parse a signal number or name: `10`, `SIGUSR1`, `usr1`.
+///
+/// ## Errors
+///
+/// - `InvalidArgument`: The text is neither a known name nor a number between 1 and [`MAX_SIGNAL`]
+pub fn parse_signal(text: &str) -> Result<i32>
+{
+    let signal = match text.parse::<i32>() {
+        Ok(number) => number,
+        Err(_) => {
+            let upper = text.to_ascii_uppercase();
+            let name = if upper.starts_with("SIG") {
+                upper
+            } else {
+                format!("SIG{upper}")
+            };
+            NAMES
+                .iter()
+                .find(|(known, _)| *known == name)
+                .map(|(_, number)| *number)
+                .ok_or_else(|| DebuggerError::InvalidArgument(format!("Unknown signal '{text}'")))?
+        }
+    };
+    if !(1..=MAX_SIGNAL).contains(&signal) {
+        return Err(DebuggerError::InvalidArgument(format!(
+            "{signal} is not a signal number (1-{MAX_SIGNAL})"
+        )));
+    }
+    Ok(signal)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn signals_parse_by_number_or_name()
+    {
+        assert_eq!(parse_signal("11").unwrap(), libc::SIGSEGV);
+        assert_eq!(parse_signal("SIGUSR1").unwrap(), libc::SIGUSR1);
+        assert_eq!(parse_signal("usr2").unwrap(), libc::SIGUSR2);
+        assert_eq!(signal_name(libc::SIGINT), Some("SIGINT"));
+        assert!(parse_signal("0").unwrap_err().to_string().contains("not a signal number"));
+        assert!(
+            parse_signal("SIGNOPE")
+                .unwrap_err()
+                .to_string()
+                .contains("Unknown signal 'SIGNOPE'")
+        );
+    }
+
+    #[test]
+    fn stop_is_the_default_and_clears_an_entry()
+    {
+        let mut policies = SignalPolicies::default();
+        policies.set(libc::SIGUSR1, SignalPolicy::Ignore).unwrap();
+        policies.set(libc::SIGINT, SignalPolicy::PassThrough).unwrap();
+        assert_eq!(
+            policies.iter().collect::<Vec<_>>(),
+            [
+                (libc::SIGINT, SignalPolicy::PassThrough),
+                (libc::SIGUSR1, SignalPolicy::Ignore)
+            ]
+        );
+
+        policies.set(libc::SIGINT, SignalPolicy::Stop).unwrap();
+        assert_eq!(policies.policy(libc::SIGINT), SignalPolicy::Stop);
+        assert_eq!(policies.iter().count(), 1);
+        assert!(policies.set(64, SignalPolicy::Ignore).is_err());
+        assert!(
+            "drop"
+                .parse::<SignalPolicy>()
+                .unwrap_err()
+                .to_string()
+                .contains("expected stop, pass or ignore")
+        );
+    }
+}
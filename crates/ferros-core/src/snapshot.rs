@@ -0,0 +1,1033 @@
+//! Snapshots of a stopped target for post-mortem inspection.
+//!
+//! [`Debugger::write_snapshot`] saves what a stopped target looks like: every
+//! thread's registers, the memory region table, the images the symbol cache
+//! had loaded and the contents of the regions [`SnapshotOptions`] picks
+//! (writable ones by default, everything readable with
+//! [`with_all_regions`](SnapshotOptions::with_all_regions)), up to a size cap.
+//! [`SnapshotDebugger`] opens the file again as a read-only [`Debugger`], so
+//! the TUI can browse registers, memory and stacks with no process around.
+//!
+//! ## File format
+//!
+//! Everything is little-endian. Strings are a `u32` byte length followed by
+//! UTF-8; an optional string uses the length `u32::MAX` for "none".
+//!
+//! | Field | Encoding |
+//! |-------|----------|
+//! | magic | the 8 bytes `FERROSNP` |
+//! | version | `u32`, currently 1 |
+//! | architecture | `u8`: 0 unknown, 1 arm64, 2 x86-64 |
+//! | active thread | `u64`, `u64::MAX` when there is none |
+//! | stop reason | `u8` kind, then three `u64` operands (see below) |
+//! | counts | `u32` each: threads, regions, images, memory records |
+//! | threads | id, pc, sp, fp and status as `u64`, a `u32` count of general registers, then each as `u64` |
+//! | regions | id, start and end as `u64`, permissions string, optional name |
+//! | images | load address as `u64`, path string |
+//! | memory records | start address and length as `u64`, then that many bytes |
+//!
+//! Stop reason kinds follow [`StopReason`]'s declaration order: 0 running,
+//! 1 suspended, 2 signal (number), 3 breakpoint (address), 4 step (PC),
+//! 5 watchpoint (address, PC), 6 stack overflow (thread, SP, guard page),
+//! 7 exited (code), 8 unknown. Signal numbers and exit codes are stored as
+//! the `u64` of the `i32`; unused operands are 0.
+//!
+//! Memory records don't overlap and are in address order. Pages that couldn't
+//! be read are left out, so a region can have gaps. Vector and floating-point
+//! registers aren't saved.
+//!
+//! ```rust,no_run
+//! use std::path::Path;
+//!
+//! use ferros_core::TargetInspector;
+//! use ferros_core::snapshot::SnapshotDebugger;
+//!
+//! let snapshot = SnapshotDebugger::open(Path::new("dump.ferros"))?;
+//! println!("pc = {}", snapshot.read_registers()?.pc);
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::debugger::Debugger;
+use crate::error::{DebuggerError, Result};
+use crate::inspector::TargetInspector;
+use crate::symbols::unwind::{MemoryAccess, StackTrace, StackUnwinder, UnwindOptions};
+use crate::symbols::{DebugLevel, ImageDescriptor, ImageInfo, SymbolCache, TypeSummary};
+use crate::types::{
+    Address, Architecture, BadAccessKind, LaunchOptions, MemoryRegion, MemoryRegionId, ProcessId, Registers, SourceLocation,
+    StackFrame, StopReason, SymbolName, ThreadId,
+};
+
+/// First bytes of every snapshot file.
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"FERROSNP";
+
+/// Format version written by this build.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Default cap on the memory a snapshot holds: 512 MiB.
+pub const DEFAULT_SNAPSHOT_MAX_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Bytes read from the target at a time; an unreadable chunk is left out whole.
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Which memory regions a snapshot saves the contents of.
+///
+/// The region table is always saved in full; this only decides which regions
+/// have their bytes in it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnapshotRegions
+{
+    /// Regions whose permissions include `w`: stacks, heap and data.
+    #[default]
+    Writable,
+    /// Every region, including code and read-only data.
+    All,
+}
+
+/// What a snapshot saves of the target's memory.
+///
+/// ```rust
+/// use ferros_core::snapshot::{SnapshotOptions, SnapshotRegions};
+///
+/// let options = SnapshotOptions::new()
+///     .with_all_regions()
+///     .with_max_bytes(64 * 1024 * 1024);
+/// assert_eq!(options.regions, SnapshotRegions::All);
+/// assert_eq!(options.max_bytes, 64 * 1024 * 1024);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotOptions
+{
+    /// Regions whose contents are saved.
+    pub regions: SnapshotRegions,
+    /// Most bytes of memory saved; regions past the cap are left out, lowest addresses first.
+    pub max_bytes: u64,
+}
+
+impl SnapshotOptions
+{
+    /// Writable regions, up to [`DEFAULT_SNAPSHOT_MAX_BYTES`].
+    #[must_use]
+    pub fn new() -> Self
+    {
+        Self {
+            regions: SnapshotRegions::Writable,
+            max_bytes: DEFAULT_SNAPSHOT_MAX_BYTES,
+        }
+    }
+
+    /// Save every readable region, not just the writable ones.
+    #[must_use]
+    pub fn with_all_regions(mut self) -> Self
+    {
+        self.regions = SnapshotRegions::All;
+        self
+    }
+
+    /// Save at most `bytes` of memory.
+    #[must_use]
+    pub fn with_max_bytes(mut self, bytes: u64) -> Self
+    {
+        self.max_bytes = bytes;
+        self
+    }
+
+    fn includes(&self, region: &MemoryRegion) -> bool
+    {
+        match self.regions {
+            SnapshotRegions::Writable => region.permissions.contains('w'),
+            SnapshotRegions::All => region.permissions.contains('r'),
+        }
+    }
+}
+
+impl Default for SnapshotOptions
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+/// Saved bytes starting at `start`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRecord
+{
+    /// Address of the first byte.
+    pub start: Address,
+    /// The bytes, read as the program saw them (without breakpoint traps).
+    pub bytes: Vec<u8>,
+}
+
+impl MemoryRecord
+{
+    fn end(&self) -> u64
+    {
+        self.start.value() + self.bytes.len() as u64
+    }
+}
+
+/// Everything a snapshot file holds.
+#[derive(Debug, Clone)]
+pub struct Snapshot
+{
+    /// Architecture of the target.
+    pub architecture: Architecture,
+    /// Why the target was stopped.
+    pub stop_reason: StopReason,
+    /// Thread that was active.
+    pub active_thread: Option<ThreadId>,
+    /// Registers of every thread that could be read, in the target's thread order.
+    pub threads: Vec<(ThreadId, Registers)>,
+    /// The target's memory regions, whether or not their contents were saved.
+    pub regions: Vec<MemoryRegion>,
+    /// Images the symbol cache had loaded, as path and load address.
+    pub images: Vec<(PathBuf, Address)>,
+    /// Saved memory, in address order.
+    pub memory: Vec<MemoryRecord>,
+}
+
+impl Snapshot
+{
+    /// Capture the stopped `target`.
+    ///
+    /// The active thread's registers must be readable; other threads, images
+    /// and memory are captured best-effort, so an unreadable chunk of a region
+    /// becomes a gap rather than an error.
+    ///
+    /// # Errors
+    ///
+    /// - `NotAttached`: There is no target
+    /// - `NotStopped`: The target is running
+    /// - Any error from reading the active thread's registers or the region table
+    pub fn capture<D: Debugger + ?Sized>(target: &D, options: SnapshotOptions) -> Result<Self>
+    {
+        if !target.is_attached() {
+            return Err(DebuggerError::NotAttached);
+        }
+        if !target.is_stopped() {
+            return Err(DebuggerError::NotStopped);
+        }
+
+        let active_thread = target.active_thread();
+        let active_registers = target.read_registers()?;
+        let mut threads = Vec::new();
+        for thread in target.threads().unwrap_or_default() {
+            if Some(thread) == active_thread {
+                threads.push((thread, active_registers.clone()));
+            } else if let Ok(registers) = target.read_registers_for(thread) {
+                threads.push((thread, registers));
+            }
+        }
+        if let Some(thread) = active_thread
+            && !threads.iter().any(|(id, _)| *id == thread)
+        {
+            threads.insert(0, (thread, active_registers));
+        }
+
+        let regions = target.get_memory_regions()?;
+        let mut memory: Vec<MemoryRecord> = Vec::new();
+        let mut budget = options.max_bytes;
+        for region in regions.iter().filter(|region| options.includes(region)) {
+            let mut cursor = region.start.value();
+            while cursor < region.end.value() && budget > 0 {
+                let len = (region.end.value() - cursor).min(CHUNK_SIZE).min(budget);
+                // len is at most CHUNK_SIZE
+                #[allow(clippy::cast_possible_truncation)]
+                let chunk = target.read_memory_logical(Address::from(cursor), len as usize);
+                if let Ok(bytes) = chunk
+                    && !bytes.is_empty()
+                {
+                    budget -= bytes.len() as u64;
+                    match memory.last_mut() {
+                        Some(last) if last.end() == cursor => last.bytes.extend_from_slice(&bytes),
+                        _ => memory.push(MemoryRecord {
+                            start: Address::from(cursor),
+                            bytes,
+                        }),
+                    }
+                }
+                cursor += len;
+            }
+        }
+
+        let images = target
+            .modules()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|image| (image.path, image.load_address))
+            .collect();
+
+        Ok(Self {
+            architecture: target.architecture(),
+            stop_reason: target.stop_reason(),
+            active_thread,
+            threads,
+            regions,
+            images,
+            memory,
+        })
+    }
+
+    /// Bytes of memory the snapshot holds.
+    #[must_use]
+    pub fn memory_bytes(&self) -> u64
+    {
+        self.memory.iter().map(|record| record.bytes.len() as u64).sum()
+    }
+
+    /// Saved bytes at `addr`, or `None` if any of them wasn't saved.
+    #[must_use]
+    pub fn read_memory(&self, addr: Address, len: usize) -> Option<&[u8]>
+    {
+        let start = addr.value();
+        let end = start.checked_add(len as u64)?;
+        let index = self.memory.partition_point(|record| record.end() <= start);
+        let record = self.memory.get(index)?;
+        if record.start.value() > start || record.end() < end {
+            return None;
+        }
+        // Both offsets are within the record's bytes
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = (start - record.start.value()) as usize;
+        Some(&record.bytes[offset..offset + len])
+    }
+
+    /// Registers of `thread`, if they were saved.
+    #[must_use]
+    pub fn registers_for(&self, thread: ThreadId) -> Option<&Registers>
+    {
+        self.threads
+            .iter()
+            .find(|(id, _)| *id == thread)
+            .map(|(_, registers)| registers)
+    }
+
+    /// Write the snapshot to `path`, replacing any file there.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Io` if the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<()>
+    {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_to(&mut out)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Read a snapshot written by [`save`](Self::save).
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidArgument`: The file isn't a snapshot or is from a newer format
+    /// - `Io`: The file can't be read or ends early
+    pub fn load(path: &Path) -> Result<Self>
+    {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Write the snapshot in the format described in the [module docs](self).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Io` if `out` can't be written.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> Result<()>
+    {
+        out.write_all(SNAPSHOT_MAGIC)?;
+        write_u32(out, SNAPSHOT_VERSION)?;
+        out.write_all(&[architecture_code(self.architecture)])?;
+        write_u64(out, self.active_thread.map_or(u64::MAX, |thread| thread.raw()))?;
+        let (kind, operands) = encode_stop_reason(self.stop_reason);
+        out.write_all(&[kind])?;
+        for operand in operands {
+            write_u64(out, operand)?;
+        }
+        write_len(out, self.threads.len())?;
+        write_len(out, self.regions.len())?;
+        write_len(out, self.images.len())?;
+        write_len(out, self.memory.len())?;
+
+        for (thread, registers) in &self.threads {
+            for value in [
+                thread.raw(),
+                registers.pc.value(),
+                registers.sp.value(),
+                registers.fp.value(),
+                registers.status,
+            ] {
+                write_u64(out, value)?;
+            }
+            write_len(out, registers.general.len())?;
+            for &value in &registers.general {
+                write_u64(out, value)?;
+            }
+        }
+        for region in &self.regions {
+            write_u64(out, region.id.0 as u64)?;
+            write_u64(out, region.start.value())?;
+            write_u64(out, region.end.value())?;
+            write_str(out, &region.permissions)?;
+            match &region.name {
+                Some(name) => write_str(out, name)?,
+                None => write_u32(out, u32::MAX)?,
+            }
+        }
+        for (path, load_address) in &self.images {
+            write_u64(out, load_address.value())?;
+            write_str(out, &path.to_string_lossy())?;
+        }
+        for record in &self.memory {
+            write_u64(out, record.start.value())?;
+            write_u64(out, record.bytes.len() as u64)?;
+            out.write_all(&record.bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Read a snapshot in the format described in the [module docs](self).
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidArgument`: The input isn't a snapshot or is from a newer format
+    /// - `Io`: The input can't be read or ends early
+    pub fn read_from<R: Read>(input: &mut R) -> Result<Self>
+    {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_MAGIC {
+            return Err(DebuggerError::InvalidArgument("Not a ferros snapshot".to_string()));
+        }
+        let version = read_u32(input)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "Snapshot format version {version} is not supported (expected {SNAPSHOT_VERSION})"
+            )));
+        }
+        let architecture = match read_u8(input)? {
+            1 => Architecture::Arm64,
+            2 => Architecture::X86_64,
+            _ => Architecture::Unknown("unknown"),
+        };
+        let active_thread = match read_u64(input)? {
+            u64::MAX => None,
+            raw => Some(ThreadId::from(raw)),
+        };
+        let kind = read_u8(input)?;
+        let operands = [read_u64(input)?, read_u64(input)?, read_u64(input)?];
+        let stop_reason = decode_stop_reason(kind, operands);
+        let thread_count = read_u32(input)?;
+        let region_count = read_u32(input)?;
+        let image_count = read_u32(input)?;
+        let record_count = read_u32(input)?;
+
+        let mut threads = Vec::new();
+        for _ in 0..thread_count {
+            let thread = ThreadId::from(read_u64(input)?);
+            let mut registers = Registers::new().with_arch(architecture);
+            registers.pc = Address::from(read_u64(input)?);
+            registers.sp = Address::from(read_u64(input)?);
+            registers.fp = Address::from(read_u64(input)?);
+            registers.status = read_u64(input)?;
+            let general = read_u32(input)?;
+            registers.general = (0..general).map(|_| read_u64(input)).collect::<io::Result<_>>()?;
+            threads.push((thread, registers));
+        }
+        let mut regions = Vec::new();
+        for _ in 0..region_count {
+            // Region ids are indexes the backend handed out, so they fit in usize
+            #[allow(clippy::cast_possible_truncation)]
+            let id = MemoryRegionId(read_u64(input)? as usize);
+            let start = Address::from(read_u64(input)?);
+            let end = Address::from(read_u64(input)?);
+            let permissions = read_str(input)?;
+            let name = read_optional_str(input)?;
+            regions.push(MemoryRegion::new(id, start, end, permissions, name));
+        }
+        let mut images = Vec::new();
+        for _ in 0..image_count {
+            let load_address = Address::from(read_u64(input)?);
+            images.push((PathBuf::from(read_str(input)?), load_address));
+        }
+        let mut memory = Vec::new();
+        for _ in 0..record_count {
+            let start = Address::from(read_u64(input)?);
+            let len = read_u64(input)?;
+            memory.push(MemoryRecord {
+                start,
+                bytes: read_bytes(input, len)?,
+            });
+        }
+
+        Ok(Self {
+            architecture,
+            stop_reason,
+            active_thread,
+            threads,
+            regions,
+            images,
+            memory,
+        })
+    }
+}
+
+impl MemoryAccess for Snapshot
+{
+    fn read_u64(&self, address: Address) -> Result<u64>
+    {
+        let bytes = self.read_memory(address, 8).ok_or_else(|| not_saved(address))?;
+        let mut word = [0; 8];
+        word.copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(word))
+    }
+}
+
+fn not_saved(address: Address) -> DebuggerError
+{
+    DebuggerError::InvalidArgument(format!("Memory at {address} is not in the snapshot"))
+}
+
+fn read_only() -> DebuggerError
+{
+    DebuggerError::InvalidArgument("A snapshot is read-only".to_string())
+}
+
+const fn architecture_code(architecture: Architecture) -> u8
+{
+    match architecture {
+        Architecture::Arm64 => 1,
+        Architecture::X86_64 => 2,
+        Architecture::Unknown(_) => 0,
+    }
+}
+
+// Signal numbers and exit codes go through u64 and back unchanged
+#[allow(clippy::cast_sign_loss)]
+fn encode_stop_reason(reason: StopReason) -> (u8, [u64; 3])
+{
+    match reason {
+        StopReason::Running => (0, [0; 3]),
+        StopReason::Suspended => (1, [0; 3]),
+        StopReason::Signal(signal) => (2, [signal as u64, 0, 0]),
+        StopReason::Breakpoint(address) => (3, [address, 0, 0]),
+        StopReason::Step(pc) => (4, [pc, 0, 0]),
+        StopReason::Watchpoint { address, pc } => (5, [address, pc, 0]),
+        StopReason::StackOverflow { thread, sp, guard_page } => (6, [thread.raw(), sp, guard_page]),
+        StopReason::Exited(code) => (7, [code as u64, 0, 0]),
+        StopReason::Unknown => (8, [0; 3]),
+        StopReason::BadAccess { address, kind } => (9, [address, bad_access_code(kind), 0]),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn decode_stop_reason(kind: u8, [first, second, third]: [u64; 3]) -> StopReason
+{
+    match kind {
+        0 => StopReason::Running,
+        1 => StopReason::Suspended,
+        2 => StopReason::Signal(first as i32),
+        3 => StopReason::Breakpoint(first),
+        4 => StopReason::Step(first),
+        5 => StopReason::Watchpoint {
+            address: first,
+            pc: second,
+        },
+        6 => StopReason::StackOverflow {
+            thread: ThreadId::from(first),
+            sp: second,
+            guard_page: third,
+        },
+        7 => StopReason::Exited(first as i32),
+        9 => StopReason::BadAccess {
+            address: first,
+            kind: match second {
+                1 => BadAccessKind::Read,
+                2 => BadAccessKind::Write,
+                3 => BadAccessKind::Execute,
+                _ => BadAccessKind::Unknown,
+            },
+        },
+        _ => StopReason::Unknown,
+    }
+}
+
+fn bad_access_code(kind: BadAccessKind) -> u64
+{
+    match kind {
+        BadAccessKind::Unknown => 0,
+        BadAccessKind::Read => 1,
+        BadAccessKind::Write => 2,
+        BadAccessKind::Execute => 3,
+    }
+}
+
+fn write_u32<W: Write>(out: &mut W, value: u32) -> io::Result<()>
+{
+    out.write_all(&value.to_le_bytes())
+}
+
+fn write_u64<W: Write>(out: &mut W, value: u64) -> io::Result<()>
+{
+    out.write_all(&value.to_le_bytes())
+}
+
+fn write_len<W: Write>(out: &mut W, len: usize) -> io::Result<()>
+{
+    let len = u32::try_from(len).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many entries"))?;
+    write_u32(out, len)
+}
+
+fn write_str<W: Write>(out: &mut W, text: &str) -> io::Result<()>
+{
+    write_len(out, text.len())?;
+    out.write_all(text.as_bytes())
+}
+
+fn read_u8<R: Read>(input: &mut R) -> io::Result<u8>
+{
+    let mut byte = [0; 1];
+    input.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u32<R: Read>(input: &mut R) -> io::Result<u32>
+{
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(input: &mut R) -> io::Result<u64>
+{
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Read `len` bytes without trusting `len` enough to allocate it up front.
+fn read_bytes<R: Read>(input: &mut R, len: u64) -> io::Result<Vec<u8>>
+{
+    let mut bytes = Vec::new();
+    input.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "snapshot ends inside a record"));
+    }
+    Ok(bytes)
+}
+
+fn read_str<R: Read>(input: &mut R) -> io::Result<String>
+{
+    let len = read_u32(input)?;
+    String::from_utf8(read_bytes(input, u64::from(len))?).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn read_optional_str<R: Read>(input: &mut R) -> io::Result<Option<String>>
+{
+    match read_u32(input)? {
+        u32::MAX => Ok(None),
+        len => String::from_utf8(read_bytes(input, u64::from(len))?)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+    }
+}
+
+/// A snapshot opened as a [`Debugger`] that can look but not touch.
+///
+/// Reads answer from the snapshot: registers of any saved thread, saved
+/// memory and the region table. Stacks are unwound from saved memory with
+/// the symbols of whichever images are still on disk at their old paths.
+/// Anything that would change or run the target fails with
+/// `InvalidArgument`; [`detach`](Debugger::detach) just closes the snapshot.
+pub struct SnapshotDebugger
+{
+    snapshot: Snapshot,
+    active_thread: Option<ThreadId>,
+    symbol_cache: SymbolCache,
+    open: bool,
+}
+
+impl SnapshotDebugger
+{
+    /// Open the snapshot file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns whatever [`Snapshot::load`] fails with.
+    pub fn open(path: &Path) -> Result<Self>
+    {
+        Ok(Self::new(Snapshot::load(path)?))
+    }
+
+    /// Inspect `snapshot`, loading the symbols of the images it lists.
+    ///
+    /// Images that moved or were rebuilt since the snapshot was taken are
+    /// skipped; their frames show addresses without names.
+    #[must_use]
+    pub fn new(snapshot: Snapshot) -> Self
+    {
+        let symbol_cache = SymbolCache::new();
+        for (path, load_address) in &snapshot.images {
+            if !path.exists() {
+                continue;
+            }
+            let descriptor = ImageDescriptor {
+                path: path.clone(),
+                load_address: load_address.value(),
+            };
+            if let Err(err) = symbol_cache.load_image(descriptor) {
+                tracing::debug!("Skipping symbols of {}: {err}", path.display());
+            }
+        }
+        Self {
+            active_thread: snapshot.active_thread,
+            snapshot,
+            symbol_cache,
+            open: true,
+        }
+    }
+
+    /// The snapshot being inspected.
+    #[must_use]
+    pub fn snapshot(&self) -> &Snapshot
+    {
+        &self.snapshot
+    }
+
+    fn registers_of(&self, thread: ThreadId) -> Result<&Registers>
+    {
+        self.snapshot
+            .registers_for(thread)
+            .ok_or(DebuggerError::ThreadNotFound(thread))
+    }
+
+    fn unwind(&self, thread: ThreadId, options: UnwindOptions) -> Result<StackTrace>
+    {
+        let registers = self.registers_of(thread)?;
+        let unwinder = StackUnwinder::new(self.snapshot.architecture, &self.symbol_cache, &self.snapshot);
+        unwinder.unwind_with_options(thread, registers, options)
+    }
+
+    fn active(&self) -> Result<ThreadId>
+    {
+        self.active_thread
+            .ok_or_else(|| DebuggerError::InvalidArgument("The snapshot has no active thread".to_string()))
+    }
+}
+
+impl TargetInspector for SnapshotDebugger
+{
+    fn architecture(&self) -> Architecture
+    {
+        self.snapshot.architecture
+    }
+
+    fn is_attached(&self) -> bool
+    {
+        self.open
+    }
+
+    fn is_stopped(&self) -> bool
+    {
+        true
+    }
+
+    fn stop_reason(&self) -> StopReason
+    {
+        self.snapshot.stop_reason
+    }
+
+    fn threads(&self) -> Result<Vec<ThreadId>>
+    {
+        Ok(self.snapshot.threads.iter().map(|(thread, _)| *thread).collect())
+    }
+
+    fn active_thread(&self) -> Option<ThreadId>
+    {
+        self.active_thread
+    }
+
+    fn read_registers(&self) -> Result<Registers>
+    {
+        self.registers_of(self.active()?).cloned()
+    }
+
+    fn read_registers_for(&self, thread: ThreadId) -> Result<Registers>
+    {
+        self.registers_of(thread).cloned()
+    }
+
+    fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+    {
+        self.snapshot
+            .read_memory(addr, len)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| not_saved(addr))
+    }
+
+    fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+    {
+        Ok(self.snapshot.regions.clone())
+    }
+
+    fn debug_level(&self, address: Address) -> Option<DebugLevel>
+    {
+        self.symbol_cache.debug_level_for(address)
+    }
+
+    fn function_start(&self, address: Address) -> Option<Address>
+    {
+        self.symbol_cache.function_start(address)
+    }
+
+    fn function_name(&self, address: Address) -> Option<SymbolName>
+    {
+        let symbolication = self.symbol_cache.symbolicate_function(address)?;
+        symbolication.frames.into_iter().next().map(|frame| frame.symbol)
+    }
+
+    fn source_location(&self, address: Address) -> Option<SourceLocation>
+    {
+        let symbolication = self.symbol_cache.symbolicate(address)?;
+        symbolication.frames.into_iter().next_back().and_then(|frame| frame.location)
+    }
+
+    fn describe_type(&self, name: &str) -> Option<Arc<TypeSummary>>
+    {
+        self.symbol_cache.describe_type(name).ok().flatten()
+    }
+
+    fn backtrace(&self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        Ok(self.unwind(thread, UnwindOptions::new(max_frames))?.frames)
+    }
+}
+
+impl Debugger for SnapshotDebugger
+{
+    fn launch_with_options(&mut self, _program: &str, _options: &LaunchOptions) -> Result<ProcessId>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "A snapshot can't launch a process".to_string(),
+        ))
+    }
+
+    fn attach(&mut self, _pid: ProcessId) -> Result<()>
+    {
+        Err(DebuggerError::InvalidArgument(
+            "A snapshot can't attach to a process".to_string(),
+        ))
+    }
+
+    fn detach(&mut self) -> Result<()>
+    {
+        self.open = false;
+        Ok(())
+    }
+
+    fn write_registers(&mut self, _regs: &Registers) -> Result<()>
+    {
+        Err(read_only())
+    }
+
+    fn modules(&self) -> Result<Vec<ImageInfo>>
+    {
+        Ok(self.symbol_cache.loaded_images())
+    }
+
+    fn stack_trace(&mut self, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        self.backtrace(self.active()?, max_frames)
+    }
+
+    fn stack_trace_for(&mut self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+    {
+        self.backtrace(thread, max_frames)
+    }
+
+    fn stack_trace_with_options(&mut self, options: UnwindOptions) -> Result<StackTrace>
+    {
+        self.unwind(self.active()?, options)
+    }
+
+    fn write_memory(&mut self, _addr: Address, _data: &[u8]) -> Result<usize>
+    {
+        Err(read_only())
+    }
+
+    fn suspend(&mut self) -> Result<()>
+    {
+        // Always stopped
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<()>
+    {
+        Err(DebuggerError::InvalidArgument("A snapshot can't be resumed".to_string()))
+    }
+
+    fn set_active_thread(&mut self, thread: ThreadId) -> Result<()>
+    {
+        self.registers_of(thread)?;
+        self.active_thread = Some(thread);
+        Ok(())
+    }
+
+    fn refresh_threads(&mut self) -> Result<()>
+    {
+        Ok(())
+    }
+
+    fn write_snapshot_with_options(&mut self, path: &Path, _options: SnapshotOptions) -> Result<()>
+    {
+        // Saving again keeps everything, not just what the options would pick
+        self.snapshot.save(path)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn registers(pc: u64, sp: u64) -> Registers
+    {
+        let mut registers = Registers::new().with_arch(Architecture::Arm64);
+        registers.pc = Address::from(pc);
+        registers.sp = Address::from(sp);
+        registers.fp = Address::from(sp + 0x20);
+        registers.general = (0..29).collect();
+        registers.status = 0x6000_0000;
+        registers
+    }
+
+    /// What the format saves of each thread, comparable since `Registers` isn't
+    fn thread_state(snapshot: &Snapshot) -> Vec<(ThreadId, u64, u64, u64, u64, Vec<u64>)>
+    {
+        snapshot
+            .threads
+            .iter()
+            .map(|(thread, regs)| {
+                (
+                    *thread,
+                    regs.pc.value(),
+                    regs.sp.value(),
+                    regs.fp.value(),
+                    regs.status,
+                    regs.general.clone(),
+                )
+            })
+            .collect()
+    }
+
+    fn snapshot() -> Snapshot
+    {
+        Snapshot {
+            architecture: Architecture::Arm64,
+            stop_reason: StopReason::StackOverflow {
+                thread: ThreadId::from(2),
+                sp: 0x7000,
+                guard_page: 0x6000,
+            },
+            active_thread: Some(ThreadId::from(1)),
+            threads: vec![
+                (ThreadId::from(1), registers(0x1_0000_1000, 0x7100)),
+                (ThreadId::from(2), registers(0x1_0000_2000, 0x7000)),
+            ],
+            regions: vec![
+                MemoryRegion::new(
+                    MemoryRegionId(0),
+                    Address::from(0x7000),
+                    Address::from(0x9000),
+                    "rw-".to_string(),
+                    None,
+                ),
+                MemoryRegion::new(
+                    MemoryRegionId(1),
+                    Address::from(0x1_0000_0000),
+                    Address::from(0x1_0000_4000),
+                    "r-x".to_string(),
+                    Some("/bin/demo".to_string()),
+                ),
+            ],
+            images: vec![(PathBuf::from("/nonexistent/demo"), Address::from(0x1_0000_0000))],
+            memory: vec![
+                MemoryRecord {
+                    start: Address::from(0x7000),
+                    bytes: (0..=255).cycle().take(0x2000).collect(),
+                },
+                MemoryRecord {
+                    start: Address::from(0x1_0000_0000),
+                    bytes: vec![0xc0, 0x03, 0x5f, 0xd6],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_a_file()
+    {
+        let path = std::env::temp_dir().join(format!("ferros-snapshot-{}.ferros", std::process::id()));
+        let original = snapshot();
+        original.save(&path).unwrap();
+        let loaded = Snapshot::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.architecture, original.architecture);
+        assert_eq!(loaded.stop_reason, original.stop_reason);
+        assert_eq!(loaded.active_thread, original.active_thread);
+        assert_eq!(thread_state(&loaded), thread_state(&original));
+        assert_eq!(loaded.regions, original.regions);
+        assert_eq!(loaded.images, original.images);
+        assert_eq!(loaded.memory, original.memory);
+
+        let error = Snapshot::read_from(&mut &b"ELF\0not a snapshot"[..]).unwrap_err();
+        assert!(matches!(error, DebuggerError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn a_snapshot_debugger_reads_what_was_saved_and_refuses_changes()
+    {
+        let mut debugger = SnapshotDebugger::new(snapshot());
+        assert!(debugger.is_attached() && debugger.is_stopped());
+        assert_eq!(debugger.threads().unwrap().len(), 2);
+        assert_eq!(debugger.read_registers().unwrap().pc, Address::from(0x1_0000_1000));
+        assert_eq!(debugger.read_memory(Address::from(0x7004), 2).unwrap(), vec![4, 5]);
+        assert!(debugger.read_memory(Address::from(0x8fff), 2).is_err());
+
+        debugger.set_active_thread(ThreadId::from(2)).unwrap();
+        assert_eq!(debugger.read_registers().unwrap().sp, Address::from(0x7000));
+        assert!(debugger.set_active_thread(ThreadId::from(9)).is_err());
+
+        assert!(debugger.write_memory(Address::from(0x7000), &[0]).is_err());
+        assert!(debugger.resume().is_err());
+        debugger.detach().unwrap();
+        assert!(!debugger.is_attached());
+    }
+
+    #[test]
+    fn capture_saves_writable_regions_up_to_the_cap()
+    {
+        let debugger = SnapshotDebugger::new(snapshot());
+
+        let writable = Snapshot::capture(&debugger, SnapshotOptions::new()).unwrap();
+        assert_eq!(thread_state(&writable), thread_state(&snapshot()));
+        assert_eq!(writable.regions.len(), 2);
+        assert_eq!(writable.memory.len(), 1);
+        assert_eq!(writable.memory[0].start, Address::from(0x7000));
+        assert_eq!(writable.memory_bytes(), 0x2000);
+
+        // Only 4 bytes of the code region were saved, so reading it fails and is skipped
+        let all = Snapshot::capture(&debugger, SnapshotOptions::new().with_all_regions()).unwrap();
+        assert_eq!(all.memory.len(), 1);
+        assert_eq!(all.memory_bytes(), 0x2000);
+
+        let capped = Snapshot::capture(&debugger, SnapshotOptions::new().with_max_bytes(0x100)).unwrap();
+        assert_eq!(capped.memory_bytes(), 0x100);
+        assert_eq!(capped.read_memory(Address::from(0x70ff), 1), Some(&[0xff][..]));
+        assert_eq!(capped.read_memory(Address::from(0x7100), 1), None);
+    }
+}
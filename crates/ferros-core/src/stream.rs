@@ -0,0 +1,518 @@
+//! Streaming reads of large memory ranges.
+//!
+//! [`TargetInspector::read_memory`](crate::TargetInspector::read_memory) returns the whole
+//! range in one buffer, which is fine for a stack frame but not for dumping
+//! hundreds of megabytes of heap. [`stream_memory`] reads a range in chunks of
+//! bounded size and writes each one to a sink before reading the next, so at
+//! most one chunk is held in memory however large the range is.
+//!
+//! ## Holes
+//!
+//! A chunk that can't be read in full is retried page by page
+//! ([`STREAM_PAGE_SIZE`]). Pages that still fail are written as
+//! [`StreamOptions::fill_byte`] so offsets in the output match offsets in the
+//! target, and are reported as [`MemoryHole`]s in the [`ReadStats`]. Adjacent
+//! unreadable pages are merged into one hole.
+//!
+//! ## Progress, Cancellation and Rate Limiting
+//!
+//! A progress callback runs after every chunk and may cancel the read by
+//! returning [`StreamControl::Cancel`]; what was written so far stays in the
+//! sink. With [`StreamOptions::with_rate_limit`] the reader sleeps between
+//! chunks to keep its average throughput under the limit, so dumping a live
+//! production target doesn't starve it.
+//!
+//! ```rust
+//! use ferros_core::stream::{StreamOptions, stream_memory};
+//! use ferros_core::types::Address;
+//!
+//! // 16 KiB of target memory whose third page (0x3000..0x4000) is unmapped
+//! let read = |address: Address, buffer: &mut [u8]| {
+//!     let end = address.value() + buffer.len() as u64;
+//!     if address.value() < 0x4000 && end > 0x3000 {
+//!         return Err(ferros_core::DebuggerError::InvalidArgument(
+//!             "unmapped".into(),
+//!         ));
+//!     }
+//!     buffer.fill(0xaa);
+//!     Ok(buffer.len())
+//! };
+//!
+//! let mut sink = Vec::new();
+//! let range = Address::from(0x1000)..Address::from(0x5000);
+//! let stats = stream_memory(range, 8192, &mut sink, &mut StreamOptions::new(), read)?;
+//! assert_eq!(sink.len(), 0x4000);
+//! assert_eq!(stats.hole_bytes(), 4096);
+//! assert_eq!(sink[0x2000], 0);
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use std::io::Write;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use crate::error::{DebuggerError, Result};
+use crate::types::Address;
+
+/// Granularity at which unreadable parts of a chunk are retried and reported.
+pub const STREAM_PAGE_SIZE: u64 = 4096;
+
+/// Chunk size used when the caller has no better idea.
+pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Whether a streamed read should go on after a progress report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamControl
+{
+    /// Read the next chunk.
+    Continue,
+    /// Stop after the chunk just written.
+    Cancel,
+}
+
+/// Progress of a streamed read, reported after every chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadProgress
+{
+    /// Bytes written to the sink so far, fill bytes included.
+    pub done: u64,
+    /// Bytes in the whole range.
+    pub total: u64,
+    /// Bytes so far that were unreadable and written as fill.
+    pub hole_bytes: u64,
+    /// Time since the read started.
+    pub elapsed: Duration,
+}
+
+impl ReadProgress
+{
+    /// Completed fraction in `0.0..=1.0`.
+    #[must_use]
+    pub fn fraction(&self) -> f64
+    {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f64 / self.total as f64
+        }
+    }
+}
+
+/// Unreadable range that was written as fill bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryHole
+{
+    /// First unreadable address.
+    pub start: Address,
+    /// Length in bytes.
+    pub len: u64,
+}
+
+/// Outcome of a streamed read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadStats
+{
+    /// Bytes in the requested range.
+    pub requested: u64,
+    /// Bytes written to the sink, fill bytes included.
+    pub written: u64,
+    /// Unreadable ranges, in address order.
+    pub holes: Vec<MemoryHole>,
+    /// Whether the progress callback stopped the read early.
+    pub cancelled: bool,
+    /// Time spent, including rate-limit sleeps.
+    pub elapsed: Duration,
+    /// Largest buffer held at once.
+    pub peak_buffer: usize,
+}
+
+impl ReadStats
+{
+    /// Bytes written as fill because they couldn't be read.
+    #[must_use]
+    pub fn hole_bytes(&self) -> u64
+    {
+        self.holes.iter().map(|hole| hole.len).sum()
+    }
+}
+
+/// Fill byte, rate limit and progress callback of a streamed read.
+pub struct StreamOptions<'a>
+{
+    /// Byte written in place of unreadable memory.
+    pub fill_byte: u8,
+    /// Maximum average throughput in bytes per second, if limited.
+    pub rate_limit: Option<u64>,
+    /// Show the code under software breakpoints instead of their traps, on
+    /// backends that can (see [`TargetInspector::read_memory_logical`]).
+    ///
+    /// [`TargetInspector::read_memory_logical`]: crate::inspector::TargetInspector::read_memory_logical
+    pub logical: bool,
+    progress: Option<&'a mut dyn FnMut(&ReadProgress) -> StreamControl>,
+}
+
+impl<'a> StreamOptions<'a>
+{
+    /// Zero fill, no rate limit and no progress reports.
+    #[must_use]
+    pub fn new() -> Self
+    {
+        Self {
+            fill_byte: 0,
+            rate_limit: None,
+            logical: false,
+            progress: None,
+        }
+    }
+
+    /// Write `byte` in place of unreadable memory.
+    #[must_use]
+    pub fn with_fill_byte(mut self, byte: u8) -> Self
+    {
+        self.fill_byte = byte;
+        self
+    }
+
+    /// Keep the average throughput under `bytes_per_sec`.
+    #[must_use]
+    pub fn with_rate_limit(mut self, bytes_per_sec: u64) -> Self
+    {
+        self.rate_limit = Some(bytes_per_sec).filter(|&rate| rate > 0);
+        self
+    }
+
+    /// Show the code under software breakpoints instead of their traps.
+    #[must_use]
+    pub fn with_logical_view(mut self, logical: bool) -> Self
+    {
+        self.logical = logical;
+        self
+    }
+
+    /// Call `progress` after every chunk; it can cancel the read.
+    #[must_use]
+    pub fn with_progress(mut self, progress: &'a mut dyn FnMut(&ReadProgress) -> StreamControl) -> Self
+    {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn report(&mut self, progress: &ReadProgress) -> StreamControl
+    {
+        self.progress
+            .as_mut()
+            .map_or(StreamControl::Continue, |callback| callback(progress))
+    }
+}
+
+impl Default for StreamOptions<'_>
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+/// Stream `range` to `sink` in chunks of at most `chunk_size` bytes.
+///
+/// `read` fills a buffer from the target and returns how many leading bytes
+/// it read; an error or a short read marks the rest of the buffer for a
+/// page-by-page retry. One buffer of at most `chunk_size` bytes is reused for
+/// the whole range.
+///
+/// ## Errors
+///
+/// Returns `InvalidArgument` if `chunk_size` is zero or the range is
+/// reversed, and `Io` if the sink fails. Read errors become holes.
+pub fn stream_memory(
+    range: Range<Address>,
+    chunk_size: usize,
+    sink: &mut dyn Write,
+    options: &mut StreamOptions<'_>,
+    mut read: impl FnMut(Address, &mut [u8]) -> Result<usize>,
+) -> Result<ReadStats>
+{
+    if chunk_size == 0 {
+        return Err(DebuggerError::InvalidArgument("chunk size must be non-zero".to_string()));
+    }
+    if range.end < range.start {
+        return Err(DebuggerError::InvalidArgument(format!(
+            "memory range {}..{} is reversed",
+            range.start, range.end
+        )));
+    }
+
+    let started = Instant::now();
+    let (start, end) = (range.start.value(), range.end.value());
+    let total = end - start;
+    let mut buffer = vec![0_u8; usize::try_from(total).map_or(chunk_size, |total| total.min(chunk_size))];
+    let mut stats = ReadStats {
+        requested: total,
+        peak_buffer: buffer.len(),
+        ..ReadStats::default()
+    };
+
+    let mut cursor = start;
+    while cursor < end {
+        let len = usize::try_from(end - cursor).map_or(buffer.len(), |left| left.min(buffer.len()));
+        let chunk = &mut buffer[..len];
+        let got = read(Address::from(cursor), chunk).unwrap_or(0).min(len);
+        if got < len {
+            read_pages(
+                cursor + got as u64,
+                &mut chunk[got..],
+                &mut read,
+                options.fill_byte,
+                &mut stats.holes,
+            );
+        }
+        sink.write_all(chunk)?;
+        cursor += len as u64;
+        stats.written += len as u64;
+
+        let progress = ReadProgress {
+            done: stats.written,
+            total,
+            hole_bytes: stats.hole_bytes(),
+            elapsed: started.elapsed(),
+        };
+        if options.report(&progress) == StreamControl::Cancel {
+            stats.cancelled = cursor < end;
+            break;
+        }
+        if let Some(rate) = options.rate_limit
+            && cursor < end
+        {
+            std::thread::sleep(throttle_delay(stats.written, rate, started.elapsed()));
+        }
+    }
+
+    sink.flush()?;
+    stats.elapsed = started.elapsed();
+    Ok(stats)
+}
+
+/// Retry `buffer` (target memory from `start`) one page at a time, filling
+/// and recording whatever still can't be read.
+fn read_pages(
+    start: u64,
+    buffer: &mut [u8],
+    read: &mut impl FnMut(Address, &mut [u8]) -> Result<usize>,
+    fill_byte: u8,
+    holes: &mut Vec<MemoryHole>,
+)
+{
+    let mut offset = 0;
+    while offset < buffer.len() {
+        let address = start + offset as u64;
+        let page_end = (address / STREAM_PAGE_SIZE + 1) * STREAM_PAGE_SIZE;
+        let len = usize::try_from(page_end - address).map_or(buffer.len() - offset, |len| len.min(buffer.len() - offset));
+        let piece = &mut buffer[offset..offset + len];
+        let got = read(Address::from(address), piece).unwrap_or(0).min(len);
+        if got < len {
+            piece[got..].fill(fill_byte);
+            record_hole(holes, address + got as u64, (len - got) as u64);
+        }
+        offset += len;
+    }
+}
+
+fn record_hole(holes: &mut Vec<MemoryHole>, start: u64, len: u64)
+{
+    if let Some(last) = holes.last_mut()
+        && last.start.value() + last.len == start
+    {
+        last.len += len;
+        return;
+    }
+    holes.push(MemoryHole {
+        start: Address::from(start),
+        len,
+    });
+}
+
+/// How long to wait so that `done` bytes after `elapsed` average at most `rate` bytes per second.
+#[must_use]
+pub fn throttle_delay(done: u64, rate: u64, elapsed: Duration) -> Duration
+{
+    if rate == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(done as f64 / rate as f64).saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::io;
+
+    use super::*;
+
+    const BASE: u64 = 0x1_0000_0000;
+    const REGION: u64 = 64 * 1024 * 1024;
+    const CHUNK: usize = 1024 * 1024;
+
+    /// Unreadable pages of the synthetic region, as page indices: a lone page,
+    /// a run straddling a chunk boundary and the region's last page
+    fn is_hole(page: u64) -> bool
+    {
+        page == 3 || (255..=258).contains(&page) || page == REGION / STREAM_PAGE_SIZE - 1
+    }
+
+    fn pattern(address: u64) -> u8
+    {
+        (address ^ (address >> 13)) as u8
+    }
+
+    /// Reads the synthetic region like `mach_vm_read_overwrite` would: a
+    /// range touching an unreadable page fails as a whole
+    fn synthetic_read(address: Address, buffer: &mut [u8]) -> Result<usize>
+    {
+        let start = address.value() - BASE;
+        let first = start / STREAM_PAGE_SIZE;
+        let last = (start + buffer.len() as u64 - 1) / STREAM_PAGE_SIZE;
+        if (first..=last).any(is_hole) {
+            return Err(DebuggerError::InvalidArgument("unmapped".to_string()));
+        }
+        for (offset, byte) in buffer.iter_mut().enumerate() {
+            *byte = pattern(address.value() + offset as u64);
+        }
+        Ok(buffer.len())
+    }
+
+    /// Checks every byte as it arrives instead of keeping 64 MiB around
+    #[derive(Default)]
+    struct CheckingSink
+    {
+        offset: u64,
+        largest_write: usize,
+        mismatches: u64,
+    }
+
+    impl Write for CheckingSink
+    {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize>
+        {
+            self.largest_write = self.largest_write.max(bytes.len());
+            for &byte in bytes {
+                let expected = if is_hole(self.offset / STREAM_PAGE_SIZE) {
+                    0xee
+                } else {
+                    pattern(BASE + self.offset)
+                };
+                self.mismatches += u64::from(byte != expected);
+                self.offset += 1;
+            }
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()>
+        {
+            Ok(())
+        }
+    }
+
+    fn region() -> Range<Address>
+    {
+        Address::from(BASE)..Address::from(BASE + REGION)
+    }
+
+    #[test]
+    fn large_region_streams_with_holes_filled()
+    {
+        let mut sink = CheckingSink::default();
+        let mut reports = 0;
+        let mut count = |_: &ReadProgress| {
+            reports += 1;
+            StreamControl::Continue
+        };
+        let mut options = StreamOptions::new().with_fill_byte(0xee).with_progress(&mut count);
+        let stats = stream_memory(region(), CHUNK, &mut sink, &mut options, synthetic_read).unwrap();
+
+        assert_eq!(sink.offset, REGION);
+        assert_eq!(sink.mismatches, 0);
+        assert_eq!(stats.written, REGION);
+        assert!(!stats.cancelled);
+        let page = |index: u64| Address::from(BASE + index * STREAM_PAGE_SIZE);
+        assert_eq!(
+            stats.holes,
+            vec![
+                MemoryHole {
+                    start: page(3),
+                    len: STREAM_PAGE_SIZE
+                },
+                MemoryHole {
+                    start: page(255),
+                    len: 4 * STREAM_PAGE_SIZE
+                },
+                MemoryHole {
+                    start: page(REGION / STREAM_PAGE_SIZE - 1),
+                    len: STREAM_PAGE_SIZE
+                },
+            ]
+        );
+        assert_eq!(stats.hole_bytes(), 6 * STREAM_PAGE_SIZE);
+        assert_eq!(reports, REGION / CHUNK as u64);
+        // Only ever one chunk in flight
+        assert_eq!(stats.peak_buffer, CHUNK);
+        assert_eq!(sink.largest_write, CHUNK);
+    }
+
+    #[test]
+    fn cancelling_stops_after_the_current_chunk()
+    {
+        let mut sink = CheckingSink::default();
+        let mut last = None;
+        let mut stop_halfway = |progress: &ReadProgress| {
+            last = Some(*progress);
+            if progress.fraction() >= 0.5 {
+                StreamControl::Cancel
+            } else {
+                StreamControl::Continue
+            }
+        };
+        let mut options = StreamOptions::new().with_fill_byte(0xee).with_progress(&mut stop_halfway);
+        let stats = stream_memory(region(), CHUNK, &mut sink, &mut options, synthetic_read).unwrap();
+
+        assert!(stats.cancelled);
+        assert_eq!(stats.written, REGION / 2);
+        assert_eq!(sink.offset, REGION / 2);
+        assert_eq!(sink.mismatches, 0);
+        assert_eq!(stats.hole_bytes(), 5 * STREAM_PAGE_SIZE);
+        assert_eq!(last.map(|progress| progress.hole_bytes), Some(5 * STREAM_PAGE_SIZE));
+    }
+
+    #[test]
+    fn short_reads_and_bad_arguments()
+    {
+        // A reader that only ever returns the first 100 bytes it's asked for
+        let stingy = |_: Address, buffer: &mut [u8]| {
+            let len = buffer.len().min(100);
+            buffer[..len].fill(1);
+            Ok(len)
+        };
+        let mut sink = Vec::new();
+        let range = Address::from(0x1000)..Address::from(0x1000 + 2 * STREAM_PAGE_SIZE);
+        let stats = stream_memory(range.clone(), 8192, &mut sink, &mut StreamOptions::new(), stingy).unwrap();
+        assert_eq!(sink.len(), 8192);
+        assert_eq!(stats.holes.len(), 2);
+        assert_eq!(stats.holes[0].start, Address::from(0x1000 + 100 + 100));
+        assert_eq!(stats.hole_bytes(), 8192 - 300);
+
+        assert!(stream_memory(range.clone(), 0, &mut sink, &mut StreamOptions::new(), stingy).is_err());
+        let reversed = range.end..range.start;
+        assert!(stream_memory(reversed, 8192, &mut sink, &mut StreamOptions::new(), stingy).is_err());
+    }
+
+    #[test]
+    fn throttling_holds_the_average_rate()
+    {
+        let rate = 4 * 1024 * 1024;
+        assert_eq!(
+            throttle_delay(rate, rate, Duration::from_millis(250)),
+            Duration::from_millis(750)
+        );
+        assert_eq!(throttle_delay(rate, rate, Duration::from_secs(2)), Duration::ZERO);
+        assert_eq!(throttle_delay(rate, 0, Duration::ZERO), Duration::ZERO);
+    }
+}
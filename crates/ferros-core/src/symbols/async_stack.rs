@@ -0,0 +1,620 @@
+//! Logical async stacks: the chain of futures a task is suspended in.
+//!
+//! A suspended task has no stack of its own. Its `async fn`s live in the
+//! task's future as nested state machines, and the thread that runs it is
+//! parked in the executor. rustc describes each state machine in DWARF as a
+//! structure named `{async_fn_env#N}` (or `{async_block_env#N}` for an
+//! `async` block) inside the namespace of the function, with a variant part
+//! keyed on an artificial `__state` member. The variants are `Unresumed`,
+//! `Returned`, `Panicked` and one `SuspendN` per `.await`, and each
+//! `SuspendN` holds the future being awaited as `__awaitee`.
+//!
+//! Starting from a state machine in a `SuspendN` state, following
+//! `__awaitee` through every nested state machine yields the awaited
+//! functions, outermost first, down to the leaf future (a timer, a channel,
+//! `core::future::pending`) the task is actually waiting on.
+//!
+//! ## Finding the Root
+//!
+//! DWARF doesn't say where the task's future is. An executor that blocks on
+//! a future pins it on its own stack, so [`StateMachines::find_in`] scans the
+//! stack of every frame in an executor entry point ([`is_executor_entry`])
+//! for a state machine whose chain of awaitees is consistent: every nested
+//! state machine must be suspended too. The longest chain wins, which picks
+//! the outermost future over the ones nested inside it.
+//!
+//! This covers `block_on` and a current-thread runtime. Tasks spawned onto a
+//! scheduler live on the heap and aren't found.
+
+use std::collections::{BTreeMap, HashMap};
+
+use gimli::{AttributeValue, DebuggingInformationEntry, Reader, Unit, UnitOffset, constants};
+
+use super::demangle::map_dwarf_error;
+use super::unwind::MemoryAccess;
+use super::{OwnedDwarf, OwnedReader};
+use crate::error::Result;
+use crate::types::Address;
+
+/// Symbol fragments of the functions an executor polls its futures from.
+///
+/// `core::future` covers `<F as Future>::poll` and `std::future::poll_fn`,
+/// which std re-exports from core.
+pub(crate) const EXECUTOR_ENTRY_POINTS: &[&str] = &["tokio::runtime::", "std::future::", "core::future::"];
+
+/// Deepest chain of awaits followed from one root.
+pub(crate) const MAX_AWAIT_DEPTH: usize = 64;
+
+/// Most bytes of a frame's stack scanned for a root future.
+pub(crate) const MAX_SCAN_BYTES: u64 = 64 * 1024;
+
+/// Whether `function` is one of the [`EXECUTOR_ENTRY_POINTS`].
+///
+/// v0 symbols name crates with a disambiguator (`tokio[64d69e9b3ae3752]`),
+/// which is ignored.
+#[must_use]
+pub(crate) fn is_executor_entry(function: &str) -> bool
+{
+    let mut plain = String::with_capacity(function.len());
+    let mut rest = function;
+    while let Some(open) = rest.find('[') {
+        plain.push_str(&rest[..open]);
+        rest = &rest[open..];
+        match rest.find(']') {
+            Some(close) if rest[1..close].chars().all(|c| c.is_ascii_hexdigit()) => rest = &rest[close + 1..],
+            _ => {
+                plain.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+    plain.push_str(rest);
+    EXECUTOR_ENTRY_POINTS.iter().any(|entry| plain.contains(entry))
+}
+
+/// The function an `async fn` or `async` block state machine belongs to.
+///
+/// `app::fetch::{async_fn_env#0}` is `app::fetch`, and
+/// `app::main::{async_block_env#0}` is `app::main::{async_block#0}`.
+/// Other names are returned unchanged.
+#[must_use]
+pub(crate) fn awaited_function(type_name: &str) -> String
+{
+    let Some((path, env)) = type_name.rsplit_once("::") else {
+        return type_name.to_string();
+    };
+    if env.starts_with("{async_fn_env#") {
+        path.to_string()
+    } else if env.starts_with("{async_block_env#") {
+        format!("{path}::{}", env.replacen("_env", "", 1))
+    } else {
+        type_name.to_string()
+    }
+}
+
+/// One level of a suspended task, outermost first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AwaitStep
+{
+    /// The awaited function, or the leaf future's type.
+    pub(crate) name: String,
+    /// Namespace-qualified type of the future.
+    pub(crate) type_name: String,
+    /// Where the future lives in the target.
+    pub(crate) address: u64,
+}
+
+/// A state machine's layout, from DWARF.
+#[derive(Debug, Clone)]
+pub(crate) struct StateMachine
+{
+    name: String,
+    size: u64,
+    alignment: u64,
+    state_offset: u64,
+    state_size: u64,
+    suspends: Vec<SuspendPoint>,
+}
+
+/// A `SuspendN` variant: the `__state` value selecting it and its `__awaitee`.
+#[derive(Debug, Clone)]
+struct SuspendPoint
+{
+    state: u64,
+    awaitee_offset: u64,
+    awaitee: Option<String>,
+}
+
+impl StateMachine
+{
+    fn suspend_point(&self, state: u64) -> Option<&SuspendPoint>
+    {
+        self.suspends.iter().find(|suspend| suspend.state == state)
+    }
+}
+
+/// Every state machine with a suspend point in one image, by qualified type name.
+#[derive(Debug, Default)]
+pub(crate) struct StateMachines
+{
+    by_name: BTreeMap<String, StateMachine>,
+}
+
+impl StateMachines
+{
+    /// Collect the state machines described in `dwarf`.
+    ///
+    /// A type emitted by several units keeps the first layout seen.
+    pub(crate) fn build(dwarf: &OwnedDwarf) -> Result<Self>
+    {
+        let mut machines = Self::default();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers
+            .next()
+            .map_err(|err| map_dwarf_error("reading .debug_info unit header", err))?
+        {
+            let unit = dwarf
+                .unit(header)
+                .map_err(|err| map_dwarf_error("parsing compilation unit", err))?;
+            machines.collect_unit(dwarf, &unit)?;
+        }
+        Ok(machines)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool
+    {
+        self.by_name.is_empty()
+    }
+
+    /// The longest suspended chain rooted in `[start, end)`.
+    ///
+    /// `read(address, size)` returns `size` little-endian bytes of target memory.
+    pub(crate) fn find_in(&self, start: u64, end: u64, read: &dyn Fn(u64, u64) -> Option<u64>) -> Option<Vec<AwaitStep>>
+    {
+        let mut best: Option<Vec<AwaitStep>> = None;
+        for machine in self.by_name.values() {
+            let mut address = start.next_multiple_of(machine.alignment);
+            while address.saturating_add(machine.size) <= end {
+                if let Some(chain) = self.chain(machine, address, read)
+                    && best.as_ref().is_none_or(|best| chain.len() > best.len())
+                {
+                    best = Some(chain);
+                }
+                address += machine.alignment;
+            }
+        }
+        best
+    }
+
+    /// The chain of awaits of the state machine `machine` at `address`.
+    ///
+    /// `None` unless it and every state machine nested in it are suspended.
+    fn chain(&self, machine: &StateMachine, address: u64, read: &dyn Fn(u64, u64) -> Option<u64>) -> Option<Vec<AwaitStep>>
+    {
+        let mut chain = Vec::new();
+        let (mut machine, mut address) = (machine, address);
+        while chain.len() < MAX_AWAIT_DEPTH {
+            let state = read(address + machine.state_offset, machine.state_size)?;
+            let suspend = machine.suspend_point(state)?;
+            chain.push(AwaitStep {
+                name: awaited_function(&machine.name),
+                type_name: machine.name.clone(),
+                address,
+            });
+            let awaitee_address = address + suspend.awaitee_offset;
+            if let Some(inner) = suspend.awaitee.as_deref().and_then(|awaitee| self.by_name.get(awaitee)) {
+                machine = inner;
+                address = awaitee_address;
+                continue;
+            }
+            let type_name = suspend.awaitee.clone().unwrap_or_else(|| "<unknown future>".to_string());
+            chain.push(AwaitStep {
+                name: type_name.clone(),
+                type_name,
+                address: awaitee_address,
+            });
+            break;
+        }
+        Some(chain)
+    }
+
+    fn collect_unit(&mut self, dwarf: &OwnedDwarf, unit: &Unit<OwnedReader>) -> Result<()>
+    {
+        // Qualified names of the unit's named types, for resolving `__awaitee`
+        let mut types = HashMap::new();
+        let mut envs = Vec::new();
+        let mut cursor = unit.entries();
+        let mut depth: isize = 0;
+        // Namespace names of the current entry's ancestors, by depth
+        let mut scopes: Vec<Option<String>> = Vec::new();
+        while let Some((delta, entry)) = cursor.next_dfs().map_err(|err| map_dwarf_error("traversing DIE tree", err))? {
+            depth += delta;
+            scopes.truncate(usize::try_from(depth).unwrap_or_default());
+            let name = entry_name(dwarf, unit, entry)?;
+            if matches!(
+                entry.tag(),
+                constants::DW_TAG_structure_type | constants::DW_TAG_union_type | constants::DW_TAG_enumeration_type
+            ) && let Some(name) = &name
+            {
+                let qualified = scopes
+                    .iter()
+                    .flatten()
+                    .map(String::as_str)
+                    .chain([name.as_str()])
+                    .collect::<Vec<_>>()
+                    .join("::");
+                if name.starts_with("{async_fn_env#") || name.starts_with("{async_block_env#") {
+                    envs.push((entry.offset(), qualified.clone()));
+                }
+                types.insert(entry.offset(), qualified);
+            }
+            scopes.push(name.filter(|_| entry.tag() == constants::DW_TAG_namespace));
+        }
+
+        for (offset, name) in envs {
+            if self.by_name.contains_key(&name) {
+                continue;
+            }
+            if let Some(machine) = state_machine(dwarf, unit, offset, name, &types)? {
+                self.by_name.insert(machine.name.clone(), machine);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Layout of the state machine at `offset`, if it has a size and a suspend point.
+fn state_machine(
+    dwarf: &OwnedDwarf,
+    unit: &Unit<OwnedReader>,
+    offset: UnitOffset<usize>,
+    name: String,
+    types: &HashMap<UnitOffset<usize>, String>,
+) -> Result<Option<StateMachine>>
+{
+    let entry = unit
+        .entry(offset)
+        .map_err(|err| map_dwarf_error("resolving state machine", err))?;
+    let Some(size) = udata(&entry, constants::DW_AT_byte_size)? else {
+        return Ok(None);
+    };
+    let alignment = udata(&entry, constants::DW_AT_alignment)?.unwrap_or(1).max(1);
+
+    let mut tree = unit
+        .entries_tree(Some(offset))
+        .map_err(|err| map_dwarf_error("building state machine tree", err))?;
+    let root = tree
+        .root()
+        .map_err(|err| map_dwarf_error("navigating state machine root", err))?;
+    let mut children = root.children();
+    while let Some(child) = children
+        .next()
+        .map_err(|err| map_dwarf_error("iterating state machine children", err))?
+    {
+        if child.entry().tag() != constants::DW_TAG_variant_part {
+            continue;
+        }
+        let Some(AttributeValue::UnitRef(discr)) = child
+            .entry()
+            .attr_value(constants::DW_AT_discr)
+            .map_err(|err| map_dwarf_error("reading DW_AT_discr", err))?
+        else {
+            return Ok(None);
+        };
+        let state = unit.entry(discr).map_err(|err| map_dwarf_error("resolving __state", err))?;
+        let Some(state_offset) = udata(&state, constants::DW_AT_data_member_location)? else {
+            return Ok(None);
+        };
+        let state_size = match type_entry(unit, &state)? {
+            Some(ty) => udata(&ty, constants::DW_AT_byte_size)?.unwrap_or(1),
+            None => 1,
+        };
+
+        let mut suspends = Vec::new();
+        let mut variants = child.children();
+        while let Some(variant) = variants
+            .next()
+            .map_err(|err| map_dwarf_error("iterating state machine variants", err))?
+        {
+            if variant.entry().tag() != constants::DW_TAG_variant {
+                continue;
+            }
+            let Some(state) = udata(variant.entry(), constants::DW_AT_discr_value)? else {
+                continue;
+            };
+            let mut members = variant.children();
+            while let Some(member) = members
+                .next()
+                .map_err(|err| map_dwarf_error("iterating variant members", err))?
+            {
+                if member.entry().tag() == constants::DW_TAG_member
+                    && let Some(point) = suspend_point(dwarf, unit, member.entry(), state, types)?
+                {
+                    suspends.push(point);
+                }
+            }
+        }
+
+        if suspends.is_empty() || state_size > 8 {
+            return Ok(None);
+        }
+        return Ok(Some(StateMachine {
+            name,
+            size,
+            alignment,
+            state_offset,
+            state_size,
+            suspends,
+        }));
+    }
+    Ok(None)
+}
+
+/// The suspend point a variant's member describes, if its wrapper struct holds an `__awaitee`.
+fn suspend_point(
+    dwarf: &OwnedDwarf,
+    unit: &Unit<OwnedReader>,
+    member: &DebuggingInformationEntry<'_, '_, OwnedReader>,
+    state: u64,
+    types: &HashMap<UnitOffset<usize>, String>,
+) -> Result<Option<SuspendPoint>>
+{
+    let base = udata(member, constants::DW_AT_data_member_location)?.unwrap_or(0);
+    let Some(wrapper) = type_entry(unit, member)? else {
+        return Ok(None);
+    };
+    let mut tree = unit
+        .entries_tree(Some(wrapper.offset()))
+        .map_err(|err| map_dwarf_error("building suspend variant tree", err))?;
+    let root = tree
+        .root()
+        .map_err(|err| map_dwarf_error("navigating suspend variant root", err))?;
+    let mut fields = root.children();
+    while let Some(field) = fields
+        .next()
+        .map_err(|err| map_dwarf_error("iterating suspend variant fields", err))?
+    {
+        let field = field.entry();
+        if field.tag() != constants::DW_TAG_member || entry_name(dwarf, unit, field)?.as_deref() != Some("__awaitee") {
+            continue;
+        }
+        let awaitee = type_entry(unit, field)?.and_then(|ty| types.get(&ty.offset()).cloned());
+        return Ok(Some(SuspendPoint {
+            state,
+            awaitee_offset: base + udata(field, constants::DW_AT_data_member_location)?.unwrap_or(0),
+            awaitee,
+        }));
+    }
+    Ok(None)
+}
+
+/// The entry `entry`'s `DW_AT_type` refers to, within the same unit.
+fn type_entry<'u>(
+    unit: &'u Unit<OwnedReader>,
+    entry: &DebuggingInformationEntry<'_, '_, OwnedReader>,
+) -> Result<Option<DebuggingInformationEntry<'u, 'u, OwnedReader>>>
+{
+    match entry
+        .attr_value(constants::DW_AT_type)
+        .map_err(|err| map_dwarf_error("reading DW_AT_type", err))?
+    {
+        Some(AttributeValue::UnitRef(offset)) => Ok(Some(
+            unit.entry(offset)
+                .map_err(|err| map_dwarf_error("resolving type reference", err))?,
+        )),
+        _ => Ok(None),
+    }
+}
+
+fn udata(entry: &DebuggingInformationEntry<'_, '_, OwnedReader>, name: constants::DwAt) -> Result<Option<u64>>
+{
+    Ok(entry
+        .attr_value(name)
+        .map_err(|err| map_dwarf_error("reading attribute", err))?
+        .and_then(|value| value.udata_value()))
+}
+
+fn entry_name(
+    dwarf: &OwnedDwarf,
+    unit: &Unit<OwnedReader>,
+    entry: &DebuggingInformationEntry<'_, '_, OwnedReader>,
+) -> Result<Option<String>>
+{
+    let Some(value) = entry
+        .attr_value(constants::DW_AT_name)
+        .map_err(|err| map_dwarf_error("reading DW_AT_name", err))?
+    else {
+        return Ok(None);
+    };
+    let reader = dwarf
+        .attr_string(unit, value)
+        .map_err(|err| map_dwarf_error("resolving DWARF string", err))?;
+    Ok(Some(
+        reader
+            .to_string_lossy()
+            .map_err(|err| map_dwarf_error("decoding DWARF string", err))?
+            .into_owned(),
+    ))
+}
+
+/// A frame's stack, read once so a scan doesn't read every word per candidate.
+pub(crate) struct StackSnapshot<'m, M>
+{
+    base: u64,
+    bytes: Vec<u8>,
+    memory: &'m M,
+}
+
+impl<'m, M: MemoryAccess> StackSnapshot<'m, M>
+{
+    /// Read `[start, end)`, or as much of it as is readable from `start` on.
+    pub(crate) fn read(memory: &'m M, start: u64, end: u64) -> Self
+    {
+        let base = start - start % 8;
+        let mut bytes = Vec::new();
+        let mut address = base;
+        while address < end {
+            let Ok(word) = memory.read_u64(Address::from(address)) else {
+                break;
+            };
+            bytes.extend_from_slice(&word.to_le_bytes());
+            address += 8;
+        }
+        Self { base, bytes, memory }
+    }
+
+    /// `size` little-endian bytes at `address`, from the snapshot when it covers them.
+    pub(crate) fn value(&self, address: u64, size: u64) -> Option<u64>
+    {
+        let mask = if size >= 8 { u64::MAX } else { (1 << (size * 8)) - 1 };
+        let offset = usize::try_from(address.checked_sub(self.base)?).ok()?;
+        if let Some(bytes) = self.bytes.get(offset..offset + 8) {
+            return Some(u64::from_le_bytes(bytes.try_into().ok()?) & mask);
+        }
+        self.memory.read_u64(Address::from(address)).ok().map(|word| word & mask)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::future::Future;
+    use std::sync::{Arc, OnceLock};
+    use std::task::{Context, Poll, Waker};
+
+    use gimli::{Dwarf, EndianArcSlice, RunTimeEndian};
+    use object::{Object, ObjectSection};
+
+    use super::*;
+
+    async fn leaf_wait(n: u32) -> u32
+    {
+        std::future::pending::<()>().await;
+        n
+    }
+
+    async fn middle(n: u32) -> u32
+    {
+        leaf_wait(n + 1).await + 1
+    }
+
+    async fn outer() -> u32
+    {
+        middle(3).await * 2
+    }
+
+    /// State machines of this test binary, collected once for every test
+    fn own_state_machines() -> &'static StateMachines
+    {
+        static MACHINES: OnceLock<StateMachines> = OnceLock::new();
+        MACHINES.get_or_init(|| {
+            let bytes = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+            let file = object::File::parse(&*bytes).unwrap();
+            let dwarf: OwnedDwarf = Dwarf::load(|id| {
+                let data = file
+                    .section_by_name(id.name())
+                    .and_then(|section| section.uncompressed_data().ok())
+                    .unwrap_or_default();
+                Ok::<_, gimli::Error>(EndianArcSlice::new(Arc::from(&*data), RunTimeEndian::Little))
+            })
+            .unwrap();
+            StateMachines::build(&dwarf).unwrap()
+        })
+    }
+
+    /// Little-endian reads from `bytes`, which are taken to live at `base`.
+    fn reader(base: u64, bytes: &[u8]) -> impl Fn(u64, u64) -> Option<u64> + '_
+    {
+        move |address, size| {
+            let offset = usize::try_from(address.checked_sub(base)?).ok()?;
+            let slice = bytes.get(offset..offset + usize::try_from(size).ok()?)?;
+            let mut word = [0; 8];
+            word[..slice.len()].copy_from_slice(slice);
+            Some(u64::from_le_bytes(word))
+        }
+    }
+
+    #[test]
+    fn state_machines_are_named_after_their_functions()
+    {
+        assert_eq!(awaited_function("app::fetch::{async_fn_env#0}"), "app::fetch");
+        assert_eq!(
+            awaited_function("app::main::{async_block_env#1}"),
+            "app::main::{async_block#1}"
+        );
+        assert_eq!(awaited_function("tokio::time::sleep::Sleep"), "tokio::time::sleep::Sleep");
+        assert!(is_executor_entry(
+            "tokio::runtime::scheduler::current_thread::CurrentThread::block_on"
+        ));
+        assert!(is_executor_entry(
+            "<tokio[64d69e9b3ae3752]::runtime::scheduler::current_thread::CurrentThread>::block_on::<app::main::{closure#\
+             0}>"
+        ));
+        assert!(!is_executor_entry("app::main"));
+        assert!(!is_executor_entry("<[u8; 4] as app::Parse>::parse"));
+    }
+
+    #[test]
+    fn a_pinned_task_is_found_and_its_awaits_followed()
+    {
+        let mut task = Box::pin(outer());
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(task.as_mut().poll(&mut cx), Poll::Pending);
+        // SAFETY: the bytes are only inspected, the way a debugger reads a
+        // target; the future stays pinned and alive until the end of the test
+        let future =
+            unsafe { std::slice::from_raw_parts(std::ptr::from_ref(&*task).cast::<u8>(), std::mem::size_of_val(&*task)) };
+
+        // The task somewhere in a frame's stack, between unrelated words
+        let mut stack = vec![0xa5; 40];
+        stack.extend_from_slice(future);
+        stack.extend_from_slice(&[0xa5; 40]);
+        let base = 0x7000_0000;
+        let machines = own_state_machines();
+        let chain = machines
+            .find_in(base, base + stack.len() as u64, &reader(base, &stack))
+            .unwrap();
+
+        let names: Vec<_> = chain.iter().map(|step| step.name.as_str()).collect();
+        let module = "ferros_core::symbols::async_stack::tests";
+        assert_eq!(
+            names,
+            vec![
+                format!("{module}::outer").as_str(),
+                format!("{module}::middle").as_str(),
+                format!("{module}::leaf_wait").as_str(),
+                "core::future::pending::Pending<()>",
+            ]
+        );
+        assert_eq!(chain[0].address, base + 40);
+        assert_eq!(chain[0].type_name, format!("{module}::outer::{{async_fn_env#0}}"));
+        assert!(chain.windows(2).all(|pair| pair[0].address <= pair[1].address));
+    }
+
+    #[test]
+    fn a_future_that_is_not_suspended_breaks_the_chain()
+    {
+        let mut task = Box::pin(outer());
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(task.as_mut().poll(&mut cx), Poll::Pending);
+        // SAFETY: as above
+        let mut future =
+            unsafe { std::slice::from_raw_parts(std::ptr::from_ref(&*task).cast::<u8>(), std::mem::size_of_val(&*task)) }
+                .to_vec();
+
+        let base = 0x1000;
+        let machines = own_state_machines();
+        let chain = machines
+            .find_in(base, base + future.len() as u64, &reader(base, &future))
+            .unwrap();
+        assert_eq!(chain.len(), 4);
+
+        // Mark `middle` as returned: neither it nor `outer` is a consistent root any more
+        let middle = &machines.by_name[&chain[1].type_name];
+        future[usize::try_from(chain[1].address - base + middle.state_offset).unwrap()] = 1;
+        let rest = machines
+            .find_in(base, base + future.len() as u64, &reader(base, &future))
+            .unwrap();
+        assert_eq!(rest, chain[2..]);
+    }
+}
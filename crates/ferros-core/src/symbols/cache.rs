@@ -15,7 +15,7 @@
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>>
 //! {
-//!     let mut cache = SymbolCache::new();
+//!     let cache = SymbolCache::new();
 //!     let descriptor = ImageDescriptor {
 //!         path: "/path/to/binary".into(),
 //!         load_address: 0x100000000,
@@ -24,6 +24,15 @@
 //!     // Load the binary image (parses DWARF sections)
 //!     let _image = cache.load_image(descriptor)?;
 //!
+//!     // Load the remaining images in parallel
+//!     let libraries = vec![ImageDescriptor {
+//!         path: "/path/to/libfoo.dylib".into(),
+//!         load_address: 0x200000000,
+//!     }];
+//!     for result in cache.load_images(libraries) {
+//!         result?;
+//!     }
+//!
 //!     // Symbolicate an address
 //!     if let Some(symbolication) = cache.symbolicate(Address::from(0x100001000)) {
 //!         for frame in symbolication.frames {
@@ -41,11 +50,22 @@
 //! }
 //! ```
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeSet, HashMap};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock, Weak, mpsc};
+use std::thread;
+
+use once_cell::sync::OnceCell;
 
+use super::demangle::{make_symbol_name, plain_name};
 use super::extractor::TypeSummary;
-use super::image::{BinaryImage, ImageDescriptor, ImageId};
+use super::image::{BinaryImage, DebugLevel, ImageDescriptor, ImageId, ImageInfo};
+use super::paths::{SourceMap, normalize_path};
+use super::tls::ThreadLocalVariable;
+use super::unwind::UnwindTableCache;
+use crate::breakpoints::BreakpointLocation;
 use crate::error::{DebuggerError, Result};
 use crate::types::{Address, FunctionParameter, SourceLocation, SymbolName};
 
@@ -77,6 +97,45 @@ pub struct SymbolFrame
     pub parameters: Vec<FunctionParameter>,
 }
 
+/// Most threads [`SymbolCache::load_images`] uses by default.
+pub const DEFAULT_LOAD_THREADS: usize = 4;
+
+/// Counters describing what a [`SymbolCache`] holds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SymbolCacheStats
+{
+    /// Images loaded.
+    pub images: usize,
+    /// Images whose addr2line line context has been built.
+    pub line_contexts: usize,
+    /// Binaries parsed, including ones that failed to parse.
+    pub parses: usize,
+    /// Unwind sections indexed; see [`UnwindTableCache`].
+    pub unwind_tables: usize,
+}
+
+/// How the target's image list changed since the last [`SymbolCache::sync_images`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageChanges
+{
+    /// Images listed now that weren't before.
+    pub loaded: Vec<ImageDescriptor>,
+    /// Images listed before that aren't any more.
+    pub unloaded: Vec<ImageDescriptor>,
+    /// Whether this was the first list seen, in which case every image is in `loaded`.
+    pub initial: bool,
+}
+
+impl ImageChanges
+{
+    /// Whether the list is unchanged.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.loaded.is_empty() && self.unloaded.is_empty()
+    }
+}
+
 /// Cache for binary images and their DWARF metadata.
 ///
 /// This cache stores parsed binary images to avoid re-parsing DWARF sections
@@ -85,12 +144,84 @@ pub struct SymbolFrame
 ///
 /// ## Thread Safety
 ///
-/// The cache is not thread-safe. If you need concurrent access, wrap it in
-/// a `Mutex` or `RwLock`.
-#[derive(Default)]
+/// Loads and lookups only need `&self`, so the cache can be shared between
+/// threads. Images for distinct paths parse in parallel, and a second request
+/// for an image that is still parsing waits for the first one's result
+/// instead of parsing it again. An image only becomes visible to lookups once
+/// it has been parsed completely. Each image builds its line context in its
+/// own once-cell, so a slow first lookup in one image doesn't block
+/// symbolication in the others.
+///
+/// ## Unwind Tables
+///
+/// The FDE indexes stack unwinding builds for each image are kept in an
+/// [`UnwindTableCache`] beside the images, so a backtrace doesn't reparse
+/// `.eh_frame` for every frame. Both are dropped together by
+/// [`invalidate_image`](Self::invalidate_image) and [`clear`](Self::clear).
+///
+/// ## Background Loading
+///
+/// Parsing a large binary's DWARF takes seconds. After
+/// [`start_background_loading`](Self::start_background_loading), images
+/// [`queue_images`](Self::queue_images) and [`sync_images`](Self::sync_images)
+/// are given are parsed on a worker thread instead of the caller's, and
+/// lookups in them return nothing until they are published, so a stack trace
+/// taken meanwhile has addresses but no names. The worker reports each batch
+/// it finishes to a callback; [`wait_for_loads`](Self::wait_for_loads) blocks
+/// for callers that need every queued image, such as resolving a breakpoint.
 pub struct SymbolCache
 {
-    images: HashMap<ImageId, Arc<BinaryImage>>,
+    /// Fully parsed images
+    images: RwLock<HashMap<ImageId, Arc<BinaryImage>>>,
+    /// Parses in progress, shared by every request for the same image
+    loading: Mutex<HashMap<ImageId, Arc<OnceCell<Arc<BinaryImage>>>>>,
+    /// FDE indexes of the loaded images
+    unwind_tables: UnwindTableCache,
+    /// The target's image list as of the last `sync_images`
+    listed: Mutex<Option<HashMap<ImageId, ImageDescriptor>>>,
+    source_map: RwLock<SourceMap>,
+    /// Where to look for the dSYMs of images without DWARF
+    dsym_search_paths: RwLock<Vec<PathBuf>>,
+    load_threads: usize,
+    parses: AtomicUsize,
+    /// Queue of the background worker, while one is running
+    background: Mutex<Option<mpsc::Sender<LoadBatch>>>,
+    /// Images queued for the worker and not yet parsed
+    pending: Mutex<usize>,
+    /// Signalled whenever `pending` drops
+    pending_done: Condvar,
+    /// Bumped by `clear`, so batches queued for a previous process are dropped
+    epoch: AtomicUsize,
+}
+
+/// Images queued for the background worker together
+struct LoadBatch
+{
+    epoch: usize,
+    descriptors: Vec<ImageDescriptor>,
+}
+
+impl Default for SymbolCache
+{
+    fn default() -> Self
+    {
+        Self {
+            images: RwLock::new(HashMap::new()),
+            loading: Mutex::new(HashMap::new()),
+            unwind_tables: UnwindTableCache::default(),
+            listed: Mutex::new(None),
+            source_map: RwLock::new(SourceMap::default()),
+            dsym_search_paths: RwLock::new(Vec::new()),
+            load_threads: thread::available_parallelism()
+                .map_or(1, NonZeroUsize::get)
+                .min(DEFAULT_LOAD_THREADS),
+            parses: AtomicUsize::new(0),
+            background: Mutex::new(None),
+            pending: Mutex::new(0),
+            pending_done: Condvar::new(),
+            epoch: AtomicUsize::new(0),
+        }
+    }
 }
 
 impl SymbolCache
@@ -99,7 +230,185 @@ impl SymbolCache
     #[must_use]
     pub fn new() -> Self
     {
-        Self { images: HashMap::new() }
+        Self::default()
+    }
+
+    /// Use at most `threads` threads in [`load_images`](Self::load_images).
+    ///
+    /// Defaults to the available parallelism, capped at [`DEFAULT_LOAD_THREADS`].
+    /// `0` is treated as `1`, which loads images one at a time on the caller's thread.
+    pub fn set_load_threads(&mut self, threads: usize)
+    {
+        self.load_threads = threads.max(1);
+    }
+
+    /// Remap build-machine source paths to local ones in every symbolication.
+    pub fn set_source_map(&self, source_map: SourceMap)
+    {
+        *self.source_map.write().unwrap() = source_map;
+    }
+
+    /// The source map applied to symbolicated locations.
+    #[must_use]
+    pub fn source_map(&self) -> SourceMap
+    {
+        self.source_map.read().unwrap().clone()
+    }
+
+    /// Also look for dSYM bundles in `path`, after the ones next to each binary.
+    ///
+    /// `path` is either a `.dSYM` bundle or a directory of them. Images
+    /// already loaded without DWARF are loaded again, through
+    /// [`queue_images`](Self::queue_images), so their dSYM is picked up;
+    /// returns how many. Adding a path twice does nothing.
+    pub fn add_dsym_search_path(&self, path: PathBuf) -> usize
+    {
+        {
+            let mut paths = self.dsym_search_paths.write().unwrap();
+            if paths.contains(&path) {
+                return 0;
+            }
+            paths.push(path);
+        }
+
+        let reload: Vec<ImageDescriptor> = self
+            .loaded()
+            .iter()
+            .filter(|image| !image.debug_level().has_dwarf())
+            .map(|image| ImageDescriptor {
+                path: image.path().to_path_buf(),
+                load_address: image.runtime_range().0,
+            })
+            .collect();
+        for descriptor in &reload {
+            self.invalidate_image(ImageId::from_parts(&descriptor.path, descriptor.load_address));
+        }
+        let count = reload.len();
+        self.queue_images(reload);
+        count
+    }
+
+    /// The dSYM search paths, in the order they are tried.
+    #[must_use]
+    pub fn dsym_search_paths(&self) -> Vec<PathBuf>
+    {
+        self.dsym_search_paths.read().unwrap().clone()
+    }
+
+    /// Parse queued images on a worker thread from now on.
+    ///
+    /// `on_loaded` runs on the worker after each batch of images is parsed
+    /// and published, with the number of images that loaded. The worker
+    /// holds only a weak reference, so it ends once the cache is dropped or
+    /// [`stop_background_loading`](Self::stop_background_loading) is called.
+    /// Starting it again replaces the previous worker.
+    ///
+    /// ## Errors
+    ///
+    /// - `Io`: The worker thread couldn't be spawned
+    pub fn start_background_loading(self: &Arc<Self>, on_loaded: impl Fn(usize) + Send + 'static) -> Result<()>
+    {
+        let (queue, batches) = mpsc::channel::<LoadBatch>();
+        let cache = Arc::downgrade(self);
+        thread::Builder::new()
+            .name("ferros-symbols".to_string())
+            .spawn(move || Self::run_background_loads(&cache, &batches, &on_loaded))?;
+        *self.background.lock().unwrap() = Some(queue);
+        Ok(())
+    }
+
+    /// Parse queued images on the caller's thread again; batches already queued still finish.
+    pub fn stop_background_loading(&self)
+    {
+        self.background.lock().unwrap().take();
+    }
+
+    /// Whether queued images are parsed on a worker thread.
+    #[must_use]
+    pub fn is_loading_in_background(&self) -> bool
+    {
+        self.background.lock().unwrap().is_some()
+    }
+
+    /// Worker loop: parse each batch, unless it was queued before a `clear`
+    fn run_background_loads(cache: &Weak<Self>, batches: &mpsc::Receiver<LoadBatch>, on_loaded: &dyn Fn(usize))
+    {
+        while let Ok(batch) = batches.recv() {
+            let Some(cache) = cache.upgrade() else {
+                break;
+            };
+            let count = batch.descriptors.len();
+            let mut loaded = 0;
+            if batch.epoch == cache.epoch.load(Ordering::Acquire) {
+                let images: Vec<_> = cache.load_images(batch.descriptors).into_iter().flatten().collect();
+                loaded = images.len();
+                // Cleared while parsing: the images belong to the previous process
+                if batch.epoch != cache.epoch.load(Ordering::Acquire) {
+                    for image in images {
+                        cache.invalidate_image(image.id());
+                    }
+                }
+            }
+            cache.finish_pending(count);
+            if batch.epoch == cache.epoch.load(Ordering::Acquire) {
+                on_loaded(loaded);
+            }
+        }
+    }
+
+    fn finish_pending(&self, count: usize)
+    {
+        let mut pending = self.pending.lock().unwrap();
+        *pending = pending.saturating_sub(count);
+        self.pending_done.notify_all();
+    }
+
+    /// Load `descriptors` on the background worker if one is running, else right away.
+    ///
+    /// Failures are only logged; the image is left unloaded, like a failed
+    /// [`load_images`](Self::load_images) entry.
+    pub fn queue_images(&self, descriptors: Vec<ImageDescriptor>)
+    {
+        if descriptors.is_empty() {
+            return;
+        }
+        let count = descriptors.len();
+        let batch = LoadBatch {
+            epoch: self.epoch.load(Ordering::Acquire),
+            descriptors,
+        };
+        let batch = match &*self.background.lock().unwrap() {
+            Some(queue) => {
+                *self.pending.lock().unwrap() += count;
+                match queue.send(batch) {
+                    Ok(()) => return,
+                    Err(mpsc::SendError(batch)) => {
+                        self.finish_pending(count);
+                        batch
+                    }
+                }
+            }
+            None => batch,
+        };
+        for result in self.load_images(batch.descriptors) {
+            if let Err(err) = result {
+                tracing::debug!("Failed to load image: {err}");
+            }
+        }
+    }
+
+    /// Images queued for the background worker that it hasn't parsed yet.
+    #[must_use]
+    pub fn pending_loads(&self) -> usize
+    {
+        *self.pending.lock().unwrap()
+    }
+
+    /// Block until the background worker has parsed every image queued so far.
+    pub fn wait_for_loads(&self)
+    {
+        let pending = self.pending.lock().unwrap();
+        drop(self.pending_done.wait_while(pending, |pending| *pending > 0).unwrap());
     }
 
     /// Load a binary image and parse its DWARF sections.
@@ -110,6 +419,9 @@ impl SymbolCache
     /// 3. Parses the binary and loads DWARF sections
     /// 4. Caches the parsed image for future lookups
     ///
+    /// The line context is left for the first lookup that needs it; see
+    /// [`BinaryImage`] for the two loading phases.
+    ///
     /// ## Parameters
     ///
     /// - `descriptor`: Image descriptor with path and load address
@@ -119,13 +431,16 @@ impl SymbolCache
     /// An `Arc<BinaryImage>` containing the parsed binary and DWARF metadata.
     /// If the image was already cached, returns the cached version.
     ///
+    /// If another thread is already parsing the same image this waits for it
+    /// and returns its result.
+    ///
     /// ## Errors
     ///
     /// Returns an error if:
     /// - The image path cannot be canonicalized
     /// - The binary cannot be parsed
     /// - DWARF sections cannot be loaded
-    pub fn load_image(&mut self, descriptor: ImageDescriptor) -> Result<Arc<BinaryImage>>
+    pub fn load_image(&self, descriptor: ImageDescriptor) -> Result<Arc<BinaryImage>>
     {
         let canonical = if descriptor.path.is_absolute() {
             descriptor.path.clone()
@@ -136,17 +451,157 @@ impl SymbolCache
         };
 
         let id = ImageId::from_parts(&canonical, descriptor.load_address);
-        if let Some(existing) = self.images.get(&id) {
-            return Ok(existing.clone());
+        let pending = {
+            // Checked under the `loading` lock so a parse that finishes in
+            // between is either found here or still has its cell registered
+            let mut loading = self.loading.lock().unwrap();
+            if let Some(existing) = self.images.read().unwrap().get(&id) {
+                return Ok(existing.clone());
+            }
+            loading.entry(id).or_default().clone()
+        };
+
+        let result = pending
+            .get_or_try_init(|| {
+                self.parses.fetch_add(1, Ordering::Relaxed);
+                BinaryImage::parse(
+                    ImageDescriptor {
+                        path: canonical,
+                        load_address: descriptor.load_address,
+                    },
+                    &self.dsym_search_paths(),
+                )
+                .map(Arc::new)
+            })
+            .cloned();
+
+        // Publish the finished image before retiring its cell; a failed parse
+        // leaves nothing behind, so a later request tries again
+        if let Ok(image) = &result {
+            self.images.write().unwrap().entry(id).or_insert_with(|| image.clone());
         }
+        let mut loading = self.loading.lock().unwrap();
+        if loading.get(&id).is_some_and(|cell| Arc::ptr_eq(cell, &pending)) {
+            loading.remove(&id);
+        }
+        result
+    }
+
+    /// Load several images, parsing them on up to
+    /// [`set_load_threads`](Self::set_load_threads) threads at once.
+    ///
+    /// Results are in the order of `descriptors`. Images that are already
+    /// cached, or listed twice, are only parsed once.
+    pub fn load_images(&self, descriptors: Vec<ImageDescriptor>) -> Vec<Result<Arc<BinaryImage>>>
+    {
+        let threads = self.load_threads.min(descriptors.len());
+        if threads <= 1 {
+            return descriptors
+                .into_iter()
+                .map(|descriptor| self.load_image(descriptor))
+                .collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let mut results: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut loaded = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(descriptor) = descriptors.get(index) else {
+                                break loaded;
+                            };
+                            loaded.push((index, self.load_image(descriptor.clone())));
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("image loader panicked"))
+                .collect()
+        });
+        results.sort_unstable_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Forget the image `id` and its unwind tables.
+    ///
+    /// For an image that is gone or was reloaded at another base; a later
+    /// [`load_image`](Self::load_image) parses it again. Returns whether the
+    /// image was loaded.
+    pub fn invalidate_image(&self, id: ImageId) -> bool
+    {
+        self.unwind_tables.invalidate(id);
+        self.images.write().unwrap().remove(&id).is_some()
+    }
+
+    /// Bring the cache in line with the image list the target reports now.
+    ///
+    /// Images new to the list are loaded, and images gone from it are
+    /// invalidated, so a library unloaded and another loaded over its range
+    /// doesn't keep resolving to the old one. Images the list keeps are left
+    /// alone, including ones that failed to load.
+    pub fn sync_images(&self, descriptors: Vec<ImageDescriptor>) -> ImageChanges
+    {
+        let current: HashMap<ImageId, ImageDescriptor> = descriptors
+            .into_iter()
+            .map(|descriptor| (ImageId::from_parts(&descriptor.path, descriptor.load_address), descriptor))
+            .collect();
+        let previous = self.listed.lock().unwrap().replace(current.clone());
+
+        let mut changes = match previous {
+            None => ImageChanges {
+                loaded: current.into_values().collect(),
+                initial: true,
+                ..ImageChanges::default()
+            },
+            Some(mut previous) => {
+                let loaded = current
+                    .into_iter()
+                    .filter_map(|(id, descriptor)| previous.remove(&id).is_none().then_some(descriptor))
+                    .collect();
+                // What's left of the previous list is gone from the current one
+                for id in previous.keys() {
+                    self.invalidate_image(*id);
+                }
+                ImageChanges {
+                    loaded,
+                    unloaded: previous.into_values().collect(),
+                    initial: false,
+                }
+            }
+        };
+        changes.loaded.sort_by_key(|descriptor| descriptor.load_address);
+        changes.unloaded.sort_by_key(|descriptor| descriptor.load_address);
+        self.queue_images(changes.loaded.clone());
+        changes
+    }
+
+    /// Forget every image and unwind table, before attaching to another process.
+    ///
+    /// Images still queued for the background worker are dropped unparsed.
+    pub fn clear(&self)
+    {
+        self.epoch.fetch_add(1, Ordering::AcqRel);
+        self.unwind_tables.clear();
+        self.images.write().unwrap().clear();
+        *self.listed.lock().unwrap() = None;
+    }
+
+    /// FDE indexes of the loaded images, shared by every unwind over this cache.
+    #[must_use]
+    pub fn unwind_tables(&self) -> &UnwindTableCache
+    {
+        &self.unwind_tables
+    }
 
-        #[allow(clippy::arc_with_non_send_sync)]
-        let image = Arc::new(BinaryImage::parse(ImageDescriptor {
-            path: canonical,
-            load_address: descriptor.load_address,
-        })?);
-        self.images.insert(id, image.clone());
-        Ok(image)
+    /// Every loaded image, for work that shouldn't hold the lock while it runs.
+    fn loaded(&self) -> Vec<Arc<BinaryImage>>
+    {
+        self.images.read().unwrap().values().cloned().collect()
     }
 
     /// Find the binary image containing the given address.
@@ -159,7 +614,21 @@ impl SymbolCache
     /// `Some(image)` if an image containing the address is found, `None` otherwise.
     pub fn image_for_address(&self, address: Address) -> Option<Arc<BinaryImage>>
     {
-        self.images.values().find(|image| image.contains(address)).cloned()
+        self.images
+            .read()
+            .unwrap()
+            .values()
+            .find(|image| image.contains(address))
+            .cloned()
+    }
+
+    /// How much debug information the image containing `address` carries.
+    ///
+    /// Returns `None` if no loaded image contains the address.
+    #[must_use]
+    pub fn debug_level_for(&self, address: Address) -> Option<DebugLevel>
+    {
+        self.image_for_address(address).map(|image| image.debug_level())
     }
 
     /// Symbolicate an address to function names and source locations.
@@ -177,7 +646,44 @@ impl SymbolCache
     /// `Some(symbolication)` if the address is found in a cached image, `None` otherwise.
     pub fn symbolicate(&self, address: Address) -> Option<Symbolication>
     {
-        self.image_for_address(address).and_then(|image| image.symbolicate(address))
+        let mut symbolication = self.image_for_address(address)?.symbolicate(address)?;
+        let source_map = self.source_map.read().unwrap();
+        if !source_map.is_empty() {
+            for location in symbolication.frames.iter_mut().filter_map(|frame| frame.location.as_mut()) {
+                if let Some(local) = source_map.to_local(&location.file) {
+                    location.file = local;
+                }
+            }
+        }
+        Some(symbolication)
+    }
+
+    /// Symbolicate an address to its function name only.
+    ///
+    /// Unlike [`symbolicate`](Self::symbolicate), this never builds an image's
+    /// line context; see [`BinaryImage::symbolicate_function`].
+    #[must_use]
+    pub fn symbolicate_function(&self, address: Address) -> Option<Symbolication>
+    {
+        self.image_for_address(address)?.symbolicate_function(address)
+    }
+
+    /// Runtime address of the function containing `address`.
+    ///
+    /// See [`BinaryImage::function_start`].
+    #[must_use]
+    pub fn function_start(&self, address: Address) -> Option<Address>
+    {
+        self.image_for_address(address)?.function_start(address)
+    }
+
+    /// Closest address at or before `address` known to start an instruction.
+    ///
+    /// See [`BinaryImage::known_instruction_start`].
+    #[must_use]
+    pub fn known_instruction_start(&self, address: Address) -> Option<Address>
+    {
+        self.image_for_address(address)?.known_instruction_start(address)
     }
 
     /// Describe a type by name using DWARF type information.
@@ -195,11 +701,774 @@ impl SymbolCache
     /// or an error if DWARF parsing fails.
     pub fn describe_type(&self, name: &str) -> Result<Option<Arc<TypeSummary>>>
     {
-        for image in self.images.values() {
+        for image in self.loaded() {
             if let Some(summary) = image.describe_type(name)? {
                 return Ok(Some(summary));
             }
         }
         Ok(None)
     }
+
+    /// Resolve a breakpoint location to runtime addresses across all cached images.
+    ///
+    /// A file given as a local path also matches the build-machine paths the
+    /// source map remaps to it. Addresses are returned lowest first. An empty list means no loaded image
+    /// knows the location (it may live in an image that isn't loaded yet).
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if DWARF parsing fails for one of the images.
+    pub fn resolve_location(&self, location: &BreakpointLocation) -> Result<Vec<Address>>
+    {
+        let source_map = self.source_map();
+        let mut addresses = Vec::new();
+        for image in self.loaded() {
+            match location {
+                BreakpointLocation::Address(address) => {
+                    if image.contains(*address) {
+                        addresses.push(*address);
+                    }
+                }
+                BreakpointLocation::Function(name) => addresses.extend(image.resolve_function(name)?),
+                BreakpointLocation::Line { file, line } => {
+                    addresses.extend(image.resolve_line(file, *line)?);
+                    for dwarf_file in source_map.to_dwarf(&normalize_path(file)) {
+                        addresses.extend(image.resolve_line(&dwarf_file, *line)?);
+                    }
+                }
+            }
+        }
+        addresses.sort_unstable();
+        addresses.dedup();
+        Ok(addresses)
+    }
+
+    /// Lines of `file` that compiled to code in any cached image.
+    ///
+    /// Like a `file:line` breakpoint, a local path also matches the
+    /// build-machine paths the source map remaps to it.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if DWARF parsing fails for one of the images.
+    pub fn code_lines(&self, file: &str) -> Result<BTreeSet<u32>>
+    {
+        let mut files = self.source_map().to_dwarf(&normalize_path(file));
+        files.push(file.to_string());
+        let mut lines = BTreeSet::new();
+        for image in self.loaded() {
+            for file in &files {
+                lines.extend(image.code_lines(file)?);
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Functions called `name` across all cached images, with their names, lowest address first.
+    ///
+    /// `name` matches as a [`BreakpointLocation::Function`] does: the plain
+    /// name, a `::`-separated suffix of the demangled path, or a linker symbol.
+    /// When some matches are named exactly `name` (see [`plain_name`]) only
+    /// those are returned, so `main` doesn't also find every `my_app::main`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if DWARF parsing fails for one of the images.
+    pub fn find_function(&self, name: &str) -> Result<Vec<(Address, SymbolName)>>
+    {
+        let mut functions: Vec<(Address, SymbolName)> = self
+            .resolve_location(&BreakpointLocation::Function(name.to_string()))?
+            .into_iter()
+            .map(|address| {
+                let symbol = self
+                    .symbolicate_function(address)
+                    .and_then(|symbolication| symbolication.frames.into_iter().next())
+                    .map_or_else(|| make_symbol_name(name.to_string()), |frame| frame.symbol);
+                (address, symbol)
+            })
+            .collect();
+        if functions.iter().any(|(_, symbol)| plain_name(symbol) == name) {
+            functions.retain(|(_, symbol)| plain_name(symbol) == name);
+        }
+        Ok(functions)
+    }
+
+    /// Error for a `location` that [`resolve_location`](Self::resolve_location)
+    /// found no code for.
+    ///
+    /// A `file:line` can only be found through DWARF line tables, so when no
+    /// loaded image has any the error says so instead of suggesting the line
+    /// has no code.
+    #[must_use]
+    pub fn unresolved_location_error(&self, location: &BreakpointLocation) -> DebuggerError
+    {
+        let images = self.loaded();
+        let message = match location {
+            BreakpointLocation::Line { .. }
+                if !images.is_empty() && images.iter().all(|image| !image.debug_level().has_dwarf()) =>
+            {
+                format!(
+                    "No code found for {location}: no loaded image has debug info (symbols only), so lines can't be \
+                     resolved; break on a function name or address instead"
+                )
+            }
+            _ => format!("No code found for {location}"),
+        };
+        DebuggerError::InvalidArgument(message)
+    }
+
+    /// Thread-local variables named `name` across all cached images.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if DWARF parsing fails for one of the images.
+    pub fn thread_locals(&self, name: &str) -> Result<Vec<ThreadLocalVariable>>
+    {
+        let mut variables = Vec::new();
+        for image in self.loaded() {
+            variables.extend(image.thread_locals(name)?);
+        }
+        Ok(variables)
+    }
+
+    /// Every loaded image, lowest load address first.
+    #[must_use]
+    pub fn loaded_images(&self) -> Vec<ImageInfo>
+    {
+        let mut images: Vec<ImageInfo> = self.loaded().iter().map(|image| image.info()).collect();
+        images.sort_by_key(|image| image.load_address);
+        images
+    }
+
+    /// Whether any image has been loaded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.images.read().unwrap().is_empty()
+    }
+
+    /// How many images are loaded, how many have built line contexts, how
+    /// many parses it took and how many unwind sections were indexed.
+    #[must_use]
+    pub fn stats(&self) -> SymbolCacheStats
+    {
+        let images = self.images.read().unwrap();
+        SymbolCacheStats {
+            images: images.len(),
+            line_contexts: images.values().filter(|image| image.has_line_context()).count(),
+            parses: self.parses.load(Ordering::Relaxed),
+            unwind_tables: self.unwind_tables.builds(),
+        }
+    }
+}
+
+/// Why the `functions` that `name` found can't be broken on together, if they can't.
+///
+/// Several addresses with the same [`plain_name`] are copies of one function,
+/// such as generic instances or one library loaded twice. Differently named
+/// functions mean `name` was ambiguous; the message lists them so the user
+/// can pick one by its full name instead of ferros guessing.
+#[must_use]
+pub fn ambiguous_functions(name: &str, functions: &[(Address, SymbolName)]) -> Option<String>
+{
+    let mut names: Vec<String> = Vec::new();
+    for (_, symbol) in functions {
+        let plain = plain_name(symbol);
+        if !names.contains(&plain) {
+            names.push(plain);
+        }
+    }
+    if names.len() < 2 {
+        return None;
+    }
+    let candidates: Vec<String> = functions
+        .iter()
+        .map(|(address, symbol)| format!("{} at {address}", plain_name(symbol)))
+        .collect();
+    Some(format!(
+        "{name} matches {} functions: {}; use the full name",
+        names.len(),
+        candidates.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::path::Path;
+    use std::time::Instant;
+
+    use object::Object;
+
+    use super::*;
+    use crate::symbols::image::{image_uuid, text_segment_address};
+    use crate::symbols::symtab::SymbolTable;
+
+    #[unsafe(no_mangle)]
+    #[inline(never)]
+    extern "C" fn ferros_cache_probe() -> u32
+    {
+        std::hint::black_box(5)
+    }
+
+    /// The test binary's path, its link-time base and the address of `symbol`.
+    fn own_image(symbol: &str) -> (PathBuf, u64, u64)
+    {
+        let path = std::env::current_exe().unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let file = object::File::parse(&*bytes).unwrap();
+        let base = text_segment_address(&file).unwrap();
+        let address = SymbolTable::from_object(&file).addresses_of(symbol)[0];
+        (path, base, address)
+    }
+
+    #[test]
+    fn names_resolve_before_the_line_context_and_locations_after()
+    {
+        assert_eq!(ferros_cache_probe(), 5);
+        let (path, base, address) = own_image("ferros_cache_probe");
+
+        let cache = SymbolCache::new();
+        let image = cache
+            .load_image(ImageDescriptor {
+                path,
+                load_address: base,
+            })
+            .unwrap();
+        assert_eq!(
+            cache.stats(),
+            SymbolCacheStats {
+                images: 1,
+                line_contexts: 0,
+                parses: 1,
+                unwind_tables: 0,
+            }
+        );
+
+        let names = cache.symbolicate_function(Address::from(address)).unwrap();
+        assert_eq!(names.frames.len(), 1);
+        assert_eq!(names.frames[0].symbol.display_name(), "ferros_cache_probe");
+        assert!(names.frames[0].location.is_none());
+        assert!(!image.has_line_context());
+
+        let full = cache.symbolicate(Address::from(address)).unwrap();
+        assert!(image.has_line_context());
+        assert_eq!(cache.stats().line_contexts, 1);
+        let frame = full.frames.last().unwrap();
+        assert!(frame.symbol.display_name().ends_with("ferros_cache_probe"));
+        let location = frame.location.as_ref().unwrap();
+        assert!(location.file.ends_with("symbols/cache.rs"), "{}", location.file);
+        assert!(location.line.is_some_and(|line| line > 0));
+
+        // Once built, name-only lookups reuse the line context
+        assert!(
+            cache.symbolicate_function(Address::from(address)).unwrap().frames[0]
+                .location
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn code_lines_come_from_the_line_tables()
+    {
+        assert_eq!(ferros_cache_probe(), 5);
+        let (path, base, address) = own_image("ferros_cache_probe");
+        let cache = SymbolCache::new();
+        cache
+            .load_image(ImageDescriptor {
+                path,
+                load_address: base,
+            })
+            .unwrap();
+        let frames = cache.symbolicate(Address::from(address)).unwrap().frames;
+        let line = frames.last().and_then(|frame| frame.location.as_ref()?.line).unwrap();
+
+        let lines = cache.code_lines("symbols/cache.rs").unwrap();
+        assert!(lines.contains(&line), "{line} not in {lines:?}");
+        // The module docs compile to nothing
+        assert!(!lines.contains(&1));
+        assert!(cache.code_lines("no/such/file.rs").unwrap().is_empty());
+
+        // A function's entry starts its first row; a byte in starts nothing closer
+        assert_eq!(
+            cache.known_instruction_start(Address::from(address)),
+            Some(Address::from(address))
+        );
+        let start = cache.known_instruction_start(Address::from(address + 1)).unwrap();
+        assert!((address..=address + 1).contains(&start.value()), "{start}");
+    }
+
+    /// Copy of the test binary named `name`, with every `from` replaced by the
+    /// `to` of the same length so the sections they name drop out of sight.
+    fn renamed_copy(name: &str, renames: &[(&[u8], &[u8])]) -> PathBuf
+    {
+        let mut bytes = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        for (from, to) in renames {
+            let mut at = 0;
+            while let Some(offset) = bytes[at..].windows(from.len()).position(|window| window == *from) {
+                at += offset;
+                bytes[at..at + to.len()].copy_from_slice(to);
+                at += to.len();
+            }
+        }
+        let path = std::env::temp_dir().join(format!("ferros-{name}-{}", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    /// Copy of the test binary with its DWARF sections renamed out of sight,
+    /// standing in for a release build that only has a symbol table.
+    fn stripped_copy() -> PathBuf
+    {
+        renamed_copy("stripped", &[(b".debug_", b".nodbg_"), (b"__debug", b"__nodbg")])
+    }
+
+    #[test]
+    fn images_without_dwarf_symbolicate_to_names_only()
+    {
+        assert_eq!(ferros_cache_probe(), 5);
+        let (own, base, address) = own_image("ferros_cache_probe");
+        let stripped = stripped_copy();
+
+        let cache = SymbolCache::new();
+        let image = cache
+            .load_image(ImageDescriptor {
+                path: stripped.clone(),
+                load_address: base,
+            })
+            .unwrap();
+        std::fs::remove_file(stripped).unwrap();
+        assert_eq!(image.debug_level(), DebugLevel::SymbolsOnly);
+        assert_eq!(cache.debug_level_for(Address::from(address)), Some(DebugLevel::SymbolsOnly));
+        assert_eq!(cache.debug_level_for(Address::from(u64::MAX)), None);
+
+        let frames = cache.symbolicate(Address::from(address)).unwrap().frames;
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].symbol.display_name(), "ferros_cache_probe");
+        assert!(frames[0].location.is_none());
+        assert!(!image.has_line_context());
+
+        // Names resolve through the symbol table; lines can't resolve at all
+        let function = BreakpointLocation::Function("ferros_cache_probe".to_string());
+        assert_eq!(cache.resolve_location(&function).unwrap(), [Address::from(address)]);
+        let found = cache.find_function("ferros_cache_probe").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(ambiguous_functions("ferros_cache_probe", &found), None);
+        assert_eq!(
+            (found[0].0, found[0].1.display_name()),
+            (Address::from(address), "ferros_cache_probe")
+        );
+        let line = BreakpointLocation::Line {
+            file: "symbols/cache.rs".to_string(),
+            line: 1,
+        };
+        assert!(cache.resolve_location(&line).unwrap().is_empty());
+        let error = cache.unresolved_location_error(&line).to_string();
+        assert!(error.contains("no loaded image has debug info (symbols only)"), "{error}");
+
+        // With an image that has DWARF loaded, the line just has no code
+        let offset = 1 << 32;
+        let full = cache
+            .load_image(ImageDescriptor {
+                path: own,
+                load_address: base + offset,
+            })
+            .unwrap();
+        assert_eq!(full.debug_level(), DebugLevel::Full);
+        assert_eq!(cache.debug_level_for(Address::from(address + offset)), Some(DebugLevel::Full));
+        let error = cache.unresolved_location_error(&line).to_string();
+        assert!(!error.contains("debug info"), "{error}");
+
+        // Listed lowest first, each with what it was found to carry
+        let images = cache.loaded_images();
+        assert_eq!(images.len(), 2);
+        assert_eq!(
+            (images[0].id, images[0].load_address, images[0].debug_level),
+            (image.id(), Address::from(base), DebugLevel::SymbolsOnly)
+        );
+        assert_eq!(
+            (images[1].id, images[1].load_address, images[1].debug_level),
+            (full.id(), Address::from(base + offset), DebugLevel::Full)
+        );
+        assert!(images.iter().all(|info| info.has_eh_frame && info.symbol_count > 0));
+        assert!(images[1].end_address > images[1].load_address);
+        assert_eq!(images[0].symbol_count, images[1].symbol_count);
+    }
+
+    /// `<dir>/<name>.dSYM` holding `dwarf` as the bundle's DWARF file
+    fn dsym_bundle(dir: &Path, name: &std::ffi::OsStr, dwarf: &Path) -> PathBuf
+    {
+        let mut bundle = name.to_os_string();
+        bundle.push(".dSYM");
+        let dwarf_dir = dir.join(bundle).join("Contents/Resources/DWARF");
+        std::fs::create_dir_all(&dwarf_dir).unwrap();
+        let target = dwarf_dir.join(name);
+        std::fs::copy(dwarf, &target).unwrap();
+        target
+    }
+
+    #[test]
+    fn dsyms_supply_dwarf_only_when_their_uuid_matches()
+    {
+        assert_eq!(ferros_cache_probe(), 5);
+        let (own, base, address) = own_image("ferros_cache_probe");
+        let binary = renamed_copy("dsym-binary", &[(b".debug_", b".nodbg_"), (b"__debug", b"__nodbg")]);
+        let name = binary.file_name().unwrap().to_os_string();
+        let descriptor = ImageDescriptor {
+            path: binary.clone(),
+            load_address: base,
+        };
+
+        // Another build of the same binary: same DWARF, different UUID
+        let uuid = image_uuid(&own).unwrap();
+        let id = object::File::parse(&*std::fs::read(&own).unwrap())
+            .unwrap()
+            .build_id()
+            .unwrap()
+            .unwrap()
+            .to_vec();
+        let other_id: Vec<u8> = id.iter().map(|byte| !byte).collect();
+        let other = renamed_copy("dsym-other", &[(&id, &other_id)]);
+        let root = std::env::temp_dir().join(format!("ferros-dsyms-{}", std::process::id()));
+        let (matching, stale) = (root.join("matching"), root.join("stale"));
+        let matching_dwarf = dsym_bundle(&matching, &name, &own);
+        let stale_dwarf = dsym_bundle(&stale, &name, &other);
+        std::fs::remove_file(other).unwrap();
+
+        let cache = SymbolCache::new();
+        assert_eq!(cache.add_dsym_search_path(stale.clone()), 0);
+        let image = cache.load_image(descriptor.clone()).unwrap();
+        assert_eq!(image.debug_level(), DebugLevel::SymbolsOnly);
+        assert_eq!(image.dsym_path(), None);
+        let mismatch = image.info().dsym_mismatch.unwrap();
+        assert_eq!(mismatch.path, stale_dwarf);
+        assert_eq!(mismatch.expected, uuid);
+        assert_ne!(mismatch.found.as_deref(), Some(uuid.as_str()));
+
+        // Images without DWARF are reloaded when a path is added
+        assert_eq!(cache.add_dsym_search_path(matching.clone()), 1);
+        assert_eq!(cache.add_dsym_search_path(matching.clone()), 0);
+        assert_eq!(cache.dsym_search_paths(), [stale, matching]);
+        let image = cache.image_for_address(Address::from(address)).unwrap();
+        assert_eq!(image.debug_level(), DebugLevel::Full);
+        assert_eq!(image.dsym_path(), Some(matching_dwarf.as_path()));
+        assert_eq!(image.info().dsym_mismatch.map(|mismatch| mismatch.path), Some(stale_dwarf));
+        let frame = cache.symbolicate(Address::from(address)).unwrap().frames.pop().unwrap();
+        assert!(frame.location.unwrap().file.ends_with("cache.rs"));
+
+        std::fs::remove_file(binary).unwrap();
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn only_differently_named_functions_are_ambiguous()
+    {
+        let function = |raw: &str, address: u64| (Address::from(address), make_symbol_name(raw.to_string()));
+        // Two instances of one generic only differ in their hash
+        let copies = [
+            function("_ZN6my_app3run17h0123456789abcdefE", 0x1000),
+            function("_ZN6my_app3run17hfedcba9876543210E", 0x2000),
+        ];
+        assert_eq!(ambiguous_functions("run", &copies), None);
+
+        let modules = [
+            function("_ZN6my_app3run17h0123456789abcdefE", 0x1000),
+            function("_ZN5other3run17h0123456789abcdefE", 0x2000),
+        ];
+        assert_eq!(
+            ambiguous_functions("run", &modules).as_deref(),
+            Some(
+                "run matches 2 functions: my_app::run at 0x0000000000001000, other::run at 0x0000000000002000; use the \
+                 full name"
+            )
+        );
+    }
+
+    /// `words` stack slots from `sp` up, each holding `value`.
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    struct RepeatedWord
+    {
+        sp: u64,
+        words: u64,
+        value: u64,
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    impl crate::symbols::unwind::MemoryAccess for RepeatedWord
+    {
+        fn read_u64(&self, address: Address) -> Result<u64>
+        {
+            let offset = address.value().wrapping_sub(self.sp);
+            if offset.is_multiple_of(8) && offset / 8 < self.words {
+                Ok(self.value)
+            } else {
+                Err(DebuggerError::MemoryReadFailed {
+                    address,
+                    length: 8,
+                    kern_return: None,
+                })
+            }
+        }
+    }
+
+    /// ELF `.eh_frame` without its `.eh_frame_hdr` search table, so every
+    /// frame is looked up in the index the cache builds.
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    #[test]
+    fn a_deep_unwind_indexes_each_image_once()
+    {
+        use crate::symbols::UnwindStrategy;
+        use crate::symbols::unwind::StackUnwinder;
+        use crate::types::{Architecture, FrameStatus, Registers, ThreadId};
+
+        assert_eq!(ferros_cache_probe(), 5);
+        let (_, base, address) = own_image("ferros_cache_probe");
+        let copy = renamed_copy("no-eh-frame-hdr", &[(b".eh_frame_hdr", b".eh_frame_xxx")]);
+
+        let cache = SymbolCache::new();
+        let image = cache
+            .load_image(ImageDescriptor {
+                path: copy.clone(),
+                load_address: base,
+            })
+            .unwrap();
+        std::fs::remove_file(copy).unwrap();
+        assert!(image.eh_frame_hdr_section().is_none());
+
+        // Stopped on the probe's first instruction, which "returns" to itself 64 times
+        let memory = RepeatedWord {
+            sp: 0x7000_0000,
+            words: 64,
+            value: address,
+        };
+        let mut regs = Registers::new();
+        regs.pc = Address::from(address);
+        regs.sp = Address::from(0x7000_0000);
+        let unwind = || {
+            StackUnwinder::new(Architecture::X86_64, &cache, &memory)
+                .unwind_with_diagnostics(ThreadId::from(1), &regs, 64)
+                .unwrap()
+        };
+
+        let trace = unwind();
+        assert_eq!(trace.diagnostics[0].resolved_by, Some(UnwindStrategy::Cfi));
+        assert_eq!(trace.frames[1].status, FrameStatus::Complete);
+        // The stopped frame and the 64 it returns to
+        assert_eq!(trace.recursion[0].repeats, 65);
+        let stats = cache.stats();
+        assert_eq!((stats.parses, stats.unwind_tables), (1, 1));
+
+        // A second backtrace reuses the index
+        unwind();
+        assert_eq!(cache.unwind_tables().builds(), 1);
+
+        // Invalidating the image drops its index with it
+        assert!(cache.invalidate_image(image.id()));
+        assert!(!cache.invalidate_image(image.id()));
+        assert!(cache.is_empty());
+        assert_eq!(unwind().frames.len(), 1);
+        assert_eq!(cache.unwind_tables().builds(), 1);
+    }
+
+    /// The test binary at `count` load addresses, each a distinct image.
+    fn own_images(path: &std::path::Path, base: u64, count: u64) -> Vec<ImageDescriptor>
+    {
+        (0..count)
+            .map(|index| ImageDescriptor {
+                path: path.to_path_buf(),
+                load_address: base + (index << 32),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn concurrent_loads_parse_each_image_once()
+    {
+        const IMAGES: u64 = 6;
+        assert_eq!(ferros_cache_probe(), 5);
+        let (path, base, address) = own_image("ferros_cache_probe");
+        let descriptors = own_images(&path, base, IMAGES);
+
+        let cache = SymbolCache::new();
+        thread::scope(|scope| {
+            for worker in 0..8 {
+                let cache = &cache;
+                let mut descriptors = descriptors.clone();
+                // Half the threads request the images in the opposite order
+                if worker % 2 == 1 {
+                    descriptors.reverse();
+                }
+                scope.spawn(move || {
+                    for result in cache.load_images(descriptors) {
+                        result.unwrap();
+                    }
+                });
+            }
+        });
+
+        let stats = cache.stats();
+        assert_eq!(stats.images, IMAGES as usize);
+        assert_eq!(stats.parses, IMAGES as usize);
+        for index in 0..IMAGES {
+            let names = cache.symbolicate_function(Address::from(address + (index << 32))).unwrap();
+            assert_eq!(names.frames[0].symbol.display_name(), "ferros_cache_probe");
+        }
+
+        // Loading again, or listing an image twice, parses nothing
+        let again = cache.load_images([descriptors.clone(), descriptors].concat());
+        assert_eq!(again.len(), 2 * IMAGES as usize);
+        assert!(Arc::ptr_eq(
+            again[0].as_ref().unwrap(),
+            again[IMAGES as usize].as_ref().unwrap()
+        ));
+        assert_eq!(cache.stats().parses, IMAGES as usize);
+    }
+
+    #[test]
+    fn failed_loads_keep_their_place_and_are_retried()
+    {
+        let (path, base, _) = own_image("ferros_cache_probe");
+        let mut descriptors = own_images(&path, base, 2);
+        descriptors.insert(
+            1,
+            ImageDescriptor {
+                path: PathBuf::from("/nonexistent/ferros-image"),
+                load_address: base,
+            },
+        );
+
+        let cache = SymbolCache::new();
+        let results = cache.load_images(descriptors.clone());
+        assert!(results[0].is_ok() && results[1].is_err() && results[2].is_ok());
+        assert_eq!(cache.stats().images, 2);
+
+        assert!(cache.load_image(descriptors.remove(1)).is_err());
+        assert_eq!(cache.stats().parses, 4);
+    }
+
+    #[test]
+    fn syncing_the_image_list_loads_new_images_and_drops_gone_ones()
+    {
+        let (path, base, address) = own_image("ferros_cache_probe");
+        let descriptors = own_images(&path, base, 3);
+        let cache = SymbolCache::new();
+
+        let first = cache.sync_images(descriptors[..2].to_vec());
+        assert!(first.initial);
+        assert_eq!(first.loaded, descriptors[..2]);
+        assert_eq!(cache.stats().images, 2);
+        assert!(cache.sync_images(descriptors[..2].to_vec()).is_empty());
+
+        // The second image is unloaded and a third one loaded
+        let changes = cache.sync_images(vec![descriptors[0].clone(), descriptors[2].clone()]);
+        assert_eq!(
+            changes,
+            ImageChanges {
+                loaded: vec![descriptors[2].clone()],
+                unloaded: vec![descriptors[1].clone()],
+                initial: false,
+            }
+        );
+        assert!(cache.symbolicate(Address::from(address + (1 << 32))).is_none());
+        assert!(cache.symbolicate(Address::from(address + (2 << 32))).is_some());
+        assert_eq!(cache.stats().parses, 3);
+
+        cache.clear();
+        assert!(cache.sync_images(descriptors).initial);
+    }
+
+    #[test]
+    fn background_loads_publish_images_from_the_worker()
+    {
+        let (path, base, address) = own_image("ferros_cache_probe");
+        let descriptors = own_images(&path, base, 4);
+        let cache = Arc::new(SymbolCache::new());
+        let (loaded_tx, loaded_rx) = mpsc::channel();
+        cache
+            .start_background_loading(move |images| {
+                let _ = loaded_tx.send((images, thread::current().name().map(str::to_string)));
+            })
+            .unwrap();
+        assert!(cache.is_loading_in_background());
+
+        assert!(cache.sync_images(descriptors[..2].to_vec()).initial);
+        let (images, worker) = loaded_rx.recv_timeout(std::time::Duration::from_secs(60)).unwrap();
+        assert_eq!(images, 2);
+        assert_eq!(worker.as_deref(), Some("ferros-symbols"));
+        assert_eq!(cache.pending_loads(), 0);
+        assert!(cache.symbolicate_function(Address::from(address + (1 << 32))).is_some());
+
+        cache.queue_images(vec![descriptors[2].clone()]);
+        cache.wait_for_loads();
+        assert!(cache.symbolicate_function(Address::from(address + (2 << 32))).is_some());
+
+        // Without the worker, queued images load before the call returns
+        cache.stop_background_loading();
+        cache.queue_images(vec![descriptors[3].clone()]);
+        assert_eq!(cache.stats().images, 4);
+    }
+
+    /// Loading 16 images on one thread versus the default pool.
+    ///
+    /// Run with `cargo test -p ferros-core -- --ignored --nocapture load_images_benchmark`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn load_images_benchmark()
+    {
+        let (path, base, _) = own_image("ferros_cache_probe");
+        let descriptors = own_images(&path, base, 16);
+
+        let load = |threads: usize| {
+            let mut cache = SymbolCache::new();
+            cache.set_load_threads(threads);
+            let start = Instant::now();
+            assert!(cache.load_images(descriptors.clone()).iter().all(Result::is_ok));
+            start.elapsed()
+        };
+
+        let serial = load(1);
+        let parallel = load(DEFAULT_LOAD_THREADS);
+        println!("serial:   {serial:?}");
+        println!("parallel: {parallel:?} ({DEFAULT_LOAD_THREADS} threads)");
+    }
+
+    /// Attach plus first backtrace with 30 images, building every line context
+    /// at load (eager) versus only in the image the backtrace touches (lazy).
+    ///
+    /// Run with `cargo test -p ferros-core -- --ignored --nocapture line_context_benchmark`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn line_context_benchmark()
+    {
+        const IMAGES: u64 = 30;
+        const FRAMES: u64 = 8;
+        let (path, base, address) = own_image("ferros_cache_probe");
+
+        let attach = |eager: bool| {
+            let start = Instant::now();
+            let cache = SymbolCache::new();
+            for index in 0..IMAGES {
+                let offset = index << 32;
+                let image = cache
+                    .load_image(ImageDescriptor {
+                        path: path.clone(),
+                        load_address: base + offset,
+                    })
+                    .unwrap();
+                if eager {
+                    let _ = image.symbolicate(Address::from(address + offset));
+                }
+            }
+            for _ in 0..FRAMES {
+                assert!(cache.symbolicate(Address::from(address)).is_some());
+            }
+            (start.elapsed(), cache.stats())
+        };
+
+        let (eager, eager_stats) = attach(true);
+        let (lazy, lazy_stats) = attach(false);
+        println!("eager: {eager:?} ({} line contexts)", eager_stats.line_contexts);
+        println!("lazy:  {lazy:?} ({} line contexts)", lazy_stats.line_contexts);
+        assert_eq!(lazy_stats.line_contexts, 1);
+    }
 }
@@ -1,7 +1,7 @@
 //! Symbol demangling utilities.
 //!
 //! This module provides functions to demangle symbol names and detect their
-//! programming language. It handles Rust, C++, and C symbols.
+//! programming language. It handles Rust, C++, C, and Objective-C symbols.
 //!
 //! ## Symbol Mangling
 //!
@@ -11,14 +11,18 @@
 //! - **Rust**: Uses v0 mangling scheme (e.g., `_R...`)
 //! - **C++**: Uses Itanium ABI mangling (e.g., `_Z...`)
 //! - **C**: Typically unmangled (global symbols)
+//! - **Objective-C**: Methods are named `-[Class selector]` by the compiler and
+//!   are never demangled
 //!
 //! ## Language Detection
 //!
 //! The module detects the language of a symbol by examining its mangling pattern:
 //!
+//! - Objective-C methods: `-[Class selector]` or `+[Class selector]`
 //! - Rust symbols: Start with `_R` or `_ZN`, or contain `::`
 //! - C++ symbols: Start with `_Z` (Itanium mangling)
-//! - C symbols: Everything else
+//! - C symbols: Plain identifiers
+//! - Unknown: Everything else
 
 use rustc_demangle::try_demangle;
 
@@ -50,11 +54,17 @@ use crate::types::{SymbolLanguage, SymbolName};
 /// ```
 pub(crate) fn make_symbol_name(raw: String) -> SymbolName
 {
+    if ObjcMethodName::parse(&raw).is_some() {
+        return SymbolName::new(raw, None, SymbolLanguage::ObjC);
+    }
+
     let demangled = try_demangle(&raw).ok().map(|d| d.to_string());
     let language = if raw.starts_with("_R") || raw.starts_with("_ZN") || raw.contains("::") {
         SymbolLanguage::Rust
     } else if raw.starts_with("_Z") {
         SymbolLanguage::Cpp
+    } else if is_c_identifier(&raw) {
+        SymbolLanguage::C
     } else {
         SymbolLanguage::Unknown
     };
@@ -62,6 +72,88 @@ pub(crate) fn make_symbol_name(raw: String) -> SymbolName
     SymbolName::new(raw, demangled, language)
 }
 
+/// Name of `symbol` the way it's typed to break on it.
+///
+/// Rust symbols are demangled without their `::h<hash>` suffix
+/// (`my_app::worker::run`); anything else is its display name.
+#[must_use]
+pub fn plain_name(symbol: &SymbolName) -> String
+{
+    match try_demangle(symbol.raw()) {
+        Ok(demangled) => format!("{demangled:#}"),
+        Err(_) => symbol.display_name().to_string(),
+    }
+}
+
+/// Whether `name` is a plain C identifier (`malloc`, `_start`).
+fn is_c_identifier(name: &str) -> bool
+{
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c == '_' || c.is_ascii_alphabetic()) && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+/// An Objective-C method name as the compiler emits it: `-[NSView drawRect:]`.
+///
+/// Category methods carry the category in parentheses after the class
+/// (`-[NSString(Extras) trimmed]`); matching ignores it, since users name the
+/// class they send the message to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ObjcMethodName<'a>
+{
+    /// `-` instance method (`true`) or `+` class method (`false`).
+    pub instance: bool,
+    /// Class name without the category.
+    pub class: &'a str,
+    /// Category name, if the method comes from one.
+    pub category: Option<&'a str>,
+    /// Selector, e.g. `drawRect:` or `initWithFrame:options:`.
+    pub selector: &'a str,
+}
+
+impl<'a> ObjcMethodName<'a>
+{
+    /// Parse `-[Class selector]`, `+[Class selector]` or the category form.
+    ///
+    /// Returns `None` for anything else, including plain C and Rust names.
+    pub(crate) fn parse(name: &'a str) -> Option<Self>
+    {
+        let name = name.trim();
+        let instance = match name.chars().next()? {
+            '-' => true,
+            '+' => false,
+            _ => return None,
+        };
+        let body = name[1..].trim_start().strip_prefix('[')?.strip_suffix(']')?;
+        let (receiver, selector) = body.trim().split_once(char::is_whitespace)?;
+        let selector = selector.trim();
+        if receiver.is_empty() || selector.is_empty() || selector.contains(char::is_whitespace) {
+            return None;
+        }
+        let (class, category) = match receiver.split_once('(') {
+            Some((class, category)) => (class, Some(category.strip_suffix(')')?)),
+            None => (receiver, None),
+        };
+        if class.is_empty() {
+            return None;
+        }
+        Some(Self {
+            instance,
+            class,
+            category,
+            selector,
+        })
+    }
+
+    /// Whether this method is the one a user spec such as `-[NSView drawRect:]` names.
+    pub(crate) fn matches(&self, spec: &ObjcMethodName<'_>) -> bool
+    {
+        self.instance == spec.instance
+            && self.class == spec.class
+            && self.selector == spec.selector
+            && (spec.category.is_none() || self.category == spec.category)
+    }
+}
+
 /// Check if a type name represents a Rust trait object.
 ///
 /// This function uses heuristics to detect trait objects by looking for the
@@ -110,3 +202,53 @@ pub(crate) fn map_dwarf_error(context: &str, err: gimli::Error) -> DebuggerError
 {
     DebuggerError::InvalidArgument(format!("{context}: {err}"))
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn languages_are_detected_without_demangling_c_or_objc()
+    {
+        let objc = make_symbol_name("-[NSView drawRect:]".to_string());
+        assert_eq!((objc.language(), objc.demangled()), (SymbolLanguage::ObjC, None));
+        assert_eq!(
+            make_symbol_name("+[NSColor(Extras) ferrosRed]".to_string()).language(),
+            SymbolLanguage::ObjC
+        );
+
+        let c = make_symbol_name("malloc".to_string());
+        assert_eq!((c.language(), c.display_name()), (SymbolLanguage::C, "malloc"));
+
+        let rust = make_symbol_name("_ZN6my_app6worker3run17h0123456789abcdefE".to_string());
+        assert_eq!(rust.language(), SymbolLanguage::Rust);
+        assert_eq!(plain_name(&rust), "my_app::worker::run");
+        assert_eq!(plain_name(&c), "malloc");
+        assert_eq!(
+            make_symbol_name("operator new".to_string()).language(),
+            SymbolLanguage::Unknown
+        );
+    }
+
+    #[test]
+    fn objc_method_names_parse()
+    {
+        let method = ObjcMethodName::parse("-[NSString(Extras) stringByTrimming:options:]").unwrap();
+        assert!(method.instance);
+        assert_eq!(method.class, "NSString");
+        assert_eq!(method.category, Some("Extras"));
+        assert_eq!(method.selector, "stringByTrimming:options:");
+        assert!(!ObjcMethodName::parse("+[NSView layerClass]").unwrap().instance);
+
+        for invalid in [
+            "-[NSView]",
+            "[NSView drawRect:]",
+            "-NSView drawRect:",
+            "-[ drawRect:]",
+            "-[NSView draw Rect:]",
+        ] {
+            assert_eq!(ObjcMethodName::parse(invalid), None, "{invalid}");
+        }
+    }
+}
@@ -75,6 +75,12 @@ pub struct TypeSummary
     /// Each variant contains its name, discriminant value, and fields (if it's
     /// a tuple or struct variant).
     pub variants: Vec<TypeVariant>,
+    /// Member holding the discriminant of an enum with data, if DWARF names one
+    ///
+    /// Its value selects the variant with that [`discriminant`](TypeVariant::discriminant),
+    /// or the variant without one when none match (the niche-filling layout
+    /// of `Option<&T>` stores only the other variants' values).
+    pub discriminant: Option<TypeField>,
 }
 
 impl TypeSummary
@@ -215,10 +221,21 @@ pub struct TypeVariant
     /// Fields of this variant (for tuple or struct variants)
     ///
     /// Empty for unit variants (e.g., `None`). Contains fields for tuple variants
-    /// (e.g., `Some(T)`) and struct variants (e.g., `Ok { value: T }`).
+    /// (e.g., `Some(T)`, whose field is `__0`) and struct variants (e.g.,
+    /// `Ok { value: T }`), at their offsets within the enum.
     pub fields: Vec<TypeField>,
 }
 
+/// Members of a structure DIE, and its variant part if it is a Rust enum
+#[derive(Default)]
+struct StructMembers
+{
+    fields: Vec<TypeField>,
+    variants: Vec<TypeVariant>,
+    discriminant: Option<TypeField>,
+    has_variant_part: bool,
+}
+
 pub(crate) struct TypeExtractor<'a>
 {
     dwarf: &'a OwnedDwarf,
@@ -308,13 +325,15 @@ impl<'a> TypeExtractor<'a>
 
         let mut fields = Vec::new();
         let mut variants = Vec::new();
+        let mut discriminant = None;
 
         match entry.tag() {
             constants::DW_TAG_structure_type | constants::DW_TAG_class_type => {
-                let (struct_fields, struct_variants, has_variants) = self.collect_struct_members(unit, entry.offset())?;
-                fields = struct_fields;
-                variants = struct_variants;
-                if has_variants {
+                let members = self.collect_struct_members(unit, entry.offset())?;
+                fields = members.fields;
+                variants = members.variants;
+                discriminant = members.discriminant;
+                if members.has_variant_part {
                     kind = TypeKind::Enum;
                 }
             }
@@ -335,6 +354,7 @@ impl<'a> TypeExtractor<'a>
             size_bits,
             fields,
             variants,
+            discriminant,
         })
     }
 
@@ -359,15 +379,9 @@ impl<'a> TypeExtractor<'a>
         Ok(None)
     }
 
-    fn collect_struct_members(
-        &self,
-        unit: &Unit<OwnedReader>,
-        offset: UnitOffset<usize>,
-    ) -> Result<(Vec<TypeField>, Vec<TypeVariant>, bool)>
+    fn collect_struct_members(&self, unit: &Unit<OwnedReader>, offset: UnitOffset<usize>) -> Result<StructMembers>
     {
-        let mut fields = Vec::new();
-        let mut variants = Vec::new();
-        let mut has_variant_part = false;
+        let mut members = StructMembers::default();
 
         let mut tree = unit
             .entries_tree(Some(offset))
@@ -380,16 +394,74 @@ impl<'a> TypeExtractor<'a>
         {
             let child_entry = child.entry().clone();
             match child_entry.tag() {
-                constants::DW_TAG_member => fields.push(self.build_field(unit, &child_entry)?),
+                constants::DW_TAG_member => members.fields.push(self.build_field(unit, &child_entry)?),
                 constants::DW_TAG_variant_part => {
-                    has_variant_part = true;
-                    variants.extend(self.collect_variants_from_offset(unit, child_entry.offset())?);
+                    members.has_variant_part = true;
+                    members.discriminant = self.variant_part_discriminant(unit, &child_entry)?;
+                    members
+                        .variants
+                        .extend(self.collect_variants_from_offset(unit, child_entry.offset())?);
                 }
                 _ => {}
             }
         }
 
-        Ok((fields, variants, has_variant_part))
+        Ok(members)
+    }
+
+    /// The member a variant part's `DW_AT_discr` points at
+    fn variant_part_discriminant(
+        &self,
+        unit: &Unit<OwnedReader>,
+        entry: &DebuggingInformationEntry<'_, '_, OwnedReader>,
+    ) -> Result<Option<TypeField>>
+    {
+        let Some(AttributeValue::UnitRef(offset)) = entry
+            .attr_value(constants::DW_AT_discr)
+            .map_err(|err| map_dwarf_error("reading DW_AT_discr", err))?
+        else {
+            return Ok(None);
+        };
+        let member = unit
+            .entry(offset)
+            .map_err(|err| map_dwarf_error("resolving discriminant member", err))?;
+        Ok(Some(self.build_field(unit, &member)?))
+    }
+
+    /// Fields of one variant, at their offsets within the enum
+    ///
+    /// rustc wraps each variant's fields in a struct named after the variant
+    /// and gives the variant a single member of that type; its fields are
+    /// returned instead of the wrapper.
+    fn variant_fields(
+        &self,
+        unit: &Unit<OwnedReader>,
+        member: &DebuggingInformationEntry<'_, '_, OwnedReader>,
+    ) -> Result<Vec<TypeField>>
+    {
+        let outer = self.build_field(unit, member)?;
+        let wrapper = match member
+            .attr_value(constants::DW_AT_type)
+            .map_err(|err| map_dwarf_error("reading variant member type", err))?
+        {
+            Some(AttributeValue::UnitRef(offset)) => unit
+                .entry(offset)
+                .map_err(|err| map_dwarf_error("resolving variant member type", err))?,
+            _ => return Ok(vec![outer]),
+        };
+        if wrapper.tag() != constants::DW_TAG_structure_type || self.entry_name(unit, &wrapper)? != outer.name {
+            return Ok(vec![outer]);
+        }
+        let base = outer.offset_bits.unwrap_or(0);
+        Ok(self
+            .collect_struct_members(unit, wrapper.offset())?
+            .fields
+            .into_iter()
+            .map(|field| TypeField {
+                offset_bits: field.offset_bits.map(|bits| base + bits),
+                ..field
+            })
+            .collect())
     }
 
     fn collect_union_members(&self, unit: &Unit<OwnedReader>, offset: UnitOffset<usize>) -> Result<Vec<TypeField>>
@@ -434,7 +506,7 @@ impl<'a> TypeExtractor<'a>
             {
                 let field_entry = field_node.entry().clone();
                 if field_entry.tag() == constants::DW_TAG_member {
-                    variant_fields.push(self.build_field(unit, &field_entry)?);
+                    variant_fields.extend(self.variant_fields(unit, &field_entry)?);
                 }
             }
 
@@ -21,22 +21,37 @@
 //! than its link-time address. The `BinaryImage` handles this relocation by
 //! computing a "slide" (offset) between the file's virtual addresses and the
 //! runtime addresses.
+//!
+//! ## External Debug Info
+//!
+//! Release builds on macOS usually keep their DWARF in a separate `.dSYM`
+//! bundle. An image without `.debug_info` of its own looks for
+//! `<binary>.dSYM/Contents/Resources/DWARF/<name>` next to the binary and in
+//! the search paths it was given, and loads the DWARF sections of the first
+//! one whose UUID matches its own. A bundle with another UUID was built from
+//! other sources and is reported instead of used
+//! ([`ImageInfo::dsym_mismatch`]). Without any DWARF the linker symbol table
+//! still names functions.
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 
 use addr2line::Context;
 use gimli::{Dwarf, EndianArcSlice, RunTimeEndian, SectionId};
 use object::{Object, ObjectSection, ObjectSegment};
 use once_cell::sync::OnceCell;
 
+use super::async_stack::StateMachines;
 use super::cache::{SymbolFrame, Symbolication};
-use super::demangle::make_symbol_name;
+use super::demangle::{ObjcMethodName, make_symbol_name};
 use super::extractor::{TypeExtractor, TypeSummary};
+use super::locate::{LocationResolver, RowIndex};
+use super::symtab::SymbolTable;
+use super::tls::{ThreadLocalVariable, TlsLocation, TlsResolver};
 use super::{OwnedDwarf, OwnedReader};
 use crate::error::{DebuggerError, Result};
 use crate::types::{Address, Architecture, FunctionParameter, SourceLocation};
@@ -58,7 +73,7 @@ use crate::types::{Address, Architecture, FunctionParameter, SourceLocation};
 ///     load_address: 0x100000000, // Address where binary is loaded
 /// };
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ImageDescriptor
 {
     /// Path to the binary file on disk
@@ -72,6 +87,58 @@ pub struct ImageDescriptor
     pub load_address: u64,
 }
 
+/// What a loaded image provides, as listed by [`SymbolCache::loaded_images`](super::SymbolCache::loaded_images).
+///
+/// Shows which binaries got symbols and where, for diagnosing frames that
+/// symbolicate to nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageInfo
+{
+    /// Cache key of the image
+    pub id: ImageId,
+    /// Absolute path of the binary on disk
+    pub path: PathBuf,
+    /// Where the image's `__TEXT` segment (or lowest ELF segment) is mapped
+    pub load_address: Address,
+    /// End of the image's runtime range, which starts at `load_address`
+    pub end_address: Address,
+    /// Whether DWARF or only the symbol table was found
+    pub debug_level: DebugLevel,
+    /// Whether the image has `.eh_frame` CFI to unwind through it
+    pub has_eh_frame: bool,
+    /// Code symbols in the linker symbol table
+    pub symbol_count: usize,
+    /// DWARF file of the dSYM bundle the debug info was loaded from, if any
+    pub dsym: Option<PathBuf>,
+    /// A dSYM that was found but belongs to another build of the binary
+    pub dsym_mismatch: Option<DsymMismatch>,
+}
+
+/// A dSYM found for an image whose UUID doesn't match the image's own.
+///
+/// Its DWARF would describe other code at the same addresses, so it isn't loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsymMismatch
+{
+    /// DWARF file inside the bundle
+    pub path: PathBuf,
+    /// UUID of the image, as hex
+    pub expected: String,
+    /// UUID of the dSYM, or `None` if it has none or isn't a binary
+    pub found: Option<String>,
+}
+
+impl std::fmt::Display for DsymMismatch
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match &self.found {
+            Some(found) => write!(f, "{} has UUID {found}, not {}", self.path.display(), self.expected),
+            None => write!(f, "{} has no UUID; expected {}", self.path.display(), self.expected),
+        }
+    }
+}
+
 /// Unique identifier for a binary image.
 ///
 /// This ID is computed from the image's path and load address, ensuring that
@@ -134,6 +201,44 @@ impl ImageId
     }
 }
 
+/// How much debug information an image carries.
+///
+/// Release builds and system libraries often have no DWARF at all. Their
+/// frames still get function names from the linker symbol table, but no
+/// source locations, so line-based features are unavailable in them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DebugLevel
+{
+    /// DWARF `.debug_info`: function names, source lines and types
+    Full,
+    /// No DWARF, but the linker symbol table names functions
+    SymbolsOnly,
+    /// Neither DWARF nor a symbol table, as in fully stripped binaries
+    None,
+}
+
+impl DebugLevel
+{
+    /// Whether the image has DWARF, so source lines and types can be looked up
+    #[must_use]
+    pub const fn has_dwarf(self) -> bool
+    {
+        matches!(self, DebugLevel::Full)
+    }
+}
+
+impl std::fmt::Display for DebugLevel
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        f.write_str(match self {
+            DebugLevel::Full => "full debug info",
+            DebugLevel::SymbolsOnly => "symbols only",
+            DebugLevel::None => "no symbols",
+        })
+    }
+}
+
 #[derive(Clone)]
 struct SectionBlob
 {
@@ -165,6 +270,139 @@ const DWARF_SECTIONS: &[(&str, &[&str])] = &[
     (".debug_str_sup", &[".debug_str_sup"]),
 ];
 
+/// Link-time address of the segment the image's load address refers to.
+///
+/// That's `__TEXT` for Mach-O. ELF program headers have no names, so the
+/// lowest loadable segment stands in for it.
+pub(crate) fn text_segment_address(file: &object::File<'_>) -> Option<u64>
+{
+    let named = file.segments().find(|segment| {
+        if let Ok(Some(name)) = segment.name() {
+            name == "__TEXT" || name == ".text"
+        } else {
+            false
+        }
+    });
+    match named {
+        Some(segment) => Some(segment.address()),
+        None if file.format() == object::BinaryFormat::Elf => file.segments().map(|segment| segment.address()).min(),
+        None => None,
+    }
+}
+
+/// Build identifier of the binary at `path`: the Mach-O `LC_UUID`, or the
+/// ELF `.note.gnu.build-id`, as hex.
+///
+/// Returns `None` if the file can't be read or has no identifier.
+#[must_use]
+pub fn image_uuid(path: &Path) -> Option<String>
+{
+    let bytes = fs::read(path).ok()?;
+    let file = object::File::parse(&*bytes).ok()?;
+    build_id(&file)
+}
+
+fn build_id(file: &object::File<'_>) -> Option<String>
+{
+    let id = match file.mach_uuid() {
+        Ok(Some(uuid)) => uuid.to_vec(),
+        _ => file.build_id().ok()??.to_vec(),
+    };
+    Some(id.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Where the dSYM of `binary` may be, in the order they are tried.
+///
+/// First the bundle next to the binary, then for each search path either the
+/// path itself if it is a `.dSYM` bundle or `<name>.dSYM` inside it.
+fn dsym_candidates(binary: &Path, search_paths: &[PathBuf]) -> Vec<PathBuf>
+{
+    let Some(name) = binary.file_name() else {
+        return Vec::new();
+    };
+    let dwarf_file = |bundle: PathBuf| bundle.join("Contents/Resources/DWARF").join(name);
+    let bundle_name = {
+        let mut bundle_name = name.to_os_string();
+        bundle_name.push(".dSYM");
+        bundle_name
+    };
+
+    let mut candidates = vec![dwarf_file(binary.with_file_name(&bundle_name))];
+    for dir in search_paths {
+        let bundle = if dir.extension().is_some_and(|extension| extension == "dSYM") {
+            dir.clone()
+        } else {
+            dir.join(&bundle_name)
+        };
+        let candidate = dwarf_file(bundle);
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+/// What looking for an image's dSYM found
+#[derive(Default)]
+struct DsymSearch
+{
+    /// The matching DWARF file and its contents
+    found: Option<(PathBuf, Arc<[u8]>)>,
+    /// The first bundle that was rejected
+    mismatch: Option<DsymMismatch>,
+}
+
+/// Find the dSYM of `binary` whose UUID is `uuid`.
+fn find_dsym(binary: &Path, uuid: &str, search_paths: &[PathBuf]) -> DsymSearch
+{
+    let mut search = DsymSearch::default();
+    for candidate in dsym_candidates(binary, search_paths) {
+        let Ok(bytes) = fs::read(&candidate) else {
+            continue;
+        };
+        let found = object::File::parse(&*bytes).ok().and_then(|file| build_id(&file));
+        if found.as_deref() == Some(uuid) {
+            search.found = Some((candidate, Arc::from(bytes)));
+            return search;
+        }
+
+        let mismatch = DsymMismatch {
+            path: candidate,
+            expected: uuid.to_string(),
+            found,
+        };
+        tracing::warn!("Ignoring dSYM for {}: {mismatch}", binary.display());
+        search.mismatch.get_or_insert(mismatch);
+    }
+    search
+}
+
+/// DWARF section contents by canonical name, empty for the ones a file lacks
+type DebugSections = HashMap<&'static str, Arc<[u8]>>;
+
+/// Every DWARF section of `file` by canonical name, with the sizes of `.debug_info` and `.debug_line`
+fn load_dwarf_sections(file: &object::File<'_>) -> Result<(DebugSections, usize, usize)>
+{
+    let mut sections = HashMap::new();
+    let mut debug_info_size = 0;
+    let mut debug_line_size = 0;
+    for (canonical, aliases) in DWARF_SECTIONS {
+        let data = load_section_bytes(file, aliases)?;
+        if *canonical == ".debug_info" {
+            debug_info_size = data.len();
+        }
+        if *canonical == ".debug_line" {
+            debug_line_size = data.len();
+        }
+        if !data.is_empty() {
+            use tracing::debug;
+            debug!("Loaded DWARF section {} ({} bytes)", canonical, data.len());
+        }
+        sections.insert(*canonical, data);
+    }
+    Ok((sections, debug_info_size, debug_line_size))
+}
+
 fn load_section_bytes<'data>(file: &object::File<'data>, names: &[&str]) -> Result<Arc<[u8]>>
 {
     for name in names {
@@ -216,6 +454,13 @@ fn load_section_blob<'data>(file: &object::File<'data>, names: &[&str]) -> Resul
 /// The `BinaryImage` caches parsed DWARF data and symbolication contexts to avoid
 /// re-parsing on every lookup. The caches are lazily initialized on first use.
 ///
+/// Loading happens in two phases. Parsing reads the headers, the linker symbol
+/// table and the unwind sections, which is enough for name-only symbolication
+/// and unwinding. The addr2line line context, which decodes every line program
+/// in the image, is only built on the first lookup that needs a file and line
+/// (see [`has_line_context`](Self::has_line_context)). Most images loaded at
+/// attach never get one.
+///
 /// ## Address Relocation
 ///
 /// The image handles address relocation between file addresses (link-time addresses)
@@ -226,21 +471,35 @@ pub struct BinaryImage
     id: ImageId,
     path: PathBuf,
     architecture: Architecture,
+    macho: bool,
     endian: RunTimeEndian,
     slide: i64,
     runtime_range: (u64, u64),
-    debug_sections: HashMap<&'static str, Arc<[u8]>>,
+    debug_level: DebugLevel,
+    debug_sections: DebugSections,
     eh_frame: Option<SectionBlob>,
     eh_frame_hdr: Option<SectionBlob>,
     debug_frame: Option<SectionBlob>,
+    symtab: SymbolTable,
+    /// DWARF file of the dSYM the debug sections came from
+    dsym: Option<PathBuf>,
+    dsym_mismatch: Option<DsymMismatch>,
     dwarf_cache: OnceCell<OwnedDwarf>,
-    context_cache: OnceCell<Context<OwnedReader>>,
+    /// addr2line fills its caches lazily through `&self` and isn't `Sync`, so
+    /// lookups in one image take turns; other images are unaffected.
+    line_context: OnceCell<Mutex<Context<OwnedReader>>>,
     type_cache: RwLock<HashMap<String, Arc<TypeSummary>>>,
+    /// Layouts of the image's `async` state machines, collected on the first async unwind.
+    state_machines: OnceCell<StateMachines>,
+    /// Line-table rows by address, built on the first instruction-boundary lookup.
+    row_index: OnceCell<RowIndex>,
 }
 
 impl BinaryImage
 {
-    pub(crate) fn parse(desc: ImageDescriptor) -> Result<Self>
+    /// Parse the binary `desc` describes, looking for its dSYM in
+    /// `dsym_search_paths` if it has no DWARF of its own.
+    pub(crate) fn parse(desc: ImageDescriptor, dsym_search_paths: &[PathBuf]) -> Result<Self>
     {
         let bytes = fs::read(&desc.path)?;
         let data = Arc::<[u8]>::from(bytes);
@@ -259,18 +518,8 @@ impl BinaryImage
             _ => Architecture::Unknown("unknown"),
         };
 
-        let text_segment = file
-            .segments()
-            .find(|segment| {
-                if let Ok(Some(name)) = segment.name() {
-                    name == "__TEXT" || name == ".text"
-                } else {
-                    false
-                }
-            })
+        let text_vmaddr = text_segment_address(&file)
             .ok_or_else(|| DebuggerError::InvalidArgument(format!("{} missing __TEXT segment", desc.path.display())))?;
-
-        let text_vmaddr = text_segment.address();
         let mut max_addr = text_vmaddr;
         for segment in file.segments() {
             let start = segment.address();
@@ -283,33 +532,44 @@ impl BinaryImage
         let runtime_end = runtime_start.saturating_add(size);
         let slide = desc.load_address as i64 - text_vmaddr as i64;
 
-        let mut sections = HashMap::new();
-        let mut debug_info_size = 0;
-        let mut debug_line_size = 0;
-        for (canonical, aliases) in DWARF_SECTIONS {
-            let data = load_section_bytes(&file, aliases)?;
-            if *canonical == ".debug_info" {
-                debug_info_size = data.len();
-            }
-            if *canonical == ".debug_line" {
-                debug_line_size = data.len();
-            }
-            if !data.is_empty() {
-                use tracing::debug;
-                debug!("Loaded DWARF section {} ({} bytes)", canonical, data.len());
+        let (mut sections, mut debug_info_size, mut debug_line_size) = load_dwarf_sections(&file)?;
+        let mut symtab = SymbolTable::from_object(&file);
+        let mut debug_frame = load_section_blob(&file, &[".debug_frame", "__debug_frame"])?;
+
+        // The dSYM's sections have the binary's link-time addresses, so the
+        // slide computed above applies to them unchanged
+        let mut dsym = None;
+        let mut dsym_mismatch = None;
+        if debug_info_size == 0
+            && let Some(uuid) = build_id(&file)
+        {
+            let search = find_dsym(&desc.path, &uuid, dsym_search_paths);
+            dsym_mismatch = search.mismatch;
+            if let Some((path, bytes)) = search.found {
+                let dsym_file = object::File::parse(&*bytes)
+                    .map_err(|err| DebuggerError::InvalidArgument(format!("failed to parse {}: {err}", path.display())))?;
+                (sections, debug_info_size, debug_line_size) = load_dwarf_sections(&dsym_file)?;
+                if debug_frame.is_none() {
+                    debug_frame = load_section_blob(&dsym_file, &[".debug_frame", "__debug_frame"])?;
+                }
+                // Stripped binaries keep only exported names; the dSYM has them all
+                if symtab.is_empty() {
+                    symtab = SymbolTable::from_object(&dsym_file);
+                }
+                tracing::info!("Loaded debug info for {} from {}", desc.path.display(), path.display());
+                dsym = Some(path);
             }
-            sections.insert(*canonical, data);
         }
-        
-        if debug_info_size == 0 || debug_line_size == 0 {
-            use tracing::warn;
-            warn!(
-                "Binary {} missing critical DWARF sections: .debug_info={} bytes, .debug_line={} bytes. Build with debug symbols!",
-                desc.path.display(),
-                debug_info_size,
-                debug_line_size
-            );
+
+        let debug_level = if debug_info_size > 0 {
+            DebugLevel::Full
+        } else if symtab.is_empty() {
+            DebugLevel::None
         } else {
+            DebugLevel::SymbolsOnly
+        };
+
+        if debug_level.has_dwarf() && debug_line_size > 0 {
             use tracing::info;
             info!(
                 "Binary {} has DWARF debug information (.debug_info: {} bytes, .debug_line: {} bytes)",
@@ -317,26 +577,46 @@ impl BinaryImage
                 debug_info_size,
                 debug_line_size
             );
+        } else if debug_level.has_dwarf() {
+            use tracing::warn;
+            warn!(
+                "Binary {} has .debug_info ({} bytes) but no .debug_line; source lines are unavailable",
+                desc.path.display(),
+                debug_info_size
+            );
+        } else {
+            // Expected for release builds and system libraries, not an error
+            use tracing::info;
+            info!(
+                "Binary {} has no DWARF ({debug_level}); frames get function names only",
+                desc.path.display()
+            );
         }
 
         let eh_frame = load_section_blob(&file, &[".eh_frame", "__eh_frame"])?;
         let eh_frame_hdr = load_section_blob(&file, &[".eh_frame_hdr", "__eh_frame_hdr"])?;
-        let debug_frame = load_section_blob(&file, &[".debug_frame", "__debug_frame"])?;
 
         Ok(Self {
             id: ImageId::from_parts(&desc.path, desc.load_address),
             path: desc.path,
             architecture,
+            macho: file.format() == object::BinaryFormat::MachO,
             endian,
             slide,
             runtime_range: (runtime_start, runtime_end),
+            debug_level,
             debug_sections: sections,
             eh_frame,
             eh_frame_hdr,
             debug_frame,
+            symtab,
+            dsym,
+            dsym_mismatch,
             dwarf_cache: OnceCell::new(),
-            context_cache: OnceCell::new(),
+            line_context: OnceCell::new(),
             type_cache: RwLock::new(HashMap::new()),
+            state_machines: OnceCell::new(),
+            row_index: OnceCell::new(),
         })
     }
 
@@ -345,6 +625,23 @@ impl BinaryImage
         self.runtime_range
     }
 
+    /// Where the image is mapped and which symbols and unwind info it has.
+    #[must_use]
+    pub fn info(&self) -> ImageInfo
+    {
+        ImageInfo {
+            id: self.id,
+            path: self.path.clone(),
+            load_address: Address::from(self.runtime_range.0),
+            end_address: Address::from(self.runtime_range.1),
+            debug_level: self.debug_level,
+            has_eh_frame: self.eh_frame.is_some(),
+            symbol_count: self.symtab.len(),
+            dsym: self.dsym.clone(),
+            dsym_mismatch: self.dsym_mismatch.clone(),
+        }
+    }
+
     /// Get the pointer size in bytes for this image's architecture.
     ///
     /// Returns 8 for 64-bit architectures (ARM64, x86-64), or 8 for unknown
@@ -356,7 +653,7 @@ impl BinaryImage
     /// use ferros_core::symbols::{BinaryImage, ImageDescriptor, SymbolCache};
     /// use ferros_core::types::Address;
     ///
-    /// let mut cache = SymbolCache::new();
+    /// let cache = SymbolCache::new();
     /// let descriptor = ImageDescriptor {
     ///     path: std::path::PathBuf::from("/usr/bin/prog"),
     ///     load_address: 0x100000000,
@@ -407,7 +704,7 @@ impl BinaryImage
     /// ```rust,no_run
     /// use ferros_core::symbols::{BinaryImage, ImageDescriptor, SymbolCache};
     ///
-    /// let mut cache = SymbolCache::new();
+    /// let cache = SymbolCache::new();
     /// let descriptor = ImageDescriptor {
     ///     path: std::path::PathBuf::from("/usr/bin/prog"),
     ///     load_address: 0x100000000,
@@ -429,7 +726,7 @@ impl BinaryImage
     /// ```rust,no_run
     /// use ferros_core::symbols::{BinaryImage, ImageDescriptor, SymbolCache};
     ///
-    /// let mut cache = SymbolCache::new();
+    /// let cache = SymbolCache::new();
     /// let descriptor = ImageDescriptor {
     ///     path: std::path::PathBuf::from("/usr/bin/prog"),
     ///     load_address: 0x100000000,
@@ -453,7 +750,7 @@ impl BinaryImage
     /// use ferros_core::symbols::{BinaryImage, ImageDescriptor, SymbolCache};
     /// use ferros_core::types::Architecture;
     ///
-    /// let mut cache = SymbolCache::new();
+    /// let cache = SymbolCache::new();
     /// let descriptor = ImageDescriptor {
     ///     path: std::path::PathBuf::from("/usr/bin/prog"),
     ///     load_address: 0x100000000,
@@ -470,6 +767,25 @@ impl BinaryImage
         self.architecture
     }
 
+    /// How much debug information the image carries.
+    ///
+    /// Images without DWARF still symbolicate to function names from the
+    /// linker symbol table, with no source locations.
+    #[must_use]
+    pub fn debug_level(&self) -> DebugLevel
+    {
+        self.debug_level
+    }
+
+    /// DWARF file of the dSYM bundle the image's debug info came from.
+    ///
+    /// `None` if the binary has DWARF of its own or no matching dSYM was found.
+    #[must_use]
+    pub fn dsym_path(&self) -> Option<&Path>
+    {
+        self.dsym.as_deref()
+    }
+
     /// Check if an address is within this image's address range.
     ///
     /// Returns `true` if the address falls within the image's runtime address
@@ -485,7 +801,7 @@ impl BinaryImage
     /// use ferros_core::symbols::{BinaryImage, ImageDescriptor, SymbolCache};
     /// use ferros_core::types::Address;
     ///
-    /// let mut cache = SymbolCache::new();
+    /// let cache = SymbolCache::new();
     /// let descriptor = ImageDescriptor {
     ///     path: std::path::PathBuf::from("/usr/bin/prog"),
     ///     load_address: 0x100000000,
@@ -522,7 +838,7 @@ impl BinaryImage
     /// use ferros_core::symbols::{BinaryImage, ImageDescriptor, SymbolCache};
     /// use ferros_core::types::Address;
     ///
-    /// let mut cache = SymbolCache::new();
+    /// let cache = SymbolCache::new();
     /// let descriptor = ImageDescriptor {
     ///     path: std::path::PathBuf::from("/usr/bin/prog"),
     ///     load_address: 0x100000000,
@@ -587,14 +903,33 @@ impl BinaryImage
         EndianArcSlice::new(data, self.endian)
     }
 
-    fn symbol_context(&self) -> Result<&Context<OwnedReader>>
+    /// Whether the image has DWARF line tables to build a line context from.
+    fn has_line_info(&self) -> bool
+    {
+        self.debug_sections.get(".debug_line").is_some_and(|data| !data.is_empty())
+    }
+
+    /// Whether the addr2line line context has been built.
+    ///
+    /// It's built by the first [`symbolicate`](Self::symbolicate) in an image
+    /// with line tables; [`symbolicate_function`](Self::symbolicate_function)
+    /// never builds it.
+    #[must_use]
+    pub fn has_line_context(&self) -> bool
+    {
+        self.line_context.get().is_some()
+    }
+
+    fn symbol_context(&self) -> Result<MutexGuard<'_, Context<OwnedReader>>>
     {
-        self.context_cache.get_or_try_init(|| {
+        let context = self.line_context.get_or_try_init(|| {
             let dwarf = Dwarf::load(|section| Ok::<_, gimli::Error>(self.section_reader(section)))
                 .map_err(|err| DebuggerError::InvalidArgument(format!("failed to load DWARF for addr2line: {err}")))?;
             Context::from_dwarf(dwarf)
+                .map(Mutex::new)
                 .map_err(|err| DebuggerError::InvalidArgument(format!("failed to build addr2line context: {err}")))
-        })
+        })?;
+        Ok(context.lock().unwrap_or_else(std::sync::PoisonError::into_inner))
     }
 
     /// Symbolicate an address to function names and source locations.
@@ -618,7 +953,7 @@ impl BinaryImage
     /// use ferros_core::symbols::{BinaryImage, ImageDescriptor, SymbolCache};
     /// use ferros_core::types::Address;
     ///
-    /// let mut cache = SymbolCache::new();
+    /// let cache = SymbolCache::new();
     /// let descriptor = ImageDescriptor {
     ///     path: std::path::PathBuf::from("/usr/bin/prog"),
     ///     load_address: 0x100000000,
@@ -644,16 +979,20 @@ impl BinaryImage
                 return None;
             }
         };
-        
+
+        if !self.has_line_info() {
+            return self.symtab_symbolication(file_addr);
+        }
+
         let ctx = match self.symbol_context() {
             Ok(ctx) => ctx,
             Err(e) => {
                 use tracing::debug;
                 debug!("Failed to get symbol context for {}: {}", self.path.display(), e);
-                return None;
+                return self.symtab_symbolication(file_addr);
             }
         };
-        
+
         let mut frames = Vec::new();
 
         let lookup = ctx.find_frames(file_addr);
@@ -661,8 +1000,13 @@ impl BinaryImage
             Ok(iter) => iter,
             Err(e) => {
                 use tracing::debug;
-                debug!("Failed to find frames for 0x{:x} in {}: {:?}", file_addr, self.path.display(), e);
-                return None;
+                debug!(
+                    "Failed to find frames for 0x{:x} in {}: {:?}",
+                    file_addr,
+                    self.path.display(),
+                    e
+                );
+                return self.symtab_symbolication(file_addr);
             }
         };
 
@@ -672,12 +1016,11 @@ impl BinaryImage
                 .as_ref()
                 .and_then(|func| func.raw_name().ok())
                 .map(|raw| make_symbol_name(raw.to_string()));
+            // addr2line has already joined the comp dir and line-table directory
             let location = frame.location.as_ref().and_then(|loc| {
-                loc.file.as_ref().map(|file| SourceLocation {
-                    file: file.to_string(),
-                    line: loc.line,
-                    column: loc.column,
-                })
+                loc.file
+                    .as_ref()
+                    .map(|file| SourceLocation::from_dwarf(file.to_string(), loc.line, loc.column))
             });
 
             if let Some(symbol) = symbol_name {
@@ -697,7 +1040,7 @@ impl BinaryImage
         if frames.is_empty() {
             use tracing::debug;
             debug!("No frames found for address 0x{:x} in {}", file_addr, self.path.display());
-            return None;
+            return self.symtab_symbolication(file_addr);
         }
 
         Some(Symbolication {
@@ -706,14 +1049,68 @@ impl BinaryImage
         })
     }
 
+    /// Symbolicate an address to its function name only.
+    ///
+    /// Uses the line context if it's already built, and the linker symbol
+    /// table otherwise, so frames have no source locations or inlined frames
+    /// until something calls [`symbolicate`](Self::symbolicate).
+    #[must_use]
+    pub fn symbolicate_function(&self, address: Address) -> Option<Symbolication>
+    {
+        if self.has_line_context() {
+            return self.symbolicate(address);
+        }
+        self.symtab_symbolication(self.file_address(address)?)
+    }
+
+    /// Runtime address of the function containing `address`.
+    ///
+    /// Taken from the linker symbol table, so it's the closest symbol at or
+    /// below `address`; `None` if the address isn't in this image or the
+    /// image is fully stripped.
+    #[must_use]
+    pub fn function_start(&self, address: Address) -> Option<Address>
+    {
+        let start = self.symtab.symbol_start(self.file_address(address)?)?;
+        Some(Address::from(self.relocated_address(start)))
+    }
+
+    /// Closest runtime address at or before `address` known to start an instruction.
+    ///
+    /// The start of the line-table row covering `address` when the image has
+    /// DWARF, since every row starts an instruction, or else the entry of the
+    /// enclosing function from the symbol table, whichever is closer. `None`
+    /// if neither is known.
+    #[must_use]
+    pub fn known_instruction_start(&self, address: Address) -> Option<Address>
+    {
+        let file_address = self.file_address(address)?;
+        let row = self.row_index().ok().and_then(|index| index.row_start(file_address));
+        let function = self.symtab.symbol_start(file_address);
+        row.into_iter()
+            .chain(function)
+            .max()
+            .map(|start| Address::from(self.relocated_address(start)))
+    }
+
+    /// Name-only symbolication from the linker symbol table, for code without DWARF.
+    fn symtab_symbolication(&self, file_addr: u64) -> Option<Symbolication>
+    {
+        let name = self.symtab.symbol_for(file_addr)?;
+        Some(Symbolication {
+            image_id: self.id,
+            frames: vec![SymbolFrame {
+                symbol: make_symbol_name(name.to_string()),
+                location: None,
+                parameters: Vec::new(),
+            }],
+        })
+    }
+
     /// Extract function parameters from DWARF for a given address and frame.
     ///
     /// This method walks the DWARF DIEs to find function parameters.
-    fn extract_function_parameters(
-        &self,
-        file_addr: u64,
-        _frame: &addr2line::Frame<OwnedReader>,
-    ) -> Vec<FunctionParameter>
+    fn extract_function_parameters(&self, file_addr: u64, _frame: &addr2line::Frame<OwnedReader>) -> Vec<FunctionParameter>
     {
         // TODO: Implement full DWARF parameter extraction
         // For now, return empty vector as parameter extraction from DWARF
@@ -748,7 +1145,7 @@ impl BinaryImage
     /// ```rust,no_run
     /// use ferros_core::symbols::{BinaryImage, ImageDescriptor, SymbolCache};
     ///
-    /// let mut cache = SymbolCache::new();
+    /// let cache = SymbolCache::new();
     /// let descriptor = ImageDescriptor {
     ///     path: std::path::PathBuf::from("/usr/bin/prog"),
     ///     load_address: 0x100000000,
@@ -781,4 +1178,123 @@ impl BinaryImage
         }
         Ok(Some(summary))
     }
+
+    /// The `async fn` and `async` block state machines described in the image's DWARF.
+    ///
+    /// Collected on first use and kept for the life of the image. An image
+    /// without DWARF has none.
+    pub(crate) fn state_machines(&self) -> Result<&StateMachines>
+    {
+        self.state_machines.get_or_try_init(|| {
+            if !self.debug_level.has_dwarf() {
+                return Ok(StateMachines::default());
+            }
+            StateMachines::build(self.dwarf()?)
+        })
+    }
+
+    /// Every line-table row in the image, sorted for address lookups.
+    ///
+    /// Built on first use and kept for the life of the image. An image
+    /// without DWARF has no rows.
+    fn row_index(&self) -> Result<&RowIndex>
+    {
+        self.row_index.get_or_try_init(|| {
+            if !self.debug_level.has_dwarf() {
+                return Ok(RowIndex::default());
+            }
+            LocationResolver::new(self.dwarf()?).row_index()
+        })
+    }
+
+    /// Runtime addresses of the statements compiled from `file:line`, lowest first.
+    ///
+    /// `file` may be a path suffix such as `src/main.rs`. Returns an empty list if
+    /// the image has no matching line table rows.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if DWARF parsing fails.
+    pub fn resolve_line(&self, file: &str, line: u32) -> Result<Vec<Address>>
+    {
+        if !self.debug_level.has_dwarf() {
+            return Ok(Vec::new());
+        }
+        let addresses = LocationResolver::new(self.dwarf()?).line_addresses(file, line)?;
+        Ok(addresses
+            .into_iter()
+            .map(|addr| Address::from(self.relocated_address(addr)))
+            .collect())
+    }
+
+    /// Lines of `file` that compiled to code in this image, from its line tables.
+    ///
+    /// `file` matches as in [`resolve_line`](Self::resolve_line). Empty
+    /// without DWARF.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if DWARF parsing fails.
+    pub fn code_lines(&self, file: &str) -> Result<BTreeSet<u32>>
+    {
+        if !self.debug_level.has_dwarf() {
+            return Ok(BTreeSet::new());
+        }
+        LocationResolver::new(self.dwarf()?).code_lines(file)
+    }
+
+    /// Runtime entry addresses of functions named `name`, lowest first.
+    ///
+    /// `name` matches either the plain DWARF name (`run`) or a suffix of the
+    /// demangled path (`worker::run`). The linker symbol table is searched
+    /// too, so C functions without debug info (`malloc`, via the image's
+    /// symbol stub if it only calls it) and Objective-C methods
+    /// (`-[NSView drawRect:]`) resolve as well.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if DWARF parsing fails.
+    pub fn resolve_function(&self, name: &str) -> Result<Vec<Address>>
+    {
+        // DWARF subprograms have no Objective-C selector syntax to match against
+        let mut addresses = if ObjcMethodName::parse(name).is_some() {
+            Vec::new()
+        } else {
+            LocationResolver::new(self.dwarf()?).function_addresses(name)?
+        };
+        addresses.extend(self.symtab.addresses_of(name));
+        addresses.sort_unstable();
+        addresses.dedup();
+        Ok(addresses
+            .into_iter()
+            .map(|addr| Address::from(self.relocated_address(addr)))
+            .collect())
+    }
+
+    /// Thread-local variables named `name`, as found in DWARF.
+    ///
+    /// `name` matches the namespace-qualified name or a `::`-separated suffix
+    /// of it (`COUNTER`, `worker::COUNTER`). Mach-O descriptors are relocated
+    /// to runtime addresses.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if DWARF parsing fails.
+    pub fn thread_locals(&self, name: &str) -> Result<Vec<ThreadLocalVariable>>
+    {
+        Ok(TlsResolver::new(self.dwarf()?)
+            .variables(name)?
+            .into_iter()
+            .map(|variable| ThreadLocalVariable {
+                name: variable.name,
+                type_name: variable.type_name,
+                size: variable.size,
+                location: if self.macho {
+                    TlsLocation::Descriptor(Address::from(self.relocated_address(variable.operand)))
+                } else {
+                    TlsLocation::BlockOffset(variable.operand)
+                },
+            })
+            .collect())
+    }
 }
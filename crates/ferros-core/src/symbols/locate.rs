@@ -0,0 +1,374 @@
+//! Reverse symbol lookup: source lines and function names to addresses.
+//!
+//! Symbolication maps an address to a location; setting a breakpoint by
+//! `file:line` or by function name needs the opposite direction. The resolver
+//! walks DWARF line programs and `DW_TAG_subprogram` entries and returns
+//! **file** addresses; [`BinaryImage`](super::BinaryImage) relocates them to
+//! runtime addresses.
+//!
+//! ## Matching Rules
+//!
+//! - Compiled paths are joined onto `DW_AT_comp_dir` and normalized as in
+//!   symbolication ([`super::paths`]). Files match if that path ends with
+//!   the normalized requested path on a component boundary (`./src/main.rs`
+//!   matches `/work/app/src/main.rs`).
+//! - Lines only match rows flagged `is_stmt`, i.e. recommended breakpoint
+//!   locations, and only the first such row per line sequence is kept so a
+//!   line doesn't resolve to every instruction it compiled to.
+//! - Functions match on `DW_AT_name` (`run`) or on the demangled linkage name
+//!   without its hash (`my_crate::worker::run`).
+
+use std::collections::{BTreeSet, HashMap};
+
+use gimli::{AttributeValue, DebuggingInformationEntry, Reader, Unit, constants};
+
+use super::demangle::map_dwarf_error;
+use super::paths::{join_dwarf_path, normalize_path, path_matches};
+use super::{OwnedDwarf, OwnedReader};
+use crate::error::Result;
+
+pub(crate) struct LocationResolver<'a>
+{
+    dwarf: &'a OwnedDwarf,
+}
+
+impl<'a> LocationResolver<'a>
+{
+    pub(crate) fn new(dwarf: &'a OwnedDwarf) -> Self
+    {
+        Self { dwarf }
+    }
+
+    /// File addresses of the statements compiled from `file:line`, lowest first.
+    pub(crate) fn line_addresses(&self, file: &str, line: u32) -> Result<Vec<u64>>
+    {
+        let mut addresses = Vec::new();
+        let mut headers = self.dwarf.units();
+        while let Some(header) = headers
+            .next()
+            .map_err(|err| map_dwarf_error("reading .debug_info unit header", err))?
+        {
+            let unit = self
+                .dwarf
+                .unit(header)
+                .map_err(|err| map_dwarf_error("parsing compilation unit", err))?;
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+            let comp_dir = match &unit.comp_dir {
+                Some(dir) => Some(
+                    dir.to_string_lossy()
+                        .map_err(|err| map_dwarf_error("decoding DW_AT_comp_dir", err))?
+                        .into_owned(),
+                ),
+                None => None,
+            };
+
+            let mut rows = program.rows();
+            let mut matched_in_sequence = false;
+            while let Some((header, row)) = rows
+                .next_row()
+                .map_err(|err| map_dwarf_error("reading line program row", err))?
+            {
+                if row.end_sequence() {
+                    matched_in_sequence = false;
+                    continue;
+                }
+                if matched_in_sequence || !row.is_stmt() || row.line().map(|l| l.get()) != Some(u64::from(line)) {
+                    continue;
+                }
+                let Some(entry) = row.file(header) else {
+                    continue;
+                };
+
+                let directory = match entry.directory(header) {
+                    Some(directory) => Some(self.attr_to_string(&unit, directory)?),
+                    None => None,
+                };
+                let name = self.attr_to_string(&unit, entry.path_name())?;
+                let full = normalize_path(&join_dwarf_path(comp_dir.as_deref(), directory.as_deref(), &name));
+                if path_matches(&full, file) {
+                    addresses.push(row.address());
+                    matched_in_sequence = true;
+                }
+            }
+        }
+
+        addresses.sort_unstable();
+        addresses.dedup();
+        Ok(addresses)
+    }
+
+    /// Lines of `file` with `is_stmt` rows, i.e. where a line breakpoint can land.
+    pub(crate) fn code_lines(&self, file: &str) -> Result<BTreeSet<u32>>
+    {
+        let mut lines = BTreeSet::new();
+        let mut headers = self.dwarf.units();
+        while let Some(header) = headers
+            .next()
+            .map_err(|err| map_dwarf_error("reading .debug_info unit header", err))?
+        {
+            let unit = self
+                .dwarf
+                .unit(header)
+                .map_err(|err| map_dwarf_error("parsing compilation unit", err))?;
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+            let comp_dir = match &unit.comp_dir {
+                Some(dir) => Some(
+                    dir.to_string_lossy()
+                        .map_err(|err| map_dwarf_error("decoding DW_AT_comp_dir", err))?
+                        .into_owned(),
+                ),
+                None => None,
+            };
+
+            // Rows name their file by index; each index is compared once per unit
+            let mut matches: HashMap<u64, bool> = HashMap::new();
+            let mut rows = program.rows();
+            while let Some((header, row)) = rows
+                .next_row()
+                .map_err(|err| map_dwarf_error("reading line program row", err))?
+            {
+                let Some(line) = row.line().and_then(|line| u32::try_from(line.get()).ok()) else {
+                    continue;
+                };
+                if row.end_sequence() || !row.is_stmt() || lines.contains(&line) {
+                    continue;
+                }
+                let matched = match matches.get(&row.file_index()) {
+                    Some(&matched) => matched,
+                    None => {
+                        let matched = match row.file(header) {
+                            Some(entry) => {
+                                let directory = match entry.directory(header) {
+                                    Some(directory) => Some(self.attr_to_string(&unit, directory)?),
+                                    None => None,
+                                };
+                                let name = self.attr_to_string(&unit, entry.path_name())?;
+                                path_matches(
+                                    &normalize_path(&join_dwarf_path(comp_dir.as_deref(), directory.as_deref(), &name)),
+                                    file,
+                                )
+                            }
+                            None => false,
+                        };
+                        matches.insert(row.file_index(), matched);
+                        matched
+                    }
+                };
+                if matched {
+                    lines.insert(line);
+                }
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Address ranges of every line-table row in the image, for [`RowIndex::row_start`].
+    pub(crate) fn row_index(&self) -> Result<RowIndex>
+    {
+        let mut rows = Vec::new();
+        let mut headers = self.dwarf.units();
+        while let Some(header) = headers
+            .next()
+            .map_err(|err| map_dwarf_error("reading .debug_info unit header", err))?
+        {
+            let unit = self
+                .dwarf
+                .unit(header)
+                .map_err(|err| map_dwarf_error("parsing compilation unit", err))?;
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+
+            // A row runs to the next row of its sequence; the end row is just past the last instruction
+            let mut program_rows = program.rows();
+            let mut previous: Option<u64> = None;
+            while let Some((_, row)) = program_rows
+                .next_row()
+                .map_err(|err| map_dwarf_error("reading line program row", err))?
+            {
+                if let Some(start) = previous
+                    && start < row.address()
+                {
+                    rows.push((start, row.address()));
+                }
+                previous = (!row.end_sequence()).then(|| row.address());
+            }
+        }
+
+        rows.sort_unstable();
+        rows.dedup();
+        Ok(RowIndex { rows })
+    }
+
+    /// Entry addresses of functions named `name`, lowest first.
+    pub(crate) fn function_addresses(&self, name: &str) -> Result<Vec<u64>>
+    {
+        let mut addresses = Vec::new();
+        let mut headers = self.dwarf.units();
+        while let Some(header) = headers
+            .next()
+            .map_err(|err| map_dwarf_error("reading .debug_info unit header", err))?
+        {
+            let unit = self
+                .dwarf
+                .unit(header)
+                .map_err(|err| map_dwarf_error("parsing compilation unit", err))?;
+            let mut cursor = unit.entries();
+            while let Some((_delta, entry)) = cursor.next_dfs().map_err(|err| map_dwarf_error("traversing DIE tree", err))? {
+                if entry.tag() != constants::DW_TAG_subprogram {
+                    continue;
+                }
+                let Some(low_pc) = self.low_pc(&unit, entry)? else {
+                    continue;
+                };
+                if self.subprogram_matches(&unit, entry, name)? {
+                    addresses.push(low_pc);
+                }
+            }
+        }
+
+        addresses.sort_unstable();
+        addresses.dedup();
+        Ok(addresses)
+    }
+
+    fn low_pc(&self, unit: &Unit<OwnedReader>, entry: &DebuggingInformationEntry<'_, '_, OwnedReader>)
+    -> Result<Option<u64>>
+    {
+        let value = entry
+            .attr_value(constants::DW_AT_low_pc)
+            .map_err(|err| map_dwarf_error("reading DW_AT_low_pc", err))?;
+        match value {
+            Some(AttributeValue::Addr(address)) => Ok(Some(address)),
+            Some(AttributeValue::DebugAddrIndex(index)) => self
+                .dwarf
+                .address(unit, index)
+                .map(Some)
+                .map_err(|err| map_dwarf_error("resolving DW_AT_low_pc", err)),
+            _ => Ok(None),
+        }
+    }
+
+    fn subprogram_matches(
+        &self,
+        unit: &Unit<OwnedReader>,
+        entry: &DebuggingInformationEntry<'_, '_, OwnedReader>,
+        wanted: &str,
+    ) -> Result<bool>
+    {
+        if let Some(value) = entry
+            .attr_value(constants::DW_AT_name)
+            .map_err(|err| map_dwarf_error("reading DW_AT_name", err))?
+            && self.attr_to_string(unit, value)? == wanted
+        {
+            return Ok(true);
+        }
+        if let Some(value) = entry
+            .attr_value(constants::DW_AT_linkage_name)
+            .map_err(|err| map_dwarf_error("reading DW_AT_linkage_name", err))?
+        {
+            let linkage = self.attr_to_string(unit, value)?;
+            return Ok(linkage_matches(&linkage, wanted));
+        }
+        Ok(false)
+    }
+
+    fn attr_to_string(&self, unit: &Unit<OwnedReader>, value: AttributeValue<OwnedReader>) -> Result<String>
+    {
+        let reader = self
+            .dwarf
+            .attr_string(unit, value)
+            .map_err(|err| map_dwarf_error("resolving DWARF string", err))?;
+        Ok(reader
+            .to_string_lossy()
+            .map_err(|err| map_dwarf_error("decoding DWARF string", err))?
+            .into_owned())
+    }
+}
+
+/// Whether a mangled linkage name refers to `wanted`.
+///
+/// Rust names are compared without the trailing `::h<hash>`, either in full or
+/// by a `::`-separated suffix, so `worker::run` matches `my_crate::worker::run`.
+pub(crate) fn linkage_matches(linkage: &str, wanted: &str) -> bool
+{
+    if linkage == wanted {
+        return true;
+    }
+    let Ok(demangled) = rustc_demangle::try_demangle(linkage) else {
+        return false;
+    };
+    let demangled = format!("{demangled:#}");
+    demangled == wanted || demangled.strip_suffix(wanted).is_some_and(|prefix| prefix.ends_with("::"))
+}
+
+/// Line-table rows of one image sorted by start address, built once and
+/// binary-searched so an address lookup doesn't walk every line program.
+#[derive(Debug, Default)]
+pub(crate) struct RowIndex
+{
+    /// `(start, end)` file addresses of each row's instructions
+    rows: Vec<(u64, u64)>,
+}
+
+impl RowIndex
+{
+    /// File address of the line-table row covering `address`, if a sequence does.
+    ///
+    /// Every row starts an instruction, so this is an instruction boundary at
+    /// or before `address`.
+    pub(crate) fn row_start(&self, address: u64) -> Option<u64>
+    {
+        let index = self.rows.partition_point(|&(start, _)| start <= address);
+        let (start, end) = self.rows[..index].last().copied()?;
+        (address < end).then_some(start)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn path_suffix_matches_on_component_boundary()
+    {
+        let compiled = "/work/app/src/main.rs";
+        assert!(path_matches(compiled, "src/main.rs"));
+        assert!(path_matches(compiled, "main.rs"));
+        assert!(path_matches(compiled, "/work/app/src/main.rs"));
+        assert!(!path_matches(compiled, "in.rs"));
+        assert!(!path_matches(compiled, "lib.rs"));
+    }
+
+    #[test]
+    fn linkage_name_matches_demangled_path_suffix()
+    {
+        let mangled = "_ZN6my_app6worker3run17h0123456789abcdefE";
+        assert!(linkage_matches(mangled, "my_app::worker::run"));
+        assert!(linkage_matches(mangled, "worker::run"));
+        assert!(linkage_matches(mangled, "run"));
+        assert!(!linkage_matches(mangled, "un"));
+        assert!(!linkage_matches("main", "other"));
+        assert!(linkage_matches("main", "main"));
+    }
+
+    #[test]
+    fn row_start_finds_the_row_covering_an_address()
+    {
+        // Two sequences with a gap between them
+        let index = RowIndex {
+            rows: vec![(0x1000, 0x1004), (0x1004, 0x100a), (0x2000, 0x2008)],
+        };
+        assert_eq!(index.row_start(0x1000), Some(0x1000));
+        assert_eq!(index.row_start(0x1007), Some(0x1004));
+        assert_eq!(index.row_start(0x2007), Some(0x2000));
+        assert_eq!(index.row_start(0x0fff), None);
+        assert_eq!(index.row_start(0x100a), None);
+        assert_eq!(index.row_start(0x2008), None);
+    }
+}
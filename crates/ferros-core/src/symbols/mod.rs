@@ -6,10 +6,16 @@
 //!
 //! ## Module Structure
 //!
+//! - **`async_stack`**: Chains of suspended futures, found through DWARF state machine layouts
 //! - **`cache`**: Symbol cache for binary images and address symbolication
 //! - **`demangle`**: Symbol demangling utilities (Rust, C++)
 //! - **`extractor`**: DWARF type extraction and introspection
 //! - **`image`**: Binary image parsing and DWARF section loading
+//! - **`locate`**: Reverse lookup of source lines and function names to addresses
+//! - **`paths`**: Normalization and remapping of DWARF source paths
+//! - **`render`**: Rendering target memory as Rust values using the extracted types
+//! - **`symtab`**: Linker symbol tables for C and Objective-C code without DWARF
+//! - **`tls`**: Thread-local variables and each thread's copy of them
 //!
 //! ## DWARF Sections
 //!
@@ -29,10 +35,16 @@
 
 use gimli::{Dwarf, EndianArcSlice, RunTimeEndian};
 
+mod async_stack;
 pub mod cache;
 pub mod demangle;
 pub mod extractor;
 pub mod image;
+mod locate;
+pub mod paths;
+pub mod render;
+mod symtab;
+pub mod tls;
 pub mod unwind;
 
 // Shared type aliases
@@ -40,6 +52,13 @@ pub(crate) type OwnedReader = EndianArcSlice<RunTimeEndian>;
 pub(crate) type OwnedDwarf = Dwarf<OwnedReader>;
 
 // Re-exports
-pub use cache::{SymbolCache, SymbolFrame, Symbolication};
+pub use cache::{
+    DEFAULT_LOAD_THREADS, ImageChanges, SymbolCache, SymbolCacheStats, SymbolFrame, Symbolication, ambiguous_functions,
+};
 pub use extractor::{TypeField, TypeKind, TypeSummary, TypeVariant};
-pub use image::{BinaryImage, ImageDescriptor, ImageId};
+pub use image::{BinaryImage, DebugLevel, DsymMismatch, ImageDescriptor, ImageId, ImageInfo, image_uuid};
+pub use tls::{ThreadLocalValue, ThreadLocalVariable, TlsLocation};
+pub use unwind::{
+    RecursionCycle, StackTrace, UnwindAttempt, UnwindDiagnostic, UnwindFailure, UnwindOptions, UnwindStrategy,
+    UnwindTableCache,
+};
@@ -0,0 +1,467 @@
+//! Source path normalization.
+//!
+//! DWARF spells the same file in many ways: absolute build-server paths, names
+//! relative to a line-table directory or `DW_AT_comp_dir`, `./src/...` forms,
+//! and now and then `\`-separated paths from vendored Windows code. Comparing
+//! those strings directly puts one file under several keys. Symbolication
+//! therefore normalizes every path once, and everything downstream (breakpoint
+//! matching, the source cache) keys on the result.
+//!
+//! ## Steps
+//!
+//! 1. [`join_dwarf_path`]: a relative file name is joined onto its directory,
+//!    then onto the compilation directory.
+//! 2. [`normalize_path`]: `\` becomes `/`, a leading `~/` expands to `$HOME`,
+//!    and empty, `.` and `..` segments collapse. This is purely lexical: the
+//!    build machine's paths usually don't exist here.
+//! 3. [`SourceMap::to_local`]: a build-server prefix is replaced by the place
+//!    the sources live on this machine (`--source-map /build/app=~/src/app`).
+//!
+//! [`SourceLocation`](crate::types::SourceLocation) keeps both the path as
+//! DWARF recorded it and the normalized local one. When the local one doesn't
+//! exist either, [`SourceResolver`] looks for the file under the workspace
+//! and the user's source directories before a view gives up on it.
+//!
+//! ```rust
+//! use ferros_core::symbols::paths::{SourceMap, join_dwarf_path, normalize_path};
+//!
+//! let dwarf = join_dwarf_path(Some("/build/app"), None, "./src/../src/main.rs");
+//! assert_eq!(normalize_path(&dwarf), "/build/app/src/main.rs");
+//!
+//! let map = SourceMap::new([("/build/app".to_string(), "/home/me/app".to_string())]);
+//! assert_eq!(
+//!     map.to_local("/build/app/src/main.rs").as_deref(),
+//!     Some("/home/me/app/src/main.rs")
+//! );
+//! ```
+
+use std::path::PathBuf;
+
+/// Whether `path` is absolute in Unix (`/x`) or Windows (`\x`, `C:\x`, `C:/x`) form.
+#[must_use]
+pub fn is_absolute(path: &str) -> bool
+{
+    path.starts_with(['/', '\\']) || drive_prefix(path).is_some()
+}
+
+/// Join a DWARF file name onto its line-table directory and compilation directory.
+///
+/// Each part is only prepended while the path built so far is still relative.
+/// The result is not normalized, so it is what DWARF describes.
+#[must_use]
+pub fn join_dwarf_path(comp_dir: Option<&str>, directory: Option<&str>, name: &str) -> String
+{
+    let mut path = name.to_string();
+    for base in [directory, comp_dir].into_iter().flatten() {
+        if is_absolute(&path) {
+            break;
+        }
+        if !base.is_empty() {
+            path = format!("{}/{path}", base.trim_end_matches(['/', '\\']));
+        }
+    }
+    path
+}
+
+/// Canonical form of `path`, computed without touching the filesystem.
+///
+/// Separators become `/`, a leading `~` expands to `$HOME`, drive letters are
+/// upper-cased, and empty and `.` segments are dropped. A `..` removes the
+/// segment before it; one that would go above the root is dropped, while a
+/// relative path keeps its leading `..` segments.
+#[must_use]
+pub fn normalize_path(path: &str) -> String
+{
+    let home = std::env::var("HOME").ok();
+    normalize_with_home(path, home.as_deref())
+}
+
+fn normalize_with_home(path: &str, home: Option<&str>) -> String
+{
+    let path = path.replace('\\', "/");
+    let path = match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => format!("{home}{rest}"),
+        _ => path,
+    };
+
+    let (root, rest) = if let Some(drive) = drive_prefix(&path) {
+        (format!("{}:/", drive.to_ascii_uppercase()), &path[2..])
+    } else if let Some(rest) = path.strip_prefix('/') {
+        ("/".to_string(), rest)
+    } else {
+        (String::new(), path.as_str())
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in rest.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => match segments.last() {
+                Some(&last) if last != ".." => {
+                    segments.pop();
+                }
+                // Above the root there is nothing to go up to
+                _ if !root.is_empty() => {}
+                _ => segments.push(".."),
+            },
+            segment => segments.push(segment),
+        }
+    }
+
+    let joined = segments.join("/");
+    if root.is_empty() && joined.is_empty() {
+        ".".to_string()
+    } else {
+        root + &joined
+    }
+}
+
+/// Drive letter of a Windows path such as `C:\src` or `c:/src`.
+fn drive_prefix(path: &str) -> Option<char>
+{
+    let mut chars = path.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+    (chars.next() == Some(':') && matches!(chars.next(), Some('/' | '\\'))).then_some(drive)
+}
+
+/// Whether normalized `path` is `prefix` or lies below it.
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str>
+{
+    let prefix = prefix.trim_end_matches('/');
+    let rest = path.strip_prefix(prefix)?;
+    (rest.is_empty() || rest.starts_with('/') || prefix.is_empty()).then_some(rest)
+}
+
+/// Prefix rewrites from build-machine paths to local source trees.
+///
+/// Both sides are normalized when added. The longest matching prefix wins and
+/// only whole path components match, so `/build/app` doesn't touch
+/// `/build/application`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap
+{
+    remaps: Vec<(String, String)>,
+}
+
+impl SourceMap
+{
+    /// Map built from `(dwarf prefix, local prefix)` pairs.
+    #[must_use]
+    pub fn new(remaps: impl IntoIterator<Item = (String, String)>) -> Self
+    {
+        let mut map = Self::default();
+        for (from, to) in remaps {
+            map.add(&from, &to);
+        }
+        map
+    }
+
+    /// Add a remap from a DWARF prefix to a local prefix.
+    pub fn add(&mut self, from: &str, to: &str)
+    {
+        self.remaps.push((normalize_path(from), normalize_path(to)));
+        self.remaps.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+    }
+
+    /// Parse a `FROM=TO` remap as given to `--source-map`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a message if there is no `=` or either side is empty.
+    pub fn parse_remap(spec: &str) -> Result<(String, String), String>
+    {
+        match spec.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok((from.to_string(), to.to_string())),
+            _ => Err(format!("invalid source map '{spec}' (expected FROM=TO)")),
+        }
+    }
+
+    /// Whether no remaps are configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.remaps.is_empty()
+    }
+
+    /// The normalized `(dwarf prefix, local prefix)` pairs, longest DWARF prefix first.
+    #[must_use]
+    pub fn remaps(&self) -> &[(String, String)]
+    {
+        &self.remaps
+    }
+
+    /// Local path for a normalized DWARF path, if a remap applies.
+    #[must_use]
+    pub fn to_local(&self, path: &str) -> Option<String>
+    {
+        self.remaps
+            .iter()
+            .find_map(|(from, to)| strip_path_prefix(path, from).map(|rest| format!("{}{rest}", to.trim_end_matches('/'))))
+    }
+
+    /// DWARF paths that remap to a normalized local path, e.g. to resolve a
+    /// breakpoint the user wrote against their checkout.
+    #[must_use]
+    pub fn to_dwarf(&self, path: &str) -> Vec<String>
+    {
+        self.remaps
+            .iter()
+            .filter_map(|(from, to)| strip_path_prefix(path, to).map(|rest| format!("{}{rest}", from.trim_end_matches('/'))))
+            .collect()
+    }
+}
+
+/// Finds symbolicated source files on this machine.
+///
+/// A [`SourceLocation`](crate::types::SourceLocation) file has been through
+/// the [`SourceMap`] already, but a build machine's path often still doesn't
+/// exist here. The candidates, most likely first:
+///
+/// 1. the path itself, a relative one under the workspace root;
+/// 2. the part after the root's directory name, under the root, so
+///    `/ci/work/ferros/src/x.rs` is found in a checkout named `ferros`;
+/// 3. ever shorter trailing parts of the path down to the file name, in each
+///    search directory and then the root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceResolver
+{
+    root: Option<PathBuf>,
+    search_dirs: Vec<PathBuf>,
+}
+
+impl SourceResolver
+{
+    /// Resolver for the workspace at `root`, also searching `search_dirs`.
+    #[must_use]
+    pub fn new(root: Option<PathBuf>, search_dirs: Vec<PathBuf>) -> Self
+    {
+        Self { root, search_dirs }
+    }
+
+    /// Resolver rooted at the current directory.
+    #[must_use]
+    pub fn in_current_dir(search_dirs: Vec<PathBuf>) -> Self
+    {
+        Self::new(std::env::current_dir().ok(), search_dirs)
+    }
+
+    /// Where `file` is looked for, in order and without repeats.
+    #[must_use]
+    pub fn candidates(&self, file: &str) -> Vec<PathBuf>
+    {
+        let absolute = is_absolute(file);
+        let mut candidates = vec![match &self.root {
+            Some(root) if !absolute => root.join(file),
+            _ => PathBuf::from(file),
+        }];
+
+        let skip = usize::from(drive_prefix(file).is_some());
+        let components: Vec<&str> = file.split('/').filter(|c| !c.is_empty()).skip(skip).collect();
+        if absolute
+            && let Some(root) = &self.root
+            && let Some(name) = root.file_name().and_then(|name| name.to_str())
+            && let Some(at) = components.iter().position(|component| *component == name)
+        {
+            candidates.push(root.join(components[at + 1..].join("/")));
+        }
+        for dir in self.search_dirs.iter().chain(&self.root) {
+            for start in 0..components.len() {
+                candidates.push(dir.join(components[start..].join("/")));
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        candidates.retain(|candidate| seen.insert(candidate.clone()));
+        candidates
+    }
+
+    /// The first of [`candidates`](Self::candidates) that is a file.
+    ///
+    /// ## Errors
+    ///
+    /// Returns every candidate tried if none exists.
+    pub fn resolve(&self, file: &str) -> Result<PathBuf, Vec<PathBuf>>
+    {
+        let candidates = self.candidates(file);
+        candidates
+            .iter()
+            .find(|candidate| candidate.is_file())
+            .cloned()
+            .ok_or(candidates)
+    }
+}
+
+/// Whether normalized `compiled` names the file `wanted`, on a component boundary.
+///
+/// `wanted` is normalized first, so `./src/main.rs` and `src\main.rs` both
+/// match `/work/app/src/main.rs`. An absolute `wanted` must match in full.
+pub(crate) fn path_matches(compiled: &str, wanted: &str) -> bool
+{
+    let wanted = normalize_path(wanted);
+    if compiled == wanted {
+        return true;
+    }
+    !is_absolute(&wanted)
+        && !wanted.starts_with("..")
+        && compiled
+            .strip_suffix(wanted.as_str())
+            .is_some_and(|prefix| prefix.ends_with('/'))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// `(comp_dir, directory, name)` as found in a line table
+    type Spelling<'a> = (Option<&'a str>, Option<&'a str>, &'a str);
+
+    fn canonical(comp_dir: Option<&str>, directory: Option<&str>, name: &str) -> String
+    {
+        normalize_with_home(&join_dwarf_path(comp_dir, directory, name), Some("/home/dev"))
+    }
+
+    #[test]
+    fn spellings_of_one_file_share_a_canonical_key()
+    {
+        let matrix: &[&[Spelling<'_>]] = &[
+            &[
+                (Some("/work/app"), None, "src/main.rs"),
+                (Some("/work/app"), None, "./src/main.rs"),
+                (Some("/work/app/"), Some("src"), "main.rs"),
+                (Some("/work/app/target/.."), None, "src/main.rs"),
+                (Some("/work/app/src"), None, "../src/./main.rs"),
+                (Some("/elsewhere"), Some("/work/app"), "src//main.rs"),
+                (None, None, "/work/app/src/main.rs"),
+                (Some("/ignored"), Some("/ignored/too"), "/work/app/src/main.rs"),
+                (Some("~/../../work/app"), None, "src/main.rs"),
+            ],
+            &[
+                (Some("C:\\work\\app"), None, "src\\main.rs"),
+                (Some("c:/work/app"), None, "src/main.rs"),
+                (Some("C:\\work\\app\\vendor"), None, "..\\src\\main.rs"),
+                (Some("D:\\other"), Some("C:\\work\\app"), ".\\src\\main.rs"),
+                (None, None, "C:/work/app/src/./main.rs"),
+            ],
+            &[
+                (Some("~"), Some("proj"), "lib.rs"),
+                (None, None, "/home/dev/proj/lib.rs"),
+                (Some("/home/dev/proj/src"), None, "../lib.rs"),
+            ],
+        ];
+        let expected = ["/work/app/src/main.rs", "C:/work/app/src/main.rs", "/home/dev/proj/lib.rs"];
+
+        for (spellings, expected) in matrix.iter().zip(expected) {
+            let keys: HashSet<String> = spellings
+                .iter()
+                .map(|&(comp_dir, directory, name)| canonical(comp_dir, directory, name))
+                .collect();
+            assert_eq!(keys, HashSet::from([expected.to_string()]), "{spellings:?}");
+        }
+    }
+
+    #[test]
+    fn relative_paths_stay_relative()
+    {
+        assert_eq!(normalize_with_home("./src/main.rs", None), "src/main.rs");
+        assert_eq!(normalize_with_home("src/../../lib.rs", None), "../lib.rs");
+        assert_eq!(normalize_with_home("./", None), ".");
+        assert_eq!(normalize_with_home("/../..", None), "/");
+        assert_eq!(normalize_with_home("~/x.rs", None), "~/x.rs");
+        assert_eq!(normalize_with_home("~user/x.rs", Some("/home/dev")), "~user/x.rs");
+    }
+
+    #[test]
+    fn source_map_rewrites_longest_prefix_on_component_boundary()
+    {
+        let map = SourceMap::new([
+            ("/build".to_string(), "/mnt/build".to_string()),
+            ("/build/app/".to_string(), "/home/me/app".to_string()),
+            ("C:\\ci\\vendor".to_string(), "/home/me/vendor".to_string()),
+        ]);
+        assert_eq!(
+            map.to_local("/build/app/src/main.rs").as_deref(),
+            Some("/home/me/app/src/main.rs")
+        );
+        assert_eq!(
+            map.to_local("/build/application/x.rs").as_deref(),
+            Some("/mnt/build/application/x.rs")
+        );
+        assert_eq!(
+            map.to_local("C:/ci/vendor/zlib/inflate.c").as_deref(),
+            Some("/home/me/vendor/zlib/inflate.c")
+        );
+        assert_eq!(map.to_local("/usr/src/x.rs"), None);
+        assert_eq!(map.to_dwarf("/home/me/app/src/main.rs"), vec!["/build/app/src/main.rs"]);
+
+        assert_eq!(SourceMap::parse_remap("/a=/b"), Ok(("/a".to_string(), "/b".to_string())));
+        assert!(SourceMap::parse_remap("/a").is_err());
+        assert!(SourceMap::parse_remap("=/b").is_err());
+    }
+
+    #[test]
+    fn sources_are_found_by_trailing_path_in_search_dirs_then_the_workspace()
+    {
+        let resolver = SourceResolver::new(Some(PathBuf::from("/home/me/ferros")), vec![PathBuf::from("/src")]);
+        let tried = |file: &str| -> Vec<String> {
+            resolver
+                .candidates(file)
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect()
+        };
+        assert_eq!(
+            tried("/ci/ferros/crates/core/lib.rs"),
+            [
+                "/ci/ferros/crates/core/lib.rs",
+                "/home/me/ferros/crates/core/lib.rs",
+                "/src/ci/ferros/crates/core/lib.rs",
+                "/src/ferros/crates/core/lib.rs",
+                "/src/crates/core/lib.rs",
+                "/src/core/lib.rs",
+                "/src/lib.rs",
+                "/home/me/ferros/ci/ferros/crates/core/lib.rs",
+                "/home/me/ferros/ferros/crates/core/lib.rs",
+                "/home/me/ferros/core/lib.rs",
+                "/home/me/ferros/lib.rs",
+            ]
+        );
+        assert_eq!(
+            tried("src/main.rs"),
+            [
+                "/home/me/ferros/src/main.rs",
+                "/src/src/main.rs",
+                "/src/main.rs",
+                "/home/me/ferros/main.rs"
+            ]
+        );
+        assert_eq!(
+            tried("C:/ci/x.rs")[1..],
+            ["/src/ci/x.rs", "/src/x.rs", "/home/me/ferros/ci/x.rs", "/home/me/ferros/x.rs"]
+        );
+
+        let root = std::env::temp_dir().join(format!("ferros-sources-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("core")).unwrap();
+        std::fs::write(root.join("core/lib.rs"), "").unwrap();
+        let resolver = SourceResolver::new(None, vec![root.clone()]);
+        assert_eq!(resolver.resolve("/runner/work/core/lib.rs"), Ok(root.join("core/lib.rs")));
+        assert_eq!(
+            resolver.resolve("/runner/work/core/main.rs").map_err(|tried| tried.len()),
+            Err(5)
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn requested_files_are_normalized_before_matching()
+    {
+        let compiled = "/work/app/src/main.rs";
+        assert!(path_matches(compiled, "./src/main.rs"));
+        assert!(path_matches(compiled, "src\\main.rs"));
+        assert!(path_matches(compiled, "/work/app/src/../src/main.rs"));
+        assert!(!path_matches(compiled, "/app/src/main.rs"));
+        assert!(!path_matches(compiled, "../src/main.rs"));
+    }
+}
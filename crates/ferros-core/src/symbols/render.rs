@@ -0,0 +1,795 @@
+//! Rendering target memory as Rust values.
+//!
+//! [`render_value`] reads a value of a named type and formats it the way
+//! Rust's `Debug` would, using the layouts [`TargetInspector::describe_type`]
+//! extracts from DWARF:
+//!
+//! - Integers, floats, `bool` and `char`
+//! - `&str` and `String` as quoted text, `&[T]` and `Vec<T>` as their first
+//!   elements when `T` is a primitive
+//! - `Option<T>` and other enums, by their discriminant or niche
+//! - Structs and tuples field by field
+//!
+//! Pointers inside a `String` or `Vec` are followed; other pointers are shown
+//! as addresses. A [`RenderBudget`] caps the nesting depth, the bytes read
+//! and how much of a string or sequence is shown, so a corrupt length can't
+//! read the whole address space. A value whose layout can't be interpreted is
+//! shown as its raw bytes, or as its type and address if even its size is
+//! unknown.
+//!
+//! ```rust
+//! use ferros_core::symbols::render::{format_primitive, generic_args};
+//!
+//! assert_eq!(format_primitive("i16", &[0xfe, 0xff]).as_deref(), Some("-2"));
+//! assert_eq!(generic_args("core::option::Option<u64>"), ["u64"]);
+//! ```
+
+use std::fmt::Write;
+
+use crate::inspector::TargetInspector;
+use crate::symbols::{TypeField, TypeKind, TypeSummary};
+use crate::types::Address;
+
+/// Deepest nesting [`leaf_fields`](Renderer::leaf_fields) follows into a container's fields.
+const MAX_LAYOUT_DEPTH: usize = 8;
+
+/// Most bytes of a value shown when it falls back to hex.
+const MAX_HEX_BYTES: usize = 32;
+
+/// Limits on how much of a value [`render_value`] reads and shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderBudget
+{
+    /// Levels of structs and enums shown; deeper ones are elided as `{ … }`.
+    pub max_depth: usize,
+    /// Bytes of target memory read for the whole value.
+    pub max_bytes: usize,
+    /// Bytes of a string shown.
+    pub max_text: usize,
+    /// Elements of a slice or `Vec` shown.
+    pub max_elements: usize,
+}
+
+impl Default for RenderBudget
+{
+    fn default() -> Self
+    {
+        Self {
+            max_depth: 2,
+            max_bytes: 4096,
+            max_text: 256,
+            max_elements: 16,
+        }
+    }
+}
+
+/// Render the value of type `type_name` at `address`, within `budget`.
+///
+/// `type_name` is the name DWARF gives the type, such as `u32`, `&str`,
+/// `alloc::string::String` or `core::option::Option<u64>`. Never fails:
+/// unreadable memory and unknown layouts are described in the result.
+pub fn render_value<T: TargetInspector + ?Sized>(
+    target: &T,
+    type_name: &str,
+    address: Address,
+    budget: RenderBudget,
+) -> String
+{
+    let mut renderer = Renderer {
+        target,
+        budget,
+        bytes_left: budget.max_bytes,
+    };
+    renderer.render(type_name, address, 0)
+}
+
+/// Size in bytes of a primitive type, `None` if `ty` isn't one.
+#[must_use]
+pub fn primitive_size(ty: &str) -> Option<usize>
+{
+    Some(match ty {
+        "()" => 0,
+        "bool" | "u8" | "i8" => 1,
+        "u16" | "i16" => 2,
+        "u32" | "i32" | "f32" | "char" => 4,
+        "u64" | "i64" | "f64" | "usize" | "isize" => 8,
+        "u128" | "i128" => 16,
+        _ => return None,
+    })
+}
+
+/// Format the little-endian `bytes` of primitive type `ty`.
+///
+/// Returns `None` if `ty` isn't a primitive or `bytes` is too short.
+#[must_use]
+pub fn format_primitive(ty: &str, bytes: &[u8]) -> Option<String>
+{
+    let bytes = bytes.get(..primitive_size(ty)?)?;
+    let mut wide = [0_u8; 16];
+    wide[..bytes.len()].copy_from_slice(bytes);
+    let unsigned = u128::from_le_bytes(wide);
+    // Sign-extend from the type's width
+    let shift = 128 - 8 * bytes.len() as u32;
+    let signed = (i128::from_le_bytes(wide) << (shift % 128)) >> (shift % 128);
+    Some(match ty {
+        "()" => "()".to_string(),
+        "bool" => match bytes[0] {
+            0 => "false".to_string(),
+            1 => "true".to_string(),
+            other => format!("<invalid bool {other:#x}>"),
+        },
+        "char" => {
+            let code = u32::from_le_bytes(bytes.try_into().ok()?);
+            char::from_u32(code).map_or_else(|| format!("<invalid char {code:#x}>"), |c| format!("{c:?}"))
+        }
+        "f32" => f32::from_le_bytes(bytes.try_into().ok()?).to_string(),
+        "f64" => f64::from_le_bytes(bytes.try_into().ok()?).to_string(),
+        _ if ty.starts_with('i') => signed.to_string(),
+        _ => unsigned.to_string(),
+    })
+}
+
+/// Generic arguments of `ty`: `["u8", "alloc::alloc::Global"]` for `Vec<u8, alloc::alloc::Global>`.
+#[must_use]
+pub fn generic_args(ty: &str) -> Vec<&str>
+{
+    let Some(open) = ty.find('<') else {
+        return Vec::new();
+    };
+    let Some(inner) = ty[open + 1..].strip_suffix('>') else {
+        return Vec::new();
+    };
+    let mut args = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (index, c) in inner.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(inner[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(inner[start..].trim());
+    args
+}
+
+/// A field inside a nested layout, at its offset from the outer value
+struct Leaf
+{
+    /// Field names from the outer value down, joined with `.`
+    path: String,
+    ty: String,
+    offset: u64,
+}
+
+/// Pointer, length and capacity of a `Vec`'s buffer
+struct Buffer
+{
+    ptr: Address,
+    len: u64,
+    cap: Option<u64>,
+}
+
+struct Renderer<'a, T: ?Sized>
+{
+    target: &'a T,
+    budget: RenderBudget,
+    bytes_left: usize,
+}
+
+impl<T: TargetInspector + ?Sized> Renderer<'_, T>
+{
+    /// Read `len` bytes, or the text to show instead when they can't be read
+    fn read(&mut self, address: Address, len: usize) -> Result<Vec<u8>, String>
+    {
+        if len > self.bytes_left {
+            return Err("…".to_string());
+        }
+        self.bytes_left -= len;
+        match self.target.read_memory(address, len) {
+            Ok(bytes) if bytes.len() == len => Ok(bytes),
+            _ => Err(format!("<unreadable at {address}>")),
+        }
+    }
+
+    fn read_u64(&mut self, address: Address) -> Result<u64, String>
+    {
+        let bytes = self.read(address, 8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+    }
+
+    fn render(&mut self, ty: &str, address: Address, depth: usize) -> String
+    {
+        if let Some(size) = primitive_size(ty) {
+            return match self.read(address, size) {
+                Ok(bytes) => format_primitive(ty, &bytes).unwrap_or_default(),
+                Err(shown) => shown,
+            };
+        }
+        if matches!(ty, "&str" | "&mut str") {
+            return self.render_slice("u8", address, true);
+        }
+        if let Some(element) = ty
+            .strip_prefix("&[")
+            .or_else(|| ty.strip_prefix("&mut ["))
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            return self.render_slice(element, address, false);
+        }
+        if ty.starts_with('&') || ty.starts_with("*const ") || ty.starts_with("*mut ") {
+            return match self.read_u64(address) {
+                Ok(pointer) => format!("{pointer:#x}"),
+                Err(shown) => shown,
+            };
+        }
+
+        let Some(summary) = self.target.describe_type(ty) else {
+            return format!("<{ty} at {address}>");
+        };
+        // One message for a value that isn't mapped, rather than one per field
+        if let Err(shown) = self.read(address, 1) {
+            return shown;
+        }
+        if is_string(&summary.name) || is_vec(&summary.name) {
+            return self.render_vec(&summary, address);
+        }
+        match summary.kind {
+            TypeKind::Enum => self.render_enum(&summary, address, depth),
+            TypeKind::Struct => self.render_struct(&summary, address, depth),
+            TypeKind::Union | TypeKind::TraitObject | TypeKind::Unknown => self.render_hex(&summary, address),
+        }
+    }
+
+    /// `&str` or `&[T]`: a data pointer and a length
+    fn render_slice(&mut self, element: &str, address: Address, text: bool) -> String
+    {
+        let (ptr, len) = match self.read(address, 16) {
+            Ok(bytes) => (
+                u64::from_le_bytes(bytes[..8].try_into().unwrap_or_default()),
+                u64::from_le_bytes(bytes[8..].try_into().unwrap_or_default()),
+            ),
+            Err(shown) => return shown,
+        };
+        let buffer = Buffer {
+            ptr: Address::from(ptr),
+            len,
+            cap: None,
+        };
+        if text {
+            self.render_text(&buffer)
+        } else {
+            self.render_elements(element, &buffer)
+        }
+    }
+
+    /// `String` and `Vec<T>`, whose buffer is a few structs down
+    fn render_vec(&mut self, summary: &TypeSummary, address: Address) -> String
+    {
+        let leaves = self.leaf_fields(summary, 0);
+        let field = |wanted: &dyn Fn(&Leaf) -> bool| leaves.iter().find(|leaf| wanted(leaf));
+        let (Some(ptr), Some(len)) = (
+            field(&|leaf| leaf.ty.starts_with("*const ") || leaf.ty.starts_with("*mut ")),
+            field(&|leaf| leaf.path.rsplit('.').next() == Some("len")),
+        ) else {
+            return self.render_hex(summary, address);
+        };
+        let cap = field(&|leaf| leaf.path.split('.').any(|name| name == "cap")).map(|leaf| leaf.offset);
+        let (ptr_offset, len_offset) = (ptr.offset, len.offset);
+        let read = |renderer: &mut Self, offset| renderer.read_u64(address + offset);
+        let buffer = match (read(self, ptr_offset), read(self, len_offset)) {
+            (Ok(ptr), Ok(len)) => Buffer {
+                ptr: Address::from(ptr),
+                len,
+                cap: cap.and_then(|offset| read(self, offset).ok()),
+            },
+            (Err(shown), _) | (_, Err(shown)) => return shown,
+        };
+        if is_string(&summary.name) {
+            return self.render_text(&buffer);
+        }
+        let element = generic_args(&summary.name).first().copied().unwrap_or_default().to_string();
+        self.render_elements(&element, &buffer)
+    }
+
+    /// Quoted UTF-8 text, cut at [`RenderBudget::max_text`]
+    fn render_text(&mut self, buffer: &Buffer) -> String
+    {
+        let shown = usize::try_from(buffer.len).map_or(self.budget.max_text, |len| len.min(self.budget.max_text));
+        let bytes = match self.read(buffer.ptr, shown) {
+            Ok(bytes) => bytes,
+            Err(shown) => return shown,
+        };
+        let mut text = format!("\"{}\"", String::from_utf8_lossy(&bytes).escape_debug());
+        if (shown as u64) < buffer.len {
+            text.insert(text.len() - 1, '…');
+            let _ = write!(text, " ({} bytes)", buffer.len);
+        }
+        text
+    }
+
+    /// `[a, b, …]`, with the elements only when they're primitives
+    fn render_elements(&mut self, element: &str, buffer: &Buffer) -> String
+    {
+        let counts = match buffer.cap {
+            Some(cap) => format!(" (len {}, cap {cap})", buffer.len),
+            None => format!(" (len {})", buffer.len),
+        };
+        if buffer.len == 0 {
+            return format!("[]{}", if buffer.cap.is_some() { counts } else { String::new() });
+        }
+        let Some(size) = primitive_size(element).filter(|&size| size > 0) else {
+            return format!("[…]{counts}");
+        };
+        let shown = usize::try_from(buffer.len).map_or(self.budget.max_elements, |len| len.min(self.budget.max_elements));
+        let bytes = match self.read(buffer.ptr, shown * size) {
+            Ok(bytes) => bytes,
+            Err(shown) => return format!("[{shown}]{counts}"),
+        };
+        let mut items: Vec<String> = bytes
+            .chunks(size)
+            .filter_map(|chunk| format_primitive(element, chunk))
+            .collect();
+        if (shown as u64) < buffer.len {
+            items.push("…".to_string());
+        }
+        let counts = if buffer.cap.is_some() || (shown as u64) < buffer.len {
+            counts
+        } else {
+            String::new()
+        };
+        format!("[{}]{counts}", items.join(", "))
+    }
+
+    fn render_enum(&mut self, summary: &TypeSummary, address: Address, depth: usize) -> String
+    {
+        let (offset, ty) = match &summary.discriminant {
+            Some(field) => (field.offset_bits.unwrap_or(0) / 8, field.ty.clone().unwrap_or_default()),
+            // A C-like enum is its discriminant
+            None => (0, unsigned_of_bits(summary.size_bits).unwrap_or_default().to_string()),
+        };
+        let Some(size) = primitive_size(&ty).filter(|&size| (1..=8).contains(&size)) else {
+            return self.render_hex(summary, address);
+        };
+        let value = match self.read(address + offset, size) {
+            Ok(bytes) => {
+                let mut word = [0_u8; 8];
+                word[..size].copy_from_slice(&bytes);
+                u64::from_le_bytes(word)
+            }
+            Err(shown) => return shown,
+        };
+        let mask = u64::MAX >> (64 - 8 * size as u32);
+        let variant = summary
+            .variants
+            .iter()
+            .find(|variant| variant.discriminant.is_some_and(|d| d.cast_unsigned() & mask == value))
+            .or_else(|| summary.variants.iter().find(|variant| variant.discriminant.is_none()));
+        let Some(variant) = variant else {
+            return format!("<{} with unknown discriminant {value}>", summary.name);
+        };
+        let name = variant.name.clone().unwrap_or_else(|| "?".to_string());
+        if variant.fields.is_empty() {
+            return name;
+        }
+        self.render_fields(&name, &variant.fields, address, depth)
+    }
+
+    fn render_struct(&mut self, summary: &TypeSummary, address: Address, depth: usize) -> String
+    {
+        if summary.fields.is_empty() && summary.size_bits.unwrap_or(0) > 0 {
+            return self.render_hex(summary, address);
+        }
+        // Tuples are named after their element types
+        let name = if summary.name.starts_with('(') {
+            ""
+        } else {
+            short_name(&summary.name)
+        };
+        self.render_fields(name, &summary.fields, address, depth)
+    }
+
+    /// `Name { a: 1, b: 2 }`, or `Name(1, 2)` for tuple fields
+    fn render_fields(&mut self, name: &str, fields: &[TypeField], address: Address, depth: usize) -> String
+    {
+        let tuple = fields
+            .iter()
+            .all(|field| field.name.as_deref().is_some_and(|name| name.starts_with("__")));
+        if depth >= self.budget.max_depth {
+            return if tuple {
+                format!("{name}(…)")
+            } else {
+                format!("{name} {{ … }}")
+            };
+        }
+        let values: Vec<String> = fields
+            .iter()
+            .map(|field| {
+                let value = match (&field.ty, field.offset_bits) {
+                    (Some(ty), Some(bits)) => self.render(ty, address + bits / 8, depth + 1),
+                    _ => "?".to_string(),
+                };
+                match (&field.name, tuple) {
+                    (Some(field_name), false) => format!("{field_name}: {value}"),
+                    _ => value,
+                }
+            })
+            .collect();
+        if tuple {
+            format!("{name}({})", values.join(", "))
+        } else if values.is_empty() {
+            name.to_string()
+        } else {
+            format!("{name} {{ {} }}", values.join(", "))
+        }
+    }
+
+    /// The raw bytes of a value whose layout isn't understood
+    fn render_hex(&mut self, summary: &TypeSummary, address: Address) -> String
+    {
+        let Some(size) = summary.size_bits.and_then(|bits| usize::try_from(bits / 8).ok()) else {
+            return format!("<{} at {address}>", summary.name);
+        };
+        let shown = size.min(MAX_HEX_BYTES);
+        match self.read(address, shown) {
+            Ok(bytes) => {
+                let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+                let more = if shown < size { " …" } else { "" };
+                format!("<{} bytes {}{more}>", short_name(&summary.name), hex.join(" "))
+            }
+            Err(shown) => shown,
+        }
+    }
+
+    /// Fields of `summary` that have no layout of their own, following nested structs
+    fn leaf_fields(&self, summary: &TypeSummary, depth: usize) -> Vec<Leaf>
+    {
+        let mut leaves = Vec::new();
+        for field in &summary.fields {
+            let (Some(name), Some(ty), Some(bits)) = (&field.name, &field.ty, field.offset_bits) else {
+                continue;
+            };
+            let nested = (depth < MAX_LAYOUT_DEPTH && primitive_size(ty).is_none() && !ty.starts_with('*'))
+                .then(|| self.target.describe_type(ty))
+                .flatten()
+                .filter(|nested| nested.kind == TypeKind::Struct);
+            match nested {
+                Some(nested) => leaves.extend(self.leaf_fields(&nested, depth + 1).into_iter().map(|leaf| Leaf {
+                    path: format!("{name}.{}", leaf.path),
+                    offset: bits / 8 + leaf.offset,
+                    ..leaf
+                })),
+                None => leaves.push(Leaf {
+                    path: name.clone(),
+                    ty: ty.clone(),
+                    offset: bits / 8,
+                }),
+            }
+        }
+        leaves
+    }
+}
+
+fn is_string(name: &str) -> bool
+{
+    matches!(name, "alloc::string::String" | "String")
+}
+
+fn is_vec(name: &str) -> bool
+{
+    name.starts_with("alloc::vec::Vec<") || name.starts_with("Vec<")
+}
+
+/// Unsigned integer type as wide as `size_bits`
+fn unsigned_of_bits(size_bits: Option<u64>) -> Option<&'static str>
+{
+    Some(match size_bits? {
+        8 => "u8",
+        16 => "u16",
+        32 => "u32",
+        64 => "u64",
+        _ => return None,
+    })
+}
+
+/// `Point` for `app::geometry::Point`, keeping generic arguments whole
+fn short_name(name: &str) -> &str
+{
+    let path_end = name.find('<').unwrap_or(name.len());
+    name[..path_end].rfind("::").map_or(name, |at| &name[at + 2..])
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::error::{DebuggerError, Result};
+    use crate::symbols::TypeVariant;
+    use crate::types::{Architecture, MemoryRegion, Registers, StopReason, ThreadId};
+
+    #[derive(Default)]
+    struct Target
+    {
+        memory: HashMap<u64, u8>,
+        types: HashMap<String, Arc<TypeSummary>>,
+    }
+
+    impl Target
+    {
+        fn write(&mut self, address: u64, bytes: &[u8])
+        {
+            for (at, &byte) in (address..).zip(bytes) {
+                self.memory.insert(at, byte);
+            }
+        }
+
+        fn write_u64s(&mut self, address: u64, words: &[u64])
+        {
+            let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+            self.write(address, &bytes);
+        }
+
+        fn add_type(&mut self, kind: TypeKind, name: &str, size: u64, fields: &[(&str, &str, u64)])
+        {
+            self.types
+                .insert(name.to_string(), Arc::new(summary(kind, name, size, fields)));
+        }
+    }
+
+    fn field(name: &str, ty: &str, offset: u64) -> TypeField
+    {
+        TypeField {
+            name: Some(name.to_string()),
+            ty: Some(ty.to_string()),
+            offset_bits: Some(offset * 8),
+        }
+    }
+
+    fn summary(kind: TypeKind, name: &str, size: u64, fields: &[(&str, &str, u64)]) -> TypeSummary
+    {
+        TypeSummary {
+            name: name.to_string(),
+            kind,
+            size_bits: Some(size * 8),
+            fields: fields.iter().map(|&(name, ty, offset)| field(name, ty, offset)).collect(),
+            variants: Vec::new(),
+            discriminant: None,
+        }
+    }
+
+    impl TargetInspector for Target
+    {
+        fn architecture(&self) -> Architecture
+        {
+            Architecture::Arm64
+        }
+
+        fn is_attached(&self) -> bool
+        {
+            true
+        }
+
+        fn is_stopped(&self) -> bool
+        {
+            true
+        }
+
+        fn stop_reason(&self) -> StopReason
+        {
+            StopReason::Suspended
+        }
+
+        fn threads(&self) -> Result<Vec<ThreadId>>
+        {
+            Ok(vec![ThreadId::from(1)])
+        }
+
+        fn active_thread(&self) -> Option<ThreadId>
+        {
+            Some(ThreadId::from(1))
+        }
+
+        fn read_registers(&self) -> Result<Registers>
+        {
+            Err(DebuggerError::NotAttached)
+        }
+
+        fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+        {
+            (addr.value()..addr.value() + len as u64)
+                .map(|address| self.memory.get(&address).copied())
+                .collect::<Option<_>>()
+                .ok_or(DebuggerError::MemoryReadFailed {
+                    address: addr,
+                    length: len,
+                    kern_return: None,
+                })
+        }
+
+        fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+        {
+            Ok(Vec::new())
+        }
+
+        fn describe_type(&self, name: &str) -> Option<Arc<TypeSummary>>
+        {
+            self.types.get(name).cloned()
+        }
+    }
+
+    /// `String`'s layout in recent toolchains: `Vec<u8>` → `RawVec` → `RawVecInner` → `Unique` → `NonNull`
+    fn add_vec_layout(target: &mut Target, vec: &str, element: &str)
+    {
+        let raw_vec = format!("alloc::raw_vec::RawVec<{element}, alloc::alloc::Global>");
+        target.add_type(TypeKind::Struct, vec, 24, &[("buf", &raw_vec, 0), ("len", "usize", 16)]);
+        target.add_type(TypeKind::Struct, &raw_vec, 16, &[("inner", "alloc::raw_vec::RawVecInner", 0)]);
+        target.add_type(
+            TypeKind::Struct,
+            "alloc::raw_vec::RawVecInner",
+            16,
+            &[("ptr", "core::ptr::unique::Unique<u8>", 0), ("cap", "alloc::raw_vec::Cap", 8)],
+        );
+        target.add_type(TypeKind::Struct, "alloc::raw_vec::Cap", 8, &[("__0", "usize", 0)]);
+        target.add_type(
+            TypeKind::Struct,
+            "core::ptr::unique::Unique<u8>",
+            8,
+            &[("pointer", "core::ptr::non_null::NonNull<u8>", 0)],
+        );
+        target.add_type(
+            TypeKind::Struct,
+            "core::ptr::non_null::NonNull<u8>",
+            8,
+            &[("pointer", "*const u8", 0)],
+        );
+    }
+
+    fn render(target: &Target, ty: &str, address: u64) -> String
+    {
+        render_value(target, ty, Address::from(address), RenderBudget::default())
+    }
+
+    #[test]
+    fn primitives_decode_by_width_and_sign()
+    {
+        assert_eq!(format_primitive("i8", &[0xff]).as_deref(), Some("-1"));
+        assert_eq!(format_primitive("u16", &[0x34, 0x12]).as_deref(), Some("4660"));
+        assert_eq!(format_primitive("i64", &(-5_i64).to_le_bytes()).as_deref(), Some("-5"));
+        assert_eq!(format_primitive("bool", &[1]).as_deref(), Some("true"));
+        assert_eq!(
+            format_primitive("char", &u32::from('é').to_le_bytes()).as_deref(),
+            Some("'é'")
+        );
+        assert_eq!(format_primitive("f64", &1.5_f64.to_le_bytes()).as_deref(), Some("1.5"));
+        assert_eq!(format_primitive("u32", &[1, 2]), None);
+        assert_eq!(
+            generic_args("alloc::vec::Vec<(u8, u16), alloc::alloc::Global>"),
+            ["(u8, u16)", "alloc::alloc::Global"]
+        );
+    }
+
+    #[test]
+    fn strings_and_vecs_follow_their_buffer()
+    {
+        let mut target = Target::default();
+        add_vec_layout(&mut target, "alloc::vec::Vec<u8, alloc::alloc::Global>", "u8");
+        add_vec_layout(&mut target, "alloc::vec::Vec<u32, alloc::alloc::Global>", "u32");
+        target.add_type(
+            TypeKind::Struct,
+            "alloc::string::String",
+            24,
+            &[("vec", "alloc::vec::Vec<u8, alloc::alloc::Global>", 0)],
+        );
+        target.write(0x9000, b"hello \"ferros\"");
+        target.write_u64s(0x1000, &[0x9000, 14]);
+        assert_eq!(render(&target, "&str", 0x1000), r#""hello \"ferros\"""#);
+
+        // ptr, cap, len
+        target.write_u64s(0x2000, &[0x9000, 32, 5]);
+        assert_eq!(render(&target, "alloc::string::String", 0x2000), r#""hello""#);
+
+        let words: Vec<u8> = (1_u32..=20).flat_map(u32::to_le_bytes).collect();
+        target.write(0xa000, &words);
+        target.write_u64s(0x3000, &[0xa000, 4, 3]);
+        assert_eq!(
+            render(&target, "alloc::vec::Vec<u32, alloc::alloc::Global>", 0x3000),
+            "[1, 2, 3] (len 3, cap 4)"
+        );
+        // Only max_elements of a long one, and a corrupt length stays within the byte budget
+        target.write_u64s(0x3000, &[0xa000, 20, 20]);
+        let long = render(&target, "alloc::vec::Vec<u32, alloc::alloc::Global>", 0x3000);
+        assert!(
+            long.starts_with("[1, 2, ") && long.ends_with(", 16, …] (len 20, cap 20)"),
+            "{long}"
+        );
+        target.write_u64s(0x1000, &[0x9000, u64::MAX]);
+        let budget = RenderBudget {
+            max_bytes: 64,
+            ..RenderBudget::default()
+        };
+        assert_eq!(render_value(&target, "&str", Address::from(0x1000), budget), "…");
+    }
+
+    #[test]
+    fn options_pick_their_variant_by_discriminant_or_niche()
+    {
+        let mut target = Target::default();
+        let mut option = summary(TypeKind::Enum, "core::option::Option<u32>", 8, &[]);
+        option.discriminant = Some(field("<discriminant>", "u32", 0));
+        option.variants = vec![
+            TypeVariant {
+                name: Some("None".to_string()),
+                discriminant: Some(0),
+                fields: Vec::new(),
+            },
+            TypeVariant {
+                name: Some("Some".to_string()),
+                discriminant: Some(1),
+                fields: vec![field("__0", "u32", 4)],
+            },
+        ];
+        target.types.insert(option.name.clone(), Arc::new(option));
+        target.write(0x1000, &[1, 0, 0, 0, 42, 0, 0, 0]);
+        target.write(0x2000, &[0; 8]);
+        assert_eq!(render(&target, "core::option::Option<u32>", 0x1000), "Some(42)");
+        assert_eq!(render(&target, "core::option::Option<u32>", 0x2000), "None");
+
+        // Option<&u8>: None is the null pointer, Some has no value of its own
+        let mut niche = summary(TypeKind::Enum, "core::option::Option<&u8>", 8, &[]);
+        niche.discriminant = Some(field("<discriminant>", "u64", 0));
+        niche.variants = vec![
+            TypeVariant {
+                name: Some("None".to_string()),
+                discriminant: Some(0),
+                fields: Vec::new(),
+            },
+            TypeVariant {
+                name: Some("Some".to_string()),
+                discriminant: None,
+                fields: vec![field("__0", "&u8", 0)],
+            },
+        ];
+        target.types.insert(niche.name.clone(), Arc::new(niche));
+        target.write_u64s(0x3000, &[0x9000]);
+        assert_eq!(render(&target, "core::option::Option<&u8>", 0x3000), "Some(0x9000)");
+    }
+
+    #[test]
+    fn structs_render_fieldwise_and_degrade_to_hex()
+    {
+        let mut target = Target::default();
+        target.add_type(TypeKind::Struct, "app::Point", 8, &[("x", "i32", 0), ("y", "i32", 4)]);
+        target.add_type(
+            TypeKind::Struct,
+            "app::Line",
+            16,
+            &[("from", "app::Point", 0), ("to", "app::Point", 8)],
+        );
+        target.add_type(TypeKind::Union, "app::Raw", 4, &[("word", "u32", 0)]);
+        let corners: Vec<u8> = [1_i32, -2, 3, 4].iter().flat_map(|n| n.to_le_bytes()).collect();
+        target.write(0x1000, &corners);
+
+        assert_eq!(render(&target, "app::Point", 0x1000), "Point { x: 1, y: -2 }");
+        assert_eq!(
+            render(&target, "app::Line", 0x1000),
+            "Line { from: Point { x: 1, y: -2 }, to: Point { x: 3, y: 4 } }"
+        );
+        let shallow = RenderBudget {
+            max_depth: 1,
+            ..RenderBudget::default()
+        };
+        assert_eq!(
+            render_value(&target, "app::Line", Address::from(0x1000), shallow),
+            "Line { from: Point { … }, to: Point { … } }"
+        );
+        assert_eq!(render(&target, "app::Raw", 0x1000), "<Raw bytes 01 00 00 00>");
+        assert_eq!(render(&target, "app::Unknown", 0x1000), "<app::Unknown at 0x0000000000001000>");
+        assert_eq!(render(&target, "app::Point", 0x5000), "<unreadable at 0x0000000000005000>");
+    }
+}
@@ -0,0 +1,289 @@
+//! Linker symbol tables: names for code without DWARF.
+//!
+//! DWARF only describes code compiled with debug info. C and Objective-C from
+//! system libraries and prebuilt objects are known only through the linker
+//! symbol table (Mach-O `LC_SYMTAB` nlist entries, ELF `.symtab`), which also
+//! carries the compiler-generated Objective-C method names
+//! (`-[NSView drawRect:]`). Breakpoints by name consult this table in
+//! addition to DWARF subprograms, and symbolication falls back to it.
+//!
+//! ## Matching Rules
+//!
+//! - Names starting with `-[` or `+[` are Objective-C specs and match methods
+//!   with the same class and selector, in any category.
+//! - Everything else matches the symbol name exactly, so C names are never
+//!   demangled, or a demangled Rust path as in DWARF lookups.
+//! - Mach-O prefixes C-level names with `_`; it is stripped on load so
+//!   `malloc` matches `_malloc`.
+//!
+//! ## Symbol Stubs
+//!
+//! Calls from a Mach-O image into a dylib go through a stub in the caller's
+//! `__stubs` section. On current macOS the system dylibs (`malloc` lives in
+//! `libsystem_malloc`) exist only inside the dyld shared cache, not on disk,
+//! so their own symbols can't be read. The stubs are named after the symbol
+//! they call through, so `malloc` still resolves to the executable's stub and
+//! a breakpoint there hits on every call the executable makes. Calls made
+//! from inside other libraries don't pass through it.
+//!
+//! Objective-C methods of stripped images (most system frameworks) are not
+//! in the symbol table either; finding those would require walking the
+//! `__objc_classlist` method lists.
+
+use object::endian::{Endianness, U32};
+use object::read::ReadRef;
+use object::read::macho::{MachOFile64, Nlist, Section};
+use object::{Object, ObjectSymbol, SymbolIndex, SymbolKind, macho};
+
+use super::demangle::ObjcMethodName;
+use super::locate::linkage_matches;
+
+/// Defined code symbols of one image, sorted by file address.
+#[derive(Debug, Default)]
+pub(crate) struct SymbolTable
+{
+    entries: Vec<SymbolEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct SymbolEntry
+{
+    name: String,
+    address: u64,
+}
+
+impl SymbolTable
+{
+    /// Collect the defined code symbols and, for 64-bit Mach-O, the symbol stubs.
+    pub(crate) fn from_object(file: &object::File<'_>) -> Self
+    {
+        let macho = file.format() == object::BinaryFormat::MachO;
+        let mut entries: Vec<SymbolEntry> = file
+            .symbols()
+            .filter(|symbol| symbol.is_definition() && symbol.kind() == SymbolKind::Text)
+            .filter_map(|symbol| {
+                let name = symbol.name().ok().filter(|name| !name.is_empty())?;
+                Some(SymbolEntry {
+                    name: strip_global_prefix(name, macho).to_string(),
+                    address: symbol.address(),
+                })
+            })
+            .collect();
+        if let object::File::MachO64(file) = file {
+            entries.extend(macho_stubs(file).unwrap_or_default());
+        }
+        entries.sort_by_key(|entry| entry.address);
+        Self { entries }
+    }
+
+    /// File addresses of the symbols `wanted` names, lowest first.
+    pub(crate) fn addresses_of(&self, wanted: &str) -> Vec<u64>
+    {
+        let spec = ObjcMethodName::parse(wanted);
+        let mut addresses: Vec<u64> = self
+            .entries
+            .iter()
+            .filter(|entry| match &spec {
+                Some(spec) => ObjcMethodName::parse(&entry.name).is_some_and(|method| method.matches(spec)),
+                None => entry.name == wanted || linkage_matches(&entry.name, wanted),
+            })
+            .map(|entry| entry.address)
+            .collect();
+        addresses.dedup();
+        addresses
+    }
+
+    /// Name of the closest symbol at or below `address`.
+    ///
+    /// Symbol tables carry no sizes, so an address past the end of the last
+    /// function still reports it; callers only use this when DWARF has nothing.
+    pub(crate) fn symbol_for(&self, address: u64) -> Option<&str>
+    {
+        self.entry_for(address).map(|entry| entry.name.as_str())
+    }
+
+    /// File address of the closest symbol at or below `address`.
+    ///
+    /// Same caveat as [`symbol_for`](Self::symbol_for) about addresses past
+    /// the last function.
+    pub(crate) fn symbol_start(&self, address: u64) -> Option<u64>
+    {
+        self.entry_for(address).map(|entry| entry.address)
+    }
+
+    fn entry_for(&self, address: u64) -> Option<&SymbolEntry>
+    {
+        let index = self.entries.partition_point(|entry| entry.address <= address);
+        index.checked_sub(1).map(|index| &self.entries[index])
+    }
+
+    /// Whether the image has no code symbols at all, as when fully stripped.
+    pub(crate) fn is_empty(&self) -> bool
+    {
+        self.entries.is_empty()
+    }
+
+    /// How many code symbols the image defines.
+    pub(crate) fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+}
+
+/// Drop the `_` Mach-O puts in front of every C-level name.
+///
+/// Objective-C method names are emitted without it.
+fn strip_global_prefix(name: &str, macho: bool) -> &str
+{
+    if macho { name.strip_prefix('_').unwrap_or(name) } else { name }
+}
+
+/// Stub entries of `__stubs`-type sections, named after the symbol they call.
+///
+/// Each stub section's `reserved1` is its first index into the indirect symbol
+/// table and `reserved2` the size of one stub. Returns `None` if the tables
+/// are malformed.
+fn macho_stubs(file: &MachOFile64<'_, Endianness>) -> Option<Vec<SymbolEntry>>
+{
+    let endian = file.endian();
+    let symbols = file.macho_symbol_table();
+    let mut commands = file.macho_load_commands().ok()?;
+    let dysymtab = loop {
+        let command = commands.next().ok()??;
+        if let Some(dysymtab) = command.dysymtab().ok()? {
+            break dysymtab;
+        }
+    };
+    let indirect: &[U32<Endianness>] = file
+        .data()
+        .read_slice_at(
+            u64::from(dysymtab.indirectsymoff.get(endian)),
+            dysymtab.nindirectsyms.get(endian) as usize,
+        )
+        .ok()?;
+
+    let mut stubs = Vec::new();
+    for section in file.sections() {
+        let section = section.macho_section();
+        let stub_size = u64::from(section.reserved2.get(endian));
+        if section.flags(endian) & macho::SECTION_TYPE != macho::S_SYMBOL_STUBS || stub_size == 0 {
+            continue;
+        }
+        let first = section.reserved1.get(endian) as usize;
+        for stub in 0..section.size(endian) / stub_size {
+            let Some(index) = indirect.get(first + stub as usize) else {
+                break;
+            };
+            let index = index.get(endian);
+            if index & (macho::INDIRECT_SYMBOL_LOCAL | macho::INDIRECT_SYMBOL_ABS) != 0 {
+                continue;
+            }
+            let Some(name) = symbols
+                .symbol(SymbolIndex(index as usize))
+                .and_then(|nlist| nlist.name(endian, symbols.strings()))
+                .ok()
+            else {
+                continue;
+            };
+            stubs.push(SymbolEntry {
+                name: strip_global_prefix(&String::from_utf8_lossy(name), true).to_string(),
+                address: section.addr(endian) + stub * stub_size,
+            });
+        }
+    }
+    Some(stubs)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[unsafe(no_mangle)]
+    #[inline(never)]
+    extern "C" fn ferros_symtab_probe() -> u32
+    {
+        std::hint::black_box(7)
+    }
+
+    #[inline(never)]
+    fn rust_probe() -> u32
+    {
+        std::hint::black_box(11)
+    }
+
+    fn entries(names: &[(&str, u64)]) -> SymbolTable
+    {
+        SymbolTable {
+            entries: names
+                .iter()
+                .map(|&(name, address)| SymbolEntry {
+                    name: name.to_string(),
+                    address,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn objc_specs_match_methods_by_class_and_selector()
+    {
+        let table = entries(&[
+            ("-[NSView drawRect:]", 0x100),
+            ("-[NSView(Debug) layout]", 0x180),
+            ("+[NSView layerClass]", 0x200),
+            ("-[NSViewController view]", 0x280),
+            ("drawRect", 0x300),
+        ]);
+        assert_eq!(table.addresses_of("-[NSView drawRect:]"), vec![0x100]);
+        assert_eq!(table.addresses_of("-[NSView  layout]"), vec![0x180]);
+        assert_eq!(table.addresses_of("-[NSView(Debug) layout]"), vec![0x180]);
+        assert!(table.addresses_of("-[NSView(Other) layout]").is_empty());
+        // Class and instance methods are distinct
+        assert!(table.addresses_of("-[NSView layerClass]").is_empty());
+        assert_eq!(table.addresses_of("+[NSView layerClass]"), vec![0x200]);
+        assert!(table.addresses_of("-[NSView view]").is_empty());
+    }
+
+    #[test]
+    fn c_names_match_exactly()
+    {
+        let table = entries(&[
+            ("malloc", 0x10),
+            ("malloc_zone_malloc", 0x20),
+            ("_ZN4core3ptr4read17h0123456789abcdefE", 0x30),
+        ]);
+        assert_eq!(table.addresses_of("malloc"), vec![0x10]);
+        assert!(table.addresses_of("zone_malloc").is_empty());
+        assert_eq!(table.addresses_of("core::ptr::read"), vec![0x30]);
+        assert_eq!(table.symbol_for(0x24), Some("malloc_zone_malloc"));
+        assert_eq!(table.symbol_for(0x8), None);
+    }
+
+    #[test]
+    fn mach_o_global_prefix_is_stripped()
+    {
+        assert_eq!(strip_global_prefix("_malloc", true), "malloc");
+        assert_eq!(
+            strip_global_prefix("__ZN4core3ptr4read17h0123456789abcdefE", true),
+            "_ZN4core3ptr4read17h0123456789abcdefE"
+        );
+        assert_eq!(strip_global_prefix("-[NSView drawRect:]", true), "-[NSView drawRect:]");
+        assert_eq!(strip_global_prefix("_start", false), "_start");
+    }
+
+    #[test]
+    fn own_symbol_table_resolves_c_and_rust_names()
+    {
+        assert_eq!(ferros_symtab_probe() + rust_probe(), 18);
+
+        let bytes = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let file = object::File::parse(&*bytes).unwrap();
+        let table = SymbolTable::from_object(&file);
+
+        let c = table.addresses_of("ferros_symtab_probe");
+        assert_eq!(c.len(), 1);
+        assert_eq!(table.symbol_for(c[0]), Some("ferros_symtab_probe"));
+        assert_eq!(table.addresses_of("symtab::tests::rust_probe").len(), 1);
+    }
+}
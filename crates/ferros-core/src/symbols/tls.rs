@@ -0,0 +1,490 @@
+//! Thread-local variables: finding them in DWARF and locating one thread's copy.
+//!
+//! A thread-local has one instance per thread, so DWARF doesn't give it an
+//! address. Its `DW_AT_location` is a TLS lookup instead: a constant followed
+//! by `DW_OP_form_tls_address` (or the older `DW_OP_GNU_push_tls_address`).
+//! What the constant means depends on the object format:
+//!
+//! - **Mach-O**: the file address of the variable's TLV descriptor in
+//!   `__thread_vars`. dyld fills the descriptor with a pthread key and an
+//!   offset. The thread's TSD slot for that key points at its TLV block, which
+//!   `tlv_bootstrap` allocates the first time the thread touches one of the
+//!   image's thread-locals, and the variable lives at block + offset.
+//! - **ELF**: the variable's offset in the module's TLS block. Turning that
+//!   into an address needs the thread pointer and the dynamic thread vector.
+//!
+//! ## Finding a Thread's TSD
+//!
+//! Each thread's TSD array is what `TPIDRRO_EL0` (arm64) or the GS base
+//! (x86_64) points at. Neither is part of the thread state Mach hands out on
+//! arm64, so the base is derived from `thread_info(THREAD_IDENTIFIER_INFO)`'s
+//! `thread_handle` by [`tsd_base_from_handle`], which checks the TSD's first
+//! slot (`pthread_self`) before trusting it.
+//!
+//! ## Names
+//!
+//! Variables are matched on their namespace-qualified name, by `::`-separated
+//! suffix as with functions. `thread_local!` hides its storage in a static
+//! named `__RUST_STD_INTERNAL_VAL` inside closure namespaces under the key, so
+//! those parts are dropped: `COUNTER` declared in `my_app` is
+//! `my_app::COUNTER`. Depending on the initializer, std may emit more than one
+//! storage static per key; each one is reported.
+
+use gimli::{AttributeValue, DebuggingInformationEntry, Encoding, Expression, Operation, Reader, Unit, constants};
+
+use super::demangle::map_dwarf_error;
+use super::render::{RenderBudget, primitive_size, render_value};
+use super::{OwnedDwarf, OwnedReader};
+use crate::error::Result;
+use crate::inspector::TargetInspector;
+use crate::pins::{MAX_PIN_BYTES, PinType};
+use crate::types::{Address, ThreadId};
+
+/// Offset of `offsetof(struct _pthread, tsd)` on 64-bit Darwin.
+const PTHREAD_TSD_OFFSET: u64 = 0xe0;
+
+/// Offsets of `key` and `offset` in dyld's `TLVDescriptor { thunk, key, offset }`.
+const TLV_KEY_OFFSET: u64 = 8;
+const TLV_OFFSET_OFFSET: u64 = 16;
+
+/// Names std gives the storage behind a `thread_local!` key.
+const STD_STORAGE_NAMES: &[&str] = &["__RUST_STD_INTERNAL_VAL", "VAL", "__KEY"];
+
+/// Bytes read for a variable whose type has no size.
+const DEFAULT_READ_LEN: u64 = 8;
+
+/// Deepest chain of typedefs and qualifiers followed to find a type's size.
+const MAX_TYPE_DEPTH: usize = 8;
+
+/// Where each thread's copy of a thread-local lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsLocation
+{
+    /// Runtime address of a Mach-O TLV descriptor.
+    Descriptor(Address),
+    /// Offset into the module's ELF TLS block.
+    BlockOffset(u64),
+}
+
+/// A thread-local variable described in DWARF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadLocalVariable
+{
+    /// Namespace-qualified name, without std's storage wrappers.
+    pub name: String,
+    /// Name of the variable's type, if DWARF gives one.
+    pub type_name: Option<String>,
+    /// Size of the variable in bytes, if known.
+    pub size: Option<u64>,
+    /// How to find a thread's copy.
+    pub location: TlsLocation,
+}
+
+impl ThreadLocalVariable
+{
+    /// Bytes to read for one copy, capped at [`MAX_PIN_BYTES`].
+    #[must_use]
+    pub fn read_len(&self) -> usize
+    {
+        usize::try_from(self.size.unwrap_or(DEFAULT_READ_LEN)).map_or(MAX_PIN_BYTES, |len| len.min(MAX_PIN_BYTES))
+    }
+}
+
+/// One thread's copy of a thread-local variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadLocalValue
+{
+    /// The variable.
+    pub variable: ThreadLocalVariable,
+    /// Thread the value was read from.
+    pub thread: ThreadId,
+    /// Address of the thread's copy, or `None` if the thread hasn't touched
+    /// the image's thread-locals yet and has no copy.
+    pub address: Option<Address>,
+    /// Raw bytes of the copy, empty if there is none.
+    pub bytes: Vec<u8>,
+}
+
+impl ThreadLocalValue
+{
+    /// One-line description naming the thread, e.g.
+    /// `my_app::COUNTER [thread 42] @ 0x... (Cell<u64>) = 3 (0x3)`.
+    #[must_use]
+    pub fn describe(&self) -> String
+    {
+        let name = &self.variable.name;
+        let thread = self.thread.raw();
+        let Some(address) = self.address else {
+            return format!("{name} [thread {thread}] = <not initialized on this thread>");
+        };
+        let ty = match self.bytes.len() {
+            1 => PinType::U8,
+            2 => PinType::U16,
+            4 => PinType::U32,
+            8 => PinType::U64,
+            len => PinType::Bytes(len),
+        };
+        let value = ty.format(&self.bytes).unwrap_or_else(|| "<unreadable>".to_string());
+        match &self.variable.type_name {
+            Some(type_name) => format!("{name} [thread {thread}] @ {address} ({type_name}) = {value}"),
+            None => format!("{name} [thread {thread}] @ {address} = {value}"),
+        }
+    }
+
+    /// Like [`describe`](Self::describe), but with the value rendered from
+    /// its type when `target` knows the type's layout, e.g.
+    /// `my_app::NAME [thread 42] @ 0x... (RefCell<String>) = RefCell { ... }`.
+    pub fn render<T: TargetInspector + ?Sized>(&self, target: &T) -> String
+    {
+        let (Some(address), Some(type_name)) = (self.address, &self.variable.type_name) else {
+            return self.describe();
+        };
+        if primitive_size(type_name).is_none() && target.describe_type(type_name).is_none() {
+            return self.describe();
+        }
+        let value = render_value(target, type_name, address, RenderBudget::default());
+        let (name, thread) = (&self.variable.name, self.thread.raw());
+        format!("{name} [thread {thread}] @ {address} ({type_name}) = {value}")
+    }
+}
+
+/// Address of a thread's TSD slot for `key`.
+#[must_use]
+pub fn tsd_slot(tsd_base: Address, key: u64) -> Address
+{
+    tsd_base + key * 8
+}
+
+/// Base of a thread's TSD array, given the `thread_handle` XNU reports for it.
+///
+/// Depending on the OS release the handle is either the `pthread_t` or the TSD
+/// base itself. Slot 0 of the TSD holds `pthread_self`, which tells the two
+/// apart. Returns `None` if neither reading is consistent.
+///
+/// ## Errors
+///
+/// Returns the error of a failed memory read.
+pub fn tsd_base_from_handle(handle: u64, mut read_u64: impl FnMut(Address) -> Result<u64>) -> Result<Option<Address>>
+{
+    if handle == 0 {
+        return Ok(None);
+    }
+    let behind_pthread = Address::from(handle + PTHREAD_TSD_OFFSET);
+    if read_u64(behind_pthread).ok() == Some(handle) {
+        return Ok(Some(behind_pthread));
+    }
+    let own = Address::from(handle);
+    Ok((read_u64(own)? == handle.wrapping_sub(PTHREAD_TSD_OFFSET)).then_some(own))
+}
+
+/// Address of a thread's copy of the variable behind a Mach-O TLV descriptor.
+///
+/// Returns `None` if the thread's slot for the descriptor's key is empty,
+/// i.e. the thread has no TLV block for the image yet.
+///
+/// ## Errors
+///
+/// Returns the error of a failed memory read.
+pub fn tlv_address(
+    descriptor: Address,
+    tsd_base: Address,
+    mut read_u64: impl FnMut(Address) -> Result<u64>,
+) -> Result<Option<Address>>
+{
+    let key = read_u64(descriptor + TLV_KEY_OFFSET)?;
+    let offset = read_u64(descriptor + TLV_OFFSET_OFFSET)?;
+    let block = read_u64(tsd_slot(tsd_base, key))?;
+    Ok((block != 0).then(|| Address::from(block) + offset))
+}
+
+/// A thread-local found in DWARF, before the image interprets its operand.
+pub(crate) struct DwarfThreadLocal
+{
+    pub(crate) name: String,
+    pub(crate) type_name: Option<String>,
+    pub(crate) size: Option<u64>,
+    /// The constant fed to the TLS lookup.
+    pub(crate) operand: u64,
+}
+
+pub(crate) struct TlsResolver<'a>
+{
+    dwarf: &'a OwnedDwarf,
+}
+
+impl<'a> TlsResolver<'a>
+{
+    pub(crate) fn new(dwarf: &'a OwnedDwarf) -> Self
+    {
+        Self { dwarf }
+    }
+
+    /// Thread-local variables whose qualified name is `wanted` or ends with `::wanted`.
+    pub(crate) fn variables(&self, wanted: &str) -> Result<Vec<DwarfThreadLocal>>
+    {
+        let mut found = Vec::new();
+        let mut headers = self.dwarf.units();
+        while let Some(header) = headers
+            .next()
+            .map_err(|err| map_dwarf_error("reading .debug_info unit header", err))?
+        {
+            let unit = self
+                .dwarf
+                .unit(header)
+                .map_err(|err| map_dwarf_error("parsing compilation unit", err))?;
+            let mut cursor = unit.entries();
+            let mut depth: isize = 0;
+            // Namespace names of the current entry's ancestors, by depth
+            let mut scopes: Vec<Option<String>> = Vec::new();
+            while let Some((delta, entry)) = cursor.next_dfs().map_err(|err| map_dwarf_error("traversing DIE tree", err))? {
+                depth += delta;
+                scopes.truncate(usize::try_from(depth).unwrap_or_default());
+                let name = self.entry_name(&unit, entry)?;
+                if entry.tag() == constants::DW_TAG_variable
+                    && let Some(name) = &name
+                    && let Some(operand) = Self::tls_operand(&unit, entry)?
+                {
+                    let qualified = qualified_name(scopes.iter().flatten().map(String::as_str), name);
+                    if name_matches(&qualified, wanted) {
+                        let (type_name, size) = self.type_of(&unit, entry)?;
+                        found.push(DwarfThreadLocal {
+                            name: qualified,
+                            type_name,
+                            size,
+                            operand,
+                        });
+                    }
+                }
+                scopes.push(name.filter(|_| entry.tag() == constants::DW_TAG_namespace));
+            }
+        }
+        Ok(found)
+    }
+
+    fn tls_operand(unit: &Unit<OwnedReader>, entry: &DebuggingInformationEntry<'_, '_, OwnedReader>) -> Result<Option<u64>>
+    {
+        match entry
+            .attr_value(constants::DW_AT_location)
+            .map_err(|err| map_dwarf_error("reading DW_AT_location", err))?
+        {
+            Some(AttributeValue::Exprloc(expression)) => Ok(tls_operand(expression, unit.encoding())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Name and byte size of a variable's type, following typedefs and qualifiers.
+    fn type_of(
+        &self,
+        unit: &Unit<OwnedReader>,
+        entry: &DebuggingInformationEntry<'_, '_, OwnedReader>,
+    ) -> Result<(Option<String>, Option<u64>)>
+    {
+        let mut type_name = None;
+        let mut next = entry
+            .attr_value(constants::DW_AT_type)
+            .map_err(|err| map_dwarf_error("reading DW_AT_type", err))?;
+        for _ in 0..MAX_TYPE_DEPTH {
+            let Some(AttributeValue::UnitRef(offset)) = next else {
+                break;
+            };
+            let die = unit
+                .entry(offset)
+                .map_err(|err| map_dwarf_error("resolving type reference", err))?;
+            if type_name.is_none() {
+                type_name = self.entry_name(unit, &die)?;
+            }
+            if let Some(size) = die
+                .attr_value(constants::DW_AT_byte_size)
+                .map_err(|err| map_dwarf_error("reading DW_AT_byte_size", err))?
+                .and_then(|value| value.udata_value())
+            {
+                return Ok((type_name, Some(size)));
+            }
+            next = die
+                .attr_value(constants::DW_AT_type)
+                .map_err(|err| map_dwarf_error("reading DW_AT_type", err))?;
+        }
+        Ok((type_name, None))
+    }
+
+    fn entry_name(
+        &self,
+        unit: &Unit<OwnedReader>,
+        entry: &DebuggingInformationEntry<'_, '_, OwnedReader>,
+    ) -> Result<Option<String>>
+    {
+        let Some(value) = entry
+            .attr_value(constants::DW_AT_name)
+            .map_err(|err| map_dwarf_error("reading DW_AT_name", err))?
+        else {
+            return Ok(None);
+        };
+        let reader = self
+            .dwarf
+            .attr_string(unit, value)
+            .map_err(|err| map_dwarf_error("resolving DWARF string", err))?;
+        Ok(Some(
+            reader
+                .to_string_lossy()
+                .map_err(|err| map_dwarf_error("decoding DWARF string", err))?
+                .into_owned(),
+        ))
+    }
+}
+
+/// Operand of a location expression that is a TLS lookup, or `None` for any other expression.
+fn tls_operand<R: Reader>(expression: Expression<R>, encoding: Encoding) -> Option<u64>
+{
+    let mut operations = expression.operations(encoding);
+    let operand = match operations.next().ok()?? {
+        Operation::UnsignedConstant { value } => value,
+        Operation::Address { address } => address,
+        _ => return None,
+    };
+    match (operations.next().ok()??, operations.next().ok()?) {
+        (Operation::TLS, None) => Some(operand),
+        _ => None,
+    }
+}
+
+/// `scopes::name`, leaving out closure scopes and std's `thread_local!` storage name.
+fn qualified_name<'a>(scopes: impl Iterator<Item = &'a str>, name: &str) -> String
+{
+    let mut parts: Vec<&str> = scopes.filter(|scope| !scope.starts_with('{')).collect();
+    if parts.is_empty() || !STD_STORAGE_NAMES.contains(&name) {
+        parts.push(name);
+    }
+    parts.join("::")
+}
+
+fn name_matches(qualified: &str, wanted: &str) -> bool
+{
+    qualified == wanted || qualified.strip_suffix(wanted).is_some_and(|prefix| prefix.ends_with("::"))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use gimli::{Dwarf, EndianArcSlice, EndianSlice, Format, LittleEndian, RunTimeEndian};
+    use object::{Object, ObjectSection};
+
+    use super::*;
+    use crate::error::DebuggerError;
+
+    thread_local! {
+        static PROBE_COUNTER: Cell<u64> = const { Cell::new(0) };
+    }
+
+    fn memory(words: &[(u64, u64)]) -> impl FnMut(Address) -> Result<u64> + use<>
+    {
+        let words: HashMap<u64, u64> = words.iter().copied().collect();
+        move |address| {
+            words.get(&address.value()).copied().ok_or(DebuggerError::MemoryReadFailed {
+                address,
+                length: 8,
+                kern_return: None,
+            })
+        }
+    }
+
+    fn operand(bytes: &[u8]) -> Option<u64>
+    {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        tls_operand(Expression(EndianSlice::new(bytes, LittleEndian)), encoding)
+    }
+
+    #[test]
+    fn each_thread_reads_its_own_copy()
+    {
+        // One descriptor (key 3, offset 0x10); two threads that each bumped the
+        // counter a different number of times and one that never touched it
+        let descriptor = Address::from(0x1_0000_8000);
+        let threads = [(0x7000_0000, 0x6000_0000, 3), (0x7100_0000, 0x6100_0000, 7)];
+        let mut words = vec![(0x1_0000_8008, 3), (0x1_0000_8010, 0x10), (0x7200_0018, 0)];
+        for (tsd, block, count) in threads {
+            words.push((tsd + 3 * 8, block));
+            words.push((block + 0x10, count));
+        }
+        let mut read = memory(&words);
+
+        let mut counts = Vec::new();
+        for (tsd, _, _) in threads {
+            let address = tlv_address(descriptor, Address::from(tsd), &mut read).unwrap().unwrap();
+            counts.push(read(address).unwrap());
+        }
+        assert_eq!(counts, vec![3, 7]);
+        assert_eq!(tlv_address(descriptor, Address::from(0x7200_0000), &mut read).unwrap(), None);
+    }
+
+    #[test]
+    fn tsd_base_accepts_either_handle()
+    {
+        let pthread = 0x1_6f00_0000_u64;
+        let tsd = pthread + PTHREAD_TSD_OFFSET;
+        let mut read = memory(&[(tsd, pthread)]);
+        assert_eq!(tsd_base_from_handle(pthread, &mut read).unwrap(), Some(Address::from(tsd)));
+        assert_eq!(tsd_base_from_handle(tsd, &mut read).unwrap(), Some(Address::from(tsd)));
+        assert!(tsd_base_from_handle(0x5000, &mut read).is_err());
+        assert_eq!(tsd_base_from_handle(0, &mut read).unwrap(), None);
+    }
+
+    #[test]
+    fn only_tls_lookups_have_an_operand()
+    {
+        // DW_OP_const8u 0x20; DW_OP_GNU_push_tls_address
+        assert_eq!(operand(&[0x0e, 0x20, 0, 0, 0, 0, 0, 0, 0, 0xe0]), Some(0x20));
+        // DW_OP_const8u 0x100008000; DW_OP_form_tls_address
+        assert_eq!(operand(&[0x0e, 0x00, 0x80, 0, 0, 1, 0, 0, 0, 0x9b]), Some(0x1_0000_8000));
+        // DW_OP_addr 0x1000: a plain static
+        assert_eq!(operand(&[0x03, 0x00, 0x10, 0, 0, 0, 0, 0, 0]), None);
+        // DW_OP_fbreg -16: a local
+        assert_eq!(operand(&[0x91, 0x70]), None);
+    }
+
+    #[test]
+    fn std_storage_is_named_after_its_key()
+    {
+        let scopes = ["my_app", "COUNTER", "{constant#0}", "{closure#1}"];
+        let name = qualified_name(scopes.into_iter(), "__RUST_STD_INTERNAL_VAL");
+        assert_eq!(name, "my_app::COUNTER");
+        assert!(name_matches(&name, "COUNTER"));
+        assert!(name_matches(&name, "my_app::COUNTER"));
+        assert!(!name_matches(&name, "TER"));
+        assert_eq!(qualified_name(["my_app"].into_iter(), "STATE"), "my_app::STATE");
+        assert_eq!(qualified_name(std::iter::empty(), "VAL"), "VAL");
+    }
+
+    #[test]
+    fn own_thread_locals_are_found_in_dwarf()
+    {
+        PROBE_COUNTER.with(|counter| counter.set(counter.get() + 1));
+
+        let bytes = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let file = object::File::parse(&*bytes).unwrap();
+        let dwarf: OwnedDwarf = Dwarf::load(|id| {
+            let data = file
+                .section_by_name(id.name())
+                .and_then(|section| section.uncompressed_data().ok())
+                .unwrap_or_default();
+            Ok::<_, gimli::Error>(EndianArcSlice::new(Arc::from(&*data), RunTimeEndian::Little))
+        })
+        .unwrap();
+
+        let found = TlsResolver::new(&dwarf).variables("tls::tests::PROBE_COUNTER").unwrap();
+        assert!(!found.is_empty());
+        assert!(
+            found
+                .iter()
+                .all(|variable| variable.name.ends_with("symbols::tls::tests::PROBE_COUNTER"))
+        );
+        assert!(found.iter().any(|variable| variable.size == Some(8)));
+    }
+}
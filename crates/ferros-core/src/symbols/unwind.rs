@@ -18,7 +18,39 @@
 //! - **`.debug_frame`**: Debug frame information (used by debuggers)
 //!
 //! Both sections contain FDEs (Frame Description Entries) that describe how to unwind
-//! the stack for each function.
+//! the stack for each function. The FDE for a PC is found through the
+//! `.eh_frame_hdr` search table when the image has one, and otherwise through an
+//! index of the section built on the image's first unwind. Only a caller
+//! recovered from CFI is [`FrameStatus::Complete`]; the fallbacks produce
+//! `CfiFallback` or `Heuristic` frames.
+//!
+//! ## Diagnostics
+//!
+//! A backtrace that stops early or degrades to `Heuristic` frames usually has a
+//! mundane cause: no image is loaded for the PC, no FDE covers it, a CFI rule uses
+//! an expression we don't evaluate, or a stack read failed. [`StackUnwinder::unwind_with_diagnostics`]
+//! records an [`UnwindDiagnostic`] per physical frame naming every strategy that was
+//! tried, why it failed, and which one (if any) recovered the caller. Plain
+//! [`StackUnwinder::unwind`] skips the bookkeeping entirely.
+//!
+//! ## Deep Recursion
+//!
+//! A stack overflow usually leaves tens of thousands of identical frames on the
+//! stack. When the walk sees the same sequence of PCs (up to
+//! [`MAX_CYCLE_PERIOD`] frames long) repeat [`MIN_CYCLE_REPEATS`] times with a
+//! constant SP step, it keeps one occurrence, skips the rest while counting them,
+//! and resumes emitting frames once the sequence ends. Each collapsed run is
+//! reported as a [`RecursionCycle`], so the frames below the recursion (the code
+//! that started it) still make it into the trace.
+//!
+//! ## Async Stacks
+//!
+//! A thread running an async task spends most of its time parked in the
+//! executor, so its physical stack ends in `tokio::runtime` whatever the task
+//! is waiting on. [`UnwindOptions::with_async_frames`] adds the task's
+//! logical stack on top of the executor frame it was found in: one
+//! [`FrameKind::Async`] frame per `async fn` it is suspended in, read from the
+//! task's state machines in memory.
 //!
 //! ## References
 //!
@@ -26,13 +58,24 @@
 //! - [DWARF CFI Specification](https://dwarfstd.org/doc/DWARF5.pdf#page=179)
 //! - [gimli crate documentation](https://docs.rs/gimli/latest/gimli/)
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
 use gimli::{
     self, BaseAddresses, CfaRule, DebugFrame, EhFrame, EhFrameHdr, Register, RegisterRule, UnwindContext, UnwindSection,
 };
+use once_cell::sync::OnceCell;
 
+use super::async_stack::{AwaitStep, MAX_SCAN_BYTES, StackSnapshot, is_executor_entry};
 use crate::error::{DebuggerError, Result};
-use crate::symbols::{BinaryImage, SymbolCache, SymbolFrame, Symbolication};
-use crate::types::{Address, Architecture, FrameId, FrameKind, FrameStatus, Registers, StackFrame, ThreadId};
+use crate::symbols::{BinaryImage, ImageId, SymbolCache, SymbolFrame, Symbolication};
+use crate::types::registers::dwarf;
+use crate::types::{
+    Address, Architecture, FrameId, FrameKind, FrameStatus, Registers, StackFrame, SymbolLanguage, SymbolName, ThreadId,
+};
 
 /// Minimal memory accessor required for stack unwinding.
 ///
@@ -53,6 +96,391 @@ pub trait MemoryAccess
     fn read_u64(&self, address: Address) -> Result<u64>;
 }
 
+/// Options controlling how a stack trace is captured.
+///
+/// ## Example
+///
+/// ```rust
+/// use ferros_core::symbols::unwind::UnwindOptions;
+///
+/// let options = UnwindOptions::new(32).with_diagnostics();
+/// assert_eq!(options.max_frames, 32);
+/// assert!(options.collect_diagnostics);
+/// assert!(!options.async_frames);
+/// assert!(!options.cfi_only);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnwindOptions
+{
+    /// Maximum number of frames (physical and inlined) to produce.
+    pub max_frames: usize,
+    /// Whether to record an [`UnwindDiagnostic`] for every physical frame.
+    pub collect_diagnostics: bool,
+    /// Whether to add [`FrameKind::Async`] frames for the futures a task is
+    /// suspended in. See [`StackUnwinder::unwind_with_options`].
+    pub async_frames: bool,
+    /// Whether to follow only CFI, failing with
+    /// [`CfiUnavailable`](DebuggerError::CfiUnavailable) where a frame's code
+    /// has none instead of falling back to frame pointers or stack scanning.
+    pub cfi_only: bool,
+}
+
+impl UnwindOptions
+{
+    /// Options for a plain unwind of at most `max_frames` frames.
+    #[must_use]
+    pub fn new(max_frames: usize) -> Self
+    {
+        Self {
+            max_frames,
+            collect_diagnostics: false,
+            async_frames: false,
+            cfi_only: false,
+        }
+    }
+
+    /// Request per-frame unwind diagnostics.
+    #[must_use]
+    pub fn with_diagnostics(mut self) -> Self
+    {
+        self.collect_diagnostics = true;
+        self
+    }
+
+    /// Request the logical async stack of a task blocked on in an executor frame.
+    #[must_use]
+    pub fn with_async_frames(mut self) -> Self
+    {
+        self.async_frames = true;
+        self
+    }
+
+    /// Unwind with CFI alone, for callers that can't use a guessed caller.
+    #[must_use]
+    pub fn with_cfi_only(mut self) -> Self
+    {
+        self.cfi_only = true;
+        self
+    }
+}
+
+impl Default for UnwindOptions
+{
+    fn default() -> Self
+    {
+        Self::new(64)
+    }
+}
+
+/// Repetitions of a frame sequence needed before it's treated as recursion.
+pub const MIN_CYCLE_REPEATS: usize = 4;
+
+/// Longest frame sequence, in physical frames, checked for repetition.
+///
+/// Covers direct recursion (period 1) and mutual recursion through a few helpers.
+pub const MAX_CYCLE_PERIOD: usize = 8;
+
+/// Upper bound on physical frames walked in a single unwind.
+///
+/// Only reachable while collapsing recursion, since every other step emits a
+/// frame and the walk already stops at `max_frames`.
+pub const MAX_UNWIND_STEPS: usize = 100_000;
+
+/// Frames produced by an unwind, plus any diagnostics that were requested.
+#[derive(Debug, Clone, Default)]
+pub struct StackTrace
+{
+    /// Frames ordered from most recent (top) to oldest (bottom).
+    pub frames: Vec<StackFrame>,
+    /// One entry per physical frame, in the same order. Empty unless requested.
+    pub diagnostics: Vec<UnwindDiagnostic>,
+    /// Recursive runs that were collapsed out of `frames`, top first.
+    pub recursion: Vec<RecursionCycle>,
+}
+
+impl StackTrace
+{
+    /// Find the diagnostic describing how the caller of `frame` was recovered.
+    ///
+    /// Inlined frames share the diagnostic of their physical frame.
+    #[must_use]
+    pub fn diagnostic_for(&self, frame: &StackFrame) -> Option<&UnwindDiagnostic>
+    {
+        find_diagnostic(&self.diagnostics, frame)
+    }
+}
+
+/// Look up the diagnostic for `frame` in a diagnostics list produced by
+/// [`StackUnwinder::unwind_with_diagnostics`].
+#[must_use]
+pub fn find_diagnostic<'d>(diagnostics: &'d [UnwindDiagnostic], frame: &StackFrame) -> Option<&'d UnwindDiagnostic>
+{
+    diagnostics.iter().find(|diag| diag.pc == frame.pc && diag.sp == frame.sp)
+}
+
+/// The frame the innermost function returns to, for running until it returns.
+///
+/// That is the frame after the innermost physical frame, so stepping out of
+/// an inlined function runs to the caller of the function it was inlined into.
+/// Async frames are skipped.
+///
+/// ## Errors
+///
+/// - `UnreliableReturnAddress`: `frames` has no caller, or the caller was only
+///   recovered heuristically ([`FrameStatus::Heuristic`]), so its PC may be garbage
+pub fn return_frame(frames: &[StackFrame]) -> Result<&StackFrame>
+{
+    let caller = frames
+        .iter()
+        .position(|frame| frame.kind == FrameKind::Physical)
+        .and_then(|innermost| frames[innermost + 1..].iter().find(|frame| !frame.kind.is_async()))
+        .ok_or_else(|| DebuggerError::UnreliableReturnAddress("the unwinder found no caller frame".to_string()))?;
+    if caller.status == FrameStatus::Heuristic {
+        return Err(DebuggerError::UnreliableReturnAddress(format!(
+            "the caller at {} was only recovered heuristically (no CFI or frame pointer)",
+            caller.pc
+        )));
+    }
+    Ok(caller)
+}
+
+/// A run of repeated frames that the unwinder collapsed to a single occurrence.
+///
+/// ## Example
+///
+/// ```rust
+/// use ferros_core::symbols::unwind::RecursionCycle;
+///
+/// let cycle = RecursionCycle {
+///     first_frame: 2,
+///     frame_count: 1,
+///     period: 1,
+///     repeats: 17_400,
+///     function: Some("app::recurse".to_string()),
+///     truncated: false,
+/// };
+/// assert_eq!(
+///     cycle.to_string(),
+///     "frame #2 (app::recurse) repeated ~17400 times"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecursionCycle
+{
+    /// Index in [`StackTrace::frames`] of the first frame of the kept occurrence.
+    pub first_frame: usize,
+    /// Number of frames (physical and inlined) in the kept occurrence.
+    pub frame_count: usize,
+    /// Number of physical frames in one repetition.
+    pub period: usize,
+    /// How many times the sequence occurred, including the kept one.
+    pub repeats: usize,
+    /// Function at the top of the cycle, if it could be symbolicated.
+    pub function: Option<String>,
+    /// The walk hit [`MAX_UNWIND_STEPS`] before the cycle ended, so `repeats`
+    /// is a lower bound.
+    pub truncated: bool,
+}
+
+impl fmt::Display for RecursionCycle
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        if self.frame_count > 1 {
+            write!(f, "frames #{}-#{}", self.first_frame, self.first_frame + self.frame_count - 1)?;
+        } else {
+            write!(f, "frame #{}", self.first_frame)?;
+        }
+        if let Some(function) = &self.function {
+            write!(f, " ({function})")?;
+        }
+        let bound = if self.truncated { "at least " } else { "~" };
+        write!(f, " repeated {bound}{} times", self.repeats)
+    }
+}
+
+/// A strategy the unwinder can use to recover the caller of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnwindStrategy
+{
+    /// DWARF CFI from `.eh_frame` (via `.eh_frame_hdr` when present) or `.debug_frame`.
+    Cfi,
+    /// Walking the saved frame-pointer chain (RBP/X29).
+    FramePointer,
+    /// Treating the word at SP as a return address.
+    StackScan,
+    /// Using LR (X30) as the return address (ARM64 only).
+    LinkRegister,
+}
+
+impl UnwindStrategy
+{
+    /// Strategies in the order the unwinder tries them.
+    pub const ORDER: [UnwindStrategy; 4] = [
+        UnwindStrategy::Cfi,
+        UnwindStrategy::FramePointer,
+        UnwindStrategy::StackScan,
+        UnwindStrategy::LinkRegister,
+    ];
+}
+
+impl fmt::Display for UnwindStrategy
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let label = match self {
+            UnwindStrategy::Cfi => "CFI",
+            UnwindStrategy::FramePointer => "frame pointer",
+            UnwindStrategy::StackScan => "stack scan",
+            UnwindStrategy::LinkRegister => "link register",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Why a single unwind strategy could not recover the caller frame.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UnwindFailure
+{
+    /// No loaded binary image contains the program counter.
+    #[error("no loaded image contains the PC")]
+    NoImageForPc,
+    /// The image has neither `.eh_frame` nor `.debug_frame`.
+    #[error("image has no .eh_frame or .debug_frame section")]
+    NoCfiSections,
+    /// CFI exists for the image but no FDE covers the program counter.
+    #[error("no FDE covers the PC")]
+    NoFdeForPc,
+    /// The CFA is described by a DWARF expression, which we don't evaluate.
+    #[error("CFA is defined by an unsupported DWARF expression")]
+    UnsupportedCfaRule,
+    /// The rule for a register uses an unsupported form (usually an expression).
+    #[error("unsupported CFI rule for DWARF register {register}")]
+    UnsupportedRegisterRule
+    {
+        /// DWARF register number.
+        register: u16,
+    },
+    /// A CFI rule referenced a register whose value we don't have.
+    #[error("DWARF register {register} required by CFI is unavailable")]
+    MissingRegister
+    {
+        /// DWARF register number.
+        register: u16,
+    },
+    /// CFI does not say where the return address was saved.
+    #[error("CFI does not describe where the return address was saved")]
+    ReturnAddressUnavailable,
+    /// The recovered return address was null.
+    #[error("recovered return address is null")]
+    NullReturnAddress,
+    /// The unwind tables could not be parsed.
+    #[error("malformed CFI while {context}: {details}")]
+    MalformedCfi
+    {
+        /// What the unwinder was doing.
+        context: &'static str,
+        /// Parser error message.
+        details: String,
+    },
+    /// Reading target memory failed.
+    #[error("memory read failed at {address}")]
+    MemoryRead
+    {
+        /// Address that could not be read.
+        address: Address,
+    },
+    /// The frame pointer register is zero.
+    #[error("frame pointer is zero")]
+    NoFramePointer,
+    /// The stack pointer register is zero.
+    #[error("stack pointer is zero")]
+    NoStackPointer,
+    /// The word at SP is zero or equal to the current PC.
+    #[error("no plausible return address at SP")]
+    NoReturnAddressOnStack,
+    /// LR is missing, zero, or equal to the current PC.
+    #[error("link register is empty or equals the PC")]
+    NoLinkRegister,
+    /// The strategy has no implementation for this architecture.
+    #[error("strategy is not available on this architecture")]
+    UnsupportedArchitecture,
+}
+
+/// A failed strategy attempt recorded while unwinding a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnwindAttempt
+{
+    /// Strategy that was tried.
+    pub strategy: UnwindStrategy,
+    /// Why it failed.
+    pub failure: UnwindFailure,
+}
+
+/// How the caller of one physical frame was (or wasn't) recovered.
+///
+/// The diagnostic for the last frame of a trace explains why the trace stopped:
+/// either every strategy failed (`resolved_by` is `None`) or the recovered caller
+/// PC was null, which marks the end of the stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnwindDiagnostic
+{
+    /// Physical depth of the frame (0 = innermost).
+    pub depth: u32,
+    /// Program counter of the frame.
+    pub pc: Address,
+    /// Stack pointer of the frame.
+    pub sp: Address,
+    /// Strategies that were tried and failed, in order.
+    pub failed: Vec<UnwindAttempt>,
+    /// Strategy that recovered the caller, if any.
+    pub resolved_by: Option<UnwindStrategy>,
+    /// PC of the recovered caller.
+    pub caller_pc: Option<Address>,
+}
+
+impl UnwindDiagnostic
+{
+    /// Whether the caller was recovered by something other than CFI.
+    #[must_use]
+    pub fn is_degraded(&self) -> bool
+    {
+        self.resolved_by != Some(UnwindStrategy::Cfi)
+    }
+
+    /// One-line description suitable for a status line or log entry.
+    #[must_use]
+    pub fn summary(&self) -> String
+    {
+        let outcome = match (self.resolved_by, self.caller_pc) {
+            (Some(_), Some(pc)) if pc == Address::ZERO => "end of stack".to_string(),
+            (Some(strategy), Some(pc)) => format!("caller {pc} via {strategy}"),
+            (Some(strategy), None) => format!("caller recovered via {strategy}"),
+            (None, _) => "unwinding stopped".to_string(),
+        };
+
+        if self.failed.is_empty() {
+            return format!("#{} {}: {outcome}", self.depth, self.pc);
+        }
+
+        let failures = self
+            .failed
+            .iter()
+            .map(|attempt| format!("{} failed: {}", attempt.strategy, attempt.failure))
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!("#{} {}: {outcome} ({failures})", self.depth, self.pc)
+    }
+}
+
+impl fmt::Display for UnwindDiagnostic
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(&self.summary())
+    }
+}
+
 /// CFI-driven stack unwinder with frame-pointer fallbacks.
 ///
 /// This unwinder attempts to build a call stack by:
@@ -76,6 +504,8 @@ pub struct StackUnwinder<'a, M>
     architecture: Architecture,
     symbols: &'a SymbolCache,
     memory: &'a M,
+    /// Scratch space for evaluating CFI, reused by every frame of the walk.
+    context: RefCell<UnwindContext<usize>>,
 }
 
 impl<'a, M: MemoryAccess> StackUnwinder<'a, M>
@@ -93,6 +523,7 @@ impl<'a, M: MemoryAccess> StackUnwinder<'a, M>
             architecture,
             symbols,
             memory,
+            context: RefCell::new(UnwindContext::new()),
         }
     }
 
@@ -121,50 +552,208 @@ impl<'a, M: MemoryAccess> StackUnwinder<'a, M>
     /// ## Frame Status
     ///
     /// - `Complete`: Frame was successfully unwound with CFI
-    /// - `Incomplete`: Frame was unwound using fallback heuristics
-    /// - `Error`: Frame unwinding failed
+    /// - `CfiFallback`: Frame was unwound by walking the frame-pointer chain
+    /// - `Heuristic`: Frame was recovered by stack scanning or the link register
     ///
     /// ## Errors
     ///
-    /// Returns an error if memory reads fail or CFI parsing fails.
+    /// Strategy failures (malformed CFI, unreadable stack) end the walk rather than
+    /// failing it; use [`unwind_with_diagnostics`](Self::unwind_with_diagnostics) to
+    /// find out why.
     pub fn unwind(&self, thread: ThreadId, regs: &Registers, max_frames: usize) -> Result<Vec<StackFrame>>
     {
+        let mut recursion = Vec::new();
+        self.walk(thread, regs, UnwindOptions::new(max_frames), None, &mut recursion)
+    }
+
+    /// Unwind the call stack and record why each frame was (or wasn't) recovered.
+    ///
+    /// Produces the same frames as [`unwind`](Self::unwind) together with one
+    /// [`UnwindDiagnostic`] per physical frame and the [`RecursionCycle`]s that
+    /// were collapsed.
+    ///
+    /// ## Errors
+    ///
+    /// Same as [`unwind`](Self::unwind).
+    pub fn unwind_with_diagnostics(&self, thread: ThreadId, regs: &Registers, max_frames: usize) -> Result<StackTrace>
+    {
+        self.unwind_with_options(thread, regs, UnwindOptions::new(max_frames).with_diagnostics())
+    }
+
+    /// Unwind according to `options`, always reporting collapsed recursion.
+    ///
+    /// Diagnostics are only collected when `options.collect_diagnostics` is set.
+    ///
+    /// With `options.async_frames`, the stack of every frame in an executor
+    /// entry point (`tokio::runtime`, `core::future`) is searched for a task
+    /// suspended in `async fn` state machines, and the awaited functions are
+    /// added above the frame the task was found in as [`FrameKind::Async`]
+    /// frames, the leaf future first. They count towards `max_frames`.
+    ///
+    /// ## Errors
+    ///
+    /// Same as [`unwind`](Self::unwind), and with `options.cfi_only`:
+    ///
+    /// - `CfiUnavailable`: A frame's code isn't in a loaded image, or its image
+    ///   has no CFI covering it
+    pub fn unwind_with_options(&self, thread: ThreadId, regs: &Registers, options: UnwindOptions) -> Result<StackTrace>
+    {
+        let mut diagnostics = Vec::new();
+        let mut recursion = Vec::new();
+        let sink = options.collect_diagnostics.then_some(&mut diagnostics);
+        let frames = self.walk(thread, regs, options, sink, &mut recursion)?;
+        let mut trace = StackTrace {
+            frames,
+            diagnostics,
+            recursion,
+        };
+        if options.async_frames
+            && let Some((executor, chain)) = self.find_suspended_task(&trace.frames)
+        {
+            insert_async_frames(&mut trace, executor, &chain, options.max_frames);
+        }
+        Ok(trace)
+    }
+
+    /// The longest chain of awaits found in the stack of an executor frame,
+    /// with the index of that frame.
+    fn find_suspended_task(&self, frames: &[StackFrame]) -> Option<(usize, Vec<AwaitStep>)>
+    {
+        let physical: Vec<usize> = (0..frames.len())
+            .filter(|&index| frames[index].kind == FrameKind::Physical)
+            .collect();
+        let mut best: Option<(usize, Vec<AwaitStep>)> = None;
+        for pair in physical.windows(2) {
+            let (index, caller) = (pair[0], pair[1]);
+            let frame = &frames[index];
+            let in_executor = frames[first_of_group(frames, index)..=index]
+                .iter()
+                .filter_map(|frame| frame.symbol.as_ref())
+                .any(|symbol| is_executor_entry(symbol.display_name()));
+            if !in_executor {
+                continue;
+            }
+            let Some(image) = self.symbols.image_for_address(frame.pc) else {
+                continue;
+            };
+            let machines = match image.state_machines() {
+                Ok(machines) if !machines.is_empty() => machines,
+                Ok(_) => continue,
+                Err(err) => {
+                    tracing::debug!("No async state machines for {}: {err}", image.path().display());
+                    continue;
+                }
+            };
+            let start = frame.sp.value();
+            let end = frames[caller].sp.value().min(start.saturating_add(MAX_SCAN_BYTES));
+            let snapshot = StackSnapshot::read(self.memory, start, end);
+            if let Some(chain) = machines.find_in(start, end, &|address, size| snapshot.value(address, size))
+                && best.as_ref().is_none_or(|(_, best)| chain.len() > best.len())
+            {
+                best = Some((index, chain));
+            }
+        }
+        best
+    }
+
+    /// Shared frame walk. Diagnostics are only built when a sink is supplied.
+    fn walk(
+        &self,
+        thread: ThreadId,
+        regs: &Registers,
+        options: UnwindOptions,
+        mut diagnostics: Option<&mut Vec<UnwindDiagnostic>>,
+        recursion: &mut Vec<RecursionCycle>,
+    ) -> Result<Vec<StackFrame>>
+    {
+        let max_frames = options.max_frames;
         let mut frames = Vec::new();
         let mut cursor = regs.clone();
         let mut depth: u32 = 0;
         let mut status = FrameStatus::Complete;
         let mut return_address = None;
+        let mut steps: Vec<WalkStep> = Vec::new();
+        let mut collapsing: Option<Collapse> = None;
+
+        while (depth as usize) < MAX_UNWIND_STEPS && cursor.pc != Address::ZERO {
+            if let Some(active) = collapsing.as_mut() {
+                if cursor.pc == active.pcs[active.position % active.pcs.len()] {
+                    active.position += 1;
+                } else if let Some(done) = collapsing.take() {
+                    recursion.push(done.finish(&frames, false));
+                }
+            }
+            if collapsing.is_some() {
+                match self.recover_caller(&cursor, options.cfi_only, &mut Vec::new(), false)? {
+                    Some((_, outcome)) => {
+                        cursor = outcome.next;
+                        return_address = outcome.return_address;
+                        status = outcome.status;
+                        depth += 1;
+                        continue;
+                    }
+                    None => break,
+                }
+            }
+
+            steps.push(WalkStep {
+                pc: cursor.pc,
+                sp: cursor.sp,
+                frames: frames.len(),
+                diagnostics: diagnostics.as_deref().map_or(0, Vec::len),
+            });
 
-        while depth < max_frames as u32 && cursor.pc != Address::ZERO {
             let symbolication = self.symbols.symbolicate(cursor.pc);
             // Only log if we have an image for this address but still can't symbolicate it
             // (this indicates a real problem, not just a missing system library)
-            if symbolication.is_none() {
-                if self.symbols.image_for_address(cursor.pc).is_some() {
-                    use tracing::debug;
-                    debug!(
-                        "No symbolication for address 0x{:x} (image loaded but symbolication failed)",
-                        cursor.pc.value()
-                    );
-                }
-                // Otherwise, it's expected - address is in a system library we haven't loaded
+            if symbolication.is_none() && self.symbols.image_for_address(cursor.pc).is_some() {
+                use tracing::debug;
+                debug!(
+                    "No symbolication for address 0x{:x} (image loaded but symbolication failed)",
+                    cursor.pc.value()
+                );
             }
+            // Otherwise, it's expected - address is in a system library we haven't loaded
             append_logical_frames(&mut frames, thread, depth, &cursor, &symbolication, status, return_address);
 
             if frames.len() >= max_frames {
                 break;
             }
 
-            let outcome = self
-                .unwind_once(&cursor)?
-                .or_else(|| self.frame_pointer_fallback(&cursor).transpose().ok().flatten())
-                .or_else(|| self.stack_scan_fallback(&cursor).transpose().ok().flatten())
-                .or_else(|| self.link_register_fallback(&cursor).transpose().ok().flatten());
+            let mut failed = Vec::new();
+            let resolved = self.recover_caller(&cursor, options.cfi_only, &mut failed, diagnostics.is_some())?;
 
-            let Some(outcome) = outcome else {
+            if let Some(sink) = diagnostics.as_deref_mut() {
+                sink.push(UnwindDiagnostic {
+                    depth,
+                    pc: cursor.pc,
+                    sp: cursor.sp,
+                    failed,
+                    resolved_by: resolved.as_ref().map(|(strategy, _)| *strategy),
+                    caller_pc: resolved.as_ref().map(|(_, step)| step.next.pc),
+                });
+            }
+
+            let Some((_, outcome)) = resolved else {
                 break;
             };
 
+            if let Some(period) = detect_cycle(&steps) {
+                // Keep the topmost occurrence and drop the other repeats already emitted
+                let first = steps.len() - period * MIN_CYCLE_REPEATS;
+                let kept_end = &steps[first + period];
+                frames.truncate(kept_end.frames);
+                if let Some(sink) = diagnostics.as_deref_mut() {
+                    sink.truncate(kept_end.diagnostics);
+                }
+                collapsing = Some(Collapse {
+                    pcs: steps[first..first + period].iter().map(|step| step.pc).collect(),
+                    first_frame: steps[first].frames,
+                    position: 0,
+                });
+                steps.truncate(first + period);
+            }
+
             cursor = outcome.next;
             return_address = outcome.return_address;
             status = outcome.status;
@@ -175,52 +764,132 @@ impl<'a, M: MemoryAccess> StackUnwinder<'a, M>
             }
         }
 
+        if let Some(active) = collapsing {
+            recursion.push(active.finish(&frames, depth as usize >= MAX_UNWIND_STEPS));
+        }
+
         Ok(frames)
     }
 
+    /// Recover the caller of the frame at `regs`, with CFI alone when `cfi_only` is set.
+    ///
+    /// Failures are appended to `failed` only when `record` is set.
+    ///
+    /// ## Errors
+    ///
+    /// - `CfiUnavailable`: `cfi_only` is set and no CFI covers `regs.pc`
+    fn recover_caller(
+        &self,
+        regs: &Registers,
+        cfi_only: bool,
+        failed: &mut Vec<UnwindAttempt>,
+        record: bool,
+    ) -> Result<Option<(UnwindStrategy, UnwindStep)>>
+    {
+        if !cfi_only {
+            return Ok(self.attempt_all(regs, failed, record));
+        }
+        match self.unwind_once(regs) {
+            Ok(step) => Ok(Some((UnwindStrategy::Cfi, step))),
+            Err(UnwindFailure::NoImageForPc | UnwindFailure::NoCfiSections | UnwindFailure::NoFdeForPc) => {
+                Err(DebuggerError::CfiUnavailable)
+            }
+            Err(failure) => {
+                if record {
+                    failed.push(UnwindAttempt {
+                        strategy: UnwindStrategy::Cfi,
+                        failure,
+                    });
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Try every strategy in order until one recovers the caller.
+    ///
+    /// Failures are appended to `failed` only when `record` is set.
+    fn attempt_all(
+        &self,
+        regs: &Registers,
+        failed: &mut Vec<UnwindAttempt>,
+        record: bool,
+    ) -> Option<(UnwindStrategy, UnwindStep)>
+    {
+        for strategy in UnwindStrategy::ORDER {
+            match self.attempt(strategy, regs) {
+                Ok(step) => return Some((strategy, step)),
+                Err(failure) => {
+                    if record {
+                        failed.push(UnwindAttempt { strategy, failure });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Run a single strategy against the current frame.
+    fn attempt(&self, strategy: UnwindStrategy, regs: &Registers) -> StepResult
+    {
+        match strategy {
+            UnwindStrategy::Cfi => self.unwind_once(regs),
+            UnwindStrategy::FramePointer => self.frame_pointer_fallback(regs),
+            UnwindStrategy::StackScan => self.stack_scan_fallback(regs),
+            UnwindStrategy::LinkRegister => self.link_register_fallback(regs),
+        }
+    }
+
     /// Attempt a single unwind step using available DWARF metadata for the image that
     /// contains the supplied program counter.
-    fn unwind_once(&self, regs: &Registers) -> Result<Option<UnwindStep>>
+    fn unwind_once(&self, regs: &Registers) -> StepResult
     {
         let Some(image) = self.symbols.image_for_address(regs.pc) else {
-            return Ok(None);
+            return Err(UnwindFailure::NoImageForPc);
         };
-        if let Some(step) = self.try_unwind_eh_frame(&image, regs)? {
-            return Ok(Some(step));
-        }
-        if let Some(step) = self.try_unwind_debug_frame(&image, regs)? {
-            return Ok(Some(step));
+        let eh_failure = match self.try_unwind_eh_frame(&image, regs) {
+            Ok(step) => return Ok(step),
+            Err(failure) => failure,
+        };
+        match self.try_unwind_debug_frame(&image, regs) {
+            Ok(step) => Ok(step),
+            // Report whichever section actually had something to say.
+            Err(df_failure) if eh_failure == UnwindFailure::NoCfiSections => Err(df_failure),
+            Err(_) => Err(eh_failure),
         }
-        Ok(None)
     }
 
     /// Evaluate a DWARF register rule using the provided register state and computed
     /// Canonical Frame Address.
-    fn evaluate_rule(&self, rule: &RegisterRule<usize>, regs: &Registers, cfa: u64) -> Result<u64>
+    fn evaluate_rule(
+        &self,
+        register: Register,
+        rule: &RegisterRule<usize>,
+        regs: &Registers,
+        cfa: u64,
+    ) -> std::result::Result<u64, UnwindFailure>
     {
         match rule {
-            RegisterRule::Undefined | RegisterRule::SameValue => {
-                Err(DebuggerError::InvalidArgument("register rule unavailable".into()))
-            }
+            RegisterRule::Undefined | RegisterRule::SameValue => Err(UnwindFailure::ReturnAddressUnavailable),
             RegisterRule::Offset(offset) => {
                 let addr = Address::from((cfa as i64 + *offset) as u64);
-                self.memory.read_u64(addr)
+                self.memory
+                    .read_u64(addr)
+                    .map_err(|_| UnwindFailure::MemoryRead { address: addr })
             }
             RegisterRule::ValOffset(offset) => Ok((cfa as i64 + *offset) as u64),
-            RegisterRule::Register(register) => read_register_value(self.architecture, regs, *register)
-                .ok_or_else(|| DebuggerError::InvalidArgument("register redirect missing value".into())),
-            _ => Err(DebuggerError::InvalidArgument(
-                "unsupported CFI expression encountered".into(),
-            )),
+            RegisterRule::Register(source) => read_register_value(self.architecture, regs, *source)
+                .ok_or(UnwindFailure::MissingRegister { register: source.0 }),
+            _ => Err(UnwindFailure::UnsupportedRegisterRule { register: register.0 }),
         }
     }
 
     /// Attempt to unwind the current frame using `.eh_frame` data (and header table
     /// when available) from the binary image that owns the PC.
-    fn try_unwind_eh_frame(&self, image: &BinaryImage, regs: &Registers) -> Result<Option<UnwindStep>>
+    fn try_unwind_eh_frame(&self, image: &BinaryImage, regs: &Registers) -> StepResult
     {
         let Some((eh_vmaddr, eh_bytes)) = image.eh_frame_section() else {
-            return Ok(None);
+            return Err(UnwindFailure::NoCfiSections);
         };
 
         let (text_base, _) = image.runtime_range();
@@ -236,181 +905,172 @@ impl<'a, M: MemoryAccess> StackUnwinder<'a, M>
 
         if let Some((_hdr_vmaddr, hdr_bytes)) = image.eh_frame_hdr_section() {
             let header = EhFrameHdr::new(hdr_bytes, image.endian());
-            if let Some(step) = self.unwind_with_eh_frame_hdr(&eh_frame, header, bases.clone(), regs)? {
-                return Ok(Some(step));
+            if let Ok(step) = self.unwind_with_eh_frame_hdr(&eh_frame, header, &bases, regs) {
+                return Ok(step);
             }
         }
 
-        self.unwind_with_cfi(&eh_frame, bases, regs)
+        let index = self
+            .symbols
+            .unwind_tables()
+            .eh_frame_index(image.id(), || FdeIndex::build(&eh_frame, &bases));
+        self.unwind_with_cfi(&eh_frame, &bases, &index, regs.pc.value(), regs)
     }
 
     /// Attempt to unwind the current frame using `.debug_frame` data when the runtime
     /// `.eh_frame` path is unavailable.
-    fn try_unwind_debug_frame(&self, image: &BinaryImage, regs: &Registers) -> Result<Option<UnwindStep>>
+    fn try_unwind_debug_frame(&self, image: &BinaryImage, regs: &Registers) -> StepResult
     {
-        let Some((df_vmaddr, df_bytes)) = image.debug_frame_section() else {
-            return Ok(None);
+        let Some((_, df_bytes)) = image.debug_frame_section() else {
+            return Err(UnwindFailure::NoCfiSections);
         };
 
-        let (text_base, _) = image.runtime_range();
-        // In gimli 0.32, debug_frame uses the same base address mechanism
-        let bases = BaseAddresses::default()
-            .set_text(text_base)
-            .set_eh_frame(image.relocated_address(df_vmaddr));
+        // `.debug_frame` addresses are absolute link-time addresses, so the
+        // PC is looked up unslid and no section bases are needed
+        let bases = BaseAddresses::default();
+        let Some(file_pc) = image.file_address(regs.pc) else {
+            return Err(UnwindFailure::NoFdeForPc);
+        };
 
         let mut debug_frame = DebugFrame::new(df_bytes, image.endian());
         debug_frame.set_address_size(self.architecture.pointer_size_bytes());
-        self.unwind_with_cfi(&debug_frame, bases, regs)
+        let index = self
+            .symbols
+            .unwind_tables()
+            .debug_frame_index(image.id(), || FdeIndex::build(&debug_frame, &bases));
+        self.unwind_with_cfi(&debug_frame, &bases, &index, file_pc, regs)
     }
 
     /// Translate a resolved DWARF unwind row into the `Registers` describing the next
-    /// frame. Returns `Ok(None)` when the row cannot produce a valid next PC.
-    fn build_step_from_row(&self, regs: &Registers, row: &gimli::UnwindTableRow<usize>) -> Result<Option<UnwindStep>>
+    /// frame.
+    ///
+    /// Every register the row has a rule for is restored, so a caller whose
+    /// CFA is based on the frame pointer finds the value it saved rather than
+    /// the callee's. Registers without a rule, or marked undefined or
+    /// same-value, keep the callee's value.
+    fn build_step_from_row(&self, regs: &Registers, row: &gimli::UnwindTableRow<usize>) -> StepResult
     {
         let cfa = match row.cfa() {
             CfaRule::RegisterAndOffset { register, offset } => {
                 let base = read_register_value(self.architecture, regs, *register)
-                    .ok_or_else(|| DebuggerError::InvalidArgument("missing register for CFA evaluation".to_string()))?;
+                    .ok_or(UnwindFailure::MissingRegister { register: register.0 })?;
                 (base as i64 + offset) as u64
             }
-            _ => return Ok(None),
+            _ => return Err(UnwindFailure::UnsupportedCfaRule),
         };
 
         let return_reg = return_register(self.architecture);
         let rule = row.register(return_reg);
-        let (pc, status) = match rule {
-            RegisterRule::Undefined | RegisterRule::SameValue => (Address::ZERO, FrameStatus::CfiFallback),
-            _ => match self.evaluate_rule(&rule, regs, cfa) {
-                Ok(value) => (Address::from(value), FrameStatus::Complete),
-                Err(_) => (Address::ZERO, FrameStatus::CfiFallback),
-            },
-        };
-
+        let pc = Address::from(self.evaluate_rule(return_reg, &rule, regs, cfa)?);
         if pc == Address::ZERO {
-            return Ok(None);
+            return Err(UnwindFailure::NullReturnAddress);
         }
 
         let mut next = regs.clone();
+        for (register, rule) in row.registers() {
+            if *register == return_reg || matches!(rule, RegisterRule::Undefined | RegisterRule::SameValue) {
+                continue;
+            }
+            let value = self.evaluate_rule(*register, rule, regs, cfa)?;
+            if let Some(id) = dwarf::dwarf_to_register_id(self.architecture, register.0) {
+                next.set_in(self.architecture, id, value);
+            }
+        }
         next.sp = Address::from(cfa);
         next.pc = pc;
 
-        Ok(Some(UnwindStep {
+        Ok(UnwindStep {
             next,
             return_address: Some(pc),
-            status,
-        }))
+            status: FrameStatus::Complete,
+        })
     }
 
     /// Walk the frame-pointer chain (RBP/X29) when structured unwind info is missing.
-    fn frame_pointer_fallback(&self, regs: &Registers) -> Option<Result<UnwindStep>>
+    fn frame_pointer_fallback(&self, regs: &Registers) -> StepResult
     {
         match self.architecture {
-            Architecture::Arm64 => {
-                let fp = regs.fp;
-                if fp == Address::ZERO {
-                    return None;
-                }
-
-                let saved_fp = match self.memory.read_u64(fp) {
-                    Ok(value) => value,
-                    Err(err) => return Some(Err(err)),
-                };
-                let saved_lr = match self.memory.read_u64(Address::from(fp.value() + 8)) {
-                    Ok(value) => value,
-                    Err(err) => return Some(Err(err)),
-                };
-
-                let mut next = regs.clone();
-                next.fp = Address::from(saved_fp);
-                next.sp = Address::from(fp.value() + 16);
-                next.pc = Address::from(saved_lr);
-
-                Some(Ok(UnwindStep {
-                    next,
-                    return_address: Some(Address::from(saved_lr)),
-                    status: FrameStatus::CfiFallback,
-                }))
-            }
-            Architecture::X86_64 => {
+            Architecture::Arm64 | Architecture::X86_64 => {
+                // Both ABIs lay out [saved FP, return address] at the frame pointer.
                 let fp = regs.fp;
                 if fp == Address::ZERO {
-                    return None;
+                    return Err(UnwindFailure::NoFramePointer);
                 }
 
-                let saved_fp = match self.memory.read_u64(fp) {
-                    Ok(value) => value,
-                    Err(err) => return Some(Err(err)),
-                };
-                let return_addr = match self.memory.read_u64(Address::from(fp.value() + 8)) {
-                    Ok(value) => value,
-                    Err(err) => return Some(Err(err)),
-                };
+                let saved_fp = self.read_stack_word(fp)?;
+                let return_addr = self.read_stack_word(Address::from(fp.value() + 8))?;
 
                 let mut next = regs.clone();
                 next.fp = Address::from(saved_fp);
                 next.sp = Address::from(fp.value() + 16);
                 next.pc = Address::from(return_addr);
 
-                Some(Ok(UnwindStep {
+                Ok(UnwindStep {
                     next,
                     return_address: Some(Address::from(return_addr)),
                     status: FrameStatus::CfiFallback,
-                }))
+                })
             }
-            _ => None,
+            Architecture::Unknown(_) => Err(UnwindFailure::UnsupportedArchitecture),
         }
     }
 
     /// Heuristically scan the stack for a plausible return address when both CFI and
     /// frame-pointer strategies fail.
-    fn stack_scan_fallback(&self, regs: &Registers) -> Option<Result<UnwindStep>>
+    fn stack_scan_fallback(&self, regs: &Registers) -> StepResult
     {
         if regs.sp == Address::ZERO {
-            return None;
+            return Err(UnwindFailure::NoStackPointer);
         }
 
         match self.architecture {
             Architecture::Arm64 | Architecture::X86_64 => {
-                let return_addr = match self.memory.read_u64(regs.sp) {
-                    Ok(value) if value != 0 && value != regs.pc.value() => value,
-                    Ok(_) => return None,
-                    Err(err) => return Some(Err(err)),
+                let return_addr = match self.read_stack_word(regs.sp)? {
+                    value if value != 0 && value != regs.pc.value() => value,
+                    _ => return Err(UnwindFailure::NoReturnAddressOnStack),
                 };
 
                 let mut next = regs.clone();
                 next.sp = Address::from(regs.sp.value() + 8);
                 next.pc = Address::from(return_addr);
 
-                Some(Ok(UnwindStep {
+                Ok(UnwindStep {
                     next,
                     return_address: Some(Address::from(return_addr)),
                     status: FrameStatus::Heuristic,
-                }))
+                })
             }
-            _ => None,
+            Architecture::Unknown(_) => Err(UnwindFailure::UnsupportedArchitecture),
         }
     }
 
     /// ARM64-only heuristic that treats the link register as the next return address.
-    fn link_register_fallback(&self, regs: &Registers) -> Option<Result<UnwindStep>>
+    fn link_register_fallback(&self, regs: &Registers) -> StepResult
     {
         if self.architecture != Architecture::Arm64 {
-            return None;
+            return Err(UnwindFailure::UnsupportedArchitecture);
         }
 
-        let lr = regs.general.get(30).copied()?;
-        if lr == 0 || lr == regs.pc.value() {
-            return None;
-        }
+        let lr = match regs.general.get(30).copied() {
+            Some(lr) if lr != 0 && lr != regs.pc.value() => lr,
+            _ => return Err(UnwindFailure::NoLinkRegister),
+        };
 
         let mut next = regs.clone();
         next.pc = Address::from(lr);
 
-        Some(Ok(UnwindStep {
+        Ok(UnwindStep {
             next,
             return_address: Some(Address::from(lr)),
             status: FrameStatus::Heuristic,
-        }))
+        })
+    }
+
+    fn read_stack_word(&self, address: Address) -> std::result::Result<u64, UnwindFailure>
+    {
+        self.memory
+            .read_u64(address)
+            .map_err(|_| UnwindFailure::MemoryRead { address })
     }
 }
 
@@ -422,89 +1082,227 @@ impl<'a, M: MemoryAccess> StackUnwinder<'a, M>
         &self,
         eh_frame: &EhFrame<R>,
         header: EhFrameHdr<R>,
-        bases: BaseAddresses,
+        bases: &BaseAddresses,
         regs: &Registers,
-    ) -> Result<Option<UnwindStep>>
+    ) -> StepResult
     where
         R: gimli::Reader<Offset = usize>,
     {
         let parsed = header
-            .parse(&bases, self.architecture.pointer_size_bytes())
-            .map_err(|err| map_gimli_error("parsing .eh_frame_hdr", err))?;
+            .parse(bases, self.architecture.pointer_size_bytes())
+            .map_err(|err| malformed("parsing .eh_frame_hdr", err))?;
         let Some(table) = parsed.table() else {
-            return Ok(None);
+            return Err(UnwindFailure::NoFdeForPc);
         };
 
         let pc = regs.pc.value();
-        let pointer = table
-            .lookup(pc, &bases)
-            .map_err(|err| map_gimli_error("looking up FDE in .eh_frame_hdr", err))?;
+        let pointer = match table.lookup(pc, bases) {
+            Ok(pointer) => pointer,
+            Err(gimli::Error::NoUnwindInfoForAddress) => return Err(UnwindFailure::NoFdeForPc),
+            Err(err) => return Err(malformed("looking up FDE in .eh_frame_hdr", err)),
+        };
         let offset = table
             .pointer_to_offset(pointer)
-            .map_err(|err| map_gimli_error("resolving FDE pointer", err))?;
+            .map_err(|err| malformed("resolving FDE pointer", err))?;
 
         let partial = eh_frame
-            .partial_fde_from_offset(&bases, offset)
-            .map_err(|err| map_gimli_error("loading FDE from .eh_frame_hdr", err))?;
+            .partial_fde_from_offset(bases, offset)
+            .map_err(|err| malformed("loading FDE from .eh_frame_hdr", err))?;
         let fde = partial
             .parse(|section, base_addresses, cie_offset| section.cie_from_offset(base_addresses, cie_offset))
-            .map_err(|err| map_gimli_error("parsing frame description entry", err))?;
-
-        if !fde.contains(pc) {
-            return Ok(None);
-        }
+            .map_err(|err| malformed("parsing frame description entry", err))?;
 
-        let mut ctx = UnwindContext::<usize>::new();
-        match fde.unwind_info_for_address(eh_frame, &bases, &mut ctx, pc) {
-            Ok(row) => self.build_step_from_row(regs, row),
-            Err(gimli::Error::NoUnwindInfoForAddress) => Ok(None),
-            Err(err) => Err(map_gimli_error("evaluating unwind row", err)),
-        }
+        self.unwind_with_fde(eh_frame, bases, &fde, pc, regs)
     }
 
-    /// Iterate through an unwind section, parsing each FDE until one contains the
-    /// current PC, then evaluate it to produce the next frame.
+    /// Find the FDE covering `lookup_pc` in the image's cached index of an unwind
+    /// section, then evaluate it to produce the next frame.
+    ///
+    /// `lookup_pc` is `regs.pc` for `.eh_frame` and its file address for
+    /// `.debug_frame`, whose FDEs carry link-time addresses.
     fn unwind_with_cfi<R, Section>(
         &self,
         section: &Section,
-        bases: BaseAddresses,
+        bases: &BaseAddresses,
+        index: &FdeIndex,
+        lookup_pc: u64,
         regs: &Registers,
-    ) -> Result<Option<UnwindStep>>
+    ) -> StepResult
     where
         R: gimli::Reader<Offset = usize>,
         Section: gimli::UnwindSection<R>,
     {
-        let pc = regs.pc.value();
-        let mut entries = section.entries(&bases);
-        let mut ctx = UnwindContext::<usize>::new();
-        while let Some(entry) = entries.next().map_err(|err| map_gimli_error("reading unwind entry", err))? {
+        let Some(offset) = index.lookup(lookup_pc) else {
+            return Err(UnwindFailure::NoFdeForPc);
+        };
+        let fde = section
+            .fde_from_offset(
+                bases,
+                Section::Offset::from(offset),
+                |unwind_section, base_addresses, cie_offset| unwind_section.cie_from_offset(base_addresses, cie_offset),
+            )
+            .map_err(|err| malformed("parsing frame description entry", err))?;
+
+        self.unwind_with_fde(section, bases, &fde, lookup_pc, regs)
+    }
+
+    /// Evaluate the unwind row an FDE gives for `lookup_pc` and build the next frame from it.
+    fn unwind_with_fde<R, Section>(
+        &self,
+        section: &Section,
+        bases: &BaseAddresses,
+        fde: &gimli::FrameDescriptionEntry<R>,
+        lookup_pc: u64,
+        regs: &Registers,
+    ) -> StepResult
+    where
+        R: gimli::Reader<Offset = usize>,
+        Section: gimli::UnwindSection<R>,
+    {
+        if !fde.contains(lookup_pc) {
+            return Err(UnwindFailure::NoFdeForPc);
+        }
+
+        let mut ctx = self.context.borrow_mut();
+        match fde.unwind_info_for_address(section, bases, &mut ctx, lookup_pc) {
+            Ok(row) => self.build_step_from_row(regs, row),
+            Err(gimli::Error::NoUnwindInfoForAddress) => Err(UnwindFailure::NoFdeForPc),
+            Err(err) => Err(malformed("evaluating unwind row", err)),
+        }
+    }
+}
+
+/// Sorted FDE indexes of the images stacks have been unwound through, keyed by [`ImageId`].
+///
+/// Lives in the [`SymbolCache`] beside the images it indexes, so every
+/// [`StackUnwinder`] over that cache shares it. A section is indexed the first
+/// time a PC in its image needs it and kept until the image is invalidated
+/// with [`SymbolCache::invalidate_image`]. An image reloaded at another base
+/// has another `ImageId`, so it never finds the old image's index.
+#[derive(Debug, Default)]
+pub struct UnwindTableCache
+{
+    tables: RwLock<HashMap<ImageId, Arc<UnwindTables>>>,
+    builds: AtomicUsize,
+}
+
+/// The indexes of one image's unwind sections, each built on first use.
+#[derive(Debug, Default)]
+struct UnwindTables
+{
+    eh_frame: OnceCell<Arc<FdeIndex>>,
+    debug_frame: OnceCell<Arc<FdeIndex>>,
+}
+
+impl UnwindTableCache
+{
+    /// How many section indexes have been built, including rebuilds after an invalidation.
+    #[must_use]
+    pub fn builds(&self) -> usize
+    {
+        self.builds.load(Ordering::Relaxed)
+    }
+
+    /// Index of `image`'s `.eh_frame`, built with `build` if this is its first use.
+    pub(crate) fn eh_frame_index(&self, image: ImageId, build: impl FnOnce() -> FdeIndex) -> Arc<FdeIndex>
+    {
+        self.tables(image).eh_frame.get_or_init(|| self.build(build)).clone()
+    }
+
+    /// Index of `image`'s `.debug_frame`, built with `build` if this is its first use.
+    pub(crate) fn debug_frame_index(&self, image: ImageId, build: impl FnOnce() -> FdeIndex) -> Arc<FdeIndex>
+    {
+        self.tables(image).debug_frame.get_or_init(|| self.build(build)).clone()
+    }
+
+    /// Drop `image`'s indexes. Unwinds already holding one keep using it.
+    pub(crate) fn invalidate(&self, image: ImageId)
+    {
+        self.tables.write().unwrap().remove(&image);
+    }
+
+    /// Drop every image's indexes.
+    pub(crate) fn clear(&self)
+    {
+        self.tables.write().unwrap().clear();
+    }
+
+    fn tables(&self, image: ImageId) -> Arc<UnwindTables>
+    {
+        if let Some(tables) = self.tables.read().unwrap().get(&image) {
+            return tables.clone();
+        }
+        self.tables.write().unwrap().entry(image).or_default().clone()
+    }
+
+    fn build(&self, build: impl FnOnce() -> FdeIndex) -> Arc<FdeIndex>
+    {
+        self.builds.fetch_add(1, Ordering::Relaxed);
+        Arc::new(build())
+    }
+}
+
+/// The FDEs of one unwind section, sorted by start address.
+///
+/// Built once per image and section by the [`UnwindTableCache`], so a PC
+/// without an `.eh_frame_hdr` entry costs a binary search rather than a parse
+/// of every entry in the section. Entries that fail to parse are left out; a
+/// section that can't be walked to the end keeps the FDEs read before the error.
+#[derive(Debug, Default)]
+pub(crate) struct FdeIndex
+{
+    /// `(start, end, offset)` of each FDE, by start address.
+    entries: Vec<(u64, u64, usize)>,
+}
+
+impl FdeIndex
+{
+    /// Index every FDE in `section`.
+    pub(crate) fn build<R, Section>(section: &Section, bases: &BaseAddresses) -> Self
+    where
+        R: gimli::Reader<Offset = usize>,
+        Section: gimli::UnwindSection<R>,
+    {
+        let mut entries = Vec::new();
+        let mut iter = section.entries(bases);
+        loop {
+            let entry = match iter.next() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(err) => {
+                    tracing::debug!("Stopped indexing unwind entries: {err}");
+                    break;
+                }
+            };
             let gimli::CieOrFde::Fde(partial) = entry else {
                 continue;
             };
-            let fde = partial
-                .parse(|unwind_section, base_addresses, cie_offset| {
-                    unwind_section.cie_from_offset(base_addresses, cie_offset)
-                })
-                .map_err(|err| map_gimli_error("parsing frame description entry", err))?;
-            if !fde.contains(pc) {
-                continue;
-            }
-
-            match fde.unwind_info_for_address(section, &bases, &mut ctx, pc) {
-                Ok(row) => {
-                    if let Some(step) = self.build_step_from_row(regs, row)? {
-                        return Ok(Some(step));
-                    }
-                }
-                Err(gimli::Error::NoUnwindInfoForAddress) => continue,
-                Err(err) => return Err(map_gimli_error("evaluating unwind row", err)),
+            let offset = partial.offset();
+            match partial.parse(|unwind_section, base_addresses, cie_offset| {
+                unwind_section.cie_from_offset(base_addresses, cie_offset)
+            }) {
+                Ok(fde) if fde.len() > 0 => entries.push((fde.initial_address(), fde.end_address(), offset)),
+                Ok(_) => {}
+                Err(err) => tracing::debug!("Skipping unparseable FDE at offset {offset:#x}: {err}"),
             }
         }
+        entries.sort_unstable_by_key(|&(start, _, _)| start);
+        Self { entries }
+    }
 
-        Ok(None)
+    /// Section offset of the FDE covering `pc`.
+    fn lookup(&self, pc: u64) -> Option<usize>
+    {
+        let after = self.entries.partition_point(|&(start, _, _)| start <= pc);
+        let &(_, end, offset) = self.entries.get(after.checked_sub(1)?)?;
+        (pc < end).then_some(offset)
     }
 }
 
+/// Outcome of a single unwind strategy.
+type StepResult = std::result::Result<UnwindStep, UnwindFailure>;
+
+#[derive(Debug)]
 struct UnwindStep
 {
     next: Registers,
@@ -512,6 +1310,72 @@ struct UnwindStep
     status: FrameStatus,
 }
 
+/// Bookkeeping for one physical frame emitted by the walk.
+struct WalkStep
+{
+    pc: Address,
+    sp: Address,
+    /// Length of the frame list before this step's frames were appended.
+    frames: usize,
+    /// Length of the diagnostics list before this step's entry was pushed.
+    diagnostics: usize,
+}
+
+/// A recursion cycle currently being skipped over.
+struct Collapse
+{
+    /// PCs of one occurrence, top first.
+    pcs: Vec<Address>,
+    /// Index of the kept occurrence's first frame.
+    first_frame: usize,
+    /// Physical frames skipped since the kept occurrence was emitted.
+    position: usize,
+}
+
+impl Collapse
+{
+    fn finish(self, frames: &[StackFrame], truncated: bool) -> RecursionCycle
+    {
+        let function = frames
+            .get(self.first_frame)
+            .and_then(|frame| frame.symbol.as_ref())
+            .map(ToString::to_string);
+        RecursionCycle {
+            first_frame: self.first_frame,
+            frame_count: frames.len().saturating_sub(self.first_frame),
+            period: self.pcs.len(),
+            repeats: MIN_CYCLE_REPEATS + self.position / self.pcs.len(),
+            function,
+            truncated,
+        }
+    }
+}
+
+/// Whether the most recent steps end in [`MIN_CYCLE_REPEATS`] repetitions of
+/// the same PC sequence, each moving SP by the same amount. Returns the
+/// shortest such period.
+fn detect_cycle(steps: &[WalkStep]) -> Option<usize>
+{
+    (1..=MAX_CYCLE_PERIOD).find(|&period| {
+        let span = period * MIN_CYCLE_REPEATS;
+        if steps.len() < span {
+            return false;
+        }
+        let window = &steps[steps.len() - span..];
+        let same_pcs = window
+            .iter()
+            .zip(&window[period..])
+            .all(|(earlier, later)| earlier.pc == later.pc);
+        let sp_step = window[period].sp.value().wrapping_sub(window[0].sp.value());
+        let steady_sp = sp_step != 0
+            && window
+                .iter()
+                .zip(&window[period..])
+                .all(|(earlier, later)| later.sp.value().wrapping_sub(earlier.sp.value()) == sp_step);
+        same_pcs && steady_sp
+    })
+}
+
 fn append_logical_frames(
     frames: &mut Vec<StackFrame>,
     thread: ThreadId,
@@ -575,48 +1439,568 @@ fn append_logical_frames(
         status,
     });
 }
-fn read_register_value(architecture: Architecture, regs: &Registers, register: Register) -> Option<u64>
+
+/// Index of the first frame of the physical frame at `index`: its innermost inlined frame.
+fn first_of_group(frames: &[StackFrame], index: usize) -> usize
 {
-    let reg_num = register.0;
-    match architecture {
-        Architecture::Arm64 => {
-            // ARM64 DWARF register mapping
-            match reg_num {
-                31 => Some(regs.sp.value()),         // SP
-                30 => regs.general.get(30).copied(), // LR
-                29 => Some(regs.fp.value()),         // FP
-                0..=28 => regs.general.get(reg_num as usize).copied(),
-                _ => None,
-            }
+    let physical = &frames[index];
+    let mut first = index;
+    while first > 0
+        && frames[first - 1].is_inlined()
+        && frames[first - 1].pc == physical.pc
+        && frames[first - 1].sp == physical.sp
+    {
+        first -= 1;
+    }
+    first
+}
+
+/// Add a frame per step of `chain` above the executor frame at `executor`,
+/// innermost first, keeping frame indices and recursion cycles in step.
+fn insert_async_frames(trace: &mut StackTrace, executor: usize, chain: &[AwaitStep], max_frames: usize)
+{
+    let at = first_of_group(&trace.frames, executor);
+    let physical = trace.frames[executor].clone();
+    let async_frames = chain.iter().rev().enumerate().map(|(depth, step)| {
+        let depth = u8::try_from(depth).unwrap_or(u8::MAX);
+        StackFrame {
+            id: FrameId::new(physical.thread, u32::MAX, depth, physical.pc, Address::from(step.address)),
+            kind: FrameKind::Async {
+                physical: physical.id,
+                depth,
+            },
+            symbol: Some(SymbolName::new(
+                step.type_name.clone(),
+                Some(step.name.clone()),
+                SymbolLanguage::Rust,
+            )),
+            location: None,
+            parameters: Vec::new(),
+            status: FrameStatus::Heuristic,
+            ..physical.clone()
         }
-        Architecture::X86_64 => {
-            // x86-64 DWARF register mapping
-            match reg_num {
-                7 => Some(regs.sp.value()),  // RSP
-                16 => Some(regs.pc.value()), // RIP
-                6 => Some(regs.fp.value()),  // RBP
-                0..=15 => {
-                    // Map DWARF register numbers to our general register array
-                    let mapping = [0, 2, 1, 3, 6, 7, 5, 4, 8, 9, 10, 11, 12, 13, 14, 15];
-                    mapping.get(reg_num as usize).and_then(|&idx| regs.general.get(idx).copied())
-                }
-                _ => None,
-            }
+    });
+    trace.frames.splice(at..at, async_frames);
+    trace.frames.truncate(max_frames);
+    for (index, frame) in trace.frames.iter_mut().enumerate() {
+        frame.index = index;
+    }
+    for cycle in &mut trace.recursion {
+        if cycle.first_frame >= at {
+            cycle.first_frame += chain.len();
         }
-        _ => None,
     }
+    let len = trace.frames.len();
+    trace.recursion.retain(|cycle| cycle.first_frame < len);
+}
+
+fn read_register_value(architecture: Architecture, regs: &Registers, register: Register) -> Option<u64>
+{
+    dwarf::dwarf_to_register_id(architecture, register.0).and_then(|id| regs.get_in(architecture, id))
 }
 
 fn return_register(architecture: Architecture) -> Register
 {
-    match architecture {
-        Architecture::Arm64 => Register(30),  // LR
-        Architecture::X86_64 => Register(16), // RA (return address, same as RIP)
-        _ => Register(0),
+    Register(dwarf::return_address_register(architecture).unwrap_or(0))
+}
+
+fn malformed(context: &'static str, err: gimli::Error) -> UnwindFailure
+{
+    UnwindFailure::MalformedCfi {
+        context,
+        details: err.to_string(),
     }
 }
 
-fn map_gimli_error(context: &str, err: gimli::Error) -> DebuggerError
+#[cfg(test)]
+mod tests
 {
-    DebuggerError::InvalidArgument(format!("{context}: {err}"))
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::error::DebuggerError;
+
+    /// Sparse stack memory; any address not in the map is unreadable.
+    struct FakeMemory(HashMap<u64, u64>);
+
+    impl MemoryAccess for FakeMemory
+    {
+        fn read_u64(&self, address: Address) -> Result<u64>
+        {
+            self.0.get(&address.value()).copied().ok_or(DebuggerError::MemoryReadFailed {
+                address,
+                length: 8,
+                kern_return: None,
+            })
+        }
+    }
+
+    fn regs(pc: u64, sp: u64, fp: u64) -> Registers
+    {
+        let mut regs = Registers::new();
+        regs.pc = Address::from(pc);
+        regs.sp = Address::from(sp);
+        regs.fp = Address::from(fp);
+        regs
+    }
+
+    #[test]
+    fn frame_pointer_chain_records_missing_cfi()
+    {
+        let symbols = SymbolCache::new();
+        // fp=0x1000 -> [saved fp = 0, return = 0x4000]
+        let memory = FakeMemory(HashMap::from([(0x1000, 0), (0x1008, 0x4000)]));
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+
+        let trace = unwinder
+            .unwind_with_diagnostics(ThreadId::from(1), &regs(0x2000, 0x0ff0, 0x1000), 8)
+            .unwrap();
+
+        assert_eq!(trace.frames.len(), 2);
+        assert_eq!(trace.frames[1].pc, Address::from(0x4000));
+        assert_eq!(trace.frames[1].status, FrameStatus::CfiFallback);
+
+        let first = &trace.diagnostics[0];
+        assert_eq!(first.resolved_by, Some(UnwindStrategy::FramePointer));
+        assert_eq!(
+            first.failed,
+            vec![UnwindAttempt {
+                strategy: UnwindStrategy::Cfi,
+                failure: UnwindFailure::NoImageForPc,
+            }]
+        );
+        assert!(first.is_degraded());
+        assert!(trace.diagnostic_for(&trace.frames[0]).is_some());
+    }
+
+    #[test]
+    fn cfi_only_unwind_refuses_code_without_cfi()
+    {
+        let symbols = SymbolCache::new();
+        let memory = FakeMemory(HashMap::from([(0x1000, 0), (0x1008, 0x4000)]));
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+        let start = regs(0x2000, 0x0ff0, 0x1000);
+
+        // The frame pointer chain that a normal unwind falls back to isn't followed
+        let options = UnwindOptions::new(8).with_cfi_only();
+        let err = unwinder.unwind_with_options(ThreadId::from(1), &start, options).unwrap_err();
+        assert!(matches!(err, DebuggerError::CfiUnavailable), "{err}");
+        assert_eq!(unwinder.unwind(ThreadId::from(1), &start, 8).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn exhausted_strategies_explain_why_unwinding_stopped()
+    {
+        let symbols = SymbolCache::new();
+        let memory = FakeMemory(HashMap::new());
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+
+        let trace = unwinder
+            .unwind_with_diagnostics(ThreadId::from(1), &regs(0x2000, 0x3000, 0x1000), 8)
+            .unwrap();
+
+        assert_eq!(trace.frames.len(), 1);
+        let last = trace.diagnostics.last().unwrap();
+        assert_eq!(last.resolved_by, None);
+        let failures: Vec<_> = last.failed.iter().map(|attempt| attempt.failure.clone()).collect();
+        assert_eq!(
+            failures,
+            vec![
+                UnwindFailure::NoImageForPc,
+                UnwindFailure::MemoryRead {
+                    address: Address::from(0x1000)
+                },
+                UnwindFailure::MemoryRead {
+                    address: Address::from(0x3000)
+                },
+                UnwindFailure::UnsupportedArchitecture,
+            ]
+        );
+        assert!(last.summary().contains("unwinding stopped"));
+    }
+
+    #[test]
+    fn stack_scan_used_when_frame_pointer_is_zero()
+    {
+        let symbols = SymbolCache::new();
+        let memory = FakeMemory(HashMap::from([(0x3000, 0x5000)]));
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+
+        let trace = unwinder
+            .unwind_with_diagnostics(ThreadId::from(1), &regs(0x2000, 0x3000, 0), 2)
+            .unwrap();
+
+        assert_eq!(trace.frames[1].pc, Address::from(0x5000));
+        assert_eq!(trace.frames[1].status, FrameStatus::Heuristic);
+        let first = &trace.diagnostics[0];
+        assert_eq!(first.resolved_by, Some(UnwindStrategy::StackScan));
+        assert_eq!(first.failed[1].failure, UnwindFailure::NoFramePointer);
+    }
+
+    #[test]
+    fn return_frame_only_trusts_callers_from_cfi_or_frame_pointers()
+    {
+        let symbols = SymbolCache::new();
+        let memory = FakeMemory(HashMap::from([(0x1000, 0), (0x1008, 0x4000), (0x3000, 0x5000)]));
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+        let unwind = |regs: Registers| unwinder.unwind(ThreadId::from(1), &regs, 8).unwrap();
+
+        let chained = unwind(regs(0x2000, 0x0ff0, 0x1000));
+        assert_eq!(return_frame(&chained).unwrap().pc, Address::from(0x4000));
+
+        let scanned = unwind(regs(0x2000, 0x3000, 0));
+        let err = return_frame(&scanned).unwrap_err();
+        assert!(matches!(err, DebuggerError::UnreliableReturnAddress(_)), "{err}");
+        assert!(err.to_string().contains("heuristically"), "{err}");
+
+        let alone = unwind(regs(0x2000, 0x7000, 0));
+        assert_eq!(alone.len(), 1);
+        assert!(matches!(return_frame(&alone), Err(DebuggerError::UnreliableReturnAddress(_))));
+    }
+
+    #[test]
+    fn link_register_is_last_resort_on_arm64()
+    {
+        let symbols = SymbolCache::new();
+        let memory = FakeMemory(HashMap::new());
+        let unwinder = StackUnwinder::new(Architecture::Arm64, &symbols, &memory);
+        let mut start = regs(0x2000, 0, 0);
+        start.general = vec![0; 31];
+        start.general[30] = 0x6000;
+
+        let trace = unwinder.unwind_with_diagnostics(ThreadId::from(1), &start, 2).unwrap();
+
+        assert_eq!(trace.frames[1].pc, Address::from(0x6000));
+        let first = &trace.diagnostics[0];
+        assert_eq!(first.resolved_by, Some(UnwindStrategy::LinkRegister));
+        let failures: Vec<_> = first.failed.iter().map(|attempt| attempt.failure.clone()).collect();
+        assert_eq!(
+            failures,
+            vec![
+                UnwindFailure::NoImageForPc,
+                UnwindFailure::NoFramePointer,
+                UnwindFailure::NoStackPointer,
+            ]
+        );
+    }
+
+    /// Frame-pointer chain starting at `fp` whose return addresses are `pcs`, terminated by a null frame.
+    fn fp_chain(fp: u64, pcs: impl IntoIterator<Item = u64>) -> HashMap<u64, u64>
+    {
+        let mut memory = HashMap::new();
+        let mut frame = fp;
+        for pc in pcs {
+            memory.insert(frame, frame + 0x20);
+            memory.insert(frame + 8, pc);
+            frame += 0x20;
+        }
+        memory.insert(frame, 0);
+        memory.insert(frame + 8, 0);
+        memory
+    }
+
+    #[test]
+    fn direct_recursion_is_collapsed_to_one_frame()
+    {
+        let symbols = SymbolCache::new();
+        // Crash site in `recurse`, 1000 recursive calls, then `main`
+        let pcs = std::iter::repeat_n(0x2010, 1000).chain([0x3000]);
+        let memory = FakeMemory(fp_chain(0x10_0000, pcs));
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+
+        let trace = unwinder
+            .unwind_with_diagnostics(ThreadId::from(1), &regs(0x2000, 0x0f_fff0, 0x10_0000), 64)
+            .unwrap();
+
+        let pcs: Vec<_> = trace.frames.iter().map(|frame| frame.pc.value()).collect();
+        assert_eq!(pcs, vec![0x2000, 0x2010, 0x3000]);
+        assert_eq!(trace.diagnostics.len(), trace.frames.len());
+        assert_eq!(
+            trace.recursion,
+            vec![RecursionCycle {
+                first_frame: 1,
+                frame_count: 1,
+                period: 1,
+                repeats: 1000,
+                function: None,
+                truncated: false,
+            }]
+        );
+        assert_eq!(trace.recursion[0].to_string(), "frame #1 repeated ~1000 times");
+
+        let plain = unwinder
+            .unwind_with_options(ThreadId::from(1), &regs(0x2000, 0x0f_fff0, 0x10_0000), UnwindOptions::new(64))
+            .unwrap();
+        assert_eq!(plain.frames.len(), 3);
+        assert!(plain.diagnostics.is_empty());
+        assert_eq!(plain.recursion, trace.recursion);
+    }
+
+    #[test]
+    fn async_frames_go_above_the_executor_frame_innermost_first()
+    {
+        let symbols = SymbolCache::new();
+        let pcs = std::iter::repeat_n(0x2010, 10).chain([0x3000]);
+        let memory = FakeMemory(fp_chain(0x10_0000, pcs));
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+        let mut trace = unwinder
+            .unwind_with_options(ThreadId::from(1), &regs(0x2000, 0x0f_fff0, 0x10_0000), UnwindOptions::new(8))
+            .unwrap();
+        assert_eq!(trace.recursion[0].first_frame, 1);
+
+        let step = |name: &str, address| AwaitStep {
+            name: name.to_string(),
+            type_name: format!("{name}::{{async_fn_env#0}}"),
+            address,
+        };
+        let chain = [step("app::outer", 0x500), step("app::inner", 0x510), step("Sleep", 0x518)];
+        insert_async_frames(&mut trace, 1, &chain, 8);
+
+        let names: Vec<_> = trace
+            .frames
+            .iter()
+            .map(|frame| frame.symbol.as_ref().map_or("?", |symbol| symbol.display_name()))
+            .collect();
+        assert_eq!(names, vec!["?", "Sleep", "app::inner", "app::outer", "?", "?"]);
+        assert!(trace.frames.iter().enumerate().all(|(index, frame)| frame.index == index));
+        assert_eq!(
+            trace.frames[3].kind,
+            FrameKind::Async {
+                physical: trace.frames[4].id,
+                depth: 2
+            }
+        );
+        assert_eq!(trace.frames[3].pc, trace.frames[4].pc);
+        assert_eq!(trace.recursion[0].first_frame, 4);
+        assert_eq!(return_frame(&trace.frames).unwrap().pc, Address::from(0x2010));
+
+        // Pushed past `max_frames`, the frames below and their recursion are dropped
+        insert_async_frames(&mut trace, 4, &chain, 6);
+        assert_eq!(trace.frames.len(), 6);
+        assert!(trace.recursion.is_empty());
+    }
+
+    #[test]
+    fn mutual_recursion_collapses_whole_period()
+    {
+        let symbols = SymbolCache::new();
+        let pcs = [0x2010, 0x4010].repeat(50).into_iter().chain([0x3000]);
+        let memory = FakeMemory(fp_chain(0x10_0000, pcs));
+        let unwinder = StackUnwinder::new(Architecture::Arm64, &symbols, &memory);
+
+        let trace = unwinder
+            .unwind_with_diagnostics(ThreadId::from(1), &regs(0x2000, 0x0f_fff0, 0x10_0000), 64)
+            .unwrap();
+
+        let pcs: Vec<_> = trace.frames.iter().map(|frame| frame.pc.value()).collect();
+        assert_eq!(pcs, vec![0x2000, 0x2010, 0x4010, 0x3000]);
+        let cycle = &trace.recursion[0];
+        assert_eq!(
+            (cycle.first_frame, cycle.frame_count, cycle.period, cycle.repeats),
+            (1, 2, 2, 50)
+        );
+        assert_eq!(cycle.to_string(), "frames #1-#2 repeated ~50 times");
+    }
+
+    #[test]
+    fn repeated_pc_without_stack_growth_is_not_recursion()
+    {
+        let symbols = SymbolCache::new();
+        // A corrupt chain that points back at itself never moves SP forward
+        let memory = FakeMemory(HashMap::from([(0x1000, 0x1000), (0x1008, 0x2010)]));
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+
+        let trace = unwinder
+            .unwind_with_diagnostics(ThreadId::from(1), &regs(0x2000, 0x0ff0, 0x1000), 16)
+            .unwrap();
+
+        assert_eq!(trace.frames.len(), 16);
+        assert!(trace.recursion.is_empty());
+    }
+
+    #[test]
+    fn plain_unwind_matches_diagnostic_frames()
+    {
+        let symbols = SymbolCache::new();
+        let memory = FakeMemory(HashMap::from([(0x1000, 0), (0x1008, 0x4000)]));
+        let unwinder = StackUnwinder::new(Architecture::Arm64, &symbols, &memory);
+        let start = regs(0x2000, 0x0ff0, 0x1000);
+
+        let plain = unwinder.unwind(ThreadId::from(1), &start, 8).unwrap();
+        let traced = unwinder.unwind_with_diagnostics(ThreadId::from(1), &start, 8).unwrap();
+
+        let pcs = |frames: &[StackFrame]| frames.iter().map(|frame| frame.pc).collect::<Vec<_>>();
+        assert_eq!(pcs(&plain), pcs(&traced.frames));
+    }
+
+    #[test]
+    fn cfi_registers_use_abi_numbering()
+    {
+        // General registers are stored RAX, RBX, RCX, RDX, ...; DWARF numbers them RAX, RDX, RCX, RBX
+        let mut start = regs(0x2000, 0x7000, 0x7100);
+        start.general = (0..14).map(|index| 0x100 + index).collect();
+
+        assert_eq!(read_register_value(Architecture::X86_64, &start, Register(1)), Some(0x103));
+        assert_eq!(read_register_value(Architecture::X86_64, &start, Register(3)), Some(0x101));
+        assert_eq!(read_register_value(Architecture::X86_64, &start, Register(6)), Some(0x7100));
+        assert_eq!(read_register_value(Architecture::X86_64, &start, Register(16)), Some(0x2000));
+        assert_eq!(read_register_value(Architecture::Arm64, &start, Register(31)), Some(0x7000));
+        assert_eq!(return_register(Architecture::X86_64), Register(16));
+    }
+
+    /// x86-64 `.eh_frame` with absolute (`DW_EH_PE_udata4`) FDE addresses:
+    /// a CIE with CFA = RSP+8 and the return address at CFA-8, then
+    ///
+    /// - at offset 0x18, `caller` (0x1000..0x1040): `push rbp` (CFA = RSP+16,
+    ///   RBP at CFA-16) then `mov rbp, rsp` (CFA = RBP+16)
+    /// - at offset 0x34, `leaf` (0x2000..0x2020): `sub rsp, 0x18` with no
+    ///   frame pointer (CFA = RSP+32)
+    #[rustfmt::skip]
+    const EH_FRAME: &[u8] = &[
+        // CIE: length, id, version, "zR", code align 1, data align -8, RA r16
+        0x14, 0, 0, 0,  0, 0, 0, 0,  1,  b'z', b'R', 0,  1,  0x78,  16,
+        // augmentation data: FDE encoding udata4
+        1, 0x03,
+        // def_cfa rsp+8; offset r16 at cfa-8; nop padding
+        0x0c, 7, 8,  0x90, 1,  0, 0,
+        // FDE `caller`: length, CIE pointer, start, range, no augmentation data
+        0x18, 0, 0, 0,  0x1c, 0, 0, 0,  0x00, 0x10, 0, 0,  0x40, 0, 0, 0,  0,
+        // advance 1; def_cfa_offset 16; offset rbp at cfa-16; advance 3; def_cfa_register rbp
+        0x41,  0x0e, 16,  0x86, 2,  0x43,  0x0d, 6,  0, 0, 0,
+        // FDE `leaf`
+        0x10, 0, 0, 0,  0x38, 0, 0, 0,  0x00, 0x20, 0, 0,  0x20, 0, 0, 0,  0,
+        // advance 4; def_cfa_offset 32
+        0x44,  0x0e, 32,
+        // terminator
+        0, 0, 0, 0,
+    ];
+
+    /// Where [`EH_FRAME`] is loaded in the fixtures.
+    const EH_FRAME_ADDRESS: u64 = 0x10000;
+
+    /// `.eh_frame_hdr` for [`EH_FRAME`] with an absolute (`udata4`) search table.
+    #[rustfmt::skip]
+    const EH_FRAME_HDR: &[u8] = &[
+        // version, eh_frame_ptr, fde_count and table encodings
+        1, 0x03, 0x03, 0x03,
+        0x00, 0x00, 0x01, 0x00,  2, 0, 0, 0,
+        0x00, 0x10, 0, 0,  0x18, 0x00, 0x01, 0x00,
+        0x00, 0x20, 0, 0,  0x34, 0x00, 0x01, 0x00,
+    ];
+
+    fn eh_frame() -> (EhFrame<gimli::EndianSlice<'static, gimli::LittleEndian>>, BaseAddresses)
+    {
+        let mut eh_frame = EhFrame::new(EH_FRAME, gimli::LittleEndian);
+        eh_frame.set_address_size(8);
+        (eh_frame, BaseAddresses::default().set_eh_frame(EH_FRAME_ADDRESS))
+    }
+
+    /// Stopped in `leaf`, called from `caller` at 0x1030, called from 0x5000
+    /// whose frame pointer is 0x9000.
+    fn cfi_fixture() -> (Registers, FakeMemory)
+    {
+        let memory = FakeMemory(HashMap::from([
+            // leaf's CFA is 0x8000: return address into caller
+            (0x7ff8, 0x1030),
+            // caller's CFA is RBP+16 = 0x8010: saved RBP, then return address
+            (0x8000, 0x9000),
+            (0x8008, 0x5000),
+        ]));
+        (regs(0x2010, 0x7fe0, 0x8000), memory)
+    }
+
+    #[test]
+    fn fde_index_finds_the_entry_covering_a_pc()
+    {
+        let (eh_frame, bases) = eh_frame();
+        let index = FdeIndex::build(&eh_frame, &bases);
+
+        assert_eq!(index.lookup(0x1000), Some(0x18));
+        assert_eq!(index.lookup(0x103f), Some(0x18));
+        assert_eq!(index.lookup(0x1040), None);
+        assert_eq!(index.lookup(0x2010), Some(0x34));
+        assert_eq!(index.lookup(0x0fff), None);
+        assert_eq!(index.lookup(0x5000), None);
+        assert!(FdeIndex::default().lookup(0x1000).is_none());
+    }
+
+    #[test]
+    fn cfi_unwinds_through_frameless_and_frame_pointer_functions()
+    {
+        let symbols = SymbolCache::new();
+        let (start, memory) = cfi_fixture();
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+        let (eh_frame, bases) = eh_frame();
+        let index = FdeIndex::build(&eh_frame, &bases);
+        let step = |regs: &Registers| unwinder.unwind_with_cfi(&eh_frame, &bases, &index, regs.pc.value(), regs);
+
+        let leaf = step(&start).unwrap();
+        assert_eq!(leaf.status, FrameStatus::Complete);
+        assert_eq!(leaf.return_address, Some(Address::from(0x1030)));
+        assert_eq!((leaf.next.sp, leaf.next.fp), (Address::from(0x8000), Address::from(0x8000)));
+
+        let caller = step(&leaf.next).unwrap();
+        assert_eq!(caller.next.pc, Address::from(0x5000));
+        assert_eq!(caller.next.sp, Address::from(0x8010));
+        // RBP is restored from the slot `push rbp` saved it in
+        assert_eq!(caller.next.fp, Address::from(0x9000));
+
+        assert_eq!(step(&caller.next).unwrap_err(), UnwindFailure::NoFdeForPc);
+    }
+
+    #[test]
+    fn cfi_rows_follow_the_prologue()
+    {
+        let symbols = SymbolCache::new();
+        // Before `push rbp` the return address is at RSP; after it, at RSP+8
+        let memory = FakeMemory(HashMap::from([(0x7000, 0x4000), (0x6ff8, 0x7100)]));
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+        let (eh_frame, bases) = eh_frame();
+        let index = FdeIndex::build(&eh_frame, &bases);
+        let step = |regs: Registers| unwinder.unwind_with_cfi(&eh_frame, &bases, &index, regs.pc.value(), &regs);
+
+        let entry = step(regs(0x1000, 0x7000, 0x7100)).unwrap();
+        assert_eq!((entry.next.pc, entry.next.sp), (Address::from(0x4000), Address::from(0x7008)));
+
+        let pushed = step(regs(0x1001, 0x6ff8, 0x7100)).unwrap();
+        assert_eq!(pushed.next.pc, Address::from(0x4000));
+        assert_eq!(pushed.next.fp, Address::from(0x7100));
+
+        // Past the prologue the CFA follows RBP, whatever RSP is
+        let unreadable = step(regs(0x1010, 0x4000, 0x5000)).unwrap_err();
+        assert_eq!(
+            unreadable,
+            UnwindFailure::MemoryRead {
+                address: Address::from(0x5008)
+            }
+        );
+    }
+
+    #[test]
+    fn eh_frame_hdr_search_table_agrees_with_the_index()
+    {
+        let symbols = SymbolCache::new();
+        let (start, memory) = cfi_fixture();
+        let unwinder = StackUnwinder::new(Architecture::X86_64, &symbols, &memory);
+        let (eh_frame, bases) = eh_frame();
+        let header = || EhFrameHdr::new(EH_FRAME_HDR, gimli::LittleEndian);
+
+        let leaf = unwinder
+            .unwind_with_eh_frame_hdr(&eh_frame, header(), &bases, &start)
+            .unwrap();
+        assert_eq!(leaf.next.pc, Address::from(0x1030));
+        let caller = unwinder
+            .unwind_with_eh_frame_hdr(&eh_frame, header(), &bases, &leaf.next)
+            .unwrap();
+        assert_eq!(
+            (caller.next.pc, caller.next.fp),
+            (Address::from(0x5000), Address::from(0x9000))
+        );
+        assert_eq!(
+            unwinder
+                .unwind_with_eh_frame_hdr(&eh_frame, header(), &bases, &caller.next)
+                .unwrap_err(),
+            UnwindFailure::NoFdeForPc
+        );
+    }
 }
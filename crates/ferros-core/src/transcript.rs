@@ -0,0 +1,708 @@
+//! Target I/O transcripts lined up with debugger events.
+//!
+//! With `--transcript <path>` every line the target writes to its captured
+//! stdout/stderr (and every stdin write) is appended to a JSONL file as it
+//! happens. Each record carries a monotonic offset from the start of the
+//! transcript, a wall-clock time and the stream it came from. Debugger events
+//! (stops, resumes, breakpoint hits) are written inline as `event` records, so
+//! the interleaving of I/O and events is explicit:
+//!
+//! ```json
+//! {"seq":0,"mono_us":1520,"wall_ms":1700000000001,"stream":"stdout","text":"name?"}
+//! {"seq":1,"mono_us":1873,"wall_ms":1700000000001,"stream":"event","text":"Hit breakpoint at 0x100003f20 (thread 1)"}
+//! ```
+//!
+//! Records are appended in the order they reach the writer. Output is stamped
+//! when its pump read it and events when the backend emitted them, so sort by
+//! `mono_us` for the exact order.
+//!
+//! A line the target hasn't finished stays pending until its newline arrives.
+//! [`Transcript::flush_partial`] writes pending lines with `"partial": true`
+//! followed by a `marker` record. Front-ends call it when the session ends and
+//! [`Transcript::flush_after_crash`] from the crash handler, so output isn't
+//! lost when ferros itself fails.
+//!
+//! How often the file is fsynced is set by [`FsyncPolicy`]. Once the file
+//! grows past [`TranscriptOptions::warn_bytes`] a marker is written and
+//! [`Transcript::take_size_warning`] hands out a warning, once.
+//!
+//! ```rust
+//! use ferros_core::events::{DebuggerEvent, EventEnvelope};
+//! use ferros_core::transcript::{
+//!     Transcript, TranscriptOptions, TranscriptStream, read_transcript,
+//! };
+//!
+//! let path = std::env::temp_dir().join(format!(
+//!     "ferros-transcript-doc-{}.jsonl",
+//!     std::process::id()
+//! ));
+//! let transcript = Transcript::create(&path, TranscriptOptions::default())?;
+//! let lines = transcript.record_stream(TranscriptStream::Stdout, b"ready\nwaiting for in");
+//! assert_eq!(lines.len(), 1);
+//! transcript.record_event(&EventEnvelope::new(DebuggerEvent::TargetResumed));
+//! transcript.finish("session ended")?;
+//!
+//! let records = read_transcript(&path)?;
+//! assert_eq!(records.len(), 4); // line, event, partial line, marker
+//! assert!(records[2].partial);
+//!
+//! # std::fs::remove_file(&path)?;
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DebuggerError, Result};
+use crate::events::EventEnvelope;
+use crate::output::OutputSink;
+
+/// File size past which a transcript warns about disk usage.
+pub const DEFAULT_TRANSCRIPT_WARN_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Where a transcript record came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptStream
+{
+    /// Bytes written to the target's stdin.
+    Stdin,
+    /// A line the target wrote to stdout.
+    Stdout,
+    /// A line the target wrote to stderr.
+    Stderr,
+    /// A debugger event: stop, resume or breakpoint hit.
+    Event,
+    /// A note from the writer itself, e.g. where the session ended.
+    Marker,
+}
+
+impl TranscriptStream
+{
+    /// Index of the pending-line buffer for streams that carry bytes
+    fn pending_slot(self) -> Option<usize>
+    {
+        match self {
+            Self::Stdin => Some(0),
+            Self::Stdout => Some(1),
+            Self::Stderr => Some(2),
+            Self::Event | Self::Marker => None,
+        }
+    }
+}
+
+/// One line of a transcript file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptRecord
+{
+    /// Position in the file, from 0.
+    pub seq: u64,
+    /// Microseconds since the transcript was created, on the monotonic clock.
+    pub mono_us: u64,
+    /// Wall-clock time in milliseconds since the Unix epoch.
+    pub wall_ms: u64,
+    /// Where the record came from.
+    pub stream: TranscriptStream,
+    /// The line without its line ending, or the event description.
+    pub text: String,
+    /// The line was cut off before its newline arrived.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+}
+
+impl TranscriptRecord
+{
+    /// Time since the transcript was created.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration
+    {
+        Duration::from_micros(self.mono_us)
+    }
+}
+
+/// When a transcript is fsynced to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsyncPolicy
+{
+    /// Only when the transcript is finished; the OS decides otherwise.
+    Never,
+    /// After every debugger event and marker, so the I/O leading up to a stop survives.
+    #[default]
+    Events,
+    /// After every record. Slow for chatty targets.
+    Always,
+}
+
+impl FromStr for FsyncPolicy
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err>
+    {
+        match s.to_ascii_lowercase().as_str() {
+            "never" => Ok(Self::Never),
+            "events" => Ok(Self::Events),
+            "always" => Ok(Self::Always),
+            _ => Err(format!("invalid fsync policy '{s}' (expected never, events or always)")),
+        }
+    }
+}
+
+impl fmt::Display for FsyncPolicy
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::Never => "never",
+            Self::Events => "events",
+            Self::Always => "always",
+        })
+    }
+}
+
+/// How a transcript is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TranscriptOptions
+{
+    /// When the file is fsynced.
+    pub fsync: FsyncPolicy,
+    /// File size that triggers the disk usage warning.
+    pub warn_bytes: u64,
+}
+
+impl Default for TranscriptOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            fsync: FsyncPolicy::default(),
+            warn_bytes: DEFAULT_TRANSCRIPT_WARN_BYTES,
+        }
+    }
+}
+
+/// Shared handle to a transcript file.
+///
+/// Clones write to the same file; output pumps, the event loop and the crash
+/// handler each hold one.
+#[derive(Debug, Clone)]
+pub struct Transcript
+{
+    writer: Arc<Mutex<TranscriptWriter>>,
+}
+
+impl Transcript
+{
+    /// Create (or truncate) the transcript file at `path`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Io` if the file can't be created.
+    pub fn create(path: &Path, options: TranscriptOptions) -> Result<Self>
+    {
+        let file = File::create(path)?;
+        Ok(Self {
+            writer: Arc::new(Mutex::new(TranscriptWriter {
+                out: BufWriter::new(file),
+                path: path.to_path_buf(),
+                options,
+                started: Instant::now(),
+                next_seq: 0,
+                bytes: 0,
+                pending: Default::default(),
+                size_warning: None,
+                warned: false,
+                failed: None,
+            })),
+        })
+    }
+
+    /// Path of the transcript file.
+    #[must_use]
+    pub fn path(&self) -> PathBuf
+    {
+        self.lock().path.clone()
+    }
+
+    /// Record bytes read from (or written to) a target stream.
+    ///
+    /// Complete lines are written right away and returned, stamped now. The
+    /// rest is kept until its newline arrives or [`flush_partial`](Self::flush_partial).
+    pub fn record_stream(&self, stream: TranscriptStream, bytes: &[u8]) -> Vec<TranscriptRecord>
+    {
+        let stamp = Stamp::now();
+        let mut writer = self.lock();
+        let Some(slot) = stream.pending_slot() else {
+            return Vec::new();
+        };
+        let mut records = Vec::new();
+        let mut rest = bytes;
+        while let Some(newline) = rest.iter().position(|&byte| byte == b'\n') {
+            let mut line = std::mem::take(&mut writer.pending[slot]);
+            line.extend_from_slice(&rest[..newline]);
+            records.push(writer.write(stamp, stream, line_text(&line), false));
+            rest = &rest[newline + 1..];
+        }
+        writer.pending[slot].extend_from_slice(rest);
+        writer.flush();
+        records
+    }
+
+    /// Record a debugger event, stamped with when the backend emitted it.
+    pub fn record_event(&self, envelope: &EventEnvelope)
+    {
+        let stamp = Stamp {
+            at: envelope.emitted,
+            wall: envelope.emitted_at,
+        };
+        let mut writer = self.lock();
+        writer.write(stamp, TranscriptStream::Event, envelope.event.describe(), false);
+        writer.flush();
+    }
+
+    /// Write a marker record, e.g. `"detached"`.
+    pub fn record_marker(&self, text: &str)
+    {
+        let mut writer = self.lock();
+        writer.write(Stamp::now(), TranscriptStream::Marker, text.to_string(), false);
+        writer.flush();
+    }
+
+    /// Write the pending part of `stream`'s last line, if any, as a partial record.
+    ///
+    /// Called when a pump stops, so the last line shows up like it does
+    /// without a transcript.
+    pub fn flush_stream(&self, stream: TranscriptStream) -> Option<TranscriptRecord>
+    {
+        let mut writer = self.lock();
+        let record = writer.flush_pending(Stamp::now(), stream);
+        writer.flush();
+        record
+    }
+
+    /// Write every pending partial line, then `marker`, and fsync.
+    pub fn flush_partial(&self, marker: &str)
+    {
+        self.lock().close(marker);
+    }
+
+    /// [`flush_partial`](Self::flush_partial) for the crash handler.
+    ///
+    /// Gives up rather than wait if another thread holds the writer, since
+    /// the panicking thread may be the one holding it.
+    pub fn flush_after_crash(&self)
+    {
+        let mut writer = match self.writer.try_lock() {
+            Ok(writer) => writer,
+            Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => return,
+        };
+        writer.close("ferros crashed; transcript ends here");
+    }
+
+    /// The disk usage warning, the first time it's asked for after the file passed the threshold.
+    pub fn take_size_warning(&self) -> Option<String>
+    {
+        self.lock().size_warning.take()
+    }
+
+    /// Flush partial lines with `marker` and fsync, for the end of the session.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the first error writing the transcript ran into; recording
+    /// stops at that point.
+    pub fn finish(&self, marker: &str) -> Result<()>
+    {
+        let mut writer = self.lock();
+        writer.close(marker);
+        match writer.failed.take() {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// An [`OutputSink`] that records `stream` and passes each recorded line to `emit`.
+    ///
+    /// `emit` returns `false` to stop the pump, like a [`LineSink`](crate::output::LineSink)'s.
+    pub fn sink<F>(&self, stream: TranscriptStream, emit: F) -> Box<dyn OutputSink>
+    where
+        F: FnMut(TranscriptRecord) -> bool + Send + 'static,
+    {
+        Box::new(TranscriptSink {
+            transcript: self.clone(),
+            stream,
+            emit,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TranscriptWriter>
+    {
+        // Records written before a panic are still worth keeping
+        self.writer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// When a record happened
+#[derive(Debug, Clone, Copy)]
+struct Stamp
+{
+    at: Instant,
+    wall: SystemTime,
+}
+
+impl Stamp
+{
+    fn now() -> Self
+    {
+        Self {
+            at: Instant::now(),
+            wall: SystemTime::now(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TranscriptWriter
+{
+    out: BufWriter<File>,
+    path: PathBuf,
+    options: TranscriptOptions,
+    started: Instant,
+    next_seq: u64,
+    bytes: u64,
+    /// Unfinished last line of stdin, stdout and stderr
+    pending: [Vec<u8>; 3],
+    size_warning: Option<String>,
+    warned: bool,
+    /// First write error; nothing is written after it
+    failed: Option<io::Error>,
+}
+
+impl TranscriptWriter
+{
+    fn write(&mut self, stamp: Stamp, stream: TranscriptStream, text: String, partial: bool) -> TranscriptRecord
+    {
+        let record = TranscriptRecord {
+            seq: self.next_seq,
+            mono_us: u64::try_from(stamp.at.saturating_duration_since(self.started).as_micros()).unwrap_or(u64::MAX),
+            wall_ms: stamp
+                .wall
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| u64::try_from(since.as_millis()).unwrap_or(u64::MAX)),
+            stream,
+            text,
+            partial,
+        };
+        self.next_seq += 1;
+        if self.failed.is_some() {
+            return record;
+        }
+
+        let mut line = serde_json::to_vec(&record).unwrap_or_default();
+        line.push(b'\n');
+        if let Err(e) = self.out.write_all(&line) {
+            self.fail(e);
+            return record;
+        }
+        self.bytes += line.len() as u64;
+
+        let sync = match self.options.fsync {
+            FsyncPolicy::Never => false,
+            FsyncPolicy::Events => matches!(stream, TranscriptStream::Event | TranscriptStream::Marker),
+            FsyncPolicy::Always => true,
+        };
+        if sync {
+            self.sync();
+        }
+        if !self.warned && self.bytes >= self.options.warn_bytes {
+            self.warned = true;
+            let warning = format!(
+                "Transcript {} is over {} MiB; output is still being recorded",
+                self.path.display(),
+                self.options.warn_bytes / (1024 * 1024)
+            );
+            tracing::warn!("{warning}");
+            self.write(
+                Stamp::now(),
+                TranscriptStream::Marker,
+                format!("file passed {} bytes", self.options.warn_bytes),
+                false,
+            );
+            self.size_warning = Some(warning);
+        }
+        record
+    }
+
+    fn flush_pending(&mut self, stamp: Stamp, stream: TranscriptStream) -> Option<TranscriptRecord>
+    {
+        let slot = stream.pending_slot()?;
+        if self.pending[slot].is_empty() {
+            return None;
+        }
+        let line = std::mem::take(&mut self.pending[slot]);
+        Some(self.write(stamp, stream, line_text(&line), true))
+    }
+
+    fn close(&mut self, marker: &str)
+    {
+        let stamp = Stamp::now();
+        for stream in [TranscriptStream::Stdin, TranscriptStream::Stdout, TranscriptStream::Stderr] {
+            self.flush_pending(stamp, stream);
+        }
+        self.write(stamp, TranscriptStream::Marker, marker.to_string(), false);
+        self.sync();
+    }
+
+    fn flush(&mut self)
+    {
+        if self.failed.is_none()
+            && let Err(e) = self.out.flush()
+        {
+            self.fail(e);
+        }
+    }
+
+    fn sync(&mut self)
+    {
+        if self.failed.is_none()
+            && let Err(e) = self.out.flush().and_then(|()| self.out.get_ref().sync_data())
+        {
+            self.fail(e);
+        }
+    }
+
+    fn fail(&mut self, error: io::Error)
+    {
+        tracing::warn!("Stopped recording transcript {}: {error}", self.path.display());
+        self.failed = Some(error);
+    }
+}
+
+/// Read the records of a transcript file, in file order.
+///
+/// ## Errors
+///
+/// Returns `Io` if the file can't be read and `InvalidArgument` for a line
+/// that isn't a record.
+pub fn read_transcript(path: &Path) -> Result<Vec<TranscriptRecord>>
+{
+    std::fs::read_to_string(path)?
+        .lines()
+        .map(|line| {
+            serde_json::from_str(line)
+                .map_err(|e| DebuggerError::InvalidArgument(format!("bad transcript record in {}: {e}", path.display())))
+        })
+        .collect()
+}
+
+/// Line bytes as text, without a trailing `\r`
+fn line_text(line: &[u8]) -> String
+{
+    String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned()
+}
+
+/// Records a pump's output and forwards the recorded lines
+struct TranscriptSink<F>
+{
+    transcript: Transcript,
+    stream: TranscriptStream,
+    emit: F,
+}
+
+impl<F> OutputSink for TranscriptSink<F>
+where
+    F: FnMut(TranscriptRecord) -> bool + Send,
+{
+    fn write(&mut self, bytes: &[u8]) -> bool
+    {
+        self.transcript
+            .record_stream(self.stream, bytes)
+            .into_iter()
+            .all(|record| (self.emit)(record))
+    }
+
+    fn finish(&mut self)
+    {
+        if let Some(record) = self.transcript.flush_stream(self.stream) {
+            (self.emit)(record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::events::DebuggerEvent;
+    use crate::types::{StopReason, ThreadId};
+
+    fn temp_path(name: &str) -> PathBuf
+    {
+        std::env::temp_dir().join(format!("ferros-transcript-{name}-{}.jsonl", std::process::id()))
+    }
+
+    fn read_records(path: &Path) -> Vec<TranscriptRecord>
+    {
+        let records = read_transcript(path).unwrap();
+        std::fs::remove_file(path).ok();
+        records
+    }
+
+    fn stopped_at(address: u64) -> EventEnvelope
+    {
+        EventEnvelope::new(DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(address),
+            thread: Some(ThreadId::from(1)),
+        })
+    }
+
+    #[test]
+    fn output_and_events_interleave_in_arrival_order()
+    {
+        let path = temp_path("interleave");
+        let transcript = Transcript::create(&path, TranscriptOptions::default()).unwrap();
+        transcript.record_stream(TranscriptStream::Stdout, b"name? ");
+        transcript.record_stream(TranscriptStream::Stdin, b"ferris\n");
+        transcript.record_stream(TranscriptStream::Stdout, b"hello\r\nbye");
+        transcript.record_event(&stopped_at(0x1000));
+        transcript.record_stream(TranscriptStream::Stderr, b"warning: low disk\n");
+        transcript.record_event(&EventEnvelope::new(DebuggerEvent::TargetResumed));
+        transcript.finish("detached").unwrap();
+
+        let records = read_records(&path);
+        let summary: Vec<_> = records
+            .iter()
+            .map(|record| (record.stream, record.text.as_str(), record.partial))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (TranscriptStream::Stdin, "ferris", false),
+                (TranscriptStream::Stdout, "name? hello", false),
+                (TranscriptStream::Event, "Hit breakpoint at 0x1000 (thread 1)", false),
+                (TranscriptStream::Stderr, "warning: low disk", false),
+                (TranscriptStream::Event, "Target resumed execution", false),
+                (TranscriptStream::Stdout, "bye", true),
+                (TranscriptStream::Marker, "detached", false),
+            ]
+        );
+        assert!(records.iter().enumerate().all(|(index, record)| record.seq == index as u64));
+        assert!(records.windows(2).all(|pair| pair[0].wall_ms <= pair[1].wall_ms));
+    }
+
+    #[test]
+    fn events_keep_their_emission_time()
+    {
+        let path = temp_path("stamps");
+        let transcript = Transcript::create(&path, TranscriptOptions::default()).unwrap();
+        // Emitted before the line was read, delivered after it
+        let stop = stopped_at(0x2000);
+        std::thread::sleep(Duration::from_millis(5));
+        let line = transcript.record_stream(TranscriptStream::Stdout, b"late line\n").remove(0);
+        transcript.record_event(&stop);
+        transcript.finish("done").unwrap();
+
+        let records = read_records(&path);
+        assert_eq!(records[0], line);
+        assert_eq!(records[1].stream, TranscriptStream::Event);
+        assert!(records[1].seq > line.seq && records[1].mono_us < line.mono_us);
+        assert!(line.elapsed() >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn sinks_forward_recorded_lines_and_flush_the_last_one()
+    {
+        let path = temp_path("sink");
+        let transcript = Transcript::create(&path, TranscriptOptions::default()).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut sink = transcript.sink(TranscriptStream::Stderr, {
+            let seen = Arc::clone(&seen);
+            move |record| {
+                seen.lock().unwrap().push((record.text, record.partial));
+                true
+            }
+        });
+        assert!(sink.write(b"one\ntw"));
+        assert!(sink.write(b"o\nthr"));
+        sink.finish();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                ("one".to_string(), false),
+                ("two".to_string(), false),
+                ("thr".to_string(), true)
+            ]
+        );
+        transcript.finish("done").unwrap();
+        assert_eq!(read_records(&path).len(), 4);
+    }
+
+    #[test]
+    fn crash_flush_marks_partial_lines()
+    {
+        let path = temp_path("crash");
+        let transcript = Transcript::create(
+            &path,
+            TranscriptOptions {
+                fsync: FsyncPolicy::Always,
+                ..TranscriptOptions::default()
+            },
+        )
+        .unwrap();
+        transcript.record_stream(TranscriptStream::Stdout, b"progress: 41%");
+        transcript.flush_after_crash();
+
+        let records = read_records(&path);
+        assert_eq!(records.len(), 2);
+        assert!(records[0].partial && records[0].text == "progress: 41%");
+        assert_eq!(records[1].stream, TranscriptStream::Marker);
+        assert!(records[1].text.starts_with("ferros crashed"));
+    }
+
+    #[test]
+    fn size_warning_fires_once_past_the_threshold()
+    {
+        let path = temp_path("size");
+        let transcript = Transcript::create(
+            &path,
+            TranscriptOptions {
+                warn_bytes: 200,
+                ..TranscriptOptions::default()
+            },
+        )
+        .unwrap();
+        transcript.record_stream(TranscriptStream::Stdout, b"short\n");
+        assert_eq!(transcript.take_size_warning(), None);
+        for _ in 0..5 {
+            transcript.record_stream(TranscriptStream::Stdout, b"a somewhat longer line of output\n");
+        }
+        let warning = transcript.take_size_warning().unwrap();
+        assert!(warning.contains(&path.display().to_string()));
+        assert_eq!(transcript.take_size_warning(), None);
+        transcript.finish("done").unwrap();
+
+        let markers = read_records(&path)
+            .into_iter()
+            .filter(|record| record.stream == TranscriptStream::Marker)
+            .count();
+        assert_eq!(markers, 2);
+    }
+
+    #[test]
+    fn fsync_policies_parse_and_round_trip()
+    {
+        for policy in [FsyncPolicy::Never, FsyncPolicy::Events, FsyncPolicy::Always] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        assert_eq!("ALWAYS".parse(), Ok(FsyncPolicy::Always));
+        assert!("sometimes".parse::<FsyncPolicy>().is_err());
+    }
+}
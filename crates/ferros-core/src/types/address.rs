@@ -2,6 +2,11 @@
 
 use std::fmt;
 use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+
+use crate::error::{DebuggerError, Result};
 
 /// Strongly typed memory address
 ///
@@ -156,6 +161,15 @@ impl fmt::Display for Address
     }
 }
 
+/// Serializes as a `0x`-prefixed hex string, the way the MI protocol sends addresses
+impl Serialize for Address
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    {
+        serializer.serialize_str(&format!("0x{:x}", self.0))
+    }
+}
+
 impl Add<u64> for Address
 {
     type Output = Address;
@@ -175,3 +189,362 @@ impl Sub<u64> for Address
         Address(self.0.wrapping_sub(rhs))
     }
 }
+
+/// How tables print addresses
+///
+/// [`Address`]'s `Display` always prints 16 hex digits, which is what logs and
+/// errors use and what other tools print. Tables can choose a narrower style
+/// and format their addresses through an [`AddressFormatter`] built from the
+/// rows they show, so every row of a table has the same width.
+///
+/// ## Example
+///
+/// ```rust
+/// use ferros_core::types::AddressFormat;
+///
+/// let rows = [0x1_0000_4000_u64, 0x1_6fe0_fff0];
+/// let formatter = AddressFormat::Trimmed.formatter(rows);
+/// assert_eq!(formatter.format(0x1000_u64), "0x000001000");
+/// assert_eq!(
+///     AddressFormat::Full16.formatter(rows).format(0x1000_u64),
+///     "0x0000000000001000"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AddressFormat
+{
+    /// 16 hex digits, as `Display` prints them
+    #[default]
+    Full16,
+    /// As many hex digits as the largest address in the table needs
+    Trimmed,
+    /// 12 hex digits, enough for 48-bit user-space addresses
+    Short12,
+}
+
+impl AddressFormat
+{
+    /// The style after this one, for a key that cycles through them
+    #[must_use]
+    pub const fn next(self) -> Self
+    {
+        match self {
+            Self::Full16 => Self::Trimmed,
+            Self::Trimmed => Self::Short12,
+            Self::Short12 => Self::Full16,
+        }
+    }
+
+    /// Hex digits a table whose largest address is `largest` prints
+    ///
+    /// Addresses are never cut short: one that doesn't fit in 12 digits
+    /// widens a `Short12` table.
+    #[must_use]
+    pub const fn digits(self, largest: u64) -> usize
+    {
+        let needed = if largest == 0 {
+            1
+        } else {
+            (64 - largest.leading_zeros() as usize).div_ceil(4)
+        };
+        match self {
+            Self::Full16 => 16,
+            Self::Trimmed => needed,
+            Self::Short12 => {
+                if needed > 12 {
+                    needed
+                } else {
+                    12
+                }
+            }
+        }
+    }
+
+    /// Formatter for a table showing `addresses`
+    #[must_use]
+    pub fn formatter<A: Into<u64>>(self, addresses: impl IntoIterator<Item = A>) -> AddressFormatter
+    {
+        let largest = addresses.into_iter().map(Into::into).max().unwrap_or(0);
+        AddressFormatter {
+            digits: self.digits(largest),
+        }
+    }
+}
+
+impl fmt::Display for AddressFormat
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::Full16 => "full",
+            Self::Trimmed => "trimmed",
+            Self::Short12 => "short",
+        })
+    }
+}
+
+impl FromStr for AddressFormat
+{
+    type Err = String;
+
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err>
+    {
+        match text {
+            "full" | "full16" => Ok(Self::Full16),
+            "trimmed" => Ok(Self::Trimmed),
+            "short" | "short12" => Ok(Self::Short12),
+            _ => Err(format!("Unknown address format '{text}' (full, trimmed, short)")),
+        }
+    }
+}
+
+/// Prints the addresses of one table at a shared width
+///
+/// Build one per render with [`AddressFormat::formatter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressFormatter
+{
+    digits: usize,
+}
+
+impl AddressFormatter
+{
+    /// Hex digits after the `0x`
+    #[must_use]
+    pub const fn digits(self) -> usize
+    {
+        self.digits
+    }
+
+    /// Characters a formatted address takes, `0x` included
+    #[must_use]
+    pub const fn width(self) -> usize
+    {
+        self.digits + 2
+    }
+
+    /// `address` as `0x` and zero-padded hex
+    #[must_use]
+    pub fn format(self, address: impl Into<u64>) -> String
+    {
+        format!("0x{}", self.hex(address))
+    }
+
+    /// `address` as zero-padded hex without the `0x`, for hex dump rows
+    #[must_use]
+    pub fn hex(self, address: impl Into<u64>) -> String
+    {
+        format!("{:0width$x}", address.into(), width = self.digits)
+    }
+}
+
+/// An address as a user types it: a number, or a symbol plus an offset
+///
+/// Parse one with [`parse_address`] and turn it into a runtime address with
+/// [`Debugger::resolve_address`](crate::debugger::Debugger::resolve_address).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AddressExpr
+{
+    /// A literal address
+    Absolute(Address),
+    /// `offset` bytes from the entry of the function `name`
+    Symbol
+    {
+        /// Function name, matched like [`BreakpointLocation::Function`](crate::breakpoints::BreakpointLocation::Function)
+        name: String,
+        /// Signed byte offset from the function's entry
+        offset: i64,
+    },
+}
+
+impl fmt::Display for AddressExpr
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            Self::Absolute(address) => write!(f, "{address}"),
+            Self::Symbol { name, offset: 0 } => write!(f, "{name}"),
+            Self::Symbol { name, offset } if *offset < 0 => write!(f, "{name}-0x{:x}", offset.unsigned_abs()),
+            Self::Symbol { name, offset } => write!(f, "{name}+0x{offset:x}"),
+        }
+    }
+}
+
+/// Parse an address typed by a user
+///
+/// Accepts `0x`-prefixed hex (`0x1000`), decimal (`4096`), a function name
+/// (`main`, `my_crate::run`, `-[NSView drawRect:]`) and a function name with a
+/// hex or decimal offset (`main+0x10`, `my_crate::run-8`). Numbers must be
+/// plain digits: signs, whitespace and trailing garbage are rejected rather
+/// than ignored.
+///
+/// ## Errors
+///
+/// Returns `InvalidArgument` for empty input, malformed or overflowing
+/// numbers, and names that start with a digit.
+///
+/// ## Example
+///
+/// ```rust
+/// use ferros_core::types::{Address, AddressExpr, parse_address};
+///
+/// assert_eq!(
+///     parse_address("0x1000")?,
+///     AddressExpr::Absolute(Address::from(0x1000))
+/// );
+/// assert_eq!(
+///     parse_address("main+0x10")?,
+///     AddressExpr::Symbol {
+///         name: "main".to_string(),
+///         offset: 0x10
+///     }
+/// );
+/// assert!(parse_address("0x10zz").is_err());
+/// # Ok::<(), ferros_core::DebuggerError>(())
+/// ```
+pub fn parse_address(text: &str) -> Result<AddressExpr>
+{
+    let invalid = |reason: &str| DebuggerError::InvalidArgument(format!("Invalid address {text:?}: {reason}"));
+    let text_trimmed = text.trim();
+    if text_trimmed.is_empty() {
+        return Err(invalid("empty"));
+    }
+    if text_trimmed.starts_with(|c: char| c.is_ascii_digit()) {
+        return parse_number(text_trimmed)
+            .map(|value| AddressExpr::Absolute(Address(value)))
+            .ok_or_else(|| invalid("expected a hex (0x...) or decimal number"));
+    }
+
+    // The offset follows the last `+` or `-`; a leading one belongs to an
+    // Objective-C method name such as `-[NSView drawRect:]`
+    let (name, offset) = match text_trimmed.rfind(['+', '-']).filter(|&split| split > 0) {
+        Some(split) => {
+            let magnitude = parse_number(text_trimmed[split + 1..].trim_start())
+                .and_then(|value| i64::try_from(value).ok())
+                .ok_or_else(|| invalid("expected a hex (0x...) or decimal offset"))?;
+            let sign = if text_trimmed.as_bytes()[split] == b'-' { -1 } else { 1 };
+            (text_trimmed[..split].trim_end(), sign * magnitude)
+        }
+        None => (text_trimmed, 0),
+    };
+    let objc_method = name.starts_with("-[") || name.starts_with("+[");
+    if !objc_method && !name.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '<') {
+        return Err(invalid("expected a number or a symbol name"));
+    }
+    Ok(AddressExpr::Symbol {
+        name: name.to_string(),
+        offset,
+    })
+}
+
+/// Plain `0x` hex or decimal digits, or `None`
+fn parse_number(text: &str) -> Option<u64>
+{
+    let (digits, radix) = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(digits) => (digits, 16),
+        None => (text, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    u64::from_str_radix(digits, radix).ok()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn symbol(name: &str, offset: i64) -> AddressExpr
+    {
+        AddressExpr::Symbol {
+            name: name.to_string(),
+            offset,
+        }
+    }
+
+    #[test]
+    fn numbers_and_symbols_parse()
+    {
+        assert_eq!(parse_address("0x1000").unwrap(), AddressExpr::Absolute(Address(0x1000)));
+        assert_eq!(parse_address("0X1f").unwrap(), AddressExpr::Absolute(Address(0x1f)));
+        assert_eq!(parse_address("4096").unwrap(), AddressExpr::Absolute(Address(4096)));
+        assert_eq!(parse_address(" main ").unwrap(), symbol("main", 0));
+        assert_eq!(parse_address("my_crate::run+0x10").unwrap(), symbol("my_crate::run", 0x10));
+        assert_eq!(parse_address("main - 8").unwrap(), symbol("main", -8));
+        assert_eq!(
+            parse_address("-[NSView drawRect:]").unwrap(),
+            symbol("-[NSView drawRect:]", 0)
+        );
+        assert_eq!(
+            parse_address("-[NSView drawRect:]+4").unwrap(),
+            symbol("-[NSView drawRect:]", 4)
+        );
+        assert_eq!(symbol("main", -8).to_string(), "main-0x8");
+    }
+
+    #[test]
+    fn trimmed_width_fits_the_largest_address()
+    {
+        assert_eq!(AddressFormat::Trimmed.digits(0), 1);
+        assert_eq!(AddressFormat::Trimmed.digits(0xf), 1);
+        assert_eq!(AddressFormat::Trimmed.digits(0x10), 2);
+        assert_eq!(AddressFormat::Trimmed.digits(0x1_0000_4000), 9);
+        assert_eq!(AddressFormat::Trimmed.digits(u64::MAX), 16);
+
+        let formatter = AddressFormat::Trimmed.formatter([Address(0x1000), Address(0x1_6fe0_fff0)]);
+        assert_eq!(formatter.width(), 11);
+        assert_eq!(formatter.format(Address(0x1000)), "0x000001000");
+        assert_eq!(formatter.hex(0x1_6fe0_fff0_u64), "16fe0fff0");
+        assert_eq!(AddressFormat::Trimmed.formatter(Vec::<u64>::new()).format(0_u64), "0x0");
+    }
+
+    #[test]
+    fn each_style_formats_at_its_width()
+    {
+        let rows = [0x1_0000_4000_u64, 0x7000_0000];
+        let formatted = |style: AddressFormat| style.formatter(rows).format(0x1_0000_4000_u64);
+        assert_eq!(formatted(AddressFormat::Full16), Address(0x1_0000_4000).to_string());
+        assert_eq!(formatted(AddressFormat::Trimmed), "0x100004000");
+        assert_eq!(formatted(AddressFormat::Short12), "0x000100004000");
+
+        // Nothing is cut short: a kernel-half address widens a short table
+        let wide = AddressFormat::Short12.formatter([0xffff_8000_0000_1000_u64, 0x1000]);
+        assert_eq!(wide.format(0x1000_u64), "0x0000000000001000");
+        assert_eq!(AddressFormat::Short12.digits(0x1_0000_4000), 12);
+    }
+
+    #[test]
+    fn styles_cycle_and_parse()
+    {
+        let mut style = AddressFormat::default();
+        assert_eq!(style, AddressFormat::Full16);
+        for expected in [AddressFormat::Trimmed, AddressFormat::Short12, AddressFormat::Full16] {
+            style = style.next();
+            assert_eq!(style, expected);
+            assert_eq!(style.to_string().parse::<AddressFormat>(), Ok(style));
+        }
+        assert_eq!("short12".parse::<AddressFormat>(), Ok(AddressFormat::Short12));
+        assert!("wide".parse::<AddressFormat>().is_err());
+    }
+
+    #[test]
+    fn odd_inputs_are_rejected()
+    {
+        for text in [
+            "",
+            "  ",
+            "0x",
+            "0x10zz",
+            "10 20",
+            "+10",
+            "1e3",
+            "0x1_000",
+            "99999999999999999999",
+            "main+0xzz",
+            "+0x10",
+        ] {
+            assert!(parse_address(text).is_err(), "{text:?} should not parse");
+        }
+    }
+}
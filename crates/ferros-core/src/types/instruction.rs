@@ -0,0 +1,60 @@
+//! Disassembled instruction types.
+
+use super::Address;
+
+/// One machine instruction decoded from target memory.
+///
+/// Returned by [`Debugger::disassemble`](crate::Debugger::disassemble). The
+/// bytes are the original ones: breakpoint traps the debugger wrote are
+/// already replaced, so a breakpoint doesn't show up as `brk` or `int3`.
+///
+/// ## Example
+///
+/// ```rust
+/// use ferros_core::types::{Address, DisassembledInstruction};
+///
+/// let nop = DisassembledInstruction {
+///     address: Address::from(0x1000),
+///     bytes: vec![0x1f, 0x20, 0x03, 0xd5],
+///     mnemonic: "nop".to_string(),
+///     operands: String::new(),
+///     is_current_pc: true,
+/// };
+/// assert_eq!(nop.end(), Address::from(0x1004));
+/// assert_eq!(nop.text(), "nop");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledInstruction
+{
+    /// Where the instruction starts
+    pub address: Address,
+    /// The instruction's encoding
+    pub bytes: Vec<u8>,
+    /// Mnemonic, or `(bad)` for bytes that don't decode
+    pub mnemonic: String,
+    /// Operands as the decoder prints them; empty when there are none
+    pub operands: String,
+    /// Whether the active thread's PC is at this instruction
+    pub is_current_pc: bool,
+}
+
+impl DisassembledInstruction
+{
+    /// Address just past the instruction
+    #[must_use]
+    pub fn end(&self) -> Address
+    {
+        self.address + self.bytes.len() as u64
+    }
+
+    /// Mnemonic and operands as one line of assembly
+    #[must_use]
+    pub fn text(&self) -> String
+    {
+        if self.operands.is_empty() {
+            self.mnemonic.clone()
+        } else {
+            format!("{} {}", self.mnemonic, self.operands)
+        }
+    }
+}
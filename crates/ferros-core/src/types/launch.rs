@@ -0,0 +1,198 @@
+//! How a launched target is started: arguments, environment and working directory.
+//!
+//! [`LaunchOptions`] is passed to
+//! [`Debugger::launch_with_options`](crate::Debugger::launch_with_options).
+//! The target's environment is built by [`LaunchOptions::environment`]:
+//!
+//! 1. ferros's own environment, unless `inherit_env` is off
+//! 2. the debugging defaults in [`DEBUG_ENVIRONMENT`], where not already set
+//! 3. every `env` entry in order, so a later one wins
+//!
+//! `clear_env` skips the first two steps, for a target that should see only
+//! what was given with `env`.
+//!
+//! ```rust
+//! use ferros_core::types::LaunchOptions;
+//!
+//! let options = LaunchOptions::new(["/bin/app", "--serve"])
+//!     .env("APP_CONFIG", "dev.toml")
+//!     .cwd("/tmp")
+//!     .clear_env();
+//! let environment = options.environment(std::env::vars());
+//! assert_eq!(
+//!     environment,
+//!     [("APP_CONFIG".to_string(), "dev.toml".to_string())]
+//! );
+//! ```
+
+use std::path::PathBuf;
+
+use crate::error::{DebuggerError, Result};
+
+/// Variables set for the target unless it sets them itself or `clear_env` is on.
+///
+/// Backtraces on panic for Rust targets, and allocation stack logging on
+/// macOS for everything else.
+pub const DEBUG_ENVIRONMENT: &[(&str, &str)] = &[("RUST_BACKTRACE", "1"), ("MallocStackLogging", "1")];
+
+/// Arguments, environment and working directory for a launched target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct LaunchOptions
+{
+    /// Command-line arguments, the first being the program name (`argv[0]`).
+    pub args: Vec<String>,
+    /// Variables set for the target, applied in order over the rest.
+    pub env: Vec<(String, String)>,
+    /// Start from ferros's own environment. On by default.
+    pub inherit_env: bool,
+    /// Working directory; ferros's own when `None`.
+    pub cwd: Option<PathBuf>,
+    /// Start from an empty environment: neither ferros's variables nor the
+    /// [`DEBUG_ENVIRONMENT`] defaults, only `env`.
+    pub clear_env: bool,
+}
+
+impl Default for LaunchOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            args: Vec::new(),
+            env: Vec::new(),
+            inherit_env: true,
+            cwd: None,
+            clear_env: false,
+        }
+    }
+}
+
+impl LaunchOptions
+{
+    /// Launch with `args`, the first being the program name, and ferros's environment.
+    pub fn new<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            args: args.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Set `key` to `value` in the target's environment.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self
+    {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Whether the target starts from ferros's own environment.
+    pub fn inherit_env(mut self, inherit: bool) -> Self
+    {
+        self.inherit_env = inherit;
+        self
+    }
+
+    /// Start the target in `dir`.
+    pub fn cwd(mut self, dir: impl Into<PathBuf>) -> Self
+    {
+        self.cwd = Some(dir.into());
+        self
+    }
+
+    /// Give the target only the variables set with [`env`](Self::env).
+    pub fn clear_env(mut self) -> Self
+    {
+        self.clear_env = true;
+        self
+    }
+
+    /// The target's environment, given ferros's own as `inherited`.
+    ///
+    /// Each variable appears once, sorted by name. See the [module docs](self)
+    /// for the order things are applied in.
+    #[must_use]
+    pub fn environment(&self, inherited: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String)>
+    {
+        let mut vars = std::collections::BTreeMap::new();
+        if !self.clear_env {
+            if self.inherit_env {
+                vars.extend(inherited);
+            }
+            for (key, value) in DEBUG_ENVIRONMENT {
+                vars.entry((*key).to_string()).or_insert_with(|| (*value).to_string());
+            }
+        }
+        vars.extend(self.env.iter().cloned());
+        vars.into_iter().collect()
+    }
+}
+
+/// Parse `KEY=VALUE`, as given to `--env`. The value may be empty or contain `=`.
+///
+/// ## Errors
+///
+/// - `InvalidArgument`: There's no `=`, or the name is empty
+pub fn parse_env_spec(spec: &str) -> Result<(String, String)>
+{
+    match spec.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(DebuggerError::InvalidArgument(format!("expected KEY=VALUE, got '{spec}'"))),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)>
+    {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn the_environment_layers_inherited_defaults_and_overrides()
+    {
+        let inherited = vars(&[("HOME", "/Users/me"), ("RUST_BACKTRACE", "full")]);
+
+        let options = LaunchOptions::new(["app"]).env("APP_MODE", "dev").env("APP_MODE", "test");
+        assert_eq!(
+            options.environment(inherited.clone()),
+            vars(&[
+                ("APP_MODE", "test"),
+                ("HOME", "/Users/me"),
+                ("MallocStackLogging", "1"),
+                ("RUST_BACKTRACE", "full"),
+            ])
+        );
+
+        let options = LaunchOptions::new(["app"]).inherit_env(false).env("HOME", "/tmp");
+        assert_eq!(
+            options.environment(inherited.clone()),
+            vars(&[("HOME", "/tmp"), ("MallocStackLogging", "1"), ("RUST_BACKTRACE", "1")])
+        );
+
+        let options = LaunchOptions::new(["app"]).clear_env().env("ONLY", "this");
+        assert_eq!(options.environment(inherited), vars(&[("ONLY", "this")]));
+    }
+
+    #[test]
+    fn env_specs_split_at_the_first_equals()
+    {
+        assert_eq!(parse_env_spec("A=b=c").unwrap(), ("A".to_string(), "b=c".to_string()));
+        assert_eq!(parse_env_spec("EMPTY=").unwrap(), ("EMPTY".to_string(), String::new()));
+        assert!(
+            parse_env_spec("NOVALUE")
+                .unwrap_err()
+                .to_string()
+                .contains("expected KEY=VALUE")
+        );
+        assert!(parse_env_spec("=x").is_err());
+    }
+}
@@ -7,14 +7,27 @@
 //! knowing whether we're on macOS, Linux, or Windows.
 
 pub mod address;
+pub mod instruction;
+pub mod launch;
+pub mod payload;
 pub mod process;
 pub mod registers;
 pub mod stack;
 pub mod symbols;
+pub mod thread;
 
 // Re-export all public types
-pub use address::Address;
-pub use process::{Architecture, MemoryRegion, MemoryRegionId, ProcessId, StopReason, ThreadId};
-pub use registers::{Arm64Register, FloatingPointState, RegisterId, Registers, VectorRegisterValue, X86_64Register};
+pub use address::{Address, AddressExpr, AddressFormat, AddressFormatter, parse_address};
+pub use instruction::DisassembledInstruction;
+pub use launch::{LaunchOptions, parse_env_spec};
+pub use payload::{check_writable, parse_payload, parse_write_spec};
+pub use process::{
+    Architecture, BadAccessKind, MemoryCacheStats, MemoryRegion, MemoryRegionId, ProcessId, StopReason, ThreadId,
+};
+pub use registers::{
+    Arm64Register, FloatingPointState, RegisterCategory, RegisterId, RegisterRow, Registers, VectorRegisterValue,
+    X86_64Register, argument_values, register_by_name, register_display_order,
+};
 pub use stack::{FrameId, FrameKind, FrameStatus, StackFrame};
 pub use symbols::{FunctionParameter, SourceLocation, SymbolLanguage, SymbolName};
+pub use thread::{StopPolicy, ThreadInfo, ThreadRunState};
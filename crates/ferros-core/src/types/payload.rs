@@ -0,0 +1,315 @@
+//! Bytes to write to target memory, parsed from user input.
+//!
+//! [`parse_payload`] accepts three forms, used by the TUI's `:write` and the
+//! `memory-write` command, and for the patterns `:find` searches for:
+//!
+//! - Hex bytes, in the order they are written: `de ad be ef` or `deadbeef`
+//! - A sized integer, `<type>:<value>`: `u8`-`u64` or `i8`-`i64`, little-endian
+//!   like the targets ferros supports, or big-endian with a `be` suffix
+//!   (`u32be:0x41424344`). The value is decimal or `0x` hex; signed types take
+//!   a leading `-`
+//! - A quoted string, `"GET /\r\n"`, written without a terminating NUL;
+//!   `\n`, `\r`, `\t`, `\0`, `\\`, `\"` and `\xNN` escapes are understood
+//!
+//! ```rust
+//! use ferros_core::types::parse_payload;
+//!
+//! assert_eq!(parse_payload("de ad be ef")?, [0xde, 0xad, 0xbe, 0xef]);
+//! assert_eq!(parse_payload("u32:0x41424344")?, *b"DCBA");
+//! assert_eq!(parse_payload("u16be:0x4142")?, *b"AB");
+//! assert_eq!(parse_payload(r#""hi\n""#)?, *b"hi\n");
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+//!
+//! [`check_writable`] checks the destination against the target's regions
+//! first, so writing to code or a read-only mapping is refused with the
+//! region's permissions rather than a bare kernel error.
+
+use crate::error::{DebuggerError, Result};
+use crate::types::{Address, AddressExpr, MemoryRegion, parse_address};
+
+/// Parse a memory write payload into the bytes to write.
+///
+/// ## Errors
+///
+/// - `InvalidArgument`: The text is empty or malformed, or an integer doesn't fit its type
+pub fn parse_payload(text: &str) -> Result<Vec<u8>>
+{
+    let invalid = |reason: String| DebuggerError::InvalidArgument(format!("Invalid payload {text:?}: {reason}"));
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err(invalid("it is empty".to_string()));
+    }
+    if let Some(body) = trimmed.strip_prefix('"') {
+        return quoted(body).map_err(invalid);
+    }
+    if let Some((ty, value)) = trimmed.split_once(':') {
+        return sized_integer(ty.trim(), value.trim()).map_err(invalid);
+    }
+    hex_bytes(trimmed).map_err(invalid)
+}
+
+/// Parse `ADDRESS=PAYLOAD`, as given to `--write`.
+///
+/// ## Errors
+///
+/// - `InvalidArgument`: There's no `=`, or either side doesn't parse
+pub fn parse_write_spec(spec: &str) -> Result<(AddressExpr, Vec<u8>)>
+{
+    let (address, payload) = spec
+        .split_once('=')
+        .ok_or_else(|| DebuggerError::InvalidArgument(format!("expected ADDRESS=PAYLOAD, got '{spec}'")))?;
+    Ok((parse_address(address.trim())?, parse_payload(payload)?))
+}
+
+/// Check that all `len` bytes at `start` are mapped in `regions` and writable.
+///
+/// ## Errors
+///
+/// - `InvalidArgument`: Part of the range isn't in any region, or is in one
+///   without `w`, named with its permission string
+pub fn check_writable(regions: &[MemoryRegion], start: Address, len: usize) -> Result<()>
+{
+    let end = start.value().saturating_add(len as u64);
+    let mut cursor = start;
+    while cursor.value() < end {
+        let refuse =
+            |reason: String| DebuggerError::InvalidArgument(format!("Can't write {len} bytes at {start}: {reason}"));
+        let Some(region) = regions.iter().find(|region| region.contains(cursor)) else {
+            return Err(refuse(format!("{cursor} is not mapped")));
+        };
+        if !region.is_writable() {
+            let name = region.name.as_deref().map_or_else(String::new, |name| format!(" ({name})"));
+            return Err(refuse(format!(
+                "{cursor} is in the region at {}{name} with permissions {}",
+                region.start, region.permissions
+            )));
+        }
+        cursor = region.end;
+    }
+    Ok(())
+}
+
+/// `de ad be ef` or `deadbeef`
+fn hex_bytes(text: &str) -> std::result::Result<Vec<u8>, String>
+{
+    let mut bytes = Vec::new();
+    for token in text.split_whitespace() {
+        if token.starts_with("0x") || token.starts_with("0X") {
+            return Err(format!("`{token}` looks like an integer; give its size, e.g. u32:{token}"));
+        }
+        if let Some(bad) = token.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(format!("`{bad}` in `{token}` is not a hex digit"));
+        }
+        if token.len() % 2 != 0 {
+            return Err(format!("`{token}` has an odd number of hex digits"));
+        }
+        for pair in token.as_bytes().chunks(2) {
+            let pair = std::str::from_utf8(pair).unwrap_or_default();
+            bytes.push(u8::from_str_radix(pair, 16).map_err(|e| e.to_string())?);
+        }
+    }
+    Ok(bytes)
+}
+
+/// `u32:0x41414141`, `i16:-2`, `u64be:1`
+fn sized_integer(ty: &str, value: &str) -> std::result::Result<Vec<u8>, String>
+{
+    let (name, big_endian) = match ty.strip_suffix("be") {
+        Some(name) => (name, true),
+        None => (ty.strip_suffix("le").unwrap_or(ty), false),
+    };
+    let (signed, bits) = match name.split_at_checked(1) {
+        Some(("u", bits)) => (false, bits),
+        Some(("i", bits)) => (true, bits),
+        _ => return Err(format!("unknown type `{ty}`, expected u8-u64 or i8-i64")),
+    };
+    let size = match bits {
+        "8" => 1,
+        "16" => 2,
+        "32" => 4,
+        "64" => 8,
+        _ => return Err(format!("unknown type `{ty}`, expected u8-u64 or i8-i64")),
+    };
+
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let magnitude = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse::<u64>(),
+    }
+    .map_err(|_| format!("`{value}` is not a decimal or 0x hex number"))?;
+    let bits = size * 8;
+    let raw = if negative {
+        let limit = 1_u128 << (bits - 1);
+        if !signed || u128::from(magnitude) > limit {
+            return Err(format!("`{value}` doesn't fit in {name}"));
+        }
+        0_u64.wrapping_sub(magnitude)
+    } else {
+        let max = if signed {
+            (1_u128 << (bits - 1)) - 1
+        } else {
+            (1_u128 << bits) - 1
+        };
+        if u128::from(magnitude) > max {
+            return Err(format!("`{value}` doesn't fit in {name}"));
+        }
+        magnitude
+    };
+
+    let mut bytes = raw.to_le_bytes()[..size].to_vec();
+    if big_endian {
+        bytes.reverse();
+    }
+    Ok(bytes)
+}
+
+/// The rest of a quoted string after its opening quote
+fn quoted(body: &str) -> std::result::Result<Vec<u8>, String>
+{
+    let mut bytes = Vec::new();
+    let mut chars = body.chars();
+    loop {
+        let Some(c) = chars.next() else {
+            return Err("the string has no closing quote".to_string());
+        };
+        match c {
+            '"' => break,
+            '\\' => {
+                let escaped = chars.next().ok_or("the string ends in a lone `\\`")?;
+                match escaped {
+                    'n' => bytes.push(b'\n'),
+                    'r' => bytes.push(b'\r'),
+                    't' => bytes.push(b'\t'),
+                    '0' => bytes.push(0),
+                    '\\' | '"' => bytes.push(escaped as u8),
+                    'x' => {
+                        let hex: String = chars.by_ref().take(2).collect();
+                        let byte = u8::from_str_radix(&hex, 16)
+                            .ok()
+                            .filter(|_| hex.len() == 2)
+                            .ok_or_else(|| format!("`\\x{hex}` needs two hex digits"))?;
+                        bytes.push(byte);
+                    }
+                    other => return Err(format!("unknown escape `\\{other}`")),
+                }
+            }
+            c => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            }
+        }
+    }
+    let rest = chars.as_str().trim();
+    if !rest.is_empty() {
+        return Err(format!("unexpected `{rest}` after the closing quote"));
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn error(text: &str) -> String
+    {
+        parse_payload(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn hex_bytes_keep_their_order()
+    {
+        assert_eq!(parse_payload("de ad be ef").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(parse_payload("DEADbeef 00").unwrap(), [0xde, 0xad, 0xbe, 0xef, 0]);
+        assert_eq!(parse_payload("  90  ").unwrap(), [0x90]);
+    }
+
+    #[test]
+    fn sized_integers_are_little_endian_unless_marked_big()
+    {
+        assert_eq!(parse_payload("u32:0x41424344").unwrap(), [0x44, 0x43, 0x42, 0x41]);
+        assert_eq!(parse_payload("u32le:0x41424344").unwrap(), [0x44, 0x43, 0x42, 0x41]);
+        assert_eq!(parse_payload("u32be:0x41424344").unwrap(), [0x41, 0x42, 0x43, 0x44]);
+        assert_eq!(parse_payload("u16:258").unwrap(), [2, 1]);
+        assert_eq!(parse_payload("u8:255").unwrap(), [0xff]);
+        assert_eq!(parse_payload("i16:-2").unwrap(), [0xfe, 0xff]);
+        assert_eq!(parse_payload("i8:-128").unwrap(), [0x80]);
+        assert_eq!(parse_payload("i64be:-1").unwrap(), [0xff; 8]);
+        assert_eq!(parse_payload("u64:0xffffffffffffffff").unwrap(), [0xff; 8]);
+    }
+
+    #[test]
+    fn quoted_strings_are_written_without_a_nul()
+    {
+        assert_eq!(parse_payload(r#""AAAA""#).unwrap(), *b"AAAA");
+        assert_eq!(parse_payload(r#""a\"b\\c\x41\0""#).unwrap(), *b"a\"b\\cA\0");
+        assert_eq!(parse_payload(r#""é""#).unwrap(), "é".as_bytes());
+    }
+
+    #[test]
+    fn write_specs_split_at_the_first_equals()
+    {
+        let (address, bytes) = parse_write_spec(r#"buf+8="a=b""#).unwrap();
+        assert_eq!(address, parse_address("buf+8").unwrap());
+        assert_eq!(bytes, *b"a=b");
+        assert!(
+            parse_write_spec("0x1000")
+                .unwrap_err()
+                .to_string()
+                .contains("expected ADDRESS=PAYLOAD")
+        );
+        assert!(parse_write_spec("0x1000=").unwrap_err().to_string().contains("it is empty"));
+    }
+
+    #[test]
+    fn writes_are_checked_against_region_permissions()
+    {
+        let region = |id, start: u64, end: u64, permissions: &str, name: Option<&str>| {
+            MemoryRegion::new(
+                crate::types::MemoryRegionId(id),
+                Address::from(start),
+                Address::from(end),
+                permissions.to_string(),
+                name.map(ToString::to_string),
+            )
+        };
+        let regions = [
+            region(0, 0x1000, 0x2000, "r-x", Some("/bin/demo")),
+            region(1, 0x2000, 0x3000, "rw-", None),
+            region(2, 0x3000, 0x4000, "rw-", None),
+        ];
+
+        assert!(check_writable(&regions, Address::from(0x2ffe), 4).is_ok());
+        assert!(check_writable(&regions, Address::from(0x2000), 0).is_ok());
+        let code = check_writable(&regions, Address::from(0x1ffe), 4).unwrap_err().to_string();
+        assert!(
+            code.contains("0x0000000000001ffe is in the region at 0x0000000000001000 (/bin/demo) with permissions r-x"),
+            "{code}"
+        );
+        let unmapped = check_writable(&regions, Address::from(0x3ffe), 4).unwrap_err().to_string();
+        assert!(unmapped.contains("0x0000000000004000 is not mapped"), "{unmapped}");
+    }
+
+    #[test]
+    fn malformed_payloads_say_what_is_wrong()
+    {
+        assert!(error("").contains("it is empty"));
+        assert!(error("de ad b").contains("odd number of hex digits"));
+        assert!(error("de zz").contains("`z` in `zz` is not a hex digit"));
+        assert!(error("0x41414141").contains("give its size, e.g. u32:0x41414141"));
+        assert!(error("u8:256").contains("doesn't fit in u8"));
+        assert!(error("i8:128").contains("doesn't fit in i8"));
+        assert!(error("i8:-129").contains("doesn't fit in i8"));
+        assert!(error("u32:-1").contains("doesn't fit in u32"));
+        assert!(error("u24:1").contains("unknown type `u24`"));
+        assert!(error("u32:twelve").contains("not a decimal or 0x hex number"));
+        assert!(error(r#""open"#).contains("no closing quote"));
+        assert!(error(r#""a" b"#).contains("unexpected `b` after the closing quote"));
+        assert!(error(r#""\q""#).contains("unknown escape `\\q`"));
+        assert!(error(r#""\x4""#).contains("needs two hex digits"));
+    }
+}
@@ -2,6 +2,9 @@
 
 use std::fmt;
 
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
 use super::Address;
 
 /// Process identifier (PID)
@@ -51,21 +54,24 @@ impl From<ProcessId> for u32
 
 /// Thread identifier
 ///
-/// A thread identifier uniquely identifies a thread within a process. The exact
-/// representation is platform-specific:
+/// A thread identifier uniquely identifies a thread within a process and stays
+/// the same for the thread's whole lifetime, so it is safe to store across thread
+/// list refreshes (breakpoint thread filters, the active thread, UI selections).
+/// The exact value is platform-specific:
 ///
-/// - **macOS**: Mach thread port (`thread_act_t`), which is a `mach_port_t`
+/// - **macOS**: Global thread id from `thread_info(THREAD_IDENTIFIER_INFO)`, not
+///   the Mach thread port (ports change between enumerations and are reused)
 /// - **Linux**: Thread ID (TID) from the kernel
-/// - **Windows**: Thread handle or thread ID
+/// - **Windows**: Thread ID
 ///
 /// We store it as a `u64` to provide a platform-agnostic interface. Platform-specific
-/// implementations convert between their native types and `ThreadId`.
+/// implementations keep their own mapping from `ThreadId` to native thread handles.
 ///
 /// ## Example
 ///
 /// ```rust,no_run
-/// use ferros_core::Debugger;
 /// use ferros_core::types::ThreadId;
+/// use ferros_core::{Debugger, TargetInspector};
 ///
 /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
 /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
@@ -75,7 +81,8 @@ impl From<ProcessId> for u32
 /// }
 /// # Ok::<(), ferros_core::error::DebuggerError>(())
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
 pub struct ThreadId(pub u64);
 
 impl ThreadId
@@ -85,9 +92,9 @@ impl ThreadId
     /// This returns the underlying platform-specific thread identifier as a `u64`.
     /// The exact meaning depends on the platform:
     ///
-    /// - **macOS**: Mach thread port value
+    /// - **macOS**: Global thread id (`thread_identifier_info.thread_id`)
     /// - **Linux**: Thread ID (TID)
-    /// - **Windows**: Thread handle or thread ID
+    /// - **Windows**: Thread ID
     ///
     /// ## Example
     ///
@@ -122,6 +129,8 @@ impl From<u64> for ThreadId
 /// - `Running` → `Suspended`: Process was explicitly suspended via `suspend()`
 /// - `Running` → `Signal(n)`: Process received a signal (e.g., SIGSTOP, SIGINT)
 /// - `Running` → `Breakpoint(addr)`: Process hit a breakpoint at `addr`
+/// - `Running` → `BadAccess { .. }`: A thread touched memory it may not access
+/// - `Running` → `StackOverflow { .. }`: A thread faulted in the guard page below its stack
 /// - `Running` → `Exited(code)`: Process exited with exit code `code`
 /// - `Suspended` → `Running`: Process was resumed via `resume()`
 ///
@@ -134,8 +143,8 @@ impl From<u64> for ThreadId
 /// ## Example
 ///
 /// ```rust,no_run
-/// use ferros_core::Debugger;
 /// use ferros_core::types::StopReason;
+/// use ferros_core::{Debugger, TargetInspector};
 ///
 /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
 /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
@@ -144,7 +153,15 @@ impl From<u64> for ThreadId
 ///     StopReason::Suspended => println!("Process is suspended"),
 ///     StopReason::Signal(sig) => println!("Stopped by signal: {}", sig),
 ///     StopReason::Breakpoint(addr) => println!("Hit breakpoint at 0x{:x}", addr),
-///     StopReason::Exited(code) => println!("Process exited with code: {}", code),
+///     StopReason::Step(pc) => println!("Stepped to 0x{:x}", pc),
+///     StopReason::Watchpoint { address, pc } => {
+///         println!("Watched 0x{:x} accessed at 0x{:x}", address, pc)
+///     }
+///     StopReason::BadAccess { address, kind } => println!("Bad {} at 0x{:x}", kind, address),
+///     StopReason::StackOverflow { thread, .. } => {
+///         println!("Thread {} overflowed its stack", thread.raw())
+///     }
+///     StopReason::Exited(code) => println!("Process exited with code {}", code),
 ///     StopReason::Unknown => println!("Stopped for unknown reason"),
 /// }
 /// # Ok::<(), ferros_core::error::DebuggerError>(())
@@ -174,6 +191,50 @@ pub enum StopReason
     /// The `u64` value is the memory address where the breakpoint was hit.
     /// This is set when the process executes an instruction at a breakpoint location.
     Breakpoint(u64),
+    /// Finished a single-instruction step
+    ///
+    /// The `u64` value is the program counter after the step, i.e. the next
+    /// instruction the stepped thread will execute. Reported by
+    /// [`Debugger::step_instruction`](crate::Debugger::step_instruction).
+    Step(u64),
+    /// An instruction accessed memory a watchpoint covers
+    ///
+    /// The access traps before the instruction completes, so the thread is
+    /// stopped on the instruction that made it; resuming steps over it with
+    /// the watchpoint out of the way first.
+    Watchpoint
+    {
+        /// Address the watchpoint was set on.
+        address: u64,
+        /// Instruction that made the access.
+        pc: u64,
+    },
+    /// A thread accessed memory it may not, such as through a null or dangling pointer
+    ///
+    /// `EXC_BAD_ACCESS` on macOS. Reported instead of `Signal(SIGSEGV)` so the
+    /// faulting address isn't lost; signal policies for `SIGSEGV` still apply.
+    BadAccess
+    {
+        /// Address the thread tried to access.
+        address: u64,
+        /// Whether it was a load, a store or an instruction fetch.
+        kind: BadAccessKind,
+    },
+    /// A thread overflowed its stack
+    ///
+    /// Reported instead of `Signal(SIGSEGV)` or `BadAccess` when a bad access lands in the guard
+    /// page just below the faulting thread's stack, which almost always means
+    /// runaway recursion. The unwinder collapses the repeating frames, see
+    /// [`RecursionCycle`](crate::symbols::unwind::RecursionCycle).
+    StackOverflow
+    {
+        /// Thread that ran out of stack.
+        thread: ThreadId,
+        /// Stack pointer of that thread when it faulted.
+        sp: u64,
+        /// Start of the guard region the fault landed in.
+        guard_page: u64,
+    },
     /// Process exited with status code
     ///
     /// The `i32` value is the exit code (0 typically means success, non-zero means error).
@@ -186,6 +247,101 @@ pub enum StopReason
     Unknown,
 }
 
+impl StopReason
+{
+    /// Short snake_case name of the variant, such as `"breakpoint"` or `"stack_overflow"`
+    ///
+    /// Used as the `reason` field when a stop is serialized and as the key of
+    /// per-reason stop counts.
+    #[must_use]
+    pub const fn key(self) -> &'static str
+    {
+        match self {
+            StopReason::Running => "running",
+            StopReason::Suspended => "suspended",
+            StopReason::Signal(_) => "signal",
+            StopReason::Breakpoint(_) => "breakpoint",
+            StopReason::Step(_) => "step",
+            StopReason::Watchpoint { .. } => "watchpoint",
+            StopReason::BadAccess { .. } => "bad_access",
+            StopReason::StackOverflow { .. } => "stack_overflow",
+            StopReason::Exited(_) => "exited",
+            StopReason::Unknown => "unknown",
+        }
+    }
+}
+
+/// What the access that caused a [`StopReason::BadAccess`] was trying to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadAccessKind
+{
+    /// A load from the address
+    Read,
+    /// A store to the address
+    Write,
+    /// Fetching an instruction, i.e. a jump or call to the address
+    Execute,
+    /// The fault didn't say
+    Unknown,
+}
+
+impl BadAccessKind
+{
+    /// Lowercase name, such as `"read"`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str
+    {
+        match self {
+            BadAccessKind::Read => "read",
+            BadAccessKind::Write => "write",
+            BadAccessKind::Execute => "execute",
+            BadAccessKind::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for BadAccessKind
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Serializes as an object naming the [`key`](StopReason::key) under `reason`,
+/// with the variant's fields beside it and addresses as hex strings:
+/// `{"reason": "breakpoint", "address": "0x1000"}`
+impl Serialize for StopReason
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let hex = |value: u64| Address::from(value);
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("reason", self.key())?;
+        match *self {
+            StopReason::Signal(signal) => map.serialize_entry("signal", &signal)?,
+            StopReason::Breakpoint(address) => map.serialize_entry("address", &hex(address))?,
+            StopReason::Step(pc) => map.serialize_entry("pc", &hex(pc))?,
+            StopReason::Watchpoint { address, pc } => {
+                map.serialize_entry("address", &hex(address))?;
+                map.serialize_entry("pc", &hex(pc))?;
+            }
+            StopReason::BadAccess { address, kind } => {
+                map.serialize_entry("address", &hex(address))?;
+                map.serialize_entry("access", kind.as_str())?;
+            }
+            StopReason::StackOverflow { thread, sp, guard_page } => {
+                map.serialize_entry("thread", &thread)?;
+                map.serialize_entry("sp", &hex(sp))?;
+                map.serialize_entry("guard_page", &hex(guard_page))?;
+            }
+            StopReason::Exited(code) => map.serialize_entry("code", &code)?,
+            StopReason::Running | StopReason::Suspended | StopReason::Unknown => {}
+        }
+        map.end()
+    }
+}
+
 /// Identifier for memory regions
 ///
 /// This is a stable identifier for a memory region within a process. It's used
@@ -201,8 +357,8 @@ pub enum StopReason
 /// ## Example
 ///
 /// ```rust,no_run
-/// use ferros_core::Debugger;
 /// use ferros_core::types::MemoryRegionId;
+/// use ferros_core::{Debugger, TargetInspector};
 ///
 /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
 /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
@@ -217,7 +373,8 @@ pub enum StopReason
 /// }
 /// # Ok::<(), ferros_core::error::DebuggerError>(())
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
 pub struct MemoryRegionId(pub usize);
 
 impl MemoryRegionId
@@ -271,7 +428,7 @@ impl MemoryRegionId
 ///     Some("[heap]".to_string()),
 /// );
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MemoryRegion
 {
     /// Stable identifier for the region.
@@ -456,6 +613,44 @@ impl MemoryRegion
     }
 }
 
+/// Counters of a backend's memory cache
+///
+/// A hit is a page served from the cache, a miss a page read from the target.
+/// `bytes` counts only pages cached since the current stop.
+///
+/// ```
+/// use ferros_core::types::MemoryCacheStats;
+///
+/// let stats = MemoryCacheStats {
+///     hits: 3,
+///     misses: 1,
+///     bytes: 0x4000,
+/// };
+/// assert_eq!(stats.hit_rate(), Some(0.75));
+/// assert_eq!(MemoryCacheStats::default().hit_rate(), None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryCacheStats
+{
+    /// Pages served from the cache
+    pub hits: u64,
+    /// Pages read from the target
+    pub misses: u64,
+    /// Bytes of target memory currently cached
+    pub bytes: u64,
+}
+
+impl MemoryCacheStats
+{
+    /// Fraction of page lookups served from the cache, or `None` before any lookup
+    #[must_use]
+    pub fn hit_rate(&self) -> Option<f64>
+    {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
 /// CPU architecture of the debug target
 ///
 /// This enum represents the CPU architecture of the process being debugged.
@@ -477,8 +672,8 @@ impl MemoryRegion
 /// ## Example
 ///
 /// ```rust,no_run
-/// use ferros_core::Debugger;
 /// use ferros_core::types::Architecture;
+/// use ferros_core::{Debugger, TargetInspector};
 ///
 /// # let mut debugger = ferros_core::platform::macos::MacOSDebugger::new()?;
 /// # debugger.attach(ferros_core::types::ProcessId::from(12345))?;
@@ -559,6 +754,16 @@ impl Architecture
             Architecture::Unknown(_) => 8,
         }
     }
+
+    /// Alignment every instruction start has: 4 on ARM64, none (1) on x86-64.
+    #[must_use]
+    pub const fn instruction_alignment(self) -> u64
+    {
+        match self {
+            Architecture::Arm64 => 4,
+            Architecture::X86_64 | Architecture::Unknown(_) => 1,
+        }
+    }
 }
 
 impl fmt::Display for Architecture
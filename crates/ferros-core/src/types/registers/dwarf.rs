@@ -0,0 +1,269 @@
+//! DWARF register numbers.
+//!
+//! CFI rules, location expressions and the gdb remote protocol name registers
+//! by their DWARF number, which is fixed per architecture by the ABI and
+//! unrelated to the order of [`Registers::general`](super::Registers::general).
+//! Everything that turns a DWARF number into a value goes through this module
+//! so the tables live in one place.
+//!
+//! ## ARM64
+//!
+//! From *DWARF for the Arm 64-bit Architecture* (AADWARF64), section 4.1:
+//!
+//! | DWARF | Register | `RegisterId` |
+//! |-------|----------|--------------|
+//! | 0-28  | X0-X28   | `Arm64(X(n))` |
+//! | 29    | X29 (FP) | `Fp` |
+//! | 30    | X30 (LR) | `Arm64(X(30))` |
+//! | 31    | SP       | `Sp` |
+//! | 64-95 | V0-V31   | `Vector(0..=31)` |
+//!
+//! The PC has no number compilers emit; CFI recovers it from LR.
+//!
+//! ## x86-64
+//!
+//! From the *System V AMD64 ABI*, figure 3.36. Note the order is not
+//! RAX, RBX, RCX, RDX:
+//!
+//! | DWARF | Register | `RegisterId` |
+//! |-------|----------|--------------|
+//! | 0     | RAX      | `X86_64(Rax)` |
+//! | 1     | RDX      | `X86_64(Rdx)` |
+//! | 2     | RCX      | `X86_64(Rcx)` |
+//! | 3     | RBX      | `X86_64(Rbx)` |
+//! | 4     | RSI      | `X86_64(Rsi)` |
+//! | 5     | RDI      | `X86_64(Rdi)` |
+//! | 6     | RBP      | `Fp` |
+//! | 7     | RSP      | `Sp` |
+//! | 8-15  | R8-R15   | `X86_64(R8..R15)` |
+//! | 16    | Return address (RIP) | `Pc` |
+//! | 17-32 | XMM0-XMM15 | `Vector(0..=15)` |
+//!
+//! ```rust
+//! use ferros_core::types::registers::dwarf::{dwarf_to_register_id, register_id_to_dwarf};
+//! use ferros_core::types::{Architecture, RegisterId, X86_64Register};
+//!
+//! let rdx = dwarf_to_register_id(Architecture::X86_64, 1);
+//! assert_eq!(rdx, Some(RegisterId::X86_64(X86_64Register::Rdx)));
+//! assert_eq!(
+//!     register_id_to_dwarf(Architecture::Arm64, RegisterId::Sp),
+//!     Some(31)
+//! );
+//! ```
+
+use super::{Arm64Register, RegisterId, X86_64Register};
+use crate::types::Architecture;
+
+/// ARM64 frame pointer, X29.
+pub const ARM64_FP: u16 = 29;
+/// ARM64 link register, X30.
+pub const ARM64_LR: u16 = 30;
+/// ARM64 stack pointer.
+pub const ARM64_SP: u16 = 31;
+/// ARM64 V0; V1-V31 follow.
+pub const ARM64_V0: u16 = 64;
+/// Number of ARM64 vector registers.
+pub const ARM64_VECTOR_COUNT: u8 = 32;
+
+/// x86-64 frame pointer, RBP.
+pub const X86_64_RBP: u16 = 6;
+/// x86-64 stack pointer, RSP.
+pub const X86_64_RSP: u16 = 7;
+/// x86-64 return address column, RIP.
+pub const X86_64_RA: u16 = 16;
+/// x86-64 XMM0; XMM1-XMM15 follow.
+pub const X86_64_XMM0: u16 = 17;
+/// Number of x86-64 XMM registers.
+pub const X86_64_VECTOR_COUNT: u8 = 16;
+
+/// x86-64 DWARF 0-15 in ABI order; 6 and 7 are RBP and RSP.
+const X86_64_GENERAL: [Option<X86_64Register>; 16] = [
+    Some(X86_64Register::Rax),
+    Some(X86_64Register::Rdx),
+    Some(X86_64Register::Rcx),
+    Some(X86_64Register::Rbx),
+    Some(X86_64Register::Rsi),
+    Some(X86_64Register::Rdi),
+    None,
+    None,
+    Some(X86_64Register::R8),
+    Some(X86_64Register::R9),
+    Some(X86_64Register::R10),
+    Some(X86_64Register::R11),
+    Some(X86_64Register::R12),
+    Some(X86_64Register::R13),
+    Some(X86_64Register::R14),
+    Some(X86_64Register::R15),
+];
+
+/// Register a DWARF number names on `architecture`, if it has one.
+#[must_use]
+pub fn dwarf_to_register_id(architecture: Architecture, number: u16) -> Option<RegisterId>
+{
+    match architecture {
+        Architecture::Arm64 => match number {
+            ARM64_FP => Some(RegisterId::Fp),
+            ARM64_SP => Some(RegisterId::Sp),
+            0..=30 => Some(RegisterId::Arm64(Arm64Register::X(number as u8))),
+            _ => vector_index(number, ARM64_V0, ARM64_VECTOR_COUNT).map(RegisterId::Vector),
+        },
+        Architecture::X86_64 => match number {
+            X86_64_RBP => Some(RegisterId::Fp),
+            X86_64_RSP => Some(RegisterId::Sp),
+            X86_64_RA => Some(RegisterId::Pc),
+            0..=15 => X86_64_GENERAL[number as usize].map(RegisterId::X86_64),
+            _ => vector_index(number, X86_64_XMM0, X86_64_VECTOR_COUNT).map(RegisterId::Vector),
+        },
+        Architecture::Unknown(_) => None,
+    }
+}
+
+/// DWARF number of `register` on `architecture`, if it has one.
+///
+/// X29 and `Fp` both map to 29 on ARM64.
+#[must_use]
+pub fn register_id_to_dwarf(architecture: Architecture, register: RegisterId) -> Option<u16>
+{
+    match (architecture, register) {
+        (Architecture::Arm64, RegisterId::Fp) => Some(ARM64_FP),
+        (Architecture::Arm64, RegisterId::Sp) => Some(ARM64_SP),
+        (Architecture::Arm64, RegisterId::Arm64(Arm64Register::X(n))) if n <= 30 => Some(u16::from(n)),
+        (Architecture::Arm64, RegisterId::Vector(n)) if n < ARM64_VECTOR_COUNT => Some(ARM64_V0 + u16::from(n)),
+        (Architecture::X86_64, RegisterId::Fp) => Some(X86_64_RBP),
+        (Architecture::X86_64, RegisterId::Sp) => Some(X86_64_RSP),
+        (Architecture::X86_64, RegisterId::Pc) => Some(X86_64_RA),
+        (Architecture::X86_64, RegisterId::X86_64(reg)) => X86_64_GENERAL
+            .iter()
+            .position(|&entry| entry == Some(reg))
+            .map(|number| number as u16),
+        (Architecture::X86_64, RegisterId::Vector(n)) if n < X86_64_VECTOR_COUNT => Some(X86_64_XMM0 + u16::from(n)),
+        _ => None,
+    }
+}
+
+/// Column CFI uses for the return address on `architecture`.
+#[must_use]
+pub fn return_address_register(architecture: Architecture) -> Option<u16>
+{
+    match architecture {
+        Architecture::Arm64 => Some(ARM64_LR),
+        Architecture::X86_64 => Some(X86_64_RA),
+        Architecture::Unknown(_) => None,
+    }
+}
+
+fn vector_index(number: u16, first: u16, count: u8) -> Option<u8>
+{
+    let index = number.checked_sub(first)?;
+    u8::try_from(index).ok().filter(|&index| index < count)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn x86(register: X86_64Register) -> RegisterId
+    {
+        RegisterId::X86_64(register)
+    }
+
+    #[test]
+    fn x86_64_matches_the_system_v_abi()
+    {
+        use X86_64Register::*;
+
+        let table = [
+            (0, x86(Rax)),
+            (1, x86(Rdx)),
+            (2, x86(Rcx)),
+            (3, x86(Rbx)),
+            (4, x86(Rsi)),
+            (5, x86(Rdi)),
+            (6, RegisterId::Fp),
+            (7, RegisterId::Sp),
+            (8, x86(R8)),
+            (9, x86(R9)),
+            (10, x86(R10)),
+            (11, x86(R11)),
+            (12, x86(R12)),
+            (13, x86(R13)),
+            (14, x86(R14)),
+            (15, x86(R15)),
+            (16, RegisterId::Pc),
+        ];
+        for (number, register) in table {
+            assert_eq!(
+                dwarf_to_register_id(Architecture::X86_64, number),
+                Some(register),
+                "DWARF {number}"
+            );
+            assert_eq!(register_id_to_dwarf(Architecture::X86_64, register), Some(number));
+        }
+        for xmm in 0..16 {
+            assert_eq!(
+                dwarf_to_register_id(Architecture::X86_64, 17 + xmm),
+                Some(RegisterId::Vector(xmm as u8))
+            );
+        }
+        assert_eq!(dwarf_to_register_id(Architecture::X86_64, 33), None);
+        assert_eq!(register_id_to_dwarf(Architecture::X86_64, RegisterId::Status), None);
+        assert_eq!(register_id_to_dwarf(Architecture::X86_64, RegisterId::Vector(16)), None);
+        assert_eq!(
+            register_id_to_dwarf(Architecture::X86_64, RegisterId::Arm64(Arm64Register::X(0))),
+            None
+        );
+    }
+
+    #[test]
+    fn arm64_matches_aadwarf64()
+    {
+        for n in 0..=28 {
+            assert_eq!(
+                dwarf_to_register_id(Architecture::Arm64, n),
+                Some(RegisterId::Arm64(Arm64Register::X(n as u8)))
+            );
+        }
+        assert_eq!(dwarf_to_register_id(Architecture::Arm64, 29), Some(RegisterId::Fp));
+        assert_eq!(
+            dwarf_to_register_id(Architecture::Arm64, 30),
+            Some(RegisterId::Arm64(Arm64Register::X(30)))
+        );
+        assert_eq!(dwarf_to_register_id(Architecture::Arm64, 31), Some(RegisterId::Sp));
+        for v in 0..32 {
+            assert_eq!(
+                dwarf_to_register_id(Architecture::Arm64, 64 + v),
+                Some(RegisterId::Vector(v as u8))
+            );
+        }
+        for unmapped in [32, 33, 34, 46, 63, 96] {
+            assert_eq!(dwarf_to_register_id(Architecture::Arm64, unmapped), None, "DWARF {unmapped}");
+        }
+
+        assert_eq!(
+            register_id_to_dwarf(Architecture::Arm64, RegisterId::Arm64(Arm64Register::X(29))),
+            Some(29)
+        );
+        assert_eq!(
+            register_id_to_dwarf(Architecture::Arm64, RegisterId::Arm64(Arm64Register::X(31))),
+            None
+        );
+        assert_eq!(register_id_to_dwarf(Architecture::Arm64, RegisterId::Pc), None);
+        assert_eq!(register_id_to_dwarf(Architecture::Arm64, RegisterId::Vector(32)), None);
+    }
+
+    #[test]
+    fn every_mapped_number_round_trips()
+    {
+        for architecture in [Architecture::Arm64, Architecture::X86_64] {
+            let mapped = (0..=u16::MAX)
+                .filter_map(|number| dwarf_to_register_id(architecture, number).map(|id| (number, id)))
+                .inspect(|&(number, id)| assert_eq!(register_id_to_dwarf(architecture, id), Some(number)))
+                .count();
+            assert_eq!(mapped, if architecture == Architecture::Arm64 { 64 } else { 33 });
+        }
+        assert_eq!(dwarf_to_register_id(Architecture::Unknown("riscv"), 0), None);
+        assert_eq!(return_address_register(Architecture::Arm64), Some(ARM64_LR));
+        assert_eq!(return_address_register(Architecture::X86_64), Some(X86_64_RA));
+    }
+}
@@ -1,4 +1,14 @@
 //! CPU register types and access.
+//!
+//! [`dwarf`] maps between [`RegisterId`] and DWARF register numbers;
+//! [`order`] is the row order register tables display.
+
+pub mod dwarf;
+pub mod order;
+
+pub use order::{RegisterCategory, RegisterRow, argument_values, register_by_name, register_display_order};
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
 
 use super::{Address, Architecture};
 
@@ -65,6 +75,11 @@ pub enum RegisterId
     ///
     /// Use this variant to access x86-64 general-purpose registers (RAX, RBX, etc.).
     X86_64(X86_64Register),
+    /// SIMD/vector register, by index into [`Registers::vector`]
+    ///
+    /// On ARM64, V0-V31. On x86-64, XMM0-XMM15. [`Registers::get`] reads the
+    /// low 64 bits, which is where a scalar `f32`/`f64` lives.
+    Vector(u8),
 }
 
 /// ARM64 general-purpose register identifier
@@ -515,6 +530,39 @@ impl Registers
     /// let x0 = regs.get(RegisterId::Arm64(Arm64Register::X(0)));
     /// ```
     pub fn get(&self, id: RegisterId) -> Option<u64>
+    {
+        self.get_in(self.architecture, id)
+    }
+
+    /// Get a register by its DWARF number
+    ///
+    /// Returns `None` if the number names no register on this architecture
+    /// (see [`dwarf`]) or the register isn't populated.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use ferros_core::types::{Address, Architecture, Registers};
+    ///
+    /// let mut regs = Registers::new().with_arch(Architecture::X86_64);
+    /// regs.general = vec![0; 14];
+    /// regs.general[3] = 0x42; // RDX
+    /// regs.sp = Address::from(0x7000);
+    ///
+    /// assert_eq!(regs.get_dwarf(1), Some(0x42));
+    /// assert_eq!(regs.get_dwarf(7), Some(0x7000));
+    /// ```
+    #[must_use]
+    pub fn get_dwarf(&self, number: u16) -> Option<u64>
+    {
+        dwarf::dwarf_to_register_id(self.architecture, number).and_then(|id| self.get(id))
+    }
+
+    /// [`get`](Self::get), treating the registers as belonging to `architecture`
+    ///
+    /// For callers such as the unwinder that know the architecture even when
+    /// the register set wasn't tagged with it.
+    pub(crate) fn get_in(&self, architecture: Architecture, id: RegisterId) -> Option<u64>
     {
         match id {
             RegisterId::Pc => Some(self.pc.value()),
@@ -522,18 +570,19 @@ impl Registers
             RegisterId::Fp => Some(self.fp.value()),
             RegisterId::Status => Some(self.status),
             RegisterId::Arm64(Arm64Register::X(idx)) => {
-                if self.architecture != Architecture::Arm64 {
+                if architecture != Architecture::Arm64 {
                     return None;
                 }
                 self.general.get(idx as usize).copied()
             }
             RegisterId::X86_64(reg) => {
-                if self.architecture != Architecture::X86_64 {
+                if architecture != Architecture::X86_64 {
                     return None;
                 }
                 let idx = reg.index();
                 self.general.get(idx).copied()
             }
+            RegisterId::Vector(idx) => self.vector.get(idx as usize).map(|value| value.as_u128() as u64),
         }
     }
 
@@ -544,6 +593,8 @@ impl Registers
     ///   ARM64 registers on an x86-64 register set)
     /// - The register index is out of bounds (for architecture-specific registers)
     ///
+    /// Setting a [`RegisterId::Vector`] replaces its low 64 bits and keeps the rest.
+    ///
     /// ## Example
     ///
     /// ```rust
@@ -559,6 +610,15 @@ impl Registers
     /// regs.set(RegisterId::Arm64(Arm64Register::X(0)), 0x1234);
     /// ```
     pub fn set(&mut self, id: RegisterId, value: u64) -> Option<()>
+    {
+        self.set_in(self.architecture, id, value)
+    }
+
+    /// [`set`](Self::set), treating the registers as belonging to `architecture`
+    ///
+    /// The write-side counterpart of `get_in`, used by the unwinder to restore
+    /// callee-saved registers into an untagged register set.
+    pub(crate) fn set_in(&mut self, architecture: Architecture, id: RegisterId, value: u64) -> Option<()>
     {
         match id {
             RegisterId::Pc => {
@@ -578,7 +638,7 @@ impl Registers
                 Some(())
             }
             RegisterId::Arm64(Arm64Register::X(idx)) => {
-                if self.architecture != Architecture::Arm64 {
+                if architecture != Architecture::Arm64 {
                     return None;
                 }
                 let slot = self.general.get_mut(idx as usize)?;
@@ -586,16 +646,23 @@ impl Registers
                 Some(())
             }
             RegisterId::X86_64(reg) => {
-                if self.architecture != Architecture::X86_64 {
+                if architecture != Architecture::X86_64 {
                     return None;
                 }
                 let slot = self.general.get_mut(reg.index())?;
                 *slot = value;
                 Some(())
             }
+            RegisterId::Vector(idx) => {
+                let slot = self.vector.get_mut(idx as usize)?;
+                let high = slot.as_u128() & !u128::from(u64::MAX);
+                *slot = VectorRegisterValue::from_u128(high | u128::from(value));
+                Some(())
+            }
         }
     }
 
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
     pub(crate) fn set_architecture(&mut self, architecture: Architecture)
     {
         self.architecture = architecture;
@@ -697,3 +764,21 @@ impl Default for Registers
         Self::new()
     }
 }
+
+/// Serializes as an object from lowercase register name to `0x`-prefixed hex
+/// value, in [`register_display_order`]: `{"pc": "0x100003f40", "sp": ...}`
+///
+/// Registers the backend didn't fill are left out.
+impl Serialize for Registers
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut map = serializer.serialize_map(None)?;
+        for row in register_display_order(self.architecture) {
+            if let Some(value) = self.get(row.id) {
+                map.serialize_entry(&row.label.to_ascii_lowercase(), &format!("0x{value:x}"))?;
+            }
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,346 @@
+//! Display order for register tables.
+//!
+//! Every view that lists registers (the TUI's Registers view, headless
+//! output) renders from [`register_display_order`] so they show the same
+//! rows in the same order, and a row index names the same register across
+//! refreshes. Each logical register appears once: on ARM64 the frame
+//! pointer is X29, listed under that name with an `fp` role rather than as a
+//! separate `FP` row.
+//!
+//! Rows are grouped by their role in the platform calling convention
+//! (AAPCS64 on ARM64, System V on x86-64):
+//!
+//! | Category | ARM64 | x86-64 |
+//! |----------|-------|--------|
+//! | Special | PC, SP, X29 (fp), X30 (lr), CPSR | RIP, RSP, RBP (fp), RFLAGS |
+//! | Arguments | X0-X7 | RDI, RSI, RDX, RCX, R8, R9 |
+//! | Callee-saved | X19-X28 | RBX, R12-R15 |
+//! | Temporaries | X8-X18 | RAX, R10, R11 |
+//!
+//! ```rust
+//! use ferros_core::types::registers::order::{RegisterCategory, register_display_order};
+//! use ferros_core::types::{Architecture, RegisterId};
+//!
+//! let rows = register_display_order(Architecture::Arm64);
+//! let fp = rows.iter().find(|row| row.id == RegisterId::Fp).unwrap();
+//! assert_eq!(fp.display_name(), "X29 (fp)");
+//! assert_eq!(fp.category, RegisterCategory::Special);
+//! ```
+
+use std::fmt;
+
+use super::{Arm64Register, RegisterId, Registers, X86_64Register};
+use crate::types::{Architecture, FunctionParameter};
+
+/// Group a register is listed under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegisterCategory
+{
+    /// Program counter, stack and frame pointers, link register and flags
+    Special,
+    /// Registers that carry function arguments
+    Arguments,
+    /// Registers a callee must preserve
+    CalleeSaved,
+    /// Registers a call may clobber that don't carry arguments
+    Temporaries,
+}
+
+impl fmt::Display for RegisterCategory
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            RegisterCategory::Special => "Special",
+            RegisterCategory::Arguments => "Arguments",
+            RegisterCategory::CalleeSaved => "Callee-saved",
+            RegisterCategory::Temporaries => "Temporaries",
+        })
+    }
+}
+
+/// One row of a register table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterRow
+{
+    /// Register to read with [`Registers::get`](super::Registers::get)
+    pub id: RegisterId,
+    /// Architectural name, such as `X29` or `RBP`
+    pub label: &'static str,
+    /// Conventional role for registers that have one, such as `fp` or `lr`
+    pub role: Option<&'static str>,
+    /// Group the row belongs to
+    pub category: RegisterCategory,
+}
+
+impl RegisterRow
+{
+    const fn new(id: RegisterId, label: &'static str, category: RegisterCategory) -> Self
+    {
+        Self {
+            id,
+            label,
+            role: None,
+            category,
+        }
+    }
+
+    const fn with_role(mut self, role: &'static str) -> Self
+    {
+        self.role = Some(role);
+        self
+    }
+
+    /// Label with the role in parentheses, e.g. `X30 (lr)`
+    #[must_use]
+    pub fn display_name(&self) -> String
+    {
+        match self.role {
+            Some(role) => format!("{} ({role})", self.label),
+            None => self.label.to_string(),
+        }
+    }
+}
+
+static ARM64_NAMES: [&str; 31] = [
+    "X0", "X1", "X2", "X3", "X4", "X5", "X6", "X7", "X8", "X9", "X10", "X11", "X12", "X13", "X14", "X15", "X16", "X17",
+    "X18", "X19", "X20", "X21", "X22", "X23", "X24", "X25", "X26", "X27", "X28", "X29", "X30",
+];
+
+/// Rows for `architecture`, in display order
+///
+/// Unknown architectures get only the registers every architecture has.
+#[must_use]
+pub fn register_display_order(architecture: Architecture) -> Vec<RegisterRow>
+{
+    match architecture {
+        Architecture::Arm64 => arm64_rows(),
+        Architecture::X86_64 => x86_64_rows(),
+        Architecture::Unknown(_) => vec![
+            RegisterRow::new(RegisterId::Pc, "PC", RegisterCategory::Special),
+            RegisterRow::new(RegisterId::Sp, "SP", RegisterCategory::Special),
+            RegisterRow::new(RegisterId::Fp, "FP", RegisterCategory::Special),
+            RegisterRow::new(RegisterId::Status, "Status", RegisterCategory::Special),
+        ],
+    }
+}
+
+/// Row for the register called `name` on `architecture`
+///
+/// Matches a row's label or role, or its display name, ignoring case and a
+/// leading `$`: `x0`, `$X29`, `fp`, `lr`, `rip`, `X30 (lr)`. `pc`, `sp`, `fp`
+/// and `flags` name those registers on every architecture.
+///
+/// ```rust
+/// use ferros_core::types::registers::order::register_by_name;
+/// use ferros_core::types::{Architecture, Arm64Register, RegisterId};
+///
+/// let lr = register_by_name(Architecture::Arm64, "$lr").unwrap();
+/// assert_eq!(lr.id, RegisterId::Arm64(Arm64Register::X(30)));
+/// assert_eq!(
+///     register_by_name(Architecture::X86_64, "pc").unwrap().label,
+///     "RIP"
+/// );
+/// assert!(register_by_name(Architecture::X86_64, "x0").is_none());
+/// ```
+#[must_use]
+pub fn register_by_name(architecture: Architecture, name: &str) -> Option<RegisterRow>
+{
+    let name = name.trim();
+    let name = name.strip_prefix('$').unwrap_or(name);
+    let generic = match name.to_ascii_lowercase().as_str() {
+        "pc" => Some(RegisterId::Pc),
+        "sp" => Some(RegisterId::Sp),
+        "fp" => Some(RegisterId::Fp),
+        "flags" | "status" => Some(RegisterId::Status),
+        _ => None,
+    };
+    register_display_order(architecture).into_iter().find(|row| {
+        Some(row.id) == generic
+            || row.label.eq_ignore_ascii_case(name)
+            || row.role.is_some_and(|role| role.eq_ignore_ascii_case(name))
+            || row.display_name().eq_ignore_ascii_case(name)
+    })
+}
+
+/// Argument registers as `name (register) = 0x<value>`, in calling-convention order
+///
+/// `parameters` name the registers in order; registers past the last
+/// parameter, or whose parameter has no name, are listed by register alone.
+#[must_use]
+pub fn argument_values(registers: &Registers, parameters: &[FunctionParameter]) -> Vec<String>
+{
+    register_display_order(registers.architecture())
+        .into_iter()
+        .filter(|row| row.category == RegisterCategory::Arguments)
+        .enumerate()
+        .filter_map(|(index, row)| {
+            let value = registers.get(row.id)?;
+            let register = row.label.to_ascii_lowercase();
+            Some(match parameters.get(index).and_then(|parameter| parameter.name.as_deref()) {
+                Some(name) => format!("{name} ({register}) = 0x{value:x}"),
+                None => format!("{register} = 0x{value:x}"),
+            })
+        })
+        .collect()
+}
+
+fn arm64_rows() -> Vec<RegisterRow>
+{
+    let x = |n: u8, category| RegisterRow::new(RegisterId::Arm64(Arm64Register::X(n)), ARM64_NAMES[n as usize], category);
+    let mut rows = vec![
+        RegisterRow::new(RegisterId::Pc, "PC", RegisterCategory::Special),
+        RegisterRow::new(RegisterId::Sp, "SP", RegisterCategory::Special),
+        RegisterRow::new(RegisterId::Fp, ARM64_NAMES[29], RegisterCategory::Special).with_role("fp"),
+        x(30, RegisterCategory::Special).with_role("lr"),
+        RegisterRow::new(RegisterId::Status, "CPSR", RegisterCategory::Special),
+    ];
+    rows.extend((0..=7).map(|n| x(n, RegisterCategory::Arguments)));
+    rows.extend((19..=28).map(|n| x(n, RegisterCategory::CalleeSaved)));
+    rows.extend((8..=18).map(|n| x(n, RegisterCategory::Temporaries)));
+    rows
+}
+
+fn x86_64_rows() -> Vec<RegisterRow>
+{
+    use X86_64Register::*;
+
+    let general = |register, label, category| RegisterRow::new(RegisterId::X86_64(register), label, category);
+    vec![
+        RegisterRow::new(RegisterId::Pc, "RIP", RegisterCategory::Special),
+        RegisterRow::new(RegisterId::Sp, "RSP", RegisterCategory::Special),
+        RegisterRow::new(RegisterId::Fp, "RBP", RegisterCategory::Special).with_role("fp"),
+        RegisterRow::new(RegisterId::Status, "RFLAGS", RegisterCategory::Special),
+        general(Rdi, "RDI", RegisterCategory::Arguments),
+        general(Rsi, "RSI", RegisterCategory::Arguments),
+        general(Rdx, "RDX", RegisterCategory::Arguments),
+        general(Rcx, "RCX", RegisterCategory::Arguments),
+        general(R8, "R8", RegisterCategory::Arguments),
+        general(R9, "R9", RegisterCategory::Arguments),
+        general(Rbx, "RBX", RegisterCategory::CalleeSaved),
+        general(R12, "R12", RegisterCategory::CalleeSaved),
+        general(R13, "R13", RegisterCategory::CalleeSaved),
+        general(R14, "R14", RegisterCategory::CalleeSaved),
+        general(R15, "R15", RegisterCategory::CalleeSaved),
+        general(Rax, "RAX", RegisterCategory::Temporaries),
+        general(R10, "R10", RegisterCategory::Temporaries),
+        general(R11, "R11", RegisterCategory::Temporaries),
+    ]
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::types::Registers;
+
+    /// Registers as a backend fills them: every general slot populated
+    fn populated(architecture: Architecture, general: usize) -> Registers
+    {
+        let mut registers = Registers::new().with_arch(architecture);
+        registers.general = (0..general as u64).collect();
+        registers
+    }
+
+    fn assert_covers(architecture: Architecture, expected: &HashSet<RegisterId>)
+    {
+        let rows = register_display_order(architecture);
+        let ids: HashSet<_> = rows.iter().map(|row| row.id).collect();
+        assert_eq!(ids.len(), rows.len(), "{architecture:?} lists a register twice");
+        assert_eq!(&ids, expected);
+
+        let labels: HashSet<_> = rows.iter().map(|row| row.label).collect();
+        assert_eq!(labels.len(), rows.len(), "{architecture:?} repeats a label");
+
+        // Categories are contiguous and in a fixed order
+        let mut categories: Vec<_> = rows.iter().map(|row| row.category).collect();
+        categories.dedup();
+        assert!(categories.len() <= 4 && categories.first() == Some(&RegisterCategory::Special));
+        assert!(categories.windows(2).all(|pair| (pair[0] as u8) < (pair[1] as u8)));
+    }
+
+    #[test]
+    fn arm64_lists_each_register_once()
+    {
+        let mut expected: HashSet<_> = [RegisterId::Pc, RegisterId::Sp, RegisterId::Fp, RegisterId::Status].into();
+        expected.extend((0..=30).filter(|&n| n != 29).map(|n| RegisterId::Arm64(Arm64Register::X(n))));
+        assert_covers(Architecture::Arm64, &expected);
+
+        let rows = register_display_order(Architecture::Arm64);
+        let names: Vec<_> = rows.iter().take(5).map(RegisterRow::display_name).collect();
+        assert_eq!(names, ["PC", "SP", "X29 (fp)", "X30 (lr)", "CPSR"]);
+        assert!(!rows.iter().any(|row| row.label == "FP" || row.label == "LR"));
+    }
+
+    #[test]
+    fn x86_64_lists_each_register_once()
+    {
+        use X86_64Register::*;
+
+        let mut expected: HashSet<_> = [RegisterId::Pc, RegisterId::Sp, RegisterId::Fp, RegisterId::Status].into();
+        expected.extend([Rax, Rbx, Rcx, Rdx, Rsi, Rdi, R8, R9, R10, R11, R12, R13, R14, R15].map(RegisterId::X86_64));
+        assert_covers(Architecture::X86_64, &expected);
+
+        let arguments: Vec<_> = register_display_order(Architecture::X86_64)
+            .into_iter()
+            .filter(|row| row.category == RegisterCategory::Arguments)
+            .map(|row| row.label)
+            .collect();
+        assert_eq!(arguments, ["RDI", "RSI", "RDX", "RCX", "R8", "R9"]);
+    }
+
+    #[test]
+    fn names_resolve_on_their_architecture()
+    {
+        let id = |architecture, name| register_by_name(architecture, name).map(|row| row.id);
+
+        assert_eq!(id(Architecture::Arm64, "$x0"), Some(RegisterId::Arm64(Arm64Register::X(0))));
+        assert_eq!(id(Architecture::Arm64, "X29"), Some(RegisterId::Fp));
+        assert_eq!(id(Architecture::Arm64, "fp"), Some(RegisterId::Fp));
+        assert_eq!(
+            id(Architecture::Arm64, "X30 (lr)"),
+            Some(RegisterId::Arm64(Arm64Register::X(30)))
+        );
+        assert_eq!(id(Architecture::Arm64, "cpsr"), Some(RegisterId::Status));
+        assert_eq!(id(Architecture::Arm64, "rax"), None);
+        assert_eq!(id(Architecture::Arm64, "x31"), None);
+
+        assert_eq!(id(Architecture::X86_64, "$rip"), Some(RegisterId::Pc));
+        assert_eq!(id(Architecture::X86_64, "sp"), Some(RegisterId::Sp));
+        assert_eq!(id(Architecture::X86_64, "RBP"), Some(RegisterId::Fp));
+        assert_eq!(id(Architecture::X86_64, "r12"), Some(RegisterId::X86_64(X86_64Register::R12)));
+        assert_eq!(id(Architecture::X86_64, "flags"), Some(RegisterId::Status));
+        assert_eq!(id(Architecture::Unknown("riscv"), "pc"), Some(RegisterId::Pc));
+    }
+
+    #[test]
+    fn every_row_resolves()
+    {
+        for (architecture, general) in [
+            (Architecture::Arm64, 31),
+            (Architecture::X86_64, 14),
+            (Architecture::Unknown("riscv"), 0),
+        ] {
+            let registers = populated(architecture, general);
+            for row in register_display_order(architecture) {
+                assert!(registers.get(row.id).is_some(), "{architecture:?} {}", row.label);
+            }
+        }
+        assert_eq!(register_display_order(Architecture::Unknown("riscv")).len(), 4);
+    }
+
+    #[test]
+    fn registers_serialize_as_names_to_hex_in_display_order()
+    {
+        let mut registers = populated(Architecture::Arm64, 31);
+        registers.pc = crate::types::Address::from(0x1_0000_3f40);
+        let json = serde_json::to_value(&registers).unwrap();
+        let object = json.as_object().unwrap();
+
+        assert_eq!(object["pc"], "0x100003f40");
+        assert_eq!(object["x1"], "0x1");
+        assert_eq!(object.len(), register_display_order(Architecture::Arm64).len());
+    }
+}
@@ -3,6 +3,9 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
 use super::symbols::{FunctionParameter, SourceLocation, SymbolName};
 use super::{Address, ThreadId};
 
@@ -120,6 +123,11 @@ impl FrameId
 /// function calls that were optimized away. They don't have their own stack
 /// frame but share storage with their parent physical frame.
 ///
+/// ## Async Frames
+///
+/// Async frames stand for the `async fn`s a task is suspended in, found by
+/// reading the task's future while its thread is parked in the executor.
+///
 /// ## Example
 ///
 /// ```rust
@@ -164,6 +172,19 @@ pub enum FrameKind
         /// A depth of 0 means this is the innermost inline call.
         depth: u8,
     },
+    /// A future the task running in the `physical` executor frame is suspended in.
+    ///
+    /// Synthesized by an async unwind
+    /// ([`UnwindOptions::with_async_frames`](crate::symbols::UnwindOptions::with_async_frames))
+    /// from the task's state machines in memory. It has no stack storage; its
+    /// PC and SP are the executor frame's.
+    Async
+    {
+        /// Executor frame the task was found in.
+        physical: FrameId,
+        /// Depth inside the chain of awaits (0 = the leaf future being awaited).
+        depth: u8,
+    },
 }
 
 impl FrameKind
@@ -197,6 +218,12 @@ impl FrameKind
     {
         matches!(self, FrameKind::Inlined { .. })
     }
+
+    /// Returns `true` if this is a future synthesized by an async unwind.
+    pub const fn is_async(self) -> bool
+    {
+        matches!(self, FrameKind::Async { .. })
+    }
 }
 
 /// Indicates how reliable a frame's unwind data is.
@@ -207,7 +234,8 @@ impl FrameKind
 ///
 /// ## Reliability Levels
 ///
-/// - `Complete`: Most reliable - full debug information available
+/// - `Complete`: Most reliable - the innermost frame, or a caller recovered from
+///   DWARF CFI (`.eh_frame` or `.debug_frame`)
 /// - `CfiFallback`: Moderate reliability - using frame pointers or link register
 /// - `Heuristic`: Least reliable - best-effort reconstruction
 ///
@@ -224,14 +252,16 @@ impl FrameKind
 ///     FrameStatus::Heuristic => println!("Frame data may be inaccurate"),
 /// }
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FrameStatus
 {
     /// CFI (Call Frame Information) provided full register restoration.
     ///
     /// This is the most reliable status. The frame was unwound using complete
     /// DWARF CFI (Call Frame Information) data, which provides accurate register
-    /// restoration and frame boundaries.
+    /// restoration and frame boundaries. The innermost frame, read straight from
+    /// the thread's registers, is also `Complete`; no other strategy produces it.
     Complete,
     /// DWARF CFI missing; fallback to frame pointers or link register.
     ///
@@ -388,3 +418,26 @@ impl StackFrame
         self.kind.is_inlined()
     }
 }
+
+/// Serializes the parts of a frame a script reads: its position, registers as
+/// hex strings, the function's display name and the source file and line,
+/// with `null` for whatever isn't known
+impl Serialize for StackFrame
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("index", &self.index)?;
+        map.serialize_entry("thread", &self.thread)?;
+        map.serialize_entry("inlined", &self.is_inlined())?;
+        map.serialize_entry("pc", &self.pc)?;
+        map.serialize_entry("sp", &self.sp)?;
+        map.serialize_entry("fp", &self.fp)?;
+        map.serialize_entry("return_address", &self.return_address)?;
+        map.serialize_entry("function", &self.symbol.as_ref().map(SymbolName::display_name))?;
+        map.serialize_entry("file", &self.location.as_ref().map(|location| &location.file))?;
+        map.serialize_entry("line", &self.location.as_ref().and_then(|location| location.line))?;
+        map.serialize_entry("status", &self.status)?;
+        map.end()
+    }
+}
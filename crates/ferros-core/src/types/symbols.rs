@@ -47,6 +47,8 @@ pub enum SymbolLanguage
     Cpp,
     /// C symbol or unmangled global.
     C,
+    /// Objective-C method (`-[Class selector]` / `+[Class selector]`).
+    ObjC,
     /// Unknown or mixed language.
     Unknown,
 }
@@ -59,6 +61,7 @@ impl fmt::Display for SymbolLanguage
             SymbolLanguage::Rust => "rust",
             SymbolLanguage::Cpp => "c++",
             SymbolLanguage::C => "c",
+            SymbolLanguage::ObjC => "objc",
             SymbolLanguage::Unknown => "unknown",
         };
         write!(f, "{label}")
@@ -120,15 +123,21 @@ impl fmt::Display for SymbolName
 }
 
 /// Source code location for a symbol or frame.
+///
+/// `file` is the normalized local path (see [`crate::symbols::paths`]): the
+/// one to display, compare and key caches on.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SourceLocation
 {
-    /// Absolute or workspace-relative path.
+    /// Normalized local path, absolute or workspace-relative.
     pub file: String,
     /// Line number, if known.
     pub line: Option<u32>,
     /// Column number, if known.
     pub column: Option<u32>,
+    /// Path as DWARF recorded it, before normalization and source-map
+    /// remapping; `None` if the location didn't come from DWARF.
+    pub dwarf_file: Option<String>,
 }
 
 impl SourceLocation
@@ -140,6 +149,28 @@ impl SourceLocation
             file: file.into(),
             line: None,
             column: None,
+            dwarf_file: None,
         }
     }
+
+    /// Location for a DWARF path, with `file` set to its normalized form.
+    #[must_use]
+    pub fn from_dwarf(dwarf_file: String, line: Option<u32>, column: Option<u32>) -> Self
+    {
+        Self {
+            file: crate::symbols::paths::normalize_path(&dwarf_file),
+            line,
+            column,
+            dwarf_file: Some(dwarf_file),
+        }
+    }
+
+    /// The path DWARF recorded, if normalization or the source map changed it.
+    ///
+    /// Exports include it next to `file` so either spelling can be matched.
+    #[must_use]
+    pub fn original_file(&self) -> Option<&str>
+    {
+        self.dwarf_file.as_deref().filter(|dwarf_file| *dwarf_file != self.file)
+    }
 }
@@ -0,0 +1,143 @@
+//! What a thread is doing: its name, run state and CPU use, and which
+//! threads stop when one of them hits a breakpoint.
+//!
+//! [`ThreadInfo`] is read with
+//! [`TargetInspector::thread_info`](crate::TargetInspector::thread_info).
+//! On macOS it comes from `thread_info()` with `THREAD_EXTENDED_INFO`, whose
+//! `pth_name` is the name the thread gave itself with
+//! `pthread_setname_np()`, and `THREAD_BASIC_INFO` for the suspend count.
+//!
+//! [`StopPolicy`] is set with
+//! [`Debugger::set_stop_policy`](crate::Debugger::set_stop_policy).
+//!
+//! ```rust
+//! use ferros_core::types::{StopPolicy, ThreadId, ThreadInfo, ThreadRunState};
+//!
+//! let mut info = ThreadInfo::new(ThreadId::from(7));
+//! info.name = Some("tokio-runtime-worker".to_string());
+//! info.state = ThreadRunState::Waiting;
+//! assert!(info.name_matches("Tokio"));
+//! assert_eq!(info.state.to_string(), "waiting");
+//! assert_eq!("all".parse::<StopPolicy>()?, StopPolicy::AllThreads);
+//! # Ok::<(), ferros_core::DebuggerError>(())
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{DebuggerError, Result};
+use crate::types::ThreadId;
+
+/// Whether a thread is running, and if not, why.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ThreadRunState
+{
+    /// On a CPU or ready to be.
+    Running,
+    /// Blocked: sleeping, or waiting on a lock, I/O or a message.
+    Waiting,
+    /// Suspended by the debugger, the target or the kernel, and won't run until resumed.
+    Suspended,
+    /// Stopped for good: the thread is exiting.
+    Halted,
+    /// The backend couldn't tell.
+    #[default]
+    Unknown,
+}
+
+impl fmt::Display for ThreadRunState
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::Running => "running",
+            Self::Waiting => "waiting",
+            Self::Suspended => "suspended",
+            Self::Halted => "halted",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+/// A thread's name, run state and CPU use, as the kernel reports them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadInfo
+{
+    /// The thread this describes.
+    pub id: ThreadId,
+    /// Name the thread gave itself, if any (`std::thread::Builder::name`,
+    /// `pthread_setname_np()`).
+    pub name: Option<String>,
+    /// Whether it is running.
+    pub state: ThreadRunState,
+    /// Outstanding suspensions from anyone, not only this debugger; see
+    /// [`TargetInspector::thread_suspend_count`](crate::TargetInspector::thread_suspend_count)
+    /// for the debugger's own.
+    pub suspend_count: u32,
+    /// Recent CPU use as a percentage of one core.
+    pub cpu_percent: f64,
+}
+
+impl ThreadInfo
+{
+    /// Info for `id` with nothing known about it yet.
+    #[must_use]
+    pub fn new(id: ThreadId) -> Self
+    {
+        Self {
+            id,
+            name: None,
+            state: ThreadRunState::Unknown,
+            suspend_count: 0,
+            cpu_percent: 0.0,
+        }
+    }
+
+    /// Whether the name contains `filter`, ignoring case. Unnamed threads never match.
+    #[must_use]
+    pub fn name_matches(&self, filter: &str) -> bool
+    {
+        self.name
+            .as_ref()
+            .is_some_and(|name| name.to_lowercase().contains(&filter.to_lowercase()))
+    }
+}
+
+/// Which threads stop when one of them stops for a breakpoint, watchpoint or signal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StopPolicy
+{
+    /// Only the thread that hit the stop waits; the others keep running.
+    #[default]
+    HitThreadOnly,
+    /// Every other thread is suspended before the stop is reported, and
+    /// resumed with the thread that hit it.
+    AllThreads,
+}
+
+impl fmt::Display for StopPolicy
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::HitThreadOnly => "one",
+            Self::AllThreads => "all",
+        })
+    }
+}
+
+impl FromStr for StopPolicy
+{
+    type Err = DebuggerError;
+
+    fn from_str(text: &str) -> Result<Self>
+    {
+        match text {
+            "one" => Ok(Self::HitThreadOnly),
+            "all" => Ok(Self::AllThreads),
+            _ => Err(DebuggerError::InvalidArgument(format!(
+                "Unknown stop policy '{text}', expected all or one"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,459 @@
+//! Deadline supervision for unattended sessions.
+//!
+//! In CI a target can deadlock before it reaches the breakpoint a script is
+//! waiting for, or never get going at all (a missing dylib, a resource that
+//! never shows up). Instead of blocking forever, a front-end bounds the wait
+//! with [`wait_for_stop`](crate::events::wait_for_stop). When the deadline
+//! passes it suspends the target, takes a [`HangReport`] with the symbolicated
+//! backtrace of every thread, and applies a [`TimeoutAction`].
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use ferros_core::watchdog::{TimeoutAction, parse_duration};
+//!
+//! assert_eq!(parse_duration("1m30s"), Ok(Duration::from_secs(90)));
+//! assert_eq!("detach".parse::<TimeoutAction>(), Ok(TimeoutAction::Detach));
+//! ```
+
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::debugger::Debugger;
+use crate::error::Result;
+use crate::inspector::{LiveInspector, TargetInspector};
+use crate::types::{StackFrame, ThreadId};
+
+/// Frames unwound per thread for a hang report.
+pub const HANG_REPORT_MAX_FRAMES: usize = 64;
+
+/// Functions a thread sits in while it waits on something.
+///
+/// Matched against the innermost frame's symbol name.
+pub(crate) const BLOCKING_CALLS: &[&str] = &[
+    "__psynch_cvwait",
+    "__psynch_mutexwait",
+    "__psynch_rw_rdlock",
+    "__psynch_rw_wrlock",
+    "__semwait_signal",
+    "__ulock_wait",
+    "__ulock_wait2",
+    "__workq_kernreturn",
+    "mach_msg_trap",
+    "mach_msg2_trap",
+    "__select",
+    "__read_nocancel",
+    "read",
+    "__wait4",
+    "kevent",
+    "kevent_id",
+    "poll",
+    "nanosleep",
+];
+
+/// What to do with the target once a deadline has passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeoutAction
+{
+    /// Kill the target.
+    #[default]
+    Kill,
+    /// Resume the target and detach, leaving it running.
+    Detach,
+    /// Detach but leave the target suspended, for inspection by other tools.
+    Keep,
+}
+
+impl TimeoutAction
+{
+    /// Apply the action to a suspended target.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the error of the kill, resume or detach that failed.
+    pub fn apply(self, debugger: &mut dyn Debugger) -> Result<()>
+    {
+        match self {
+            Self::Kill => debugger.kill(),
+            Self::Detach => {
+                debugger.resume()?;
+                debugger.detach()
+            }
+            Self::Keep => debugger.detach(),
+        }
+    }
+}
+
+impl FromStr for TimeoutAction
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err>
+    {
+        match s.to_ascii_lowercase().as_str() {
+            "kill" => Ok(Self::Kill),
+            "detach" => Ok(Self::Detach),
+            "keep" => Ok(Self::Keep),
+            _ => Err(format!("invalid timeout action '{s}' (expected kill, detach or keep)")),
+        }
+    }
+}
+
+impl fmt::Display for TimeoutAction
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(match self {
+            Self::Kill => "kill",
+            Self::Detach => "detach",
+            Self::Keep => "keep",
+        })
+    }
+}
+
+/// Parse a duration such as `30s`, `500ms`, `2m` or `1m30s`.
+///
+/// A bare number is seconds. Units are `ms`, `s`, `m` and `h`.
+///
+/// ## Errors
+///
+/// Returns a message naming the part that couldn't be parsed.
+pub fn parse_duration(s: &str) -> std::result::Result<Duration, String>
+{
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("empty duration".to_string());
+    }
+    if let Ok(seconds) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let value: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| format!("invalid duration '{s}': expected a number before '{}'", &rest[digits..]))?;
+        rest = &rest[digits..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let part = match &rest[..unit_len] {
+            "ms" => Duration::from_millis(value),
+            "s" => Duration::from_secs(value),
+            "m" => Duration::from_secs(value * 60),
+            "h" => Duration::from_secs(value * 3600),
+            unit => return Err(format!("invalid duration '{s}': unknown unit '{unit}' (use ms, s, m or h)")),
+        };
+        total += part;
+        rest = &rest[unit_len..];
+    }
+    Ok(total)
+}
+
+/// Backtrace of one thread at the time of a hang.
+#[derive(Debug, Clone)]
+pub struct ThreadBacktrace
+{
+    /// The thread.
+    pub thread: ThreadId,
+    /// Frames, innermost first.
+    pub frames: Vec<StackFrame>,
+    /// Why the thread couldn't be unwound, if it couldn't.
+    pub error: Option<String>,
+}
+
+impl ThreadBacktrace
+{
+    /// The wait primitive the thread is blocked in, if its innermost frame is one.
+    #[must_use]
+    pub fn blocked_in(&self) -> Option<&str>
+    {
+        let name = self.frames.first()?.symbol.as_ref()?.display_name();
+        BLOCKING_CALLS.contains(&name).then_some(name)
+    }
+
+    /// Innermost frame in the program's own code: one with a source location
+    /// outside the Rust standard library.
+    #[must_use]
+    pub fn program_frame(&self) -> Option<&StackFrame>
+    {
+        self.frames.iter().find(|frame| {
+            frame
+                .location
+                .as_ref()
+                .is_some_and(|location| !location.file.starts_with("/rustc/"))
+        })
+    }
+}
+
+/// All-thread snapshot of a target that missed its deadline.
+#[derive(Debug, Clone)]
+pub struct HangReport
+{
+    /// How long ferros waited for a stop.
+    pub waited: Duration,
+    /// One backtrace per thread.
+    pub threads: Vec<ThreadBacktrace>,
+}
+
+impl HangReport
+{
+    /// Suspend the target and unwind every thread.
+    ///
+    /// The active thread is restored afterwards. See [`collect`](Self::collect).
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the target can't be suspended or its threads
+    /// can't be listed.
+    pub fn capture(debugger: &mut dyn Debugger, waited: Duration) -> Result<Self>
+    {
+        debugger.suspend()?;
+        debugger.refresh_threads()?;
+        Self::collect(&LiveInspector::new(debugger), waited)
+    }
+
+    /// Build the report from the backtraces of an already stopped target.
+    ///
+    /// Threads that can't be unwound are kept with their error so the report
+    /// still lists them.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the target's threads can't be listed.
+    pub fn collect(target: &dyn TargetInspector, waited: Duration) -> Result<Self>
+    {
+        let threads = target
+            .threads()?
+            .into_iter()
+            .map(|thread| match target.backtrace(thread, HANG_REPORT_MAX_FRAMES) {
+                Ok(frames) => ThreadBacktrace {
+                    thread,
+                    frames,
+                    error: None,
+                },
+                Err(err) => ThreadBacktrace {
+                    thread,
+                    frames: Vec::new(),
+                    error: Some(err.to_string()),
+                },
+            })
+            .collect();
+        Ok(Self { waited, threads })
+    }
+
+    /// Plain-text report: a one-line analysis per thread, then the backtraces.
+    #[must_use]
+    pub fn render(&self) -> String
+    {
+        let mut report = format!(
+            "# ferros hang report\n\nNo stop within {:.1?}; {} thread(s) at the deadline.\n\n## Analysis\n\n",
+            self.waited,
+            self.threads.len()
+        );
+        for backtrace in &self.threads {
+            let state = match (backtrace.blocked_in(), &backtrace.error) {
+                (_, Some(err)) => format!("could not unwind: {err}"),
+                (Some(call), None) => format!("blocked in {call}"),
+                (None, None) => "running".to_string(),
+            };
+            let _ = write!(report, "thread {}: {state}", backtrace.thread.raw());
+            if let Some(frame) = backtrace.program_frame() {
+                let _ = write!(report, ", from {}", describe_frame(frame));
+            }
+            report.push('\n');
+        }
+
+        report.push_str("\n## Backtraces\n");
+        for backtrace in &self.threads {
+            let _ = writeln!(report, "\nthread {}:", backtrace.thread.raw());
+            for frame in &backtrace.frames {
+                let _ = writeln!(
+                    report,
+                    "  #{:<3} 0x{:016x} {}",
+                    frame.index,
+                    frame.pc.value(),
+                    describe_frame(frame)
+                );
+            }
+        }
+        report
+    }
+}
+
+fn describe_frame(frame: &StackFrame) -> String
+{
+    let function = frame.symbol.as_ref().map_or("<unknown>", |symbol| symbol.display_name());
+    match &frame.location {
+        Some(location) => match location.line {
+            Some(line) => format!("{function} at {}:{line}", location.file),
+            None => format!("{function} at {}", location.file),
+        },
+        None => function.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::error::DebuggerError;
+    use crate::types::{
+        Address, Architecture, FrameId, FrameKind, FrameStatus, MemoryRegion, Registers, SourceLocation, StopReason,
+        SymbolLanguage, SymbolName,
+    };
+
+    fn frame(index: usize, function: &str, file: Option<&str>) -> StackFrame
+    {
+        let thread = ThreadId::from(1);
+        let pc = Address::from(0x1_0000_1000 + index as u64 * 0x40);
+        StackFrame {
+            id: FrameId::new(thread, u32::try_from(index).unwrap(), 0, pc, Address::ZERO),
+            thread,
+            index,
+            kind: FrameKind::Physical,
+            pc,
+            sp: Address::ZERO,
+            fp: Address::ZERO,
+            return_address: None,
+            symbol: Some(SymbolName::new(function.to_string(), None, SymbolLanguage::C)),
+            location: file.map(|file| SourceLocation {
+                file: file.to_string(),
+                line: Some(12),
+                column: None,
+                dwarf_file: None,
+            }),
+            parameters: Vec::new(),
+            status: FrameStatus::Complete,
+        }
+    }
+
+    #[test]
+    fn durations_parse_with_units()
+    {
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h2m3s"), Ok(Duration::from_secs(3723)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("10 seconds").is_err());
+        assert!(parse_duration("ms").is_err());
+    }
+
+    #[test]
+    fn report_names_the_blocking_call_and_program_frame()
+    {
+        let report = HangReport {
+            waited: Duration::from_secs(5),
+            threads: vec![
+                ThreadBacktrace {
+                    thread: ThreadId::from(1),
+                    frames: vec![
+                        frame(0, "__psynch_mutexwait", None),
+                        frame(
+                            1,
+                            "std::sys::sync::mutex::lock",
+                            Some("/rustc/abc/library/std/src/sys/sync/mutex.rs"),
+                        ),
+                        frame(2, "fixture::main", Some("src/main.rs")),
+                    ],
+                    error: None,
+                },
+                ThreadBacktrace {
+                    thread: ThreadId::from(2),
+                    frames: vec![frame(0, "fixture::spin", Some("src/main.rs"))],
+                    error: None,
+                },
+                ThreadBacktrace {
+                    thread: ThreadId::from(3),
+                    frames: Vec::new(),
+                    error: Some("thread exited".to_string()),
+                },
+            ],
+        };
+
+        let text = report.render();
+        assert!(text.contains("No stop within 5.0s; 3 thread(s)"));
+        assert!(text.contains("thread 1: blocked in __psynch_mutexwait, from fixture::main at src/main.rs:12\n"));
+        assert!(text.contains("thread 2: running, from fixture::spin at src/main.rs:12\n"));
+        assert!(text.contains("thread 3: could not unwind: thread exited\n"));
+        assert!(text.contains("  #2   0x0000000100001080 fixture::main at src/main.rs:12\n"));
+    }
+
+    /// Stacks saved from a hung target; nothing here can suspend or resume it.
+    struct SavedStacks(HashMap<ThreadId, Vec<StackFrame>>);
+
+    impl TargetInspector for SavedStacks
+    {
+        fn architecture(&self) -> Architecture
+        {
+            Architecture::Arm64
+        }
+
+        fn is_attached(&self) -> bool
+        {
+            false
+        }
+
+        fn is_stopped(&self) -> bool
+        {
+            true
+        }
+
+        fn stop_reason(&self) -> StopReason
+        {
+            StopReason::Suspended
+        }
+
+        fn threads(&self) -> Result<Vec<ThreadId>>
+        {
+            Ok(vec![ThreadId::from(1), ThreadId::from(2)])
+        }
+
+        fn active_thread(&self) -> Option<ThreadId>
+        {
+            Some(ThreadId::from(1))
+        }
+
+        fn read_registers(&self) -> Result<Registers>
+        {
+            Err(DebuggerError::NotAttached)
+        }
+
+        fn read_memory(&self, _addr: Address, _len: usize) -> Result<Vec<u8>>
+        {
+            Err(DebuggerError::NotAttached)
+        }
+
+        fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+        {
+            Ok(Vec::new())
+        }
+
+        fn backtrace(&self, thread: ThreadId, max_frames: usize) -> Result<Vec<StackFrame>>
+        {
+            let frames = self.0.get(&thread).ok_or(DebuggerError::NotAttached)?;
+            Ok(frames.iter().take(max_frames).cloned().collect())
+        }
+    }
+
+    #[test]
+    fn report_is_collected_from_an_inspector_only_target()
+    {
+        let stacks = SavedStacks(HashMap::from([(
+            ThreadId::from(1),
+            vec![
+                frame(0, "__semwait_signal", None),
+                frame(1, "fixture::main", Some("src/main.rs")),
+            ],
+        )]));
+        let report = HangReport::collect(&stacks, Duration::from_secs(2)).unwrap();
+
+        let text = report.render();
+        assert!(text.contains("thread 1: blocked in __semwait_signal, from fixture::main at src/main.rs:12\n"));
+        assert!(text.contains("thread 2: could not unwind: Not attached to a process\n"));
+    }
+}
@@ -0,0 +1,102 @@
+//! `Content-Length` framing of DAP messages.
+//!
+//! Each message is a header block of `Name: value` lines ending in a blank
+//! line, followed by exactly `Content-Length` bytes of UTF-8 JSON. Header lines
+//! end in `\r\n`; a bare `\n` is accepted too.
+
+use std::io::{self, BufRead, Write};
+
+use serde::Serialize;
+
+/// Read the body of the next message, or `None` at end of input.
+///
+/// # Errors
+///
+/// Returns `InvalidData` for a header block without a `Content-Length` or
+/// with one that isn't a number, and `UnexpectedEof` if input ends inside a
+/// message.
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Vec<u8>>>
+{
+    let mut length = None;
+    let mut started = false;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            if started {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "input ended inside a header"));
+            }
+            return Ok(None);
+        }
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            if started {
+                break;
+            }
+            // Blank lines between messages are harmless
+            continue;
+        }
+        started = true;
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            let value = value.trim();
+            length = Some(
+                value
+                    .parse::<usize>()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid Content-Length: {value}")))?,
+            );
+        }
+    }
+
+    let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "message has no Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// Write `message` as JSON behind its `Content-Length` header and flush.
+///
+/// # Errors
+///
+/// Returns an error if `message` can't be serialized or `out` can't be written.
+pub fn write_message<W: Write, T: Serialize>(out: &mut W, message: &T) -> io::Result<()>
+{
+    let body = serde_json::to_vec(message)?;
+    write!(out, "Content-Length: {}\r\n\r\n", body.len())?;
+    out.write_all(&body)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::io::Cursor;
+
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    #[test]
+    fn messages_round_trip_through_their_headers()
+    {
+        let mut wire = Vec::new();
+        write_message(&mut wire, &json!({"seq": 1, "command": "threads"})).unwrap();
+        // Other headers are ignored and the name is case-insensitive
+        wire.extend_from_slice(b"content-type: application/json\ncontent-length: 2\n\n{}");
+
+        let mut input = Cursor::new(wire);
+        let first: Value = serde_json::from_slice(&read_message(&mut input).unwrap().unwrap()).unwrap();
+        assert_eq!(first["command"], "threads");
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), b"{}");
+        assert!(read_message(&mut input).unwrap().is_none());
+    }
+
+    #[test]
+    fn a_header_without_a_length_is_rejected()
+    {
+        let mut input = Cursor::new(b"Content-Type: application/json\r\n\r\n{}".to_vec());
+        let error = read_message(&mut input).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
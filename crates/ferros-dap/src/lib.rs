@@ -0,0 +1,49 @@
+//! # Debug Adapter Protocol
+//!
+//! `ferros dap` speaks the [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/)
+//! on stdin/stdout, so VS Code, nvim-dap and other DAP clients can drive a
+//! [`Debugger`](ferros_core::Debugger). Each message is a JSON body behind a
+//! `Content-Length` header (see [`framing`]); the core knows nothing of it.
+//!
+//! ```text
+//! → {"seq":1,"type":"request","command":"initialize","arguments":{"adapterID":"ferros"}}
+//! ← {"seq":1,"type":"response","request_seq":1,"command":"initialize","success":true,"body":{...}}
+//! ← {"seq":2,"type":"event","event":"initialized"}
+//! → {"seq":2,"type":"request","command":"launch","arguments":{"program":"./target/debug/app"}}
+//! → {"seq":3,"type":"request","command":"setBreakpoints","arguments":{"source":{"path":"src/main.rs"},"breakpoints":[{"line":12}]}}
+//! → {"seq":4,"type":"request","command":"configurationDone"}
+//! ← {"seq":7,"type":"event","event":"stopped","body":{"reason":"breakpoint","threadId":259,"hitBreakpointIds":[1],...}}
+//! ```
+//!
+//! ## Supported requests
+//!
+//! `initialize`, `launch` (`program`, `args`, `cwd`, `env`, `stopOnEntry`),
+//! `attach` (`pid`), `setBreakpoints`, `configurationDone`, `threads`,
+//! `stackTrace`, `scopes`, `variables`, `continue`, `next`, `stepIn`,
+//! `stepOut`, `pause` and `disconnect`. Anything else is answered with
+//! `"success": false`.
+//!
+//! ## Rules
+//!
+//! - The target is launched or attached suspended and only runs once
+//!   `configurationDone` has arrived, so breakpoints are in place first.
+//! - Source breakpoints resolve through the debug info's line table. Lines
+//!   with no code yet are unverified and retried at every stop and library
+//!   load; a `breakpoint` event announces them once installed.
+//! - Stepping is by instruction: `next` runs calls to completion, `stepIn`
+//!   executes one instruction and `stepOut` runs to the caller.
+//! - `scopes` lists a frame's arguments, and for the innermost frame its
+//!   registers. Only the innermost frame's arguments have values.
+//! - Nothing but protocol messages is written to stdout; logs go to the log
+//!   file as in TUI mode.
+
+pub mod framing;
+mod messages;
+mod session;
+
+pub use messages::{
+    AttachArguments, Breakpoint, Capabilities, DisconnectArguments, Event, LaunchArguments, Message, Request, Response,
+    Scope, ScopesArguments, SetBreakpointsArguments, Source, SourceBreakpoint, StackFrame, StackTraceArguments, Thread,
+    ThreadArguments, Variable, VariablesArguments,
+};
+pub use session::DapSession;
@@ -0,0 +1,271 @@
+//! Wire types for the subset of DAP that ferros speaks.
+//!
+//! Field names follow the protocol specification (camelCase on the wire);
+//! fields ferros has no use for are ignored when reading and left out when
+//! writing.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A request from the client.
+///
+/// Arguments are kept as raw JSON and parsed per command, so a request for a
+/// command ferros doesn't support can still be answered by name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request
+{
+    /// Client sequence number, echoed back as `request_seq`.
+    pub seq: u64,
+    /// Command name, such as `setBreakpoints`.
+    pub command: String,
+    /// Command arguments; `null` when the client sent none.
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// A message from the adapter to the client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Message
+{
+    Response(Response),
+    Event(Event),
+}
+
+/// The answer to one request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(clippy::large_stack_arrays)]
+pub struct Response
+{
+    /// Adapter sequence number, shared with events.
+    pub seq: u64,
+    /// `seq` of the request this answers.
+    pub request_seq: u64,
+    /// The request's command.
+    pub command: String,
+    /// Whether the request succeeded.
+    pub success: bool,
+    /// Why the request failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Command-specific result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// Something that happened without being asked for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event
+{
+    /// Adapter sequence number, shared with responses.
+    pub seq: u64,
+    /// Event name, such as `stopped`.
+    pub event: String,
+    /// Event-specific details.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// Arguments of `launch`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchArguments
+{
+    /// Path to the executable.
+    pub program: String,
+    /// Arguments, not including the program name.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory; the adapter's own when absent.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// Variables set for the target on top of the adapter's environment.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Report a stop before the first instruction instead of running on `configurationDone`.
+    #[serde(default)]
+    pub stop_on_entry: bool,
+}
+
+/// Arguments of `attach`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachArguments
+{
+    /// Process to attach to.
+    #[serde(alias = "processId")]
+    pub pid: u32,
+    /// Keep the target stopped after `configurationDone`.
+    #[serde(default)]
+    pub stop_on_entry: bool,
+}
+
+/// A source file as the client names it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Source
+{
+    /// Short name shown in the UI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Path on disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// One breakpoint in `setBreakpoints`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SourceBreakpoint
+{
+    /// 1-based line number.
+    pub line: u32,
+}
+
+/// Arguments of `setBreakpoints`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SetBreakpointsArguments
+{
+    /// The file whose breakpoints are replaced.
+    pub source: Source,
+    /// Every breakpoint the file should have; empty clears them.
+    #[serde(default)]
+    pub breakpoints: Vec<SourceBreakpoint>,
+}
+
+/// Arguments of `continue`, `next`, `stepIn`, `stepOut` and `pause`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadArguments
+{
+    /// Thread the request is for.
+    pub thread_id: u64,
+}
+
+/// Arguments of `stackTrace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTraceArguments
+{
+    /// Thread to unwind.
+    pub thread_id: u64,
+    /// Index of the first frame to return.
+    #[serde(default)]
+    pub start_frame: usize,
+    /// Number of frames to return; all of them when absent or 0.
+    #[serde(default)]
+    pub levels: usize,
+}
+
+/// Arguments of `scopes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesArguments
+{
+    /// A frame id from `stackTrace`.
+    pub frame_id: u64,
+}
+
+/// Arguments of `variables`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesArguments
+{
+    /// A reference from `scopes`.
+    pub variables_reference: u64,
+}
+
+/// Arguments of `disconnect`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisconnectArguments
+{
+    /// Kill the target instead of detaching from it.
+    #[serde(default)]
+    pub terminate_debuggee: bool,
+}
+
+/// What the adapter supports, returned by `initialize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities
+{
+    pub supports_configuration_done_request: bool,
+    pub supports_terminate_debuggee: bool,
+}
+
+/// A thread in `threads`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Thread
+{
+    pub id: u64,
+    pub name: String,
+}
+
+/// A frame in `stackTrace`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::large_stack_arrays)]
+pub struct StackFrame
+{
+    /// Id for `scopes`, valid until the target next runs.
+    pub id: u64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    /// 1-based line, 0 when unknown.
+    pub line: u32,
+    /// 1-based column, 0 when unknown.
+    pub column: u32,
+    /// The frame's PC as `0x…`.
+    pub instruction_pointer_reference: String,
+    /// `subtle` for frames the unwinder wasn't sure of.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<&'static str>,
+}
+
+/// A scope in `scopes`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope
+{
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<&'static str>,
+    /// Reference for `variables`.
+    pub variables_reference: u64,
+    pub expensive: bool,
+}
+
+/// A variable in `variables`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable
+{
+    pub name: String,
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    /// Always 0: ferros variables have no children.
+    pub variables_reference: u64,
+}
+
+/// A breakpoint in `setBreakpoints` responses and `breakpoint` events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::large_stack_arrays)]
+pub struct Breakpoint
+{
+    pub id: u64,
+    /// Whether the line resolved to code and a trap is installed.
+    pub verified: bool,
+    /// Why the breakpoint isn't verified yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub source: Source,
+    pub line: u32,
+    /// First address the breakpoint is installed at, as `0x…`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_reference: Option<String>,
+}
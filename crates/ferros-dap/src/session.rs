@@ -0,0 +1,906 @@
+//! Drives a [`Debugger`] from DAP requests.
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use ferros_core::Debugger;
+use ferros_core::breakpoints::BreakpointLocation;
+use ferros_core::error::{DebuggerError, Result as DebuggerResult};
+use ferros_core::symbols::paths::SourceResolver;
+use ferros_core::types::{
+    FrameStatus, LaunchOptions, ProcessId, RegisterCategory, StackFrame as CoreFrame, StopReason, ThreadId,
+    register_display_order,
+};
+use ferros_protocol::controller::{self, ClientBreakpoint, ControllerEvent, Frontend, SessionController, StopKind};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use tracing::{debug, warn};
+
+use super::framing::{read_message, write_message};
+use super::messages::{
+    AttachArguments, Breakpoint, Capabilities, DisconnectArguments, Event, LaunchArguments, Message, Request, Response,
+    Scope, ScopesArguments, SetBreakpointsArguments, Source, StackFrame, StackTraceArguments, Thread, ThreadArguments,
+    Variable, VariablesArguments,
+};
+
+/// Frames unwound for a `stackTrace` that doesn't limit `levels`.
+const MAX_FRAMES: usize = 256;
+
+/// A breakpoint on one line, kept by the controller under `id`.
+struct LineBreakpoint
+{
+    id: u64,
+    line: u32,
+}
+
+/// Every breakpoint the client set in one file.
+struct SourceBreakpoints
+{
+    source: Source,
+    path: String,
+    breakpoints: Vec<LineBreakpoint>,
+}
+
+/// What a `variablesReference` handed out by `scopes` lists.
+#[derive(Clone, Copy)]
+enum ScopeRef
+{
+    /// Parameters of the frame at this index in `frames`
+    Arguments(usize),
+    /// A thread's registers, shown for its innermost frame
+    Registers(ThreadId),
+}
+
+/// One DAP session over a single debugger.
+///
+/// Requests are handled in order, and debugger events and target output are
+/// flushed before each one and while waiting for input, as in `ferros mi`;
+/// both run on a [`SessionController`], which also keeps the breakpoints.
+/// Frame ids and variable references are only valid until the target next runs.
+#[allow(clippy::struct_excessive_bools)]
+pub struct DapSession<W: Write>
+{
+    controller: SessionController,
+    out: W,
+    seq: u64,
+    /// Whether `configurationDone` has arrived
+    configured: bool,
+    /// Whether the target has been let go after configuration
+    started: bool,
+    stop_on_entry: bool,
+    sources: Vec<SourceBreakpoints>,
+    frames: Vec<CoreFrame>,
+    scopes: Vec<ScopeRef>,
+    /// Set by every `stopped` or `exited` event, so a step that published
+    /// its own stop isn't reported twice
+    stop_reported: bool,
+    /// Where the last step ended, reported if no event did
+    step_stop: Option<(StopReason, ThreadId)>,
+}
+
+impl<W: Write> DapSession<W>
+{
+    /// Create a session that writes messages to `out`.
+    pub fn new(debugger: Box<dyn Debugger>, out: W) -> Self
+    {
+        Self {
+            controller: SessionController::new(debugger),
+            out,
+            seq: 0,
+            configured: false,
+            started: false,
+            stop_on_entry: false,
+            sources: Vec::new(),
+            frames: Vec::new(),
+            scopes: Vec::new(),
+            stop_reported: false,
+            step_stop: None,
+        }
+    }
+
+    /// Read requests from `input` until `disconnect` or end of input.
+    ///
+    /// Input is read on a helper thread so events keep flowing while the client
+    /// is idle. On end of input, or input that isn't framed DAP, the target is
+    /// detached as if `disconnect` had been sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output fails.
+    pub fn run<R: BufRead + Send + 'static>(mut self, mut input: R) -> io::Result<()>
+    {
+        controller::serve(&mut self, move || match read_message(&mut input) {
+            Ok(body) => body,
+            Err(err) => {
+                warn!("Unreadable DAP input, disconnecting: {}", err);
+                None
+            }
+        })
+    }
+
+    /// Handle one request body and write its response.
+    ///
+    /// Returns `false` once the session is over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output fails.
+    pub fn handle_message(&mut self, body: &[u8]) -> io::Result<bool>
+    {
+        let request = match serde_json::from_slice::<Request>(body) {
+            Ok(request) => request,
+            Err(err) => {
+                let (request_seq, command) = controller::identify(body);
+                self.respond(request_seq, command, Err(format!("invalid request: {err}")))?;
+                return Ok(true);
+            }
+        };
+
+        self.pump()?;
+        let command = request.command.as_str();
+        let result = self.execute(command, request.arguments).map_err(|err| err.to_string());
+        let succeeded = result.is_ok();
+        self.respond(request.seq, command.to_string(), result)?;
+        if succeeded {
+            match command {
+                "initialize" => self.emit("initialized", None)?,
+                "launch" | "attach" => {
+                    // Breakpoints set before the target existed can resolve now
+                    let events = self.controller.resolve_pending();
+                    self.report(events)?;
+                    self.start_if_configured()?;
+                }
+                "configurationDone" => self.start_if_configured()?,
+                _ => {}
+            }
+        }
+        self.pump()?;
+        if let Some((reason, thread)) = self.step_stop.take()
+            && !self.stop_reported
+        {
+            let events = self.controller.stopped(reason, Some(thread));
+            self.report(events)?;
+        }
+        Ok(command != "disconnect")
+    }
+
+    /// Emit every queued debugger event and output chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output fails.
+    pub fn pump(&mut self) -> io::Result<()>
+    {
+        let events = self.controller.poll();
+        self.report(events)
+    }
+
+    /// Emit each controller event as its DAP event; image events have none.
+    fn report(&mut self, events: Vec<ControllerEvent>) -> io::Result<()>
+    {
+        for event in events {
+            match event {
+                ControllerEvent::Output { category, output } => {
+                    self.emit("output", Some(json!({"category": category, "output": output})))?;
+                }
+                ControllerEvent::Continued => {
+                    self.forget_frames();
+                    let thread = self.controller.debugger().active_thread().map_or(0, |thread| thread.raw());
+                    self.emit("continued", Some(json!({"threadId": thread, "allThreadsContinued": true})))?;
+                }
+                ControllerEvent::Stopped {
+                    kind,
+                    thread,
+                    hit_breakpoint_ids,
+                    description,
+                } => {
+                    self.stop_reported = true;
+                    self.emit(
+                        "stopped",
+                        Some(json!({
+                            "reason": stop_reason_name(kind),
+                            "threadId": thread.map(|thread| thread.raw()),
+                            "allThreadsStopped": true,
+                            "hitBreakpointIds": hit_breakpoint_ids,
+                            "description": description,
+                        })),
+                    )?;
+                }
+                ControllerEvent::Exited { code } => {
+                    self.stop_reported = true;
+                    self.emit("exited", Some(json!({"exitCode": code})))?;
+                    self.emit("terminated", None)?;
+                }
+                ControllerEvent::BreakpointResolved { id } => {
+                    let Some(breakpoint) = self.breakpoint_body(id) else {
+                        continue;
+                    };
+                    self.emit("breakpoint", Some(json!({"reason": "changed", "breakpoint": breakpoint})))?;
+                }
+                ControllerEvent::ImageLoaded { .. } | ControllerEvent::ImageUnloaded { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn execute(&mut self, command: &str, arguments: Value) -> DebuggerResult<Option<Value>>
+    {
+        match command {
+            "initialize" => Ok(Some(json!(Capabilities {
+                supports_configuration_done_request: true,
+                supports_terminate_debuggee: true,
+            }))),
+            "launch" => {
+                let launch: LaunchArguments = parse(arguments)?;
+                let mut args = vec![launch.program.clone()];
+                args.extend(launch.args);
+                let options = LaunchOptions {
+                    args,
+                    env: launch.env.into_iter().collect(),
+                    cwd: launch.cwd,
+                    ..LaunchOptions::default()
+                };
+                self.controller.launch(&launch.program, &options)?;
+                self.stop_on_entry = launch.stop_on_entry;
+                Ok(None)
+            }
+            "attach" => {
+                let attach: AttachArguments = parse(arguments)?;
+                self.controller.attach(ProcessId::from(attach.pid))?;
+                self.stop_on_entry = attach.stop_on_entry;
+                Ok(None)
+            }
+            "configurationDone" => {
+                self.configured = true;
+                Ok(None)
+            }
+            "setBreakpoints" => {
+                let arguments: SetBreakpointsArguments = parse(arguments)?;
+                let breakpoints = self.set_breakpoints(arguments)?;
+                Ok(Some(json!({"breakpoints": breakpoints})))
+            }
+            "threads" => {
+                let debugger = self.controller.debugger();
+                let threads: Vec<Thread> = debugger
+                    .threads()?
+                    .into_iter()
+                    .map(|thread| Thread {
+                        id: thread.raw(),
+                        name: debugger
+                            .thread_info(thread)
+                            .ok()
+                            .and_then(|info| info.name)
+                            .unwrap_or_else(|| format!("Thread {}", thread.raw())),
+                    })
+                    .collect();
+                Ok(Some(json!({"threads": threads})))
+            }
+            "stackTrace" => {
+                let arguments: StackTraceArguments = parse(arguments)?;
+                let levels = if arguments.levels == 0 { MAX_FRAMES } else { arguments.levels };
+                let frames = self.controller.debugger_mut().stack_trace_for(
+                    ThreadId::from(arguments.thread_id),
+                    arguments.start_frame.saturating_add(levels),
+                )?;
+                let total = frames.len();
+                let frames: Vec<StackFrame> = frames
+                    .into_iter()
+                    .skip(arguments.start_frame)
+                    .map(|frame| self.remember_frame(frame))
+                    .collect();
+                Ok(Some(json!({"stackFrames": frames, "totalFrames": total})))
+            }
+            "scopes" => {
+                let arguments: ScopesArguments = parse(arguments)?;
+                let scopes = self.scopes_for(arguments.frame_id)?;
+                Ok(Some(json!({"scopes": scopes})))
+            }
+            "variables" => {
+                let arguments: VariablesArguments = parse(arguments)?;
+                let variables = self.variables(arguments.variables_reference)?;
+                Ok(Some(json!({"variables": variables})))
+            }
+            "continue" => {
+                self.controller.debugger_mut().resume()?;
+                Ok(Some(json!({"allThreadsContinued": true})))
+            }
+            "next" => self.step(arguments, |debugger| debugger.step_over()),
+            "stepIn" => self.step(arguments, |debugger| debugger.step_instruction()),
+            "stepOut" => self.step(arguments, |debugger| debugger.step_out()),
+            "pause" => {
+                self.controller.debugger_mut().suspend()?;
+                Ok(None)
+            }
+            "disconnect" => {
+                let arguments: DisconnectArguments = if arguments.is_null() {
+                    DisconnectArguments::default()
+                } else {
+                    parse(arguments)?
+                };
+                self.controller.disconnect(arguments.terminate_debuggee);
+                Ok(None)
+            }
+            _ => Err(DebuggerError::InvalidArgument(format!("Unsupported request: {command}"))),
+        }
+    }
+
+    /// Run the target once it is both started and configured, or report the
+    /// entry stop for `stopOnEntry`
+    fn start_if_configured(&mut self) -> io::Result<()>
+    {
+        if !self.configured || !self.controller.is_attached() || self.started {
+            return Ok(());
+        }
+        self.started = true;
+        let debugger = self.controller.debugger_mut();
+        if self.stop_on_entry {
+            if debugger.is_stopped() {
+                let thread = debugger.active_thread().map(|thread| thread.raw());
+                return self.emit(
+                    "stopped",
+                    Some(json!({"reason": "entry", "threadId": thread, "allThreadsStopped": true})),
+                );
+            }
+            // The suspension is reported like any other pause
+            if let Err(err) = debugger.suspend() {
+                warn!("Failed to stop the target on entry: {}", err);
+            }
+        } else if debugger.is_stopped()
+            && let Err(err) = debugger.resume()
+        {
+            warn!("Failed to start the target after configuration: {}", err);
+        }
+        Ok(())
+    }
+
+    /// `next`, `stepIn` or `stepOut` on the requested thread
+    ///
+    /// Stepping is by instruction. The stop is reported after the response,
+    /// by the backend's own event if it published one.
+    fn step(
+        &mut self,
+        arguments: Value,
+        step: impl FnOnce(&mut dyn Debugger) -> DebuggerResult<StopReason>,
+    ) -> DebuggerResult<Option<Value>>
+    {
+        let arguments: ThreadArguments = parse(arguments)?;
+        let thread = ThreadId::from(arguments.thread_id);
+        self.controller.debugger_mut().set_active_thread(thread)?;
+        self.forget_frames();
+        self.stop_reported = false;
+        let reason = step(self.controller.debugger_mut())?;
+        self.step_stop = Some((reason, thread));
+        Ok(None)
+    }
+
+    /// Replace the breakpoints in one file, installing those that resolve
+    fn set_breakpoints(&mut self, arguments: SetBreakpointsArguments) -> DebuggerResult<Vec<Breakpoint>>
+    {
+        let path = arguments
+            .source
+            .path
+            .clone()
+            .or_else(|| arguments.source.name.clone())
+            .ok_or_else(|| DebuggerError::InvalidArgument("setBreakpoints needs a source path".to_string()))?;
+
+        if let Some(index) = self.sources.iter().position(|source| source.path == path) {
+            let old = self.sources.remove(index);
+            for breakpoint in old.breakpoints {
+                self.controller.remove_breakpoint(breakpoint.id);
+            }
+        }
+
+        let mut source = SourceBreakpoints {
+            source: arguments.source,
+            path,
+            breakpoints: Vec::new(),
+        };
+        for requested in arguments.breakpoints {
+            let location = BreakpointLocation::Line {
+                file: source.path.clone(),
+                line: requested.line,
+            };
+            match self.controller.add_breakpoint(location) {
+                Ok(id) => source.breakpoints.push(LineBreakpoint {
+                    id,
+                    line: requested.line,
+                }),
+                Err(err) => {
+                    // The client will resend the whole file, so keep none of it
+                    for breakpoint in source.breakpoints {
+                        self.controller.remove_breakpoint(breakpoint.id);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        let bodies = source
+            .breakpoints
+            .iter()
+            .filter_map(|breakpoint| {
+                let installed = self.controller.breakpoint(breakpoint.id)?;
+                Some(breakpoint_body(&source.source, breakpoint.line, installed))
+            })
+            .collect();
+        if !source.breakpoints.is_empty() {
+            self.sources.push(source);
+        }
+        Ok(bodies)
+    }
+
+    /// The client's view of the breakpoint the controller keeps under `id`
+    fn breakpoint_body(&self, id: u64) -> Option<Breakpoint>
+    {
+        let (source, line) = self.sources.iter().find_map(|source| {
+            let breakpoint = source.breakpoints.iter().find(|breakpoint| breakpoint.id == id)?;
+            Some((&source.source, breakpoint.line))
+        })?;
+        Some(breakpoint_body(source, line, self.controller.breakpoint(id)?))
+    }
+
+    /// Keep `frame` for `scopes` and describe it for `stackTrace`
+    fn remember_frame(&mut self, frame: CoreFrame) -> StackFrame
+    {
+        let location = frame.location.as_ref();
+        let body = StackFrame {
+            id: self.frames.len() as u64 + 1,
+            name: frame.symbol.as_ref().map_or_else(
+                || format!("0x{:x}", frame.pc.value()),
+                |symbol| symbol.display_name().to_string(),
+            ),
+            source: location.map(|location| source_for(&local_source(&location.file))),
+            line: location.and_then(|location| location.line).unwrap_or(0),
+            column: location.and_then(|location| location.column).unwrap_or(0),
+            instruction_pointer_reference: format!("0x{:x}", frame.pc.value()),
+            presentation_hint: (frame.status != FrameStatus::Complete).then_some("subtle"),
+        };
+        self.frames.push(frame);
+        body
+    }
+
+    /// The arguments of a frame, plus the registers of an innermost one
+    fn scopes_for(&mut self, frame_id: u64) -> DebuggerResult<Vec<Scope>>
+    {
+        let slot = usize::try_from(frame_id)
+            .ok()
+            .and_then(|id| id.checked_sub(1))
+            .filter(|&slot| slot < self.frames.len())
+            .ok_or_else(|| DebuggerError::InvalidArgument(format!("Unknown frame id {frame_id}")))?;
+        let frame = &self.frames[slot];
+        let mut refs = vec![("Arguments", "arguments", ScopeRef::Arguments(slot))];
+        if frame.index == 0 {
+            refs.push(("Registers", "registers", ScopeRef::Registers(frame.thread)));
+        }
+        Ok(refs
+            .into_iter()
+            .map(|(name, hint, scope)| {
+                self.scopes.push(scope);
+                Scope {
+                    name: name.to_string(),
+                    presentation_hint: Some(hint),
+                    variables_reference: self.scopes.len() as u64,
+                    expensive: false,
+                }
+            })
+            .collect())
+    }
+
+    /// What a `variablesReference` from `scopes` lists
+    ///
+    /// Arguments of the innermost frame show their argument register's value;
+    /// further out those registers have been reused, so only names and types
+    /// are shown.
+    fn variables(&self, reference: u64) -> DebuggerResult<Vec<Variable>>
+    {
+        let scope = usize::try_from(reference)
+            .ok()
+            .and_then(|reference| reference.checked_sub(1))
+            .and_then(|index| self.scopes.get(index))
+            .copied()
+            .ok_or_else(|| DebuggerError::InvalidArgument(format!("Unknown variables reference {reference}")))?;
+        match scope {
+            ScopeRef::Arguments(slot) => {
+                let frame = &self.frames[slot];
+                let values: Vec<Option<u64>> = if frame.index == 0 {
+                    let registers = self.controller.debugger().read_registers_for(frame.thread)?;
+                    register_display_order(registers.architecture())
+                        .into_iter()
+                        .filter(|row| row.category == RegisterCategory::Arguments)
+                        .map(|row| registers.get(row.id))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                Ok(frame
+                    .parameters
+                    .iter()
+                    .enumerate()
+                    .map(|(index, parameter)| Variable {
+                        name: parameter.name.clone().unwrap_or_else(|| format!("arg{index}")),
+                        value: match values.get(index).copied().flatten() {
+                            Some(value) => format!("0x{value:x}"),
+                            None => "<unavailable>".to_string(),
+                        },
+                        type_name: parameter.type_name.clone(),
+                        variables_reference: 0,
+                    })
+                    .collect())
+            }
+            ScopeRef::Registers(thread) => {
+                let registers = self.controller.debugger().read_registers_for(thread)?;
+                Ok(register_display_order(registers.architecture())
+                    .into_iter()
+                    .filter_map(|row| {
+                        Some(Variable {
+                            name: row.label.to_ascii_lowercase(),
+                            value: format!("0x{:x}", registers.get(row.id)?),
+                            type_name: None,
+                            variables_reference: 0,
+                        })
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Frame ids and variable references die when the target runs
+    fn forget_frames(&mut self)
+    {
+        self.frames.clear();
+        self.scopes.clear();
+    }
+
+    fn respond(&mut self, request_seq: u64, command: String, result: Result<Option<Value>, String>) -> io::Result<()>
+    {
+        let seq = self.next_seq();
+        let (success, message, body) = match result {
+            Ok(body) => (true, None, body),
+            Err(message) => (false, Some(message), None),
+        };
+        write_message(
+            &mut self.out,
+            &Message::Response(Response {
+                seq,
+                request_seq,
+                command,
+                success,
+                message,
+                body,
+            }),
+        )
+    }
+
+    fn emit(&mut self, event: &str, body: Option<Value>) -> io::Result<()>
+    {
+        let seq = self.next_seq();
+        write_message(
+            &mut self.out,
+            &Message::Event(Event {
+                seq,
+                event: event.to_string(),
+                body,
+            }),
+        )
+    }
+
+    fn next_seq(&mut self) -> u64
+    {
+        self.seq += 1;
+        self.seq
+    }
+}
+
+impl<W: Write> Frontend for DapSession<W>
+{
+    type Request = Vec<u8>;
+
+    fn pump(&mut self) -> io::Result<()>
+    {
+        DapSession::pump(self)
+    }
+
+    fn handle(&mut self, body: Vec<u8>) -> io::Result<bool>
+    {
+        self.handle_message(&body)
+    }
+
+    fn input_closed(&mut self)
+    {
+        debug!("DAP input closed, disconnecting");
+        self.controller.disconnect(false);
+    }
+}
+
+/// Parse a request's arguments, failing the request on a mismatch
+fn parse<T: DeserializeOwned>(arguments: Value) -> DebuggerResult<T>
+{
+    serde_json::from_value(arguments).map_err(|err| DebuggerError::InvalidArgument(format!("invalid arguments: {err}")))
+}
+
+/// Where the client can open a symbolicated `file`: the first place it exists, else the path as it is
+fn local_source(file: &str) -> String
+{
+    SourceResolver::in_current_dir(Vec::new())
+        .resolve(file)
+        .map_or_else(|_| file.to_string(), |path| path.display().to_string())
+}
+
+fn source_for(path: &str) -> Source
+{
+    Source {
+        name: Path::new(path).file_name().map(|name| name.to_string_lossy().into_owned()),
+        path: Some(path.to_string()),
+    }
+}
+
+fn breakpoint_body(source: &Source, line: u32, breakpoint: &ClientBreakpoint) -> Breakpoint
+{
+    let verified = breakpoint.verified();
+    Breakpoint {
+        id: breakpoint.id,
+        verified,
+        message: (!verified).then(|| "No code at this line yet".to_string()),
+        source: source.clone(),
+        line,
+        instruction_reference: breakpoint
+            .addresses()
+            .next()
+            .map(|address| format!("0x{:x}", address.value())),
+    }
+}
+
+/// DAP's name for a kind of stop
+const fn stop_reason_name(kind: StopKind) -> &'static str
+{
+    match kind {
+        StopKind::Breakpoint => "breakpoint",
+        StopKind::Step => "step",
+        StopKind::Watchpoint => "data breakpoint",
+        StopKind::Signal | StopKind::StackOverflow => "exception",
+        StopKind::Pause | StopKind::Unknown => "pause",
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::io::{BufReader, Cursor};
+
+    use ferros_core::BreakpointId;
+    use ferros_core::mock::{MAIN_LINE_12, MockDebugger};
+    use ferros_core::types::Address;
+
+    use super::*;
+
+    /// Frame `requests` as DAP messages, feed them to a session through a pipe
+    /// and return every message it wrote, as JSON.
+    fn run_over_pipe(debugger: Box<dyn Debugger>, requests: Vec<Value>) -> Vec<Value>
+    {
+        let (reader, mut writer) = io::pipe().unwrap();
+        for request in requests {
+            write_message(&mut writer, &request).unwrap();
+        }
+        drop(writer);
+
+        let mut out = Vec::new();
+        DapSession::new(debugger, &mut out).run(BufReader::new(reader)).unwrap();
+
+        let mut input = Cursor::new(out);
+        let mut messages = Vec::new();
+        while let Some(body) = read_message(&mut input).unwrap() {
+            messages.push(serde_json::from_slice(&body).expect("adapter wrote invalid JSON"));
+        }
+        messages
+    }
+
+    /// One line per message, e.g. `response 3 next ok` or `event stopped`.
+    fn outline(messages: &[Value]) -> Vec<String>
+    {
+        messages
+            .iter()
+            .map(|message| match message["type"].as_str() {
+                Some("response") => format!(
+                    "response {} {} {}",
+                    message["request_seq"],
+                    message["command"].as_str().unwrap(),
+                    if message["success"] == true { "ok" } else { "error" }
+                ),
+                _ => format!("event {}", message["event"].as_str().unwrap()),
+            })
+            .collect()
+    }
+
+    fn response(messages: &[Value], request_seq: u64) -> &Value
+    {
+        messages
+            .iter()
+            .find(|message| message["type"] == "response" && message["request_seq"] == request_seq)
+            .expect("every request should be answered")
+    }
+
+    fn request(seq: u64, command: &str, arguments: &Value) -> Value
+    {
+        json!({"seq": seq, "type": "request", "command": command, "arguments": arguments})
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn an_editor_session_from_launch_to_exit()
+    {
+        let (debugger, state) = MockDebugger::scripted(1);
+        let messages = run_over_pipe(
+            debugger,
+            vec![
+                request(1, "initialize", &json!({"adapterID": "ferros"})),
+                request(2, "launch", &json!({"program": "/bin/app", "args": ["--fast"]})),
+                request(
+                    3,
+                    "setBreakpoints",
+                    &json!({"source": {"path": "src/main.rs"}, "breakpoints": [{"line": 12}, {"line": 40}]}),
+                ),
+                request(4, "configurationDone", &Value::Null),
+                request(5, "threads", &Value::Null),
+                request(6, "stackTrace", &json!({"threadId": 1})),
+                request(7, "scopes", &json!({"frameId": 1})),
+                request(8, "variables", &json!({"variablesReference": 1})),
+                request(9, "next", &json!({"threadId": 1})),
+                request(10, "evaluate", &json!({"expression": "x"})),
+                request(11, "continue", &json!({"threadId": 1})),
+                request(12, "disconnect", &Value::Null),
+            ],
+        );
+
+        assert_eq!(
+            outline(&messages),
+            vec![
+                "response 1 initialize ok",
+                "event initialized",
+                "response 2 launch ok",
+                "response 3 setBreakpoints ok",
+                "response 4 configurationDone ok",
+                "event continued",
+                "event stopped",
+                "response 5 threads ok",
+                "response 6 stackTrace ok",
+                "response 7 scopes ok",
+                "response 8 variables ok",
+                "response 9 next ok",
+                "event stopped",
+                "response 10 evaluate error",
+                "response 11 continue ok",
+                "event continued",
+                "event exited",
+                "event terminated",
+                "response 12 disconnect ok",
+            ]
+        );
+
+        // Responses and events share one sequence that never skips
+        let seqs: Vec<u64> = messages.iter().map(|message| message["seq"].as_u64().unwrap()).collect();
+        assert_eq!(seqs, (1..=messages.len() as u64).collect::<Vec<_>>());
+
+        assert_eq!(response(&messages, 1)["body"]["supportsConfigurationDoneRequest"], true);
+        let breakpoints = &response(&messages, 3)["body"]["breakpoints"];
+        assert_eq!(breakpoints[0]["verified"], true);
+        assert_eq!(breakpoints[0]["instructionReference"], format!("0x{MAIN_LINE_12:x}"));
+        assert_eq!(breakpoints[1]["verified"], false);
+
+        let stopped = &messages[6]["body"];
+        assert_eq!(stopped["reason"], "breakpoint");
+        assert_eq!(stopped["threadId"], 1);
+        assert_eq!(stopped["hitBreakpointIds"], json!([breakpoints[0]["id"]]));
+
+        assert_eq!(
+            response(&messages, 5)["body"]["threads"],
+            json!([{"id": 1, "name": "Thread 1"}])
+        );
+        let frames = &response(&messages, 6)["body"]["stackFrames"];
+        assert_eq!(frames.as_array().unwrap().len(), 2);
+        assert_eq!(frames[0]["name"], "app::main");
+        assert_eq!(frames[0]["line"], 12);
+        assert_eq!(frames[0]["source"], json!({"name": "main.rs", "path": "src/main.rs"}));
+
+        let scopes = &response(&messages, 7)["body"]["scopes"];
+        assert_eq!(scopes[0]["name"], "Arguments");
+        assert_eq!(scopes[1]["name"], "Registers");
+        let variables = &response(&messages, 8)["body"]["variables"];
+        assert_eq!(variables[0]["name"], "argc");
+        assert_eq!(variables[0]["type"], "isize");
+        assert_eq!(variables[0]["value"], "0x1");
+
+        // The mock's step publishes no event, so the adapter reports the stop itself
+        assert_eq!(messages[12]["body"]["reason"], "step");
+        assert_eq!(messages[12]["body"]["threadId"], 1);
+        assert!(
+            response(&messages, 10)["message"]
+                .as_str()
+                .unwrap()
+                .contains("Unsupported request")
+        );
+        assert_eq!(messages[16]["body"]["exitCode"], 0);
+
+        let state = state.borrow();
+        assert_eq!(state.software_breakpoints(), [Address::from(MAIN_LINE_12)]);
+        assert_eq!(state.steps, 1);
+        // The target had already exited, so there was nothing to detach from
+        assert!(!state.detached);
+    }
+
+    #[test]
+    fn breakpoints_are_replaced_per_file_and_resolved_after_launch()
+    {
+        let (debugger, state) = MockDebugger::scripted(0);
+        let messages = run_over_pipe(
+            debugger,
+            vec![
+                request(
+                    1,
+                    "setBreakpoints",
+                    &json!({"source": {"path": "src/main.rs"}, "breakpoints": [{"line": 7}]}),
+                ),
+                request(
+                    2,
+                    "setBreakpoints",
+                    &json!({"source": {"path": "src/main.rs"}, "breakpoints": [{"line": 12}]}),
+                ),
+                request(3, "launch", &json!({"program": "/bin/app", "stopOnEntry": true})),
+                request(4, "configurationDone", &Value::Null),
+                request(
+                    5,
+                    "setBreakpoints",
+                    &json!({"source": {"path": "src/main.rs"}, "breakpoints": []}),
+                ),
+            ],
+        );
+
+        assert_eq!(
+            outline(&messages),
+            vec![
+                "response 1 setBreakpoints ok",
+                "response 2 setBreakpoints ok",
+                "response 3 launch ok",
+                "event breakpoint",
+                "response 4 configurationDone ok",
+                "event stopped",
+                "response 5 setBreakpoints ok",
+            ]
+        );
+        let resolved = &messages[3]["body"]["breakpoint"];
+        assert_eq!(resolved["id"], 2);
+        assert_eq!(resolved["line"], 12);
+        assert_eq!(resolved["verified"], true);
+        assert_eq!(messages[5]["body"]["reason"], "entry");
+
+        let state = state.borrow();
+        assert!(state.removed.contains(&BreakpointId::from_raw(1)));
+        // End of input detaches from a target that is still there
+        assert!(state.detached);
+    }
+
+    #[test]
+    fn malformed_requests_are_answered_and_the_session_continues()
+    {
+        let (debugger, _state) = MockDebugger::scripted(0);
+        let messages = run_over_pipe(
+            debugger,
+            vec![
+                json!({"seq": 1, "type": "request"}),
+                request(2, "attach", &json!({})),
+                request(3, "scopes", &json!({"frameId": 9})),
+            ],
+        );
+
+        assert_eq!(
+            outline(&messages),
+            vec!["response 1  error", "response 2 attach error", "response 3 scopes error"]
+        );
+        assert!(
+            response(&messages, 2)["message"]
+                .as_str()
+                .unwrap()
+                .contains("invalid arguments")
+        );
+    }
+}
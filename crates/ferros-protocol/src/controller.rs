@@ -0,0 +1,604 @@
+//! # Session Controller
+//!
+//! The half of a frontend session that doesn't depend on the wire format.
+//! `ferros mi` and `ferros dap` both drive a [`SessionController`], which
+//! owns the [`Debugger`] and does what any client session needs:
+//!
+//! - draining debugger events and captured target output as
+//!   [`ControllerEvent`]s, feeding them to a [`SessionRecorder`] if one is
+//!   installed,
+//! - keeping client breakpoints whose location doesn't resolve yet and
+//!   retrying them on every stop, library load and symbol load,
+//! - naming why the target stopped, through [`StopKind`].
+//!
+//! A frontend parses its requests, calls the controller and translates the
+//! events it hands back into its own messages. [`serve`] is the request loop
+//! both frontends run.
+
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+use std::{io, thread};
+
+use ferros_core::breakpoints::{Breakpoint, BreakpointLocation};
+use ferros_core::error::{DebuggerError, Result as DebuggerResult};
+use ferros_core::events::{DebuggerEvent, DebuggerEventReceiver};
+use ferros_core::output::OutputSink;
+use ferros_core::session::{SessionRecorder, SessionSummary};
+use ferros_core::types::{Address, LaunchOptions, ProcessId, StopReason, ThreadId};
+use ferros_core::{BreakpointId, Debugger};
+use tracing::warn;
+
+/// How long [`serve`] waits for a request before checking for events again.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Why the target stopped, as frontends report it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopKind
+{
+    /// A software breakpoint was hit.
+    Breakpoint,
+    /// A step finished.
+    Step,
+    /// A watchpoint fired.
+    Watchpoint,
+    /// A signal or bad memory access.
+    Signal,
+    /// A thread ran off the end of its stack.
+    StackOverflow,
+    /// The target was suspended on request.
+    Pause,
+    /// The backend couldn't say.
+    Unknown,
+}
+
+impl StopKind
+{
+    /// Classify a stop reason.
+    #[must_use]
+    pub const fn of(reason: StopReason) -> Self
+    {
+        match reason {
+            StopReason::Breakpoint(_) => Self::Breakpoint,
+            StopReason::Step(_) => Self::Step,
+            StopReason::Watchpoint { .. } => Self::Watchpoint,
+            StopReason::Signal(_) | StopReason::BadAccess { .. } => Self::Signal,
+            StopReason::StackOverflow { .. } => Self::StackOverflow,
+            StopReason::Suspended => Self::Pause,
+            StopReason::Running | StopReason::Exited(_) | StopReason::Unknown => Self::Unknown,
+        }
+    }
+
+    /// Name used in `ferros mi` events.
+    #[must_use]
+    pub const fn name(self) -> &'static str
+    {
+        match self {
+            Self::Breakpoint => "breakpoint",
+            Self::Step => "step",
+            Self::Watchpoint => "watchpoint",
+            Self::Signal => "signal",
+            Self::StackOverflow => "stack-overflow",
+            Self::Pause => "pause",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Something a frontend reports to its client, in the order it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControllerEvent
+{
+    /// A chunk of the target's captured output.
+    Output
+    {
+        /// `stdout` or `stderr`.
+        category: &'static str,
+        /// The chunk, decoded lossily as UTF-8.
+        output: String,
+    },
+    /// The target resumed.
+    Continued,
+    /// The target stopped.
+    Stopped
+    {
+        /// Why it stopped.
+        kind: StopKind,
+        /// Thread the stop is about: the one that caused it, else the active one.
+        thread: Option<ThreadId>,
+        /// Client breakpoints installed at the stop address.
+        hit_breakpoint_ids: Vec<u64>,
+        /// Human-readable description.
+        description: String,
+    },
+    /// The target exited; the session is no longer attached.
+    Exited
+    {
+        /// Exit code.
+        code: i32,
+    },
+    /// A breakpoint that was unverified is now installed.
+    BreakpointResolved
+    {
+        /// Id from [`SessionController::add_breakpoint`].
+        id: u64,
+    },
+    /// A library was loaded after the first stop.
+    ImageLoaded
+    {
+        /// Path of the image.
+        path: PathBuf,
+        /// Load address.
+        base: Address,
+    },
+    /// A library was unloaded.
+    ImageUnloaded
+    {
+        /// Path of the image.
+        path: PathBuf,
+        /// Load address it had.
+        base: Address,
+    },
+}
+
+/// A client breakpoint and the core breakpoints backing it.
+#[derive(Debug, Clone)]
+pub struct ClientBreakpoint
+{
+    /// Identifier handed to the client; stable for the session.
+    pub id: u64,
+    /// Where the client asked for it.
+    pub location: BreakpointLocation,
+    installed: Vec<(Address, BreakpointId)>,
+}
+
+impl ClientBreakpoint
+{
+    /// Whether the location resolved and the breakpoint is installed.
+    #[must_use]
+    pub fn verified(&self) -> bool
+    {
+        !self.installed.is_empty()
+    }
+
+    /// Installed addresses, empty while unverified.
+    pub fn addresses(&self) -> impl Iterator<Item = Address> + '_
+    {
+        self.installed.iter().map(|(address, _)| *address)
+    }
+}
+
+/// Drives one [`Debugger`] for a protocol frontend.
+///
+/// Nothing is written from here: each call hands back the events the
+/// frontend should report, in order.
+pub struct SessionController
+{
+    debugger: Box<dyn Debugger>,
+    events: Option<DebuggerEventReceiver>,
+    output_tx: Sender<(&'static str, String)>,
+    output_rx: Receiver<(&'static str, String)>,
+    attached: bool,
+    breakpoints: Vec<ClientBreakpoint>,
+    next_breakpoint_id: u64,
+    recorder: Option<SessionRecorder>,
+}
+
+impl SessionController
+{
+    /// Take over `debugger` and its event stream.
+    #[must_use]
+    pub fn new(mut debugger: Box<dyn Debugger>) -> Self
+    {
+        let events = debugger.take_event_receiver();
+        let (output_tx, output_rx) = mpsc::channel();
+        Self {
+            debugger,
+            events,
+            output_tx,
+            output_rx,
+            attached: false,
+            breakpoints: Vec::new(),
+            next_breakpoint_id: 1,
+            recorder: None,
+        }
+    }
+
+    /// Feed debugger events into `recorder` for a `--summary` document.
+    #[must_use]
+    pub fn with_recorder(mut self, recorder: SessionRecorder) -> Self
+    {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// The debugger, for requests the controller has no part in.
+    #[must_use]
+    pub fn debugger(&self) -> &dyn Debugger
+    {
+        self.debugger.as_ref()
+    }
+
+    /// The debugger, for requests the controller has no part in.
+    pub fn debugger_mut(&mut self) -> &mut dyn Debugger
+    {
+        self.debugger.as_mut()
+    }
+
+    /// Whether a target is attached or launched and hasn't exited.
+    #[must_use]
+    pub const fn is_attached(&self) -> bool
+    {
+        self.attached
+    }
+
+    /// Launch `program` suspended, streaming its output as [`ControllerEvent::Output`].
+    ///
+    /// Call [`resolve_pending`](Self::resolve_pending) once the client has
+    /// its answer, for breakpoints set before there was a target.
+    ///
+    /// # Errors
+    ///
+    /// Returns the backend's error if the launch fails.
+    pub fn launch(&mut self, program: &str, options: &LaunchOptions) -> DebuggerResult<ProcessId>
+    {
+        self.debugger.set_capture_process_output(true);
+        let pid = self.debugger.launch_with_options(program, options)?;
+        self.forward_output();
+        self.started(pid);
+        Ok(pid)
+    }
+
+    /// Attach to `pid`, leaving it suspended.
+    ///
+    /// As with [`launch`](Self::launch), pending breakpoints are resolved by
+    /// a later [`resolve_pending`](Self::resolve_pending).
+    ///
+    /// # Errors
+    ///
+    /// Returns the backend's error if the attach fails.
+    pub fn attach(&mut self, pid: ProcessId) -> DebuggerResult<()>
+    {
+        self.debugger.attach(pid)?;
+        self.started(pid);
+        Ok(())
+    }
+
+    /// Common tail of `attach` and `launch`.
+    fn started(&mut self, pid: ProcessId)
+    {
+        self.attached = true;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_started(pid.0);
+        }
+    }
+
+    /// Add a client breakpoint at `location` and return its id.
+    ///
+    /// A location that doesn't resolve yet, or a session without a target, is
+    /// not an error: the breakpoint stays unverified and is retried on every
+    /// stop, library load and symbol load.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the location is invalid or a resolved address
+    /// can't take a breakpoint.
+    pub fn add_breakpoint(&mut self, location: BreakpointLocation) -> DebuggerResult<u64>
+    {
+        let mut breakpoint = ClientBreakpoint {
+            id: self.next_breakpoint_id,
+            location,
+            installed: Vec::new(),
+        };
+        if self.attached {
+            self.install(&mut breakpoint)?;
+        }
+        self.next_breakpoint_id += 1;
+        let id = breakpoint.id;
+        self.breakpoints.push(breakpoint);
+        Ok(id)
+    }
+
+    /// A client breakpoint by id.
+    #[must_use]
+    pub fn breakpoint(&self, id: u64) -> Option<&ClientBreakpoint>
+    {
+        self.breakpoints.iter().find(|breakpoint| breakpoint.id == id)
+    }
+
+    /// Remove a client breakpoint and the core breakpoints backing it.
+    pub fn remove_breakpoint(&mut self, id: u64)
+    {
+        let Some(index) = self.breakpoints.iter().position(|breakpoint| breakpoint.id == id) else {
+            return;
+        };
+        let breakpoint = self.breakpoints.remove(index);
+        for (address, core) in breakpoint.installed {
+            if let Err(err) = self.debugger.remove_breakpoint(core) {
+                warn!("Failed to remove breakpoint at {}: {}", address, err);
+            }
+        }
+    }
+
+    /// Try to resolve and install `breakpoint`.
+    fn install(&mut self, breakpoint: &mut ClientBreakpoint) -> DebuggerResult<()>
+    {
+        let addresses = match self.debugger.resolve_location(&breakpoint.location) {
+            Ok(addresses) => addresses,
+            Err(DebuggerError::NotAttached) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        for address in addresses {
+            let id = self.debugger.add_breakpoint(Breakpoint::software(address).build())?;
+            breakpoint.installed.push((address, id));
+        }
+        Ok(())
+    }
+
+    /// Retry every unverified breakpoint, with an event for each that resolves.
+    pub fn resolve_pending(&mut self) -> Vec<ControllerEvent>
+    {
+        if !self.attached {
+            return Vec::new();
+        }
+        let mut breakpoints = std::mem::take(&mut self.breakpoints);
+        let mut resolved = Vec::new();
+        for breakpoint in breakpoints.iter_mut().filter(|breakpoint| !breakpoint.verified()) {
+            match self.install(breakpoint) {
+                Ok(()) if breakpoint.verified() => resolved.push(ControllerEvent::BreakpointResolved { id: breakpoint.id }),
+                Ok(()) => {}
+                Err(err) => warn!(
+                    "Failed to install breakpoint {} at {}: {}",
+                    breakpoint.id, breakpoint.location, err
+                ),
+            }
+        }
+        self.breakpoints = breakpoints;
+        resolved
+    }
+
+    /// Every queued output chunk and debugger event, as frontend events.
+    pub fn poll(&mut self) -> Vec<ControllerEvent>
+    {
+        let mut events: Vec<ControllerEvent> = self
+            .output_rx
+            .try_iter()
+            .map(|(category, output)| ControllerEvent::Output { category, output })
+            .collect();
+
+        let pending: Vec<DebuggerEvent> = match &self.events {
+            Some(events) => events.try_iter().map(|envelope| envelope.event).collect(),
+            None => Vec::new(),
+        };
+        for event in pending {
+            if let Some(recorder) = &mut self.recorder {
+                recorder.record_event(&event);
+            }
+            match event {
+                DebuggerEvent::TargetResumed => events.push(ControllerEvent::Continued),
+                DebuggerEvent::TargetStopped { reason, thread } => events.extend(self.stopped(reason, thread)),
+                DebuggerEvent::ImageLoaded { path, base } => {
+                    events.push(ControllerEvent::ImageLoaded { path, base });
+                    // Breakpoints waiting on a library may be in this one
+                    events.extend(self.resolve_pending());
+                }
+                DebuggerEvent::ImageUnloaded { path, base } => events.push(ControllerEvent::ImageUnloaded { path, base }),
+                // Breakpoints waiting on symbols may resolve now
+                DebuggerEvent::SymbolsLoaded { .. } => events.extend(self.resolve_pending()),
+            }
+        }
+        events
+    }
+
+    /// Events for a stop, or for the end of the target on [`StopReason::Exited`].
+    ///
+    /// [`poll`](Self::poll) calls this for every stop the backend publishes;
+    /// a frontend calls it for a stop it learned of another way, such as the
+    /// result of a step.
+    pub fn stopped(&mut self, reason: StopReason, thread: Option<ThreadId>) -> Vec<ControllerEvent>
+    {
+        if let StopReason::Exited(code) = reason {
+            self.attached = false;
+            return vec![ControllerEvent::Exited { code }];
+        }
+
+        // Libraries may have been loaded since the last stop
+        let mut events = self.resolve_pending();
+        let hit_breakpoint_ids = match reason {
+            StopReason::Breakpoint(pc) => self.breakpoints_at(Address::from(pc)),
+            _ => Vec::new(),
+        };
+        let thread = thread.or_else(|| self.debugger.active_thread());
+        events.push(ControllerEvent::Stopped {
+            kind: StopKind::of(reason),
+            thread,
+            hit_breakpoint_ids,
+            description: DebuggerEvent::TargetStopped { reason, thread }.describe(),
+        });
+        events
+    }
+
+    fn breakpoints_at(&self, pc: Address) -> Vec<u64>
+    {
+        self.breakpoints
+            .iter()
+            .filter(|breakpoint| breakpoint.addresses().any(|address| address == pc))
+            .map(|breakpoint| breakpoint.id)
+            .collect()
+    }
+
+    /// Stream captured target stdout/stderr into `Output` events.
+    fn forward_output(&mut self)
+    {
+        let stdout = output_sink("stdout", self.output_tx.clone());
+        let stderr = output_sink("stderr", self.output_tx.clone());
+        if let Err(err) = self.debugger.pump_process_output(stdout, stderr) {
+            warn!("Failed to forward process output: {}", err);
+        }
+    }
+
+    /// Let go of the target: kill it if `terminate`, else detach.
+    pub fn disconnect(&mut self, terminate: bool)
+    {
+        if !self.attached {
+            return;
+        }
+        self.attached = false;
+        let result = if terminate {
+            self.debugger.kill()
+        } else {
+            self.debugger.detach()
+        };
+        if let Err(err) = result {
+            warn!("Failed to end the target on disconnect: {}", err);
+        }
+    }
+
+    /// The session summary, if a recorder was installed.
+    #[must_use]
+    pub fn finish(mut self) -> Option<SessionSummary>
+    {
+        let mut recorder = self.recorder.take()?;
+        Some(recorder.finish(Some(self.debugger.as_mut())))
+    }
+}
+
+fn output_sink(category: &'static str, tx: Sender<(&'static str, String)>) -> Box<dyn OutputSink>
+{
+    Box::new(move |bytes: &[u8]| tx.send((category, String::from_utf8_lossy(bytes).into_owned())).is_ok())
+}
+
+/// A protocol frontend run by [`serve`].
+pub trait Frontend
+{
+    /// One request as read from the input.
+    type Request: Send + 'static;
+
+    /// Write every event queued in the controller.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output fails.
+    fn pump(&mut self) -> io::Result<()>;
+
+    /// Handle one request and write its response; `false` once the session is over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output fails.
+    fn handle(&mut self, request: Self::Request) -> io::Result<bool>;
+
+    /// The input ended without a request ending the session.
+    fn input_closed(&mut self);
+}
+
+/// Hand the requests `read` returns to `frontend` until it ends the session or the input runs out.
+///
+/// Input is read on a helper thread so events keep flowing while the client
+/// is idle; the frontend is pumped before waiting for each request and once
+/// more at the end.
+///
+/// # Errors
+///
+/// Returns an error if writing to the output fails.
+pub fn serve<F: Frontend>(frontend: &mut F, mut read: impl FnMut() -> Option<F::Request> + Send + 'static)
+-> io::Result<()>
+{
+    let (request_tx, request_rx) = mpsc::channel();
+    thread::spawn(move || {
+        while let Some(request) = read() {
+            if request_tx.send(request).is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        frontend.pump()?;
+        match request_rx.recv_timeout(POLL_INTERVAL) {
+            Ok(request) => {
+                if !frontend.handle(request)? {
+                    break;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                frontend.input_closed();
+                break;
+            }
+        }
+    }
+
+    frontend.pump()
+}
+
+/// `(seq, command)` of a request that didn't parse, so its failure can be matched
+///
+/// Either is left at `0` or empty if the body doesn't carry it.
+#[must_use]
+pub fn identify(body: &[u8]) -> (u64, String)
+{
+    let raw: Option<serde_json::Value> = serde_json::from_slice(body).ok();
+    let field = |name: &str| raw.as_ref().and_then(|value| value.get(name).cloned());
+    let request_seq = field("seq").and_then(|seq| seq.as_u64()).unwrap_or(0);
+    let command = field("command")
+        .and_then(|command| command.as_str().map(str::to_string))
+        .unwrap_or_default();
+    (request_seq, command)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ferros_core::mock::{MAIN_LINE_12, MockDebugger};
+
+    use super::*;
+
+    #[test]
+    fn breakpoints_wait_for_a_target_and_stops_name_the_ones_hit()
+    {
+        let (debugger, state) = MockDebugger::scripted(1);
+        let mut controller = SessionController::new(debugger);
+        let id = controller
+            .add_breakpoint(BreakpointLocation::Line {
+                file: "src/main.rs".to_string(),
+                line: 12,
+            })
+            .unwrap();
+        assert!(!controller.breakpoint(id).unwrap().verified());
+        // Nothing to install into yet
+        assert!(controller.resolve_pending().is_empty());
+
+        controller.launch("/bin/app", &LaunchOptions::new(["/bin/app"])).unwrap();
+        assert_eq!(controller.resolve_pending(), vec![ControllerEvent::BreakpointResolved { id }]);
+        let addresses: Vec<Address> = controller.breakpoint(id).unwrap().addresses().collect();
+        assert_eq!(addresses, [Address::from(MAIN_LINE_12)]);
+
+        let events = controller.stopped(StopReason::Breakpoint(MAIN_LINE_12), None);
+        let [
+            ControllerEvent::Stopped {
+                kind,
+                thread,
+                hit_breakpoint_ids,
+                ..
+            },
+        ] = &events[..]
+        else {
+            panic!("expected one stopped event, got {events:?}");
+        };
+        assert_eq!(*kind, StopKind::Breakpoint);
+        // Filled in from the active thread when the backend doesn't say
+        assert_eq!(*thread, controller.debugger().active_thread());
+        assert_eq!(hit_breakpoint_ids, &[id]);
+
+        controller.remove_breakpoint(id);
+        assert!(controller.breakpoint(id).is_none());
+        assert_eq!(state.borrow().removed.len(), 1);
+
+        assert_eq!(
+            controller.stopped(StopReason::Exited(3), None),
+            vec![ControllerEvent::Exited { code: 3 }]
+        );
+        assert!(!controller.is_attached());
+    }
+}
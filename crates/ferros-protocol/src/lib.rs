@@ -1 +1,11 @@
+//! # ferros-protocol
+//!
+//! Communication layer between the Ferros debugger and frontends.
+//!
+//! This is the [`mi`] machine interface used by `ferros mi`: a
+//! line-oriented JSON protocol for editors and scripts that would otherwise
+//! have to drive the TUI. The [`controller`] under it is shared with the
+//! Debug Adapter Protocol server in `ferros-dap`.
 
+pub mod controller;
+pub mod mi;
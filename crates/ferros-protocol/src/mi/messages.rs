@@ -0,0 +1,680 @@
+//! Wire types for the machine interface.
+//!
+//! Every type here maps one-to-one onto a JSON object on the wire; see the
+//! [module docs](super) for the framing and examples.
+
+use serde::{Deserialize, Serialize};
+
+/// A command sent by the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Request
+{
+    /// Client-chosen sequence number, echoed back as `request_seq`.
+    pub seq: u64,
+    /// The command and its arguments.
+    #[serde(flatten)]
+    pub command: Command,
+}
+
+/// Commands understood by the machine interface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", content = "arguments", rename_all = "kebab-case")]
+pub enum Command
+{
+    /// Attach to a running process.
+    Attach
+    {
+        /// Process ID to attach to.
+        pid: u32,
+    },
+    /// Launch a program suspended; send `continue` to start it.
+    Launch
+    {
+        /// Path to the executable.
+        program: String,
+        /// Arguments, not including the program name.
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Set a breakpoint, deferring it until the location can be resolved.
+    SetBreakpoint
+    {
+        /// Where to break.
+        location: Location,
+    },
+    /// Resume the target.
+    Continue,
+    /// Step one source line.
+    Step,
+    /// Backtrace of the active thread.
+    StackTrace
+    {
+        /// Maximum number of frames to return.
+        #[serde(default = "default_levels")]
+        levels: usize,
+    },
+    /// Parameters of one frame of the active thread.
+    Variables
+    {
+        /// Frame index as reported by `stack-trace`.
+        #[serde(default)]
+        frame: usize,
+    },
+    /// Read target memory.
+    ReadMemory
+    {
+        /// Start address.
+        #[serde(with = "hex")]
+        address: u64,
+        /// Number of bytes to read.
+        count: usize,
+    },
+    /// Detach from the target and end the session.
+    Disconnect,
+}
+
+fn default_levels() -> usize
+{
+    64
+}
+
+impl Command
+{
+    /// Wire name of the command, as used in the `command` field.
+    #[must_use]
+    pub fn name(&self) -> &'static str
+    {
+        match self {
+            Self::Attach { .. } => "attach",
+            Self::Launch { .. } => "launch",
+            Self::SetBreakpoint { .. } => "set-breakpoint",
+            Self::Continue => "continue",
+            Self::Step => "step",
+            Self::StackTrace { .. } => "stack-trace",
+            Self::Variables { .. } => "variables",
+            Self::ReadMemory { .. } => "read-memory",
+            Self::Disconnect => "disconnect",
+        }
+    }
+}
+
+/// Breakpoint location as written by the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum Location
+{
+    /// `{"file": "src/main.rs", "line": 10}`
+    Line
+    {
+        /// Source path or a suffix of it.
+        file: String,
+        /// 1-based line number.
+        line: u32,
+    },
+    /// `{"function": "worker::run"}`
+    Function
+    {
+        /// Function name, optionally qualified.
+        function: String,
+    },
+    /// `{"address": "0x100003f40"}`
+    Address
+    {
+        /// Runtime address.
+        #[serde(with = "hex")]
+        address: u64,
+    },
+}
+
+/// Anything the server writes to stdout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Message
+{
+    /// Reply to exactly one request.
+    Response(Response),
+    /// Asynchronous notification.
+    Event(Event),
+}
+
+/// Reply to a [`Request`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::large_stack_arrays)]
+pub struct Response
+{
+    /// Server sequence number.
+    pub seq: u64,
+    /// `seq` of the request being answered (0 if it could not be parsed).
+    pub request_seq: u64,
+    /// Wire name of the command being answered.
+    pub command: String,
+    /// Whether the command succeeded.
+    pub success: bool,
+    /// Error description when `success` is false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Command-specific result, if the command returns one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<ResponseBody>,
+}
+
+/// Command-specific response payloads.
+///
+/// Each variant has a distinct set of fields, which is how a client tells them
+/// apart without looking at `command`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum ResponseBody
+{
+    /// `attach` / `launch`
+    Process
+    {
+        /// Target process ID.
+        pid: u32,
+    },
+    /// `set-breakpoint`
+    Breakpoint
+    {
+        /// The new breakpoint.
+        breakpoint: BreakpointBody,
+    },
+    /// `stack-trace`
+    StackTrace
+    {
+        /// Frames, innermost first.
+        frames: Vec<FrameBody>,
+    },
+    /// `variables`
+    Variables
+    {
+        /// Variables visible in the frame.
+        variables: Vec<VariableBody>,
+    },
+    /// `read-memory`
+    Memory
+    {
+        /// Address the data was read from.
+        #[serde(with = "hex")]
+        address: u64,
+        /// Bytes as lowercase hex, two characters per byte.
+        data: String,
+    },
+}
+
+/// A breakpoint as reported to the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BreakpointBody
+{
+    /// Identifier assigned by the server; stable for the session.
+    pub id: u64,
+    /// Whether the location resolved and the breakpoint is installed.
+    pub verified: bool,
+    /// Installed addresses (empty while unverified).
+    #[serde(with = "hex_list")]
+    pub addresses: Vec<u64>,
+}
+
+/// One stack frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(clippy::large_stack_arrays)]
+pub struct FrameBody
+{
+    /// Frame index, 0 being the innermost.
+    pub index: usize,
+    /// Program counter.
+    #[serde(with = "hex")]
+    pub pc: u64,
+    /// Demangled function name, if known.
+    pub function: Option<String>,
+    /// Source file, if known, as a normalized local path.
+    pub file: Option<String>,
+    /// Source file as DWARF recorded it, when that differs from `file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dwarf_file: Option<String>,
+    /// Source line, if known.
+    pub line: Option<u32>,
+}
+
+/// One variable of a frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VariableBody
+{
+    /// Variable name, if DWARF has one.
+    pub name: Option<String>,
+    /// Type name, if known.
+    pub type_name: Option<String>,
+    /// Where the value lives (register, frame offset, ...).
+    pub location: Option<String>,
+}
+
+/// Asynchronous notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event
+{
+    /// Server sequence number.
+    pub seq: u64,
+    /// Event name and payload.
+    #[serde(flatten)]
+    pub body: EventBody,
+}
+
+/// Event payloads, tagged by the `event` field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "body", rename_all = "kebab-case")]
+pub enum EventBody
+{
+    /// The target stopped.
+    Stopped
+    {
+        /// `breakpoint`, `step`, `watchpoint`, `signal`, `stack-overflow`, `pause` or `unknown`.
+        reason: String,
+        /// Thread that caused the stop, if known.
+        thread_id: Option<u64>,
+        /// Breakpoints that were hit (ids from `set-breakpoint`).
+        #[serde(default)]
+        hit_breakpoint_ids: Vec<u64>,
+        /// Human-readable description.
+        description: String,
+    },
+    /// The target resumed.
+    Continued,
+    /// The target exited.
+    Exited
+    {
+        /// Exit status.
+        exit_code: i32,
+    },
+    /// Target output.
+    Output
+    {
+        /// `stdout` or `stderr`.
+        category: String,
+        /// Output text (may be a partial line).
+        output: String,
+    },
+    /// A deferred breakpoint was installed.
+    BreakpointResolved
+    {
+        /// The breakpoint, now verified.
+        breakpoint: BreakpointBody,
+    },
+    /// The target loaded a library after it was first stopped.
+    ImageLoaded
+    {
+        /// Path of the library.
+        path: String,
+        /// Load address.
+        #[serde(with = "hex")]
+        base: u64,
+    },
+    /// The target unloaded a library.
+    ImageUnloaded
+    {
+        /// Path of the library.
+        path: String,
+        /// Address it was loaded at.
+        #[serde(with = "hex")]
+        base: u64,
+    },
+}
+
+/// Addresses are written as `"0x…"` strings: JSON numbers lose precision past
+/// 2^53 in most clients. Decimal strings and plain numbers are accepted on input.
+mod hex
+{
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw
+    {
+        Number(u64),
+        Text(String),
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.serialize_str(&format!("0x{value:x}"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error>
+    {
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(value) => Ok(value),
+            Raw::Text(text) => parse(&text).map_err(D::Error::custom),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<u64, String>
+    {
+        let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(digits) => u64::from_str_radix(digits, 16),
+            None => text.parse(),
+        };
+        parsed.map_err(|err| format!("invalid address {text:?}: {err}"))
+    }
+}
+
+mod hex_list
+{
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct Entry(#[serde(with = "super::hex")] u64);
+
+    pub fn serialize<S: Serializer>(values: &[u64], serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&format!("0x{value:x}"))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error>
+    {
+        let entries = Vec::<Entry>::deserialize(deserializer)?;
+        Ok(entries.into_iter().map(|Entry(value)| value).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn round_trip_request(wire: &Value, expected: &Request)
+    {
+        let parsed: Request = serde_json::from_value(wire.clone()).expect("request should parse");
+        assert_eq!(&parsed, expected);
+        assert_eq!(&serde_json::to_value(&parsed).unwrap(), wire);
+    }
+
+    fn round_trip_message(message: &Message, wire: &Value)
+    {
+        assert_eq!(&serde_json::to_value(message).unwrap(), wire);
+        let parsed: Message = serde_json::from_value(wire.clone()).expect("message should parse");
+        assert_eq!(&parsed, message);
+    }
+
+    fn request(seq: u64, command: Command) -> Request
+    {
+        Request { seq, command }
+    }
+
+    #[test]
+    fn every_command_round_trips()
+    {
+        let cases = vec![
+            (
+                json!({"seq": 1, "command": "attach", "arguments": {"pid": 42}}),
+                Command::Attach { pid: 42 },
+            ),
+            (
+                json!({"seq": 2, "command": "launch", "arguments": {"program": "/bin/app", "args": ["-v"]}}),
+                Command::Launch {
+                    program: "/bin/app".into(),
+                    args: vec!["-v".into()],
+                },
+            ),
+            (
+                json!({"seq": 3, "command": "set-breakpoint", "arguments": {"location": {"file": "src/main.rs", "line": 7}}}),
+                Command::SetBreakpoint {
+                    location: Location::Line {
+                        file: "src/main.rs".into(),
+                        line: 7,
+                    },
+                },
+            ),
+            (
+                json!({"seq": 4, "command": "set-breakpoint", "arguments": {"location": {"function": "worker::run"}}}),
+                Command::SetBreakpoint {
+                    location: Location::Function {
+                        function: "worker::run".into(),
+                    },
+                },
+            ),
+            (
+                json!({"seq": 5, "command": "set-breakpoint", "arguments": {"location": {"address": "0x1000"}}}),
+                Command::SetBreakpoint {
+                    location: Location::Address { address: 0x1000 },
+                },
+            ),
+            (json!({"seq": 6, "command": "continue"}), Command::Continue),
+            (json!({"seq": 7, "command": "step"}), Command::Step),
+            (
+                json!({"seq": 8, "command": "stack-trace", "arguments": {"levels": 3}}),
+                Command::StackTrace { levels: 3 },
+            ),
+            (
+                json!({"seq": 9, "command": "variables", "arguments": {"frame": 1}}),
+                Command::Variables { frame: 1 },
+            ),
+            (
+                json!({"seq": 10, "command": "read-memory", "arguments": {"address": "0xffff800000001000", "count": 16}}),
+                Command::ReadMemory {
+                    address: 0xffff_8000_0000_1000,
+                    count: 16,
+                },
+            ),
+            (json!({"seq": 11, "command": "disconnect"}), Command::Disconnect),
+        ];
+
+        for (wire, command) in cases {
+            let seq = wire["seq"].as_u64().unwrap();
+            assert_eq!(wire["command"], command.name());
+            round_trip_request(&wire, &request(seq, command));
+        }
+    }
+
+    #[test]
+    fn optional_arguments_take_defaults()
+    {
+        let parsed: Request = serde_json::from_str(r#"{"seq":1,"command":"launch","arguments":{"program":"a"}}"#).unwrap();
+        assert_eq!(
+            parsed.command,
+            Command::Launch {
+                program: "a".into(),
+                args: Vec::new()
+            }
+        );
+
+        let parsed: Request = serde_json::from_str(r#"{"seq":2,"command":"stack-trace","arguments":{}}"#).unwrap();
+        assert_eq!(parsed.command, Command::StackTrace { levels: 64 });
+
+        let parsed: Request = serde_json::from_str(r#"{"seq":3,"command":"variables","arguments":{}}"#).unwrap();
+        assert_eq!(parsed.command, Command::Variables { frame: 0 });
+    }
+
+    #[test]
+    fn addresses_accept_numbers_and_decimal_strings()
+    {
+        for address in r#"4096 "4096" "0x1000" "0X1000""#.split(' ') {
+            let wire: Value = serde_json::from_str(&format!(
+                r#"{{"seq":1,"command":"read-memory","arguments":{{"address":{address},"count":1}}}}"#
+            ))
+            .unwrap();
+            let parsed: Request = serde_json::from_value(wire).unwrap();
+            assert_eq!(
+                parsed.command,
+                Command::ReadMemory {
+                    address: 0x1000,
+                    count: 1
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_requests_are_rejected()
+    {
+        let lines = r#"
+{"seq":1,"command":"frobnicate"}
+{"seq":1,"command":"attach"}
+{"seq":1,"command":"attach","arguments":{"pid":"x"}}
+{"command":"continue"}
+{"seq":1,"command":"set-breakpoint","arguments":{"location":{"file":"a.rs"}}}
+{"seq":1,"command":"set-breakpoint","arguments":{"location":{"function":"f","line":3}}}
+{"seq":1,"command":"read-memory","arguments":{"address":"0xzz","count":1}}
+"#;
+        for line in lines.lines().filter(|line| !line.is_empty()) {
+            assert!(serde_json::from_str::<Request>(line).is_err(), "{line} should not parse");
+        }
+    }
+
+    #[test]
+    fn every_response_body_round_trips()
+    {
+        let breakpoint = BreakpointBody {
+            id: 1,
+            verified: true,
+            addresses: vec![0x1000, 0x2000],
+        };
+        let cases = vec![
+            (ResponseBody::Process { pid: 7 }, json!({"pid": 7})),
+            (
+                ResponseBody::Breakpoint {
+                    breakpoint: breakpoint.clone(),
+                },
+                json!({"breakpoint": {"id": 1, "verified": true, "addresses": ["0x1000", "0x2000"]}}),
+            ),
+            (
+                ResponseBody::StackTrace {
+                    frames: vec![FrameBody {
+                        index: 0,
+                        pc: 0x1004,
+                        function: Some("app::main".into()),
+                        file: Some("src/main.rs".into()),
+                        dwarf_file: None,
+                        line: Some(3),
+                    }],
+                },
+                json!({"frames": [{"index": 0, "pc": "0x1004", "function": "app::main", "file": "src/main.rs", "line": 3}]}),
+            ),
+            (
+                ResponseBody::Variables {
+                    variables: vec![VariableBody {
+                        name: Some("n".into()),
+                        type_name: Some("u32".into()),
+                        location: None,
+                    }],
+                },
+                json!({"variables": [{"name": "n", "type_name": "u32", "location": null}]}),
+            ),
+            (
+                ResponseBody::Memory {
+                    address: 0x1000,
+                    data: "00ff".into(),
+                },
+                json!({"address": "0x1000", "data": "00ff"}),
+            ),
+        ];
+
+        for (index, (body, body_wire)) in cases.into_iter().enumerate() {
+            let seq = index as u64 + 1;
+            let message = Message::Response(Response {
+                seq,
+                request_seq: seq,
+                command: "any".into(),
+                success: true,
+                message: None,
+                body: Some(body),
+            });
+            let wire = json!({
+                "type": "response", "seq": seq, "request_seq": seq, "command": "any", "success": true, "body": body_wire
+            });
+            round_trip_message(&message, &wire);
+        }
+    }
+
+    #[test]
+    fn error_response_omits_body()
+    {
+        let message = Message::Response(Response {
+            seq: 3,
+            request_seq: 9,
+            command: "step".into(),
+            success: false,
+            message: Some("not supported".into()),
+            body: None,
+        });
+        round_trip_message(
+            &message,
+            &json!({"type": "response", "seq": 3, "request_seq": 9, "command": "step", "success": false, "message": "not supported"}),
+        );
+    }
+
+    #[test]
+    fn every_event_round_trips()
+    {
+        let cases = vec![
+            (
+                EventBody::Stopped {
+                    reason: "breakpoint".into(),
+                    thread_id: Some(5),
+                    hit_breakpoint_ids: vec![2],
+                    description: "Hit breakpoint at 0x1000 (thread 5)".into(),
+                },
+                json!({"reason": "breakpoint", "thread_id": 5, "hit_breakpoint_ids": [2], "description": "Hit breakpoint at 0x1000 (thread 5)"}),
+            ),
+            (EventBody::Continued, Value::Null),
+            (EventBody::Exited { exit_code: 3 }, json!({"exit_code": 3})),
+            (
+                EventBody::Output {
+                    category: "stdout".into(),
+                    output: "hi\n".into(),
+                },
+                json!({"category": "stdout", "output": "hi\n"}),
+            ),
+            (
+                EventBody::BreakpointResolved {
+                    breakpoint: BreakpointBody {
+                        id: 4,
+                        verified: true,
+                        addresses: vec![0x2a],
+                    },
+                },
+                json!({"breakpoint": {"id": 4, "verified": true, "addresses": ["0x2a"]}}),
+            ),
+            (
+                EventBody::ImageLoaded {
+                    path: "/usr/lib/libz.dylib".into(),
+                    base: 0x1_9000_0000,
+                },
+                json!({"path": "/usr/lib/libz.dylib", "base": "0x190000000"}),
+            ),
+            (
+                EventBody::ImageUnloaded {
+                    path: "/usr/lib/libz.dylib".into(),
+                    base: 0x1_9000_0000,
+                },
+                json!({"path": "/usr/lib/libz.dylib", "base": "0x190000000"}),
+            ),
+        ];
+
+        for (seq, (body, body_wire)) in (1_u64..).zip(cases) {
+            let name = match &body {
+                EventBody::Stopped { .. } => "stopped",
+                EventBody::Continued => "continued",
+                EventBody::Exited { .. } => "exited",
+                EventBody::Output { .. } => "output",
+                EventBody::BreakpointResolved { .. } => "breakpoint-resolved",
+                EventBody::ImageLoaded { .. } => "image-loaded",
+                EventBody::ImageUnloaded { .. } => "image-unloaded",
+            };
+            let mut wire = json!({"type": "event", "seq": seq, "event": name});
+            if !body_wire.is_null() {
+                wire["body"] = body_wire;
+            }
+            round_trip_message(&Message::Event(Event { seq, body }), &wire);
+        }
+    }
+}
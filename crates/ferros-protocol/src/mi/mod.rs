@@ -0,0 +1,44 @@
+//! # Machine Interface
+//!
+//! `ferros mi` reads one JSON request per line on stdin and writes one JSON
+//! message per line on stdout. The shape follows the Debug Adapter Protocol
+//! closely enough that DAP clients can be adapted with little more than a
+//! field rename:
+//!
+//! ```text
+//! → {"seq":1,"command":"launch","arguments":{"program":"./target/debug/app"}}
+//! ← {"type":"response","seq":1,"request_seq":1,"command":"launch","success":true,"body":{"pid":4242}}
+//! → {"seq":2,"command":"set-breakpoint","arguments":{"location":{"file":"src/main.rs","line":12}}}
+//! ← {"type":"response","seq":2,"request_seq":2,"command":"set-breakpoint","success":true,"body":{"breakpoint":{"id":1,"verified":true,"addresses":["0x100003f40"]}}}
+//! → {"seq":3,"command":"continue"}
+//! ← {"type":"response","seq":3,"request_seq":3,"command":"continue","success":true}
+//! ← {"type":"event","seq":4,"event":"continued"}
+//! ← {"type":"event","seq":5,"event":"stopped","body":{"reason":"breakpoint","thread_id":259,"hit_breakpoint_ids":[1],"description":"..."}}
+//! ```
+//!
+//! ## Rules
+//!
+//! - Every request gets exactly one response, in request order. Failures are
+//!   `"success": false` with a `message`; a line that isn't a valid request is
+//!   answered with `request_seq` 0 unless its `seq` could be read.
+//! - Commands that take arguments need an `arguments` object, even if every
+//!   field in it has a default (`{"command":"stack-trace","arguments":{}}`).
+//! - Server messages carry their own increasing `seq`, shared by responses and
+//!   events, so the stream can be ordered without timestamps.
+//! - `launch` leaves the target suspended; send `continue` to run it.
+//! - Breakpoints that can't be resolved yet are answered with
+//!   `"verified": false` and retried whenever the target stops; a
+//!   `breakpoint-resolved` event announces them once installed.
+//! - Libraries loaded or unloaded after the first stop are announced with
+//!   `image-loaded` and `image-unloaded` events.
+//! - Addresses are `"0x…"` strings.
+//! - Nothing but protocol messages is written to stdout; logs go to the log
+//!   file as in TUI mode.
+
+mod messages;
+mod session;
+
+pub use messages::{
+    BreakpointBody, Command, Event, EventBody, FrameBody, Location, Message, Request, Response, ResponseBody, VariableBody,
+};
+pub use session::MiSession;
@@ -0,0 +1,572 @@
+//! Drives a [`Debugger`] from machine-interface requests.
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+
+use ferros_core::Debugger;
+use ferros_core::breakpoints::BreakpointLocation;
+use ferros_core::error::{DebuggerError, Result as DebuggerResult};
+use ferros_core::session::{SessionRecorder, SessionSummary};
+use ferros_core::types::{Address, LaunchOptions, ProcessId};
+use tracing::debug;
+
+use super::messages::{
+    BreakpointBody, Command, Event, EventBody, FrameBody, Location, Message, Request, Response, ResponseBody, VariableBody,
+};
+use crate::controller::{self, ClientBreakpoint, ControllerEvent, Frontend, SessionController};
+
+/// One machine-interface session over a single debugger.
+///
+/// Requests are handled strictly in order. Debugger events and target output
+/// are flushed before each request is handled and while waiting for input, so
+/// a response never overtakes an event that was already queued when the
+/// request arrived.
+pub struct MiSession<W: Write>
+{
+    controller: SessionController,
+    out: W,
+    seq: u64,
+}
+
+impl<W: Write> MiSession<W>
+{
+    /// Create a session that writes messages to `out`.
+    pub fn new(debugger: Box<dyn Debugger>, out: W) -> Self
+    {
+        Self {
+            controller: SessionController::new(debugger),
+            out,
+            seq: 0,
+        }
+    }
+
+    /// Feed debugger events into `recorder` for a `--summary` document.
+    #[must_use]
+    pub fn with_recorder(mut self, recorder: SessionRecorder) -> Self
+    {
+        self.controller = self.controller.with_recorder(recorder);
+        self
+    }
+
+    /// Read requests from `input` until `disconnect` or end of input.
+    ///
+    /// Input is read on a helper thread so events keep flowing while the client
+    /// is idle. On end of input the target is detached as if `disconnect` had
+    /// been sent.
+    ///
+    /// Returns the session summary if a recorder was installed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output fails.
+    pub fn run<R: BufRead + Send + 'static>(mut self, input: R) -> io::Result<Option<SessionSummary>>
+    {
+        let mut lines = input.lines();
+        controller::serve(&mut self, move || lines.next()?.ok())?;
+        Ok(self.controller.finish())
+    }
+
+    /// Handle one request line and write its response.
+    ///
+    /// Returns `false` once the session is over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output fails.
+    pub fn handle_line(&mut self, line: &str) -> io::Result<bool>
+    {
+        let request = match serde_json::from_str::<Request>(line) {
+            Ok(request) => request,
+            Err(err) => {
+                let (request_seq, command) = controller::identify(line.as_bytes());
+                self.respond(request_seq, command, Err(format!("invalid request: {err}")))?;
+                return Ok(true);
+            }
+        };
+
+        self.pump()?;
+        let name = request.command.name();
+        let keep_going = !matches!(request.command, Command::Disconnect);
+        let starts_target = matches!(request.command, Command::Attach { .. } | Command::Launch { .. });
+        let result = self.execute(request.command).map_err(|err| err.to_string());
+        let succeeded = result.is_ok();
+        self.respond(request.seq, name.to_string(), result)?;
+        if starts_target && succeeded {
+            // Breakpoints set before the target existed can resolve now
+            let events = self.controller.resolve_pending();
+            self.report(events)?;
+        }
+        self.pump()?;
+        Ok(keep_going)
+    }
+
+    /// Emit every queued debugger event and output chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output fails.
+    pub fn pump(&mut self) -> io::Result<()>
+    {
+        let events = self.controller.poll();
+        self.report(events)
+    }
+
+    /// Emit each controller event as its MI event.
+    fn report(&mut self, events: Vec<ControllerEvent>) -> io::Result<()>
+    {
+        for event in events {
+            let body = match event {
+                ControllerEvent::Output { category, output } => EventBody::Output {
+                    category: category.to_string(),
+                    output,
+                },
+                ControllerEvent::Continued => EventBody::Continued,
+                ControllerEvent::Stopped {
+                    kind,
+                    thread,
+                    hit_breakpoint_ids,
+                    description,
+                } => EventBody::Stopped {
+                    reason: kind.name().to_string(),
+                    thread_id: thread.map(|thread| thread.raw()),
+                    hit_breakpoint_ids,
+                    description,
+                },
+                ControllerEvent::Exited { code } => EventBody::Exited { exit_code: code },
+                ControllerEvent::BreakpointResolved { id } => {
+                    let Some(breakpoint) = self.controller.breakpoint(id) else {
+                        continue;
+                    };
+                    EventBody::BreakpointResolved {
+                        breakpoint: breakpoint_body(breakpoint),
+                    }
+                }
+                ControllerEvent::ImageLoaded { path, base } => EventBody::ImageLoaded {
+                    path: path.display().to_string(),
+                    base: base.value(),
+                },
+                ControllerEvent::ImageUnloaded { path, base } => EventBody::ImageUnloaded {
+                    path: path.display().to_string(),
+                    base: base.value(),
+                },
+            };
+            self.emit(body)?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, command: Command) -> DebuggerResult<Option<ResponseBody>>
+    {
+        match command {
+            Command::Attach { pid } => {
+                self.controller.attach(ProcessId::from(pid))?;
+                Ok(Some(ResponseBody::Process { pid }))
+            }
+            Command::Launch { program, args } => {
+                let mut argv = vec![program.clone()];
+                argv.extend(args);
+                let pid = self.controller.launch(&program, &LaunchOptions::new(argv))?;
+                Ok(Some(ResponseBody::Process { pid: pid.0 }))
+            }
+            Command::SetBreakpoint { location } => {
+                let id = self.controller.add_breakpoint(to_core_location(location))?;
+                let breakpoint = self.controller.breakpoint(id).map(breakpoint_body);
+                Ok(breakpoint.map(|breakpoint| ResponseBody::Breakpoint { breakpoint }))
+            }
+            Command::Continue => {
+                self.controller.debugger_mut().resume()?;
+                Ok(None)
+            }
+            Command::Step => Err(DebuggerError::InvalidArgument(
+                "Stepping is not supported on this debugger".to_string(),
+            )),
+            Command::StackTrace { levels } => {
+                let frames = self.controller.debugger_mut().stack_trace(levels)?;
+                let frames = frames
+                    .iter()
+                    .map(|frame| FrameBody {
+                        index: frame.index,
+                        pc: frame.pc.value(),
+                        function: frame.symbol.as_ref().map(|symbol| symbol.display_name().to_string()),
+                        file: frame.location.as_ref().map(|location| location.file.clone()),
+                        dwarf_file: frame
+                            .location
+                            .as_ref()
+                            .and_then(|location| location.original_file().map(str::to_string)),
+                        line: frame.location.as_ref().and_then(|location| location.line),
+                    })
+                    .collect();
+                Ok(Some(ResponseBody::StackTrace { frames }))
+            }
+            Command::Variables { frame } => {
+                let frames = self.controller.debugger_mut().stack_trace(frame.saturating_add(1))?;
+                let frame = frames
+                    .into_iter()
+                    .find(|candidate| candidate.index == frame)
+                    .ok_or_else(|| DebuggerError::InvalidArgument(format!("No frame {frame} on the active thread")))?;
+                let variables = frame
+                    .parameters
+                    .into_iter()
+                    .map(|parameter| VariableBody {
+                        name: parameter.name,
+                        type_name: parameter.type_name,
+                        location: parameter.location,
+                    })
+                    .collect();
+                Ok(Some(ResponseBody::Variables { variables }))
+            }
+            Command::ReadMemory { address, count } => {
+                let bytes = self.controller.debugger_mut().read_memory(Address::from(address), count)?;
+                let data = bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut data, byte| {
+                    let _ = write!(data, "{byte:02x}");
+                    data
+                });
+                Ok(Some(ResponseBody::Memory { address, data }))
+            }
+            Command::Disconnect => {
+                self.controller.disconnect(false);
+                Ok(None)
+            }
+        }
+    }
+
+    fn respond(&mut self, request_seq: u64, command: String, result: Result<Option<ResponseBody>, String>)
+    -> io::Result<()>
+    {
+        let seq = self.next_seq();
+        let (success, message, body) = match result {
+            Ok(body) => (true, None, body),
+            Err(message) => (false, Some(message), None),
+        };
+        self.write(&Message::Response(Response {
+            seq,
+            request_seq,
+            command,
+            success,
+            message,
+            body,
+        }))
+    }
+
+    fn emit(&mut self, body: EventBody) -> io::Result<()>
+    {
+        let seq = self.next_seq();
+        self.write(&Message::Event(Event { seq, body }))
+    }
+
+    fn next_seq(&mut self) -> u64
+    {
+        self.seq += 1;
+        self.seq
+    }
+
+    fn write(&mut self, message: &Message) -> io::Result<()>
+    {
+        serde_json::to_writer(&mut self.out, message)?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Frontend for MiSession<W>
+{
+    type Request = String;
+
+    fn pump(&mut self) -> io::Result<()>
+    {
+        MiSession::pump(self)
+    }
+
+    fn handle(&mut self, line: String) -> io::Result<bool>
+    {
+        if line.trim().is_empty() {
+            return Ok(true);
+        }
+        self.handle_line(&line)
+    }
+
+    fn input_closed(&mut self)
+    {
+        debug!("MI input closed, disconnecting");
+        self.controller.disconnect(false);
+    }
+}
+
+fn to_core_location(location: Location) -> BreakpointLocation
+{
+    match location {
+        Location::Line { file, line } => BreakpointLocation::Line { file, line },
+        Location::Function { function } => BreakpointLocation::Function(function),
+        Location::Address { address } => BreakpointLocation::Address(Address::from(address)),
+    }
+}
+
+fn breakpoint_body(breakpoint: &ClientBreakpoint) -> BreakpointBody
+{
+    BreakpointBody {
+        id: breakpoint.id,
+        verified: breakpoint.verified(),
+        addresses: breakpoint.addresses().map(Address::value).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::io::Cursor;
+
+    use ferros_core::mock::{LATE_INIT, MAIN_LINE_12, MockDebugger};
+    use ferros_core::session::{SessionInvocation, SessionOutcome};
+
+    use super::*;
+
+    /// Run the request lines in `script` through a session and return every message it wrote.
+    fn run_script(
+        debugger: Box<dyn Debugger>,
+        script: &str,
+        recorder: Option<SessionRecorder>,
+    ) -> (Vec<Message>, Option<SessionSummary>)
+    {
+        let mut out = Vec::new();
+        let mut session = MiSession::new(debugger, &mut out);
+        if let Some(recorder) = recorder {
+            session = session.with_recorder(recorder);
+        }
+        let summary = session.run(Cursor::new(script.to_string())).unwrap();
+        let messages = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("server wrote an invalid message"))
+            .collect();
+        (messages, summary)
+    }
+
+    /// One line per message, e.g. `response 3 continue ok` or `event stopped`.
+    fn outline(messages: &[Message]) -> Vec<String>
+    {
+        messages
+            .iter()
+            .map(|message| match message {
+                Message::Response(response) => format!(
+                    "response {} {} {}",
+                    response.request_seq,
+                    response.command,
+                    if response.success { "ok" } else { "error" }
+                ),
+                Message::Event(event) => {
+                    let value = serde_json::to_value(event).unwrap();
+                    format!("event {}", value["event"].as_str().unwrap())
+                }
+            })
+            .collect()
+    }
+
+    fn response(messages: &[Message], request_seq: u64) -> &Response
+    {
+        messages
+            .iter()
+            .find_map(|message| match message {
+                Message::Response(response) if response.request_seq == request_seq => Some(response),
+                _ => None,
+            })
+            .expect("every request should be answered")
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn launch_break_backtrace_disconnect()
+    {
+        let (debugger, state) = MockDebugger::scripted(1);
+        state.borrow_mut().memory = (0x10..0x14u8).map(|byte| (u64::from(byte), byte)).collect();
+        let (messages, _) = run_script(
+            debugger,
+            r#"
+{"seq":1,"command":"set-breakpoint","arguments":{"location":{"function":"late_init"}}}
+{"seq":2,"command":"launch","arguments":{"program":"/bin/app","args":["--fast"]}}
+{"seq":3,"command":"set-breakpoint","arguments":{"location":{"file":"src/main.rs","line":12}}}
+{"seq":4,"command":"continue"}
+{"seq":5,"command":"stack-trace","arguments":{"levels":8}}
+{"seq":6,"command":"variables","arguments":{"frame":0}}
+{"seq":7,"command":"read-memory","arguments":{"address":"0x10","count":4}}
+{"seq":8,"command":"step"}
+{"seq":9,"command":"disconnect"}
+"#,
+            None,
+        );
+
+        assert_eq!(
+            outline(&messages),
+            vec![
+                "response 1 set-breakpoint ok",
+                "response 2 launch ok",
+                "response 3 set-breakpoint ok",
+                "response 4 continue ok",
+                "event continued",
+                "event breakpoint-resolved",
+                "event stopped",
+                "response 5 stack-trace ok",
+                "response 6 variables ok",
+                "response 7 read-memory ok",
+                "response 8 step error",
+                "response 9 disconnect ok",
+            ]
+        );
+
+        // Server sequence numbers are shared by responses and events and never skip
+        let seqs: Vec<u64> = messages
+            .iter()
+            .map(|message| match message {
+                Message::Response(response) => response.seq,
+                Message::Event(event) => event.seq,
+            })
+            .collect();
+        assert_eq!(seqs, (1..=messages.len() as u64).collect::<Vec<_>>());
+
+        assert_eq!(
+            response(&messages, 1).body,
+            Some(ResponseBody::Breakpoint {
+                breakpoint: BreakpointBody {
+                    id: 1,
+                    verified: false,
+                    addresses: Vec::new()
+                }
+            })
+        );
+        assert_eq!(response(&messages, 2).body, Some(ResponseBody::Process { pid: 1001 }));
+        assert_eq!(
+            response(&messages, 3).body,
+            Some(ResponseBody::Breakpoint {
+                breakpoint: BreakpointBody {
+                    id: 2,
+                    verified: true,
+                    addresses: vec![MAIN_LINE_12]
+                }
+            })
+        );
+
+        let events: Vec<&EventBody> = messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::Event(event) => Some(&event.body),
+                Message::Response(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            events[1],
+            &EventBody::BreakpointResolved {
+                breakpoint: BreakpointBody {
+                    id: 1,
+                    verified: true,
+                    addresses: vec![LATE_INIT]
+                }
+            }
+        );
+        let EventBody::Stopped {
+            reason,
+            thread_id,
+            hit_breakpoint_ids,
+            ..
+        } = events[2]
+        else {
+            panic!("expected a stopped event, got {:?}", events[2]);
+        };
+        assert_eq!(reason, "breakpoint");
+        assert_eq!(*thread_id, Some(1));
+        assert_eq!(hit_breakpoint_ids, &[2]);
+
+        let Some(ResponseBody::StackTrace { frames }) = &response(&messages, 5).body else {
+            panic!("stack-trace should return frames");
+        };
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].pc, MAIN_LINE_12);
+        assert_eq!(frames[0].function.as_deref(), Some("app::main"));
+        assert_eq!(frames[0].line, Some(12));
+
+        let Some(ResponseBody::Variables { variables }) = &response(&messages, 6).body else {
+            panic!("variables should return a list");
+        };
+        assert_eq!(variables[0].name.as_deref(), Some("argc"));
+
+        assert_eq!(
+            response(&messages, 7).body,
+            Some(ResponseBody::Memory {
+                address: 0x10,
+                data: "10111213".into()
+            })
+        );
+        assert!(response(&messages, 8).message.as_deref().unwrap().contains("not supported"));
+
+        let state = state.borrow();
+        assert_eq!(
+            state.software_breakpoints(),
+            [Address::from(MAIN_LINE_12), Address::from(LATE_INIT)]
+        );
+        assert!(state.detached);
+    }
+
+    #[test]
+    fn exit_ends_in_exited_event_and_summary()
+    {
+        let (debugger, state) = MockDebugger::scripted(0);
+        let recorder = SessionRecorder::new(SessionInvocation::launch("/bin/app", Vec::new()));
+        let (messages, summary) = run_script(
+            debugger,
+            r#"
+{"seq":1,"command":"launch","arguments":{"program":"/bin/app"}}
+{"seq":2,"command":"continue"}
+"#,
+            Some(recorder),
+        );
+
+        assert_eq!(
+            outline(&messages),
+            vec![
+                "response 1 launch ok",
+                "response 2 continue ok",
+                "event continued",
+                "event exited"
+            ]
+        );
+        assert!(matches!(
+            messages.last(),
+            Some(Message::Event(Event {
+                body: EventBody::Exited { exit_code: 0 },
+                ..
+            }))
+        ));
+        // The target is gone, so end of input must not try to detach from it
+        assert!(!state.borrow().detached);
+
+        let summary = summary.expect("a recorder was installed");
+        assert_eq!(summary.invocation.pid, Some(1001));
+        assert_eq!(summary.outcome, SessionOutcome::Exited { code: 0 });
+    }
+
+    #[test]
+    fn invalid_lines_are_answered_and_session_continues()
+    {
+        let (debugger, _state) = MockDebugger::scripted(0);
+        let (messages, _) = run_script(
+            debugger,
+            r#"
+not json
+{"seq":4,"command":"attach"}
+
+{"seq":5,"command":"stack-trace","arguments":{}}
+"#,
+            None,
+        );
+
+        assert_eq!(
+            outline(&messages),
+            vec!["response 0  error", "response 4 attach error", "response 5 stack-trace ok"]
+        );
+        assert!(
+            response(&messages, 4)
+                .message
+                .as_deref()
+                .unwrap()
+                .starts_with("invalid request")
+        );
+    }
+}
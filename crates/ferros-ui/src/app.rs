@@ -1,19 +1,72 @@
 //! Application state and logic
 
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::fmt::Write;
+use std::path::{Path, PathBuf};
 
-use ferros_core::events::{DebuggerEvent, format_stop_reason};
-use ferros_core::types::{Address, FrameId, SourceLocation, StackFrame, StopReason, ThreadId};
-use ferros_core::{BreakpointId, BreakpointInfo, Debugger};
+use ferros_core::assertions::{AssertionSet, AssertionStop, ViolationAction};
+use ferros_core::breakpoints::{BreakpointAction, BreakpointFile, UnresolvedBreakpoint};
+use ferros_core::checkpoints::{CheckpointHistory, CheckpointOptions};
+use ferros_core::crash::{self, CrashSite, FaultRegion, ProbableOverflow, crash_frame};
+use ferros_core::eval::{Expression, Value};
+use ferros_core::events::{DebuggerEvent, EventEnvelope, LatencyWindow, format_stop_reason};
+use ferros_core::heartbeat::{DEFAULT_HANG_SAMPLES, HangEvent, HangProbe};
+use ferros_core::inspector::LiveInspector;
+use ferros_core::notes::{ImageAnchor, NOTE_MARKER, Note, NoteRegistry, NoteTarget};
+use ferros_core::operations::{OperationLog, OperationLogOptions};
+use ferros_core::panics::{PanicBreakpoints, PanicReport};
+use ferros_core::permissions::PermissionAdvice;
+use ferros_core::pins::{PinRegistry, PinType};
+use ferros_core::range_checkpoints::{self, RangeDiff, RangeSet, RangeSnapshot, WatchedRange};
+use ferros_core::search::MemorySearchScope;
+use ferros_core::session::{SessionFile, SessionRecorder};
+use ferros_core::signals::{SignalPolicy, parse_signal, signal_name};
+use ferros_core::symbols::paths::SourceResolver;
+use ferros_core::symbols::render::{RenderBudget, render_value};
+use ferros_core::symbols::unwind::find_diagnostic;
+use ferros_core::symbols::{DebugLevel, ImageId, image_uuid};
+use ferros_core::transcript::{Transcript, TranscriptStream};
+use ferros_core::types::{
+    Address, AddressFormat, BadAccessKind, FrameId, FrameStatus, LaunchOptions, RegisterId, RegisterRow, Registers,
+    SourceLocation, StackFrame, StopPolicy, StopReason, ThreadId, ThreadInfo, parse_address, parse_payload,
+    register_by_name, register_display_order,
+};
+use ferros_core::{
+    Breakpoint, BreakpointId, BreakpointInfo, BreakpointLocation, BreakpointRequest, BreakpointRequestKind, Debugger,
+    DebuggerError, RecursionCycle, UnwindDiagnostic, UnwindOptions,
+};
+use ferros_utils::config::Config;
 use ratatui::widgets::TableState;
 
+use crate::commands::{self, BreakFlags, Step, build_breakpoint};
+use crate::config::{self, Keymap, UserConfig};
+use crate::disassembly::DisassemblyView;
+use crate::emphasis::{self, StopEmphasis};
+use crate::hexdump::{AddressHistory, MemoryView};
+use crate::highlight::SourceFile;
+use crate::picker::ProcessPicker;
+use crate::registers::RegisterSnapshot;
+use crate::text;
+use crate::timeline::{self, TimelineView};
+
 /// Maximum number of process output lines retained in memory.
 const MAX_PROCESS_OUTPUT_LINES: usize = 4096;
 /// Maximum number of debugger stop events retained.
 const MAX_STOP_EVENTS: usize = 128;
 /// Maximum number of timeline log entries retained.
 const MAX_TIMELINE_ENTRIES: usize = 256;
+/// How often pins are re-read while the target runs (`:pin live`).
+const PIN_LIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// Runs per range written to the timeline after `:checkpoint run`; the popup shows them all.
+const MAX_TIMELINE_DIFF_RUNS: usize = 8;
+/// Number of debugger event latency samples kept for `:perf`.
+const EVENT_LATENCY_SAMPLES: usize = 256;
+/// Failed breakpoint installs remembered for the resume prompt.
+const MAX_BREAKPOINT_FAILURES: usize = 8;
+/// Stops kept for the History view and `:back` before the oldest is dropped.
+const MAX_HISTORY_STOPS: usize = 256;
+/// Frames unwound for a stop on a thread other than the active one.
+const STOP_RECORD_FRAMES: usize = 16;
 
 /// Indicates which stream produced a captured line.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,6 +74,8 @@ pub enum ProcessOutputSource
 {
     Stdout,
     Stderr,
+    /// A line typed into the Output view and sent to the target
+    Stdin,
 }
 
 /// Captured line of process output.
@@ -29,6 +84,18 @@ pub struct ProcessOutputLine
 {
     pub source: ProcessOutputSource,
     pub text: String,
+    /// Time since the transcript started, for lines recorded in one
+    pub at: Option<std::time::Duration>,
+}
+
+/// Breakpoint the backend refused to install
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakpointFailure
+{
+    /// Address or location the breakpoint was requested at
+    pub location: String,
+    /// Why installing it failed
+    pub reason: String,
 }
 
 /// Application state
@@ -41,20 +108,36 @@ pub struct App
     pub pid: Option<u32>,
     /// Whether this process was launched by us (vs attached to existing)
     pub was_launched: bool,
+    /// Program and options the target was launched with, for `:restart`
+    launch: Option<(String, LaunchOptions)>,
+    /// When `:restart` launched the current process; earlier events are the killed one's
+    target_started: Option<std::time::Instant>,
+    /// Set by `:restart` until the TUI connects the new process's output and stdin
+    pub process_restarted: bool,
     /// Whether the application should exit
     pub should_quit: bool,
     /// Current view mode
     pub view_mode: ViewMode,
     /// State for the registers table
     pub registers_state: TableState,
+    /// Hex value being typed for the selected register after `e` in the Registers view
+    pub register_edit: Option<String>,
     /// State for the threads table
     pub threads_state: TableState,
     /// State for the memory regions table
     pub memory_regions_state: TableState,
+    /// State for the loaded images table
+    pub modules_state: TableState,
     /// Currently selected thread index
     pub selected_thread_index: usize,
+    /// Threads marked in the Threads view for bulk suspend/resume
+    pub marked_threads: HashSet<ThreadId>,
     /// Error message to display (if any)
     pub error_message: Option<String>,
+    /// Permission advice for the error message, expanded with `e`
+    pub permission_advice: Option<PermissionAdvice>,
+    /// Whether the permission advice popup is open
+    pub permission_advice_open: bool,
     /// Success/info message to display (if any) - cleared after a short time
     pub info_message: Option<String>,
     /// Timestamp when info message was set (for auto-clearing)
@@ -63,10 +146,24 @@ pub struct App
     pub process_output: VecDeque<ProcessOutputLine>,
     /// Number of lines scrolled back from the end of the output buffer
     pub output_scrollback: usize,
+    /// Whether the Output view shows transcript timestamps in the gutter (`t`)
+    pub output_timestamps: bool,
+    /// Line being typed for the target's stdin after `i` in the Output view
+    pub stdin_input: Option<String>,
+    /// Writer for the launched target's stdin; dropping it closes the pipe
+    pub process_stdin: Option<tokio::sync::mpsc::UnboundedSender<Vec<u8>>>,
     /// Timestamp of last thread list refresh (to avoid refreshing too frequently)
     last_thread_refresh: std::time::Instant,
     /// Whether the target is currently stopped.
     pub target_is_stopped: bool,
+    /// PID of the target once the backend reported it gone
+    pub target_gone: Option<u32>,
+    /// Exit code once the backend reported the target exited
+    pub target_exit: Option<i32>,
+    /// Summary of the crash the live target stopped at, until it resumes
+    pub crash_banner: Option<String>,
+    /// Panic the live target stopped at, until it resumes
+    pub panic_report: Option<PanicReport>,
     /// Last reported stop reason.
     pub last_stop_reason: StopReason,
     /// Recent stop/resume events for display.
@@ -81,6 +178,10 @@ pub struct App
     pub command_history_index: Option<usize>,
     /// Cached stack trace for the active thread
     pub cached_stack_trace: Option<Vec<StackFrame>>,
+    /// Unwind diagnostics for the cached stack trace (one per physical frame)
+    pub unwind_diagnostics: Vec<UnwindDiagnostic>,
+    /// Recursive runs the unwinder collapsed out of the cached stack trace
+    pub recursion_cycles: Vec<RecursionCycle>,
     /// Selected frame ID in the stack view
     pub selected_frame_id: Option<FrameId>,
     /// State for the stack frames table
@@ -91,29 +192,288 @@ pub struct App
     pub cached_breakpoints: Vec<BreakpointInfo>,
     /// Cache of breakpoint addresses to source locations (for UI indicators)
     pub breakpoint_locations: std::collections::HashMap<Address, Option<SourceLocation>>,
-    /// Source code cache (file path -> lines)
-    pub source_cache: std::collections::HashMap<String, Vec<String>>,
+    /// Breakpoints already reported on the timeline as slowing the target
+    pub overhead_warned: HashSet<BreakpointId>,
+    /// Source code cache (file path -> lines and their highlighting)
+    pub source_cache: std::collections::HashMap<String, SourceFile>,
     /// Current source file being displayed
     pub current_source_file: Option<String>,
+    /// Extra directories searched for sources not found where DWARF says (`source.paths`)
+    pub source_dirs: Vec<PathBuf>,
     /// Source view scroll position (line number)
     pub source_scroll: usize,
     /// Source view selected line (for setting breakpoints)
     pub source_selected_line: Option<usize>,
+    /// Help view scroll position (line number, clamped when drawn)
+    pub help_scroll: usize,
+    /// Instructions and selection of the Disassembly view
+    pub disassembly: DisassemblyView,
+    /// Address and bytes of the Memory view
+    pub memory: MemoryView,
+    /// Where the Memory view was before following pointers, for going back
+    pub memory_history: AddressHistory,
     /// Timeline log entries (chronological events)
     pub timeline_log: VecDeque<TimelineEntry>,
+    /// Filter, search and scroll position of the Timeline view
+    pub timeline: TimelineView,
     /// Current layout preset
     pub layout_preset: LayoutPreset,
+    /// Most frames unwound for the Stack view (`stack.max_frames`)
+    pub max_stack_frames: usize,
+    /// How tables print addresses (`x` or `:set address.format`)
+    pub address_format: AddressFormat,
     /// Breakpoint editor state
     pub breakpoint_editor: Option<BreakpointEditorState>,
+    /// Session recorder and destination for `--summary`
+    pub session_summary: Option<(SessionRecorder, PathBuf)>,
+    /// I/O transcript for `--transcript`, shared with the output pumps
+    pub transcript: Option<Transcript>,
+    /// Pinned memory values shown in the Overview and the pin strip
+    pub pins: PinRegistry,
+    /// Whether pins are also re-read on a timer while the target runs
+    pub pins_live: bool,
+    /// Timestamp of the last live pin refresh
+    last_pin_refresh: std::time::Instant,
+    /// Per-binary session file that pins and notes are persisted to
+    session_file: Option<PathBuf>,
+    /// Per-binary file breakpoints are restored from at startup and saved to on quit
+    breakpoint_file: Option<PathBuf>,
+    /// Restored breakpoints that didn't resolve yet, retried at every stop
+    pub pending_breakpoints: Vec<UnresolvedBreakpoint>,
+    /// Notes attached with `:note`
+    pub notes: NoteRegistry,
+    /// `--assert-reached` / `--assert-not-reached` assertions checked at every stop
+    pub assertions: AssertionSet,
+    /// `:set break.panic`; `None` (auto) stops at panics of launched targets only
+    pub break_on_panic: Option<bool>,
+    /// Hidden breakpoints on the panic handler, once its symbol has loaded
+    pub panic_breakpoints: PanicBreakpoints,
+    /// Selected row of the `:notes` list while it is open
+    pub notes_list: Option<usize>,
+    /// Hits of the last `:find` while their list is open
+    pub memory_search: Option<MemorySearchPanel>,
+    /// Lines of the `:where` popup while it is open
+    pub where_summary: Option<Vec<String>>,
+    /// Lines of the `:all-stacks` report while it is open
+    pub all_stacks: Option<Vec<String>>,
+    /// `:all-stacks` report scroll position (line number)
+    pub all_stacks_scroll: u16,
+    /// Whether `:async-stacks on` adds the futures a task is suspended in to the call stack
+    pub async_stacks: bool,
+    /// Images already reported in the timeline; `None` until a stop first finds some
+    known_images: Option<HashSet<ImageId>>,
+    /// Session start in milliseconds since the Unix epoch; names this session in timeline notes
+    pub session_id: u64,
+    /// Sequence number given to the next timeline entry
+    next_timeline_sequence: u64,
+    /// Checkpoint of every stop, for the History view and `:back` / `:forward`
+    ///
+    /// Stack memory is only copied after `:checkpoints on`.
+    pub history: CheckpointHistory,
+    /// State for the History view's table
+    pub history_state: TableState,
+    /// Every thread's registers, read once at the current stop
+    pub register_snapshot: RegisterSnapshot,
+    /// Memory ranges captured by `:checkpoint run`
+    pub checkpoint_ranges: RangeSet,
+    /// Snapshots taken by `:checkpoint run`, diffed at the next stop
+    pub pending_range_snapshots: Option<Vec<RangeSnapshot>>,
+    /// Diff shown in the region checkpoint popup
+    pub range_diffs: Option<Vec<RangeDiff>>,
+    /// Region checkpoint popup scroll position (line number)
+    pub range_diff_scroll: u16,
+    /// Backend emission to TUI ingestion latency of recent debugger events
+    pub event_latency: LatencyWindow,
+    /// Backend receipt of the OS event to emission, where the backend reports it
+    pub handler_latency: LatencyWindow,
+    /// Header flash, toasts and bell for stops (`:set stop.*`)
+    pub stop_emphasis: StopEmphasis,
+    /// Most recent failed breakpoint installs, oldest first
+    pub breakpoint_failures: VecDeque<BreakpointFailure>,
+    /// Installs that failed since the target last resumed
+    failures_since_resume: usize,
+    /// Whether a breakpoint was requested since the target last resumed
+    breakpoints_edited: bool,
+    /// Whether resuming past failed or pending breakpoints asks first (`:set resume.confirm`)
+    pub confirm_resume: bool,
+    /// Question shown while waiting for `y` to resume anyway
+    pub resume_prompt: Option<String>,
+    /// Whether `:debug` commands are available (`:set developer.tools`)
+    pub developer_tools: bool,
+    /// Most recent stop the backend reported, re-dispatched by `:debug replay-stop`
+    last_stop: Option<DebuggerEvent>,
+    /// Main-thread sampler while the target runs; `None` when `:set hang.probe off`
+    pub hang_probe: Option<HangProbe>,
+    /// Consecutive samples in one function that count as a hang (`:set hang.samples`)
+    pub hang_samples: usize,
+    /// Whether a main-thread hang suspends the target (`:set hang.autostop`)
+    pub hang_autostop: bool,
+    /// Global keys moved by `keymap.toml`
+    pub keymap: Keymap,
+    /// Config problems found at startup, shown above the views until a key is pressed
+    pub config_banner: Option<String>,
+    /// Running processes to attach to, while the TUI was started without a target
+    pub process_picker: Option<ProcessPicker>,
+}
+
+/// Stop reason named by `:debug emit-stop`, attributed to `thread` where the reason names one
+///
+/// Accepts `running`, `suspended`, `signal <n>`, `breakpoint <addr>`, `step <pc>`,
+/// `watch <addr> <pc>`, `badaccess <addr> read|write|execute`, `overflow <sp> <guard>`, `exited <code>`
+/// and `unknown`.
+fn parse_stop_reason(args: &[&str], thread: Option<ThreadId>) -> Result<StopReason, String>
+{
+    let hex = |value: &str| {
+        u64::from_str_radix(value.trim_start_matches("0x"), 16).map_err(|_| format!("Invalid address: {value}"))
+    };
+    let number = |value: &str| value.parse::<i32>().map_err(|_| format!("Invalid number: {value}"));
+    match args {
+        ["running"] => Ok(StopReason::Running),
+        ["suspended"] => Ok(StopReason::Suspended),
+        ["signal", signal] => Ok(StopReason::Signal(number(signal)?)),
+        ["breakpoint", address] => Ok(StopReason::Breakpoint(hex(address)?)),
+        ["step", pc] => Ok(StopReason::Step(hex(pc)?)),
+        ["watch", address, pc] => Ok(StopReason::Watchpoint {
+            address: hex(address)?,
+            pc: hex(pc)?,
+        }),
+        ["badaccess", address, kind] => Ok(StopReason::BadAccess {
+            address: hex(address)?,
+            kind: match *kind {
+                "read" => BadAccessKind::Read,
+                "write" => BadAccessKind::Write,
+                "execute" => BadAccessKind::Execute,
+                _ => return Err(format!("Invalid access kind: {kind}")),
+            },
+        }),
+        ["overflow", sp, guard_page] => Ok(StopReason::StackOverflow {
+            thread: thread.ok_or("A stack overflow needs an active thread")?,
+            sp: hex(sp)?,
+            guard_page: hex(guard_page)?,
+        }),
+        ["exited", code] => Ok(StopReason::Exited(number(code)?)),
+        ["unknown"] => Ok(StopReason::Unknown),
+        _ => Err(
+            "Stop reasons: running, suspended, signal <n>, breakpoint <addr>, step <pc>, watch <addr> <pc>, badaccess \
+             <addr> read|write|execute, overflow <sp> <guard>, exited <code>, unknown"
+                .to_string(),
+        ),
+    }
+}
+
+/// Places a missing source file was looked for, listed in its load error
+const SOURCE_TRIES_SHOWN: usize = 6;
+
+/// The Source view's error for a file found nowhere: the DWARF path, where it was remapped to and the places tried
+fn source_not_found(location: &SourceLocation, tried: &[PathBuf]) -> String
+{
+    let mut detail = String::new();
+    match location.dwarf_file.as_deref() {
+        Some(dwarf) if dwarf != location.file => {
+            let _ = writeln!(detail, "DWARF path: {dwarf}\nRemapped to: {}", location.file);
+        }
+        _ => {
+            let _ = writeln!(detail, "Path: {}", location.file);
+        }
+    }
+    detail.push_str("Tried:\n");
+    for path in tried.iter().take(SOURCE_TRIES_SHOWN) {
+        let _ = writeln!(detail, "  {}", path.display());
+    }
+    if tried.len() > SOURCE_TRIES_SHOWN {
+        let _ = writeln!(detail, "  ({} more)", tried.len() - SOURCE_TRIES_SHOWN);
+    }
+    detail.push_str(
+        "\nTip: Source paths in DWARF may be absolute paths from the build machine; map them with source-map add FROM \
+         TO, --source-map FROM=TO or source.map in config.toml, or list directories to search in source.paths.",
+    );
+    detail
+}
+
+/// Format a latency with a unit suited to its size
+#[must_use]
+pub fn format_latency(latency: std::time::Duration) -> String
+{
+    if latency < std::time::Duration::from_millis(1) {
+        format!("{}µs", latency.as_micros())
+    } else if latency < std::time::Duration::from_secs(1) {
+        format!("{:.1}ms", latency.as_secs_f64() * 1000.0)
+    } else {
+        format!("{:.2}s", latency.as_secs_f64())
+    }
 }
 
 /// Timeline log entry
 #[derive(Debug, Clone)]
+#[allow(clippy::large_stack_arrays)]
 pub struct TimelineEntry
 {
     pub timestamp: std::time::Instant,
     pub kind: TimelineEntryKind,
     pub message: String,
+    /// Delivery timing, for entries logged from a debugger event
+    pub timing: Option<EventTiming>,
+    /// Breakpoint the entry is about, for Enter in the Timeline
+    pub breakpoint: Option<BreakpointId>,
+    /// Thread the entry is about
+    pub thread: Option<ThreadId>,
+    /// Checkpoint captured at this stop, when checkpointing is on
+    pub checkpoint: Option<u64>,
+    /// Position in the session's timeline, assigned when the entry is logged
+    pub sequence: u64,
+}
+
+impl TimelineEntry
+{
+    /// Entry logged now, with no timing or cross-references
+    #[must_use]
+    pub fn new(kind: TimelineEntryKind, message: String) -> Self
+    {
+        Self {
+            timestamp: std::time::Instant::now(),
+            kind,
+            message,
+            timing: None,
+            breakpoint: None,
+            thread: None,
+            checkpoint: None,
+            sequence: 0,
+        }
+    }
+
+    /// Attach the delivery timing of the event behind the entry
+    #[must_use]
+    pub fn with_timing(mut self, timing: Option<EventTiming>) -> Self
+    {
+        self.timing = timing;
+        self
+    }
+
+    /// Reference a breakpoint
+    #[must_use]
+    pub fn with_breakpoint(mut self, breakpoint: Option<BreakpointId>) -> Self
+    {
+        self.breakpoint = breakpoint;
+        self
+    }
+
+    /// Reference a thread
+    #[must_use]
+    pub fn with_thread(mut self, thread: Option<ThreadId>) -> Self
+    {
+        self.thread = thread;
+        self
+    }
+}
+
+/// How long the debugger event behind a timeline entry took to arrive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventTiming
+{
+    /// Backend emission to TUI ingestion
+    pub delivery: std::time::Duration,
+    /// Backend receipt of the OS event to emission, if the backend reports it
+    pub handler: Option<std::time::Duration>,
 }
 
 /// Timeline entry kind
@@ -126,6 +486,33 @@ pub enum TimelineEntryKind
     Signal,
     Output,
     Error,
+    Warning,
+    Diagnostic,
+    MemoryDiff,
+    Pin,
+    ThreadLocal,
+    Image,
+    Panic,
+}
+
+/// Per-thread operation applied from the Threads view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadOperation
+{
+    Suspend,
+    Resume,
+}
+
+/// Where a breakpoint set from the selected stack frame goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameBreakpoint
+{
+    /// The frame's own PC
+    Pc,
+    /// Where the frame returns to in its caller
+    Return,
+    /// The PC of the frame's callee, one frame below it
+    CallTarget,
 }
 
 /// Breakpoint editor state
@@ -139,6 +526,20 @@ pub struct BreakpointEditorState
     pub editing_existing: Option<BreakpointId>,
 }
 
+/// The `:find` results list
+#[derive(Debug, Clone)]
+pub struct MemorySearchPanel
+{
+    /// What was searched for, as typed
+    pub pattern: String,
+    /// Each hit with the function or region it is in
+    pub hits: Vec<(Address, String)>,
+    /// The search stopped at its hit limit
+    pub truncated: bool,
+    /// Selected hit
+    pub selected: usize,
+}
+
 /// Different view modes in the TUI
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ViewMode
@@ -151,6 +552,10 @@ pub enum ViewMode
     Threads,
     /// Memory regions view
     MemoryRegions,
+    /// Hex dump of target memory (`:mem <addr>`)
+    Memory,
+    /// Images the symbol cache has loaded (`:modules`)
+    Modules,
     /// Process output view
     Output,
     /// Source code + breakpoint view
@@ -159,6 +564,10 @@ pub enum ViewMode
     Stack,
     /// Timeline/log panel
     Timeline,
+    /// Instructions around the PC
+    Disassembly,
+    /// Past stops, newest first (`:history`)
+    History,
     /// Help view showing keyboard shortcuts and commands
     Help,
 }
@@ -177,9 +586,10 @@ pub enum LayoutPreset
 
 impl App
 {
-    /// Create a new application instance
+    /// Create a new application instance, seeded with the startup defaults from `config.toml`
     #[must_use]
-    pub fn new(debugger: Box<dyn Debugger>, pid: Option<u32>, was_launched: bool) -> Self
+    #[allow(clippy::too_many_lines)]
+    pub fn new(debugger: Box<dyn Debugger>, pid: Option<u32>, was_launched: bool, config: &Config) -> Self
     {
         let initial_is_stopped = debugger.is_stopped();
         let initial_stop_reason = if initial_is_stopped {
@@ -197,29 +607,50 @@ impl App
         let mut memory_regions_state = TableState::default();
         memory_regions_state.select(Some(0));
 
+        let mut modules_state = TableState::default();
+        modules_state.select(Some(0));
+
         let mut stack_frames_state = TableState::default();
         stack_frames_state.select(Some(0));
 
         let mut breakpoints_state = TableState::default();
         breakpoints_state.select(Some(0));
 
+        let mut history_state = TableState::default();
+        history_state.select(Some(0));
+
         let mut app = Self {
             debugger,
             pid,
             was_launched,
+            launch: None,
+            target_started: None,
+            process_restarted: false,
             should_quit: false,
             view_mode: ViewMode::Overview,
             registers_state,
+            register_edit: None,
             threads_state,
             memory_regions_state,
+            modules_state,
             selected_thread_index: 0,
+            marked_threads: HashSet::new(),
             error_message: None,
+            permission_advice: None,
+            permission_advice_open: false,
             info_message: None,
             info_message_time: None,
             process_output: VecDeque::new(),
             output_scrollback: 0,
+            output_timestamps: false,
+            stdin_input: None,
+            process_stdin: None,
             last_thread_refresh: std::time::Instant::now(),
             target_is_stopped: initial_is_stopped,
+            target_gone: None,
+            target_exit: None,
+            crash_banner: None,
+            panic_report: None,
             last_stop_reason: initial_stop_reason,
             stop_event_log: VecDeque::new(),
             command_input: String::new(),
@@ -227,18 +658,75 @@ impl App
             command_history: VecDeque::new(),
             command_history_index: None,
             cached_stack_trace: None,
+            unwind_diagnostics: Vec::new(),
+            recursion_cycles: Vec::new(),
             selected_frame_id: None,
             stack_frames_state,
             breakpoints_state,
             cached_breakpoints: Vec::new(),
             breakpoint_locations: std::collections::HashMap::new(),
+            overhead_warned: HashSet::new(),
             source_cache: std::collections::HashMap::new(),
             current_source_file: None,
+            source_dirs: Vec::new(),
             source_scroll: 0,
+            help_scroll: 0,
+            disassembly: DisassemblyView::default(),
+            memory: MemoryView::default(),
+            memory_history: AddressHistory::default(),
             source_selected_line: None,
             timeline_log: VecDeque::new(),
+            timeline: TimelineView::default(),
             layout_preset: LayoutPreset::Standard,
+            max_stack_frames: ferros_utils::config::DEFAULT_MAX_STACK_FRAMES,
+            address_format: AddressFormat::default(),
             breakpoint_editor: None,
+            session_summary: None,
+            transcript: None,
+            pins: PinRegistry::new(),
+            pins_live: false,
+            last_pin_refresh: std::time::Instant::now(),
+            session_file: None,
+            breakpoint_file: None,
+            pending_breakpoints: Vec::new(),
+            notes: NoteRegistry::new(),
+            assertions: AssertionSet::default(),
+            break_on_panic: None,
+            panic_breakpoints: PanicBreakpoints::default(),
+            notes_list: None,
+            memory_search: None,
+            where_summary: None,
+            all_stacks: None,
+            all_stacks_scroll: 0,
+            async_stacks: false,
+            known_images: None,
+            session_id: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)),
+            next_timeline_sequence: 0,
+            history: CheckpointHistory::new(CheckpointOptions::new(MAX_HISTORY_STOPS).with_stack_budget(0)),
+            history_state,
+            register_snapshot: RegisterSnapshot::default(),
+            checkpoint_ranges: RangeSet::new(),
+            pending_range_snapshots: None,
+            range_diffs: None,
+            range_diff_scroll: 0,
+            event_latency: LatencyWindow::new(EVENT_LATENCY_SAMPLES),
+            handler_latency: LatencyWindow::new(EVENT_LATENCY_SAMPLES),
+            stop_emphasis: StopEmphasis::default(),
+            breakpoint_failures: VecDeque::new(),
+            failures_since_resume: 0,
+            breakpoints_edited: false,
+            confirm_resume: true,
+            resume_prompt: None,
+            developer_tools: false,
+            last_stop: None,
+            hang_probe: None,
+            hang_samples: DEFAULT_HANG_SAMPLES,
+            hang_autostop: false,
+            keymap: Keymap::default(),
+            config_banner: None,
+            process_picker: None,
         };
 
         if initial_is_stopped {
@@ -248,6 +736,7 @@ impl App
 
         // Initialize cached breakpoints
         app.refresh_breakpoints();
+        app.apply_config(config);
 
         app
     }
@@ -258,7 +747,21 @@ impl App
     /// It performs cleanup operations like killing launched processes and detaching.
     pub async fn cleanup(&mut self)
     {
+        // Capture the summary before the target is killed or detached
+        self.write_session_summary();
+        if let Some(transcript) = &self.transcript
+            && let Err(e) = transcript.finish("session ended")
+        {
+            eprintln!("Warning: Failed to write transcript {}: {e}", transcript.path().display());
+        }
+
         if self.debugger.is_attached() {
+            if let Some(path) = self.breakpoint_file.clone()
+                && let Err(e) = self.save_breakpoint_file(&path)
+            {
+                eprintln!("Warning: Failed to save breakpoints to {}: {e}", path.display());
+            }
+
             // If we launched the process, kill it first before detaching
             // This ensures clean shutdown
             if self.was_launched
@@ -291,6 +794,232 @@ impl App
         }
     }
 
+    /// Record this session and write its summary to `path` on cleanup
+    pub fn enable_session_summary(&mut self, recorder: SessionRecorder, path: PathBuf)
+    {
+        self.session_summary = Some((recorder, path));
+    }
+
+    /// Record debugger events into `transcript` next to the output the pumps record
+    pub fn enable_transcript(&mut self, transcript: Transcript)
+    {
+        self.add_timeline_entry(
+            TimelineEntryKind::Diagnostic,
+            format!("Recording I/O transcript to {}", transcript.path().display()),
+        );
+        self.transcript = Some(transcript);
+    }
+
+    /// Apply the startup defaults from `config.toml`: layout, stack depth, source search paths and `break.panic`
+    ///
+    /// These come before [`App::enable_user_config`], whose `:set` values win.
+    fn apply_config(&mut self, config: &Config)
+    {
+        self.layout_preset = match config.layout.as_str() {
+            "compact" => LayoutPreset::Compact,
+            "widescreen" => LayoutPreset::Widescreen,
+            _ => LayoutPreset::Standard,
+        };
+        self.max_stack_frames = config.max_stack_frames;
+        self.source_dirs.clone_from(&config.source_paths);
+        if config.break_on_panic.is_some() {
+            self.break_on_panic = config.break_on_panic;
+            self.sync_panic_breakpoints();
+        }
+    }
+
+    /// Apply `config.toml` settings and `keymap.toml` bindings loaded at startup
+    ///
+    /// Problems don't stop the session: the banner summarizes them and each
+    /// one is logged to the timeline with its file and line.
+    pub fn enable_user_config(&mut self, config: UserConfig)
+    {
+        for (key, value) in &config.settings {
+            if let Err(e) = self.apply_setting(key, value) {
+                self.add_timeline_entry(TimelineEntryKind::Warning, format!("config.toml: {key}: {e}"));
+            }
+        }
+        self.keymap = config.keymap;
+        if !config.report.is_empty() {
+            for problem in config.report.problems() {
+                self.add_timeline_entry(TimelineEntryKind::Warning, problem.to_string());
+            }
+            self.config_banner = Some(format!("{} (see Timeline)", config.report.summary()));
+        }
+    }
+
+    /// Tell a user without a config file that `ferros init` exists
+    pub fn show_first_run_hint(&mut self)
+    {
+        self.info_message = Some(config::FIRST_RUN_HINT.to_string());
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// Show the running processes instead of the views, to pick one to attach to
+    pub fn open_process_picker(&mut self)
+    {
+        self.process_picker = Some(ProcessPicker::load());
+    }
+
+    /// Restore pins and notes from the per-binary session file at `path` and save them there on change
+    ///
+    /// Notes anchored to an image move to where it is loaded in this run.
+    pub fn enable_session_file(&mut self, path: PathBuf)
+    {
+        let loaded = SessionFile::load(&path);
+        self.session_file = Some(path);
+        match loaded {
+            Ok(file) => {
+                self.pins = PinRegistry::from_specs(&file.pins);
+                self.refresh_pins();
+                self.notes = NoteRegistry::from_notes(&file.notes);
+                self.rebase_notes();
+            }
+            Err(e) => self.error_message = Some(format!("Failed to load session file: {e}")),
+        }
+    }
+
+    /// Move anchored notes to this run's image addresses, saving if any moved
+    fn rebase_notes(&mut self)
+    {
+        if self.notes.is_empty() || !self.debugger.is_attached() {
+            return;
+        }
+        let regions = self.debugger.get_memory_regions().unwrap_or_default();
+        let moved = self.notes.rebase(&regions, image_uuid);
+        if moved > 0 {
+            self.add_timeline_entry(
+                TimelineEntryKind::Diagnostic,
+                format!("Moved {moved} note(s) to this run's image addresses"),
+            );
+            self.save_session_file();
+        }
+    }
+
+    /// Restore the breakpoints saved in the per-binary file at `path` and save them there on quit
+    pub fn enable_breakpoint_file(&mut self, path: PathBuf)
+    {
+        if path.exists() && self.debugger.is_attached() {
+            self.load_breakpoint_file(&path);
+        }
+        self.breakpoint_file = Some(path);
+    }
+
+    /// Install the breakpoints saved at `path`, keeping the ones that don't resolve pending
+    fn load_breakpoint_file(&mut self, path: &Path)
+    {
+        match self.debugger.load_breakpoints(path) {
+            Ok(report) => {
+                let restored = report.installed.len();
+                for unresolved in &report.unresolved {
+                    self.add_timeline_entry(
+                        TimelineEntryKind::Warning,
+                        format!("Saved breakpoint at {} is pending: {}", unresolved.saved, unresolved.reason),
+                    );
+                }
+                let pending = report.unresolved.len();
+                self.pending_breakpoints.extend(report.unresolved);
+                self.refresh_breakpoints();
+                let message = match pending {
+                    0 => format!("Restored {restored} breakpoint(s) from {}", path.display()),
+                    _ => format!("Restored {restored} breakpoint(s) from {}, {pending} pending", path.display()),
+                };
+                self.add_timeline_entry(TimelineEntryKind::BreakpointHit, message.clone());
+                self.info_message = Some(message);
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            Err(e) => self.report_error("Failed to load breakpoints", &e),
+        }
+    }
+
+    /// Save the installed and pending breakpoints to `path`. Returns how many were saved.
+    fn save_breakpoint_file(&self, path: &Path) -> Result<usize, DebuggerError>
+    {
+        let mut file = BreakpointFile::capture(&*self.debugger);
+        file.breakpoints
+            .extend(self.pending_breakpoints.iter().map(|pending| pending.saved.clone()));
+        file.save(path)?;
+        Ok(file.breakpoints.len())
+    }
+
+    /// Try the pending restored breakpoints again, now that more images may be loaded
+    fn retry_pending_breakpoints(&mut self)
+    {
+        let pending = std::mem::take(&mut self.pending_breakpoints);
+        for unresolved in pending {
+            let saved = &unresolved.saved;
+            let installed = saved
+                .resolve(&mut *self.debugger)
+                .and_then(|address| self.debugger.add_breakpoint(saved.request(address)));
+            match installed {
+                Ok(id) => self.add_timeline_entry(
+                    TimelineEntryKind::BreakpointHit,
+                    format!("Saved breakpoint at {saved} resolved as breakpoint {}", id.raw()),
+                ),
+                Err(e) => self.pending_breakpoints.push(UnresolvedBreakpoint {
+                    reason: e.to_string(),
+                    ..unresolved
+                }),
+            }
+        }
+    }
+
+    /// `bp save [path]` / `bp load [path]`, defaulting to the per-binary breakpoint file
+    fn breakpoint_file_command(&mut self, args: &[&str])
+    {
+        let path = match args.get(1) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => self.breakpoint_file.clone(),
+        };
+        match (args.first().copied(), path) {
+            (Some("save"), Some(path)) => match self.save_breakpoint_file(&path) {
+                Ok(count) => {
+                    self.info_message = Some(format!("Saved {count} breakpoint(s) to {}", path.display()));
+                    self.info_message_time = Some(std::time::Instant::now());
+                }
+                Err(e) => self.report_error("Failed to save breakpoints", &e),
+            },
+            (Some("load"), Some(path)) => self.load_breakpoint_file(&path),
+            (Some("save" | "load"), None) => {
+                self.error_message = Some("No breakpoint file for this target; use bp save|load <path>".to_string());
+            }
+            _ => self.error_message = Some("Usage: bp save|load [path]".to_string()),
+        }
+    }
+
+    /// Check stops against `assertions`, whose breakpoints are already installed
+    pub fn enable_assertions(&mut self, assertions: AssertionSet)
+    {
+        self.add_timeline_entry(
+            TimelineEntryKind::Diagnostic,
+            format!("Checking {} assertion(s)", assertions.len()),
+        );
+        self.assertions = assertions;
+    }
+
+    /// Finish the session recorder (if any) and write the summary file
+    ///
+    /// Assertions that had to be reached and weren't fail here.
+    pub fn write_session_summary(&mut self)
+    {
+        let violations = self.assertions.finish();
+        let Some((mut recorder, path)) = self.session_summary.take() else {
+            return;
+        };
+        for violation in &violations {
+            recorder.record_violation(violation);
+        }
+        let debugger: Option<&mut dyn Debugger> = if self.debugger.is_attached() {
+            Some(self.debugger.as_mut())
+        } else {
+            None
+        };
+        let summary = recorder.finish(debugger);
+        if let Err(e) = summary.write_to(&path) {
+            eprintln!("Warning: Failed to write session summary to {}: {e}", path.display());
+        }
+    }
+
     /// Handle a keyboard event
     ///
     /// Returns `true` if the application should quit, `false` otherwise.
@@ -299,7 +1028,31 @@ impl App
     {
         use crossterm::event::{KeyCode, KeyModifiers};
 
+        // The advice popup keeps the error it explains; any key closes it
+        if self.permission_advice_open {
+            self.permission_advice_open = false;
+            return false;
+        }
+        // Likewise the `:where` summary
+        if self.where_summary.take().is_some() {
+            return false;
+        }
+        if key_event.code == KeyCode::Char('e')
+            && self.error_message.is_some()
+            && self.permission_advice.is_some()
+            && !self.command_palette_active
+            && self.breakpoint_editor.is_none()
+            && self.timeline.search_input.is_none()
+            && self.register_edit.is_none()
+            && self.stdin_input.is_none()
+        {
+            self.permission_advice_open = true;
+            return false;
+        }
+
         self.error_message = None;
+        self.permission_advice = None;
+        self.config_banner = None;
 
         // Check for Ctrl+Q FIRST - this should always work to quit, regardless of mode
         if matches!(key_event.code, KeyCode::Char('q' | 'Q')) && key_event.modifiers.contains(KeyModifiers::CONTROL) {
@@ -308,16 +1061,85 @@ impl App
             return true;
         }
 
+        // Without a target, keys go to the process picker
+        if self.process_picker.is_some() {
+            return self.handle_process_picker_input(key_event);
+        }
+
+        // Answer the resume prompt; anything but `y` keeps the target stopped
+        if self.resume_prompt.take().is_some() {
+            if matches!(key_event.code, KeyCode::Char('y' | 'Y')) {
+                self.resume_target();
+            } else {
+                self.info_message = Some("Resume cancelled".to_string());
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            return false;
+        }
+
         // Handle breakpoint editor input
         if self.breakpoint_editor.is_some() {
             return self.handle_breakpoint_editor_input(key_event);
         }
 
+        // Handle the region checkpoint popup
+        if self.range_diffs.is_some() {
+            self.handle_range_diff_input(key_event);
+            return false;
+        }
+
+        // Handle the `:all-stacks` report
+        if self.all_stacks.is_some() {
+            self.handle_all_stacks_input(key_event);
+            return false;
+        }
+
+        // Handle the notes list
+        if self.notes_list.is_some() {
+            self.handle_notes_list_input(key_event);
+            return false;
+        }
+
+        // Handle the `:find` results
+        if self.memory_search.is_some() {
+            self.handle_memory_search_input(key_event);
+            return false;
+        }
+
         // Handle command palette input
         if self.command_palette_active {
             return self.handle_command_palette_input(key_event);
         }
 
+        // Handle the Timeline search prompt
+        if self.timeline.search_input.is_some() {
+            self.handle_timeline_search_input(key_event);
+            return false;
+        }
+
+        // Handle the Registers view's value edit
+        if self.register_edit.is_some() {
+            self.handle_register_edit_input(key_event);
+            return false;
+        }
+
+        // Handle the Output view's stdin line
+        if self.stdin_input.is_some() {
+            self.handle_stdin_input(key_event);
+            return false;
+        }
+
+        // Keys moved by keymap.toml dispatch as the action's default key
+        let mut key_event = key_event;
+        if let KeyCode::Char(pressed) = key_event.code
+            && !key_event.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            match self.keymap.translate(pressed) {
+                Some(key) => key_event.code = KeyCode::Char(key),
+                None => return false,
+            }
+        }
+
         match key_event.code {
             KeyCode::Char('q' | 'Q') => {
                 // Regular 'q' without Ctrl - show help message
@@ -369,7 +1191,12 @@ impl App
             KeyCode::Char('9') => {
                 self.view_mode = ViewMode::Help;
             }
-            KeyCode::Char('?') | KeyCode::Char('h') | KeyCode::Char('H') => {
+            KeyCode::Char('0') => {
+                // Pressed again, this goes back to the PC
+                self.view_mode = ViewMode::Disassembly;
+                self.refresh_disassembly();
+            }
+            KeyCode::Char('?' | 'h' | 'H') => {
                 // Toggle help view
                 if self.view_mode == ViewMode::Help {
                     // Return to previous view (default to Overview)
@@ -383,14 +1210,38 @@ impl App
                 self.command_palette_active = true;
                 self.command_input.clear();
             }
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.restart_target();
+            }
+            KeyCode::Char('b') if self.view_mode == ViewMode::Stack => {
+                self.break_at_selected_frame(FrameBreakpoint::Pc);
+            }
+            KeyCode::Char('c') if self.view_mode == ViewMode::Stack => {
+                self.break_at_selected_frame(FrameBreakpoint::Return);
+            }
+            KeyCode::Char('C') if self.view_mode == ViewMode::Stack => {
+                self.break_at_selected_frame(FrameBreakpoint::CallTarget);
+            }
+            KeyCode::Char('b') if self.view_mode == ViewMode::Disassembly => {
+                if let Some(address) = self.disassembly.selected_address() {
+                    self.toggle_breakpoint_at_address(address);
+                }
+            }
+            KeyCode::Char('b')
+                if self.view_mode == ViewMode::Source && self.selected_frame_without_debug_info().is_some() =>
+            {
+                self.refuse_line_breakpoint();
+            }
             KeyCode::Char('b') => {
                 // Toggle breakpoint - use context-aware address selection
                 if self.debugger.is_attached() && self.target_is_stopped {
                     let address = match self.view_mode {
                         ViewMode::Source => {
                             // In source view, try to use the selected line or current line
-                            if let Some(ref file) = self.current_source_file {
+                            if let Some(file) = self.current_source_file.clone() {
+                                // The selected line is an index into the file; source lines are 1-based
                                 let line_num = self.source_selected_line
+                                    .map(|index| index + 1)
                                     .or_else(|| {
                                         // Use current frame's line if no line selected
                                         let selected_idx = self.stack_frames_state.selected().unwrap_or(0);
@@ -399,7 +1250,7 @@ impl App
                                             .and_then(|frames| frames.get(selected_idx))
                                             .and_then(|frame| frame.location.as_ref())
                                             .and_then(|loc| {
-                                                if loc.file == *file {
+                                                if loc.file == file {
                                                     loc.line.map(|l| l as usize)
                                                 } else {
                                                     None
@@ -407,32 +1258,23 @@ impl App
                                             })
                                     });
                                 
-                                if let Some(line) = line_num {
-                                    // Try to find address for this source line
-                                    self.find_address_for_source_line(file, line)
-                                } else {
-                                    // Fallback to selected frame's PC
-                                    let selected_idx = self.stack_frames_state.selected().unwrap_or(0);
-                                    self.cached_stack_trace
-                                        .as_ref()
-                                        .and_then(|frames| frames.get(selected_idx))
-                                        .map(|frame| frame.pc)
+                                if let Some(line) = line_num.and_then(|line| u32::try_from(line).ok()) {
+                                    self.toggle_line_breakpoint(&file, line);
+                                    return false;
                                 }
+                                // Fallback to selected frame's PC
+                                let selected_idx = self.stack_frames_state.selected().unwrap_or(0);
+                                self.cached_stack_trace
+                                    .as_ref()
+                                    .and_then(|frames| frames.get(selected_idx))
+                                    .map(|frame| frame.pc)
                             } else {
                                 None
                             }
                         }
-                        ViewMode::Stack => {
-                            // Use the selected frame's PC
-                            let selected_idx = self.stack_frames_state.selected().unwrap_or(0);
-                            self.cached_stack_trace
-                                .as_ref()
-                                .and_then(|frames| frames.get(selected_idx))
-                                .map(|frame| frame.pc)
-                        }
                         _ => {
                             // For other views, use current PC
-                            self.debugger.read_registers().ok().map(|regs| regs.pc)
+                            self.active_registers().ok().map(|regs| regs.pc)
                         }
                     };
                     
@@ -440,7 +1282,7 @@ impl App
                         self.toggle_breakpoint_at_address(addr);
                     } else {
                         // Fallback to current PC if context lookup failed
-                        if let Ok(regs) = self.debugger.read_registers() {
+                        if let Ok(regs) = self.active_registers() {
                             self.toggle_breakpoint_at_address(regs.pc);
                         }
                     }
@@ -450,10 +1292,99 @@ impl App
                 // Open breakpoint editor
                 self.open_breakpoint_editor(None);
             }
+            KeyCode::Char(' ') if self.view_mode == ViewMode::Threads => {
+                self.toggle_thread_mark();
+            }
+            KeyCode::Char('a') if self.view_mode == ViewMode::Threads => {
+                self.toggle_all_thread_marks();
+            }
+            KeyCode::Char('s') if self.view_mode == ViewMode::Threads => {
+                self.apply_thread_operation(ThreadOperation::Suspend);
+            }
+            KeyCode::Char('r') if self.view_mode == ViewMode::Threads => {
+                self.apply_thread_operation(ThreadOperation::Resume);
+            }
+            KeyCode::Enter if self.view_mode == ViewMode::Threads => {
+                self.show_selected_thread_stack();
+            }
+            KeyCode::Char('/') if self.view_mode == ViewMode::Timeline => {
+                self.timeline.search_input = Some(String::new());
+            }
+            KeyCode::Char('n' | 'N') if self.view_mode == ViewMode::Timeline => {
+                self.step_timeline_match(key_event.code == KeyCode::Char('n'));
+            }
+            KeyCode::Char('f') if self.view_mode == ViewMode::Timeline => {
+                self.timeline.filter = self.timeline.filter.next();
+            }
+            KeyCode::Enter if self.view_mode == ViewMode::Registers => {
+                self.follow_selected_register();
+            }
+            KeyCode::Char('e') if self.view_mode == ViewMode::Registers => {
+                if self.require_live("change registers") {
+                    self.register_edit = Some(String::new());
+                }
+            }
+            KeyCode::Enter if self.view_mode == ViewMode::Memory => {
+                self.follow_selected_word();
+            }
+            KeyCode::Backspace | KeyCode::Char('[') if self.view_mode == ViewMode::Memory => {
+                self.memory_history_step(true);
+            }
+            KeyCode::Char(']') if self.view_mode == ViewMode::Memory => {
+                self.memory_history_step(false);
+            }
+            KeyCode::Left if self.view_mode == ViewMode::Memory => {
+                self.select_memory_word(false);
+            }
+            KeyCode::Right if self.view_mode == ViewMode::Memory => {
+                self.select_memory_word(true);
+            }
+            KeyCode::PageUp if self.view_mode == ViewMode::Memory => {
+                self.scroll_memory(-self.memory_page());
+            }
+            KeyCode::PageDown if self.view_mode == ViewMode::Memory => {
+                self.scroll_memory(self.memory_page());
+            }
+            KeyCode::PageUp if self.view_mode == ViewMode::Timeline => {
+                self.scroll_timeline(-self.timeline_page());
+            }
+            KeyCode::PageDown if self.view_mode == ViewMode::Timeline => {
+                self.scroll_timeline(self.timeline_page());
+            }
+            KeyCode::Char('t') if self.view_mode == ViewMode::Output => {
+                self.toggle_output_timestamps();
+            }
+            KeyCode::Char('i') if self.view_mode == ViewMode::Output => {
+                if self.process_stdin.is_some() {
+                    self.stdin_input = Some(String::new());
+                } else {
+                    self.error_message = Some(
+                        "The target has no stdin to type into: it was closed, or ferros didn't launch the target"
+                            .to_string(),
+                    );
+                }
+            }
+            KeyCode::End if self.view_mode == ViewMode::Timeline => {
+                self.timeline.select(None);
+            }
+            KeyCode::Enter if self.view_mode == ViewMode::Timeline => {
+                self.open_timeline_entry();
+            }
+            KeyCode::Enter if self.view_mode == ViewMode::History => {
+                self.inspect_selected_stop(ViewMode::Stack);
+            }
+            KeyCode::Char('v') if self.view_mode == ViewMode::History => {
+                self.inspect_selected_stop(ViewMode::Registers);
+            }
+            KeyCode::Char('s') if self.target_ended() => {
+                self.require_target("suspend");
+            }
             KeyCode::Char('s') => {
                 if self.debugger.is_attached() {
-                    if let Err(e) = self.debugger.suspend() {
-                        self.error_message = Some(format!("Failed to suspend: {e}"));
+                    if let Err(e) = self.debugger.suspend()
+                        && !self.observe_target_gone(&e)
+                    {
+                        self.report_error("Failed to suspend", &e);
                         self.info_message = None;
                     }
                 } else {
@@ -461,11 +1392,20 @@ impl App
                     self.info_message = None;
                 }
             }
+            KeyCode::Char('r') if !self.is_live() => {
+                self.require_live("resume");
+            }
+            KeyCode::Char('r') if self.target_ended() => {
+                self.require_target("resume");
+            }
             KeyCode::Char('r') => {
                 if self.debugger.is_attached() {
-                    if let Err(e) = self.debugger.resume() {
-                        self.error_message = Some(format!("Failed to resume: {e}"));
-                        self.info_message = None;
+                    match self.resume_confirmation() {
+                        Some(prompt) => {
+                            self.resume_prompt = Some(prompt);
+                            self.info_message = None;
+                        }
+                        None => self.resume_target(),
                     }
                 } else {
                     self.error_message = Some("Not attached to a process".to_string());
@@ -502,6 +1442,15 @@ impl App
                 // Cycle layout presets
                 self.cycle_layout_preset();
             }
+            KeyCode::Char('w') => self.show_where(),
+            KeyCode::Char('i') => self.step(Step::Instruction),
+            KeyCode::Char('o') => self.step(Step::Over),
+            KeyCode::Char('u') => self.step(Step::Out),
+            KeyCode::Char('x') => {
+                self.address_format = self.address_format.next();
+                self.info_message = Some(format!("address.format = {}", self.address_format));
+                self.info_message_time = Some(std::time::Instant::now());
+            }
             _ => {}
         }
 
@@ -543,6 +1492,17 @@ impl App
                     self.memory_regions_state.select(Some(next));
                 }
             }
+            ViewMode::Modules => {
+                let i = self.modules_state.selected().unwrap_or(0);
+                if let Ok(images) = self.debugger.modules() {
+                    let max = images.len().saturating_sub(1);
+                    if max == 0 {
+                        return;
+                    }
+                    let next = if i == 0 { max } else { i - 1 };
+                    self.modules_state.select(Some(next));
+                }
+            }
             ViewMode::Output => {
                 self.scroll_output_up();
             }
@@ -558,10 +1518,24 @@ impl App
             ViewMode::Stack => {
                 self.navigate_stack_up();
             }
-            ViewMode::Timeline | ViewMode::Overview | ViewMode::Help => {
-                // Timeline auto-scrolls to bottom, no manual navigation needed
-                // Help view doesn't support navigation
+            ViewMode::Help => {
+                self.help_scroll = self.help_scroll.saturating_sub(1);
+            }
+            ViewMode::Timeline => {
+                self.scroll_timeline(-1);
+            }
+            ViewMode::Disassembly => {
+                self.disassembly.select_previous(self.debugger.as_mut());
+            }
+            ViewMode::Memory => {
+                self.scroll_memory(-1);
             }
+            ViewMode::History => {
+                let max = self.history.len().saturating_sub(1);
+                let i = self.history_state.selected().unwrap_or(0);
+                self.history_state.select(Some(if i == 0 { max } else { i - 1 }));
+            }
+            ViewMode::Overview => {}
         }
     }
 
@@ -600,14 +1574,25 @@ impl App
                     self.memory_regions_state.select(Some(next));
                 }
             }
+            ViewMode::Modules => {
+                let i = self.modules_state.selected().unwrap_or(0);
+                if let Ok(images) = self.debugger.modules() {
+                    let max = images.len().saturating_sub(1);
+                    if max == 0 {
+                        return;
+                    }
+                    let next = if i >= max { 0 } else { i + 1 };
+                    self.modules_state.select(Some(next));
+                }
+            }
             ViewMode::Output => {
                 self.scroll_output_down();
             }
             ViewMode::Source => {
                 // Check if we can scroll further
                 if let Some(ref file) = self.current_source_file {
-                    if let Some(lines) = self.source_cache.get(file) {
-                        let max_scroll = lines.len().saturating_sub(1);
+                    if let Some(source) = self.source_cache.get(file) {
+                        let max_scroll = source.lines.len().saturating_sub(1);
                         if self.source_scroll < max_scroll {
                             self.source_scroll += 1;
                         }
@@ -625,19 +1610,33 @@ impl App
             ViewMode::Stack => {
                 self.navigate_stack_down();
             }
-            ViewMode::Timeline | ViewMode::Overview | ViewMode::Help => {
-                // Timeline auto-scrolls to bottom, no manual navigation needed
-                // Help view doesn't support navigation
+            ViewMode::Help => {
+                // Clamped to the content by draw_help
+                self.help_scroll += 1;
+            }
+            ViewMode::Timeline => {
+                self.scroll_timeline(1);
             }
+            ViewMode::Disassembly => {
+                self.disassembly.select_next(self.debugger.as_mut());
+            }
+            ViewMode::Memory => {
+                self.scroll_memory(1);
+            }
+            ViewMode::History => {
+                let max = self.history.len().saturating_sub(1);
+                let i = self.history_state.selected().unwrap_or(0);
+                self.history_state.select(Some(if i >= max { 0 } else { i + 1 }));
+            }
+            ViewMode::Overview => {}
         }
     }
 
     /// Get the number of registers to display
     fn get_register_count(&self) -> usize
     {
-        if let Ok(regs) = self.debugger.read_registers() {
-            // Common registers (PC, SP, FP, Status) + general registers
-            4 + regs.general.len()
+        if let Ok(regs) = self.inspected_registers() {
+            ferros_core::types::register_display_order(regs.architecture()).len()
         } else {
             0
         }
@@ -647,12 +1646,14 @@ impl App
     pub fn tick(&mut self)
     {
         // Refresh thread list periodically, but not too frequently
-        // Mach thread ports are ephemeral and change on each refresh, so we only
-        // refresh every 2 seconds to avoid showing constantly changing thread IDs
+        // Thread IDs are stable across refreshes, but enumerating threads is a
+        // syscall per thread, so only refresh every 2 seconds
         if self.debugger.is_attached() {
             const THREAD_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
             if self.last_thread_refresh.elapsed() >= THREAD_REFRESH_INTERVAL {
-                let _ = self.debugger.refresh_threads();
+                if let Err(e) = self.debugger.refresh_threads() {
+                    self.observe_target_gone(&e);
+                }
                 self.last_thread_refresh = std::time::Instant::now();
             }
 
@@ -663,51 +1664,478 @@ impl App
             // This ensures symbols are loaded even if user isn't viewing stack
             if self.target_is_stopped {
                 self.refresh_stack_trace();
+                // A running target's memory changes under the dump; it is re-read once it stops
+                if self.view_mode == ViewMode::Memory {
+                    self.memory.refresh(self.debugger.as_ref());
+                }
+            } else if self.pins_live && self.last_pin_refresh.elapsed() >= PIN_LIVE_INTERVAL {
+                self.refresh_pins();
             }
+            self.probe_main_thread();
+        }
+
+        if let Some(warning) = self.transcript.as_ref().and_then(Transcript::take_size_warning) {
+            self.add_timeline_entry(TimelineEntryKind::Warning, warning.clone());
+            self.error_message = Some(warning);
         }
 
         // Auto-clear info messages after 3 seconds
-        if let Some(time) = self.info_message_time {
-            if time.elapsed().as_secs() >= 3 {
-                self.info_message = None;
-                self.info_message_time = None;
-            }
+        if let Some(time) = self.info_message_time
+            && time.elapsed().as_secs() >= 3
+        {
+            self.info_message = None;
+            self.info_message_time = None;
         }
     }
 
-    /// Consume an asynchronous debugger event from the core backend.
-    pub fn handle_debugger_event(&mut self, event: &DebuggerEvent)
+    /// Check a stop against the assertions, logging what it meant
+    ///
+    /// Returns `true` if the stop was an assertion breakpoint the target was
+    /// resumed past or killed at, so there is no stop to show.
+    fn check_assertions(&mut self, reason: StopReason, thread: Option<ThreadId>) -> bool
+    {
+        match self.assertions.on_stop(self.debugger.as_mut(), reason, thread) {
+            None => false,
+            Some(AssertionStop::Reached(assertion)) => {
+                self.add_timeline_entry(TimelineEntryKind::Diagnostic, format!("Assertion {assertion} reached"));
+                self.resume_after_assertion()
+            }
+            Some(AssertionStop::Violated(violation)) => {
+                if let Some((recorder, _)) = &mut self.session_summary {
+                    recorder.record_violation(&violation);
+                }
+                let mut message = violation.to_string();
+                if !violation.arguments.is_empty() {
+                    let _ = write!(message, "; {}", violation.arguments.join(", "));
+                }
+                self.push_timeline_entry(TimelineEntry::new(TimelineEntryKind::Error, message).with_thread(thread));
+                self.error_message = Some(violation.to_string());
+                if self.assertions.action() == ViolationAction::Kill {
+                    if let Err(e) = self.debugger.kill() {
+                        self.report_error("Failed to kill the target after an assertion failed", &e);
+                        return false;
+                    }
+                    self.target_is_stopped = false;
+                    return true;
+                }
+                self.resume_after_assertion()
+            }
+        }
+    }
+
+    /// Resume past an assertion breakpoint; if that fails, show the stop instead
+    fn resume_after_assertion(&mut self) -> bool
+    {
+        match self.debugger.resume() {
+            Ok(()) => true,
+            Err(e) => {
+                if !self.observe_target_gone(&e) {
+                    self.report_error("Failed to resume after an assertion", &e);
+                }
+                false
+            }
+        }
+    }
+
+    /// Consume an asynchronous debugger event from the core backend.
+    pub fn handle_debugger_event(&mut self, event: &DebuggerEvent)
+    {
+        self.apply_debugger_event(event, None, None);
+    }
+
+    /// Handle a debugger event taken off the backend channel at `received`
+    ///
+    /// Records the emission to ingestion latency for `:perf` and the timeline.
+    pub fn handle_debugger_envelope(&mut self, envelope: &EventEnvelope, received: std::time::Instant)
+    {
+        // Still queued from the process `:restart` killed
+        if self.target_started.is_some_and(|started| envelope.emitted < started) {
+            return;
+        }
+        let timing = EventTiming {
+            delivery: envelope.latency_at(received),
+            handler: envelope.handler_delay(),
+        };
+        self.event_latency.record(timing.delivery);
+        if let Some(handler) = timing.handler {
+            self.handler_latency.record(handler);
+        }
+        if let Some(transcript) = &self.transcript {
+            transcript.record_event(envelope);
+        }
+        self.apply_debugger_event(&envelope.event, Some(timing), None);
+    }
+
+    /// Handle an event that didn't come from the backend, labelled `tag` in the timeline
+    ///
+    /// Runs the same stop handling as a real event, but leaves out everything
+    /// that acts on the target or outlives the stop: session recording,
+    /// assertions, thread switches, stop notifications and checkpoints.
+    pub fn handle_synthetic_event(&mut self, event: &DebuggerEvent, tag: &str)
+    {
+        self.apply_debugger_event(event, None, Some(tag));
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn apply_debugger_event(&mut self, event: &DebuggerEvent, timing: Option<EventTiming>, synthetic: Option<&str>)
     {
+        if synthetic.is_none() {
+            if let Some((recorder, _)) = &mut self.session_summary {
+                recorder.record_event(event);
+            }
+
+            if let DebuggerEvent::TargetStopped { reason, thread } = event {
+                if self.check_assertions(*reason, *thread) {
+                    return;
+                }
+                self.last_stop = Some(event.clone());
+            }
+        }
+
         match event {
             DebuggerEvent::TargetStopped { reason, thread } => {
                 self.target_is_stopped = true;
                 self.last_stop_reason = *reason;
-                let mut message = format_stop_reason(*reason);
+                // Unwind the thread that faulted, not whichever was active
+                let crashed = crash::is_crash(*reason);
+                let faulting_thread = match reason {
+                    StopReason::StackOverflow { thread, .. } => Some(*thread),
+                    _ => *thread,
+                };
+                if crashed
+                    && synthetic.is_none()
+                    && let Some(faulting) = faulting_thread
+                {
+                    let _ = self.debugger.set_active_thread(faulting);
+                }
+                let panic = match synthetic {
+                    None => self.panic_breakpoints.on_stop(self.debugger.as_mut(), *reason, *thread),
+                    Some(_) => None,
+                };
+                let overflow = match (reason, faulting_thread) {
+                    (StopReason::BadAccess { address, .. }, Some(faulting)) => self.probable_overflow(*address, faulting),
+                    _ => None,
+                };
+                let mut message = panic
+                    .as_ref()
+                    .map_or_else(|| format_stop_reason(*reason), ToString::to_string);
                 if let Some(thread_id) = thread {
                     let _ = write!(message, " (thread {})", thread_id.raw());
                 }
+                if let Some(overflow) = overflow {
+                    let _ = write!(message, " — {overflow}");
+                }
+                if let Some(tag) = synthetic {
+                    message = format!("[{tag}] {message}");
+                }
                 self.record_stop_event(message.clone());
 
                 // Add to timeline
                 let timeline_kind = match reason {
-                    StopReason::Breakpoint(_) => TimelineEntryKind::BreakpointHit,
-                    StopReason::Signal(_) => TimelineEntryKind::Signal,
+                    _ if panic.is_some() => TimelineEntryKind::Panic,
+                    StopReason::Breakpoint(_) | StopReason::Watchpoint { .. } => TimelineEntryKind::BreakpointHit,
+                    StopReason::Signal(_) | StopReason::BadAccess { .. } | StopReason::StackOverflow { .. } => {
+                        TimelineEntryKind::Signal
+                    }
                     _ => TimelineEntryKind::Stop,
                 };
-                self.add_timeline_entry(timeline_kind, message);
+                let breakpoint = match reason {
+                    StopReason::Breakpoint(address) => self
+                        .cached_breakpoints
+                        .iter()
+                        .find(|bp| bp.address.value() == *address)
+                        .map(|bp| bp.id),
+                    StopReason::Watchpoint { address, .. } => self
+                        .cached_breakpoints
+                        .iter()
+                        .find(|bp| bp.kind == ferros_core::BreakpointKind::Watchpoint && bp.address.value() == *address)
+                        .map(|bp| bp.id),
+                    _ => None,
+                };
+                // Looked up now: a one-shot breakpoint is gone once the breakpoints are re-read
+                let actions = breakpoint
+                    .and_then(|id| self.cached_breakpoints.iter().find(|bp| bp.id == id))
+                    .map(|bp| bp.options.actions.clone())
+                    .unwrap_or_default();
+                self.push_timeline_entry(
+                    TimelineEntry::new(timeline_kind, message.clone())
+                        .with_timing(timing)
+                        .with_breakpoint(breakpoint)
+                        .with_thread(*thread),
+                );
+                // Nothing is left to unwind or read
+                if let StopReason::Exited(code) = reason
+                    && synthetic.is_none()
+                {
+                    self.observe_exit(*code, message);
+                    return;
+                }
 
+                // Read every thread's registers once; the views draw from them until the target runs
+                self.register_snapshot = RegisterSnapshot::capture(self.debugger.as_ref());
                 // Refresh stack trace when stopped
                 self.refresh_stack_trace();
+                self.refresh_disassembly();
+                if synthetic.is_none() && !self.pending_breakpoints.is_empty() {
+                    self.retry_pending_breakpoints();
+                }
+                self.announce_new_images();
+                if synthetic.is_none() {
+                    self.sync_panic_breakpoints();
+                }
+                // The user asked for a suspend or step and is already looking at the result
+                if synthetic.is_none()
+                    && !matches!(reason, StopReason::Suspended | StopReason::Running | StopReason::Step(_))
+                {
+                    let detail = self.stop_symbol();
+                    self.stop_emphasis.on_stop(message, detail, std::time::Instant::now());
+                }
                 self.refresh_breakpoints();
+                // Captured before pins are re-read, so the stop is still the newest entry
+                if synthetic.is_none()
+                    && let Some(id) = self.capture_checkpoint(*reason, faulting_thread)
+                    && let Some(entry) = self.timeline_log.back_mut()
+                {
+                    entry.checkpoint = Some(id);
+                }
+                self.refresh_pins();
+                if synthetic.is_none() {
+                    self.finish_range_checkpoint();
+                    if let Some(id) = breakpoint
+                        && !actions.is_empty()
+                    {
+                        self.run_breakpoint_actions(id, actions);
+                    }
+                }
+
+                if let Some(overflow) = self.stack_overflow_message() {
+                    self.add_timeline_entry(TimelineEntryKind::Signal, overflow.clone());
+                    self.error_message = Some(overflow);
+                }
+
+                self.crash_banner = None;
+                self.panic_report = panic;
+                if self.panic_report.is_some() && self.is_live() {
+                    let frames = self.live_stack();
+                    if let Some(frame) = crash_frame(&frames) {
+                        self.focus_crash(CrashSite {
+                            reason: *reason,
+                            thread: *thread,
+                            frame,
+                        });
+                    }
+                }
+                if crashed {
+                    let frames = self.live_stack();
+                    let site = CrashSite::locate(*reason, faulting_thread.or(self.debugger.active_thread()), &frames);
+                    self.crash_banner = site.map(|site| site.summary(&frames));
+                    if let StopReason::BadAccess { address, .. } = reason {
+                        let summary = self.crash_banner.take().unwrap_or_else(|| format_stop_reason(*reason));
+                        let mut report = format!("{summary} — {}", self.describe_fault_address(*address));
+                        if let Some(overflow) = overflow {
+                            let _ = write!(report, " — {overflow}");
+                        }
+                        self.add_timeline_entry(TimelineEntryKind::Signal, report.clone());
+                        self.crash_banner = Some(report);
+                    }
+                    if let Some(site) = site
+                        && self.is_live()
+                    {
+                        self.focus_crash(site);
+                    }
+                }
             }
             DebuggerEvent::TargetResumed => {
                 self.target_is_stopped = false;
+                self.register_snapshot.clear();
+                self.failures_since_resume = 0;
+                self.breakpoints_edited = false;
+                self.crash_banner = None;
+                self.panic_report = None;
                 self.last_stop_reason = StopReason::Running;
-                let message = "Target resumed execution".to_string();
+                let message = match synthetic {
+                    Some(tag) => format!("[{tag}] Target resumed execution"),
+                    None => "Target resumed execution".to_string(),
+                };
                 self.record_stop_event(message.clone());
-                self.add_timeline_entry(TimelineEntryKind::Resume, message);
+                self.push_timeline_entry(TimelineEntry::new(TimelineEntryKind::Resume, message).with_timing(timing));
+            }
+            DebuggerEvent::ImageLoaded { path, base } => {
+                // The stop that loaded it may already have listed it, with its debug info
+                let id = ImageId::from_parts(path, base.value());
+                if self.known_images.as_mut().is_some_and(|known| !known.insert(id)) {
+                    return;
+                }
+                self.push_timeline_entry(TimelineEntry::new(TimelineEntryKind::Image, event.describe()).with_timing(timing));
+            }
+            DebuggerEvent::ImageUnloaded { path, base } => {
+                if let Some(known) = &mut self.known_images {
+                    known.remove(&ImageId::from_parts(path, base.value()));
+                }
+                self.push_timeline_entry(TimelineEntry::new(TimelineEntryKind::Image, event.describe()).with_timing(timing));
+            }
+            DebuggerEvent::SymbolsLoaded { .. } => {
+                // Frames unwound while the images were loading have no names yet
+                if self.target_is_stopped && self.target_gone.is_none() {
+                    self.refresh_stack_trace();
+                    self.refresh_source_view();
+                    if !self.pending_breakpoints.is_empty() {
+                        self.retry_pending_breakpoints();
+                    }
+                }
+                if self.target_gone.is_none() {
+                    self.sync_panic_breakpoints();
+                }
+            }
+        }
+    }
+
+    /// Function and source line of the innermost frame, for the stop toast
+    fn stop_symbol(&self) -> Option<String>
+    {
+        let frame = self.cached_stack_trace.as_ref()?.first()?;
+        let function = frame.symbol.as_ref()?.display_name();
+        Some(match &frame.location {
+            Some(SourceLocation {
+                file, line: Some(line), ..
+            }) => {
+                let file = std::path::Path::new(file)
+                    .file_name()
+                    .map_or_else(|| file.clone(), |name| name.to_string_lossy().into_owned());
+                format!("{function} ({file}:{line})")
+            }
+            _ => function.to_string(),
+        })
+    }
+
+    /// Run the active thread one instruction, over a call, or out of the current function
+    fn step(&mut self, step: Step)
+    {
+        if !self.require_live("step") || !self.require_target("step") {
+            return;
+        }
+        if !self.debugger.is_attached() {
+            self.error_message = Some("Not attached to a process".to_string());
+            self.info_message = None;
+            return;
+        }
+        match commands::step(self.debugger.as_mut(), step) {
+            Ok(reason) => {
+                self.info_message = Some(format_stop_reason(reason));
+                self.info_message_time = Some(std::time::Instant::now());
+                self.refresh_stack_trace();
+                self.refresh_disassembly();
+            }
+            Err(e) => {
+                if !self.observe_target_gone(&e) {
+                    self.report_error("Failed to step", &e);
+                    self.info_message = None;
+                }
+            }
+        }
+    }
+
+    /// Resume the target, reporting why if it can't be
+    fn resume_target(&mut self)
+    {
+        if let Err(e) = self.debugger.resume()
+            && !self.observe_target_gone(&e)
+        {
+            self.report_error("Failed to resume", &e);
+            self.info_message = None;
+        }
+    }
+
+    /// Question to ask before resuming, if breakpoints set at this stop aren't all installed
+    ///
+    /// Asks when installs failed since the last resume, when breakpoints are
+    /// still waiting to resolve, or when breakpoints were requested but none
+    /// ended up installed.
+    #[must_use]
+    pub fn resume_confirmation(&self) -> Option<String>
+    {
+        if !self.confirm_resume {
+            return None;
+        }
+        let counts = self.debugger.breakpoint_counts();
+        let failed = self.failures_since_resume;
+        let plural = |n: usize| if n == 1 { "breakpoint" } else { "breakpoints" };
+
+        let mut problems = Vec::new();
+        if failed > 0 {
+            let reasons: Vec<String> = self
+                .breakpoint_failures
+                .iter()
+                .rev()
+                .take(failed)
+                .rev()
+                .map(|failure| format!("{}: {}", failure.location, failure.reason))
+                .collect();
+            problems.push(format!(
+                "{failed} {} failed to install ({})",
+                plural(failed),
+                reasons.join("; ")
+            ));
+        }
+        let pending = counts.requested + self.pending_breakpoints.len();
+        if pending > 0 {
+            problems.push(format!("{pending} pending"));
+        }
+        if problems.is_empty() && self.breakpoints_edited && counts.resolved == 0 {
+            problems.push("no breakpoints installed".to_string());
+        }
+        if problems.is_empty() {
+            return None;
+        }
+        Some(format!("{} — resume anyway? [y/N]", problems.join(", ")))
+    }
+
+    /// Install `request`, remembering why it failed so the resume prompt can name it
+    fn install_breakpoint(&mut self, request: BreakpointRequest) -> Result<BreakpointId, DebuggerError>
+    {
+        let capabilities = self.debugger.capabilities();
+        let (supported, what) = match request.kind {
+            BreakpointRequestKind::Watchpoint { .. } => (capabilities.watchpoints, "watchpoints"),
+            _ => (capabilities.breakpoints, "breakpoints"),
+        };
+        if !supported {
+            return Err(DebuggerError::InvalidArgument(format!("This backend doesn't support {what}")));
+        }
+        let location = match &request.kind {
+            BreakpointRequestKind::Location(location) => location.to_string(),
+            _ => request.address().map_or_else(String::new, |address| address.to_string()),
+        };
+        self.breakpoints_edited = true;
+        let result = self.debugger.add_breakpoint(request);
+        if let Err(e) = &result {
+            if self.breakpoint_failures.len() == MAX_BREAKPOINT_FAILURES {
+                self.breakpoint_failures.pop_front();
             }
+            self.breakpoint_failures.push_back(BreakpointFailure {
+                location,
+                reason: e.to_string(),
+            });
+            self.failures_since_resume += 1;
         }
+        result
+    }
+
+    /// Whether a popup (resume prompt, command palette, breakpoint editor, region diff, notes list, where summary, all stacks) has input focus
+    #[must_use]
+    pub fn modal_open(&self) -> bool
+    {
+        self.resume_prompt.is_some()
+            || self.command_palette_active
+            || self.breakpoint_editor.is_some()
+            || self.range_diffs.is_some()
+            || self.notes_list.is_some()
+            || self.memory_search.is_some()
+            || self.permission_advice_open
+            || self.where_summary.is_some()
+            || self.all_stacks.is_some()
+            || self.register_edit.is_some()
+            || self.stdin_input.is_some()
+            || self.process_picker.is_some()
     }
 
     fn record_stop_event(&mut self, message: String)
@@ -722,22 +2150,133 @@ impl App
     #[must_use]
     pub fn status_message(&self) -> String
     {
+        if let Some(code) = self.target_exit {
+            return format_stop_reason(StopReason::Exited(code));
+        }
+        if let Some(pid) = self.target_gone {
+            return format!("Process {pid} is gone");
+        }
         if !self.debugger.is_attached() {
             return "Not attached to a process".to_string();
         }
 
         if self.target_is_stopped {
-            format_stop_reason(self.last_stop_reason)
+            self.stop_reason_message()
         } else {
             "Process is running".to_string()
         }
     }
 
+    /// Latency percentiles of recent debugger events, shown by `:perf`
+    #[must_use]
+    pub fn perf_summary(&self) -> String
+    {
+        let (Some(p50), Some(p95), Some(max)) = (
+            self.event_latency.percentile(50),
+            self.event_latency.percentile(95),
+            self.event_latency.max(),
+        ) else {
+            return "No debugger events received yet".to_string();
+        };
+        let mut summary = format!(
+            "Event latency over {} events: p50 {}",
+            self.event_latency.len(),
+            format_latency(p50)
+        );
+        let _ = write!(summary, ", p95 {}, max {}", format_latency(p95), format_latency(max));
+        if let (Some(p50), Some(p95)) = (self.handler_latency.percentile(50), self.handler_latency.percentile(95)) {
+            let _ = write!(
+                summary,
+                " | in handler: p50 {}, p95 {}",
+                format_latency(p50),
+                format_latency(p95)
+            );
+        }
+        summary
+    }
+
+    /// The stop reason as the Overview and status line show it: bad accesses
+    /// and stack overflows get their explanation, the rest are formatted as is
+    #[must_use]
+    pub fn stop_reason_message(&self) -> String
+    {
+        self.panic_report
+            .as_ref()
+            .map(ToString::to_string)
+            .or_else(|| self.bad_access_message())
+            .or_else(|| self.stack_overflow_message())
+            .unwrap_or_else(|| format_stop_reason(self.last_stop_reason))
+    }
+
+    /// The crashing function and the region the address faulted in, e.g.
+    /// ``EXC_BAD_ACCESS (read) at 0x10 — in function `demo::foo` (foo.rs:42) — null pointer dereference``.
+    ///
+    /// `None` unless the target is stopped with [`StopReason::BadAccess`].
+    #[must_use]
+    pub fn bad_access_message(&self) -> Option<String>
+    {
+        match self.last_stop_reason {
+            StopReason::BadAccess { .. } => self.crash_banner.clone(),
+            _ => None,
+        }
+    }
+
+    /// What a faulting address points into, with the function or data symbol it lands in
+    fn describe_fault_address(&self, address: u64) -> String
+    {
+        let regions = self.debugger.get_memory_regions().unwrap_or_default();
+        let region = FaultRegion::classify(address, &regions);
+        if region == FaultRegion::Null {
+            return region.to_string();
+        }
+        let mut description = format!("address {region}");
+        if let Some(symbol) = self.debugger.function_name(Address::from(address)) {
+            let _ = write!(description, " at `{}`", symbol.display_name());
+        }
+        description
+    }
+
+    /// A bad access at `address` by `thread` just below that thread's stack,
+    /// which the backend didn't already report as a stack overflow
+    fn probable_overflow(&self, address: u64, thread: ThreadId) -> Option<ProbableOverflow>
+    {
+        let sp = self.debugger.read_registers_for(thread).ok()?.sp.value();
+        let bounds = self.debugger.thread_stack_bounds(thread).ok()?;
+        ProbableOverflow::detect(address, sp, bounds)
+    }
+
+    /// Explanation of a stack overflow stop that names the recursive function.
+    ///
+    /// `None` unless the target is stopped with [`StopReason::StackOverflow`].
+    #[must_use]
+    pub fn stack_overflow_message(&self) -> Option<String>
+    {
+        let StopReason::StackOverflow { thread, .. } = self.last_stop_reason else {
+            return None;
+        };
+        let thread = thread.raw();
+        Some(match self.recursion_cycles.first() {
+            Some(cycle) => {
+                let function = cycle.function.as_deref().unwrap_or("<unknown>");
+                let bound = if cycle.truncated { "at least " } else { "~" };
+                let extent = format!("{bound}{} times (frame #{})", cycle.repeats, cycle.first_frame);
+                format!("Stack overflow on thread {thread}: `{function}` recursed {extent}")
+            }
+            None => format!("Stack overflow on thread {thread}: no repeating frames found, check for large stack frames"),
+        })
+    }
+
     /// Append a captured process output line to the buffer.
-    pub fn push_process_output(&mut self, source: ProcessOutputSource, line: &str)
+    ///
+    /// `at` is the line's transcript timestamp, when one is being recorded.
+    pub fn push_process_output(&mut self, source: ProcessOutputSource, line: &str, at: Option<std::time::Duration>)
     {
         let cleaned = line.trim_end_matches('\r').to_string();
-        self.process_output.push_back(ProcessOutputLine { source, text: cleaned });
+        self.process_output.push_back(ProcessOutputLine {
+            source,
+            text: cleaned,
+            at,
+        });
         if self.process_output.len() > MAX_PROCESS_OUTPUT_LINES {
             self.process_output.pop_front();
         }
@@ -748,6 +2287,91 @@ impl App
         }
     }
 
+    fn handle_stdin_input(&mut self, key_event: crossterm::event::KeyEvent)
+    {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        let Some(input) = self.stdin_input.as_mut() else {
+            return;
+        };
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Enter => {
+                let mut line = std::mem::take(input);
+                line.push('\n');
+                self.send_stdin(&line);
+            }
+            // Like a terminal, Ctrl+D sends what's typed without a newline, then EOF
+            KeyCode::Char('d') if control => {
+                let rest = std::mem::take(input);
+                self.stdin_input = None;
+                if !rest.is_empty() && !self.send_stdin(&rest) {
+                    return;
+                }
+                self.process_stdin = None;
+                self.info_message = Some("Closed the target's stdin".to_string());
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            KeyCode::Esc => {
+                self.stdin_input = None;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if !control => {
+                input.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// Write `text` to the target's stdin and echo it in the Output view
+    ///
+    /// Returns whether the writer took it; when it's gone the target closed
+    /// its end, and input mode is left.
+    fn send_stdin(&mut self, text: &str) -> bool
+    {
+        let sent = self
+            .process_stdin
+            .as_ref()
+            .is_some_and(|stdin| stdin.send(text.as_bytes().to_vec()).is_ok());
+        if !sent {
+            self.process_stdin = None;
+            self.stdin_input = None;
+            self.error_message = Some("The target's stdin is closed".to_string());
+            return false;
+        }
+
+        // Recorded lines carry their transcript timestamp; a partial one is echoed as is
+        let records = self
+            .transcript
+            .as_ref()
+            .map(|transcript| transcript.record_stream(TranscriptStream::Stdin, text.as_bytes()))
+            .unwrap_or_default();
+        if records.is_empty() {
+            self.push_process_output(ProcessOutputSource::Stdin, text.trim_end_matches('\n'), None);
+        }
+        for record in records {
+            self.push_process_output(ProcessOutputSource::Stdin, &record.text, Some(record.elapsed()));
+        }
+        true
+    }
+
+    fn toggle_output_timestamps(&mut self)
+    {
+        if self.transcript.is_none() {
+            self.error_message =
+                Some("Output timestamps come from the transcript; start ferros with --transcript <path>".to_string());
+            return;
+        }
+        self.output_timestamps = !self.output_timestamps;
+        self.info_message = Some(format!(
+            "Output timestamps {}",
+            if self.output_timestamps { "on" } else { "off" }
+        ));
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
     fn scroll_output_up(&mut self)
     {
         if self.process_output.is_empty() {
@@ -766,582 +2390,6306 @@ impl App
         }
     }
 
-    /// Refresh the cached stack trace
-    pub fn refresh_stack_trace(&mut self)
+    /// Timeline entries shown under the current filter, as indices into the log
+    #[must_use]
+    pub fn visible_timeline(&self) -> Vec<usize>
     {
-        if self.debugger.is_attached()
-            && self.target_is_stopped
-            && let Ok(frames) = self.debugger.stack_trace(64)
-        {
-            self.cached_stack_trace = Some(frames);
-            if let Some(ref frames) = self.cached_stack_trace
-                && !frames.is_empty()
-            {
-                self.selected_frame_id = Some(frames[0].id);
-            }
-        }
+        timeline::visible_entries(&self.timeline_log, self.timeline.filter)
     }
 
-    /// Refresh the cached breakpoints list
-    pub fn refresh_breakpoints(&mut self)
+    /// Rows `PageUp`/`PageDown` move in the Timeline
+    fn timeline_page(&self) -> isize
     {
-        self.cached_breakpoints = self.debugger.breakpoints();
+        isize::try_from(self.timeline.page_rows.max(1)).unwrap_or(1)
+    }
 
-        // Resolve breakpoint addresses to source locations for UI indicators
-        // This allows us to show breakpoint markers in the source view
-        // Use stack trace frames which already have symbolication
-        self.breakpoint_locations.clear();
-        for bp in &self.cached_breakpoints {
-            if bp.enabled && bp.state == ferros_core::BreakpointState::Resolved {
-                // Try to find source location from stack trace frames
-                let location = self.cached_stack_trace
-                    .as_ref()
-                    .and_then(|frames| {
-                        frames
-                            .iter()
-                            .find(|frame| frame.pc == bp.address)
-                            .and_then(|frame| frame.location.clone())
-                    });
-                self.breakpoint_locations.insert(bp.address, location);
+    fn scroll_timeline(&mut self, delta: isize)
+    {
+        let selected = timeline::scroll(&self.visible_timeline(), self.timeline.selected, delta);
+        self.timeline.select(selected);
+    }
+
+    fn handle_timeline_search_input(&mut self, key_event: crossterm::event::KeyEvent)
+    {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        let Some(input) = self.timeline.search_input.as_mut() else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Enter => {
+                self.timeline.query = std::mem::take(input);
+                self.timeline.search_input = None;
+                if !self.timeline.query.is_empty() {
+                    self.step_timeline_match(true);
+                }
             }
+            KeyCode::Esc => {
+                self.timeline.search_input = None;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.push(c);
+            }
+            _ => {}
         }
     }
 
-    /// Refresh the source view based on current frame
-    pub fn refresh_source_view(&mut self)
+    /// `n`/`N` in the Timeline: select the next or previous entry matching the search
+    fn step_timeline_match(&mut self, forward: bool)
     {
-        // Use the selected frame from stack view, or fall back to first frame
-        let selected_idx = self.stack_frames_state.selected().unwrap_or(0);
-        
-        // Try to find a frame with source location (start from selected, then try all frames)
-        let frame_with_source = if let Some(ref frames) = self.cached_stack_trace {
-            // First try the selected frame
-            frames.get(selected_idx)
-                .and_then(|f| f.location.as_ref().map(|_| f))
-                // If that doesn't work, try the first frame
-                .or_else(|| frames.first().and_then(|f| f.location.as_ref().map(|_| f)))
-                // If that doesn't work, search all frames for one with source
-                .or_else(|| {
-                    frames.iter().find(|f| f.location.is_some())
-                })
-        } else {
-            None
-        };
-        
-        if let Some(frame) = frame_with_source
-            && let Some(ref location) = frame.location
-        {
-            let file_path = &location.file;
-            
-            // Try multiple path resolution strategies
-            let resolved_path = if std::path::Path::new(file_path).exists() {
-                // Path exists as-is
-                Some(file_path.clone())
-            } else if file_path.starts_with('/') {
-                // Absolute path that doesn't exist - try to find it relative to workspace
-                // This handles cases where DWARF has absolute paths from build machine
-                let workspace_root = std::env::current_dir().ok();
-                let mut found_path = None;
-                if let Some(root) = workspace_root {
-                    let path = std::path::Path::new(file_path);
-                    
-                    // Strategy 1: Try to find the file by matching the workspace name in the path
-                    // e.g., if DWARF has /some/absolute/path/ferros/crates/ferros/examples/test_target.rs
-                    // and workspace is /Users/.../ferros, try to extract the relative part
-                    let path_str = path.to_string_lossy();
-                    if let Some(workspace_name) = root.file_name().and_then(|n| n.to_str()) {
-                        // Try to find the workspace name in the absolute path
-                        if let Some(pos) = path_str.find(workspace_name) {
-                            // Extract everything after the workspace name
-                            let relative_part = &path_str[pos + workspace_name.len()..];
-                            let test_path = root.join(relative_part.trim_start_matches('/'));
-                            if test_path.exists() {
-                                if let Some(str_path) = test_path.to_str() {
-                                    found_path = Some(str_path.to_string());
-                                }
-                            }
-                        }
-                    }
-                    
-                    // Strategy 2: If that didn't work, try searching by filename in common locations
-                    if found_path.is_none() {
-                        if let Some(file_name) = path.file_name() {
-                            let search_paths = vec![
-                                root.join(file_name),
-                                root.join("crates").join(file_name),
-                                root.join("target").join("debug").join(file_name),
-                            ];
-                            
-                            for search_path in search_paths {
-                                if search_path.exists() {
-                                    if let Some(str_path) = search_path.to_str() {
-                                        found_path = Some(str_path.to_string());
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                found_path
-            } else {
-                // Relative path - try resolving from current directory
-                let current_dir = std::env::current_dir().ok();
-                if let Some(dir) = current_dir {
-                    let resolved = dir.join(file_path);
-                    if resolved.exists() {
-                        resolved.to_str().map(|s| s.to_string())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            };
-            
-            let path_to_load = resolved_path.as_ref().unwrap_or(file_path);
-            
-            if !self.source_cache.contains_key(path_to_load) {
-                // Try to load the source file
-                match std::fs::read_to_string(path_to_load) {
-                    Ok(content) => {
-                        let lines: Vec<String> = content.lines().map(str::to_string).collect();
-                        self.source_cache.insert(path_to_load.clone(), lines);
-                        // Also cache under original path for lookup
-                        if path_to_load != file_path {
-                            if let Some(cached_lines) = self.source_cache.get(path_to_load) {
-                                self.source_cache.insert(file_path.clone(), cached_lines.clone());
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        // Store error info for display - show both original and attempted paths
-                        let error_detail = if resolved_path.is_some() && path_to_load != file_path {
-                            format!("DWARF path: {}\nTried: {}\nError: {}\n\nTip: Source paths in DWARF may be absolute paths from build machine.", file_path, path_to_load, e)
-                        } else {
-                            format!("Path: {}\nError: {}\n\nTip: Check if the file exists at this path.", path_to_load, e)
-                        };
-                        self.error_message = Some(format!("Failed to load source file:\n{}", error_detail));
-                        self.info_message = None;
-                    }
-                }
-            }
-            
-            // Use resolved path if available, otherwise original
-            let display_path = resolved_path.as_ref().unwrap_or(file_path);
-            self.current_source_file = Some(display_path.clone());
-            if let Some(line) = location.line {
-                let line_usize = line as usize;
-                self.source_scroll = line_usize.saturating_sub(10).max(0);
-                self.source_selected_line = Some(line_usize.saturating_sub(1)); // Line numbers are 1-based, array is 0-based
-            }
+        if self.timeline.query.is_empty() {
+            self.error_message = Some("No search; press / to search the timeline".to_string());
+            return;
         }
+        let matches = timeline::matching_entries(&self.timeline_log, &self.visible_timeline(), &self.timeline.query);
+        let Some(entry) = timeline::step_match(&matches, self.timeline.selected, forward) else {
+            self.error_message = Some(format!(
+                "No {} entries match '{}'",
+                self.timeline.filter.label(),
+                self.timeline.query
+            ));
+            return;
+        };
+        self.timeline.select(Some(entry));
+        let position = matches.iter().position(|&index| index == entry).unwrap_or(0) + 1;
+        self.info_message = Some(format!("Match {position}/{} for '{}'", matches.len(), self.timeline.query));
+        self.info_message_time = Some(std::time::Instant::now());
+        self.error_message = None;
     }
 
-    /// Navigate up in stack view
-    fn navigate_stack_up(&mut self)
+    /// Enter in the Timeline: open what the selected (or newest) entry refers to
+    ///
+    /// A breakpoint hit focuses its breakpoint; a stop with a checkpoint opens
+    /// that snapshot's stack; any other stop on a known thread highlights it.
+    fn open_timeline_entry(&mut self)
     {
-        if let Some(ref frames) = self.cached_stack_trace {
-            let current_idx = self.stack_frames_state.selected().unwrap_or(0);
-            if current_idx > 0 {
-                self.stack_frames_state.select(Some(current_idx - 1));
-                if let Some(frame) = frames.get(current_idx - 1) {
-                    self.selected_frame_id = Some(frame.id);
-                }
-                // Refresh source view when frame selection changes
-                self.refresh_source_view();
+        let Some(entry) = self
+            .timeline
+            .selected
+            .or_else(|| self.visible_timeline().last().copied())
+            .and_then(|index| self.timeline_log.get(index))
+        else {
+            return;
+        };
+        let (breakpoint, checkpoint, thread) = (entry.breakpoint, entry.checkpoint, entry.thread);
+
+        if let Some(id) = breakpoint {
+            self.focus_breakpoint(id);
+        } else if let Some(id) = checkpoint {
+            if !self.history.seek(id) {
+                self.error_message = Some("The checkpoint for this stop has been dropped".to_string());
+                return;
             }
+            self.view_mode = ViewMode::Stack;
+            self.show_history_position();
+        } else if let Some(thread) = thread {
+            let Some(row) = self
+                .debugger
+                .threads()
+                .ok()
+                .and_then(|threads| threads.iter().position(|&t| t == thread))
+            else {
+                self.error_message = Some(format!("Thread {} has exited", thread.raw()));
+                return;
+            };
+            self.view_mode = ViewMode::Threads;
+            self.threads_state.select(Some(row));
+        } else {
+            self.error_message = Some("Nothing to open for this entry".to_string());
         }
     }
 
-    /// Navigate down in stack view
-    fn navigate_stack_down(&mut self)
+    /// Show breakpoint `id` highlighted in the Source view's breakpoint list
+    fn focus_breakpoint(&mut self, id: BreakpointId)
     {
-        if let Some(ref frames) = self.cached_stack_trace {
-            let current_idx = self.stack_frames_state.selected().unwrap_or(0);
-            let max = frames.len().saturating_sub(1);
-            if current_idx < max {
-                self.stack_frames_state.select(Some(current_idx + 1));
-                if let Some(frame) = frames.get(current_idx + 1) {
-                    self.selected_frame_id = Some(frame.id);
-                }
-                // Refresh source view when frame selection changes
-                self.refresh_source_view();
-            }
+        let Some(index) = self.cached_breakpoints.iter().position(|bp| bp.id == id) else {
+            self.error_message = Some(format!("Breakpoint {} no longer exists", id.raw()));
+            return;
+        };
+        let bp = &self.cached_breakpoints[index];
+        let mut message = format!("Breakpoint {} at {} (hit {} times)", id.raw(), bp.address, bp.hit_count);
+        for note in self.notes.for_breakpoint(bp.address.value()) {
+            let _ = write!(message, " {NOTE_MARKER} {}", note.text);
         }
+        self.info_message = Some(message);
+        self.info_message_time = Some(std::time::Instant::now());
+        self.breakpoints_state.select(Some(index));
+        self.view_mode = ViewMode::Source;
+        self.refresh_source_view();
     }
 
-    /// Find address for a given source file and line number.
-    /// This searches through known addresses to find one that matches the source location.
-    fn find_address_for_source_line(&self, file: &str, line: usize) -> Option<Address>
+    /// Refresh the cached stack trace
+    ///
+    /// In history mode this shows the inspected stop's stack instead.
+    pub fn refresh_stack_trace(&mut self)
     {
-        // First, try to find in cached stack trace frames
-        if let Some(ref frames) = self.cached_stack_trace {
-            for frame in frames {
-                if let Some(ref location) = frame.location {
-                    if location.file == file && location.line == Some(line as u32) {
-                        return Some(frame.pc);
-                    }
-                }
+        if let Some(checkpoint) = self.history.current() {
+            self.selected_frame_id = checkpoint.frames.first().map(|frame| frame.id);
+            self.cached_stack_trace = Some(checkpoint.frames.clone());
+            self.unwind_diagnostics.clear();
+            self.recursion_cycles.clear();
+            return;
+        }
+        if self.debugger.is_attached()
+            && self.target_is_stopped
+            && let Ok(trace) = self
+                .debugger
+                .stack_trace_with_options(self.unwind_options().with_diagnostics())
+        {
+            self.cached_stack_trace = Some(trace.frames);
+            self.unwind_diagnostics = trace.diagnostics;
+            self.recursion_cycles = trace.recursion;
+            if let Some(ref frames) = self.cached_stack_trace
+                && !frames.is_empty()
+            {
+                self.selected_frame_id = Some(frames[0].id);
             }
         }
-        
-        // If not found in frames, we could iterate through breakpoints or use symbolication
-        // For now, return None and let the caller fall back to frame PC
-        None
     }
 
-    /// Toggle breakpoint at the given address
-    fn toggle_breakpoint_at_address(&mut self, address: Address)
+    /// Call stack of the live target's active thread
+    fn live_stack(&mut self) -> Vec<StackFrame>
     {
-        // Check if breakpoint already exists
-        let existing_bp = self.cached_breakpoints.iter().find(|bp| bp.address == address).cloned();
-
-        if let Some(bp) = existing_bp {
-            // Toggle existing breakpoint
-            let was_enabled = bp.enabled;
-            if let Err(e) = self.debugger.toggle_breakpoint(bp.id) {
-                self.error_message = Some(format!("Failed to toggle breakpoint: {e}"));
-                self.info_message = None;
-            } else {
-                self.refresh_breakpoints();
-                let action = if was_enabled { "Disabled" } else { "Enabled" };
-                let message = format!("{} breakpoint #{} at {}", action, bp.id.raw(), address);
-                self.info_message = Some(message.clone());
-                self.info_message_time = Some(std::time::Instant::now());
-                self.error_message = None;
-                self.add_timeline_entry(TimelineEntryKind::BreakpointHit, format!("Toggled breakpoint at {address}"));
-            }
+        // In history mode the cached stack is the inspected stop's, not the live one
+        if self.is_live() {
+            self.cached_stack_trace.clone().unwrap_or_default()
         } else {
-            // Add new breakpoint
-            if let Err(e) = self
-                .debugger
-                .add_breakpoint(ferros_core::BreakpointRequest::Software { address })
-            {
-                self.error_message = Some(format!("Failed to add breakpoint: {e}"));
-                self.info_message = None;
-            } else {
-                self.refresh_breakpoints();
-                // Find the newly created breakpoint to show its type and ID
-                let new_bp = self.cached_breakpoints.iter().find(|bp| bp.address == address).cloned();
+            self.debugger
+                .stack_trace_with_options(self.unwind_options())
+                .map(|trace| trace.frames)
+                .unwrap_or_default()
+        }
+    }
 
-                if let Some(bp) = new_bp {
-                    let kind_str = match bp.kind {
-                        ferros_core::BreakpointKind::Software => "software",
-                        ferros_core::BreakpointKind::Hardware => "hardware",
-                        ferros_core::BreakpointKind::Watchpoint => "watchpoint",
-                    };
-                    let message = format!("Added {} breakpoint #{} at {}", kind_str, bp.id.raw(), address);
-                    self.info_message = Some(message.clone());
-                    self.info_message_time = Some(std::time::Instant::now());
-                    self.error_message = None;
-                    self.add_timeline_entry(TimelineEntryKind::BreakpointHit, message);
-                }
-            }
+    /// Options for unwinding the active thread, with async frames after `:async-stacks on`
+    fn unwind_options(&self) -> UnwindOptions
+    {
+        let options = UnwindOptions::new(self.max_stack_frames);
+        if self.async_stacks {
+            options.with_async_frames()
+        } else {
+            options
         }
     }
 
-    /// Open breakpoint editor
-    fn open_breakpoint_editor(&mut self, existing_id: Option<BreakpointId>)
+    /// Add a timeline entry for each image the symbol cache loaded since the last stop
+    ///
+    /// The images found at the first stop are summed up in one entry, since
+    /// attaching loads every library at once; later ones are lazily loaded
+    /// dylibs and are listed one by one.
+    fn announce_new_images(&mut self)
     {
-        if let Some(id) = existing_id {
-            if let Ok(info) = self.debugger.breakpoint_info(id) {
-                self.breakpoint_editor = Some(BreakpointEditorState {
-                    address_input: format!("{}", info.address),
-                    kind_input: match info.kind {
-                        ferros_core::BreakpointKind::Software => "software".to_string(),
-                        ferros_core::BreakpointKind::Hardware => "hardware".to_string(),
-                        ferros_core::BreakpointKind::Watchpoint => "watchpoint".to_string(),
-                    },
-                    watch_length_input: info.watch_length.map(|l| l.to_string()).unwrap_or_default(),
-                    watch_access_input: info
-                        .watch_access
-                        .map(|a| match a {
-                            ferros_core::WatchpointAccess::Read => "read".to_string(),
-                            ferros_core::WatchpointAccess::Write => "write".to_string(),
-                            ferros_core::WatchpointAccess::ReadWrite => "readwrite".to_string(),
-                        })
-                        .unwrap_or_default(),
-                    editing_existing: Some(id),
-                });
+        let Ok(images) = self.debugger.modules() else {
+            return;
+        };
+        let new: Vec<_> = if let Some(known) = &mut self.known_images {
+            let new: Vec<_> = images.into_iter().filter(|image| known.insert(image.id)).collect();
+            for image in &new {
+                self.add_timeline_entry(
+                    TimelineEntryKind::Image,
+                    format!(
+                        "Loaded {} at {} ({})",
+                        image.path.display(),
+                        image.load_address,
+                        image.debug_level
+                    ),
+                );
             }
+            new
         } else {
-            self.breakpoint_editor = Some(BreakpointEditorState {
-                address_input: String::new(),
-                kind_input: "software".to_string(),
-                watch_length_input: String::new(),
-                watch_access_input: String::new(),
-                editing_existing: None,
-            });
+            if images.is_empty() {
+                return;
+            }
+            self.known_images = Some(images.iter().map(|image| image.id).collect());
+            self.add_timeline_entry(
+                TimelineEntryKind::Image,
+                format!("{} images loaded; :modules lists them", images.len()),
+            );
+            images
+        };
+        // A dSYM from another build would misplace every line, so it's ignored; say so
+        for image in new {
+            if let Some(mismatch) = image.dsym_mismatch {
+                self.add_timeline_entry(
+                    TimelineEntryKind::Error,
+                    format!("Ignored the dSYM of {}: {mismatch}", image.path.display()),
+                );
+            }
         }
     }
 
-    /// Handle input in breakpoint editor
-    fn handle_breakpoint_editor_input(&mut self, key_event: crossterm::event::KeyEvent) -> bool
+    /// Capture the current stop of `thread` (else the active thread) into the history ring, returning its checkpoint id
+    fn capture_checkpoint(&mut self, reason: StopReason, thread: Option<ThreadId>) -> Option<u64>
     {
-        use crossterm::event::{KeyCode, KeyModifiers};
-
-        if let Some(ref mut editor) = self.breakpoint_editor {
-            match key_event.code {
-                KeyCode::Enter => {
-                    // Apply breakpoint
-                    self.apply_breakpoint_editor();
-                    return false;
-                }
-                KeyCode::Esc => {
-                    self.breakpoint_editor = None;
-                    return false;
-                }
-                KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
-                    editor.address_input.push(c);
-                }
-                KeyCode::Backspace => {
-                    editor.address_input.pop();
-                }
-                _ => {}
+        // The active thread was just unwound; other threads are unwound only as deep as recorded
+        let active = self.debugger.active_thread();
+        let frames = match thread {
+            Some(id) if thread != active => self.debugger.stack_trace_for(id, STOP_RECORD_FRAMES).unwrap_or_default(),
+            _ => self.live_stack(),
+        };
+        match self.history.capture(self.debugger.as_ref(), reason, thread, frames) {
+            Ok(checkpoint) => Some(checkpoint.id),
+            Err(e) => {
+                self.add_timeline_entry(TimelineEntryKind::Error, format!("Failed to capture checkpoint: {e}"));
+                None
             }
         }
-        false
     }
 
-    /// Apply breakpoint editor changes
-    fn apply_breakpoint_editor(&mut self)
+    /// Inspect the stop selected in the History view and open `view` on it
+    fn inspect_selected_stop(&mut self, view: ViewMode)
     {
-        if let Some(editor) = self.breakpoint_editor.take() {
-            // Parse address
-            if let Ok(addr_value) = u64::from_str_radix(editor.address_input.trim_start_matches("0x"), 16) {
-                let address = Address::from(addr_value);
-
-                let request = match editor.kind_input.as_str() {
-                    "hardware" => ferros_core::BreakpointRequest::Hardware { address },
-                    "watchpoint" => {
-                        let length = editor.watch_length_input.parse().unwrap_or(8);
-                        let access = match editor.watch_access_input.as_str() {
-                            "read" => ferros_core::WatchpointAccess::Read,
-                            "write" => ferros_core::WatchpointAccess::Write,
-                            _ => ferros_core::WatchpointAccess::ReadWrite,
-                        };
-                        ferros_core::BreakpointRequest::Watchpoint { address, length, access }
-                    }
-                    _ => ferros_core::BreakpointRequest::Software { address },
-                };
+        let row = self.history_state.selected().unwrap_or(0);
+        let Some(id) = self.history.iter().rev().nth(row).map(|checkpoint| checkpoint.id) else {
+            self.error_message = Some("No stops recorded yet".to_string());
+            return;
+        };
+        // The newest stop is the live one
+        self.history.seek(id);
+        self.view_mode = view;
+        self.show_history_position();
+    }
 
-                if let Some(existing_id) = editor.editing_existing {
-                    // Remove old and add new
-                    let _ = self.debugger.remove_breakpoint(existing_id);
-                }
+    /// Whether the views show the live target rather than a past stop
+    #[must_use]
+    pub fn is_live(&self) -> bool
+    {
+        self.history.is_live()
+    }
 
-                if let Err(e) = self.debugger.add_breakpoint(request) {
-                    self.error_message = Some(format!("Failed to add breakpoint: {e}"));
-                } else {
-                    self.refresh_breakpoints();
-                    self.add_timeline_entry(TimelineEntryKind::BreakpointHit, format!("Breakpoint added at {address}"));
-                }
-            } else {
-                self.error_message = Some("Invalid address format".to_string());
-            }
+    /// Registers shown in the inspection views: the inspected stop's in history mode
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the live registers can't be read.
+    pub fn inspected_registers(&self) -> ferros_core::Result<Registers>
+    {
+        match self.history.current() {
+            Some(checkpoint) => Ok(checkpoint.registers.clone()),
+            None => self.active_registers(),
         }
     }
 
-    /// Handle input in command palette
-    fn handle_command_palette_input(&mut self, key_event: crossterm::event::KeyEvent) -> bool
+    /// Registers of `thread` at the current stop, if the snapshot has them
+    ///
+    /// `None` while the target runs, or for a thread the snapshot doesn't cover.
+    #[must_use]
+    pub fn cached_registers_for(&self, thread: ThreadId) -> Option<&Registers>
     {
-        use crossterm::event::{KeyCode, KeyModifiers};
+        self.register_snapshot.get(thread).filter(|_| self.target_is_stopped)
+    }
 
-        match key_event.code {
-            KeyCode::Enter => {
-                self.execute_command();
-                return false;
-            }
-            KeyCode::Esc => {
-                self.command_palette_active = false;
-                self.command_input.clear();
-                return false;
-            }
-            KeyCode::Up => {
-                // Navigate command history
-                if let Some(ref mut idx) = self.command_history_index {
-                    if *idx > 0 {
-                        *idx -= 1;
-                        if let Some(cmd) = self.command_history.get(*idx) {
-                            self.command_input = cmd.clone();
-                        }
-                    }
-                } else if !self.command_history.is_empty() {
-                    self.command_history_index = Some(self.command_history.len() - 1);
-                    if let Some(cmd) = self.command_history.back() {
-                        self.command_input = cmd.clone();
-                    }
-                }
-            }
-            KeyCode::Down => {
-                // Navigate command history forward
-                if let Some(ref mut idx) = self.command_history_index {
-                    if *idx < self.command_history.len().saturating_sub(1) {
-                        *idx += 1;
-                        if let Some(cmd) = self.command_history.get(*idx) {
-                            self.command_input = cmd.clone();
-                        }
-                    } else {
-                        self.command_history_index = None;
-                        self.command_input.clear();
-                    }
-                }
-            }
-            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.command_input.push(c);
-                self.command_history_index = None;
-            }
-            KeyCode::Backspace => {
-                self.command_input.pop();
-                self.command_history_index = None;
-            }
-            _ => {}
+    /// Registers of `thread`, from the snapshot while stopped, else read live
+    fn registers_for(&self, thread: ThreadId) -> ferros_core::Result<Registers>
+    {
+        match self.cached_registers_for(thread) {
+            Some(registers) => Ok(registers.clone()),
+            None => self.debugger.read_registers_for(thread),
+        }
+    }
+
+    /// Registers of the live target's active thread, from the snapshot while stopped
+    fn active_registers(&self) -> ferros_core::Result<Registers>
+    {
+        match self.debugger.active_thread() {
+            Some(thread) => self.registers_for(thread),
+            None => self.debugger.read_registers(),
+        }
+    }
+
+    /// Show `err` in the error banner after `context`.
+    ///
+    /// Permission advice attached to `err` is kept for the details popup.
+    pub fn report_error(&mut self, context: &str, err: &DebuggerError)
+    {
+        self.error_message = Some(format!("{context}: {err}"));
+        self.permission_advice = err.permission_advice().cloned();
+    }
+
+    /// Refuse an action that would change the target while a past stop is inspected
+    fn require_live(&mut self, action: &str) -> bool
+    {
+        if self.is_live() {
+            return true;
         }
+        self.error_message = Some(format!("Viewing a past stop (read-only); use :live to return before you {action}"));
+        self.info_message = None;
         false
     }
 
-    /// Execute a command from the command palette
-    #[allow(clippy::too_many_lines)]
-    fn execute_command(&mut self)
+    /// Refuse a feature the backend doesn't list in its capabilities
+    fn require_capability(&mut self, supported: bool, what: &str) -> bool
     {
-        let cmd = self.command_input.trim();
-        if cmd.is_empty() {
-            self.command_palette_active = false;
-            return;
+        if supported {
+            return true;
         }
+        self.error_message = Some(format!("This backend doesn't support {what}"));
+        self.info_message = None;
+        false
+    }
 
-        // Add to history
-        if self.command_history.back().map(String::as_str) != Some(cmd) {
-            self.command_history.push_back(cmd.to_string());
-            if self.command_history.len() > 100 {
-                self.command_history.pop_front();
-            }
+    /// Refuse an action once the target has disappeared
+    fn require_target(&mut self, action: &str) -> bool
+    {
+        let ended = match (self.target_exit, self.target_gone) {
+            (Some(code), _) => format_stop_reason(StopReason::Exited(code)),
+            (None, Some(pid)) => format!("Process {pid} is gone"),
+            (None, None) => return true,
+        };
+        self.error_message = Some(format!("{ended}; can't {action}"));
+        self.info_message = None;
+        false
+    }
+
+    /// Whether the target exited or disappeared, so nothing can run or stop it
+    fn target_ended(&self) -> bool
+    {
+        self.target_exit.is_some() || self.target_gone.is_some()
+    }
+
+    /// Note the backend's report that the target exited with `code`.
+    ///
+    /// Like [`observe_target_gone`](Self::observe_target_gone) it drops what
+    /// was cached about the process, but an exit is expected, so it is shown
+    /// as information rather than an error.
+    fn observe_exit(&mut self, code: i32, message: String)
+    {
+        self.target_exit = Some(code);
+        self.crash_banner = None;
+        self.forget_process();
+        self.error_message = None;
+        self.info_message = Some(message);
+    }
+
+    /// Note a [`DebuggerError::TargetGone`] from the backend.
+    ///
+    /// The first one logs to the timeline, feeds the session summary and
+    /// drops everything cached about the process. Returns whether `err` was
+    /// `TargetGone`, so callers can skip their own error message.
+    pub fn observe_target_gone(&mut self, err: &DebuggerError) -> bool
+    {
+        let DebuggerError::TargetGone { pid } = *err else {
+            return false;
+        };
+        // An exit already said why; the backend's later errors repeat it
+        if self.target_ended() {
+            return true;
         }
+        self.target_gone = Some(pid);
+        self.forget_process();
+        self.error_message = Some(err.to_string());
+        self.info_message = None;
+        self.add_timeline_entry(TimelineEntryKind::Error, err.to_string());
+        if let Some((recorder, _)) = &mut self.session_summary {
+            recorder.record_error(err);
+        }
+        true
+    }
 
-        // Parse and execute command
-        let parts: Vec<&str> = cmd.split_whitespace().collect();
-        if parts.is_empty() {
-            self.command_palette_active = false;
+    /// Drop what was cached about a process that is no longer there
+    fn forget_process(&mut self)
+    {
+        self.target_is_stopped = false;
+        self.cached_stack_trace = None;
+        self.unwind_diagnostics.clear();
+        self.recursion_cycles.clear();
+        self.marked_threads.clear();
+        self.pending_range_snapshots = None;
+        self.known_images = None;
+        self.panic_breakpoints = PanicBreakpoints::default();
+        self.panic_report = None;
+    }
+
+    /// Let `:restart` launch `program` again with `options`
+    pub fn enable_restart(&mut self, program: String, options: LaunchOptions)
+    {
+        self.launch = Some((program, options));
+    }
+
+    /// Kill the target if it still runs and detach from it
+    fn end_target(&mut self) -> Result<(), DebuggerError>
+    {
+        if !self.debugger.is_attached() {
+            return Ok(());
+        }
+        if self.target_ended() {
+            self.debugger.detach()
+        } else {
+            self.debugger.kill()
+        }
+    }
+
+    /// `:kill`: terminate the target and stay open without one
+    fn kill_target(&mut self)
+    {
+        if !self.require_target("kill it") {
             return;
         }
+        let Some(pid) = self.pid.filter(|_| self.debugger.is_attached()) else {
+            self.error_message = Some("No target to kill".to_string());
+            return;
+        };
+        if let Err(e) = self.end_target() {
+            self.report_error("Failed to kill the target", &e);
+            return;
+        }
+        self.target_gone = Some(pid);
+        self.forget_process();
+        let message = if self.launch.is_some() {
+            format!("Killed process {pid}; :restart launches it again")
+        } else {
+            format!("Killed process {pid}")
+        };
+        self.add_timeline_entry(TimelineEntryKind::Stop, format!("Killed process {pid}"));
+        self.info_message = Some(message);
+        self.info_message_time = Some(std::time::Instant::now());
+    }
 
-        match parts[0] {
-            "break" | "b" => {
-                if parts.len() > 1
-                    && let Ok(addr) = u64::from_str_radix(parts[1].trim_start_matches("0x"), 16)
-                {
-                    let address = Address::from(addr);
-                    if let Err(e) = self
-                        .debugger
-                        .add_breakpoint(ferros_core::BreakpointRequest::Software { address })
-                    {
-                        self.error_message = Some(format!("Failed to add breakpoint: {e}"));
-                    } else {
-                        self.refresh_breakpoints();
-                        self.add_timeline_entry(TimelineEntryKind::BreakpointHit, format!("Breakpoint at {address}"));
-                    }
-                } else if parts.len() > 1 {
-                    self.error_message = Some("Invalid address format".to_string());
-                }
-            }
-            "delete" | "d" => {
-                if parts.len() > 1
-                    && let Ok(id) = parts[1].parse::<u64>()
-                {
-                    let bp_id = ferros_core::BreakpointId::from_raw(id);
-                    if let Err(e) = self.debugger.remove_breakpoint(bp_id) {
-                        self.error_message = Some(format!("Failed to remove breakpoint: {e}"));
-                    } else {
-                        self.refresh_breakpoints();
-                        self.add_timeline_entry(TimelineEntryKind::BreakpointHit, format!("Removed breakpoint {id}"));
-                    }
-                }
-            }
-            "enable" | "e" => {
-                if parts.len() > 1
-                    && let Ok(id) = parts[1].parse::<u64>()
-                {
-                    let bp_id = ferros_core::BreakpointId::from_raw(id);
-                    if let Err(e) = self.debugger.enable_breakpoint(bp_id) {
-                        self.error_message = Some(format!("Failed to enable breakpoint: {e}"));
-                    } else {
-                        self.refresh_breakpoints();
-                    }
+    /// `:restart` / Ctrl+R: kill a launched target and launch it again
+    ///
+    /// Breakpoints are saved as `bp save` would and re-resolved in the new
+    /// process, so they follow their function or line; ones saved only by
+    /// address move with their image. The new process then runs.
+    fn restart_target(&mut self)
+    {
+        let Some((program, options)) = self.launch.clone() else {
+            self.error_message = Some(match self.pid {
+                Some(pid) if !self.was_launched => {
+                    format!("Process {pid} was attached to, not launched by ferros, so it can't be restarted")
                 }
+                _ => "Nothing to restart; ferros didn't launch this target".to_string(),
+            });
+            self.info_message = None;
+            return;
+        };
+        if !self.require_live("restart the target") {
+            return;
+        }
+
+        let mut saved = BreakpointFile::capture(&*self.debugger);
+        saved
+            .breakpoints
+            .extend(self.pending_breakpoints.drain(..).map(|pending| pending.saved));
+        let previous = self.debugger.get_memory_regions().unwrap_or_default();
+        if let Err(e) = self.end_target() {
+            self.report_error("Failed to kill the target", &e);
+            return;
+        }
+        self.forget_process();
+
+        self.target_started = Some(std::time::Instant::now());
+        let pid = match self.debugger.launch_with_options(&program, &options) {
+            Ok(pid) => pid.0,
+            Err(e) => {
+                self.target_gone = self.pid;
+                self.report_error(&format!("Failed to restart {program}"), &e);
+                return;
             }
-            "disable" => {
-                if parts.len() > 1
-                    && let Ok(id) = parts[1].parse::<u64>()
-                {
-                    let bp_id = ferros_core::BreakpointId::from_raw(id);
-                    if let Err(e) = self.debugger.disable_breakpoint(bp_id) {
-                        self.error_message = Some(format!("Failed to disable breakpoint: {e}"));
-                    } else {
-                        self.refresh_breakpoints();
-                    }
-                }
+        };
+        self.pid = Some(pid);
+        self.target_exit = None;
+        self.target_gone = None;
+        self.crash_banner = None;
+        self.process_restarted = true;
+        if let Some((recorder, _)) = &mut self.session_summary {
+            recorder.record_started(pid);
+        }
+
+        let regions = self.debugger.get_memory_regions().unwrap_or_default();
+        saved.relocate(&previous, &regions, image_uuid);
+        let report = saved.restore(&mut *self.debugger);
+        for unresolved in &report.unresolved {
+            self.add_timeline_entry(
+                TimelineEntryKind::Warning,
+                format!("Breakpoint at {} is pending: {}", unresolved.saved, unresolved.reason),
+            );
+        }
+        let (restored, pending) = (report.installed.len(), report.unresolved.len());
+        self.pending_breakpoints = report.unresolved;
+        self.rebase_notes();
+        if !self.assertions.is_empty() {
+            self.add_timeline_entry(
+                TimelineEntryKind::Warning,
+                "Assertions are only checked in the first run, not after a restart".to_string(),
+            );
+        }
+
+        let name = Path::new(&program)
+            .file_name()
+            .map_or_else(|| program.clone(), |name| name.to_string_lossy().into_owned());
+        let mut message = format!("Restarted {name} as process {pid} with {restored} breakpoint(s)");
+        if pending > 0 {
+            let _ = write!(message, ", {pending} pending");
+        }
+        self.add_timeline_entry(TimelineEntryKind::Resume, message.clone());
+        self.sync_panic_breakpoints();
+        match self.debugger.resume() {
+            Ok(()) => self.target_is_stopped = false,
+            Err(e) => {
+                self.target_is_stopped = self.debugger.is_stopped();
+                self.report_error("Restarted, but failed to resume", &e);
             }
-            "help" | "h" => {
-                // Toggle help view
-                if self.view_mode == ViewMode::Help {
-                    self.view_mode = ViewMode::Overview;
-                } else {
-                    self.view_mode = ViewMode::Help;
+        }
+        self.refresh_breakpoints();
+        self.info_message = Some(message);
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// `:thread <id|name>`: make a thread active by its id or any part of its name
+    fn switch_thread(&mut self, target: &str)
+    {
+        if target.is_empty() {
+            self.error_message = Some("Usage: thread <id|name>".to_string());
+            return;
+        }
+        let thread = match target.parse::<u64>() {
+            Ok(id) => ThreadId::from(id),
+            Err(_) => match self.thread_named(target) {
+                Ok(thread) => thread,
+                Err(message) => {
+                    self.error_message = Some(message);
+                    return;
                 }
+            },
+        };
+        if let Err(e) = self.debugger.set_active_thread(thread) {
+            self.report_error("Failed to set active thread", &e);
+        } else {
+            self.refresh_stack_trace();
+        }
+    }
+
+    /// The one thread whose name contains `filter`, ignoring case
+    fn thread_named(&self, filter: &str) -> Result<ThreadId, String>
+    {
+        let threads = self.debugger.threads().map_err(|e| format!("Failed to list threads: {e}"))?;
+        let matches: Vec<ThreadInfo> = threads
+            .into_iter()
+            .filter_map(|thread| self.debugger.thread_info(thread).ok())
+            .filter(|info| info.name_matches(filter))
+            .collect();
+        match matches.as_slice() {
+            [] => Err(format!("No thread is named like '{filter}'")),
+            [only] => Ok(only.id),
+            several => {
+                let listed: Vec<String> = several
+                    .iter()
+                    .map(|info| format!("{} ({})", info.name.as_deref().unwrap_or_default(), info.id.raw()))
+                    .collect();
+                Err(format!(
+                    "'{filter}' matches {} threads: {}; use a thread id",
+                    several.len(),
+                    listed.join(", ")
+                ))
             }
-            "frame" | "f" => {
-                if parts.len() > 1
-                    && let Ok(idx) = parts[1].parse::<usize>()
-                    && let Some(ref frames) = self.cached_stack_trace
-                    && idx < frames.len()
-                {
-                    self.stack_frames_state.select(Some(idx));
-                    self.selected_frame_id = Some(frames[idx].id);
-                    self.view_mode = ViewMode::Stack;
+        }
+    }
+
+    /// `:back`, `:forward` and `:live`: move through the stops in the history
+    fn navigate_history(&mut self, command: &str)
+    {
+        let moved = match command {
+            "back" => {
+                if self.history.is_empty() {
+                    self.error_message = Some("No stops recorded yet".to_string());
+                    return;
                 }
+                self.history.back().is_some()
             }
-            "thread" | "t" => {
-                if parts.len() > 1
-                    && let Ok(thread_id) = parts[1].parse::<u64>()
-                {
-                    let tid = ThreadId::from(thread_id);
-                    if let Err(e) = self.debugger.set_active_thread(tid) {
-                        self.error_message = Some(format!("Failed to set active thread: {e}"));
-                    } else {
-                        self.refresh_stack_trace();
-                    }
+            "forward" => {
+                if self.history.is_live() {
+                    self.error_message = Some("Already at the live state".to_string());
+                    return;
                 }
+                self.history.forward();
+                true
             }
             _ => {
-                self.error_message = Some(format!("Unknown command: {cmd}. Type 'help' for commands."));
+                self.history.live();
+                true
             }
+        };
+        if !moved {
+            self.error_message = Some("No older stop".to_string());
+            return;
         }
 
-        self.command_palette_active = false;
-        self.command_input.clear();
+        self.show_history_position();
     }
 
-    /// Cycle through layout presets
-    fn cycle_layout_preset(&mut self)
+    /// Refresh the views after moving through the history; a crash stop opens on its crash frame
+    fn show_history_position(&mut self)
     {
-        self.layout_preset = match self.layout_preset {
-            LayoutPreset::Compact => LayoutPreset::Standard,
-            LayoutPreset::Standard => LayoutPreset::Widescreen,
-            LayoutPreset::Widescreen => LayoutPreset::Compact,
-        };
+        self.info_message = Some(match self.history.position() {
+            Some((index, count)) => format!("Viewing stop {index}/{count} (read-only); :live to return"),
+            None => "Back to the live target".to_string(),
+        });
+        self.info_message_time = Some(std::time::Instant::now());
+        self.stack_frames_state.select(Some(0));
+        self.refresh_stack_trace();
+        self.refresh_source_view();
+        self.refresh_disassembly();
+        if let Some(site) = self.history.current().and_then(|checkpoint| checkpoint.crash) {
+            self.focus_crash(site);
+        }
     }
 
-    /// Add an entry to the timeline log
-    pub fn add_timeline_entry(&mut self, kind: TimelineEntryKind, message: String)
+    /// Summary of the crash being inspected: the past stop's in history mode, else the live one
+    #[must_use]
+    pub fn crash_summary(&self) -> Option<String>
     {
-        self.timeline_log.push_back(TimelineEntry {
-            timestamp: std::time::Instant::now(),
-            kind,
-            message,
-        });
-        if self.timeline_log.len() > MAX_TIMELINE_ENTRIES {
-            self.timeline_log.pop_front();
+        match self.history.current() {
+            Some(checkpoint) => checkpoint.crash.map(|site| site.summary(&checkpoint.frames)),
+            None => self.crash_banner.clone(),
+        }
+    }
+
+    /// Put the user on a crash: faulting thread highlighted, Stack view on the
+    /// crash frame with its source loaded, and the registers at the top
+    fn focus_crash(&mut self, site: CrashSite)
+    {
+        if let Some(thread) = site.thread
+            && let Some(row) = self
+                .debugger
+                .threads()
+                .ok()
+                .and_then(|threads| threads.iter().position(|&t| t == thread))
+        {
+            self.threads_state.select(Some(row));
+            self.selected_thread_index = row;
+        }
+        self.registers_state.select(Some(0));
+        self.view_mode = ViewMode::Stack;
+        self.stack_frames_state.select(Some(site.frame));
+        self.selected_frame_id = self
+            .cached_stack_trace
+            .as_ref()
+            .and_then(|frames| frames.get(site.frame))
+            .map(|frame| frame.id);
+        self.refresh_source_view();
+    }
+
+    /// Sample the main thread for `:set hang.probe`, logging hangs to the timeline
+    fn probe_main_thread(&mut self)
+    {
+        let Some(probe) = self.hang_probe.as_mut() else {
+            return;
+        };
+        if self.target_is_stopped || self.target_gone.is_some() || self.target_exit.is_some() {
+            probe.reset();
+            return;
         }
+        let interval = probe.interval();
+        match probe.poll(self.debugger.as_mut(), std::time::Instant::now()) {
+            Ok(None) => {}
+            Ok(Some(HangEvent::Hung(hang))) => {
+                self.add_timeline_entry(TimelineEntryKind::Warning, hang.summary(interval));
+                if self.hang_autostop {
+                    match self.debugger.suspend() {
+                        Ok(()) => {
+                            self.info_message = Some("Suspended the target at a main-thread hang".to_string());
+                            self.info_message_time = Some(std::time::Instant::now());
+                        }
+                        Err(e) => {
+                            if !self.observe_target_gone(&e) {
+                                self.report_error("Failed to suspend", &e);
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(Some(HangEvent::Recovered { function, stuck })) => {
+                self.add_timeline_entry(
+                    TimelineEntryKind::Diagnostic,
+                    format!("Main thread left {function} after ~{stuck:.1?}"),
+                );
+            }
+            Err(e) => {
+                self.observe_target_gone(&e);
+            }
+        }
+    }
+
+    /// Change one `:set` / `config.toml` setting
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the key is unknown or the value doesn't parse.
+    pub fn apply_setting(&mut self, key: &str, value: &str) -> Result<(), String>
+    {
+        match key {
+            "address.format" => self.address_format = value.parse()?,
+            "resume.confirm" => self.confirm_resume = emphasis::parse_switch(key, value)?,
+            "developer.tools" => self.developer_tools = emphasis::parse_switch(key, value)?,
+            "break.panic" => {
+                self.break_on_panic = config::parse_break_on_panic(key, value)?;
+                self.sync_panic_breakpoints();
+            }
+            "hang.probe" | "hang.samples" | "hang.autostop" => self.set_hang_option(key, value)?,
+            _ => self.stop_emphasis.settings.set(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Whether panics stop the target: `:set break.panic`, or when auto, whether ferros launched it
+    #[must_use]
+    pub fn breaks_on_panic(&self) -> bool
+    {
+        self.break_on_panic.unwrap_or(self.was_launched)
+    }
+
+    /// Install or remove the panic breakpoints to match `break.panic`
+    ///
+    /// Installing waits for the panic handler's symbol, so this is retried at
+    /// every stop and whenever symbols finish loading until it succeeds.
+    fn sync_panic_breakpoints(&mut self)
+    {
+        if !self.debugger.is_attached() {
+            return;
+        }
+        if !self.breaks_on_panic() {
+            if self.panic_breakpoints.is_installed() {
+                self.panic_breakpoints.remove(self.debugger.as_mut());
+            }
+            return;
+        }
+        if self.panic_breakpoints.is_installed() {
+            return;
+        }
+        match self.panic_breakpoints.install(self.debugger.as_mut()) {
+            Ok(true) => {
+                let entry_point = self.panic_breakpoints.entry_point().unwrap_or_default();
+                self.add_timeline_entry(TimelineEntryKind::Diagnostic, format!("Stopping at panics ({entry_point})"));
+            }
+            Ok(false) => {}
+            Err(e) => {
+                // Not retried: the same breakpoint would fail again at every stop
+                self.break_on_panic = Some(false);
+                self.add_timeline_entry(TimelineEntryKind::Warning, format!("Can't stop at panics: {e}"));
+            }
+        }
+    }
+
+    /// Apply `:set hang.probe|hang.samples|hang.autostop`
+    fn set_hang_option(&mut self, key: &str, value: &str) -> Result<(), String>
+    {
+        match key {
+            "hang.probe" => {
+                self.hang_probe =
+                    config::parse_hang_probe(value)?.map(|interval| HangProbe::new(interval, self.hang_samples));
+            }
+            "hang.samples" => {
+                let samples = config::parse_hang_samples(value)?;
+                self.hang_samples = samples;
+                if let Some(probe) = &mut self.hang_probe {
+                    *probe = HangProbe::new(probe.interval(), samples);
+                }
+            }
+            _ => self.hang_autostop = emphasis::parse_switch(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Re-read every pin and log the ones that changed to the timeline
+    pub fn refresh_pins(&mut self)
+    {
+        self.last_pin_refresh = std::time::Instant::now();
+        if self.pins.is_empty() || !self.debugger.is_attached() {
+            return;
+        }
+        for change in self.pins.refresh_from(self.debugger.as_ref()) {
+            self.add_timeline_entry(TimelineEntryKind::Pin, change.to_string());
+        }
+    }
+
+    /// Refresh the cached breakpoints list
+    pub fn refresh_breakpoints(&mut self)
+    {
+        self.cached_breakpoints = self.debugger.breakpoints();
+        self.cached_breakpoints.retain(|bp| !bp.options.hidden);
+
+        // Resolve breakpoint addresses to source locations for UI indicators
+        // This allows us to show breakpoint markers in the source view
+        // Use stack trace frames which already have symbolication
+        self.breakpoint_locations.clear();
+        for bp in &self.cached_breakpoints {
+            if bp.enabled && bp.state == ferros_core::BreakpointState::Resolved {
+                // Try to find source location from stack trace frames
+                let location = self.cached_stack_trace
+                    .as_ref()
+                    .and_then(|frames| {
+                        frames
+                            .iter()
+                            .find(|frame| frame.pc == bp.address)
+                            .and_then(|frame| frame.location.clone())
+                    });
+                self.breakpoint_locations.insert(bp.address, location);
+            }
+        }
+
+        self.warn_expensive_breakpoints();
+    }
+
+    /// Log a timeline warning the first time a breakpoint's overhead crosses the threshold
+    #[allow(clippy::large_stack_arrays)]
+    fn warn_expensive_breakpoints(&mut self)
+    {
+        let now = std::time::Instant::now();
+        let expensive: Vec<TimelineEntry> = self
+            .cached_breakpoints
+            .iter()
+            .filter(|bp| bp.stats.is_expensive(now) && !self.overhead_warned.contains(&bp.id))
+            .map(|bp| {
+                let message = format!(
+                    "Breakpoint {} at {} is taking {:.0}% of the target's time ({:.0} hits/s); add a condition or disable \
+                     it",
+                    bp.id.raw(),
+                    bp.address,
+                    bp.stats.overhead(now) * 100.0,
+                    bp.stats.hits_per_second(now),
+                );
+                TimelineEntry::new(TimelineEntryKind::Warning, message).with_breakpoint(Some(bp.id))
+            })
+            .collect();
+        for entry in expensive {
+            if let Some(id) = entry.breakpoint {
+                self.overhead_warned.insert(id);
+            }
+            self.push_timeline_entry(entry);
+        }
+    }
+
+    /// Refresh the source view based on current frame
+    pub fn refresh_source_view(&mut self)
+    {
+        // Use the selected frame from stack view, or fall back to first frame
+        let selected_idx = self.stack_frames_state.selected().unwrap_or(0);
+        
+        // Try to find a frame with source location (start from selected, then try all frames)
+        let frame_with_source = if let Some(ref frames) = self.cached_stack_trace {
+            // First try the selected frame
+            frames.get(selected_idx)
+                .and_then(|f| f.location.as_ref().map(|_| f))
+                // If that doesn't work, try the first frame
+                .or_else(|| frames.first().and_then(|f| f.location.as_ref().map(|_| f)))
+                // If that doesn't work, search all frames for one with source
+                .or_else(|| {
+                    frames.iter().find(|f| f.location.is_some())
+                })
+        } else {
+            None
+        };
+        
+        if let Some(frame) = frame_with_source
+            && let Some(ref location) = frame.location
+        {
+            let file_path = &location.file;
+
+            // Keyed on the normalized path symbolication produced, like the
+            // breakpoint locations, so one file has one entry wherever it was read from
+            if !self.source_cache.contains_key(file_path) {
+                let resolver = SourceResolver::in_current_dir(self.source_dirs.clone());
+                let loaded = match resolver.resolve(file_path) {
+                    Ok(path) => std::fs::read_to_string(&path)
+                        .map_err(|e| format!("Path: {}\nError: {e}\n\nTip: Check that the file is readable.", path.display())),
+                    Err(tried) => Err(source_not_found(location, &tried)),
+                };
+                match loaded {
+                    Ok(content) => {
+                        let lines: Vec<String> = content.lines().map(str::to_string).collect();
+                        let code_lines = self.debugger.code_lines(file_path).ok();
+                        self.source_cache
+                            .insert(file_path.clone(), SourceFile::new(lines, code_lines));
+                    }
+                    Err(detail) => {
+                        self.error_message = Some(format!("Failed to load source file:\n{detail}"));
+                        self.info_message = None;
+                    }
+                }
+            }
+            
+            self.current_source_file = Some(file_path.clone());
+            if let Some(line) = location.line {
+                let line_usize = line as usize;
+                self.source_scroll = line_usize.saturating_sub(10);
+                self.source_selected_line = Some(line_usize.saturating_sub(1)); // Line numbers are 1-based, array is 0-based
+            }
+        }
+    }
+
+    /// Show the Memory view dumping from `address`
+    fn open_memory(&mut self, address: Address)
+    {
+        self.memory.show(address);
+        self.memory.refresh(self.debugger.as_ref());
+        self.view_mode = ViewMode::Memory;
+    }
+
+    /// Move the memory dump by `rows` rows and read what it shows now
+    fn scroll_memory(&mut self, rows: i64)
+    {
+        self.memory.scroll(rows);
+        self.memory.refresh(self.debugger.as_ref());
+    }
+
+    /// Rows PgUp/PgDn move the memory dump by
+    fn memory_page(&self) -> i64
+    {
+        i64::try_from(self.memory.row_count()).unwrap_or(i64::MAX)
+    }
+
+    /// Follow the value of the selected register into the Memory view
+    fn follow_selected_register(&mut self)
+    {
+        let registers = match self.inspected_registers() {
+            Ok(registers) => registers,
+            Err(e) => {
+                self.report_error("Failed to read registers", &e);
+                return;
+            }
+        };
+        let index = self.registers_state.selected().unwrap_or(0);
+        let Some(row) = register_display_order(registers.architecture()).into_iter().nth(index) else {
+            return;
+        };
+        match registers.get(row.id) {
+            Some(value) if row.id == RegisterId::Status => {
+                self.error_message = Some(format!("{} = {value:#x} doesn't look like an address", row.display_name()));
+            }
+            Some(value) => self.follow_pointer(&row.display_name(), value),
+            None => self.error_message = Some(format!("{} is unavailable", row.display_name())),
+        }
+    }
+
+    fn handle_register_edit_input(&mut self, key_event: crossterm::event::KeyEvent)
+    {
+        use crossterm::event::KeyCode;
+
+        let Some(input) = self.register_edit.as_mut() else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Enter => {
+                let input = std::mem::take(input);
+                self.register_edit = None;
+                self.commit_register_edit(&input);
+            }
+            KeyCode::Esc => {
+                self.register_edit = None;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_hexdigit() || matches!(c, 'x' | 'X') => {
+                input.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// Write the hex value typed in the Registers view to the selected register
+    fn commit_register_edit(&mut self, input: &str)
+    {
+        let digits = input.trim_start_matches("0x").trim_start_matches("0X");
+        let Ok(value) = u64::from_str_radix(digits, 16) else {
+            self.error_message = Some(format!("Invalid hex value '{input}'"));
+            return;
+        };
+        let index = self.registers_state.selected().unwrap_or(0);
+        let Some(row) = register_display_order(self.debugger.architecture()).into_iter().nth(index) else {
+            return;
+        };
+        self.write_register(row, value);
+    }
+
+    /// `:set $<register> = <expr>`: write the value of an expression to a register of the active thread
+    fn set_register_command(&mut self, text: &str)
+    {
+        let Some((name, source)) = text.split_once('=') else {
+            self.error_message = Some("Usage: set $<register> = <expr>".to_string());
+            return;
+        };
+        let name = name.trim();
+        let architecture = self.debugger.architecture();
+        let Some(row) = register_by_name(architecture, name) else {
+            self.error_message = Some(format!("Unknown register {name} on {architecture}"));
+            return;
+        };
+        let value = match self.evaluate_expression(source.trim()).map(|value| value.as_u64()) {
+            Ok(Some(value)) => value,
+            Ok(None) => {
+                self.error_message = Some(format!("`{}` is a string, not a register value", source.trim()));
+                return;
+            }
+            Err(e) => {
+                self.report_error("Failed to evaluate", &e);
+                return;
+            }
+        };
+        self.write_register(row, value);
+    }
+
+    /// Write `value` to the active thread's `row` register
+    ///
+    /// Moving the PC, SP or FP changes the stack, so the cached trace and the
+    /// views drawn from it are refreshed.
+    fn write_register(&mut self, row: RegisterRow, value: u64)
+    {
+        if !self.require_live("change registers") {
+            return;
+        }
+        if let Err(e) = self.debugger.set_register(row.id, value) {
+            self.report_error(&format!("Failed to set {}", row.display_name()), &e);
+            return;
+        }
+        if let Some(thread) = self.debugger.active_thread() {
+            self.register_snapshot.update(self.debugger.as_ref(), thread);
+        }
+        self.info_message = Some(format!("{} = {value:#x}", row.display_name()));
+        self.info_message_time = Some(std::time::Instant::now());
+        if matches!(row.id, RegisterId::Pc | RegisterId::Sp | RegisterId::Fp) {
+            self.cached_stack_trace = None;
+            self.stack_frames_state.select(Some(0));
+            self.refresh_stack_trace();
+            self.refresh_source_view();
+            self.refresh_disassembly();
+        }
+    }
+
+    /// Follow the selected word of the memory dump
+    fn follow_selected_word(&mut self)
+    {
+        let Some(word) = self.memory.selected_word() else {
+            return;
+        };
+        match self.memory.selected_value() {
+            Some(value) => self.follow_pointer(&format!("[{word}]"), value),
+            None => self.error_message = Some(format!("[{word}] couldn't be read")),
+        }
+    }
+
+    /// Dump the memory `value` points at, remembering where the dump was to go back to
+    ///
+    /// `source` names where the value came from in the messages. Values that
+    /// don't look like addresses or that aren't in a readable region are
+    /// reported instead of dumped, so the view doesn't sit on an address
+    /// every read fails at.
+    fn follow_pointer(&mut self, source: &str, value: u64)
+    {
+        if !crate::widgets::looks_like_address(value) {
+            self.error_message = Some(format!("{source} = {value:#x} doesn't look like an address"));
+            return;
+        }
+        // Without a region list there's nothing to check against; the dump shows what reads
+        let address = Address::from(value);
+        if let Ok(regions) = self.debugger.get_memory_regions()
+            && !regions.is_empty()
+        {
+            match regions.iter().find(|region| region.contains(address)) {
+                None => {
+                    self.error_message = Some(format!("{source} = {value:#x} isn't in a mapped region"));
+                    return;
+                }
+                Some(region) if !region.is_readable() => {
+                    self.error_message = Some(format!(
+                        "{source} = {value:#x} is in a region that isn't readable ({})",
+                        region.permissions
+                    ));
+                    return;
+                }
+                Some(_) => {}
+            }
+        }
+        self.memory_history.follow(self.memory.address, address);
+        self.open_memory(address);
+    }
+
+    /// Go back (or forward) through the pointers the Memory view followed
+    fn memory_history_step(&mut self, back: bool)
+    {
+        let Some(current) = self.memory.address else {
+            return;
+        };
+        let target = if back {
+            self.memory_history.back(current)
+        } else {
+            self.memory_history.forward(current)
+        };
+        match target {
+            Some(address) => self.open_memory(address),
+            None if back => self.info_message = Some("No earlier address to go back to".to_string()),
+            None => self.info_message = Some("No later address to go forward to".to_string()),
+        }
+    }
+
+    /// Move the selected word of the memory dump, reading again if the dump scrolled
+    fn select_memory_word(&mut self, next: bool)
+    {
+        let before = self.memory.address;
+        if next {
+            self.memory.select_next_word();
+        } else {
+            self.memory.select_previous_word();
+        }
+        if self.memory.address != before {
+            self.memory.refresh(self.debugger.as_ref());
+        }
+    }
+
+    /// Reload the Disassembly view around the inspected PC while it is shown
+    fn refresh_disassembly(&mut self)
+    {
+        if self.view_mode != ViewMode::Disassembly {
+            return;
+        }
+        let pc = if self.debugger.is_attached() && self.target_is_stopped {
+            self.inspected_registers().ok().map(|registers| registers.pc)
+        } else {
+            None
+        };
+        match pc {
+            Some(pc) => self.disassembly.load(self.debugger.as_mut(), pc),
+            None => self.disassembly.clear(),
+        }
+    }
+
+    /// Navigate up in stack view
+    fn navigate_stack_up(&mut self)
+    {
+        if let Some(ref frames) = self.cached_stack_trace {
+            let current_idx = self.stack_frames_state.selected().unwrap_or(0);
+            if current_idx > 0 {
+                self.stack_frames_state.select(Some(current_idx - 1));
+                if let Some(frame) = frames.get(current_idx - 1) {
+                    self.selected_frame_id = Some(frame.id);
+                }
+                // Refresh source view when frame selection changes
+                self.refresh_source_view();
+            }
+        }
+    }
+
+    /// Navigate down in stack view
+    fn navigate_stack_down(&mut self)
+    {
+        if let Some(ref frames) = self.cached_stack_trace {
+            let current_idx = self.stack_frames_state.selected().unwrap_or(0);
+            let max = frames.len().saturating_sub(1);
+            if current_idx < max {
+                self.stack_frames_state.select(Some(current_idx + 1));
+                if let Some(frame) = frames.get(current_idx + 1) {
+                    self.selected_frame_id = Some(frame.id);
+                }
+                // Refresh source view when frame selection changes
+                self.refresh_source_view();
+            }
+        }
+    }
+
+    /// Find address for a given source file and line number.
+    /// This searches the cached stack trace for a frame stopped on that line.
+    fn find_address_for_source_line(&self, file: &str, line: u32) -> Option<Address>
+    {
+        self.cached_stack_trace
+            .as_ref()?
+            .iter()
+            .find(|frame| {
+                frame
+                    .location
+                    .as_ref()
+                    .is_some_and(|location| location.file == file && location.line == Some(line))
+            })
+            .map(|frame| frame.pc)
+    }
+
+    /// `break <location>`: one breakpoint per address the location finds
+    ///
+    /// Inlined copies and generic instances give a line or function several
+    /// addresses. A name that finds differently named functions (`run` in two
+    /// modules) sets nothing and lists them instead. A location that doesn't
+    /// resolve yet goes to the backend as is, which explains why or keeps it
+    /// pending. With `one_shot` (`tbreak`) each address's breakpoint is
+    /// removed after its own first hit, and with `force` (`-f`) it's placed
+    /// even where no instruction starts.
+    fn break_at(&mut self, location: BreakpointLocation, flags: BreakFlags)
+    {
+        let plan = match commands::plan_breakpoints(self.debugger.as_mut(), location) {
+            Ok(plan) => plan,
+            Err(DebuggerError::InvalidArgument(message)) => {
+                self.info_message = Some(message);
+                self.info_message_time = Some(std::time::Instant::now());
+                self.error_message = None;
+                return;
+            }
+            Err(e) => {
+                self.report_error("Failed to add breakpoint", &e);
+                return;
+            }
+        };
+        if !plan.addresses.is_empty() && !matches!(plan.location, BreakpointLocation::Address(_)) {
+            self.add_breakpoints_at(&plan.location, &plan.addresses, flags);
+            return;
+        }
+        for request in plan.requests(flags) {
+            if let Err(e) = self.install_breakpoint(request) {
+                self.report_error("Failed to add breakpoint", &e);
+                return;
+            }
+        }
+        self.refresh_breakpoints();
+        self.add_timeline_entry(TimelineEntryKind::BreakpointHit, format!("Breakpoint at {}", plan.location));
+    }
+
+    /// `b` on a Source view line: toggle the breakpoints on every address it compiled to
+    ///
+    /// Existing breakpoints at those addresses are toggled, otherwise one is
+    /// added per address. When the line tables don't know the line, a stack
+    /// frame stopped on it still gives an address.
+    fn toggle_line_breakpoint(&mut self, file: &str, line: u32)
+    {
+        let location = BreakpointLocation::Line {
+            file: file.to_string(),
+            line,
+        };
+        let addresses = match self.debugger.resolve_location(&location) {
+            Ok(addresses) if !addresses.is_empty() => addresses,
+            result => {
+                if let Some(address) = self.find_address_for_source_line(file, line) {
+                    vec![address]
+                } else {
+                    match result {
+                        Err(e) => self.report_error(&format!("Failed to resolve {location}"), &e),
+                        Ok(_) => self.error_message = Some(format!("No code at {location}")),
+                    }
+                    self.info_message = None;
+                    return;
+                }
+            }
+        };
+        if let [address] = addresses[..] {
+            self.toggle_breakpoint_at_address(address);
+            return;
+        }
+
+        let existing: Vec<BreakpointId> = self
+            .cached_breakpoints
+            .iter()
+            .filter(|bp| addresses.contains(&bp.address))
+            .map(|bp| bp.id)
+            .collect();
+        if existing.is_empty() {
+            self.add_breakpoints_at(&location, &addresses, BreakFlags::default());
+            return;
+        }
+        for &id in &existing {
+            if let Err(e) = self.debugger.toggle_breakpoint(id) {
+                self.report_error("Failed to toggle breakpoint", &e);
+                self.info_message = None;
+                self.refresh_breakpoints();
+                return;
+            }
+        }
+        self.refresh_breakpoints();
+        let message = format!("Toggled {} breakpoints at {location}", existing.len());
+        self.info_message = Some(message.clone());
+        self.info_message_time = Some(std::time::Instant::now());
+        self.error_message = None;
+        self.add_timeline_entry(TimelineEntryKind::BreakpointHit, message);
+    }
+
+    /// Add a software breakpoint at each of the `addresses` that `location` resolved to
+    fn add_breakpoints_at(&mut self, location: &BreakpointLocation, addresses: &[Address], flags: BreakFlags)
+    {
+        let mut added = 0;
+        let mut failed = false;
+        for &address in addresses {
+            if let Err(e) = self.install_breakpoint(build_breakpoint(Breakpoint::software(address), flags)) {
+                self.report_error(&format!("Failed to add breakpoint at {address}"), &e);
+                failed = true;
+            } else {
+                added += 1;
+            }
+        }
+        self.refresh_breakpoints();
+        if added == 0 {
+            self.info_message = None;
+            return;
+        }
+
+        let kind = if flags.one_shot {
+            "temporary breakpoint"
+        } else {
+            "breakpoint"
+        };
+        let message = if failed {
+            let count = format!("{added} of {}", addresses.len());
+            format!("Added {count} {kind}s at {location}")
+        } else if added == 1 {
+            format!("Added {kind} at {location} ({})", addresses[0])
+        } else {
+            format!("Added {added} {kind}s at {location}")
+        };
+        self.info_message = Some(message.clone());
+        self.info_message_time = Some(std::time::Instant::now());
+        if !failed {
+            self.error_message = None;
+        }
+        self.add_timeline_entry(TimelineEntryKind::BreakpointHit, message);
+    }
+
+    /// Toggle breakpoint at the given address
+    fn toggle_breakpoint_at_address(&mut self, address: Address)
+    {
+        // Check if breakpoint already exists
+        let existing_bp = self.cached_breakpoints.iter().find(|bp| bp.address == address).cloned();
+
+        if let Some(bp) = existing_bp {
+            // Toggle existing breakpoint
+            let was_enabled = bp.enabled;
+            if let Err(e) = self.debugger.toggle_breakpoint(bp.id) {
+                self.report_error("Failed to toggle breakpoint", &e);
+                self.info_message = None;
+            } else {
+                self.refresh_breakpoints();
+                let action = if was_enabled { "Disabled" } else { "Enabled" };
+                let message = format!("{} breakpoint #{} at {}", action, bp.id.raw(), address);
+                self.info_message = Some(message.clone());
+                self.info_message_time = Some(std::time::Instant::now());
+                self.error_message = None;
+                self.add_timeline_entry(TimelineEntryKind::BreakpointHit, format!("Toggled breakpoint at {address}"));
+            }
+        } else {
+            // Add new breakpoint
+            if let Err(e) = self.install_breakpoint(Breakpoint::software(address).build()) {
+                self.report_error("Failed to add breakpoint", &e);
+                self.info_message = None;
+            } else {
+                self.refresh_breakpoints();
+                // Find the newly created breakpoint to show its type and ID
+                let new_bp = self.cached_breakpoints.iter().find(|bp| bp.address == address).cloned();
+
+                if let Some(bp) = new_bp {
+                    let kind_str = match bp.kind {
+                        ferros_core::BreakpointKind::Software => "software",
+                        ferros_core::BreakpointKind::Hardware => "hardware",
+                        ferros_core::BreakpointKind::Watchpoint => "watchpoint",
+                    };
+                    let message = format!("Added {} breakpoint #{} at {}", kind_str, bp.id.raw(), address);
+                    self.info_message = Some(message.clone());
+                    self.info_message_time = Some(std::time::Instant::now());
+                    self.error_message = None;
+                    self.add_timeline_entry(TimelineEntryKind::BreakpointHit, message);
+                }
+            }
+        }
+    }
+
+    /// Frame highlighted in the Stack view, with its index
+    fn selected_frame(&self) -> Option<(usize, &StackFrame)>
+    {
+        let index = self.stack_frames_state.selected().unwrap_or(0);
+        self.cached_stack_trace.as_ref()?.get(index).map(|frame| (index, frame))
+    }
+
+    /// Image `address` is in and its debug level, when that image has no DWARF
+    ///
+    /// The image is named after the memory region holding `address`. `None`
+    /// when the image has DWARF or the backend doesn't know which image it is.
+    #[must_use]
+    pub fn missing_debug_info(&self, address: Address) -> Option<(String, DebugLevel)>
+    {
+        let level = self.debugger.debug_level(address).filter(|level| !level.has_dwarf())?;
+        let image = self
+            .debugger
+            .get_memory_regions()
+            .ok()
+            .and_then(|regions| {
+                regions
+                    .into_iter()
+                    .find(|region| region.start <= address && address < region.end)
+            })
+            .and_then(|region| region.name)
+            .map_or_else(
+                || "this image".to_string(),
+                |name| name.rsplit('/').next().unwrap_or(&name).to_string(),
+            );
+        Some((image, level))
+    }
+
+    /// [`missing_debug_info`](Self::missing_debug_info) for the selected stack
+    /// frame, if it has no source location
+    #[must_use]
+    pub fn selected_frame_without_debug_info(&self) -> Option<(String, DebugLevel)>
+    {
+        let (_, frame) = self.selected_frame()?;
+        if frame.location.is_some() {
+            return None;
+        }
+        self.missing_debug_info(frame.pc)
+    }
+
+    /// `b` in the Source view on a frame whose image has no line tables
+    fn refuse_line_breakpoint(&mut self)
+    {
+        if let Some((image, level)) = self.selected_frame_without_debug_info() {
+            self.error_message = Some(format!(
+                "Line breakpoints unavailable: no debug info in {image} ({level}); use break <function> or b in the Stack \
+                 view"
+            ));
+            self.info_message = None;
+        }
+    }
+
+    /// Set a breakpoint relative to the selected stack frame.
+    ///
+    /// Unlike `b` in other views this never falls back to the live PC: with no
+    /// frame to work from it reports an error instead.
+    fn break_at_selected_frame(&mut self, target: FrameBreakpoint)
+    {
+        if !self.debugger.is_attached() || !self.target_is_stopped {
+            return;
+        }
+        let Some((index, selected)) = self.selected_frame() else {
+            self.error_message = Some("No stack frame selected".to_string());
+            return;
+        };
+        let frames = self.cached_stack_trace.as_deref().unwrap_or_default();
+        let name = |frame: &StackFrame| {
+            frame
+                .symbol
+                .as_ref()
+                .map_or_else(|| frame.pc.to_string(), |symbol| symbol.display_name().to_string())
+        };
+        // Inlined frames share their physical frame's PC and SP, so the caller and
+        // callee are the nearest frames that belong to a different activation
+        let same_activation = |frame: &&StackFrame| frame.pc == selected.pc && frame.sp == selected.sp;
+        let outer = frames[index + 1..].iter().find(|frame| !same_activation(frame));
+        let inner = frames[..index].iter().rev().find(|frame| !same_activation(frame));
+
+        let (request, description, warning) = match target {
+            FrameBreakpoint::Pc => {
+                self.toggle_breakpoint_at_address(selected.pc);
+                return;
+            }
+            FrameBreakpoint::Return => {
+                // The unwinder records each return address on the frame it unwound
+                // to, so the selected frame's is on its caller
+                let Some((caller, address)) = outer.and_then(|frame| Some((frame, frame.return_address?))) else {
+                    self.error_message = Some(format!("{} has no recorded return address", name(selected)));
+                    return;
+                };
+                let warning = (caller.status == FrameStatus::Heuristic).then(|| {
+                    format!("Return address {address} was recovered heuristically and may be wrong; check the Stack view")
+                });
+                (
+                    Breakpoint::software(address).one_shot().build(),
+                    format!(
+                        "one-shot breakpoint at {address} in {} (return from {})",
+                        name(caller),
+                        name(selected)
+                    ),
+                    warning,
+                )
+            }
+            FrameBreakpoint::CallTarget => {
+                let Some(callee) = inner else {
+                    self.error_message = Some(format!("{} is the innermost frame and has no callee", name(selected)));
+                    return;
+                };
+                (
+                    Breakpoint::software(callee.pc).build(),
+                    format!(
+                        "breakpoint at {} in {} (called from {})",
+                        callee.pc,
+                        name(callee),
+                        name(selected)
+                    ),
+                    None,
+                )
+            }
+        };
+
+        match self.install_breakpoint(request) {
+            Ok(id) => {
+                self.refresh_breakpoints();
+                let message = format!("Added {description} as #{}", id.raw());
+                self.info_message = Some(message.clone());
+                self.info_message_time = Some(std::time::Instant::now());
+                self.error_message = None;
+                self.push_timeline_entry(
+                    TimelineEntry::new(TimelineEntryKind::BreakpointHit, message).with_breakpoint(Some(id)),
+                );
+                if let Some(warning) = warning {
+                    self.push_timeline_entry(
+                        TimelineEntry::new(TimelineEntryKind::Warning, warning.clone()).with_breakpoint(Some(id)),
+                    );
+                    self.error_message = Some(warning);
+                }
+            }
+            Err(e) => {
+                self.report_error("Failed to add breakpoint", &e);
+                self.info_message = None;
+            }
+        }
+    }
+
+    /// Open breakpoint editor
+    fn open_breakpoint_editor(&mut self, existing_id: Option<BreakpointId>)
+    {
+        if let Some(id) = existing_id {
+            if let Ok(info) = self.debugger.breakpoint_info(id) {
+                self.breakpoint_editor = Some(BreakpointEditorState {
+                    address_input: format!("{}", info.address),
+                    kind_input: match info.kind {
+                        ferros_core::BreakpointKind::Software => "software".to_string(),
+                        ferros_core::BreakpointKind::Hardware => "hardware".to_string(),
+                        ferros_core::BreakpointKind::Watchpoint => "watchpoint".to_string(),
+                    },
+                    watch_length_input: info.watch_length.map(|l| l.to_string()).unwrap_or_default(),
+                    watch_access_input: info
+                        .watch_access
+                        .map(|a| match a {
+                            ferros_core::WatchpointAccess::Read => "read".to_string(),
+                            ferros_core::WatchpointAccess::Write => "write".to_string(),
+                            ferros_core::WatchpointAccess::ReadWrite => "readwrite".to_string(),
+                        })
+                        .unwrap_or_default(),
+                    editing_existing: Some(id),
+                });
+            }
+        } else {
+            self.breakpoint_editor = Some(BreakpointEditorState {
+                address_input: String::new(),
+                kind_input: "software".to_string(),
+                watch_length_input: String::new(),
+                watch_access_input: String::new(),
+                editing_existing: None,
+            });
+        }
+    }
+
+    /// Handle input in breakpoint editor
+    fn handle_breakpoint_editor_input(&mut self, key_event: crossterm::event::KeyEvent) -> bool
+    {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        if let Some(ref mut editor) = self.breakpoint_editor {
+            match key_event.code {
+                KeyCode::Enter => {
+                    // Apply breakpoint
+                    self.apply_breakpoint_editor();
+                    return false;
+                }
+                KeyCode::Esc => {
+                    self.breakpoint_editor = None;
+                    return false;
+                }
+                KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    editor.address_input.push(c);
+                }
+                KeyCode::Backspace => {
+                    editor.address_input.pop();
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Apply breakpoint editor changes
+    fn apply_breakpoint_editor(&mut self)
+    {
+        if let Some(editor) = self.breakpoint_editor.take() {
+            // Parse address
+            if let Ok(addr_value) = u64::from_str_radix(editor.address_input.trim_start_matches("0x"), 16) {
+                let address = Address::from(addr_value);
+
+                let request = match editor.kind_input.as_str() {
+                    "hardware" => Breakpoint::hardware(address),
+                    "watchpoint" => {
+                        let length = editor.watch_length_input.parse().unwrap_or(8);
+                        let access = match editor.watch_access_input.as_str() {
+                            "read" => ferros_core::WatchpointAccess::Read,
+                            "write" => ferros_core::WatchpointAccess::Write,
+                            _ => ferros_core::WatchpointAccess::ReadWrite,
+                        };
+                        Breakpoint::watchpoint(address, length, access)
+                    }
+                    _ => Breakpoint::software(address),
+                };
+
+                if let Some(existing_id) = editor.editing_existing {
+                    // Remove old and add new
+                    let _ = self.debugger.remove_breakpoint(existing_id);
+                }
+
+                if let Err(e) = self.install_breakpoint(request.build()) {
+                    self.report_error("Failed to add breakpoint", &e);
+                } else {
+                    self.refresh_breakpoints();
+                    self.add_timeline_entry(TimelineEntryKind::BreakpointHit, format!("Breakpoint added at {address}"));
+                }
+            } else {
+                self.error_message = Some("Invalid address format".to_string());
+            }
+        }
+    }
+
+    /// Handle input in command palette
+    fn handle_command_palette_input(&mut self, key_event: crossterm::event::KeyEvent) -> bool
+    {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        match key_event.code {
+            KeyCode::Enter => {
+                self.execute_command();
+                return false;
+            }
+            KeyCode::Esc => {
+                self.command_palette_active = false;
+                self.command_input.clear();
+                return false;
+            }
+            KeyCode::Up => {
+                // Navigate command history
+                if let Some(ref mut idx) = self.command_history_index {
+                    if *idx > 0 {
+                        *idx -= 1;
+                        if let Some(cmd) = self.command_history.get(*idx) {
+                            self.command_input = cmd.clone();
+                        }
+                    }
+                } else if !self.command_history.is_empty() {
+                    self.command_history_index = Some(self.command_history.len() - 1);
+                    if let Some(cmd) = self.command_history.back() {
+                        self.command_input = cmd.clone();
+                    }
+                }
+            }
+            KeyCode::Down => {
+                // Navigate command history forward
+                if let Some(ref mut idx) = self.command_history_index {
+                    if *idx < self.command_history.len().saturating_sub(1) {
+                        *idx += 1;
+                        if let Some(cmd) = self.command_history.get(*idx) {
+                            self.command_input = cmd.clone();
+                        }
+                    } else {
+                        self.command_history_index = None;
+                        self.command_input.clear();
+                    }
+                }
+            }
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.command_input.push(c);
+                self.command_history_index = None;
+            }
+            KeyCode::Backspace => {
+                self.command_input.pop();
+                self.command_history_index = None;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Execute a command from the command palette
+    #[allow(clippy::too_many_lines)]
+    fn execute_command(&mut self)
+    {
+        let cmd = self.command_input.trim();
+        if cmd.is_empty() {
+            self.command_palette_active = false;
+            return;
+        }
+
+        // Add to history
+        if self.command_history.back().map(String::as_str) != Some(cmd) {
+            self.command_history.push_back(cmd.to_string());
+            if self.command_history.len() > 100 {
+                self.command_history.pop_front();
+            }
+        }
+
+        // Parse and execute command
+        let parts: Vec<&str> = cmd.split_whitespace().collect();
+        if parts.is_empty() {
+            self.command_palette_active = false;
+            return;
+        }
+
+        match parts[0] {
+            "break" | "b" | "tbreak" | "tb" => {
+                // `tbreak` breakpoints are removed after their first hit
+                if let (flags, Some(target)) = BreakFlags::parse(parts[0], &parts[1..]) {
+                    // Hex address, `file:line`, or a function name
+                    match target.parse() {
+                        Ok(location) => self.break_at(location, flags),
+                        Err(message) => self.error_message = Some(message),
+                    }
+                }
+            }
+            "delete" | "d" => {
+                if parts.len() > 1
+                    && let Ok(id) = parts[1].parse::<u64>()
+                {
+                    let bp_id = ferros_core::BreakpointId::from_raw(id);
+                    if let Err(e) = self.debugger.remove_breakpoint(bp_id) {
+                        self.report_error("Failed to remove breakpoint", &e);
+                    } else {
+                        self.refresh_breakpoints();
+                        self.add_timeline_entry(TimelineEntryKind::BreakpointHit, format!("Removed breakpoint {id}"));
+                    }
+                }
+            }
+            "enable" | "e" | "disable" => {
+                if let Some(target) = parts.get(1).map(ToString::to_string) {
+                    self.set_breakpoints_enabled(&target, parts[0] != "disable");
+                }
+            }
+            "ignore" => {
+                if parts.len() > 2
+                    && let Ok(id) = parts[1].parse::<u64>()
+                    && let Ok(count) = parts[2].parse::<u64>()
+                {
+                    let bp_id = ferros_core::BreakpointId::from_raw(id);
+                    if let Err(e) = self.debugger.set_breakpoint_ignore_count(bp_id, count) {
+                        self.report_error("Failed to set ignore count", &e);
+                    } else {
+                        self.refresh_breakpoints();
+                        self.info_message = Some(match count {
+                            0 => format!("Breakpoint {id} will stop on its next hit"),
+                            1 => format!("Will ignore the next hit of breakpoint {id}"),
+                            _ => format!("Will ignore the next {count} hits of breakpoint {id}"),
+                        });
+                    }
+                }
+            }
+            "bp" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.breakpoint_file_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "help" | "h" => {
+                // Toggle help view
+                if self.view_mode == ViewMode::Help {
+                    self.view_mode = ViewMode::Overview;
+                } else {
+                    self.view_mode = ViewMode::Help;
+                }
+            }
+            "frame" | "f" => {
+                if parts.len() > 1
+                    && let Ok(idx) = parts[1].parse::<usize>()
+                    && let Some(ref frames) = self.cached_stack_trace
+                    && idx < frames.len()
+                {
+                    self.stack_frames_state.select(Some(idx));
+                    self.selected_frame_id = Some(frames[idx].id);
+                    self.view_mode = ViewMode::Stack;
+                }
+            }
+            "thread" | "t" => {
+                // Thread names may contain spaces
+                let target = cmd
+                    .split_once(char::is_whitespace)
+                    .map_or("", |(_, rest)| rest.trim())
+                    .to_string();
+                self.switch_thread(&target);
+            }
+            "pin" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.pin_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "unpin" => match parts.get(1).map(ToString::to_string) {
+                Some(name) if self.pins.unpin(&name) => {
+                    self.info_message = Some(format!("Unpinned {name}"));
+                    self.info_message_time = Some(std::time::Instant::now());
+                    self.save_session_file();
+                }
+                Some(name) => self.error_message = Some(format!("No pin named {name}")),
+                None => self.error_message = Some("Usage: unpin <name>".to_string()),
+            },
+            "note" => {
+                let args = cmd
+                    .split_once(char::is_whitespace)
+                    .map_or("", |(_, rest)| rest.trim())
+                    .to_string();
+                self.note_command(&args);
+            }
+            "notes" => self.open_notes_list(),
+            "mem" => match parts.get(1) {
+                Some(address) => match commands::resolve_address(self.debugger.as_mut(), address) {
+                    Ok(address) => self.open_memory(address),
+                    Err(e) => self.report_error("Failed to resolve memory address", &e),
+                },
+                None if self.memory.address.is_some() => self.view_mode = ViewMode::Memory,
+                None => self.error_message = Some("Usage: mem <address>".to_string()),
+            },
+            "print" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.print_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "where" => self.show_where(),
+            "all-stacks" => self.show_all_stacks(),
+            "restart" => self.restart_target(),
+            "kill" => self.kill_target(),
+            "stepi" | "si" => self.step(Step::Instruction),
+            "nexti" | "ni" => self.step(Step::Over),
+            "finish" | "fin" => self.step(Step::Out),
+            "debug" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.debug_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "find" => {
+                // A quoted pattern keeps its spacing, as with `write`
+                let text = cmd[parts[0].len()..].trim().to_string();
+                self.find_command(&text);
+            }
+            "write" => {
+                // The payload can be a quoted string, so keep its spacing
+                let text = cmd[parts[0].len()..].trim().to_string();
+                self.write_memory_command(&text);
+            }
+            "handle" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.handle_signal_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "stop-policy" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.stop_policy_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "modules" => {
+                self.announce_new_images();
+                self.view_mode = ViewMode::Modules;
+            }
+            "history" => {
+                self.view_mode = ViewMode::History;
+            }
+            "async-stacks" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.async_stacks_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "tls" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.thread_local_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "back" | "forward" | "live" => {
+                let command = parts[0].to_string();
+                self.navigate_history(&command);
+            }
+            "checkpoints" => match parts.get(1).copied() {
+                Some("on") => {
+                    self.history.set_stack_budget(CheckpointOptions::default().stack_budget);
+                    self.info_message = Some("Capturing stack memory at every stop".to_string());
+                    self.info_message_time = Some(std::time::Instant::now());
+                }
+                Some("off") => {
+                    self.history.set_stack_budget(0);
+                    self.info_message = Some("Stack capture off; stops keep registers and frames".to_string());
+                    self.info_message_time = Some(std::time::Instant::now());
+                }
+                Some("clear") => {
+                    self.history.clear();
+                    self.refresh_stack_trace();
+                    self.info_message = Some("Checkpoints cleared".to_string());
+                    self.info_message_time = Some(std::time::Instant::now());
+                }
+                _ => {
+                    let state = if self.history.options().stack_budget > 0 { "on" } else { "off" };
+                    let kib = self.history.footprint() / 1024;
+                    self.info_message = Some(format!("Checkpoints {state}: {} held, {kib} KiB of stack", self.history.len()));
+                    self.info_message_time = Some(std::time::Instant::now());
+                }
+            },
+            "checkpoint" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.range_checkpoint_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "set" => match parts[1..] {
+                [name, ..] if name.starts_with('$') => {
+                    let text = parts[1..].join(" ");
+                    self.set_register_command(&text);
+                }
+                [] => {
+                    let mut summary = format!(
+                        "{} address.format={} resume.confirm={}",
+                        self.stop_emphasis.settings.describe(),
+                        self.address_format,
+                        if self.confirm_resume { "on" } else { "off" }
+                    );
+                    let _ = write!(summary, " developer.tools={}", if self.developer_tools { "on" } else { "off" });
+                    let break_on_panic = match self.break_on_panic {
+                        None => "auto",
+                        Some(true) => "on",
+                        Some(false) => "off",
+                    };
+                    let _ = write!(summary, " break.panic={break_on_panic}");
+                    match &self.hang_probe {
+                        Some(probe) => {
+                            let _ = write!(summary, " hang.probe={:?}", probe.interval());
+                        }
+                        None => summary.push_str(" hang.probe=off"),
+                    }
+                    let _ = write!(summary, " hang.samples={}", self.hang_samples);
+                    let _ = write!(summary, " hang.autostop={}", if self.hang_autostop { "on" } else { "off" });
+                    self.info_message = Some(summary);
+                    self.info_message_time = Some(std::time::Instant::now());
+                }
+                [key, value] => {
+                    let (key, value) = (key.to_string(), value.to_string());
+                    match self.apply_setting(&key, &value) {
+                        Ok(()) => {
+                            self.info_message = Some(format!("{key} = {value}"));
+                            self.info_message_time = Some(std::time::Instant::now());
+                        }
+                        Err(e) => self.error_message = Some(e),
+                    }
+                }
+                _ => self.error_message = Some("Usage: set [<key> <value>] or set $<register> = <expr>".to_string()),
+            },
+            "perf" => {
+                self.info_message = Some(self.perf_summary());
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            "opslog" => {
+                let args: Vec<String> = parts[1..].iter().map(ToString::to_string).collect();
+                self.operation_log_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            "source-map" => match &parts[1..] {
+                [] => self.show_source_map(),
+                ["add", from, to] => {
+                    let (from, to) = ((*from).to_string(), (*to).to_string());
+                    self.add_source_map(&from, &to);
+                }
+                _ => self.error_message = Some("Usage: source-map [add <from> <to>]".to_string()),
+            },
+            "dsym" => match parts.get(1).copied() {
+                Some("add") if parts.len() > 2 => {
+                    // The rest of the line, so paths with spaces work
+                    let path = cmd[cmd.find("add").unwrap_or_default() + 3..].trim().to_string();
+                    self.add_dsym_search_path(Path::new(&path));
+                }
+                _ => self.error_message = Some("Usage: dsym add <path>".to_string()),
+            },
+            "backtrace" | "bt" => {
+                if parts.get(1) == Some(&"why") {
+                    self.dump_unwind_diagnostics();
+                } else {
+                    self.refresh_stack_trace();
+                    self.view_mode = ViewMode::Stack;
+                }
+            }
+            _ => {
+                self.error_message = Some(format!("Unknown command: {cmd}. Type 'help' for commands."));
+            }
+        }
+
+        self.command_palette_active = false;
+        self.command_input.clear();
+    }
+
+    /// `:enable`/`:disable <id|group>`: arm or disarm one breakpoint, or every breakpoint in a group
+    fn set_breakpoints_enabled(&mut self, target: &str, enabled: bool)
+    {
+        let by_id = target.parse::<u64>().ok().map(BreakpointId::from_raw);
+        let ids = if let Some(id) = by_id {
+            vec![id]
+        } else {
+            self.refresh_breakpoints();
+            let ids: Vec<BreakpointId> = self
+                .cached_breakpoints
+                .iter()
+                .filter(|bp| bp.options.group.as_deref() == Some(target))
+                .map(|bp| bp.id)
+                .collect();
+            if ids.is_empty() {
+                self.error_message = Some(format!("No breakpoint has the id or group '{target}'"));
+                return;
+            }
+            ids
+        };
+        for id in &ids {
+            let result = if enabled {
+                self.debugger.enable_breakpoint(*id)
+            } else {
+                self.debugger.disable_breakpoint(*id)
+            };
+            if let Err(e) = result {
+                let action = if enabled { "enable" } else { "disable" };
+                self.report_error(&format!("Failed to {action} breakpoint"), &e);
+                break;
+            }
+        }
+        self.refresh_breakpoints();
+        if by_id.is_none() {
+            let state = if enabled { "Enabled" } else { "Disabled" };
+            self.info_message = Some(format!("{state} {} breakpoint(s) in group {target}", ids.len()));
+            self.info_message_time = Some(std::time::Instant::now());
+        }
+    }
+
+    /// Run the [`actions`](ferros_core::breakpoints::BreakpointOptions::actions) of a
+    /// breakpoint that stopped the target
+    ///
+    /// Messages go to the timeline and commands run as if typed in the palette,
+    /// leaving whatever the user was typing alone.
+    fn run_breakpoint_actions(&mut self, id: BreakpointId, actions: Vec<BreakpointAction>)
+    {
+        for action in actions {
+            match action {
+                BreakpointAction::Log(message) => self.push_timeline_entry(
+                    TimelineEntry::new(
+                        TimelineEntryKind::BreakpointHit,
+                        format!("Breakpoint {}: {message}", id.raw()),
+                    )
+                    .with_breakpoint(Some(id)),
+                ),
+                BreakpointAction::Command(command) => {
+                    let typed = std::mem::replace(&mut self.command_input, command);
+                    let palette = self.command_palette_active;
+                    self.execute_command();
+                    self.command_input = typed;
+                    self.command_palette_active = palette;
+                }
+            }
+        }
+    }
+
+    /// `:handle <signal> stop|pass|ignore`: choose what a signal does; the policies are listed in the Overview
+    fn handle_signal_command(&mut self, args: &[&str])
+    {
+        let [signal, policy] = args else {
+            self.error_message = Some("Usage: handle <signal> stop|pass|ignore".to_string());
+            return;
+        };
+        let parsed = parse_signal(signal).and_then(|signal| policy.parse::<SignalPolicy>().map(|policy| (signal, policy)));
+        let (signal, policy) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        };
+        if let Err(e) = self.debugger.set_signal_policy(signal, policy) {
+            self.report_error("Failed to set signal policy", &e);
+            return;
+        }
+        let name = signal_name(signal).map_or_else(|| signal.to_string(), ToString::to_string);
+        self.info_message = Some(match policy {
+            SignalPolicy::Stop => format!("{name} stops the target"),
+            SignalPolicy::PassThrough => format!("{name} is passed to the target without stopping"),
+            SignalPolicy::Ignore => format!("{name} is dropped without stopping"),
+        });
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// `:stop-policy all|one`: choose whether a breakpoint stops every thread; shown in the Overview
+    fn stop_policy_command(&mut self, args: &[&str])
+    {
+        let [policy] = args else {
+            self.error_message = Some("Usage: stop-policy all|one".to_string());
+            return;
+        };
+        let policy = match policy.parse::<StopPolicy>() {
+            Ok(policy) => policy,
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        };
+        if let Err(e) = self.debugger.set_stop_policy(policy) {
+            self.report_error("Failed to set stop policy", &e);
+            return;
+        }
+        self.info_message = Some(match policy {
+            StopPolicy::AllThreads => "Every thread stops when one hits a breakpoint".to_string(),
+            StopPolicy::HitThreadOnly => "Only the thread that hits a breakpoint stops".to_string(),
+        });
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// `:async-stacks on|off`: show the `async fn`s a task is suspended in above its executor frame
+    fn async_stacks_command(&mut self, args: &[&str])
+    {
+        self.async_stacks = match args {
+            ["on"] => true,
+            ["off"] => false,
+            _ => {
+                self.error_message = Some("Usage: async-stacks on|off".to_string());
+                return;
+            }
+        };
+        self.refresh_stack_trace();
+        self.info_message = Some(if self.async_stacks {
+            "Call stacks show the futures a task is suspended in".to_string()
+        } else {
+            "Call stacks show only physical and inlined frames".to_string()
+        });
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// `:tls <name> [<thread>|all]`: read a thread-local on the active thread, another thread or every thread
+    fn thread_local_command(&mut self, args: &[&str])
+    {
+        let (name, threads) = match args {
+            [name] => (*name, self.debugger.active_thread().into_iter().collect()),
+            [name, "all"] => (*name, self.debugger.threads().unwrap_or_default()),
+            [name, thread] => {
+                let Ok(id) = thread.parse::<u64>() else {
+                    self.error_message = Some(format!("Invalid thread id: {thread}"));
+                    return;
+                };
+                (*name, vec![ThreadId::from(id)])
+            }
+            _ => {
+                self.error_message = Some("Usage: tls <name> [<thread>|all]".to_string());
+                return;
+            }
+        };
+        if threads.is_empty() {
+            self.error_message = Some("No thread to read thread-locals from".to_string());
+            return;
+        }
+        if !self.require_capability(self.debugger.capabilities().thread_locals, "thread-locals") {
+            return;
+        }
+
+        let mut lines = Vec::new();
+        for thread in threads {
+            match self.debugger.read_thread_local(thread, name) {
+                Ok(values) => lines.extend(values.iter().map(|value| value.render(&*self.debugger))),
+                Err(e) => {
+                    self.error_message = Some(format!("Failed to read {name} on thread {}: {e}", thread.raw()));
+                    return;
+                }
+            }
+        }
+        if lines.is_empty() {
+            self.error_message = Some(format!("No thread-local named {name}"));
+            return;
+        }
+        for line in &lines {
+            self.add_timeline_entry(TimelineEntryKind::ThreadLocal, line.clone());
+        }
+        self.info_message = Some(lines.join("; "));
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// `:print <expr>`: the value of an expression over the selected frame's registers and memory
+    ///
+    /// `:print <expr> as <type>` shows the value of `type` at the address the
+    /// expression gives, which may also be a symbol[+offset].
+    fn print_command(&mut self, args: &[&str])
+    {
+        if args.is_empty() {
+            self.error_message = Some("Usage: print <expr> [as <type>]".to_string());
+            return;
+        }
+        // Generic types have spaces after their commas
+        let text = args.join(" ");
+        let line = match text.rsplit_once(" as ") {
+            Some((source, type_name)) => match self.print_address(source.trim()) {
+                Ok(address) => {
+                    let type_name = type_name.trim();
+                    let value = render_value(&*self.debugger, type_name, address, RenderBudget::default());
+                    format!("{address} ({type_name}) = {value}")
+                }
+                Err(e) => {
+                    self.report_error("Failed to resolve address", &e);
+                    return;
+                }
+            },
+            None => match self.evaluate_expression(&text) {
+                Ok(Value::Address(address)) => match text::symbol_offset(&*self.debugger, address, None) {
+                    Some(symbol) => format!("{text} = {address} <{symbol}>"),
+                    None => format!("{text} = {address}"),
+                },
+                Ok(value) => format!("{text} = {value}"),
+                Err(e) => {
+                    self.report_error("Failed to evaluate", &e);
+                    return;
+                }
+            },
+        };
+        self.add_timeline_entry(TimelineEntryKind::Pin, line.clone());
+        self.info_message = Some(line);
+        self.info_message_time = Some(std::time::Instant::now());
+        self.error_message = None;
+    }
+
+    /// `:write <addr> <payload>`: write hex bytes, a sized integer or a quoted string to memory
+    ///
+    /// Regions without `w` are refused with their permissions. Every view
+    /// that shows memory is reread afterwards, since the write may have
+    /// patched code under the Source or Disassembly view.
+    fn write_memory_command(&mut self, text: &str)
+    {
+        let Some((address, payload)) = text.split_once(char::is_whitespace) else {
+            self.error_message = Some("Usage: write <addr> <hex bytes | u32:<value> | \"string\">".to_string());
+            return;
+        };
+        if !self.require_live("write memory") {
+            return;
+        }
+        let address = match self.print_address(address) {
+            Ok(address) => address,
+            Err(e) => {
+                self.report_error("Failed to resolve address", &e);
+                return;
+            }
+        };
+        let written = parse_payload(payload).and_then(|bytes| self.debugger.write_memory_checked(address, &bytes));
+        match written {
+            Ok(written) => {
+                let line = format!("Wrote {written} bytes at {address}");
+                self.add_timeline_entry(TimelineEntryKind::Pin, line.clone());
+                self.info_message = Some(line);
+                self.info_message_time = Some(std::time::Instant::now());
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.report_error("Failed to write memory", &e);
+                return;
+            }
+        }
+        self.memory.refresh(self.debugger.as_ref());
+        self.refresh_source_view();
+        self.refresh_disassembly();
+    }
+
+    /// Evaluate `source` with the selected frame's PC, SP and FP over the inspected registers
+    fn evaluate_expression(&self, source: &str) -> ferros_core::Result<Value>
+    {
+        let expression = Expression::parse(source)?;
+        let mut registers = self.inspected_registers()?;
+        if let Some((_, frame)) = self.selected_frame() {
+            registers.pc = frame.pc;
+            registers.sp = frame.sp;
+            registers.fp = frame.fp;
+        }
+        expression.evaluate(&*self.debugger, &registers)
+    }
+
+    /// The address `:print ... as <type>` reads: an expression, or failing that a symbol[+offset]
+    fn print_address(&mut self, source: &str) -> ferros_core::Result<Address>
+    {
+        let value = match self.evaluate_expression(source) {
+            Ok(value) => value,
+            Err(e) => {
+                return parse_address(source)
+                    .and_then(|expr| self.debugger.resolve_address(&expr))
+                    .map_err(|_| e);
+            }
+        };
+        value
+            .as_u64()
+            .map(Address::from)
+            .ok_or_else(|| DebuggerError::InvalidArgument(format!("`{source}` is a string, not an address")))
+    }
+
+    /// `:checkpoint add <addr> <len> [name]`, `:checkpoint run`, `:checkpoint list` and `:checkpoint clear`
+    fn range_checkpoint_command(&mut self, args: &[&str])
+    {
+        match args {
+            ["add", address, len, name @ ..] if name.len() <= 1 => {
+                let len = match range_checkpoints::parse_length(len) {
+                    Ok(len) => len,
+                    Err(e) => {
+                        self.error_message = Some(format!("Failed to add range: {e}"));
+                        return;
+                    }
+                };
+                let name = name.first().map(ToString::to_string);
+                let result = parse_address(address)
+                    .and_then(|expr| self.debugger.resolve_address(&expr))
+                    .and_then(|start| self.checkpoint_ranges.add(start, len, name));
+                match result {
+                    Ok(()) => {
+                        let label = self.checkpoint_ranges.ranges().last().map(WatchedRange::label);
+                        self.info_message = Some(format!("Checkpointing {len} bytes as {}", label.unwrap_or_default()));
+                        self.info_message_time = Some(std::time::Instant::now());
+                    }
+                    Err(e) => self.error_message = Some(format!("Failed to add range: {e}")),
+                }
+            }
+            ["run"] => self.start_range_checkpoint(),
+            ["list"] => {
+                let lines: Vec<String> = self
+                    .checkpoint_ranges
+                    .ranges()
+                    .iter()
+                    .map(|range| format!("{} ({} bytes at {})", range.label(), range.len, range.start))
+                    .collect();
+                self.info_message = Some(if lines.is_empty() {
+                    "No checkpointed ranges".to_string()
+                } else {
+                    format!("{} range(s): {}", lines.len(), lines.join("; "))
+                });
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            ["clear"] => {
+                self.checkpoint_ranges.clear();
+                self.pending_range_snapshots = None;
+                self.info_message = Some("Checkpointed ranges cleared".to_string());
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            _ => {
+                self.error_message = Some(
+                    "Usage: checkpoint add <address> <length> [name] | checkpoint run | checkpoint list | checkpoint clear"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    /// Capture every checkpointed range and resume; the diff is shown at the next stop
+    fn start_range_checkpoint(&mut self)
+    {
+        if !self.require_live("run a region checkpoint") || !self.require_target("run a region checkpoint") {
+            return;
+        }
+        if self.checkpoint_ranges.is_empty() {
+            self.error_message = Some("No ranges to checkpoint; add one with 'checkpoint add'".to_string());
+            return;
+        }
+        if !self.target_is_stopped {
+            self.error_message = Some("Stop the target before running a region checkpoint".to_string());
+            return;
+        }
+        let snapshots = match self.debugger.capture_ranges(self.checkpoint_ranges.ranges()) {
+            Ok(snapshots) => snapshots,
+            Err(e) => {
+                self.report_error("Failed to capture ranges", &e);
+                return;
+            }
+        };
+        if let Err(e) = self.debugger.resume() {
+            if !self.observe_target_gone(&e) {
+                self.report_error("Failed to resume", &e);
+            }
+            return;
+        }
+        let bytes = self.checkpoint_ranges.total_bytes();
+        self.pending_range_snapshots = Some(snapshots);
+        self.info_message = Some(format!("Captured {bytes} bytes; the diff opens at the next stop"));
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// Diff the ranges captured by `:checkpoint run` now that the target has stopped again
+    fn finish_range_checkpoint(&mut self)
+    {
+        let Some(snapshots) = self.pending_range_snapshots.take() else {
+            return;
+        };
+        let diffs = match self.debugger.diff_ranges(&snapshots) {
+            Ok(diffs) => diffs,
+            Err(e) => {
+                self.add_timeline_entry(TimelineEntryKind::Error, format!("Failed to diff checkpointed ranges: {e}"));
+                return;
+            }
+        };
+        for diff in &diffs {
+            self.add_timeline_entry(TimelineEntryKind::MemoryDiff, diff.summary());
+            for run in diff.runs.iter().take(MAX_TIMELINE_DIFF_RUNS) {
+                self.add_timeline_entry(TimelineEntryKind::MemoryDiff, format!("  {run}"));
+            }
+            if let Some(more) = diff.runs.len().checked_sub(MAX_TIMELINE_DIFF_RUNS).filter(|&more| more > 0) {
+                self.add_timeline_entry(TimelineEntryKind::MemoryDiff, format!("  … {more} more runs"));
+            }
+        }
+        self.range_diff_scroll = 0;
+        self.range_diffs = Some(diffs);
+    }
+
+    /// Scroll or close the region checkpoint popup
+    fn handle_range_diff_input(&mut self, key_event: crossterm::event::KeyEvent)
+    {
+        use crossterm::event::KeyCode;
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.range_diffs = None,
+            KeyCode::Up | KeyCode::Char('k') => self.range_diff_scroll = self.range_diff_scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.range_diff_scroll = self.range_diff_scroll.saturating_add(1),
+            KeyCode::PageUp => self.range_diff_scroll = self.range_diff_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.range_diff_scroll = self.range_diff_scroll.saturating_add(10),
+            _ => {}
+        }
+    }
+
+    /// `:pin <name> <addr> <type>`, `:pin list` and `:pin live`
+    fn pin_command(&mut self, args: &[&str])
+    {
+        match args {
+            ["list"] => {
+                if self.pins.is_empty() {
+                    self.info_message = Some("No pinned values".to_string());
+                } else {
+                    let lines: Vec<String> = self
+                        .pins
+                        .iter()
+                        .map(|pin| {
+                            let value = pin.value.as_ref().map_or_else(|| "-".to_string(), ToString::to_string);
+                            let location = format!("{} ({})", pin.address, pin.ty);
+                            format!("{} @ {location} = {value}", pin.name)
+                        })
+                        .collect();
+                    for line in &lines {
+                        self.add_timeline_entry(TimelineEntryKind::Pin, line.clone());
+                    }
+                    self.info_message = Some(format!("{} pinned value(s): {}", lines.len(), lines.join("; ")));
+                }
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            ["live"] => {
+                self.pins_live = !self.pins_live;
+                let state = if self.pins_live { "on" } else { "off" };
+                self.info_message = Some(format!("Live pin refresh while running: {state}"));
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            [name, address, ty] => {
+                let Ok(address) = u64::from_str_radix(address.trim_start_matches("0x"), 16) else {
+                    self.error_message = Some("Invalid address format".to_string());
+                    return;
+                };
+                let result = ty
+                    .parse::<PinType>()
+                    .and_then(|ty| self.pins.pin(name, Address::from(address), ty));
+                match result {
+                    Ok(_) => {
+                        self.refresh_pins();
+                        self.save_session_file();
+                        self.info_message = Some(format!("Pinned {name}"));
+                        self.info_message_time = Some(std::time::Instant::now());
+                    }
+                    Err(e) => self.error_message = Some(format!("Failed to pin {name}: {e}")),
+                }
+            }
+            _ => {
+                self.error_message = Some("Usage: pin <name> <address> <type> | pin list | pin live".to_string());
+            }
+        }
+    }
+
+    /// `:note <text>`, `:note addr <address> <text>` and `:note rm <id>`
+    ///
+    /// Without `addr` the note goes on what the view has selected: the
+    /// Timeline's entry, the Source view's breakpoint, or otherwise the
+    /// selected stack frame's PC.
+    fn note_command(&mut self, args: &str)
+    {
+        const USAGE: &str = "Usage: note <text> | note addr <address> <text> | note rm <id>";
+
+        let (first, rest) = args
+            .split_once(char::is_whitespace)
+            .map_or((args, ""), |(first, rest)| (first, rest.trim()));
+        let (target, text) = match first {
+            "" => {
+                self.error_message = Some(USAGE.to_string());
+                return;
+            }
+            "rm" => {
+                match rest.parse::<u64>() {
+                    Ok(id) if self.notes.remove(id) => {
+                        self.save_session_file();
+                        self.info_message = Some(format!("Removed note {id}"));
+                        self.info_message_time = Some(std::time::Instant::now());
+                    }
+                    Ok(id) => self.error_message = Some(format!("No note {id}")),
+                    Err(_) => self.error_message = Some(USAGE.to_string()),
+                }
+                return;
+            }
+            "addr" => {
+                let Some((address, text)) = rest.split_once(char::is_whitespace) else {
+                    self.error_message = Some(USAGE.to_string());
+                    return;
+                };
+                let resolved = parse_address(address).and_then(|expr| self.debugger.resolve_address(&expr));
+                match resolved {
+                    Ok(address) => (self.address_note_target(address.value()), text),
+                    Err(e) => {
+                        self.report_error("Failed to resolve note address", &e);
+                        return;
+                    }
+                }
+            }
+            _ => {
+                let Some(target) = self.selected_note_target() else {
+                    self.error_message =
+                        Some("Nothing selected to attach a note to; use note addr <address> <text>".to_string());
+                    return;
+                };
+                (target, args)
+            }
+        };
+
+        let label = target.to_string();
+        match self.notes.add(target, text) {
+            Ok(id) => {
+                self.save_session_file();
+                self.info_message = Some(format!("Note {id} on {label}"));
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            Err(e) => self.error_message = Some(format!("Failed to add note: {e}")),
+        }
+    }
+
+    /// What `:note <text>` attaches to in the current view
+    fn selected_note_target(&self) -> Option<NoteTarget>
+    {
+        if self.view_mode == ViewMode::Timeline
+            && let Some(entry) = self
+                .timeline
+                .selected
+                .or_else(|| self.visible_timeline().last().copied())
+                .and_then(|index| self.timeline_log.get(index))
+        {
+            return Some(NoteTarget::Timeline {
+                session: self.session_id,
+                entry: entry.sequence,
+                message: entry.message.clone(),
+            });
+        }
+        if self.view_mode == ViewMode::Source
+            && let Some(bp) = self
+                .breakpoints_state
+                .selected()
+                .and_then(|index| self.cached_breakpoints.get(index))
+        {
+            let address = bp.address.value();
+            return Some(
+                NoteTarget::Breakpoint {
+                    id: bp.id.raw(),
+                    address,
+                    image: None,
+                }
+                .with_image(self.image_anchor(address)),
+            );
+        }
+        let (_, frame) = self.selected_frame()?;
+        Some(self.address_note_target(frame.pc.value()))
+    }
+
+    /// Note target for `address`, with its image-relative form when it has one
+    fn address_note_target(&self, address: u64) -> NoteTarget
+    {
+        NoteTarget::address(address).with_image(self.image_anchor(address))
+    }
+
+    fn image_anchor(&self, address: u64) -> Option<ImageAnchor>
+    {
+        let regions = self.debugger.get_memory_regions().ok()?;
+        ImageAnchor::for_address(address, &regions, image_uuid)
+    }
+
+    /// Texts of the notes on `address` itself
+    #[must_use]
+    pub fn notes_at(&self, address: Address) -> Vec<&str>
+    {
+        self.notes
+            .at_address(address.value())
+            .map(|note| note.text.as_str())
+            .collect()
+    }
+
+    /// `:where` / `w`: summarize where the active thread stopped, in a popup and the timeline
+    fn show_where(&mut self)
+    {
+        let inspector = LiveInspector::new(self.debugger.as_mut());
+        match text::where_summary(&inspector, &self.notes, self.address_format) {
+            Ok(lines) => {
+                for line in &lines {
+                    self.add_timeline_entry(TimelineEntryKind::Diagnostic, line.clone());
+                }
+                self.where_summary = Some(lines);
+            }
+            Err(e) => self.report_error("Can't tell where the thread is", &e),
+        }
+    }
+
+    /// `:all-stacks`: every thread's call stack in one scrollable report
+    fn show_all_stacks(&mut self)
+    {
+        if !self.require_live("unwind the live threads") {
+            return;
+        }
+        let inspector = LiveInspector::new(self.debugger.as_mut());
+        match text::all_stacks(&inspector, self.address_format) {
+            Ok(lines) => {
+                self.all_stacks_scroll = 0;
+                self.all_stacks = Some(lines);
+            }
+            Err(e) => self.report_error("Can't show the threads' stacks", &e),
+        }
+    }
+
+    /// Scroll or close the `:all-stacks` report
+    fn handle_all_stacks_input(&mut self, key_event: crossterm::event::KeyEvent)
+    {
+        use crossterm::event::KeyCode;
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.all_stacks = None,
+            KeyCode::Up | KeyCode::Char('k') => self.all_stacks_scroll = self.all_stacks_scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.all_stacks_scroll = self.all_stacks_scroll.saturating_add(1),
+            KeyCode::PageUp => self.all_stacks_scroll = self.all_stacks_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.all_stacks_scroll = self.all_stacks_scroll.saturating_add(10),
+            _ => {}
+        }
+    }
+
+    /// `:debug replay-stop` / `:debug emit-stop <reason>`: push stops through the UI without the target
+    fn debug_command(&mut self, args: &[&str])
+    {
+        if !self.developer_tools {
+            self.error_message = Some("Developer tools are off; turn them on with set developer.tools on".to_string());
+            return;
+        }
+        match args {
+            ["replay-stop"] => match self.last_stop.clone() {
+                Some(event) => self.handle_synthetic_event(&event, "replay"),
+                None => self.error_message = Some("No stop to replay yet".to_string()),
+            },
+            ["emit-stop", reason @ ..] => {
+                let thread = self.debugger.active_thread();
+                match parse_stop_reason(reason, thread) {
+                    Ok(StopReason::Running) => self.handle_synthetic_event(&DebuggerEvent::TargetResumed, "synthetic"),
+                    Ok(reason) => self.handle_synthetic_event(&DebuggerEvent::TargetStopped { reason, thread }, "synthetic"),
+                    Err(e) => self.error_message = Some(e),
+                }
+            }
+            _ => self.error_message = Some("Usage: debug replay-stop | debug emit-stop <reason>".to_string()),
+        }
+    }
+
+    /// `:opslog on [<path>]` / `:opslog off`: record every backend operation as JSON lines
+    ///
+    /// The log goes to `~/.ferros/ops-<pid>.jsonl` unless a path is given.
+    /// Memory contents are only digested; `--ops-log-full` is CLI-only.
+    fn operation_log_command(&mut self, args: &[&str])
+    {
+        let (log, message) = match args {
+            ["on"] | ["on", _] => {
+                let path = args.get(1).map_or_else(
+                    || {
+                        let name = self
+                            .pid
+                            .map_or_else(|| "ops.jsonl".to_string(), |pid| format!("ops-{pid}.jsonl"));
+                        ferros_utils::ferros_dir().join(name)
+                    },
+                    PathBuf::from,
+                );
+                if let Some(parent) = path.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                match OperationLog::create(&path, OperationLogOptions::default()) {
+                    Ok(log) => (Some(log), format!("Logging backend operations to {}", path.display())),
+                    Err(e) => {
+                        self.report_error("Failed to create the operation log", &e);
+                        return;
+                    }
+                }
+            }
+            ["off"] => (None, "Operation log off".to_string()),
+            _ => {
+                self.error_message = Some("Usage: opslog on [<path>] | opslog off".to_string());
+                return;
+            }
+        };
+        match self.debugger.set_operation_log(log) {
+            Ok(()) => {
+                self.add_timeline_entry(TimelineEntryKind::Diagnostic, message.clone());
+                self.info_message = Some(message);
+                self.info_message_time = Some(std::time::Instant::now());
+            }
+            Err(e) => self.report_error("Can't log operations", &e),
+        }
+    }
+
+    /// `:source-map`: list the remaps from build-machine source paths to local ones
+    fn show_source_map(&mut self)
+    {
+        let map = self.debugger.source_map();
+        let message = if map.is_empty() {
+            "No source remaps; add one with source-map add <from> <to>".to_string()
+        } else {
+            let remaps: Vec<String> = map.remaps().iter().map(|(from, to)| format!("{from} -> {to}")).collect();
+            remaps.join(", ")
+        };
+        self.info_message = Some(message);
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// `:source-map add <from> <to>`: show sources compiled under `from` from `to`, symbolicating the stack again
+    fn add_source_map(&mut self, from: &str, to: &str)
+    {
+        let mut map = self.debugger.source_map();
+        map.add(from, to);
+        self.debugger.set_source_map(map);
+        let message = format!("Showing sources under {from} from {to}");
+        self.add_timeline_entry(TimelineEntryKind::Diagnostic, message.clone());
+        self.info_message = Some(message);
+        self.info_message_time = Some(std::time::Instant::now());
+        self.source_cache.clear();
+        if self.target_is_stopped {
+            self.refresh_stack_trace();
+            self.refresh_breakpoints();
+            self.refresh_source_view();
+        }
+    }
+
+    /// `:dsym add <path>`: look for dSYM bundles in `path` too, reloading images that have no DWARF
+    fn add_dsym_search_path(&mut self, path: &Path)
+    {
+        let message = match self.debugger.add_dsym_search_path(path.to_path_buf()) {
+            Ok(0) => format!("Searching {} for dSYMs", path.display()),
+            Ok(reloaded) => format!(
+                "Searching {} for dSYMs; reloading {reloaded} image{}",
+                path.display(),
+                if reloaded == 1 { "" } else { "s" }
+            ),
+            Err(e) => {
+                self.report_error("Can't add the dSYM path", &e);
+                return;
+            }
+        };
+        self.add_timeline_entry(TimelineEntryKind::Diagnostic, message.clone());
+        self.info_message = Some(message);
+        self.info_message_time = Some(std::time::Instant::now());
+        if self.target_is_stopped {
+            self.refresh_stack_trace();
+            self.refresh_source_view();
+        }
+    }
+
+    /// `:notes`: open the list of every note
+    fn open_notes_list(&mut self)
+    {
+        if self.notes.is_empty() {
+            self.info_message = Some("No notes; add one with note <text>".to_string());
+            self.info_message_time = Some(std::time::Instant::now());
+        } else {
+            self.notes_list = Some(0);
+        }
+    }
+
+    /// Move through, jump to, or close the `:notes` list
+    fn handle_notes_list_input(&mut self, key_event: crossterm::event::KeyEvent)
+    {
+        use crossterm::event::KeyCode;
+
+        let Some(selected) = self.notes_list else {
+            return;
+        };
+        let last = self.notes.len().saturating_sub(1);
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.notes_list = None,
+            KeyCode::Up | KeyCode::Char('k') => self.notes_list = Some(selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.notes_list = Some((selected + 1).min(last)),
+            KeyCode::Enter => {
+                self.notes_list = None;
+                if let Some(note) = self.notes.notes().get(selected).cloned() {
+                    self.jump_to_note(&note);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `:find <hex bytes | u32:<value> | "string">`: search readable memory and list the hits
+    fn find_command(&mut self, text: &str)
+    {
+        if text.is_empty() {
+            self.error_message = Some("Usage: find <hex bytes | u32:<value> | \"string\">".to_string());
+            return;
+        }
+        let found =
+            parse_payload(text).and_then(|pattern| self.debugger.search_memory(&pattern, MemorySearchScope::default()));
+        let found = match found {
+            Ok(found) => found,
+            Err(e) => {
+                self.report_error("Failed to search memory", &e);
+                return;
+            }
+        };
+        if found.hits.is_empty() {
+            self.info_message = Some(format!("No match for {text} in readable memory"));
+            self.info_message_time = Some(std::time::Instant::now());
+            return;
+        }
+
+        let regions = self.debugger.get_memory_regions().unwrap_or_default();
+        let hits = found
+            .hits
+            .iter()
+            .map(|&address| {
+                let place = text::symbol_offset(&*self.debugger, address, None).or_else(|| {
+                    regions
+                        .iter()
+                        .find(|region| region.contains(address))
+                        .map(|region| match &region.name {
+                            Some(name) => format!("{name} ({})", region.permissions),
+                            None => format!("{} ({})", region.start, region.permissions),
+                        })
+                });
+                (address, place.unwrap_or_default())
+            })
+            .collect();
+        self.memory_search = Some(MemorySearchPanel {
+            pattern: text.to_string(),
+            hits,
+            truncated: found.truncated,
+            selected: 0,
+        });
+    }
+
+    /// Move through the `:find` hits, show one in the Memory view, or close the list
+    fn handle_memory_search_input(&mut self, key_event: crossterm::event::KeyEvent)
+    {
+        use crossterm::event::KeyCode;
+
+        let Some(panel) = &mut self.memory_search else {
+            return;
+        };
+        let last = panel.hits.len().saturating_sub(1);
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.memory_search = None,
+            KeyCode::Up | KeyCode::Char('k') => panel.selected = panel.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => panel.selected = (panel.selected + 1).min(last),
+            KeyCode::PageUp => panel.selected = panel.selected.saturating_sub(10),
+            KeyCode::PageDown => panel.selected = (panel.selected + 10).min(last),
+            KeyCode::Home => panel.selected = 0,
+            KeyCode::End => panel.selected = last,
+            KeyCode::Enter => {
+                let hit = panel.hits.get(panel.selected).map(|&(address, _)| address);
+                self.memory_search = None;
+                if let Some(address) = hit {
+                    self.memory_history.follow(self.memory.address, address);
+                    self.open_memory(address);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Filter the process picker, move its highlight, or attach to the highlighted process
+    ///
+    /// Esc clears the filter, or quits when it is already empty.
+    fn handle_process_picker_input(&mut self, key_event: crossterm::event::KeyEvent) -> bool
+    {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        let Some(picker) = &mut self.process_picker else {
+            return false;
+        };
+        match key_event.code {
+            KeyCode::Esc if picker.filter.is_empty() => {
+                self.should_quit = true;
+                return true;
+            }
+            KeyCode::Esc => {
+                picker.filter.clear();
+                picker.selected = 0;
+            }
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => picker.reload(),
+            KeyCode::Char(c) => picker.push(c),
+            KeyCode::Backspace => picker.pop(),
+            KeyCode::Up => picker.move_by(-1),
+            KeyCode::Down => picker.move_by(1),
+            KeyCode::PageUp => picker.move_by(-10),
+            KeyCode::PageDown => picker.move_by(10),
+            KeyCode::Home => picker.selected = 0,
+            KeyCode::End => picker.move_by(isize::MAX),
+            KeyCode::Enter => self.attach_picked_process(),
+            _ => {}
+        }
+        false
+    }
+
+    /// Attach to the process highlighted in the picker and switch to the Overview
+    ///
+    /// A failed attach keeps the picker open with the error and its remedies.
+    fn attach_picked_process(&mut self)
+    {
+        let Some(picker) = &mut self.process_picker else {
+            return;
+        };
+        let Some(process) = picker.selected_process().cloned() else {
+            return;
+        };
+        let label = format!("{} ({})", process.name, process.pid.0);
+        if let Err(e) = self.debugger.attach(process.pid) {
+            picker.attach_failed(&label, &e);
+            return;
+        }
+
+        self.process_picker = None;
+        self.pid = Some(process.pid.0);
+        self.was_launched = false;
+        self.view_mode = ViewMode::Overview;
+        self.target_is_stopped = self.debugger.is_stopped();
+        self.last_stop_reason = if self.target_is_stopped {
+            self.debugger.stop_reason()
+        } else {
+            StopReason::Running
+        };
+        self.add_timeline_entry(TimelineEntryKind::Diagnostic, format!("Attached to {label}"));
+        if self.target_is_stopped {
+            self.record_stop_event(format_stop_reason(self.last_stop_reason));
+            self.add_timeline_entry(TimelineEntryKind::Stop, format_stop_reason(self.last_stop_reason));
+        }
+        self.refresh_breakpoints();
+        self.info_message = Some(format!("Attached to {label}"));
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// Show what `note` is attached to
+    ///
+    /// Breakpoint notes focus the breakpoint at their address, timeline notes
+    /// select their entry, and address notes select the stack frame at that PC.
+    fn jump_to_note(&mut self, note: &Note)
+    {
+        match &note.target {
+            NoteTarget::Timeline { session, entry, .. } => {
+                let index = (*session == self.session_id)
+                    .then(|| self.timeline_log.iter().position(|logged| logged.sequence == *entry))
+                    .flatten();
+                let Some(index) = index else {
+                    self.error_message = Some(format!("{} is no longer in the timeline", note.target));
+                    return;
+                };
+                self.view_mode = ViewMode::Timeline;
+                self.timeline.select(Some(index));
+            }
+            NoteTarget::Breakpoint { address, .. } => {
+                match self.cached_breakpoints.iter().find(|bp| bp.address.value() == *address) {
+                    Some(bp) => {
+                        let id = bp.id;
+                        self.focus_breakpoint(id);
+                    }
+                    None => self.error_message = Some(format!("No breakpoint at 0x{address:x} now")),
+                }
+            }
+            NoteTarget::Address { address, .. } => {
+                let frame = self.cached_stack_trace.as_ref().and_then(|frames| {
+                    frames
+                        .iter()
+                        .position(|frame| frame.pc.value() == *address)
+                        .map(|i| (i, frames[i].id))
+                });
+                if let Some((index, id)) = frame {
+                    self.view_mode = ViewMode::Stack;
+                    self.stack_frames_state.select(Some(index));
+                    self.selected_frame_id = Some(id);
+                } else {
+                    self.info_message = Some(format!("Note at 0x{address:x} (not on the stack): {}", note.text));
+                    self.info_message_time = Some(std::time::Instant::now());
+                }
+            }
+        }
+    }
+
+    /// Persist pins and notes to the per-binary session file, if there is one
+    fn save_session_file(&mut self)
+    {
+        let Some(path) = &self.session_file else {
+            return;
+        };
+        let file = SessionFile {
+            pins: self.pins.specs(),
+            notes: self.notes.notes().to_vec(),
+        };
+        if let Err(e) = file.save(path) {
+            self.error_message = Some(format!("Failed to save session file: {e}"));
+        }
+    }
+
+    /// Unwind diagnostic for the currently selected stack frame, if collected
+    #[must_use]
+    pub fn selected_unwind_diagnostic(&self) -> Option<&UnwindDiagnostic>
+    {
+        let frames = self.cached_stack_trace.as_ref()?;
+        let frame = frames.iter().find(|frame| Some(frame.id) == self.selected_frame_id)?;
+        find_diagnostic(&self.unwind_diagnostics, frame)
+    }
+
+    /// Write every unwind diagnostic for the current stack to the timeline
+    fn dump_unwind_diagnostics(&mut self)
+    {
+        self.refresh_stack_trace();
+        if self.unwind_diagnostics.is_empty() {
+            self.error_message = Some("No unwind diagnostics available (is the target stopped?)".to_string());
+            return;
+        }
+
+        let summaries: Vec<String> = self.unwind_diagnostics.iter().map(UnwindDiagnostic::summary).collect();
+        let count = summaries.len();
+        for summary in summaries {
+            self.add_timeline_entry(TimelineEntryKind::Diagnostic, summary);
+        }
+        self.info_message = Some(format!("Wrote {count} unwind diagnostics to the timeline (press 8)"));
+        self.info_message_time = Some(std::time::Instant::now());
+    }
+
+    /// Thread currently highlighted in the Threads view
+    fn highlighted_thread(&self) -> Option<ThreadId>
+    {
+        let threads = self.debugger.threads().ok()?;
+        threads.get(self.threads_state.selected().unwrap_or(0)).copied()
+    }
+
+    /// Toggle the bulk-operation mark on the highlighted thread
+    fn toggle_thread_mark(&mut self)
+    {
+        if let Some(thread) = self.highlighted_thread()
+            && !self.marked_threads.remove(&thread)
+        {
+            self.marked_threads.insert(thread);
+        }
+    }
+
+    /// Make the highlighted thread active and open its call stack
+    fn show_selected_thread_stack(&mut self)
+    {
+        let Some(thread) = self.highlighted_thread() else {
+            return;
+        };
+        if !self.require_live("switch threads") {
+            return;
+        }
+        if let Err(e) = self.debugger.set_active_thread(thread) {
+            self.report_error("Failed to set active thread", &e);
+            return;
+        }
+        self.cached_stack_trace = None;
+        self.refresh_stack_trace();
+        self.stack_frames_state.select(Some(0));
+        self.view_mode = ViewMode::Stack;
+    }
+
+    /// Mark every thread, or clear all marks if every thread is already marked
+    fn toggle_all_thread_marks(&mut self)
+    {
+        let threads = self.debugger.threads().unwrap_or_default();
+        if !threads.is_empty() && threads.iter().all(|thread| self.marked_threads.contains(thread)) {
+            self.marked_threads.clear();
+        } else {
+            self.marked_threads = threads.into_iter().collect();
+        }
+    }
+
+    /// Suspend or resume the marked threads (or the highlighted one when nothing is marked)
+    fn apply_thread_operation(&mut self, operation: ThreadOperation)
+    {
+        if operation == ThreadOperation::Resume && !self.require_live("resume threads") {
+            return;
+        }
+        let action = match operation {
+            ThreadOperation::Suspend => "suspend threads",
+            ThreadOperation::Resume => "resume threads",
+        };
+        if !self.require_target(action) {
+            return;
+        }
+        if !self.require_capability(self.debugger.capabilities().thread_control, "per-thread suspend and resume") {
+            return;
+        }
+        if !self.debugger.is_attached() {
+            self.error_message = Some("Not attached to a process".to_string());
+            self.info_message = None;
+            return;
+        }
+
+        // Drop marks on threads that have exited since they were marked
+        let threads = self.debugger.threads().unwrap_or_default();
+        self.marked_threads.retain(|thread| threads.contains(thread));
+
+        let targets: Vec<ThreadId> = if self.marked_threads.is_empty() {
+            self.highlighted_thread().into_iter().collect()
+        } else {
+            threads.into_iter().filter(|thread| self.marked_threads.contains(thread)).collect()
+        };
+        if targets.is_empty() {
+            self.error_message = Some("No thread selected".to_string());
+            return;
+        }
+
+        let (verb, past, kind) = match operation {
+            ThreadOperation::Suspend => ("suspend", "Suspended", TimelineEntryKind::Stop),
+            ThreadOperation::Resume => ("resume", "Resumed", TimelineEntryKind::Resume),
+        };
+        let mut succeeded = 0;
+        let mut failures = Vec::new();
+        for thread in targets {
+            let result = match operation {
+                ThreadOperation::Suspend => self.debugger.suspend_thread(thread),
+                ThreadOperation::Resume => self.debugger.resume_thread(thread),
+            };
+            match result {
+                Ok(()) => {
+                    succeeded += 1;
+                    self.add_timeline_entry(kind, format!("{past} thread {}", thread.raw()));
+                }
+                Err(e) => {
+                    self.add_timeline_entry(TimelineEntryKind::Error, format!("Thread {}: {verb} failed: {e}", thread.raw()));
+                    failures.push(format!("{}: {e}", thread.raw()));
+                }
+            }
+        }
+
+        if succeeded > 0 {
+            self.info_message = Some(format!("{past} {succeeded} thread(s)"));
+            self.info_message_time = Some(std::time::Instant::now());
+        } else {
+            self.info_message = None;
+        }
+        if !failures.is_empty() {
+            self.error_message = Some(format!("Failed to {verb} {} thread(s): {}", failures.len(), failures.join("; ")));
+        }
+    }
+
+    /// Cycle through layout presets
+    fn cycle_layout_preset(&mut self)
+    {
+        self.layout_preset = match self.layout_preset {
+            LayoutPreset::Compact => LayoutPreset::Standard,
+            LayoutPreset::Standard => LayoutPreset::Widescreen,
+            LayoutPreset::Widescreen => LayoutPreset::Compact,
+        };
+    }
+
+    /// Add an entry to the timeline log
+    pub fn add_timeline_entry(&mut self, kind: TimelineEntryKind, message: String)
+    {
+        self.push_timeline_entry(TimelineEntry::new(kind, message));
+    }
+
+    fn push_timeline_entry(&mut self, mut entry: TimelineEntry)
+    {
+        entry.sequence = self.next_timeline_sequence;
+        self.next_timeline_sequence += 1;
+        let kind = entry.kind;
+        self.timeline_log.push_back(entry);
+        let evicted = self.timeline_log.len() > MAX_TIMELINE_ENTRIES;
+        if evicted {
+            self.timeline_log.pop_front();
+        }
+        self.timeline.on_push(kind, evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ferros_core::mock::{self, MockDebugger};
+    use ferros_core::pins::PinValue;
+    use ferros_core::types::{Architecture, MemoryRegion, MemoryRegionId};
+
+    use super::*;
+    use crate::hexdump::{BYTES_PER_ROW, DEFAULT_ROWS};
+
+    fn press(app: &mut App, c: char)
+    {
+        app.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+
+    fn threads_view(count: u64) -> (App, std::rc::Rc<std::cell::RefCell<mock::MockState>>)
+    {
+        let (debugger, state) = MockDebugger::with_threads(count);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        press(&mut app, '3');
+        (app, state)
+    }
+
+    #[test]
+    fn thread_command_takes_an_id_or_part_of_a_name()
+    {
+        let (mut app, state) = threads_view(4);
+        let names = ["tokio-runtime-worker", "tokio-runtime-worker", "Signal Handler"];
+        for (id, name) in (2..).zip(names) {
+            let mut info = ThreadInfo::new(ThreadId::from(id));
+            info.name = Some(name.to_string());
+            state.borrow_mut().thread_infos.insert(info.id, info);
+        }
+
+        app.command_input = "thread signal h".to_string();
+        app.execute_command();
+        assert_eq!(state.borrow().active_thread, Some(ThreadId::from(4)));
+
+        app.command_input = "t 2".to_string();
+        app.execute_command();
+        assert_eq!(state.borrow().active_thread, Some(ThreadId::from(2)));
+
+        app.command_input = "t tokio".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.error_message.take().as_deref(),
+            Some("'tokio' matches 2 threads: tokio-runtime-worker (2), tokio-runtime-worker (3); use a thread id")
+        );
+        app.command_input = "t main".to_string();
+        app.execute_command();
+        assert_eq!(app.error_message.as_deref(), Some("No thread is named like 'main'"));
+        assert_eq!(state.borrow().active_thread, Some(ThreadId::from(2)));
+    }
+
+    #[test]
+    fn enter_on_a_thread_opens_its_stack_and_all_stacks_shows_every_thread()
+    {
+        let (mut app, state) = threads_view(3);
+        state.borrow_mut().stack.frames = vec![mock::frame(ThreadId::from(2), 0, "demo::wait", 3)];
+        app.target_is_stopped = true;
+        app.threads_state.select(Some(1));
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(state.borrow().active_thread, Some(ThreadId::from(2)));
+        assert_eq!(app.view_mode, ViewMode::Stack);
+        assert_eq!(app.cached_stack_trace.as_ref().map(Vec::len), Some(1));
+
+        app.command_input = "all-stacks".to_string();
+        app.execute_command();
+        let report = app.all_stacks.clone().unwrap();
+        assert_eq!(report.iter().filter(|line| line.starts_with("Thread ")).count(), 3);
+        assert!(app.modal_open());
+        app.handle_key_event(KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE));
+        assert_eq!(app.all_stacks_scroll, 10);
+        app.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(app.all_stacks.is_none() && !app.should_quit);
+        assert_eq!(state.borrow().active_thread, Some(ThreadId::from(2)));
+    }
+
+    #[test]
+    fn space_toggles_mark_on_highlighted_thread()
+    {
+        let (mut app, _) = threads_view(3);
+        app.threads_state.select(Some(1));
+
+        press(&mut app, ' ');
+        assert_eq!(app.marked_threads, HashSet::from([ThreadId::from(2)]));
+
+        press(&mut app, ' ');
+        assert!(app.marked_threads.is_empty());
+    }
+
+    #[test]
+    fn a_marks_all_then_clears()
+    {
+        let (mut app, _) = threads_view(3);
+        app.threads_state.select(Some(0));
+        press(&mut app, ' ');
+
+        press(&mut app, 'a');
+        assert_eq!(app.marked_threads.len(), 3);
+
+        press(&mut app, 'a');
+        assert!(app.marked_threads.is_empty());
+    }
+
+    #[test]
+    fn suspend_applies_to_marked_threads_only()
+    {
+        let (mut app, state) = threads_view(4);
+        app.threads_state.select(Some(3));
+        press(&mut app, ' ');
+        app.threads_state.select(Some(1));
+        press(&mut app, ' ');
+
+        press(&mut app, 's');
+
+        // Dispatched in thread-list order, not mark order
+        assert_eq!(state.borrow().calls, vec!["suspend 2", "suspend 4"]);
+        assert_eq!(app.debugger.thread_suspend_count(ThreadId::from(2)), 1);
+        assert_eq!(app.debugger.thread_suspend_count(ThreadId::from(3)), 0);
+        assert!(state.borrow().stopped, "whole-task resume/suspend must not be used");
+        assert_eq!(app.info_message.as_deref(), Some("Suspended 2 thread(s)"));
+    }
+
+    #[test]
+    fn resume_falls_back_to_highlighted_thread()
+    {
+        let (mut app, state) = threads_view(3);
+        app.threads_state.select(Some(2));
+
+        press(&mut app, 'r');
+
+        assert_eq!(state.borrow().calls, vec!["resume 3"]);
+        assert!(state.borrow().stopped, "whole-task resume must not be used");
+    }
+
+    #[test]
+    fn per_thread_failures_are_reported()
+    {
+        let (mut app, state) = threads_view(3);
+        state.borrow_mut().failing_threads.push(ThreadId::from(3));
+        press(&mut app, 'a');
+
+        press(&mut app, 's');
+
+        assert_eq!(state.borrow().calls.len(), 3);
+        assert_eq!(app.info_message.as_deref(), Some("Suspended 2 thread(s)"));
+        let error = app.error_message.as_deref().unwrap();
+        assert!(error.starts_with("Failed to suspend 1 thread(s): 3:"), "{error}");
+        assert!(
+            app.timeline_log
+                .iter()
+                .any(|entry| entry.kind == TimelineEntryKind::Error && entry.message.contains("Thread 3"))
+        );
+    }
+
+    #[test]
+    fn s_outside_threads_view_suspends_whole_task()
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        state.borrow_mut().stopped = false;
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        press(&mut app, 's');
+
+        assert!(state.borrow().calls.is_empty());
+        assert!(state.borrow().stopped);
+    }
+
+    #[test]
+    fn features_the_backend_lacks_are_refused_before_reaching_it()
+    {
+        use ferros_core::Capabilities;
+
+        let (mut app, state) = threads_view(2);
+        state.borrow_mut().capabilities = Some(Capabilities {
+            breakpoints: true,
+            ..Capabilities::NONE
+        });
+
+        press(&mut app, 's');
+        assert!(state.borrow().calls.is_empty());
+        assert_eq!(
+            app.error_message.take().as_deref(),
+            Some("This backend doesn't support per-thread suspend and resume")
+        );
+
+        app.command_input = "tls COUNTER".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.error_message.take().as_deref(),
+            Some("This backend doesn't support thread-locals")
+        );
+
+        let watch = Breakpoint::watchpoint(Address::from(0x3000), 8, ferros_core::WatchpointAccess::Write).build();
+        assert!(app.install_breakpoint(watch).is_err());
+        assert!(
+            app.install_breakpoint(Breakpoint::software(Address::from(0x2000)).build())
+                .is_ok()
+        );
+        assert_eq!(state.borrow().breakpoint_requests.len(), 1);
+        // Refusals aren't install failures the resume prompt should name
+        assert!(app.breakpoint_failures.is_empty());
+    }
+
+    #[test]
+    fn stack_overflow_names_recursive_function()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().stack.recursion.push(RecursionCycle {
+            first_frame: 1,
+            frame_count: 1,
+            period: 1,
+            repeats: 17_400,
+            function: Some("stack_overflow::recurse".to_string()),
+            truncated: false,
+        });
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::StackOverflow {
+                thread: ThreadId::from(1),
+                sp: 0x7000_4010,
+                guard_page: 0x7000_0000,
+            },
+            thread: Some(ThreadId::from(1)),
+        });
+
+        let expected = "Stack overflow on thread 1: `stack_overflow::recurse` recursed ~17400 times (frame #1)";
+        assert_eq!(app.status_message(), expected);
+        assert_eq!(app.error_message.as_deref(), Some(expected));
+        let last = app.timeline_log.back().unwrap();
+        assert_eq!((last.kind, last.message.as_str()), (TimelineEntryKind::Signal, expected));
+
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        assert_eq!(app.stack_overflow_message(), None);
+    }
+
+    #[test]
+    fn event_latency_is_recorded_and_aggregated()
+    {
+        use std::time::Duration;
+
+        let (debugger, _) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        assert_eq!(app.perf_summary(), "No debugger events received yet");
+
+        for ms in 1..=20 {
+            let event = if ms % 2 == 0 {
+                DebuggerEvent::TargetResumed
+            } else {
+                DebuggerEvent::TargetStopped {
+                    reason: StopReason::Breakpoint(0x1000),
+                    thread: Some(ThreadId::from(1)),
+                }
+            };
+            let mut envelope = EventEnvelope::new(event);
+            if ms % 2 == 1 {
+                // The stop spent 2ms in the backend before being emitted
+                envelope.source_received = Some(envelope.emitted);
+                envelope.emitted += Duration::from_millis(2);
+            }
+            app.handle_debugger_envelope(&envelope, envelope.emitted + Duration::from_millis(ms));
+        }
+
+        assert_eq!(app.event_latency.total(), 20);
+        assert_eq!(app.event_latency.percentile(50), Some(Duration::from_millis(10)));
+        assert_eq!(app.event_latency.percentile(95), Some(Duration::from_millis(19)));
+        assert_eq!(app.handler_latency.len(), 10);
+        assert_eq!(
+            app.perf_summary(),
+            "Event latency over 20 events: p50 10.0ms, p95 19.0ms, max 20.0ms | in handler: p50 2.0ms, p95 2.0ms"
+        );
+
+        let last = app.timeline_log.back().unwrap();
+        assert_eq!(last.kind, TimelineEntryKind::Resume);
+        let timing = last.timing.unwrap();
+        assert_eq!((timing.delivery, timing.handler), (Duration::from_millis(20), None));
+        let stop = &app.timeline_log[app.timeline_log.len() - 2];
+        assert_eq!(stop.timing.unwrap().handler, Some(Duration::from_millis(2)));
+
+        // Events without an envelope leave the statistics alone
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        assert_eq!(app.event_latency.total(), 20);
+        assert_eq!(app.timeline_log.back().unwrap().timing, None);
+    }
+
+    #[test]
+    fn tui_session_summary_matches_recorded_events()
+    {
+        use ferros_core::session::{SessionInvocation, SessionOutcome, SessionSummary};
+
+        let (debugger, _) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), true, &Config::default());
+        let path = std::env::temp_dir().join(format!("ferros-summary-{}.json", std::process::id()));
+        let mut recorder = SessionRecorder::new(SessionInvocation::launch("fixture", Vec::new()));
+        recorder.record_started(1);
+        app.enable_session_summary(recorder, path.clone());
+
+        for _ in 0..2 {
+            app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+                reason: StopReason::Breakpoint(0x1000),
+                thread: None,
+            });
+            app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        }
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Exited(3),
+            thread: None,
+        });
+        app.write_session_summary();
+
+        let summary = SessionSummary::from_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(summary.outcome, SessionOutcome::Exited { code: 3 });
+        assert_eq!(summary.stops_by_reason.get("breakpoint"), Some(&2));
+        assert!(app.session_summary.is_none());
+    }
+
+    #[test]
+    fn transcript_orders_output_before_the_stop_that_followed_it()
+    {
+        use ferros_core::transcript::{TranscriptOptions, TranscriptStream, read_transcript};
+
+        let (debugger, _) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), true, &Config::default());
+        press(&mut app, '5');
+        press(&mut app, 't');
+        assert!(!app.output_timestamps, "timestamps come from a transcript");
+
+        let path = std::env::temp_dir().join(format!("ferros-ui-transcript-{}.jsonl", std::process::id()));
+        let transcript = Transcript::create(&path, TranscriptOptions::default()).unwrap();
+        app.enable_transcript(transcript.clone());
+
+        // The stdout pump reads a line, then the target stops
+        let (sender, received) = std::sync::mpsc::channel();
+        let mut sink = transcript.sink(TranscriptStream::Stdout, move |record| sender.send(record).is_ok());
+        assert!(sink.write(b"about to divide by zero\n"));
+        for record in received.try_iter() {
+            app.push_process_output(ProcessOutputSource::Stdout, &record.text, Some(record.elapsed()));
+        }
+        let stop = EventEnvelope::new(DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x1000),
+            thread: Some(ThreadId::from(1)),
+        });
+        app.handle_debugger_envelope(&stop, stop.emitted);
+        press(&mut app, 't');
+        assert!(app.output_timestamps);
+        transcript.finish("session ended").unwrap();
+
+        let records = read_transcript(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let streams: Vec<_> = records.iter().map(|record| record.stream).collect();
+        assert_eq!(
+            streams,
+            [TranscriptStream::Stdout, TranscriptStream::Event, TranscriptStream::Marker]
+        );
+        let (line, event) = (&records[0], &records[1]);
+        assert_eq!(line.text, "about to divide by zero");
+        assert_eq!(event.text, "Hit breakpoint at 0x1000 (thread 1)");
+        assert!(line.seq < event.seq && line.mono_us <= event.mono_us);
+        assert_eq!(app.process_output.back().unwrap().at, Some(line.elapsed()));
+    }
+
+    #[test]
+    fn typed_lines_go_to_stdin_until_ctrl_d_closes_it()
+    {
+        let (debugger, _) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), true, &Config::default());
+        press(&mut app, '5');
+        press(&mut app, 'i');
+        assert!(
+            app.stdin_input.is_none() && app.error_message.is_some(),
+            "no stdin to type into"
+        );
+
+        let (writer, mut written) = tokio::sync::mpsc::unbounded_channel();
+        app.process_stdin = Some(writer);
+        press(&mut app, 'i');
+        for c in "ferris".chars() {
+            press(&mut app, c);
+        }
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(written.try_recv().unwrap(), b"ferris\n");
+        assert_eq!(
+            app.stdin_input.as_deref(),
+            Some(""),
+            "input mode stays open for the next line"
+        );
+        let echo = app.process_output.back().unwrap();
+        assert_eq!((echo.source, echo.text.as_str()), (ProcessOutputSource::Stdin, "ferris"));
+
+        press(&mut app, 'q');
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+        assert_eq!(written.try_recv().unwrap(), b"q");
+        assert!(app.stdin_input.is_none() && app.process_stdin.is_none());
+        assert!(written.try_recv().is_err(), "the writer sees the channel close");
+    }
+
+    #[test]
+    fn restart_relaunches_with_the_same_breakpoints()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().breakpoints.push(BreakpointInfo::new(
+            BreakpointId::from_raw(1),
+            Address::from(0x1000),
+            ferros_core::BreakpointKind::Software,
+        ));
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("Process 1 was attached to, not launched by ferros, so it can't be restarted")
+        );
+        assert!(state.borrow().launches.is_empty());
+
+        app.enable_restart("/bin/app".to_string(), LaunchOptions::new(["/bin/app"]));
+        app.error_message = None;
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(app.error_message, None);
+        assert_eq!(app.pid, Some(1001));
+        assert!(app.process_restarted && !app.target_is_stopped);
+        let state = state.borrow();
+        assert_eq!((state.kills, state.launches.len()), (1, 1));
+        assert_eq!(state.launches[0].1.args, ["/bin/app"]);
+        assert_eq!(state.breakpoint_requests.len(), 1);
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("Restarted app as process 1001 with 1 breakpoint(s)")
+        );
+    }
+
+    #[test]
+    fn groups_enable_together_and_actions_run_when_a_breakpoint_stops()
+    {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        use ferros_core::breakpoints::BreakpointOptions;
+
+        let (debugger, state) = MockDebugger::with_threads(1);
+        // 1 and 2 are in the group `io`
+        for id in 1..=3 {
+            let mut info = BreakpointInfo::new(
+                BreakpointId::from_raw(id),
+                Address::from(0x1000 * id),
+                ferros_core::BreakpointKind::Software,
+            );
+            info.enabled = true;
+            info.options = BreakpointOptions {
+                group: (id < 3).then(|| "io".to_string()),
+                ..BreakpointOptions::default()
+            };
+            state.borrow_mut().breakpoints.push(info);
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let run = |app: &mut App, command: &str| {
+            app.command_input = command.to_string();
+            app.error_message = None;
+            app.execute_command();
+            app.error_message.clone().or_else(|| app.info_message.clone())
+        };
+        let enabled = |state: &Rc<RefCell<mock::MockState>>| {
+            state.borrow().breakpoints.iter().map(|bp| bp.enabled).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            run(&mut app, "disable io").as_deref(),
+            Some("Disabled 2 breakpoint(s) in group io")
+        );
+        assert_eq!(enabled(&state), [false, false, true]);
+        run(&mut app, "enable io");
+        run(&mut app, "disable 3");
+        assert_eq!(enabled(&state), [true, true, false]);
+        assert_eq!(
+            run(&mut app, "disable net").as_deref(),
+            Some("No breakpoint has the id or group 'net'")
+        );
+
+        state.borrow_mut().breakpoints[0].options.actions = vec![
+            BreakpointAction::Log("reached".to_string()),
+            BreakpointAction::Command("disable 2".to_string()),
+        ];
+        app.refresh_breakpoints();
+        app.command_input = "pri".to_string();
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x1000),
+            thread: Some(ThreadId::from(1)),
+        });
+        assert!(
+            app.timeline_log
+                .iter()
+                .any(|entry| entry.message == "Breakpoint 1: reached" && entry.breakpoint == Some(BreakpointId::from_raw(1)))
+        );
+        assert_eq!(enabled(&state), [true, false, false]);
+        assert_eq!(app.command_input, "pri");
+
+        // Other stops run no actions
+        state.borrow_mut().breakpoints[1].enabled = true;
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x2000),
+            thread: Some(ThreadId::from(1)),
+        });
+        assert_eq!(enabled(&state), [true, true, false]);
+    }
+
+    #[test]
+    fn kill_leaves_the_session_without_a_target()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(7), true, &Config::default());
+        app.enable_restart("/bin/app".to_string(), LaunchOptions::new(["/bin/app"]));
+        app.command_input = "kill".to_string();
+        app.execute_command();
+        assert_eq!(state.borrow().kills, 1);
+        assert_eq!(app.target_gone, Some(7));
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("Killed process 7; :restart launches it again")
+        );
+
+        app.command_input = "kill".to_string();
+        app.execute_command();
+        assert_eq!(state.borrow().kills, 1, "nothing left to kill");
+    }
+
+    #[test]
+    fn vanished_target_is_reported_once_and_blocks_control()
+    {
+        use ferros_core::session::{SessionInvocation, SessionOutcome, SessionSummary};
+
+        let (debugger, state) = MockDebugger::with_threads(2);
+        let mut app = App::new(debugger, Some(42), true, &Config::default());
+        let path = std::env::temp_dir().join(format!("ferros-gone-{}.json", std::process::id()));
+        let mut recorder = SessionRecorder::new(SessionInvocation::attach(42));
+        recorder.record_started(42);
+        app.enable_session_summary(recorder, path.clone());
+        app.marked_threads.insert(ThreadId::from(1));
+
+        state.borrow_mut().gone = Some(42);
+        app.last_thread_refresh -= std::time::Duration::from_secs(3);
+        app.tick();
+        assert_eq!(app.target_gone, Some(42));
+        assert_eq!(app.status_message(), "Process 42 is gone");
+        assert!(app.marked_threads.is_empty());
+        assert!(!app.target_is_stopped);
+        let entries = app.timeline_log.len();
+        assert_eq!(app.timeline_log.back().unwrap().kind, TimelineEntryKind::Error);
+
+        // Later reads report it again but only the first one is logged
+        let err = app.debugger.read_registers().unwrap_err();
+        assert!(app.observe_target_gone(&err));
+        assert_eq!(app.timeline_log.len(), entries);
+
+        press(&mut app, 'r');
+        assert_eq!(app.error_message.as_deref(), Some("Process 42 is gone; can't resume"));
+        assert!(state.borrow().stopped);
+
+        app.write_session_summary();
+        let summary = SessionSummary::from_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(summary.outcome, SessionOutcome::TerminatedExternally { pid: 42 });
+    }
+
+    #[test]
+    fn exited_target_shows_its_code_and_blocks_control()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(7), false, &Config::default());
+        app.marked_threads.insert(ThreadId::from(1));
+
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Exited(3),
+            thread: None,
+        });
+        assert_eq!(app.target_exit, Some(3));
+        assert_eq!(app.status_message(), "Process exited with code 3");
+        assert_eq!(app.info_message.as_deref(), Some("Process exited with code 3"));
+        assert!(!app.target_is_stopped);
+        assert!(app.marked_threads.is_empty());
+        let entry = app.timeline_log.back().unwrap();
+        assert_eq!(entry.kind, TimelineEntryKind::Stop);
+        assert_eq!(entry.message, "Process exited with code 3");
+
+        // The backend's TargetGone after the exit adds nothing
+        state.borrow_mut().gone = Some(7);
+        let entries = app.timeline_log.len();
+        let err = app.debugger.read_registers().unwrap_err();
+        assert!(app.observe_target_gone(&err));
+        assert_eq!(app.target_gone, None);
+        assert_eq!(app.timeline_log.len(), entries);
+
+        press(&mut app, 'r');
+        assert_eq!(app.error_message.as_deref(), Some("Process exited with code 3; can't resume"));
+        press(&mut app, 's');
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("Process exited with code 3; can't suspend")
+        );
+        press(&mut app, 'i');
+        assert_eq!(app.error_message.as_deref(), Some("Process exited with code 3; can't step"));
+        assert_eq!(app.status_message(), "Process exited with code 3");
+    }
+
+    #[test]
+    fn address_format_is_set_or_cycled()
+    {
+        let (debugger, _) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        assert_eq!(app.address_format, AddressFormat::Full16);
+
+        press(&mut app, 'x');
+        assert_eq!(app.address_format, AddressFormat::Trimmed);
+        assert_eq!(app.info_message.as_deref(), Some("address.format = trimmed"));
+
+        app.command_input = "set address.format short".to_string();
+        app.execute_command();
+        assert_eq!(app.address_format, AddressFormat::Short12);
+        app.command_input = "set address.format wide".to_string();
+        app.execute_command();
+        assert_eq!(app.address_format, AddressFormat::Short12);
+        assert!(app.error_message.as_deref().unwrap().contains("full, trimmed, short"));
+
+        app.command_input = "set".to_string();
+        app.execute_command();
+        assert!(app.info_message.as_deref().unwrap().ends_with(
+            "address.format=short resume.confirm=on developer.tools=off break.panic=auto hang.probe=off hang.samples=8 \
+             hang.autostop=off"
+        ));
+        press(&mut app, 'x');
+        assert_eq!(app.address_format, AddressFormat::Full16);
+    }
+
+    #[test]
+    fn stops_raise_toasts_but_requested_suspends_do_not()
+    {
+        let (debugger, _) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "set stop.bell on".to_string();
+        app.execute_command();
+        assert!(app.stop_emphasis.settings.bell);
+
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: None,
+        });
+        assert_eq!(app.stop_emphasis.toasts().count(), 0);
+        assert!(!app.stop_emphasis.take_bell());
+
+        for _ in 0..2 {
+            app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+                reason: StopReason::Breakpoint(0x2000),
+                thread: Some(ThreadId::from(1)),
+            });
+        }
+        let toasts: Vec<(&str, u32)> = app.stop_emphasis.toasts().map(|t| (t.title.as_str(), t.repeats)).collect();
+        assert_eq!(toasts, vec![("Hit breakpoint at 0x2000 (thread 1)", 2)]);
+        assert!(app.stop_emphasis.take_bell());
+    }
+
+    #[test]
+    fn pinned_value_change_is_logged_and_unmapping_degrades()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().memory = (0x1000..0x1004).map(|a| (a, 0)).collect();
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "pin counter 0x1000 u32".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.pins.get("counter").unwrap().value.as_ref().unwrap().to_string(),
+            "0 (0x0)"
+        );
+
+        state.borrow_mut().memory.insert(0x1000, 7);
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x2000),
+            thread: None,
+        });
+        let pin = app.pins.get("counter").unwrap();
+        assert!(pin.changed);
+        let last = app.timeline_log.back().unwrap();
+        assert_eq!(last.kind, TimelineEntryKind::Pin);
+        assert_eq!(last.message, "counter: 0 (0x0) → 7 (0x7)");
+
+        state.borrow_mut().memory.remove(&0x1002);
+        app.refresh_pins();
+        assert_eq!(app.pins.get("counter").unwrap().value, Some(PinValue::Unreadable));
+
+        app.command_input = "unpin counter".to_string();
+        app.execute_command();
+        assert!(app.pins.is_empty());
+    }
+
+    #[test]
+    fn thread_locals_are_read_per_thread()
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        for (thread, count) in [(1, 3), (2, 7)] {
+            state
+                .borrow_mut()
+                .thread_locals
+                .insert(("demo::COUNTER".to_string(), ThreadId::from(thread)), count);
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.command_input = "tls COUNTER".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("demo::COUNTER [thread 1] @ 0x0000000060001000 (Cell<u64>) = 3 (0x3)")
+        );
+
+        app.command_input = "tls COUNTER all".to_string();
+        app.execute_command();
+        let logged: Vec<&str> = app
+            .timeline_log
+            .iter()
+            .filter(|entry| entry.kind == TimelineEntryKind::ThreadLocal)
+            .map(|entry| entry.message.as_str())
+            .collect();
+        assert_eq!(logged.len(), 3);
+        assert!(logged[2].starts_with("demo::COUNTER [thread 2]") && logged[2].ends_with("= 7 (0x7)"));
+
+        app.command_input = "tls MISSING".to_string();
+        app.execute_command();
+        assert_eq!(app.error_message.as_deref(), Some("No thread-local named MISSING"));
+    }
+
+    #[test]
+    fn print_and_tls_render_values_from_their_type()
+    {
+        use ferros_core::symbols::{TypeField, TypeKind, TypeSummary};
+
+        let layout = |name: &str, size: u64, fields: Vec<(&str, &str, u64)>| TypeSummary {
+            name: name.to_string(),
+            kind: TypeKind::Struct,
+            size_bits: Some(size * 8),
+            fields: fields
+                .into_iter()
+                .map(|(field, ty, offset)| TypeField {
+                    name: Some(field.to_string()),
+                    ty: Some(ty.to_string()),
+                    offset_bits: Some(offset * 8),
+                })
+                .collect(),
+            variants: Vec::new(),
+            discriminant: None,
+        };
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            let mut add = |summary: TypeSummary| {
+                state.types.insert(summary.name.clone(), std::sync::Arc::new(summary));
+            };
+            add(layout(
+                "alloc::string::String",
+                24,
+                vec![("ptr", "*const u8", 0), ("cap", "usize", 8), ("len", "usize", 16)],
+            ));
+            add(layout(
+                "app::Config",
+                32,
+                vec![("name", "alloc::string::String", 0), ("retries", "u32", 24)],
+            ));
+            add(layout("Cell<u64>", 8, vec![("value", "u64", 0)]));
+            let config: Vec<u8> = [0x9000_u64, 16, 6, 3].iter().flat_map(|word| word.to_le_bytes()).collect();
+            state.memory.extend((0x1000..).zip(config));
+            state.memory.extend((0x9000..).zip(*b"ferros"));
+            state.memory.extend((0x6000_1000..).zip(5_u64.to_le_bytes()));
+            state
+                .thread_locals
+                .insert(("demo::COUNTER".to_string(), ThreadId::from(1)), 5);
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.command_input = "print 0x1000 as app::Config".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some(r#"0x0000000000001000 (app::Config) = Config { name: "ferros", retries: 3 }"#)
+        );
+        app.command_input = "print".to_string();
+        app.execute_command();
+        assert_eq!(app.error_message.as_deref(), Some("Usage: print <expr> [as <type>]"));
+
+        app.command_input = "tls COUNTER".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("demo::COUNTER [thread 1] @ 0x0000000060001000 (Cell<u64>) = Cell<u64> { value: 5 }")
+        );
+    }
+
+    #[test]
+    fn print_evaluates_expressions_and_names_bad_tokens()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.pc = 0x1010;
+            state.general = vec![0; 31];
+            state.general[0] = 0x2000;
+            state.general[1] = 6;
+            state.memory.extend((0x2000..).zip(40_u64.to_le_bytes()));
+            state.function_starts.push(0x1000);
+            state.function_names.insert(0x1000, "demo::main".to_string());
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.command_input = "print *$x0 + $x1 / 3".to_string();
+        app.execute_command();
+        assert_eq!(app.info_message.as_deref(), Some("*$x0 + $x1 / 3 = 42 (0x2a)"));
+        assert_eq!(
+            app.timeline_log.back().map(|entry| entry.message.as_str()),
+            Some("*$x0 + $x1 / 3 = 42 (0x2a)")
+        );
+
+        app.command_input = "print $pc + 4".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("$pc + 4 = 0x0000000000001014 <demo::main+0x14>")
+        );
+
+        app.command_input = "print $x0 + ) 1".to_string();
+        app.execute_command();
+        let error = app.error_message.clone().unwrap();
+        assert!(error.contains("expected a value but found `)` at column 7"), "{error}");
+
+        app.command_input = "print *($x0 + 8)".to_string();
+        app.execute_command();
+        let error = app.error_message.clone().unwrap();
+        assert!(error.contains("reading 8 bytes at 0x0000000000002008 failed"), "{error}");
+    }
+
+    #[test]
+    fn registers_are_written_from_set_and_the_registers_view()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.pc = 0x1000;
+            state.general = vec![0; 31];
+            state.stack.frames = vec![mock::frame(ThreadId::from(1), 0, "demo::main", 3)];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.target_is_stopped = true;
+
+        app.command_input = "set $x0 = 0x20 + 10".to_string();
+        app.execute_command();
+        assert_eq!(app.info_message.as_deref(), Some("X0 = 0x2a"));
+        assert_eq!(state.borrow().general[0], 42);
+
+        // Moving the PC unwinds again
+        assert!(app.cached_stack_trace.is_none());
+        app.command_input = "set $pc = $pc + 8".to_string();
+        app.execute_command();
+        assert_eq!(state.borrow().pc, 0x1008);
+        assert_eq!(app.cached_stack_trace.as_ref().map(Vec::len), Some(1));
+
+        // `e` edits the selected row in place
+        app.view_mode = ViewMode::Registers;
+        let x1 = register_display_order(Architecture::Arm64)
+            .iter()
+            .position(|row| row.label == "X1");
+        app.registers_state.select(x1);
+        press(&mut app, 'e');
+        for c in "0xfz".chars() {
+            press(&mut app, c);
+        }
+        assert_eq!(app.register_edit.as_deref(), Some("0xf"));
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(app.register_edit, None);
+        assert_eq!(state.borrow().general[1], 0xf);
+
+        app.command_input = "set $rax = 1".to_string();
+        app.execute_command();
+        assert_eq!(app.error_message.as_deref(), Some("Unknown register $rax on arm64"));
+
+        state.borrow_mut().stopped = false;
+        app.command_input = "set $x0 = 1".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("Failed to set X0: Process must be stopped for this operation")
+        );
+        assert_eq!(state.borrow().general[0], 42);
+    }
+
+    #[test]
+    fn back_walks_checkpoints_and_resume_waits_for_live()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "checkpoints on".to_string();
+        app.execute_command();
+
+        let pc_at = |step: u64| 0x1_0000 + step * 4;
+        let sp_at = |step: u64| 0x7000_0000 - step * 32;
+        for step in 0..10 {
+            {
+                let mut state = state.borrow_mut();
+                state.pc = pc_at(step);
+                state.sp = sp_at(step);
+            }
+            app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+                reason: StopReason::Breakpoint(pc_at(step)),
+                thread: None,
+            });
+        }
+        assert_eq!(app.history.len(), 10);
+
+        for back in 1..=5 {
+            app.command_input = "back".to_string();
+            app.execute_command();
+            let registers = app.inspected_registers().unwrap();
+            assert_eq!(registers.pc, Address::from(pc_at(9 - back)));
+            assert_eq!(registers.sp, Address::from(sp_at(9 - back)));
+        }
+        assert_eq!(app.history.position(), Some((5, 10)));
+
+        press(&mut app, 'r');
+        assert!(state.borrow().stopped);
+        assert!(app.error_message.as_deref().unwrap().contains(":live"));
+
+        app.command_input = "live".to_string();
+        app.execute_command();
+        assert_eq!(app.inspected_registers().unwrap().pc, Address::from(pc_at(9)));
+        press(&mut app, 'r');
+        assert!(!state.borrow().stopped);
+    }
+
+    #[test]
+    fn history_view_inspects_recorded_stops_read_only()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let pc_at = |step: u64| 0x2_0000 + step * 8;
+        for step in 0..3 {
+            state.borrow_mut().pc = pc_at(step);
+            app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+                reason: StopReason::Breakpoint(pc_at(step)),
+                thread: None,
+            });
+        }
+        // Recorded without :checkpoints on, with no stack memory
+        assert_eq!(app.history.len(), 3);
+        assert_eq!(app.history.footprint(), 0);
+
+        app.command_input = "history".to_string();
+        app.execute_command();
+        assert_eq!(app.view_mode, ViewMode::History);
+
+        // Rows are newest first; the second row is the middle stop
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        press(&mut app, 'v');
+        assert_eq!(app.view_mode, ViewMode::Registers);
+        assert!(!app.is_live());
+        assert_eq!(app.inspected_registers().unwrap().pc, Address::from(pc_at(1)));
+
+        press(&mut app, 'r');
+        assert!(state.borrow().stopped);
+        assert!(app.error_message.as_deref().unwrap().contains("a past stop"));
+
+        app.command_input = "live".to_string();
+        app.execute_command();
+        assert!(app.is_live());
+        assert_eq!(app.inspected_registers().unwrap().pc, Address::from(pc_at(2)));
+    }
+
+    #[test]
+    fn history_keeps_the_registers_and_frames_of_the_stopped_thread()
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        let worker = ThreadId::from(2);
+        {
+            let mut state = state.borrow_mut();
+            state.pc = 0x1000;
+            state.stack.frames = vec![mock::frame(ThreadId::from(1), 0, "demo::main", 3)];
+            state.thread_pcs.insert(worker, 0x3000);
+            state
+                .thread_stacks
+                .insert(worker, vec![mock::frame(worker, 0, "demo::worker", 7)]);
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        // A breakpoint hit on a thread that isn't the active one
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x3000),
+            thread: Some(worker),
+        });
+        assert_eq!(app.debugger.active_thread(), Some(ThreadId::from(1)));
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: None,
+        });
+
+        app.command_input = "history".to_string();
+        app.execute_command();
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(app.view_mode, ViewMode::Stack);
+        let checkpoint = app.history.current().unwrap();
+        assert_eq!(checkpoint.thread, Some(worker));
+        assert_eq!(app.inspected_registers().unwrap().pc, Address::from(0x3000));
+        let frames = app.cached_stack_trace.as_ref().unwrap();
+        assert_eq!(frames[0].symbol.as_ref().unwrap().display_name(), "demo::worker");
+    }
+
+    #[test]
+    fn back_and_live_move_through_stops_without_stack_memory()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "back".to_string();
+        app.execute_command();
+        assert_eq!(app.error_message.as_deref(), Some("No stops recorded yet"));
+
+        for pc in [0x1000, 0x2000] {
+            state.borrow_mut().pc = pc;
+            app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+                reason: StopReason::Breakpoint(pc),
+                thread: None,
+            });
+        }
+        assert_eq!(app.history.footprint(), 0);
+
+        app.command_input = "back".to_string();
+        app.execute_command();
+        assert_eq!(app.history.position(), Some((1, 2)));
+        assert_eq!(app.inspected_registers().unwrap().pc, Address::from(0x1000));
+        app.command_input = "back".to_string();
+        app.execute_command();
+        assert_eq!(app.error_message.as_deref(), Some("No older stop"));
+
+        // Registers and frames only; no stack pages were copied
+        assert!(app.history.current().unwrap().captured_ranges().is_empty());
+        assert!(app.cached_stack_trace.is_some());
+
+        app.command_input = "live".to_string();
+        app.execute_command();
+        assert!(app.is_live());
+        assert_eq!(app.info_message.as_deref(), Some("Back to the live target"));
+        assert_eq!(app.inspected_registers().unwrap().pc, Address::from(0x2000));
+    }
+
+    #[test]
+    fn views_read_registers_from_the_stop_snapshot_until_resume()
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        state.borrow_mut().pc = 0x1000;
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x1000),
+            thread: None,
+        });
+        assert_eq!(app.register_snapshot.len(), 2);
+
+        let reads = state.borrow().register_reads;
+        state.borrow_mut().pc = 0x2000;
+        for _ in 0..3 {
+            assert_eq!(app.inspected_registers().unwrap().pc, Address::from(0x1000));
+        }
+        assert_eq!(state.borrow().register_reads, reads);
+        assert!(app.cached_registers_for(ThreadId::from(2)).is_some());
+
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        assert!(app.cached_registers_for(ThreadId::from(1)).is_none());
+        assert_eq!(app.inspected_registers().unwrap().pc, Address::from(0x2000));
+    }
+
+    #[test]
+    fn stacks_are_unwound_again_once_background_symbols_load()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let thread = ThreadId::from(1);
+        let mut unnamed = mock::frame(thread, 0, "main", 3);
+        unnamed.symbol = None;
+        unnamed.location = None;
+        state.borrow_mut().stack.frames = vec![unnamed];
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: None,
+        });
+        assert!(app.cached_stack_trace.as_ref().unwrap()[0].symbol.is_none());
+
+        state.borrow_mut().stack.frames = vec![mock::frame(thread, 0, "main", 3)];
+        app.handle_debugger_event(&DebuggerEvent::SymbolsLoaded { images: 2 });
+        let frames = app.cached_stack_trace.as_ref().unwrap();
+        assert_eq!(frames[0].symbol.as_ref().unwrap().display_name(), "main");
+    }
+
+    #[test]
+    fn break_command_accepts_addresses_lines_and_functions()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        for command in ["break 0x1000", "b src/main.rs:42", "b my_crate::run"] {
+            app.command_input = command.to_string();
+            app.execute_command();
+        }
+
+        let requests = state.borrow().breakpoint_requests.clone();
+        assert_eq!(
+            requests,
+            vec![
+                Breakpoint::software(Address::from(0x1000)).build(),
+                Breakpoint::line("src/main.rs", 42).build(),
+                Breakpoint::symbol("my_crate::run").build(),
+            ]
+        );
+        assert!(app.error_message.is_none());
+    }
+
+    #[test]
+    fn function_breakpoints_list_the_candidates_of_an_ambiguous_name()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.function_names.insert(0x1_0000_1000, "demo::worker::run".to_string());
+            state.function_names.insert(0x1_0000_2000, "demo::server::run".to_string());
+            state.function_names.insert(0x1_0000_3000, "demo::main".to_string());
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.command_input = "break run".to_string();
+        app.execute_command();
+        assert!(state.borrow().breakpoint_requests.is_empty());
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some(
+                "run matches 2 functions: demo::worker::run at 0x0000000100001000, demo::server::run at \
+                 0x0000000100002000; use the full name"
+            )
+        );
+
+        app.command_input = "b worker::run".to_string();
+        app.execute_command();
+        assert_eq!(
+            state.borrow().breakpoint_requests,
+            vec![Breakpoint::software(Address::from(0x1_0000_1000)).build()]
+        );
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("Added breakpoint at demo::worker::run (0x0000000100001000)")
+        );
+        assert!(app.error_message.is_none());
+    }
+
+    #[test]
+    fn tbreak_adds_one_shot_breakpoints()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state
+            .borrow_mut()
+            .source_lines
+            .insert(("src/main.rs".to_string(), 42), vec![0x1_0000_2000, 0x1_0000_3000]);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.command_input = "tbreak src/main.rs:42".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("Added 2 temporary breakpoints at src/main.rs:42")
+        );
+        app.command_input = "tb 0x4000".to_string();
+        app.execute_command();
+        app.command_input = "b 0x5000".to_string();
+        app.execute_command();
+
+        let one_shot: Vec<_> = state
+            .borrow()
+            .breakpoint_requests
+            .iter()
+            .map(|request| (request.address().map(Address::value), request.options.one_shot))
+            .collect();
+        assert_eq!(
+            one_shot,
+            vec![
+                (Some(0x1_0000_2000), true),
+                (Some(0x1_0000_3000), true),
+                (Some(0x4000), true),
+                (Some(0x5000), false)
+            ]
+        );
+
+        // `-f` forces the breakpoint past the instruction boundary check
+        app.command_input = "b -f 0x5001".to_string();
+        app.execute_command();
+        let forced: Vec<_> = state
+            .borrow()
+            .breakpoint_requests
+            .iter()
+            .map(|request| request.options.force)
+            .collect();
+        assert_eq!(forced, [false, false, false, false, true]);
+    }
+
+    #[test]
+    fn disassembly_view_follows_the_pc_and_toggles_breakpoints_on_the_selection()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = true;
+            state.pc = 0x1040;
+            // nop everywhere, with a ret at the PC
+            for word in (0xf80..0x1100).step_by(4) {
+                let insn: u32 = if word == 0x1040 { 0xd65f_03c0 } else { 0xd503_201f };
+                state.memory.extend((word..).zip(insn.to_le_bytes()));
+            }
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let up = |app: &mut App| app.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+
+        press(&mut app, '0');
+        assert_eq!(app.view_mode, ViewMode::Disassembly);
+        let view = &app.disassembly;
+        assert_eq!(view.listing.first().map(|insn| insn.address), Some(Address::from(0x1020)));
+        assert_eq!(view.selected_address(), Some(Address::from(0x1040)));
+        let current: Vec<_> = view
+            .listing
+            .iter()
+            .filter(|insn| insn.is_current_pc)
+            .map(ferros_core::types::DisassembledInstruction::text)
+            .collect();
+        assert_eq!(current, ["ret"]);
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        press(&mut app, 'b');
+        let requested: Vec<_> = state
+            .borrow()
+            .breakpoint_requests
+            .iter()
+            .filter_map(BreakpointRequest::address)
+            .collect();
+        assert_eq!(requested, [Address::from(0x1044)]);
+
+        // Scrolling past the top decodes the instructions above the window, a page at a time
+        let first = |app: &App| app.disassembly.listing.first().map(|insn| insn.address.value());
+        for _ in 0..10 {
+            up(&mut app);
+        }
+        assert_eq!(app.disassembly.selected_address(), Some(Address::from(0x101c)));
+        assert_eq!(first(&app), Some(0x1000));
+        for _ in 0..8 {
+            up(&mut app);
+        }
+        assert_eq!(app.disassembly.selected_address(), Some(Address::from(0xffc)));
+        assert_eq!(first(&app), Some(0xf80));
+
+        // Pressing 0 again goes back to the PC
+        press(&mut app, '0');
+        assert_eq!(app.disassembly.selected_address(), Some(Address::from(0x1040)));
+    }
+
+    #[test]
+    fn memory_view_dumps_from_the_palette_and_from_registers()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = true;
+            state.pc = 0x10;
+            state.sp = 0x1_0000_1800;
+            // One mapped page
+            state.memory = (0x1_0000_1000..0x1_0000_2000).map(|address| (address, 0x41)).collect();
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let key = |app: &mut App, code: KeyCode| app.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+
+        // The dump runs off the end of the page; the rest is unreadable rather than an error
+        app.command_input = "mem 0x100001ff0".to_string();
+        app.execute_command();
+        assert_eq!(app.view_mode, ViewMode::Memory);
+        let readable = app.memory.bytes.iter().filter(|byte| byte.is_some()).count();
+        assert_eq!((app.memory.bytes.len(), readable), (DEFAULT_ROWS * BYTES_PER_ROW, 16));
+        assert!(app.memory.error.is_none());
+
+        key(&mut app, KeyCode::PageUp);
+        assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1ef0)));
+        assert!(app.memory.bytes.iter().all(|&byte| byte == Some(0x41)));
+        key(&mut app, KeyCode::Down);
+        assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1f00)));
+
+        // Enter on a register dumps where it points, if it looks like an address
+        let order = register_display_order(Architecture::Arm64);
+        let row = |id: RegisterId| order.iter().position(|row| row.id == id);
+        app.view_mode = ViewMode::Registers;
+        app.registers_state.select(row(RegisterId::Sp));
+        key(&mut app, KeyCode::Enter);
+        assert_eq!(app.view_mode, ViewMode::Memory);
+        assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1800)));
+
+        app.view_mode = ViewMode::Registers;
+        app.registers_state.select(row(RegisterId::Pc));
+        key(&mut app, KeyCode::Enter);
+        assert_eq!(app.view_mode, ViewMode::Registers);
+        let message = app.error_message.as_deref().unwrap_or_default();
+        assert!(message.ends_with("= 0x10 doesn't look like an address"), "{message}");
+    }
+
+    #[test]
+    fn write_patches_writable_memory_and_refuses_code()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = true;
+            state.memory = (0x1000..0x3000).map(|address| (address, 0)).collect();
+            state.regions = vec![
+                MemoryRegion::new(
+                    MemoryRegionId(0),
+                    Address::from(0x1000),
+                    Address::from(0x2000),
+                    "r-x".into(),
+                    Some("/bin/demo".into()),
+                ),
+                MemoryRegion::new(
+                    MemoryRegionId(1),
+                    Address::from(0x2000),
+                    Address::from(0x3000),
+                    "rw-".into(),
+                    None,
+                ),
+            ];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "mem 0x2000".to_string();
+        app.execute_command();
+
+        app.command_input = "write 0x2000 u32:0x41424344".to_string();
+        app.execute_command();
+        assert_eq!(app.info_message.as_deref(), Some("Wrote 4 bytes at 0x0000000000002000"));
+        assert_eq!(app.memory.bytes[..4], [Some(0x44), Some(0x43), Some(0x42), Some(0x41)]);
+
+        app.command_input = r#"write 0x2004 "a  b""#.to_string();
+        app.execute_command();
+        assert_eq!(app.memory.bytes[4..8], [Some(b'a'), Some(b' '), Some(b' '), Some(b'b')]);
+
+        app.command_input = "write 0x1000 d5 03 20 1f".to_string();
+        app.execute_command();
+        let message = app.error_message.clone().unwrap_or_default();
+        assert!(message.contains("(/bin/demo) with permissions r-x"), "{message}");
+        assert_eq!(state.borrow().memory[&0x1000], 0);
+
+        app.command_input = "write 0x2000 0x41".to_string();
+        app.execute_command();
+        let message = app.error_message.clone().unwrap_or_default();
+        assert!(message.contains("give its size, e.g. u32:0x41"), "{message}");
+    }
+
+    #[test]
+    fn find_lists_hits_and_shows_the_selected_one_in_memory()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = true;
+            state.memory = (0x1000..0x3000).map(|address| (address, 0)).collect();
+            for at in [0x1800, 0x2010, 0x2ffc] {
+                state.memory.extend((at..).zip(*b"ferros"));
+            }
+            state.regions = vec![
+                MemoryRegion::new(
+                    MemoryRegionId(0),
+                    Address::from(0x1000),
+                    Address::from(0x2000),
+                    "r-x".into(),
+                    Some("/bin/demo".into()),
+                ),
+                MemoryRegion::new(
+                    MemoryRegionId(1),
+                    Address::from(0x2000),
+                    Address::from(0x3000),
+                    "rw-".into(),
+                    None,
+                ),
+            ];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.command_input = r#"find "ferros""#.to_string();
+        app.execute_command();
+        let panel = app.memory_search.as_ref().expect("hits are listed");
+        let hits: Vec<u64> = panel.hits.iter().map(|(address, _)| address.value()).collect();
+        assert_eq!(hits, [0x1800, 0x2010]);
+        assert_eq!(panel.hits[0].1, "/bin/demo (r-x)");
+        assert!(app.modal_open());
+
+        press(&mut app, 'j');
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(app.memory_search.is_none());
+        assert_eq!(app.view_mode, ViewMode::Memory);
+        assert_eq!(app.memory.address, Some(Address::from(0x2010)));
+
+        app.command_input = "find de ad".to_string();
+        app.execute_command();
+        assert!(app.memory_search.is_none());
+        assert_eq!(app.info_message.as_deref(), Some("No match for de ad in readable memory"));
+
+        app.command_input = "find".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some(r#"Usage: find <hex bytes | u32:<value> | "string">"#)
+        );
+    }
+
+    #[test]
+    fn handle_sets_signal_policies()
+    {
+        use ferros_core::signals::{SignalPolicy, parse_signal};
+
+        let usr1 = parse_signal("USR1").unwrap();
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let mut run = |command: &str| {
+            app.command_input = command.to_string();
+            app.error_message = None;
+            app.execute_command();
+            app.error_message.clone().or_else(|| app.info_message.clone())
+        };
+
+        assert_eq!(
+            run("handle SIGUSR1 pass").as_deref(),
+            Some("SIGUSR1 is passed to the target without stopping")
+        );
+        assert_eq!(run("handle 2 ignore").as_deref(), Some("SIGINT is dropped without stopping"));
+        assert_eq!(
+            state.borrow().signal_policies.iter().collect::<Vec<_>>(),
+            [(2, SignalPolicy::Ignore), (usr1, SignalPolicy::PassThrough)]
+        );
+        assert_eq!(run("handle int stop").as_deref(), Some("SIGINT stops the target"));
+        assert_eq!(state.borrow().signal_policies.policy(2), SignalPolicy::Stop);
+
+        assert!(run("handle SIGNOPE pass").unwrap().contains("Unknown signal 'SIGNOPE'"));
+        assert!(run("handle 99 pass").unwrap().contains("99 is not a signal number"));
+        assert!(run("handle 10 drop").unwrap().contains("expected stop, pass or ignore"));
+        assert_eq!(run("handle 10").as_deref(), Some("Usage: handle <signal> stop|pass|ignore"));
+    }
+
+    #[test]
+    fn stop_policy_switches_between_all_and_one_thread()
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let mut run = |command: &str| {
+            app.command_input = command.to_string();
+            app.error_message = None;
+            app.execute_command();
+            app.error_message.clone().or_else(|| app.info_message.clone())
+        };
+
+        assert_eq!(
+            run("stop-policy all").as_deref(),
+            Some("Every thread stops when one hits a breakpoint")
+        );
+        assert_eq!(state.borrow().stop_policy, StopPolicy::AllThreads);
+        assert_eq!(
+            run("stop-policy one").as_deref(),
+            Some("Only the thread that hits a breakpoint stops")
+        );
+        assert_eq!(state.borrow().stop_policy, StopPolicy::HitThreadOnly);
+
+        assert!(run("stop-policy some").unwrap().contains("expected all or one"));
+        assert_eq!(run("stop-policy").as_deref(), Some("Usage: stop-policy all|one"));
+    }
+
+    #[test]
+    fn async_stacks_asks_the_unwinder_for_async_frames()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.target_is_stopped = true;
+        let mut run = |command: &str| {
+            app.command_input = command.to_string();
+            app.error_message = None;
+            app.execute_command();
+            app.error_message.clone().or_else(|| app.info_message.clone())
+        };
+
+        assert_eq!(
+            run("async-stacks on").as_deref(),
+            Some("Call stacks show the futures a task is suspended in")
+        );
+        assert!(state.borrow().unwind_options.last().unwrap().async_frames);
+        assert_eq!(
+            run("async-stacks off").as_deref(),
+            Some("Call stacks show only physical and inlined frames")
+        );
+        assert!(!state.borrow().unwind_options.last().unwrap().async_frames);
+        assert_eq!(run("async-stacks maybe").as_deref(), Some("Usage: async-stacks on|off"));
+    }
+
+    #[test]
+    fn dsym_paths_reload_images_and_mismatched_dsyms_are_reported()
+    {
+        use ferros_core::symbols::{DsymMismatch, ImageInfo};
+
+        let image = |path: &str, load_address: u64, dsym_mismatch: Option<DsymMismatch>| ImageInfo {
+            id: ImageId::from_parts(Path::new(path), load_address),
+            path: path.into(),
+            load_address: Address::from(load_address),
+            end_address: Address::from(load_address + 0x4000),
+            debug_level: DebugLevel::SymbolsOnly,
+            has_eh_frame: true,
+            symbol_count: 12,
+            dsym: None,
+            dsym_mismatch,
+        };
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let mismatch = DsymMismatch {
+            path: "/bin/demo.dSYM/Contents/Resources/DWARF/demo".into(),
+            expected: "00112233".to_string(),
+            found: Some("44556677".to_string()),
+        };
+        state.borrow_mut().modules = vec![image("/bin/demo", 0x1_0000_0000, Some(mismatch))];
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: None,
+        });
+        let ignored = app
+            .timeline_log
+            .iter()
+            .find(|entry| entry.kind == TimelineEntryKind::Error)
+            .map(|entry| entry.message.as_str());
+        assert_eq!(
+            ignored,
+            Some("Ignored the dSYM of /bin/demo: /bin/demo.dSYM/Contents/Resources/DWARF/demo has UUID 44556677, not 00112233")
+        );
+
+        let mut run = |command: &str| {
+            app.command_input = command.to_string();
+            app.error_message = None;
+            app.execute_command();
+            app.error_message.clone().or_else(|| app.info_message.clone())
+        };
+        assert_eq!(
+            run("dsym add /Volumes/Build Symbols").as_deref(),
+            Some("Searching /Volumes/Build Symbols for dSYMs; reloading 1 image")
+        );
+        assert_eq!(state.borrow().dsym_search_paths, [PathBuf::from("/Volumes/Build Symbols")]);
+        assert_eq!(run("dsym add").as_deref(), Some("Usage: dsym add <path>"));
+        assert_eq!(run("dsym").as_deref(), Some("Usage: dsym add <path>"));
+    }
+
+    #[test]
+    fn source_maps_and_source_dirs_find_sources_built_elsewhere()
+    {
+        let root = std::env::temp_dir().join(format!("ferros-source-map-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("app/src")).unwrap();
+        std::fs::write(root.join("app/src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(root.join("util.rs"), "pub fn util() {}\n").unwrap();
+
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut frame = mock::frame(ThreadId::from(1), 0, "demo::main", 1);
+        let location = frame.location.as_mut().unwrap();
+        location.file = "/home/runner/work/app/src/main.rs".to_string();
+        location.dwarf_file = Some(location.file.clone());
+        state.borrow_mut().stack.frames = vec![frame];
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.refresh_stack_trace();
+        app.refresh_source_view();
+        let error = app.error_message.take().unwrap_or_default();
+        assert!(error.contains("Path: /home/runner/work/app/src/main.rs\nTried:\n  /home/runner/work/app/src/main.rs\n"), "{error}");
+        assert!(error.contains("source-map add FROM TO"), "{error}");
+
+        let run = |app: &mut App, command: &str| {
+            app.command_input = command.to_string();
+            app.error_message = None;
+            app.execute_command();
+            app.error_message.clone().or_else(|| app.info_message.clone())
+        };
+        let local = root.join("app");
+        let message = run(&mut app, &format!("source-map add /home/runner/work/app {}", local.display()));
+        assert_eq!(message, Some(format!("Showing sources under /home/runner/work/app from {}", local.display())));
+        let file = local.join("src/main.rs").display().to_string();
+        assert_eq!(app.current_source_file.as_deref(), Some(file.as_str()));
+        assert_eq!(app.source_cache[&file].lines, ["fn main() {}"]);
+        assert_eq!(
+            run(&mut app, "source-map"),
+            Some(format!("/home/runner/work/app -> {}", local.display()))
+        );
+        assert_eq!(run(&mut app, "source-map add /x").as_deref(), Some("Usage: source-map [add <from> <to>]"));
+
+        // A file no remap covers is found by name in the `source.paths` directories
+        state.borrow_mut().stack.frames[0].location.as_mut().unwrap().file = "/ci/vendor/util.rs".to_string();
+        app.source_dirs = vec![root.clone()];
+        state.borrow_mut().code_lines = Some(std::collections::BTreeSet::from([1]));
+        app.refresh_stack_trace();
+        app.refresh_source_view();
+        assert_eq!(app.source_cache["/ci/vendor/util.rs"].lines, ["pub fn util() {}"]);
+        // Loaded with the line table's lines, to dim the rest
+        assert_eq!(app.source_cache["/ci/vendor/util.rs"].has_code(1), Some(true));
+        assert_eq!(app.source_cache[&file].has_code(1), None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn images_loaded_after_the_first_stop_get_timeline_entries()
+    {
+        use ferros_core::symbols::ImageInfo;
+
+        let image = |path: &str, load_address: u64| ImageInfo {
+            id: ImageId::from_parts(Path::new(path), load_address),
+            path: path.into(),
+            load_address: Address::from(load_address),
+            end_address: Address::from(load_address + 0x4000),
+            debug_level: DebugLevel::SymbolsOnly,
+            has_eh_frame: true,
+            symbol_count: 12,
+            dsym: None,
+            dsym_mismatch: None,
+        };
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let stop = |app: &mut App| {
+            app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+                reason: StopReason::Suspended,
+                thread: None,
+            });
+        };
+        let images = |app: &App| -> Vec<String> {
+            app.timeline_log
+                .iter()
+                .filter(|entry| entry.kind == TimelineEntryKind::Image)
+                .map(|entry| entry.message.clone())
+                .collect()
+        };
+
+        state.borrow_mut().modules = vec![image("/bin/demo", 0x1_0000_0000), image("/usr/lib/libc.dylib", 0x1_8000_0000)];
+        stop(&mut app);
+        stop(&mut app);
+        assert_eq!(images(&app), ["2 images loaded; :modules lists them"]);
+
+        state.borrow_mut().modules.push(image("/usr/lib/libz.dylib", 0x1_9000_0000));
+        stop(&mut app);
+        assert_eq!(images(&app)[1..], ["Loaded /usr/lib/libz.dylib at 0x0000000190000000 (symbols only)"]);
+
+        app.command_input = "modules".to_string();
+        app.execute_command();
+        assert_eq!(app.view_mode, ViewMode::Modules);
+        assert_eq!(images(&app).len(), 2);
+
+        // The backend's event for an image a stop already listed adds nothing
+        let loaded = |path: &str, base: u64| DebuggerEvent::ImageLoaded {
+            path: path.into(),
+            base: Address::from(base),
+        };
+        app.handle_debugger_event(&loaded("/usr/lib/libz.dylib", 0x1_9000_0000));
+        app.handle_debugger_event(&loaded("/tmp/plugin.dylib", 0x1_a000_0000));
+        app.handle_debugger_event(&DebuggerEvent::ImageUnloaded {
+            path: "/tmp/plugin.dylib".into(),
+            base: Address::from(0x1_a000_0000),
+        });
+        assert_eq!(
+            images(&app)[2..],
+            [
+                "Loaded /tmp/plugin.dylib at 0x00000001a0000000",
+                "Unloaded /tmp/plugin.dylib from 0x00000001a0000000",
+            ]
+        );
+        // Loaded again after the unload, it is announced again
+        app.handle_debugger_event(&loaded("/tmp/plugin.dylib", 0x1_a000_0000));
+        assert_eq!(images(&app).len(), 5);
+    }
+
+    #[test]
+    fn the_process_picker_filters_and_attaches_or_explains_a_refusal()
+    {
+        use ferros_core::permissions::{PrivilegedOperation, current_environment};
+        use ferros_core::processes::ProcessInfo;
+        use ferros_core::types::ProcessId;
+
+        let process = |pid: u32, name: &str| ProcessInfo {
+            pid: ProcessId(pid),
+            name: name.to_string(),
+            path: None,
+        };
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, None, false, &Config::default());
+        app.process_picker = Some(ProcessPicker::new(vec![
+            process(1, "launchd"),
+            process(40, "my_app"),
+            process(41, "my_app_helper"),
+        ]));
+        state.borrow_mut().attach_denial = Some(PermissionAdvice::for_operation(
+            PrivilegedOperation::Attach,
+            current_environment(),
+        ));
+
+        // `q`, `s` and digits are filter text here, not view keys
+        for c in "app".chars() {
+            press(&mut app, c);
+        }
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let picker = app.process_picker.as_ref().expect("a refused attach keeps the picker open");
+        let error = picker.error.as_deref().unwrap();
+        assert!(
+            error.starts_with("Failed to attach to my_app_helper (41): Permission denied"),
+            "{error}"
+        );
+        assert!(!picker.remedies.is_empty());
+        assert!(!app.should_quit);
+
+        state.borrow_mut().attach_denial = None;
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(app.process_picker.is_none());
+        assert_eq!(state.borrow().attached_to, Some(ProcessId(41)));
+        assert_eq!(app.pid, Some(41));
+        assert_eq!(app.view_mode, ViewMode::Overview);
+        assert_eq!(app.info_message.as_deref(), Some("Attached to my_app_helper (41)"));
+        assert!(app.target_is_stopped);
+    }
+
+    #[test]
+    fn escape_clears_the_picker_filter_then_quits()
+    {
+        let (debugger, _state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, None, false, &Config::default());
+        app.process_picker = Some(ProcessPicker::default());
+        press(&mut app, 'q');
+        assert_eq!(app.process_picker.as_ref().unwrap().filter, "q");
+        let escape = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert!(!app.handle_key_event(escape));
+        assert!(app.process_picker.as_ref().unwrap().filter.is_empty());
+        assert!(app.handle_key_event(escape));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn following_pointers_checks_the_regions_and_can_go_back()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = true;
+            state.sp = 0x1_0000_1000;
+            state.memory = (0x1_0000_1000..0x1_0000_2000).map(|address| (address, 0)).collect();
+            // Words at 0x1_0000_1008.. point into the page, at nothing, and at an unreadable region
+            for (word, value) in [
+                (0x1_0000_1008_u64, 0x1_0000_1800_u64),
+                (0x1_0000_1010, 0x2_0000_0000),
+                (0x1_0000_1018, 0x3_0000_0000),
+            ] {
+                for (offset, byte) in (0..).zip(value.to_le_bytes()) {
+                    state.memory.insert(word + offset, byte);
+                }
+            }
+            state.regions = vec![
+                MemoryRegion::new(
+                    MemoryRegionId(0),
+                    Address::from(0x1_0000_1000),
+                    Address::from(0x1_0000_2000),
+                    "rw-".into(),
+                    None,
+                ),
+                MemoryRegion::new(
+                    MemoryRegionId(1),
+                    Address::from(0x3_0000_0000),
+                    Address::from(0x3_0000_1000),
+                    "---".into(),
+                    None,
+                ),
+            ];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let key = |app: &mut App, code: KeyCode| app.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+
+        let order = register_display_order(Architecture::Arm64);
+        app.registers_state
+            .select(order.iter().position(|row| row.id == RegisterId::Sp));
+        app.view_mode = ViewMode::Registers;
+        key(&mut app, KeyCode::Enter);
+        assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1000)));
+
+        key(&mut app, KeyCode::Right);
+        assert_eq!(app.memory.selected_value(), Some(0x1_0000_1800));
+        key(&mut app, KeyCode::Enter);
+        assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1800)));
+        assert_eq!(app.memory_history.depth(), 1);
+
+        key(&mut app, KeyCode::Backspace);
+        assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1000)));
+        key(&mut app, KeyCode::Char(']'));
+        assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1800)));
+        key(&mut app, KeyCode::Char('['));
+        assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1000)));
+
+        // Neither an unmapped nor an unreadable target is followed
+        for (words, reason) in [(2, "isn't in a mapped region"), (1, "isn't readable (---)")] {
+            for _ in 0..words {
+                key(&mut app, KeyCode::Right);
+            }
+            app.error_message = None;
+            key(&mut app, KeyCode::Enter);
+            let message = app.error_message.clone().unwrap_or_default();
+            assert!(message.contains(reason), "{message}");
+            assert_eq!(app.memory.address, Some(Address::from(0x1_0000_1000)));
+        }
+    }
+
+    #[test]
+    fn line_breakpoints_cover_every_address_the_line_compiled_to()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stack.frames = vec![mock::frame(ThreadId::from(1), 0, "demo::main", 7)];
+            // An inlined copy and the out-of-line body
+            state.source_lines.insert(("src/main.rs".to_string(), 42), vec![0x1_0000_2000, 0x1_0000_3000]);
+            state.source_lines.insert(("src/main.rs".to_string(), 12), vec![0x1_0000_1200]);
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.target_is_stopped = true;
+        app.refresh_stack_trace();
+
+        app.command_input = "break src/main.rs:42".to_string();
+        app.execute_command();
+        let addresses = || -> Vec<_> {
+            state
+                .borrow()
+                .breakpoint_requests
+                .iter()
+                .map(|request| request.address().map(Address::value))
+                .collect()
+        };
+        assert_eq!(addresses(), [Some(0x1_0000_2000), Some(0x1_0000_3000)]);
+        assert_eq!(app.info_message.as_deref(), Some("Added 2 breakpoints at src/main.rs:42"));
+
+        // `b` in the Source view uses the selected line, not the PC
+        press(&mut app, '6');
+        app.current_source_file = Some("src/main.rs".to_string());
+        app.source_selected_line = Some(11);
+        press(&mut app, 'b');
+        assert_eq!(addresses()[2..], [Some(0x1_0000_1200)]);
+
+        // Without line tables the frame stopped on the line still gives an address
+        app.source_selected_line = Some(6);
+        press(&mut app, 'b');
+        assert_eq!(addresses()[3..], [Some(0x1_0000_1000)]);
+
+        app.source_selected_line = Some(99);
+        press(&mut app, 'b');
+        assert_eq!(app.error_message.as_deref(), Some("No code at src/main.rs:100"));
+        assert_eq!(addresses().len(), 4);
+    }
+
+    /// Advice the mock attaches when it refuses a breakpoint
+    fn denied() -> PermissionAdvice
+    {
+        use ferros_core::permissions::{PrivilegedOperation, current_environment};
+
+        PermissionAdvice::for_operation(PrivilegedOperation::HardwareBreakpoint, current_environment())
+    }
+
+    #[test]
+    fn line_breakpoints_are_refused_in_images_without_debug_info()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            let mut top = mock::frame(ThreadId::from(1), 0, "release::compute", 3);
+            top.location = None;
+            state.stack.frames = vec![top];
+            state.images = vec![(0x1_0000_0000..0x1_0001_0000, DebugLevel::None)];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.target_is_stopped = true;
+        app.refresh_stack_trace();
+        assert_eq!(
+            app.selected_frame_without_debug_info(),
+            Some(("this image".to_string(), DebugLevel::None))
+        );
+
+        press(&mut app, '6');
+        press(&mut app, 'b');
+        assert!(state.borrow().breakpoint_requests.is_empty());
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some(
+                "Line breakpoints unavailable: no debug info in this image (no symbols); use break <function> or b in the \
+                 Stack view"
+            )
+        );
+
+        // Breaking on the frame's address still works from the Stack view
+        press(&mut app, '7');
+        press(&mut app, 'b');
+        assert_eq!(state.borrow().breakpoint_requests.len(), 1);
+    }
+
+    #[test]
+    fn resuming_past_failed_or_pending_breakpoints_asks_first()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().breakpoint_denial = Some(denied());
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "break 0x1000".to_string();
+        app.execute_command();
+        app.command_input = "break main.rs:12".to_string();
+        app.execute_command();
+        assert_eq!(app.breakpoint_failures.len(), 2);
+        assert_eq!(app.breakpoint_failures[1].location, "main.rs:12");
+
+        // `n` (or anything but `y`) keeps the target stopped
+        press(&mut app, 'r');
+        let prompt = app.resume_prompt.clone().expect("resume prompt");
+        assert!(
+            prompt.starts_with("2 breakpoints failed to install (0x0000000000001000: Permission denied"),
+            "{prompt}"
+        );
+        assert!(prompt.contains("main.rs:12: Permission denied"), "{prompt}");
+        assert!(prompt.ends_with("resume anyway? [y/N]"), "{prompt}");
+        assert!(app.modal_open());
+        press(&mut app, 'n');
+        assert!(app.resume_prompt.is_none());
+        assert!(state.borrow().stopped);
+
+        press(&mut app, 'r');
+        assert!(app.resume_prompt.is_some());
+        press(&mut app, 'y');
+        assert!(app.resume_prompt.is_none());
+        assert!(!state.borrow().stopped);
+
+        // Failures are forgotten for the prompt once the target has resumed
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        assert_eq!(app.resume_confirmation(), None);
+        assert_eq!(app.breakpoint_failures.len(), 2);
+    }
+
+    #[test]
+    fn pending_or_missing_breakpoints_ask_before_resuming()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut info = BreakpointInfo::new(
+            BreakpointId::from_raw(1),
+            Address::from(0x1000),
+            ferros_core::BreakpointKind::Software,
+        );
+        state.borrow_mut().breakpoints.push(info.clone());
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        assert_eq!(app.resume_confirmation().as_deref(), Some("1 pending — resume anyway? [y/N]"));
+
+        // Breakpoints requested at this stop that never installed
+        state.borrow_mut().breakpoints.clear();
+        assert_eq!(app.resume_confirmation(), None);
+        app.command_input = "break 0x2000".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.resume_confirmation().as_deref(),
+            Some("no breakpoints installed — resume anyway? [y/N]")
+        );
+
+        // Nothing to ask once everything is resolved
+        info.state = ferros_core::BreakpointState::Resolved;
+        info.enabled = true;
+        state.borrow_mut().breakpoints.push(info);
+        assert_eq!(app.resume_confirmation(), None);
+        press(&mut app, 'r');
+        assert!(app.resume_prompt.is_none());
+        assert!(!state.borrow().stopped);
+    }
+
+    #[test]
+    fn ignore_command_sets_the_hits_to_skip()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().breakpoints.push(BreakpointInfo::new(
+            BreakpointId::from_raw(3),
+            Address::from(0x1000),
+            ferros_core::BreakpointKind::Software,
+        ));
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.command_input = "ignore 3 5".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("Will ignore the next 5 hits of breakpoint 3")
+        );
+        assert_eq!(app.cached_breakpoints[0].ignore_count, 5);
+
+        app.command_input = "ignore 4 1".to_string();
+        app.execute_command();
+        assert!(app.error_message.take().is_some());
+        assert_eq!(state.borrow().breakpoints[0].ignore_count, 5);
+    }
+
+    #[test]
+    fn saved_breakpoints_are_re_resolved_and_unresolved_ones_stay_pending()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.function_starts = vec![0x1_0000_1000, 0x1_0000_2000, 0x1_0000_3000];
+            state.function_names.insert(0x1_0000_1000, "app::run".to_string());
+            state.function_names.insert(0x1_0000_3000, "app::later".to_string());
+            state
+                .source_lines
+                .insert(("src/main.rs".to_string(), 42), vec![0x1_0000_2000]);
+            let addresses: [u64; 3] = [0x1_0000_1010, 0x1_0000_2000, 0x1_0000_3000];
+            for (id, address) in (1..).zip(addresses) {
+                let kind = match id {
+                    2 => ferros_core::BreakpointKind::Hardware,
+                    _ => ferros_core::BreakpointKind::Software,
+                };
+                let info = BreakpointInfo::new(BreakpointId::from_raw(id), Address::from(address), kind);
+                state.breakpoints.push(info);
+            }
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let path = std::env::temp_dir().join(format!("ferros-ui-breakpoints-{}.json", std::process::id()));
+        app.command_input = format!("bp save {}", path.display());
+        app.execute_command();
+        assert_eq!(app.info_message, Some(format!("Saved 3 breakpoint(s) to {}", path.display())));
+
+        // The next run is loaded somewhere else, and app::later isn't loaded yet
+        {
+            let mut state = state.borrow_mut();
+            state.breakpoints.clear();
+            state.function_starts = vec![0x5_0000_1000, 0x5_0000_2000];
+            state.function_names.clear();
+            state.function_names.insert(0x5_0000_1000, "app::run".to_string());
+            state.source_lines.clear();
+            state
+                .source_lines
+                .insert(("src/main.rs".to_string(), 42), vec![0x5_0000_2000]);
+        }
+        app.command_input = format!("bp load {}", path.display());
+        app.execute_command();
+        let requests = |state: &std::cell::RefCell<mock::MockState>| -> Vec<Option<u64>> {
+            state
+                .borrow()
+                .breakpoint_requests
+                .iter()
+                .map(|request| request.address().map(Address::value))
+                .collect()
+        };
+        assert_eq!(requests(&state), [Some(0x5_0000_1010), Some(0x5_0000_2000)]);
+        assert!(matches!(
+            state.borrow().breakpoint_requests[1].kind,
+            BreakpointRequestKind::Hardware { .. }
+        ));
+        assert_eq!(app.pending_breakpoints.len(), 1);
+        assert_eq!(app.pending_breakpoints[0].saved.to_string(), "app::later");
+        assert_eq!(app.resume_confirmation().as_deref(), Some("1 pending — resume anyway? [y/N]"));
+
+        // Pending breakpoints are saved too, and resolve once their image loads
+        app.command_input = format!("bp save {}", path.display());
+        app.execute_command();
+        assert_eq!(BreakpointFile::load(&path).unwrap().breakpoints.len(), 1);
+        {
+            let mut state = state.borrow_mut();
+            state.function_starts.push(0x5_0000_3000);
+            state.function_names.insert(0x5_0000_3000, "app::later".to_string());
+        }
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: None,
+        });
+        assert!(app.pending_breakpoints.is_empty());
+        assert_eq!(requests(&state).last(), Some(&Some(0x5_0000_3000)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resume_confirmation_can_be_turned_off()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().breakpoint_denial = Some(denied());
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "break 0x1000".to_string();
+        app.execute_command();
+
+        app.command_input = "set resume.confirm off".to_string();
+        app.execute_command();
+        assert!(!app.confirm_resume);
+        press(&mut app, 'r');
+        assert!(app.resume_prompt.is_none());
+        assert!(!state.borrow().stopped);
+
+        app.command_input = "set resume.confirm maybe".to_string();
+        app.execute_command();
+        assert!(app.error_message.is_some());
+    }
+
+    #[test]
+    fn step_instruction_reports_the_new_pc_and_needs_a_stopped_target()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = true;
+            state.pc = 0x1_0000_2000;
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        press(&mut app, 'i');
+        assert_eq!(state.borrow().pc, 0x1_0000_2004);
+        assert_eq!(app.info_message.as_deref(), Some("Stepped to 0x100002004"));
+
+        app.command_input = "stepi".to_string();
+        app.execute_command();
+        assert_eq!(state.borrow().pc, 0x1_0000_2008);
+
+        state.borrow_mut().stopped = false;
+        press(&mut app, 'i');
+        assert_eq!(state.borrow().pc, 0x1_0000_2008);
+        assert!(app.error_message.is_some());
+    }
+
+    #[test]
+    fn step_over_runs_through_the_backend_step_over()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = true;
+            state.pc = 0x1_0000_2000;
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        press(&mut app, 'o');
+        app.command_input = "nexti".to_string();
+        app.execute_command();
+        assert_eq!(state.borrow().pc, 0x1_0000_2008);
+        assert_eq!(state.borrow().steps_over, 2);
+        assert_eq!(app.info_message.as_deref(), Some("Stepped to 0x100002008"));
+    }
+
+    #[test]
+    fn step_out_runs_to_the_caller_or_explains_why_it_cant()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let thread = ThreadId::from(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = true;
+            state.stack.frames = vec![
+                mock::frame(thread, 0, "app::leaf", 10),
+                mock::frame(thread, 1, "app::main", 20),
+            ];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        press(&mut app, 'u');
+        let caller = state.borrow().stack.frames[1].pc.value();
+        assert_eq!(state.borrow().pc, caller);
+        assert_eq!(app.info_message, Some(format!("Stepped to 0x{caller:x}")));
+
+        state.borrow_mut().stack.frames[1].status = FrameStatus::Heuristic;
+        app.command_input = "finish".to_string();
+        app.execute_command();
+        let error = app.error_message.clone().unwrap();
+        assert!(error.contains("heuristically"), "{error}");
+    }
+
+    #[test]
+    fn permission_errors_expand_into_advice()
+    {
+        use ferros_core::permissions::{CodeSigning, PermissionEnvironment, PrivilegedOperation};
+
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let environment = PermissionEnvironment {
+            euid: 501,
+            executable: None,
+            signing: Some(CodeSigning::Unsigned),
+            sip_enabled: Some(true),
+            command: None,
+        };
+        let advice = PermissionAdvice::for_operation(PrivilegedOperation::HardwareBreakpoint, &environment);
+        state.borrow_mut().breakpoint_denial = Some(advice.clone());
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.command_input = "break 0x1000".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("Failed to add breakpoint: Permission denied: thread_set_state refused")
+        );
+        assert_eq!(app.permission_advice.as_ref(), Some(&advice));
+
+        // `e` opens the details without clearing the error; any key closes them
+        press(&mut app, 'e');
+        assert!(app.permission_advice_open);
+        assert!(app.modal_open());
+        assert!(app.error_message.is_some());
+        press(&mut app, 'x');
+        assert!(!app.permission_advice_open);
+        assert!(app.error_message.is_some());
+
+        // The next key dismisses the error and its advice, so `e` means nothing
+        press(&mut app, '1');
+        assert!(app.permission_advice.is_none());
+        press(&mut app, 'e');
+        assert!(!app.permission_advice_open);
+    }
+
+    #[test]
+    #[allow(clippy::large_stack_arrays)]
+    fn crash_checkpoint_opens_on_the_crash_frame_of_the_faulting_thread()
+    {
+        let (debugger, state) = MockDebugger::with_threads(3);
+        let faulting = ThreadId::from(2);
+        state.borrow_mut().stack.frames = ["__pthread_kill", "pthread_kill", "abort", "demo::parse_header", "demo::main"]
+            .iter()
+            .enumerate()
+            .map(|(index, function)| mock::frame(faulting, index, function, 40 + u32::try_from(index).unwrap()))
+            .collect();
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "checkpoints on".to_string();
+        app.execute_command();
+
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Signal(6), // SIGABRT
+            thread: Some(faulting),
+        });
+        assert_eq!(app.debugger.active_thread(), Some(faulting));
+        assert_eq!(app.view_mode, ViewMode::Stack);
+        assert_eq!(app.stack_frames_state.selected(), Some(3));
+        assert_eq!(app.threads_state.selected(), Some(1));
+        assert_eq!(
+            app.crash_banner.as_deref(),
+            Some("SIGABRT at 0x1000010c0 in demo::parse_header (src/main.rs:43)")
+        );
+
+        // Move on, then open the crash checkpoint from a fresh Overview
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        assert!(app.crash_banner.is_none());
+        state.borrow_mut().active_thread = Some(ThreadId::from(1));
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: None,
+        });
+        press(&mut app, '1');
+        app.command_input = "back".to_string();
+        app.execute_command();
+
+        assert_eq!(app.history.current().unwrap().crash.unwrap().thread, Some(faulting));
+        assert_eq!(app.view_mode, ViewMode::Stack);
+        let selected = app
+            .cached_stack_trace
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|frame| Some(frame.id) == app.selected_frame_id)
+            .unwrap();
+        assert_eq!(selected.symbol.as_ref().unwrap().display_name(), "demo::parse_header");
+        assert_eq!(app.source_selected_line, Some(42));
+        assert!(app.crash_summary().unwrap().starts_with("SIGABRT"));
+    }
+
+    #[test]
+    fn bad_access_stops_name_the_crashing_function_and_the_faulting_region()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let thread = ThreadId::from(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stack.frames = ["demo::parse_header", "demo::main"]
+                .iter()
+                .enumerate()
+                .map(|(index, function)| mock::frame(thread, index, function, 42 + u32::try_from(index).unwrap()))
+                .collect();
+            state.regions = vec![MemoryRegion::new(
+                MemoryRegionId(0),
+                Address::from(0x1_0000_8000),
+                Address::from(0x1_0000_c000),
+                "r--".into(),
+                Some("/bin/demo".into()),
+            )];
+            state.function_starts = vec![0x1_0000_8000];
+            state.function_names.insert(0x1_0000_8000, "demo::TABLE".to_string());
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::BadAccess {
+                address: 0x18,
+                kind: BadAccessKind::Read,
+            },
+            thread: Some(thread),
+        });
+        assert_eq!(
+            app.status_message(),
+            "EXC_BAD_ACCESS (read) at 0x18 — in function `demo::parse_header` (main.rs:42) — null pointer dereference"
+        );
+        assert_eq!(app.view_mode, ViewMode::Stack);
+
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        assert_eq!(app.bad_access_message(), None);
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::BadAccess {
+                address: 0x1_0000_8010,
+                kind: BadAccessKind::Write,
+            },
+            thread: Some(thread),
+        });
+        let report = "EXC_BAD_ACCESS (write) at 0x100008010 — in function `demo::parse_header` (main.rs:42) — address in \
+                      /bin/demo (r--) at `demo::TABLE`";
+        assert_eq!(app.bad_access_message().as_deref(), Some(report));
+        assert_eq!(app.timeline_log.back().unwrap().message, report);
+        assert_eq!(app.timeline_log.back().unwrap().kind, TimelineEntryKind::Signal);
+
+        app.command_input = "set developer.tools on".to_string();
+        app.execute_command();
+        app.command_input = "debug emit-stop badaccess 0xdead0000 execute".to_string();
+        app.execute_command();
+        assert!(app.status_message().ends_with("— address not in any mapped region"));
+    }
+
+    #[test]
+    #[allow(clippy::large_stack_arrays)]
+    fn launched_targets_stop_at_panics_and_show_the_message()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let thread = ThreadId::from(1);
+        {
+            let mut state = state.borrow_mut();
+            state.function_starts = vec![0x1_0000_9000];
+            state.function_names.insert(0x1_0000_9000, "rust_begin_unwind".to_string());
+            state.stack.frames = [
+                "rust_begin_unwind",
+                "core::panicking::panic_fmt",
+                "demo::parse_header",
+                "demo::main",
+            ]
+            .iter()
+            .enumerate()
+            .map(|(index, function)| mock::frame(thread, index, function, 40 + u32::try_from(index).unwrap()))
+            .collect();
+            // x0 = &PanicInfo { message: &Arguments, location: &Location }
+            state.general = vec![0x5000];
+            let words = |values: &[u64]| values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
+            let layout = [
+                (0x5000, words(&[0x5100, 0x5200])),
+                (0x5100, words(&[0x5300, 1, 0, 0, 0x5400, 1])),
+                (0x5300, words(&[0x5500, 12])),
+                (0x5500, b"bad header: ".to_vec()),
+                (0x5200, [words(&[0x5600, 11]), vec![4, 0, 0, 0, 5, 0, 0, 0]].concat()),
+                (0x5600, b"src/main.rs".to_vec()),
+            ];
+            for (address, bytes) in layout {
+                for (offset, byte) in bytes.into_iter().enumerate() {
+                    state.memory.insert(address + offset as u64, byte);
+                }
+            }
+        }
+
+        // Attached targets only stop at panics when asked to
+        let (attached, attached_state) = MockDebugger::with_threads(1);
+        attached_state.borrow_mut().function_names = state.borrow().function_names.clone();
+        attached_state.borrow_mut().function_starts = vec![0x1_0000_9000];
+        let mut attached_app = App::new(attached, Some(1), false, &Config::default());
+        attached_app.handle_debugger_event(&DebuggerEvent::SymbolsLoaded { images: 1 });
+        assert!(attached_state.borrow().breakpoint_requests.is_empty());
+
+        let mut app = App::new(debugger, Some(1), true, &Config::default());
+        app.handle_debugger_event(&DebuggerEvent::SymbolsLoaded { images: 1 });
+        let requests = state.borrow().breakpoint_requests.clone();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].address(), Some(Address::from(0x1_0000_9000)));
+        assert!(requests[0].options.hidden);
+
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x1_0000_9000),
+            thread: Some(thread),
+        });
+        let panic = "Panicked at src/main.rs:4:5: bad header: {…}";
+        let entry = app
+            .timeline_log
+            .iter()
+            .find(|entry| entry.kind == TimelineEntryKind::Panic)
+            .unwrap();
+        assert_eq!(entry.message, format!("{panic} (thread 1)"));
+        assert_eq!(app.status_message(), panic);
+        assert_eq!(app.view_mode, ViewMode::Stack);
+        assert_eq!(app.stack_frames_state.selected(), Some(2));
+
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        assert!(app.panic_report.is_none());
+        app.command_input = "set break.panic off".to_string();
+        app.execute_command();
+        assert!(!app.panic_breakpoints.is_installed());
+    }
+
+    #[test]
+    fn bad_accesses_just_below_the_stack_are_reported_as_probable_overflows()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let thread = ThreadId::from(1);
+        {
+            let mut state = state.borrow_mut();
+            state.sp = 0x7000_4010;
+            state.regions = vec![MemoryRegion::new(
+                MemoryRegionId(0),
+                Address::from(0x7000_4000),
+                Address::from(0x7008_4000),
+                "rw-".into(),
+                None,
+            )];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let stop = |address| DebuggerEvent::TargetStopped {
+            reason: StopReason::BadAccess {
+                address,
+                kind: BadAccessKind::Write,
+            },
+            thread: Some(thread),
+        };
+
+        app.handle_debugger_event(&stop(0x7000_3ff0));
+        let overflow = "probable stack overflow (SP = 0x70004010, stack base = 0x70084000)";
+        let logged = app
+            .timeline_log
+            .iter()
+            .find(|entry| entry.kind == TimelineEntryKind::Signal)
+            .unwrap();
+        assert_eq!(
+            logged.message,
+            format!("EXC_BAD_ACCESS (write) at 0x70003ff0 (thread 1) — {overflow}")
+        );
+        assert!(app.status_message().ends_with(overflow));
+
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        app.handle_debugger_event(&stop(0x20_0000_0000));
+        assert!(!app.status_message().contains("stack overflow"));
+    }
+
+    #[test]
+    #[allow(clippy::large_stack_arrays)]
+    fn stack_view_keys_break_relative_to_the_selected_frame()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let thread = ThreadId::from(1);
+        {
+            let mut state = state.borrow_mut();
+            // Live PC is somewhere else entirely, so using it would show up below
+            state.pc = 0xdead_0000;
+            state.stack.frames = ["demo::read_chunk", "demo::parse_header", "demo::main"]
+                .iter()
+                .enumerate()
+                .map(|(index, function)| {
+                    let mut frame = mock::frame(thread, index, function, 10 + u32::try_from(index).unwrap());
+                    // The unwinder records the return address on the frame it unwound to
+                    frame.return_address = (index > 0).then_some(frame.pc);
+                    frame
+                })
+                .collect();
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: Some(thread),
+        });
+        press(&mut app, '7');
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+
+        press(&mut app, 'b');
+        press(&mut app, 'c');
+        press(&mut app, 'C');
+        let requests: Vec<_> = state
+            .borrow()
+            .breakpoint_requests
+            .iter()
+            .map(|request| (request.address().map(Address::value), request.options.one_shot))
+            .collect();
+        assert_eq!(
+            requests,
+            [
+                (Some(0x1_0000_1040), false), // parse_header's PC
+                (Some(0x1_0000_1080), true),  // return into main
+                (Some(0x1_0000_1000), false), // read_chunk's PC
+            ]
+        );
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("Added breakpoint at 0x0000000100001000 in demo::read_chunk (called from demo::parse_header) as #3")
+        );
+        assert!(app.error_message.is_none());
+
+        // A return address recovered by stack scanning is used but flagged
+        state.borrow_mut().stack.frames[2].status = FrameStatus::Heuristic;
+        app.refresh_stack_trace();
+        press(&mut app, 'c');
+        assert_eq!(
+            app.info_message.as_deref(),
+            Some("Added one-shot breakpoint at 0x0000000100001080 in demo::main (return from demo::parse_header) as #4")
+        );
+        assert!(app.error_message.as_deref().unwrap().contains("recovered heuristically"));
+        assert_eq!(app.timeline_log.back().unwrap().kind, TimelineEntryKind::Warning);
+
+        // The outermost frame has nowhere to return to and the innermost has no callee
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        press(&mut app, 'c');
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("demo::main has no recorded return address")
+        );
+        press(&mut app, 'p');
+        press(&mut app, 'p');
+        press(&mut app, 'C');
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("demo::read_chunk is the innermost frame and has no callee")
+        );
+        assert_eq!(state.borrow().breakpoint_requests.len(), 4);
+    }
+
+    #[test]
+    fn timeline_search_filter_and_enter_jump_to_related_views()
+    {
+        use ferros_core::BreakpointKind;
+
+        let (debugger, state) = MockDebugger::with_threads(2);
+        state.borrow_mut().breakpoints = vec![BreakpointInfo::new(
+            BreakpointId::from_raw(3),
+            Address::from(0x2000),
+            BreakpointKind::Software,
+        )];
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "checkpoints on".to_string();
+        app.execute_command();
+        app.refresh_breakpoints();
+
+        for hit in [false, false, true] {
+            app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+                reason: if hit {
+                    StopReason::Breakpoint(0x2000)
+                } else {
+                    StopReason::Suspended
+                },
+                thread: Some(ThreadId::from(2)),
+            });
+            app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        }
+        press(&mut app, '8');
+        press(&mut app, 'f');
+        // The initial stop plus the three events; resumes are hidden
+        assert_eq!(app.visible_timeline().len(), 4);
+
+        press(&mut app, '/');
+        for c in "BREAKPOINT".chars() {
+            press(&mut app, c);
+        }
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let hit = app.timeline.selected.unwrap();
+        assert_eq!(app.timeline_log[hit].breakpoint, Some(BreakpointId::from_raw(3)));
+        assert_eq!(app.timeline_log[hit].thread, Some(ThreadId::from(2)));
+
+        // New entries while scrolled back are counted, not followed
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: None,
+        });
+        assert_eq!((app.timeline.selected, app.timeline.unseen), (Some(hit), 1));
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(app.view_mode, ViewMode::Source);
+        assert_eq!(app.breakpoints_state.selected(), Some(0));
+
+        // The first stop event opens its checkpoint's stack
+        press(&mut app, '8');
+        for _ in 0..2 {
+            app.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        }
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(app.view_mode, ViewMode::Stack);
+        assert_eq!(app.history.position(), Some((1, 4)));
+
+        press(&mut app, '8');
+        app.handle_key_event(KeyEvent::new(KeyCode::End, KeyModifiers::NONE));
+        assert!(app.timeline.is_following());
+        assert_eq!(app.timeline.unseen, 0);
+    }
+
+    #[test]
+    fn notes_attach_to_the_selection_in_the_active_view()
+    {
+        use ferros_core::BreakpointKind;
+        use ferros_core::notes::NoteTarget;
+
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let thread = ThreadId::from(1);
+        {
+            let mut state = state.borrow_mut();
+            state.stack.frames = ["demo::parse", "demo::main"]
+                .iter()
+                .enumerate()
+                .map(|(index, function)| mock::frame(thread, index, function, 10))
+                .collect();
+            state.breakpoints = vec![BreakpointInfo::new(
+                BreakpointId::from_raw(4),
+                Address::from(0x2000),
+                BreakpointKind::Software,
+            )];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.source_cache
+            .insert("src/main.rs".to_string(), SourceFile::new(vec![String::new(); 20], None));
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: Some(thread),
+        });
+        app.refresh_breakpoints();
+        let run = |app: &mut App, command: &str| {
+            app.command_input = command.to_string();
+            app.execute_command();
+        };
+
+        // Stack: the selected frame's PC
+        press(&mut app, '7');
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        run(&mut app, "note caller of parse");
+        let caller_pc = app.cached_stack_trace.as_ref().unwrap()[1].pc;
+        assert_eq!(app.notes.get(1).unwrap().target, NoteTarget::address(caller_pc.value()));
+        assert_eq!(app.notes_at(caller_pc), ["caller of parse"]);
+
+        // Source: the selected breakpoint, keyed by address
+        press(&mut app, '6');
+        app.breakpoints_state.select(Some(0));
+        run(&mut app, "note  hit twice per frame ");
+        let note = app.notes.get(2).unwrap();
+        assert!(matches!(
+            note.target,
+            NoteTarget::Breakpoint {
+                id: 4,
+                address: 0x2000,
+                ..
+            }
+        ));
+        assert_eq!(note.text, "hit twice per frame");
+
+        // Timeline: the selected entry of this session
+        press(&mut app, '8');
+        app.timeline.select(Some(0));
+        run(&mut app, "note first stop");
+        let expected = NoteTarget::Timeline {
+            session: app.session_id,
+            entry: app.timeline_log[0].sequence,
+            message: app.timeline_log[0].message.clone(),
+        };
+        assert_eq!(app.notes.get(3).unwrap().target, expected);
+
+        run(&mut app, "note addr 0x3000 scratch buffer");
+        assert_eq!(app.notes.at_address(0x3000).count(), 1);
+        run(&mut app, "note addr 0x3000");
+        assert!(app.error_message.take().unwrap().starts_with("Usage"));
+        run(&mut app, "note rm 9");
+        assert_eq!(app.error_message.take().as_deref(), Some("No note 9"));
+
+        // The list jumps to what each note is attached to
+        run(&mut app, "notes");
+        assert_eq!(app.notes_list, Some(0));
+        assert!(app.modal_open());
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!((app.view_mode, app.stack_frames_state.selected()), (ViewMode::Stack, Some(1)));
+
+        run(&mut app, "notes");
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(app.view_mode, ViewMode::Source);
+        assert!(app.info_message.as_deref().unwrap().contains("hit twice per frame"));
+
+        run(&mut app, "notes");
+        for _ in 0..2 {
+            app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        }
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!((app.view_mode, app.timeline.selected), (ViewMode::Timeline, Some(0)));
+
+        run(&mut app, "note rm 1");
+        assert_eq!(app.notes.len(), 3);
+        assert!(app.notes_list.is_none());
+    }
+
+    #[test]
+    fn where_opens_a_popup_and_logs_to_the_timeline()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().stack.frames = vec![mock::frame(ThreadId::from(1), 0, "demo::main", 7)];
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let logged = app.timeline_log.len();
+
+        press(&mut app, 'w');
+        let summary = app.where_summary.clone().unwrap();
+        assert_eq!(summary[0], "Thread 1: Process is suspended");
+        assert!(app.modal_open());
+        let messages: Vec<_> = app
+            .timeline_log
+            .iter()
+            .skip(logged)
+            .map(|entry| entry.message.clone())
+            .collect();
+        assert_eq!(messages, summary);
+
+        // Any key closes it without acting
+        press(&mut app, 's');
+        assert!(app.where_summary.is_none());
+        assert_eq!(app.view_mode, ViewMode::Overview);
+
+        app.command_input = "where".to_string();
+        app.execute_command();
+        assert!(app.where_summary.take().is_some());
+
+        state.borrow_mut().stopped = false;
+        press(&mut app, 'w');
+        assert!(app.where_summary.is_none());
+        assert!(app.error_message.as_deref().unwrap().contains("Process is running"));
+    }
+
+    #[test]
+    fn replayed_stops_are_tagged_and_raise_no_notifications()
+    {
+        let (debugger, _state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let run = |app: &mut App, command: &str| {
+            app.command_input = command.to_string();
+            app.execute_command();
+            app.error_message.take()
+        };
+
+        assert_eq!(
+            run(&mut app, "debug replay-stop").as_deref(),
+            Some("Developer tools are off; turn them on with set developer.tools on")
+        );
+        assert_eq!(run(&mut app, "set developer.tools on"), None);
+        assert_eq!(run(&mut app, "debug replay-stop").as_deref(), Some("No stop to replay yet"));
+
+        app.handle_debugger_event(&DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(0x2000),
+            thread: Some(ThreadId::from(1)),
+        });
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        assert_eq!(run(&mut app, "debug replay-stop"), None);
+        assert!(app.target_is_stopped);
+        assert_eq!(app.last_stop_reason, StopReason::Breakpoint(0x2000));
+        let entry = app.timeline_log.back().unwrap();
+        assert_eq!(entry.kind, TimelineEntryKind::BreakpointHit);
+        assert_eq!(entry.message, "[replay] Hit breakpoint at 0x2000 (thread 1)");
+        let titles: Vec<_> = app
+            .stop_emphasis
+            .toasts()
+            .map(|toast| (toast.title.as_str(), toast.repeats))
+            .collect();
+        assert_eq!(titles, vec![("Hit breakpoint at 0x2000 (thread 1)", 1)]);
+
+        // Synthetic stops are shown but never become the stop to replay
+        assert_eq!(run(&mut app, "debug emit-stop exited 3"), None);
+        assert_eq!(app.status_message(), "Process exited with code 3");
+        assert_eq!(run(&mut app, "debug replay-stop"), None);
+        assert_eq!(app.last_stop_reason, StopReason::Breakpoint(0x2000));
+
+        assert_eq!(
+            run(&mut app, "debug emit-stop signal SEGV").as_deref(),
+            Some("Invalid number: SEGV")
+        );
+        assert!(
+            run(&mut app, "debug emit-stop watchpoint")
+                .unwrap()
+                .starts_with("Stop reasons: running")
+        );
+        assert!(run(&mut app, "debug").unwrap().starts_with("Usage: debug"));
+    }
+
+    #[test]
+    fn opslog_records_backend_operations_while_on()
+    {
+        use ferros_core::operations::JournaledDebugger;
+
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(Box::new(JournaledDebugger::new(debugger)), Some(1), false, &Config::default());
+        let path = std::env::temp_dir().join(format!("ferros-opslog-{}.jsonl", std::process::id()));
+
+        app.command_input = format!("opslog on {}", path.display());
+        app.execute_command();
+        assert_eq!(app.error_message, None);
+        assert!(
+            app.info_message
+                .as_deref()
+                .unwrap()
+                .starts_with("Logging backend operations to")
+        );
+        press(&mut app, 'r');
+        assert!(!state.borrow().stopped);
+        app.command_input = "opslog off".to_string();
+        app.execute_command();
+        press(&mut app, 's');
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(log.lines().any(|line| line.contains(r#""op":"resume""#)), "{log}");
+        assert!(!log.contains(r#""op":"suspend""#), "{log}");
+
+        // A backend that isn't wrapped can't record
+        let (debugger, _state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "opslog on /dev/null".to_string();
+        app.execute_command();
+        assert!(app.error_message.as_deref().unwrap().starts_with("Can't log operations:"));
+    }
+
+    #[test]
+    fn hang_probe_warns_once_and_autostops_a_stuck_main_thread()
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        {
+            let mut state = state.borrow_mut();
+            state.stopped = false;
+            state.pc = 0x1_8000_0000;
+            state.function_starts = vec![0x1_8000_0000];
+            state.function_names.insert(0x1_8000_0000, "__psynch_mutexwait".to_string());
+            state.stack.frames = ["__psynch_mutexwait", "app::Model::save", "main"]
+                .iter()
+                .enumerate()
+                .map(|(index, function)| mock::frame(ThreadId::from(1), index, function, 10))
+                .collect();
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        for setting in ["set hang.probe 1ms", "set hang.samples 3", "set hang.autostop on"] {
+            app.command_input = setting.to_string();
+            app.execute_command();
+            assert_eq!(app.error_message, None, "{setting}");
+        }
+
+        let hangs = |app: &App| {
+            app.timeline_log
+                .iter()
+                .filter(|entry| entry.kind == TimelineEntryKind::Warning)
+                .map(|entry| entry.message.clone())
+                .collect::<Vec<_>>()
+        };
+        for _ in 0..2 {
+            app.probe_main_thread();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        assert!(hangs(&app).is_empty());
+        assert!(!state.borrow().stopped);
+
+        app.probe_main_thread();
+        assert_eq!(
+            hangs(&app),
+            [
+                "Main thread blocked in __psynch_mutexwait for ~2.0ms (3 samples): __psynch_mutexwait <- app::Model::save \
+                 <- main"
+            ]
+        );
+        assert!(state.borrow().stopped);
+
+        // The same run isn't reported again
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        app.probe_main_thread();
+        assert_eq!(hangs(&app).len(), 1);
+
+        app.command_input = "set hang.samples 1".to_string();
+        app.execute_command();
+        assert_eq!(app.error_message.as_deref(), Some("hang.samples needs at least 2 samples"));
+        app.command_input = "set hang.probe off".to_string();
+        app.execute_command();
+        assert!(app.hang_probe.is_none());
+    }
+
+    #[test]
+    fn config_settings_match_set_and_apply_at_startup()
+    {
+        use std::path::Path;
+
+        let (debugger, _) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.command_input = "set".to_string();
+        app.execute_command();
+        let defaults: Vec<String> = config::SETTINGS
+            .iter()
+            .map(|setting| format!("{}={}", setting.key, setting.default))
+            .collect();
+        assert_eq!(app.info_message.as_deref(), Some(defaults.join(" ").as_str()));
+
+        let mut user = UserConfig::default();
+        user.settings = config::check_settings(
+            Path::new("config.toml"),
+            "address.format = \"short\"\nstop.bell = true\nhang.samples = 0\n",
+            &mut user.report,
+        );
+        user.keymap = config::check_keymap(Path::new("keymap.toml"), "where = \"W\"\n", &mut user.report);
+        app.enable_user_config(user);
+        assert_eq!(app.address_format, AddressFormat::Short12);
+        assert!(app.stop_emphasis.settings.bell);
+        assert_eq!(
+            app.config_banner.as_deref(),
+            Some("1 config problem in 1 file; those entries use their defaults (see Timeline)")
+        );
+        assert!(
+            app.timeline_log
+                .iter()
+                .any(|entry| entry.message == "config.toml:3: hang.samples needs at least 2 samples")
+        );
+
+        press(&mut app, 'w');
+        assert!(app.where_summary.is_none());
+        assert_eq!(app.config_banner, None);
+        press(&mut app, 'W');
+        assert!(app.where_summary.is_some());
+    }
+
+    #[test]
+    fn startup_config_seeds_layout_stack_depth_and_source_dirs()
+    {
+        use std::path::Path;
+
+        let (debugger, _) = MockDebugger::with_threads(1);
+        let text = "layout.preset = \"widescreen\"\nstack.max_frames = 200\nsource.paths = \"/src/a:/src/b\"\nbreak.panic \
+                    = \"on\"\n";
+        let mut app = App::new(debugger, Some(1), false, &Config::parse(text).0);
+        assert_eq!(app.layout_preset, LayoutPreset::Widescreen);
+        assert_eq!(app.unwind_options().max_frames, 200);
+        assert_eq!(app.source_dirs, vec![PathBuf::from("/src/a"), PathBuf::from("/src/b")]);
+        assert_eq!(app.break_on_panic, Some(true));
+
+        // `:set` values from the same file are applied afterwards and win
+        let mut user = UserConfig::default();
+        user.settings = config::check_settings(Path::new("config.toml"), "break.panic = \"off\"\n", &mut user.report);
+        app.enable_user_config(user);
+        assert_eq!(app.break_on_panic, Some(false));
+    }
+
+    #[test]
+    fn expensive_breakpoints_are_warned_about_once()
+    {
+        use std::time::{Duration, Instant};
+
+        use ferros_core::BreakpointKind;
+
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut hot = BreakpointInfo::new(BreakpointId::from_raw(4), Address::from(0x3000), BreakpointKind::Software);
+        let cold = BreakpointInfo::new(BreakpointId::from_raw(5), Address::from(0x4000), BreakpointKind::Software);
+        // 500 hits of 1ms over the last second: half of the target's time
+        let start = Instant::now().checked_sub(Duration::from_secs(1)).unwrap();
+        for hit in 0..500 {
+            hot.stats
+                .record(start + Duration::from_millis(hit * 2), Duration::from_millis(1));
+        }
+        state.borrow_mut().breakpoints = vec![hot, cold];
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        let warnings = |app: &App| {
+            app.timeline_log
+                .iter()
+                .filter(|entry| entry.kind == TimelineEntryKind::Warning)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        app.refresh_breakpoints();
+        let logged = warnings(&app);
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].breakpoint, Some(BreakpointId::from_raw(4)));
+        assert!(logged[0].message.contains("add a condition or disable it"));
+
+        app.refresh_breakpoints();
+        assert_eq!(warnings(&app).len(), 1);
+    }
+
+    #[test]
+    fn region_checkpoint_diffs_a_buffer_memset_between_stops()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().memory.extend((0x5000..0x5040).map(|address| (address, 0)));
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        let stop = DebuggerEvent::TargetStopped {
+            reason: StopReason::Suspended,
+            thread: Some(ThreadId::from(1)),
+        };
+        app.handle_debugger_event(&stop);
+
+        app.command_input = "checkpoint run".to_string();
+        app.execute_command();
+        assert!(app.error_message.as_deref().unwrap().contains("No ranges"));
+
+        app.command_input = "checkpoint add 0x5000 64 buf".to_string();
+        app.execute_command();
+        app.command_input = "checkpoint add 0x5000 0x1000001".to_string();
+        app.execute_command();
+        assert!(app.error_message.as_deref().unwrap().contains("budget"));
+        assert_eq!(app.checkpoint_ranges.ranges().len(), 1);
+
+        app.command_input = "checkpoint run".to_string();
+        app.execute_command();
+        assert!(!state.borrow().stopped, "run resumes the target");
+        assert!(app.pending_range_snapshots.is_some());
+
+        // The target memsets part of the buffer before it stops again
+        {
+            let memory = &mut state.borrow_mut().memory;
+            memory.extend((0x5010..0x5020).map(|address| (address, 0xff)));
+            memory.insert(0x503f, 1);
+        }
+        app.handle_debugger_event(&DebuggerEvent::TargetResumed);
+        app.handle_debugger_event(&stop);
+
+        let diffs = app.range_diffs.as_ref().unwrap();
+        assert_eq!(diffs[0].summary(), "buf: 17 of 64 bytes changed in 2 runs");
+        let spans: Vec<(u64, u64)> = diffs[0].runs.iter().map(|run| (run.offset, run.len)).collect();
+        assert_eq!(spans, [(0x10, 16), (0x3f, 1)]);
+        let logged: Vec<&str> = app
+            .timeline_log
+            .iter()
+            .filter(|entry| entry.kind == TimelineEntryKind::MemoryDiff)
+            .map(|entry| entry.message.as_str())
+            .collect();
+        assert_eq!(logged.len(), 3);
+        assert_eq!(logged[2], "  +0x3f (1 byte): 00 → 01");
+
+        // The popup holds focus until closed, and later stops don't reopen it
+        assert!(app.modal_open());
+        app.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(app.range_diffs.is_none());
+        assert!(!app.should_quit);
+        app.handle_debugger_event(&stop);
+        assert!(app.range_diffs.is_none());
+    }
+
+    #[test]
+    fn assertion_stops_are_checked_and_resumed_past()
+    {
+        use ferros_core::BreakpointKind;
+        use ferros_core::assertions::{AssertionKind, ViolationKind};
+
+        let (mut debugger, state) = MockDebugger::with_threads(1);
+        let mut assertions = AssertionSet::new(ViolationAction::Continue);
+        assertions.add(AssertionKind::NotReached, "0x3000").unwrap();
+        assertions.add(AssertionKind::Reached, "0x4000").unwrap();
+        assertions.add(AssertionKind::Reached, "0x5000").unwrap();
+        assertions.install(debugger.as_mut()).unwrap();
+        assert!(
+            state
+                .borrow()
+                .breakpoint_requests
+                .iter()
+                .all(|request| request.options.hidden)
+        );
+        {
+            let mut state = state.borrow_mut();
+            state.breakpoints = [0x3000, 0x4000, 0x5000]
+                .into_iter()
+                .zip(1..)
+                .map(|(address, id)| {
+                    let mut info =
+                        BreakpointInfo::new(BreakpointId::from_raw(id), Address::from(address), BreakpointKind::Software);
+                    info.options.hidden = true;
+                    info
+                })
+                .collect();
+            state.breakpoints.push(BreakpointInfo::new(
+                BreakpointId::from_raw(9),
+                Address::from(0x6000),
+                BreakpointKind::Software,
+            ));
+            state.stack.frames = vec![mock::frame(ThreadId::from(1), 0, "forbidden", 12)];
+        }
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.enable_assertions(assertions);
+        app.refresh_breakpoints();
+        assert_eq!(app.cached_breakpoints.len(), 1, "assertion breakpoints stay out of the list");
+
+        let hit = |address| DebuggerEvent::TargetStopped {
+            reason: StopReason::Breakpoint(address),
+            thread: Some(ThreadId::from(1)),
+        };
+        state.borrow_mut().stopped = true;
+        app.handle_debugger_event(&hit(0x4000));
+        assert!(!state.borrow().stopped, "reaching a required location resumes");
+        state.borrow_mut().stopped = true;
+        app.handle_debugger_event(&hit(0x3000));
+        assert!(!state.borrow().stopped, "continue resumes past a violation");
+        assert!(
+            app.error_message
+                .as_deref()
+                .unwrap()
+                .contains("not-reached 0x0000000000003000 failed")
+        );
+        let violation = &app.assertions.violations()[0];
+        assert_eq!(violation.kind, ViolationKind::Reached);
+        assert_eq!(violation.backtrace[0].function.as_deref(), Some("forbidden"));
+        assert!(
+            app.timeline_log
+                .iter()
+                .any(|entry| entry.kind == TimelineEntryKind::Error && entry.message.contains("was reached"))
+        );
+
+        // Ordinary breakpoints still stop
+        app.handle_debugger_event(&hit(0x6000));
+        assert!(app.target_is_stopped);
+
+        let violations = app.assertions.finish();
+        let kinds: Vec<_> = violations
+            .iter()
+            .map(|violation| (violation.assertion.as_str(), violation.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("not-reached 0x0000000000003000", ViolationKind::Reached),
+                ("reached 0x0000000000005000", ViolationKind::NeverReached)
+            ]
+        );
     }
 }
@@ -0,0 +1,224 @@
+//! Debugger commands shared by the TUI command palette and `ferros repl`
+//!
+//! Each function does the debugger side of one command and returns what
+//! happened; the caller decides how to show it. The palette turns results into
+//! status messages and timeline entries and the REPL prints them, so `break`,
+//! `stepi` or `mem` find the same addresses and fail the same way in both.
+
+use ferros_core::error::{DebuggerError, Result};
+use ferros_core::symbols::ambiguous_functions;
+use ferros_core::symbols::demangle::plain_name;
+use ferros_core::types::{Address, StopReason, parse_address};
+use ferros_core::{Breakpoint, BreakpointBuilder, BreakpointLocation, BreakpointRequest, Debugger};
+
+/// How far `stepi`, `nexti` and `finish` (`i`, `o` and `u` in the TUI) run the active thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step
+{
+    /// One instruction
+    Instruction,
+    /// One instruction, running a call to completion
+    Over,
+    /// Until the current function returns
+    Out,
+}
+
+/// Run the active thread as far as `step` says, returning why it stopped
+///
+/// # Errors
+///
+/// Returns whatever the backend's step fails with, such as `NotStopped`.
+pub fn step(debugger: &mut dyn Debugger, step: Step) -> Result<StopReason>
+{
+    match step {
+        Step::Instruction => debugger.step_instruction(),
+        Step::Over => debugger.step_over(),
+        Step::Out => debugger.step_out(),
+    }
+}
+
+/// How `break` finishes its breakpoints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BreakFlags
+{
+    /// `tbreak`: removed after its first hit
+    pub one_shot: bool,
+    /// `-f` or `--force`: placed even where no instruction starts
+    pub force: bool,
+}
+
+impl BreakFlags
+{
+    /// Flags of the `break` variant `command` and its `args`, and the location among them
+    ///
+    /// `tbreak` and `tb` are one-shot; `-f` or `--force` can come before or
+    /// after the location.
+    #[must_use]
+    pub fn parse<'a>(command: &str, args: &[&'a str]) -> (Self, Option<&'a str>)
+    {
+        let mut flags = Self {
+            one_shot: command.starts_with('t'),
+            force: false,
+        };
+        let mut location = None;
+        for &arg in args {
+            match arg {
+                "-f" | "--force" => flags.force = true,
+                _ => location = location.or(Some(arg)),
+            }
+        }
+        (flags, location)
+    }
+}
+
+/// Where `break` puts its breakpoints
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakPlan
+{
+    /// The location as reported back: a function found by a partial name is
+    /// shown under its plain name
+    pub location: BreakpointLocation,
+    /// One per copy of the code; empty when the location doesn't resolve yet
+    pub addresses: Vec<Address>,
+}
+
+impl BreakPlan
+{
+    /// One software breakpoint per address, or a single location breakpoint
+    /// that the backend keeps pending or explains when there are none
+    #[must_use]
+    pub fn requests(&self, flags: BreakFlags) -> Vec<BreakpointRequest>
+    {
+        if self.addresses.is_empty() {
+            let builder = match &self.location {
+                BreakpointLocation::Address(address) => Breakpoint::software(*address),
+                BreakpointLocation::Function(name) => Breakpoint::symbol(name.clone()),
+                BreakpointLocation::Line { file, line } => Breakpoint::line(file.clone(), *line),
+            };
+            return vec![build_breakpoint(builder, flags)];
+        }
+        self.addresses
+            .iter()
+            .map(|&address| build_breakpoint(Breakpoint::software(address), flags))
+            .collect()
+    }
+}
+
+/// Find where `break <location>` sets breakpoints
+///
+/// Inlined copies and generic instances give a line or a function several
+/// addresses. A line or name that doesn't resolve yet plans no addresses, so
+/// the breakpoint goes to the backend by location.
+///
+/// # Errors
+///
+/// Returns `InvalidArgument` listing the candidates when a function name finds
+/// differently named functions (`run` in two modules), rather than guessing.
+pub fn plan_breakpoints(debugger: &mut dyn Debugger, location: BreakpointLocation) -> Result<BreakPlan>
+{
+    match location {
+        BreakpointLocation::Address(address) => Ok(BreakPlan {
+            location,
+            addresses: vec![address],
+        }),
+        BreakpointLocation::Line { .. } => {
+            let addresses = debugger.resolve_location(&location).unwrap_or_default();
+            Ok(BreakPlan { location, addresses })
+        }
+        BreakpointLocation::Function(name) => {
+            let functions = debugger.find_function(&name).unwrap_or_default();
+            if let Some(message) = ambiguous_functions(&name, &functions) {
+                return Err(DebuggerError::InvalidArgument(message));
+            }
+            let location = BreakpointLocation::Function(match functions.first() {
+                Some((_, symbol)) => plain_name(symbol),
+                None => name,
+            });
+            let addresses = functions.iter().map(|&(address, _)| address).collect();
+            Ok(BreakPlan { location, addresses })
+        }
+    }
+}
+
+/// Finish a breakpoint, making it one-shot for `tbreak` and forced for `-f`
+#[must_use]
+pub fn build_breakpoint(builder: BreakpointBuilder, flags: BreakFlags) -> BreakpointRequest
+{
+    let builder = if flags.one_shot { builder.one_shot() } else { builder };
+    if flags.force { builder.force() } else { builder }.build()
+}
+
+/// The address `text` names: a number, or a function plus an offset (`main+0x10`)
+///
+/// # Errors
+///
+/// Returns `InvalidArgument` for malformed text or a function that can't be found.
+pub fn resolve_address(debugger: &mut dyn Debugger, text: &str) -> Result<Address>
+{
+    parse_address(text).and_then(|expr| debugger.resolve_address(&expr))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ferros_core::mock::MockDebugger;
+
+    use super::*;
+
+    #[test]
+    fn a_line_plans_an_address_per_copy_or_one_pending_breakpoint()
+    {
+        let (mut debugger, state) = MockDebugger::with_threads(1);
+        state
+            .borrow_mut()
+            .source_lines
+            .insert(("src/main.rs".to_string(), 42), vec![0x1000, 0x2000]);
+
+        let line = |line| BreakpointLocation::Line {
+            file: "src/main.rs".to_string(),
+            line,
+        };
+        let plan = plan_breakpoints(debugger.as_mut(), line(42)).unwrap();
+        assert_eq!(plan.addresses, [Address::from(0x1000), Address::from(0x2000)]);
+        assert_eq!(plan.requests(BreakFlags::default()).len(), 2);
+
+        let pending = plan_breakpoints(debugger.as_mut(), line(7)).unwrap();
+        assert!(pending.addresses.is_empty());
+        let one_shot = BreakFlags {
+            one_shot: true,
+            force: false,
+        };
+        assert_eq!(pending.requests(one_shot).len(), 1);
+    }
+
+    #[test]
+    fn break_flags_come_from_the_command_and_either_side_of_the_location()
+    {
+        assert_eq!(BreakFlags::parse("b", &["main"]), (BreakFlags::default(), Some("main")));
+        let (flags, location) = BreakFlags::parse("tbreak", &["0x1001", "--force"]);
+        assert_eq!((flags.one_shot, flags.force, location), (true, true, Some("0x1001")));
+        assert_eq!(BreakFlags::parse("break", &["-f"]).1, None);
+    }
+
+    #[test]
+    fn a_partial_function_name_is_planned_under_its_plain_name()
+    {
+        let (mut debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().function_names.insert(0x3000, "worker::run".to_string());
+
+        let plan = plan_breakpoints(debugger.as_mut(), BreakpointLocation::Function("run".to_string())).unwrap();
+        assert_eq!(plan.location, BreakpointLocation::Function("worker::run".to_string()));
+        assert_eq!(plan.addresses, [Address::from(0x3000)]);
+    }
+
+    #[test]
+    fn an_ambiguous_function_name_plans_nothing()
+    {
+        let (mut debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().function_names.insert(0x3000, "worker::run".to_string());
+        state.borrow_mut().function_names.insert(0x4000, "server::run".to_string());
+
+        let error = plan_breakpoints(debugger.as_mut(), BreakpointLocation::Function("run".to_string())).unwrap_err();
+        assert!(matches!(error, DebuggerError::InvalidArgument(message) if message.contains("server::run")));
+    }
+}
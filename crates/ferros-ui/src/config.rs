@@ -0,0 +1,806 @@
+//! User configuration in `~/.ferros`
+//!
+//! `config.toml` sets the same values as `:set` when the TUI starts and
+//! `keymap.toml` moves the global keys to other characters. Both are TOML
+//! read by [`ferros_utils::config::parse_entries`]: settings use dotted keys
+//! such as `stop.flash`, or the same key inside a `[stop]` table.
+//!
+//! `config.toml` also holds the startup defaults of
+//! [`ferros_utils::config::Config`] (log level, layout, search paths, signal
+//! policies). Those are read before the TUI starts; here they are only
+//! checked, so `ferros doctor` reports them with the rest. `FERROS_CONFIG`
+//! names another file to use instead.
+//!
+//! Missing files mean defaults. Malformed entries never stop ferros: each one
+//! becomes a [`ConfigProblem`] naming its file and line, the entry keeps its
+//! default, and the collected [`ConfigReport`] is shown as a TUI banner or
+//! printed to stderr. `ferros doctor` runs the same checks.
+//!
+//! The commented files written by `ferros init` are generated from
+//! [`SETTINGS`] and [`KEYMAP`], so they list exactly what this module accepts.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, io};
+
+use ferros_core::error::DebuggerError;
+use ferros_core::heartbeat::DEFAULT_PROBE_INTERVAL;
+use ferros_core::types::AddressFormat;
+use ferros_core::{signals, watchdog};
+pub use ferros_utils::config::CONFIG_FILE;
+use ferros_utils::config::{self as startup, Config};
+
+use crate::emphasis::{self, NotificationSettings};
+
+/// Key bindings file, relative to the ferros directory
+pub const KEYMAP_FILE: &str = "keymap.toml";
+/// Command history file, relative to the ferros directory
+pub const HISTORY_FILE: &str = "history";
+/// Directories `ferros init` creates inside the ferros directory
+pub const DIRECTORIES: &[&str] = &["breakpoints", "sessions"];
+/// Written once the first-run hint was shown, so it isn't repeated
+pub const HINT_MARKER: &str = ".init-hint-shown";
+/// Shown once to users without a config file
+pub const FIRST_RUN_HINT: &str = "Tip: run `ferros init` to create ~/.ferros with a documented config.toml and keymap.toml";
+
+/// One value accepted by `:set` and `config.toml`
+#[derive(Debug, Clone, Copy)]
+pub struct Setting
+{
+    /// Key, as typed after `:set`
+    pub key: &'static str,
+    /// Value a fresh session starts with
+    pub default: &'static str,
+    /// One line for the generated file
+    pub description: &'static str,
+    check: fn(&str, &str) -> Result<(), String>,
+}
+
+impl Setting
+{
+    /// Check `value` without applying it
+    ///
+    /// # Errors
+    ///
+    /// Returns the message `:set` would show for the same value.
+    pub fn validate(&self, value: &str) -> Result<(), String>
+    {
+        (self.check)(self.key, value)
+    }
+}
+
+/// Every `:set` key, in the order `:set` lists them
+pub const SETTINGS: &[Setting] = &[
+    Setting {
+        key: "stop.flash",
+        default: "on",
+        description: "Flash the header when the target stops",
+        check: check_notification,
+    },
+    Setting {
+        key: "stop.toast_ms",
+        default: "3000",
+        description: "How long a stop toast stays up in milliseconds; 0 disables toasts",
+        check: check_notification,
+    },
+    Setting {
+        key: "stop.bell",
+        default: "off",
+        description: "Ring the terminal bell when the target stops",
+        check: check_notification,
+    },
+    Setting {
+        key: "address.format",
+        default: "full",
+        description: "How tables print addresses: full, trimmed or short",
+        check: check_address_format,
+    },
+    Setting {
+        key: "resume.confirm",
+        default: "on",
+        description: "Ask before resuming past failed or pending breakpoints",
+        check: check_switch,
+    },
+    Setting {
+        key: "developer.tools",
+        default: "off",
+        description: "Enable the :debug developer commands",
+        check: check_switch,
+    },
+    Setting {
+        key: "break.panic",
+        default: "auto",
+        description: "Stop at Rust panics: on, off, or auto for launched targets only",
+        check: |key, value| parse_break_on_panic(key, value).map(drop),
+    },
+    Setting {
+        key: "hang.probe",
+        default: "off",
+        description: "Sample the main thread while the target runs: off, on, or an interval such as 500ms",
+        check: |_, value| parse_hang_probe(value).map(drop),
+    },
+    Setting {
+        key: "hang.samples",
+        default: "8",
+        description: "Samples in the same blocking call before the main thread counts as hung",
+        check: |_, value| parse_hang_samples(value).map(drop),
+    },
+    Setting {
+        key: "hang.autostop",
+        default: "off",
+        description: "Suspend the target when a main-thread hang is detected",
+        check: check_switch,
+    },
+];
+
+/// The setting named `key`
+#[must_use]
+pub fn setting(key: &str) -> Option<&'static Setting>
+{
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+fn check_notification(key: &str, value: &str) -> Result<(), String>
+{
+    NotificationSettings::default().set(key, value)
+}
+
+fn check_switch(key: &str, value: &str) -> Result<(), String>
+{
+    emphasis::parse_switch(key, value).map(drop)
+}
+
+fn check_address_format(_: &str, value: &str) -> Result<(), String>
+{
+    value.parse::<AddressFormat>().map(drop)
+}
+
+/// Parse a `hang.probe` value: `None` for off, otherwise the sampling interval
+///
+/// # Errors
+///
+/// Returns a message if the value is neither a switch nor a non-zero duration.
+pub fn parse_hang_probe(value: &str) -> Result<Option<Duration>, String>
+{
+    let interval = match value {
+        "off" => return Ok(None),
+        "on" => DEFAULT_PROBE_INTERVAL,
+        _ => watchdog::parse_duration(value)?,
+    };
+    if interval.is_zero() {
+        return Err("hang.probe needs an interval above zero".to_string());
+    }
+    Ok(Some(interval))
+}
+
+/// Parse a `break.panic` value: `auto` is `None`, else a switch
+///
+/// # Errors
+///
+/// Returns a message if the value is neither `auto` nor a switch.
+pub fn parse_break_on_panic(key: &str, value: &str) -> Result<Option<bool>, String>
+{
+    startup::parse_auto_switch(value).map_err(|_| format!("{key} expects auto, on or off, got '{value}'"))
+}
+
+/// Parse a `hang.samples` value
+///
+/// # Errors
+///
+/// Returns a message if the value isn't a number of at least 2.
+pub fn parse_hang_samples(value: &str) -> Result<usize, String>
+{
+    let samples: usize = value.parse().map_err(|_| format!("Invalid number: {value}"))?;
+    if samples < 2 {
+        return Err("hang.samples needs at least 2 samples".to_string());
+    }
+    Ok(samples)
+}
+
+/// A global key that `keymap.toml` can move
+#[derive(Debug, Clone, Copy)]
+pub struct Binding
+{
+    /// Name used in `keymap.toml`
+    pub action: &'static str,
+    /// Key the action is on by default
+    pub key: char,
+    /// One line for the generated file
+    pub description: &'static str,
+}
+
+/// Global keys that can be rebound
+///
+/// A moved key also moves where a view gives the same key its own meaning
+/// (Threads `s`/`r`, Stack `b`). View numbers can't be bound.
+pub const KEYMAP: &[Binding] = &[
+    Binding {
+        action: "command_palette",
+        key: ':',
+        description: "Open the command palette",
+    },
+    Binding {
+        action: "help",
+        key: '?',
+        description: "Toggle the help view",
+    },
+    Binding {
+        action: "toggle_breakpoint",
+        key: 'b',
+        description: "Toggle a breakpoint at the current line or PC",
+    },
+    Binding {
+        action: "breakpoint_editor",
+        key: 'B',
+        description: "Open the breakpoint editor",
+    },
+    Binding {
+        action: "suspend",
+        key: 's',
+        description: "Suspend the target",
+    },
+    Binding {
+        action: "resume",
+        key: 'r',
+        description: "Resume the target",
+    },
+    Binding {
+        action: "step_instruction",
+        key: 'i',
+        description: "Execute one instruction on the active thread",
+    },
+    Binding {
+        action: "step_over",
+        key: 'o',
+        description: "Execute one instruction, running calls to completion",
+    },
+    Binding {
+        action: "step_out",
+        key: 'u',
+        description: "Run until the current function returns",
+    },
+    Binding {
+        action: "cycle_layout",
+        key: 'l',
+        description: "Cycle layout presets",
+    },
+    Binding {
+        action: "where",
+        key: 'w',
+        description: "Show where the active thread is stopped",
+    },
+    Binding {
+        action: "address_format",
+        key: 'x',
+        description: "Cycle the address format",
+    },
+];
+
+/// Global keys after applying `keymap.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Keymap
+{
+    /// Pressed key -> the default key of the action it is bound to
+    moved: HashMap<char, char>,
+}
+
+impl Keymap
+{
+    /// The default key to dispatch for `pressed`, or `None` if its action moved away
+    ///
+    /// Keys that are neither rebound nor an action's default pass through.
+    #[must_use]
+    pub fn translate(&self, pressed: char) -> Option<char>
+    {
+        if let Some(&key) = self.moved.get(&pressed) {
+            return Some(key);
+        }
+        if self.moved.values().any(|&key| key == pressed) {
+            return None;
+        }
+        Some(pressed)
+    }
+
+    /// Whether every action is on its default key
+    #[must_use]
+    pub fn is_default(&self) -> bool
+    {
+        self.moved.is_empty()
+    }
+}
+
+/// One malformed entry in a config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem
+{
+    /// File the entry is in
+    pub file: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}:{}: {}", self.file.display(), self.line, self.message)
+    }
+}
+
+/// Every problem found while loading the config files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigReport
+{
+    problems: Vec<ConfigProblem>,
+}
+
+impl ConfigReport
+{
+    /// Record a problem at `file:line`
+    pub fn push(&mut self, file: &Path, line: usize, message: impl Into<String>)
+    {
+        self.problems.push(ConfigProblem {
+            file: file.to_path_buf(),
+            line,
+            message: message.into(),
+        });
+    }
+
+    /// Order the problems from index `first` on by line, once a file is fully checked
+    fn sort_from(&mut self, first: usize)
+    {
+        self.problems[first..].sort_by_key(|problem| problem.line);
+    }
+
+    /// Problems by file, in line order
+    #[must_use]
+    pub fn problems(&self) -> &[ConfigProblem]
+    {
+        &self.problems
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.problems.is_empty()
+    }
+
+    /// One line saying how many problems there are and in how many files
+    #[must_use]
+    pub fn summary(&self) -> String
+    {
+        let mut files: Vec<&Path> = self.problems.iter().map(|problem| problem.file.as_path()).collect();
+        files.dedup();
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        let mut summary = format!("{} config problem{}", self.problems.len(), plural(self.problems.len()));
+        let _ = write!(
+            summary,
+            " in {} file{}; those entries use their defaults",
+            files.len(),
+            plural(files.len())
+        );
+        summary
+    }
+
+    /// The summary followed by one indented `file:line: message` per problem
+    #[must_use]
+    pub fn render(&self) -> String
+    {
+        let mut text = self.summary();
+        for problem in &self.problems {
+            text.push_str("\n  ");
+            text.push_str(&problem.to_string());
+        }
+        text
+    }
+}
+
+/// Settings and keys loaded from the ferros directory
+#[derive(Debug, Clone, Default)]
+pub struct UserConfig
+{
+    /// Valid `config.toml` entries as `:set` key/value pairs, in file order
+    pub settings: Vec<(String, String)>,
+    /// Global keys from `keymap.toml`
+    pub keymap: Keymap,
+    /// What was wrong with either file
+    pub report: ConfigReport,
+}
+
+impl UserConfig
+{
+    /// Load `config.toml` and `keymap.toml` from `dir`
+    ///
+    /// Missing files are not problems; unreadable ones are. `FERROS_CONFIG`
+    /// replaces `config.toml`.
+    #[must_use]
+    pub fn load(dir: &Path) -> Self
+    {
+        let mut config = Self::default();
+        let config_path = startup::path_in(dir);
+        if let Some(text) = read_optional(&config_path, &mut config.report) {
+            config.settings = check_settings(&config_path, &text, &mut config.report);
+        }
+        let keymap_path = dir.join(KEYMAP_FILE);
+        if let Some(text) = read_optional(&keymap_path, &mut config.report) {
+            config.keymap = check_keymap(&keymap_path, &text, &mut config.report);
+        }
+        config
+    }
+}
+
+fn read_optional(path: &Path, report: &mut ConfigReport) -> Option<String>
+{
+    match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => {
+            report.push(path, 0, format!("could not read: {e}"));
+            None
+        }
+    }
+}
+
+/// Parse `config.toml` text, keeping the entries that pass their setting's check
+///
+/// Startup keys are checked but not kept; [`Config`] applies them, and its
+/// warnings are reported here with the rest.
+pub fn check_settings(path: &Path, text: &str, report: &mut ConfigReport) -> Vec<(String, String)>
+{
+    let first = report.problems.len();
+    let mut settings = Vec::new();
+    for (line, key, value) in entries(path, text, report) {
+        match (setting(&key), Config::validate(&key, &value)) {
+            (Some(setting), _) => match value.as_text().map(|text| (text, setting.validate(text))) {
+                Some((text, Ok(()))) => settings.push((key, text.to_string())),
+                Some((_, Err(e))) => report.push(path, line, e),
+                None => report.push(path, line, format!("{key}: expected a string, integer or boolean")),
+            },
+            // Reported from Config's warnings below
+            (None, Some(Err(_))) => {}
+            (None, Some(Ok(()))) => {
+                // Config only checks the policy; the name has to be one the backend knows
+                let signal = key.strip_prefix("signal.").map(signals::parse_signal);
+                if let Some(Err(DebuggerError::InvalidArgument(e))) = signal {
+                    report.push(path, line, format!("{key}: {e}"));
+                }
+            }
+            (None, None) => report.push(path, line, format!("unknown setting '{key}'")),
+        }
+    }
+    for warning in Config::parse(text).1 {
+        report.push(path, warning.line, warning.to_string());
+    }
+    report.sort_from(first);
+    settings
+}
+
+/// Parse `keymap.toml` text into a [`Keymap`]
+///
+/// Bindings are single characters. Two actions on the same key are both dropped.
+pub fn check_keymap(path: &Path, text: &str, report: &mut ConfigReport) -> Keymap
+{
+    let first = report.problems.len();
+    let mut claimed: HashMap<char, (usize, &'static Binding)> = HashMap::new();
+    let mut rejected = Vec::new();
+    for (line, action, value) in entries(path, text, report) {
+        let Some(binding) = KEYMAP.iter().find(|binding| binding.action == action) else {
+            report.push(path, line, format!("unknown action '{action}'"));
+            continue;
+        };
+        let Some(value) = value.as_text() else {
+            report.push(path, line, format!("{action} needs a single character"));
+            continue;
+        };
+        let mut chars = value.chars();
+        let (Some(key), None) = (chars.next(), chars.next()) else {
+            report.push(path, line, format!("{action} needs a single character, got '{value}'"));
+            continue;
+        };
+        if key.is_whitespace() {
+            report.push(path, line, format!("{action} can't be bound to whitespace"));
+            continue;
+        }
+        if key.is_ascii_digit() {
+            report.push(path, line, format!("{action} can't use '{key}'; digits switch views"));
+            continue;
+        }
+        if let Some((first, other)) = claimed.get(&key) {
+            report.push(
+                path,
+                line,
+                format!("'{key}' is already bound to {} on line {first}", other.action),
+            );
+            rejected.push(key);
+            continue;
+        }
+        claimed.insert(key, (line, binding));
+    }
+    for key in rejected {
+        claimed.remove(&key);
+    }
+    // Taking another action's default key only works if that action moved too
+    let moved: Vec<&str> = claimed
+        .iter()
+        .filter(|(key, (_, binding))| **key != binding.key)
+        .map(|(_, (_, binding))| binding.action)
+        .collect();
+    let mut stolen: Vec<(usize, char, &str)> = claimed
+        .iter()
+        .filter_map(|(&key, &(line, binding))| {
+            let owner = KEYMAP
+                .iter()
+                .find(|other| other.key == key && other.action != binding.action)?;
+            (!moved.contains(&owner.action)).then_some((line, key, owner.action))
+        })
+        .collect();
+    stolen.sort_unstable();
+    for (line, key, owner) in stolen {
+        report.push(
+            path,
+            line,
+            format!("'{key}' is {owner}'s default key; bind {owner} elsewhere first"),
+        );
+        claimed.remove(&key);
+    }
+    report.sort_from(first);
+    Keymap {
+        moved: claimed
+            .into_iter()
+            .filter(|(key, (_, binding))| *key != binding.key)
+            .map(|(key, (_, binding))| (key, binding.key))
+            .collect(),
+    }
+}
+
+/// `(line, key, value)` for each well-formed line; the rest are reported
+fn entries(path: &Path, text: &str, report: &mut ConfigReport) -> Vec<(usize, String, startup::Value)>
+{
+    let (entries, problems) = startup::parse_entries(text);
+    for (line, message) in problems {
+        report.push(path, line, message);
+    }
+    entries
+        .into_iter()
+        .map(|entry| (entry.line, entry.key, entry.value))
+        .collect()
+}
+
+/// `value` as it is written in a generated file
+fn toml_value(value: &str) -> String
+{
+    if value.parse::<u64>().is_ok() || value.starts_with('[') {
+        value.to_string()
+    } else {
+        format!("\"{value}\"")
+    }
+}
+
+/// Commented `config.toml` listing every setting at its default
+#[must_use]
+pub fn default_config() -> String
+{
+    let mut text = String::from(
+        "# ferros settings, applied when the TUI starts\n#\n# Every key is also available at runtime with `:set <key> \
+         <value>`.\n# Uncomment a line to change it; the values shown are the defaults.\n",
+    );
+    for setting in SETTINGS {
+        let _ = write!(
+            text,
+            "\n# {}\n# {} = {}\n",
+            setting.description,
+            setting.key,
+            toml_value(setting.default)
+        );
+    }
+    text.push_str(
+        "\n# Read when ferros starts; command-line flags override them.\n# `ferros config show` prints the values in \
+         effect.\n",
+    );
+    for key in startup::KEYS {
+        let _ = write!(text, "\n# {}\n# {} = {}\n", key.description, key.key, toml_value(key.default));
+    }
+    text.push_str(
+        "\n# What a signal does: stop, pass or ignore, as with :handle; one line per signal\n# signal.SIGPIPE = \"pass\"\n",
+    );
+    text
+}
+
+/// Commented `keymap.toml` listing every rebindable action on its default key
+#[must_use]
+pub fn default_keymap() -> String
+{
+    let mut text = String::from(
+        "# ferros global key bindings\n#\n# Bind an action to another single character. Its default key is then\n# free, \
+         including in views that reuse it. Digits switch views and can't be bound.\n",
+    );
+    for binding in KEYMAP {
+        let _ = write!(
+            text,
+            "\n# {}\n# {} = {}\n",
+            binding.description,
+            binding.action,
+            toml_value(&binding.key.to_string())
+        );
+    }
+    text
+}
+
+/// What `ferros init` did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitReport
+{
+    /// Files and directories that were created
+    pub created: Vec<PathBuf>,
+    /// Files that already existed and were left alone
+    pub kept: Vec<PathBuf>,
+}
+
+/// Create the ferros directory tree in `dir` without touching existing files
+///
+/// # Errors
+///
+/// Returns the first I/O error creating a directory or writing a file.
+pub fn init(dir: &Path) -> io::Result<InitReport>
+{
+    let mut report = InitReport::default();
+    fs::create_dir_all(dir)?;
+    for name in DIRECTORIES {
+        let path = dir.join(name);
+        if !path.is_dir() {
+            fs::create_dir_all(&path)?;
+            report.created.push(path);
+        }
+    }
+    for name in [CONFIG_FILE, KEYMAP_FILE, HISTORY_FILE] {
+        let path = dir.join(name);
+        if path.exists() {
+            report.kept.push(path);
+            continue;
+        }
+        let contents = match name {
+            CONFIG_FILE => default_config(),
+            KEYMAP_FILE => default_keymap(),
+            _ => String::new(),
+        };
+        fs::write(&path, contents)?;
+        report.created.push(path);
+    }
+    let _ = fs::write(dir.join(HINT_MARKER), "");
+    Ok(report)
+}
+
+/// Whether to show [`FIRST_RUN_HINT`], recording that it was shown
+///
+/// The hint is skipped once `dir` has a config file or the marker, and if
+/// the marker can't be written, so it never shows twice.
+#[must_use]
+pub fn take_first_run_hint(dir: &Path) -> bool
+{
+    if dir.join(CONFIG_FILE).exists() || dir.join(HINT_MARKER).exists() {
+        return false;
+    }
+    fs::create_dir_all(dir)
+        .and_then(|()| fs::write(dir.join(HINT_MARKER), ""))
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn uncommented(text: &str) -> String
+    {
+        text.lines()
+            .map(|line| line.strip_prefix("# ").filter(|rest| rest.contains(" = ")).unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn generated_files_list_every_entry_and_parse_cleanly()
+    {
+        let config = default_config();
+        let keymap = default_keymap();
+        for setting in SETTINGS {
+            assert!(config.contains(&format!("# {} = ", setting.key)), "{} missing", setting.key);
+            assert!(setting.validate(setting.default).is_ok(), "{} default rejected", setting.key);
+        }
+        for binding in KEYMAP {
+            assert!(keymap.contains(&format!("# {} = \"{}\"", binding.action, binding.key)));
+        }
+
+        // As shipped everything is commented out; uncommented it sets every default
+        let mut report = ConfigReport::default();
+        assert!(check_settings(Path::new("config.toml"), &config, &mut report).is_empty());
+        let settings = check_settings(Path::new("config.toml"), &uncommented(&config), &mut report);
+        let keys: Vec<&str> = settings.iter().map(|(key, _)| key.as_str()).collect();
+        let expected: Vec<&str> = SETTINGS.iter().map(|setting| setting.key).collect();
+        assert_eq!(keys, expected);
+        let keymap = check_keymap(Path::new("keymap.toml"), &uncommented(&keymap), &mut report);
+        assert!(keymap.is_default());
+        assert!(report.is_empty(), "{}", report.render());
+    }
+
+    #[test]
+    fn problems_from_both_files_are_collected_with_their_lines()
+    {
+        let mut report = ConfigReport::default();
+        let config = "stop.flash = \"on\"\nstop.bell = maybe\nhang.samples = 1\ncolour = \"red\"\nstop.flash = \
+                      \"off\"\nlayout.preset = \"tiny\"\nstack.max_frames = 128\nsignal.SIGFOO = \
+                      \"pass\"\n[address]\nformat = \"tiny\"\n";
+        let settings = check_settings(Path::new("config.toml"), config, &mut report);
+        assert_eq!(settings, vec![("stop.flash".to_string(), "on".to_string())]);
+
+        let keymap = "suspend = \"z\"\nresume = \"z\"\nwhere = \"ww\"\nfly = \"f\"\nhelp = \"1\"\naddress_format = \"l\"\n";
+        let keys = check_keymap(Path::new("keymap.toml"), keymap, &mut report);
+        assert!(keys.is_default());
+
+        let lines: Vec<String> = report.problems().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "config.toml:2: string values must be quoted, expected literal string: maybe",
+                "config.toml:3: hang.samples needs at least 2 samples",
+                "config.toml:4: unknown setting 'colour'",
+                "config.toml:5: duplicate key: flash",
+                "config.toml:6: layout.preset: expected compact, standard or widescreen, got 'tiny'",
+                "config.toml:8: signal.SIGFOO: Unknown signal 'SIGFOO'",
+                "config.toml:10: Unknown address format 'tiny' (full, trimmed, short)",
+                "keymap.toml:2: 'z' is already bound to suspend on line 1",
+                "keymap.toml:3: where needs a single character, got 'ww'",
+                "keymap.toml:4: unknown action 'fly'",
+                "keymap.toml:5: help can't use '1'; digits switch views",
+                "keymap.toml:6: 'l' is cycle_layout's default key; bind cycle_layout elsewhere first",
+            ]
+        );
+        assert!(
+            report
+                .render()
+                .starts_with("12 config problems in 2 files; those entries use their defaults\n  config.toml:2:")
+        );
+    }
+
+    #[test]
+    fn a_rebound_action_frees_its_default_key()
+    {
+        let mut report = ConfigReport::default();
+        let text = "suspend = \"z\" # easier to reach\nresume = \"s\"\n";
+        let keymap = check_keymap(Path::new("keymap.toml"), text, &mut report);
+        assert!(report.is_empty(), "{}", report.render());
+        assert_eq!(keymap.translate('z'), Some('s'));
+        assert_eq!(keymap.translate('s'), Some('r'));
+        assert_eq!(keymap.translate('r'), None);
+        assert_eq!(keymap.translate('w'), Some('w'));
+    }
+
+    #[test]
+    fn init_creates_the_tree_once_and_keeps_existing_files()
+    {
+        let dir = std::env::temp_dir().join(format!("ferros-init-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(take_first_run_hint(&dir));
+        assert!(!take_first_run_hint(&dir));
+
+        let first = init(&dir).unwrap();
+        assert_eq!(first.created.len(), DIRECTORIES.len() + 3);
+        assert!(first.kept.is_empty());
+        assert_eq!(fs::read_to_string(dir.join(CONFIG_FILE)).unwrap(), default_config());
+        assert!(UserConfig::load(&dir).report.is_empty());
+
+        fs::write(dir.join(CONFIG_FILE), "stop.bell = \"on\"\n").unwrap();
+        let second = init(&dir).unwrap();
+        assert!(second.created.is_empty());
+        assert_eq!(second.kept.len(), 3);
+        assert_eq!(
+            UserConfig::load(&dir).settings,
+            vec![("stop.bell".to_string(), "on".to_string())]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,199 @@
+//! Disassembly view: decoded instructions around the PC
+//!
+//! The view holds a window of instructions and a selection. It is loaded
+//! around the inspected PC at each stop and grows at either end when the
+//! selection moves past it, so scrolling decodes only what comes into view.
+//!
+//! ARM64 instructions are all 4 bytes, so the window can start any number of
+//! words before the PC. x86-64 can't be decoded backwards: the window starts
+//! at the enclosing function when the symbols know it (and it isn't too far
+//! away), otherwise at the PC, and scrolling up stops at a function start.
+
+use ferros_core::Debugger;
+use ferros_core::types::{Address, Architecture, DisassembledInstruction};
+use ratatui::widgets::TableState;
+
+/// Instructions decoded below the window each time it grows
+pub const DISASSEMBLY_CHUNK: usize = 32;
+
+/// Instructions shown above the PC when the view is loaded
+pub const CONTEXT_BEFORE: usize = 8;
+
+/// Page size used to keep ARM64 reads above the window inside one page
+const PAGE_SIZE: u64 = 4096;
+
+/// Furthest an x86-64 function start can be before an address to decode from it
+const MAX_LEAD_BYTES: usize = 1024;
+
+/// State of the Disassembly view
+#[derive(Debug, Clone, Default)]
+pub struct DisassemblyView
+{
+    /// Decoded instructions, lowest address first
+    pub listing: Vec<DisassembledInstruction>,
+    /// Index of the selected instruction in `listing`
+    pub selected: usize,
+    /// PC the view was loaded around
+    pub pc: Option<Address>,
+    /// Why the last load failed
+    pub error: Option<String>,
+    /// Scroll position of the drawn table
+    pub table: TableState,
+}
+
+impl DisassemblyView
+{
+    /// Decode the instructions around `pc` and select the one at `pc`
+    pub fn load(&mut self, debugger: &mut dyn Debugger, pc: Address)
+    {
+        let (start, count) = lead_in(debugger, pc);
+        // Decoding from too early can run past the PC on x86-64; start at the PC then
+        let listing = match debugger.disassemble(start, count) {
+            Ok(listing) if listing.iter().any(|insn| insn.address == pc) => Ok(listing),
+            _ => debugger.disassemble(pc, DISASSEMBLY_CHUNK),
+        };
+        self.pc = Some(pc);
+        self.table = TableState::default();
+        match listing {
+            Ok(mut listing) => {
+                let at = listing.iter().position(|insn| insn.address == pc).unwrap_or(0);
+                let first = at.saturating_sub(CONTEXT_BEFORE);
+                listing.drain(..first);
+                listing.truncate(at - first + DISASSEMBLY_CHUNK);
+                self.listing = listing;
+                self.selected = at - first;
+                self.error = None;
+                self.mark_pc();
+            }
+            Err(e) => {
+                self.listing.clear();
+                self.selected = 0;
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Forget the listing, for when there's no stopped target to decode
+    pub fn clear(&mut self)
+    {
+        *self = Self::default();
+    }
+
+    /// Address of the selected instruction
+    #[must_use]
+    pub fn selected_address(&self) -> Option<Address>
+    {
+        self.listing.get(self.selected).map(|insn| insn.address)
+    }
+
+    /// Move the selection up one instruction, decoding more above the window at its top
+    pub fn select_previous(&mut self, debugger: &mut dyn Debugger)
+    {
+        if self.selected == 0 {
+            self.grow_up(debugger);
+        }
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Move the selection down one instruction, decoding more below the window at its bottom
+    pub fn select_next(&mut self, debugger: &mut dyn Debugger)
+    {
+        if self.selected + 1 >= self.listing.len() {
+            self.grow_down(debugger);
+        }
+        if self.selected + 1 < self.listing.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Decode the instructions that end where the window starts
+    fn grow_up(&mut self, debugger: &mut dyn Debugger)
+    {
+        let Some(first) = self.listing.first().map(|insn| insn.address) else {
+            return;
+        };
+        let Some((start, count)) = preceding(debugger, first) else {
+            return;
+        };
+        let Ok(mut before) = debugger.disassemble(start, count) else {
+            return;
+        };
+        before.retain(|insn| insn.end() <= first);
+        // x86-64 decoding from a function start has to land exactly on the window
+        if before.last().is_none_or(|insn| insn.end() != first) {
+            return;
+        }
+        let added = before.len();
+        before.append(&mut self.listing);
+        self.listing = before;
+        self.selected += added;
+        *self.table.offset_mut() += added;
+        self.mark_pc();
+    }
+
+    /// Decode the instructions after the end of the window
+    fn grow_down(&mut self, debugger: &mut dyn Debugger)
+    {
+        let Some(end) = self.listing.last().map(DisassembledInstruction::end) else {
+            return;
+        };
+        if let Ok(after) = debugger.disassemble(end, DISASSEMBLY_CHUNK) {
+            self.listing.extend(after);
+            self.mark_pc();
+        }
+    }
+
+    /// Mark the instruction at the PC the view was loaded around, which in
+    /// history mode isn't the live one `disassemble` marked
+    fn mark_pc(&mut self)
+    {
+        for insn in &mut self.listing {
+            insn.is_current_pc = Some(insn.address) == self.pc;
+        }
+    }
+}
+
+/// Where to start decoding so [`CONTEXT_BEFORE`] instructions come before `pc`, and how many to decode
+fn lead_in(debugger: &dyn Debugger, pc: Address) -> (Address, usize)
+{
+    match debugger.architecture() {
+        Architecture::X86_64 => match function_lead(debugger, pc) {
+            Some((start, lead)) => (start, lead + DISASSEMBLY_CHUNK),
+            None => (pc, DISASSEMBLY_CHUNK),
+        },
+        Architecture::Arm64 | Architecture::Unknown(_) => {
+            let words = (pc.value() / 4).min(CONTEXT_BEFORE as u64);
+            (pc - words * 4, words as usize + DISASSEMBLY_CHUNK)
+        }
+    }
+}
+
+/// Where to start decoding to reach the instruction before `first`, and how many to decode
+fn preceding(debugger: &dyn Debugger, first: Address) -> Option<(Address, usize)>
+{
+    match debugger.architecture() {
+        Architecture::X86_64 => {
+            let (start, lead) = function_lead(debugger, first.checked_sub(1)?)?;
+            Some((start, lead + 1))
+        }
+        Architecture::Arm64 | Architecture::Unknown(_) => {
+            // Stay on the window's page unless it starts one, so an unmapped page above doesn't stop the read
+            let on_page = (first.value() % PAGE_SIZE) / 4;
+            let words = match on_page {
+                0 => first.value() / 4,
+                _ => on_page,
+            };
+            let words = usize::try_from(words).map_or(DISASSEMBLY_CHUNK, |words| words.min(DISASSEMBLY_CHUNK));
+            (words > 0).then(|| (first - words as u64 * 4, words))
+        }
+    }
+}
+
+/// Start of the function containing `address` and how many bytes it is before it,
+/// if it's close enough to decode from
+fn function_lead(debugger: &dyn Debugger, address: Address) -> Option<(Address, usize)>
+{
+    let start = debugger.function_start(address)?;
+    let lead = usize::try_from(address.value() - start.value()).ok()?;
+    (lead <= MAX_LEAD_BYTES).then_some((start, lead))
+}
@@ -0,0 +1,306 @@
+//! Stop emphasis: header flash, corner toasts and the terminal bell
+//!
+//! A breakpoint hit while another view is open only changes one status line.
+//! When the TUI ingests a stop the user didn't ask for, [`StopEmphasis`]
+//! flashes the header for [`FLASH_FRAMES`] frames, queues a toast with the
+//! stop reason and symbol, and optionally rings the bell. Nothing here polls:
+//! stops come in through [`StopEmphasis::on_stop`] and the draw loop consumes
+//! flash frames and expires toasts as it renders.
+//!
+//! Settings are changed with `:set` ([`NotificationSettings::set`]):
+//!
+//! - `stop.flash on|off` - flash the header (default on)
+//! - `stop.toast_ms <ms>` - how long a toast stays up, 0 disables toasts (default 3000)
+//! - `stop.bell on|off` - ring the terminal bell (default off)
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Render frames the header stays inverted after a stop
+pub const FLASH_FRAMES: u8 = 3;
+/// Toasts shown at once; older ones are dropped first
+pub const MAX_TOASTS: usize = 3;
+/// Toast lifetime unless changed with `stop.toast_ms`
+pub const DEFAULT_TOAST: Duration = Duration::from_secs(3);
+
+/// User-configurable notification behavior
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationSettings
+{
+    /// Invert the header for a few frames on a stop
+    pub flash: bool,
+    /// How long a stop toast stays up; zero disables toasts
+    pub toast: Duration,
+    /// Ring the terminal bell on a stop
+    pub bell: bool,
+}
+
+impl Default for NotificationSettings
+{
+    fn default() -> Self
+    {
+        Self {
+            flash: true,
+            toast: DEFAULT_TOAST,
+            bell: false,
+        }
+    }
+}
+
+impl NotificationSettings
+{
+    /// Change one setting by its `:set` key
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the key is unknown or the value doesn't parse.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String>
+    {
+        match key {
+            "stop.flash" => self.flash = parse_switch(key, value)?,
+            "stop.bell" => self.bell = parse_switch(key, value)?,
+            "stop.toast_ms" => {
+                let ms = value
+                    .parse()
+                    .map_err(|_| format!("{key} expects milliseconds, got '{value}'"))?;
+                self.toast = Duration::from_millis(ms);
+            }
+            _ => {
+                let keys: Vec<&str> = crate::config::SETTINGS.iter().map(|setting| setting.key).collect();
+                return Err(format!("Unknown setting '{key}' ({})", keys.join(", ")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Current values as `key=value` pairs, as accepted by `:set`
+    #[must_use]
+    pub fn describe(&self) -> String
+    {
+        let switch = |on: bool| if on { "on" } else { "off" };
+        format!(
+            "stop.flash={} stop.toast_ms={} stop.bell={}",
+            switch(self.flash),
+            self.toast.as_millis(),
+            switch(self.bell)
+        )
+    }
+}
+
+/// Parse an `on`/`off` setting value for `key`
+pub(crate) fn parse_switch(key: &str, value: &str) -> Result<bool, String>
+{
+    match value {
+        "on" | "true" | "1" => Ok(true),
+        "off" | "false" | "0" => Ok(false),
+        _ => Err(format!("{key} expects on or off, got '{value}'")),
+    }
+}
+
+/// Transient corner notice for one stop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toast
+{
+    /// Stop reason
+    pub title: String,
+    /// Symbol (and location) the target stopped in, if known
+    pub detail: Option<String>,
+    /// How many identical stops this toast stands for
+    pub repeats: u32,
+    /// When the latest of those stops arrived
+    pub shown_at: Instant,
+}
+
+impl Toast
+{
+    /// Whether the toast is in the last third of its `lifetime` and should be drawn faded
+    #[must_use]
+    pub fn is_fading(&self, lifetime: Duration, now: Instant) -> bool
+    {
+        now.saturating_duration_since(self.shown_at) >= lifetime * 2 / 3
+    }
+}
+
+/// Flash, toast and bell state driven by stop events
+#[derive(Debug, Default)]
+pub struct StopEmphasis
+{
+    /// Current settings
+    pub settings: NotificationSettings,
+    toasts: VecDeque<Toast>,
+    flash_frames: u8,
+    bell_pending: bool,
+}
+
+impl StopEmphasis
+{
+    /// Emphasis with the given settings and nothing pending
+    #[must_use]
+    pub fn new(settings: NotificationSettings) -> Self
+    {
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// Record a stop at `now`
+    ///
+    /// A stop identical to a queued toast bumps that toast's repeat count and
+    /// moves it to the end instead of adding another. At most [`MAX_TOASTS`]
+    /// toasts are kept.
+    pub fn on_stop(&mut self, title: String, detail: Option<String>, now: Instant)
+    {
+        if self.settings.flash {
+            self.flash_frames = FLASH_FRAMES;
+        }
+        self.bell_pending |= self.settings.bell;
+        if self.settings.toast.is_zero() {
+            return;
+        }
+
+        let repeats = match self
+            .toasts
+            .iter()
+            .position(|toast| toast.title == title && toast.detail == detail)
+        {
+            Some(index) => self.toasts.remove(index).map_or(1, |toast| toast.repeats + 1),
+            None => 1,
+        };
+        self.toasts.push_back(Toast {
+            title,
+            detail,
+            repeats,
+            shown_at: now,
+        });
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    /// Drop toasts that have been up longer than the configured lifetime
+    pub fn expire(&mut self, now: Instant)
+    {
+        let lifetime = self.settings.toast;
+        self.toasts
+            .retain(|toast| now.saturating_duration_since(toast.shown_at) < lifetime);
+    }
+
+    /// Queued toasts, oldest first
+    pub fn toasts(&self) -> impl Iterator<Item = &Toast>
+    {
+        self.toasts.iter()
+    }
+
+    /// Whether the frame about to be drawn flashes; uses up one flash frame
+    pub fn next_frame_flashes(&mut self) -> bool
+    {
+        let flashes = self.flash_frames > 0;
+        self.flash_frames = self.flash_frames.saturating_sub(1);
+        flashes
+    }
+
+    /// Drop any remaining flash frames, e.g. while a popup has focus
+    pub fn cancel_flash(&mut self)
+    {
+        self.flash_frames = 0;
+    }
+
+    /// Whether a bell is due; clears it
+    pub fn take_bell(&mut self) -> bool
+    {
+        std::mem::take(&mut self.bell_pending)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn stop(emphasis: &mut StopEmphasis, title: &str, now: Instant)
+    {
+        emphasis.on_stop(title.to_string(), Some("demo::main".to_string()), now);
+    }
+
+    #[test]
+    fn repeats_collapse_and_queue_is_bounded()
+    {
+        let start = Instant::now();
+        let mut emphasis = StopEmphasis::default();
+        stop(&mut emphasis, "Hit breakpoint at 0x1000", start);
+        stop(&mut emphasis, "Hit breakpoint at 0x2000", start);
+        stop(&mut emphasis, "Hit breakpoint at 0x1000", start + Duration::from_millis(10));
+
+        let queued: Vec<(&str, u32)> = emphasis.toasts().map(|t| (t.title.as_str(), t.repeats)).collect();
+        assert_eq!(queued, vec![("Hit breakpoint at 0x2000", 1), ("Hit breakpoint at 0x1000", 2)]);
+
+        for address in 0..10 {
+            stop(&mut emphasis, &format!("Hit breakpoint at 0x{address}"), start);
+        }
+        assert_eq!(emphasis.toasts().count(), MAX_TOASTS);
+        assert_eq!(emphasis.toasts().last().unwrap().title, "Hit breakpoint at 0x9");
+    }
+
+    #[test]
+    fn toasts_fade_then_expire()
+    {
+        let start = Instant::now();
+        let mut emphasis = StopEmphasis::default();
+        emphasis.settings.toast = Duration::from_millis(900);
+        stop(&mut emphasis, "Stopped by signal: 11", start);
+
+        let toast = emphasis.toasts().next().unwrap().clone();
+        assert!(!toast.is_fading(emphasis.settings.toast, start + Duration::from_millis(500)));
+        assert!(toast.is_fading(emphasis.settings.toast, start + Duration::from_millis(600)));
+
+        emphasis.expire(start + Duration::from_millis(899));
+        assert_eq!(emphasis.toasts().count(), 1);
+        emphasis.expire(start + Duration::from_millis(900));
+        assert_eq!(emphasis.toasts().count(), 0);
+
+        // A repeat restarts the lifetime
+        stop(&mut emphasis, "Stopped by signal: 11", start);
+        stop(&mut emphasis, "Stopped by signal: 11", start + Duration::from_millis(800));
+        emphasis.expire(start + Duration::from_secs(1));
+        assert_eq!(emphasis.toasts().next().map(|t| t.repeats), Some(2));
+    }
+
+    #[test]
+    fn flash_lasts_a_few_frames_and_bell_fires_once()
+    {
+        let mut emphasis = StopEmphasis::new(NotificationSettings {
+            bell: true,
+            ..NotificationSettings::default()
+        });
+        stop(&mut emphasis, "Hit breakpoint at 0x1000", Instant::now());
+
+        let frames: Vec<bool> = (0..5).map(|_| emphasis.next_frame_flashes()).collect();
+        assert_eq!(frames, vec![true, true, true, false, false]);
+        assert!(emphasis.take_bell());
+        assert!(!emphasis.take_bell());
+
+        stop(&mut emphasis, "Hit breakpoint at 0x1000", Instant::now());
+        emphasis.cancel_flash();
+        assert!(!emphasis.next_frame_flashes());
+    }
+
+    #[test]
+    fn settings_parse_and_disable()
+    {
+        let mut settings = NotificationSettings::default();
+        assert_eq!(settings.describe(), "stop.flash=on stop.toast_ms=3000 stop.bell=off");
+        settings.set("stop.flash", "off").unwrap();
+        settings.set("stop.bell", "on").unwrap();
+        settings.set("stop.toast_ms", "0").unwrap();
+        assert_eq!(settings.describe(), "stop.flash=off stop.toast_ms=0 stop.bell=on");
+        assert!(settings.set("stop.toast_ms", "soon").is_err());
+        assert!(settings.set("stop.sound", "on").is_err());
+
+        let mut emphasis = StopEmphasis::new(settings);
+        stop(&mut emphasis, "Hit breakpoint at 0x1000", Instant::now());
+        assert!(!emphasis.next_frame_flashes());
+        assert_eq!(emphasis.toasts().count(), 0);
+        assert!(emphasis.take_bell());
+    }
+}
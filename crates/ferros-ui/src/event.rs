@@ -5,7 +5,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, KeyEventKind};
-use ferros_core::events::DebuggerEvent;
+use ferros_core::events::EventEnvelope;
 use tokio::sync::mpsc;
 
 use crate::app::ProcessOutputSource;
@@ -21,10 +21,13 @@ pub enum Event
     /// Captured process output ready to display
     ProcessOutput
     {
-        source: ProcessOutputSource, line: String
+        source: ProcessOutputSource,
+        line: String,
+        /// Transcript timestamp, when one is being recorded
+        at: Option<Duration>,
     },
-    /// Asynchronous debugger state change.
-    Debugger(DebuggerEvent),
+    /// Asynchronous debugger state change, with its emission time.
+    Debugger(EventEnvelope),
 }
 
 /// Event handler that reads from crossterm and produces TUI events
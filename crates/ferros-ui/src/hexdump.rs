@@ -0,0 +1,443 @@
+//! Memory view: a hex and ASCII dump of target memory
+//!
+//! The view dumps [`BYTES_PER_ROW`] bytes a row from an address picked with
+//! `:mem <addr>` or with Enter on a register in the Registers view. Bytes are
+//! read through [`TargetInspector::read_memory_streamed`], which retries a
+//! failed read page by page, so an unmapped page shows as `??` instead of
+//! failing the whole dump.
+//!
+//! One 8-byte-aligned word of the dump is selected at a time. When it holds
+//! something that looks like a pointer, the App can follow it, recording
+//! where the view was in an [`AddressHistory`] so it can go back.
+//!
+//! ```rust
+//! use ferros_ui::hexdump::format_row;
+//!
+//! let bytes = [Some(b'h'), Some(b'i'), Some(0), None];
+//! let (hex, ascii) = format_row(&bytes);
+//! assert!(hex.starts_with("68 69 00 ??"));
+//! assert_eq!(ascii, "hi. ");
+//! ```
+
+use std::fmt::Write;
+
+use ferros_core::TargetInspector;
+use ferros_core::error::Result;
+use ferros_core::stream::StreamOptions;
+use ferros_core::types::{Address, AddressFormat};
+
+/// Bytes shown on one row of the dump
+pub const BYTES_PER_ROW: usize = 16;
+
+/// Rows read before the view has been drawn and knows its height
+pub const DEFAULT_ROWS: usize = 16;
+
+/// Size and alignment of the words that can be selected and followed
+pub const WORD_SIZE: usize = 8;
+
+/// State of the Memory view
+#[derive(Debug, Clone, Default)]
+pub struct MemoryView
+{
+    /// First address shown
+    pub address: Option<Address>,
+    /// Bytes read from `address` on, `None` where the read failed
+    pub bytes: Vec<Option<u8>>,
+    /// Rows that fit in the view, updated when it is drawn
+    pub rows: usize,
+    /// Index of the selected word among the aligned words the dump has room for
+    pub word: usize,
+    /// Why the last read failed as a whole
+    pub error: Option<String>,
+}
+
+impl MemoryView
+{
+    /// Rows to read: as many as fit in the view
+    #[must_use]
+    pub fn row_count(&self) -> usize
+    {
+        if self.rows == 0 { DEFAULT_ROWS } else { self.rows }
+    }
+
+    /// Start the dump at `address`, forgetting the bytes read elsewhere
+    pub fn show(&mut self, address: Address)
+    {
+        self.address = Some(address);
+        self.bytes.clear();
+        self.error = None;
+        self.word = 0;
+    }
+
+    /// Move the dump by `rows` rows, staying inside the address space
+    pub fn scroll(&mut self, rows: i64)
+    {
+        let Some(address) = self.address else {
+            return;
+        };
+        let offset = rows.unsigned_abs().saturating_mul(BYTES_PER_ROW as u64);
+        let moved = if rows < 0 {
+            address.checked_sub(offset).unwrap_or(Address::from(0))
+        } else {
+            address.checked_add(offset).unwrap_or(address)
+        };
+        if moved != address {
+            // Keep the selection where it is on screen
+            let word = self.word;
+            self.show(moved);
+            self.word = word;
+        }
+    }
+
+    /// Address of the selected word
+    #[must_use]
+    pub fn selected_word(&self) -> Option<Address>
+    {
+        let index = self.word.min(self.word_count().checked_sub(1)?);
+        self.first_word()?.checked_add((index * WORD_SIZE) as u64)
+    }
+
+    /// Value of the selected word, if all of its bytes were read
+    #[must_use]
+    pub fn selected_value(&self) -> Option<u64>
+    {
+        let offset = usize::try_from(self.selected_word()?.value() - self.address?.value()).ok()?;
+        let bytes: Vec<u8> = self
+            .bytes
+            .get(offset..offset + WORD_SIZE)?
+            .iter()
+            .copied()
+            .collect::<Option<_>>()?;
+        // Both supported architectures are little-endian
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    /// Select the word before the selected one, scrolling up a row at the top
+    pub fn select_previous_word(&mut self)
+    {
+        let index = self.word.min(self.word_count().saturating_sub(1));
+        if index > 0 {
+            self.word = index - 1;
+        } else if self.scrolled(-1) {
+            self.word = WORDS_PER_ROW - 1;
+        }
+    }
+
+    /// Select the word after the selected one, scrolling down a row at the bottom
+    pub fn select_next_word(&mut self)
+    {
+        let index = self.word.min(self.word_count().saturating_sub(1));
+        self.word = index;
+        if index + 1 < self.word_count() {
+            self.word = index + 1;
+        } else if self.scrolled(1) {
+            self.word = index.saturating_sub(WORDS_PER_ROW - 1);
+        }
+    }
+
+    /// Scroll by `rows` and tell whether the dump moved
+    fn scrolled(&mut self, rows: i64) -> bool
+    {
+        let before = self.address;
+        self.scroll(rows);
+        self.address != before
+    }
+
+    /// First aligned word at or after the dump's address
+    fn first_word(&self) -> Option<Address>
+    {
+        let address = self.address?;
+        let skew = address.value() % WORD_SIZE as u64;
+        if skew == 0 {
+            Some(address)
+        } else {
+            address.checked_add(WORD_SIZE as u64 - skew)
+        }
+    }
+
+    /// Aligned words the rows that fit have room for
+    fn word_count(&self) -> usize
+    {
+        let (Some(address), Some(first)) = (self.address, self.first_word()) else {
+            return 0;
+        };
+        let lead = usize::try_from(first.value() - address.value()).unwrap_or(WORD_SIZE);
+        (self.row_count() * BYTES_PER_ROW).saturating_sub(lead) / WORD_SIZE
+    }
+
+    /// Read the rows that fit from `target`
+    pub fn refresh(&mut self, target: &dyn TargetInspector)
+    {
+        let Some(address) = self.address else {
+            return;
+        };
+        match read_dump(target, address, self.row_count() * BYTES_PER_ROW) {
+            Ok(bytes) => {
+                self.bytes = bytes;
+                self.error = None;
+            }
+            Err(e) => {
+                self.bytes.clear();
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Words on one row of the dump
+const WORDS_PER_ROW: usize = BYTES_PER_ROW / WORD_SIZE;
+
+/// Addresses the Memory view has been at while following pointers
+///
+/// Like a browser's history: the entry at the position is where the view is
+/// now, going back and forward moves the position, and following a pointer
+/// from the middle drops the entries after it.
+#[derive(Debug, Clone, Default)]
+pub struct AddressHistory
+{
+    entries: Vec<Address>,
+    position: usize,
+}
+
+impl AddressHistory
+{
+    /// Record following a pointer from `from`, where the view was if anywhere, to `to`
+    pub fn follow(&mut self, from: Option<Address>, to: Address)
+    {
+        if self.entries.is_empty() {
+            self.entries.extend(from);
+        } else {
+            self.entries.truncate(self.position + 1);
+            if let Some(from) = from {
+                self.entries[self.position] = from;
+            }
+        }
+        self.entries.push(to);
+        self.position = self.entries.len() - 1;
+    }
+
+    /// Where the view was before `current`, remembering `current` to come forward to
+    pub fn back(&mut self, current: Address) -> Option<Address>
+    {
+        if self.position == 0 || self.entries.is_empty() {
+            return None;
+        }
+        self.entries[self.position] = current;
+        self.position -= 1;
+        Some(self.entries[self.position])
+    }
+
+    /// Where the view went after `current`, before going back
+    pub fn forward(&mut self, current: Address) -> Option<Address>
+    {
+        if self.position + 1 >= self.entries.len() {
+            return None;
+        }
+        self.entries[self.position] = current;
+        self.position += 1;
+        Some(self.entries[self.position])
+    }
+
+    /// How many times [`Self::back`] can go back
+    #[must_use]
+    pub fn depth(&self) -> usize
+    {
+        self.position
+    }
+}
+
+/// Read `len` bytes at `start`, with `None` for the bytes on pages that couldn't be read
+///
+/// The dump stops at the end of the address space.
+///
+/// # Errors
+///
+/// Returns whatever the streamed read fails with as a whole; unreadable pages aren't errors.
+pub fn read_dump(target: &dyn TargetInspector, start: Address, len: usize) -> Result<Vec<Option<u8>>>
+{
+    let end = start.checked_add(len as u64).unwrap_or(Address::from(u64::MAX));
+    let mut sink = Vec::with_capacity(len);
+    let stats = target.read_memory_streamed(start..end, len.max(1), &mut sink, &mut StreamOptions::new())?;
+    let mut bytes: Vec<Option<u8>> = sink.into_iter().map(Some).collect();
+    for hole in &stats.holes {
+        let from = usize::try_from(hole.start.value() - start.value()).unwrap_or(usize::MAX);
+        let to = from.saturating_add(usize::try_from(hole.len).unwrap_or(usize::MAX));
+        for byte in bytes.iter_mut().take(to).skip(from) {
+            *byte = None;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Hex and ASCII columns for one row of up to [`BYTES_PER_ROW`] bytes
+///
+/// Unreadable bytes are `??` in the hex column and blank in the ASCII one;
+/// bytes that aren't printable ASCII show as `.`. A short row is padded so
+/// the ASCII column still lines up.
+#[must_use]
+pub fn format_row(bytes: &[Option<u8>]) -> (String, String)
+{
+    let mut hex = String::with_capacity(BYTES_PER_ROW * 3 + 1);
+    let mut ascii = String::with_capacity(BYTES_PER_ROW);
+    for index in 0..BYTES_PER_ROW {
+        if index == BYTES_PER_ROW / 2 {
+            hex.push(' ');
+        }
+        match bytes.get(index) {
+            Some(Some(byte)) => {
+                let _ = write!(hex, "{byte:02x} ");
+                ascii.push(if byte.is_ascii_graphic() || *byte == b' ' {
+                    char::from(*byte)
+                } else {
+                    '.'
+                });
+            }
+            Some(None) => {
+                hex.push_str("?? ");
+                ascii.push(' ');
+            }
+            None => hex.push_str("   "),
+        }
+    }
+    (hex.trim_end().to_string(), ascii)
+}
+
+/// `len` bytes at `start` as text rows of address, hex and ASCII, for `mem` in `ferros repl`
+///
+/// # Errors
+///
+/// Returns whatever [`read_dump`] fails with.
+pub fn dump_lines(target: &dyn TargetInspector, start: Address, len: usize, format: AddressFormat) -> Result<Vec<String>>
+{
+    let bytes = read_dump(target, start, len)?;
+    let rows = (0..bytes.len())
+        .step_by(BYTES_PER_ROW)
+        .map(|offset| start.value() + offset as u64);
+    let addresses = format.formatter(rows.clone());
+    Ok(rows
+        .zip(bytes.chunks(BYTES_PER_ROW))
+        .map(|(address, row)| {
+            let (hex, ascii) = format_row(row);
+            format!("{}  {hex}  |{ascii}|", addresses.format(address))
+        })
+        .collect())
+}
+
+/// Where the hex digits of the byte at `index` start in a row from [`format_row`]
+#[must_use]
+pub fn hex_offset(index: usize) -> usize
+{
+    index * 3 + usize::from(index >= BYTES_PER_ROW / 2)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ferros_core::mock::MockDebugger;
+
+    use super::*;
+
+    #[test]
+    fn dump_lines_print_one_row_per_sixteen_bytes()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().memory = (0x1000..0x1100)
+            .map(|address: u64| (address, b'a' + address.to_le_bytes()[0] % 26))
+            .collect();
+
+        let lines = dump_lines(debugger.as_ref(), Address::from(0x1010), 20, AddressFormat::Short12).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("0x000000001010  71 72 73"), "{}", lines[0]);
+        assert!(lines[0].ends_with("|qrstuvwxyzabcdef|"), "{}", lines[0]);
+        assert_eq!(lines[1], "0x000000001020  67 68 69 6a  |ghij|");
+    }
+
+    #[test]
+    fn unreadable_pages_dump_as_unknown_bytes()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        // 0x1000..0x2000 is mapped; the page after it isn't
+        state.borrow_mut().memory = (0x1000..0x2000).map(|address: u64| (address, address.to_le_bytes()[0])).collect();
+
+        let bytes = read_dump(debugger.as_ref(), Address::from(0x1ff8), 16).unwrap();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(bytes[..8], [0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff].map(Some));
+        assert!(bytes[8..].iter().all(Option::is_none));
+
+        let (hex, ascii) = format_row(&bytes);
+        assert_eq!(hex, "f8 f9 fa fb fc fd fe ff  ?? ?? ?? ?? ?? ?? ?? ??");
+        assert_eq!(ascii, format!("........{}", " ".repeat(8)));
+    }
+
+    #[test]
+    fn scrolling_stays_inside_the_address_space()
+    {
+        let mut view = MemoryView::default();
+        view.show(Address::from(0x20));
+        view.scroll(-1);
+        assert_eq!(view.address, Some(Address::from(0x10)));
+        view.scroll(-4);
+        assert_eq!(view.address, Some(Address::from(0)));
+        view.show(Address::from(u64::MAX - 4));
+        view.scroll(1);
+        assert_eq!(view.address, Some(Address::from(u64::MAX - 4)));
+
+        let (hex, ascii) = format_row(&[Some(b'o'), Some(b'k')]);
+        assert_eq!(hex, "6f 6b");
+        assert_eq!(ascii, "ok");
+    }
+
+    #[test]
+    fn selected_words_are_aligned_and_scroll_the_dump()
+    {
+        let mut view = MemoryView {
+            rows: 2,
+            ..MemoryView::default()
+        };
+        view.show(Address::from(0x1004));
+        // 0x1004..0x1024 has room for the words at 0x1008, 0x1010 and 0x1018
+        assert_eq!(view.selected_word(), Some(Address::from(0x1008)));
+        view.select_next_word();
+        view.select_next_word();
+        assert_eq!(view.selected_word(), Some(Address::from(0x1018)));
+        view.select_next_word();
+        assert_eq!(view.address, Some(Address::from(0x1014)));
+        assert_eq!(view.selected_word(), Some(Address::from(0x1020)));
+        view.select_previous_word();
+        view.select_previous_word();
+        view.select_previous_word();
+        assert_eq!(view.address, Some(Address::from(0x1004)));
+        assert_eq!(view.selected_word(), Some(Address::from(0x1008)));
+
+        view.bytes = (0..32).map(|byte| Some(byte + 1)).collect();
+        assert_eq!(view.selected_value(), Some(u64::from_le_bytes([5, 6, 7, 8, 9, 10, 11, 12])));
+        view.bytes[6] = None;
+        assert_eq!(view.selected_value(), None);
+        assert_eq!(hex_offset(9), "00 01 02 03 04 05 06 07  ".len() + 3);
+    }
+
+    #[test]
+    fn history_goes_back_and_forward_through_followed_pointers()
+    {
+        let [a, b, c, d] = [0x1000, 0x2000, 0x3000, 0x4000].map(Address::from);
+        let mut history = AddressHistory::default();
+        assert_eq!(history.back(a), None);
+
+        history.follow(Some(a), b);
+        history.follow(Some(b), c);
+        assert_eq!(history.depth(), 2);
+        // Scrolled from c to d before going back: forward returns to d
+        assert_eq!(history.back(d), Some(b));
+        assert_eq!(history.back(b), Some(a));
+        assert_eq!(history.back(a), None);
+        assert_eq!(history.forward(a), Some(b));
+        assert_eq!(history.forward(b), Some(d));
+        assert_eq!(history.forward(d), None);
+
+        // Following from the middle drops what was ahead
+        history.back(d);
+        history.follow(Some(b), a);
+        assert_eq!(history.forward(a), None);
+        assert_eq!(history.back(a), Some(b));
+    }
+}
@@ -0,0 +1,458 @@
+//! Rust syntax highlighting for the Source view
+//!
+//! A small hand-written tokenizer rather than a grammar engine: it tells
+//! comments, strings, chars, lifetimes, numbers, keywords, type names,
+//! function calls, macros and attributes apart, which is most of what makes
+//! code easy to scan. It works a line at a time, carrying open block
+//! comments and strings over to the next line.
+//!
+//! [`SourceFile`] highlights lazily. Drawing asks for the lines in view and
+//! only the lines up to them are tokenized, each once. Lines past
+//! [`MAX_HIGHLIGHTED_LINES`] are drawn plain, so a huge generated file costs
+//! no more than its first few thousand lines.
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Lines highlighted per file; the rest are drawn plain
+pub const MAX_HIGHLIGHTED_LINES: usize = 5000;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static",
+    "struct", "super", "trait", "true", "type", "union", "unsafe", "use", "where", "while", "yield",
+];
+
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128",
+    "usize",
+];
+
+/// What a highlighted piece of a line is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind
+{
+    Keyword,
+    /// Capitalized names and primitive types
+    Type,
+    /// A name followed by `(`
+    Function,
+    /// A name followed by `!`, with the `!`
+    Macro,
+    /// String literals, byte and raw strings included
+    String,
+    /// Char and byte literals
+    Char,
+    Lifetime,
+    Number,
+    Comment,
+    /// `#[...]` and `#![...]`
+    Attribute,
+}
+
+/// A highlighted byte range of one line; the text between tokens is plain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token
+{
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+/// What is still open at the end of a line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Carry
+{
+    #[default]
+    Code,
+    /// Nesting depth of `/* */` comments
+    BlockComment(usize),
+    /// A `"` string, which may continue over lines
+    Str,
+    /// A raw string closed by `"` and this many `#`
+    RawStr(usize),
+}
+
+/// A source file loaded for the Source view, highlighted as it is drawn
+#[derive(Debug, Clone, Default)]
+pub struct SourceFile
+{
+    /// The file's lines, without line endings
+    pub lines: Vec<String>,
+    /// 1-based lines with code in the line tables; `None` if the debugger can't tell
+    pub code_lines: Option<BTreeSet<u32>>,
+    /// Tokens of the first lines, in order
+    highlighted: Vec<Vec<Token>>,
+    /// State after the last highlighted line
+    carry: Carry,
+}
+
+impl SourceFile
+{
+    #[must_use]
+    pub fn new(lines: Vec<String>, code_lines: Option<BTreeSet<u32>>) -> Self
+    {
+        Self {
+            lines,
+            code_lines,
+            highlighted: Vec::new(),
+            carry: Carry::Code,
+        }
+    }
+
+    /// Tokens of the 0-based line `index`, highlighting the lines before it first
+    ///
+    /// `None` past the end of the file or [`MAX_HIGHLIGHTED_LINES`].
+    pub fn tokens(&mut self, index: usize) -> Option<&[Token]>
+    {
+        if index >= self.lines.len().min(MAX_HIGHLIGHTED_LINES) {
+            return None;
+        }
+        while self.highlighted.len() <= index {
+            let (tokens, carry) = tokenize(&self.lines[self.highlighted.len()], self.carry);
+            self.highlighted.push(tokens);
+            self.carry = carry;
+        }
+        Some(&self.highlighted[index])
+    }
+
+    /// Whether the 1-based `line` has code; `None` when that isn't known
+    #[must_use]
+    pub fn has_code(&self, line: usize) -> Option<bool>
+    {
+        let line = u32::try_from(line).ok()?;
+        self.code_lines.as_ref().map(|lines| lines.contains(&line))
+    }
+
+    /// Number of lines tokenized so far
+    #[must_use]
+    pub fn highlighted_lines(&self) -> usize
+    {
+        self.highlighted.len()
+    }
+}
+
+/// Tokens of `line`, starting in `carry`, and what is still open at its end
+fn tokenize(line: &str, carry: Carry) -> (Vec<Token>, Carry)
+{
+    let mut lexer = Lexer {
+        line,
+        bytes: line.as_bytes(),
+        at: 0,
+        tokens: Vec::new(),
+    };
+    let mut carry = match carry {
+        Carry::Code => Carry::Code,
+        Carry::BlockComment(depth) => lexer.block_comment(0, depth),
+        Carry::Str => lexer.string(0),
+        Carry::RawStr(hashes) => lexer.raw_string(0, hashes),
+    };
+    while carry == Carry::Code && lexer.at < lexer.bytes.len() {
+        carry = lexer.next_token();
+    }
+    (lexer.tokens, carry)
+}
+
+struct Lexer<'a>
+{
+    line: &'a str,
+    bytes: &'a [u8],
+    at: usize,
+    tokens: Vec<Token>,
+}
+
+impl Lexer<'_>
+{
+    fn peek(&self, offset: usize) -> Option<u8>
+    {
+        self.bytes.get(self.at + offset).copied()
+    }
+
+    fn push(&mut self, start: usize, kind: TokenKind)
+    {
+        self.tokens.push(Token {
+            range: start..self.at,
+            kind,
+        });
+    }
+
+    /// Scan one token at `at`; returns what it left open at the end of the line
+    fn next_token(&mut self) -> Carry
+    {
+        let start = self.at;
+        let Some(c) = self.line[start..].chars().next() else {
+            return Carry::Code;
+        };
+        match (c, self.peek(1)) {
+            ('/', Some(b'/')) => {
+                self.at = self.bytes.len();
+                self.push(start, TokenKind::Comment);
+            }
+            ('/', Some(b'*')) => {
+                self.at += 2;
+                return self.block_comment(start, 1);
+            }
+            ('"', _) => {
+                self.at += 1;
+                return self.string(start);
+            }
+            ('\'', _) => self.quote(start),
+            ('#', Some(b'[' | b'!')) => self.attribute(start),
+            (c, _) if c.is_ascii_digit() => self.number(start),
+            (c, _) if c == '_' || c.is_alphabetic() => return self.word(start),
+            (c, _) => self.at += c.len_utf8(),
+        }
+        Carry::Code
+    }
+
+    /// A `/* */` comment from `start`, `depth` deep at `at`
+    fn block_comment(&mut self, start: usize, mut depth: usize) -> Carry
+    {
+        while self.at < self.bytes.len() {
+            match (self.bytes[self.at], self.peek(1)) {
+                (b'/', Some(b'*')) => {
+                    depth += 1;
+                    self.at += 2;
+                }
+                (b'*', Some(b'/')) => {
+                    depth -= 1;
+                    self.at += 2;
+                    if depth == 0 {
+                        self.push(start, TokenKind::Comment);
+                        return Carry::Code;
+                    }
+                }
+                _ => self.at += 1,
+            }
+        }
+        self.push(start, TokenKind::Comment);
+        Carry::BlockComment(depth)
+    }
+
+    /// A string from `start`, with `at` past its opening quote or at the line start
+    fn string(&mut self, start: usize) -> Carry
+    {
+        while self.at < self.bytes.len() {
+            match self.bytes[self.at] {
+                b'\\' => self.at += 2,
+                b'"' => {
+                    self.at += 1;
+                    self.push(start, TokenKind::String);
+                    return Carry::Code;
+                }
+                _ => self.at += 1,
+            }
+        }
+        self.at = self.bytes.len();
+        self.push(start, TokenKind::String);
+        Carry::Str
+    }
+
+    /// A raw string from `start`, closed by `"` and `hashes` `#`s
+    fn raw_string(&mut self, start: usize, hashes: usize) -> Carry
+    {
+        while self.at < self.bytes.len() {
+            if self.bytes[self.at] == b'"'
+                && self.bytes[self.at + 1..].iter().take(hashes).filter(|&&b| b == b'#').count() == hashes
+            {
+                self.at += 1 + hashes;
+                self.push(start, TokenKind::String);
+                return Carry::Code;
+            }
+            self.at += 1;
+        }
+        self.push(start, TokenKind::String);
+        Carry::RawStr(hashes)
+    }
+
+    /// A char literal or a lifetime
+    fn quote(&mut self, start: usize)
+    {
+        self.at += 1;
+        let rest = &self.line[self.at..];
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some('\\'), _) => {
+                // Escapes run to the closing quote: '\n', '\u{1F600}'
+                let end = rest[1..].find('\'').map_or(rest.len(), |at| at + 2);
+                self.at += end;
+                self.push(start, TokenKind::Char);
+            }
+            (Some(c), Some('\'')) => {
+                self.at += c.len_utf8() + 1;
+                self.push(start, TokenKind::Char);
+            }
+            (Some(c), _) if c == '_' || c.is_alphabetic() => {
+                self.at += ident_len(rest);
+                self.push(start, TokenKind::Lifetime);
+            }
+            _ => {}
+        }
+    }
+
+    /// `#[...]` or `#![...]`, to its closing bracket or the end of the line
+    fn attribute(&mut self, start: usize)
+    {
+        let mut depth = 0usize;
+        while self.at < self.bytes.len() {
+            match self.bytes[self.at] {
+                b'[' => depth += 1,
+                b']' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        self.at += 1;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            self.at += 1;
+        }
+        self.push(start, TokenKind::Attribute);
+    }
+
+    /// Digits with their suffix, and a fraction unless the `.` starts a range or method call
+    fn number(&mut self, start: usize)
+    {
+        self.at += ident_len(&self.line[self.at..]);
+        if self.peek(0) == Some(b'.') && self.peek(1).is_some_and(|b| b.is_ascii_digit()) {
+            self.at += 1;
+            self.at += ident_len(&self.line[self.at..]);
+        }
+        self.push(start, TokenKind::Number);
+    }
+
+    /// A name, keyword, macro, or the prefix of a byte, raw or C string
+    fn word(&mut self, start: usize) -> Carry
+    {
+        let len = ident_len(&self.line[start..]);
+        let word = &self.line[start..start + len];
+        self.at += len;
+        let raw = matches!(word, "r" | "br" | "cr");
+        if raw && matches!(self.peek(0), Some(b'"' | b'#')) {
+            let hashes = self.bytes[self.at..].iter().take_while(|&&b| b == b'#').count();
+            if self.peek(hashes) == Some(b'"') {
+                self.at += hashes + 1;
+                return self.raw_string(start, hashes);
+            }
+        }
+        match (word, self.peek(0)) {
+            ("b" | "c", Some(b'"')) => {
+                self.at += 1;
+                return self.string(start);
+            }
+            ("b", Some(b'\'')) => {
+                let before = self.tokens.len();
+                self.quote(start);
+                if let Some(token) = self.tokens.get_mut(before) {
+                    token.kind = TokenKind::Char;
+                }
+            }
+            (_, Some(b'!')) if self.peek(1) != Some(b'=') => {
+                self.at += 1;
+                self.push(start, TokenKind::Macro);
+            }
+            _ if KEYWORDS.contains(&word) => self.push(start, TokenKind::Keyword),
+            _ if PRIMITIVES.contains(&word) || word.starts_with(char::is_uppercase) => self.push(start, TokenKind::Type),
+            (_, Some(b'(')) => self.push(start, TokenKind::Function),
+            _ => {}
+        }
+        Carry::Code
+    }
+}
+
+/// Length in bytes of the identifier characters at the start of `text`
+fn ident_len(text: &str) -> usize
+{
+    text.char_indices()
+        .find(|&(_, c)| !(c == '_' || c.is_alphanumeric()))
+        .map_or(text.len(), |(at, _)| at)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// `"Kind text"` for each token in the lines of `source`
+    fn highlight(source: &str) -> Vec<Vec<String>>
+    {
+        let mut file = SourceFile::new(source.lines().map(str::to_string).collect(), None);
+        (0..file.lines.len())
+            .map(|index| {
+                let line = file.lines[index].clone();
+                file.tokens(index)
+                    .unwrap()
+                    .iter()
+                    .map(|token| format!("{:?} {}", token.kind, &line[token.range.clone()]))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rust_tokens_get_their_kinds()
+    {
+        let lines = highlight(
+            "#[inline]\npub fn total<'a>(items: &'a [u32]) -> Option<u64> { // sum\n    let c = '\\n'; println!(\"{} é\", \
+             1.5e3 + 0x1F_u64); items.iter().sum::<u32>(); for _ in 0..2 {} }",
+        );
+        assert_eq!(lines[0], vec!["Attribute #[inline]"]);
+        assert_eq!(
+            lines[1],
+            vec![
+                "Keyword pub",
+                "Keyword fn",
+                "Lifetime 'a",
+                "Lifetime 'a",
+                "Type u32",
+                "Type Option",
+                "Type u64",
+                "Comment // sum",
+            ]
+        );
+        assert_eq!(
+            lines[2],
+            vec![
+                "Keyword let",
+                "Char '\\n'",
+                "Macro println!",
+                "String \"{} é\"",
+                "Number 1.5e3",
+                "Number 0x1F_u64",
+                "Function iter",
+                "Type u32",
+                "Keyword for",
+                "Keyword in",
+                "Number 0",
+                "Number 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_and_strings_carry_over_lines()
+    {
+        let lines = highlight(
+            "/* outer /* inner */\nstill comment */ let x = \"two\nlines\"; let raw = r#\"a \"quoted\"\nb\"#; b'x'",
+        );
+        assert_eq!(lines[0], vec!["Comment /* outer /* inner */"]);
+        assert_eq!(lines[1], vec!["Comment still comment */", "Keyword let", "String \"two",]);
+        assert_eq!(lines[2], vec!["String lines\"", "Keyword let", "String r#\"a \"quoted\"",]);
+        assert_eq!(lines[3], vec!["String b\"#", "Char b'x'"]);
+    }
+
+    #[test]
+    fn highlighting_stops_at_the_line_cap_and_only_runs_up_to_the_line_asked_for()
+    {
+        let mut file = SourceFile::new(vec!["let x = 1;".to_string(); MAX_HIGHLIGHTED_LINES + 10], None);
+        assert!(file.tokens(20).is_some());
+        assert_eq!(file.highlighted_lines(), 21);
+        assert!(file.tokens(MAX_HIGHLIGHTED_LINES - 1).is_some());
+        assert!(file.tokens(MAX_HIGHLIGHTED_LINES).is_none());
+        assert_eq!(file.highlighted_lines(), MAX_HIGHLIGHTED_LINES);
+
+        let file = SourceFile::new(vec![String::new(); 3], Some(BTreeSet::from([2])));
+        assert_eq!((file.has_code(1), file.has_code(2)), (Some(false), Some(true)));
+        assert_eq!(SourceFile::new(Vec::new(), None).has_code(1), None);
+    }
+}
@@ -0,0 +1,267 @@
+//! JSON documents for `--output json`
+//!
+//! The machine-readable counterparts of [`crate::text`]: the same registers,
+//! threads, memory regions, images and frames, as [`serde_json::Value`]s a
+//! script can index instead of columns it has to parse. Addresses and register
+//! values are `0x`-prefixed hex strings, as in the MI protocol, and anything
+//! unknown is `null` rather than left out.
+
+use std::fmt::Write as _;
+
+use ferros_core::error::{DebuggerError, Result};
+use ferros_core::processes::ProcessInfo;
+use ferros_core::stream::StreamOptions;
+use ferros_core::symbols::ImageInfo;
+use ferros_core::types::{Address, StopReason};
+use ferros_core::{BreakpointInfo, TargetInspector};
+use serde_json::{Value, json};
+
+/// What `print_debugger_info` logs: architecture, whether the target is
+/// attached and stopped, why, and how many threads and regions it has
+#[must_use]
+pub fn debugger_info(target: &dyn TargetInspector) -> Value
+{
+    let attached = target.is_attached();
+    let threads = attached.then(|| target.threads().ok()).flatten();
+    let regions = attached.then(|| target.get_memory_regions().ok()).flatten();
+    json!({
+        "architecture": target.architecture().to_string(),
+        "attached": attached,
+        "stopped": target.is_stopped(),
+        "stop_reason": target.stop_reason(),
+        "threads": threads.map(|threads| threads.len()),
+        "active_thread": target.active_thread(),
+        "regions": regions.map(|regions| regions.len()),
+    })
+}
+
+/// The active thread's registers, name to hex value
+///
+/// # Errors
+///
+/// Returns an error if the registers can't be read.
+pub fn registers(target: &dyn TargetInspector) -> Result<Value>
+{
+    Ok(json!(target.read_registers()?))
+}
+
+/// Threads in target order, with the active one flagged
+///
+/// `suspend_count` is the debugger's own suspensions, as in the threads table.
+///
+/// # Errors
+///
+/// Returns an error if the thread list can't be read.
+pub fn threads(target: &dyn TargetInspector) -> Result<Value>
+{
+    let active = target.active_thread();
+    let threads = target.threads()?;
+    Ok(threads
+        .iter()
+        .enumerate()
+        .map(|(index, &thread)| {
+            let info = target.thread_info(thread).ok();
+            json!({
+                "index": index,
+                "id": thread,
+                "name": info.as_ref().and_then(|info| info.name.clone()),
+                "state": info.as_ref().map(|info| info.state.to_string()),
+                "cpu_percent": info.as_ref().map(|info| info.cpu_percent),
+                "active": active == Some(thread),
+                "suspend_count": target.thread_suspend_count(thread),
+            })
+        })
+        .collect())
+}
+
+/// Memory regions in address order
+///
+/// # Errors
+///
+/// Returns an error if the regions can't be read.
+pub fn regions(target: &dyn TargetInspector) -> Result<Value>
+{
+    Ok(json!(target.get_memory_regions()?))
+}
+
+/// Images the symbol cache has loaded
+#[must_use]
+pub fn modules(images: &[ImageInfo]) -> Value
+{
+    images
+        .iter()
+        .map(|image| {
+            json!({
+                "path": image.path.display().to_string(),
+                "load_address": image.load_address,
+                "end_address": image.end_address,
+                "debug_level": image.debug_level.to_string(),
+                "has_eh_frame": image.has_eh_frame,
+                "symbol_count": image.symbol_count,
+                "dsym": image.dsym.as_ref().map(|path| path.display().to_string()),
+                "dsym_mismatch": image.dsym_mismatch.as_ref().map(ToString::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Processes for `ferros ps`, with `null` paths where they couldn't be read
+#[must_use]
+pub fn processes(processes: &[ProcessInfo]) -> Value
+{
+    processes
+        .iter()
+        .map(|process| {
+            json!({
+                "pid": process.pid.0,
+                "name": process.name,
+                "path": process.path.as_ref().map(|path| path.display().to_string()),
+            })
+        })
+        .collect()
+}
+
+/// User breakpoints with their ids, addresses and states; hidden ones are left out
+#[must_use]
+pub fn breakpoints(breakpoints: &[BreakpointInfo]) -> Value
+{
+    breakpoints
+        .iter()
+        .filter(|info| !info.options.hidden)
+        .map(|info| json!(info))
+        .collect()
+}
+
+/// The active thread's top `max_frames` frames
+///
+/// # Errors
+///
+/// Returns an error if the target is running, has no active thread, or
+/// can't be unwound.
+pub fn backtrace(target: &dyn TargetInspector, max_frames: usize) -> Result<Value>
+{
+    if !target.is_stopped() {
+        return Err(DebuggerError::InvalidArgument(
+            "Process is running; stop it to see its stack".to_string(),
+        ));
+    }
+    let thread = target
+        .active_thread()
+        .ok_or_else(|| DebuggerError::InvalidArgument("No active thread".to_string()))?;
+    Ok(json!(target.backtrace(thread, max_frames)?))
+}
+
+/// Where the active thread stopped: the thread, why, the breakpoint it hit,
+/// PC and SP and the top [`text::WHERE_FRAMES`](crate::text::WHERE_FRAMES) frames
+///
+/// Frames that can't be unwound are `null`, as the text summary says they're
+/// unavailable rather than failing.
+///
+/// # Errors
+///
+/// Returns an error if the target is running, has no active thread, or its
+/// registers can't be read.
+pub fn where_summary(target: &dyn TargetInspector) -> Result<Value>
+{
+    if !target.is_stopped() {
+        return Err(DebuggerError::InvalidArgument(
+            "Process is running; stop it to see where it is".to_string(),
+        ));
+    }
+    let thread = target
+        .active_thread()
+        .ok_or_else(|| DebuggerError::InvalidArgument("No active thread".to_string()))?;
+    let registers = target.read_registers()?;
+    let reason = target.stop_reason();
+    let breakpoint = match reason {
+        StopReason::Breakpoint(address) => target
+            .breakpoints()
+            .iter()
+            .find(|info| info.address.value() == address)
+            .map(|info| info.id),
+        _ => None,
+    };
+    let frames = target.backtrace(thread, crate::text::WHERE_FRAMES).ok();
+    Ok(json!({
+        "thread": thread,
+        "stop": reason,
+        "breakpoint": breakpoint,
+        "pc": registers.pc,
+        "sp": registers.sp,
+        "frames": frames,
+    }))
+}
+
+/// `len` bytes at `start` as one hex string, with the unreadable ranges listed
+///
+/// Unreadable bytes are zeros in `data`, as in the hex dump. Code under
+/// software breakpoints is shown as it was before the trap was patched in.
+///
+/// # Errors
+///
+/// Returns whatever the streamed read fails with as a whole; unreadable pages aren't errors.
+pub fn memory(target: &dyn TargetInspector, start: Address, len: usize) -> Result<Value>
+{
+    let end = start.checked_add(len as u64).unwrap_or(Address::from(u64::MAX));
+    let mut bytes = Vec::with_capacity(len);
+    let mut options = StreamOptions::new().with_logical_view(true);
+    let stats = target.read_memory_streamed(start..end, len.max(1), &mut bytes, &mut options)?;
+    let holes: Vec<Value> = stats
+        .holes
+        .iter()
+        .map(|hole| json!({"address": hole.start, "length": hole.len}))
+        .collect();
+    Ok(json!({
+        "address": start,
+        "length": bytes.len(),
+        "data": hex(&bytes),
+        "unreadable": holes,
+    }))
+}
+
+/// Lowercase hex digits, two per byte
+#[must_use]
+pub fn hex(bytes: &[u8]) -> String
+{
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ferros_core::inspector::LiveInspector;
+    use ferros_core::mock::{self, MockDebugger};
+    use ferros_core::types::ThreadId;
+
+    use super::*;
+
+    #[test]
+    fn threads_memory_and_frames_are_plain_values()
+    {
+        let (mut debugger, state) = MockDebugger::with_threads(2);
+        {
+            let mut state = state.borrow_mut();
+            state.memory = (0x2000..0x2004).map(|address: u64| (address, 0xab)).collect();
+            state.stack.frames = vec![mock::frame(ThreadId::from(1), 0, "demo::work", 7)];
+        }
+        let target = &LiveInspector::new(debugger.as_mut());
+
+        let threads = threads(target).unwrap();
+        assert_eq!(threads.as_array().unwrap().len(), 2);
+        assert_eq!(threads[0]["index"], 0);
+        assert_eq!(threads[0]["active"], true);
+
+        let memory = memory(target, Address::from(0x2000), 4).unwrap();
+        assert_eq!(memory["address"], "0x2000");
+        assert_eq!(memory["data"], "abababab");
+        assert!(memory["unreadable"].as_array().unwrap().is_empty());
+
+        let frames = backtrace(target, 4).unwrap();
+        assert_eq!(frames[0]["function"], "demo::work");
+        assert_eq!(frames[0]["line"], 7);
+        assert_eq!(frames[0]["pc"], "0x100001000");
+    }
+}
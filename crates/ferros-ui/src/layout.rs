@@ -0,0 +1,226 @@
+//! Size-aware layout helpers
+//!
+//! The views are laid out for a roomy terminal but have to stay usable in a
+//! small tmux split. Below [`MIN_WIDTH`]x[`MIN_HEIGHT`] nothing but a "terminal
+//! too small" notice is drawn; above it views adapt:
+//!
+//! - tables drop optional columns in priority order ([`visible_columns`])
+//!   instead of truncating headers mid-word
+//! - side panes (Frame Details, the Source view's breakpoint list) collapse
+//!   below [`SPLIT_MIN_WIDTH`]
+//! - the header loses its border below [`COMPACT_HEIGHT`]
+//! - popups are clamped to the screen ([`centered_rect`]) and scroll their
+//!   content when it doesn't fit ([`popup_scroll`])
+
+use ratatui::layout::{Constraint, Rect};
+
+/// Narrowest terminal the views are drawn in
+pub const MIN_WIDTH: u16 = 40;
+/// Shortest terminal the views are drawn in
+pub const MIN_HEIGHT: u16 = 12;
+/// Narrower than this, two-pane views show only their main pane
+pub const SPLIT_MIN_WIDTH: u16 = 100;
+/// Shorter than this, the header and footer shrink to leave room for content
+pub const COMPACT_HEIGHT: u16 = 24;
+
+/// Horizontal space a bordered table spends on borders and the `>> ` highlight symbol
+const TABLE_CHROME: u16 = 5;
+
+/// Whether `area` is below the hard floor
+#[must_use]
+pub fn is_too_small(area: Rect) -> bool
+{
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
+}
+
+/// A `width`x`height` rect centered in `area`, shrunk to fit inside it
+#[must_use]
+pub fn centered_rect(area: Rect, width: u16, height: u16) -> Rect
+{
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x.saturating_add((area.width - width) / 2),
+        y: area.y.saturating_add((area.height - height) / 2),
+        width,
+        height,
+    }
+}
+
+/// Lines to scroll a popup so that line `focus` of its content stays visible
+///
+/// `inner_height` is the popup height without borders.
+#[must_use]
+pub fn popup_scroll(inner_height: u16, focus: usize) -> u16
+{
+    let scroll = (focus + 1).saturating_sub(usize::from(inner_height));
+    u16::try_from(scroll).unwrap_or(u16::MAX)
+}
+
+/// A table column that may be dropped when space runs out
+#[derive(Debug, Clone, Copy)]
+pub struct Column
+{
+    /// Header text; the width should leave room for it
+    pub header: &'static str,
+    /// Width, or minimum width for a `fill` column
+    pub width: u16,
+    /// Lower is more important; priority 0 columns are never dropped
+    pub priority: u8,
+    /// Whether the column takes up remaining space
+    pub fill: bool,
+}
+
+impl Column
+{
+    /// Fixed-width column
+    #[must_use]
+    pub const fn new(header: &'static str, width: u16, priority: u8) -> Self
+    {
+        Self {
+            header,
+            width,
+            priority,
+            fill: false,
+        }
+    }
+
+    /// Column that grows into the remaining space
+    #[must_use]
+    pub const fn fill(header: &'static str, min_width: u16, priority: u8) -> Self
+    {
+        Self {
+            header,
+            width: min_width,
+            priority,
+            fill: true,
+        }
+    }
+
+    fn constraint(self) -> Constraint
+    {
+        if self.fill {
+            Constraint::Min(self.width)
+        } else {
+            Constraint::Length(self.width)
+        }
+    }
+}
+
+/// Indices of the columns that fit in a bordered table of `table_width`
+///
+/// Columns are admitted in priority order (ties by position) while they fit;
+/// priority 0 columns are always kept. The result is in display order.
+#[must_use]
+pub fn visible_columns(table_width: u16, columns: &[Column]) -> Vec<usize>
+{
+    let available = table_width.saturating_sub(TABLE_CHROME);
+    let mut by_priority: Vec<usize> = (0..columns.len()).collect();
+    by_priority.sort_by_key(|&index| columns[index].priority);
+
+    let mut keep = Vec::new();
+    let mut used: u16 = 0;
+    for index in by_priority {
+        let column = columns[index];
+        // One cell of spacing between adjacent columns
+        let needed = if keep.is_empty() { column.width } else { column.width + 1 };
+        if column.priority == 0 || used.saturating_add(needed) <= available {
+            used = used.saturating_add(needed);
+            keep.push(index);
+        }
+    }
+    keep.sort_unstable();
+    keep
+}
+
+/// Constraints for the kept columns
+#[must_use]
+pub fn column_constraints(columns: &[Column], keep: &[usize]) -> Box<[Constraint]>
+{
+    keep.iter().map(|&index| columns[index].constraint()).collect()
+}
+
+/// `columns` with the ones at `indices` sized to content `width` characters wide
+///
+/// For columns whose width is only known when drawing, such as addresses in
+/// a trimmed format. A column is never made narrower than its header.
+#[must_use]
+pub fn fit_columns<const N: usize>(columns: &[Column; N], indices: &[usize], width: usize) -> [Column; N]
+{
+    let mut fitted = *columns;
+    for &index in indices {
+        let column = &mut fitted[index];
+        let width = width.max(column.header.chars().count());
+        column.width = u16::try_from(width).unwrap_or(u16::MAX);
+    }
+    fitted
+}
+
+/// Keep only the items at the `keep` indices, in order
+#[must_use]
+pub fn select<T>(items: Vec<T>, keep: &[usize]) -> Vec<T>
+{
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| keep.contains(index))
+        .map(|(_, item)| item)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn centered_rect_never_leaves_the_area()
+    {
+        let area = Rect::new(2, 3, 30, 5);
+        assert_eq!(centered_rect(area, 80, 8), area);
+        assert_eq!(centered_rect(area, 10, 3), Rect::new(12, 4, 10, 3));
+        assert_eq!(centered_rect(Rect::new(0, 0, 0, 0), 80, 3), Rect::new(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn columns_are_dropped_in_priority_order()
+    {
+        let columns = vec![
+            Column::new("ID", 5, 3),
+            Column::new("Start", 18, 0),
+            Column::new("End", 18, 2),
+            Column::new("Perms", 6, 0),
+            Column::fill("Name", 8, 1),
+        ];
+        assert_eq!(visible_columns(80, &columns), vec![0, 1, 2, 3, 4]);
+        // 55 usable: Start + Perms + Name + End = 53
+        assert_eq!(visible_columns(60, &columns), vec![1, 2, 3, 4]);
+        assert_eq!(visible_columns(40, &columns), vec![1, 3, 4]);
+        // Essential columns stay even when they overflow
+        assert_eq!(visible_columns(10, &columns), vec![1, 3]);
+    }
+
+    #[test]
+    fn fitted_columns_hold_content_and_header()
+    {
+        static COLUMNS: [Column; 3] = [
+            Column::new("ID", 5, 3),
+            Column::new("Start", 18, 0),
+            Column::new("Address", 18, 0),
+        ];
+        let fitted = fit_columns(&COLUMNS, &[1, 2], 5);
+        let widths: Vec<u16> = fitted.iter().map(|column| column.width).collect();
+        assert_eq!(widths, [5, 5, 7]);
+        // Narrower address columns leave room for more of the others
+        assert_eq!(visible_columns(25, &COLUMNS), vec![1, 2]);
+        assert_eq!(visible_columns(25, &fitted), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn popup_scroll_keeps_focus_visible()
+    {
+        assert_eq!(popup_scroll(6, 3), 0);
+        assert_eq!(popup_scroll(2, 3), 2);
+        assert_eq!(popup_scroll(4, 5), 2);
+    }
+}
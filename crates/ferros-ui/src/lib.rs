@@ -25,7 +25,20 @@
 //! ```
 
 pub mod app;
+pub mod commands;
+pub mod config;
+pub mod disassembly;
+pub mod emphasis;
 pub mod event;
+pub mod hexdump;
+pub mod highlight;
+pub mod json;
+pub mod layout;
+pub mod picker;
+pub mod registers;
+pub mod repl;
+pub mod text;
+pub mod timeline;
 pub mod tui;
 pub mod ui;
 pub mod widgets;
@@ -0,0 +1,170 @@
+//! Process picker shown when the TUI starts without a target
+//!
+//! `ferros` without a subcommand (or `ferros browse`) opens the TUI on a list
+//! of running processes. Typing narrows it to the processes whose name
+//! contains the filter, ↑/↓ move the highlight and Enter attaches to the
+//! highlighted process, after which the normal views take over.
+//!
+//! An attach that fails stays in the picker with the error and what to do
+//! about it, so a permission problem doesn't end the session.
+
+use ferros_core::error::DebuggerError;
+use ferros_core::processes::{ProcessInfo, list_processes};
+
+/// Remedy offered for a refused attach the backend gave no advice for
+const SUDO_REMEDY: &str = "re-run ferros under sudo to attach to processes you don't own";
+
+/// The process list, its filter and the last attach failure
+#[derive(Debug, Clone, Default)]
+pub struct ProcessPicker
+{
+    /// Every process when the list was read, lowest PID first
+    pub processes: Vec<ProcessInfo>,
+    /// Name filter, as typed
+    pub filter: String,
+    /// Highlighted row of the filtered list
+    pub selected: usize,
+    /// Why the last attach failed, or why the list couldn't be read
+    pub error: Option<String>,
+    /// What to try about `error`, most likely fix first
+    pub remedies: Vec<String>,
+}
+
+impl ProcessPicker
+{
+    /// Picker over the processes running now, without ferros itself
+    #[must_use]
+    pub fn load() -> Self
+    {
+        let own = std::process::id();
+        match list_processes() {
+            Ok(processes) => Self::new(processes.into_iter().filter(|process| process.pid.0 != own).collect()),
+            Err(e) => Self {
+                error: Some(format!("Failed to list processes: {e}")),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Picker over `processes`
+    #[must_use]
+    pub fn new(processes: Vec<ProcessInfo>) -> Self
+    {
+        Self {
+            processes,
+            ..Self::default()
+        }
+    }
+
+    /// Read the process list again, keeping the filter
+    pub fn reload(&mut self)
+    {
+        let filter = std::mem::take(&mut self.filter);
+        *self = Self { filter, ..Self::load() };
+    }
+
+    /// Processes whose name contains the filter, ignoring case
+    #[must_use]
+    pub fn visible(&self) -> Vec<&ProcessInfo>
+    {
+        self.processes
+            .iter()
+            .filter(|process| process.matches(&self.filter))
+            .collect()
+    }
+
+    /// The highlighted process, if any pass the filter
+    #[must_use]
+    pub fn selected_process(&self) -> Option<&ProcessInfo>
+    {
+        self.visible().get(self.selected).copied()
+    }
+
+    /// Add `c` to the filter and highlight the first match
+    pub fn push(&mut self, c: char)
+    {
+        self.filter.push(c);
+        self.selected = 0;
+    }
+
+    /// Remove the last character of the filter and highlight the first match
+    pub fn pop(&mut self)
+    {
+        self.filter.pop();
+        self.selected = 0;
+    }
+
+    /// Move the highlight by `rows`, stopping at either end
+    pub fn move_by(&mut self, rows: isize)
+    {
+        let last = self.visible().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(rows).min(last);
+    }
+
+    /// Show why attaching failed and, for a permission error, what to do
+    pub fn attach_failed(&mut self, process: &str, err: &DebuggerError)
+    {
+        self.error = Some(format!("Failed to attach to {process}: {err}"));
+        self.remedies = match (err, err.permission_advice()) {
+            (_, Some(advice)) => advice.remedies.clone(),
+            (DebuggerError::PermissionDenied { .. }, None) => vec![SUDO_REMEDY.to_string()],
+            _ => Vec::new(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ferros_core::types::ProcessId;
+
+    use super::*;
+
+    fn process(pid: u32, name: &str) -> ProcessInfo
+    {
+        ProcessInfo {
+            pid: ProcessId(pid),
+            name: name.to_string(),
+            path: None,
+        }
+    }
+
+    #[test]
+    fn typing_filters_and_resets_the_highlight()
+    {
+        let mut picker = ProcessPicker::new(vec![
+            process(1, "launchd"),
+            process(40, "my_app"),
+            process(41, "My_App_Helper"),
+        ]);
+        picker.move_by(5);
+        assert_eq!(picker.selected, 2);
+        for c in "app".chars() {
+            picker.push(c);
+        }
+        assert_eq!(picker.selected, 0);
+        assert_eq!(picker.visible().len(), 2);
+        picker.move_by(1);
+        assert_eq!(picker.selected_process().map(|p| p.pid.0), Some(41));
+
+        picker.push('z');
+        assert!(picker.selected_process().is_none());
+        picker.pop();
+        assert_eq!(picker.selected_process().map(|p| p.pid.0), Some(40));
+    }
+
+    #[test]
+    fn refused_attaches_suggest_sudo()
+    {
+        let mut picker = ProcessPicker::default();
+        picker.attach_failed("my_app (40)", &DebuggerError::permission_denied("task_for_pid failed", None));
+        assert_eq!(
+            picker.error.as_deref(),
+            Some("Failed to attach to my_app (40): Permission denied: task_for_pid failed")
+        );
+        assert_eq!(picker.remedies, [SUDO_REMEDY]);
+
+        picker.attach_failed("my_app (40)", &DebuggerError::NotAttached);
+        assert!(picker.remedies.is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+//! Register snapshot: every thread's registers, read once per stop
+//!
+//! A stopped target's registers only change when the debugger writes them,
+//! so the TUI reads them for every thread when a stop arrives and draws from
+//! the [`RegisterSnapshot`] instead of making a `thread_get_state()` call per
+//! render. Values also can't change halfway through drawing a frame.
+//!
+//! The snapshot is cleared when the target resumes, and a thread's entry is
+//! re-read after the user writes one of its registers. A thread the snapshot
+//! doesn't cover (it started since, or its read failed) is read live.
+
+use std::collections::HashMap;
+
+use ferros_core::Debugger;
+use ferros_core::types::{Registers, ThreadId};
+
+/// Registers of every thread at the current stop
+#[derive(Debug, Clone, Default)]
+pub struct RegisterSnapshot
+{
+    registers: HashMap<ThreadId, Registers>,
+}
+
+impl RegisterSnapshot
+{
+    /// Read the registers of every thread of `debugger` once
+    ///
+    /// Best-effort: a thread whose registers can't be read is left out, and
+    /// the snapshot is empty if the thread list can't be read.
+    #[must_use]
+    pub fn capture(debugger: &dyn Debugger) -> Self
+    {
+        let registers = debugger
+            .threads()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|thread| Some((thread, debugger.read_registers_for(thread).ok()?)))
+            .collect();
+        Self { registers }
+    }
+
+    /// Registers of `thread` at the stop, if they were read
+    #[must_use]
+    pub fn get(&self, thread: ThreadId) -> Option<&Registers>
+    {
+        self.registers.get(&thread)
+    }
+
+    /// Re-read `thread`'s registers after they were written, dropping its entry if that fails
+    pub fn update(&mut self, debugger: &dyn Debugger, thread: ThreadId)
+    {
+        match debugger.read_registers_for(thread) {
+            Ok(registers) => {
+                self.registers.insert(thread, registers);
+            }
+            Err(_) => {
+                self.registers.remove(&thread);
+            }
+        }
+    }
+
+    /// Forget every thread's registers, for when the target runs again
+    pub fn clear(&mut self)
+    {
+        self.registers.clear();
+    }
+
+    /// Number of threads captured
+    #[must_use]
+    pub fn len(&self) -> usize
+    {
+        self.registers.len()
+    }
+
+    /// Whether no thread was captured
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.registers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ferros_core::mock::MockDebugger;
+
+    use super::*;
+
+    #[test]
+    fn capture_reads_each_thread_once_and_update_rereads_one()
+    {
+        let (debugger, state) = MockDebugger::with_threads(3);
+        state.borrow_mut().pc = 0x1000;
+
+        let mut snapshot = RegisterSnapshot::capture(debugger.as_ref());
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(state.borrow().register_reads, 3);
+        assert_eq!(snapshot.get(ThreadId::from(2)).map(|r| r.pc.value()), Some(0x1000));
+        assert!(snapshot.get(ThreadId::from(4)).is_none());
+
+        state.borrow_mut().pc = 0x2000;
+        snapshot.update(debugger.as_ref(), ThreadId::from(2));
+        assert_eq!(state.borrow().register_reads, 4);
+        assert_eq!(snapshot.get(ThreadId::from(2)).map(|r| r.pc.value()), Some(0x2000));
+        assert_eq!(snapshot.get(ThreadId::from(1)).map(|r| r.pc.value()), Some(0x1000));
+
+        snapshot.clear();
+        assert!(snapshot.is_empty());
+    }
+}
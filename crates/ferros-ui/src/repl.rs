@@ -0,0 +1,606 @@
+//! Line-oriented debugger session for `ferros repl`
+//!
+//! [`Repl`] keeps one [`Debugger`] for the whole session and runs one command
+//! per line against it, printing the same tables as `--headless --print` and
+//! doing the debugger work through [`crate::commands`], like the TUI command
+//! palette. Without a terminal it reads commands from a pipe or here-doc, so
+//! a session can be scripted or run over plain SSH:
+//!
+//! ```text
+//! ferros repl 1234 <<'EOF'
+//! break worker::run
+//! c
+//! bt
+//! regs
+//! EOF
+//! ```
+//!
+//! With `--output json` ([`Repl::with_json`]) each result is printed as one
+//! line of JSON from [`crate::json`] instead, so a script can read the session
+//! as JSON Lines.
+
+use std::fmt::Write as _;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use ferros_core::error::{DebuggerError, Result};
+use ferros_core::events::{DebuggerEvent, DebuggerEventReceiver, format_stop_reason, wait_for_stop};
+use ferros_core::inspector::LiveInspector;
+use ferros_core::notes::NoteRegistry;
+use ferros_core::types::{AddressFormat, StopReason};
+use ferros_core::{BreakpointId, BreakpointLocation, Debugger};
+use serde_json::{Value, json};
+
+use crate::commands::{self, BreakFlags, Step};
+use crate::{json, text};
+
+/// Bytes `mem` dumps when not given a length
+pub const DEFAULT_MEMORY_LEN: usize = 64;
+
+/// Most bytes one `mem` dumps, so a typo doesn't flood the terminal
+pub const MAX_MEMORY_LEN: usize = 64 * 1024;
+
+/// How often `c` checks for Ctrl+C while waiting for the target to stop
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Listed by `help`
+const HELP: &str = "\
+Commands:
+  regs                    registers of the active thread
+  mem <addr> [len]        hex dump len bytes (default 64) at an address or function+offset
+  threads                 threads and their run state
+  bt [n]                  the active thread's call stack (top n frames)
+  where                   where the target stopped
+  regions                 memory regions
+  modules                 loaded images
+  break [-f] <spec>       breakpoint at a function, file:line or 0x address (tbreak: one-shot, -f: unchecked)
+  delete <id>             remove a breakpoint
+  c                       continue until the next stop (Ctrl+C suspends)
+  step | stepi            one instruction
+  next | nexti            one instruction, running calls to completion
+  finish                  run until the current function returns
+  suspend                 stop a running target
+  detach                  detach and leave the target running, then quit
+  quit                    detach (or kill a launched target) and quit
+Lines starting with # are ignored.";
+
+/// Whether the session goes on after a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow
+{
+    /// Read the next command
+    Continue,
+    /// The session is over
+    Quit,
+}
+
+/// A debugger session driven one command line at a time
+#[allow(clippy::struct_excessive_bools)]
+pub struct Repl
+{
+    debugger: Box<dyn Debugger>,
+    events: Option<DebuggerEventReceiver>,
+    notes: NoteRegistry,
+    format: AddressFormat,
+    color: bool,
+    /// Print results as JSON lines instead of tables and text
+    json: bool,
+    /// Whether ferros started the target, so quitting kills it rather than detaching
+    launched: bool,
+    /// Whether the target exited, leaving nothing to detach from
+    ended: bool,
+}
+
+impl Repl
+{
+    /// Session on an attached or launched `debugger`
+    ///
+    /// `color` styles the tables; `format` sets the width of the addresses in them.
+    #[must_use]
+    pub fn new(mut debugger: Box<dyn Debugger>, launched: bool, format: AddressFormat, color: bool) -> Self
+    {
+        let events = debugger.take_event_receiver();
+        Self {
+            debugger,
+            events,
+            notes: NoteRegistry::new(),
+            format,
+            color,
+            json: false,
+            launched,
+            ended: false,
+        }
+    }
+
+    /// Annotate addresses in `regs` and `where` with these notes
+    #[must_use]
+    pub fn with_notes(mut self, notes: NoteRegistry) -> Self
+    {
+        self.notes = notes;
+        self
+    }
+
+    /// Print each result as one line of JSON rather than as tables and text
+    #[must_use]
+    pub fn with_json(mut self, json: bool) -> Self
+    {
+        self.json = json;
+        self
+    }
+
+    /// Run one command line, writing its output to `out`
+    ///
+    /// `cancel` is polled while `c` waits for a stop; setting it (from a
+    /// Ctrl+C handler) suspends the target.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of a command that failed, or of writing to `out`.
+    #[allow(clippy::too_many_lines)]
+    pub fn execute(&mut self, line: &str, out: &mut dyn Write, cancel: &AtomicBool) -> Result<Flow>
+    {
+        let line = line.trim();
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some(&command) = parts.first().filter(|_| !line.starts_with('#')) else {
+            return Ok(Flow::Continue);
+        };
+        let args = &parts[1..];
+
+        match command {
+            "regs" | "registers" => {
+                let target = &LiveInspector::new(self.debugger.as_mut());
+                if self.json {
+                    emit(&json::registers(target)?, out)?;
+                } else {
+                    let table = text::registers_table(target, &self.notes, self.format)?;
+                    write!(out, "{}", table.render(self.color))?;
+                }
+            }
+            "mem" | "x" => {
+                let (Some(address), len) = (args.first(), args.get(1)) else {
+                    return Err(usage("mem <address> [len]"));
+                };
+                let len = len.map_or(Ok(DEFAULT_MEMORY_LEN), |len| parse_length(len))?;
+                let address = commands::resolve_address(self.debugger.as_mut(), address)?;
+                let target = &LiveInspector::new(self.debugger.as_mut());
+                if self.json {
+                    emit(&json::memory(target, address, len)?, out)?;
+                } else {
+                    for row in crate::hexdump::dump_lines(target, address, len, self.format)? {
+                        writeln!(out, "{row}")?;
+                    }
+                }
+            }
+            "threads" => {
+                let target = &LiveInspector::new(self.debugger.as_mut());
+                if self.json {
+                    emit(&json::threads(target)?, out)?;
+                } else {
+                    write!(out, "{}", text::threads_table(target)?.render(self.color))?;
+                }
+            }
+            "bt" | "backtrace" => {
+                let frames = match args.first() {
+                    Some(count) => count
+                        .parse()
+                        .map_err(|_| DebuggerError::InvalidArgument(format!("Invalid frame count '{count}'")))?,
+                    None => text::BACKTRACE_FRAMES,
+                };
+                let target = &LiveInspector::new(self.debugger.as_mut());
+                if self.json {
+                    emit(&json::backtrace(target, frames)?, out)?;
+                } else {
+                    for line in text::backtrace(target, frames, self.format)? {
+                        writeln!(out, "{line}")?;
+                    }
+                }
+            }
+            "where" => {
+                let target = &LiveInspector::new(self.debugger.as_mut());
+                if self.json {
+                    emit(&json::where_summary(target)?, out)?;
+                } else {
+                    for line in text::where_summary(target, &self.notes, self.format)? {
+                        writeln!(out, "{line}")?;
+                    }
+                }
+            }
+            "regions" => {
+                let target = &LiveInspector::new(self.debugger.as_mut());
+                if self.json {
+                    emit(&json::regions(target)?, out)?;
+                } else {
+                    write!(out, "{}", text::regions_table(target, self.format)?.render(self.color))?;
+                }
+            }
+            "modules" => {
+                let images = self.debugger.modules()?;
+                if self.json {
+                    emit(&json::modules(&images), out)?;
+                } else {
+                    write!(out, "{}", text::modules_table(&images, self.format).render(self.color))?;
+                }
+            }
+            "break" | "b" | "tbreak" | "tb" => {
+                let (flags, Some(spec)) = BreakFlags::parse(command, args) else {
+                    return Err(usage("break [-f] <function | file:line | 0xaddress>"));
+                };
+                let location = spec.parse().map_err(DebuggerError::InvalidArgument)?;
+                self.break_at(location, flags, out)?;
+            }
+            "delete" | "d" => {
+                let Some(id) = args.first().and_then(|id| id.parse().ok()) else {
+                    return Err(usage("delete <breakpoint id>"));
+                };
+                self.debugger.remove_breakpoint(BreakpointId::from_raw(id))?;
+                self.say(&json!({"deleted": id}), &format!("Deleted breakpoint {id}"), out)?;
+            }
+            "c" | "continue" => self.continue_target(out, cancel)?,
+            "step" | "s" | "stepi" | "si" => self.step(Step::Instruction, out)?,
+            "next" | "n" | "nexti" | "ni" => self.step(Step::Over, out)?,
+            "finish" | "fin" => self.step(Step::Out, out)?,
+            "suspend" => {
+                self.debugger.suspend()?;
+                self.report_stop(None, out)?;
+            }
+            "detach" => {
+                self.debugger.detach()?;
+                self.say(&json!({"detached": true}), "Detached; the target keeps running", out)?;
+                return Ok(Flow::Quit);
+            }
+            "quit" | "q" | "exit" => {
+                self.quit(out)?;
+                return Ok(Flow::Quit);
+            }
+            "help" | "h" | "?" => writeln!(out, "{HELP}")?,
+            _ => {
+                return Err(DebuggerError::InvalidArgument(format!(
+                    "Unknown command: {command}. Type 'help' for commands."
+                )));
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// End the session: kill a target ferros launched, detach from one it attached to
+    ///
+    /// Nothing is left to do once the target has exited.
+    ///
+    /// # Errors
+    ///
+    /// Returns whatever killing or detaching fails with.
+    pub fn quit(&mut self, out: &mut dyn Write) -> Result<()>
+    {
+        if self.ended || !self.debugger.is_attached() {
+            return Ok(());
+        }
+        if self.launched {
+            self.debugger.kill()?;
+            self.say(&json!({"killed": true}), "Killed the launched target", out)?;
+        } else {
+            self.debugger.detach()?;
+            self.say(&json!({"detached": true}), "Detached", out)?;
+        }
+        Ok(())
+    }
+
+    /// Write `value` in JSON mode, `text` otherwise
+    fn say(&self, value: &Value, text: &str, out: &mut dyn Write) -> Result<()>
+    {
+        if self.json {
+            emit(value, out)
+        } else {
+            writeln!(out, "{text}")?;
+            Ok(())
+        }
+    }
+
+    /// Set the breakpoints `break` plans, one line per breakpoint
+    fn break_at(&mut self, location: BreakpointLocation, flags: BreakFlags, out: &mut dyn Write) -> Result<()>
+    {
+        let plan = commands::plan_breakpoints(self.debugger.as_mut(), location)?;
+        let one_shot = flags.one_shot;
+        let kind = if one_shot { "Temporary breakpoint" } else { "Breakpoint" };
+        let requests = plan.requests(flags);
+        if plan.addresses.is_empty() {
+            let id = self
+                .debugger
+                .add_breakpoint(requests.into_iter().next().expect("one pending request"))?;
+            let value = json!({"id": id, "address": null, "location": plan.location.to_string(), "one_shot": one_shot});
+            let text = format!("{kind} {} at {} (pending)", id.raw(), plan.location);
+            return self.say(&value, &text, out);
+        }
+        for (request, address) in requests.into_iter().zip(&plan.addresses) {
+            let id = self.debugger.add_breakpoint(request)?;
+            let mut line = format!("{kind} {} at {address}", id.raw());
+            if !matches!(plan.location, BreakpointLocation::Address(_)) {
+                let _ = write!(line, " ({})", plan.location);
+            }
+            let value = json!({"id": id, "address": address, "location": plan.location.to_string(), "one_shot": one_shot});
+            self.say(&value, &line, out)?;
+        }
+        Ok(())
+    }
+
+    /// Resume and wait for the next stop, printing images loaded meanwhile
+    ///
+    /// Without an event channel the target is polled instead. Ctrl+C
+    /// suspends it.
+    fn continue_target(&mut self, out: &mut dyn Write, cancel: &AtomicBool) -> Result<()>
+    {
+        // Stops queued before resuming are old news
+        if let Some(events) = &self.events {
+            for envelope in events.try_iter() {
+                self.report_event(&envelope.event, out)?;
+            }
+        }
+        cancel.store(false, Ordering::Relaxed);
+        self.debugger.resume()?;
+
+        let reason = loop {
+            if cancel.swap(false, Ordering::Relaxed) {
+                self.debugger.suspend()?;
+                self.say(&json!({"interrupted": true}), "Interrupted", out)?;
+                break None;
+            }
+            let Some(events) = &self.events else {
+                if self.debugger.is_stopped() {
+                    break None;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            };
+            let mut seen = Vec::new();
+            let stop = wait_for_stop(events, POLL_INTERVAL, |envelope| seen.push(envelope.event.clone()));
+            for event in &seen {
+                self.report_event(event, out)?;
+            }
+            match stop {
+                Ok(envelope) => match envelope.event {
+                    DebuggerEvent::TargetStopped { reason, .. } => break Some(reason),
+                    _ => break None,
+                },
+                Err(DebuggerError::TimedOut(_)) => {}
+                Err(e) => return Err(e),
+            }
+        };
+        self.report_stop(reason, out)
+    }
+
+    /// `stepi`, `nexti` or `finish`, then where the thread ended up
+    fn step(&mut self, step: Step, out: &mut dyn Write) -> Result<()>
+    {
+        let reason = commands::step(self.debugger.as_mut(), step)?;
+        self.report_stop(Some(reason), out)
+    }
+
+    /// Why the target stopped (`reason`, or the backend's when not known) and its top frame
+    fn report_stop(&mut self, reason: Option<StopReason>, out: &mut dyn Write) -> Result<()>
+    {
+        let reason = reason.unwrap_or_else(|| self.debugger.stop_reason());
+        self.ended = matches!(reason, StopReason::Exited(_));
+        let json = self.json;
+        let format = self.format;
+        let target = &LiveInspector::new(self.debugger.as_mut());
+        if json {
+            let frame = json::backtrace(target, 1).map_or(Value::Null, |frames| frames[0].clone());
+            return emit(&json!({"stop": reason, "frame": frame}), out);
+        }
+        writeln!(out, "{}", format_stop_reason(reason))?;
+        if let StopReason::Exited(_) = reason {
+            return Ok(());
+        }
+        if let Ok(frames) = text::backtrace(target, 1, format) {
+            for line in frames {
+                writeln!(out, "{line}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Images loaded or unloaded while the target ran; stops and resumes are reported by the caller
+    fn report_event(&self, event: &DebuggerEvent, out: &mut dyn Write) -> Result<()>
+    {
+        if matches!(event, DebuggerEvent::ImageLoaded { .. } | DebuggerEvent::ImageUnloaded { .. }) {
+            self.say(&json!({"event": event.describe()}), &event.describe(), out)?;
+        }
+        Ok(())
+    }
+}
+
+/// `value` as one line of JSON
+fn emit(value: &Value, out: &mut dyn Write) -> Result<()>
+{
+    writeln!(out, "{value}")?;
+    Ok(())
+}
+
+/// A byte count in decimal or `0x` hex, at most [`MAX_MEMORY_LEN`]
+fn parse_length(text: &str) -> Result<usize>
+{
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    match parsed {
+        Ok(len) if (1..=MAX_MEMORY_LEN).contains(&len) => Ok(len),
+        Ok(_) => Err(DebuggerError::InvalidArgument(format!(
+            "Length must be between 1 and {MAX_MEMORY_LEN} bytes"
+        ))),
+        Err(_) => Err(DebuggerError::InvalidArgument(format!("Invalid length '{text}'"))),
+    }
+}
+
+fn usage(command: &str) -> DebuggerError
+{
+    DebuggerError::InvalidArgument(format!("Usage: {command}"))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use ferros_core::mock::{self, MockDebugger, MockState};
+    use ferros_core::types::ThreadId;
+
+    use super::*;
+
+    fn repl(launched: bool) -> (Repl, Rc<RefCell<MockState>>)
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        (Repl::new(debugger, launched, AddressFormat::Full16, false), state)
+    }
+
+    /// Output of `line`, or its error message
+    fn run(repl: &mut Repl, line: &str) -> std::result::Result<String, String>
+    {
+        let mut out = Vec::new();
+        repl.execute(line, &mut out, &AtomicBool::new(false))
+            .map(|_| String::from_utf8(out).unwrap())
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn tables_and_dumps_are_printed_for_the_stopped_target()
+    {
+        let (mut repl, state) = repl(false);
+        {
+            let mut state = state.borrow_mut();
+            state.pc = 0x1_0000_1000;
+            state.memory = (0x2000..0x2040).map(|address: u64| (address, b'A')).collect();
+            let thread = ThreadId::from(1);
+            state.stack.frames = vec![
+                mock::frame(thread, 0, "demo::work", 7),
+                mock::frame(thread, 1, "demo::main", 12),
+            ];
+        }
+
+        let threads = run(&mut repl, "threads").unwrap();
+        assert!(threads.lines().next().unwrap().starts_with("Index"), "{threads}");
+        assert_eq!(threads.lines().count(), 3);
+        assert!(run(&mut repl, "regs").unwrap().contains("0x0000000100001000"));
+
+        let dump = run(&mut repl, "mem 0x2000 0x20").unwrap();
+        assert_eq!(dump.lines().count(), 2);
+        assert!(dump.starts_with("0x0000000000002000  41 41"), "{dump}");
+
+        let bt = run(&mut repl, "bt").unwrap();
+        assert_eq!(
+            bt,
+            "  #0   0x0000000100001000 demo::work at src/main.rs:7\n  #1   0x0000000100001040 demo::main at \
+             src/main.rs:12\n"
+        );
+        assert_eq!(run(&mut repl, "bt 1").unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn mistakes_are_reported_without_ending_the_session()
+    {
+        let (mut repl, _state) = repl(false);
+        assert_eq!(
+            run(&mut repl, "frobnicate").unwrap_err(),
+            "Invalid argument: Unknown command: frobnicate. Type 'help' for commands."
+        );
+        assert!(run(&mut repl, "mem").unwrap_err().contains("Usage: mem"));
+        assert!(run(&mut repl, "mem 0x1000 lots").unwrap_err().contains("Invalid length"));
+        assert!(run(&mut repl, "mem 0x1000 0").unwrap_err().contains("between 1 and"));
+        assert_eq!(run(&mut repl, "# a comment").unwrap(), "");
+        assert_eq!(run(&mut repl, "").unwrap(), "");
+    }
+
+    #[test]
+    fn break_reports_each_breakpoint_it_sets()
+    {
+        let (mut repl, state) = repl(false);
+        state
+            .borrow_mut()
+            .source_lines
+            .insert(("src/main.rs".to_string(), 42), vec![0x1000, 0x2000]);
+        state.borrow_mut().function_names.insert(0x3000, "worker::run".to_string());
+
+        assert_eq!(
+            run(&mut repl, "break src/main.rs:42").unwrap(),
+            "Breakpoint 1 at 0x0000000000001000 (src/main.rs:42)\nBreakpoint 2 at 0x0000000000002000 (src/main.rs:42)\n"
+        );
+        assert_eq!(
+            run(&mut repl, "tbreak run").unwrap(),
+            "Temporary breakpoint 3 at 0x0000000000003000 (worker::run)\n"
+        );
+        assert_eq!(
+            run(&mut repl, "b src/lib.rs:9").unwrap(),
+            "Breakpoint 4 at src/lib.rs:9 (pending)\n"
+        );
+        assert_eq!(run(&mut repl, "b 0x4000").unwrap(), "Breakpoint 5 at 0x0000000000004000\n");
+        assert!(state.borrow().breakpoint_requests[2].options.one_shot);
+    }
+
+    #[test]
+    fn step_and_continue_report_where_the_target_stopped()
+    {
+        let (mut repl, state) = repl(false);
+        state.borrow_mut().pc = 0x1000;
+
+        let stepped = run(&mut repl, "stepi").unwrap();
+        assert!(stepped.starts_with("Stepped to 0x1004"), "{stepped}");
+        assert_eq!(state.borrow().pc, 0x1004);
+
+        // The mock never stops by itself, so Ctrl+C is what ends the wait
+        let mut out = Vec::new();
+        let cancel = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(POLL_INTERVAL);
+                cancel.store(true, Ordering::Relaxed);
+            });
+            repl.execute("c", &mut out, &cancel).unwrap();
+        });
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("Interrupted\n"), "{out}");
+        assert!(state.borrow().stopped);
+    }
+
+    #[test]
+    fn json_mode_prints_one_value_per_result()
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        let mut repl = Repl::new(debugger, false, AddressFormat::Full16, false).with_json(true);
+        state.borrow_mut().memory = (0x2000..0x2004).map(|address: u64| (address, 0x41)).collect();
+        state.borrow_mut().pc = 0x1000;
+        let parse = |out: String| -> Vec<Value> { out.lines().map(|line| serde_json::from_str(line).unwrap()).collect() };
+
+        let memory = parse(run(&mut repl, "mem 0x2000 4").unwrap());
+        assert_eq!(memory[0]["address"], "0x2000");
+        assert_eq!(memory[0]["data"], "41414141");
+        assert_eq!(parse(run(&mut repl, "threads").unwrap())[0].as_array().unwrap().len(), 2);
+
+        let set = parse(run(&mut repl, "b 0x4000").unwrap());
+        assert_eq!(set[0]["id"], 1);
+        assert_eq!(set[0]["address"], "0x4000");
+
+        let stepped = parse(run(&mut repl, "stepi").unwrap());
+        assert_eq!(stepped[0]["stop"], json!({"reason": "step", "pc": "0x1004"}));
+        assert_eq!(parse(run(&mut repl, "q").unwrap()), [json!({"detached": true})]);
+    }
+
+    #[test]
+    fn quitting_kills_a_launched_target_and_detaches_from_others()
+    {
+        let (mut launched, state) = repl(true);
+        assert_eq!(
+            launched.execute("quit", &mut Vec::new(), &AtomicBool::new(false)).unwrap(),
+            Flow::Quit
+        );
+        assert_eq!(state.borrow().kills, 1);
+
+        let (mut attached, state) = repl(false);
+        assert_eq!(run(&mut attached, "q").unwrap(), "Detached\n");
+        assert_eq!(state.borrow().kills, 0);
+        assert_eq!(
+            attached.execute("detach", &mut Vec::new(), &AtomicBool::new(false)).unwrap(),
+            Flow::Quit
+        );
+    }
+}
@@ -0,0 +1,1013 @@
+//! Plain-text tables for headless output
+//!
+//! Headless sessions print the same registers, threads, memory regions and
+//! loaded images the TUI shows, under the same column names so the two can be compared.
+//! [`TextTable`] aligns columns to their content and adds ANSI styling only
+//! when asked to; [`use_color`] makes that decision from `--no-color`,
+//! `NO_COLOR` and whether stdout is a terminal. Addresses are printed in the
+//! [`AddressFormat`] chosen with `--address-format`, like the TUI's `x` key.
+
+use std::fmt::Write as _;
+use std::io::IsTerminal;
+
+use crossterm::style::Stylize;
+use ferros_core::TargetInspector;
+use ferros_core::error::{DebuggerError, Result};
+use ferros_core::events::format_stop_reason;
+use ferros_core::notes::{NOTE_MARKER, NoteRegistry};
+use ferros_core::processes::ProcessInfo;
+use ferros_core::symbols::{DebugLevel, ImageInfo};
+use ferros_core::types::{
+    Address, AddressFormat, MemoryRegion, RegisterId, StackFrame, StopReason, SymbolName, ThreadRunState, argument_values,
+    register_display_order,
+};
+
+use crate::widgets::{
+    MEMORY_REGION_COLUMNS, MODULE_COLUMNS, REGISTER_COLUMNS, THREAD_COLUMNS, format_memory_size, looks_like_address,
+};
+
+/// Spaces between adjacent columns
+const COLUMN_GAP: usize = 2;
+
+/// Whether to style output written to stdout
+///
+/// Off with `--no-color` (`no_color_flag`), when `NO_COLOR` is set to
+/// anything non-empty, or when stdout isn't a terminal.
+#[must_use]
+pub fn use_color(no_color_flag: bool) -> bool
+{
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color_flag && !no_color_env && std::io::stdout().is_terminal()
+}
+
+/// How a cell is styled when color is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tone
+{
+    /// Unstyled
+    #[default]
+    Plain,
+    /// Secondary information, such as address annotations
+    Dim,
+    /// Rows worth spotting, such as executable regions
+    Highlight,
+    /// The active thread
+    Active,
+    /// Something the debugger is holding back, such as a suspended thread
+    Alert,
+}
+
+/// One table cell
+#[derive(Debug, Clone, Default)]
+pub struct TextCell
+{
+    text: String,
+    tone: Tone,
+}
+
+impl TextCell
+{
+    /// Cell with the given tone
+    #[must_use]
+    pub fn new(text: impl Into<String>, tone: Tone) -> Self
+    {
+        Self { text: text.into(), tone }
+    }
+
+    fn styled(&self, color: bool) -> String
+    {
+        let text = self.text.as_str();
+        match self.tone {
+            // Styling an empty cell would leave escapes that stop trailing padding from being trimmed
+            _ if !color || text.is_empty() => text.to_string(),
+            Tone::Plain => text.to_string(),
+            Tone::Dim => text.dim().to_string(),
+            Tone::Highlight => text.green().to_string(),
+            Tone::Active => text.cyan().bold().to_string(),
+            Tone::Alert => text.red().to_string(),
+        }
+    }
+}
+
+impl From<String> for TextCell
+{
+    fn from(text: String) -> Self
+    {
+        Self::new(text, Tone::Plain)
+    }
+}
+
+impl From<&str> for TextCell
+{
+    fn from(text: &str) -> Self
+    {
+        Self::new(text, Tone::Plain)
+    }
+}
+
+/// Table whose column widths are computed from its content
+#[derive(Debug, Clone)]
+pub struct TextTable
+{
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<TextCell>>,
+    empty: &'static str,
+}
+
+impl TextTable
+{
+    /// Empty table; `empty` is printed under the header when no rows are added
+    #[must_use]
+    pub fn new(headers: impl IntoIterator<Item = &'static str>, empty: &'static str) -> Self
+    {
+        Self {
+            headers: headers.into_iter().collect(),
+            rows: Vec::new(),
+            empty,
+        }
+    }
+
+    /// Append a row; missing trailing cells are left blank
+    pub fn push_row(&mut self, cells: Vec<TextCell>)
+    {
+        self.rows.push(cells);
+    }
+
+    /// Number of rows
+    #[must_use]
+    pub fn len(&self) -> usize
+    {
+        self.rows.len()
+    }
+
+    /// Whether no rows were added
+    #[must_use]
+    pub fn is_empty(&self) -> bool
+    {
+        self.rows.is_empty()
+    }
+
+    /// Render with one line per row and a bold header when `color` is set
+    ///
+    /// Columns are left-aligned and padded to their widest cell; trailing
+    /// whitespace is trimmed so the plain output diffs cleanly.
+    #[must_use]
+    pub fn render(&self, color: bool) -> String
+    {
+        let mut widths: Vec<usize> = self.headers.iter().map(|header| header.chars().count()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.text.chars().count());
+            }
+        }
+
+        let header: Vec<TextCell> = self.headers.iter().map(|header| TextCell::from(*header)).collect();
+        let mut out = String::new();
+        push_line(&mut out, &header, &widths, |cell| {
+            if color {
+                cell.text.as_str().bold().to_string()
+            } else {
+                cell.text.clone()
+            }
+        });
+        if self.rows.is_empty() {
+            let _ = writeln!(out, "{}", TextCell::new(self.empty, Tone::Dim).styled(color));
+        }
+        for row in &self.rows {
+            push_line(&mut out, row, &widths, |cell| cell.styled(color));
+        }
+        out
+    }
+}
+
+fn push_line(out: &mut String, cells: &[TextCell], widths: &[usize], style: impl Fn(&TextCell) -> String)
+{
+    let mut line = String::new();
+    for (index, width) in widths.iter().enumerate() {
+        let cell = cells.get(index).cloned().unwrap_or_default();
+        if index > 0 {
+            line.push_str(&" ".repeat(COLUMN_GAP));
+        }
+        line.push_str(&style(&cell));
+        line.push_str(&" ".repeat(width - cell.text.chars().count()));
+    }
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
+
+/// The memory region `value` points into, as `<name or start> +0x<offset> <perms>`
+///
+/// `symbol`, the code `value` points at, follows, then the notes on `value`,
+/// each after a marker.
+#[must_use]
+pub fn describe_address(
+    value: u64,
+    regions: &[MemoryRegion],
+    symbol: Option<&str>,
+    notes: &NoteRegistry,
+    format: AddressFormat,
+) -> Option<String>
+{
+    let region = regions.iter().find(|region| region.contains(Address::from(value)))?;
+    let base = region
+        .name
+        .clone()
+        .unwrap_or_else(|| format.formatter([region.start]).format(region.start));
+    let mut description = format!("{base} +0x{:x} {}", value - region.start.value(), region.permissions);
+    if let Some(symbol) = symbol {
+        let _ = write!(description, " {symbol}");
+    }
+    for note in notes.at_address(value) {
+        let _ = write!(description, " {NOTE_MARKER} {}", note.text);
+    }
+    Some(description)
+}
+
+/// `address` as `function+0x<offset>`
+///
+/// The function is `name` when the caller already knows it and otherwise
+/// comes from the target's [`function_name`](TargetInspector::function_name)
+/// hook. The offset is left off when the target can't say where the function
+/// starts.
+#[must_use]
+pub fn symbol_offset(target: &dyn TargetInspector, address: Address, name: Option<&str>) -> Option<String>
+{
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => target.function_name(address)?.display_name().to_string(),
+    };
+    Some(match target.function_start(address) {
+        Some(start) if start.value() <= address.value() => format!("{name}+0x{:x}", address.value() - start.value()),
+        _ => name,
+    })
+}
+
+/// Registers of the active thread
+///
+/// Values that look like addresses are annotated with the region they point
+/// into, the function there if the target can name it, and any notes there,
+/// dimmed.
+///
+/// # Errors
+///
+/// Returns an error if the registers can't be read.
+pub fn registers_table(target: &dyn TargetInspector, notes: &NoteRegistry, format: AddressFormat) -> Result<TextTable>
+{
+    let registers = target.read_registers()?;
+    let regions = target.get_memory_regions().unwrap_or_default();
+    let mut table = TextTable::new(REGISTER_COLUMNS.iter().map(|column| column.header), "(no registers)");
+
+    let order = register_display_order(registers.architecture());
+    let values = format.formatter(order.iter().filter_map(|row| registers.get(row.id)));
+    let annotation = |value: u64| {
+        let text = if looks_like_address(value) {
+            let symbol = regions
+                .iter()
+                .any(|region| region.is_executable() && region.contains(Address::from(value)))
+                .then(|| symbol_offset(target, Address::from(value), None))
+                .flatten();
+            describe_address(value, &regions, symbol.as_deref(), notes, format).unwrap_or_else(|| "unmapped".to_string())
+        } else {
+            String::new()
+        };
+        TextCell::new(text, Tone::Dim)
+    };
+    for row in order {
+        let name = row.display_name().into();
+        match registers.get(row.id) {
+            Some(value) if row.id == RegisterId::Status => table.push_row(vec![name, values.format(value).into()]),
+            Some(value) => table.push_row(vec![name, values.format(value).into(), annotation(value)]),
+            None => table.push_row(vec![name, TextCell::new("unavailable", Tone::Dim)]),
+        }
+    }
+    Ok(table)
+}
+
+/// Threads with the active one marked `→` and debugger suspensions flagged
+///
+/// # Errors
+///
+/// Returns an error if the thread list can't be read.
+pub fn threads_table(target: &dyn TargetInspector) -> Result<TextTable>
+{
+    let threads = target.threads()?;
+    let active = target.active_thread();
+    // The TUI's first column holds marks for multi-thread actions; there are none here
+    let mut table = TextTable::new(THREAD_COLUMNS[1..].iter().map(|column| column.header), "(no threads)");
+
+    for (index, thread) in threads.iter().enumerate() {
+        let is_active = active == Some(*thread);
+        let tone = if is_active { Tone::Active } else { Tone::Plain };
+        let suspended = match target.thread_suspend_count(*thread) {
+            0 => String::new(),
+            1 => "Yes".to_string(),
+            n => format!("Yes (x{n})"),
+        };
+        let info = target.thread_info(*thread).ok();
+        let state_tone = match info.as_ref().map(|info| info.state) {
+            Some(ThreadRunState::Suspended) => Tone::Alert,
+            Some(ThreadRunState::Halted | ThreadRunState::Unknown) => Tone::Dim,
+            _ => tone,
+        };
+        table.push_row(vec![
+            TextCell::new(format!("{}{index}", if is_active { "→ " } else { "  " }), tone),
+            TextCell::new(thread.raw().to_string(), tone),
+            TextCell::new(info.as_ref().and_then(|info| info.name.clone()).unwrap_or_default(), tone),
+            TextCell::new(
+                info.as_ref().map(|info| info.state.to_string()).unwrap_or_default(),
+                state_tone,
+            ),
+            TextCell::new(
+                info.as_ref()
+                    .map(|info| format!("{:.1}", info.cpu_percent))
+                    .unwrap_or_default(),
+                tone,
+            ),
+            TextCell::new(if is_active { "Active" } else { "" }, tone),
+            TextCell::new(suspended, Tone::Alert),
+        ]);
+    }
+    Ok(table)
+}
+
+/// Memory regions with executable ones highlighted
+///
+/// # Errors
+///
+/// Returns an error if the regions can't be read.
+pub fn regions_table(target: &dyn TargetInspector, format: AddressFormat) -> Result<TextTable>
+{
+    let regions = target.get_memory_regions()?;
+    let addresses = format.formatter(regions.iter().flat_map(|region| [region.start, region.end]));
+    let mut table = TextTable::new(MEMORY_REGION_COLUMNS.iter().map(|column| column.header), "(no regions)");
+
+    for region in &regions {
+        let tone = if region.is_executable() {
+            Tone::Highlight
+        } else {
+            Tone::Plain
+        };
+        table.push_row(vec![
+            TextCell::new(region.id.value().to_string(), tone),
+            TextCell::new(addresses.format(region.start), tone),
+            TextCell::new(addresses.format(region.end), tone),
+            TextCell::new(format_memory_size(region.size()), tone),
+            TextCell::new(region.permissions.clone(), tone),
+            TextCell::new(region.name.clone().unwrap_or_default(), tone),
+        ]);
+    }
+    Ok(table)
+}
+
+/// Images the symbol cache has loaded, dimmed when they have no symbols at all
+#[must_use]
+pub fn modules_table(images: &[ImageInfo], format: AddressFormat) -> TextTable
+{
+    let addresses = format.formatter(images.iter().flat_map(|image| [image.load_address, image.end_address]));
+    let mut table = TextTable::new(MODULE_COLUMNS.iter().map(|column| column.header), "(no images loaded)");
+    for image in images {
+        let tone = if image.debug_level == DebugLevel::None {
+            Tone::Dim
+        } else {
+            Tone::Plain
+        };
+        table.push_row(vec![
+            TextCell::new(addresses.format(image.load_address), tone),
+            TextCell::new(addresses.format(image.end_address), tone),
+            TextCell::new(image.debug_level.to_string(), tone),
+            TextCell::new(if image.has_eh_frame { "yes" } else { "no" }, tone),
+            TextCell::new(image.symbol_count.to_string(), tone),
+            TextCell::new(image.path.display().to_string(), tone),
+        ]);
+    }
+    table
+}
+
+/// Processes for `ferros ps` and an ambiguous `ferros attach --name`, paths dimmed
+#[must_use]
+pub fn processes_table(processes: &[ProcessInfo]) -> TextTable
+{
+    let mut table = TextTable::new(["PID", "Name", "Path"], "(no processes)");
+    for process in processes {
+        table.push_row(vec![
+            TextCell::from(process.pid.0.to_string()),
+            TextCell::from(process.name.as_str()),
+            TextCell::new(
+                process
+                    .path
+                    .as_ref()
+                    .map_or_else(String::new, |path| path.display().to_string()),
+                Tone::Dim,
+            ),
+        ]);
+    }
+    table
+}
+
+/// Frames listed by [`where_summary`]
+pub const WHERE_FRAMES: usize = 5;
+
+/// Where the stopped active thread is, for `:where` and `ferros where`
+///
+/// Lines, in order: the thread and why it stopped (naming the breakpoint
+/// that caused it), the PC as `symbol+offset` with its source line, the
+/// enclosing function with its arguments when their names are known, the
+/// regions the PC and SP fall in, and the top [`WHERE_FRAMES`] frames.
+///
+/// Frames come from [`TargetInspector::backtrace`], so a live debugger must
+/// be wrapped in a [`LiveInspector`](ferros_core::inspector::LiveInspector).
+///
+/// # Errors
+///
+/// Returns an error if the target is running, has no active thread, or its
+/// registers can't be read.
+pub fn where_summary(target: &dyn TargetInspector, notes: &NoteRegistry, format: AddressFormat) -> Result<Vec<String>>
+{
+    if !target.is_stopped() {
+        return Err(DebuggerError::InvalidArgument(
+            "Process is running; stop it to see where it is".to_string(),
+        ));
+    }
+    let thread = target
+        .active_thread()
+        .ok_or_else(|| DebuggerError::InvalidArgument("No active thread".to_string()))?;
+    let registers = target.read_registers()?;
+    let regions = target.get_memory_regions().unwrap_or_default();
+    let frames = target.backtrace(thread, WHERE_FRAMES);
+    let (pc, sp) = (registers.pc.value(), registers.sp.value());
+    let top = frames.as_ref().ok().and_then(|frames| frames.first());
+
+    let reason = target.stop_reason();
+    let mut stop = format!("Thread {}: {}", thread.raw(), format_stop_reason(reason));
+    if let StopReason::Breakpoint(address) = reason
+        && let Some(breakpoint) = target.breakpoints().iter().find(|info| info.address.value() == address)
+    {
+        let _ = write!(stop, " (breakpoint {})", breakpoint.id.raw());
+    }
+
+    let addresses = format.formatter([pc, sp]);
+    let function = top.and_then(|frame| frame.symbol.as_ref()).map(SymbolName::display_name);
+    let symbol = symbol_offset(target, registers.pc, function).unwrap_or_else(|| "<unknown>".to_string());
+    let mut lines = vec![
+        stop,
+        format!("PC     {}  {}", addresses.format(pc), with_location(symbol, top)),
+    ];
+    if let (Some(name), Some(frame)) = (function, top) {
+        if frame.parameters.is_empty() {
+            lines.push(format!("Fn     {name} (arguments unknown)"));
+        } else {
+            let arguments = argument_values(&registers, &frame.parameters);
+            let named = arguments.len().min(frame.parameters.len());
+            lines.push(format!("Fn     {name}({})", arguments[..named].join(", ")));
+        }
+    }
+    let region = |value| describe_address(value, &regions, None, notes, format).unwrap_or_else(|| "unmapped".to_string());
+    lines.push(format!("PC in  {}", region(pc)));
+    lines.push(format!("SP     {}  {}", addresses.format(sp), region(sp)));
+
+    match frames {
+        Ok(frames) if frames.is_empty() => lines.push("Frames (none)".to_string()),
+        Ok(frames) => {
+            lines.push("Frames".to_string());
+            lines.extend(frame_lines(&frames, format));
+        }
+        Err(err) => lines.push(format!("Frames unavailable: {err}")),
+    }
+    Ok(lines)
+}
+
+/// Frames listed per thread by [`all_stacks`]
+pub const ALL_STACKS_FRAMES: usize = 16;
+
+/// Every thread's call stack, for `:all-stacks`
+///
+/// Per thread a header with its index, id, name and run state, marking the
+/// active thread, then its top [`ALL_STACKS_FRAMES`] frames and a blank line.
+/// A thread that can't be unwound says why instead of failing the report,
+/// so one odd thread doesn't hide the rest when looking for a deadlock.
+///
+/// Frames come from [`TargetInspector::backtrace`], as for [`where_summary`].
+///
+/// # Errors
+///
+/// Returns an error if the target is running or its threads can't be listed.
+pub fn all_stacks(target: &dyn TargetInspector, format: AddressFormat) -> Result<Vec<String>>
+{
+    if !target.is_stopped() {
+        return Err(DebuggerError::InvalidArgument(
+            "Process is running; stop it to see its stacks".to_string(),
+        ));
+    }
+    let threads = target.threads()?;
+    let active = target.active_thread();
+    let mut lines = Vec::new();
+    for (index, thread) in threads.iter().enumerate() {
+        let mut header = format!("Thread {index} (id {})", thread.raw());
+        if let Ok(info) = target.thread_info(*thread) {
+            if let Some(name) = &info.name {
+                let _ = write!(header, " \"{name}\"");
+            }
+            let _ = write!(header, " {}", info.state);
+        }
+        if active == Some(*thread) {
+            header.push_str(" [active]");
+        }
+        lines.push(header);
+        match target.backtrace(*thread, ALL_STACKS_FRAMES) {
+            Ok(frames) if frames.is_empty() => lines.push("  (no frames)".to_string()),
+            Ok(frames) => lines.extend(frame_lines(&frames, format)),
+            Err(err) => lines.push(format!("  Frames unavailable: {err}")),
+        }
+        lines.push(String::new());
+    }
+    lines.pop();
+    Ok(lines)
+}
+
+/// Frames `bt` lists in `ferros repl` when not given a count
+pub const BACKTRACE_FRAMES: usize = 64;
+
+/// The active thread's top `max_frames` frames, for `bt` in `ferros repl`
+///
+/// One line per frame as in [`all_stacks`].
+///
+/// # Errors
+///
+/// Returns an error if the target is running, has no active thread, or
+/// can't be unwound.
+pub fn backtrace(target: &dyn TargetInspector, max_frames: usize, format: AddressFormat) -> Result<Vec<String>>
+{
+    if !target.is_stopped() {
+        return Err(DebuggerError::InvalidArgument(
+            "Process is running; stop it to see its stack".to_string(),
+        ));
+    }
+    let thread = target
+        .active_thread()
+        .ok_or_else(|| DebuggerError::InvalidArgument("No active thread".to_string()))?;
+    let frames = target.backtrace(thread, max_frames)?;
+    if frames.is_empty() {
+        return Ok(vec!["  (no frames)".to_string()]);
+    }
+    Ok(frame_lines(&frames, format))
+}
+
+/// `  #n   pc function at file:line` for each frame
+fn frame_lines(frames: &[StackFrame], format: AddressFormat) -> Vec<String>
+{
+    let addresses = format.formatter(frames.iter().map(|frame| frame.pc));
+    frames
+        .iter()
+        .map(|frame| {
+            let function = frame
+                .symbol
+                .as_ref()
+                .map_or_else(|| "<unknown>".to_string(), |symbol| symbol.display_name().to_string());
+            format!(
+                "  #{:<3} {} {}",
+                frame.index,
+                addresses.format(frame.pc),
+                with_location(function, Some(frame))
+            )
+        })
+        .collect()
+}
+
+/// `text` followed by ` at file:line` when `frame` has a source location
+fn with_location(text: String, frame: Option<&StackFrame>) -> String
+{
+    match frame.and_then(|frame| frame.location.as_ref()) {
+        Some(location) => match location.line {
+            Some(line) => format!("{text} at {}:{line}", location.file),
+            None => format!("{text} at {}", location.file),
+        },
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ferros_core::mock::{self, MockDebugger};
+    use ferros_core::types::{MemoryRegionId, ThreadId, ThreadInfo};
+
+    use super::*;
+
+    /// Drop ANSI escape sequences
+    fn strip_ansi(text: &str) -> String
+    {
+        let mut out = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(char::is_ascii_alphabetic);
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    fn region(id: usize, start: u64, end: u64, permissions: &str, name: Option<&str>) -> MemoryRegion
+    {
+        MemoryRegion::new(
+            MemoryRegionId(id),
+            Address::from(start),
+            Address::from(end),
+            permissions.to_string(),
+            name.map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn registers_are_aligned_and_annotated()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.pc = 0x1_0000_1040;
+            state.sp = 0x1_6fe0_fff0;
+            state.general = vec![0x2a, 0x1_0000_1000, 0x7000_0000];
+            state.regions = vec![
+                region(0, 0x1_0000_0000, 0x1_0000_4000, "r-x", Some("demo")),
+                region(1, 0x1_6fe0_0000, 0x1_6fe1_0000, "rw-", None),
+            ];
+        }
+
+        let table = registers_table(debugger.as_ref(), &NoteRegistry::new(), AddressFormat::Full16).unwrap();
+        let colored = table.render(true);
+        assert!(colored.contains('\x1b'));
+        assert_eq!(
+            strip_ansi(&colored),
+            table.render(false),
+            "styling must not change the layout"
+        );
+        // Rows follow the core schema; registers the backend didn't fill in
+        // keep their row so indices stay put
+        let plain = table.render(false);
+        let head: Vec<_> = plain.lines().take(10).collect();
+        assert_eq!(
+            head.join("\n"),
+            "\
+Register  Value (hex)         Address
+PC        0x0000000100001040  demo +0x1040 r-x
+SP        0x000000016fe0fff0  0x000000016fe00000 +0xfff0 rw-
+X29 (fp)  0x0000000000000000
+X30 (lr)  unavailable
+CPSR      0x0000000000000000
+X0        0x000000000000002a
+X1        0x0000000100001000  demo +0x1000 r-x
+X2        0x0000000070000000  unmapped
+X3        unavailable"
+        );
+        assert_eq!(plain.lines().count(), 1 + 34);
+    }
+
+    #[test]
+    fn noted_addresses_show_their_notes()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.pc = 0x1_0000_1040;
+            state.regions = vec![region(0, 0x1_0000_0000, 0x1_0000_4000, "r-x", Some("demo"))];
+        }
+        let mut notes = NoteRegistry::new();
+        notes
+            .add(ferros_core::notes::NoteTarget::address(0x1_0000_1040), "parser entry")
+            .unwrap();
+
+        let plain = registers_table(debugger.as_ref(), &notes, AddressFormat::Full16)
+            .unwrap()
+            .render(false);
+        let pc = plain.lines().nth(1).unwrap();
+        assert_eq!(
+            pc,
+            format!("PC        0x0000000100001040  demo +0x1040 r-x {NOTE_MARKER} parser entry")
+        );
+        assert!(!plain.lines().nth(2).unwrap().contains(NOTE_MARKER));
+    }
+
+    #[test]
+    fn code_addresses_are_named_through_the_inspector()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.pc = 0x1_0000_1040;
+            state.general = vec![0x1_0000_3008];
+            state.regions = vec![
+                region(0, 0x1_0000_0000, 0x1_0000_2000, "r-x", Some("demo")),
+                region(1, 0x1_0000_2000, 0x1_0000_4000, "rw-", Some("demo")),
+            ];
+            state.function_starts = vec![0x1_0000_1000, 0x1_0000_3000];
+            state.function_names = [(0x1_0000_1000, "demo::parse"), (0x1_0000_3000, "not code")]
+                .into_iter()
+                .map(|(start, name)| (start, name.to_string()))
+                .collect();
+        }
+
+        let plain = registers_table(debugger.as_ref(), &NoteRegistry::new(), AddressFormat::Full16)
+            .unwrap()
+            .render(false);
+        assert!(plain.contains("PC        0x0000000100001040  demo +0x1040 r-x demo::parse+0x40\n"));
+        // Data pointers aren't symbolicated
+        assert!(plain.contains("X0        0x0000000100003008  demo +0x1008 rw-\n"));
+    }
+
+    #[test]
+    fn threads_mark_the_active_and_suspended_threads()
+    {
+        let (debugger, state) = MockDebugger::with_threads(3);
+        {
+            let mut state = state.borrow_mut();
+            state.active_thread = Some(ThreadId::from(2));
+            state.suspend_counts.insert(ThreadId::from(3), 2);
+            let states = vec![
+                (Some("main"), ThreadRunState::Running, 12.5),
+                (Some("tokio-runtime-worker"), ThreadRunState::Waiting, 0.0),
+                (None, ThreadRunState::Suspended, 0.0),
+            ];
+            for (id, (name, run_state, cpu_percent)) in (1..).zip(states) {
+                let mut info = ThreadInfo::new(ThreadId::from(id));
+                info.name = name.map(str::to_string);
+                info.state = run_state;
+                info.cpu_percent = cpu_percent;
+                state.thread_infos.insert(info.id, info);
+            }
+        }
+
+        let table = threads_table(debugger.as_ref()).unwrap();
+        assert_eq!(strip_ansi(&table.render(true)), table.render(false));
+        assert_eq!(
+            table.render(false),
+            "\
+Index  Thread ID  Name                  State      CPU%  Status  Suspended by debugger
+  0    1          main                  running    12.5
+→ 1    2          tokio-runtime-worker  waiting    0.0   Active
+  2    3                                suspended  0.0           Yes (x2)
+"
+        );
+    }
+
+    #[test]
+    fn regions_use_tui_headers_and_highlight_code()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().regions = vec![
+            region(0, 0x1_0000_0000, 0x1_0000_4000, "r-x", Some("/tmp/demo")),
+            region(7, 0x1_0000_4000, 0x1_0010_4000, "rw-", None),
+        ];
+
+        let table = regions_table(debugger.as_ref(), AddressFormat::Full16).unwrap();
+        let colored = table.render(true);
+        let code_line = colored.lines().nth(1).unwrap();
+        assert!(code_line.contains("\x1b[38;5;10m") || code_line.contains("\x1b[32m"));
+        assert!(!colored.lines().nth(2).unwrap().contains("\x1b[3"));
+        assert_eq!(
+            table.render(false),
+            "\
+ID  Start               End                 Size      Perms  Name
+0   0x0000000100000000  0x0000000100004000  16.00 KB  r-x    /tmp/demo
+7   0x0000000100004000  0x0000000100104000  1.00 MB   rw-
+"
+        );
+    }
+
+    #[test]
+    fn modules_show_debug_info_and_dim_images_without_symbols()
+    {
+        use ferros_core::symbols::ImageId;
+
+        let image = |path: &str, load_address: u64, debug_level: DebugLevel, symbol_count: usize| ImageInfo {
+            id: ImageId::from_parts(path.as_ref(), load_address),
+            path: path.into(),
+            load_address: Address::from(load_address),
+            end_address: Address::from(load_address + 0x8000),
+            debug_level,
+            has_eh_frame: debug_level != DebugLevel::None,
+            symbol_count,
+            dsym: None,
+            dsym_mismatch: None,
+        };
+        let images = vec![
+            image("/tmp/demo", 0x1_0000_0000, DebugLevel::Full, 412),
+            image("/usr/lib/libblob.dylib", 0x1_8000_0000, DebugLevel::None, 0),
+        ];
+
+        let table = modules_table(&images, AddressFormat::Trimmed);
+        assert!(table.render(true).lines().nth(2).unwrap().contains("\x1b[2m"));
+        assert_eq!(
+            table.render(false),
+            "\
+Start        End          Debug Info       eh_frame  Symbols  Path
+0x100000000  0x100008000  full debug info  yes       412      /tmp/demo
+0x180000000  0x180008000  no symbols       no        0        /usr/lib/libblob.dylib
+"
+        );
+        assert_eq!(
+            modules_table(&[], AddressFormat::Full16).render(false),
+            "Start  End  Debug Info  eh_frame  Symbols  Path\n(no images loaded)\n"
+        );
+    }
+
+    #[test]
+    fn processes_list_pid_name_and_path()
+    {
+        use ferros_core::processes::ProcessInfo;
+        use ferros_core::types::ProcessId;
+
+        let processes = vec![
+            ProcessInfo {
+                pid: ProcessId(812),
+                name: "my_app".to_string(),
+                path: Some("/Users/me/my_app/target/debug/my_app".into()),
+            },
+            ProcessInfo {
+                pid: ProcessId(4211),
+                name: "my_app_helper".to_string(),
+                path: None,
+            },
+        ];
+        let table = processes_table(&processes);
+        assert_eq!(strip_ansi(&table.render(true)), table.render(false));
+        assert_eq!(
+            table.render(false),
+            "\
+PID   Name           Path
+812   my_app         /Users/me/my_app/target/debug/my_app
+4211  my_app_helper
+"
+        );
+        assert_eq!(processes_table(&[]).render(false), "PID  Name  Path\n(no processes)\n");
+    }
+
+    #[test]
+    fn trimmed_tables_fit_their_widest_address()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        {
+            let mut state = state.borrow_mut();
+            state.pc = 0x1_0000_1040;
+            state.general = vec![0x2a];
+            state.regions = vec![
+                region(0, 0x1_0000_0000, 0x1_0000_4000, "r-x", Some("demo")),
+                region(1, 0x7000_0000, 0x7001_0000, "rw-", None),
+            ];
+        }
+
+        let plain = regions_table(debugger.as_ref(), AddressFormat::Trimmed)
+            .unwrap()
+            .render(false);
+        assert_eq!(
+            plain,
+            "\
+ID  Start        End          Size      Perms  Name
+0   0x100000000  0x100004000  16.00 KB  r-x    demo
+1   0x070000000  0x070010000  64.00 KB  rw-
+"
+        );
+
+        let plain = registers_table(debugger.as_ref(), &NoteRegistry::new(), AddressFormat::Short12)
+            .unwrap()
+            .render(false);
+        let head: Vec<_> = plain.lines().take(2).collect();
+        assert_eq!(
+            head,
+            [
+                "Register  Value (hex)     Address",
+                "PC        0x000100001040  demo +0x1040 r-x"
+            ]
+        );
+        assert!(plain.contains("X0        0x00000000002a\n"));
+    }
+
+    #[test]
+    fn empty_tables_say_so()
+    {
+        let (debugger, _state) = MockDebugger::with_threads(0);
+
+        let threads = threads_table(debugger.as_ref()).unwrap();
+        assert!(threads.is_empty());
+        assert_eq!(
+            threads.render(false),
+            "Index  Thread ID  Name  State  CPU%  Status  Suspended by debugger\n(no threads)\n"
+        );
+        assert_eq!(
+            regions_table(debugger.as_ref(), AddressFormat::Full16).unwrap().render(false),
+            "ID  Start  End  Size  Perms  Name\n(no regions)\n"
+        );
+    }
+
+    #[test]
+    fn where_summary_snapshot()
+    {
+        use ferros_core::inspector::LiveInspector;
+        use ferros_core::types::FunctionParameter;
+        use ferros_core::{BreakpointId, BreakpointInfo, BreakpointKind};
+
+        let (mut debugger, state) = MockDebugger::with_threads(2);
+        let thread = ThreadId::from(2);
+        {
+            let mut state = state.borrow_mut();
+            state.active_thread = Some(thread);
+            state.pc = 0x1_0000_1000;
+            state.sp = 0x1_6fe0_fff0;
+            state.general = vec![0x2a, 0x1_0000_3000];
+            state.regions = vec![
+                region(0, 0x1_0000_0000, 0x1_0000_4000, "r-x", Some("demo")),
+                region(1, 0x1_6fe0_0000, 0x1_6fe1_0000, "rw-", None),
+            ];
+            state.stack.frames = (0..7_u32)
+                .map(|index| mock::frame(thread, index as usize, &format!("demo::f{index}"), 10 + index))
+                .collect();
+            state.stack.frames[0].parameters = vec![FunctionParameter::new("len"), FunctionParameter::new("buf")];
+            state.function_starts = vec![0x1_0000_0f80, 0x1_0000_1040];
+            state.stop_reason = Some(StopReason::Breakpoint(0x1_0000_1000));
+            state.breakpoints = vec![BreakpointInfo::new(
+                BreakpointId::from_raw(3),
+                Address::from(0x1_0000_1000),
+                BreakpointKind::Software,
+            )];
+        }
+
+        let mut summary = || {
+            where_summary(
+                &LiveInspector::new(debugger.as_mut()),
+                &NoteRegistry::new(),
+                AddressFormat::Full16,
+            )
+        };
+        let lines = summary().unwrap();
+        assert_eq!(
+            lines.join("\n"),
+            "\
+Thread 2: Hit breakpoint at 0x100001000 (breakpoint 3)
+PC     0x0000000100001000  demo::f0+0x80 at src/main.rs:10
+Fn     demo::f0(len (x0) = 0x2a, buf (x1) = 0x100003000)
+PC in  demo +0x1000 r-x
+SP     0x000000016fe0fff0  0x000000016fe00000 +0xfff0 rw-
+Frames
+  #0   0x0000000100001000 demo::f0 at src/main.rs:10
+  #1   0x0000000100001040 demo::f1 at src/main.rs:11
+  #2   0x0000000100001080 demo::f2 at src/main.rs:12
+  #3   0x00000001000010c0 demo::f3 at src/main.rs:13
+  #4   0x0000000100001100 demo::f4 at src/main.rs:14"
+        );
+
+        // Without parameter names the arguments can't be attributed
+        state.borrow_mut().stack.frames[0].parameters.clear();
+        assert_eq!(summary().unwrap()[2], "Fn     demo::f0 (arguments unknown)");
+
+        state.borrow_mut().stopped = false;
+        assert!(summary().is_err());
+        // The active thread is selected again after unwinding
+        assert_eq!(debugger.active_thread(), Some(thread));
+    }
+
+    #[test]
+    fn all_stacks_lists_every_thread_with_its_frames()
+    {
+        use ferros_core::inspector::LiveInspector;
+        use ferros_core::types::ThreadRunState;
+
+        let (mut debugger, state) = MockDebugger::with_threads(3);
+        {
+            let mut state = state.borrow_mut();
+            state.active_thread = Some(ThreadId::from(2));
+            let main = ThreadId::from(1);
+            state.thread_stacks.insert(
+                main,
+                vec![
+                    mock::frame(main, 0, "std::sys::sync::mutex::lock", 40),
+                    mock::frame(main, 1, "demo::main", 12),
+                ],
+            );
+            let mut info = ThreadInfo::new(main);
+            info.name = Some("main".to_string());
+            info.state = ThreadRunState::Waiting;
+            state.thread_infos.insert(main, info);
+            let worker = ThreadId::from(2);
+            let frames = (0..20).map(|index| mock::frame(worker, index, "demo::spin", 7)).collect();
+            state.thread_stacks.insert(worker, frames);
+        }
+
+        let lines = all_stacks(&LiveInspector::new(debugger.as_mut()), AddressFormat::Full16).unwrap();
+        assert_eq!(
+            lines[..5].join("\n"),
+            "\
+Thread 0 (id 1) \"main\" waiting
+  #0   0x0000000100001000 std::sys::sync::mutex::lock at src/main.rs:40
+  #1   0x0000000100001040 demo::main at src/main.rs:12
+
+Thread 1 (id 2) unknown [active]"
+        );
+        // Frames are capped per thread, and a thread without frames says so
+        assert_eq!(lines.len(), 5 + ALL_STACKS_FRAMES + 3);
+        assert_eq!(lines[lines.len() - 2..], ["Thread 2 (id 3) unknown", "  (no frames)"]);
+        assert_eq!(debugger.active_thread(), Some(ThreadId::from(2)));
+
+        state.borrow_mut().stopped = false;
+        assert!(all_stacks(&LiveInspector::new(debugger.as_mut()), AddressFormat::Full16).is_err());
+    }
+}
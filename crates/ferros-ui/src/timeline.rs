@@ -0,0 +1,339 @@
+//! Timeline navigation: kind filter, search and manual scrolling
+//!
+//! The Timeline view follows the newest entry until the user scrolls. From
+//! then on [`TimelineView::selected`] pins an entry and the title counts what
+//! arrived since. Moving past the bottom picks the tail back up.
+//!
+//! Everything that decides what is shown is a pure function over the log so it
+//! can be tested without a terminal:
+//!
+//! - [`visible_entries`] - entries the kind filter keeps, in log order
+//! - [`matching_entries`] - visible entries whose message contains the query
+//! - [`step_match`] - the next or previous match, wrapping around
+//! - [`scroll`] - move the selection by rows, or back to following
+//! - [`window`] - rows to draw for a viewport
+//! - [`match_ranges`] - byte ranges to highlight in a message
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::app::{TimelineEntry, TimelineEntryKind};
+
+/// Which entry kinds the Timeline shows (`f` cycles)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimelineFilter
+{
+    #[default]
+    All,
+    /// Anything that stopped the target: stops, breakpoint hits and signals
+    Stops,
+    Breakpoints,
+    Signals,
+    /// Errors and warnings
+    Errors,
+}
+
+impl TimelineFilter
+{
+    /// The filter `f` switches to
+    #[must_use]
+    pub fn next(self) -> Self
+    {
+        match self {
+            Self::All => Self::Stops,
+            Self::Stops => Self::Breakpoints,
+            Self::Breakpoints => Self::Signals,
+            Self::Signals => Self::Errors,
+            Self::Errors => Self::All,
+        }
+    }
+
+    /// Name shown in the Timeline title
+    #[must_use]
+    pub fn label(self) -> &'static str
+    {
+        match self {
+            Self::All => "All",
+            Self::Stops => "Stops",
+            Self::Breakpoints => "Breakpoints",
+            Self::Signals => "Signals",
+            Self::Errors => "Errors",
+        }
+    }
+
+    /// Whether entries of `kind` pass the filter
+    #[must_use]
+    pub fn matches(self, kind: TimelineEntryKind) -> bool
+    {
+        match self {
+            Self::All => true,
+            Self::Stops => matches!(
+                kind,
+                TimelineEntryKind::Stop
+                    | TimelineEntryKind::BreakpointHit
+                    | TimelineEntryKind::Signal
+                    | TimelineEntryKind::Panic
+            ),
+            Self::Breakpoints => kind == TimelineEntryKind::BreakpointHit,
+            Self::Signals => matches!(kind, TimelineEntryKind::Signal | TimelineEntryKind::Panic),
+            Self::Errors => matches!(kind, TimelineEntryKind::Error | TimelineEntryKind::Warning),
+        }
+    }
+}
+
+/// Navigation state of the Timeline view
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::large_stack_arrays)]
+pub struct TimelineView
+{
+    /// Kinds shown
+    pub filter: TimelineFilter,
+    /// Committed search; empty means no search
+    pub query: String,
+    /// Query being typed after `/`, while the prompt is open
+    pub search_input: Option<String>,
+    /// Selected entry as an index into the log, or `None` while following the tail
+    pub selected: Option<usize>,
+    /// Visible entries logged since the user stopped following
+    pub unseen: usize,
+    /// Rows the last draw had room for; `PageUp`/`PageDown` move by this much
+    pub page_rows: usize,
+}
+
+impl TimelineView
+{
+    /// Whether the view is following the newest entry
+    #[must_use]
+    pub fn is_following(&self) -> bool
+    {
+        self.selected.is_none()
+    }
+
+    /// Track an entry of `kind` appended to the log; `evicted` when the oldest was dropped to make room
+    pub fn on_push(&mut self, kind: TimelineEntryKind, evicted: bool)
+    {
+        let Some(selected) = self.selected else {
+            return;
+        };
+        if evicted {
+            self.selected = Some(selected.saturating_sub(1));
+        }
+        if self.filter.matches(kind) {
+            self.unseen += 1;
+        }
+    }
+
+    /// Select `entry` (or follow the tail with `None`); following clears the unseen count
+    pub fn select(&mut self, entry: Option<usize>)
+    {
+        if entry.is_none() {
+            self.unseen = 0;
+        }
+        self.selected = entry;
+    }
+}
+
+/// Indices of the entries `filter` keeps, oldest first
+#[must_use]
+pub fn visible_entries(entries: &VecDeque<TimelineEntry>, filter: TimelineFilter) -> Vec<usize>
+{
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| filter.matches(entry.kind))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Indices among `visible` whose message contains `query`, ignoring ASCII case
+#[must_use]
+pub fn matching_entries(entries: &VecDeque<TimelineEntry>, visible: &[usize], query: &str) -> Vec<usize>
+{
+    if query.is_empty() {
+        return Vec::new();
+    }
+    visible
+        .iter()
+        .copied()
+        .filter(|&index| !match_ranges(&entries[index].message, query).is_empty())
+        .collect()
+}
+
+/// The match after (`forward`) or before `from`, wrapping around
+///
+/// From the tail (`from` is `None`) the next match wraps to the oldest and the
+/// previous one is the newest.
+#[must_use]
+pub fn step_match(matches: &[usize], from: Option<usize>, forward: bool) -> Option<usize>
+{
+    let (&first, &last) = (matches.first()?, matches.last()?);
+    match (from, forward) {
+        (None, true) => Some(first),
+        (None, false) => Some(last),
+        (Some(from), true) => matches.iter().copied().find(|&index| index > from).or(Some(first)),
+        (Some(from), false) => matches.iter().rev().copied().find(|&index| index < from).or(Some(last)),
+    }
+}
+
+/// Row of `selected` among `visible`, or of the first visible entry after it if it's filtered out
+fn row_of(visible: &[usize], selected: usize) -> usize
+{
+    visible
+        .partition_point(|&index| index < selected)
+        .min(visible.len().saturating_sub(1))
+}
+
+/// Move the selection `delta` rows (negative is older)
+///
+/// From the tail the first step up selects the newest row. Moving down past
+/// the newest row returns `None`, which means following the tail again.
+#[must_use]
+pub fn scroll(visible: &[usize], selected: Option<usize>, delta: isize) -> Option<usize>
+{
+    let last = visible.len().checked_sub(1)?;
+    let row = match selected {
+        Some(selected) => row_of(visible, selected),
+        None if delta < 0 => last + 1,
+        None => return None,
+    };
+    let target = row.checked_add_signed(delta).unwrap_or(0);
+    if target > last { None } else { Some(visible[target]) }
+}
+
+/// Rows of `visible` to draw in `viewport` rows, with the selected entry's row (if any)
+///
+/// Following shows the tail; otherwise the selection is kept roughly centred.
+#[must_use]
+pub fn window(visible: &[usize], viewport: usize, selected: Option<usize>) -> (Range<usize>, Option<usize>)
+{
+    let len = visible.len();
+    let viewport = viewport.max(1);
+    let Some(selected) = selected.filter(|_| len > 0) else {
+        return (len.saturating_sub(viewport)..len, None);
+    };
+    let row = row_of(visible, selected);
+    let start = row.saturating_sub(viewport / 2).min(len.saturating_sub(viewport));
+    (start..(start + viewport).min(len), Some(row))
+}
+
+/// Byte ranges of `query` in `text`, ignoring ASCII case
+#[must_use]
+pub fn match_ranges(text: &str, query: &str) -> Vec<Range<usize>>
+{
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let haystack = text.to_ascii_lowercase();
+    let needle = query.to_ascii_lowercase();
+    haystack
+        .match_indices(&needle)
+        .map(|(start, _)| start..start + needle.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn log(entries: &[(TimelineEntryKind, &str)]) -> VecDeque<TimelineEntry>
+    {
+        entries
+            .iter()
+            .map(|&(kind, message)| TimelineEntry::new(kind, message.to_string()))
+            .collect()
+    }
+
+    #[allow(clippy::large_stack_arrays)]
+    fn sample() -> VecDeque<TimelineEntry>
+    {
+        log(&[
+            (TimelineEntryKind::BreakpointHit, "Hit breakpoint 3 at 0x1000"),
+            (TimelineEntryKind::Resume, "Target resumed execution"),
+            (TimelineEntryKind::Output, "stdout: hello"),
+            (TimelineEntryKind::Signal, "Received signal 11"),
+            (TimelineEntryKind::Error, "Failed to capture checkpoint"),
+            (TimelineEntryKind::BreakpointHit, "Hit Breakpoint 3 at 0x1000"),
+            (TimelineEntryKind::Stop, "Suspended"),
+        ])
+    }
+
+    #[test]
+    fn filters_keep_chronological_order()
+    {
+        let entries = sample();
+        assert_eq!(visible_entries(&entries, TimelineFilter::All), (0..7).collect::<Vec<_>>());
+        assert_eq!(visible_entries(&entries, TimelineFilter::Stops), vec![0, 3, 5, 6]);
+        assert_eq!(visible_entries(&entries, TimelineFilter::Breakpoints), vec![0, 5]);
+        assert_eq!(visible_entries(&entries, TimelineFilter::Signals), vec![3]);
+        assert_eq!(visible_entries(&entries, TimelineFilter::Errors), vec![4]);
+
+        let mut filter = TimelineFilter::All;
+        for _ in 0..5 {
+            filter = filter.next();
+        }
+        assert_eq!(filter, TimelineFilter::All);
+    }
+
+    #[test]
+    fn search_wraps_and_respects_the_filter()
+    {
+        let entries = sample();
+        let all = visible_entries(&entries, TimelineFilter::All);
+        let matches = matching_entries(&entries, &all, "breakpoint 3");
+        assert_eq!(matches, vec![0, 5]);
+        assert_eq!(step_match(&matches, None, true), Some(0));
+        assert_eq!(step_match(&matches, None, false), Some(5));
+        assert_eq!(step_match(&matches, Some(0), true), Some(5));
+        assert_eq!(step_match(&matches, Some(5), true), Some(0));
+        assert_eq!(step_match(&matches, Some(3), false), Some(0));
+        assert_eq!(step_match(&matches, Some(0), false), Some(5));
+        assert_eq!(step_match(&[], None, true), None);
+
+        let errors = visible_entries(&entries, TimelineFilter::Errors);
+        assert!(matching_entries(&entries, &errors, "breakpoint").is_empty());
+        assert!(matching_entries(&entries, &all, "").is_empty());
+        assert_eq!(
+            match_ranges("Hit BREAKPOINT at breakpoint", "breakpoint"),
+            vec![4..14, 18..28]
+        );
+    }
+
+    #[test]
+    fn scrolling_leaves_and_rejoins_the_tail()
+    {
+        let visible = vec![0, 3, 5, 6];
+        assert_eq!(scroll(&visible, None, -1), Some(6));
+        assert_eq!(scroll(&visible, None, -10), Some(0));
+        assert_eq!(scroll(&visible, None, 1), None);
+        assert_eq!(scroll(&visible, Some(3), 1), Some(5));
+        assert_eq!(scroll(&visible, Some(6), 1), None);
+        assert_eq!(scroll(&visible, Some(0), -1), Some(0));
+        // A selection hidden by the filter moves relative to the next visible entry
+        assert_eq!(scroll(&visible, Some(4), -1), Some(3));
+        assert_eq!(scroll(&[], None, -1), None);
+
+        assert_eq!(window(&visible, 2, None), (2..4, None));
+        assert_eq!(window(&visible, 2, Some(0)), (0..2, Some(0)));
+        assert_eq!(window(&visible, 3, Some(5)), (1..4, Some(2)));
+        assert_eq!(window(&visible, 10, Some(6)), (0..4, Some(3)));
+    }
+
+    #[test]
+    fn unseen_entries_count_only_while_scrolled_back()
+    {
+        let mut view = TimelineView::default();
+        view.on_push(TimelineEntryKind::Stop, false);
+        assert_eq!(view.unseen, 0);
+
+        view.filter = TimelineFilter::Errors;
+        view.select(Some(4));
+        view.on_push(TimelineEntryKind::Error, true);
+        view.on_push(TimelineEntryKind::Output, false);
+        assert_eq!((view.selected, view.unseen), (Some(3), 1));
+
+        view.select(None);
+        assert!(view.is_following());
+        assert_eq!(view.unseen, 0);
+    }
+}
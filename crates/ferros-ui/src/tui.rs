@@ -1,13 +1,20 @@
 //! Terminal User Interface initialization and management
 
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Stdout};
+use std::io::{self, Stdout, Write};
 use std::panic;
+use std::path::PathBuf;
 
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::execute;
 use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode};
 use ferros_core::Debugger;
+use ferros_core::assertions::{AssertionSet, Violation};
+use ferros_core::output::{DEFAULT_DRAIN_DEADLINE, LineSink, OutputSink};
+use ferros_core::session::SessionRecorder;
+use ferros_core::transcript::{Transcript, TranscriptStream};
+use ferros_core::types::{AddressFormat, LaunchOptions};
+use ferros_utils::config::Config;
 use ferros_utils::{info, warn};
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
@@ -15,6 +22,7 @@ use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
 use crate::app::{App, ProcessOutputSource};
+use crate::config::UserConfig;
 use crate::event::Event;
 
 /// Terminal User Interface for Ferros debugger
@@ -24,6 +32,17 @@ use crate::event::Event;
 pub struct Tui
 {
     terminal: Terminal<CrosstermBackend<Stdout>>,
+    session_summary: Option<(SessionRecorder, PathBuf)>,
+    session_file: Option<PathBuf>,
+    breakpoint_file: Option<PathBuf>,
+    assertions: Option<AssertionSet>,
+    violations: Vec<Violation>,
+    address_format: AddressFormat,
+    transcript: Option<Transcript>,
+    config: Option<Config>,
+    user_config: Option<UserConfig>,
+    first_run_hint: bool,
+    launch: Option<(String, LaunchOptions)>,
 }
 
 impl Tui
@@ -56,30 +75,182 @@ impl Tui
             original_hook(panic_info);
         }));
 
-        Ok(Self { terminal })
+        Ok(Self {
+            terminal,
+            session_summary: None,
+            session_file: None,
+            breakpoint_file: None,
+            assertions: None,
+            violations: Vec::new(),
+            address_format: AddressFormat::default(),
+            transcript: None,
+            config: None,
+            user_config: None,
+            first_run_hint: false,
+            launch: None,
+        })
+    }
+
+    /// Write a JSON session summary to `path` when the TUI exits
+    ///
+    /// The recorder is fed every debugger event the TUI sees and is finished
+    /// before the target is killed or detached, so breakpoint hit counts are
+    /// still available.
+    #[must_use]
+    pub fn with_session_summary(mut self, recorder: SessionRecorder, path: PathBuf) -> Self
+    {
+        self.session_summary = Some((recorder, path));
+        self
+    }
+
+    /// Restore and persist pinned values in the per-binary session file at `path`
+    #[must_use]
+    pub fn with_session_file(mut self, path: PathBuf) -> Self
+    {
+        self.session_file = Some(path);
+        self
+    }
+
+    /// Restore breakpoints saved at `path` and save them there when the TUI exits
+    #[must_use]
+    pub fn with_breakpoint_file(mut self, path: PathBuf) -> Self
+    {
+        self.breakpoint_file = Some(path);
+        self
+    }
+
+    /// Check stops against `assertions`, whose breakpoints are already installed
+    ///
+    /// Violations are logged in the timeline as they happen and are available
+    /// from [`violations`](Self::violations) once [`run`](Self::run) returns.
+    #[must_use]
+    pub fn with_assertions(mut self, assertions: AssertionSet) -> Self
+    {
+        self.assertions = Some(assertions);
+        self
+    }
+
+    /// Start with tables printing addresses in `format`; `x` cycles it while running
+    #[must_use]
+    pub fn with_address_format(mut self, format: AddressFormat) -> Self
+    {
+        self.address_format = format;
+        self
+    }
+
+    /// Record captured output and debugger events into `transcript`
+    ///
+    /// The Output view's timestamps (`t`) come from its records. The
+    /// transcript is finished when the TUI exits.
+    #[must_use]
+    pub fn with_transcript(mut self, transcript: Transcript) -> Self
+    {
+        self.transcript = Some(transcript);
+        self
+    }
+
+    /// Start from the defaults in `config.toml` (layout, stack depth, source paths)
+    #[must_use]
+    pub fn with_config(mut self, config: Config) -> Self
+    {
+        self.config = Some(config);
+        self
+    }
+
+    /// Apply settings and key bindings loaded from `~/.ferros`, showing any problems in a banner
+    #[must_use]
+    pub fn with_user_config(mut self, config: UserConfig) -> Self
+    {
+        self.user_config = Some(config);
+        self
+    }
+
+    /// Let `:restart` (Ctrl+R) launch `program` again with `options`
+    ///
+    /// `program` is the path the target was launched from and `options`
+    /// include its `argv[0]`, as passed to [`Debugger::launch_with_options`].
+    #[must_use]
+    pub fn with_launch(mut self, program: String, options: LaunchOptions) -> Self
+    {
+        self.launch = Some((program, options));
+        self
+    }
+
+    /// Mention `ferros init` in the status line when the TUI opens
+    #[must_use]
+    pub fn with_first_run_hint(mut self) -> Self
+    {
+        self.first_run_hint = true;
+        self
+    }
+
+    /// Assertions that failed during the last [`run`](Self::run)
+    #[must_use]
+    pub fn violations(&self) -> &[Violation]
+    {
+        &self.violations
+    }
+
+    /// Hand what the `with_*` builders were given to `app`
+    fn configure(&mut self, app: &mut App)
+    {
+        app.address_format = self.address_format;
+        if let Some((recorder, path)) = self.session_summary.take() {
+            app.enable_session_summary(recorder, path);
+        }
+        if let Some(path) = self.session_file.take() {
+            app.enable_session_file(path);
+        }
+        if let Some(path) = self.breakpoint_file.take() {
+            app.enable_breakpoint_file(path);
+        }
+        if let Some(assertions) = self.assertions.take() {
+            app.enable_assertions(assertions);
+        }
+        if let Some(transcript) = self.transcript.take() {
+            app.enable_transcript(transcript);
+        }
+        if let Some(config) = self.user_config.take() {
+            app.enable_user_config(config);
+        }
+        if self.first_run_hint {
+            app.show_first_run_hint();
+        }
+        if let Some((program, options)) = self.launch.take() {
+            app.enable_restart(program, options);
+        }
     }
 
     /// Run the TUI event loop
     ///
     /// This starts the interactive debugger interface and handles user input
-    /// until the user quits.
+    /// until the user quits. A `debugger` that isn't attached yet opens the
+    /// process picker; the event pump starts once a process is picked.
     ///
     /// # Errors
     ///
     /// Returns an error if terminal drawing fails or terminal restoration fails
     pub async fn run(&mut self, debugger: Box<dyn Debugger>, pid: Option<u32>, was_launched: bool) -> io::Result<()>
     {
-        use std::io::Write;
-
         if let Some(pid) = pid {
             info!("Ferros TUI started (PID: {}, launched: {})", pid, was_launched);
         } else {
             info!("Ferros TUI started");
         }
 
-        let mut app = App::new(debugger, pid, was_launched);
+        let config = self.config.take().unwrap_or_default();
+        let mut app = App::new(debugger, pid, was_launched, &config);
+        self.configure(&mut app);
+        // Parsing a large binary's DWARF must not freeze the UI; stacks refresh once it's done
+        if let Err(err) = app.debugger.set_background_symbol_loading(true) {
+            warn!("Loading symbols on the UI thread: {err}");
+        }
+        if !app.debugger.is_attached() {
+            app.open_process_picker();
+        }
         let mut event_handler = crate::event::EventHandler::new();
-        let mut background_tasks = spawn_background_tasks(&mut app, event_handler.sender());
+        let mut background_tasks = Vec::new();
+        let mut pumping = false;
 
         loop {
             // Check if we should quit before drawing
@@ -87,7 +258,24 @@ impl Tui
                 break;
             }
 
-            self.terminal.draw(|frame| crate::ui::draw(frame, &mut app))?;
+            // Start forwarding events and output once there is a target
+            if !pumping && app.process_picker.is_none() {
+                background_tasks = spawn_background_tasks(&mut app, event_handler.sender());
+                pumping = true;
+            }
+            // A restarted target has new pipes
+            if std::mem::take(&mut app.process_restarted) {
+                background_tasks.extend(spawn_process_io(&mut app, event_handler.sender()));
+            }
+
+            if let Err(e) = self.terminal.draw(|frame| crate::ui::draw(frame, &mut app)) {
+                ferros_utils::report_nonfatal(&e, "drawing the TUI");
+                return Err(e);
+            }
+            if app.stop_emphasis.take_bell() {
+                let backend = self.terminal.backend_mut();
+                let _ = backend.write_all(b"\x07").and_then(|()| backend.flush());
+            }
 
             // Check again after drawing
             if app.should_quit {
@@ -105,11 +293,11 @@ impl Tui
                     Event::Tick => {
                         app.tick();
                     }
-                    Event::ProcessOutput { source, line } => {
-                        app.push_process_output(source, &line.clone());
+                    Event::ProcessOutput { source, line, at } => {
+                        app.push_process_output(source, &line, at);
                     }
-                    Event::Debugger(debugger_event) => {
-                        app.handle_debugger_event(&debugger_event);
+                    Event::Debugger(envelope) => {
+                        app.handle_debugger_envelope(&envelope, std::time::Instant::now());
                     }
                 },
                 Ok(None) => {
@@ -134,24 +322,29 @@ impl Tui
         // Stop the event handler to allow the program to exit
         event_handler.stop();
 
-        // Don't wait indefinitely for output tasks - use timeout
-        // This prevents blocking if tasks are stuck reading from pipes
-        let output_handles = std::mem::take(&mut background_tasks);
+        // The output pumps wake up and exit even if the target is silent
+        app.debugger.stop_output_pumps(DEFAULT_DRAIN_DEADLINE);
+        // The stdin writer exits once its sender is gone
+        app.process_stdin = None;
+
+        // Don't wait indefinitely for the event forwarder - use timeout
+        let handles = std::mem::take(&mut background_tasks);
         let timeout_result = tokio::time::timeout(std::time::Duration::from_millis(100), async {
-            for handle in output_handles {
+            for handle in handles {
                 if let Err(e) = handle.await {
-                    warn!("Process output task exited with error: {e}");
+                    warn!("Background task exited with error: {e}");
                 }
             }
         })
         .await;
         if timeout_result.is_err() {
-            warn!("Output tasks didn't finish in time, dropping");
+            warn!("Background tasks didn't finish in time, dropping");
         }
 
         // Cleanup after terminal is restored (async, non-blocking)
         // User can see what's happening in normal terminal mode
         app.cleanup().await;
+        self.violations = app.assertions.violations().to_vec();
 
         // Flush stdout to ensure any messages are visible
         let _ = std::io::stdout().flush();
@@ -164,7 +357,7 @@ impl Tui
             if let Some(pid) = app.pid {
                 println!("\nDebugger detached. Process {pid} was terminated.");
             }
-        } else {
+        } else if pumping {
             println!("\nDebugger detached from process.");
         }
 
@@ -190,41 +383,82 @@ impl Tui
 
 fn spawn_background_tasks(app: &mut App, sender: mpsc::Sender<Event>) -> Vec<JoinHandle<()>>
 {
-    let mut handles = Vec::new();
+    let mut handles = spawn_process_io(app, sender.clone());
 
-    if let Some(stdout) = app.debugger.take_process_stdout() {
-        handles.push(spawn_output_reader(stdout, ProcessOutputSource::Stdout, sender.clone()));
+    if let Some(events) = app.debugger.take_event_receiver() {
+        handles.push(spawn_debugger_event_forwarder(events, sender));
     }
 
-    if let Some(stderr) = app.debugger.take_process_stderr() {
-        handles.push(spawn_output_reader(stderr, ProcessOutputSource::Stderr, sender.clone()));
+    handles
+}
+
+/// Pump the launched target's output into events and connect its stdin to the Output view
+fn spawn_process_io(app: &mut App, sender: mpsc::Sender<Event>) -> Vec<JoinHandle<()>>
+{
+    let mut handles = Vec::new();
+
+    let stdout = output_sink(ProcessOutputSource::Stdout, app.transcript.as_ref(), sender.clone());
+    let stderr = output_sink(ProcessOutputSource::Stderr, app.transcript.as_ref(), sender);
+    if let Err(e) = app.debugger.pump_process_output(stdout, stderr) {
+        warn!("Failed to start process output pumps: {e}");
     }
 
-    if let Some(events) = app.debugger.take_event_receiver() {
-        handles.push(spawn_debugger_event_forwarder(events, sender));
+    if let Some(stdin) = app.debugger.take_process_stdin() {
+        let (writer, handle) = spawn_stdin_writer(stdin);
+        app.process_stdin = Some(writer);
+        handles.push(handle);
     }
 
     handles
 }
 
-fn spawn_output_reader(file: File, source: ProcessOutputSource, sender: mpsc::Sender<Event>) -> JoinHandle<()>
+/// Forward each captured line as an [`Event::ProcessOutput`] until the event loop goes away
+///
+/// With a transcript, lines are recorded first and carry their record's timestamp.
+fn output_sink(
+    source: ProcessOutputSource,
+    transcript: Option<&Transcript>,
+    sender: mpsc::Sender<Event>,
+) -> Box<dyn OutputSink>
 {
-    tokio::task::spawn_blocking(move || {
-        let reader = BufReader::new(file);
-        for line in reader.lines() {
-            match line {
-                Ok(line) => {
-                    if sender.blocking_send(Event::ProcessOutput { source, line }).is_err() {
-                        break;
-                    }
-                }
-                Err(err) => {
-                    warn!("Failed to read process output: {err}");
-                    break;
-                }
+    let Some(transcript) = transcript else {
+        return Box::new(LineSink::new(move |line| {
+            sender.blocking_send(Event::ProcessOutput { source, line, at: None }).is_ok()
+        }));
+    };
+    let stream = match source {
+        ProcessOutputSource::Stdout => TranscriptStream::Stdout,
+        ProcessOutputSource::Stderr => TranscriptStream::Stderr,
+        ProcessOutputSource::Stdin => TranscriptStream::Stdin,
+    };
+    transcript.sink(stream, move |record| {
+        let at = Some(record.elapsed());
+        sender
+            .blocking_send(Event::ProcessOutput {
+                source,
+                line: record.text,
+                at,
+            })
+            .is_ok()
+    })
+}
+
+/// Write what the Output view sends to the target's stdin, off the event loop
+///
+/// The pipe is closed once every sender is dropped, or the target stops reading.
+fn spawn_stdin_writer(stdin: File) -> (mpsc::UnboundedSender<Vec<u8>>, JoinHandle<()>)
+{
+    let (writer, mut received) = mpsc::unbounded_channel::<Vec<u8>>();
+    let handle = tokio::task::spawn_blocking(move || {
+        let mut stdin = stdin;
+        while let Some(bytes) = received.blocking_recv() {
+            if let Err(e) = stdin.write_all(&bytes) {
+                warn!("Stopped writing to the target's stdin: {e}");
+                break;
             }
         }
-    })
+    });
+    (writer, handle)
 }
 
 fn spawn_debugger_event_forwarder(
@@ -233,8 +467,8 @@ fn spawn_debugger_event_forwarder(
 ) -> JoinHandle<()>
 {
     tokio::task::spawn_blocking(move || {
-        while let Ok(event) = receiver.recv() {
-            if sender.blocking_send(Event::Debugger(event)).is_err() {
+        while let Ok(envelope) = receiver.recv() {
+            if sender.blocking_send(Event::Debugger(envelope)).is_err() {
                 break;
             }
         }
@@ -7,32 +7,87 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 
 use crate::app::{App, ViewMode};
+use crate::layout;
 
 /// Draw the UI
 pub fn draw(frame: &mut Frame, app: &mut App)
 {
+    if layout::is_too_small(frame.area()) {
+        draw_too_small(frame, frame.area());
+        return;
+    }
+    let compact = frame.area().height < layout::COMPACT_HEIGHT;
+
+    let now = std::time::Instant::now();
+    app.stop_emphasis.expire(now);
+    // A popup has focus; flashing or covering the screen behind it would only distract
+    let modal = app.modal_open();
+    let flash = if modal {
+        app.stop_emphasis.cancel_flash();
+        false
+    } else {
+        app.stop_emphasis.next_frame_flashes()
+    };
+
     // Use boxed slice to avoid large stack array warning
-    // Make footer taller if there's an error or info message to display
-    let footer_height = if app.error_message.is_some() || app.info_message.is_some() {
+    // Make footer taller if there's an error or info message to display, unless the screen is short
+    let has_message = app.error_message.is_some() || app.info_message.is_some() || app.resume_prompt.is_some();
+    let footer_height = if has_message && !compact {
         5 // Extra space for wrapped messages
     } else {
         3
     };
+    let header_height = if compact { 1 } else { 3 };
+    let banner_height = u16::from(app.config_banner.is_some());
     
     let constraints: Box<[Constraint]> = Box::new([
-        Constraint::Length(3), // Header
-        Constraint::Min(0),    // Main content
+        Constraint::Length(header_height), // Header
+        Constraint::Length(banner_height), // Config problems, until a key is pressed
+        Constraint::Min(0),                // Main content
         Constraint::Length(footer_height), // Footer/status (taller if error)
     ]);
     let chunks = Layout::vertical(constraints).split(frame.area());
 
-    draw_header(frame, chunks[0], app);
-    draw_main_content(frame, chunks[1], app);
-    draw_footer(frame, chunks[2], app);
+    draw_header(frame, chunks[0], app, compact, flash);
+    if let Some(banner) = &app.config_banner {
+        draw_config_banner(frame, chunks[1], banner);
+    }
+    draw_main_content(frame, chunks[2], app);
+    if !modal {
+        crate::widgets::draw_stop_toasts(frame, chunks[2], app, now);
+    }
+    draw_footer(frame, chunks[3], app, compact);
+}
+
+/// Draw the one-line summary of config problems found at startup
+fn draw_config_banner(frame: &mut Frame, area: Rect, banner: &str)
+{
+    let line = Line::from(Span::styled(
+        format!("⚠ {banner}"),
+        Style::default().fg(Color::Black).bg(Color::Yellow),
+    ));
+    frame.render_widget(Paragraph::new(line).style(Style::default().bg(Color::Yellow)), area);
 }
 
-/// Draw the header bar
-fn draw_header(frame: &mut Frame, area: Rect, app: &App)
+/// Draw the notice shown instead of the views when the terminal is below the minimum size
+fn draw_too_small(frame: &mut Frame, area: Rect)
+{
+    let lines = vec![
+        Line::from(Span::styled(
+            "Terminal too small",
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("need {}x{}", layout::MIN_WIDTH, layout::MIN_HEIGHT)),
+        Line::from(format!("have {}x{}", area.width, area.height)),
+    ];
+    let notice = Paragraph::new(lines)
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    frame.render_widget(notice, layout::centered_rect(area, area.width, 3));
+}
+
+/// Draw the header bar; `compact` drops the border to save two lines, `flash` inverts it
+fn draw_header(frame: &mut Frame, area: Rect, app: &App, compact: bool, flash: bool)
 {
     let title = if app.debugger.is_attached() {
         if let Some(pid) = app.pid {
@@ -44,9 +99,41 @@ fn draw_header(frame: &mut Frame, area: Rect, app: &App)
         "Ferros Debugger - Not Attached".to_string()
     };
 
-    let header = Paragraph::new(title)
-        .block(Block::default().borders(Borders::ALL).title("Ferros"))
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+    // History mode and a vanished target must be unmistakable: nothing shown is the live target
+    let (title, crash_color) = match app.crash_summary() {
+        Some(crash) => (format!("{title} - CRASH: {crash}"), Color::Red),
+        None => (title, Color::Cyan),
+    };
+    let historical = app.history.position().map(|(index, count)| format!("stop {index}/{count}"));
+    let (title, color) = match (app.target_gone, historical) {
+        _ if app.target_exit.is_some() => (format!("Ferros Debugger - {}", app.status_message()), Color::Yellow),
+        (Some(pid), _) => (
+            format!("Ferros Debugger - Process {pid} is gone (exited or killed outside ferros)"),
+            Color::Red,
+        ),
+        (None, Some(viewing)) => (
+            format!("{title} - HISTORY: {viewing} (read-only, :live to return)"),
+            Color::Magenta,
+        ),
+        (None, None) => (title, crash_color),
+    };
+
+    let title = match app.debugger.pending_symbol_loads() {
+        0 => title,
+        1 => format!("{title} - loading symbols for 1 image…"),
+        images => format!("{title} - loading symbols for {images} images…"),
+    };
+
+    let block = if compact {
+        Block::default()
+    } else {
+        Block::default().borders(Borders::ALL).title("Ferros")
+    };
+    let mut style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+    if flash {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    let header = Paragraph::new(title).block(block).style(style);
 
     frame.render_widget(header, area);
 }
@@ -54,6 +141,12 @@ fn draw_header(frame: &mut Frame, area: Rect, app: &App)
 /// Draw the main content area
 fn draw_main_content(frame: &mut Frame, area: Rect, app: &mut App)
 {
+    // Without a target there is nothing to show but the processes to pick from
+    if app.process_picker.is_some() {
+        crate::widgets::draw_process_picker(frame, area, app);
+        return;
+    }
+
     // Draw command palette overlay if active
     if app.command_palette_active {
         crate::widgets::draw_command_palette(frame, area, app);
@@ -66,45 +159,141 @@ fn draw_main_content(frame: &mut Frame, area: Rect, app: &mut App)
         return;
     }
 
+    // Draw the region checkpoint diff if one is open
+    if app.range_diffs.is_some() {
+        crate::widgets::draw_range_diff(frame, area, app);
+        return;
+    }
+
+    // Draw the notes list if it is open
+    if app.notes_list.is_some() {
+        crate::widgets::draw_notes(frame, area, app);
+        return;
+    }
+
+    // Draw the `:find` hits if they are open
+    if app.memory_search.is_some() {
+        crate::widgets::draw_memory_search(frame, area, app);
+        return;
+    }
+
+    // Draw the `:where` summary if it is open
+    if app.where_summary.is_some() {
+        crate::widgets::draw_where(frame, area, app);
+        return;
+    }
+
+    // Draw the `:all-stacks` report if it is open
+    if app.all_stacks.is_some() {
+        crate::widgets::draw_all_stacks(frame, area, app);
+        return;
+    }
+
+    // Draw the advice for a permission error if it was expanded
+    if app.permission_advice_open {
+        crate::widgets::draw_permission_advice(frame, area, app);
+        return;
+    }
+
+    // Keep pinned values visible in every view; the Overview has its own section
+    let area = if app.pins.is_empty() || matches!(app.view_mode, ViewMode::Overview | ViewMode::Help) {
+        area
+    } else {
+        let constraints: Box<[Constraint]> = Box::new([Constraint::Length(1), Constraint::Min(0)]);
+        let chunks = Layout::vertical(constraints).split(area);
+        crate::widgets::draw_pin_strip(frame, chunks[0], app);
+        chunks[1]
+    };
+
     // Draw main content based on view mode and layout
     match app.view_mode {
         ViewMode::Overview => crate::widgets::draw_overview(frame, area, app),
         ViewMode::Registers => crate::widgets::draw_registers(frame, area, app),
         ViewMode::Threads => crate::widgets::draw_threads(frame, area, app),
         ViewMode::MemoryRegions => crate::widgets::draw_memory_regions(frame, area, app),
+        ViewMode::Memory => crate::widgets::draw_memory(frame, area, app),
+        ViewMode::Modules => crate::widgets::draw_modules(frame, area, app),
         ViewMode::Output => crate::widgets::draw_output(frame, area, app),
         ViewMode::Source => crate::widgets::draw_source_view(frame, area, app),
         ViewMode::Stack => crate::widgets::draw_stack_view(frame, area, app),
         ViewMode::Timeline => crate::widgets::draw_timeline(frame, area, app),
+        ViewMode::Disassembly => crate::widgets::draw_disassembly(frame, area, app),
+        ViewMode::History => crate::widgets::draw_history(frame, area, app),
         ViewMode::Help => crate::widgets::draw_help(frame, area, app),
     }
 }
 
-/// Draw the footer with help text
-fn draw_footer(frame: &mut Frame, area: Rect, app: &App)
+/// Help text for the footer of the current view
+fn footer_help(app: &App) -> &'static str
 {
-    let help_text = match app.view_mode {
+    if app.process_picker.is_some() {
+        return "Type:Filter | ↑/↓:Select Enter:Attach | Ctrl+R:Refresh | Esc:Clear filter/Quit";
+    }
+    if app.stdin_input.is_some() {
+        return "Type:Input | Enter:Send line | Ctrl+D:Close stdin | Esc:Stop typing";
+    }
+    match app.view_mode {
         ViewMode::Overview => {
-            "1:Overview 2:Regs 3:Threads 4:Memory 5:Output 6:Source 7:Stack 8:Timeline | :Cmd | s:Suspend r:Resume \
-             b:Breakpoint B:EditBP l:Layout Esc:Quit"
+            "1:Overview 2:Regs 3:Threads 4:Memory 5:Output 6:Source 7:Stack 8:Timeline 0:Disasm | :Cmd | s:Suspend r:Resume \
+             b:Breakpoint B:EditBP w:Where l:Layout x:Addr Esc:Quit"
         }
-        ViewMode::Registers | ViewMode::Threads | ViewMode::MemoryRegions => {
-            "↑/↓:Navigate | 1-8:Switch View | :Cmd | s:Suspend r:Resume b:Breakpoint | Esc:Quit"
+        ViewMode::MemoryRegions => {
+            "↑/↓:Navigate | 1-8:Switch View | :Cmd | s:Suspend r:Resume b:Breakpoint x:Addr | Esc:Quit"
+        }
+        ViewMode::Modules => "↑/↓:Navigate | 1-8:Switch View | :Cmd | s:Suspend r:Resume x:Addr | Esc:Quit",
+        ViewMode::Registers => {
+            "↑/↓:Navigate Enter:Follow | 1-8:Switch View | :Cmd | s:Suspend r:Resume b:Breakpoint x:Addr | Esc:Quit"
+        }
+        ViewMode::Memory => {
+            "↑/↓:Row PgUp/PgDn:Page ←/→:Word Enter:Follow Bksp/[:Back ]:Forward | :mem <addr> | 1-8:Switch View | :Cmd | \
+             Esc:Quit"
+        }
+        ViewMode::Threads => {
+            "↑/↓:Navigate Enter:Stack Space:Mark a:MarkAll | s/r:Suspend/Resume marked threads | 1-8:Switch View | :Cmd | \
+             Esc:Quit"
+        }
+        ViewMode::Output => {
+            "↑/↓:Scroll | t:Timestamps i:Type to stdin | 1-8:Switch View | :Cmd | s:Suspend r:Resume | Esc:Quit"
+        }
+        ViewMode::Source if app.selected_frame_without_debug_info().is_some() => {
+            "↑/↓:Scroll | 1-8:Switch View | :Cmd | b:n/a (no debug info) | Esc:Quit"
         }
-        ViewMode::Output => "↑/↓:Scroll | 1-8:Switch View | :Cmd | s:Suspend r:Resume | Esc:Quit",
         ViewMode::Source => "↑/↓:Scroll | 1-8:Switch View | :Cmd | b:ToggleBP | Esc:Quit",
-        ViewMode::Stack => "↑/↓/n/p:Navigate | 1-8:Switch View | :Cmd | f:Frame | Esc:Quit",
-        ViewMode::Timeline => "↑/↓:Scroll | 1-8:Switch View | :Cmd | Esc:Quit",
+        ViewMode::Stack => {
+            "↑/↓/n/p:Navigate | b:BreakPC c:BreakReturn C:BreakCallee | 1-8:Switch View | :Cmd | f:Frame | Esc:Quit"
+        }
+        ViewMode::Timeline => {
+            "↑/↓/PgUp/PgDn:Scroll End:Follow | /:Search n/N:Match f:Filter Enter:Open | 1-8:Switch View | :Cmd | Esc:Quit"
+        }
+        ViewMode::Disassembly => "↑/↓:Select | 0:Back to PC | b:ToggleBP | 1-8:Switch View | :Cmd | Esc:Quit",
+        ViewMode::History => "↑/↓:Select Enter:Stack v:Registers | :live to return | 1-8:Switch View | :Cmd | Esc:Quit",
         ViewMode::Help => "Press ? or h to close help | 1-8:Switch View | Esc:Quit",
-    };
+    }
+}
 
-    let mut footer_lines = vec![Line::from(help_text)];
+/// Draw the footer with help text
+///
+/// When `compact`, a pending message replaces the help text instead of being added below it.
+fn draw_footer(frame: &mut Frame, area: Rect, app: &App, compact: bool)
+{
+    let help_text = footer_help(app);
 
-    // Show info message (success) in green, or error message in red
-    if let Some(ref info) = app.info_message {
+    let mut footer_lines = Vec::new();
+    if !compact || (app.info_message.is_none() && app.error_message.is_none() && app.resume_prompt.is_none()) {
+        footer_lines.push(Line::from(help_text));
+    }
+
+    // The resume prompt waits for an answer, so it takes the place of any message
+    if let Some(ref prompt) = app.resume_prompt {
+        footer_lines.push(Line::from(Span::styled(
+            format!("? {prompt}"),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )));
+    } else if let Some(ref info) = app.info_message {
+        // Show info message (success) in green, or error message in red
         // Split long info messages into multiple lines to avoid truncation
         let max_width = area.width.saturating_sub(4); // Account for borders
-        let info_text = format!("✓ {}", info);
+        let info_text = format!("✓ {info}");
         
         // Break info into chunks that fit the width
         let mut info_lines = Vec::new();
@@ -114,10 +303,10 @@ fn draw_footer(frame: &mut Frame, area: Rect, app: &App)
             let test_line = if current_line.is_empty() {
                 word.to_string()
             } else {
-                format!("{} {}", current_line, word)
+                format!("{current_line} {word}")
             };
             
-            if test_line.len() as u16 <= max_width {
+            if test_line.len() <= usize::from(max_width) {
                 current_line = test_line;
             } else {
                 if !current_line.is_empty() {
@@ -139,7 +328,11 @@ fn draw_footer(frame: &mut Frame, area: Rect, app: &App)
     } else if let Some(ref error) = app.error_message {
         // Split long error messages into multiple lines to avoid truncation
         let max_width = area.width.saturating_sub(4); // Account for borders
-        let error_text = format!("Error: {error}");
+        let error_text = if app.permission_advice.is_some() {
+            format!("Error: {error} (e: what to do)")
+        } else {
+            format!("Error: {error}")
+        };
         
         // Break error into chunks that fit the width
         let mut error_lines = Vec::new();
@@ -149,10 +342,10 @@ fn draw_footer(frame: &mut Frame, area: Rect, app: &App)
             let test_line = if current_line.is_empty() {
                 word.to_string()
             } else {
-                format!("{} {}", current_line, word)
+                format!("{current_line} {word}")
             };
             
-            if test_line.len() as u16 <= max_width {
+            if test_line.len() <= usize::from(max_width) {
                 current_line = test_line;
             } else {
                 if !current_line.is_empty() {
@@ -180,3 +373,589 @@ fn draw_footer(frame: &mut Frame, area: Rect, app: &App)
 
     frame.render_widget(footer, area);
 }
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::BTreeSet;
+
+    use ferros_core::breakpoints::{BreakpointId, BreakpointInfo, BreakpointKind};
+    use ferros_core::mock::MockDebugger;
+    use ferros_core::symbols::DebugLevel;
+    use ferros_core::types::{
+        Address, AddressFormat, FrameId, FrameKind, FrameStatus, MemoryRegion, MemoryRegionId, SourceLocation, StackFrame,
+        SymbolLanguage, SymbolName, ThreadId,
+    };
+    use ferros_utils::config::Config;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+    use crate::app::LayoutPreset;
+    use crate::highlight::SourceFile;
+
+    const SOURCE_FILE: &str = "src/main.rs";
+
+    fn frame(index: usize, function: &str, line: u32) -> StackFrame
+    {
+        let thread = ThreadId::from(1);
+        let pc = Address::from(0x1_0000_1000 + index as u64 * 0x40);
+        let sp = Address::from(0x7000_0000 + index as u64 * 0x100);
+        StackFrame {
+            id: FrameId::new(thread, u32::try_from(index).unwrap(), 0, pc, sp),
+            thread,
+            index,
+            kind: FrameKind::Physical,
+            pc,
+            sp,
+            fp: Address::ZERO,
+            return_address: None,
+            symbol: Some(SymbolName::new(function.to_string(), Some(function.to_string()), SymbolLanguage::Rust)),
+            location: Some(SourceLocation {
+                file: SOURCE_FILE.to_string(),
+                line: Some(line),
+                column: None,
+                dwarf_file: None,
+            }),
+            parameters: Vec::new(),
+            status: FrameStatus::Complete,
+        }
+    }
+
+    /// Stopped app with a stack, source file and breakpoint to render
+    fn stopped_app() -> App
+    {
+        let (debugger, state) = MockDebugger::with_threads(2);
+        state.borrow_mut().pc = 0x1_0000_1000;
+        state.borrow_mut().regions = vec![MemoryRegion::new(
+            MemoryRegionId(0),
+            Address::from(0x1_0000_0000),
+            Address::from(0x1_0000_4000),
+            "r-x".to_string(),
+            Some("demo".to_string()),
+        )];
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.target_is_stopped = true;
+
+        let frames = vec![frame(0, "demo::compute_total", 3), frame(1, "demo::main", 7)];
+        app.selected_frame_id = Some(frames[0].id);
+        app.cached_stack_trace = Some(frames);
+
+        let source = (1..=10).map(|line| format!("let line_{line} = {line};")).collect();
+        app.source_cache
+            .insert(SOURCE_FILE.to_string(), SourceFile::new(source, None));
+        app.current_source_file = Some(SOURCE_FILE.to_string());
+
+        let address = Address::from(0x1_0000_2000);
+        let mut breakpoint = BreakpointInfo::new(BreakpointId::from_raw(1), address, BreakpointKind::Software);
+        breakpoint.enabled = true;
+        app.cached_breakpoints = vec![breakpoint];
+        app.breakpoints_state.select(Some(0));
+        app.breakpoint_locations.insert(
+            address,
+            Some(SourceLocation {
+                file: SOURCE_FILE.to_string(),
+                line: Some(5),
+                column: None,
+                dwarf_file: None,
+            }),
+        );
+        app
+    }
+
+    fn render(app: &mut App, width: u16, height: u16) -> String
+    {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                text.push_str(buffer[(x, y)].symbol());
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    #[test]
+    fn overview_lists_signals_that_dont_stop()
+    {
+        use ferros_core::signals::SignalPolicy;
+
+        let mut app = stopped_app();
+        assert!(!render(&mut app, 120, 40).contains("Signals"));
+
+        app.debugger.set_signal_policy(13, SignalPolicy::Ignore).unwrap();
+        let screen = render(&mut app, 120, 40);
+        assert!(screen.contains("Signals (others stop"), "{screen}");
+        assert!(screen.contains("SIGPIPE (13)"), "{screen}");
+        assert!(screen.contains("ignore"), "{screen}");
+    }
+
+    #[test]
+    fn overview_shows_the_stop_policy()
+    {
+        use ferros_core::types::StopPolicy;
+
+        let mut app = stopped_app();
+        assert!(render(&mut app, 120, 40).contains("Stop Policy: only the hitting thread stops"));
+
+        app.debugger.set_stop_policy(StopPolicy::AllThreads).unwrap();
+        let screen = render(&mut app, 120, 40);
+        assert!(screen.contains("Stop Policy: all threads stop at a breakpoint"), "{screen}");
+        assert!(screen.contains("Memory Regions"), "{screen}");
+    }
+
+    #[test]
+    fn the_header_counts_images_whose_symbols_are_loading()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        assert!(!render(&mut app, 120, 40).contains("loading symbols"));
+
+        state.borrow_mut().pending_symbol_loads = 3;
+        let screen = render(&mut app, 120, 40);
+        assert!(
+            screen.contains("Attached (PID: 1) - loading symbols for 3 images…"),
+            "{screen}"
+        );
+    }
+
+    #[test]
+    fn the_process_picker_replaces_the_views_and_shows_attach_failures()
+    {
+        use ferros_core::processes::ProcessInfo;
+        use ferros_core::types::ProcessId;
+
+        use crate::picker::ProcessPicker;
+
+        let (debugger, _state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, None, false, &Config::default());
+        let mut picker = ProcessPicker::new(vec![ProcessInfo {
+            pid: ProcessId(812),
+            name: "my_app".to_string(),
+            path: Some("/Users/me/my_app/target/debug/my_app".into()),
+        }]);
+        picker.attach_failed(
+            "my_app (812)",
+            &ferros_core::DebuggerError::permission_denied("task_for_pid() refused", None),
+        );
+        app.process_picker = Some(picker);
+
+        let screen = render(&mut app, 120, 30);
+        assert!(
+            screen.contains("Processes - 1 of 1 (type to filter, Enter to attach)"),
+            "{screen}"
+        );
+        assert!(screen.contains("/Users/me/my_app/target/debug/my_app"), "{screen}");
+        assert!(screen.contains("Failed to attach to my_app (812)"), "{screen}");
+        assert!(screen.contains("- re-run ferros under sudo"), "{screen}");
+        assert!(screen.contains("Enter:Attach"), "{screen}");
+        assert!(!screen.contains("Registers"), "{screen}");
+    }
+
+    #[test]
+    fn every_view_renders_at_common_sizes()
+    {
+        let views = [
+            ViewMode::Overview,
+            ViewMode::Registers,
+            ViewMode::Threads,
+            ViewMode::MemoryRegions,
+            ViewMode::Memory,
+            ViewMode::Modules,
+            ViewMode::Output,
+            ViewMode::Source,
+            ViewMode::Stack,
+            ViewMode::Timeline,
+            ViewMode::Disassembly,
+            ViewMode::History,
+            ViewMode::Help,
+        ];
+        for preset in [LayoutPreset::Compact, LayoutPreset::Standard, LayoutPreset::Widescreen] {
+            for view in views {
+                for (width, height) in [(80, 20), (100, 30), (60, 15), (40, 12)] {
+                    let mut app = stopped_app();
+                    app.layout_preset = preset;
+                    app.view_mode = view;
+                    render(&mut app, width, height);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn key_information_survives_small_sizes()
+    {
+        for (width, height) in [(80, 20), (60, 15)] {
+            let mut app = stopped_app();
+            app.view_mode = ViewMode::Registers;
+            assert!(
+                render(&mut app, width, height).contains("0x0000000100001000"),
+                "PC at {width}x{height}"
+            );
+
+            app.view_mode = ViewMode::Stack;
+            assert!(
+                render(&mut app, width, height).contains("demo::compute_total"),
+                "frame at {width}x{height}"
+            );
+
+            app.view_mode = ViewMode::Source;
+            let screen = render(&mut app, width, height);
+            assert!(screen.contains("let line_3 = 3;"), "current line at {width}x{height}");
+            assert!(screen.contains('●'), "breakpoint marker at {width}x{height}");
+        }
+
+        let mut app = stopped_app();
+        app.layout_preset = LayoutPreset::Widescreen;
+        app.view_mode = ViewMode::Source;
+        assert!(render(&mut app, 100, 30).contains("0x0000000100002000"));
+    }
+
+    #[test]
+    fn source_view_marks_the_current_line_and_dims_lines_without_code()
+    {
+        use ratatui::style::{Color, Modifier};
+
+        let mut app = stopped_app();
+        app.view_mode = ViewMode::Source;
+        let mut lines: Vec<String> = (1..=10).map(|line| format!("let line_{line} = {line};")).collect();
+        lines[0] = "// setup".to_string();
+        app.source_cache.insert(
+            SOURCE_FILE.to_string(),
+            SourceFile::new(lines, Some(BTreeSet::from([2, 3, 4]))),
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let text_from = |x: u16, y: u16| (x..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        let row = |text: &str| (0..buffer.area.height).find(|&y| text_from(0, y).contains(text)).unwrap();
+        let cell = |y: u16, text: &str| {
+            let x = (0..buffer.area.width).find(|&x| text_from(x, y).starts_with(text)).unwrap();
+            &buffer[(x, y)]
+        };
+
+        // The frame stopped at line 3 gets the arrow, and no other line does
+        let current = row("let line_3 = 3;");
+        assert_eq!(cell(current, "▶").fg, Color::Yellow);
+        assert_eq!(buffer.content().iter().filter(|cell| cell.symbol() == "▶").count(), 1);
+        assert!(cell(current, "line_3").modifier.contains(Modifier::BOLD));
+
+        // Keywords, numbers and comments are colored
+        assert_eq!(cell(current, "let").fg, Color::Magenta);
+        assert_eq!(cell(current, "3;").fg, Color::LightYellow);
+        let comment = cell(row("// setup"), "// setup");
+        assert_eq!(comment.fg, Color::Gray);
+
+        // The line tables have code for lines 2-4 only
+        assert!(comment.modifier.contains(Modifier::DIM));
+        assert!(!cell(row("let line_2"), "line_2").modifier.contains(Modifier::DIM));
+        assert!(cell(row("let line_5"), "line_5").modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn hardware_breakpoints_show_their_slot()
+    {
+        let mut app = stopped_app();
+        app.layout_preset = LayoutPreset::Widescreen;
+        app.view_mode = ViewMode::Source;
+        let breakpoint = &mut app.cached_breakpoints[0];
+        breakpoint.kind = BreakpointKind::Hardware;
+        breakpoint.hardware_slot = Some(2);
+        let screen = render(&mut app, 200, 30);
+        assert!(
+            screen
+                .lines()
+                .any(|line| line.contains("0x0000000100002000") && line.contains("HW2"))
+        );
+
+        // A disabled one has given its slot back
+        app.cached_breakpoints[0].hardware_slot = None;
+        assert!(!render(&mut app, 200, 30).contains("HW2"));
+    }
+
+    #[test]
+    fn notes_are_marked_where_they_are_attached()
+    {
+        use ferros_core::notes::{NOTE_MARKER, NoteTarget};
+
+        let mut app = stopped_app();
+        app.notes.add(NoteTarget::address(0x1_0000_1000), "checks the total").unwrap();
+        let breakpoint = NoteTarget::Breakpoint {
+            id: 1,
+            address: 0x1_0000_2000,
+            image: None,
+        };
+        app.notes.add(breakpoint, "flaky on CI").unwrap();
+
+        app.view_mode = ViewMode::Stack;
+        let screen = render(&mut app, 100, 30);
+        let frame_row = screen.lines().find(|line| line.contains("demo::compute_total")).unwrap();
+        assert!(frame_row.contains(NOTE_MARKER));
+        assert!(
+            !screen
+                .lines()
+                .find(|line| line.contains("demo::main"))
+                .unwrap()
+                .contains(NOTE_MARKER)
+        );
+        assert!(screen.contains("Note: checks the total"));
+
+        app.layout_preset = LayoutPreset::Widescreen;
+        app.view_mode = ViewMode::Source;
+        let screen = render(&mut app, 120, 30);
+        assert!(
+            screen
+                .lines()
+                .any(|line| line.contains("0x0000000100002000") && line.contains(NOTE_MARKER))
+        );
+
+        app.notes_list = Some(1);
+        let screen = render(&mut app, 100, 30);
+        assert!(screen.contains("Notes (Enter to jump"));
+        assert!(screen.contains("flaky on CI"));
+    }
+
+    #[test]
+    #[allow(clippy::large_stack_arrays)]
+    fn every_stop_reason_renders_in_overview_and_timeline()
+    {
+        use ferros_core::events::{DebuggerEvent, format_stop_reason};
+        use ferros_core::types::{BadAccessKind, StopReason};
+
+        let thread = ThreadId::from(1);
+        let reasons = [
+            StopReason::Running,
+            StopReason::Suspended,
+            StopReason::Signal(11),
+            StopReason::BadAccess {
+                address: 0x10,
+                kind: BadAccessKind::Read,
+            },
+            StopReason::Breakpoint(0x1_0000_2000),
+            StopReason::Step(0x1_0000_2004),
+            StopReason::Watchpoint {
+                address: 0x1_0000_8000,
+                pc: 0x1_0000_2008,
+            },
+            StopReason::StackOverflow {
+                thread,
+                sp: 0x16fd_ff000,
+                guard_page: 0x16fd_fc000,
+            },
+            StopReason::Exited(3),
+            StopReason::Unknown,
+        ];
+        let (debugger, _state) = MockDebugger::with_threads(1);
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+
+        for reason in reasons {
+            // Exhaustive so a new variant has to be covered here
+            let (event, status, logged) = match reason {
+                StopReason::Running => (
+                    DebuggerEvent::TargetResumed,
+                    "Process is running".to_string(),
+                    "[synthetic] Target resumed execution".to_string(),
+                ),
+                StopReason::StackOverflow { .. } => (
+                    DebuggerEvent::TargetStopped {
+                        reason,
+                        thread: Some(thread),
+                    },
+                    "Stack overflow on thread 1: no repeating frames found".to_string(),
+                    format!("[synthetic] {} (thread 1)", format_stop_reason(reason)),
+                ),
+                StopReason::BadAccess { .. } => (
+                    DebuggerEvent::TargetStopped {
+                        reason,
+                        thread: Some(thread),
+                    },
+                    "— null pointer dereference".to_string(),
+                    format!("[synthetic] {} (thread 1)", format_stop_reason(reason)),
+                ),
+                StopReason::Suspended
+                | StopReason::Signal(_)
+                | StopReason::Breakpoint(_)
+                | StopReason::Step(_)
+                | StopReason::Watchpoint { .. }
+                | StopReason::Exited(_)
+                | StopReason::Unknown => (
+                    DebuggerEvent::TargetStopped {
+                        reason,
+                        thread: Some(thread),
+                    },
+                    format_stop_reason(reason),
+                    format!("[synthetic] {} (thread 1)", format_stop_reason(reason)),
+                ),
+            };
+
+            app.handle_synthetic_event(&event, "synthetic");
+            assert_eq!(app.last_stop_reason, reason);
+
+            app.view_mode = ViewMode::Overview;
+            let screen = render(&mut app, 160, 40);
+            assert!(
+                screen.contains(&status),
+                "{reason:?}: status {status:?} missing from\n{screen}"
+            );
+            assert!(screen.contains(&format!("Last event: {logged}")), "{reason:?}: last event");
+
+            app.view_mode = ViewMode::Timeline;
+            let screen = render(&mut app, 160, 40);
+            assert!(screen.contains(&logged), "{reason:?}: {logged:?} missing from\n{screen}");
+        }
+    }
+
+    /// Longest run of hex digits after a `0x` in `screen`
+    fn widest_address(screen: &str) -> usize
+    {
+        screen
+            .match_indices("0x")
+            .map(|(at, _)| screen[at + 2..].chars().take_while(char::is_ascii_hexdigit).count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn every_address_goes_through_the_chosen_format()
+    {
+        use ferros_core::notes::NoteTarget;
+        use ferros_core::pins::PinType;
+
+        let mut app = stopped_app();
+        app.layout_preset = LayoutPreset::Widescreen;
+        app.address_format = AddressFormat::Trimmed;
+        app.pins.pin("total", Address::from(0x1_0000_3000), PinType::U64).unwrap();
+        app.notes.add(NoteTarget::address(0x1_0000_1000), "checks the total").unwrap();
+        if let Some(frames) = &mut app.cached_stack_trace {
+            frames[1].location = None;
+        }
+
+        // Timeline entries are log lines and keep the full width
+        for view in [
+            ViewMode::Overview,
+            ViewMode::Registers,
+            ViewMode::MemoryRegions,
+            ViewMode::Source,
+            ViewMode::Stack,
+        ] {
+            app.view_mode = view;
+            let screen = render(&mut app, 160, 40);
+            assert_eq!(widest_address(&screen), 9, "{view:?}:\n{screen}");
+        }
+        app.notes_list = Some(0);
+        assert_eq!(widest_address(&render(&mut app, 160, 40)), 9);
+
+        // Every address in a table shares its width, and narrower columns fit more of them
+        app.notes_list = None;
+        app.view_mode = ViewMode::MemoryRegions;
+        assert!(render(&mut app, 160, 40).contains("0x100000000 0x100004000"));
+        app.address_format = AddressFormat::Full16;
+        assert!(!render(&mut app, 60, 20).contains("End"));
+        app.address_format = AddressFormat::Trimmed;
+        assert!(render(&mut app, 60, 20).contains("End"));
+    }
+
+    #[test]
+    fn tiny_terminal_shows_size_notice()
+    {
+        let mut app = stopped_app();
+        let screen = render(&mut app, 30, 8);
+        assert!(screen.contains("Terminal too small"));
+        assert!(screen.contains("need 40x12"));
+        assert!(screen.contains("have 30x8"));
+    }
+
+    #[test]
+    fn popups_fit_small_terminals()
+    {
+        let mut app = stopped_app();
+        app.command_palette_active = true;
+        app.command_input = "break 0x1000 with a long tail".to_string();
+        assert!(render(&mut app, 40, 12).contains("Command"));
+
+        let mut app = stopped_app();
+        app.handle_key_event(crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('B'),
+            crossterm::event::KeyModifiers::NONE,
+        ));
+        assert!(app.breakpoint_editor.is_some());
+        render(&mut app, 40, 12);
+    }
+
+    #[test]
+    fn stop_toast_gives_way_to_popups()
+    {
+        let mut app = stopped_app();
+        let stop = |app: &mut App| {
+            app.stop_emphasis.on_stop(
+                "Hit breakpoint at 0x1000".to_string(),
+                Some("demo::compute_total (main.rs:3)".to_string()),
+                std::time::Instant::now(),
+            );
+        };
+        stop(&mut app);
+        let screen = render(&mut app, 100, 30);
+        assert!(screen.contains("Hit breakpoint at 0x1000"));
+        assert!(screen.contains("demo::compute_total (main.rs:3)"));
+
+        stop(&mut app);
+        app.command_palette_active = true;
+        let screen = render(&mut app, 100, 30);
+        assert!(!screen.contains("Hit breakpoint at 0x1000"));
+        assert!(!app.stop_emphasis.next_frame_flashes(), "flash must not run behind a popup");
+    }
+
+    #[test]
+    fn help_scrolls_and_clamps()
+    {
+        let mut app = stopped_app();
+        app.view_mode = ViewMode::Help;
+        app.help_scroll = usize::MAX;
+        let screen = render(&mut app, 60, 15);
+        assert!(screen.contains("Help (↑/↓ to scroll)"));
+        assert!(app.help_scroll < usize::MAX && app.help_scroll > 0);
+    }
+
+    #[test]
+    fn frames_without_debug_info_say_so_instead_of_suggesting_a_debug_build()
+    {
+        let (debugger, state) = MockDebugger::with_threads(1);
+        state.borrow_mut().regions = vec![MemoryRegion::new(
+            MemoryRegionId(0),
+            Address::from(0x1_0000_0000),
+            Address::from(0x1_0000_4000),
+            "r-x".to_string(),
+            Some("/usr/lib/librelease.dylib".to_string()),
+        )];
+        state.borrow_mut().images = vec![(0x1_0000_0000..0x1_0000_4000, DebugLevel::SymbolsOnly)];
+        let mut app = App::new(debugger, Some(1), false, &Config::default());
+        app.target_is_stopped = true;
+        let mut top = frame(0, "release::compute", 3);
+        top.location = None;
+        app.cached_stack_trace = Some(vec![top]);
+
+        app.view_mode = ViewMode::Source;
+        let screen = render(&mut app, 140, 30);
+        assert!(
+            screen.contains("No debug info in librelease.dylib (symbols only)"),
+            "{screen}"
+        );
+        assert!(!screen.contains("cargo build"));
+        assert!(screen.contains("b:n/a (no debug info)"));
+
+        app.view_mode = ViewMode::MemoryRegions;
+        let screen = render(&mut app, 140, 30);
+        assert!(screen.contains("/usr/lib/librelease.dylib (symbols only)"), "{screen}");
+
+        // Frames in images with DWARF keep the usual hints
+        state.borrow_mut().images[0].1 = DebugLevel::Full;
+        app.view_mode = ViewMode::Source;
+        let screen = render(&mut app, 140, 30);
+        assert!(!screen.contains("No debug info"));
+        assert!(screen.contains("b:ToggleBP"));
+    }
+}
@@ -1,14 +1,125 @@
 //! Widget components for displaying debugger information
 
+use std::fmt::Write;
+
+use ferros_core::DebuggerError;
 use ferros_core::events::format_stop_reason;
-use ferros_core::types::Architecture;
+use ferros_core::notes::{NOTE_MARKER, NoteTarget};
+use ferros_core::signals::{SignalPolicies, SignalPolicy, signal_name};
+use ferros_core::symbols::DebugLevel;
+use ferros_core::types::{
+    FrameKind, MemoryCacheStats, MemoryRegion, RegisterId, StopPolicy, ThreadRunState, register_display_order,
+};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table};
 
 use crate::app::{App, ProcessOutputLine, ProcessOutputSource};
+use crate::hexdump::{BYTES_PER_ROW, WORD_SIZE, format_row, hex_offset};
+use crate::highlight::TokenKind;
+use crate::layout::{self, Column};
+
+/// Register table columns; the address hint goes first when space runs out
+pub(crate) static REGISTER_COLUMNS: [Column; 3] = [
+    Column::new("Register", 10, 0),
+    Column::new("Value (hex)", 20, 0),
+    Column::new("Address", 20, 1),
+];
+
+/// Thread table columns
+pub(crate) static THREAD_COLUMNS: [Column; 8] = [
+    Column::new("", 4, 0),
+    Column::new("Index", 10, 3),
+    Column::new("Thread ID", 20, 0),
+    Column::fill("Name", 12, 1),
+    Column::new("State", 10, 1),
+    Column::new("CPU%", 6, 2),
+    Column::new("Status", 10, 1),
+    Column::new("Suspended by debugger", 22, 2),
+];
+
+/// Colour for a thread's run state in the threads view
+pub(crate) fn run_state_color(state: ThreadRunState) -> Color
+{
+    match state {
+        ThreadRunState::Running => Color::Green,
+        ThreadRunState::Waiting => Color::Reset,
+        ThreadRunState::Suspended => Color::Yellow,
+        ThreadRunState::Halted | ThreadRunState::Unknown => Color::DarkGray,
+    }
+}
+
+/// Memory region table columns
+pub(crate) static MEMORY_REGION_COLUMNS: [Column; 6] = [
+    Column::new("ID", 5, 4),
+    Column::new("Start", 18, 0),
+    Column::new("End", 18, 2),
+    Column::new("Size", 12, 1),
+    Column::new("Perms", 6, 0),
+    Column::fill("Name", 8, 3),
+];
+
+/// Loaded image table columns
+pub(crate) static MODULE_COLUMNS: [Column; 6] = [
+    Column::new("Start", 18, 0),
+    Column::new("End", 18, 3),
+    Column::new("Debug Info", 15, 1),
+    Column::new("eh_frame", 8, 2),
+    Column::new("Symbols", 8, 2),
+    Column::fill("Path", 10, 0),
+];
+
+/// Stop history columns
+static HISTORY_COLUMNS: [Column; 6] = [
+    Column::new("#", 6, 0),
+    Column::new("Age", 9, 2),
+    Column::fill("Reason", 16, 0),
+    Column::new("Thread", 8, 3),
+    Column::new("PC", 18, 1),
+    Column::fill("Function", 16, 1),
+];
+
+/// Suffix on a one-shot breakpoint's kind in the breakpoints list
+const ONE_SHOT_MARKER: &str = "¹";
+
+/// Breakpoint list columns
+static BREAKPOINT_COLUMNS: [Column; 9] = [
+    Column::new("ID", 5, 0),
+    Column::new("E", 2, 0),
+    Column::new("K", 4, 2),
+    Column::new("Address", 18, 0),
+    Column::new("Hits", 5, 1),
+    Column::new("Ignore", 6, 2),
+    Column::new("Overhead", 8, 3),
+    Column::new("Rate", 8, 4),
+    Column::new("CondErr", 7, 4),
+];
+
+/// Disassembly columns; the raw bytes go first when space runs out
+static DISASSEMBLY_COLUMNS: [Column; 4] = [
+    Column::new("", 2, 0),
+    Column::new("Address", 18, 0),
+    Column::new("Bytes", 15, 1),
+    Column::fill("Instruction", 20, 0),
+];
+
+/// Call stack columns
+static STACK_COLUMNS: [Column; 3] = [
+    Column::new("Frame", 8, 0),
+    Column::fill("Function", 20, 0),
+    Column::fill("Location", 20, 1),
+];
+
+/// Bold header row for the kept columns
+fn header_row(columns: &[Column], keep: &[usize]) -> Row<'static>
+{
+    Row::new(
+        keep.iter()
+            .map(|&index| Cell::from(columns[index].header).style(Style::default().add_modifier(Modifier::BOLD))),
+    )
+}
 
 /// Check if a register value looks like a valid memory address
 ///
@@ -17,7 +128,7 @@ use crate::app::{App, ProcessOutputLine, ProcessOutputSource};
 /// - Non-zero
 /// - Within reasonable memory ranges (not too small, not too large)
 /// - Often aligned (though not always)
-fn looks_like_address(value: u64) -> bool
+pub(crate) fn looks_like_address(value: u64) -> bool
 {
     // Zero is not a valid address (null pointer)
     if value == 0 {
@@ -46,9 +157,32 @@ fn looks_like_address(value: u64) -> bool
     true
 }
 
+/// Share of wall time a breakpoint held the target over the stats window, e.g. `12.5%`
+fn format_overhead(stats: &ferros_core::BreakpointStats, now: std::time::Instant) -> String
+{
+    if stats.timed_hits == 0 {
+        return "-".to_string();
+    }
+    format!("{:.1}%", stats.overhead(now) * 100.0)
+}
+
+/// Recent hits per second, e.g. `3.2/s` or `1.4k/s`
+fn format_hit_rate(stats: &ferros_core::BreakpointStats, now: std::time::Instant) -> String
+{
+    if stats.timed_hits == 0 {
+        return "-".to_string();
+    }
+    let rate = stats.hits_per_second(now);
+    if rate >= 1000.0 {
+        format!("{:.1}k/s", rate / 1000.0)
+    } else {
+        format!("{rate:.1}/s")
+    }
+}
+
 /// Format a memory size in bytes to a human-readable string (KB, MB, or GB)
 #[allow(clippy::large_stack_arrays)]
-fn format_memory_size(size_bytes: u64) -> String
+pub(crate) fn format_memory_size(size_bytes: u64) -> String
 {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
@@ -78,14 +212,145 @@ fn format_memory_size(size_bytes: u64) -> String
 pub fn draw_overview(frame: &mut Frame, area: Rect, app: &App)
 {
     // Use boxed slice to avoid large stack array warning
+    let pinned_height = if app.pins.is_empty() {
+        0
+    } else {
+        u16::try_from(app.pins.len()).unwrap_or(u16::MAX).saturating_add(2)
+    };
+    let signals = app.debugger.signal_policies();
+    // Borders and a header around one row per signal
+    let signals_height = if signals.is_empty() {
+        0
+    } else {
+        u16::try_from(signals.iter().count()).unwrap_or(u16::MAX).saturating_add(3)
+    };
     let constraints: Box<[Constraint]> = Box::new([
-        Constraint::Length(10), // Debugger info
-        Constraint::Min(0),     // Status
+        Constraint::Length(11),             // Debugger info
+        Constraint::Length(pinned_height),  // Pinned values
+        Constraint::Length(signals_height), // Signal policies
+        Constraint::Min(0),                 // Status
     ]);
     let chunks = Layout::vertical(constraints).split(area);
 
     draw_debugger_info(frame, chunks[0], app);
-    draw_status(frame, chunks[1], app);
+    if !app.pins.is_empty() {
+        draw_pinned(frame, chunks[1], app);
+    }
+    if !signals.is_empty() {
+        draw_signal_policies(frame, chunks[2], &signals);
+    }
+    draw_status(frame, chunks[3], app);
+}
+
+/// Draw the "Signals" section of the Overview: the signals that don't stop the target
+fn draw_signal_policies(frame: &mut Frame, area: Rect, signals: &SignalPolicies)
+{
+    let rows: Vec<Row> = signals
+        .iter()
+        .map(|(signal, policy)| {
+            let name = signal_name(signal).map_or_else(|| signal.to_string(), |name| format!("{name} ({signal})"));
+            let style = match policy {
+                SignalPolicy::Ignore => Style::default().fg(Color::DarkGray),
+                _ => Style::default().fg(Color::White),
+            };
+            Row::new(vec![Cell::from(name), Cell::from(policy.to_string())]).style(style)
+        })
+        .collect();
+    let table = Table::new(rows, [Constraint::Length(16), Constraint::Min(6)])
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Signals (others stop; :handle <signal> stop|pass|ignore)"),
+        )
+        .header(Row::new(vec!["Signal", "Policy"]).style(Style::default().add_modifier(Modifier::BOLD)));
+    frame.render_widget(table, area);
+}
+
+/// Style for a pinned value: highlighted if it changed at the last refresh
+fn pin_style(pin: &ferros_core::pins::PinnedValue) -> Style
+{
+    match &pin.value {
+        Some(ferros_core::pins::PinValue::Unreadable) => Style::default().fg(Color::DarkGray),
+        _ if pin.changed => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        _ => Style::default().fg(Color::White),
+    }
+}
+
+/// Draw the "Pinned" section of the Overview
+fn draw_pinned(frame: &mut Frame, area: Rect, app: &App)
+{
+    let addresses = app.address_format.formatter(app.pins.iter().map(|pin| pin.address));
+    let lines: Vec<Line> = app
+        .pins
+        .iter()
+        .map(|pin| {
+            let value = pin.value.as_ref().map_or_else(|| "-".to_string(), ToString::to_string);
+            Line::from(vec![
+                Span::styled(format!("{}: ", pin.name), Style::default().fg(Color::Yellow)),
+                Span::styled(value, pin_style(pin)),
+                Span::styled(
+                    format!("  {} {}", addresses.format(pin.address), pin.ty),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])
+        })
+        .collect();
+
+    let pinned = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Pinned"));
+    frame.render_widget(pinned, area);
+}
+
+/// Draw stop toasts stacked in the top-right corner of `area`, newest lowest
+///
+/// Toasts in the last third of their lifetime are drawn dimmed; those that
+/// don't fit below the others are left out until older ones expire.
+pub fn draw_stop_toasts(frame: &mut Frame, area: Rect, app: &App, now: std::time::Instant)
+{
+    let lifetime = app.stop_emphasis.settings.toast;
+    let mut y = area.y;
+    for toast in app.stop_emphasis.toasts() {
+        let title = if toast.repeats > 1 {
+            format!("{} (×{})", toast.title, toast.repeats)
+        } else {
+            toast.title.clone()
+        };
+        let mut lines = vec![Line::from(title)];
+        if let Some(detail) = &toast.detail {
+            lines.push(Line::from(detail.as_str()));
+        }
+        let text_width = lines.iter().map(Line::width).max().unwrap_or(0);
+        let width = u16::try_from(text_width + 2).unwrap_or(u16::MAX).min(area.width);
+        let height = u16::try_from(lines.len() + 2).unwrap_or(u16::MAX);
+        if y + height > area.bottom() {
+            break;
+        }
+
+        let style = if toast.is_fading(lifetime, now) {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        };
+        let toast_area = Rect::new(area.right() - width, y, width, height);
+        let widget = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Stopped"))
+            .style(style);
+        frame.render_widget(Clear, toast_area);
+        frame.render_widget(widget, toast_area);
+        y += height;
+    }
+}
+
+/// Draw a one-line strip of pinned values above the other views
+pub fn draw_pin_strip(frame: &mut Frame, area: Rect, app: &App)
+{
+    let mut spans = vec![Span::styled("Pinned ", Style::default().fg(Color::Yellow))];
+    for pin in app.pins.iter() {
+        let value = pin.value.as_ref().map_or_else(|| "-".to_string(), ToString::to_string);
+        spans.push(Span::raw(format!("| {}=", pin.name)));
+        spans.push(Span::styled(value, pin_style(pin)));
+        spans.push(Span::raw(" "));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
 }
 
 /// Draw debugger information
@@ -107,11 +372,18 @@ fn draw_debugger_info(frame: &mut Frame, area: Rect, app: &App)
         Line::from(vec![
             Span::styled("Stop Reason: ", Style::default().fg(Color::Yellow)),
             Span::raw(if app.target_is_stopped {
-                format_stop_reason(app.last_stop_reason)
+                app.stop_reason_message()
             } else {
                 "N/A".to_string()
             }),
         ]),
+        Line::from(vec![
+            Span::styled("Stop Policy: ", Style::default().fg(Color::Yellow)),
+            Span::raw(match app.debugger.stop_policy() {
+                StopPolicy::AllThreads => "all threads stop at a breakpoint",
+                StopPolicy::HitThreadOnly => "only the hitting thread stops",
+            }),
+        ]),
     ];
 
     let mut lines = info_lines;
@@ -137,6 +409,13 @@ fn draw_debugger_info(frame: &mut Frame, area: Rect, app: &App)
                 Span::raw(format!("{}", regions.len())),
             ]));
         }
+
+        if let Some(stats) = app.debugger.memory_cache_stats() {
+            lines.push(Line::from(vec![
+                Span::styled("Memory Cache: ", Style::default().fg(Color::Yellow)),
+                Span::raw(format_cache_stats(stats)),
+            ]));
+        }
     }
 
     let info = Paragraph::new(lines)
@@ -146,6 +425,17 @@ fn draw_debugger_info(frame: &mut Frame, area: Rect, app: &App)
     frame.render_widget(info, area);
 }
 
+/// Hits, misses and cached size of the backend's memory cache, e.g. "120 hits, 30 misses (80%), 64 KiB cached"
+fn format_cache_stats(stats: MemoryCacheStats) -> String
+{
+    let counts = format!("{} hits, {} misses", stats.hits, stats.misses);
+    let rate = stats
+        .hit_rate()
+        .map_or_else(String::new, |rate| format!(" ({:.0}%)", rate * 100.0));
+    let kib = stats.bytes / 1024;
+    format!("{counts}{rate}, {kib} KiB cached")
+}
+
 /// Draw status information
 fn draw_status(frame: &mut Frame, area: Rect, app: &App)
 {
@@ -162,107 +452,79 @@ fn draw_status(frame: &mut Frame, area: Rect, app: &App)
     frame.render_widget(status, area);
 }
 
+/// Show why a view has nothing to draw; a vanished target gets an empty state, not an error
+fn draw_read_error(frame: &mut Frame, area: Rect, app: &mut App, title: &str, what: &str, err: &DebuggerError)
+{
+    let (text, color) = if app.observe_target_gone(err) {
+        (format!("No {what}: the process is gone"), Color::DarkGray)
+    } else {
+        (format!("Error reading {what}: {err}"), Color::Red)
+    };
+    let error = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(title.to_string()))
+        .style(Style::default().fg(color));
+    frame.render_widget(error, area);
+}
+
 /// Draw the registers view
 pub fn draw_registers(frame: &mut Frame, area: Rect, app: &mut App)
 {
-    let registers = match app.debugger.read_registers() {
+    let registers = match app.inspected_registers() {
         Ok(regs) => regs,
         Err(e) => {
-            let error = Paragraph::new(format!("Error reading registers: {e}"))
-                .block(Block::default().borders(Borders::ALL).title("Registers"))
-                .style(Style::default().fg(Color::Red));
-            frame.render_widget(error, area);
+            draw_read_error(frame, area, app, "Registers", "registers", &e);
             return;
         }
     };
 
-    let arch = registers.architecture();
-    let mut rows = Vec::new();
-
-    // Common registers
-    rows.push(Row::new(vec![
-        Cell::from("PC"),
-        Cell::from(format!("{}", registers.pc)),
-        Cell::from(format!("0x{:016x}", registers.pc.value())),
-    ]));
-    rows.push(Row::new(vec![
-        Cell::from("SP"),
-        Cell::from(format!("{}", registers.sp)),
-        Cell::from(format!("0x{:016x}", registers.sp.value())),
-    ]));
-    rows.push(Row::new(vec![
-        Cell::from("FP"),
-        Cell::from(format!("{}", registers.fp)),
-        Cell::from(format!("0x{:016x}", registers.fp.value())),
-    ]));
-    rows.push(Row::new(vec![
-        Cell::from("Status"),
-        Cell::from(format!("0x{:016x}", registers.status)),
-        Cell::from(""),
-    ]));
-
-    // Architecture-specific registers
-    match arch {
-        Architecture::Arm64 => {
-            for (i, val) in registers.general.iter().enumerate() {
-                let address_cell = if looks_like_address(*val) {
-                    format!("0x{val:016x}")
-                } else {
-                    String::new()
-                };
-                rows.push(Row::new(vec![
-                    Cell::from(format!("X{i}")),
-                    Cell::from(format!("0x{val:016x}")),
-                    Cell::from(address_cell),
-                ]));
+    // One row per schema entry, so the selection index names the same
+    // register on every refresh
+    let order = register_display_order(registers.architecture());
+    let selected = app.registers_state.selected();
+    let values = app
+        .address_format
+        .formatter(order.iter().filter_map(|row| registers.get(row.id)));
+    let rows: Vec<_> = order
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            if let Some(input) = app.register_edit.as_ref().filter(|_| selected == Some(index)) {
+                return vec![
+                    Cell::from(row.display_name()),
+                    Cell::from(format!("{input}_")).style(Style::default().fg(Color::Black).bg(Color::Yellow)),
+                    Cell::from(""),
+                ];
             }
-        }
-        Architecture::X86_64 => {
-            // Use boxed slice to avoid large stack array warning
-            let reg_names: Box<[&str]> = vec![
-                "RAX", "RBX", "RCX", "RDX", "RSI", "RDI", "R8", "R9", "R10", "R11", "R12", "R13", "R14", "R15",
+            let Some(value) = registers.get(row.id) else {
+                return vec![
+                    Cell::from(row.display_name()),
+                    Cell::from("unavailable").style(Style::default().fg(Color::DarkGray)),
+                    Cell::from(""),
+                ];
+            };
+            let address_cell = if row.id != RegisterId::Status && looks_like_address(value) {
+                values.format(value)
+            } else {
+                String::new()
+            };
+            vec![
+                Cell::from(row.display_name()),
+                Cell::from(values.format(value)),
+                Cell::from(address_cell),
             ]
-            .into_boxed_slice();
-            for (i, val) in registers.general.iter().enumerate() {
-                if i < reg_names.len() {
-                    let address_cell = if looks_like_address(*val) {
-                        format!("0x{val:016x}")
-                    } else {
-                        String::new()
-                    };
-                    rows.push(Row::new(vec![
-                        Cell::from(reg_names[i]),
-                        Cell::from(format!("0x{val:016x}")),
-                        Cell::from(address_cell),
-                    ]));
-                }
-            }
-        }
-        Architecture::Unknown(_) => {
-            for (i, val) in registers.general.iter().enumerate() {
-                let address_cell = if looks_like_address(*val) {
-                    format!("0x{val:016x}")
-                } else {
-                    String::new()
-                };
-                rows.push(Row::new(vec![
-                    Cell::from(format!("R{i}")),
-                    Cell::from(format!("0x{val:016x}")),
-                    Cell::from(address_cell),
-                ]));
-            }
-        }
-    }
+        })
+        .collect();
 
-    // Use boxed slice to avoid large stack array warning
-    let constraints: Box<[Constraint]> = Box::new([Constraint::Length(10), Constraint::Length(20), Constraint::Length(20)]);
-    let table = Table::new(rows, constraints)
-        .block(Block::default().borders(Borders::ALL).title("Registers"))
-        .header(Row::new(vec![
-            Cell::from("Register").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Value (hex)").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Address").style(Style::default().add_modifier(Modifier::BOLD)),
-        ]))
+    let columns = layout::fit_columns(&REGISTER_COLUMNS, &[1, 2], values.width());
+    let keep = layout::visible_columns(area.width, &columns);
+    let rows = rows.into_iter().map(|cells| Row::new(layout::select(cells, &keep)));
+    let title = match app.register_edit {
+        Some(_) => "Registers - type a hex value, Enter to write, Esc to cancel",
+        None => "Registers",
+    };
+    let table = Table::new(rows, layout::column_constraints(&columns, &keep))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .header(header_row(&columns, &keep))
         .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
 
@@ -275,15 +537,13 @@ pub fn draw_threads(frame: &mut Frame, area: Rect, app: &mut App)
     let threads = match app.debugger.threads() {
         Ok(threads) => threads,
         Err(e) => {
-            let error = Paragraph::new(format!("Error reading threads: {e}"))
-                .block(Block::default().borders(Borders::ALL).title("Threads"))
-                .style(Style::default().fg(Color::Red));
-            frame.render_widget(error, area);
+            draw_read_error(frame, area, app, "Threads", "threads", &e);
             return;
         }
     };
 
     let active_thread = app.debugger.active_thread();
+    let keep = layout::visible_columns(area.width, &THREAD_COLUMNS);
 
     let rows: Vec<Row> = threads
         .iter()
@@ -291,87 +551,305 @@ pub fn draw_threads(frame: &mut Frame, area: Rect, app: &mut App)
         .map(|(i, thread)| {
             let is_active = active_thread.is_some_and(|t| t == *thread);
             let prefix = if is_active { "→ " } else { "  " };
-            Row::new(vec![
+            let mark = if app.marked_threads.contains(thread) { "[x]" } else { "[ ]" };
+            let suspended = match app.debugger.thread_suspend_count(*thread) {
+                0 => String::new(),
+                1 => "Yes".to_string(),
+                n => format!("Yes (x{n})"),
+            };
+            // Threads that exited since the last refresh have no details
+            let info = app.debugger.thread_info(*thread).ok();
+            let (name, state, cpu) = info.map_or_else(Default::default, |info| {
+                (
+                    Cell::from(info.name.unwrap_or_default()),
+                    Cell::from(info.state.to_string()).style(Style::default().fg(run_state_color(info.state))),
+                    Cell::from(format!("{:.1}", info.cpu_percent)),
+                )
+            });
+            let cells = vec![
+                Cell::from(mark),
                 Cell::from(format!("{prefix}{i}")),
                 Cell::from(format!("{}", thread.raw())),
+                name,
+                state,
+                cpu,
                 Cell::from(if is_active { "Active" } else { "" }),
-            ])
+                Cell::from(suspended).style(Style::default().fg(Color::Red)),
+            ];
+            Row::new(layout::select(cells, &keep))
         })
         .collect();
 
-    // Use boxed slice to avoid large stack array warning
-    let constraints: Box<[Constraint]> = Box::new([Constraint::Length(10), Constraint::Length(20), Constraint::Length(10)]);
-    let table = Table::new(rows, constraints)
-        .block(Block::default().borders(Borders::ALL).title("Threads"))
-        .header(Row::new(vec![
-            Cell::from("Index").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Thread ID").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Status").style(Style::default().add_modifier(Modifier::BOLD)),
-        ]))
+    let title = if app.marked_threads.is_empty() {
+        "Threads".to_string()
+    } else {
+        format!("Threads ({} marked)", app.marked_threads.len())
+    };
+
+    let table = Table::new(rows, layout::column_constraints(&THREAD_COLUMNS, &keep))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .header(header_row(&THREAD_COLUMNS, &keep))
         .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
 
     frame.render_stateful_widget(table, area, &mut app.threads_state);
 }
 
+/// Region name, followed by the image's debug level when it has no DWARF
+fn region_name_cell(app: &App, region: &MemoryRegion) -> Cell<'static>
+{
+    let name = region.name.clone().unwrap_or_default();
+    match app.debugger.debug_level(region.start).filter(|level| !level.has_dwarf()) {
+        Some(level) => Cell::from(Line::from(vec![
+            Span::raw(name),
+            Span::styled(format!(" ({level})"), Style::default().fg(Color::DarkGray)),
+        ])),
+        None => Cell::from(name),
+    }
+}
+
 /// Draw the memory regions view
 pub fn draw_memory_regions(frame: &mut Frame, area: Rect, app: &mut App)
 {
     let regions = match app.debugger.get_memory_regions() {
         Ok(regions) => regions,
         Err(e) => {
-            let error = Paragraph::new(format!("Error reading memory regions: {e}"))
-                .block(Block::default().borders(Borders::ALL).title("Memory Regions"))
-                .style(Style::default().fg(Color::Red));
-            frame.render_widget(error, area);
+            draw_read_error(frame, area, app, "Memory Regions", "memory regions", &e);
             return;
         }
     };
 
+    let addresses = app
+        .address_format
+        .formatter(regions.iter().flat_map(|region| [region.start, region.end]));
+    let columns = layout::fit_columns(&MEMORY_REGION_COLUMNS, &[1, 2], addresses.width());
+    let keep = layout::visible_columns(area.width, &columns);
     let rows: Vec<Row> = regions
         .iter()
         .map(|region| {
             let size_str = format_memory_size(region.size());
-            Row::new(vec![
+            let cells = vec![
                 Cell::from(format!("{}", region.id.value())),
-                Cell::from(format!("{}", region.start)),
-                Cell::from(format!("{}", region.end)),
+                Cell::from(addresses.format(region.start)),
+                Cell::from(addresses.format(region.end)),
                 Cell::from(size_str),
                 Cell::from(region.permissions.clone()),
-                Cell::from(region.name.as_deref().unwrap_or("").to_string()),
-            ])
+                region_name_cell(app, region),
+            ];
+            Row::new(layout::select(cells, &keep))
         })
         .collect();
 
-    // Use boxed slice to avoid large stack array warning
-    let constraints: Box<[Constraint]> = vec![
-        Constraint::Length(5),
-        Constraint::Length(18),
-        Constraint::Length(18),
-        Constraint::Length(12),
-        Constraint::Length(6),
-        Constraint::Min(0),
-    ]
-    .into_boxed_slice();
-    let table = Table::new(rows, constraints)
+    let table = Table::new(rows, layout::column_constraints(&columns, &keep))
         .block(Block::default().borders(Borders::ALL).title("Memory Regions"))
-        .header(Row::new(vec![
-            Cell::from("ID").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Start").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("End").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Size").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Perms").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Name").style(Style::default().add_modifier(Modifier::BOLD)),
-        ]))
+        .header(header_row(&columns, &keep))
         .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
 
     frame.render_stateful_widget(table, area, &mut app.memory_regions_state);
 }
 
+/// Draw the images the symbol cache has loaded (`:modules`)
+pub fn draw_modules(frame: &mut Frame, area: Rect, app: &mut App)
+{
+    let images = match app.debugger.modules() {
+        Ok(images) => images,
+        Err(e) => {
+            draw_read_error(frame, area, app, "Modules", "loaded images", &e);
+            return;
+        }
+    };
+
+    let addresses = app
+        .address_format
+        .formatter(images.iter().flat_map(|image| [image.load_address, image.end_address]));
+    let columns = layout::fit_columns(&MODULE_COLUMNS, &[0, 1], addresses.width());
+    let keep = layout::visible_columns(area.width, &columns);
+    let rows: Vec<Row> = images
+        .iter()
+        .map(|image| {
+            let cells = vec![
+                Cell::from(addresses.format(image.load_address)),
+                Cell::from(addresses.format(image.end_address)),
+                Cell::from(image.debug_level.to_string()),
+                Cell::from(if image.has_eh_frame { "yes" } else { "no" }),
+                Cell::from(image.symbol_count.to_string()),
+                Cell::from(image.path.display().to_string()),
+            ];
+            let row = Row::new(layout::select(cells, &keep));
+            if image.debug_level == DebugLevel::None {
+                row.style(Style::default().fg(Color::DarkGray))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let title = format!("Modules - {} loaded", images.len());
+    let table = Table::new(rows, layout::column_constraints(&columns, &keep))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .header(header_row(&columns, &keep))
+        .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.modules_state);
+}
+
+/// Draw the stops recorded this session, newest first (`:history`)
+pub fn draw_history(frame: &mut Frame, area: Rect, app: &mut App)
+{
+    if app.history.is_empty() {
+        let notice = Paragraph::new("No stops recorded yet. Every stop's registers and call stack are kept here.")
+            .block(Block::default().borders(Borders::ALL).title("History"))
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(notice, area);
+        return;
+    }
+
+    let addresses = app
+        .address_format
+        .formatter(app.history.iter().map(|checkpoint| checkpoint.registers.pc));
+    let columns = layout::fit_columns(&HISTORY_COLUMNS, &[4], addresses.width());
+    let keep = layout::visible_columns(area.width, &columns);
+    let inspected = app.history.current().map(|checkpoint| checkpoint.id);
+    let rows: Vec<Row> = app
+        .history
+        .iter()
+        .rev()
+        .map(|checkpoint| {
+            let function = checkpoint
+                .frames
+                .first()
+                .and_then(|frame| frame.symbol.as_ref())
+                .map_or("<unknown>", ferros_core::SymbolName::display_name);
+            let cells = vec![
+                Cell::from(checkpoint.id.to_string()),
+                Cell::from(format!("{:6.2}s", checkpoint.timestamp.elapsed().as_secs_f64())),
+                Cell::from(format_stop_reason(checkpoint.reason)),
+                Cell::from(checkpoint.thread.map(|thread| thread.raw().to_string()).unwrap_or_default()),
+                Cell::from(addresses.format(checkpoint.registers.pc)),
+                Cell::from(function),
+            ];
+            let row = Row::new(layout::select(cells, &keep));
+            if Some(checkpoint.id) == inspected {
+                row.style(Style::default().fg(Color::Magenta))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let title = match inspected {
+        Some(id) => format!("History - {} stops | viewing #{id} (read-only)", app.history.len()),
+        None => format!("History - {} stops", app.history.len()),
+    };
+    let table = Table::new(rows, layout::column_constraints(&columns, &keep))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .header(header_row(&columns, &keep))
+        .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.history_state);
+}
+
+/// Draw the hex dump of the Memory view
+pub fn draw_memory(frame: &mut Frame, area: Rect, app: &mut App)
+{
+    app.memory.rows = usize::from(area.height.saturating_sub(2)).max(1);
+    let view = &app.memory;
+    let Some(start) = view.address else {
+        let notice = Paragraph::new(
+            "No address to dump yet.\n\nUse :mem <address> (a hex address or symbol[+offset]), or press Enter on a \
+             register holding an address in the Registers view (2).",
+        )
+        .block(Block::default().borders(Borders::ALL).title("Memory"))
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+        frame.render_widget(notice, area);
+        return;
+    };
+
+    let mut title = format!("Memory at {start}");
+    if let Some(word) = view.selected_word() {
+        match view.selected_value() {
+            Some(value) => write!(title, " | [{word}] = {value:#x}").unwrap_or_default(),
+            None => write!(title, " | [{word}] = ??").unwrap_or_default(),
+        }
+    }
+    if app.memory_history.depth() > 0 {
+        write!(title, " | back: {}", app.memory_history.depth()).unwrap_or_default();
+    }
+    if !app.target_is_stopped {
+        title.push_str(" (running; re-read at the next stop)");
+    }
+    if let Some(ref error) = view.error {
+        let error = Paragraph::new(format!("Couldn't read memory at {start}: {error}"))
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .style(Style::default().fg(Color::Red))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+        frame.render_widget(error, area);
+        return;
+    }
+
+    let rows: Vec<_> = view.bytes.chunks(BYTES_PER_ROW).enumerate().collect();
+    let row_address = |index: usize| start + (index * BYTES_PER_ROW) as u64;
+    let selected = view.selected_word().map(|word| word.value()..word.value() + WORD_SIZE as u64);
+    let addresses = app.address_format.formatter(rows.iter().map(|&(index, _)| row_address(index)));
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(index, bytes)| {
+            let (hex, ascii) = format_row(bytes);
+            let hex_style = if bytes.iter().all(Option::is_none) {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            // Highlight the part of the selected word on this row
+            let first = row_address(index).value();
+            let on_row: Vec<usize> = (0..bytes.len())
+                .filter(|&byte| selected.as_ref().is_some_and(|word| word.contains(&(first + byte as u64))))
+                .collect();
+            let (before, word, after) = match (on_row.first(), on_row.last()) {
+                (Some(&from), Some(&to)) => {
+                    let (from, to) = (hex_offset(from), hex_offset(to) + 2);
+                    (&hex[..from], &hex[from..to], &hex[to..])
+                }
+                _ => (hex.as_str(), "", ""),
+            };
+            Line::from(vec![
+                Span::styled(addresses.format(row_address(index)), Style::default().fg(Color::Cyan)),
+                Span::raw("  "),
+                Span::styled(before.to_string(), hex_style),
+                Span::styled(word.to_string(), Style::default().fg(Color::Yellow).add_modifier(Modifier::REVERSED)),
+                Span::styled(after.to_string(), hex_style),
+                Span::raw("  |"),
+                Span::styled(ascii, Style::default().fg(Color::Green)),
+                Span::raw("|"),
+            ])
+        })
+        .collect();
+
+    let dump = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(dump, area);
+}
+
 /// Draw the process output view
 pub fn draw_output(frame: &mut Frame, area: Rect, app: &App)
 {
+    // `i` opens a line for the target's stdin under the output
+    let area = if let Some(input) = &app.stdin_input {
+        let [output_area, input_area] = Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(area);
+        let prompt = Paragraph::new(input.as_str())
+            .block(Block::default().borders(Borders::ALL).title("stdin"))
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(prompt, input_area);
+        let cursor_offset = input.chars().count().min(usize::from(input_area.width.saturating_sub(2)));
+        let cursor_offset = u16::try_from(cursor_offset).unwrap_or(u16::MAX);
+        frame.set_cursor_position((input_area.x + 1 + cursor_offset, input_area.y + 1));
+        output_area
+    } else {
+        area
+    };
     let viewport_height = area.height.saturating_sub(2) as usize; // account for borders
     let mut output_text = Vec::new();
 
@@ -394,7 +872,7 @@ pub fn draw_output(frame: &mut Frame, area: Rect, app: &App)
                 .iter()
                 .skip(start_index)
                 .take(lines_to_show)
-                .map(format_process_output_line),
+                .map(|entry| format_process_output_line(entry, app.output_timestamps)),
         );
     }
 
@@ -406,34 +884,33 @@ pub fn draw_output(frame: &mut Frame, area: Rect, app: &App)
     frame.render_widget(output, area);
 }
 
-fn format_process_output_line(entry: &ProcessOutputLine) -> Line<'_>
+/// One output line, with its transcript time in the gutter when `timestamps` is on
+fn format_process_output_line(entry: &ProcessOutputLine, timestamps: bool) -> Line<'_>
 {
     let (label, color) = match entry.source {
         ProcessOutputSource::Stdout => ("stdout", Color::Green),
         ProcessOutputSource::Stderr => ("stderr", Color::Red),
+        ProcessOutputSource::Stdin => ("stdin", Color::Yellow),
     };
 
-    Line::from(vec![
-        Span::styled(format!("[{label}]"), Style::default().fg(color).add_modifier(Modifier::BOLD)),
-        Span::raw(" "),
-        Span::raw(entry.text.clone()),
-    ])
+    let mut spans = Vec::with_capacity(4);
+    if timestamps {
+        let gutter = entry
+            .at
+            .map_or_else(|| " ".repeat(11), |at| format!("{:>10.3}s", at.as_secs_f64()));
+        spans.push(Span::styled(gutter, Style::default().fg(Color::DarkGray)));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::styled(format!("[{label}]"), Style::default().fg(color).add_modifier(Modifier::BOLD)));
+    spans.push(Span::raw(" "));
+    spans.push(Span::raw(entry.text.clone()));
+    Line::from(spans)
 }
 
 /// Draw the command palette
 pub fn draw_command_palette(frame: &mut Frame, area: Rect, app: &App)
 {
-    let center_y = area.height / 2;
-    let center_x = area.width / 2;
-    let width = area.width.min(80);
-    let height = 3;
-
-    let palette_area = Rect {
-        x: center_x.saturating_sub(width / 2),
-        y: center_y.saturating_sub(height / 2),
-        width,
-        height,
-    };
+    let palette_area = layout::centered_rect(area, 80, 3);
 
     let input_text = format!(":{}", app.command_input);
     let input = Paragraph::new(input_text.as_str())
@@ -443,7 +920,7 @@ pub fn draw_command_palette(frame: &mut Frame, area: Rect, app: &App)
     frame.render_widget(input, palette_area);
 
     // Set cursor position for input
-    let cursor_offset = app.command_input.len().min(width as usize - 2);
+    let cursor_offset = app.command_input.len().min(usize::from(palette_area.width.saturating_sub(2)));
     let cursor_offset = u16::try_from(cursor_offset).unwrap_or(u16::MAX);
     frame.set_cursor_position((palette_area.x + 1 + cursor_offset, palette_area.y + 1));
 }
@@ -451,17 +928,7 @@ pub fn draw_command_palette(frame: &mut Frame, area: Rect, app: &App)
 /// Draw the breakpoint editor
 pub fn draw_breakpoint_editor(frame: &mut Frame, area: Rect, app: &App)
 {
-    let center_y = area.height / 2;
-    let center_x = area.width / 2;
-    let width = area.width.min(60);
-    let height = 8;
-
-    let editor_area = Rect {
-        x: center_x.saturating_sub(width / 2),
-        y: center_y.saturating_sub(height / 2),
-        width,
-        height,
-    };
+    let editor_area = layout::centered_rect(area, 60, 8);
 
     if let Some(ref editor) = app.breakpoint_editor {
         let lines = vec![
@@ -479,21 +946,295 @@ pub fn draw_breakpoint_editor(frame: &mut Frame, area: Rect, app: &App)
             Line::from("Press Enter to apply, Esc to cancel"),
         ];
 
+        // Scroll so the input fields stay visible if the screen is too short for the whole editor
+        let scroll = layout::popup_scroll(editor_area.height.saturating_sub(2), 3);
         let editor_widget = Paragraph::new(lines)
             .block(Block::default().borders(Borders::ALL).title("Breakpoint Editor"))
-            .style(Style::default().fg(Color::White));
+            .style(Style::default().fg(Color::White))
+            .scroll((scroll, 0));
 
         frame.render_widget(editor_widget, editor_area);
     }
 }
 
+/// Draw the region checkpoint popup: per range a summary, then each changed run
+pub fn draw_range_diff(frame: &mut Frame, area: Rect, app: &mut App)
+{
+    let popup_area = layout::centered_rect(area, 100, 24);
+    let Some(diffs) = &app.range_diffs else {
+        return;
+    };
+
+    let mut lines = Vec::new();
+    for diff in diffs {
+        lines.push(Line::from(Span::styled(
+            diff.summary(),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(diff.runs.iter().map(|run| Line::from(format!("  {run}"))));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from("Up/Down to scroll, Esc to close"));
+
+    // Don't scroll past the last line
+    let visible = popup_area.height.saturating_sub(2);
+    let max_scroll = u16::try_from(lines.len()).unwrap_or(u16::MAX).saturating_sub(visible);
+    app.range_diff_scroll = app.range_diff_scroll.min(max_scroll);
+
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Region Checkpoint"))
+        .style(Style::default().fg(Color::White))
+        .scroll((app.range_diff_scroll, 0));
+    frame.render_widget(popup, popup_area);
+}
+
+/// Draw the `:all-stacks` report: per thread a highlighted header, then its frames
+pub fn draw_all_stacks(frame: &mut Frame, area: Rect, app: &mut App)
+{
+    let popup_area = layout::centered_rect(area, 110, 40);
+    let Some(report) = &app.all_stacks else {
+        return;
+    };
+
+    let mut lines: Vec<Line> = report
+        .iter()
+        .map(|line| {
+            if line.starts_with("Thread ") {
+                Line::from(Span::styled(
+                    line.clone(),
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(line.clone())
+            }
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from("Up/Down/PgUp/PgDn to scroll, Esc to close"));
+
+    // Don't scroll past the last line
+    let visible = popup_area.height.saturating_sub(2);
+    let max_scroll = u16::try_from(lines.len()).unwrap_or(u16::MAX).saturating_sub(visible);
+    app.all_stacks_scroll = app.all_stacks_scroll.min(max_scroll);
+
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("All Stacks"))
+        .style(Style::default().fg(Color::White))
+        .scroll((app.all_stacks_scroll, 0));
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
+
+/// Draw the permission advice popup: why the error happened, what to try, and the environment
+pub fn draw_permission_advice(frame: &mut Frame, area: Rect, app: &App)
+{
+    let Some(advice) = &app.permission_advice else {
+        return;
+    };
+    let popup_area = layout::centered_rect(area, 100, 16);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            advice.summary(),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(advice.remedies.iter().map(|remedy| Line::from(format!("- {remedy}"))));
+    lines.push(Line::from(""));
+    lines.extend(
+        advice
+            .environment
+            .summary_lines()
+            .into_iter()
+            .map(|line| Line::from(Span::styled(line, Style::default().fg(Color::DarkGray)))),
+    );
+    lines.push(Line::from(""));
+    lines.push(Line::from("Press any key to close"));
+
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Permission Denied"))
+        .style(Style::default().fg(Color::White))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
+
+/// Draw the `:where` summary: stop reason, PC, function, regions and top frames
+pub fn draw_where(frame: &mut Frame, area: Rect, app: &App)
+{
+    let Some(summary) = &app.where_summary else {
+        return;
+    };
+    let popup_area = layout::centered_rect(area, 100, 16);
+
+    let mut lines = Vec::new();
+    if let Some((first, rest)) = summary.split_first() {
+        lines.push(Line::from(Span::styled(
+            first.clone(),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(rest.iter().map(|line| Line::from(line.clone())));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Press any key to close"));
+
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Where"))
+        .style(Style::default().fg(Color::White));
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
+
+/// Draw the `:notes` list: every note with what it is attached to
+pub fn draw_notes(frame: &mut Frame, area: Rect, app: &App)
+{
+    let Some(selected) = app.notes_list else {
+        return;
+    };
+    let popup_area = layout::centered_rect(area, 100, 20);
+
+    let addresses = app
+        .address_format
+        .formatter(app.notes.iter().filter_map(|note| note_address(&note.target)));
+    let rows: Vec<Row> = app
+        .notes
+        .iter()
+        .map(|note| {
+            let target = match &note.target {
+                NoteTarget::Address { address, .. } => addresses.format(*address),
+                NoteTarget::Breakpoint { id, address, .. } => format!("bp {id} @ {}", addresses.format(*address)),
+                NoteTarget::Timeline { .. } => note.target.to_string(),
+            };
+            Row::new(vec![
+                Cell::from(note.id.to_string()),
+                Cell::from(target),
+                Cell::from(note.text.clone()),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows, [Constraint::Length(4), Constraint::Percentage(40), Constraint::Min(10)])
+        .block(Block::default().borders(Borders::ALL).title("Notes (Enter to jump, Esc to close)"))
+        .header(Row::new(vec!["ID", "Target", "Note"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    let mut state = ratatui::widgets::TableState::default().with_selected(Some(selected));
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(table, popup_area, &mut state);
+}
+
+/// Draw the `:find` hits: each address with the function or region it is in
+pub fn draw_memory_search(frame: &mut Frame, area: Rect, app: &App)
+{
+    let Some(panel) = &app.memory_search else {
+        return;
+    };
+    let popup_area = layout::centered_rect(area, 100, 20);
+
+    let addresses = app
+        .address_format
+        .formatter(panel.hits.iter().map(|(address, _)| address.value()));
+    let rows: Vec<Row> = panel
+        .hits
+        .iter()
+        .map(|(address, place)| Row::new(vec![Cell::from(addresses.format(address.value())), Cell::from(place.clone())]))
+        .collect();
+    let count = if panel.truncated {
+        format!("first {} hits", panel.hits.len())
+    } else {
+        format!("{} hits", panel.hits.len())
+    };
+    let title = format!("Find {} - {count} (Enter to show in Memory, Esc to close)", panel.pattern);
+    let table = Table::new(rows, [Constraint::Length(20), Constraint::Min(10)])
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .header(Row::new(vec!["Address", "In"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    let mut state = ratatui::widgets::TableState::default().with_selected(Some(panel.selected));
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(table, popup_area, &mut state);
+}
+
+/// Draw the process picker: the filter, the last attach failure and the matching processes
+pub fn draw_process_picker(frame: &mut Frame, area: Rect, app: &App)
+{
+    let Some(picker) = &app.process_picker else {
+        return;
+    };
+    let mut notice = Vec::new();
+    if let Some(error) = &picker.error {
+        notice.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+        notice.extend(
+            picker
+                .remedies
+                .iter()
+                .map(|remedy| Line::from(Span::styled(format!("- {remedy}"), Style::default().fg(Color::Yellow)))),
+        );
+    }
+    let notice_height = u16::try_from(notice.len()).unwrap_or(u16::MAX);
+    let constraints: Box<[Constraint]> =
+        Box::new([Constraint::Length(1), Constraint::Length(notice_height), Constraint::Min(0)]);
+    let chunks = Layout::vertical(constraints).split(area);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("Filter: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{}▏", picker.filter)),
+        ])),
+        chunks[0],
+    );
+    frame.render_widget(Paragraph::new(notice).wrap(ratatui::widgets::Wrap { trim: false }), chunks[1]);
+
+    let visible = picker.visible();
+    let rows: Vec<Row> = visible
+        .iter()
+        .map(|process| {
+            let path = process
+                .path
+                .as_ref()
+                .map_or_else(String::new, |path| path.display().to_string());
+            Row::new(vec![
+                Cell::from(process.pid.0.to_string()),
+                Cell::from(process.name.clone()),
+                Cell::from(Span::styled(path, Style::default().fg(Color::DarkGray))),
+            ])
+        })
+        .collect();
+    let title = format!(
+        "Processes - {} of {} (type to filter, Enter to attach)",
+        visible.len(),
+        picker.processes.len()
+    );
+    let table = Table::new(rows, [Constraint::Length(8), Constraint::Length(24), Constraint::Min(10)])
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .header(Row::new(vec!["PID", "Name", "Path"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    let mut state = ratatui::widgets::TableState::default().with_selected(Some(picker.selected));
+    frame.render_stateful_widget(table, chunks[2], &mut state);
+}
+
+/// Address a note is attached to, if it is attached to one
+fn note_address(target: &NoteTarget) -> Option<u64>
+{
+    match target {
+        NoteTarget::Address { address, .. } | NoteTarget::Breakpoint { address, .. } => Some(*address),
+        NoteTarget::Timeline { .. } => None,
+    }
+}
+
 /// Draw the source code view with breakpoints
 pub fn draw_source_view(frame: &mut Frame, area: Rect, app: &mut App)
 {
-    // Split into source (left) and breakpoints (right) if widescreen
+    // Split into source (left) and breakpoints (right) if widescreen and there's room
     let constraints: Box<[Constraint]> = match app.layout_preset {
-        crate::app::LayoutPreset::Compact | crate::app::LayoutPreset::Standard => Box::new([Constraint::Percentage(100)]),
-        crate::app::LayoutPreset::Widescreen => Box::new([Constraint::Percentage(70), Constraint::Percentage(30)]),
+        crate::app::LayoutPreset::Widescreen if area.width >= layout::SPLIT_MIN_WIDTH => {
+            Box::new([Constraint::Percentage(70), Constraint::Percentage(30)])
+        }
+        _ => Box::new([Constraint::Percentage(100)]),
     };
 
     let chunks = Layout::horizontal(constraints).split(area);
@@ -505,16 +1246,26 @@ pub fn draw_source_view(frame: &mut Frame, area: Rect, app: &mut App)
     }
 }
 
-/// Draw source code with breakpoint gutter
-fn draw_source_code(frame: &mut Frame, area: Rect, app: &mut App)
+/// Color of a highlighted piece of source
+fn token_style(kind: TokenKind) -> Style
 {
-    // Get current PC to highlight (may be used for future features)
-    let _current_pc = if app.debugger.is_attached() && app.target_is_stopped {
-        app.debugger.read_registers().ok().map(|r| r.pc)
-    } else {
-        None
-    };
+    let style = Style::default();
+    match kind {
+        TokenKind::Keyword => style.fg(Color::Magenta),
+        TokenKind::Type => style.fg(Color::Cyan),
+        TokenKind::Function => style.fg(Color::Blue),
+        TokenKind::Macro => style.fg(Color::LightBlue),
+        TokenKind::String | TokenKind::Char => style.fg(Color::Green),
+        TokenKind::Lifetime | TokenKind::Number => style.fg(Color::LightYellow),
+        TokenKind::Comment => style.fg(Color::Gray).add_modifier(Modifier::ITALIC),
+        TokenKind::Attribute => style.fg(Color::Gray),
+    }
+}
 
+/// Draw source code with a breakpoint and current-line gutter
+#[allow(clippy::too_many_lines)]
+fn draw_source_code(frame: &mut Frame, area: Rect, app: &mut App)
+{
     // Get source file - prefer current_source_file, otherwise try to find from frames
     let source_file = app.current_source_file.clone().or_else(|| {
         if let Some(ref frames) = app.cached_stack_trace {
@@ -532,66 +1283,84 @@ fn draw_source_code(frame: &mut Frame, area: Rect, app: &mut App)
     });
 
     if let Some(ref file) = source_file {
-        if let Some(lines) = app.source_cache.get(file) {
+        // The line the selected frame is stopped at gets the PC arrow
+        let current_line = app.cached_stack_trace.as_ref().and_then(|frames| {
+            let frame = frames.get(app.stack_frames_state.selected().unwrap_or(0))?;
+            let location = frame.location.as_ref()?;
+            if location.file == *file { location.line } else { None }
+        });
+
+        if let Some(source) = app.source_cache.get_mut(file) {
             let viewport_height = area.height.saturating_sub(2) as usize;
-            let start_line = app.source_scroll.min(lines.len().saturating_sub(1));
-            let end_line = (start_line + viewport_height).min(lines.len());
+            let start_line = app.source_scroll.min(source.lines.len().saturating_sub(1));
+            let end_line = (start_line + viewport_height).min(source.lines.len());
 
             let mut source_lines = Vec::new();
-            for (i, line) in lines.iter().enumerate().skip(start_line).take(end_line - start_line) {
+            for i in start_line..end_line {
                 let line_num = i + 1;
-                let line_num_str = format!("{line_num:4} ");
+                let line_u32 = u32::try_from(line_num).unwrap_or(u32::MAX);
 
                 // Check for breakpoint at this line by matching source location
-                let line_u32 = u32::try_from(line_num).unwrap_or(u32::MAX);
                 let has_breakpoint = app.breakpoint_locations.iter().any(|(_addr, location_opt)| {
                     location_opt.as_ref().is_some_and(|location| {
                         location.file == *file && location.line == Some(line_u32)
                     })
                 });
+                let is_current = current_line == Some(line_u32);
 
-                // Check if this is the current line (use selected frame from stack view)
-                let is_current = if let Some(ref frames) = app.cached_stack_trace {
-                    let selected_idx = app.stack_frames_state.selected().unwrap_or(0);
-                    if let Some(frame) = frames.get(selected_idx)
-                        && let Some(ref location) = frame.location
-                    {
-                        let line_u32 = u32::try_from(line_num).unwrap_or(u32::MAX);
-                        location.file == *file && location.line == Some(line_u32)
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                };
-                
                 // Check if this line is selected (for setting breakpoints)
-                let is_selected = app.source_selected_line
-                    .map(|selected| selected == i)
-                    .unwrap_or(false);
-
-                let mut spans = vec![Span::styled(line_num_str, Style::default().fg(Color::DarkGray))];
+                let is_selected = app.source_selected_line.is_some_and(|selected| selected == i);
 
-                if has_breakpoint {
-                    spans.push(Span::styled("● ", Style::default().fg(Color::Red)));
-                } else {
-                    spans.push(Span::raw("  "));
+                // Lines the line tables have no code for can't be stopped at
+                let mut modifiers = Modifier::empty();
+                if source.has_code(line_num) == Some(false) {
+                    modifiers |= Modifier::DIM;
+                }
+                if is_current {
+                    modifiers |= Modifier::BOLD;
+                }
+                if is_selected {
+                    modifiers |= Modifier::UNDERLINED;
                 }
+                let line_style = |style: Style| {
+                    let style = style.add_modifier(modifiers);
+                    if is_current { style.bg(Color::DarkGray) } else { style }
+                };
 
-                let line_style = if is_current {
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD)
-                        .bg(Color::DarkGray)
-                } else if is_selected {
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::UNDERLINED)
+                let mut spans = vec![Span::styled(format!("{line_num:4} "), Style::default().fg(Color::DarkGray))];
+                spans.push(if has_breakpoint {
+                    Span::styled("●", Style::default().fg(Color::Red))
                 } else {
-                    Style::default().fg(Color::White)
-                };
+                    Span::raw(" ")
+                });
+                spans.push(if is_current {
+                    Span::styled("▶ ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                } else {
+                    Span::raw("  ")
+                });
 
-                spans.push(Span::styled(line.clone(), line_style));
+                let plain = Style::default().fg(Color::White);
+                let text = source.lines[i].clone();
+                match source.tokens(i) {
+                    Some(tokens) => {
+                        let mut at = 0;
+                        for token in tokens {
+                            if token.range.start > at {
+                                spans.push(Span::styled(text[at..token.range.start].to_string(), line_style(plain)));
+                            }
+                            spans.push(Span::styled(
+                                text[token.range.clone()].to_string(),
+                                line_style(token_style(token.kind)),
+                            ));
+                            at = token.range.end;
+                        }
+                        if at < text.len() {
+                            spans.push(Span::styled(text[at..].to_string(), line_style(plain)));
+                        }
+                    }
+                    // Past the highlighting cap
+                    None => spans.push(Span::styled(text, line_style(plain))),
+                }
                 source_lines.push(Line::from(spans));
             }
 
@@ -619,8 +1388,20 @@ fn draw_source_code(frame: &mut Frame, area: Rect, app: &mut App)
             "Process is running. Suspend the process (press 's') or wait for a breakpoint to view source."
         } else if app.cached_stack_trace.is_none() {
             "No stack trace available. The process may not have debug symbols."
-        } else if app.cached_stack_trace.as_ref().is_some_and(|frames| frames.is_empty()) {
+        } else if app.cached_stack_trace.as_ref().is_some_and(Vec::is_empty) {
             "Stack trace is empty. No frames available."
+        } else if let Some((image, level)) = app.selected_frame_without_debug_info() {
+            // Not the target's fault, so no advice about building with debug symbols
+            let notice = Paragraph::new(format!(
+                "No debug info in {image} ({level})\n\nFrames in this image have function names but no source lines, so \
+                 line breakpoints aren't available here.\nBreak on a function name (break <name>) or on the frame's \
+                 address (b in the Stack view) instead."
+            ))
+            .block(Block::default().borders(Borders::ALL).title("Source"))
+            .style(Style::default().fg(Color::DarkGray))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+            frame.render_widget(notice, area);
+            return;
         } else {
             // Check if we have frames but they don't have source locations
             let has_frames_without_source = app.cached_stack_trace.as_ref().is_some_and(|frames| {
@@ -645,7 +1426,13 @@ fn draw_source_code(frame: &mut Frame, area: Rect, app: &mut App)
 /// Draw breakpoints list
 fn draw_breakpoints_list(frame: &mut Frame, area: Rect, app: &mut App)
 {
-    let rows: Vec<Row> = app
+    let addresses = app
+        .address_format
+        .formatter(app.cached_breakpoints.iter().map(|bp| bp.address));
+    let columns = layout::fit_columns(&BREAKPOINT_COLUMNS, &[3], addresses.width());
+    let keep = layout::visible_columns(area.width, &columns);
+    let now = std::time::Instant::now();
+    let mut rows: Vec<Row> = app
         .cached_breakpoints
         .iter()
         .map(|bp| {
@@ -659,51 +1446,87 @@ fn draw_breakpoints_list(frame: &mut Frame, area: Rect, app: &mut App)
                 "-"
             };
 
-            let kind_str = match bp.kind {
-                ferros_core::BreakpointKind::Software => "SW",
-                ferros_core::BreakpointKind::Hardware => "HW",
-                ferros_core::BreakpointKind::Watchpoint => "WP",
+            // Armed hardware breakpoints show the debug register slot they hold
+            let kind_str = match bp.hardware_slot {
+                Some(slot) => format!("{}{slot}", breakpoint_kind_label(bp.kind)),
+                None => breakpoint_kind_label(bp.kind).to_string(),
             };
+            // One-shot breakpoints go away after their first hit
+            let once = if bp.options.one_shot { ONE_SHOT_MARKER } else { "" };
 
-            Row::new(vec![
-                Cell::from(format!("{}", bp.id.raw())),
+            let marker = if app.notes.for_breakpoint(bp.address.value()).next().is_some() {
+                format!(" {NOTE_MARKER}")
+            } else {
+                String::new()
+            };
+            let cells = vec![
+                Cell::from(format!("{}{marker}", bp.id.raw())),
                 Cell::from(state_str),
-                Cell::from(kind_str),
-                Cell::from(format!("{}", bp.address)),
+                Cell::from(format!("{kind_str}{once}")),
+                Cell::from(addresses.format(bp.address)),
                 Cell::from(format!("{}", bp.hit_count)),
-            ])
+                Cell::from(match bp.ignore_count {
+                    0 => "-".to_string(),
+                    remaining => remaining.to_string(),
+                }),
+                Cell::from(format_overhead(&bp.stats, now)),
+                Cell::from(format_hit_rate(&bp.stats, now)),
+                Cell::from(match bp.stats.condition_failures {
+                    0 => "-".to_string(),
+                    failures => failures.to_string(),
+                }),
+            ];
+            let row = Row::new(layout::select(cells, &keep));
+            if bp.stats.is_expensive(now) {
+                row.style(Style::default().fg(Color::Red))
+            } else {
+                row
+            }
         })
         .collect();
+    // Restored breakpoints still waiting to resolve, listed by where they'll resolve from
+    rows.extend(app.pending_breakpoints.iter().map(|pending| {
+        let saved = &pending.saved;
+        let once = if saved.one_shot { ONE_SHOT_MARKER } else { "" };
+        let cells = vec![
+            Cell::from("-"),
+            Cell::from(if saved.enabled { "○" } else { "-" }),
+            Cell::from(format!("{}{once}", breakpoint_kind_label(saved.kind))),
+            Cell::from(saved.to_string()),
+            Cell::from("-"),
+            Cell::from("-"),
+            Cell::from("-"),
+            Cell::from("-"),
+            Cell::from("-"),
+        ];
+        Row::new(layout::select(cells, &keep)).style(Style::default().fg(Color::DarkGray))
+    }));
 
-    let constraints: Box<[Constraint]> = vec![
-        Constraint::Length(5),
-        Constraint::Length(2),
-        Constraint::Length(3),
-        Constraint::Length(18),
-        Constraint::Length(5),
-    ]
-    .into_boxed_slice();
-
-    let table = Table::new(rows, constraints)
+    let table = Table::new(rows, layout::column_constraints(&columns, &keep))
         .block(Block::default().borders(Borders::ALL).title("Breakpoints"))
-        .header(Row::new(vec![
-            Cell::from("ID").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("E").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("K").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Address").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Hits").style(Style::default().add_modifier(Modifier::BOLD)),
-        ]))
+        .header(header_row(&columns, &keep))
         .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
 
     frame.render_stateful_widget(table, area, &mut app.breakpoints_state);
 }
 
+/// Short label for a breakpoint kind in the Kind column
+fn breakpoint_kind_label(kind: ferros_core::BreakpointKind) -> &'static str
+{
+    match kind {
+        ferros_core::BreakpointKind::Software => "SW",
+        ferros_core::BreakpointKind::Hardware => "HW",
+        ferros_core::BreakpointKind::Watchpoint => "WP",
+    }
+}
+
 /// Draw the call stack and frame locals view
 pub fn draw_stack_view(frame: &mut Frame, area: Rect, app: &mut App)
 {
-    // Split area into stack frames (left) and frame info (right)
+    // Split area into stack frames (left) and frame info (right); too narrow for both collapses the details
     let constraints: Box<[Constraint]> = match app.layout_preset {
+        _ if area.width < layout::SPLIT_MIN_WIDTH => Box::new([Constraint::Percentage(100)]),
         crate::app::LayoutPreset::Compact => Box::new([Constraint::Percentage(100)]),
         crate::app::LayoutPreset::Standard => Box::new([Constraint::Percentage(50), Constraint::Percentage(50)]),
         crate::app::LayoutPreset::Widescreen => Box::new([Constraint::Percentage(40), Constraint::Percentage(60)]),
@@ -733,16 +1556,22 @@ fn draw_stack_frames(frame: &mut Frame, area: Rect, app: &mut App)
         return;
     }
 
+    let keep = layout::visible_columns(area.width, &STACK_COLUMNS);
+    let addresses = app.address_format.formatter(frames.iter().map(|frame| frame.pc));
     let rows: Vec<Row> = frames
         .iter()
         .map(|frame| {
-            let prefix = if frame.kind.is_inlined() { "↪ " } else { "  " };
+            let prefix = match frame.kind {
+                FrameKind::Inlined { .. } => "↪ ",
+                FrameKind::Async { .. } => "∿ ",
+                FrameKind::Physical => "  ",
+            };
             let symbol_name = frame
                 .symbol
                 .as_ref()
                 .map_or("<unknown>", ferros_core::SymbolName::display_name);
             let location_str = frame.location.as_ref().map_or_else(
-                || format!("{}", frame.pc),
+                || addresses.format(frame.pc),
                 |loc| {
                     if let Some(line) = loc.line {
                         format!("{}:{}", loc.file.split('/').next_back().unwrap_or(&loc.file), line)
@@ -752,29 +1581,121 @@ fn draw_stack_frames(frame: &mut Frame, area: Rect, app: &mut App)
                 },
             );
 
-            Row::new(vec![
-                Cell::from(format!("{prefix}#{}", frame.index)),
+            let marker = if app.notes.at_address(frame.pc.value()).next().is_some() {
+                format!(" {NOTE_MARKER}")
+            } else {
+                String::new()
+            };
+            let cells = vec![
+                Cell::from(format!("{prefix}#{}{marker}", frame.index)),
                 Cell::from(symbol_name),
                 Cell::from(location_str),
-            ])
+            ];
+            let row = Row::new(layout::select(cells, &keep));
+            // Highlight the single occurrence kept for each collapsed recursion
+            let recursive = app
+                .recursion_cycles
+                .iter()
+                .any(|cycle| (cycle.first_frame..cycle.first_frame + cycle.frame_count).contains(&frame.index));
+            if recursive {
+                row.style(Style::default().fg(Color::Magenta))
+            } else {
+                row
+            }
         })
         .collect();
 
-    let constraints: Box<[Constraint]> = Box::new([Constraint::Length(5), Constraint::Min(20), Constraint::Min(20)]);
-
-    let table = Table::new(rows, constraints)
-        .block(Block::default().borders(Borders::ALL).title("Call Stack"))
-        .header(Row::new(vec![
-            Cell::from("Frame").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Function").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("Location").style(Style::default().add_modifier(Modifier::BOLD)),
-        ]))
+    let title = match app.recursion_cycles.first() {
+        Some(cycle) => format!("Call Stack ({cycle})"),
+        None => "Call Stack".to_string(),
+    };
+    let table = Table::new(rows, layout::column_constraints(&STACK_COLUMNS, &keep))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .header(header_row(&STACK_COLUMNS, &keep))
         .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
 
     frame.render_stateful_widget(table, area, &mut app.stack_frames_state);
 }
 
+pub fn draw_disassembly(frame: &mut Frame, area: Rect, app: &mut App)
+{
+    let view = &app.disassembly;
+    let notice = if !app.debugger.is_attached() {
+        Some("Not attached to a process. Launch or attach to a process first.".to_string())
+    } else if !app.target_is_stopped {
+        Some("Process is running. Suspend the process (press 's') or wait for a breakpoint.".to_string())
+    } else if let Some(ref error) = view.error {
+        Some(format!("Couldn't disassemble at the PC: {error}"))
+    } else if view.listing.is_empty() {
+        Some("Press 0 to disassemble at the PC.".to_string())
+    } else {
+        None
+    };
+    if let Some(notice) = notice {
+        let widget = Paragraph::new(notice)
+            .block(Block::default().borders(Borders::ALL).title("Disassembly"))
+            .style(Style::default().fg(Color::Yellow))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+        frame.render_widget(widget, area);
+        return;
+    }
+
+    let keep = layout::visible_columns(area.width, &DISASSEMBLY_COLUMNS);
+    let addresses = app.address_format.formatter(view.listing.iter().map(|insn| insn.address));
+    let rows: Vec<Row> = view
+        .listing
+        .iter()
+        .map(|insn| {
+            let marker = match app.cached_breakpoints.iter().find(|bp| bp.address == insn.address) {
+                Some(bp) if bp.enabled => Span::styled("●", Style::default().fg(Color::Red)),
+                Some(_) => Span::styled("○", Style::default().fg(Color::DarkGray)),
+                None => Span::raw(""),
+            };
+            let bytes = insn
+                .bytes
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let cells = vec![
+                Cell::from(marker),
+                Cell::from(addresses.format(insn.address)),
+                Cell::from(bytes),
+                Cell::from(insn.text()),
+            ];
+            let row = Row::new(layout::select(cells, &keep));
+            if insn.is_current_pc {
+                row.style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                        .bg(Color::DarkGray),
+                )
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let title = match view
+        .selected_address()
+        .and_then(|address| app.debugger.function_name(address))
+    {
+        Some(function) => format!("Disassembly: {}", function.display_name()),
+        None => "Disassembly".to_string(),
+    };
+    let table = Table::new(rows, layout::column_constraints(&DISASSEMBLY_COLUMNS, &keep))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .header(header_row(&DISASSEMBLY_COLUMNS, &keep))
+        .row_highlight_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+
+    let selected = view.selected;
+    app.disassembly.table.select(Some(selected));
+    frame.render_stateful_widget(table, area, &mut app.disassembly.table);
+}
+
 /// Draw frame details (locals, registers, etc.)
 fn draw_frame_details(frame: &mut Frame, area: Rect, app: &App)
 {
@@ -782,6 +1703,9 @@ fn draw_frame_details(frame: &mut Frame, area: Rect, app: &App)
     let selected_frame = app.cached_stack_trace.as_ref().and_then(|frames| frames.get(selected_idx));
 
     if let Some(selected_frame) = selected_frame {
+        let addresses = app
+            .address_format
+            .formatter([selected_frame.pc, selected_frame.sp, selected_frame.fp]);
         let mut lines = vec![
             Line::from(vec![
                 Span::styled("Frame #", Style::default().fg(Color::Yellow)),
@@ -795,6 +1719,10 @@ fn draw_frame_details(frame: &mut Frame, area: Rect, app: &App)
                 Span::styled("Function: ", Style::default().fg(Color::Yellow)),
                 Span::raw(symbol.display_name()),
             ]));
+            lines.push(Line::from(vec![
+                Span::styled("Language: ", Style::default().fg(Color::Yellow)),
+                Span::raw(symbol.language().to_string()),
+            ]));
         }
 
         if let Some(ref location) = selected_frame.location {
@@ -818,9 +1746,9 @@ fn draw_frame_details(frame: &mut Frame, area: Rect, app: &App)
             ]));
             for param in &selected_frame.parameters {
                 let param_str = match (&param.name, &param.type_name) {
-                    (Some(name), Some(ty)) => format!("  {}: {}", name, ty),
-                    (Some(name), None) => format!("  {}", name),
-                    (None, Some(ty)) => format!("  <unnamed>: {}", ty),
+                    (Some(name), Some(ty)) => format!("  {name}: {ty}"),
+                    (Some(name), None) => format!("  {name}"),
+                    (None, Some(ty)) => format!("  <unnamed>: {ty}"),
                     (None, None) => "  <unknown>".to_string(),
                 };
                 lines.push(Line::from(Span::raw(param_str)));
@@ -830,16 +1758,22 @@ fn draw_frame_details(frame: &mut Frame, area: Rect, app: &App)
         lines.push(Line::from(""));
         lines.push(Line::from(vec![
             Span::styled("PC: ", Style::default().fg(Color::Yellow)),
-            Span::raw(format!("{}", selected_frame.pc)),
+            Span::raw(addresses.format(selected_frame.pc)),
         ]));
         lines.push(Line::from(vec![
             Span::styled("SP: ", Style::default().fg(Color::Yellow)),
-            Span::raw(format!("{}", selected_frame.sp)),
+            Span::raw(addresses.format(selected_frame.sp)),
         ]));
         if selected_frame.fp.value() != 0 {
             lines.push(Line::from(vec![
                 Span::styled("FP: ", Style::default().fg(Color::Yellow)),
-                Span::raw(format!("{}", selected_frame.fp)),
+                Span::raw(addresses.format(selected_frame.fp)),
+            ]));
+        }
+        for text in app.notes_at(selected_frame.pc) {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{NOTE_MARKER} Note: "), Style::default().fg(Color::Yellow)),
+                Span::raw(text.to_string()),
             ]));
         }
 
@@ -848,6 +1782,13 @@ fn draw_frame_details(frame: &mut Frame, area: Rect, app: &App)
             Span::styled("Status: ", Style::default().fg(Color::Yellow)),
             Span::raw(format!("{:?}", selected_frame.status)),
         ]));
+        if let Some(diagnostic) = app.selected_unwind_diagnostic() {
+            let color = if diagnostic.is_degraded() { Color::Red } else { Color::White };
+            lines.push(Line::from(vec![
+                Span::styled("Unwind: ", Style::default().fg(Color::Yellow)),
+                Span::styled(diagnostic.summary(), Style::default().fg(color)),
+            ]));
+        }
 
         let details = Paragraph::new(lines)
             .block(Block::default().borders(Borders::ALL).title("Frame Details"))
@@ -863,24 +1804,40 @@ fn draw_frame_details(frame: &mut Frame, area: Rect, app: &App)
 }
 
 /// Draw the timeline/log panel
-pub fn draw_timeline(frame: &mut Frame, area: Rect, app: &App)
+pub fn draw_timeline(frame: &mut Frame, area: Rect, app: &mut App)
 {
-    let viewport_height = area.height.saturating_sub(2) as usize;
+    // The search prompt takes the last row
+    let prompt_rows = usize::from(app.timeline.search_input.is_some());
+    let viewport_height = (area.height.saturating_sub(2) as usize).saturating_sub(prompt_rows);
+    app.timeline.page_rows = viewport_height;
     let mut timeline_lines = Vec::new();
 
+    let visible = app.visible_timeline();
     if app.timeline_log.is_empty() {
         timeline_lines.push(Line::from("No timeline events yet."));
+    } else if visible.is_empty() {
+        timeline_lines.push(Line::from(format!(
+            "No {} entries; press f to change the filter.",
+            app.timeline.filter.label()
+        )));
     } else {
-        let start_idx = app.timeline_log.len().saturating_sub(viewport_height);
-        for entry in app.timeline_log.iter().skip(start_idx) {
+        let (rows, selected_row) = crate::timeline::window(&visible, viewport_height, app.timeline.selected);
+        for row in rows {
+            let entry = &app.timeline_log[visible[row]];
             let elapsed = entry.timestamp.elapsed();
             let time_str = format!("{:6.2}s", elapsed.as_secs_f64());
 
             let kind_color = match entry.kind {
                 crate::app::TimelineEntryKind::Resume => Color::Green,
-                crate::app::TimelineEntryKind::BreakpointHit => Color::Yellow,
+                crate::app::TimelineEntryKind::BreakpointHit | crate::app::TimelineEntryKind::Warning => Color::Yellow,
                 crate::app::TimelineEntryKind::Signal => Color::Magenta,
+                crate::app::TimelineEntryKind::Panic => Color::LightRed,
                 crate::app::TimelineEntryKind::Output => Color::Cyan,
+                crate::app::TimelineEntryKind::Diagnostic => Color::Blue,
+                crate::app::TimelineEntryKind::MemoryDiff => Color::LightYellow,
+                crate::app::TimelineEntryKind::Pin => Color::LightCyan,
+                crate::app::TimelineEntryKind::ThreadLocal => Color::LightMagenta,
+                crate::app::TimelineEntryKind::Image => Color::LightBlue,
                 crate::app::TimelineEntryKind::Stop | crate::app::TimelineEntryKind::Error => Color::Red,
             };
 
@@ -891,9 +1848,16 @@ pub fn draw_timeline(frame: &mut Frame, area: Rect, app: &App)
                 crate::app::TimelineEntryKind::Signal => "SIG",
                 crate::app::TimelineEntryKind::Output => "OUT",
                 crate::app::TimelineEntryKind::Error => "ERR",
+                crate::app::TimelineEntryKind::Warning => "WARN",
+                crate::app::TimelineEntryKind::Diagnostic => "DIAG",
+                crate::app::TimelineEntryKind::MemoryDiff => "DIFF",
+                crate::app::TimelineEntryKind::Pin => "PIN",
+                crate::app::TimelineEntryKind::ThreadLocal => "TLS",
+                crate::app::TimelineEntryKind::Image => "IMG",
+                crate::app::TimelineEntryKind::Panic => "PANIC",
             };
 
-            timeline_lines.push(Line::from(vec![
+            let mut spans = vec![
                 Span::styled(time_str, Style::default().fg(Color::DarkGray)),
                 Span::raw(" "),
                 Span::styled(
@@ -901,21 +1865,70 @@ pub fn draw_timeline(frame: &mut Frame, area: Rect, app: &App)
                     Style::default().fg(kind_color).add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" "),
-                Span::raw(entry.message.clone()),
-            ]));
+            ];
+            spans.extend(highlight_matches(&entry.message, &app.timeline.query));
+            for note in app.notes.for_timeline(app.session_id, entry.sequence) {
+                spans.push(Span::styled(
+                    format!("  {NOTE_MARKER} {}", note.text),
+                    Style::default().fg(Color::LightYellow),
+                ));
+            }
+            // How long the event took to reach the UI, to spot a slow debugger rather than a slow target
+            if let Some(timing) = entry.timing {
+                let delivery = crate::app::format_latency(timing.delivery);
+                let latency = match timing.handler {
+                    Some(handler) => format!("  +{delivery} (handler {})", crate::app::format_latency(handler)),
+                    None => format!("  +{delivery}"),
+                };
+                spans.push(Span::styled(latency, Style::default().fg(Color::DarkGray)));
+            }
+            let mut line = Line::from(spans);
+            if selected_row == Some(row) {
+                line = line.style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+            timeline_lines.push(line);
         }
     }
+    if let Some(input) = &app.timeline.search_input {
+        let prompt = Span::styled(format!("/{input}"), Style::default().fg(Color::Yellow));
+        timeline_lines.push(Line::from(prompt));
+    }
 
+    let mut title = format!("Timeline [{}]", app.timeline.filter.label());
+    if !app.timeline.query.is_empty() {
+        let _ = write!(title, " /{}", app.timeline.query);
+    }
+    if !app.timeline.is_following() {
+        let _ = write!(title, " - {} new entries (End to follow)", app.timeline.unseen);
+    }
     let timeline = Paragraph::new(timeline_lines)
-        .block(Block::default().borders(Borders::ALL).title("Timeline"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .style(Style::default().fg(Color::White))
         .wrap(ratatui::widgets::Wrap { trim: true });
 
     frame.render_widget(timeline, area);
 }
 
+/// Split `message` into spans with search matches highlighted
+fn highlight_matches(message: &str, query: &str) -> Vec<Span<'static>>
+{
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for range in crate::timeline::match_ranges(message, query) {
+        spans.push(Span::raw(message[end..range.start].to_string()));
+        spans.push(Span::styled(
+            message[range.clone()].to_string(),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+        end = range.end;
+    }
+    spans.push(Span::raw(message[end..].to_string()));
+    spans
+}
+
 /// Draw the help page
-pub fn draw_help(frame: &mut Frame, area: Rect, _app: &App)
+#[allow(clippy::vec_init_then_push, clippy::too_many_lines)]
+pub fn draw_help(frame: &mut Frame, area: Rect, app: &mut App)
 {
     let mut lines = Vec::new();
 
@@ -939,6 +1952,10 @@ pub fn draw_help(frame: &mut Frame, area: Rect, _app: &App)
     lines.push(Line::from("  7 - Stack: Call stack and frame details"));
     lines.push(Line::from("  8 - Timeline: Event log of debugger operations"));
     lines.push(Line::from("  9 - Help: This help page"));
+    lines.push(Line::from(
+        "  0 - Disassembly: Instructions around the PC (again to return to the PC)",
+    ));
+    lines.push(Line::from("  :mem <addr> - Memory: Hex and ASCII dump; Enter on an address register opens it too"));
     lines.push(Line::from(""));
 
     // Navigation within views
@@ -948,6 +1965,28 @@ pub fn draw_help(frame: &mut Frame, area: Rect, _app: &App)
     lines.push(Line::from("  ↑/↓ - Navigate up/down in current view (registers, threads, stack, etc.)"));
     lines.push(Line::from("  n - Next frame (in stack view)"));
     lines.push(Line::from("  p - Previous frame (in stack view)"));
+    lines.push(Line::from("  Space - Mark/unmark highlighted thread (in threads view)"));
+    lines.push(Line::from("  a - Mark/unmark all threads (in threads view)"));
+    lines.push(Line::from("  / - Search; n/N next/previous match (in timeline view)"));
+    lines.push(Line::from(
+        "  f - Filter: All/Stops/Breakpoints/Signals/Errors (in timeline view)",
+    ));
+    lines.push(Line::from(
+        "  PgUp/PgDn - Page back; End follows new entries (in timeline view)",
+    ));
+    lines.push(Line::from("  PgUp/PgDn, ↑/↓ - Move by a page or a row (in memory view)"));
+    lines.push(Line::from("  Enter - Follow the selected register or word into memory (in registers and memory views)"));
+    lines.push(Line::from("  e - Type a hex value for the selected register; Enter writes it, Esc cancels (in registers view)"));
+    lines.push(Line::from("  ←/→, Backspace/[, ] - Select a word; go back or forward through followed pointers (in memory view)"));
+    lines.push(Line::from(
+        "  t - Show transcript timestamps (in output view, with --transcript)",
+    ));
+    lines.push(Line::from(
+        "  i - Type a line for a launched target's stdin; Enter sends it, Ctrl+D closes stdin (in output view)",
+    ));
+    lines.push(Line::from(
+        "  Enter - Open the entry's breakpoint, checkpoint or thread (in timeline view)",
+    ));
     lines.push(Line::from(""));
 
     // Program Control
@@ -956,6 +1995,15 @@ pub fn draw_help(frame: &mut Frame, area: Rect, _app: &App)
     ]));
     lines.push(Line::from("  s - Suspend: Stop the process execution"));
     lines.push(Line::from("  r - Resume: Continue execution from current position"));
+    lines.push(Line::from("      Asks first (y/N) if breakpoints set at this stop failed to install or are pending"));
+    lines.push(Line::from("  i - Step instruction: Execute one instruction on the active thread (also :stepi)"));
+    lines.push(Line::from("  o - Step over: Like i, but runs a call to completion (also :nexti)"));
+    lines.push(Line::from("  u - Step out: Run until the current function returns (also :finish)"));
+    lines.push(Line::from("  Ctrl+R - Restart: Kill a launched target and launch it again with its breakpoints (also :restart)"));
+    lines.push(Line::from("  :kill - Terminate the target and leave ferros unattached"));
+    lines.push(Line::from("  In Threads view, s/r suspend/resume only the marked threads"));
+    lines.push(Line::from("  In Threads view, Enter makes the highlighted thread active and opens its stack"));
+    lines.push(Line::from("  (or the highlighted thread when none are marked)"));
     lines.push(Line::from("  Note: Process must be stopped to inspect registers, stack, or source"));
     lines.push(Line::from(""));
 
@@ -964,10 +2012,16 @@ pub fn draw_help(frame: &mut Frame, area: Rect, _app: &App)
         Span::styled("BREAKPOINTS", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
     ]));
     lines.push(Line::from("  b - Toggle breakpoint:"));
-    lines.push(Line::from("      • In Source view: at selected/current line"));
+    lines.push(Line::from("      • In Source view: at every address of the selected/current line"));
     lines.push(Line::from("      • In Stack view: at selected frame's PC"));
+    lines.push(Line::from("      • In Disassembly view: at the selected instruction"));
     lines.push(Line::from("      • Other views: at current PC"));
+    lines.push(Line::from("  c - Stack view: one-shot break where the frame returns"));
+    lines.push(Line::from("  C - Stack view: break at the frame's callee (frame below)"));
     lines.push(Line::from("  B - Open breakpoint editor to add breakpoints manually"));
+    lines.push(Line::from(format!(
+        "  {ONE_SHOT_MARKER} after the kind in the Breakpoints list marks a one-shot breakpoint"
+    )));
     lines.push(Line::from(""));
 
     // Command Palette
@@ -976,14 +2030,71 @@ pub fn draw_help(frame: &mut Frame, area: Rect, _app: &App)
     ]));
     lines.push(Line::from("  Press ':' to open the command palette"));
     lines.push(Line::from("  Commands for breakpoint management:"));
-    lines.push(Line::from("    break <address>  or  b <address>  - Add breakpoint at address (hex: 0x1000)"));
+    lines.push(Line::from("    break <loc>      or  b <loc>      - Add breakpoint at a hex address, function or file:line"));
+    lines.push(Line::from("    tbreak <loc>     or  tb <loc>     - Add a breakpoint that is removed after its first hit"));
+    lines.push(Line::from("    break -f <loc>                    - Add it even where no instruction starts (x86-64)"));
     lines.push(Line::from("    delete <id>      or  d <id>       - Remove breakpoint by ID"));
-    lines.push(Line::from("    enable <id>      or  e <id>       - Enable a disabled breakpoint"));
-    lines.push(Line::from("    disable <id>                        - Disable a breakpoint"));
+    lines.push(Line::from("    enable <id|group> or e <id>       - Enable a breakpoint, or every one in a group"));
+    lines.push(Line::from("    disable <id|group>                  - Disable a breakpoint, or every one in a group"));
+    lines.push(Line::from(
+        "    ignore <id> <n>                     - Skip the next n hits of a breakpoint",
+    ));
+    lines.push(Line::from(
+        "    bp save|load [path]               - Save or restore breakpoints (default ~/.ferros/breakpoints-<program>.json)",
+    ));
     lines.push(Line::from("  Commands for navigation:"));
     lines.push(Line::from("    frame <index>    or  f <index>    - Jump to specific stack frame"));
-    lines.push(Line::from("    thread <id>      or  t <id>       - Switch active thread"));
+    lines.push(Line::from("    thread <id|name> or  t <id|name>  - Switch active thread"));
+    lines.push(Line::from("    backtrace        or  bt           - Refresh and show the stack view"));
+    lines.push(Line::from("    bt why                            - Explain how each frame was unwound (timeline)"));
+    lines.push(Line::from("    where                             - Stop reason, PC, function, regions and top frames (also w)"));
+    lines.push(Line::from("    all-stacks                        - Every thread's top 16 frames in one report, e.g. for deadlocks"));
+    lines.push(Line::from("    modules                           - Loaded images: base address, debug info, eh_frame and symbol count"));
+    lines.push(Line::from("    dsym add <path>                   - Also look for dSYM bundles in path (a .dSYM or a folder of them)"));
+    lines.push(Line::from("    source-map [add <from> <to>]      - List source remaps or add one from a build path to a local one"));
+    lines.push(Line::from("    async-stacks on|off               - Show the async fns a blocked-on task awaits (∿) above its executor frame"));
+    lines.push(Line::from("    mem <addr>                        - Hex dump from a hex address or symbol[+offset]; ?? is unreadable"));
+    lines.push(Line::from("    print <expr>                      - Evaluate registers ($pc, $x0), literals, *deref and + - * & in the selected frame"));
+    lines.push(Line::from("    print <expr> as <type>            - Show the value of a Rust type at an address (String, Vec, Option, structs)"));
+    lines.push(Line::from("    find <payload>                    - Search readable memory for hex bytes, a sized integer or a \"string\"; Enter shows a hit"));
+    lines.push(Line::from("    write <addr> <payload>            - Write hex bytes (de ad), a sized integer (u32:0x41414141) or a \"string\" to memory"));
+    lines.push(Line::from("    handle <signal> stop|pass|ignore  - Stop at a signal, pass it to the target, or drop it; listed in Overview"));
+    lines.push(Line::from("    stop-policy all|one               - Hold every thread at a breakpoint, or only the one that hit it"));
+    lines.push(Line::from("  Commands for pinned values:"));
+    lines.push(Line::from("    pin <name> <addr> <type>          - Re-read addr at every stop (u8..u64, f32, f64, cstr, bytes:N)"));
+    lines.push(Line::from("    unpin <name>                      - Remove a pinned value"));
+    lines.push(Line::from("    pin list                          - List pinned values (timeline)"));
+    lines.push(Line::from("    pin live                          - Toggle re-reading pins every second while running"));
+    lines.push(Line::from("    tls <name> [<thread>|all]         - Read a thread-local on the active, given or every thread"));
+    lines.push(Line::from("  Commands for notes (saved with the session):"));
+    lines.push(Line::from("    note <text>                       - Note the selected frame, breakpoint or timeline entry"));
+    lines.push(Line::from("    note addr <addr> <text>           - Note an address"));
+    lines.push(Line::from("    note rm <id>                      - Remove a note"));
+    lines.push(Line::from("    notes                             - List notes; Enter jumps to one"));
+    lines.push(Line::from("  Commands for checkpoints:"));
+    lines.push(Line::from("    checkpoints on|off|clear          - Also capture stack memory at every stop"));
+    lines.push(Line::from("    back / forward                    - Inspect an earlier / later stop (read-only)"));
+    lines.push(Line::from("    live                              - Return to the live target"));
+    lines.push(Line::from("    history                           - Every stop this session; Enter shows its stack, v its registers"));
+    lines.push(Line::from("    checkpoint add <addr> <n> [name]  - Register n bytes at addr to diff across a resume"));
+    lines.push(Line::from("    checkpoint run                    - Capture the ranges, resume, and diff them at the next stop"));
+    lines.push(Line::from("    checkpoint list|clear             - List or forget the registered ranges"));
     lines.push(Line::from("  Other commands:"));
+    lines.push(Line::from("    perf                              - Event latency (p50/p95) from backend to UI"));
+    lines.push(Line::from("    opslog on [<path>] | off          - Log every backend operation as JSON lines (~/.ferros/ops-<pid>.jsonl)"));
+    lines.push(Line::from("    set [<key> <value>]               - Stop emphasis: stop.flash on|off, stop.toast_ms <ms>, stop.bell on|off"));
+    lines.push(Line::from("                                        Addresses: address.format full|trimmed|short"));
+    lines.push(Line::from("                                        Ask before resuming past failed breakpoints: resume.confirm on|off"));
+    lines.push(Line::from("                                        Developer commands: developer.tools on|off"));
+    lines.push(Line::from("                                        Stop at Rust panics: break.panic auto|on|off (auto: launched targets)"));
+    lines.push(Line::from("                                        Main-thread hang probe: hang.probe off|on|<interval>"));
+    lines.push(Line::from("                                        Samples in one function to count as a hang: hang.samples <n>"));
+    lines.push(Line::from("                                        Suspend the target at a hang: hang.autostop on|off"));
+    lines.push(Line::from("    set $<register> = <expr>          - Write a register of the active thread, e.g. set $pc = $lr or set $x0 = 42"));
+    lines.push(Line::from("    debug replay-stop                 - Run the last stop through the UI again, tagged [replay]"));
+    lines.push(Line::from("    debug emit-stop <reason>          - Handle a made-up stop: signal <n>, breakpoint <addr>, exited <code>,"));
+    lines.push(Line::from("                                        step <pc>, watch <addr> <pc>, overflow <sp> <guard>, suspended,"));
+    lines.push(Line::from("                                        running, unknown"));
     lines.push(Line::from("    help             or  h            - Show this help"));
     lines.push(Line::from("  Use ↑/↓ in command palette to navigate command history"));
     lines.push(Line::from(""));
@@ -994,6 +2105,9 @@ pub fn draw_help(frame: &mut Frame, area: Rect, _app: &App)
     ]));
     lines.push(Line::from("  ? or h - Toggle help page"));
     lines.push(Line::from("  l - Cycle layout presets (Compact/Standard/Widescreen)"));
+    lines.push(Line::from("  w - Where am I: summarize the stopped thread's location (also in the timeline)"));
+    lines.push(Line::from("  x - Cycle address format (full 16 digits, trimmed to the table, short 12 digits)"));
+    lines.push(Line::from("  e - Explain a permission error and what to do about it"));
     lines.push(Line::from("  Esc - Quit debugger (or close command palette/breakpoint editor)"));
     lines.push(Line::from("  Ctrl+Q - Force quit"));
     lines.push(Line::from(""));
@@ -1014,14 +2128,37 @@ pub fn draw_help(frame: &mut Frame, area: Rect, _app: &App)
     lines.push(Line::from("  • Use ↑/↓ to scroll, 'b' to toggle breakpoint at selected line"));
     lines.push(Line::from("  • Current execution line is highlighted in yellow"));
     lines.push(Line::from("  • Breakpoints are shown with ● in the source view"));
+    lines.push(Line::from(
+        "  • Frames in images without debug info have no source; 'b' is unavailable there",
+    ));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![Span::styled(
+        "Disassembly View (0):",
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+    )]));
+    lines.push(Line::from(
+        "  • Use ↑/↓ to select an instruction; more are decoded as you scroll past either end",
+    ));
+    lines.push(Line::from(
+        "  • The instruction at the PC is highlighted in yellow; ● / ○ mark enabled / disabled breakpoints",
+    ));
+    lines.push(Line::from("  • x86-64 code only scrolls up to the start of a known function"));
     lines.push(Line::from(""));
     lines.push(Line::from("  • Timeline view shows chronological log of all events"));
     lines.push(Line::from("  • For best debugging, build programs with debug symbols"));
 
+    // Rows after wrapping, so the last line can still be scrolled into view on narrow screens
+    let inner_width = usize::from(area.width.saturating_sub(2)).max(1);
+    let rows: usize = lines.iter().map(|line| line.width().div_ceil(inner_width).max(1)).sum();
+    let max_scroll = rows.saturating_sub(usize::from(area.height.saturating_sub(2)));
+    app.help_scroll = app.help_scroll.min(max_scroll);
+
+    let title = if max_scroll > 0 { "Help (↑/↓ to scroll)" } else { "Help" };
     let help_widget = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("Help"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .style(Style::default().fg(Color::White))
-        .wrap(ratatui::widgets::Wrap { trim: true });
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .scroll((u16::try_from(app.help_scroll).unwrap_or(u16::MAX), 0));
 
     frame.render_widget(help_widget, area);
 }
@@ -0,0 +1,695 @@
+//! # Configuration File
+//!
+//! Defaults for a ferros session, read from `~/.ferros/config.toml` (or the
+//! file named by `FERROS_CONFIG`) before the subcommand runs.
+//!
+//! The file is shared with the TUI's `:set` settings and is TOML, which
+//! [`parse_entries`] reads for both: a `[stop]` table holding `flash = "on"`
+//! is the same entry as `stop.flash = "on"`. Values are strings, integers or
+//! booleans. Lists of directories are arrays of strings, and source remaps
+//! are a `[source.map]` table of `"FROM" = "TO"` or an array of
+//! `["FROM", "TO"]` pairs. Older files wrote these as one string, with
+//! directories separated by `:` and `FROM=TO` pairs by `,`; that is still read.
+//!
+//! [`Config`] keeps the keys in [`KEYS`], `break.panic` and one
+//! `signal.<NAME>` per signal policy; the other keys belong to the TUI and
+//! are skipped here. Command-line flags win over the file: a flag replaces a
+//! value, and directories given with flags are searched first.
+//!
+//! A problem with the file never stops ferros. An entry with a bad value
+//! keeps its default and comes back from [`Config::load`] as a
+//! [`ConfigWarning`] with its line; the TUI's config report lists these too,
+//! as a banner or on stderr.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ferros_utils::config::Config;
+//!
+//! let text = "log.level = \"debug\"\ndsym.paths = [\"/Volumes/Symbols\", \
+//!             \"/opt/dsyms\"]\nsignal.SIGPIPE = \"pass\"\n";
+//! let (config, warnings) = Config::parse(text);
+//! assert!(warnings.is_empty());
+//! assert_eq!(config.dsym_paths.len(), 2);
+//! assert_eq!(
+//!     config.signals,
+//!     [("SIGPIPE".to_string(), "pass".to_string())]
+//! );
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs};
+
+use toml::de::{DeTable, DeValue};
+
+use crate::logging::{LogFormat, LogLevel, ferros_dir};
+
+/// Environment variable naming a config file to use instead of `~/.ferros/config.toml`
+pub const CONFIG_ENV: &str = "FERROS_CONFIG";
+/// Config file, relative to the ferros directory
+pub const CONFIG_FILE: &str = "config.toml";
+/// Layouts `layout.preset` accepts, in the order `l` cycles them
+pub const LAYOUT_PRESETS: &[&str] = &["compact", "standard", "widescreen"];
+/// Values a `signal.<NAME>` key accepts
+pub const SIGNAL_POLICIES: &[&str] = &["stop", "pass", "ignore"];
+/// Frames unwound for the Stack view without `stack.max_frames`
+pub const DEFAULT_MAX_STACK_FRAMES: usize = 64;
+/// Prefix of the keys that set a signal's policy
+const SIGNAL_PREFIX: &str = "signal.";
+/// Keys whose table holds `FROM = TO` pairs rather than more keys
+const PAIR_TABLES: &[&str] = &["source.map"];
+
+/// One key [`Config`] reads, for the file `ferros init` writes
+#[derive(Debug, Clone, Copy)]
+pub struct Key
+{
+    /// Dotted key
+    pub key: &'static str,
+    /// Value used when the key is missing
+    pub default: &'static str,
+    /// One line for the generated file
+    pub description: &'static str,
+}
+
+/// Keys only read when ferros starts, in the order `ferros config show` lists them
+pub const KEYS: &[Key] = &[
+    Key {
+        key: "log.level",
+        default: "info",
+        description: "Log level without --log-level or RUST_LOG: error, warn, info, debug or trace",
+    },
+    Key {
+        key: "log.format",
+        default: "pretty",
+        description: "Log format without --log-format or FERROS_LOG_FORMAT: pretty or json",
+    },
+    Key {
+        key: "layout.preset",
+        default: "standard",
+        description: "Layout the TUI opens with: compact, standard or widescreen",
+    },
+    Key {
+        key: "stack.max_frames",
+        default: "64",
+        description: "Frames unwound for the Stack view",
+    },
+    Key {
+        key: "source.paths",
+        default: "[]",
+        description: "Directories searched for sources missing at their DWARF path",
+    },
+    Key {
+        key: "source.map",
+        default: "[]",
+        description: "Build-machine source prefixes to show from local ones, as [\"FROM\", \"TO\"] pairs",
+    },
+    Key {
+        key: "dsym.paths",
+        default: "[]",
+        description: "Directories searched for dSYM bundles after any --dsym-path",
+    },
+];
+
+/// Session defaults from the config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config
+{
+    /// `log.level`; `None` leaves it to `RUST_LOG`
+    pub log_level: Option<LogLevel>,
+    /// `log.format`; `None` leaves it to `FERROS_LOG_FORMAT`
+    pub log_format: Option<LogFormat>,
+    /// `layout.preset`, one of [`LAYOUT_PRESETS`]
+    pub layout: String,
+    /// `stack.max_frames`
+    pub max_stack_frames: usize,
+    /// `source.paths`
+    pub source_paths: Vec<PathBuf>,
+    /// `source.map` as (build-machine prefix, local prefix) pairs
+    pub source_map: Vec<(String, String)>,
+    /// `dsym.paths`
+    pub dsym_paths: Vec<PathBuf>,
+    /// `break.panic`; `None` is auto, stopping at panics in launched targets only
+    pub break_on_panic: Option<bool>,
+    /// `signal.<NAME>` entries as (name, policy), in file order
+    pub signals: Vec<(String, String)>,
+}
+
+impl Default for Config
+{
+    fn default() -> Self
+    {
+        Self {
+            log_level: None,
+            log_format: None,
+            layout: "standard".to_string(),
+            max_stack_frames: DEFAULT_MAX_STACK_FRAMES,
+            source_paths: Vec::new(),
+            source_map: Vec::new(),
+            dsym_paths: Vec::new(),
+            break_on_panic: None,
+            signals: Vec::new(),
+        }
+    }
+}
+
+/// An entry [`Config`] couldn't apply; the value keeps its default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigWarning
+{
+    /// 1-based line number
+    pub line: usize,
+    /// Dotted key
+    pub key: String,
+    /// What is wrong with the value
+    pub message: String,
+}
+
+/// `key: message`; the line is left to the caller, which knows the file
+impl fmt::Display for ConfigWarning
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+impl Config
+{
+    /// Load the file named by `FERROS_CONFIG`, else `config.toml` in the ferros directory
+    #[must_use]
+    pub fn load() -> (Self, Vec<ConfigWarning>)
+    {
+        Self::load_from(&path_in(&ferros_dir()))
+    }
+
+    /// Load `path`; a missing or unreadable file gives the defaults
+    #[must_use]
+    pub fn load_from(path: &Path) -> (Self, Vec<ConfigWarning>)
+    {
+        fs::read_to_string(path).map_or_else(|_| (Self::default(), Vec::new()), |text| Self::parse(&text))
+    }
+
+    /// Parse the text of the config file, with a warning for each entry with a bad value
+    ///
+    /// Keys this type doesn't read and TOML the parser can't recover are
+    /// skipped without a warning; the TUI reports them when it checks its
+    /// own settings. Every skipped value keeps its default.
+    #[must_use]
+    pub fn parse(text: &str) -> (Self, Vec<ConfigWarning>)
+    {
+        let mut config = Self::default();
+        let mut warnings = Vec::new();
+        let (entries, _) = parse_entries(text);
+        for entry in entries {
+            if let Some(Err(message)) = config.set(&entry.key, &entry.value) {
+                warnings.push(ConfigWarning {
+                    line: entry.line,
+                    key: entry.key,
+                    message,
+                });
+            }
+        }
+        (config, warnings)
+    }
+
+    /// Check `value` for `key` without applying it; `None` if this type doesn't read `key`
+    #[must_use]
+    pub fn validate(key: &str, value: &Value) -> Option<Result<(), String>>
+    {
+        Self::default().set(key, value)
+    }
+
+    /// Apply one entry; `None` if this type doesn't read `key`
+    fn set(&mut self, key: &str, value: &Value) -> Option<Result<(), String>>
+    {
+        let result = match (key, value) {
+            ("source.paths", _) => directories(value).map(|paths| self.source_paths = paths),
+            ("source.map", _) => remaps(value).map(|remaps| self.source_map = remaps),
+            ("dsym.paths", _) => directories(value).map(|paths| self.dsym_paths = paths),
+            (_, Value::Text(text)) => return self.set_text(key, text),
+            // Only a key this type reads gets a warning
+            (_, Value::List(_) | Value::Pairs(_)) => {
+                return Self::default()
+                    .set_text(key, "")
+                    .map(|_| Err("expected a string, integer or boolean".to_string()));
+            }
+        };
+        Some(result)
+    }
+
+    /// Apply one entry with a single value; `None` if this type doesn't read `key`
+    fn set_text(&mut self, key: &str, value: &str) -> Option<Result<(), String>>
+    {
+        let result = match key {
+            "log.level" => value.parse().map(|level| self.log_level = Some(level)),
+            "log.format" => value.parse().map(|format| self.log_format = Some(format)),
+            "layout.preset" if LAYOUT_PRESETS.contains(&value) => {
+                self.layout = value.to_string();
+                Ok(())
+            }
+            "layout.preset" => Err(format!("expected compact, standard or widescreen, got '{value}'")),
+            "stack.max_frames" => match value.parse::<usize>() {
+                Ok(frames) if frames > 0 => {
+                    self.max_stack_frames = frames;
+                    Ok(())
+                }
+                _ => Err(format!("expected a number of frames above zero, got '{value}'")),
+            },
+            "break.panic" => parse_auto_switch(value).map(|switch| self.break_on_panic = switch),
+            _ => {
+                let signal = key.strip_prefix(SIGNAL_PREFIX)?;
+                if signal.is_empty() {
+                    Err("missing signal name after 'signal.'".to_string())
+                } else if SIGNAL_POLICIES.contains(&value) {
+                    self.signals.push((signal.to_string(), value.to_string()));
+                    Ok(())
+                } else {
+                    Err(format!("expected stop, pass or ignore, got '{value}'"))
+                }
+            }
+        };
+        Some(result)
+    }
+}
+
+/// The effective configuration, in the config file's syntax
+impl fmt::Display for Config
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let paths = |paths: &[PathBuf]| array(paths.iter().map(|path| path.to_string_lossy().into_owned()));
+        writeln!(f, "log.level = \"{}\"", self.log_level.unwrap_or(LogLevel::Info))?;
+        writeln!(f, "log.format = \"{}\"", self.log_format.unwrap_or(LogFormat::Pretty))?;
+        writeln!(f, "layout.preset = {}", quoted(&self.layout))?;
+        writeln!(f, "stack.max_frames = {}", self.max_stack_frames)?;
+        writeln!(f, "source.paths = {}", paths(&self.source_paths))?;
+        let remaps = self.source_map.iter().map(|(from, to)| array([from.clone(), to.clone()]));
+        writeln!(f, "source.map = {}", toml::Value::Array(remaps.collect()))?;
+        writeln!(f, "dsym.paths = {}", paths(&self.dsym_paths))?;
+        let panic = match self.break_on_panic {
+            None => "auto",
+            Some(true) => "on",
+            Some(false) => "off",
+        };
+        writeln!(f, "break.panic = \"{panic}\"")?;
+        for (signal, policy) in &self.signals {
+            writeln!(f, "{SIGNAL_PREFIX}{signal} = \"{policy}\"")?;
+        }
+        Ok(())
+    }
+}
+
+/// The config file to read for the ferros directory `dir`
+///
+/// `FERROS_CONFIG`, when set, names the file instead.
+#[must_use]
+pub fn path_in(dir: &Path) -> PathBuf
+{
+    env::var_os(CONFIG_ENV).map_or_else(|| dir.join(CONFIG_FILE), PathBuf::from)
+}
+
+/// Parse a switch that also accepts `auto`, which is `None`
+///
+/// # Errors
+///
+/// Returns a message if the value is neither `auto` nor on/off.
+pub fn parse_auto_switch(value: &str) -> Result<Option<bool>, String>
+{
+    match value {
+        "auto" => Ok(None),
+        "on" | "true" | "1" => Ok(Some(true)),
+        "off" | "false" | "0" => Ok(Some(false)),
+        _ => Err(format!("expected auto, on or off, got '{value}'")),
+    }
+}
+
+/// Directories from an array, or from a `:`-separated string, with a leading `~` for the home directory
+fn directories(value: &Value) -> Result<Vec<PathBuf>, String>
+{
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let expand = |path: &str| match (path.strip_prefix("~/"), &home) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    };
+    match value {
+        Value::Text(text) => Ok(text.split(':').filter(|path| !path.is_empty()).map(expand).collect()),
+        Value::List(paths) => Ok(paths
+            .iter()
+            .filter(|path| !path.is_empty())
+            .map(|path| expand(path))
+            .collect()),
+        Value::Pairs(_) => Err("expected an array of directories".to_string()),
+    }
+}
+
+/// Remaps from a table or an array of pairs, or from a string of `FROM=TO` pairs separated by `,`
+fn remaps(value: &Value) -> Result<Vec<(String, String)>, String>
+{
+    match value {
+        Value::Text(text) => split_remaps(text),
+        Value::Pairs(pairs) => pairs
+            .iter()
+            .map(|(from, to)| {
+                if from.is_empty() || to.is_empty() {
+                    Err(format!("expected a non-empty FROM and TO, got [\"{from}\", \"{to}\"]"))
+                } else {
+                    Ok((from.clone(), to.clone()))
+                }
+            })
+            .collect(),
+        Value::List(paths) if paths.is_empty() => Ok(Vec::new()),
+        Value::List(_) => Err("expected a table or an array of [\"FROM\", \"TO\"] pairs".to_string()),
+    }
+}
+
+/// `FROM=TO` pairs separated by `,`
+fn split_remaps(value: &str) -> Result<Vec<(String, String)>, String>
+{
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok((from.to_string(), to.to_string())),
+            _ => Err(format!("expected FROM=TO pairs separated by ',', got '{pair}'")),
+        })
+        .collect()
+}
+
+/// What an entry sets its key to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value
+{
+    /// A string's contents, or an integer or boolean as written
+    Text(String),
+    /// An array of strings
+    List(Vec<String>),
+    /// A table of strings, or an array of two-string arrays, in file order
+    Pairs(Vec<(String, String)>),
+}
+
+impl Value
+{
+    /// The single value, if this isn't an array or table
+    #[must_use]
+    pub fn as_text(&self) -> Option<&str>
+    {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::List(_) | Self::Pairs(_) => None,
+        }
+    }
+}
+
+/// One well-formed `key = value` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry
+{
+    /// 1-based line number
+    pub line: usize,
+    /// Key, trimmed
+    pub key: String,
+    /// Value as written
+    pub value: Value,
+}
+
+/// Split config text into its entries, with `(line, message)` for each problem
+///
+/// Tables are flattened into dotted keys, except those in [`PAIR_TABLES`],
+/// and entries come back in file order. The parser recovers from a syntax error, so only the entry on its
+/// line is lost; a key set twice keeps its first value.
+#[must_use]
+pub fn parse_entries(text: &str) -> (Vec<Entry>, Vec<(usize, String)>)
+{
+    let (document, errors) = DeTable::parse_recoverable(text);
+    let mut problems: Vec<(usize, String)> = errors
+        .iter()
+        .map(|e| {
+            let span = e.span().unwrap_or_default();
+            let line = line_at(text, span.start);
+            // Name what the message is about, such as the key set twice
+            match text.get(span).map(str::trim) {
+                Some(found) if !found.is_empty() && !found.contains('\n') => (line, format!("{}: {found}", e.message())),
+                _ => (line, e.message().to_string()),
+            }
+        })
+        .collect();
+    let mut entries = Vec::new();
+    flatten(text, "", document.get_ref(), &mut entries, &mut problems);
+    // What the parser recovered on a line it reported is a guess, so it isn't used
+    entries.retain(|entry| !problems.iter().any(|&(line, _)| line == entry.line));
+    entries.sort_by_key(|entry| entry.line);
+    problems.sort_by_key(|&(line, _)| line);
+    (entries, problems)
+}
+
+/// Add the values in `table` as entries under `prefix`, descending into subtables
+fn flatten(text: &str, prefix: &str, table: &DeTable<'_>, entries: &mut Vec<Entry>, problems: &mut Vec<(usize, String)>)
+{
+    for (key, value) in table {
+        let line = line_at(text, key.span().start);
+        let key = if prefix.is_empty() {
+            key.get_ref().to_string()
+        } else {
+            format!("{prefix}.{}", key.get_ref())
+        };
+        let parsed = match value.get_ref() {
+            DeValue::Table(table) if PAIR_TABLES.contains(&key.as_str()) => table_pairs(table).map(Value::Pairs),
+            DeValue::Table(table) => {
+                flatten(text, &key, table, entries, problems);
+                continue;
+            }
+            DeValue::String(value) => Some(Value::Text(value.to_string())),
+            DeValue::Integer(value) => Some(Value::Text(value.to_string())),
+            DeValue::Boolean(value) => Some(Value::Text(value.to_string())),
+            DeValue::Array(items) => array_value(items),
+            DeValue::Float(_) | DeValue::Datetime(_) => None,
+        };
+        let Some(parsed) = parsed else {
+            let expected = match value.get_ref() {
+                DeValue::Table(_) => "a table of strings",
+                DeValue::Array(_) => "an array of strings or of [\"FROM\", \"TO\"] pairs",
+                _ => "a string, integer, boolean or array",
+            };
+            problems.push((line, format!("{key}: expected {expected}")));
+            continue;
+        };
+        entries.push(Entry {
+            line,
+            key,
+            value: parsed,
+        });
+    }
+}
+
+/// A table's `FROM = TO` strings, in file order
+fn table_pairs(table: &DeTable<'_>) -> Option<Vec<(String, String)>>
+{
+    let mut pairs: Vec<(usize, String, String)> = table
+        .iter()
+        .map(|(from, to)| Some((from.span().start, from.get_ref().to_string(), string(to.get_ref())?)))
+        .collect::<Option<_>>()?;
+    pairs.sort_by_key(|&(start, ..)| start);
+    Some(pairs.into_iter().map(|(_, from, to)| (from, to)).collect())
+}
+
+/// An array of strings, or of two-string arrays; an empty array is an empty list
+fn array_value(items: &[toml::Spanned<DeValue<'_>>]) -> Option<Value>
+{
+    if let Some(strings) = items.iter().map(|item| string(item.get_ref())).collect::<Option<Vec<_>>>() {
+        return Some(Value::List(strings));
+    }
+    items
+        .iter()
+        .map(|item| match item.get_ref() {
+            DeValue::Array(pair) => match &pair[..] {
+                [from, to] => Some((string(from.get_ref())?, string(to.get_ref())?)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Option<_>>()
+        .map(Value::Pairs)
+}
+
+/// The contents of a string value
+fn string(value: &DeValue<'_>) -> Option<String>
+{
+    match value {
+        DeValue::String(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// 1-based line of the byte at `offset`
+fn line_at(text: &str, offset: usize) -> usize
+{
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+/// `value` as a TOML string, quoted so backslashes and quotes read back as written
+fn quoted(value: &str) -> String
+{
+    toml::Value::String(value.to_string()).to_string()
+}
+
+/// `values` as a TOML array of strings
+fn array(values: impl IntoIterator<Item = String>) -> toml::Value
+{
+    toml::Value::Array(values.into_iter().map(toml::Value::String).collect())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn startup_keys_are_read_and_the_rest_left_to_the_tui()
+    {
+        let text = "stop.flash = \"off\"\nlog.level = \"debug\"\nlog.format = \"json\"\nlayout.preset = \
+                    \"widescreen\"\nstack.max_frames = 128\ndsym.paths = [\"/Volumes/Symbols\", \
+                    \"/opt/dsyms\"]\nbreak.panic = \"on\"\nsignal.SIGPIPE = \"ignore\"\nhang.samples = 1\nsource.map = \
+                    [[\"/home/runner/work/app\", \"~/src/app\"], [\"/ci\", \"/mnt/ci\"]]\n";
+        let (config, warnings) = Config::parse(text);
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config.log_level, Some(LogLevel::Debug));
+        assert_eq!(config.log_format, Some(LogFormat::Json));
+        assert_eq!(config.layout, "widescreen");
+        assert_eq!(config.max_stack_frames, 128);
+        assert_eq!(
+            config.dsym_paths,
+            [PathBuf::from("/Volumes/Symbols"), PathBuf::from("/opt/dsyms")]
+        );
+        assert!(config.source_paths.is_empty());
+        assert_eq!(
+            config.source_map,
+            [
+                ("/home/runner/work/app".to_string(), "~/src/app".to_string()),
+                ("/ci".to_string(), "/mnt/ci".to_string())
+            ]
+        );
+        assert_eq!(config.break_on_panic, Some(true));
+        assert_eq!(config.signals, [("SIGPIPE".to_string(), "ignore".to_string())]);
+
+        // What `ferros config show` prints reads back as the same configuration
+        let shown = config.to_string();
+        assert!(
+            shown.contains("dsym.paths = [\"/Volumes/Symbols\", \"/opt/dsyms\"]\n"),
+            "{shown}"
+        );
+        assert_eq!(Config::parse(&shown), (config, Vec::new()));
+    }
+
+    #[test]
+    fn bad_values_keep_their_defaults_and_the_rest_still_apply()
+    {
+        let text = "log.level = \"debug\"\n\nstack.max_frames = 0\n[signal]\nSIGPIPE = \"pass\"\nSIGUSR1 = \"drop\"\n";
+        let (config, warnings) = Config::parse(text);
+        assert_eq!(config.log_level, Some(LogLevel::Debug));
+        assert_eq!(config.max_stack_frames, DEFAULT_MAX_STACK_FRAMES);
+        assert_eq!(config.signals, [("SIGPIPE".to_string(), "pass".to_string())]);
+        let warnings: Vec<(usize, String)> = warnings.iter().map(|warning| (warning.line, warning.to_string())).collect();
+        assert_eq!(
+            warnings,
+            [
+                (
+                    3,
+                    "stack.max_frames: expected a number of frames above zero, got '0'".to_string()
+                ),
+                (6, "signal.SIGUSR1: expected stop, pass or ignore, got 'drop'".to_string())
+            ]
+        );
+
+        assert!(
+            Config::validate("signal.SIGUSR1", &Value::Text("drop".to_string()))
+                .unwrap()
+                .is_err()
+        );
+        assert!(
+            Config::validate("layout.preset", &Value::Text("compact".to_string()))
+                .unwrap()
+                .is_ok()
+        );
+        assert_eq!(
+            Config::validate("source.map", &Value::Text("/build=/src,/ci".to_string())).unwrap(),
+            Err("expected FROM=TO pairs separated by ',', got '/ci'".to_string())
+        );
+        assert!(Config::validate("stop.flash", &Value::Text("on".to_string())).is_none());
+        let missing = std::env::temp_dir().join(format!("ferros-no-config-{}", std::process::id()));
+        assert_eq!(Config::load_from(&missing), (Config::default(), Vec::new()));
+    }
+
+    #[test]
+    fn tables_read_as_dotted_keys_and_a_syntax_error_loses_only_its_entry()
+    {
+        let text = "stack.max_frames = 32 # deep enough\n[log]\nlevel = \"debug\"\n[source]\nmap = \
+                    \"C:\\\\build=/src\"\npaths = maybe\n[signal]\nSIGPIPE = 'pass'\nSIGPIPE = \"stop\"\n";
+        let (entries, problems) = parse_entries(text);
+        let entries: Vec<(usize, &str, &str)> = entries
+            .iter()
+            .map(|entry| (entry.line, entry.key.as_str(), entry.value.as_text().unwrap()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (1, "stack.max_frames", "32"),
+                (3, "log.level", "debug"),
+                (5, "source.map", "C:\\build=/src"),
+                (8, "signal.SIGPIPE", "pass")
+            ]
+        );
+        assert_eq!(
+            problems,
+            [
+                (6, "string values must be quoted, expected literal string: maybe".to_string()),
+                (9, "duplicate key: SIGPIPE".to_string())
+            ]
+        );
+
+        let (config, _) = Config::parse(text);
+        assert_eq!(config.max_stack_frames, 32);
+        assert_eq!(config.source_map, [("C:\\build".to_string(), "/src".to_string())]);
+        // Shown with the backslash escaped, so it reads back the same
+        assert_eq!(Config::parse(&config.to_string()).0.source_map, config.source_map);
+    }
+
+    #[test]
+    fn lists_read_from_arrays_and_tables_as_well_as_the_older_strings()
+    {
+        let text =
+            "source.paths = \"/src::/lib\"\ndsym.paths = []\n[source.map]\n\"/ci\" = \"/mnt/ci\"\n\"/build\" = \"/src\"\n";
+        let (config, warnings) = Config::parse(text);
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config.source_paths, [PathBuf::from("/src"), PathBuf::from("/lib")]);
+        assert!(config.dsym_paths.is_empty());
+        // In file order, since the first matching prefix wins
+        assert_eq!(
+            config.source_map,
+            [
+                ("/ci".to_string(), "/mnt/ci".to_string()),
+                ("/build".to_string(), "/src".to_string())
+            ]
+        );
+
+        let text = "stack.max_frames = [1.5]\nsignal.SIGPIPE = [\"pass\"]\nsource.map = [\"/build\"]\ndsym.paths = \
+                    [[\"/a\", \"/b\"]]\n";
+        let (_, problems) = parse_entries(text);
+        assert_eq!(
+            problems,
+            [(
+                1,
+                "stack.max_frames: expected an array of strings or of [\"FROM\", \"TO\"] pairs".to_string()
+            )]
+        );
+        let warnings: Vec<String> = Config::parse(text).1.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "signal.SIGPIPE: expected a string, integer or boolean",
+                "source.map: expected a table or an array of [\"FROM\", \"TO\"] pairs",
+                "dsym.paths: expected an array of directories"
+            ]
+        );
+    }
+}
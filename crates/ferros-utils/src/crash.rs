@@ -0,0 +1,395 @@
+//! # Crash Reporting
+//!
+//! Self-diagnostics for when ferros itself fails, as opposed to the target.
+//!
+//! In TUI mode the terminal is in raw mode on the alternate screen, so a panic
+//! message printed there is wiped by the terminal restore and the backtrace is
+//! lost. [`install_crash_handler`] sets a panic hook that first writes a crash
+//! log to disk and then delegates to the previously installed hook.
+//!
+//! A crash log (`ferros-crash-<timestamp>.log`) contains:
+//!
+//! - the ferros version and the name of the panicking thread
+//! - the panic message and location
+//! - a backtrace, captured regardless of `RUST_BACKTRACE`
+//! - the last [`RECENT_LOG_CAPACITY`] log records, kept in memory by
+//!   [`RecentLogsLayer`]
+//!
+//! Failures that ferros recovers from but that still indicate a bug (for
+//! example a terminal draw error) are written with [`report_nonfatal`] to
+//! `ferros-error-<timestamp>.log` in the same directory. Reports about the
+//! target rather than ferros, such as the hang report taken when `--timeout`
+//! expires, go there too through [`write_artifact`].
+//!
+//! Anything that must be saved before ferros goes down, such as the pending
+//! lines of a `--transcript`, registers a callback with [`on_crash`]; the
+//! panic hook runs them before writing the crash log.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use ferros_utils::{ferros_dir, init_logging, install_crash_handler};
+//!
+//! init_logging().expect("Failed to initialize logging");
+//! install_crash_handler(ferros_dir());
+//! ```
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{self, Write as _};
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+
+use chrono::Utc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+
+use crate::logging::ferros_dir;
+
+/// Number of log records included in a crash log
+pub const RECENT_LOG_CAPACITY: usize = 100;
+
+/// Log records seen by the layers installed through [`crate::logging`]
+static RECENT_LOGS: LazyLock<Arc<RecentLogs>> = LazyLock::new(|| Arc::new(RecentLogs::new(RECENT_LOG_CAPACITY)));
+
+/// Directory passed to [`install_crash_handler`]
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Callbacks registered with [`on_crash`]
+static CRASH_CALLBACKS: Mutex<Vec<Box<dyn Fn() + Send>>> = Mutex::new(Vec::new());
+
+/// Bounded, thread-safe buffer of the most recent formatted log records
+#[derive(Debug)]
+pub struct RecentLogs
+{
+    records: Mutex<VecDeque<String>>,
+    capacity: usize,
+}
+
+impl RecentLogs
+{
+    /// Create a buffer that keeps at most `capacity` records
+    #[must_use]
+    pub fn new(capacity: usize) -> Self
+    {
+        Self {
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Append a record, evicting the oldest one when full
+    pub fn push(&self, record: String)
+    {
+        if self.capacity == 0 {
+            return;
+        }
+        // A poisoned lock still holds valid records; the panic hook needs them most
+        let mut records = self.records.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Copy of the buffered records, oldest first
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<String>
+    {
+        let records = self.records.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        records.iter().cloned().collect()
+    }
+}
+
+/// `tracing` layer that formats every event it sees into a [`RecentLogs`] buffer
+#[derive(Debug, Clone)]
+pub struct RecentLogsLayer
+{
+    buffer: Arc<RecentLogs>,
+}
+
+impl RecentLogsLayer
+{
+    /// Create a layer that records into `buffer`
+    #[must_use]
+    pub fn new(buffer: Arc<RecentLogs>) -> Self
+    {
+        Self { buffer }
+    }
+
+    /// Layer feeding the buffer that crash logs are built from
+    #[must_use]
+    pub fn global() -> Self
+    {
+        Self::new(Arc::clone(&RECENT_LOGS))
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogsLayer
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>)
+    {
+        let metadata = event.metadata();
+        let mut record = format!(
+            "{} {:>5} {}:",
+            Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            metadata.level(),
+            metadata.target()
+        );
+        event.record(&mut RecordVisitor(&mut record));
+        self.buffer.push(record);
+    }
+}
+
+/// Appends the message and then `name=value` for each other field
+struct RecordVisitor<'a>(&'a mut String);
+
+impl Visit for RecordVisitor<'_>
+{
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug)
+    {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {value:?}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str)
+    {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {value}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
+
+/// Install a panic hook that writes a crash log to `log_dir`
+///
+/// The hook runs before the previously installed one, so installing this
+/// right after logging init keeps any hook set up later (such as the TUI's
+/// terminal restore) in front of it. The directory is also where
+/// [`report_nonfatal`] writes; only the first call sets it.
+pub fn install_crash_handler(log_dir: impl Into<PathBuf>)
+{
+    let _ = CRASH_DIR.set(log_dir.into());
+
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        run_crash_callbacks();
+        let report = crash_report(info);
+        match write_report(crash_dir(), "crash", &report) {
+            Ok(path) => eprintln!("ferros crashed; crash log written to {}", path.display()),
+            Err(e) => eprintln!("ferros crashed; failed to write crash log: {e}"),
+        }
+        previous_hook(info);
+    }));
+}
+
+/// Run `callback` when ferros panics, before the crash log is written
+///
+/// Callbacks run on the panicking thread in the order they were registered.
+/// They must not block on locks the panicking code may hold.
+pub fn on_crash(callback: impl Fn() + Send + 'static)
+{
+    CRASH_CALLBACKS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .push(Box::new(callback));
+}
+
+fn run_crash_callbacks()
+{
+    // A panic inside a callback must not take the crash log down with it
+    let Ok(callbacks) = CRASH_CALLBACKS.try_lock() else {
+        return;
+    };
+    for callback in callbacks.iter() {
+        let _ = panic::catch_unwind(panic::AssertUnwindSafe(callback));
+    }
+}
+
+/// Record a recoverable but serious failure to the crash log directory
+///
+/// `context` says what ferros was doing, e.g. `"drawing the TUI"`. The error
+/// and its source chain are written together with a backtrace of the caller
+/// and the recent log records. Returns the path of the written file, or
+/// `None` if it could not be written (the failure is logged).
+pub fn report_nonfatal(error: &dyn Error, context: &str) -> Option<PathBuf>
+{
+    tracing::error!("{context}: {error}");
+
+    let mut report = report_header();
+    let _ = writeln!(report, "\n## Error\n\nwhile {context}: {error}");
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let _ = writeln!(report, "caused by: {cause}");
+        source = cause.source();
+    }
+    push_backtrace_and_logs(&mut report);
+
+    match write_report(crash_dir(), "error", &report) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            tracing::error!("Failed to write error report: {e}");
+            None
+        }
+    }
+}
+
+/// Write a report about the session to the crash log directory
+///
+/// The file is named `ferros-<kind>-<timestamp>.log`. Unlike
+/// [`report_nonfatal`] nothing is added to `report`.
+///
+/// # Errors
+///
+/// Returns an error if the directory or file can't be written.
+pub fn write_artifact(kind: &str, report: &str) -> io::Result<PathBuf>
+{
+    write_report(crash_dir(), kind, report)
+}
+
+fn crash_dir() -> &'static Path
+{
+    static DEFAULT: LazyLock<PathBuf> = LazyLock::new(ferros_dir);
+    CRASH_DIR.get().unwrap_or(&DEFAULT)
+}
+
+fn crash_report(info: &PanicHookInfo<'_>) -> String
+{
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_string());
+
+    let mut report = report_header();
+    let _ = writeln!(report, "\n## Panic\n\n{message}");
+    if let Some(location) = info.location() {
+        let _ = writeln!(report, "at {location}");
+    }
+    push_backtrace_and_logs(&mut report);
+    report
+}
+
+fn report_header() -> String
+{
+    let thread = std::thread::current();
+    let mut header = String::from("# ferros crash report\n\n");
+    let _ = writeln!(header, "version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(header, "time: {}", Utc::now().to_rfc3339());
+    let _ = writeln!(header, "thread: {}", thread.name().unwrap_or("<unnamed>"));
+    header
+}
+
+fn push_backtrace_and_logs(report: &mut String)
+{
+    let backtrace = std::backtrace::Backtrace::force_capture();
+    let _ = writeln!(report, "\n## Backtrace\n\n{backtrace}");
+
+    let records = RECENT_LOGS.snapshot();
+    let _ = writeln!(report, "## Recent log records ({})\n", records.len());
+    for record in records {
+        let _ = writeln!(report, "{record}");
+    }
+}
+
+fn write_report(dir: &Path, kind: &str, report: &str) -> io::Result<PathBuf>
+{
+    std::fs::create_dir_all(dir)?;
+    let timestamp = Utc::now().format("%Y%m%d-%H%M%S%.3f");
+    let path = dir.join(format!("ferros-{kind}-{timestamp}.log"));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::process::Command;
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    /// Set by `crash_log_is_written_on_panic` for the child process it spawns
+    const CHILD_DIR_ENV: &str = "FERROS_CRASH_TEST_DIR";
+
+    #[test]
+    fn recent_logs_layer_keeps_only_the_newest_records()
+    {
+        let buffer = Arc::new(RecentLogs::new(3));
+        let subscriber = tracing_subscriber::registry().with(RecentLogsLayer::new(Arc::clone(&buffer)));
+
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..5 {
+                tracing::info!(attempt = i, "reading registers");
+            }
+            tracing::warn!("target exited");
+        });
+
+        let records = buffer.snapshot();
+        assert_eq!(records.len(), 3);
+        assert!(records[0].contains(" INFO ferros_utils::crash::tests: reading registers attempt=3"));
+        assert!(records[1].ends_with("reading registers attempt=4"));
+        assert!(records[2].contains(" WARN ") && records[2].ends_with("target exited"));
+    }
+
+    /// Body of the child process: only panics when run by the test below
+    #[test]
+    fn crash_child()
+    {
+        let Ok(dir) = std::env::var(CHILD_DIR_ENV) else {
+            return;
+        };
+        let subscriber = tracing_subscriber::registry().with(RecentLogsLayer::global());
+        tracing::subscriber::set_global_default(subscriber).unwrap();
+        install_crash_handler(dir.clone());
+        on_crash(move || {
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(Path::new(&dir).join("callback-ran"), "").unwrap();
+        });
+
+        tracing::info!("about to fail");
+        panic!("controlled crash for the crash handler test");
+    }
+
+    #[test]
+    fn crash_log_is_written_on_panic()
+    {
+        let dir = std::env::temp_dir().join(format!("ferros-crash-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "crash::tests::crash_child", "--nocapture", "--test-threads=1"])
+            .env(CHILD_DIR_ENV, &dir)
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+
+        let callback_ran = dir.join("callback-ran");
+        assert!(callback_ran.exists(), "crash callbacks didn't run");
+        std::fs::remove_file(&callback_ran).unwrap();
+        let logs: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        let report = logs.first().map(std::fs::read_to_string).transpose().unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(logs.len(), 1);
+        let name = logs[0].file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("ferros-crash-") && name.ends_with(".log"));
+
+        let report = report.unwrap();
+        assert!(report.contains(&format!("version: {}", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains("thread: crash::tests::crash_child"));
+        assert!(report.contains("## Panic\n\ncontrolled crash for the crash handler test"));
+        assert!(report.contains("## Backtrace"));
+        assert!(report.contains("## Recent log records (1)"));
+        assert!(report.contains("about to fail"));
+    }
+}
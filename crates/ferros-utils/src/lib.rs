@@ -3,10 +3,18 @@
 //! Shared utilities, logging, config, and helpers for Ferros.
 //!
 //! This crate provides common functionality used across the Ferros workspace,
-//! including production-ready logging infrastructure built on `tracing`.
+//! including production-ready logging infrastructure built on `tracing`, the
+//! `~/.ferros/config.toml` session defaults and crash reporting for ferros
+//! itself.
 
+pub mod config;
+pub mod crash;
 pub mod logging;
 
+pub use config::{Config, ConfigWarning};
+pub use crash::{install_crash_handler, on_crash, report_nonfatal, write_artifact};
 // Re-export commonly used logging functions for convenience
-pub use logging::{LogFormat, LogLevel, init_logging, init_logging_for_tui, init_logging_with_level};
+pub use logging::{
+    LogFormat, LogLevel, ferros_dir, init_logging, init_logging_for_tui, init_logging_with_level, log_to_stderr,
+};
 pub use tracing::{debug, error, info, trace, warn};
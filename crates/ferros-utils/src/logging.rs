@@ -44,16 +44,20 @@
 
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{env, io};
 
 use chrono::Utc;
 use tracing::Level;
 use tracing_subscriber::fmt::time::ChronoUtc;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::fmt::{self};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{EnvFilter, Layer, Registry};
 
+use crate::crash::RecentLogsLayer;
+
 /// Log output format
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LogFormat
@@ -78,6 +82,17 @@ impl FromStr for LogFormat
     }
 }
 
+impl std::fmt::Display for LogFormat
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        f.write_str(match self {
+            LogFormat::Pretty => "pretty",
+            LogFormat::Json => "json",
+        })
+    }
+}
+
 /// Log level
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LogLevel
@@ -127,6 +142,20 @@ impl FromStr for LogLevel
     }
 }
 
+impl std::fmt::Display for LogLevel
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        f.write_str(match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        })
+    }
+}
+
 /// Initialize logging with default settings
 ///
 /// Reads configuration from environment variables:
@@ -186,6 +215,28 @@ pub fn init_logging_with_level(level: LogLevel, format: LogFormat) -> Result<(),
     init_logging_internal(format, level.into())
 }
 
+/// Whether console logs go to stderr, see [`log_to_stderr`]
+static LOG_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Write console logs to stderr instead of stdout
+///
+/// For commands whose stdout is data, such as `--output json`, so log lines
+/// don't end up in it. Call it before initializing logging.
+pub fn log_to_stderr()
+{
+    LOG_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// Where console logs are written
+fn console_writer() -> BoxMakeWriter
+{
+    if LOG_TO_STDERR.load(Ordering::Relaxed) {
+        BoxMakeWriter::new(io::stderr)
+    } else {
+        BoxMakeWriter::new(io::stdout)
+    }
+}
+
 /// Initialize logging for TUI mode (file-only, no stdout)
 ///
 /// This function configures logging to write only to a file, not to stdout/stderr,
@@ -217,20 +268,25 @@ pub fn init_logging_for_tui(level: Option<LogLevel>) -> Result<PathBuf, LoggingE
 {
     // Determine log file path with date prefix
     let today = Utc::now().format("%Y-%m-%d");
-    let log_file = if let Ok(home) = env::var("HOME") {
-        let ferros_dir = PathBuf::from(home).join(".ferros");
-        // Create directory if it doesn't exist
-        std::fs::create_dir_all(&ferros_dir).map_err(LoggingError::FileError)?;
-        ferros_dir.join(format!("{today}-ferros-tui.log"))
-    } else {
-        PathBuf::from("/tmp").join(format!("{today}-ferros-tui.log"))
-    };
+    let log_dir = ferros_dir();
+    // Create directory if it doesn't exist
+    std::fs::create_dir_all(&log_dir).map_err(LoggingError::FileError)?;
+    let log_file = log_dir.join(format!("{today}-ferros-tui.log"));
 
     let explicit_level = level.map(Into::into);
     init_logging_file_only(log_file.clone(), LogFormat::Pretty, explicit_level)?;
     Ok(log_file)
 }
 
+/// Directory ferros keeps its logs and crash reports in
+///
+/// This is `~/.ferros`, or `/tmp` if `HOME` is not set.
+#[must_use]
+pub fn ferros_dir() -> PathBuf
+{
+    env::var("HOME").map_or_else(|_| PathBuf::from("/tmp"), |home| PathBuf::from(home).join(".ferros"))
+}
+
 /// Internal initialization function
 #[allow(clippy::unnecessary_wraps)]
 fn init_logging_internal(format: LogFormat, default_level: Level) -> Result<(), LoggingError>
@@ -252,8 +308,9 @@ fn init_logging_internal(format: LogFormat, default_level: Level) -> Result<(),
                 .with_line_number(true)
                 .with_timer(ChronoUtc::rfc_3339())
                 .with_ansi(true)
-                .with_writer(io::stdout)
+                .with_writer(console_writer())
                 .with_filter(env_filter.clone());
+            let recent_filter = env_filter.clone();
 
             if let Some(file_path) = log_file {
                 // File logging with pretty format
@@ -273,10 +330,17 @@ fn init_logging_internal(format: LogFormat, default_level: Level) -> Result<(),
                     .with_ansi(false) // No ANSI in files
                     .with_filter(env_filter);
 
-                Registry::default().with(console_layer).with(file_layer).init();
+                Registry::default()
+                    .with(console_layer)
+                    .with(file_layer)
+                    .with(RecentLogsLayer::global().with_filter(recent_filter))
+                    .init();
             } else {
                 // Console only
-                Registry::default().with(console_layer).init();
+                Registry::default()
+                    .with(console_layer)
+                    .with(RecentLogsLayer::global().with_filter(recent_filter))
+                    .init();
             }
         }
         LogFormat::Json => {
@@ -290,8 +354,9 @@ fn init_logging_internal(format: LogFormat, default_level: Level) -> Result<(),
                 .with_timer(ChronoUtc::rfc_3339())
                 .with_current_span(true)
                 .with_span_list(true)
-                .with_writer(io::stdout)
+                .with_writer(console_writer())
                 .with_filter(env_filter.clone());
+            let recent_filter = env_filter.clone();
 
             if let Some(file_path) = log_file {
                 // File logging with JSON format
@@ -313,10 +378,17 @@ fn init_logging_internal(format: LogFormat, default_level: Level) -> Result<(),
                     .with_span_list(true)
                     .with_filter(env_filter);
 
-                Registry::default().with(console_layer).with(file_layer).init();
+                Registry::default()
+                    .with(console_layer)
+                    .with(file_layer)
+                    .with(RecentLogsLayer::global().with_filter(recent_filter))
+                    .init();
             } else {
                 // Console only
-                Registry::default().with(console_layer).init();
+                Registry::default()
+                    .with(console_layer)
+                    .with(RecentLogsLayer::global().with_filter(recent_filter))
+                    .init();
             }
         }
     }
@@ -345,6 +417,8 @@ fn init_logging_file_only(log_file: PathBuf, format: LogFormat, explicit_level:
         EnvFilter::new(Level::INFO.to_string())
     };
 
+    let recent_filter = env_filter.clone();
+
     match format {
         LogFormat::Pretty => {
             // File logging only with pretty format
@@ -369,7 +443,10 @@ fn init_logging_file_only(log_file: PathBuf, format: LogFormat, explicit_level:
                 .with_ansi(false) // No ANSI in files
                 .with_filter(env_filter);
 
-            Registry::default().with(file_layer).init();
+            Registry::default()
+                .with(file_layer)
+                .with(RecentLogsLayer::global().with_filter(recent_filter))
+                .init();
         }
         LogFormat::Json => {
             // File logging only with JSON format
@@ -396,7 +473,10 @@ fn init_logging_file_only(log_file: PathBuf, format: LogFormat, explicit_level:
                 .with_span_list(true)
                 .with_filter(env_filter);
 
-            Registry::default().with(file_layer).init();
+            Registry::default()
+                .with(file_layer)
+                .with(RecentLogsLayer::global().with_filter(recent_filter))
+                .init();
         }
     }
 
@@ -0,0 +1,44 @@
+//! Async backtrace test target for Ferros
+//!
+//! A current-thread tokio runtime blocks on `serve`, which awaits
+//! `handle_request`, which awaits `load_user`, which sleeps for an hour. The
+//! main thread is parked in the runtime the whole time. Stop the target and
+//! run `:async-stacks on` in the TUI: the call stack shows `load_user`,
+//! `handle_request` and `serve` above the runtime's `block_on`.
+
+use std::time::Duration;
+
+fn main()
+{
+    println!("Async Test Target Starting...");
+    println!("PID: {}", std::process::id());
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .expect("failed to build the runtime");
+    let served = runtime.block_on(serve(3));
+    println!("served {served} requests");
+}
+
+async fn serve(requests: u32) -> u32
+{
+    let mut served = 0;
+    for id in 0..requests {
+        served += handle_request(id).await;
+    }
+    served
+}
+
+async fn handle_request(id: u32) -> u32
+{
+    let user = load_user(id).await;
+    println!("request {id} for {user}");
+    1
+}
+
+async fn load_user(id: u32) -> String
+{
+    tokio::time::sleep(Duration::from_secs(3600)).await;
+    format!("user-{id}")
+}
@@ -0,0 +1,39 @@
+//! Deadlock test target for Ferros
+//!
+//! Never stops or exits: the main thread waits for a lock that a worker
+//! thread holds while it waits for a message nobody sends. Launch it headless
+//! with `--timeout` to see the deadline expire and the hang report show both
+//! threads blocked.
+
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
+
+fn main()
+{
+    println!("Hang Test Target Starting...");
+    println!("PID: {}", std::process::id());
+
+    let state = Arc::new(Mutex::new(0u64));
+    let (_never_sent, inbox) = mpsc::channel::<u64>();
+
+    let worker_state = Arc::clone(&state);
+    let worker = thread::spawn(move || hold_lock_and_wait(&worker_state, &inbox));
+
+    // Give the worker time to take the lock
+    thread::sleep(Duration::from_millis(100));
+    let value = wait_for_lock(&state);
+    println!("Unreachable: got {value}");
+    worker.join().ok();
+}
+
+fn hold_lock_and_wait(state: &Mutex<u64>, inbox: &mpsc::Receiver<u64>)
+{
+    let mut guard = state.lock().unwrap();
+    *guard = inbox.recv().unwrap_or_default();
+}
+
+fn wait_for_lock(state: &Mutex<u64>) -> u64
+{
+    *state.lock().unwrap()
+}
@@ -0,0 +1,86 @@
+//! Mixed-language test target for Ferros (macOS only)
+//!
+//! Links Foundation, registers an Objective-C class `FerrosGreeter` whose
+//! `greet` method is exported under the symbol name clang gives methods, and
+//! calls `malloc` through the executable's symbol stub. Both of these resolve
+//! from the symbol table alone, with no DWARF involved:
+//!
+//! ```text
+//! break -[FerrosGreeter greet]
+//! break malloc
+//! ```
+
+#[cfg(target_os = "macos")]
+mod target
+{
+    use std::ffi::{c_char, c_void};
+
+    type Id = *mut c_void;
+    type Class = *mut c_void;
+    type Sel = *mut c_void;
+
+    #[link(name = "Foundation", kind = "framework")]
+    unsafe extern "C" {}
+
+    #[link(name = "objc")]
+    unsafe extern "C" {
+        fn objc_getClass(name: *const c_char) -> Class;
+        fn objc_allocateClassPair(superclass: Class, name: *const c_char, extra_bytes: usize) -> Class;
+        fn objc_registerClassPair(class: Class);
+        fn class_addMethod(class: Class, name: Sel, imp: *const c_void, types: *const c_char) -> bool;
+        fn class_createInstance(class: Class, extra_bytes: usize) -> Id;
+        fn sel_registerName(name: *const c_char) -> Sel;
+        fn objc_msgSend();
+    }
+
+    unsafe extern "C" {
+        fn malloc(size: usize) -> *mut c_void;
+        fn free(ptr: *mut c_void);
+    }
+
+    /// Implementation of `-[FerrosGreeter greet]`
+    ///
+    /// The `\x01` prefix stops the `_` Mach-O normally adds, so the symbol table
+    /// holds exactly `-[FerrosGreeter greet]`, as it would for clang output.
+    #[unsafe(export_name = "\x01-[FerrosGreeter greet]")]
+    extern "C" fn greet(_this: Id, _cmd: Sel) -> u64
+    {
+        static CALLS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        CALLS.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1
+    }
+
+    pub fn run()
+    {
+        // SAFETY: plain Objective-C runtime calls on a class this program owns;
+        // `objc_msgSend` is called with the signature of the method it dispatches to.
+        unsafe {
+            let class = objc_allocateClassPair(objc_getClass(c"NSObject".as_ptr()), c"FerrosGreeter".as_ptr(), 0);
+            let selector = sel_registerName(c"greet".as_ptr());
+            // Q = unsigned long long return, @ = self, : = _cmd
+            class_addMethod(class, selector, greet as *const c_void, c"Q@:".as_ptr());
+            objc_registerClassPair(class);
+            let greeter = class_createInstance(class, 0);
+
+            let send: extern "C" fn(Id, Sel) -> u64 = std::mem::transmute(objc_msgSend as *const ());
+            loop {
+                let calls = send(greeter, selector);
+                let buffer = malloc(64);
+                println!("-[FerrosGreeter greet] called {calls} times; malloc(64) = {buffer:?}");
+                free(buffer);
+                std::thread::sleep(std::time::Duration::from_secs(1));
+            }
+        }
+    }
+}
+
+fn main()
+{
+    println!("Objective-C Mixed Test Target Starting...");
+    println!("PID: {}", std::process::id());
+
+    #[cfg(target_os = "macos")]
+    target::run();
+
+    #[cfg(not(target_os = "macos"))]
+    eprintln!("objc_mixed needs the macOS Objective-C runtime");
+}
@@ -0,0 +1,25 @@
+//! Stack overflow test target for Ferros
+//!
+//! Recurses until the main thread runs into its stack guard page. Launch it
+//! under Ferros to see the crash classified as a stack overflow and the
+//! repeated `recurse` frames collapsed in the backtrace.
+
+fn main()
+{
+    println!("Stack Overflow Test Target Starting...");
+    println!("PID: {}", std::process::id());
+
+    let depth = recurse(0);
+    println!("Unreachable: returned from depth {depth}");
+}
+
+/// Recurse forever. `black_box` keeps the frame alive so the call can't be
+/// turned into a loop.
+fn recurse(depth: u64) -> u64
+{
+    let frame = std::hint::black_box([depth; 16]);
+    if depth == u64::MAX {
+        return frame[0];
+    }
+    recurse(depth + 1) + frame[1]
+}
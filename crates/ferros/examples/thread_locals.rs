@@ -0,0 +1,46 @@
+//! Thread-local storage test target for Ferros
+//!
+//! Two workers bump the same `thread_local!` counter a different number of
+//! times (3 and 7) and then park in `report`, so each thread holds its own
+//! value. Attach, stop the target and run `:tls COUNTER all` in the TUI to
+//! read every thread's copy.
+
+use std::cell::Cell;
+use std::thread;
+use std::time::Duration;
+
+thread_local! {
+    static COUNTER: Cell<u64> = const { Cell::new(0) };
+}
+
+fn main()
+{
+    println!("Thread-Local Test Target Starting...");
+    println!("PID: {}", std::process::id());
+
+    let workers: Vec<_> = [3, 7]
+        .into_iter()
+        .map(|bumps| thread::spawn(move || count_then_report(bumps)))
+        .collect();
+    for worker in workers {
+        worker.join().ok();
+    }
+}
+
+fn count_then_report(bumps: u64)
+{
+    for _ in 0..bumps {
+        COUNTER.with(|counter| counter.set(counter.get() + 1));
+    }
+    loop {
+        report();
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+#[inline(never)]
+fn report()
+{
+    let value = COUNTER.with(Cell::get);
+    println!("{:?}: COUNTER = {value}", thread::current().id());
+}
@@ -1,10 +1,61 @@
-use std::{env, process};
+mod memory;
+mod output;
+
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use std::{env, io, process};
 
 use clap::{Parser, Subcommand};
+use ferros_core::assertions::{AssertionKind, AssertionSet, AssertionStop, Violation, ViolationAction};
+use ferros_core::breakpoints::BreakpointFile;
 use ferros_core::debugger::create_debugger;
-use ferros_core::types::ProcessId;
-use ferros_core::{Debugger, Result as DebuggerResult};
-use ferros_utils::{LogFormat, LogLevel, debug, info, init_logging, init_logging_for_tui, init_logging_with_level};
+use ferros_core::error::DebuggerError;
+use ferros_core::events::{DebuggerEvent, wait_for_stop};
+use ferros_core::inspector::LiveInspector;
+use ferros_core::notes::{NOTE_MARKER, NoteRegistry};
+use ferros_core::operations::{JournaledDebugger, OperationLog, OperationLogOptions};
+use ferros_core::processes::{find_processes, list_processes};
+use ferros_core::range_checkpoints::{RangeSet, parse_range_spec};
+use ferros_core::session::{SessionFile, SessionInvocation, SessionRecorder, SessionSummary};
+use ferros_core::signals::{SignalPolicy, parse_signal};
+use ferros_core::snapshot::{DEFAULT_SNAPSHOT_MAX_BYTES, SnapshotDebugger, SnapshotOptions};
+use ferros_core::symbols::paths::SourceMap;
+use ferros_core::symbols::{ambiguous_functions, image_uuid};
+use ferros_core::transcript::{DEFAULT_TRANSCRIPT_WARN_BYTES, FsyncPolicy, Transcript, TranscriptOptions};
+use ferros_core::types::{
+    AddressExpr, AddressFormat, LaunchOptions, ProcessId, StopReason, parse_address, parse_env_spec, parse_payload,
+    parse_write_spec,
+};
+use ferros_core::watchdog::{HangReport, TimeoutAction, parse_duration};
+use ferros_core::{
+    Breakpoint, BreakpointBuilder, BreakpointLocation, BreakpointRequest, BreakpointRequestKind, Debugger,
+    Result as DebuggerResult, TargetInspector, permissions,
+};
+use ferros_dap::DapSession;
+use ferros_protocol::mi::MiSession;
+use ferros_ui::config::{self, UserConfig};
+use ferros_ui::json;
+use ferros_ui::repl::{Flow, Repl};
+use ferros_ui::text::{self, use_color};
+use ferros_utils::{
+    Config, ConfigWarning, LogFormat, LogLevel, debug, ferros_dir, info, init_logging, init_logging_for_tui,
+    init_logging_with_level, install_crash_handler, log_to_stderr, on_crash, warn, write_artifact,
+};
+use serde_json::json;
+
+use crate::memory::MemoryArgs;
+use crate::output::{OutputFormat, Report};
+
+/// Exit status when `--timeout` expires, the same as timeout(1)
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Exit status when an `--assert-reached` or `--assert-not-reached` assertion failed
+const ASSERTION_EXIT_CODE: i32 = 3;
+
+/// How long `--checkpoint` waits for the next stop without a `--timeout`: for all practical purposes, forever
+const NO_DEADLINE: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
 
 /// A Rust-native debugger with hybrid MIR and system-level introspection.
 #[derive(Parser, Debug)]
@@ -14,30 +65,78 @@ use ferros_utils::{LogFormat, LogLevel, debug, info, init_logging, init_logging_
 struct Cli
 {
     /// Set the log level (error, warn, info, debug, trace)
-    /// Overrides RUST_LOG environment variable
+    /// Overrides RUST_LOG environment variable and log.level in config.toml
     #[arg(long, value_name = "LEVEL")]
     log_level: Option<String>,
 
     /// Set the log format (pretty, json)
-    /// Overrides FERROS_LOG_FORMAT environment variable
+    /// Overrides FERROS_LOG_FORMAT environment variable and log.format in config.toml
     #[arg(long, value_name = "FORMAT")]
     log_format: Option<String>,
 
+    /// Write a JSON session summary to PATH when the session ends
+    /// (target exit, detach, or quitting ferros)
+    #[arg(long, value_name = "PATH", global = true)]
+    summary: Option<PathBuf>,
+
+    /// Print headless results as human-readable tables or as JSON on stdout
+    /// (one document per command, one line per result in `repl`); logs go to stderr with json
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t = OutputFormat::Human, global = true)]
+    output: OutputFormat,
+
+    /// Disable colored output (also off when NO_COLOR is set or stdout isn't a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Print addresses in tables and hex dumps as full (16 hex digits), trimmed
+    /// (as wide as the largest in the table) or short (12 hex digits)
+    #[arg(long, value_name = "STYLE", default_value_t = AddressFormat::Full16, global = true)]
+    address_format: AddressFormat,
+
+    /// Show sources compiled under FROM (a build-server path) from TO on this machine; repeatable.
+    /// Added to source.map in config.toml
+    #[arg(long, value_name = "FROM=TO", value_parser = SourceMap::parse_remap, global = true)]
+    source_map: Vec<(String, String)>,
+
+    /// Also look for the dSYM bundles of binaries without debug info in PATH,
+    /// a .dSYM bundle or a folder of them; repeatable. Searched before dsym.paths in config.toml
+    #[arg(long, value_name = "PATH", global = true)]
+    dsym_path: Vec<PathBuf>,
+
+    /// Append every backend operation (arguments, result, duration) to PATH as JSON lines
+    #[arg(long, value_name = "PATH", global = true)]
+    ops_log: Option<PathBuf>,
+
+    /// Record memory contents in the operation log instead of their length and digest
+    #[arg(long, requires = "ops_log", global = true)]
+    ops_log_full: bool,
+
+    /// Without a subcommand, the TUI opens on a list of processes to attach to
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands
 {
-    /// Attach to a running process by PID
+    /// Attach to a running process by PID or name
     Attach
     {
         /// Process ID (PID) to attach to
-        pid: u32,
+        #[arg(required_unless_present = "name", conflicts_with = "name")]
+        pid: Option<u32>,
+        /// Attach to the process whose name contains SUBSTRING (ignoring case) instead of a PID
+        #[arg(long, value_name = "SUBSTRING")]
+        name: Option<String>,
         /// Use headless mode (no TUI, just print info and exit)
         #[arg(long, default_value_t = false)]
         headless: bool,
+        #[command(flatten)]
+        options: HeadlessArgs,
+        #[command(flatten)]
+        assertions: AssertionArgs,
+        #[command(flatten)]
+        transcript: TranscriptArgs,
     },
     /// Launch a new process under debugger control
     Launch
@@ -49,25 +148,99 @@ enum Commands
         ///   ferros --log-level debug launch <program>
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
+        /// Set KEY to VALUE in the program's environment; repeatable
+        #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_spec)]
+        env: Vec<(String, String)>,
+        /// Start the program in PATH instead of the current directory
+        #[arg(long, value_name = "PATH")]
+        cwd: Option<PathBuf>,
         /// Use headless mode (no TUI, just print info and exit)
         #[arg(long, default_value_t = false)]
         headless: bool,
+        #[command(flatten)]
+        options: HeadlessArgs,
+        #[command(flatten)]
+        assertions: AssertionArgs,
+        #[command(flatten)]
+        transcript: TranscriptArgs,
+    },
+    /// Line-oriented session on one process: a command per line on stdin until `quit`
+    ///
+    /// Commands mirror the TUI palette (regs, mem, threads, bt, break, c,
+    /// step, detach, quit; `help` lists them all). Without a terminal, commands
+    /// are read from a pipe or here-doc and the prompt is left out, so
+    /// sessions can be scripted.
+    Repl
+    {
+        /// Process ID (PID) to attach to
+        #[arg(required_unless_present = "launch", conflicts_with = "launch")]
+        pid: Option<u32>,
+        /// Launch PROGRAM, stopped before its first instruction, instead of attaching
+        #[arg(long, value_name = "PROGRAM")]
+        launch: Option<String>,
+        /// Arguments to pass to the launched program, after `--`
+        #[arg(last = true, requires = "launch")]
+        args: Vec<String>,
+    },
+    /// Save a stopped copy of a process to a file for post-mortem inspection with `ferros open`
+    ///
+    /// Attaches, saves every thread's registers, the region table and the
+    /// contents of the writable regions (all readable ones with
+    /// --all-regions), then detaches. The process runs on afterwards.
+    Snapshot
+    {
+        /// Process ID (PID) to snapshot
+        pid: u32,
+        /// File to write the snapshot to
+        #[arg(short = 'o', long = "out", value_name = "PATH")]
+        path: PathBuf,
+        /// Save code and read-only data as well as writable memory
+        #[arg(long)]
+        all_regions: bool,
+        /// Save at most BYTES of memory; regions past it are left out
+        #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_SNAPSHOT_MAX_BYTES)]
+        max_size: u64,
+    },
+    /// Browse a snapshot written by `ferros snapshot` in the TUI: registers, memory and stacks, read-only
+    Open
+    {
+        /// Snapshot file to open
+        path: PathBuf,
+    },
+    /// Open the TUI on a searchable list of running processes and attach to the one picked
+    ///
+    /// This is also what `ferros` does without a subcommand.
+    Browse,
+    /// List running processes (PID, name, path), for finding one to attach to
+    Ps
+    {
+        /// Only list processes whose name contains FILTER (ignoring case)
+        filter: Option<String>,
     },
     /// Display CPU registers from the attached process
     Registers,
     /// Read memory from the attached process
     Memory
     {
-        /// Memory address to read from (hex format: 0x1000 or decimal)
+        /// Memory address to read from (hex 0x1000, decimal, or a function plus offset like main+0x10)
+        address: String,
+        #[command(flatten)]
+        options: MemoryArgs,
+    },
+    /// Write bytes to memory in the attached process
+    MemoryWrite
+    {
+        /// Memory address to write to (hex 0x1000, decimal, or a function plus offset like main+0x10)
         address: String,
-        /// Number of bytes to read (default: 16)
-        #[arg(short, long, default_value_t = 16)]
-        length: usize,
+        /// Hex bytes (de ad be ef), a sized integer (u32:0x41414141, u16be:1) or a quoted string
+        payload: String,
     },
     /// List memory regions in the attached process
     Regions,
     /// List all threads in the attached process
     Threads,
+    /// Summarize where the attached process stopped: PC, function, stop reason, regions and top frames
+    Where,
     /// Suspend execution of the attached process
     Suspend,
     /// Resume execution of the attached process
@@ -78,27 +251,221 @@ enum Commands
     Info,
     /// Change directory to the log directory for easy log viewing
     FindLogs,
+    /// Check what this environment allows ferros to debug (root, code signing, SIP) and the config files
+    Doctor,
+    /// Create ~/.ferros with a documented config.toml and keymap.toml; existing files are kept
+    Init,
+    /// Inspect the defaults read from ~/.ferros/config.toml (or FERROS_CONFIG)
+    Config
+    {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Machine interface: JSON requests on stdin, responses and events on stdout
+    ///
+    /// One JSON object per line in each direction. Intended for editor
+    /// integrations and scripts; see the ferros-protocol docs for the schema.
+    Mi,
+    /// Debug Adapter Protocol server on stdin/stdout, for VS Code, nvim-dap and other DAP clients
+    Dap,
+}
+
+/// `ferros config` actions
+#[derive(Subcommand, Debug)]
+enum ConfigAction
+{
+    /// Print the configuration in effect: config.toml's values with the command-line flags applied
+    Show,
+}
+
+/// Options that only apply with --headless
+#[derive(clap::Args, Debug, Clone)]
+struct HeadlessArgs
+{
+    /// Print these tables before detaching (registers, threads, regions, where, modules)
+    #[arg(long, value_name = "TABLE", value_enum, value_delimiter = ',', requires = "headless")]
+    print: Vec<HeadlessTable>,
+
+    /// Wait at most DURATION (e.g. 30s, 500ms, 2m) for the target to stop or exit.
+    /// On expiry ferros writes a hang report and exits with status 124
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, requires = "headless")]
+    timeout: Option<Duration>,
+
+    /// What to do with the target when --timeout expires (kill, detach, keep)
+    #[arg(long, value_name = "ACTION", default_value_t = TimeoutAction::Kill, requires = "timeout")]
+    on_timeout: TimeoutAction,
+
+    /// Hex dump memory at ADDRESS (0x1000, 4096 or main+0x10) to stdout before detaching
+    #[arg(long, value_name = "ADDRESS", requires = "headless")]
+    memory: Option<String>,
+
+    #[command(flatten)]
+    memory_options: MemoryArgs,
+
+    /// Write PAYLOAD at ADDRESS before printing: hex bytes (0x1000=deadbeef), a sized integer
+    /// (buf+8=u32:0x41414141) or a quoted string. Regions without `w` are refused. Repeat for more
+    #[arg(long = "write", value_name = "ADDRESS=PAYLOAD", value_parser = parse_write_spec, requires = "headless")]
+    writes: Vec<(AddressExpr, Vec<u8>)>,
+
+    /// Capture LEN bytes at ADDRESS (e.g. 0x1000:4096 or buf+8:64), resume the target
+    /// and print what changed in them at its next stop. Repeat for more ranges
+    #[arg(long = "checkpoint", value_name = "ADDRESS:LEN", value_parser = parse_range_spec, requires = "headless")]
+    checkpoints: Vec<(AddressExpr, usize)>,
+
+    /// Run until SPEC (a function, file:line or 0x address) is hit before printing. Repeat for more.
+    /// A function name that matches several functions is refused with the list of them
+    #[arg(long = "break", value_name = "SPEC", requires = "headless")]
+    breaks: Vec<String>,
+}
+
+impl HeadlessArgs
+{
+    fn deadline(&self) -> Option<(Duration, TimeoutAction)>
+    {
+        self.timeout.map(|timeout| (timeout, self.on_timeout))
+    }
+}
+
+/// Assertion breakpoints, for using ferros as a test oracle in CI
+///
+/// With assertions a headless session runs the target to exit instead of
+/// stopping at the first stop, and fails with status 3 if any assertion did.
+#[derive(clap::Args, Debug, Clone)]
+struct AssertionArgs
+{
+    /// Fail the session if SPEC (a function, file:line or 0x address) is ever reached. Repeat for more
+    #[arg(long = "assert-not-reached", value_name = "SPEC")]
+    not_reached: Vec<String>,
+
+    /// Fail the session if SPEC is never reached before the target exits. Repeat for more
+    #[arg(long = "assert-reached", value_name = "SPEC")]
+    reached: Vec<String>,
+
+    /// What to do with the target when a --assert-not-reached location is hit (continue, kill)
+    #[arg(long, value_name = "ACTION", default_value_t = ViolationAction::Continue)]
+    on_violation: ViolationAction,
+}
+
+impl AssertionArgs
+{
+    fn build(&self) -> DebuggerResult<AssertionSet>
+    {
+        let mut assertions = AssertionSet::new(self.on_violation);
+        for spec in &self.not_reached {
+            assertions.add(AssertionKind::NotReached, spec)?;
+        }
+        for spec in &self.reached {
+            assertions.add(AssertionKind::Reached, spec)?;
+        }
+        Ok(assertions)
+    }
+}
+
+/// Recording the target's I/O in step with debugger events (TUI sessions)
+#[derive(clap::Args, Debug, Clone)]
+struct TranscriptArgs
+{
+    /// Record captured stdout/stderr and debugger events to PATH as timestamped JSON lines
+    #[arg(long, value_name = "PATH", conflicts_with = "headless")]
+    transcript: Option<PathBuf>,
+
+    /// When to fsync the transcript (never, events, always)
+    #[arg(long, value_name = "POLICY", default_value_t = FsyncPolicy::Events, requires = "transcript")]
+    transcript_fsync: FsyncPolicy,
+
+    /// Warn once the transcript grows past MIB mebibytes
+    #[arg(long, value_name = "MIB", default_value_t = DEFAULT_TRANSCRIPT_WARN_BYTES >> 20, requires = "transcript")]
+    transcript_warn_mib: u64,
+}
+
+impl TranscriptArgs
+{
+    /// Create the transcript file, flushed by the crash handler if ferros panics
+    fn open(&self) -> DebuggerResult<Option<Transcript>>
+    {
+        let Some(path) = &self.transcript else {
+            return Ok(None);
+        };
+        let transcript = Transcript::create(
+            path,
+            TranscriptOptions {
+                fsync: self.transcript_fsync,
+                warn_bytes: self.transcript_warn_mib.saturating_mul(1 << 20),
+            },
+        )?;
+        on_crash({
+            let transcript = transcript.clone();
+            move || transcript.flush_after_crash()
+        });
+        info!("Recording transcript to {}", path.display());
+        Ok(Some(transcript))
+    }
+}
+
+/// What a TUI session is started with besides the debugger
+struct TuiOptions
+{
+    summary: Option<(SessionRecorder, PathBuf)>,
+    session_file: Option<PathBuf>,
+    breakpoint_file: Option<PathBuf>,
+    assertions: AssertionSet,
+    address_format: AddressFormat,
+    transcript: Option<Transcript>,
+    /// Absolute program path and launch options, for `:restart`
+    launch: Option<(String, LaunchOptions)>,
+    /// Startup defaults from `config.toml`
+    config: Config,
+}
+
+/// Table printed by `--print`, with the same columns as the TUI view
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum HeadlessTable
+{
+    Registers,
+    Threads,
+    Regions,
+    /// The `:where` summary of the active thread
+    Where,
+    /// Images the symbol cache has loaded, as in `:modules`
+    Modules,
 }
 
 fn main()
 {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    let command = cli.command.take().unwrap_or(Commands::Browse);
+
+    // A bad startup default keeps its default value; the config report
+    // printed below, or the TUI's banner, says what was wrong
+    let (config, config_warnings) = Config::load();
+    apply_config_defaults(&mut cli, &config);
 
     // Check if we're running in TUI mode (non-headless attach/launch)
+    // MI and DAP modes own stdout for protocol messages, so they log to a file like the TUI
     let is_tui_mode = matches!(
-        cli.command,
-        Commands::Attach { headless: false, .. } | Commands::Launch { headless: false, .. }
+        command,
+        Commands::Attach { headless: false, .. }
+            | Commands::Launch { headless: false, .. }
+            | Commands::Browse
+            | Commands::Open { .. }
+            | Commands::Mi
+            | Commands::Dap
     );
 
+    // JSON output owns stdout, so console logs move to stderr
+    if cli.output == OutputFormat::Json {
+        log_to_stderr();
+    }
+
     // Initialize logging with CLI flags or environment variables
     let _log_file_path = if is_tui_mode {
         // For TUI mode, use file-only logging to prevent stdout interference
         // If --log-level is provided, validate it
-        if let Some(level_str) = &cli.log_level {
-            if level_str.parse::<LogLevel>().is_err() {
-                eprintln!("Invalid log level: {}. Use: error, warn, info, debug, or trace", level_str);
-                process::exit(1);
-            }
+        if let Some(level_str) = &cli.log_level
+            && level_str.parse::<LogLevel>().is_err()
+        {
+            eprintln!("Invalid log level: {}. Use: error, warn, info, debug, or trace", level_str);
+            process::exit(1);
         }
         // Parse log level from CLI if provided, otherwise use None (will use RUST_LOG or default to INFO)
         let log_level = cli.log_level.as_ref().and_then(|s| s.parse::<LogLevel>().ok());
@@ -108,7 +475,7 @@ fn main()
                 // Note: This will go to the log file since we're in TUI mode
                 info!("Logs are being written to: {}", path.display());
                 if let Some(level) = log_level {
-                    info!("Log level set to: {:?} (from --log-level flag)", level);
+                    info!("Log level set to: {:?} (from --log-level flag or config.toml)", level);
                 } else if env::var("RUST_LOG").is_ok() {
                     info!("Log level from RUST_LOG environment variable");
                 } else {
@@ -149,55 +516,157 @@ fn main()
         None
     };
 
+    // Write a crash log if ferros itself panics; in TUI mode the panic message
+    // would otherwise be lost when the terminal is restored
+    install_crash_handler(ferros_dir());
+
     // Check if we need async runtime for TUI (default mode, unless --headless is used)
     let needs_async = matches!(
-        cli.command,
-        Commands::Attach { headless: false, .. } | Commands::Launch { headless: false, .. }
+        command,
+        Commands::Attach { headless: false, .. }
+            | Commands::Launch { headless: false, .. }
+            | Commands::Browse
+            | Commands::Open { .. }
     );
 
     // Handle find-logs command early (before async runtime)
-    if matches!(cli.command, Commands::FindLogs) {
-        let log_dir = if let Ok(home) = std::env::var("HOME") {
-            std::path::PathBuf::from(home).join(".ferros")
-        } else {
-            std::path::PathBuf::from("/tmp")
-        };
-
-        println!("{}", log_dir.display());
+    if matches!(command, Commands::FindLogs) {
+        println!("{}", ferros_dir().display());
         return;
     }
+    if matches!(command, Commands::Init) {
+        if let Err(e) = run_init(&ferros_dir()) {
+            eprintln!("Error: Failed to initialize {}: {}", ferros_dir().display(), e);
+            process::exit(1);
+        }
+        return;
+    }
+    if let Commands::Config {
+        action: ConfigAction::Show,
+    } = command
+    {
+        show_config(&cli, config, &config_warnings);
+        return;
+    }
+    // TUI sessions show these in a banner; doctor checks the config itself
+    if !needs_async && !matches!(command, Commands::Doctor) {
+        report_user_config(&ferros_dir());
+    }
 
     if needs_async {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        if let Err(e) = rt.block_on(run_command_async(cli)) {
+        if let Err(e) = rt.block_on(run_command_async(cli, command, config)) {
             eprintln!("Error: {}", e);
-            process::exit(1);
+            let debugger_error = e.downcast_ref::<DebuggerError>();
+            if let Some(advice) = debugger_error.and_then(DebuggerError::permission_advice) {
+                eprintln!("{advice}");
+            }
+            process::exit(debugger_error.map_or(1, exit_code));
         }
-    } else if let Err(e) = run_command(cli) {
+    } else if let Err(e) = run_command(cli, command, &config) {
         eprintln!("Error: {}", e);
-        process::exit(1);
+        if let Some(advice) = e.permission_advice() {
+            eprintln!("{advice}");
+        }
+        process::exit(exit_code(&e));
     }
 }
 
-async fn run_command_async(cli: Cli) -> Result<(), Box<dyn std::error::Error>>
+/// Exit status for a session that ended with `error`
+fn exit_code(error: &DebuggerError) -> i32
+{
+    match error {
+        DebuggerError::TimedOut(_) => TIMEOUT_EXIT_CODE,
+        DebuggerError::AssertionsFailed(_) => ASSERTION_EXIT_CODE,
+        _ => 1,
+    }
+}
+
+/// How headless output is printed, from the global flags
+#[derive(Debug, Clone, Copy)]
+struct OutputStyle
+{
+    /// Style tables with ANSI colors
+    color: bool,
+    /// Address width in tables and hex dumps
+    addresses: AddressFormat,
+    /// Tables and log lines, or JSON
+    format: OutputFormat,
+}
+
+impl OutputStyle
 {
-    match cli.command {
-        Commands::Attach { pid, headless } => {
-            info!("Attaching to process {}", pid);
-            let mut debugger = create_debugger()?;
-            debugger.attach(ProcessId::from(pid))?;
-            info!("Successfully attached to process {}", pid);
+    fn new(cli: &Cli) -> Self
+    {
+        Self {
+            color: use_color(cli.no_color),
+            addresses: cli.address_format,
+            format: cli.output,
+        }
+    }
+}
 
+async fn run_command_async(cli: Cli, command: Commands, config: Config) -> Result<(), Box<dyn std::error::Error>>
+{
+    let style = OutputStyle::new(&cli);
+    let backend = BackendOptions::new(
+        cli.source_map,
+        cli.dsym_path,
+        &config.signals,
+        cli.ops_log.as_deref(),
+        cli.ops_log_full,
+    )?;
+    match command {
+        Commands::Attach {
+            pid,
+            name,
+            headless,
+            options,
+            assertions,
+            transcript,
+        } => {
+            let pid = attach_pid(pid, name.as_deref(), style)?;
+            let mut assertions = assertions.build()?;
             if headless {
-                print_debugger_info(&*debugger)?;
-                // In headless mode, detach after showing info
-                debugger.detach()?;
-            } else {
-                ferros_ui::run_tui(debugger, Some(pid), false).await?;
+                return Ok(run_headless(
+                    SessionInvocation::attach(pid),
+                    cli.summary,
+                    &backend,
+                    &options,
+                    assertions,
+                    style,
+                    |debugger, assertions| attach_target(debugger, pid, assertions, &options.breaks),
+                )?);
             }
+
+            let transcript = transcript.open()?;
+            let (debugger, recorder, pid) =
+                start_session(SessionInvocation::attach(pid), cli.summary.as_deref(), &backend, |debugger| {
+                    attach_target(debugger, pid, &mut assertions, &[])
+                })?;
+            let options = TuiOptions {
+                summary: cli.summary.map(|path| (recorder, path)),
+                session_file: None,
+                breakpoint_file: None,
+                assertions,
+                address_format: cli.address_format,
+                transcript,
+                launch: None,
+                config,
+            };
+            run_tui_session(debugger, options, Some(pid), false).await?;
             Ok(())
         }
-        Commands::Launch { program, args, headless } => {
+        Commands::Launch {
+            program,
+            args,
+            env,
+            cwd,
+            headless,
+            options,
+            assertions,
+            transcript,
+        } => {
             // Check if --log-level was accidentally passed as a program argument
             if args.iter().any(|arg| arg == "--log-level" || arg.starts_with("--log-level=")) {
                 eprintln!("Warning: --log-level flag detected in program arguments.");
@@ -206,51 +675,78 @@ async fn run_command_async(cli: Cli) -> Result<(), Box<dyn std::error::Error>>
                 eprintln!("  Your command: ferros launch <program> --log-level debug (incorrect)");
             }
 
-            info!("Launching program: {} with args: {:?}", program, args);
-            if !headless {
-                info!("Note: For best debugging experience, ensure your program was built with debug symbols");
-                info!("  Rust: Use 'cargo build' (debug mode) or 'cargo build --release' with debug=true");
-                info!("  C/C++: Compile with -g flag");
+            let mut assertions = assertions.build()?;
+            let invocation = SessionInvocation::launch(program.clone(), args.clone());
+            let launch = LaunchOptions {
+                args,
+                env,
+                cwd,
+                ..LaunchOptions::default()
+            };
+            if headless {
+                return Ok(run_headless(
+                    invocation,
+                    cli.summary,
+                    &backend,
+                    &options,
+                    assertions,
+                    style,
+                    |debugger, assertions| launch_target(debugger, &program, &launch, assertions, &options.breaks),
+                )?);
             }
-            let mut debugger = create_debugger()?;
 
-            if !headless {
-                debugger.set_capture_process_output(true);
-            }
+            info!("Note: For best debugging experience, ensure your program was built with debug symbols");
+            info!("  Rust: Use 'cargo build' (debug mode) or 'cargo build --release' with debug=true");
+            info!("  C/C++: Compile with -g flag");
 
-            // Convert relative path to absolute path for posix_spawn
-            let program_path = std::path::Path::new(&program);
-            let absolute_program = if program_path.is_absolute() {
-                program.clone()
-            } else {
-                std::env::current_dir()?
-                    .join(program_path)
-                    .canonicalize()?
-                    .to_string_lossy()
-                    .to_string()
+            let transcript = transcript.open()?;
+            let (program_path, launch) = resolve_launch(&program, launch)?;
+            let (debugger, recorder, pid) = start_session(invocation, cli.summary.as_deref(), &backend, |debugger| {
+                debugger.set_capture_process_output(true);
+                launch_target(debugger, &program_path, &launch, &mut assertions, &[])
+            })?;
+            println!("Running Ferros TUI");
+            let options = TuiOptions {
+                summary: cli.summary.map(|path| (recorder, path)),
+                session_file: SessionFile::path_for(Path::new(&program)),
+                breakpoint_file: BreakpointFile::path_for(Path::new(&program)),
+                assertions,
+                address_format: cli.address_format,
+                transcript,
+                launch: Some((program_path, launch)),
+                config,
             };
-
-            // The launch method requires at least one argument (typically the program name)
-            // If no args provided, use the program name itself
-            let args_refs: Vec<&str> = if args.is_empty() {
-                vec![&absolute_program]
-            } else {
-                args.iter().map(|s| s.as_str()).collect()
+            run_tui_session(debugger, options, Some(pid), true).await?;
+            Ok(())
+        }
+        Commands::Browse => {
+            let options = TuiOptions {
+                summary: None,
+                session_file: None,
+                breakpoint_file: None,
+                assertions: AssertionSet::default(),
+                address_format: cli.address_format,
+                transcript: None,
+                launch: None,
+                config,
             };
-
-            let pid = debugger.launch(&absolute_program, &args_refs)?;
-
-            // Process starts suspended, resume it so it runs normally
-            debugger.resume()?;
-
-            if headless {
-                print_debugger_info(&*debugger)?;
-                // In headless mode, detach after showing info
-                debugger.detach()?;
-            } else {
-                println!("Running Ferros TUI");
-                ferros_ui::run_tui(debugger, Some(pid.0), true).await?;
-            }
+            run_tui_session(new_debugger(&backend)?, options, None, false).await?;
+            Ok(())
+        }
+        Commands::Open { path } => {
+            let debugger = Box::new(SnapshotDebugger::open(&path)?);
+            info!("Opened snapshot {}", path.display());
+            let options = TuiOptions {
+                summary: None,
+                session_file: None,
+                breakpoint_file: None,
+                assertions: AssertionSet::default(),
+                address_format: cli.address_format,
+                transcript: None,
+                launch: None,
+                config,
+            };
+            run_tui_session(debugger, options, None, false).await?;
             Ok(())
         }
         _ => {
@@ -260,77 +756,125 @@ async fn run_command_async(cli: Cli) -> Result<(), Box<dyn std::error::Error>>
     }
 }
 
-fn run_command(cli: Cli) -> DebuggerResult<()>
+fn run_command(cli: Cli, command: Commands, config: &Config) -> DebuggerResult<()>
 {
-    match cli.command {
-        Commands::Attach { pid, headless: true } => {
-            info!("Attaching to process {}", pid);
-            let mut debugger = create_debugger()?;
-            debugger.attach(ProcessId::from(pid))?;
-            info!("Successfully attached to process {}", pid);
-            print_debugger_info(&*debugger)?;
-            // Detach after showing info in headless mode
-            debugger.detach()?;
-            Ok(())
+    let style = OutputStyle::new(&cli);
+    let backend = BackendOptions::new(
+        cli.source_map,
+        cli.dsym_path,
+        &config.signals,
+        cli.ops_log.as_deref(),
+        cli.ops_log_full,
+    )?;
+    match command {
+        Commands::Attach {
+            pid,
+            name,
+            headless: true,
+            options,
+            assertions,
+            ..
+        } => {
+            let pid = attach_pid(pid, name.as_deref(), style)?;
+            run_headless(
+                SessionInvocation::attach(pid),
+                cli.summary,
+                &backend,
+                &options,
+                assertions.build()?,
+                style,
+                |debugger, assertions| attach_target(debugger, pid, assertions, &options.breaks),
+            )
         }
         Commands::Launch {
             program,
             args,
+            env,
+            cwd,
             headless: true,
+            options,
+            assertions,
+            ..
         } => {
-            info!("Launching program: {} with args: {:?}", program, args);
-            let mut debugger = create_debugger()?;
-
-            // Convert relative path to absolute path for posix_spawn
-            let program_path = std::path::Path::new(&program);
-            let absolute_program = if program_path.is_absolute() {
-                program.clone()
-            } else {
-                std::env::current_dir()?
-                    .join(program_path)
-                    .canonicalize()?
-                    .to_string_lossy()
-                    .to_string()
+            let launch = LaunchOptions {
+                args: args.clone(),
+                env,
+                cwd,
+                ..LaunchOptions::default()
             };
-
-            // If no args provided, use the program name itself
-            let args_refs: Vec<&str> = if args.is_empty() {
-                vec![&absolute_program]
-            } else {
-                args.iter().map(|s| s.as_str()).collect()
-            };
-
-            let pid = debugger.launch(&absolute_program, &args_refs)?;
-            info!("Successfully launched program: {} (PID: {})", absolute_program, pid.0);
-
-            // Process starts suspended, resume it so it runs normally
-            debugger.resume()?;
-            info!("Process resumed and running");
-
-            print_debugger_info(&*debugger)?;
-            // Detach after showing info in headless mode
-            debugger.detach()?;
-            Ok(())
+            run_headless(
+                SessionInvocation::launch(program.clone(), args),
+                cli.summary,
+                &backend,
+                &options,
+                assertions.build()?,
+                style,
+                |debugger, assertions| launch_target(debugger, &program, &launch, assertions, &options.breaks),
+            )
         }
-        Commands::Attach { headless: false, .. } | Commands::Launch { headless: false, .. } => {
+        Commands::Attach { headless: false, .. }
+        | Commands::Launch { headless: false, .. }
+        | Commands::Browse
+        | Commands::Open { .. } => {
             // These should be handled by run_command_async
             Err(ferros_core::error::DebuggerError::InvalidArgument(
                 "TUI mode requires async runtime".to_string(),
             ))
         }
+        Commands::Repl { pid, launch, args } => run_repl(pid, launch.as_deref(), args, &backend, style),
+        Commands::Snapshot {
+            pid,
+            path,
+            all_regions,
+            max_size,
+        } => {
+            let mut options = SnapshotOptions::new().with_max_bytes(max_size);
+            if all_regions {
+                options = options.with_all_regions();
+            }
+            run_snapshot(pid, &path, options, &backend, style)
+        }
+        Commands::Ps { filter } => {
+            let processes = match filter {
+                Some(filter) => find_processes(&filter)?,
+                None => list_processes()?,
+            };
+            if style.format == OutputFormat::Json {
+                println!("{}", json::processes(&processes));
+            } else {
+                print!("{}", text::processes_table(&processes).render(style.color));
+            }
+            Ok(())
+        }
         Commands::Registers => {
             // TODO: Implement state management to persist debugger instance
             eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
             eprintln!(
-                "Note: This command requires an attached process. State management will be added in a future version."
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
             );
             Err(ferros_core::error::DebuggerError::NotAttached)
         }
-        Commands::Memory { address: _, length: _ } => {
+        Commands::MemoryWrite { address, payload } => {
+            // Bad arguments are reported before the missing session
+            parse_address(&address)?;
+            parse_payload(&payload)?;
             // TODO: Implement state management to persist debugger instance
             eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
             eprintln!(
-                "Note: This command requires an attached process. State management will be added in a future version."
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
+            );
+            Err(ferros_core::error::DebuggerError::NotAttached)
+        }
+        Commands::Memory { address, options } => {
+            // Bad arguments are reported before the missing session
+            if let AddressExpr::Absolute(start) = parse_address(&address)? {
+                options.range(start)?;
+            }
+            options.validate()?;
+            // TODO: Implement state management to persist debugger instance
+            eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
+            eprintln!(
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
             );
             Err(ferros_core::error::DebuggerError::NotAttached)
         }
@@ -338,7 +882,7 @@ fn run_command(cli: Cli) -> DebuggerResult<()>
             // TODO: Implement state management to persist debugger instance
             eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
             eprintln!(
-                "Note: This command requires an attached process. State management will be added in a future version."
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
             );
             Err(ferros_core::error::DebuggerError::NotAttached)
         }
@@ -346,7 +890,15 @@ fn run_command(cli: Cli) -> DebuggerResult<()>
             // TODO: Implement state management to persist debugger instance
             eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
             eprintln!(
-                "Note: This command requires an attached process. State management will be added in a future version."
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
+            );
+            Err(ferros_core::error::DebuggerError::NotAttached)
+        }
+        Commands::Where => {
+            // TODO: Implement state management to persist debugger instance
+            eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
+            eprintln!(
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
             );
             Err(ferros_core::error::DebuggerError::NotAttached)
         }
@@ -354,7 +906,7 @@ fn run_command(cli: Cli) -> DebuggerResult<()>
             // TODO: Implement state management to persist debugger instance
             eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
             eprintln!(
-                "Note: This command requires an attached process. State management will be added in a future version."
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
             );
             Err(ferros_core::error::DebuggerError::NotAttached)
         }
@@ -362,7 +914,7 @@ fn run_command(cli: Cli) -> DebuggerResult<()>
             // TODO: Implement state management to persist debugger instance
             eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
             eprintln!(
-                "Note: This command requires an attached process. State management will be added in a future version."
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
             );
             Err(ferros_core::error::DebuggerError::NotAttached)
         }
@@ -370,7 +922,7 @@ fn run_command(cli: Cli) -> DebuggerResult<()>
             // TODO: Implement state management to persist debugger instance
             eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
             eprintln!(
-                "Note: This command requires an attached process. State management will be added in a future version."
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
             );
             Err(ferros_core::error::DebuggerError::NotAttached)
         }
@@ -378,34 +930,1034 @@ fn run_command(cli: Cli) -> DebuggerResult<()>
             // TODO: Implement state management to persist debugger instance
             eprintln!("Error: No process attached. Use 'ferros attach <pid>' or 'ferros launch <program>' first.");
             eprintln!(
-                "Note: This command requires an attached process. State management will be added in a future version."
+                "Note: Each ferros command runs on its own; use 'ferros repl <pid>' to run commands against one session."
             );
             Err(ferros_core::error::DebuggerError::NotAttached)
         }
-        Commands::FindLogs => {
-            // This should be handled in main() before reaching here
-            unreachable!("FindLogs should be handled in main()")
+        Commands::FindLogs | Commands::Init | Commands::Config { .. } => {
+            // These are handled in main() before reaching here
+            unreachable!("FindLogs, Init and Config should be handled in main()")
+        }
+        Commands::Doctor => {
+            print_doctor(style.format);
+            Ok(())
+        }
+        Commands::Mi => run_mi(cli.summary, &backend),
+        Commands::Dap => run_dap(&backend),
+    }
+}
+
+/// The PID to attach to: `pid` if given, else the one process whose name contains `name`
+///
+/// Several matches are listed so the user can pick one by PID.
+fn attach_pid(pid: Option<u32>, name: Option<&str>, style: OutputStyle) -> DebuggerResult<u32>
+{
+    if let Some(pid) = pid {
+        return Ok(pid);
+    }
+    // clap requires one of the two
+    let name = name.unwrap_or_default();
+    let mut matches = find_processes(name)?;
+    match matches.len() {
+        0 => Err(DebuggerError::InvalidArgument(format!("No process name contains '{name}'"))),
+        1 => {
+            let process = matches.remove(0);
+            eprintln!("Attaching to {} ({})", process.name, process.pid.0);
+            Ok(process.pid.0)
+        }
+        count => {
+            if style.format == OutputFormat::Json {
+                println!("{}", json::processes(&matches));
+            } else {
+                print!("{}", text::processes_table(&matches).render(style.color));
+            }
+            Err(DebuggerError::InvalidArgument(format!(
+                "{count} processes match '{name}'; attach by PID or use a longer --name"
+            )))
+        }
+    }
+}
+
+/// Attach to `pid` and set the assertion breakpoints
+fn attach_target(
+    debugger: &mut dyn Debugger,
+    pid: u32,
+    assertions: &mut AssertionSet,
+    breaks: &[String],
+) -> DebuggerResult<u32>
+{
+    info!("Attaching to process {}", pid);
+    debugger.attach(ProcessId::from(pid))?;
+    info!("Successfully attached to process {}", pid);
+    assertions.install(debugger)?;
+    if !breaks.is_empty() {
+        // Attaching stops the target; let it run to the breakpoints
+        set_breakpoints(debugger, breaks)?;
+        debugger.resume()?;
+    }
+    Ok(pid)
+}
+
+/// Absolute path of `program` for `posix_spawn`, and `launch` with at least `argv[0]`
+fn resolve_launch(program: &str, mut launch: LaunchOptions) -> DebuggerResult<(String, LaunchOptions)>
+{
+    // Convert relative path to absolute path for posix_spawn
+    let program_path = Path::new(program);
+    let absolute_program = if program_path.is_absolute() {
+        program.to_string()
+    } else {
+        env::current_dir()?
+            .join(program_path)
+            .canonicalize()?
+            .to_string_lossy()
+            .to_string()
+    };
+
+    // The launch method requires at least one argument (typically the program name)
+    // If no args provided, use the program name itself
+    if launch.args.is_empty() {
+        launch.args.push(absolute_program.clone());
+    }
+    Ok((absolute_program, launch))
+}
+
+/// Launch `program` suspended, set the assertion and `--break` breakpoints, then let it run
+fn launch_target(
+    debugger: &mut dyn Debugger,
+    program: &str,
+    launch: &LaunchOptions,
+    assertions: &mut AssertionSet,
+    breaks: &[String],
+) -> DebuggerResult<u32>
+{
+    info!("Launching program: {} with args: {:?}", program, launch.args);
+
+    let (absolute_program, launch) = resolve_launch(program, launch.clone())?;
+    let pid = debugger.launch_with_options(&absolute_program, &launch)?;
+    info!("Successfully launched program: {} (PID: {})", absolute_program, pid.0);
+    // Before the first instruction runs, so early hits aren't missed
+    assertions.install(debugger)?;
+    set_breakpoints(debugger, breaks)?;
+
+    // Process starts suspended, resume it so it runs normally
+    debugger.resume()?;
+    info!("Process resumed and running");
+    Ok(pid.0)
+}
+
+/// Set the `--break` breakpoints, one per address a spec resolves to
+///
+/// Copies of a function or line (generic instances, inlined code) each get a
+/// breakpoint, but a function name that finds differently named functions is
+/// refused with the list of them rather than guessing.
+fn set_breakpoints(debugger: &mut dyn Debugger, specs: &[String]) -> DebuggerResult<()>
+{
+    for spec in specs {
+        let location: BreakpointLocation = spec.parse().map_err(DebuggerError::InvalidArgument)?;
+        let addresses = match &location {
+            BreakpointLocation::Function(name) => {
+                let functions = debugger.find_function(name)?;
+                if let Some(message) = ambiguous_functions(name, &functions) {
+                    return Err(DebuggerError::InvalidArgument(message));
+                }
+                functions.into_iter().map(|(address, _)| address).collect()
+            }
+            _ => debugger.resolve_location(&location)?,
+        };
+        let requests: Vec<BreakpointRequest> = if addresses.is_empty() {
+            // The backend explains why the location has no code
+            vec![BreakpointBuilder::new(BreakpointRequestKind::Location(location)).build()]
+        } else {
+            addresses
+                .into_iter()
+                .map(|address| Breakpoint::software(address).build())
+                .collect()
+        };
+        for request in requests {
+            let id = debugger
+                .add_breakpoint(request)
+                .map_err(|err| DebuggerError::InvalidArgument(format!("can't set breakpoint {spec}: {err}")))?;
+            info!("Breakpoint #{} set at {}", id.raw(), spec);
+        }
+    }
+    Ok(())
+}
+
+/// How the platform debugger is set up, from the global flags and `config.toml`
+struct BackendOptions
+{
+    /// `--source-map` remaps, then `source.map`
+    source_map: SourceMap,
+    /// `--dsym-path` directories, then `dsym.paths`
+    dsym_paths: Vec<PathBuf>,
+    /// `signal.*` policies
+    signals: Vec<(i32, SignalPolicy)>,
+    /// `--ops-log`, opened
+    ops_log: Option<OperationLog>,
+}
+
+impl BackendOptions
+{
+    fn new(
+        source_map: Vec<(String, String)>,
+        dsym_paths: Vec<PathBuf>,
+        signals: &[(String, String)],
+        ops_log: Option<&Path>,
+        full_memory: bool,
+    ) -> DebuggerResult<Self>
+    {
+        // The config report lists the policies that can't be used
+        let signals = signals
+            .iter()
+            .filter_map(|(signal, policy)| {
+                let parsed = parse_signal(signal).and_then(|number| Ok((number, policy.parse()?)));
+                parsed
+                    .inspect_err(|e| warn!("Ignoring signal.{signal} from config.toml: {e}"))
+                    .ok()
+            })
+            .collect();
+        let ops_log = ops_log
+            .map(|path| {
+                info!("Logging backend operations to {}", path.display());
+                OperationLog::create(path, OperationLogOptions { full_memory })
+            })
+            .transpose()?;
+        Ok(Self {
+            source_map: SourceMap::new(source_map),
+            dsym_paths,
+            signals,
+            ops_log,
+        })
+    }
+}
+
+/// Create the platform debugger with the `--source-map` remaps, dSYM directories and signal policies applied
+///
+/// It's always journaled, so the TUI's `:opslog` can start an operation log
+/// without `--ops-log`.
+fn new_debugger(backend: &BackendOptions) -> DebuggerResult<Box<dyn Debugger>>
+{
+    let mut debugger: Box<dyn Debugger> = Box::new(JournaledDebugger::new(create_debugger()?));
+    debugger.set_operation_log(backend.ops_log.clone())?;
+    if !backend.source_map.is_empty() {
+        debugger.set_source_map(backend.source_map.clone());
+    }
+    for path in &backend.dsym_paths {
+        debugger.add_dsym_search_path(path.clone())?;
+    }
+    for &(signal, policy) in &backend.signals {
+        debugger.set_signal_policy(signal, policy)?;
+    }
+    Ok(debugger)
+}
+
+/// Create a debugger and start the target with `start`.
+///
+/// If startup fails and `--summary` was given, the summary is written right
+/// away since no later cleanup will run.
+fn start_session(
+    invocation: SessionInvocation,
+    summary: Option<&Path>,
+    backend: &BackendOptions,
+    start: impl FnOnce(&mut dyn Debugger) -> DebuggerResult<u32>,
+) -> DebuggerResult<(Box<dyn Debugger>, SessionRecorder, u32)>
+{
+    let mut recorder = SessionRecorder::new(invocation);
+    let started = new_debugger(backend).and_then(|mut debugger| {
+        let pid = start(debugger.as_mut())?;
+        Ok((debugger, pid))
+    });
+
+    match started {
+        Ok((debugger, pid)) => {
+            recorder.record_started(pid);
+            Ok((debugger, recorder, pid))
+        }
+        Err(e) => {
+            if let Some(path) = summary {
+                recorder.record_error(&e);
+                write_summary(&recorder.finish(None), path);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Start the target, print debugger info and the `--print` tables, and detach
+///
+/// With a `--timeout`, first wait for the target to stop or exit; if it
+/// doesn't in time, hand over to [`give_up_on_target`]. With assertions, run
+/// the target to its end instead (see [`watch_assertions`]) and fail with
+/// `AssertionsFailed` if any assertion did.
+fn run_headless(
+    invocation: SessionInvocation,
+    summary: Option<PathBuf>,
+    backend: &BackendOptions,
+    options: &HeadlessArgs,
+    mut assertions: AssertionSet,
+    style: OutputStyle,
+    start: impl FnOnce(&mut dyn Debugger, &mut AssertionSet) -> DebuggerResult<u32>,
+) -> DebuggerResult<()>
+{
+    // Check --memory before starting the target
+    let memory = options.memory.as_deref().map(parse_address).transpose()?;
+    if memory.is_some() {
+        options.memory_options.validate()?;
+    }
+
+    let session_file = invocation
+        .program
+        .as_deref()
+        .and_then(|program| SessionFile::path_for(Path::new(program)));
+    let (mut debugger, mut recorder, pid) = start_session(invocation, summary.as_deref(), backend, |debugger| {
+        start(debugger, &mut assertions)
+    })?;
+    let events = debugger.take_event_receiver();
+    let mut report = Report::new(style.format);
+    report.set("pid", pid);
+
+    let mut ended = false;
+    let mut result = Ok(());
+    if !assertions.is_empty() {
+        let wait = options.timeout.unwrap_or(NO_DEADLINE);
+        match watch_assertions(debugger.as_mut(), events.as_ref(), &mut recorder, &mut assertions, wait) {
+            Ok(target_ended) => ended = target_ended,
+            Err(DebuggerError::TimedOut(_)) if let Some((timeout, action)) = options.deadline() => {
+                return give_up_on_target(debugger, recorder, summary.as_deref(), timeout, action);
+            }
+            Err(e) => result = Err(e),
+        }
+    } else if let Some(events) = &events
+        && (options.deadline().is_some() || !options.breaks.is_empty())
+    {
+        let wait = options.timeout.unwrap_or(NO_DEADLINE);
+        match wait_for_stop(events, wait, |envelope| recorder.record_event(&envelope.event)) {
+            Ok(envelope) => {
+                info!("Target stopped: {}", envelope.event.describe());
+                if let DebuggerEvent::TargetStopped { reason, .. } = envelope.event {
+                    report.set("stop", json!(reason));
+                }
+                ended = matches!(
+                    envelope.event,
+                    DebuggerEvent::TargetStopped {
+                        reason: StopReason::Exited(_),
+                        ..
+                    }
+                );
+            }
+            Err(DebuggerError::TimedOut(_)) if let Some((timeout, action)) = options.deadline() => {
+                return give_up_on_target(debugger, recorder, summary.as_deref(), timeout, action);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    // An exited or killed target has nothing left to print
+    report.set("ended", ended);
+    if result.is_ok() && !ended {
+        result = print_debugger_info(&*debugger, &mut report);
+        let notes = load_notes(&*debugger, session_file.as_deref());
+        if result.is_ok() {
+            result = write_memory(debugger.as_mut(), &options.writes);
+        }
+        print_tables(debugger.as_mut(), &options.print, &notes, style, &mut report);
+        if let (Ok(()), Some(expr)) = (&result, &memory) {
+            result = dump_memory(debugger.as_mut(), expr, &options.memory_options, &notes, style, &mut report);
+        }
+        if report.is_json() {
+            report.set("breakpoints", json::breakpoints(&debugger.breakpoints()));
+        }
+    }
+    for violation in assertions.finish() {
+        recorder.record_violation(&violation);
+    }
+    if result.is_ok() && !ended && !options.checkpoints.is_empty() {
+        let wait = options.timeout.unwrap_or(NO_DEADLINE);
+        result = checkpoint_across_resume(
+            debugger.as_mut(),
+            events.as_ref(),
+            &mut recorder,
+            &options.checkpoints,
+            wait,
+            &mut report,
+        );
+        if let (Err(DebuggerError::TimedOut(_)), Some((timeout, action))) = (&result, options.deadline()) {
+            return give_up_on_target(debugger, recorder, summary.as_deref(), timeout, action);
+        }
+    }
+
+    if let Some(path) = &summary {
+        if let Err(e) = &result {
+            recorder.record_error(e);
+        }
+        if let Some(events) = &events {
+            for envelope in events.try_iter() {
+                recorder.record_event(&envelope.event);
+            }
+        }
+        write_summary(&recorder.finish(Some(debugger.as_mut())), path);
+    }
+
+    if !assertions.is_empty() {
+        report.set("violations", json!(assertions.violations()));
+    }
+    // Partial results are still printed when a step failed
+    report.finish()?;
+    result?;
+    if !ended {
+        // Detach after showing info in headless mode
+        debugger.detach()?;
+    }
+    match assertions.violations().len() {
+        0 => Ok(()),
+        failed => Err(DebuggerError::AssertionsFailed(failed)),
+    }
+}
+
+/// Run the target to its end, checking every stop against the assertions
+///
+/// Reached assertions are resumed past. A violation is printed and recorded,
+/// then the target is resumed or killed per `--on-violation`. Returns whether
+/// the target ended (exited or was killed); any other stop leaves it stopped
+/// for the usual headless output. Waits at most `wait` in total; `TimedOut`
+/// is left to the caller.
+fn watch_assertions(
+    debugger: &mut dyn Debugger,
+    events: Option<&ferros_core::DebuggerEventReceiver>,
+    recorder: &mut SessionRecorder,
+    assertions: &mut AssertionSet,
+    wait: Duration,
+) -> DebuggerResult<bool>
+{
+    let Some(events) = events else {
+        return Err(DebuggerError::InvalidArgument(
+            "assertions need debugger events, which this debugger doesn't provide".to_string(),
+        ));
+    };
+    let deadline = Instant::now() + wait;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let envelope = wait_for_stop(events, remaining, |envelope| recorder.record_event(&envelope.event))?;
+        let DebuggerEvent::TargetStopped { reason, thread } = envelope.event else {
+            continue;
+        };
+        match assertions.on_stop(debugger, reason, thread) {
+            Some(AssertionStop::Reached(assertion)) => {
+                info!("Assertion {} reached", assertion);
+                debugger.resume()?;
+            }
+            Some(AssertionStop::Violated(violation)) => {
+                print_violation(&violation);
+                recorder.record_violation(&violation);
+                if assertions.action() == ViolationAction::Kill {
+                    debugger.kill()?;
+                    return Ok(true);
+                }
+                debugger.resume()?;
+            }
+            None => {
+                info!("Target stopped: {}", envelope.event.describe());
+                return Ok(matches!(reason, StopReason::Exited(_)));
+            }
+        }
+    }
+}
+
+/// Print a violation with its backtrace and arguments to stderr
+fn print_violation(violation: &Violation)
+{
+    eprintln!("Error: {}", violation);
+    for (index, frame) in violation.backtrace.iter().enumerate() {
+        let function = frame.function.as_deref().unwrap_or("<unknown>");
+        match (&frame.file, frame.line) {
+            (Some(file), Some(line)) => eprintln!("  #{index} 0x{:x} {function} at {file}:{line}", frame.pc),
+            _ => eprintln!("  #{index} 0x{:x} {function}", frame.pc),
+        }
+    }
+    if !violation.arguments.is_empty() {
+        eprintln!("  arguments: {}", violation.arguments.join(", "));
+    }
+}
+
+/// Handle a target that missed its `--timeout` deadline
+///
+/// Suspends it, writes a hang report with every thread's backtrace next to
+/// the crash logs, writes the summary, then applies `--on-timeout`. Always
+/// returns `TimedOut` so ferros exits with [`TIMEOUT_EXIT_CODE`].
+fn give_up_on_target(
+    mut debugger: Box<dyn Debugger>,
+    mut recorder: SessionRecorder,
+    summary: Option<&Path>,
+    timeout: Duration,
+    action: TimeoutAction,
+) -> DebuggerResult<()>
+{
+    eprintln!("Error: target did not stop within {timeout:?}");
+    recorder.record_timeout(timeout, action);
+
+    match HangReport::capture(debugger.as_mut(), timeout) {
+        Ok(report) => match write_artifact("hang", &report.render()) {
+            Ok(path) => {
+                eprintln!("Hang report written to {}", path.display());
+                recorder.record_artifact(path);
+            }
+            Err(e) => eprintln!("Warning: Failed to write hang report: {}", e),
+        },
+        Err(e) => {
+            eprintln!("Warning: Failed to capture backtraces: {}", e);
+            recorder.record_error(&e);
+        }
+    }
+
+    // Summarize before the action; breakpoint state is gone after detaching
+    let finished = recorder.finish(Some(debugger.as_mut()));
+    if let Err(e) = action.apply(debugger.as_mut()) {
+        eprintln!("Warning: Failed to {} the target: {}", action, e);
+    }
+    if let Some(path) = summary {
+        write_summary(&finished, path);
+    }
+    Err(DebuggerError::TimedOut(timeout))
+}
+
+/// Run the TUI until the user quits, failing with `AssertionsFailed` if any assertion did
+///
+/// Without a `pid` the debugger isn't attached yet and the TUI opens on the process picker.
+async fn run_tui_session(
+    debugger: Box<dyn Debugger>,
+    options: TuiOptions,
+    pid: Option<u32>,
+    was_launched: bool,
+) -> Result<(), Box<dyn std::error::Error>>
+{
+    let mut tui = ferros_ui::Tui::new()?.with_address_format(options.address_format);
+    if let Some((recorder, path)) = options.summary {
+        tui = tui.with_session_summary(recorder, path);
+    }
+    if let Some(path) = options.session_file {
+        tui = tui.with_session_file(path);
+    }
+    if let Some(path) = options.breakpoint_file {
+        tui = tui.with_breakpoint_file(path);
+    }
+    if !options.assertions.is_empty() {
+        tui = tui.with_assertions(options.assertions);
+    }
+    if let Some(transcript) = options.transcript {
+        tui = tui.with_transcript(transcript);
+    }
+    if let Some((program, launch)) = options.launch {
+        tui = tui.with_launch(program, launch);
+    }
+    let dir = ferros_dir();
+    tui = tui.with_config(options.config).with_user_config(UserConfig::load(&dir));
+    if config::take_first_run_hint(&dir) {
+        tui = tui.with_first_run_hint();
+    }
+    tui.run(debugger, pid, was_launched).await?;
+    for violation in tui.violations() {
+        print_violation(violation);
+    }
+    match tui.violations().len() {
+        0 => Ok(()),
+        failed => Err(DebuggerError::AssertionsFailed(failed).into()),
+    }
+}
+
+/// Serve the machine interface on stdin/stdout until the client disconnects
+fn run_mi(summary: Option<PathBuf>, backend: &BackendOptions) -> DebuggerResult<()>
+{
+    let mut session = MiSession::new(new_debugger(backend)?, io::stdout());
+    if summary.is_some() {
+        session = session.with_recorder(SessionRecorder::new(SessionInvocation::mi()));
+    }
+    info!("Serving machine interface on stdin/stdout");
+
+    let finished = session.run(io::BufReader::new(io::stdin()))?;
+    if let (Some(finished), Some(path)) = (finished, &summary) {
+        write_summary(&finished, path);
+    }
+    Ok(())
+}
+
+/// Serve the Debug Adapter Protocol on stdin/stdout until the editor disconnects
+fn run_dap(backend: &BackendOptions) -> DebuggerResult<()>
+{
+    let session = DapSession::new(new_debugger(backend)?, io::stdout());
+    info!("Serving the Debug Adapter Protocol on stdin/stdout");
+    session.run(io::BufReader::new(io::stdin()))?;
+    Ok(())
+}
+
+/// Run `ferros snapshot`: attach, save the stopped process to `path` and detach
+fn run_snapshot(
+    pid: u32,
+    path: &Path,
+    options: SnapshotOptions,
+    backend: &BackendOptions,
+    style: OutputStyle,
+) -> DebuggerResult<()>
+{
+    let mut debugger = new_debugger(backend)?;
+    debugger.attach(ProcessId::from(pid))?;
+    // Attaching stops the target on macOS; suspend it anyway where it doesn't
+    let stopped = if debugger.is_stopped() { Ok(()) } else { debugger.suspend() };
+    let written = stopped.and_then(|()| debugger.write_snapshot_with_options(path, options));
+    if let Err(err) = debugger.detach() {
+        eprintln!("Warning: failed to detach from process {pid}: {err}");
+    }
+    written?;
+
+    let size = std::fs::metadata(path)?.len();
+    if style.format == OutputFormat::Json {
+        println!("{}", json!({ "pid": pid, "path": path.display().to_string(), "bytes": size }));
+    } else {
+        println!("Wrote snapshot of process {pid} to {} ({size} bytes)", path.display());
+        println!("Browse it with: ferros open {}", path.display());
+    }
+    Ok(())
+}
+
+/// Run `ferros repl`: start the target, then run commands from stdin until `quit` or end of input
+///
+/// A launched target is left stopped before its first instruction so
+/// breakpoints can be set before `c`. A prompt is shown only when stdin is a
+/// terminal. Failed commands are reported and the session goes on; a script
+/// (stdin not a terminal) then fails the session once it ends, so a here-doc
+/// can be checked with `$?`. With `--output json` every result, the start of
+/// the session and each failure is one line of JSON on stdout.
+fn run_repl(
+    pid: Option<u32>,
+    launch: Option<&str>,
+    args: Vec<String>,
+    backend: &BackendOptions,
+    style: OutputStyle,
+) -> DebuggerResult<()>
+{
+    static CANCEL: AtomicBool = AtomicBool::new(false);
+
+    let json = style.format == OutputFormat::Json;
+    let mut debugger = new_debugger(backend)?;
+    let session_file = if let Some(program) = launch {
+        let launch = LaunchOptions {
+            args,
+            ..LaunchOptions::default()
+        };
+        let (program_path, launch) = resolve_launch(program, launch)?;
+        let pid = debugger.launch_with_options(&program_path, &launch)?;
+        if json {
+            println!("{}", json!({"launched": program_path, "pid": pid.0}));
+        } else {
+            println!(
+                "Launched {program_path} (PID {}), stopped before its first instruction",
+                pid.0
+            );
+        }
+        SessionFile::path_for(Path::new(program))
+    } else {
+        // clap requires one of the two
+        let pid = pid.unwrap_or_default();
+        debugger.attach(ProcessId::from(pid))?;
+        if json {
+            println!("{}", json!({"attached": pid}));
+        } else {
+            println!("Attached to process {pid}");
+        }
+        None
+    };
+    let notes = load_notes(&*debugger, session_file.as_deref());
+    let mut repl = Repl::new(debugger, launch.is_some(), style.addresses, style.color)
+        .with_notes(notes)
+        .with_json(json);
+
+    if let Err(e) = ctrlc::set_handler(|| CANCEL.store(true, Ordering::Relaxed)) {
+        debug!("Ctrl+C won't interrupt the target: {}", e);
+    }
+    let interactive = io::stdin().is_terminal();
+    if interactive {
+        println!("Type 'help' for commands, 'quit' or Ctrl+D to leave");
+    }
+    let mut failures = 0;
+    let mut line = String::new();
+    loop {
+        if interactive {
+            print!("(ferros) ");
+            io::stdout().flush()?;
+        }
+        line.clear();
+        if io::stdin().read_line(&mut line)? == 0 {
+            if interactive {
+                println!();
+            }
+            repl.quit(&mut io::stdout().lock())?;
+            break;
+        }
+        match repl.execute(&line, &mut io::stdout().lock(), &CANCEL) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => break,
+            Err(e) if json => {
+                println!("{}", json!({"error": e.to_string()}));
+                failures += 1;
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                if let Some(advice) = e.permission_advice() {
+                    eprintln!("{advice}");
+                }
+                failures += 1;
+            }
+        }
+    }
+    match failures {
+        _ if interactive => Ok(()),
+        0 => Ok(()),
+        1 => Err(DebuggerError::InvalidArgument("1 command failed".to_string())),
+        failed => Err(DebuggerError::InvalidArgument(format!("{failed} commands failed"))),
+    }
+}
+
+fn write_summary(summary: &SessionSummary, path: &Path)
+{
+    match summary.write_to(path) {
+        Ok(()) => info!("Session summary written to {}", path.display()),
+        Err(e) => eprintln!("Warning: Failed to write session summary to {}: {}", path.display(), e),
+    }
+}
+
+/// Notes saved in the program's session file, moved to where its images are loaded now
+///
+/// A missing or unreadable session file means no notes.
+fn load_notes(target: &dyn TargetInspector, session_file: Option<&Path>) -> NoteRegistry
+{
+    let Some(path) = session_file else {
+        return NoteRegistry::new();
+    };
+    let mut notes = match SessionFile::load(path) {
+        Ok(file) => NoteRegistry::from_notes(&file.notes),
+        Err(e) => {
+            eprintln!("Warning: Failed to load notes: {}", e);
+            return NoteRegistry::new();
+        }
+    };
+    if !notes.is_empty() {
+        let regions = target.get_memory_regions().unwrap_or_default();
+        notes.rebase(&regions, image_uuid);
+    }
+    notes
+}
+
+/// Print the tables requested with `--print`, titled like the TUI views
+///
+/// With `--output json` they go in `report` instead, keyed by table name.
+fn print_tables(
+    debugger: &mut dyn Debugger,
+    tables: &[HeadlessTable],
+    notes: &NoteRegistry,
+    style: OutputStyle,
+    report: &mut Report,
+)
+{
+    // Listed before the inspector borrows the debugger
+    let modules = tables.contains(&HeadlessTable::Modules).then(|| debugger.modules());
+    let target = &LiveInspector::new(debugger);
+    if report.is_json() {
+        for table in tables {
+            let (key, value) = match table {
+                HeadlessTable::Registers => ("registers", json::registers(target)),
+                HeadlessTable::Threads => ("threads", json::threads(target)),
+                HeadlessTable::Regions => ("regions", json::regions(target)),
+                HeadlessTable::Where => ("where", json::where_summary(target)),
+                HeadlessTable::Modules => match &modules {
+                    Some(Ok(images)) => ("modules", Ok(json::modules(images))),
+                    Some(Err(e)) => {
+                        eprintln!("Error reading modules: {e}");
+                        continue;
+                    }
+                    None => continue,
+                },
+            };
+            match value {
+                Ok(value) => report.set(key, value),
+                Err(e) => eprintln!("Error reading {key}: {e}"),
+            }
+        }
+        return;
+    }
+    for table in tables {
+        let render = |table: text::TextTable| table.render(style.color);
+        let (title, rendered) = match table {
+            HeadlessTable::Registers => ("Registers", text::registers_table(target, notes, style.addresses).map(render)),
+            HeadlessTable::Threads => ("Threads", text::threads_table(target).map(render)),
+            HeadlessTable::Regions => ("Memory Regions", text::regions_table(target, style.addresses).map(render)),
+            HeadlessTable::Where => (
+                "Where",
+                text::where_summary(target, notes, style.addresses).map(|lines| lines.join("\n") + "\n"),
+            ),
+            HeadlessTable::Modules => match &modules {
+                Some(Ok(images)) => ("Modules", Ok(render(text::modules_table(images, style.addresses)))),
+                Some(Err(e)) => {
+                    eprintln!("Error reading modules: {e}");
+                    continue;
+                }
+                None => continue,
+            },
+        };
+        match rendered {
+            Ok(rendered) => println!("{title}\n{rendered}"),
+            Err(e) => eprintln!("Error reading {}: {}", title.to_lowercase(), e),
         }
     }
 }
 
-fn print_debugger_info(debugger: &dyn Debugger) -> DebuggerResult<()>
+/// Apply the `--write`s in order, reporting each on stderr
+fn write_memory(debugger: &mut dyn Debugger, writes: &[(AddressExpr, Vec<u8>)]) -> DebuggerResult<()>
 {
+    for (expr, bytes) in writes {
+        let address = debugger.resolve_address(expr)?;
+        let written = debugger.write_memory_checked(address, bytes)?;
+        eprintln!("Wrote {written} bytes at {address}");
+    }
+    Ok(())
+}
+
+/// Hex dump `--memory` to stdout, warning first about parts that can't be read
+///
+/// Notes in the range are listed on stderr after the dump. Ctrl+C stops the
+/// dump after the chunk being read. With `--output json` the bytes go in
+/// `report` as one hex string instead.
+fn dump_memory(
+    debugger: &mut dyn Debugger,
+    expr: &AddressExpr,
+    options: &MemoryArgs,
+    notes: &NoteRegistry,
+    style: OutputStyle,
+    report: &mut Report,
+) -> DebuggerResult<()>
+{
+    static CANCEL: AtomicBool = AtomicBool::new(false);
+
+    let range = options.range(debugger.resolve_address(expr)?)?;
+    let regions = debugger.get_memory_regions().unwrap_or_default();
+    for warning in memory::warnings(&memory::plan(&range, &regions), &regions) {
+        eprintln!("Warning: {warning}");
+    }
+    if report.is_json() {
+        let len = (range.end.value() - range.start.value()) as usize;
+        report.set("memory", json::memory(&*debugger, range.start, len)?);
+        return Ok(());
+    }
+
+    if let Err(e) = ctrlc::set_handler(|| CANCEL.store(true, Ordering::Relaxed)) {
+        debug!("Ctrl+C won't interrupt the memory dump: {}", e);
+    }
+    let noted = notes.in_range(range.start.value()..range.end.value());
+    let stats = memory::dump(
+        &*debugger,
+        range,
+        options.unit,
+        notes,
+        style.addresses,
+        &mut io::stdout().lock(),
+        &CANCEL,
+    )?;
+    for note in noted {
+        eprintln!("{} {}", NOTE_MARKER, note);
+    }
+    for hole in &stats.holes {
+        eprintln!(
+            "Warning: {} bytes at {} could not be read and are shown as zeros",
+            hole.len, hole.start
+        );
+    }
+    if stats.cancelled {
+        eprintln!("Interrupted after {} of {} bytes", stats.written, stats.requested);
+    }
+    Ok(())
+}
+
+/// Capture the `--checkpoint` ranges, resume, and print what changed in them at the next stop
+///
+/// Waits at most `wait` for the stop; `TimedOut` is left to the caller. With
+/// `--output json` the changes go in `report`.
+fn checkpoint_across_resume(
+    debugger: &mut dyn Debugger,
+    events: Option<&ferros_core::DebuggerEventReceiver>,
+    recorder: &mut SessionRecorder,
+    specs: &[(AddressExpr, usize)],
+    wait: Duration,
+    report: &mut Report,
+) -> DebuggerResult<()>
+{
+    let Some(events) = events else {
+        return Err(DebuggerError::InvalidArgument(
+            "--checkpoint needs stop events, which this debugger doesn't report".to_string(),
+        ));
+    };
+    let mut ranges = RangeSet::new();
+    for (expr, len) in specs {
+        ranges.add(debugger.resolve_address(expr)?, *len, Some(expr.to_string()))?;
+    }
+
+    let snapshots = debugger.capture_ranges(ranges.ranges())?;
+    debugger.resume()?;
+    let envelope = wait_for_stop(events, wait, |envelope| recorder.record_event(&envelope.event))?;
+    info!("Target stopped: {}", envelope.event.describe());
+
+    let diffs = debugger.diff_ranges(&snapshots)?;
+    if report.is_json() {
+        let changes: Vec<_> = diffs
+            .iter()
+            .map(|diff| {
+                let runs: Vec<_> = diff.runs.iter().map(ToString::to_string).collect();
+                json!({"summary": diff.summary(), "changed": diff.changed, "runs": runs})
+            })
+            .collect();
+        report.set("checkpoints", changes);
+        return Ok(());
+    }
+    for diff in diffs {
+        println!("{}", diff.summary());
+        for run in &diff.runs {
+            println!("  {run}");
+        }
+    }
+    Ok(())
+}
+
+/// Print the permission environment and what it allows, then the config health
+fn print_doctor(format: OutputFormat)
+{
+    if format == OutputFormat::Json {
+        let doctor = json!({
+            "os": env::consts::OS,
+            "arch": env::consts::ARCH,
+            "permissions": permissions::current_environment().summary_lines(),
+            "config": config_health_lines(&ferros_dir()),
+        });
+        println!("{doctor:#}");
+        return;
+    }
+    println!("Ferros doctor");
+    println!("  Platform: {} {}", env::consts::OS, env::consts::ARCH);
+    for line in permissions::current_environment().summary_lines() {
+        println!("  {line}");
+    }
+    for line in config_health_lines(&ferros_dir()) {
+        println!("  {line}");
+    }
+}
+
+/// `ferros doctor` lines for the config files in `dir`, checked like a session start checks them
+fn config_health_lines(dir: &Path) -> Vec<String>
+{
+    if !ferros_utils::config::path_in(dir).exists() && !dir.join(config::KEYMAP_FILE).exists() {
+        return vec![format!(
+            "Config: none in {} (run `ferros init` for documented defaults)",
+            dir.display()
+        )];
+    }
+    let report = UserConfig::load(dir).report;
+    if report.is_empty() {
+        return vec![format!("Config: {} ok", dir.display())];
+    }
+    let mut lines = vec![format!("Config: {}", report.summary())];
+    lines.extend(report.problems().iter().map(|problem| format!("  {problem}")));
+    lines
+}
+
+/// Fill in the flags `config.toml` has defaults for; flags and the environment variables they override win
+fn apply_config_defaults(cli: &mut Cli, config: &Config)
+{
+    if cli.log_format.is_none() && env::var_os("FERROS_LOG_FORMAT").is_none() {
+        cli.log_format = config.log_format.map(|format| format.to_string());
+    }
+    // Without a level the format isn't applied outside the TUI, so a format alone brings the default level
+    if cli.log_level.is_none()
+        && env::var_os("RUST_LOG").is_none()
+        && (config.log_level.is_some() || cli.log_format.is_some())
+    {
+        cli.log_level = Some(config.log_level.unwrap_or(LogLevel::Info).to_string());
+    }
+    cli.source_map.extend(config.source_map.iter().cloned());
+    cli.dsym_path.extend(config.dsym_paths.iter().cloned());
+}
+
+/// Print the configuration file's path and the values in effect, in its syntax
+///
+/// Entries whose value was rejected are listed as comments, since the value shown for them is the default.
+fn show_config(cli: &Cli, mut config: Config, warnings: &[ConfigWarning])
+{
+    let path = ferros_utils::config::path_in(&ferros_dir());
+    if path.exists() {
+        println!("# {}", path.display());
+    } else {
+        println!("# {} (not found; defaults)", path.display());
+    }
+    for warning in warnings {
+        println!("# line {}: {warning}; using the default", warning.line);
+    }
+    if let Some(level) = cli.log_level.as_ref().and_then(|level| level.parse().ok()) {
+        config.log_level = Some(level);
+    }
+    if let Some(format) = cli.log_format.as_ref().and_then(|format| format.parse().ok()) {
+        config.log_format = Some(format);
+    }
+    config.source_map.clone_from(&cli.source_map);
+    config.dsym_paths.clone_from(&cli.dsym_path);
+    print!("{config}");
+}
+
+/// Create the ferros directory tree, leaving existing files alone
+fn run_init(dir: &Path) -> io::Result<()>
+{
+    let report = config::init(dir)?;
+    for path in &report.created {
+        println!("Created {}", path.display());
+    }
+    for path in &report.kept {
+        println!("Kept existing {}", path.display());
+    }
+    let problems = UserConfig::load(dir).report;
+    if !problems.is_empty() {
+        eprintln!("Warning: {}", problems.render());
+    }
+    Ok(())
+}
+
+/// Print config problems and the one-time `ferros init` hint to stderr
+fn report_user_config(dir: &Path)
+{
+    let report = UserConfig::load(dir).report;
+    if !report.is_empty() {
+        eprintln!("Warning: {}", report.render());
+    }
+    if config::take_first_run_hint(dir) {
+        eprintln!("{}", config::FIRST_RUN_HINT);
+    }
+}
+
+/// Log the target's architecture and state, or add them to the JSON report
+fn print_debugger_info(target: &dyn TargetInspector, report: &mut Report) -> DebuggerResult<()>
+{
+    if report.is_json() {
+        report.set("debugger", json::debugger_info(target));
+        return Ok(());
+    }
     info!("Debugger Information:");
-    info!("  Architecture: {}", debugger.architecture());
-    info!("  Attached: {}", debugger.is_attached());
-    info!("  Stopped: {}", debugger.is_stopped());
-    debug!("  Stop Reason: {:?}", debugger.stop_reason());
+    info!("  Architecture: {}", target.architecture());
+    info!("  Attached: {}", target.is_attached());
+    info!("  Stopped: {}", target.is_stopped());
+    debug!("  Stop Reason: {:?}", target.stop_reason());
 
-    if debugger.is_attached() {
-        if let Ok(threads) = debugger.threads() {
+    if target.is_attached() {
+        if let Ok(threads) = target.threads() {
             info!("  Threads: {}", threads.len());
-            if let Some(active) = debugger.active_thread() {
+            if let Some(active) = target.active_thread() {
                 debug!("  Active Thread: {}", active.raw());
             }
         }
 
-        if let Ok(regions) = debugger.get_memory_regions() {
+        if let Ok(regions) = target.get_memory_regions() {
             info!("  Memory Regions: {}", regions.len());
         }
     }
@@ -0,0 +1,657 @@
+//! Memory dumps from the command line
+//!
+//! Arguments are checked before anything is read: the length against a cap
+//! (`--max-length`, 16 MiB unless changed, lifted with `--force`) and the
+//! address and length against `--unit`. The range is then split against the
+//! target's regions so the user is told up front about unmapped or unreadable
+//! parts, and read in [`DUMP_CHUNK_SIZE`] chunks so large dumps print progress
+//! and Ctrl+C stops them between chunks.
+
+use std::io::{self, Write};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ferros_core::notes::{NOTE_MARKER, NoteRegistry};
+use ferros_core::stream::{ReadProgress, ReadStats, StreamControl, StreamOptions};
+use ferros_core::types::{Address, AddressFormat, AddressFormatter, MemoryRegion};
+use ferros_core::{DebuggerError, Result, TargetInspector};
+
+/// Largest read allowed without `--force`
+pub const DEFAULT_MAX_LENGTH: usize = 16 * 1024 * 1024;
+
+/// Bytes read from the target at a time
+pub const DUMP_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Dumps at least this large report progress on stderr
+const PROGRESS_THRESHOLD: u64 = 1024 * 1024;
+
+/// Bytes per hex dump row
+const ROW_BYTES: usize = 16;
+
+/// Length and layout of a memory read
+#[derive(clap::Args, Debug, Clone)]
+pub struct MemoryArgs
+{
+    /// Number of bytes to read (default: 16)
+    #[arg(short, long, default_value_t = 16)]
+    pub length: usize,
+
+    /// Group bytes in units of 1, 2, 4 or 8; the address and length must be multiples of it
+    #[arg(long, default_value_t = 1, value_parser = parse_unit)]
+    pub unit: usize,
+
+    /// Refuse reads longer than BYTES unless --force is given
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_LENGTH)]
+    pub max_length: usize,
+
+    /// Read more than --max-length bytes
+    #[arg(long)]
+    pub force: bool,
+}
+
+fn parse_unit(text: &str) -> std::result::Result<usize, String>
+{
+    match text.parse() {
+        Ok(unit @ (1 | 2 | 4 | 8)) => Ok(unit),
+        _ => Err(format!("unit must be 1, 2, 4 or 8, not {text:?}")),
+    }
+}
+
+impl MemoryArgs
+{
+    /// Check the length against the cap and the unit
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` for a zero length, a length that isn't a
+    /// multiple of the unit, or one over the cap without `--force`.
+    pub fn validate(&self) -> Result<()>
+    {
+        if self.length == 0 {
+            return Err(DebuggerError::InvalidArgument("--length must be at least 1".to_string()));
+        }
+        if !self.length.is_multiple_of(self.unit) {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "--length {} is not a multiple of --unit {}",
+                self.length, self.unit
+            )));
+        }
+        if self.length > self.max_length && !self.force {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "--length {} is over the {} byte limit; pass --force to stream it anyway, or raise --max-length",
+                self.length, self.max_length
+            )));
+        }
+        Ok(())
+    }
+
+    /// Range of `length` bytes at `start`
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if `start` isn't aligned to the unit or the
+    /// range runs past the end of the address space.
+    pub fn range(&self, start: Address) -> Result<Range<Address>>
+    {
+        if !start.value().is_multiple_of(self.unit as u64) {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "Address {start} is not aligned to --unit {}",
+                self.unit
+            )));
+        }
+        let end = start.checked_add(self.length as u64).ok_or_else(|| {
+            DebuggerError::InvalidArgument(format!("{} bytes at {start} run past the end of memory", self.length))
+        })?;
+        Ok(start..end)
+    }
+}
+
+/// Part of a requested range that lies in one region, or in none
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment
+{
+    /// Addresses covered
+    pub range: Range<Address>,
+    /// Index of the region in the list given to [`plan`], or `None` if unmapped
+    pub region: Option<usize>,
+    /// Whether the region can be read
+    pub readable: bool,
+}
+
+/// Split `range` against `regions`, in address order
+///
+/// Gaps between regions become unmapped segments, so the segments always
+/// cover the whole range.
+#[must_use]
+pub fn plan(range: &Range<Address>, regions: &[MemoryRegion]) -> Vec<Segment>
+{
+    let mut order: Vec<usize> = (0..regions.len()).collect();
+    order.sort_by_key(|&index| regions[index].start);
+
+    let mut segments = Vec::new();
+    let mut cursor = range.start;
+    for index in order {
+        let region = &regions[index];
+        if cursor >= range.end {
+            break;
+        }
+        if region.end <= cursor || region.start >= range.end {
+            continue;
+        }
+        if region.start > cursor {
+            segments.push(Segment {
+                range: cursor..region.start,
+                region: None,
+                readable: false,
+            });
+        }
+        let end = region.end.min(range.end);
+        segments.push(Segment {
+            range: cursor.max(region.start)..end,
+            region: Some(index),
+            readable: region.is_readable(),
+        });
+        cursor = end;
+    }
+    if cursor < range.end {
+        segments.push(Segment {
+            range: cursor..range.end,
+            region: None,
+            readable: false,
+        });
+    }
+    segments
+}
+
+/// Warnings to show before reading: several regions, and unmapped or unreadable parts
+#[must_use]
+pub fn warnings(segments: &[Segment], regions: &[MemoryRegion]) -> Vec<String>
+{
+    let mut warnings = Vec::new();
+    let mapped = segments.iter().filter(|segment| segment.region.is_some()).count();
+    if mapped > 1 {
+        warnings.push(format!("The range spans {mapped} memory regions"));
+    }
+    for segment in segments.iter().filter(|segment| !segment.readable) {
+        let what = match segment.region {
+            Some(index) => format!("is not readable ({})", regions[index].permissions),
+            None => "is unmapped".to_string(),
+        };
+        warnings.push(format!("{} {what}", format_range(&segment.range)));
+    }
+    if warnings.iter().any(|warning| !warning.starts_with("The range")) {
+        let readable: Vec<String> = segments
+            .iter()
+            .filter(|segment| segment.readable)
+            .map(|segment| format_range(&segment.range))
+            .collect();
+        warnings.push(if readable.is_empty() {
+            "Nothing in the range is readable".to_string()
+        } else {
+            format!("Readable: {}", readable.join(", "))
+        });
+    }
+    warnings
+}
+
+fn format_range(range: &Range<Address>) -> String
+{
+    format!("0x{:x}..0x{:x}", range.start.value(), range.end.value())
+}
+
+/// Hex dump row: address, `unit`-byte groups in memory order, then ASCII
+#[must_use]
+pub fn format_row(address: u64, bytes: &[u8], unit: usize, addresses: AddressFormatter) -> String
+{
+    let mut row = format!("{}:", addresses.hex(address));
+    for index in 0..ROW_BYTES {
+        if index % unit == 0 {
+            row.push(' ');
+        }
+        match bytes.get(index) {
+            Some(byte) => row.push_str(&format!("{byte:02x}")),
+            None => row.push_str("  "),
+        }
+    }
+    row.push_str("  ");
+    row.extend(bytes.iter().map(|&byte| {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            byte as char
+        } else {
+            '.'
+        }
+    }));
+    row.trim_end().to_string()
+}
+
+/// Writer that turns the bytes written to it into hex dump rows
+struct HexDump<'a>
+{
+    out: &'a mut dyn Write,
+    address: u64,
+    unit: usize,
+    /// Shared by every row, sized for the last one
+    addresses: AddressFormatter,
+    row: Vec<u8>,
+    /// Notes in the dumped range; rows holding one are marked in a gutter
+    notes: Option<&'a NoteRegistry>,
+}
+
+impl HexDump<'_>
+{
+    fn emit(&mut self) -> io::Result<()>
+    {
+        if !self.row.is_empty() {
+            if let Some(notes) = self.notes {
+                let end = self.address.saturating_add(self.row.len() as u64);
+                let noted = !notes.in_range(self.address..end).is_empty();
+                write!(
+                    self.out,
+                    "{}",
+                    if noted { format!("{NOTE_MARKER} ") } else { "   ".to_string() }
+                )?;
+            }
+            writeln!(self.out, "{}", format_row(self.address, &self.row, self.unit, self.addresses))?;
+            self.address = self.address.wrapping_add(self.row.len() as u64);
+            self.row.clear();
+        }
+        Ok(())
+    }
+}
+
+impl Write for HexDump<'_>
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+    {
+        for &byte in buf {
+            self.row.push(byte);
+            if self.row.len() == ROW_BYTES {
+                self.emit()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()>
+    {
+        self.out.flush()
+    }
+}
+
+fn format_progress(progress: &ReadProgress) -> String
+{
+    #[allow(clippy::cast_precision_loss)]
+    let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    format!(
+        "Read {:.1} of {:.1} MiB ({:.0}%)",
+        mib(progress.done),
+        mib(progress.total),
+        progress.fraction() * 100.0
+    )
+}
+
+/// Hex dump `range` to `out`, reading [`DUMP_CHUNK_SIZE`] bytes at a time
+///
+/// Unreadable pages are dumped as zeros and listed as holes in the returned
+/// stats. Progress goes to stderr for large ranges, and the read stops after
+/// the current chunk once `cancel` is set. When `notes` has any in the range,
+/// a gutter marks the rows they're on. Row addresses are printed in `format`.
+/// Code under software breakpoints is dumped as it was before the trap
+/// was patched in.
+///
+/// # Errors
+///
+/// Returns `Io` if `out` fails.
+pub fn dump(
+    target: &dyn TargetInspector,
+    range: Range<Address>,
+    unit: usize,
+    notes: &NoteRegistry,
+    format: AddressFormat,
+    out: &mut dyn Write,
+    cancel: &AtomicBool,
+) -> Result<ReadStats>
+{
+    let total = range.end.value() - range.start.value();
+    let last_row = range.start.value() + total.saturating_sub(1) / ROW_BYTES as u64 * ROW_BYTES as u64;
+    let show_progress = total >= PROGRESS_THRESHOLD;
+    let mut progress = |progress: &ReadProgress| {
+        if show_progress {
+            eprint!("\r{}", format_progress(progress));
+        }
+        if cancel.load(Ordering::Relaxed) {
+            StreamControl::Cancel
+        } else {
+            StreamControl::Continue
+        }
+    };
+    let mut options = StreamOptions::new().with_logical_view(true).with_progress(&mut progress);
+    let mut hex = HexDump {
+        out,
+        address: range.start.value(),
+        unit,
+        addresses: format.formatter([range.start.value(), last_row]),
+        row: Vec::with_capacity(ROW_BYTES),
+        notes: (!notes.in_range(range.start.value()..range.end.value()).is_empty()).then_some(notes),
+    };
+    let stats = target.read_memory_streamed(range, DUMP_CHUNK_SIZE, &mut hex, &mut options)?;
+    hex.emit()?;
+    hex.flush()?;
+    if show_progress {
+        eprintln!();
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ferros_core::notes::NoteTarget;
+    use ferros_core::types::{Architecture, MemoryRegionId, Registers, StopReason, ThreadId};
+
+    use super::*;
+
+    fn region(start: u64, end: u64, permissions: &str) -> MemoryRegion
+    {
+        MemoryRegion::new(
+            MemoryRegionId(0),
+            Address::from(start),
+            Address::from(end),
+            permissions.to_string(),
+            None,
+        )
+    }
+
+    fn args(length: usize, unit: usize) -> MemoryArgs
+    {
+        MemoryArgs {
+            length,
+            unit,
+            max_length: DEFAULT_MAX_LENGTH,
+            force: false,
+        }
+    }
+
+    #[test]
+    fn lengths_and_alignment_are_checked_up_front()
+    {
+        assert!(args(16, 1).validate().is_ok());
+        assert!(args(0, 1).validate().is_err());
+        assert!(args(6, 4).validate().is_err());
+
+        let mut huge = args(DEFAULT_MAX_LENGTH + 1, 1);
+        let message = huge.validate().unwrap_err().to_string();
+        assert!(message.contains("--force"), "{message}");
+        huge.force = true;
+        assert!(huge.validate().is_ok());
+
+        assert_eq!(
+            args(8, 8).range(Address::from(0x1000)).unwrap(),
+            Address::from(0x1000)..Address::from(0x1008)
+        );
+        assert!(args(8, 8).range(Address::from(0x1004)).is_err());
+        assert!(args(16, 1).range(Address::from(u64::MAX - 4)).is_err());
+        assert!(parse_unit("3").is_err());
+    }
+
+    #[test]
+    fn ranges_split_against_regions()
+    {
+        // Deliberately out of order
+        let regions = [
+            region(0x3000, 0x4000, "r--"),
+            region(0x1000, 0x2000, "rw-"),
+            region(0x4000, 0x5000, "---"),
+        ];
+        let range = Address::from(0x1800)..Address::from(0x5800);
+        let segments = plan(&range, &regions);
+        let summary: Vec<_> = segments
+            .iter()
+            .map(|segment| {
+                (
+                    segment.range.start.value(),
+                    segment.range.end.value(),
+                    segment.region,
+                    segment.readable,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0x1800, 0x2000, Some(1), true),
+                (0x2000, 0x3000, None, false),
+                (0x3000, 0x4000, Some(0), true),
+                (0x4000, 0x5000, Some(2), false),
+                (0x5000, 0x5800, None, false),
+            ]
+        );
+        assert_eq!(
+            warnings(&segments, &regions),
+            vec![
+                "The range spans 3 memory regions",
+                "0x2000..0x3000 is unmapped",
+                "0x4000..0x5000 is not readable (---)",
+                "0x5000..0x5800 is unmapped",
+                "Readable: 0x1800..0x2000, 0x3000..0x4000",
+            ]
+        );
+
+        // Inside one readable region there is nothing to say
+        let inside = Address::from(0x1100)..Address::from(0x1200);
+        assert!(warnings(&plan(&inside, &regions), &regions).is_empty());
+    }
+
+    #[test]
+    fn rows_group_by_unit()
+    {
+        let bytes: Vec<u8> = (0x41..0x51).collect();
+        let full = AddressFormat::Full16.formatter([0x1000_u64]);
+        assert_eq!(
+            format_row(0x1000, &bytes, 1, full),
+            "0000000000001000: 41 42 43 44 45 46 47 48 49 4a 4b 4c 4d 4e 4f 50  ABCDEFGHIJKLMNOP"
+        );
+        assert_eq!(
+            format_row(0x1000, &bytes[..6], 4, full),
+            "0000000000001000: 41424344 4546                        ABCDEF"
+        );
+        assert_eq!(
+            format_row(0x1000, &bytes[..2], 1, AddressFormat::Trimmed.formatter([0x1000_u64])),
+            "1000: 41 42                                            AB"
+        );
+    }
+
+    /// Stand-in for a stopped target with two mapped regions and a hole between them
+    struct FixtureTarget
+    {
+        regions: Vec<MemoryRegion>,
+    }
+
+    impl TargetInspector for FixtureTarget
+    {
+        fn read_registers(&self) -> Result<Registers>
+        {
+            Ok(Registers::new())
+        }
+
+        fn read_memory(&self, addr: Address, len: usize) -> Result<Vec<u8>>
+        {
+            let end = addr.value() + len as u64;
+            let region = self
+                .regions
+                .iter()
+                .find(|region| region.contains(addr) && end <= region.end.value() && region.is_readable())
+                .ok_or_else(|| DebuggerError::InvalidArgument(format!("{addr} is not readable")))?;
+            let fill = if region.start.value() == 0x1000 { b'a' } else { b'b' };
+            Ok(vec![fill; len])
+        }
+
+        fn get_memory_regions(&self) -> Result<Vec<MemoryRegion>>
+        {
+            Ok(self.regions.clone())
+        }
+
+        fn architecture(&self) -> Architecture
+        {
+            Architecture::Arm64
+        }
+
+        fn is_attached(&self) -> bool
+        {
+            true
+        }
+
+        fn is_stopped(&self) -> bool
+        {
+            true
+        }
+
+        fn stop_reason(&self) -> StopReason
+        {
+            StopReason::Suspended
+        }
+
+        fn threads(&self) -> Result<Vec<ThreadId>>
+        {
+            Ok(vec![ThreadId::from(1)])
+        }
+
+        fn active_thread(&self) -> Option<ThreadId>
+        {
+            Some(ThreadId::from(1))
+        }
+    }
+
+    #[test]
+    fn multi_region_dump_streams_in_chunks_and_reports_the_hole()
+    {
+        let target = FixtureTarget {
+            regions: vec![region(0x1000, 0x2_1000, "rw-"), region(0x2_2000, 0x4_0000, "r--")],
+        };
+        let range = args(0x2_0000, 1).range(Address::from(0x1_8000)).unwrap();
+        let segments = plan(&range, &target.get_memory_regions().unwrap());
+        assert_eq!(segments.len(), 3);
+
+        let mut out = Vec::new();
+        let stats = dump(
+            &target,
+            range,
+            1,
+            &NoteRegistry::new(),
+            AddressFormat::Full16,
+            &mut out,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        assert_eq!(stats.written, 0x2_0000);
+        assert_eq!(stats.hole_bytes(), 0x1000);
+        assert_eq!(stats.holes[0].start, Address::from(0x2_1000));
+        assert!(!stats.cancelled);
+
+        let text = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows.len(), 0x2_0000 / ROW_BYTES);
+        assert!(rows[0].starts_with("0000000000018000: 61 61"));
+        assert!(rows[0x9000 / ROW_BYTES].starts_with("0000000000021000: 00 00"));
+        assert!(rows.last().unwrap().starts_with("0000000000037ff0: 62 62"));
+
+        // A cancelled dump stops after the first chunk
+        let mut out = Vec::new();
+        let range = args(0x2_0000, 1).range(Address::from(0x1_8000)).unwrap();
+        let stats = dump(
+            &target,
+            range,
+            1,
+            &NoteRegistry::new(),
+            AddressFormat::Full16,
+            &mut out,
+            &AtomicBool::new(true),
+        )
+        .unwrap();
+        assert!(stats.cancelled);
+        assert_eq!(stats.written, DUMP_CHUNK_SIZE as u64);
+    }
+
+    #[test]
+    fn noted_rows_are_marked_in_the_gutter()
+    {
+        let target = FixtureTarget {
+            regions: vec![region(0x1000, 0x2000, "rw-")],
+        };
+        let mut notes = NoteRegistry::new();
+        notes.add(NoteTarget::address(0x1014), "length field").unwrap();
+        notes.add(NoteTarget::address(0x1800), "outside the dump").unwrap();
+
+        let mut out = Vec::new();
+        let range = args(0x30, 1).range(Address::from(0x1000)).unwrap();
+        dump(
+            &target,
+            range,
+            1,
+            &notes,
+            AddressFormat::Full16,
+            &mut out,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("   0000000000001000:"));
+        assert!(rows[1].starts_with(&format!("{NOTE_MARKER} 0000000000001010:")));
+        assert!(rows[2].starts_with("   0000000000001020:"));
+
+        // No gutter without notes in range
+        let mut out = Vec::new();
+        let range = args(0x10, 1).range(Address::from(0x1100)).unwrap();
+        dump(
+            &target,
+            range,
+            1,
+            &notes,
+            AddressFormat::Full16,
+            &mut out,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("0000000000001100:"));
+    }
+
+    #[test]
+    fn trimmed_rows_share_the_last_row_width()
+    {
+        let target = FixtureTarget {
+            regions: vec![region(0x1000, 0x2_1000, "rw-")],
+        };
+        let mut out = Vec::new();
+        let range = args(0x20, 1).range(Address::from(0xfff0)).unwrap();
+        dump(
+            &target,
+            range,
+            1,
+            &NoteRegistry::new(),
+            AddressFormat::Trimmed,
+            &mut out,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = text.lines().collect();
+        assert!(rows[0].starts_with("0fff0: 61 61"));
+        assert!(rows[1].starts_with("10000: 61 61"));
+
+        let mut out = Vec::new();
+        let range = args(0x10, 1).range(Address::from(0x1_0000)).unwrap();
+        dump(
+            &target,
+            range,
+            1,
+            &NoteRegistry::new(),
+            AddressFormat::Short12,
+            &mut out,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("000000010000: 61"));
+    }
+}
@@ -0,0 +1,96 @@
+//! Human or JSON output for headless commands
+//!
+//! `--output human` prints tables and log lines as results come in.
+//! `--output json` collects them in a [`Report`] instead and prints one JSON
+//! object on stdout when the command is done, with logs moved to stderr so
+//! stdout holds nothing else. `ferros repl` prints one JSON line per result.
+
+use std::io::{self, Write};
+
+use serde_json::{Map, Value};
+
+/// How headless commands print their results
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat
+{
+    /// Tables and log lines
+    #[default]
+    Human,
+    /// One JSON document on stdout (JSON lines for `repl`)
+    Json,
+}
+
+/// Results of a headless command, printed as one JSON object in JSON mode
+///
+/// In human mode callers print as they go and [`Report::set`] does nothing.
+#[derive(Debug)]
+pub struct Report
+{
+    format: OutputFormat,
+    fields: Map<String, Value>,
+}
+
+impl Report
+{
+    pub fn new(format: OutputFormat) -> Self
+    {
+        Self {
+            format,
+            fields: Map::new(),
+        }
+    }
+
+    /// Whether results go in the report rather than being printed
+    pub fn is_json(&self) -> bool
+    {
+        self.format == OutputFormat::Json
+    }
+
+    /// Record `value` under `key`, replacing an earlier value
+    pub fn set(&mut self, key: &str, value: impl Into<Value>)
+    {
+        if self.is_json() {
+            self.fields.insert(key.to_string(), value.into());
+        }
+    }
+
+    /// Print the report to stdout in JSON mode
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if stdout can't be written.
+    pub fn finish(self) -> io::Result<()>
+    {
+        if !self.is_json() {
+            return Ok(());
+        }
+        let mut out = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut out, &Value::Object(self.fields))?;
+        writeln!(out)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn only_json_reports_keep_results()
+    {
+        let mut human = Report::new(OutputFormat::Human);
+        human.set("pid", 42);
+        assert!(human.fields.is_empty());
+
+        let mut report = Report::new(OutputFormat::Json);
+        report.set("pid", 42);
+        report.set("stop", json!({"reason": "exited", "code": 0}));
+        report.set("pid", 43);
+        assert_eq!(
+            Value::Object(report.fields),
+            json!({"pid": 43, "stop": {"reason": "exited", "code": 0}})
+        );
+    }
+}